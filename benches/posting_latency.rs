@@ -0,0 +1,193 @@
+//! Latency budget of the posting path: the p50 of posting a single-split transaction must
+//! stay under `P50_BUDGET`. Needs a migrated database and exits early without one:
+//! `DATABASE_URL=postgres://... cargo bench --bench posting_latency`
+//!
+//! The server is a binary crate, so the benchmark compiles its modules in directly.
+
+#![allow(dead_code)]
+// `--all-targets` checks benchmarks with `cfg(test)`, which brings in the modules' test
+// modules without their `#[test]` functions
+#![cfg_attr(test, allow(unused_imports))]
+
+#[path = "../src/app_state.rs"]
+mod app_state;
+#[path = "../src/config.rs"]
+mod config;
+#[path = "../src/db.rs"]
+mod db;
+#[path = "../src/error.rs"]
+mod error;
+#[path = "../src/middleware/mod.rs"]
+mod middleware;
+#[path = "../src/models/mod.rs"]
+mod models;
+#[path = "../src/routes/mod.rs"]
+mod routes;
+#[path = "../src/services/mod.rs"]
+mod services;
+#[path = "../src/user/mod.rs"]
+mod user;
+#[path = "../src/utils/mod.rs"]
+mod utils;
+
+use std::{
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use models::{
+    dto::{journal_entry_dto::CreateJournalEntryDto, transaction_dto::CreateTransactionDto},
+    journal_entry::JournalEntryType,
+    transaction::{TransactionStatus, TransactionType},
+};
+use services::transaction::create_transaction;
+
+/// p50 budget for posting a single-split transaction.
+const P50_BUDGET: Duration = Duration::from_millis(10);
+/// Postings made before measuring, to fill the pool and the statement caches.
+const WARMUP: usize = 20;
+const SAMPLES: usize = 200;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+    if std::env::var("DATABASE_URL").is_err() {
+        eprintln!("posting_latency: DATABASE_URL is not set, skipping");
+        return ExitCode::SUCCESS;
+    }
+    if let Err(e) = config::init() {
+        eprintln!("{}", e);
+        return ExitCode::FAILURE;
+    }
+    let pool = PgPool::connect(&config::get().database.url)
+        .await
+        .expect("connect to DATABASE_URL");
+    let (tenant_id, user_id, cash_id, expense_id) = seed(&pool).await;
+
+    let mut timings = Vec::with_capacity(SAMPLES);
+    for sample in 0..WARMUP + SAMPLES {
+        let dto = single_split(cash_id, expense_id);
+        let started = Instant::now();
+        create_transaction(&pool, tenant_id, user_id, dto)
+            .await
+            .expect("post transaction");
+        if sample >= WARMUP {
+            timings.push(started.elapsed());
+        }
+    }
+    timings.sort();
+    let p50 = timings[SAMPLES / 2];
+    let p95 = timings[SAMPLES * 95 / 100];
+    println!(
+        "single-split posting: p50 {:?}, p95 {:?} over {} samples",
+        p50, p95, SAMPLES
+    );
+    if p50 > P50_BUDGET {
+        eprintln!("p50 posting latency is over the {:?} budget", P50_BUDGET);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// A posted expense paid from cash: one debit, one credit, base currency.
+fn single_split(cash_id: Uuid, expense_id: Uuid) -> CreateTransactionDto {
+    let amount = Decimal::new(1250, 2);
+    let entry = |account_id, entry_type| CreateJournalEntryDto {
+        account_id,
+        entry_type,
+        amount,
+        currency_code: "USD".to_string(),
+        exchange_rate: None,
+        converted_amount: None,
+        memo: None,
+        project_id: None,
+        class_id: None,
+        location_id: None,
+    };
+    CreateTransactionDto {
+        transaction_date: Utc::now().date_naive(),
+        description: "Posting latency benchmark".to_string(),
+        r#type: TransactionType::Expense,
+        category_id: None,
+        payee_id: None,
+        tags: None,
+        amount,
+        currency_code: "USD".to_string(),
+        is_reconciled: None,
+        reconciliation_date: None,
+        notes: None,
+        source_document_url: None,
+        status: Some(TransactionStatus::Posted),
+        journal_entries: vec![
+            entry(expense_id, JournalEntryType::Debit),
+            entry(cash_id, JournalEntryType::Credit),
+        ],
+        payment_account_id: None,
+        splits: Vec::new(),
+        metadata: None,
+        attribution: None,
+    }
+}
+
+/// A fresh user and tenant with a cash and an expense account.
+async fn seed(pool: &PgPool) -> (Uuid, Uuid, Uuid, Uuid) {
+    let run = Uuid::new_v4();
+    let email = format!("posting-latency-{}@example.com", run);
+    let user_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+         VALUES ($1, 'EMAIL_PASSWORD', $1, 'Posting', 'Benchmark') RETURNING id",
+    )
+    .bind(&email)
+    .fetch_one(pool)
+    .await
+    .expect("insert user");
+    sqlx::query(
+        "INSERT INTO currencies (code, name, symbol, created_by, updated_by)
+         VALUES ('USD', 'US Dollar', '$', $1, $1) ON CONFLICT (code) DO NOTHING",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .expect("insert currency");
+    let tenant_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO tenants (name, base_currency_code, fiscal_year_end_month, created_by, updated_by)
+         VALUES ($1, 'USD', 12, $2, $2) RETURNING id",
+    )
+    .bind(format!("Posting latency {}", run))
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .expect("insert tenant");
+
+    let mut account_ids = Vec::new();
+    for (type_name, account_name) in [("Asset", "Cash"), ("Expense", "Office Supplies")] {
+        let account_type_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO account_types (name, normal_balance, created_by, updated_by)
+             VALUES ($1, 'DEBIT', $2, $2)
+             ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING id",
+        )
+        .bind(type_name)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .expect("insert account type");
+        let account_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO accounts (tenant_id, account_type_id, name, currency_code, created_by, updated_by)
+             VALUES ($1, $2, $3, 'USD', $4, $4) RETURNING id",
+        )
+        .bind(tenant_id)
+        .bind(account_type_id)
+        .bind(account_name)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .expect("insert account");
+        account_ids.push(account_id);
+    }
+    (tenant_id, user_id, account_ids[0], account_ids[1])
+}
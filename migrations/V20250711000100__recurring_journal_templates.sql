@@ -0,0 +1,28 @@
+-- Standing journal entries: allow recurring definitions whose payload is a full
+-- multi-leg JOURNAL_ENTRY template rather than a single income/expense line.
+
+ALTER TABLE recurring_transactions
+    DROP CONSTRAINT recurring_transactions_type_check;
+
+ALTER TABLE recurring_transactions
+    ADD CONSTRAINT recurring_transactions_type_check
+    CHECK (type IN ('INCOME', 'EXPENSE', 'TRANSFER', 'JOURNAL_ENTRY'));
+
+-- Journal templates carry their accounts on each leg, so the primary account is optional.
+ALTER TABLE recurring_transactions
+    ALTER COLUMN account_id DROP NOT NULL;
+
+-- Stores an array of legs: [{"account_id": "...", "entry_type": "DEBIT", "amount": "100.00", "memo": null}]
+ALTER TABLE recurring_transactions
+    ADD COLUMN journal_template JSONB;
+
+ALTER TABLE recurring_transactions
+    ADD CONSTRAINT recurring_transactions_template_check
+    CHECK (
+        (type = 'JOURNAL_ENTRY' AND journal_template IS NOT NULL)
+        OR (type <> 'JOURNAL_ENTRY' AND account_id IS NOT NULL)
+    );
+
+CREATE INDEX idx_recurring_transactions_due_active
+    ON recurring_transactions (next_due_date)
+    WHERE is_active = TRUE;
@@ -0,0 +1,5 @@
+-- Supports the bank sync scheduler, which scans connections by status.
+CREATE INDEX idx_ext_conns_status ON ext_conns (status);
+
+-- Supports removal handling during sync, which looks up staged rows by provider ID.
+CREATE INDEX idx_external_transactions_staging_provider_tx_id ON external_transactions_staging (provider_transaction_id);
@@ -0,0 +1,25 @@
+-- Per-tenant custom statement layouts: ordered account groups and subtotal rows
+-- (e.g., "Gross Profit") applied by the financial statements engine.
+
+CREATE TABLE statement_layouts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    statement_type VARCHAR(50) NOT NULL CHECK (statement_type IN ('TRIAL_BALANCE', 'INCOME_STATEMENT', 'BALANCE_SHEET')),
+    -- {"rows": [{"kind": "GROUP", "key": "revenue", ...}, {"kind": "SUBTOTAL", "key": "gross_profit", ...}]}
+    definition JSONB NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE, -- Used when a report request names no layout
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id),
+    UNIQUE (tenant_id, name) -- Layout names unique per tenant
+);
+
+CREATE INDEX idx_statement_layouts_tenant_id ON statement_layouts (tenant_id);
+
+-- At most one active default layout per statement type per tenant
+CREATE UNIQUE INDEX idx_statement_layouts_default
+    ON statement_layouts (tenant_id, statement_type)
+    WHERE is_default = TRUE AND is_active = TRUE;
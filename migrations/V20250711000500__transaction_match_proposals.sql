@@ -0,0 +1,20 @@
+-- Proposed matches between staged bank-feed rows and existing ledger transactions.
+-- Accepting a proposal links the staged row to the transaction instead of creating a duplicate.
+
+CREATE TABLE transaction_match_proposals (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    staging_id UUID NOT NULL REFERENCES external_transactions_staging(id),
+    transaction_id UUID NOT NULL REFERENCES transactions(id),
+    score NUMERIC(5, 4) NOT NULL CHECK (score >= 0 AND score <= 1),
+    status VARCHAR(50) NOT NULL CHECK (status IN ('PROPOSED', 'ACCEPTED', 'REJECTED')),
+    reasons JSONB, -- {"amount": 1.0, "date": 0.8, "description": 0.5, "days_apart": 1}
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id),
+    UNIQUE (staging_id, transaction_id) -- A rejected pair is never proposed again
+);
+
+CREATE INDEX idx_transaction_match_proposals_tenant_status ON transaction_match_proposals (tenant_id, status);
+CREATE INDEX idx_transaction_match_proposals_transaction_id ON transaction_match_proposals (transaction_id);
@@ -0,0 +1,34 @@
+-- Align the budget tables with the budget services: budgets carry a currency and
+-- line items can target an account as well as a category, with soft delete.
+
+ALTER TABLE budgets
+    ADD COLUMN currency_code CHAR(3) REFERENCES currencies(code);
+
+UPDATE budgets b
+SET currency_code = t.base_currency_code
+FROM tenants t
+WHERE b.tenant_id = t.id;
+
+ALTER TABLE budgets
+    ALTER COLUMN currency_code SET NOT NULL;
+
+-- Budgets created through the API are not tied to a fixed cadence
+ALTER TABLE budgets
+    ALTER COLUMN budget_type SET DEFAULT 'CUSTOM';
+
+ALTER TABLE budget_line_items
+    RENAME COLUMN amount TO budgeted_amount;
+
+ALTER TABLE budget_line_items
+    ADD COLUMN account_id UUID REFERENCES accounts(id),
+    ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- A line item's amount covers the whole budget period unless stated otherwise
+ALTER TABLE budget_line_items
+    ALTER COLUMN frequency_type SET DEFAULT 'ONCE';
+
+ALTER TABLE budget_line_items
+    ADD CONSTRAINT budget_line_items_target_check
+    CHECK (category_id IS NOT NULL OR account_id IS NOT NULL);
+
+CREATE INDEX idx_budget_line_items_account_id ON budget_line_items (account_id);
@@ -0,0 +1,10 @@
+-- Seasonal budgets: an optional per-month schedule for a line item, keyed by month.
+-- {"2025-01": "1200.00", "2025-02": "800.00", ...}; budgeted_amount holds the schedule total.
+-- Line items without a schedule are spread evenly over the budget period.
+
+ALTER TABLE budget_line_items
+    ADD COLUMN monthly_amounts JSONB;
+
+ALTER TABLE budget_line_items
+    ADD CONSTRAINT budget_line_items_monthly_amounts_check
+    CHECK (monthly_amounts IS NULL OR jsonb_typeof(monthly_amounts) = 'object');
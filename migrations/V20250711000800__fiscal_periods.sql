@@ -0,0 +1,31 @@
+-- Fiscal periods with close/lock dates.
+-- Once a period is CLOSED, transactions dated inside it can no longer be created, edited
+-- or deleted unless the user holds the 'period.reopen' permission.
+
+CREATE TABLE fiscal_periods (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'OPEN' CHECK (status IN ('OPEN', 'CLOSED')),
+    closed_at TIMESTAMPTZ,
+    closed_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id),
+    UNIQUE (tenant_id, name),
+    CHECK (end_date >= start_date)
+);
+
+-- Lookup of the closed period covering a transaction date
+CREATE INDEX idx_fiscal_periods_tenant_dates ON fiscal_periods (tenant_id, start_date, end_date);
+
+-- Seed the reopen permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'period.reopen', 'Reopen closed fiscal periods and change transactions dated inside them', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,32 @@
+-- Notifications with per-user delivery rules.
+-- CRITICAL notifications are emailed immediately, NORMAL ones immediately outside quiet hours,
+-- and LOW ones are batched into an hourly or daily digest email.
+
+CREATE TABLE notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id),
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC', -- IANA name, e.g. 'Europe/Berlin'
+    quiet_hours_start TIME,                      -- Local time; both NULL = no quiet hours
+    quiet_hours_end TIME,                        -- May be earlier than start (spans midnight)
+    digest_frequency VARCHAR(10) NOT NULL DEFAULT 'DAILY' CHECK (digest_frequency IN ('HOURLY', 'DAILY')),
+    email_enabled BOOLEAN NOT NULL DEFAULT TRUE,  -- FALSE = in-app only (critical alerts are still emailed)
+    last_digest_sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((quiet_hours_start IS NULL) = (quiet_hours_end IS NULL))
+);
+
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    tenant_id UUID REFERENCES tenants(id), -- Nullable for account-level notifications
+    priority VARCHAR(10) NOT NULL CHECK (priority IN ('LOW', 'NORMAL', 'CRITICAL')),
+    subject VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    delivered_at TIMESTAMPTZ,  -- NULL while waiting for delivery or a digest
+    delivery_attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_notifications_user_created ON notifications (user_id, created_at DESC);
+CREATE INDEX idx_notifications_undelivered ON notifications (user_id) WHERE delivered_at IS NULL;
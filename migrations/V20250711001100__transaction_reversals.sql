@@ -0,0 +1,9 @@
+-- Reversals: instead of editing amounts in place, a transaction is reversed by an
+-- offsetting transaction with mirrored journal entries. Both rows point at each other.
+
+ALTER TABLE transactions
+    ADD COLUMN reversal_of_id UUID REFERENCES transactions(id),
+    ADD COLUMN reversed_by_id UUID REFERENCES transactions(id);
+
+-- A transaction can be reversed only once
+CREATE UNIQUE INDEX idx_transactions_reversal_of_id ON transactions (reversal_of_id) WHERE reversal_of_id IS NOT NULL;
@@ -0,0 +1,26 @@
+-- Draft -> posted workflow for transactions.
+-- Journal entries can be edited only while a transaction is DRAFT; reports count POSTED only.
+-- Existing rows were already live in the ledger, so they are backfilled as POSTED. The column
+-- default stays POSTED for system-generated rows (recurring occurrences, reversals); the API
+-- creates DRAFT unless told otherwise.
+
+ALTER TABLE transactions
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'POSTED'
+        CHECK (status IN ('DRAFT', 'PENDING_APPROVAL', 'POSTED', 'VOIDED')),
+    ADD COLUMN posted_at TIMESTAMPTZ,
+    ADD COLUMN posted_by UUID REFERENCES users(id),
+    ADD COLUMN voided_at TIMESTAMPTZ,
+    ADD COLUMN voided_by UUID REFERENCES users(id),
+    ADD COLUMN void_reason TEXT;
+
+UPDATE transactions SET posted_at = created_at, posted_by = created_by;
+
+CREATE INDEX idx_transactions_tenant_status ON transactions (tenant_id, status);
+
+-- Posting a transaction that is waiting for approval
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'tx.approve', 'Approve and post transactions submitted for approval', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
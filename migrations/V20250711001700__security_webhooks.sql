@@ -0,0 +1,28 @@
+-- Tenant-configured webhook for security events (e.g., to a SIEM).
+-- One endpoint per tenant; each event type must be opted into. Payloads are signed with
+-- HMAC-SHA256 using the per-tenant signing secret, stored encrypted.
+
+CREATE TABLE security_webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL UNIQUE REFERENCES tenants(id),
+    url TEXT NOT NULL,
+    signing_secret TEXT NOT NULL, -- Encrypted with TOKEN_ENCRYPTION_KEY
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    last_delivery_at TIMESTAMPTZ,
+    last_delivery_status INTEGER, -- HTTP status of the last attempt, NULL if unreachable
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id),
+    CHECK (event_types <@ ARRAY['MEMBER_ADDED', 'ROLE_CHANGED', 'API_KEY_CREATED', 'REPEATED_FAILED_LOGINS']::TEXT[])
+);
+
+-- Seed the management permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'security.manage', 'Configure the tenant''s security webhook', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,17 @@
+-- Append-only audit trail of security- and ledger-relevant actions.
+-- Rows are never updated or deleted; they can additionally be forwarded to a SIEM
+-- (see AUDIT_SINK in .env.example).
+
+CREATE TABLE audit_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID REFERENCES tenants(id), -- NULL for deployment-wide events
+    actor_user_id UUID REFERENCES users(id),
+    action VARCHAR(100) NOT NULL, -- e.g., 'transaction.post', 'fiscal_period.close'
+    entity_type VARCHAR(50) NOT NULL,
+    entity_id UUID,
+    details JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_events_tenant_created ON audit_events (tenant_id, created_at);
+CREATE INDEX idx_audit_events_entity ON audit_events (entity_type, entity_id);
@@ -0,0 +1,13 @@
+-- Widget types evaluated by the dashboard data endpoint: adds the cash balance trend and
+-- budget burn widgets.
+
+ALTER TABLE dashboard_widgets DROP CONSTRAINT dashboard_widgets_widget_type_check;
+ALTER TABLE dashboard_widgets ADD CONSTRAINT dashboard_widgets_widget_type_check CHECK (
+    widget_type IN (
+        'cash_balance_summary', 'cash_balance_trend', 'spending_by_category',
+        'income_vs_expense_summary', 'account_balance_list', 'budget_burn', 'custom_report_link'
+    )
+);
+
+-- At most one default dashboard per user and tenant
+CREATE UNIQUE INDEX idx_dashboards_one_default ON dashboards (tenant_id, user_id) WHERE is_default;
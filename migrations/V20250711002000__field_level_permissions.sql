@@ -0,0 +1,14 @@
+-- Field-level permissions: amounts on sensitive accounts (e.g., payroll) and attachment
+-- URLs are only shown to users holding the matching permission.
+
+ALTER TABLE accounts ADD COLUMN is_sensitive BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Seed the permissions; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT p.name, p.description, u.id, u.id
+FROM (VALUES
+    ('data.view_sensitive_accounts', 'See amounts on accounts flagged as sensitive'),
+    ('data.view_attachments', 'See attachment URLs on transactions')
+) AS p(name, description)
+CROSS JOIN (SELECT id FROM users ORDER BY created_at LIMIT 1) u
+ON CONFLICT (name) DO NOTHING;
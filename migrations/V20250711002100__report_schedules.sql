@@ -0,0 +1,49 @@
+-- Scheduled email delivery of custom reports, and per-tenant outgoing mail settings.
+
+-- Budget vs actual reports (configuration: {"budget_id": "..."})
+ALTER TABLE custom_reports DROP CONSTRAINT IF EXISTS custom_reports_report_type_check;
+ALTER TABLE custom_reports ADD CONSTRAINT custom_reports_report_type_check
+    CHECK (report_type IN ('TRANSACTION_LIST', 'SUMMARY_BY_CATEGORY', 'ACCOUNT_BALANCE_SUMMARY', 'INCOME_EXPENSE_STATEMENT', 'BUDGET_VS_ACTUAL'));
+
+CREATE TABLE report_schedules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    custom_report_id UUID NOT NULL REFERENCES custom_reports(id) ON DELETE CASCADE,
+    cron_expression VARCHAR(100) NOT NULL, -- Standard 5-field cron, e.g. '0 7 1 * *' (07:00 on the 1st)
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC', -- IANA name the cron expression is evaluated in
+    period VARCHAR(20) NOT NULL DEFAULT 'PREVIOUS_MONTH' CHECK (period IN ('PREVIOUS_MONTH', 'MONTH_TO_DATE', 'YEAR_TO_DATE')),
+    recipients TEXT[] NOT NULL CHECK (cardinality(recipients) > 0),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id)
+);
+
+CREATE INDEX idx_report_schedules_custom_report_id ON report_schedules (custom_report_id);
+CREATE INDEX idx_report_schedules_due ON report_schedules (next_run_at) WHERE is_active;
+
+-- A tenant's own SMTP server; tenants without one use the deployment's (SMTP_* env vars).
+CREATE TABLE tenant_mail_settings (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id),
+    smtp_host VARCHAR(255) NOT NULL,
+    smtp_port INTEGER NOT NULL DEFAULT 587 CHECK (smtp_port BETWEEN 1 AND 65535),
+    smtp_username VARCHAR(255),
+    smtp_password TEXT, -- Encrypted with TOKEN_ENCRYPTION_KEY
+    from_address VARCHAR(320) NOT NULL, -- e.g. 'Acme Finance <finance@acme.example>'
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id)
+);
+
+-- Seed the management permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'mail.manage', 'Configure the tenant''s outgoing mail server', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
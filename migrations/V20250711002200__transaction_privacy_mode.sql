@@ -0,0 +1,18 @@
+-- Privacy mode: transaction descriptions and journal entry memos are stored encrypted
+-- with a per-tenant data key and only shown in clear to holders of
+-- transactions.read_sensitive. Amounts, dates, accounts and categories stay in clear so
+-- reports keep working.
+
+ALTER TABLE tenants
+    ADD COLUMN privacy_mode BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN data_key TEXT; -- Tenant data key, itself encrypted with TOKEN_ENCRYPTION_KEY; created on first enable
+
+-- Seed the permissions; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT p.name, p.description, u.id, u.id
+FROM (VALUES
+    ('transactions.read_sensitive', 'Read transaction descriptions and memos protected by privacy mode'),
+    ('privacy.manage', 'Turn the tenant''s privacy mode on or off')
+) AS p(name, description)
+CROSS JOIN (SELECT id FROM users ORDER BY created_at LIMIT 1) u
+ON CONFLICT (name) DO NOTHING;
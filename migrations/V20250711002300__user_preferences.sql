@@ -0,0 +1,11 @@
+-- Per-user, per-tenant preferences. Pinned accounts are kept as an ordered array: the
+-- array position is the pin order, and account lists show pinned accounts first.
+
+CREATE TABLE user_preferences (
+    user_id UUID NOT NULL REFERENCES users(id),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    pinned_account_ids UUID[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, tenant_id)
+);
@@ -0,0 +1,23 @@
+-- Per-tenant opt-in for daily exchange rates pulled from the configured provider
+-- (EXCHANGE_RATE_PROVIDER). Fetched rates land in exchange_rates with the provider as `source`.
+CREATE TABLE exchange_rate_fetch_settings (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id),
+    is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_fetched_at TIMESTAMPTZ,
+    last_rate_date DATE,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id)
+);
+
+CREATE INDEX idx_exchange_rate_fetch_settings_enabled ON exchange_rate_fetch_settings (tenant_id) WHERE is_enabled;
+
+-- Seed the management permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'rates.manage', 'Configure and refresh the tenant''s exchange rates', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
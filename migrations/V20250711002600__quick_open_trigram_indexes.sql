@@ -0,0 +1,8 @@
+-- Trigram indexes behind GET /tenants/:id/quick-open, so substring matches stay index scans.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX idx_accounts_name_trgm ON accounts USING GIN (name gin_trgm_ops) WHERE is_active;
+CREATE INDEX idx_accounts_code_trgm ON accounts USING GIN (account_code gin_trgm_ops) WHERE is_active;
+CREATE INDEX idx_categories_name_trgm ON categories USING GIN (name gin_trgm_ops) WHERE is_active;
+CREATE INDEX idx_custom_reports_name_trgm ON custom_reports USING GIN (name gin_trgm_ops);
+CREATE INDEX idx_transactions_description_trgm ON transactions USING GIN (description gin_trgm_ops);
@@ -0,0 +1,35 @@
+-- Where unrealized exchange gains and losses are booked when foreign-currency accounts
+-- are revalued at period end.
+CREATE TABLE fx_revaluation_settings (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id),
+    unrealized_gain_account_id UUID NOT NULL REFERENCES accounts(id),
+    unrealized_loss_account_id UUID NOT NULL REFERENCES accounts(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id)
+);
+
+-- One row per posted revaluation; `lines` keeps the per-account computation.
+CREATE TABLE fx_revaluations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    as_of DATE NOT NULL,
+    transaction_id UUID NOT NULL REFERENCES transactions(id),
+    base_currency_code CHAR(3) NOT NULL REFERENCES currencies(code),
+    total_gain NUMERIC(18, 2) NOT NULL,
+    total_loss NUMERIC(18, 2) NOT NULL,
+    lines JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id)
+);
+
+CREATE INDEX idx_fx_revaluations_tenant_as_of ON fx_revaluations (tenant_id, as_of);
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'fx.revalue', 'Configure and post foreign-currency revaluations', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
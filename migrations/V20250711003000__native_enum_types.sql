@@ -0,0 +1,41 @@
+-- Native enum types for the fixed vocabularies that were TEXT columns with CHECK constraints.
+-- Journal entry sides and account normal balances share one type so they can still be
+-- compared directly (`je.entry_type = at.normal_balance`).
+
+CREATE TYPE entry_side AS ENUM ('DEBIT', 'CREDIT');
+CREATE TYPE transaction_type AS ENUM (
+    'INCOME', 'EXPENSE', 'TRANSFER', 'JOURNAL_ENTRY', 'OPENING_BALANCE', 'ADJUSTMENT'
+);
+CREATE TYPE category_type AS ENUM ('INCOME', 'EXPENSE', 'TRANSFER', 'INVESTMENT', 'OTHER');
+
+-- The old CHECK constraints compare against VARCHAR literals and would not survive the type change.
+ALTER TABLE account_types DROP CONSTRAINT account_types_normal_balance_check;
+ALTER TABLE account_types
+    ALTER COLUMN normal_balance TYPE entry_side USING normal_balance::entry_side;
+
+ALTER TABLE journal_entries DROP CONSTRAINT journal_entries_entry_type_check;
+ALTER TABLE journal_entries
+    ALTER COLUMN entry_type TYPE entry_side USING entry_type::entry_side;
+
+ALTER TABLE categories DROP CONSTRAINT categories_type_check;
+ALTER TABLE categories
+    ALTER COLUMN type TYPE category_type USING type::category_type;
+
+ALTER TABLE transactions DROP CONSTRAINT transactions_type_check;
+ALTER TABLE transactions
+    ALTER COLUMN type TYPE transaction_type USING type::transaction_type;
+
+-- Recurring definitions use a subset of transaction types; keep that restriction.
+ALTER TABLE recurring_transactions
+    DROP CONSTRAINT recurring_transactions_type_check,
+    DROP CONSTRAINT recurring_transactions_template_check;
+ALTER TABLE recurring_transactions
+    ALTER COLUMN type TYPE transaction_type USING type::transaction_type;
+ALTER TABLE recurring_transactions
+    ADD CONSTRAINT recurring_transactions_type_check
+    CHECK (type IN ('INCOME', 'EXPENSE', 'TRANSFER', 'JOURNAL_ENTRY')),
+    ADD CONSTRAINT recurring_transactions_template_check
+    CHECK (
+        (type = 'JOURNAL_ENTRY' AND journal_template IS NOT NULL)
+        OR (type <> 'JOURNAL_ENTRY' AND account_id IS NOT NULL)
+    );
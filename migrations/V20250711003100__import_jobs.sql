@@ -0,0 +1,25 @@
+-- Background import jobs: progress while rows are processed, and a downloadable file of
+-- the rows that failed (original columns plus the reason) once the job finishes.
+
+CREATE TYPE import_job_status AS ENUM ('PENDING', 'RUNNING', 'COMPLETED', 'FAILED');
+
+CREATE TABLE import_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    kind VARCHAR(50) NOT NULL CHECK (kind IN ('BUDGET_CSV')),
+    status import_job_status NOT NULL DEFAULT 'PENDING',
+    total_rows INTEGER, -- Known once the file has been read
+    processed_rows INTEGER NOT NULL DEFAULT 0,
+    failed_rows INTEGER NOT NULL DEFAULT 0,
+    result_id UUID, -- What the import created, e.g. the budget
+    error_message TEXT,
+    error_csv TEXT, -- Failed rows with reason columns, ready to fix and re-upload
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id)
+);
+
+CREATE INDEX idx_import_jobs_tenant_created ON import_jobs (tenant_id, created_at DESC);
@@ -0,0 +1,13 @@
+-- Yearly amount escalation for recurring definitions: the amount (or every leg of a journal
+-- template) rises by escalation_percent on the first of escalation_month each year.
+-- Escalation dates up to escalated_through are already reflected in the amount; when it is
+-- NULL, escalation starts after start_date.
+
+ALTER TABLE recurring_transactions
+    ADD COLUMN escalation_percent NUMERIC(7, 4),
+    ADD COLUMN escalation_month SMALLINT CHECK (escalation_month BETWEEN 1 AND 12),
+    ADD COLUMN escalated_through DATE;
+
+ALTER TABLE recurring_transactions
+    ADD CONSTRAINT recurring_transactions_escalation_check
+    CHECK ((escalation_percent IS NULL) = (escalation_month IS NULL));
@@ -0,0 +1,25 @@
+-- Links materialized occurrences to their recurring definition, and lets a definition be
+-- paused or have single occurrences skipped.
+
+ALTER TABLE transactions
+    ADD COLUMN recurring_transaction_id UUID REFERENCES recurring_transactions(id),
+    ADD COLUMN recurring_occurrence_date DATE,
+    ADD CONSTRAINT transactions_recurring_occurrence_check
+    CHECK ((recurring_transaction_id IS NULL) = (recurring_occurrence_date IS NULL));
+
+-- One transaction per occurrence; also serves lookups of a series' occurrences
+CREATE UNIQUE INDEX idx_transactions_recurring_occurrence
+    ON transactions (recurring_transaction_id, recurring_occurrence_date)
+    WHERE recurring_transaction_id IS NOT NULL;
+
+-- NULL while the definition is running
+ALTER TABLE recurring_transactions
+    ADD COLUMN paused_at TIMESTAMPTZ;
+
+CREATE TABLE recurring_transaction_skips (
+    recurring_transaction_id UUID NOT NULL REFERENCES recurring_transactions(id) ON DELETE CASCADE,
+    occurrence_date DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    PRIMARY KEY (recurring_transaction_id, occurrence_date)
+);
@@ -0,0 +1,16 @@
+-- Daily balances of bank-linked cash accounts for the treasury cash position. A row per
+-- account and day is upserted by the scheduler, so today's row tracks late postings.
+
+CREATE TABLE cash_position_snapshots (
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    account_id UUID NOT NULL REFERENCES accounts(id),
+    snapshot_date DATE NOT NULL,
+    institution VARCHAR(255) NOT NULL,
+    currency_code CHAR(3) NOT NULL REFERENCES currencies(code),
+    balance NUMERIC(18, 2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (account_id, snapshot_date)
+);
+
+CREATE INDEX idx_cash_position_snapshots_tenant_date ON cash_position_snapshots (tenant_id, snapshot_date);
@@ -0,0 +1,21 @@
+-- Merchant names cleaned from raw bank descriptors ("AMZN Mktp US*2F4" -> "Amazon"), with
+-- a category hint from the bundled rules and, when it resolves, the tenant's category.
+-- Tenant rules in merchant_rules take precedence over the bundled dataset.
+
+ALTER TABLE external_transactions_staging
+    ADD COLUMN merchant_name VARCHAR(255),
+    ADD COLUMN merchant_category_hint VARCHAR(100),
+    ADD COLUMN merchant_category_id UUID REFERENCES categories(id);
+
+CREATE TABLE merchant_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    pattern VARCHAR(255) NOT NULL, -- Matched against the cleaned, upper-cased descriptor
+    merchant_name VARCHAR(255) NOT NULL,
+    category_id UUID REFERENCES categories(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id),
+    UNIQUE (tenant_id, pattern)
+);
@@ -0,0 +1,18 @@
+-- Client metadata captured when a transaction is entered, e.g. from the mobile app:
+-- where it was entered, on what device and through which channel. One optional row per
+-- transaction; removed with the transaction.
+
+CREATE TABLE transaction_metadata (
+    transaction_id UUID PRIMARY KEY REFERENCES transactions(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    latitude DOUBLE PRECISION CHECK (latitude BETWEEN -90 AND 90),
+    longitude DOUBLE PRECISION CHECK (longitude BETWEEN -180 AND 180),
+    device VARCHAR(255),
+    entry_source VARCHAR(50),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((latitude IS NULL) = (longitude IS NULL))
+);
+
+CREATE INDEX idx_transaction_metadata_tenant_source ON transaction_metadata (tenant_id, entry_source);
+CREATE INDEX idx_transaction_metadata_tenant_location ON transaction_metadata (tenant_id, latitude, longitude)
+    WHERE latitude IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Household use: which member of the tenant a transaction is attributed to (who spent or
+-- paid), and whether it is a shared expense to be split between the members.
+
+CREATE TABLE transaction_attributions (
+    transaction_id UUID PRIMARY KEY REFERENCES transactions(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    member_id UUID NOT NULL REFERENCES users(id),
+    is_shared BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL
+);
+
+CREATE INDEX idx_transaction_attributions_tenant_member ON transaction_attributions (tenant_id, member_id);
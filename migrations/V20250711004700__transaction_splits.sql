@@ -0,0 +1,19 @@
+-- Receipts split across categories: each debit (or credit) journal entry of a split
+-- transaction carries its own category, plus the percentage the client asked for when the
+-- share was given as a percentage rather than an amount.
+
+CREATE TABLE transaction_splits (
+    journal_entry_id UUID PRIMARY KEY REFERENCES journal_entries(id) ON DELETE CASCADE,
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    category_id UUID NOT NULL REFERENCES categories(id),
+    percentage NUMERIC(7, 4) CHECK (percentage > 0 AND percentage <= 100),
+    position INT NOT NULL,
+    UNIQUE (transaction_id, position)
+);
+
+CREATE INDEX idx_transaction_splits_category ON transaction_splits (category_id);
+
+-- Two shares can book to the same account under different categories, so one transaction
+-- may now carry several entries on the same account and side.
+ALTER TABLE journal_entries
+    DROP CONSTRAINT journal_entries_transaction_id_account_id_entry_type_key;
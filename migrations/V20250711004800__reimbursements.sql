@@ -0,0 +1,30 @@
+-- Reimbursable expenses and the deposits that pay them back. An expense is marked with
+-- the payer expected to reimburse it (employer, client, insurer); deposits are matched to
+-- expenses with the amount they cover, so one deposit can settle several expenses and an
+-- expense can be repaid in instalments.
+
+CREATE TABLE reimbursable_expenses (
+    transaction_id UUID PRIMARY KEY REFERENCES transactions(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    payer VARCHAR(255) NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL
+);
+
+CREATE INDEX idx_reimbursable_expenses_tenant_payer ON reimbursable_expenses (tenant_id, payer);
+
+CREATE TABLE reimbursement_matches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    expense_transaction_id UUID NOT NULL REFERENCES reimbursable_expenses(transaction_id) ON DELETE CASCADE,
+    deposit_transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    amount NUMERIC(18, 2) NOT NULL CHECK (amount > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    UNIQUE (expense_transaction_id, deposit_transaction_id)
+);
+
+CREATE INDEX idx_reimbursement_matches_deposit ON reimbursement_matches (deposit_transaction_id);
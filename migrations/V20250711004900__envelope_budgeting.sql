@@ -0,0 +1,23 @@
+-- Envelope (zero-based) budgeting: a budget in envelope mode treats each line item as an
+-- envelope. Income received in the budget currency during the period is "ready to assign"
+-- until it is given to an envelope; money can then be moved between envelopes mid-period.
+-- An envelope's assigned amount is its budgeted_amount plus moves in, minus moves out.
+
+ALTER TABLE budgets
+    ADD COLUMN is_envelope BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE envelope_moves (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    budget_id UUID NOT NULL REFERENCES budgets(id),
+    from_line_item_id UUID NOT NULL REFERENCES budget_line_items(id),
+    to_line_item_id UUID NOT NULL REFERENCES budget_line_items(id),
+    amount NUMERIC(18, 2) NOT NULL CHECK (amount > 0),
+    moved_on DATE NOT NULL,
+    memo TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    CHECK (from_line_item_id <> to_line_item_id)
+);
+
+CREATE INDEX idx_envelope_moves_budget ON envelope_moves (budget_id, moved_on);
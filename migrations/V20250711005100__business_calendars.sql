@@ -0,0 +1,32 @@
+-- Business-day adjustment for recurring transactions.
+--
+-- Each tenant has a business calendar: its weekend days (ISO weekday numbers, 1 = Monday)
+-- and a list of holidays, which can be seeded with a country's public holidays. A
+-- recurring definition's rule says what happens to an occurrence that falls on a
+-- non-business day: post it on the previous or next business day, on the next one unless
+-- that is in the following month (modified following), or skip it.
+
+CREATE TYPE business_day_rule AS ENUM ('NONE', 'PREVIOUS', 'NEXT', 'MODIFIED_NEXT', 'SKIP');
+
+ALTER TABLE recurring_transactions
+    ADD COLUMN business_day_rule business_day_rule NOT NULL DEFAULT 'NONE';
+
+CREATE TABLE business_calendars (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    country_code CHAR(2),
+    weekend_days SMALLINT[] NOT NULL DEFAULT '{6,7}'
+        CHECK (weekend_days <@ ARRAY[1, 2, 3, 4, 5, 6, 7]::SMALLINT[] AND cardinality(weekend_days) < 7),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id)
+);
+
+CREATE TABLE business_holidays (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    holiday_date DATE NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    country_code CHAR(2), -- Set when seeded from a country's public holidays
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    UNIQUE (tenant_id, holiday_date)
+);
@@ -0,0 +1,30 @@
+-- Login sessions. Each session is a refresh token family: every refresh exchanges the
+-- current token for a new one and marks the old one rotated. Presenting a rotated token
+-- again means it was copied, so the whole session is revoked. Only SHA-256 hashes of the
+-- tokens are stored.
+
+CREATE TYPE session_revocation_reason AS ENUM ('SIGNED_OUT', 'TOKEN_REUSE');
+
+CREATE TABLE auth_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    user_agent TEXT,
+    ip_address TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    revoked_reason session_revocation_reason
+);
+
+CREATE INDEX idx_auth_sessions_user_active ON auth_sessions (user_id) WHERE revoked_at IS NULL;
+
+CREATE TABLE session_refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id UUID NOT NULL REFERENCES auth_sessions(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rotated_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_session_refresh_tokens_session ON session_refresh_tokens (session_id);
@@ -0,0 +1,40 @@
+-- API keys and sandbox tenants. A key acts as the user who created it within one tenant.
+-- Sandbox keys are redirected to the tenant's sandbox: a separate tenant holding a copy of
+-- the chart of accounts, categories, tags and member roles, but none of the books, so
+-- integrations can post and receive webhooks without touching production data.
+-- Only SHA-256 hashes of the keys are stored.
+
+-- Account codes only need to be unique within a tenant (the composite constraint stays);
+-- the global constraint would stop a sandbox from copying the chart of accounts.
+ALTER TABLE accounts DROP CONSTRAINT accounts_account_code_key;
+
+CREATE TABLE tenant_sandboxes (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id),
+    sandbox_tenant_id UUID NOT NULL UNIQUE REFERENCES tenants(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id)
+);
+
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    user_id UUID NOT NULL REFERENCES users(id),
+    name VARCHAR(255) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    is_sandbox BOOLEAN NOT NULL DEFAULT FALSE,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id)
+);
+
+CREATE INDEX idx_api_keys_tenant ON api_keys (tenant_id);
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'api_keys.manage', 'Create and revoke the tenant''s API keys', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,26 @@
+-- Export artifacts: report files rendered in the background and kept on the storage
+-- backend (STORAGE_BACKEND) until they expire, so large exports can be downloaded with
+-- Range requests and resumed instead of being rendered again on every request.
+
+CREATE TYPE export_status AS ENUM ('PENDING', 'READY', 'FAILED');
+
+CREATE TABLE export_artifacts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    kind VARCHAR(50) NOT NULL CHECK (kind IN ('CUSTOM_REPORT', 'EVENT_REPORT')),
+    source_id UUID NOT NULL, -- The custom report or event exported
+    status export_status NOT NULL DEFAULT 'PENDING',
+    file_name VARCHAR(255),
+    content_type VARCHAR(100) NOT NULL DEFAULT 'text/csv; charset=utf-8',
+    size_bytes BIGINT,
+    sha256 CHAR(64), -- Hex digest of the file, also served as its ETag
+    storage_key TEXT, -- Set once the file is stored
+    error_message TEXT,
+    expires_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id)
+);
+
+CREATE INDEX idx_export_artifacts_tenant_created ON export_artifacts (tenant_id, created_at DESC);
+CREATE INDEX idx_export_artifacts_expires ON export_artifacts (expires_at);
@@ -0,0 +1,17 @@
+-- Sign-in with OpenID Connect providers (Google, Microsoft). Such users are found by
+-- users.auth_provider_type (GOOGLE, MICROSOFT) and auth_provider_id (the provider's subject
+-- ID); they may have no password.
+-- A login state lives from the redirect to the provider until its callback, and binds the
+-- callback to the nonce and PKCE verifier of the request that started it. Only a SHA-256
+-- hash of the state is stored.
+
+CREATE TABLE oidc_login_states (
+    state_hash TEXT PRIMARY KEY,
+    provider VARCHAR(50) NOT NULL CHECK (provider IN ('GOOGLE', 'MICROSOFT')),
+    nonce TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_oidc_login_states_expires ON oidc_login_states (expires_at);
@@ -0,0 +1,17 @@
+-- Tenant backups: a ZIP of the tenant's books (one JSON file per table) stored as an export
+-- artifact, optionally encrypted with a passphrase the user supplies. The passphrase is
+-- never stored; an encrypted bundle cannot be read without it.
+
+ALTER TABLE export_artifacts DROP CONSTRAINT export_artifacts_kind_check;
+ALTER TABLE export_artifacts ADD CONSTRAINT export_artifacts_kind_check
+    CHECK (kind IN ('CUSTOM_REPORT', 'EVENT_REPORT', 'TENANT_BACKUP'));
+
+ALTER TABLE export_artifacts ADD COLUMN is_encrypted BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'tenant.backup', 'Download full backups of the tenant''s books', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,32 @@
+-- Tenant invitations: an emailed, single-use link that adds the recipient to a tenant with
+-- a role. Accepting creates the user (or links the existing account with that email) and
+-- grants the role. Only the SHA-256 hash of the token is stored.
+
+CREATE TABLE tenant_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    email VARCHAR(255) NOT NULL,
+    role_id UUID NOT NULL REFERENCES roles(id),
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    accepted_by UUID REFERENCES users(id),
+    revoked_at TIMESTAMPTZ,
+    revoked_by UUID REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL REFERENCES users(id),
+    CHECK (accepted_at IS NULL OR revoked_at IS NULL)
+);
+
+-- At most one open invitation per address and tenant
+CREATE UNIQUE INDEX idx_tenant_invitations_open
+    ON tenant_invitations (tenant_id, LOWER(email))
+    WHERE accepted_at IS NULL AND revoked_at IS NULL;
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'members.invite', 'Invite people to the tenant and revoke pending invitations', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,14 @@
+-- Anonymized tenant copies for support: the backup bundle with names, descriptions and
+-- amounts scrambled, so problems can be reproduced without seeing real financial data.
+
+ALTER TABLE export_artifacts DROP CONSTRAINT export_artifacts_kind_check;
+ALTER TABLE export_artifacts ADD CONSTRAINT export_artifacts_kind_check
+    CHECK (kind IN ('CUSTOM_REPORT', 'EVENT_REPORT', 'TENANT_BACKUP', 'TENANT_ANONYMIZED'));
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'tenant.export_anonymized', 'Download anonymized copies of the tenant''s books for support', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,19 @@
+-- Run history of the database maintenance scheduler (MAINTENANCE_INTERVAL_SECS): one row
+-- per task per run, with what the task did in `detail`. Rows older than 90 days are pruned
+-- by the scheduler itself.
+
+CREATE TYPE maintenance_run_status AS ENUM ('RUNNING', 'SUCCEEDED', 'FAILED');
+
+CREATE TABLE maintenance_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    task VARCHAR(50) NOT NULL CHECK (task IN ('ANALYZE', 'REFRESH_AGGREGATES', 'PRUNE_SESSIONS')),
+    status maintenance_run_status NOT NULL DEFAULT 'RUNNING',
+    rows_affected BIGINT NOT NULL DEFAULT 0, -- Tables analyzed, views refreshed or rows pruned
+    detail JSONB,
+    error_message TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_maintenance_runs_started ON maintenance_runs (started_at DESC);
+
@@ -0,0 +1,14 @@
+-- Retention for tables that only grow (NOTIFICATION_RETENTION_DAYS,
+-- MAINTENANCE_RUN_RETENTION_DAYS): the maintenance task `apply_retention` deletes old rows
+-- in batches, walking a (timestamp, id) index from the oldest row.
+
+ALTER TABLE maintenance_runs DROP CONSTRAINT maintenance_runs_task_check;
+ALTER TABLE maintenance_runs
+    ADD CONSTRAINT maintenance_runs_task_check
+    CHECK (task IN ('ANALYZE', 'REFRESH_AGGREGATES', 'PRUNE_SESSIONS', 'APPLY_RETENTION'));
+
+CREATE INDEX idx_notifications_created ON notifications (created_at, id);
+
+-- Also serves the newest-first listing, scanned backwards
+DROP INDEX idx_maintenance_runs_started;
+CREATE INDEX idx_maintenance_runs_started ON maintenance_runs (started_at, id);
@@ -0,0 +1,7 @@
+-- Archiving: an archived account or category stays active, so historical reports and
+-- drill-downs show it as before, but it is hidden from pickers (unless asked for) and
+-- rejected for new postings. Unlike deactivation it is meant for entities that were used
+-- but are no longer needed, and is undone with unarchive.
+
+ALTER TABLE accounts ADD COLUMN archived_at TIMESTAMPTZ;
+ALTER TABLE categories ADD COLUMN archived_at TIMESTAMPTZ;
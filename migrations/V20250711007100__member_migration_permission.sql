@@ -0,0 +1,11 @@
+-- Exporting a tenant's members with their roles and importing them into another deployment
+-- (e.g. moving from self-hosted to cloud). Memberships are matched by email, so no new
+-- tables are needed; only the permission is seeded.
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'members.migrate', 'Export the tenant''s members and roles, and import them from another deployment', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
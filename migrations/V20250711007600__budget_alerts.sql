@@ -0,0 +1,21 @@
+-- Budget alerts: each line item can carry thresholds in percent of its budgeted amount
+-- (e.g. 80 and 100). A periodic job compares actual spending to them and records every
+-- threshold crossed, once, so an alert never fires twice for the same line.
+
+ALTER TABLE budget_line_items
+    ADD COLUMN alert_thresholds NUMERIC(6, 2)[] NOT NULL DEFAULT '{}';
+
+CREATE TABLE budget_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    budget_id UUID NOT NULL REFERENCES budgets(id) ON DELETE CASCADE,
+    budget_line_item_id UUID NOT NULL REFERENCES budget_line_items(id) ON DELETE CASCADE,
+    threshold_percent NUMERIC(6, 2) NOT NULL,
+    budgeted_amount NUMERIC(18, 2) NOT NULL, -- At the time the threshold was crossed
+    actual_amount NUMERIC(18, 2) NOT NULL,
+    notified_user_id UUID REFERENCES users(id), -- The budget's owner, when still active
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (budget_line_item_id, threshold_percent)
+);
+
+CREATE INDEX idx_budget_alerts_budget ON budget_alerts (budget_id, created_at DESC);
@@ -0,0 +1,18 @@
+-- Idempotency keys: a POST sent with an Idempotency-Key header records its response, and a
+-- retry with the same key gets that response back instead of running again.
+
+CREATE TABLE idempotency_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    scope TEXT NOT NULL, -- Who sent the key: the API key's hash or the tenant
+    idempotency_key TEXT NOT NULL,
+    request_hash TEXT NOT NULL, -- SHA-256 of method, path and body; a reused key must match
+    response_status SMALLINT, -- NULL while the first request is still running
+    response_content_type TEXT,
+    response_body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    UNIQUE (scope, idempotency_key)
+);
+
+-- Walked by the retention task
+CREATE INDEX idx_idempotency_keys_created_at ON idempotency_keys (created_at, id);
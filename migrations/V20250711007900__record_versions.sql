@@ -0,0 +1,7 @@
+-- Optimistic concurrency: transactions, accounts and budgets carry a version that every
+-- change bumps. Updates must send back the version they read (If-Match), and are rejected
+-- with a conflict when someone else changed the record in between.
+
+ALTER TABLE transactions ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE accounts ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE budgets ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
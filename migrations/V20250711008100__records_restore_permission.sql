@@ -0,0 +1,11 @@
+-- Restoring deactivated accounts, categories and budgets, and listing them with
+-- ?include_inactive=true. Deactivation already keeps the rows, so no new columns are
+-- needed; only the permission is seeded.
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'records.restore', 'List deactivated accounts, categories and budgets, and restore them', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
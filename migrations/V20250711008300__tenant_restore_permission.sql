@@ -0,0 +1,10 @@
+-- Restoring a tenant backup into an empty tenant (POST /tenants/:id/import). The restored
+-- rows go into the existing tables; only the permission is seeded.
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'tenant.restore', 'Restore a backup into the tenant while it is still empty', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,11 @@
+-- Data subject requests: GET /users/:id/export and POST /users/:id/erase. Erased users'
+-- references move to a tombstone user, created by the first erasure; only the permission
+-- is seeded here.
+
+-- Seed the permission; attributed to the first user, skipped on an empty database.
+INSERT INTO permissions (name, description, created_by, updated_by)
+SELECT 'users.personal_data', 'Export and erase the personal data of the tenant''s members', id, id
+FROM users
+ORDER BY created_at
+LIMIT 1
+ON CONFLICT (name) DO NOTHING;
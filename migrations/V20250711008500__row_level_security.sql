@@ -0,0 +1,49 @@
+-- Row-level security on every table with a tenant_id, as defense in depth behind the
+-- tenant_id filters in queries. The application sets app.tenant_id on each connection a
+-- tenant-scoped request uses (see db::with_tenant_scope); other tenants' rows are then
+-- invisible and cannot be written. Work that spans tenants (background jobs, sign-in,
+-- migrations) sets app.all_tenants = 'on' instead (db::without_tenant_scope); sessions
+-- outside the application must do the same or connect as a BYPASSRLS role. A connection
+-- with neither setting sees no tenant's rows. Rows without a tenant (deployment-wide audit
+-- events, account-level notifications) are visible to every scope.
+--
+-- Tables added later need the same policy; the schema self-check reports any that lack it.
+
+CREATE FUNCTION app_tenant_id() RETURNS UUID
+LANGUAGE sql STABLE
+AS $$ SELECT NULLIF(current_setting('app.tenant_id', TRUE), '')::UUID $$;
+
+CREATE FUNCTION app_all_tenants() RETURNS BOOLEAN
+LANGUAGE sql STABLE
+AS $$ SELECT COALESCE(current_setting('app.all_tenants', TRUE) = 'on', FALSE) $$;
+
+DO $$
+DECLARE
+    scoped_table TEXT;
+BEGIN
+    FOR scoped_table IN
+        SELECT c.table_name
+        FROM information_schema.columns c
+        JOIN information_schema.tables t
+            ON t.table_schema = c.table_schema AND t.table_name = c.table_name
+        WHERE c.table_schema = current_schema() AND c.column_name = 'tenant_id'
+            AND t.table_type = 'BASE TABLE' AND c.table_name <> 'tenant_sandboxes'
+    LOOP
+        EXECUTE format('ALTER TABLE %I ENABLE ROW LEVEL SECURITY', scoped_table);
+        -- Also for the table owner, which the application usually connects as
+        EXECUTE format('ALTER TABLE %I FORCE ROW LEVEL SECURITY', scoped_table);
+        EXECUTE format(
+            'CREATE POLICY tenant_isolation ON %I '
+            'USING (app_all_tenants() OR tenant_id = app_tenant_id() '
+            'OR (tenant_id IS NULL AND app_tenant_id() IS NOT NULL))',
+            scoped_table
+        );
+    END LOOP;
+END
+$$;
+
+-- A sandbox request also sees the link to its production tenant
+ALTER TABLE tenant_sandboxes ENABLE ROW LEVEL SECURITY;
+ALTER TABLE tenant_sandboxes FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON tenant_sandboxes
+    USING (app_all_tenants() OR app_tenant_id() IN (tenant_id, sandbox_tenant_id));
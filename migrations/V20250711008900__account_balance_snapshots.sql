@@ -0,0 +1,46 @@
+-- Posted debits and credits per account and calendar month, in the tenant's base currency
+-- (converted amounts where recorded), so statements sum a few monthly rows plus the days of
+-- the bounding months instead of every journal entry. Rows are moved along in the same
+-- database transaction that posts, voids or reverses a transaction; the maintenance task
+-- `rebuild_balance_snapshots` recomputes them from the journal and repairs any drift.
+
+CREATE TABLE account_balance_snapshots (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    period_month DATE NOT NULL CHECK (EXTRACT(DAY FROM period_month) = 1), -- First day of the month
+    debits NUMERIC(18, 2) NOT NULL DEFAULT 0,
+    credits NUMERIC(18, 2) NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (account_id, period_month)
+);
+
+CREATE INDEX idx_account_balance_snapshots_tenant ON account_balance_snapshots (tenant_id, period_month);
+
+-- The days of a month not yet covered by whole-month rows are read from posted transactions
+CREATE INDEX idx_transactions_tenant_posted_date ON transactions (tenant_id, transaction_date)
+    WHERE status = 'POSTED';
+
+ALTER TABLE account_balance_snapshots ENABLE ROW LEVEL SECURITY;
+ALTER TABLE account_balance_snapshots FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON account_balance_snapshots
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+-- The backfill reads every tenant's postings, also when run outside the application
+SELECT set_config('app.all_tenants', 'on', TRUE);
+INSERT INTO account_balance_snapshots (tenant_id, account_id, period_month, debits, credits)
+SELECT
+    a.tenant_id, je.account_id, date_trunc('month', t.transaction_date)::date,
+    COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'DEBIT'), 0),
+    COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'CREDIT'), 0)
+FROM journal_entries je
+JOIN transactions t ON t.id = je.transaction_id AND t.status = 'POSTED'
+JOIN accounts a ON a.id = je.account_id
+GROUP BY a.tenant_id, je.account_id, date_trunc('month', t.transaction_date)::date;
+
+ALTER TABLE maintenance_runs DROP CONSTRAINT maintenance_runs_task_check;
+ALTER TABLE maintenance_runs
+    ADD CONSTRAINT maintenance_runs_task_check
+    CHECK (task IN (
+        'ANALYZE', 'REFRESH_AGGREGATES', 'PRUNE_SESSIONS', 'APPLY_RETENTION',
+        'REBUILD_BALANCE_SNAPSHOTS'
+    ));
@@ -0,0 +1,26 @@
+-- Printable statements: the tenant's logo for PDF headers, balance sheets as custom reports,
+-- and PDF attachments for scheduled report emails.
+
+-- At most one logo per tenant, kept as uploaded (PNG or JPEG)
+CREATE TABLE tenant_logos (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    content_type VARCHAR(20) NOT NULL CHECK (content_type IN ('image/png', 'image/jpeg')),
+    width INTEGER NOT NULL CHECK (width > 0),
+    height INTEGER NOT NULL CHECK (height > 0),
+    image BYTEA NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL REFERENCES users(id)
+);
+
+ALTER TABLE tenant_logos ENABLE ROW LEVEL SECURITY;
+ALTER TABLE tenant_logos FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON tenant_logos
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE custom_reports DROP CONSTRAINT IF EXISTS custom_reports_report_type_check;
+ALTER TABLE custom_reports ADD CONSTRAINT custom_reports_report_type_check
+    CHECK (report_type IN ('TRANSACTION_LIST', 'SUMMARY_BY_CATEGORY', 'ACCOUNT_BALANCE_SUMMARY', 'INCOME_EXPENSE_STATEMENT', 'BUDGET_VS_ACTUAL', 'BALANCE_SHEET'));
+
+-- PDF is only offered for statement reports (checked by the service)
+ALTER TABLE report_schedules
+    ADD COLUMN attachment_format VARCHAR(10) NOT NULL DEFAULT 'CSV' CHECK (attachment_format IN ('CSV', 'PDF'));
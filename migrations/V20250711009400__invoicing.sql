@@ -0,0 +1,111 @@
+-- Invoicing: customers, invoices with their lines and the payments received against them.
+-- Issuing an invoice posts AR debit / revenue credit; a payment posts deposit debit / AR
+-- credit. The posted transactions are linked from the invoice and the payment.
+
+CREATE TABLE customers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    billing_address TEXT,
+    tax_id VARCHAR(50),
+    notes TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL
+);
+
+CREATE INDEX idx_customers_tenant ON customers (tenant_id, name) WHERE is_active = TRUE;
+
+-- Numbers are handed out when an invoice is issued, so drafts never leave gaps
+CREATE TABLE invoice_sequences (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id),
+    prefix VARCHAR(20) NOT NULL DEFAULT 'INV-',
+    next_number BIGINT NOT NULL DEFAULT 1 CHECK (next_number > 0),
+    padding SMALLINT NOT NULL DEFAULT 5 CHECK (padding BETWEEN 1 AND 12),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID
+);
+
+CREATE TABLE invoices (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    customer_id UUID NOT NULL REFERENCES customers(id),
+    invoice_number VARCHAR(40), -- Set when issued
+    status VARCHAR(20) NOT NULL DEFAULT 'DRAFT' CHECK (status IN ('DRAFT', 'ISSUED', 'PARTIALLY_PAID', 'PAID')),
+    issue_date DATE NOT NULL,
+    due_date DATE NOT NULL,
+    currency_code VARCHAR(3) NOT NULL,
+    receivable_account_id UUID NOT NULL REFERENCES accounts(id),
+    total NUMERIC(18, 2) NOT NULL DEFAULT 0 CHECK (total >= 0),
+    amount_paid NUMERIC(18, 2) NOT NULL DEFAULT 0 CHECK (amount_paid >= 0 AND amount_paid <= total),
+    issue_transaction_id UUID REFERENCES transactions(id),
+    notes TEXT,
+    issued_at TIMESTAMPTZ,
+    issued_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL,
+    CHECK (due_date >= issue_date),
+    CHECK ((status = 'DRAFT') = (invoice_number IS NULL))
+);
+
+CREATE UNIQUE INDEX idx_invoices_number ON invoices (tenant_id, invoice_number) WHERE invoice_number IS NOT NULL;
+CREATE INDEX idx_invoices_customer ON invoices (customer_id);
+CREATE INDEX idx_invoices_tenant_status ON invoices (tenant_id, status, due_date);
+
+CREATE TABLE invoice_lines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    invoice_id UUID NOT NULL REFERENCES invoices(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    position INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    quantity NUMERIC(18, 4) NOT NULL CHECK (quantity > 0),
+    unit_price NUMERIC(18, 4) NOT NULL CHECK (unit_price >= 0),
+    amount NUMERIC(18, 2) NOT NULL, -- quantity * unit_price, rounded to cents
+    revenue_account_id UUID NOT NULL REFERENCES accounts(id),
+    UNIQUE (invoice_id, position)
+);
+
+CREATE TABLE invoice_payments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    invoice_id UUID NOT NULL REFERENCES invoices(id),
+    payment_date DATE NOT NULL,
+    amount NUMERIC(18, 2) NOT NULL CHECK (amount > 0),
+    deposit_account_id UUID NOT NULL REFERENCES accounts(id),
+    transaction_id UUID NOT NULL REFERENCES transactions(id),
+    memo TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL
+);
+
+CREATE INDEX idx_invoice_payments_invoice ON invoice_payments (invoice_id, payment_date);
+
+ALTER TABLE customers ENABLE ROW LEVEL SECURITY;
+ALTER TABLE customers FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON customers
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE invoice_sequences ENABLE ROW LEVEL SECURITY;
+ALTER TABLE invoice_sequences FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON invoice_sequences
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE invoices ENABLE ROW LEVEL SECURITY;
+ALTER TABLE invoices FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON invoices
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE invoice_lines ENABLE ROW LEVEL SECURITY;
+ALTER TABLE invoice_lines FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON invoice_lines
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE invoice_payments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE invoice_payments FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON invoice_payments
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
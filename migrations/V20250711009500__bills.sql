@@ -0,0 +1,94 @@
+-- Accounts payable: vendors, the bills they send, and the payments made against them.
+-- Approving a bill posts expense debit / AP credit; a payment posts AP debit / credit to the
+-- account paid from. The posted transactions are linked from the bill and the payment.
+
+CREATE TABLE vendors (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    address TEXT,
+    tax_id VARCHAR(50),
+    payment_terms_days INTEGER CHECK (payment_terms_days BETWEEN 0 AND 365), -- Default due date
+    notes TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL
+);
+
+CREATE INDEX idx_vendors_tenant ON vendors (tenant_id, name) WHERE is_active = TRUE;
+
+CREATE TABLE bills (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    vendor_id UUID NOT NULL REFERENCES vendors(id),
+    bill_number VARCHAR(100), -- The vendor's own number
+    status VARCHAR(20) NOT NULL DEFAULT 'DRAFT' CHECK (status IN ('DRAFT', 'OPEN', 'PARTIALLY_PAID', 'PAID')),
+    bill_date DATE NOT NULL,
+    due_date DATE NOT NULL,
+    currency_code VARCHAR(3) NOT NULL,
+    payable_account_id UUID NOT NULL REFERENCES accounts(id),
+    total NUMERIC(18, 2) NOT NULL DEFAULT 0 CHECK (total >= 0),
+    amount_paid NUMERIC(18, 2) NOT NULL DEFAULT 0 CHECK (amount_paid >= 0 AND amount_paid <= total),
+    approve_transaction_id UUID REFERENCES transactions(id),
+    notes TEXT,
+    approved_at TIMESTAMPTZ,
+    approved_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL,
+    CHECK (due_date >= bill_date)
+);
+
+-- The same vendor number twice is almost always a bill entered twice
+CREATE UNIQUE INDEX idx_bills_vendor_number ON bills (vendor_id, bill_number) WHERE bill_number IS NOT NULL;
+CREATE INDEX idx_bills_tenant_status ON bills (tenant_id, status, due_date);
+
+CREATE TABLE bill_lines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    bill_id UUID NOT NULL REFERENCES bills(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    position INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    amount NUMERIC(18, 2) NOT NULL CHECK (amount >= 0),
+    expense_account_id UUID NOT NULL REFERENCES accounts(id),
+    UNIQUE (bill_id, position)
+);
+
+CREATE TABLE bill_payments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    bill_id UUID NOT NULL REFERENCES bills(id),
+    payment_date DATE NOT NULL,
+    amount NUMERIC(18, 2) NOT NULL CHECK (amount > 0),
+    payment_account_id UUID NOT NULL REFERENCES accounts(id),
+    transaction_id UUID NOT NULL REFERENCES transactions(id),
+    memo TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL
+);
+
+CREATE INDEX idx_bill_payments_bill ON bill_payments (bill_id, payment_date);
+
+ALTER TABLE vendors ENABLE ROW LEVEL SECURITY;
+ALTER TABLE vendors FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON vendors
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE bills ENABLE ROW LEVEL SECURITY;
+ALTER TABLE bills FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON bills
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE bill_lines ENABLE ROW LEVEL SECURITY;
+ALTER TABLE bill_lines FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON bill_lines
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE bill_payments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE bill_payments FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON bill_payments
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
@@ -0,0 +1,44 @@
+-- Payments received from customers or sent to vendors, allocated across several invoices or
+-- bills in one go. What is not allocated stays on the payment as a credit, booked to a
+-- credit account (customer deposits, vendor prepayments), and can be applied later.
+-- Allocations are the invoice and bill payment rows, linked back to the payment.
+
+CREATE TABLE payments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    direction VARCHAR(10) NOT NULL CHECK (direction IN ('RECEIVED', 'SENT')),
+    customer_id UUID REFERENCES customers(id),
+    vendor_id UUID REFERENCES vendors(id),
+    payment_date DATE NOT NULL,
+    amount NUMERIC(18, 2) NOT NULL CHECK (amount > 0),
+    currency_code VARCHAR(3) NOT NULL,
+    account_id UUID NOT NULL REFERENCES accounts(id), -- Deposited to or paid from
+    credit_account_id UUID REFERENCES accounts(id),   -- Holds the unapplied amount
+    unapplied_amount NUMERIC(18, 2) NOT NULL CHECK (unapplied_amount >= 0 AND unapplied_amount <= amount),
+    transaction_id UUID NOT NULL REFERENCES transactions(id),
+    memo TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL,
+    CHECK (
+        (direction = 'RECEIVED' AND customer_id IS NOT NULL AND vendor_id IS NULL)
+        OR (direction = 'SENT' AND vendor_id IS NOT NULL AND customer_id IS NULL)
+    ),
+    CHECK (unapplied_amount = 0 OR credit_account_id IS NOT NULL)
+);
+
+CREATE INDEX idx_payments_tenant ON payments (tenant_id, payment_date);
+CREATE INDEX idx_payments_customer ON payments (customer_id) WHERE customer_id IS NOT NULL;
+CREATE INDEX idx_payments_vendor ON payments (vendor_id) WHERE vendor_id IS NOT NULL;
+
+ALTER TABLE invoice_payments ADD COLUMN payment_id UUID REFERENCES payments(id);
+ALTER TABLE bill_payments ADD COLUMN payment_id UUID REFERENCES payments(id);
+
+CREATE INDEX idx_invoice_payments_payment ON invoice_payments (payment_id) WHERE payment_id IS NOT NULL;
+CREATE INDEX idx_bill_payments_payment ON bill_payments (payment_id) WHERE payment_id IS NOT NULL;
+
+ALTER TABLE payments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE payments FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON payments
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
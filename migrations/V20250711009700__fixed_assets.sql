@@ -0,0 +1,58 @@
+-- Fixed assets and their monthly depreciation. An asset is depreciated from the month it
+-- is placed in service over its useful life, straight-line or declining balance, down to
+-- its salvage value. The depreciation scheduler posts one transaction per asset and month
+-- (debit depreciation expense, credit accumulated depreciation) and records it below.
+
+CREATE TABLE fixed_assets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    asset_tag VARCHAR(100),
+    description TEXT,
+    acquisition_date DATE NOT NULL,
+    in_service_date DATE NOT NULL CHECK (in_service_date >= acquisition_date),
+    cost NUMERIC(18, 2) NOT NULL CHECK (cost > 0),
+    salvage_value NUMERIC(18, 2) NOT NULL DEFAULT 0 CHECK (salvage_value >= 0 AND salvage_value < cost),
+    useful_life_months INTEGER NOT NULL CHECK (useful_life_months BETWEEN 1 AND 1200),
+    method VARCHAR(20) NOT NULL CHECK (method IN ('STRAIGHT_LINE', 'DECLINING_BALANCE')),
+    declining_factor NUMERIC(4, 2) NOT NULL DEFAULT 2.00 CHECK (declining_factor > 0 AND declining_factor <= 4),
+    currency_code VARCHAR(3) NOT NULL,
+    asset_account_id UUID NOT NULL REFERENCES accounts(id),
+    accumulated_depreciation_account_id UUID NOT NULL REFERENCES accounts(id),
+    depreciation_expense_account_id UUID NOT NULL REFERENCES accounts(id),
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE' CHECK (status IN ('ACTIVE', 'FULLY_DEPRECIATED')),
+    accumulated_depreciation NUMERIC(18, 2) NOT NULL DEFAULT 0
+        CHECK (accumulated_depreciation >= 0 AND accumulated_depreciation <= cost - salvage_value),
+    depreciated_through DATE, -- Last month end posted
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL
+);
+
+CREATE INDEX idx_fixed_assets_tenant ON fixed_assets (tenant_id, status);
+CREATE UNIQUE INDEX idx_fixed_assets_tag ON fixed_assets (tenant_id, asset_tag) WHERE asset_tag IS NOT NULL;
+
+CREATE TABLE fixed_asset_depreciations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    asset_id UUID NOT NULL REFERENCES fixed_assets(id),
+    period_end DATE NOT NULL,
+    amount NUMERIC(18, 2) NOT NULL CHECK (amount > 0),
+    transaction_id UUID NOT NULL REFERENCES transactions(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (asset_id, period_end)
+);
+
+CREATE INDEX idx_fixed_asset_depreciations_tenant ON fixed_asset_depreciations (tenant_id, period_end);
+
+ALTER TABLE fixed_assets ENABLE ROW LEVEL SECURITY;
+ALTER TABLE fixed_assets FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON fixed_assets
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
+
+ALTER TABLE fixed_asset_depreciations ENABLE ROW LEVEL SECURITY;
+ALTER TABLE fixed_asset_depreciations FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON fixed_asset_depreciations
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
@@ -0,0 +1,30 @@
+-- Payees: who a transaction was paid to or received from, beyond its free-text description.
+-- Merchant rules can now be keyed by a payee instead of a descriptor pattern; such a rule
+-- categorizes transactions entered with that payee.
+
+CREATE TABLE payees (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL
+);
+
+CREATE UNIQUE INDEX idx_payees_name ON payees (tenant_id, LOWER(name));
+
+ALTER TABLE transactions ADD COLUMN payee_id UUID REFERENCES payees(id);
+CREATE INDEX idx_transactions_payee ON transactions (payee_id) WHERE payee_id IS NOT NULL;
+
+ALTER TABLE merchant_rules ALTER COLUMN pattern DROP NOT NULL;
+ALTER TABLE merchant_rules ADD COLUMN payee_id UUID REFERENCES payees(id);
+ALTER TABLE merchant_rules ADD CONSTRAINT merchant_rules_key_check
+    CHECK ((pattern IS NULL) <> (payee_id IS NULL));
+CREATE UNIQUE INDEX idx_merchant_rules_payee ON merchant_rules (tenant_id, payee_id) WHERE payee_id IS NOT NULL;
+
+ALTER TABLE payees ENABLE ROW LEVEL SECURITY;
+ALTER TABLE payees FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON payees
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
@@ -0,0 +1,37 @@
+-- Dimensions: projects, classes and locations a journal entry can be tagged with, so
+-- revenue and expenses can be reported per project (or class, or location). Tagging is
+-- optional; untagged entries simply don't show up in a filtered report.
+
+CREATE TABLE dimensions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('PROJECT', 'CLASS', 'LOCATION')),
+    name VARCHAR(255) NOT NULL,
+    code VARCHAR(50),
+    description TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_by UUID NOT NULL
+);
+
+CREATE UNIQUE INDEX idx_dimensions_name ON dimensions (tenant_id, kind, LOWER(name));
+CREATE UNIQUE INDEX idx_dimensions_code ON dimensions (tenant_id, kind, code) WHERE code IS NOT NULL;
+
+-- One value per kind on each entry. An account may appear on several entries of the same
+-- side of a transaction (the per-account constraint went with transaction splits), so one
+-- expense can be spread over projects on a single account.
+ALTER TABLE journal_entries
+    ADD COLUMN project_id UUID REFERENCES dimensions(id),
+    ADD COLUMN class_id UUID REFERENCES dimensions(id),
+    ADD COLUMN location_id UUID REFERENCES dimensions(id);
+
+CREATE INDEX idx_journal_entries_project ON journal_entries (project_id) WHERE project_id IS NOT NULL;
+CREATE INDEX idx_journal_entries_class ON journal_entries (class_id) WHERE class_id IS NOT NULL;
+CREATE INDEX idx_journal_entries_location ON journal_entries (location_id) WHERE location_id IS NOT NULL;
+
+ALTER TABLE dimensions ENABLE ROW LEVEL SECURITY;
+ALTER TABLE dimensions FORCE ROW LEVEL SECURITY;
+CREATE POLICY tenant_isolation ON dimensions
+    USING (app_all_tenants() OR tenant_id = app_tenant_id());
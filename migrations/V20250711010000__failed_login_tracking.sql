@@ -0,0 +1,7 @@
+-- Consecutive failed password sign-ins per account, counted within a window, so repeated
+-- failures can be reported to the security webhooks of the account's tenants. Both are
+-- reset by a successful sign-in.
+
+ALTER TABLE users
+    ADD COLUMN failed_login_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN failed_login_window_started_at TIMESTAMPTZ;
@@ -0,0 +1,6 @@
+-- `records.restore` now also covers reactivating deactivated members of the tenant.
+
+UPDATE permissions
+SET description = 'List deactivated accounts, categories and budgets, and restore them and deactivated members',
+    updated_at = NOW()
+WHERE name = 'records.restore';
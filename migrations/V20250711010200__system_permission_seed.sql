@@ -0,0 +1,48 @@
+-- Permissions and the built-in `admin` role no longer depend on a user existing.
+-- Earlier migrations attributed their permission seeds to the first user and skipped an empty
+-- database, which left a fresh install without any permissions. Rows seeded by migrations are
+-- now unattributed (created_by IS NULL), and whoever creates a tenant is granted `admin` in it.
+
+ALTER TABLE permissions
+    ALTER COLUMN created_by DROP NOT NULL,
+    ALTER COLUMN updated_by DROP NOT NULL;
+
+ALTER TABLE roles
+    ALTER COLUMN created_by DROP NOT NULL,
+    ALTER COLUMN updated_by DROP NOT NULL;
+
+ALTER TABLE role_permissions ALTER COLUMN created_by DROP NOT NULL;
+
+INSERT INTO permissions (name, description)
+VALUES
+    ('period.reopen', 'Reopen closed fiscal periods and change transactions dated inside them'),
+    ('tx.approve', 'Approve and post transactions submitted for approval'),
+    ('security.manage', 'Configure the tenant''s security webhook'),
+    ('data.view_sensitive_accounts', 'See amounts on accounts flagged as sensitive'),
+    ('data.view_attachments', 'See attachment URLs on transactions'),
+    ('mail.manage', 'Configure the tenant''s outgoing mail server'),
+    ('transactions.read_sensitive', 'Read transaction descriptions and memos protected by privacy mode'),
+    ('privacy.manage', 'Turn the tenant''s privacy mode on or off'),
+    ('rates.manage', 'Configure and refresh the tenant''s exchange rates'),
+    ('fx.revalue', 'Configure and post foreign-currency revaluations'),
+    ('api_keys.manage', 'Create and revoke the tenant''s API keys'),
+    ('tenant.backup', 'Download full backups of the tenant''s books'),
+    ('members.invite', 'Invite people to the tenant and revoke pending invitations'),
+    ('tenant.export_anonymized', 'Download anonymized copies of the tenant''s books for support'),
+    ('members.migrate', 'Export the tenant''s members and roles, and import them from another deployment'),
+    ('records.restore', 'List deactivated accounts, categories and budgets, and restore them and deactivated members'),
+    ('tenant.restore', 'Restore a backup into the tenant while it is still empty'),
+    ('users.personal_data', 'Export and erase the personal data of the tenant''s members')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO roles (name, description, is_system_role)
+VALUES ('admin', 'Holds every permission in the tenant; granted to the tenant''s creator', TRUE)
+ON CONFLICT (name) DO NOTHING;
+
+-- Migrations that add a permission later must grant it to `admin` as well.
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r
+CROSS JOIN permissions p
+WHERE r.name = 'admin'
+ON CONFLICT (role_id, permission_id) DO NOTHING;
@@ -0,0 +1,14 @@
+-- Short-lived access tokens that authenticate API requests of a login session
+-- (`Authorization: Bearer`). Every sign-in and refresh issues one and drops the session's
+-- previous ones; revoking the session or deactivating the user stops them at once. Only
+-- SHA-256 hashes of the tokens are stored.
+
+CREATE TABLE session_access_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id UUID NOT NULL REFERENCES auth_sessions(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_session_access_tokens_session ON session_access_tokens (session_id);
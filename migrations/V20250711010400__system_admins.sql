@@ -0,0 +1,5 @@
+-- System administrators maintain the global reference data shared by every tenant:
+-- currencies, account types and external providers. Nobody is one by default; operators
+-- grant it directly, e.g. `UPDATE users SET is_system_admin = TRUE WHERE email = '...'`.
+
+ALTER TABLE users ADD COLUMN is_system_admin BOOLEAN NOT NULL DEFAULT FALSE;
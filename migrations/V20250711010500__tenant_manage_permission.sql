@@ -0,0 +1,12 @@
+-- `tenant.manage` guards renaming, reconfiguring and deactivating the tenant itself.
+
+INSERT INTO permissions (name, description)
+VALUES ('tenant.manage', 'Change the tenant''s name and settings, and deactivate it')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT r.id, p.id
+FROM roles r
+JOIN permissions p ON p.name = 'tenant.manage'
+WHERE r.name = 'admin'
+ON CONFLICT (role_id, permission_id) DO NOTHING;
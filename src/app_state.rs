@@ -1,10 +1,22 @@
+use axum::extract::FromRef;
+use metrics_exporter_prometheus::PrometheusHandle;
 use sqlx::PgPool;
 
+use crate::db::DbPools;
+
 /// Shared application state accessible by Axum handlers.
 ///
-/// This struct holds dependencies like the database connection pool.
+/// This struct holds dependencies like the database connection pools. Settings are not
+/// carried here; they are loaded once at startup and read with `config::get()`.
 #[derive(Clone)] // Axum requires AppState to be Clone
 pub struct AppState {
-    pub pool: PgPool,
-    // pub config: crate::config::AppConfig, // Uncomment when config is ready
+    pub pools: DbPools, // The primary and the read replicas (see `consistency::ReadPool`)
+    pub metrics: PrometheusHandle, // Renders the Prometheus recorder for GET /metrics
+}
+
+/// Handlers take `State<PgPool>` for the primary, `pools.writer`.
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> PgPool {
+        state.pools.writer.clone()
+    }
 }
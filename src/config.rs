@@ -1 +1,1124 @@
+//! Application configuration, loaded once at startup.
+//!
+//! Settings are layered: built-in defaults, then an optional TOML file (`CONFIG_FILE`, or
+//! `config.toml` in the working directory when present), then environment variables, which
+//! keep the names the app has always read (`DATABASE_URL`, `SMTP_HOST`, ...). Empty
+//! environment variables count as unset.
+//!
+//! Everything is parsed and checked by `init` before the server starts; all problems are
+//! reported together so a misconfigured deployment refuses to boot instead of failing on
+//! first use. Afterwards any module reads the settings with `config::get()`.
+//!
+//! ```toml
+//! [database]
+//! max_connections = 20
+//!
+//! [cors]
+//! allowed_origins = ["https://app.example.com"]
+//! ```
 
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    net::{IpAddr, Ipv4Addr},
+    path::Path,
+    str::FromStr,
+    sync::OnceLock,
+    time::Duration,
+};
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use serde::{Deserialize, Deserializer};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::{
+    db::IsolationLevel,
+    services::{
+        assistant::AssistantBackend, exchange_rate::RateProvider, mailer::DEFAULT_SMTP_PORT,
+        maintenance::MaintenanceTask,
+    },
+    utils::crypto,
+};
+
+/// File read when `CONFIG_FILE` is not set; skipped if it does not exist.
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+static CONFIG: OnceLock<AppConfig> = OnceLock::new();
+
+/// Every setting of the deployment, grouped by concern.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub cors: CorsConfig,
+    pub rate_limit: RateLimitConfig,
+    pub storage: StorageConfig,
+    pub schedulers: SchedulerConfig,
+    pub mail: MailConfig,
+    pub integrations: IntegrationConfig,
+    pub audit: AuditConfig,
+    pub redis: RedisConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: IpAddr,                            // APP_HOST
+    pub port: u16,                               // PORT
+    pub log_format: LogFormat,                   // LOG_FORMAT
+    pub schema_self_check: bool,                 // SCHEMA_SELF_CHECK
+    pub strict_fields_api_versions: Vec<String>, // STRICT_FIELDS_API_VERSIONS, comma-separated
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            host: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 8080,
+            log_format: LogFormat::Text,
+            schema_self_check: false,
+            strict_fields_api_versions: vec!["v1".to_string()],
+        }
+    }
+}
+
+/// Log output: human-readable lines or one JSON object per line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err("expected 'text' or 'json'".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,               // DATABASE_URL (required)
+    pub max_connections: u32,      // DATABASE_MAX_CONNECTIONS
+    pub min_connections: u32,      // DATABASE_MIN_CONNECTIONS
+    pub acquire_timeout_secs: u64, // DATABASE_ACQUIRE_TIMEOUT_SECS
+    pub replica_urls: Vec<String>, // DATABASE_REPLICA_URLS, comma-separated; heavy reads go here
+    #[serde(deserialize_with = "from_str_setting")]
+    pub financial_tx_isolation: IsolationLevel, // FINANCIAL_TX_ISOLATION
+}
+
+impl DatabaseConfig {
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_timeout_secs)
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            url: String::new(),
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout_secs: 30,
+            replica_urls: Vec::new(),
+            financial_tx_isolation: IsolationLevel::Serializable,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub refresh_token_days: u32,                 // REFRESH_TOKEN_DAYS, lifetime of a login session
+    pub access_token_minutes: u32,               // ACCESS_TOKEN_MINUTES, lifetime of a session's access token
+    pub token_encryption_key: Option<String>,    // TOKEN_ENCRYPTION_KEY, base64 of 32 bytes
+    pub oidc_redirect_base_url: Option<String>,  // OIDC_REDIRECT_BASE_URL, public origin of the API
+    pub google_client_id: Option<String>,        // GOOGLE_CLIENT_ID
+    pub google_client_secret: Option<String>,    // GOOGLE_CLIENT_SECRET
+    pub microsoft_client_id: Option<String>,     // MICROSOFT_CLIENT_ID
+    pub microsoft_client_secret: Option<String>, // MICROSOFT_CLIENT_SECRET
+    pub microsoft_tenant: String, // MICROSOFT_TENANT: common, organizations, consumers or a tenant ID
+    pub app_base_url: Option<String>, // APP_BASE_URL, public origin of the web app (links in emails)
+    pub invitation_ttl_days: u32,     // INVITATION_TTL_DAYS, how long a tenant invitation is valid
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            refresh_token_days: 30,
+            access_token_minutes: 15,
+            token_encryption_key: None,
+            oidc_redirect_base_url: None,
+            google_client_id: None,
+            google_client_secret: None,
+            microsoft_client_id: None,
+            microsoft_client_secret: None,
+            microsoft_tenant: "common".to_string(),
+            app_base_url: None,
+            invitation_ttl_days: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// CORS_ALLOWED_ORIGINS (comma-separated). Empty disables CORS; `*` allows any origin.
+    pub allowed_origins: Vec<String>,
+}
+
+impl CorsConfig {
+    /// The CORS layer for the configured origins, or `None` when CORS is disabled.
+    pub fn layer(&self) -> Option<CorsLayer> {
+        if self.allowed_origins.is_empty() {
+            return None;
+        }
+        let allow_origin = if self.allowed_origins.iter().any(|origin| origin == "*") {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(
+                self.allowed_origins
+                    .iter()
+                    .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+            )
+        };
+        let request_id = HeaderName::from_static("x-request-id");
+        let consistency_token = HeaderName::from_static("x-consistency-token");
+        let idempotency_key = HeaderName::from_static("idempotency-key");
+        let idempotent_replayed = HeaderName::from_static("idempotent-replayed");
+        Some(
+            CorsLayer::new()
+                .allow_origin(allow_origin)
+                .allow_methods([
+                    Method::GET,
+                    Method::POST,
+                    Method::PUT,
+                    Method::PATCH,
+                    Method::DELETE,
+                ])
+                .allow_headers([
+                    header::AUTHORIZATION,
+                    header::CONTENT_TYPE,
+                    header::IF_MATCH,
+                    header::IF_NONE_MATCH,
+                    HeaderName::from_static(crate::middleware::auth::TENANT_ID_HEADER),
+                    HeaderName::from_static(crate::middleware::auth::API_KEY_HEADER),
+                    HeaderName::from_static(crate::services::tenant_restore::PASSPHRASE_HEADER),
+                    request_id.clone(),
+                    consistency_token.clone(),
+                    idempotency_key,
+                ])
+                .expose_headers([
+                    header::ETAG,
+                    request_id,
+                    consistency_token,
+                    idempotent_replayed,
+                    header::RETRY_AFTER,
+                    header::CONTENT_DISPOSITION,
+                ]),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: u32, // RATE_LIMIT_REQUESTS_PER_MINUTE, 0 disables limiting
+    pub burst: u32,               // RATE_LIMIT_BURST
+    pub trust_forwarded_for: bool, // RATE_LIMIT_TRUST_FORWARDED_FOR, when behind a proxy
+}
+
+impl RateLimitConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.requests_per_minute > 0
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            requests_per_minute: 0,
+            burst: 30,
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,              // STORAGE_BACKEND
+    pub local_path: String,                   // STORAGE_LOCAL_PATH
+    pub s3_bucket: Option<String>,            // STORAGE_S3_BUCKET
+    pub s3_region: Option<String>,            // STORAGE_S3_REGION
+    pub s3_endpoint: Option<String>,          // STORAGE_S3_ENDPOINT, for S3-compatible services
+    pub s3_access_key_id: Option<String>,     // STORAGE_S3_ACCESS_KEY_ID
+    pub s3_secret_access_key: Option<String>, // STORAGE_S3_SECRET_ACCESS_KEY
+    pub signed_url_ttl_secs: u64, // STORAGE_SIGNED_URL_TTL_SECS, lifetime of S3 download links
+    pub export_ttl_hours: u64,    // EXPORT_TTL_HOURS, how long export files are kept
+    pub health_url: Option<String>, // STORAGE_HEALTH_URL
+    pub api_key: Option<String>,  // STORAGE_API_KEY
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            backend: StorageBackend::Local,
+            local_path: "./storage".to_string(),
+            s3_bucket: None,
+            s3_region: None,
+            s3_endpoint: None,
+            s3_access_key_id: None,
+            s3_secret_access_key: None,
+            signed_url_ttl_secs: 900,
+            export_ttl_hours: 72,
+            health_url: None,
+            api_key: None,
+        }
+    }
+}
+
+/// Where uploaded and generated files are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    Local,
+    S3,
+}
+
+impl FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "local" => Ok(StorageBackend::Local),
+            "s3" => Ok(StorageBackend::S3),
+            _ => Err("expected 'local' or 's3'".to_string()),
+        }
+    }
+}
+
+/// Seconds between runs of each background job.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerConfig {
+    pub recurring_interval_secs: u64, // RECURRING_SCHEDULER_INTERVAL_SECS
+    pub bank_sync_interval_secs: u64, // BANK_SYNC_INTERVAL_SECS
+    pub notification_interval_secs: u64, // NOTIFICATION_SCHEDULER_INTERVAL_SECS
+    pub report_interval_secs: u64,    // REPORT_SCHEDULER_INTERVAL_SECS
+    pub exchange_rate_interval_secs: u64, // EXCHANGE_RATE_SCHEDULER_INTERVAL_SECS
+    pub cash_position_interval_secs: u64, // CASH_POSITION_SCHEDULER_INTERVAL_SECS
+    pub export_cleanup_interval_secs: u64, // EXPORT_CLEANUP_INTERVAL_SECS
+    pub maintenance_interval_secs: u64, // MAINTENANCE_INTERVAL_SECS
+    pub budget_alert_interval_secs: u64, // BUDGET_ALERT_SCHEDULER_INTERVAL_SECS
+    pub depreciation_interval_secs: u64, // DEPRECIATION_SCHEDULER_INTERVAL_SECS
+    pub maintenance_tasks: Vec<MaintenanceTask>, // MAINTENANCE_TASKS, comma-separated
+    pub maintenance_analyze_ratio: f64, // MAINTENANCE_ANALYZE_RATIO, share of rows changed
+    pub session_retention_days: u32,  // SESSION_RETENTION_DAYS
+    pub notification_retention_days: u32, // NOTIFICATION_RETENTION_DAYS
+    pub maintenance_run_retention_days: u32, // MAINTENANCE_RUN_RETENTION_DAYS
+    pub idempotency_key_retention_days: u32, // IDEMPOTENCY_KEY_RETENTION_DAYS, also how long a key replays
+    pub retention_batch_size: u32,    // RETENTION_BATCH_SIZE, rows per delete
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        SchedulerConfig {
+            recurring_interval_secs: 3600,
+            bank_sync_interval_secs: 21600,
+            notification_interval_secs: 300,
+            report_interval_secs: 60,
+            exchange_rate_interval_secs: 21600,
+            cash_position_interval_secs: 3600,
+            export_cleanup_interval_secs: 3600,
+            maintenance_interval_secs: 86400,
+            budget_alert_interval_secs: 3600,
+            depreciation_interval_secs: 86400,
+            maintenance_tasks: MaintenanceTask::ALL.to_vec(),
+            maintenance_analyze_ratio: 0.1,
+            session_retention_days: 30,
+            notification_retention_days: 90,
+            maintenance_run_retention_days: 90,
+            idempotency_key_retention_days: 1,
+            retention_batch_size: 1000,
+        }
+    }
+}
+
+/// The deployment's SMTP server; tenants can override it with their own mail settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    pub smtp_host: Option<String>, // SMTP_HOST; unset logs emails instead of sending
+    pub smtp_port: u16,            // SMTP_PORT
+    pub smtp_username: Option<String>, // SMTP_USERNAME
+    pub smtp_password: Option<String>, // SMTP_PASSWORD
+    pub from: String,              // MAIL_FROM
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        MailConfig {
+            smtp_host: None,
+            smtp_port: DEFAULT_SMTP_PORT,
+            smtp_username: None,
+            smtp_password: None,
+            from: "Forge <no-reply@localhost>".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IntegrationConfig {
+    pub plaid_client_id: Option<String>, // PLAID_CLIENT_ID
+    pub plaid_secret: Option<String>,    // PLAID_SECRET
+    pub plaid_env: PlaidEnv,             // PLAID_ENV
+    #[serde(deserialize_with = "from_str_setting")]
+    pub exchange_rate_provider: RateProvider, // EXCHANGE_RATE_PROVIDER
+    pub currency_api_url: Option<String>, // CURRENCY_API_URL
+    pub currency_api_key: Option<String>, // CURRENCY_API_KEY
+    pub payment_provider_health_url: Option<String>, // PAYMENT_PROVIDER_HEALTH_URL
+    pub payment_provider_api_key: Option<String>, // PAYMENT_PROVIDER_API_KEY
+    pub health_cache_secs: u64,          // INTEGRATION_HEALTH_CACHE_SECS
+    #[serde(deserialize_with = "from_str_setting")]
+    pub assistant_provider: AssistantBackend, // ASSISTANT_PROVIDER: none or openai
+    pub assistant_api_url: Option<String>, // ASSISTANT_API_URL, an OpenAI-compatible chat API
+    pub assistant_api_key: Option<String>, // ASSISTANT_API_KEY
+    pub assistant_model: String,         // ASSISTANT_MODEL
+}
+
+impl Default for IntegrationConfig {
+    fn default() -> Self {
+        IntegrationConfig {
+            plaid_client_id: None,
+            plaid_secret: None,
+            plaid_env: PlaidEnv::Sandbox,
+            exchange_rate_provider: RateProvider::Ecb,
+            currency_api_url: None,
+            currency_api_key: None,
+            payment_provider_health_url: None,
+            payment_provider_api_key: None,
+            health_cache_secs: 60,
+            assistant_provider: AssistantBackend::Disabled,
+            assistant_api_url: None,
+            assistant_api_key: None,
+            assistant_model: "gpt-4o-mini".to_string(),
+        }
+    }
+}
+
+/// Plaid environment, which selects the API host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaidEnv {
+    Sandbox,
+    Development,
+    Production,
+}
+
+impl PlaidEnv {
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            PlaidEnv::Sandbox => "https://sandbox.plaid.com",
+            PlaidEnv::Development => "https://development.plaid.com",
+            PlaidEnv::Production => "https://production.plaid.com",
+        }
+    }
+}
+
+impl FromStr for PlaidEnv {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sandbox" => Ok(PlaidEnv::Sandbox),
+            "development" => Ok(PlaidEnv::Development),
+            "production" => Ok(PlaidEnv::Production),
+            _ => Err("expected 'sandbox', 'development' or 'production'".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    pub sink: AuditSink,             // AUDIT_SINK
+    pub syslog_addr: Option<String>, // AUDIT_SYSLOG_ADDR, `host:port`
+    pub cef_path: Option<String>,    // AUDIT_CEF_PATH
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        AuditConfig {
+            sink: AuditSink::None,
+            syslog_addr: None,
+            cef_path: None,
+        }
+    }
+}
+
+/// Where audit events are forwarded besides the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditSink {
+    None,
+    Syslog,
+    CefFile,
+}
+
+impl FromStr for AuditSink {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(AuditSink::None),
+            "syslog" => Ok(AuditSink::Syslog),
+            "cef_file" => Ok(AuditSink::CefFile),
+            _ => Err("expected 'none', 'syslog' or 'cef_file'".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RedisConfig {
+    pub url: Option<String>,    // REDIS_URL; shared state stays per instance without it
+    pub key_prefix: String,     // REDIS_KEY_PREFIX, for deployments sharing a Redis
+    pub report_cache_secs: u64, // REPORT_CACHE_SECS, 0 disables the report cache
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        RedisConfig {
+            url: None,
+            key_prefix: "forge:".to_string(),
+            report_cache_secs: 60,
+        }
+    }
+}
+
+/// Every problem found while loading the configuration.
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<String>);
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Invalid configuration:\n  - {}", self.0.join("\n  - "))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Loads and checks the configuration. Call once at startup, after `.env` is loaded.
+pub fn init() -> Result<&'static AppConfig, ConfigError> {
+    let config = AppConfig::load()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The loaded configuration.
+pub fn get() -> &'static AppConfig {
+    CONFIG
+        .get()
+        .expect("config::init must run at startup before settings are read")
+}
+
+impl AppConfig {
+    /// Defaults, overlaid with the config file, overlaid with the environment.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut errors = Vec::new();
+        let mut config = match read_file() {
+            Ok(Some(config)) => config,
+            Ok(None) => AppConfig::default(),
+            Err(e) => return Err(ConfigError(vec![e])),
+        };
+        config.apply_env(&mut errors);
+        config.validate(&mut errors);
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError(errors))
+        }
+    }
+
+    fn apply_env(&mut self, errors: &mut Vec<String>) {
+        let server = &mut self.server;
+        env_parse("APP_HOST", &mut server.host, errors);
+        env_parse("PORT", &mut server.port, errors);
+        env_parse("LOG_FORMAT", &mut server.log_format, errors);
+        env_bool("SCHEMA_SELF_CHECK", &mut server.schema_self_check, errors);
+        if let Some(versions) = env_value("STRICT_FIELDS_API_VERSIONS") {
+            server.strict_fields_api_versions = versions
+                .split(',')
+                .map(str::trim)
+                .filter(|version| !version.is_empty())
+                .map(str::to_lowercase)
+                .collect();
+        }
+
+        let database = &mut self.database;
+        env_string("DATABASE_URL", &mut database.url);
+        env_parse(
+            "DATABASE_MAX_CONNECTIONS",
+            &mut database.max_connections,
+            errors,
+        );
+        env_parse(
+            "DATABASE_MIN_CONNECTIONS",
+            &mut database.min_connections,
+            errors,
+        );
+        env_parse(
+            "DATABASE_ACQUIRE_TIMEOUT_SECS",
+            &mut database.acquire_timeout_secs,
+            errors,
+        );
+        if let Some(urls) = env_value("DATABASE_REPLICA_URLS") {
+            database.replica_urls = urls
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        env_parse(
+            "FINANCIAL_TX_ISOLATION",
+            &mut database.financial_tx_isolation,
+            errors,
+        );
+
+        let auth = &mut self.auth;
+        env_parse("REFRESH_TOKEN_DAYS", &mut auth.refresh_token_days, errors);
+        env_parse("ACCESS_TOKEN_MINUTES", &mut auth.access_token_minutes, errors);
+        env_optional("TOKEN_ENCRYPTION_KEY", &mut auth.token_encryption_key);
+        env_optional("OIDC_REDIRECT_BASE_URL", &mut auth.oidc_redirect_base_url);
+        env_optional("GOOGLE_CLIENT_ID", &mut auth.google_client_id);
+        env_optional("GOOGLE_CLIENT_SECRET", &mut auth.google_client_secret);
+        env_optional("MICROSOFT_CLIENT_ID", &mut auth.microsoft_client_id);
+        env_optional("MICROSOFT_CLIENT_SECRET", &mut auth.microsoft_client_secret);
+        env_string("MICROSOFT_TENANT", &mut auth.microsoft_tenant);
+        env_optional("APP_BASE_URL", &mut auth.app_base_url);
+        env_parse("INVITATION_TTL_DAYS", &mut auth.invitation_ttl_days, errors);
+
+        if let Some(origins) = env_value("CORS_ALLOWED_ORIGINS") {
+            self.cors.allowed_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        let rate_limit = &mut self.rate_limit;
+        env_parse(
+            "RATE_LIMIT_REQUESTS_PER_MINUTE",
+            &mut rate_limit.requests_per_minute,
+            errors,
+        );
+        env_parse("RATE_LIMIT_BURST", &mut rate_limit.burst, errors);
+        env_bool(
+            "RATE_LIMIT_TRUST_FORWARDED_FOR",
+            &mut rate_limit.trust_forwarded_for,
+            errors,
+        );
+
+        let storage = &mut self.storage;
+        env_parse("STORAGE_BACKEND", &mut storage.backend, errors);
+        env_string("STORAGE_LOCAL_PATH", &mut storage.local_path);
+        env_optional("STORAGE_S3_BUCKET", &mut storage.s3_bucket);
+        env_optional("STORAGE_S3_REGION", &mut storage.s3_region);
+        env_optional("STORAGE_S3_ENDPOINT", &mut storage.s3_endpoint);
+        env_optional("STORAGE_S3_ACCESS_KEY_ID", &mut storage.s3_access_key_id);
+        env_optional(
+            "STORAGE_S3_SECRET_ACCESS_KEY",
+            &mut storage.s3_secret_access_key,
+        );
+        env_parse(
+            "STORAGE_SIGNED_URL_TTL_SECS",
+            &mut storage.signed_url_ttl_secs,
+            errors,
+        );
+        env_parse("EXPORT_TTL_HOURS", &mut storage.export_ttl_hours, errors);
+        env_optional("STORAGE_HEALTH_URL", &mut storage.health_url);
+        env_optional("STORAGE_API_KEY", &mut storage.api_key);
+
+        let schedulers = &mut self.schedulers;
+        env_parse(
+            "RECURRING_SCHEDULER_INTERVAL_SECS",
+            &mut schedulers.recurring_interval_secs,
+            errors,
+        );
+        env_parse(
+            "BANK_SYNC_INTERVAL_SECS",
+            &mut schedulers.bank_sync_interval_secs,
+            errors,
+        );
+        env_parse(
+            "NOTIFICATION_SCHEDULER_INTERVAL_SECS",
+            &mut schedulers.notification_interval_secs,
+            errors,
+        );
+        env_parse(
+            "REPORT_SCHEDULER_INTERVAL_SECS",
+            &mut schedulers.report_interval_secs,
+            errors,
+        );
+        env_parse(
+            "EXCHANGE_RATE_SCHEDULER_INTERVAL_SECS",
+            &mut schedulers.exchange_rate_interval_secs,
+            errors,
+        );
+        env_parse(
+            "CASH_POSITION_SCHEDULER_INTERVAL_SECS",
+            &mut schedulers.cash_position_interval_secs,
+            errors,
+        );
+        env_parse(
+            "EXPORT_CLEANUP_INTERVAL_SECS",
+            &mut schedulers.export_cleanup_interval_secs,
+            errors,
+        );
+        env_parse(
+            "MAINTENANCE_INTERVAL_SECS",
+            &mut schedulers.maintenance_interval_secs,
+            errors,
+        );
+        env_parse(
+            "BUDGET_ALERT_SCHEDULER_INTERVAL_SECS",
+            &mut schedulers.budget_alert_interval_secs,
+            errors,
+        );
+        env_parse(
+            "DEPRECIATION_SCHEDULER_INTERVAL_SECS",
+            &mut schedulers.depreciation_interval_secs,
+            errors,
+        );
+        if let Some(tasks) = env_value("MAINTENANCE_TASKS") {
+            schedulers.maintenance_tasks = Vec::new();
+            for task in tasks
+                .split(',')
+                .map(str::trim)
+                .filter(|task| !task.is_empty())
+            {
+                match task.parse() {
+                    Ok(task) => schedulers.maintenance_tasks.push(task),
+                    Err(e) => errors.push(format!(
+                        "MAINTENANCE_TASKS has invalid task '{}': {}",
+                        task, e
+                    )),
+                }
+            }
+        }
+        env_parse(
+            "MAINTENANCE_ANALYZE_RATIO",
+            &mut schedulers.maintenance_analyze_ratio,
+            errors,
+        );
+        env_parse(
+            "SESSION_RETENTION_DAYS",
+            &mut schedulers.session_retention_days,
+            errors,
+        );
+        env_parse(
+            "NOTIFICATION_RETENTION_DAYS",
+            &mut schedulers.notification_retention_days,
+            errors,
+        );
+        env_parse(
+            "MAINTENANCE_RUN_RETENTION_DAYS",
+            &mut schedulers.maintenance_run_retention_days,
+            errors,
+        );
+        env_parse(
+            "IDEMPOTENCY_KEY_RETENTION_DAYS",
+            &mut schedulers.idempotency_key_retention_days,
+            errors,
+        );
+        env_parse(
+            "RETENTION_BATCH_SIZE",
+            &mut schedulers.retention_batch_size,
+            errors,
+        );
+
+        let mail = &mut self.mail;
+        env_optional("SMTP_HOST", &mut mail.smtp_host);
+        env_parse("SMTP_PORT", &mut mail.smtp_port, errors);
+        env_optional("SMTP_USERNAME", &mut mail.smtp_username);
+        env_optional("SMTP_PASSWORD", &mut mail.smtp_password);
+        env_string("MAIL_FROM", &mut mail.from);
+
+        let integrations = &mut self.integrations;
+        env_optional("PLAID_CLIENT_ID", &mut integrations.plaid_client_id);
+        env_optional("PLAID_SECRET", &mut integrations.plaid_secret);
+        env_parse("PLAID_ENV", &mut integrations.plaid_env, errors);
+        env_parse(
+            "EXCHANGE_RATE_PROVIDER",
+            &mut integrations.exchange_rate_provider,
+            errors,
+        );
+        env_optional("CURRENCY_API_URL", &mut integrations.currency_api_url);
+        env_optional("CURRENCY_API_KEY", &mut integrations.currency_api_key);
+        env_optional(
+            "PAYMENT_PROVIDER_HEALTH_URL",
+            &mut integrations.payment_provider_health_url,
+        );
+        env_optional(
+            "PAYMENT_PROVIDER_API_KEY",
+            &mut integrations.payment_provider_api_key,
+        );
+        env_parse(
+            "INTEGRATION_HEALTH_CACHE_SECS",
+            &mut integrations.health_cache_secs,
+            errors,
+        );
+        env_parse(
+            "ASSISTANT_PROVIDER",
+            &mut integrations.assistant_provider,
+            errors,
+        );
+        env_optional("ASSISTANT_API_URL", &mut integrations.assistant_api_url);
+        env_optional("ASSISTANT_API_KEY", &mut integrations.assistant_api_key);
+        env_string("ASSISTANT_MODEL", &mut integrations.assistant_model);
+
+        let audit = &mut self.audit;
+        env_parse("AUDIT_SINK", &mut audit.sink, errors);
+        env_optional("AUDIT_SYSLOG_ADDR", &mut audit.syslog_addr);
+        env_optional("AUDIT_CEF_PATH", &mut audit.cef_path);
+
+        let redis = &mut self.redis;
+        env_optional("REDIS_URL", &mut redis.url);
+        env_string("REDIS_KEY_PREFIX", &mut redis.key_prefix);
+        env_parse("REPORT_CACHE_SECS", &mut redis.report_cache_secs, errors);
+    }
+
+    /// Checks the combined settings, adding one message per problem.
+    fn validate(&self, errors: &mut Vec<String>) {
+        let database = &self.database;
+        if database.url.is_empty() {
+            errors.push("DATABASE_URL must be set".to_string());
+        } else if !database.url.starts_with("postgres://")
+            && !database.url.starts_with("postgresql://")
+        {
+            errors.push("DATABASE_URL must be a postgres:// or postgresql:// URL".to_string());
+        }
+        for url in &database.replica_urls {
+            if !url.starts_with("postgres://") && !url.starts_with("postgresql://") {
+                errors.push(
+                    "DATABASE_REPLICA_URLS must be postgres:// or postgresql:// URLs".to_string(),
+                );
+                break;
+            }
+        }
+        if database.max_connections == 0 {
+            errors.push("DATABASE_MAX_CONNECTIONS must be at least 1".to_string());
+        }
+        if database.min_connections > database.max_connections {
+            errors.push(format!(
+                "DATABASE_MIN_CONNECTIONS ({}) cannot exceed DATABASE_MAX_CONNECTIONS ({})",
+                database.min_connections, database.max_connections
+            ));
+        }
+        if database.acquire_timeout_secs == 0 {
+            errors.push("DATABASE_ACQUIRE_TIMEOUT_SECS must be at least 1".to_string());
+        }
+
+        if self.auth.refresh_token_days == 0 {
+            errors.push("REFRESH_TOKEN_DAYS must be at least 1".to_string());
+        }
+        if self.auth.access_token_minutes == 0 {
+            errors.push("ACCESS_TOKEN_MINUTES must be at least 1".to_string());
+        }
+        if let Some(key) = &self.auth.token_encryption_key {
+            if let Err(e) = crypto::parse_key(key) {
+                errors.push(e);
+            }
+        }
+        let oidc_clients = [
+            (
+                "GOOGLE",
+                &self.auth.google_client_id,
+                &self.auth.google_client_secret,
+            ),
+            (
+                "MICROSOFT",
+                &self.auth.microsoft_client_id,
+                &self.auth.microsoft_client_secret,
+            ),
+        ];
+        for (provider, client_id, client_secret) in oidc_clients {
+            if client_id.is_some() != client_secret.is_some() {
+                errors.push(format!(
+                    "{0}_CLIENT_ID and {0}_CLIENT_SECRET must be set together",
+                    provider
+                ));
+            }
+            if client_id.is_some() && self.auth.oidc_redirect_base_url.is_none() {
+                errors.push(format!(
+                    "OIDC_REDIRECT_BASE_URL must be set to sign in with {}",
+                    provider
+                ));
+            }
+        }
+        if self.auth.microsoft_tenant.trim().is_empty() {
+            errors.push("MICROSOFT_TENANT cannot be empty".to_string());
+        }
+        if self.auth.invitation_ttl_days == 0 {
+            errors.push("INVITATION_TTL_DAYS must be at least 1".to_string());
+        }
+
+        let origins = &self.cors.allowed_origins;
+        if origins.len() > 1 && origins.iter().any(|origin| origin == "*") {
+            errors
+                .push("CORS_ALLOWED_ORIGINS cannot combine '*' with specific origins".to_string());
+        }
+        for origin in origins.iter().filter(|origin| *origin != "*") {
+            let is_url = origin.starts_with("http://") || origin.starts_with("https://");
+            if !is_url || origin.ends_with('/') || HeaderValue::from_str(origin).is_err() {
+                errors.push(format!(
+                    "CORS origin '{}' must look like https://app.example.com (scheme and host, no path)",
+                    origin
+                ));
+            }
+        }
+
+        if self.rate_limit.is_enabled() && self.rate_limit.burst == 0 {
+            errors.push(
+                "RATE_LIMIT_BURST must be at least 1 when rate limiting is enabled".to_string(),
+            );
+        }
+
+        let storage = &self.storage;
+        match storage.backend {
+            StorageBackend::Local if storage.local_path.is_empty() => errors
+                .push("STORAGE_LOCAL_PATH must be set for the local storage backend".to_string()),
+            StorageBackend::S3 if storage.s3_bucket.is_none() || storage.s3_region.is_none() => {
+                errors.push(
+                    "STORAGE_BACKEND=s3 requires STORAGE_S3_BUCKET and STORAGE_S3_REGION"
+                        .to_string(),
+                )
+            }
+            StorageBackend::S3
+                if storage.s3_access_key_id.is_none() || storage.s3_secret_access_key.is_none() =>
+            {
+                errors.push(
+                    "STORAGE_BACKEND=s3 requires STORAGE_S3_ACCESS_KEY_ID and STORAGE_S3_SECRET_ACCESS_KEY"
+                        .to_string(),
+                )
+            }
+            _ => {}
+        }
+        // Presigned S3 URLs are valid for at most 7 days
+        if storage.signed_url_ttl_secs == 0 || storage.signed_url_ttl_secs > 604_800 {
+            errors.push("STORAGE_SIGNED_URL_TTL_SECS must be between 1 and 604800".to_string());
+        }
+        if storage.export_ttl_hours == 0 {
+            errors.push("EXPORT_TTL_HOURS must be at least 1".to_string());
+        }
+        if let Some(endpoint) = &storage.s3_endpoint {
+            if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                errors.push(format!(
+                    "STORAGE_S3_ENDPOINT '{}' must be an http(s) URL",
+                    endpoint
+                ));
+            }
+        }
+
+        let schedulers = &self.schedulers;
+        for (name, secs) in [
+            (
+                "RECURRING_SCHEDULER_INTERVAL_SECS",
+                schedulers.recurring_interval_secs,
+            ),
+            (
+                "BANK_SYNC_INTERVAL_SECS",
+                schedulers.bank_sync_interval_secs,
+            ),
+            (
+                "NOTIFICATION_SCHEDULER_INTERVAL_SECS",
+                schedulers.notification_interval_secs,
+            ),
+            (
+                "REPORT_SCHEDULER_INTERVAL_SECS",
+                schedulers.report_interval_secs,
+            ),
+            (
+                "EXCHANGE_RATE_SCHEDULER_INTERVAL_SECS",
+                schedulers.exchange_rate_interval_secs,
+            ),
+            (
+                "CASH_POSITION_SCHEDULER_INTERVAL_SECS",
+                schedulers.cash_position_interval_secs,
+            ),
+            (
+                "EXPORT_CLEANUP_INTERVAL_SECS",
+                schedulers.export_cleanup_interval_secs,
+            ),
+            (
+                "MAINTENANCE_INTERVAL_SECS",
+                schedulers.maintenance_interval_secs,
+            ),
+            (
+                "BUDGET_ALERT_SCHEDULER_INTERVAL_SECS",
+                schedulers.budget_alert_interval_secs,
+            ),
+            (
+                "DEPRECIATION_SCHEDULER_INTERVAL_SECS",
+                schedulers.depreciation_interval_secs,
+            ),
+        ] {
+            if secs == 0 {
+                errors.push(format!("{} must be at least 1", name));
+            }
+        }
+        if !(schedulers.maintenance_analyze_ratio > 0.0
+            && schedulers.maintenance_analyze_ratio <= 1.0)
+        {
+            errors.push("MAINTENANCE_ANALYZE_RATIO must be above 0 and at most 1".to_string());
+        }
+        for (name, value) in [
+            ("SESSION_RETENTION_DAYS", schedulers.session_retention_days),
+            (
+                "NOTIFICATION_RETENTION_DAYS",
+                schedulers.notification_retention_days,
+            ),
+            (
+                "MAINTENANCE_RUN_RETENTION_DAYS",
+                schedulers.maintenance_run_retention_days,
+            ),
+            (
+                "IDEMPOTENCY_KEY_RETENTION_DAYS",
+                schedulers.idempotency_key_retention_days,
+            ),
+            ("RETENTION_BATCH_SIZE", schedulers.retention_batch_size),
+        ] {
+            if value == 0 {
+                errors.push(format!("{} must be at least 1", name));
+            }
+        }
+
+        let mail = &self.mail;
+        if mail.smtp_host.is_some() && mail.smtp_port == 0 {
+            errors.push("SMTP_PORT must be a valid port".to_string());
+        }
+        if mail.smtp_username.is_some() != mail.smtp_password.is_some() {
+            errors.push("SMTP_USERNAME and SMTP_PASSWORD must be set together".to_string());
+        }
+
+        let integrations = &self.integrations;
+        if integrations.plaid_client_id.is_some() != integrations.plaid_secret.is_some() {
+            errors.push("PLAID_CLIENT_ID and PLAID_SECRET must be set together".to_string());
+        }
+        if integrations.exchange_rate_provider == RateProvider::OpenExchangeRates
+            && integrations.currency_api_key.is_none()
+        {
+            errors.push(
+                "EXCHANGE_RATE_PROVIDER=open_exchange_rates requires CURRENCY_API_KEY".to_string(),
+            );
+        }
+        if integrations.assistant_provider == AssistantBackend::OpenAi
+            && integrations.assistant_api_key.is_none()
+            && integrations.assistant_api_url.is_none()
+        {
+            errors.push(
+                "ASSISTANT_PROVIDER=openai requires ASSISTANT_API_KEY (or ASSISTANT_API_URL for a local server)"
+                    .to_string(),
+            );
+        }
+
+        match self.audit.sink {
+            AuditSink::Syslog if self.audit.syslog_addr.is_none() => {
+                errors.push("AUDIT_SINK=syslog requires AUDIT_SYSLOG_ADDR".to_string())
+            }
+            AuditSink::CefFile if self.audit.cef_path.is_none() => {
+                errors.push("AUDIT_SINK=cef_file requires AUDIT_CEF_PATH".to_string())
+            }
+            _ => {}
+        }
+
+        if let Some(url) = &self.redis.url {
+            if !url.starts_with("redis://") && !url.starts_with("rediss://") {
+                errors.push("REDIS_URL must be a redis:// or rediss:// URL".to_string());
+            }
+        }
+    }
+}
+
+/// Reads the config file: `CONFIG_FILE` must exist when set, `config.toml` is optional.
+fn read_file() -> Result<Option<AppConfig>, String> {
+    let (path, required) = match env_value("CONFIG_FILE") {
+        Some(path) => (path, true),
+        None => (DEFAULT_CONFIG_FILE.to_string(), false),
+    };
+    if !required && !Path::new(&path).exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Cannot read config file {}: {}", path, e))?;
+    toml::from_str(&contents)
+        .map(Some)
+        .map_err(|e| format!("Invalid config file {}: {}", path, e))
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn env_string(name: &str, target: &mut String) {
+    if let Some(value) = env_value(name) {
+        *target = value;
+    }
+}
+
+fn env_optional(name: &str, target: &mut Option<String>) {
+    if let Some(value) = env_value(name) {
+        *target = Some(value);
+    }
+}
+
+fn env_parse<T>(name: &str, target: &mut T, errors: &mut Vec<String>)
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Some(value) = env_value(name) else {
+        return;
+    };
+    match value.trim().parse() {
+        Ok(parsed) => *target = parsed,
+        Err(e) => errors.push(format!("{} has invalid value '{}': {}", name, value, e)),
+    }
+}
+
+fn env_bool(name: &str, target: &mut bool, errors: &mut Vec<String>) {
+    let Some(value) = env_value(name) else {
+        return;
+    };
+    match value.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" => *target = true,
+        "false" | "0" | "no" => *target = false,
+        _ => errors.push(format!("{} must be true or false, got '{}'", name, value)),
+    }
+}
+
+/// Reads a config file value through the type's `FromStr`, so the file accepts the same
+/// spellings as the environment.
+fn from_str_setting<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
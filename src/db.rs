@@ -1,21 +1,623 @@
-use sqlx::{migrate::Migrator, PgPool, Postgres};
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgConnection, PgPoolOptions},
+    PgPool, Postgres, Transaction as DbTransaction,
+};
+use std::future::Future;
 use std::path::Path;
-use tracing::info;
+use std::str::FromStr;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
 
-/// Connects to the PostgreSQL database and returns a connection pool.
+use crate::{config::DatabaseConfig, error::AppError};
+
+/// Attempts made by `with_retry` before a transient conflict is returned to the caller.
+const MAX_RETRY_ATTEMPTS: u32 = 5;
+/// Backoff before the first retry; doubled on every further attempt.
+const INITIAL_RETRY_BACKOFF_MS: u64 = 25;
+
+/// Connects to the PostgreSQL primary and any read replicas (`DATABASE_REPLICA_URLS`), each
+/// with a pool sized by the `[database]` settings.
 ///
 /// It also attempts to run database migrations from the `./migrations` directory.
-pub async fn setup_database(database_url: &str) -> Result<PgPool, sqlx::Error> {
-    // Ensure 'pub' is here
-    let pool = PgPool::connect(database_url).await?;
+pub async fn setup_database(config: &DatabaseConfig) -> Result<DbPools, sqlx::Error> {
+    let writer = pool_options(config).connect(&config.url).await?;
 
-    // Run migrations
+    // Run migrations, which may backfill rows of every tenant
     info!("Running database migrations...");
-    Migrator::new(Path::new("./migrations"))
-        .await?
-        .run(&pool)
-        .await?;
+    let migrator = Migrator::new(Path::new("./migrations")).await?;
+    without_tenant_scope(migrator.run(&writer)).await?;
     info!("Database migrations completed.");
 
-    Ok(pool)
+    let mut readers = Vec::with_capacity(config.replica_urls.len());
+    for url in &config.replica_urls {
+        readers.push(pool_options(config).connect(url).await?);
+    }
+    if !readers.is_empty() {
+        info!("Connected to {} read replica(s).", readers.len());
+    }
+
+    Ok(DbPools::new(writer, readers))
+}
+
+fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(config.acquire_timeout())
+        // Every connection carries the tenant scope of the task using it
+        .after_connect(|conn, _| Box::pin(async move { apply_tenant_scope(conn).await }))
+        .before_acquire(|conn, _| {
+            Box::pin(async move {
+                apply_tenant_scope(conn).await?;
+                Ok(true)
+            })
+        })
+}
+
+/// The primary and its read replicas.
+///
+/// Writes, and reads that feed them, use `writer`. Heavy reads (reports, dashboards, long
+/// lists) may use a replica, which can lag behind the primary: a client that must see its
+/// own writes sends the consistency token of its last write (see `reader_after`).
+#[derive(Clone)]
+pub struct DbPools {
+    pub writer: PgPool,
+    pub readers: Vec<PgPool>,
+    next_reader: Arc<AtomicUsize>,
+}
+
+impl DbPools {
+    pub fn new(writer: PgPool, readers: Vec<PgPool>) -> Self {
+        DbPools {
+            writer,
+            readers,
+            next_reader: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// A replica, in turn; the primary when there are none.
+    pub fn reader(&self) -> &PgPool {
+        if self.readers.is_empty() {
+            return &self.writer;
+        }
+        let next = self.next_reader.fetch_add(1, Ordering::Relaxed);
+        &self.readers[next % self.readers.len()]
+    }
+
+    /// A replica that has replayed the primary's WAL up to `lsn` (a consistency token from
+    /// `current_wal_lsn`), or the primary when it has not. Any replica without a token.
+    pub async fn reader_after(&self, lsn: Option<&str>) -> &PgPool {
+        let reader = self.reader();
+        let Some(lsn) = lsn.filter(|_| !self.readers.is_empty()) else {
+            return reader;
+        };
+        let caught_up: Result<Option<bool>, sqlx::Error> =
+            sqlx::query_scalar("SELECT pg_last_wal_replay_lsn() >= $1::pg_lsn")
+                .bind(lsn)
+                .fetch_one(reader)
+                .await;
+        match caught_up {
+            Ok(Some(true)) => reader,
+            Ok(_) => &self.writer,
+            Err(e) => {
+                // A malformed token or an unreachable replica: the primary always answers
+                warn!("Could not compare replica position with token {}: {}", lsn, e);
+                &self.writer
+            }
+        }
+    }
+}
+
+tokio::task_local! {
+    /// Tenant whose rows the current task may see, enforced by row-level security; `None`
+    /// for every tenant.
+    static TENANT_SCOPE: Option<Uuid>;
+}
+
+/// Runs `future` scoped to the tenant: connections it takes from the pool have
+/// `app.tenant_id` set, and the row-level security policies on tenant-scoped tables then
+/// hide other tenants' rows and refuse writes to them. `None` sees every tenant (see
+/// `without_tenant_scope`). Code running outside any scope, including tasks spawned from
+/// inside one, sees no tenant's rows, so spawned work must set its own scope.
+pub async fn with_tenant_scope<F: Future>(tenant_id: Option<Uuid>, future: F) -> F::Output {
+    TENANT_SCOPE.scope(tenant_id, future).await
+}
+
+/// Runs `future` across every tenant, for work that legitimately spans tenants: migrations,
+/// background jobs, resolving a request's tenant, and the few requests that act on several
+/// (creating a sandbox, erasing a user). Only connections acquired inside are affected, so
+/// begin transactions inside `future`.
+pub async fn without_tenant_scope<F: Future>(future: F) -> F::Output {
+    with_tenant_scope(None, future).await
+}
+
+/// Sets `app.tenant_id` and `app.all_tenants` on a connection about to be used. Outside a
+/// scope both are cleared and the policies admit no tenant's rows.
+async fn apply_tenant_scope(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    let (tenant_id, all_tenants) = match TENANT_SCOPE.try_with(|scope| *scope) {
+        Ok(Some(tenant_id)) => (tenant_id.to_string(), ""),
+        Ok(None) => (String::new(), "on"),
+        Err(_) => (String::new(), ""),
+    };
+    sqlx::query(
+        "SELECT set_config('app.tenant_id', $1, FALSE), set_config('app.all_tenants', $2, FALSE)",
+    )
+    .bind(tenant_id)
+    .bind(all_tenants)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Isolation level requested for a database transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    fn as_sql(self) -> &'static str {
+        match self {
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+impl FromStr for IsolationLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().replace(['_', '-'], " ").as_str() {
+            "READ COMMITTED" => Ok(IsolationLevel::ReadCommitted),
+            "REPEATABLE READ" => Ok(IsolationLevel::RepeatableRead),
+            "SERIALIZABLE" => Ok(IsolationLevel::Serializable),
+            _ => Err(format!("Invalid isolation level: {}", s)),
+        }
+    }
+}
+
+/// Isolation level for financial writes (posting, reconciliation), configured with
+/// `FINANCIAL_TX_ISOLATION`. Defaults to SERIALIZABLE so balance invariants hold under
+/// concurrency; callers should run inside `with_retry` to absorb serialization failures.
+pub fn financial_isolation_level() -> IsolationLevel {
+    crate::config::get().database.financial_tx_isolation
+}
+
+/// Begins a database transaction at the given isolation level.
+pub async fn begin_with_isolation(
+    pool: &PgPool,
+    level: IsolationLevel,
+) -> Result<DbTransaction<'static, Postgres>, AppError> {
+    let mut db_tx = pool.begin().await?;
+    // Must be the first statement of the transaction
+    sqlx::query(&format!("SET TRANSACTION ISOLATION LEVEL {}", level.as_sql()))
+        .execute(&mut *db_tx)
+        .await?;
+    Ok(db_tx)
+}
+
+/// Begins a database transaction for a financial write at the configured isolation level.
+pub async fn begin_financial(pool: &PgPool) -> Result<DbTransaction<'static, Postgres>, AppError> {
+    begin_with_isolation(pool, financial_isolation_level()).await
+}
+
+/// Runs a write operation, retrying it with exponential backoff when it fails with a
+/// transient conflict (serialization failure or deadlock, see `AppError::TransactionConflict`).
+///
+/// `f` must perform one complete attempt, beginning and committing its own database
+/// transaction, so a failed attempt is fully rolled back before the next one starts.
+pub async fn with_retry<'a, T, F, Fut>(pool: &'a PgPool, mut f: F) -> Result<T, AppError>
+where
+    F: FnMut(&'a PgPool) -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f(pool).await {
+            Err(AppError::TransactionConflict(msg)) if attempt < MAX_RETRY_ATTEMPTS => {
+                let backoff = Duration::from_millis(INITIAL_RETRY_BACKOFF_MS << (attempt - 1));
+                warn!(
+                    "Transient conflict on attempt {}/{} ({}), retrying in {:?}",
+                    attempt, MAX_RETRY_ATTEMPTS, msg, backoff
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// The primary's current WAL position (e.g. `16/B374D848`), handed to clients as a
+/// read-after-write consistency token.
+pub async fn current_wal_lsn(pool: &PgPool) -> Result<String, sqlx::Error> {
+    sqlx::query_scalar!(r#"SELECT pg_current_wal_lsn()::text as "lsn!""#)
+        .fetch_one(pool)
+        .await
+}
+
+/// The error for a conditional update (`... AND version = $n`) that changed no row: a 409
+/// when the record exists at another version, otherwise a 404. `table` must have `id`,
+/// `tenant_id` and `version` columns.
+pub async fn version_mismatch(
+    pool: &PgPool,
+    table: &'static str,
+    record: &str,
+    id: Uuid,
+    tenant_id: Uuid,
+    expected_version: i32,
+) -> AppError {
+    let current: Result<Option<i32>, sqlx::Error> = sqlx::query_scalar(&format!(
+        "SELECT version FROM {} WHERE id = $1 AND tenant_id = $2",
+        table
+    ))
+    .bind(id)
+    .bind(tenant_id)
+    .fetch_optional(pool)
+    .await;
+    match current {
+        Ok(Some(version)) => AppError::VersionConflict(format!(
+            "{} {} was changed by someone else: it is at version {}, not {}; fetch it again and reapply your changes",
+            record, id, version, expected_version
+        )),
+        Ok(None) => AppError::NotFound(format!(
+            "{} with ID {} not found or not owned by tenant {}",
+            record, id, tenant_id
+        )),
+        Err(e) => e.into(),
+    }
+}
+
+/// Tables and columns the models read and write, kept in sync with `src/models`.
+/// Checked at boot by `verify_schema` when `SCHEMA_SELF_CHECK=true`.
+const EXPECTED_SCHEMA: &[(&str, &[&str])] = &[
+    ("users", &["id", "auth_provider_id", "auth_provider_type", "email", "password_hash", "first_name", "last_name", "is_active", "is_system_admin", "last_login_at", "created_at", "updated_at"]),
+    ("auth_sessions", &["id", "user_id", "user_agent", "ip_address", "created_at", "last_used_at", "expires_at", "revoked_at", "revoked_reason"]),
+    ("session_refresh_tokens", &["id", "session_id", "token_hash", "created_at", "rotated_at"]),
+    ("session_access_tokens", &["id", "session_id", "token_hash", "created_at", "expires_at"]),
+    ("oidc_login_states", &["state_hash", "provider", "nonce", "code_verifier", "created_at", "expires_at"]),
+    ("tenants", &["id", "name", "industry", "base_currency_code", "fiscal_year_end_month", "privacy_mode", "data_key", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("currencies", &["code", "name", "symbol", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("exchange_rates", &["id", "tenant_id", "base_currency_code", "target_currency_code", "rate", "rate_date", "source", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("exchange_rate_fetch_settings", &["tenant_id", "is_enabled", "last_fetched_at", "last_rate_date", "last_error", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("fx_revaluation_settings", &["tenant_id", "unrealized_gain_account_id", "unrealized_loss_account_id", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("fx_revaluations", &["id", "tenant_id", "as_of", "transaction_id", "base_currency_code", "total_gain", "total_loss", "lines", "created_at", "created_by"]),
+    ("cash_position_snapshots", &["tenant_id", "account_id", "snapshot_date", "institution", "currency_code", "balance", "created_at", "updated_at"]),
+    ("calendar_feeds", &["id", "tenant_id", "user_id", "token_hash", "last_used_at", "created_at"]),
+    ("tenant_sandboxes", &["tenant_id", "sandbox_tenant_id", "created_at", "created_by"]),
+    ("tenant_invitations", &["id", "tenant_id", "email", "role_id", "token_hash", "expires_at", "accepted_at", "accepted_by", "revoked_at", "revoked_by", "created_at", "created_by"]),
+    ("api_keys", &["id", "tenant_id", "user_id", "name", "key_prefix", "key_hash", "is_sandbox", "last_used_at", "revoked_at", "created_at", "created_by"]),
+    ("import_jobs", &["id", "tenant_id", "kind", "status", "total_rows", "processed_rows", "failed_rows", "result_id", "error_message", "error_csv", "started_at", "completed_at", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("export_artifacts", &["id", "tenant_id", "kind", "source_id", "status", "file_name", "content_type", "size_bytes", "sha256", "storage_key", "is_encrypted", "error_message", "expires_at", "completed_at", "created_at", "created_by"]),
+    ("maintenance_runs", &["id", "task", "status", "rows_affected", "detail", "error_message", "started_at", "finished_at"]),
+    ("account_types", &["id", "name", "normal_balance", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("accounts", &["id", "tenant_id", "account_type_id", "name", "account_code", "description", "currency_code", "is_sensitive", "is_active", "archived_at", "created_at", "created_by", "updated_at", "updated_by", "version"]),
+    ("categories", &["id", "tenant_id", "name", "description", "type", "parent_category_id", "is_active", "archived_at", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("tags", &["id", "tenant_id", "name", "description", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("payees", &["id", "tenant_id", "name", "notes", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("dimensions", &["id", "tenant_id", "kind", "name", "code", "description", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("transactions", &["id", "tenant_id", "transaction_date", "description", "type", "category_id", "payee_id", "tags_json", "amount", "currency_code", "is_reconciled", "reconciliation_date", "notes", "source_document_url", "reversal_of_id", "reversed_by_id", "recurring_transaction_id", "recurring_occurrence_date", "status", "posted_at", "posted_by", "voided_at", "voided_by", "void_reason", "created_at", "created_by", "updated_at", "updated_by", "version"]),
+    ("transaction_metadata", &["transaction_id", "tenant_id", "latitude", "longitude", "device", "entry_source", "created_at"]),
+    ("events", &["id", "tenant_id", "name", "description", "start_date", "end_date", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("event_transactions", &["transaction_id", "event_id", "created_at", "created_by"]),
+    ("customers", &["id", "tenant_id", "name", "email", "billing_address", "tax_id", "notes", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("invoice_sequences", &["tenant_id", "prefix", "next_number", "padding", "updated_at", "updated_by"]),
+    ("invoices", &["id", "tenant_id", "customer_id", "invoice_number", "status", "issue_date", "due_date", "currency_code", "receivable_account_id", "total", "amount_paid", "issue_transaction_id", "notes", "issued_at", "issued_by", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("invoice_lines", &["id", "invoice_id", "tenant_id", "position", "description", "quantity", "unit_price", "amount", "revenue_account_id"]),
+    ("invoice_payments", &["id", "tenant_id", "invoice_id", "payment_date", "amount", "deposit_account_id", "transaction_id", "payment_id", "memo", "created_at", "created_by"]),
+    ("vendors", &["id", "tenant_id", "name", "email", "address", "tax_id", "payment_terms_days", "notes", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("bills", &["id", "tenant_id", "vendor_id", "bill_number", "status", "bill_date", "due_date", "currency_code", "payable_account_id", "total", "amount_paid", "approve_transaction_id", "notes", "approved_at", "approved_by", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("bill_lines", &["id", "bill_id", "tenant_id", "position", "description", "amount", "expense_account_id"]),
+    ("bill_payments", &["id", "tenant_id", "bill_id", "payment_date", "amount", "payment_account_id", "transaction_id", "payment_id", "memo", "created_at", "created_by"]),
+    ("payments", &["id", "tenant_id", "direction", "customer_id", "vendor_id", "payment_date", "amount", "currency_code", "account_id", "credit_account_id", "unapplied_amount", "transaction_id", "memo", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("fixed_assets", &["id", "tenant_id", "name", "asset_tag", "description", "acquisition_date", "in_service_date", "cost", "salvage_value", "useful_life_months", "method", "declining_factor", "currency_code", "asset_account_id", "accumulated_depreciation_account_id", "depreciation_expense_account_id", "status", "accumulated_depreciation", "depreciated_through", "notes", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("fixed_asset_depreciations", &["id", "tenant_id", "asset_id", "period_end", "amount", "transaction_id", "created_at"]),
+    ("transaction_attributions", &["transaction_id", "tenant_id", "member_id", "is_shared", "updated_at", "updated_by"]),
+    ("transaction_splits", &["journal_entry_id", "transaction_id", "category_id", "percentage", "position"]),
+    ("reimbursable_expenses", &["transaction_id", "tenant_id", "payer", "notes", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("reimbursement_matches", &["id", "tenant_id", "expense_transaction_id", "deposit_transaction_id", "amount", "created_at", "created_by"]),
+    ("envelope_moves", &["id", "tenant_id", "budget_id", "from_line_item_id", "to_line_item_id", "amount", "moved_on", "memo", "created_at", "created_by"]),
+    ("journal_entries", &["id", "transaction_id", "account_id", "entry_type", "amount", "currency_code", "exchange_rate", "converted_amount", "memo", "project_id", "class_id", "location_id", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("budgets", &["id", "tenant_id", "name", "start_date", "end_date", "currency_code", "is_envelope", "is_active", "created_at", "created_by", "updated_at", "updated_by", "version"]),
+    ("budget_line_items", &["id", "budget_id", "category_id", "account_id", "budgeted_amount", "monthly_amounts", "alert_thresholds", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("budget_alerts", &["id", "tenant_id", "budget_id", "budget_line_item_id", "threshold_percent", "budgeted_amount", "actual_amount", "notified_user_id", "created_at"]),
+    ("idempotency_keys", &["id", "scope", "idempotency_key", "request_hash", "response_status", "response_content_type", "response_body", "created_at", "completed_at"]),
+    ("recurring_transactions", &["id", "tenant_id", "description", "type", "category_id", "account_id", "amount", "currency_code", "frequency_value", "frequency_unit", "start_date", "end_date", "last_generated_date", "next_due_date", "is_active", "notes", "journal_template", "escalation_percent", "escalation_month", "escalated_through", "paused_at", "business_day_rule", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("business_calendars", &["tenant_id", "country_code", "weekend_days", "updated_at", "updated_by"]),
+    ("business_holidays", &["id", "tenant_id", "holiday_date", "name", "country_code", "created_at", "created_by"]),
+    ("recurring_transaction_skips", &["recurring_transaction_id", "occurrence_date", "created_at", "created_by"]),
+    ("statement_layouts", &["id", "tenant_id", "name", "statement_type", "definition", "is_default", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("ext_providers", &["id", "name", "code", "type", "description", "logo_url", "api_base_url", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("ext_conns", &["id", "tenant_id", "user_id", "provider_id", "provider_access_token", "provider_item_id", "status", "last_sync_at", "metadata", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("external_accounts", &["id", "ext_conn_id", "account_id", "provider_account_id", "name", "mask", "type", "subtype", "currency_code", "current_balance", "available_balance", "last_sync_at", "is_active", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("external_transactions_staging", &["id", "external_account_id", "provider_transaction_id", "description", "amount", "transaction_date", "posted_date", "status", "tx_id", "raw_data", "merchant_name", "merchant_category_hint", "merchant_category_id", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("merchant_rules", &["id", "tenant_id", "pattern", "payee_id", "merchant_name", "category_id", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("transaction_match_proposals", &["id", "tenant_id", "staging_id", "transaction_id", "score", "status", "reasons", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("custom_reports", &["id", "tenant_id", "user_id", "name", "description", "report_type", "configuration", "is_public", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("report_schedules", &["id", "tenant_id", "custom_report_id", "cron_expression", "timezone", "period", "recipients", "is_active", "next_run_at", "last_run_at", "last_error", "attachment_format", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("tenant_mail_settings", &["tenant_id", "smtp_host", "smtp_port", "smtp_username", "smtp_password", "from_address", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("tenant_logos", &["tenant_id", "content_type", "width", "height", "image", "updated_at", "updated_by"]),
+    ("dashboards", &["id", "tenant_id", "user_id", "name", "description", "is_default", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("dashboard_widgets", &["id", "dashboard_id", "widget_type", "title", "order_index", "parameters", "properties", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("fiscal_periods", &["id", "tenant_id", "name", "start_date", "end_date", "status", "closed_at", "closed_by", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("notifications", &["id", "user_id", "tenant_id", "priority", "subject", "body", "delivered_at", "delivery_attempts", "last_error", "created_at"]),
+    ("security_webhooks", &["id", "tenant_id", "url", "signing_secret", "event_types", "is_active", "last_delivery_at", "last_delivery_status", "last_error", "created_at", "created_by", "updated_at", "updated_by"]),
+    ("audit_events", &["id", "tenant_id", "actor_user_id", "action", "entity_type", "entity_id", "details", "created_at"]),
+    ("user_preferences", &["user_id", "tenant_id", "pinned_account_ids", "created_at", "updated_at"]),
+    ("notification_preferences", &["user_id", "timezone", "quiet_hours_start", "quiet_hours_end", "digest_frequency", "email_enabled", "last_digest_sent_at", "created_at", "updated_at"]),
+    ("roles", &["id", "name"]),
+    ("permissions", &["id", "name"]),
+    ("role_permissions", &["role_id", "permission_id"]),
+    ("user_tenant_roles", &["user_id", "tenant_id", "role_id"]),
+];
+
+/// Verifies that every table and column in `EXPECTED_SCHEMA` exists by running a
+/// `SELECT <columns> FROM <table> LIMIT 0` per table.
+///
+/// Catches drift between migrations and the `query_as!` models before traffic arrives, and
+/// tables with a `tenant_id` left without row-level security.
+/// Returns one message per mismatch (missing table, missing column, or other query failure).
+pub async fn verify_schema(pool: &PgPool) -> Result<(), Vec<String>> {
+    info!("Verifying database schema against model expectations...");
+    let mut mismatches = Vec::new();
+
+    for (table, columns) in EXPECTED_SCHEMA {
+        let probe = format!("SELECT {} FROM {} LIMIT 0", columns.join(", "), table);
+        if sqlx::query(&probe).execute(pool).await.is_ok() {
+            continue;
+        }
+
+        // The probe failed: work out exactly what is missing from the catalog
+        let existing: Vec<String> = match sqlx::query_scalar::<Postgres, String>(
+            "SELECT column_name::text FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1",
+        )
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        {
+            Ok(existing) => existing,
+            Err(e) => {
+                mismatches.push(format!("{}: could not read catalog ({})", table, e));
+                continue;
+            }
+        };
+
+        if existing.is_empty() {
+            mismatches.push(format!("{}: table is missing", table));
+            continue;
+        }
+        let missing: Vec<&str> = columns
+            .iter()
+            .copied()
+            .filter(|column| !existing.iter().any(|e| e == column))
+            .collect();
+        if missing.is_empty() {
+            mismatches.push(format!("{}: columns exist but `{}` failed", table, probe));
+        } else {
+            mismatches.push(format!("{}: missing column(s) {}", table, missing.join(", ")));
+        }
+    }
+
+    // A tenant table added without a policy would silently bypass tenant isolation
+    match sqlx::query_scalar::<Postgres, String>(
+        r#"
+        SELECT c.relname::text
+        FROM pg_class c
+        JOIN pg_attribute a ON a.attrelid = c.oid AND a.attname = 'tenant_id' AND NOT a.attisdropped
+        WHERE c.relnamespace = current_schema()::regnamespace
+            AND c.relkind IN ('r', 'p') AND NOT c.relispartition
+            AND NOT (c.relrowsecurity AND c.relforcerowsecurity)
+        ORDER BY 1
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(tables) => mismatches.extend(
+            tables
+                .into_iter()
+                .map(|table| format!("{}: has tenant_id but no row-level security", table)),
+        ),
+        Err(e) => mismatches.push(format!("could not check row-level security ({})", e)),
+    }
+
+    if mismatches.is_empty() {
+        info!("Database schema matches model expectations ({} tables).", EXPECTED_SCHEMA.len());
+        Ok(())
+    } else {
+        Err(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs `with_retry` over attempts that fail with `errors` in turn and succeed once
+    /// they run out, returning the result and the number of attempts made.
+    async fn retry_through(errors: Vec<AppError>) -> (Result<usize, AppError>, usize) {
+        // Never connects: the attempts below do not touch the database
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .expect("lazy pool");
+        let errors = std::sync::Mutex::new(errors.into_iter());
+        let attempts = AtomicUsize::new(0);
+        let result = with_retry(&pool, |_| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            let error = errors.lock().unwrap().next();
+            async move { error.map_or(Ok(attempt), Err) }
+        })
+        .await;
+        (result, attempts.into_inner())
+    }
+
+    fn conflict() -> AppError {
+        AppError::TransactionConflict("could not serialize access".to_string())
+    }
+
+    #[tokio::test]
+    async fn with_retry_absorbs_transient_conflicts() {
+        let (result, attempts) = retry_through(vec![conflict(), conflict()]).await;
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn with_retry_gives_up_after_the_last_attempt() {
+        let errors = (0..MAX_RETRY_ATTEMPTS).map(|_| conflict()).collect();
+        let (result, attempts) = retry_through(errors).await;
+        assert!(matches!(result, Err(AppError::TransactionConflict(_))));
+        assert_eq!(attempts, MAX_RETRY_ATTEMPTS as usize);
+    }
+
+    #[tokio::test]
+    async fn with_retry_returns_other_errors_at_once() {
+        let errors = vec![AppError::Validation("bad amount".to_string()), conflict()];
+        let (result, attempts) = retry_through(errors).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert_eq!(attempts, 1);
+    }
+
+    /// Counts the accounts of `tenant_ids` visible to `role` on a connection acquired in the
+    /// current task's scope. The test user is a superuser, which row-level security skips.
+    async fn visible_accounts(pool: &PgPool, role: &str, tenant_ids: &[Uuid]) -> i64 {
+        let mut conn = pool.acquire().await.expect("acquire connection");
+        sqlx::query(&format!("SET ROLE {}", role))
+            .execute(&mut *conn)
+            .await
+            .expect("set role");
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM accounts WHERE tenant_id = ANY($1)")
+                .bind(tenant_ids)
+                .fetch_one(&mut *conn)
+                .await
+                .expect("count accounts");
+        sqlx::query("RESET ROLE")
+            .execute(&mut *conn)
+            .await
+            .expect("reset role");
+        count
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn row_level_security_fails_closed_outside_a_scope() {
+        let config = DatabaseConfig {
+            url: std::env::var("DATABASE_URL").expect("DATABASE_URL"),
+            max_connections: 1,
+            ..DatabaseConfig::default()
+        };
+        let pool = pool_options(&config)
+            .connect(&config.url)
+            .await
+            .expect("connect");
+
+        let run = Uuid::new_v4();
+        let role = format!("rls_probe_{}", run.simple());
+        for statement in [
+            format!("CREATE ROLE {} NOLOGIN", role),
+            format!("GRANT SELECT ON accounts TO {}", role),
+        ] {
+            sqlx::query(&statement)
+                .execute(&pool)
+                .await
+                .expect("create role");
+        }
+
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+             VALUES ($1, 'EMAIL_PASSWORD', $1, 'Scope', 'Tester') RETURNING id",
+        )
+        .bind(format!("rls-{}@example.com", run))
+        .fetch_one(&pool)
+        .await
+        .expect("insert user");
+        sqlx::query(
+            "INSERT INTO currencies (code, name, symbol, created_by, updated_by)
+             VALUES ('USD', 'US Dollar', '$', $1, $1) ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .expect("insert currency");
+        let account_type_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO account_types (name, normal_balance, created_by, updated_by)
+             VALUES ('Asset', 'DEBIT', $1, $1)
+             ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .expect("insert account type");
+        let mut tenant_ids = Vec::new();
+        for name in ["First", "Second"] {
+            let tenant_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO tenants (name, base_currency_code, fiscal_year_end_month, created_by, updated_by)
+                 VALUES ($1, 'USD', 12, $2, $2) RETURNING id",
+            )
+            .bind(format!("{} {}", name, run))
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .expect("insert tenant");
+            sqlx::query(
+                "INSERT INTO accounts (tenant_id, account_type_id, name, currency_code, created_by, updated_by)
+                 VALUES ($1, $2, 'Cash', 'USD', $3, $3)",
+            )
+            .bind(tenant_id)
+            .bind(account_type_id)
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .expect("insert account");
+            tenant_ids.push(tenant_id);
+        }
+
+        assert_eq!(visible_accounts(&pool, &role, &tenant_ids).await, 0);
+        // A task spawned from a scoped one does not inherit the scope
+        let spawned = with_tenant_scope(Some(tenant_ids[0]), {
+            let pool = pool.clone();
+            let role = role.clone();
+            let tenant_ids = tenant_ids.clone();
+            async move {
+                tokio::spawn(async move { visible_accounts(&pool, &role, &tenant_ids).await })
+                    .await
+                    .expect("join")
+            }
+        })
+        .await;
+        assert_eq!(spawned, 0);
+        assert_eq!(
+            with_tenant_scope(
+                Some(tenant_ids[0]),
+                visible_accounts(&pool, &role, &tenant_ids)
+            )
+            .await,
+            1
+        );
+        assert_eq!(
+            without_tenant_scope(visible_accounts(&pool, &role, &tenant_ids)).await,
+            2
+        );
+
+        for statement in [
+            format!("DROP OWNED BY {}", role),
+            format!("DROP ROLE {}", role),
+        ] {
+            sqlx::query(&statement)
+                .execute(&pool)
+                .await
+                .expect("drop role");
+        }
+    }
 }
@@ -1,26 +1,162 @@
 // src/error.rs
 
-use axum::http::StatusCode;
+use axum::http::{header, StatusCode};
 use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use serde_json::json;
+use sqlx::error::ErrorKind;
+use sqlx::Error as SqlxError;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FmtResult}; // Important for the `?` operator
-use sqlx::Error as SqlxError;
+use tracing::error;
+use uuid::Uuid;
+use crate::middleware::logging::current_request_id;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+/// Seconds a client should wait before retrying after a serialization failure or deadlock.
+const TRANSACTION_CONFLICT_RETRY_SECS: u64 = 1;
+/// Seconds a client should wait before retrying when the database is unavailable.
+const SERVICE_UNAVAILABLE_RETRY_SECS: u64 = 5;
+/// Seconds a rate-limited client should wait; the limiter refills continuously.
+const TOO_MANY_REQUESTS_RETRY_SECS: u64 = 1;
+
+// Postgres SQLSTATE codes that `ErrorKind` does not cover
+const SERIALIZATION_FAILURE: &str = "40001";
+const DEADLOCK_DETECTED: &str = "40P01";
 
 #[derive(Debug)] // Derive Debug trait
 pub enum AppError {
+    /// Unexpected database failure. The message is logged, never sent to clients.
     DatabaseError(String),
     NotFound(String),
+    /// The resource existed but has expired or been removed for good (e.g., an old export).
+    Gone(String),
+    /// The caller is not authenticated.
+    Unauthorized(String),
+    /// The caller is authenticated but lacks a permission.
+    Forbidden(String),
     Validation(String),
+    /// The request failed its validation rules (422); one entry per offending field.
+    InvalidFields(Vec<FieldError>),
+    /// The request conflicts with existing data (duplicate or still-referenced record).
+    Conflict(String),
+    /// Active records still depend on the one being deactivated (409); one entry per blocker.
+    HasDependents(Vec<Blocker>),
+    /// A concurrent transaction got in the way; the same request can simply be retried.
+    TransactionConflict(String),
+    /// The record changed since the client read it (its `If-Match` version is stale).
+    VersionConflict(String),
+    /// The request must be conditional, e.g. an update without `If-Match`.
+    PreconditionRequired(String),
+    /// The database could not be reached in time; retry after a short wait.
+    ServiceUnavailable(String),
+    /// The client exceeded its request rate limit.
+    TooManyRequests(String),
+    /// Unexpected failure outside the database. The message is logged, never sent to clients.
     InternalServerError(String),
 }
 
+/// One invalid field of a request.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String, // Dotted path, e.g. `journal_entries[1].amount`
+    pub code: String,  // The failed rule, e.g. `length`, `email`, `range`
+    pub message: String,
+}
+
+/// A record that keeps another from being deactivated, e.g. a budget line on an account.
+#[derive(Debug, Clone, Serialize)]
+pub struct Blocker {
+    pub kind: String, // e.g. `budget_line_item`, `recurring_transaction`, `merchant_rule`
+    pub id: Uuid,
+    pub name: String,
+}
+
+impl AppError {
+    /// Stable machine-readable code sent as `error.code`; clients branch on this, not on
+    /// the message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::DatabaseError(_) => "DATABASE_ERROR",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Gone(_) => "GONE",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::Validation(_) | AppError::InvalidFields(_) => "VALIDATION_FAILED",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::HasDependents(_) => "HAS_DEPENDENTS",
+            AppError::TransactionConflict(_) => "TRANSACTION_CONFLICT",
+            AppError::VersionConflict(_) => "VERSION_CONFLICT",
+            AppError::PreconditionRequired(_) => "PRECONDITION_REQUIRED",
+            AppError::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE",
+            AppError::TooManyRequests(_) => "RATE_LIMITED",
+            AppError::InternalServerError(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::DatabaseError(_) | AppError::InternalServerError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Gone(_) => StatusCode::GONE,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::InvalidFields(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Conflict(_)
+            | AppError::HasDependents(_)
+            | AppError::TransactionConflict(_)
+            | AppError::VersionConflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    /// Seconds to wait before retrying, for errors where the same request may succeed.
+    fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            AppError::TransactionConflict(_) => Some(TRANSACTION_CONFLICT_RETRY_SECS),
+            AppError::ServiceUnavailable(_) => Some(SERVICE_UNAVAILABLE_RETRY_SECS),
+            AppError::TooManyRequests(_) => Some(TOO_MANY_REQUESTS_RETRY_SECS),
+            _ => None,
+        }
+    }
+}
+
 // Implement Display trait for AppError to provide user-friendly error messages
 impl Display for AppError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
             AppError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
+            AppError::Gone(msg) => write!(f, "Gone: {}", msg),
+            AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
             AppError::Validation(msg) => write!(f, "Validation error: {}", msg),
+            AppError::InvalidFields(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|e| format!("{}: {}", e.field, e.message))
+                    .collect();
+                write!(f, "Validation error: {}", fields.join("; "))
+            }
+            AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
+            AppError::HasDependents(blockers) => {
+                let blockers: Vec<String> = blockers
+                    .iter()
+                    .map(|b| format!("{} {} ({})", b.kind, b.id, b.name))
+                    .collect();
+                write!(f, "Has dependents: {}", blockers.join("; "))
+            }
+            AppError::TransactionConflict(msg) => write!(f, "Transaction conflict: {}", msg),
+            AppError::VersionConflict(msg) => write!(f, "Version conflict: {}", msg),
+            AppError::PreconditionRequired(msg) => write!(f, "Precondition required: {}", msg),
+            AppError::ServiceUnavailable(msg) => write!(f, "Service unavailable: {}", msg),
+            AppError::TooManyRequests(msg) => write!(f, "Too many requests: {}", msg),
             AppError::InternalServerError(msg) => write!(f, "Internal server error: {}", msg),
         }
     }
@@ -31,38 +167,261 @@ impl Display for AppError {
 // which is required for the `?` operator and `Box<dyn Error>`.
 impl Error for AppError {}
 
-// Implement IntoResponse for AppError to convert it into an HTTP response
+// Implement IntoResponse for AppError to convert it into an HTTP response.
+// Every error has the same shape:
+// `{"error": {"code": "...", "message": "...", "fields": [...]?, "blockers": [...]?,
+// "retry_after_secs": n?}}`
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::DatabaseError(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Database error: {}", msg),
-            ),
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            AppError::Validation(msg) => (
-                StatusCode::BAD_REQUEST,
-                format!("Validation error: {}", msg),
-            ),
-            AppError::InternalServerError(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Internal server error: {}", msg),
-            ),
-        };
+        let status = self.status();
+        let code = self.code();
+        let retry_after_secs = self.retry_after_secs();
 
-        // Create a JSON response for the error
+        let mut body = json!({ "code": code });
+        match self {
+            // Database and internal messages can contain SQL, schema or config details;
+            // keep them in the logs
+            AppError::DatabaseError(msg) => {
+                error!("Database error: {}", msg);
+                body["message"] = json!("A database error occurred");
+            }
+            AppError::InternalServerError(msg) => {
+                error!("Internal server error: {}", msg);
+                body["message"] = json!("An internal error occurred");
+            }
+            AppError::InvalidFields(fields) => {
+                body["message"] = json!("One or more fields are invalid");
+                body["fields"] = json!(fields);
+            }
+            AppError::HasDependents(blockers) => {
+                body["message"] =
+                    json!("Active records depend on this one; deactivate them first or pass cascade=true");
+                body["blockers"] = json!(blockers);
+            }
+            AppError::NotFound(msg)
+            | AppError::Gone(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Validation(msg)
+            | AppError::Conflict(msg)
+            | AppError::TransactionConflict(msg)
+            | AppError::VersionConflict(msg)
+            | AppError::PreconditionRequired(msg)
+            | AppError::ServiceUnavailable(msg)
+            | AppError::TooManyRequests(msg) => body["message"] = json!(msg),
+        }
+        // Lets clients quote the request when reporting a problem
+        if let Some(request_id) = current_request_id() {
+            body["request_id"] = json!(request_id);
+        }
+
+        // Retryable errors carry a retry hint, also as a Retry-After header
+        let Some(retry_after_secs) = retry_after_secs else {
+            return (status, Json(json!({ "error": body }))).into_response();
+        };
+        body["retry_after_secs"] = json!(retry_after_secs);
         (
             status,
-            axum::Json(serde_json::json!({
-                "error": error_message
-            })),
+            [(header::RETRY_AFTER, retry_after_secs.to_string())],
+            Json(json!({ "error": body })),
         )
             .into_response()
     }
 }
 
+impl From<ValidationErrors> for AppError {
+    /// Flattens `validator` errors (including nested structs and lists) into field errors,
+    /// sorted by field so responses are stable.
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields = Vec::new();
+        collect_field_errors("", &errors, &mut fields);
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
+        AppError::InvalidFields(fields)
+    }
+}
+
+fn collect_field_errors(prefix: &str, errors: &ValidationErrors, out: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                out.extend(field_errors.iter().map(|e| FieldError {
+                    field: path.clone(),
+                    code: e.code.to_string(),
+                    message: e.message.as_ref().map_or_else(
+                        || format!("Failed the '{}' check", e.code),
+                        |m| m.to_string(),
+                    ),
+                }))
+            }
+            ValidationErrorsKind::Struct(nested) => collect_field_errors(&path, nested, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(&format!("{}[{}]", path, index), nested, out);
+                }
+            }
+        }
+    }
+}
+
 impl From<SqlxError> for AppError {
+    /// Maps sqlx errors to client-safe variants. Raw database messages only ever end up
+    /// in `DatabaseError`, whose text is logged rather than returned.
     fn from(error: SqlxError) -> Self {
-        AppError::DatabaseError(error.to_string())
+        match &error {
+            SqlxError::RowNotFound => AppError::NotFound("Record not found".to_string()),
+            SqlxError::PoolTimedOut | SqlxError::PoolClosed | SqlxError::Io(_) => {
+                error!("Database unavailable: {}", error);
+                AppError::ServiceUnavailable(
+                    "The database is temporarily unavailable, please retry shortly".to_string(),
+                )
+            }
+            SqlxError::Database(db_error) => {
+                let code = db_error.code();
+                if matches!(code.as_deref(), Some(SERIALIZATION_FAILURE) | Some(DEADLOCK_DETECTED)) {
+                    return AppError::TransactionConflict(
+                        "The request conflicted with a concurrent change, please retry".to_string(),
+                    );
+                }
+                match db_error.kind() {
+                    ErrorKind::UniqueViolation => {
+                        AppError::Conflict("A record with the same unique values already exists".to_string())
+                    }
+                    ErrorKind::ForeignKeyViolation => AppError::Conflict(
+                        "The record references a missing record, or is still referenced by other records"
+                            .to_string(),
+                    ),
+                    ErrorKind::NotNullViolation => {
+                        AppError::Validation("A required value is missing".to_string())
+                    }
+                    ErrorKind::CheckViolation => {
+                        AppError::Validation("A value is outside the allowed range".to_string())
+                    }
+                    _ => AppError::DatabaseError(error.to_string()),
+                }
+            }
+            _ => AppError::DatabaseError(error.to_string()),
+        }
+    }
+}
+
+impl From<redis::RedisError> for AppError {
+    /// Redis only holds shared state the request cannot do without (e.g. idempotency
+    /// keys), so an outage is reported as temporary.
+    fn from(error: redis::RedisError) -> Self {
+        error!("Redis unavailable: {}", error);
+        AppError::ServiceUnavailable(
+            "The shared cache is temporarily unavailable, please retry shortly".to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::{Connection, PgConnection};
+
+    async fn response_parts(error: AppError) -> (StatusCode, Option<String>, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, retry_after, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn maps_connection_errors() {
+        let cases = [
+            (SqlxError::RowNotFound, "NOT_FOUND"),
+            (SqlxError::PoolTimedOut, "SERVICE_UNAVAILABLE"),
+            (SqlxError::PoolClosed, "SERVICE_UNAVAILABLE"),
+            (
+                SqlxError::Protocol("unexpected message".to_string()),
+                "DATABASE_ERROR",
+            ),
+        ];
+        for (error, code) in cases {
+            let description = error.to_string();
+            assert_eq!(AppError::from(error).code(), code, "{}", description);
+        }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn retryable_errors_carry_retry_after() {
+        let (status, retry_after, body) =
+            response_parts(AppError::TransactionConflict("retry".to_string())).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(retry_after.as_deref(), Some("1"));
+        assert_eq!(body["error"]["code"], "TRANSACTION_CONFLICT");
+        assert_eq!(body["error"]["retry_after_secs"], 1);
+
+        let (status, retry_after, body) =
+            response_parts(AppError::Conflict("duplicate".to_string())).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(retry_after, None);
+        assert!(body["error"].get("retry_after_secs").is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a PostgreSQL database in DATABASE_URL"]
+    async fn maps_database_errors_without_leaking_sql() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL");
+        let mut conn = PgConnection::connect(&url).await.expect("connect");
+        sqlx::query("CREATE TEMPORARY TABLE error_mapping_probe (code TEXT PRIMARY KEY)")
+            .execute(&mut conn)
+            .await
+            .expect("create table");
+        sqlx::query("INSERT INTO error_mapping_probe (code) VALUES ('USD')")
+            .execute(&mut conn)
+            .await
+            .expect("first insert");
+
+        let duplicate = sqlx::query("INSERT INTO error_mapping_probe (code) VALUES ('USD')")
+            .execute(&mut conn)
+            .await
+            .expect_err("duplicate insert");
+        let (status, retry_after, body) = response_parts(duplicate.into()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(retry_after, None);
+        assert_eq!(body["error"]["code"], "CONFLICT");
+        assert!(!body.to_string().contains("error_mapping_probe"));
+
+        // (SQLSTATE, error code, status)
+        let cases = [
+            ("23505", "CONFLICT", StatusCode::CONFLICT),
+            ("23503", "CONFLICT", StatusCode::CONFLICT),
+            ("23502", "VALIDATION_FAILED", StatusCode::BAD_REQUEST),
+            ("23514", "VALIDATION_FAILED", StatusCode::BAD_REQUEST),
+            ("40001", "TRANSACTION_CONFLICT", StatusCode::CONFLICT),
+            ("40P01", "TRANSACTION_CONFLICT", StatusCode::CONFLICT),
+            ("22012", "DATABASE_ERROR", StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (sqlstate, code, status) in cases {
+            let error = sqlx::query(&format!(
+                "DO $$ BEGIN RAISE EXCEPTION 'error_mapping_probe' USING ERRCODE = '{}'; END $$",
+                sqlstate
+            ))
+            .execute(&mut conn)
+            .await
+            .expect_err("raised error");
+            let (actual_status, _, body) = response_parts(error.into()).await;
+            assert_eq!(actual_status, status, "{}", sqlstate);
+            assert_eq!(body["error"]["code"], code, "{}", sqlstate);
+            assert!(
+                !body.to_string().contains("error_mapping_probe"),
+                "{} leaked {}",
+                sqlstate,
+                body
+            );
+        }
+    }
+}
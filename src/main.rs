@@ -6,6 +6,7 @@ use std::net::SocketAddr; // Alias for StdError to avoid conflict with AppError
 
 // Third-party crates
 use axum::{
+    middleware::{from_fn, from_fn_with_state},
     response::IntoResponse, // Added for IntoResponse trait from AppError
     Router,
 };
@@ -16,16 +17,48 @@ use tracing::{info, Level}; // For loading .env file
 
 // Internal modules
 mod app_state;
+mod config;
 mod db;
 mod error;
+mod middleware;
+mod models;
+mod routes;
+mod services;
 mod user;
+mod utils;
 
 use crate::app_state::AppState; // Import AppState from app_state module
+use config::LogFormat;
 use db::setup_database;
 use error::AppError; // This path remains the same
 
 // Update the user_routes import!
 use crate::user::handlers::user_routes; // CHANGED: from `crate::api::user_handlers::user_routes`
+use routes::{
+    account::account_routes, account_type::account_type_routes, api_key::api_key_routes,
+    assistant::assistant_routes, auth::auth_routes, bill::bill_routes, budget::budget_routes,
+    budget_line_item::budget_line_item_routes, business_calendar::business_calendar_routes,
+    calendar_feed::calendar_feed_routes, cash_position::cash_position_routes,
+    category::category_routes, currency::currency_routes, custom_report::custom_report_routes,
+    customer::customer_routes, dashboard::dashboard_routes, dimension::dimension_routes,
+    event::event_routes, exchange_rate::exchange_rate_routes, export::export_routes,
+    ext_conn::ext_conn_routes, ext_provider::ext_provider_routes,
+    fiscal_period::fiscal_period_routes, fixed_asset::fixed_asset_routes,
+    fx_revaluation::fx_revaluation_routes, health::health_routes, household::household_routes,
+    import_job::import_job_routes, invoice::invoice_routes, journal_entry::journal_entry_routes,
+    mail_settings::mail_settings_routes, member_migration::member_migration_routes,
+    merchant_rule::merchant_rule_routes, metrics::metrics_routes,
+    notification::notification_routes, opening_balance::opening_balance_routes,
+    payee::payee_routes, payment::payment_routes, privacy::privacy_routes,
+    quick_open::quick_open_routes, recurring_transaction::recurring_transaction_routes,
+    reimbursement::reimbursement_routes, report::report_routes,
+    security_webhook::security_webhook_routes, statement_layout::statement_layout_routes,
+    tenant::tenant_routes, tenant_invitation::tenant_invitation_routes,
+    transaction::transaction_routes, transaction_match::transaction_match_routes,
+    user_preference::user_preference_routes, vendor::vendor_routes, webhook::webhook_routes,
+    ws::ws_routes,
+};
+use services::{metrics, scheduler};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn StdError>> {
@@ -33,28 +66,45 @@ async fn main() -> Result<(), Box<dyn StdError>> {
     // Load environment variables from .env file
     dotenv().ok();
 
-    // Initialize tracing (logging)
-    tracing_subscriber::fmt()
-        .with_target(false)
-        .compact()
-        .init();
+    // Load and check every setting up front; a bad configuration stops the boot here
+    let config = match config::init() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Initialize tracing (logging); LOG_FORMAT=json writes one JSON object per line,
+    // with the request span (and its request ID) on every event
+    if config.server.log_format == LogFormat::Json {
+        tracing_subscriber::fmt()
+            .with_target(false)
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .init();
+    } else {
+        tracing_subscriber::fmt()
+            .with_target(false)
+            .compact()
+            .init();
+    }
 
     info!("Starting Forge API server...");
 
     // Database setup
-    let database_url =
-        std::env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
-
-    let pool = setup_database(&database_url).await.map_err(|e| {
+    let pools = setup_database(&config.database).await.map_err(|e| {
         Box::new(AppError::DatabaseError(format!(
             "Failed to connect to the database: {}",
             e
         )))
     })?;
+    // Writes and background jobs use the primary
+    let pool = pools.writer.clone();
 
-    // Run migrations
-    sqlx::migrate!("./migrations")
-        .run(&pool)
+    // Run migrations, which may backfill rows of every tenant
+    db::without_tenant_scope(sqlx::migrate!("./migrations").run(&pool))
         .await
         .map_err(|e| {
             Box::new(AppError::InternalServerError(format!(
@@ -63,31 +113,160 @@ async fn main() -> Result<(), Box<dyn StdError>> {
             )))
         })?;
 
+    // Optionally verify that the migrated schema matches what the models expect
+    if config.server.schema_self_check {
+        db::without_tenant_scope(db::verify_schema(&pool))
+            .await
+            .map_err(|mismatches| {
+                Box::new(AppError::InternalServerError(format!(
+                    "Database schema does not match model expectations:\n  - {}",
+                    mismatches.join("\n  - ")
+                )))
+            })?;
+    }
+
+    // Optional Redis shared by all instances
+    services::redis_store::connect().await.map_err(|e| {
+        Box::new(AppError::InternalServerError(format!(
+            "Failed to connect to Redis: {}",
+            e
+        )))
+    })?;
+
+    // Start background jobs
+    scheduler::spawn_recurring_scheduler(pool.clone());
+    scheduler::spawn_bank_sync_scheduler(pool.clone());
+    scheduler::spawn_notification_scheduler(pool.clone());
+    scheduler::spawn_report_scheduler(pool.clone());
+    scheduler::spawn_exchange_rate_scheduler(pool.clone());
+    scheduler::spawn_cash_position_scheduler(pool.clone());
+    scheduler::spawn_export_cleanup_scheduler(pool.clone());
+    scheduler::spawn_maintenance_scheduler(pool.clone());
+    scheduler::spawn_budget_alert_scheduler(pool.clone());
+    scheduler::spawn_depreciation_scheduler(pool.clone());
+
+    // Subscribers of the domain event bus
+    services::audit::spawn_audit_subscriber(pool.clone());
+    services::live_update::spawn_domain_event_forwarder();
+    services::redis_store::spawn_report_cache_invalidator();
+
+    // Clears cached reference data when another instance changes it
+    services::reference_cache::spawn_invalidation_listener(pool.clone());
+
     // Create AppState
-    let app_state = AppState { pool };
+    let app_state = AppState {
+        pools,
+        metrics: metrics::install_recorder(),
+    };
 
-    // Build our application routes
+    // Build our application routes. Tenant-scoped routes read the tenant from the
+    // X-Tenant-Id header; routers sharing a prefix are merged before nesting.
     let app = Router::new()
+        .nest("/healthz", health_routes())
+        .nest("/metrics", metrics_routes())
+        .nest("/api/v1/auth", auth_routes())
+        .nest("/api/v1/api-keys", api_key_routes())
         .nest("/api/v1/users", user_routes())
+        .nest("/api/v1/users/me", user_preference_routes())
+        .nest(
+            "/api/v1/tenants",
+            tenant_routes()
+                .merge(quick_open_routes())
+                .merge(calendar_feed_routes())
+                .merge(tenant_invitation_routes())
+                .merge(member_migration_routes())
+                .merge(opening_balance_routes())
+                .merge(assistant_routes()),
+        )
+        .nest("/api/v1/currencies", currency_routes())
+        .nest("/api/v1/exchange-rates", exchange_rate_routes())
+        .nest("/api/v1/fx-revaluations", fx_revaluation_routes())
+        .nest("/api/v1/account-types", account_type_routes())
+        .nest("/api/v1/accounts", account_routes())
+        .nest("/api/v1/categories", category_routes())
+        .nest("/api/v1/cash-position", cash_position_routes())
+        .nest(
+            "/api/v1/transactions",
+            transaction_routes().merge(journal_entry_routes()),
+        )
+        .nest("/api/v1/events", event_routes())
+        .nest("/api/v1/household", household_routes())
+        .nest("/api/v1/reimbursements", reimbursement_routes())
+        .nest("/api/v1/customers", customer_routes())
+        .nest("/api/v1/invoices", invoice_routes())
+        .nest("/api/v1/vendors", vendor_routes())
+        .nest("/api/v1/bills", bill_routes())
+        .nest("/api/v1/payments", payment_routes())
+        .nest("/api/v1/fixed-assets", fixed_asset_routes())
+        .nest("/api/v1/payees", payee_routes())
+        .nest("/api/v1/dimensions", dimension_routes())
+        .nest("/api/v1/recurring-transactions", recurring_transaction_routes())
+        .nest("/api/v1/business-calendar", business_calendar_routes())
+        .nest("/api/v1/fiscal-periods", fiscal_period_routes())
+        .nest(
+            "/api/v1/budgets",
+            budget_routes().merge(budget_line_item_routes()),
+        )
+        .nest("/api/v1/import-jobs", import_job_routes())
+        .nest("/api/v1/exports", export_routes())
+        .nest("/api/v1/reports", report_routes())
+        .nest("/api/v1/custom-reports", custom_report_routes())
+        .nest("/api/v1/statement-layouts", statement_layout_routes())
+        .nest("/api/v1/dashboards", dashboard_routes())
+        .nest("/api/v1/ext-providers", ext_provider_routes())
+        .nest("/api/v1/bank-connections", ext_conn_routes())
+        .nest("/api/v1/transaction-matches", transaction_match_routes())
+        .nest("/api/v1/merchant-rules", merchant_rule_routes())
+        .nest("/api/v1/notifications", notification_routes())
+        .nest("/api/v1/mail-settings", mail_settings_routes())
+        .nest("/api/v1/privacy", privacy_routes())
+        .nest("/api/v1/security-webhook", security_webhook_routes())
+        .nest("/api/v1/webhooks", webhook_routes())
+        .nest("/api/v1/ws", ws_routes())
+        // Sets `app.tenant_id` for row-level security on the connections a request uses
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            crate::middleware::tenant_scope::scope_to_tenant,
+        ))
+        // Replays the recorded response of a POST retried with the same Idempotency-Key
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            crate::middleware::idempotency::idempotent_posts,
+        ))
+        // Adds the read-after-write token to successful writes
+        .route_layer(from_fn_with_state(
+            app_state.clone(),
+            crate::middleware::consistency::issue_consistency_token,
+        ))
         .with_state(app_state)
+        // After routing, so requests are labelled by their route template
+        .route_layer(from_fn(crate::middleware::metrics::track_metrics))
+        .layer(from_fn(crate::middleware::rate_limiting::rate_limit))
         .layer(
             TraceLayer::new_for_http()
-                .make_span_with(trace::DefaultMakeSpan::new().level(Level::INFO))
+                .make_span_with(crate::middleware::logging::make_request_span)
                 .on_response(trace::DefaultOnResponse::new().level(Level::INFO)),
         );
+    // Answers browser preflights before they reach the rate limiter or routes
+    let app = match config.cors.layer() {
+        Some(cors) => app.layer(cors),
+        None => app,
+    };
+    // Outermost, so the request ID is known before the trace span is made
+    let app = app.layer(from_fn(crate::middleware::logging::request_id));
 
     // Run the server
-    let port = std::env::var("PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse::<u16>()
-        .expect("PORT must be a valid number");
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let addr = SocketAddr::from((config.server.host, config.server.port));
     info!("Forge API server listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
-    axum::serve(listener, app.into_make_service()).await?;
+    // Peer addresses are needed by the rate limiter
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     tracing::info!("Forge API server stopped gracefully.");
 
     Ok(())
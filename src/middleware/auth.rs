@@ -1,11 +1,193 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap},
+};
+use sqlx::PgPool;
 use uuid::Uuid;
 
-/// Placeholder function to get the current user's ID.
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    services::{api_key, auth},
+};
+
+/// Header carrying the tenant a request operates on.
+pub const TENANT_ID_HEADER: &str = "x-tenant-id";
+
+/// Header carrying an API key; the key determines the tenant and user.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The signed-in user of a request, authenticated by an `X-Api-Key` or by a login session's
+/// access token in `Authorization: Bearer`. Handlers that are not tenant-scoped take this
+/// extractor; tenant-scoped ones get the user from `TenantContext`.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthUser {
+    pub user_id: Uuid,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(context) = parts.extensions.get::<TenantContext>() {
+            return Ok(AuthUser {
+                user_id: context.user_id,
+            });
+        }
+
+        let api_key = api_key_header(&parts.headers)?;
+        let access_token = bearer_token(&parts.headers)?;
+        let user_id = authenticate_user(&state.pools.writer, api_key, access_token).await?;
+        Ok(AuthUser { user_id })
+    }
+}
+
+/// Reads the `X-Api-Key` header, if sent.
+pub fn api_key_header(headers: &HeaderMap) -> Result<Option<&str>, AppError> {
+    match headers.get(API_KEY_HEADER) {
+        Some(header) => header
+            .to_str()
+            .map(Some)
+            .map_err(|_| AppError::Unauthorized("Invalid X-Api-Key header".to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Reads the access token from an `Authorization: Bearer` header, if sent.
+pub fn bearer_token(headers: &HeaderMap) -> Result<Option<&str>, AppError> {
+    let Some(header) = headers.get(header::AUTHORIZATION) else {
+        return Ok(None);
+    };
+    header
+        .to_str()
+        .ok()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(Some)
+        .ok_or_else(|| {
+            AppError::Unauthorized(
+                "The Authorization header must be 'Bearer <access token>'".to_string(),
+            )
+        })
+}
+
+/// Resolves the user behind an API key or, without one, a session's access token.
+pub async fn authenticate_user(
+    pool: &PgPool,
+    api_key: Option<&str>,
+    access_token: Option<&str>,
+) -> Result<Uuid, AppError> {
+    if let Some(key) = api_key {
+        return Ok(api_key::authenticate(pool, key).await?.user_id);
+    }
+    match access_token {
+        Some(token) => auth::authenticate(pool, token).await,
+        None => Err(AppError::Unauthorized(
+            "Sign in and send the access token as 'Authorization: Bearer', or send an X-Api-Key"
+                .to_string(),
+        )),
+    }
+}
+
+/// Tenant scope of a request, resolved from the `X-Tenant-Id` header or an `X-Api-Key`.
 ///
-/// In a real application, this would extract the user ID from JWT, API key,
-/// or session information in the request context after authentication.
-pub fn get_current_user_id() -> Uuid {
-    // TODO: Replace with actual authentication logic to derive the user ID
-    // For now, returning a hardcoded UUID for testing purposes.
-    "00000000-0000-0000-0000-000000000001".parse().unwrap()
-}
\ No newline at end of file
+/// Tenant-scoped handlers take this extractor instead of reading the tenant from the path,
+/// so services always receive both the tenant and the acting user. Requests made with a
+/// sandbox key are scoped to the tenant's sandbox, so handlers need no sandbox awareness.
+#[derive(Debug, Clone, Copy)]
+pub struct TenantContext {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for TenantContext {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // Already resolved by `tenant_scope::scope_to_tenant`
+        if let Some(context) = parts.extensions.get::<TenantContext>() {
+            return Ok(*context);
+        }
+
+        let header_tenant_id = match parts.headers.get(TENANT_ID_HEADER) {
+            Some(header) => Some(
+                header
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.parse::<Uuid>().ok())
+                    .ok_or_else(|| {
+                        AppError::Validation("Invalid X-Tenant-Id header".to_string())
+                    })?,
+            ),
+            None => None,
+        };
+
+        let api_key = api_key_header(&parts.headers)?;
+        let access_token = bearer_token(&parts.headers)?;
+
+        resolve_tenant_context(state, header_tenant_id, api_key, access_token).await
+    }
+}
+
+/// Resolves the tenant scope from a tenant ID and an API key or access token, wherever they
+/// were sent. With a key, the tenant ID is optional but must name the key's tenant (or its
+/// sandbox). With an access token, the signed-in user must be a member of the tenant.
+pub async fn resolve_tenant_context(
+    state: &AppState,
+    tenant_id: Option<Uuid>,
+    api_key: Option<&str>,
+    access_token: Option<&str>,
+) -> Result<TenantContext, AppError> {
+    if let Some(key) = api_key {
+        let scope = api_key::authenticate(&state.pools.writer, key).await?;
+        if let Some(tenant_id) = tenant_id {
+            if tenant_id != scope.key_tenant_id && tenant_id != scope.tenant_id {
+                return Err(AppError::Forbidden(format!(
+                    "The API key does not belong to tenant {}",
+                    tenant_id
+                )));
+            }
+        }
+        return Ok(TenantContext {
+            tenant_id: scope.tenant_id,
+            user_id: scope.user_id,
+        });
+    }
+
+    let user_id = authenticate_user(&state.pools.writer, None, access_token).await?;
+    let tenant_id =
+        tenant_id.ok_or_else(|| AppError::Validation("Missing X-Tenant-Id header".to_string()))?;
+
+    let is_member = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM user_tenant_roles WHERE user_id = $1 AND tenant_id = $2
+        ) as "is_member!"
+        "#,
+        user_id,
+        tenant_id
+    )
+    .fetch_one(&state.pools.writer)
+    .await?;
+    if !is_member {
+        return Err(AppError::Forbidden(format!(
+            "You are not a member of tenant {}",
+            tenant_id
+        )));
+    }
+
+    Ok(TenantContext {
+        tenant_id,
+        user_id,
+    })
+}
@@ -0,0 +1,71 @@
+//! Read-after-write consistency tokens.
+//!
+//! Every successful write answers with an `X-Consistency-Token`: the primary's WAL position
+//! (LSN) once the write committed. Clients keep the latest token and echo it on later reads
+//! so a read never observes the database from before their own write; the token is opaque
+//! to them. Heavy reads taking a `ReadPool` are served by a read replica that has replayed
+//! the token's position, or by the primary, which has always caught up.
+
+use std::convert::Infallible;
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{request::Parts, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::{app_state::AppState, db};
+
+pub static X_CONSISTENCY_TOKEN: HeaderName = HeaderName::from_static("x-consistency-token");
+
+/// Adds the consistency token to the response of every successful write.
+pub async fn issue_consistency_token(
+    State(pool): State<PgPool>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let is_write = !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let mut response = next.run(req).await;
+    if !is_write || !response.status().is_success() {
+        return response;
+    }
+
+    match db::current_wal_lsn(&pool).await {
+        Ok(lsn) => {
+            if let Ok(value) = HeaderValue::from_str(&lsn) {
+                response
+                    .headers_mut()
+                    .insert(X_CONSISTENCY_TOKEN.clone(), value);
+            }
+        }
+        Err(e) => warn!(
+            "Could not read the WAL position for a consistency token: {}",
+            e
+        ),
+    }
+    response
+}
+
+/// The pool for a heavy read: a read replica when one has caught up with the caller's
+/// consistency token (or there is none), otherwise the primary.
+pub struct ReadPool(pub PgPool);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ReadPool {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(&X_CONSISTENCY_TOKEN)
+            .and_then(|value| value.to_str().ok());
+        Ok(ReadPool(state.pools.reader_after(token).await.clone()))
+    }
+}
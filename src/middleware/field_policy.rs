@@ -0,0 +1,49 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::auth::TenantContext,
+    services::field_policy::{self, FieldAccess},
+};
+
+/// Resolves the current user's field access for the request's tenant.
+#[async_trait]
+impl FromRequestParts<AppState> for FieldAccess {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let ctx = TenantContext::from_request_parts(parts, state).await?;
+        field_policy::load_field_access(&state.pools.writer, ctx.tenant_id, ctx.user_id).await
+    }
+}
+
+/// JSON response with the field policy applied to it.
+///
+/// Handlers returning data that may contain sensitive fields return
+/// `Redacted(data, access)` instead of `Json(data)`.
+pub struct Redacted<T>(pub T, pub FieldAccess);
+
+impl<T: Serialize> IntoResponse for Redacted<T> {
+    fn into_response(self) -> Response {
+        let Redacted(data, access) = self;
+        match serde_json::to_value(data) {
+            Ok(mut value) => {
+                access.apply(&mut value);
+                Json(value).into_response()
+            }
+            Err(e) => AppError::InternalServerError(format!("Failed to serialize response: {}", e))
+                .into_response(),
+        }
+    }
+}
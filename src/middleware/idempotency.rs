@@ -0,0 +1,438 @@
+//! `Idempotency-Key` support for POST requests.
+//!
+//! A client that may retry a POST (e.g. a mobile app on a flaky network) sends a unique
+//! `Idempotency-Key`. The first request with the key runs; a retry with the same key and
+//! the same method, path and body gets the original response back, marked with
+//! `Idempotent-Replayed: true`, instead of creating a duplicate. Reusing a key for a
+//! different request is a 422, and retrying while the first request is still running a 409.
+//!
+//! Keys belong to the caller (the API key, or else the signed-in user within the tenant),
+//! so clients cannot replay each other's responses. Requests that are not tenant-scoped, or
+//! whose credentials do not check out, ignore the header. Server errors and conflicts are
+//! not recorded, so they can be retried with the same key.
+
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::{OriginalUri, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, FieldError},
+    middleware::auth::{api_key_header, bearer_token, TENANT_ID_HEADER},
+    services::{
+        api_key, auth,
+        idempotency::{self, KeyClaim, StoredResponse},
+    },
+    utils::crypto::sha256_hex,
+};
+
+pub static IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+pub static IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// Longest accepted `Idempotency-Key`; a UUID is the usual choice.
+const MAX_KEY_LENGTH: usize = 255;
+/// Largest request body that can be sent with a key (it is hashed in memory).
+const MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+/// Larger responses are not recorded; the key is released so a retry runs again.
+const MAX_STORED_RESPONSE_BYTES: u64 = 1024 * 1024;
+
+/// Runs a POST with an `Idempotency-Key` at most once per key and replays its response.
+pub async fn idempotent_posts(
+    State(pool): State<PgPool>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if req.method() != Method::POST {
+        return Ok(next.run(req).await);
+    }
+    let Some(key) = req.headers().get(&IDEMPOTENCY_KEY) else {
+        return Ok(next.run(req).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "Idempotency-Key must be 1 to {} visible ASCII characters",
+                MAX_KEY_LENGTH
+            ))
+        })?
+        .to_string();
+    // Requests without valid credentials are left to the route to reject
+    let caller = authenticate_caller(&pool, req.headers()).await;
+    let Some(scope) = caller_scope(req.headers(), caller) else {
+        return Ok(next.run(req).await);
+    };
+
+    let uri = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| req.uri().to_string(), |uri| uri.0.to_string());
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, MAX_REQUEST_BYTES).await.map_err(|_| {
+        AppError::Validation(format!(
+            "A request with an Idempotency-Key can have at most {} bytes",
+            MAX_REQUEST_BYTES
+        ))
+    })?;
+    let mut fingerprint = format!("{} {}\n", parts.method, uri).into_bytes();
+    fingerprint.extend_from_slice(&body);
+    let request_hash = sha256_hex(&fingerprint);
+
+    match idempotency::claim_key(&pool, &scope, &key, &request_hash).await? {
+        KeyClaim::Claimed => {}
+        KeyClaim::Completed(stored) => return Ok(replay(stored)),
+        KeyClaim::InProgress => {
+            return Err(AppError::Conflict(
+                "A request with this Idempotency-Key is still being processed".to_string(),
+            ))
+        }
+        KeyClaim::Mismatch => {
+            return Err(AppError::InvalidFields(vec![FieldError {
+                field: "Idempotency-Key".to_string(),
+                code: "idempotency_key_reused".to_string(),
+                message: "This key was already used for a different request".to_string(),
+            }]))
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    let fits = body
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size <= MAX_STORED_RESPONSE_BYTES);
+    if !is_final(parts.status) || !fits {
+        if let Err(e) = idempotency::release_key(&pool, &scope, &key, &request_hash).await {
+            warn!("Could not release Idempotency-Key: {}", e);
+        }
+        return Ok(Response::from_parts(parts, body));
+    }
+
+    let body = to_bytes(body, MAX_STORED_RESPONSE_BYTES as usize)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to read the response body: {}", e))
+        })?;
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        content_type: parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        body: body.to_vec(),
+    };
+    // The client already has its response; a retry finds the key in progress until it goes stale
+    if let Err(e) = idempotency::complete_key(&pool, &scope, &key, &request_hash, &stored).await {
+        warn!(
+            "Could not record the response for an Idempotency-Key: {}",
+            e
+        );
+    }
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Who sent a request, once their credentials have been checked.
+enum Caller {
+    ApiKey { key_hash: String },
+    User(Uuid),
+}
+
+/// The caller behind the request's API key or, without one, its access token; `None` when
+/// the credentials are missing or invalid.
+async fn authenticate_caller(pool: &PgPool, headers: &HeaderMap) -> Option<Caller> {
+    if let Some(key) = api_key_header(headers).ok()? {
+        api_key::authenticate(pool, key).await.ok()?;
+        return Some(Caller::ApiKey {
+            key_hash: sha256_hex(key.as_bytes()),
+        });
+    }
+    let token = bearer_token(headers).ok()??;
+    auth::authenticate(pool, token).await.ok().map(Caller::User)
+}
+
+/// Whom the key belongs to, or `None` for requests that are not tenant-scoped. Without an
+/// API key that is the signed-in user within the tenant, so members of one tenant never
+/// share keys.
+fn caller_scope(headers: &HeaderMap, caller: Option<Caller>) -> Option<String> {
+    let user_id = match caller? {
+        Caller::ApiKey { key_hash } => return Some(format!("key:{}", key_hash)),
+        Caller::User(user_id) => user_id,
+    };
+    headers
+        .get(TENANT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|tenant_id| {
+            format!(
+                "tenant:{}:user:{}",
+                tenant_id.trim().to_ascii_lowercase(),
+                user_id
+            )
+        })
+}
+
+/// Whether a response is the request's final answer. Server errors, conflicts and rate
+/// limits may go away on a retry, so they are not recorded.
+fn is_final(status: StatusCode) -> bool {
+    !(status.is_server_error()
+        || status == StatusCode::CONFLICT
+        || status == StatusCode::TOO_MANY_REQUESTS)
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let mut response = (status, stored.body).into_response();
+    let headers = response.headers_mut();
+    headers.remove(header::CONTENT_TYPE);
+    if let Some(content_type) = stored
+        .content_type
+        .and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    headers.insert(
+        IDEMPOTENT_REPLAYED.clone(),
+        HeaderValue::from_static("true"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use axum::{middleware::from_fn_with_state, routing::post, Json, Router};
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::{app_state::AppState, config, db::DbPools};
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    HeaderName::from_static(name),
+                    HeaderValue::from_str(value).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn users_of_one_tenant_do_not_share_keys() {
+        let tenant = headers(&[(TENANT_ID_HEADER, "6F9619FF-8B86-D011-B42D-00C04FC964FF")]);
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let alice_scope = caller_scope(&tenant, Some(Caller::User(alice))).unwrap();
+        assert_eq!(
+            alice_scope,
+            format!("tenant:6f9619ff-8b86-d011-b42d-00c04fc964ff:user:{}", alice)
+        );
+        // The same Idempotency-Key from another member is claimed in a scope of its own
+        assert_ne!(
+            caller_scope(&tenant, Some(Caller::User(bob))).unwrap(),
+            alice_scope
+        );
+        assert_eq!(
+            caller_scope(&tenant, Some(Caller::User(alice))).unwrap(),
+            alice_scope
+        );
+    }
+
+    #[test]
+    fn api_keys_are_scoped_by_key() {
+        let request = headers(&[(TENANT_ID_HEADER, "6f9619ff-8b86-d011-b42d-00c04fc964ff")]);
+        let key_hash = sha256_hex(b"forge_test_key");
+        let scope = caller_scope(
+            &request,
+            Some(Caller::ApiKey {
+                key_hash: key_hash.clone(),
+            }),
+        )
+        .unwrap();
+        assert_eq!(scope, format!("key:{}", key_hash));
+    }
+
+    #[test]
+    fn requests_without_a_tenant_are_not_scoped() {
+        assert_eq!(
+            caller_scope(&HeaderMap::new(), Some(Caller::User(Uuid::new_v4()))),
+            None
+        );
+    }
+
+    #[test]
+    fn signed_out_requests_are_not_scoped() {
+        let tenant = headers(&[(TENANT_ID_HEADER, "6f9619ff-8b86-d011-b42d-00c04fc964ff")]);
+        assert_eq!(caller_scope(&tenant, None), None);
+    }
+
+    /// Serves a POST route behind `idempotent_posts`; returns its URL and how many times the
+    /// route has run. The route answers 500 to a body of `fail`.
+    async fn serve(pool: PgPool) -> (String, Arc<AtomicUsize>) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let state = AppState {
+            pools: DbPools::new(pool, Vec::new()),
+            metrics: PrometheusBuilder::new().build_recorder().handle(),
+        };
+        let route = post(move |body: String| async move {
+            let run = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            let status = if body == "fail" {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::CREATED
+            };
+            (status, Json(json!({ "run": run, "body": body })))
+        });
+        let app = Router::new()
+            .route("/things", route)
+            .route_layer(from_fn_with_state(state.clone(), idempotent_posts))
+            .with_state(state);
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("local address");
+        tokio::spawn(async move { axum::serve(listener, app).await.expect("serve") });
+        (format!("http://{}/things", addr), runs)
+    }
+
+    /// A signed-in client that sends its access token and a tenant with every request.
+    struct Client {
+        http: reqwest::Client,
+        url: String,
+        access_token: String,
+        tenant_id: Uuid,
+    }
+
+    impl Client {
+        async fn sign_in(pool: &PgPool, url: String) -> Self {
+            config::init().expect("load config");
+            let user_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+                 VALUES ($1, 'EMAIL_PASSWORD', $1, 'Retry', 'Tester') RETURNING id",
+            )
+            .bind(format!("idempotency-{}@example.com", Uuid::new_v4()))
+            .fetch_one(pool)
+            .await
+            .expect("insert user");
+            let tokens = auth::start_session(pool, user_id, None, None)
+                .await
+                .expect("start session");
+            Client {
+                http: reqwest::Client::new(),
+                url,
+                access_token: tokens.access_token,
+                tenant_id: Uuid::new_v4(),
+            }
+        }
+
+        async fn post(&self, key: &str, body: &str) -> reqwest::Response {
+            self.http
+                .post(&self.url)
+                .bearer_auth(&self.access_token)
+                .header(TENANT_ID_HEADER, self.tenant_id.to_string())
+                .header(&IDEMPOTENCY_KEY, key)
+                .body(body.to_string())
+                .send()
+                .await
+                .expect("send request")
+        }
+    }
+
+    fn replayed(response: &reqwest::Response) -> bool {
+        response
+            .headers()
+            .get(&IDEMPOTENT_REPLAYED)
+            .is_some_and(|value| value == "true")
+    }
+
+    async fn connect() -> PgPool {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPool::connect(&url)
+            .await
+            .expect("connect to DATABASE_URL")
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn a_retry_with_the_same_key_replays_the_response() {
+        let pool = connect().await;
+        let (url, runs) = serve(pool.clone()).await;
+        let client = Client::sign_in(&pool, url).await;
+        let key = Uuid::new_v4().to_string();
+
+        let first = client.post(&key, "new thing").await;
+        assert_eq!(first.status(), reqwest::StatusCode::CREATED);
+        assert!(!replayed(&first));
+        let first_body = first.text().await.expect("read body");
+
+        let retry = client.post(&key, "new thing").await;
+        assert_eq!(retry.status(), reqwest::StatusCode::CREATED);
+        assert!(replayed(&retry));
+        assert_eq!(
+            retry
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok()),
+            Some("application/json")
+        );
+        assert_eq!(retry.text().await.expect("read body"), first_body);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn a_key_reused_for_another_body_is_rejected() {
+        let pool = connect().await;
+        let (url, runs) = serve(pool.clone()).await;
+        let client = Client::sign_in(&pool, url).await;
+        let key = Uuid::new_v4().to_string();
+
+        assert_eq!(
+            client.post(&key, "new thing").await.status(),
+            reqwest::StatusCode::CREATED
+        );
+        let reused = client.post(&key, "another thing").await;
+        assert_eq!(reused.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(!replayed(&reused));
+        assert!(reused
+            .text()
+            .await
+            .expect("read body")
+            .contains("idempotency_key_reused"));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn a_server_error_releases_the_key() {
+        let pool = connect().await;
+        let (url, runs) = serve(pool.clone()).await;
+        let client = Client::sign_in(&pool, url).await;
+        let key = Uuid::new_v4().to_string();
+
+        for attempt in 1..=2 {
+            let response = client.post(&key, "fail").await;
+            assert_eq!(
+                response.status(),
+                reqwest::StatusCode::INTERNAL_SERVER_ERROR
+            );
+            assert!(!replayed(&response));
+            // The retry ran the route again instead of replaying the failure
+            assert_eq!(runs.load(Ordering::SeqCst), attempt);
+        }
+    }
+}
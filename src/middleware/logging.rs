@@ -0,0 +1,71 @@
+//! Request IDs for logs and error responses.
+//!
+//! Every request gets an ID: the client's `X-Request-Id` when it sent a usable one, else a
+//! new UUID. The ID is stored as a `RequestId` extension (read by the trace layer's span,
+//! so every log line of the request carries it), kept in a task-local for `AppError`
+//! responses and echoed in the `X-Request-Id` response header.
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{info_span, Span};
+use uuid::Uuid;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied request ID that is kept; longer ones are replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// The ID of the request being handled.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// The current request's ID, when called while handling a request.
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Assigns the request ID. Apply outside the trace layer so its span can read it.
+pub async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_usable(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let mut response = CURRENT_REQUEST_ID.scope(id.clone(), next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+    response
+}
+
+/// Span for the trace layer: one per request, tagged with its ID.
+pub fn make_request_span(req: &Request) -> Span {
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(id)| id.as_str())
+        .unwrap_or_default();
+    info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        uri = %req.uri(),
+    )
+}
+
+/// Client IDs are echoed into logs and headers, so only short printable ASCII is kept.
+fn is_usable(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
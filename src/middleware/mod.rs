@@ -4,5 +4,13 @@
 //! such as authentication, logging, and potentially rate limiting or CORS.
 
 pub mod auth; // For authentication middleware (e.g., JWT validation)
-pub mod logging; // For request logging (though Tower-HTTP's TraceLayer is often sufficient)
-// pub mod rate_limiting; // Example for future use
\ No newline at end of file
+pub mod field_policy; // Field-level redaction of responses
+pub mod validated_json; // JSON bodies checked against their DTO's validation rules
+pub mod metrics; // Per-route request counts and latencies for Prometheus
+pub mod logging; // Request IDs for log spans and error responses
+pub mod rate_limiting; // Per-client request limits from the rate_limit settings
+pub mod consistency; // Read-after-write tokens returned from writes
+pub mod idempotency; // Replayed responses for POSTs retried with an Idempotency-Key
+pub mod precondition; // If-Match versions and ETags for conditional requests
+pub mod tenant_scope; // Row-level security scope of tenant requests
+pub mod report_format; // JSON or XLSX for reports, from ?format= or Accept
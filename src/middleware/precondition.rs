@@ -0,0 +1,161 @@
+//! Preconditions for optimistic concurrency control.
+//!
+//! Transactions, accounts and budgets carry a `version` that every change bumps. Their
+//! update endpoints require the version the client last read, sent as `If-Match: "3"`
+//! (quotes and a `W/` prefix are accepted, so an ETag can be echoed back). The update only
+//! applies to that version; if someone else changed the record in between, the client gets
+//! a 409 and must refetch instead of silently overwriting the other change.
+//!
+//! Their single-resource GETs return the version as a weak ETag (`W/"3"`). A client that
+//! polls sends it back in `If-None-Match` and gets an empty 304 while the record is
+//! unchanged.
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::error::AppError;
+
+/// The record version a conditional update expects, from `If-Match`.
+#[derive(Debug, Clone, Copy)]
+pub struct IfMatchVersion(pub i32);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IfMatchVersion
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let value = parts.headers.get(header::IF_MATCH).ok_or_else(|| {
+            AppError::PreconditionRequired(
+                "Send the version you last read in an If-Match header".to_string(),
+            )
+        })?;
+        value
+            .to_str()
+            .ok()
+            .and_then(parse_version)
+            .map(IfMatchVersion)
+            .ok_or_else(|| {
+                AppError::Validation("If-Match must be a record version, e.g. \"3\"".to_string())
+            })
+    }
+}
+
+/// The ETags a conditional GET already holds, from `If-None-Match`.
+#[derive(Debug, Clone, Default)]
+pub struct IfNoneMatch(Option<String>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IfNoneMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let value = parts
+            .headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        Ok(IfNoneMatch(value))
+    }
+}
+
+impl IfNoneMatch {
+    /// Answers with 304 when the client already holds `version`, otherwise with `body`.
+    pub fn respond<R>(&self, version: i32, body: R) -> Conditional<R> {
+        if self.matches(version) {
+            Conditional::NotModified(version)
+        } else {
+            Conditional::Modified(version, body)
+        }
+    }
+
+    /// ETags compare weakly, so `"3"` and `W/"3"` both match version 3.
+    fn matches(&self, version: i32) -> bool {
+        let Some(value) = &self.0 else {
+            return false;
+        };
+        value
+            .split(',')
+            .any(|tag| tag.trim() == "*" || parse_version(tag) == Some(version))
+    }
+}
+
+/// A single-resource GET response carrying the record version as its ETag.
+pub enum Conditional<R> {
+    NotModified(i32),
+    Modified(i32, R),
+}
+
+impl<R: IntoResponse> IntoResponse for Conditional<R> {
+    fn into_response(self) -> Response {
+        let (version, mut response) = match self {
+            Conditional::NotModified(version) => {
+                (version, StatusCode::NOT_MODIFIED.into_response())
+            }
+            Conditional::Modified(version, body) => (version, body.into_response()),
+        };
+        if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+            if let Ok(etag) = HeaderValue::from_str(&etag(version)) {
+                response.headers_mut().insert(header::ETAG, etag);
+            }
+        }
+        response
+    }
+}
+
+/// The weak ETag for a record version.
+fn etag(version: i32) -> String {
+    format!("W/\"{}\"", version)
+}
+
+/// `3`, `"3"` or `W/"3"`.
+fn parse_version(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let value = value.strip_prefix("W/").unwrap_or(value);
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    value.parse().ok().filter(|version| *version > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_version, IfNoneMatch};
+
+    #[test]
+    fn bare_quoted_and_weak_versions_parse() {
+        assert_eq!(parse_version("3"), Some(3));
+        assert_eq!(parse_version("\"3\""), Some(3));
+        assert_eq!(parse_version("W/\"3\""), Some(3));
+        assert_eq!(parse_version(" W/\"12\" "), Some(12));
+    }
+
+    #[test]
+    fn malformed_versions_are_rejected() {
+        for value in [
+            "", "\"\"", "abc", "\"3", "3\"", "W/", "w/\"3\"", "\"3.5\"", "0", "-1", "*",
+        ] {
+            assert_eq!(parse_version(value), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn if_none_match_compares_weakly() {
+        let held = IfNoneMatch(Some("\"2\", W/\"3\"".to_string()));
+        assert!(held.matches(2));
+        assert!(held.matches(3));
+        assert!(!held.matches(4));
+        assert!(IfNoneMatch(Some("*".to_string())).matches(4));
+        assert!(!IfNoneMatch::default().matches(1));
+    }
+}
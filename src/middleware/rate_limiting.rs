@@ -0,0 +1,165 @@
+//! Per-client request rate limiting.
+//!
+//! Each client IP gets a token bucket holding up to `RATE_LIMIT_BURST` requests, refilled
+//! at `RATE_LIMIT_REQUESTS_PER_MINUTE`. Behind a proxy, set `RATE_LIMIT_TRUST_FORWARDED_FOR`
+//! so clients are told apart by the first `X-Forwarded-For` address instead of the
+//! proxy's. With Redis configured the buckets are kept there, so a client's rate is shared
+//! by all instances; otherwise (and while Redis is unreachable) they live in memory and each
+//! instance limits on its own. Health and metrics endpoints are never limited.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Mutex, OnceLock},
+    time::Instant,
+};
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+
+use redis::{aio::ConnectionManager, RedisError, Script};
+use tracing::warn;
+
+use crate::{config, error::AppError, services::redis_store};
+
+/// Number of tracked clients above which full (idle) buckets are dropped.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+static BUCKETS: OnceLock<Mutex<HashMap<IpAddr, Bucket>>> = OnceLock::new();
+
+/// The in-memory bucket logic on a Redis hash, timed by the Redis clock so instances
+/// agree. Returns 1 when a token was taken.
+const TAKE_TOKEN: &str = r#"
+local capacity = tonumber(ARGV[1])
+local per_second = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) + tonumber(time[2]) / 1000000
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'refilled_at')
+local tokens = tonumber(bucket[1]) or capacity
+local refilled_at = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - refilled_at) * per_second)
+local taken = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    taken = 1
+end
+redis.call('HSET', KEYS[1], 'tokens', string.format('%.6f', tokens), 'refilled_at', string.format('%.6f', now))
+redis.call('EXPIRE', KEYS[1], math.ceil(capacity / per_second) + 1)
+return taken
+"#;
+
+/// Rejects requests over the client's rate with 429. A no-op when rate limiting is off.
+pub async fn rate_limit(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let settings = &config::get().rate_limit;
+    let path = req.uri().path();
+    if !settings.is_enabled() || path.starts_with("/healthz") || path.starts_with("/metrics") {
+        return Ok(next.run(req).await);
+    }
+
+    let client = client_ip(peer.ip(), req.headers());
+    let capacity = f64::from(settings.burst);
+    let per_second = f64::from(settings.requests_per_minute) / 60.0;
+
+    let allowed = match redis_store::connection() {
+        Some(mut connection) => {
+            match take_shared_token(&mut connection, client, capacity, per_second).await {
+                Ok(allowed) => allowed,
+                Err(e) => {
+                    warn!("Shared rate limit unavailable, limiting locally: {}", e);
+                    take_local_token(client, capacity, per_second)
+                }
+            }
+        }
+        None => take_local_token(client, capacity, per_second),
+    };
+
+    if !allowed {
+        return Err(AppError::TooManyRequests(format!(
+            "Rate limit of {} requests per minute exceeded",
+            settings.requests_per_minute
+        )));
+    }
+    Ok(next.run(req).await)
+}
+
+/// Takes a token from the client's bucket in this instance's memory.
+fn take_local_token(client: IpAddr, capacity: f64, per_second: f64) -> bool {
+    let mut buckets = BUCKETS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let now = Instant::now();
+    if buckets.len() > PRUNE_THRESHOLD {
+        buckets.retain(|_, bucket| {
+            bucket.tokens + now.duration_since(bucket.refilled_at).as_secs_f64() * per_second
+                < capacity
+        });
+    }
+    let bucket = buckets.entry(client).or_insert(Bucket {
+        tokens: capacity,
+        refilled_at: now,
+    });
+    let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+    bucket.refilled_at = now;
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        true
+    } else {
+        false
+    }
+}
+
+/// Takes a token from the client's bucket in Redis.
+async fn take_shared_token(
+    connection: &mut ConnectionManager,
+    client: IpAddr,
+    capacity: f64,
+    per_second: f64,
+) -> Result<bool, RedisError> {
+    static SCRIPT: OnceLock<Script> = OnceLock::new();
+    let taken: i64 = SCRIPT
+        .get_or_init(|| Script::new(TAKE_TOKEN))
+        .key(redis_store::key(&format!("rate_limit:{}", client)))
+        .arg(capacity)
+        .arg(per_second)
+        .invoke_async(connection)
+        .await?;
+    Ok(taken == 1)
+}
+
+/// The client's address: the first `X-Forwarded-For` entry when the proxy is trusted,
+/// otherwise the peer.
+pub(crate) fn client_ip(peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+    if config::get().rate_limit.trust_forwarded_for {
+        forwarded_for(headers).unwrap_or(peer)
+    } else {
+        peer
+    }
+}
+
+/// The first (client) address of `X-Forwarded-For`.
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get("x-forwarded-for")?
+        .to_str()
+        .ok()?
+        .split(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
@@ -0,0 +1,67 @@
+//! Picks the representation of a report that can be downloaded as a file.
+//!
+//! `?format=xlsx`, `pdf` or `json` wins; otherwise an `Accept` header naming the XLSX or PDF
+//! media type selects it. Anything else, including `Accept: */*`, gets JSON.
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::{header, request::Parts},
+};
+use serde::Deserialize;
+
+use crate::{
+    error::AppError,
+    utils::{pdf, xlsx},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Xlsx,
+    Pdf,
+}
+
+#[derive(Deserialize)]
+struct FormatQuery {
+    format: Option<String>,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ReportFormat {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let requested = Query::<FormatQuery>::try_from_uri(&parts.uri)
+            .ok()
+            .and_then(|Query(query)| query.format);
+        match requested.as_deref().map(str::to_lowercase).as_deref() {
+            Some("xlsx") => return Ok(ReportFormat::Xlsx),
+            Some("pdf") => return Ok(ReportFormat::Pdf),
+            Some("json") => return Ok(ReportFormat::Json),
+            Some(other) => {
+                return Err(AppError::Validation(format!(
+                    "Unsupported format '{}'; use json, xlsx or pdf",
+                    other
+                )))
+            }
+            None => {}
+        }
+
+        let accepted = parts
+            .headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .find_map(
+                |media_type| match media_type.split(';').next().unwrap_or_default().trim() {
+                    xlsx::CONTENT_TYPE => Some(ReportFormat::Xlsx),
+                    pdf::CONTENT_TYPE => Some(ReportFormat::Pdf),
+                    _ => None,
+                },
+            );
+        Ok(accepted.unwrap_or(ReportFormat::Json))
+    }
+}
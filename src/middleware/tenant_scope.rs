@@ -0,0 +1,33 @@
+//! Row-level security scope of a request.
+//!
+//! Tenant-scoped tables carry Postgres row-level security policies that only admit rows of
+//! the tenant in `app.tenant_id`, as a second line of defense behind the `tenant_id` filters
+//! in every query. This middleware resolves the request's `TenantContext` once and runs the
+//! rest of the request in that tenant's scope (see `db::with_tenant_scope`); the extractor
+//! then hands handlers the context resolved here. Resolving the context, and requests
+//! without a tenant, run across every tenant (see `db::without_tenant_scope`).
+
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{app_state::AppState, db, middleware::auth::TenantContext};
+
+/// Runs the request scoped to its tenant, when it has one.
+pub async fn scope_to_tenant(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let (mut parts, body) = req.into_parts();
+    // Routes that need a tenant reject the request in their extractor, with the same error
+    let context = db::without_tenant_scope(TenantContext::from_request_parts(&mut parts, &state))
+        .await
+        .ok();
+    let mut req = Request::from_parts(parts, body);
+    match context {
+        Some(context) => {
+            req.extensions_mut().insert(context);
+            db::with_tenant_scope(Some(context.tenant_id), next.run(req)).await
+        }
+        None => db::without_tenant_scope(next.run(req)).await,
+    }
+}
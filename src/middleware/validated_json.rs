@@ -0,0 +1,147 @@
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequest, OriginalUri, Request},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use serde_path_to_error::{Path, Segment};
+use tracing::warn;
+use validator::Validate;
+
+use crate::{
+    config,
+    error::{AppError, FieldError},
+};
+
+/// JSON request body that has passed its `Validate` rules.
+///
+/// Create and update handlers take `ValidatedJson(dto)` instead of `Json(dto)`: a body that
+/// does not parse is a 400, and one that breaks a rule is a 422 listing every invalid field.
+///
+/// Create and update DTOs deny unknown fields, so a misspelt field is not silently dropped.
+/// On the API versions in `STRICT_FIELDS_API_VERSIONS` every unrecognized field is listed in
+/// a 422; on other versions they are logged and ignored, as older clients expect.
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let uri_path = req.extensions().get::<OriginalUri>().map_or_else(
+            || req.uri().path().to_string(),
+            |uri| uri.path().to_string(),
+        );
+        let Json(mut body) = Json::<JsonValue>::from_request(req, state)
+            .await
+            .map_err(json_rejection)?;
+
+        // Each unknown field is taken out of the body and parsing retried, so all are found
+        let mut unknown_fields = Vec::new();
+        let value = loop {
+            match serde_path_to_error::deserialize::<_, T>(&body) {
+                Ok(value) => break value,
+                Err(error) => {
+                    let is_unknown = error.inner().to_string().starts_with("unknown field");
+                    if !is_unknown || !remove_field(&mut body, error.path()) {
+                        return Err(AppError::Validation(format!(
+                            "Failed to deserialize the JSON body into the target type: {}",
+                            error
+                        )));
+                    }
+                    unknown_fields.push(field_path(error.path()));
+                }
+            }
+        };
+
+        if !unknown_fields.is_empty() {
+            if rejects_unknown_fields(&uri_path) {
+                return Err(AppError::InvalidFields(
+                    unknown_fields
+                        .into_iter()
+                        .map(|field| FieldError {
+                            field,
+                            code: "unknown_field".to_string(),
+                            message: "Unrecognized field".to_string(),
+                        })
+                        .collect(),
+                ));
+            }
+            warn!(
+                "Ignoring unrecognized fields {} in request to {}",
+                unknown_fields.join(", "),
+                uri_path
+            );
+        }
+
+        value.validate()?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+/// Missing content type, malformed JSON or a body that does not fit the DTO.
+fn json_rejection(rejection: JsonRejection) -> AppError {
+    AppError::Validation(rejection.body_text())
+}
+
+/// Whether the API version in `/api/<version>/...` rejects unknown fields. Paths outside
+/// the versioned API always do.
+fn rejects_unknown_fields(uri_path: &str) -> bool {
+    match uri_path
+        .strip_prefix("/api/")
+        .and_then(|rest| rest.split('/').next())
+    {
+        Some(version) => config::get()
+            .server
+            .strict_fields_api_versions
+            .iter()
+            .any(|strict| strict.eq_ignore_ascii_case(version)),
+        None => true,
+    }
+}
+
+/// Removes the field at `path` from the body; false if there is nothing to remove there.
+fn remove_field(body: &mut JsonValue, path: &Path) -> bool {
+    let segments: Vec<&Segment> = path.iter().collect();
+    let Some((Segment::Map { key }, parents)) = segments.split_last() else {
+        return false;
+    };
+    let mut value = body;
+    for segment in parents {
+        let next = match segment {
+            Segment::Map { key } => value.get_mut(key.as_str()),
+            Segment::Seq { index } => value.get_mut(*index),
+            Segment::Enum { .. } | Segment::Unknown => None,
+        };
+        let Some(next) = next else {
+            return false;
+        };
+        value = next;
+    }
+    value
+        .as_object_mut()
+        .is_some_and(|object| object.remove(key.as_str()).is_some())
+}
+
+/// Dotted path like the validation errors use, e.g. `journal_entries[1].ammount`.
+fn field_path(path: &Path) -> String {
+    let mut field = String::new();
+    for segment in path.iter() {
+        match segment {
+            Segment::Map { key } if field.is_empty() => field.push_str(key),
+            Segment::Map { key } => {
+                field.push('.');
+                field.push_str(key);
+            }
+            Segment::Seq { index } => field.push_str(&format!("[{}]", index)),
+            Segment::Enum { variant } => field.push_str(variant),
+            Segment::Unknown => field.push('?'),
+        }
+    }
+    field
+}
@@ -12,9 +12,12 @@ pub struct Account {
     pub account_code: Option<String>, // Nullable
     pub description: Option<String>,  // Nullable
     pub currency_code: String,
+    pub is_sensitive: bool, // Amounts hidden from users without data.view_sensitive_accounts
     pub is_active: bool,
+    pub archived_at: Option<DateTime<Utc>>, // While set: hidden from pickers and new postings
     pub created_at: DateTime<Utc>,
     pub created_by: Uuid,
     pub updated_at: DateTime<Utc>,
     pub updated_by: Uuid,
+    pub version: i32, // Bumped by every change; updates send it back in If-Match
 }
@@ -3,11 +3,11 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, FromRow, Serialize, Deserialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct AccountType {
     pub id: Uuid,
     pub name: String,
-    pub normal_balance: String, // Consider an enum here: AccountNormalBalance
+    pub normal_balance: AccountNormalBalance,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub created_by: Uuid,
@@ -15,14 +15,14 @@ pub struct AccountType {
     pub updated_by: Uuid,
 }
 
-// Optional: Enum for normal_balance for better type safety
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+// Stored as the Postgres enum `entry_side`, shared with `JournalEntryType`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[sqlx(type_name = "entry_side")]
 pub enum AccountNormalBalance {
     DEBIT,
     CREDIT,
 }
 
-// Implementing From<AccountNormalBalance> for String and vice-versa for SQLx
 impl From<AccountNormalBalance> for String {
     fn from(balance: AccountNormalBalance) -> Self {
         match balance {
@@ -42,23 +42,3 @@ impl std::str::FromStr for AccountNormalBalance {
         }
     }
 }
-
-// This allows sqlx to decode from String and encode to String
-impl sqlx::Type<sqlx::Postgres> for AccountNormalBalance {
-    fn type_info() -> sqlx::postgres::PgTypeInfo {
-        <String as sqlx::Type<sqlx::Postgres>>::type_info()
-    }
-}
-
-impl<'r> sqlx::Decode<'r, sqlx::Postgres> for AccountNormalBalance {
-    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
-        let s = <String as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
-        s.parse().map_err(Into::into) // Convert String error to BoxDynError
-    }
-}
-
-impl<'q> sqlx::Encode<'q, sqlx::Postgres> for AccountNormalBalance {
-    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
-        <String as sqlx::Encode<sqlx::Postgres>>::encode_by_ref(&self.to_string(), buf)
-    }
-}
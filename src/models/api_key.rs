@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// An API key of a tenant. The key itself is only returned once, by `CreatedApiKey`.
+#[derive(Debug, Serialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid, // Requests made with the key act as this user
+    pub name: String,
+    pub key_prefix: String, // First characters of the key, to tell keys apart
+    pub is_sandbox: bool,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+/// A newly created API key. `key` is not stored and is returned only here.
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// The sandbox copy of a tenant that sandbox keys operate on.
+#[derive(Debug, Serialize)]
+pub struct TenantSandbox {
+    pub tenant_id: Uuid,
+    pub sandbox_tenant_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+/// What a request authenticated with an API key may act on.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyScope {
+    pub key_tenant_id: Uuid, // The tenant the key was created in
+    pub tenant_id: Uuid,     // The tenant requests operate on; the sandbox for sandbox keys
+    pub user_id: Uuid,
+}
@@ -0,0 +1,39 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::models::transaction::TransactionType;
+
+/// The analytics queries a question can be translated into. The provider only chooses one
+/// of these and its parameters; it never writes SQL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "query", rename_all = "snake_case")]
+pub enum AnalyticsQuery {
+    /// Posted transaction totals per category, optionally of one type and limited to the
+    /// named categories (and their subcategories).
+    CategoryTotals {
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        transaction_type: Option<TransactionType>,
+        #[serde(default)]
+        categories: Vec<String>,
+    },
+    /// Revenue, expenses and net income over a period.
+    IncomeStatement {
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+    },
+    /// Assets, liabilities and equity as of a date.
+    BalanceSheet { as_of: NaiveDate },
+    /// The question cannot be answered with the queries above.
+    Unsupported { reason: String },
+}
+
+/// The answer to a natural-language question, with the query that produced it.
+#[derive(Debug, Serialize)]
+pub struct AssistantAnswer {
+    pub question: String,
+    pub query: AnalyticsQuery, // What was actually executed
+    pub answer: String,
+    pub data: JsonValue, // The query's result rows or totals
+}
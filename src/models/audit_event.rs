@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub tenant_id: Option<Uuid>, // None for deployment-wide events
+    pub actor_user_id: Option<Uuid>,
+    pub action: String, // e.g., "transaction.post"
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub details: Option<JsonValue>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Why a session was ended before it expired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(
+    type_name = "session_revocation_reason",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SessionRevocationReason {
+    SignedOut,
+    TokenReuse, // A rotated refresh token was presented again
+}
+
+/// An active login session (one device or browser), as listed to its user.
+#[derive(Debug, Serialize)]
+pub struct AuthSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Tokens issued on login and on every refresh. The tokens are not stored and are returned
+/// only here; both stop working once the refresh token is exchanged.
+#[derive(Debug, Serialize)]
+pub struct SessionTokens {
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub access_token: String, // Sent as `Authorization: Bearer` on API requests
+    pub access_token_expires_at: DateTime<Utc>,
+    pub refresh_token: String,
+    pub expires_at: DateTime<Utc>,
+}
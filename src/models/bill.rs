@@ -0,0 +1,155 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Someone the tenant buys from and pays bills to.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Vendor {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub address: Option<String>,
+    pub tax_id: Option<String>,
+    pub payment_terms_days: Option<i32>, // Due date of new bills, in days after the bill date
+    pub notes: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Bill {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub vendor_id: Uuid,
+    pub bill_number: Option<String>, // The vendor's own number
+    pub status: String,              // Consider an enum here: BillStatus
+    pub bill_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub currency_code: String,
+    pub payable_account_id: Uuid,
+    pub total: Decimal,
+    pub amount_paid: Decimal,
+    pub approve_transaction_id: Option<Uuid>, // Posted when approved
+    pub notes: Option<String>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub approved_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct BillLine {
+    pub id: Uuid,
+    pub bill_id: Uuid,
+    pub tenant_id: Uuid,
+    pub position: i32,
+    pub description: String,
+    pub amount: Decimal,
+    pub expense_account_id: Uuid,
+}
+
+/// A payment made against a bill and the transaction that booked it.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct BillPayment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub bill_id: Uuid,
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    pub payment_account_id: Uuid,
+    pub transaction_id: Uuid,
+    pub payment_id: Option<Uuid>, // Set when made through the payments resource
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+/// A bill with its lines and payments.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BillDetail {
+    #[serde(flatten)]
+    pub bill: Bill,
+    pub lines: Vec<BillLine>,
+    pub payments: Vec<BillPayment>,
+}
+
+// Enum for the bill lifecycle.
+// DRAFT -> OPEN (approved) -> PARTIALLY_PAID -> PAID; only drafts can be edited or deleted.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BillStatus {
+    Draft,
+    Open,
+    PartiallyPaid,
+    Paid,
+}
+
+impl std::str::FromStr for BillStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DRAFT" => Ok(BillStatus::Draft),
+            "OPEN" => Ok(BillStatus::Open),
+            "PARTIALLY_PAID" => Ok(BillStatus::PartiallyPaid),
+            "PAID" => Ok(BillStatus::Paid),
+            _ => Err(format!("'{}' is not a valid BillStatus", s)),
+        }
+    }
+}
+
+impl From<BillStatus> for String {
+    fn from(status: BillStatus) -> Self {
+        match status {
+            BillStatus::Draft => "DRAFT".to_string(),
+            BillStatus::Open => "OPEN".to_string(),
+            BillStatus::PartiallyPaid => "PARTIALLY_PAID".to_string(),
+            BillStatus::Paid => "PAID".to_string(),
+        }
+    }
+}
+
+/// What is owed, split by how far past its due date it is.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AgingBuckets {
+    pub current: Decimal, // Not yet due
+    pub days_1_30: Decimal,
+    pub days_31_60: Decimal,
+    pub days_61_90: Decimal,
+    pub over_90: Decimal,
+    pub total: Decimal,
+}
+
+/// One vendor's unpaid bills in one currency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayablesAgingVendor {
+    pub vendor_id: Uuid,
+    pub vendor_name: String,
+    pub currency_code: String,
+    pub bill_count: i64,
+    #[serde(flatten)]
+    pub buckets: AgingBuckets,
+}
+
+/// Accounts payable aging as of a date; amounts are never converted between currencies.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayablesAging {
+    pub as_of: NaiveDate,
+    pub vendors: Vec<PayablesAgingVendor>,
+    pub totals: Vec<PayablesAgingTotal>,
+}
+
+/// Aging of all vendors together, in one currency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayablesAgingTotal {
+    pub currency_code: String,
+    #[serde(flatten)]
+    pub buckets: AgingBuckets,
+}
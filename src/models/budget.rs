@@ -0,0 +1,78 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Budget {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub currency_code: String,
+    pub is_envelope: bool, // Envelope (zero-based) mode: line items are envelopes funded from income
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+    pub version: i32, // Bumped by every change; updates send it back in If-Match
+}
+
+/// Budget vs actual for one month of a line item.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetMonthPerformance {
+    pub month: NaiveDate, // First day of the month
+    pub budgeted: Decimal,
+    pub actual: Decimal,
+    pub variance: Decimal, // budgeted - actual
+}
+
+/// Budget vs actual for one line item over the budget period.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetLinePerformance {
+    pub line_item_id: Uuid,
+    pub category_id: Option<Uuid>,
+    pub account_id: Option<Uuid>,
+    pub label: String,
+    pub is_seasonal: bool, // true when the line has a monthly schedule
+    pub budgeted: Decimal,
+    pub actual: Decimal,
+    pub variance: Decimal,
+    pub months: Vec<BudgetMonthPerformance>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetPerformance {
+    pub budget_id: Uuid,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub currency_code: String,
+    pub total_budgeted: Decimal,
+    pub total_actual: Decimal,
+    pub total_variance: Decimal,
+    pub lines: Vec<BudgetLinePerformance>,
+}
+
+/// A category with spending in the budget period but no line in the budget.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnbudgetedCategory {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub spent: Decimal, // Posted expenses in the budget currency, period start to as_of
+    pub transaction_count: i64,
+    pub suggested_amount: Decimal, // Average spending of the three periods before the budget
+}
+
+/// Budget health: categories with significant spending the budget does not cover.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetHealth {
+    pub budget_id: Uuid,
+    pub as_of: NaiveDate,
+    pub min_amount: Decimal, // Spending threshold a category had to reach
+    pub unbudgeted_spent: Decimal,
+    pub unbudgeted: Vec<UnbudgetedCategory>, // Largest spending first
+}
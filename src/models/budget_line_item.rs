@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct BudgetLineItem {
+    pub id: Uuid,
+    pub budget_id: Uuid,
+    pub category_id: Option<Uuid>, // Nullable; at least one of category/account is set
+    pub account_id: Option<Uuid>,  // Nullable
+    pub budgeted_amount: Decimal,  // NUMERIC(18,2), covers the whole budget period
+    pub monthly_amounts: Option<JsonValue>, // Nullable JSONB, {"YYYY-MM": amount}; sums to budgeted_amount
+    pub alert_thresholds: Vec<Decimal>, // Percent of budgeted_amount that raise an alert, ascending
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// A threshold a line item's spending crossed; recorded once per line and threshold.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct BudgetAlert {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub budget_id: Uuid,
+    pub budget_line_item_id: Uuid,
+    pub threshold_percent: Decimal,
+    pub budgeted_amount: Decimal, // When the threshold was crossed
+    pub actual_amount: Decimal,
+    pub notified_user_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
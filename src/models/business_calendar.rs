@@ -0,0 +1,27 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A tenant's business calendar settings. Until it is first edited, a tenant has a
+/// Saturday/Sunday weekend.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct BusinessCalendar {
+    pub tenant_id: Uuid,
+    pub country_code: Option<String>, // Country the holidays were last seeded from
+    pub weekend_days: Vec<i16>,       // ISO weekday numbers, 1 = Monday ... 7 = Sunday
+    pub updated_at: Option<DateTime<Utc>>, // None until first edited
+    pub updated_by: Option<Uuid>,
+}
+
+/// A non-business day of a tenant.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct BusinessHoliday {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub holiday_date: NaiveDate,
+    pub name: String,
+    pub country_code: Option<String>, // Set when seeded from a country's public holidays
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
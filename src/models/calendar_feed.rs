@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// A newly created calendar subscription. `token` is not stored and is returned only here.
+#[derive(Debug, Serialize)]
+pub struct CalendarFeedToken {
+    pub tenant_id: Uuid,
+    pub token: String,
+    pub feed_path: String, // e.g. /api/v1/tenants/:id/calendar.ics?token=...
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,42 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Bank and cash balances from the latest snapshot on or before the requested date,
+/// compared with the snapshot a week earlier.
+#[derive(Debug, Serialize)]
+pub struct CashPosition {
+    pub as_of: Option<NaiveDate>, // Date of the snapshot used; None before the first snapshot
+    pub compared_to: Option<NaiveDate>, // Date of the week-earlier snapshot, if any
+    pub currencies: Vec<CashPositionCurrency>,
+}
+
+/// Balances in one currency; totals are not converted.
+#[derive(Debug, Serialize)]
+pub struct CashPositionCurrency {
+    pub currency_code: String,
+    pub total: Decimal,
+    pub previous_total: Decimal,
+    pub total_movement: Decimal,
+    pub institutions: Vec<CashPositionInstitution>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CashPositionInstitution {
+    pub institution: String,
+    pub total: Decimal,
+    pub previous_total: Decimal,
+    pub total_movement: Decimal,
+    pub accounts: Vec<CashPositionAccount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CashPositionAccount {
+    pub account_id: Uuid,
+    pub account_code: Option<String>,
+    pub name: String,
+    pub balance: Decimal,
+    pub previous_balance: Option<Decimal>, // None if the account was not in the earlier snapshot
+    pub movement: Decimal,
+}
@@ -9,18 +9,44 @@ pub struct Category {
     pub tenant_id: Uuid,
     pub name: String,
     pub description: Option<String>,      // Nullable
-    pub r#type: String,                   // 'type' is a Rust keyword, so we use r#type
+    pub r#type: CategoryType,             // 'type' is a Rust keyword, so we use r#type
     pub parent_category_id: Option<Uuid>, // Nullable
     pub is_active: bool,
+    pub archived_at: Option<DateTime<Utc>>, // While set: hidden from pickers and new postings
     pub created_at: DateTime<Utc>,
     pub created_by: Uuid,
     pub updated_at: DateTime<Utc>,
     pub updated_by: Uuid,
 }
 
-// Optional: Enum for category_type for better type safety
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+/// An active category with its active subcategories, as returned by `GET /categories/tree`.
+#[derive(Debug, Serialize)]
+pub struct CategoryTreeNode {
+    #[serde(flatten)]
+    pub category: Category,
+    pub depth: i32, // 0 for top-level categories
+    pub children: Vec<CategoryTreeNode>,
+}
+
+/// What `POST /categories/:id/merge-into/:target` moved from the source to the target.
+#[derive(Debug, Serialize)]
+pub struct CategoryMergeResult {
+    pub source: Category, // Now inactive
+    pub target: Category,
+    pub transactions_moved: u64,
+    pub splits_moved: u64,
+    pub budget_line_items_moved: u64,
+    pub budget_line_items_folded: u64, // Added into the target's line item in the same budget
+    pub recurring_transactions_moved: u64,
+    pub merchant_rules_moved: u64,
+    pub subcategories_moved: u64,
+    pub custom_reports_updated: u64,
+}
+
+// Stored as the Postgres enum `category_type`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "category_type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum CategoryType {
     Income,
     Expense,
@@ -29,7 +55,6 @@ pub enum CategoryType {
     Other,
 }
 
-// Implement FromStr, sqlx::Type, Decode, Encode for CategoryType similarly to AccountNormalBalance
 impl std::str::FromStr for CategoryType {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -55,22 +80,3 @@ impl From<CategoryType> for String {
         }
     }
 }
-
-impl sqlx::Type<sqlx::Postgres> for CategoryType {
-    fn type_info() -> sqlx::postgres::PgTypeInfo {
-        <String as sqlx::Type<sqlx::Postgres>>::type_info()
-    }
-}
-
-impl<'r> sqlx::Decode<'r, sqlx::Postgres> for CategoryType {
-    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
-        let s = <String as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
-        s.parse().map_err(Into::into)
-    }
-}
-
-impl<'q> sqlx::Encode<'q, sqlx::Postgres> for CategoryType {
-    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
-        <String as sqlx::Encode<sqlx::Postgres>>::encode_by_ref(&self.to_string(), buf)
-    }
-}
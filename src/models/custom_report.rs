@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct CustomReport {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid, // Owner
+    pub name: String,
+    pub description: Option<String>,
+    pub report_type: String, // Consider an enum here: CustomReportType
+    pub configuration: serde_json::Value, // CustomReportConfiguration
+    pub is_public: bool,     // Visible to every member of the tenant
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Enum for custom report types for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CustomReportType {
+    TransactionList,        // Posted transactions in the period
+    SummaryByCategory,      // Posted transaction totals per category
+    AccountBalanceSummary,  // Trial balance at the end of the period
+    IncomeExpenseStatement, // Profit and loss over the period
+    BudgetVsActual,         // Budget performance; needs configuration.budget_id
+    BalanceSheet,           // Assets, liabilities and equity at the end of the period
+}
+
+impl std::str::FromStr for CustomReportType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "TRANSACTION_LIST" => Ok(CustomReportType::TransactionList),
+            "SUMMARY_BY_CATEGORY" => Ok(CustomReportType::SummaryByCategory),
+            "ACCOUNT_BALANCE_SUMMARY" => Ok(CustomReportType::AccountBalanceSummary),
+            "INCOME_EXPENSE_STATEMENT" => Ok(CustomReportType::IncomeExpenseStatement),
+            "BUDGET_VS_ACTUAL" => Ok(CustomReportType::BudgetVsActual),
+            "BALANCE_SHEET" => Ok(CustomReportType::BalanceSheet),
+            _ => Err(format!("'{}' is not a valid CustomReportType", s)),
+        }
+    }
+}
+
+impl From<CustomReportType> for String {
+    fn from(report_type: CustomReportType) -> Self {
+        match report_type {
+            CustomReportType::TransactionList => "TRANSACTION_LIST".to_string(),
+            CustomReportType::SummaryByCategory => "SUMMARY_BY_CATEGORY".to_string(),
+            CustomReportType::AccountBalanceSummary => "ACCOUNT_BALANCE_SUMMARY".to_string(),
+            CustomReportType::IncomeExpenseStatement => "INCOME_EXPENSE_STATEMENT".to_string(),
+            CustomReportType::BudgetVsActual => "BUDGET_VS_ACTUAL".to_string(),
+            CustomReportType::BalanceSheet => "BALANCE_SHEET".to_string(),
+        }
+    }
+}
+
+/// Parameters stored in `custom_reports.configuration`. Every field is optional; which
+/// ones apply depends on the report type.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CustomReportConfiguration {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout_id: Option<Uuid>, // Statement layout for ACCOUNT_BALANCE_SUMMARY / INCOME_EXPENSE_STATEMENT / BALANCE_SHEET
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_id: Option<Uuid>, // Required for BUDGET_VS_ACTUAL
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub account_ids: Vec<Uuid>, // Narrows TRANSACTION_LIST to these accounts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub category_ids: Vec<Uuid>, // Narrows TRANSACTION_LIST / SUMMARY_BY_CATEGORY to these categories
+}
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::dashboard_widget::WidgetData;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid, // Owner; dashboards are private to their owner
+    pub name: String,
+    pub description: Option<String>,
+    pub is_default: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// A dashboard with the evaluated data of each of its widgets, in display order.
+#[derive(Debug, Serialize)]
+pub struct DashboardData {
+    pub dashboard: Dashboard,
+    pub widgets: Vec<WidgetData>,
+}
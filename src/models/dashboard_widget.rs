@@ -0,0 +1,167 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DashboardWidget {
+    pub id: Uuid,
+    pub dashboard_id: Uuid,
+    pub widget_type: String, // Consider an enum here: WidgetType
+    pub title: String,
+    pub order_index: i32,
+    pub parameters: Option<JsonValue>, // JSONB, query spec; shape depends on widget_type
+    pub properties: Option<JsonValue>, // JSONB, layout/presentation only, not interpreted
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Enum for widget types for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetType {
+    CashBalanceSummary,
+    CashBalanceTrend,
+    SpendingByCategory,
+    IncomeVsExpenseSummary,
+    AccountBalanceList,
+    BudgetBurn,
+    CustomReportLink,
+}
+
+impl std::str::FromStr for WidgetType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cash_balance_summary" => Ok(WidgetType::CashBalanceSummary),
+            "cash_balance_trend" => Ok(WidgetType::CashBalanceTrend),
+            "spending_by_category" => Ok(WidgetType::SpendingByCategory),
+            "income_vs_expense_summary" => Ok(WidgetType::IncomeVsExpenseSummary),
+            "account_balance_list" => Ok(WidgetType::AccountBalanceList),
+            "budget_burn" => Ok(WidgetType::BudgetBurn),
+            "custom_report_link" => Ok(WidgetType::CustomReportLink),
+            _ => Err(format!("'{}' is not a valid WidgetType", s)),
+        }
+    }
+}
+
+impl From<WidgetType> for String {
+    fn from(widget_type: WidgetType) -> Self {
+        match widget_type {
+            WidgetType::CashBalanceSummary => "cash_balance_summary".to_string(),
+            WidgetType::CashBalanceTrend => "cash_balance_trend".to_string(),
+            WidgetType::SpendingByCategory => "spending_by_category".to_string(),
+            WidgetType::IncomeVsExpenseSummary => "income_vs_expense_summary".to_string(),
+            WidgetType::AccountBalanceList => "account_balance_list".to_string(),
+            WidgetType::BudgetBurn => "budget_burn".to_string(),
+            WidgetType::CustomReportLink => "custom_report_link".to_string(),
+        }
+    }
+}
+
+// --- Query specs (`dashboard_widgets.parameters`) ---
+
+/// Parameters of `spending_by_category`. Defaults to the current month, top 10 categories.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpendingByCategoryParams {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+/// Parameters of `cash_balance_summary` and `cash_balance_trend`. Without `account_ids`,
+/// all active asset accounts are included. `months` (trend only) defaults to 6.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CashBalanceParams {
+    #[serde(default)]
+    pub account_ids: Vec<Uuid>,
+    pub months: Option<u32>,
+}
+
+/// Parameters of `income_vs_expense_summary`. Defaults to the current month.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IncomeVsExpenseParams {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
+
+/// Parameters of `account_balance_list`. Without `account_ids`, all active accounts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountBalanceListParams {
+    #[serde(default)]
+    pub account_ids: Vec<Uuid>,
+    pub as_of: Option<NaiveDate>, // Defaults to today
+}
+
+/// Parameters of `budget_burn`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BudgetBurnParams {
+    pub budget_id: Uuid,
+}
+
+// --- Evaluated widget data ---
+
+/// One widget's evaluated data. A widget that fails to evaluate carries an `error`
+/// instead of failing the whole dashboard.
+#[derive(Debug, Serialize)]
+pub struct WidgetData {
+    pub widget: DashboardWidget,
+    pub data: Option<JsonValue>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategorySpend {
+    pub category_id: Option<Uuid>, // None for uncategorised spending
+    pub category_name: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountBalanceItem {
+    pub account_id: Uuid,
+    pub account_code: Option<String>,
+    pub name: String,
+    pub balance: Decimal, // Signed by the account's normal balance
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceSummary {
+    pub as_of: NaiveDate,
+    pub total: Decimal,
+    pub accounts: Vec<AccountBalanceItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceTrendPoint {
+    pub month_end: NaiveDate, // Last day of the month, or today for the current month
+    pub balance: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IncomeVsExpense {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub income: Decimal,
+    pub expense: Decimal,
+    pub net: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BudgetBurn {
+    pub budget_id: Uuid,
+    pub name: String,
+    pub total_budgeted: Decimal,
+    pub total_actual: Decimal,
+    pub burn_pct: Option<Decimal>, // Actual as % of budget; None for a zero budget
+    pub elapsed_pct: Decimal,      // Share of the budget period that has passed
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A project, class or location journal entries can be tagged with for reporting.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Dimension {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: String, // Consider an enum here: DimensionKind
+    pub name: String, // Unique per tenant and kind, ignoring case
+    pub code: Option<String>,
+    pub description: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// The dimensions of a journal entry, or those a report is restricted to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DimensionFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<Uuid>,
+}
+
+impl DimensionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.project_id.is_none() && self.class_id.is_none() && self.location_id.is_none()
+    }
+
+    /// The dimensions set, with the kind each must be.
+    pub fn assigned(&self) -> Vec<(Uuid, DimensionKind)> {
+        [
+            (self.project_id, DimensionKind::Project),
+            (self.class_id, DimensionKind::Class),
+            (self.location_id, DimensionKind::Location),
+        ]
+        .into_iter()
+        .filter_map(|(id, kind)| id.map(|id| (id, kind)))
+        .collect()
+    }
+}
+
+// Enum for what a dimension stands for
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DimensionKind {
+    Project,
+    Class,    // e.g. a department or business line
+    Location, // e.g. a store or office
+}
+
+impl std::str::FromStr for DimensionKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PROJECT" => Ok(DimensionKind::Project),
+            "CLASS" => Ok(DimensionKind::Class),
+            "LOCATION" => Ok(DimensionKind::Location),
+            _ => Err(format!("'{}' is not a valid DimensionKind", s)),
+        }
+    }
+}
+
+impl From<DimensionKind> for String {
+    fn from(kind: DimensionKind) -> Self {
+        match kind {
+            DimensionKind::Project => "PROJECT".to_string(),
+            DimensionKind::Class => "CLASS".to_string(),
+            DimensionKind::Location => "LOCATION".to_string(),
+        }
+    }
+}
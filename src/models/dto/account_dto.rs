@@ -1,9 +1,11 @@
+use crate::utils::patch::Patch;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
 // DTO for creating a new Account
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateAccountDto {
     pub account_type_id: Uuid,
     #[validate(length(min = 1, max = 255))]
@@ -13,20 +15,32 @@ pub struct CreateAccountDto {
     pub description: Option<String>,
     #[validate(length(equal = 3))]
     pub currency_code: String,
+    pub is_sensitive: Option<bool>, // e.g., payroll/salary accounts; defaults to false
     // tenant_id and created_by will be derived from context
 }
 
+// Query parameters for deactivating an Account
+#[derive(Debug, Deserialize)]
+pub struct DeactivateAccountQuery {
+    #[serde(default)]
+    pub cascade: bool, // Also deactivate its budget lines and recurring transactions instead of refusing
+}
+
 // DTO for updating an existing Account
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateAccountDto {
     pub account_type_id: Option<Uuid>,
     #[validate(length(min = 1, max = 255))]
     pub name: Option<String>,
+    #[serde(default)]
     #[validate(length(max = 50))]
-    pub account_code: Option<String>,
-    pub description: Option<String>,
+    pub account_code: Patch<String>, // null clears it
+    #[serde(default)]
+    pub description: Patch<String>,
     #[validate(length(equal = 3))]
     pub currency_code: Option<String>,
+    pub is_sensitive: Option<bool>,
     pub is_active: Option<bool>,
     // updated_by will be derived from context
 }
@@ -4,6 +4,7 @@ use validator::Validate; // Import the enum
 
 // DTO for creating a new AccountType
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateAccountTypeDto {
     #[validate(length(min = 1, max = 100))]
     pub name: String,
@@ -13,6 +14,7 @@ pub struct CreateAccountTypeDto {
 
 // DTO for updating an existing AccountType
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateAccountTypeDto {
     #[validate(length(min = 1, max = 100))]
     pub name: Option<String>,
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for creating an API key
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateApiKeyDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    pub is_sandbox: Option<bool>, // Defaults to false; sandbox keys operate on the tenant's sandbox
+}
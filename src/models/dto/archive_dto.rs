@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// Query parameters for listings that hide archived accounts or categories by default
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct IncludeArchivedQuery {
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+// Query parameters for listings that hide deactivated records by default; needs records.restore
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct IncludeInactiveQuery {
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+// DTO for archiving accounts or categories that have not been used for a while
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ArchiveUnusedDto {
+    #[validate(range(min = 1, max = 240))]
+    pub unused_for_months: u32, // Created before, and no postings since, this many months ago
+    #[serde(default)]
+    pub dry_run: bool, // Only list what would be archived
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+use validator::Validate;
+
+// DTO for asking a natural-language question about the tenant's books
+#[derive(Debug, Deserialize, Validate)]
+pub struct AskQuestionDto {
+    #[validate(length(min = 3, max = 500))]
+    pub question: String,
+}
@@ -1 +1,27 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
 
+// DTO for signing in with email and password
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct LoginRequest {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 1, max = 1024))]
+    pub password: String,
+}
+
+// DTO for exchanging a refresh token for a new one
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct RefreshSessionRequest {
+    #[validate(length(min = 1, max = 256))]
+    pub refresh_token: String,
+}
+
+// Query of the redirect back from an identity provider
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>, // Set instead of `code` when the sign-in was refused
+    pub error_description: Option<String>,
+}
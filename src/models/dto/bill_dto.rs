@@ -0,0 +1,116 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::bill::BillStatus;
+
+// DTO for creating a new Vendor
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateVendorDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(email, length(max = 255))]
+    pub email: Option<String>,
+    #[validate(length(max = 2000))]
+    pub address: Option<String>,
+    #[validate(length(max = 50))]
+    pub tax_id: Option<String>,
+    #[validate(range(min = 0, max = 365))]
+    pub payment_terms_days: Option<i32>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    // tenant_id and created_by will be derived from context
+}
+
+// DTO for updating an existing Vendor
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateVendorDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    #[validate(email, length(max = 255))]
+    pub email: Option<String>,
+    #[validate(length(max = 2000))]
+    pub address: Option<String>,
+    #[validate(length(max = 50))]
+    pub tax_id: Option<String>,
+    #[validate(range(min = 0, max = 365))]
+    pub payment_terms_days: Option<i32>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    // updated_by will be derived from context
+}
+
+// DTO for entering a draft Bill
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateBillDto {
+    pub vendor_id: Uuid,
+    #[validate(length(min = 1, max = 100))]
+    pub bill_number: Option<String>, // The vendor's own number
+    pub bill_date: NaiveDate,
+    pub due_date: Option<NaiveDate>, // Defaults to the vendor's payment terms, else the bill date
+    #[validate(length(equal = 3))]
+    pub currency_code: String,
+    pub payable_account_id: Uuid, // Accounts payable, credited when approved
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    #[validate(length(min = 1, max = 200), nested)]
+    pub lines: Vec<BillLineDto>,
+}
+
+// One line of a bill, in the bill's currency
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct BillLineDto {
+    #[validate(length(min = 1, max = 1000))]
+    pub description: String,
+    pub amount: Decimal,          // Zero or more, at most two decimals
+    pub expense_account_id: Uuid, // Debited when approved
+}
+
+// DTO for updating a draft Bill; `lines`, when given, replaces all lines
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateBillDto {
+    pub vendor_id: Option<Uuid>,
+    #[validate(length(min = 1, max = 100))]
+    pub bill_number: Option<String>,
+    pub bill_date: Option<NaiveDate>,
+    pub due_date: Option<NaiveDate>,
+    #[validate(length(equal = 3))]
+    pub currency_code: Option<String>,
+    pub payable_account_id: Option<Uuid>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    #[validate(length(min = 1, max = 200), nested)]
+    pub lines: Option<Vec<BillLineDto>>,
+}
+
+// Query parameters for listing bills
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListBillsQuery {
+    pub status: Option<BillStatus>,
+    pub vendor_id: Option<Uuid>,
+    pub due_before: Option<NaiveDate>, // Only unpaid bills due on or before this date
+}
+
+// DTO for recording a payment made against an approved bill
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RecordBillPaymentDto {
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,          // At most what is still owed
+    pub payment_account_id: Uuid, // The bank, cash or card account paid from
+    #[validate(length(max = 1000))]
+    pub memo: Option<String>,
+}
+
+// Query parameters for the payables aging report
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PayablesAgingQuery {
+    pub as_of: Option<NaiveDate>, // Defaults to today
+}
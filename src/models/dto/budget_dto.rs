@@ -0,0 +1,71 @@
+use crate::models::budget::Budget;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for creating a new Budget
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateBudgetDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    #[validate(length(equal = 3))]
+    pub currency_code: String,
+    pub is_envelope: Option<bool>, // Envelope (zero-based) mode; defaults to false
+                                   // tenant_id and created_by will be derived from context
+}
+
+// DTO for updating an existing Budget
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateBudgetDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    #[validate(length(equal = 3))]
+    pub currency_code: Option<String>,
+    pub is_envelope: Option<bool>,
+    pub is_active: Option<bool>,
+    // updated_by will be derived from context
+}
+
+// Query parameters for the budget health check
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BudgetHealthQuery {
+    pub as_of: Option<NaiveDate>, // Defaults to today, capped at the end of the budget period
+    pub min_amount: Option<Decimal>, // Defaults to 2% of the period's categorised spending
+}
+
+// DTO for adding line items for unbudgeted categories at their suggested amounts
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct AddSuggestedLinesDto {
+    #[validate(length(min = 1, max = 100))]
+    pub category_ids: Vec<Uuid>,
+}
+
+// Query parameters for importing a budget from CSV
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BudgetImportQuery {
+    pub name: Option<String>, // Overrides the budget_name column, e.g. when re-importing an export
+}
+
+// A problem with one CSV row; row 1 is the first data row after the header
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CsvRowError {
+    pub row: usize,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+// Result of a budget CSV import. Nothing is written when any row has errors.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BudgetImportResult {
+    pub budget: Option<Budget>,
+    pub rows_imported: usize,
+    pub errors: Vec<CsvRowError>,
+}
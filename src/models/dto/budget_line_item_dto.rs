@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for creating a new BudgetLineItem
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateBudgetLineItemDto {
+    pub category_id: Option<Uuid>, // At least one of category_id/account_id is required
+    pub account_id: Option<Uuid>,
+    pub budgeted_amount: Option<Decimal>, // Required unless monthly_amounts is given
+    // Optional seasonal schedule keyed by month ("2025-01"); months must fall in the budget period
+    pub monthly_amounts: Option<BTreeMap<String, Decimal>>,
+    // Percent of the budgeted amount that raise an alert, e.g. [80, 100]
+    #[validate(length(max = 10))]
+    pub alert_thresholds: Option<Vec<Decimal>>,
+    // budget_id comes from the path; created_by will be derived from context
+}
+
+// DTO for updating an existing BudgetLineItem
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateBudgetLineItemDto {
+    pub category_id: Option<Uuid>,
+    pub account_id: Option<Uuid>,
+    pub budgeted_amount: Option<Decimal>,
+    // Replaces the schedule; budgeted_amount is then derived from it
+    pub monthly_amounts: Option<BTreeMap<String, Decimal>>,
+    // Replaces the thresholds; an empty list turns alerts off
+    #[validate(length(max = 10))]
+    pub alert_thresholds: Option<Vec<Decimal>>,
+    pub is_active: Option<bool>,
+    // updated_by will be derived from context
+}
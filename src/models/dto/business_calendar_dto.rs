@@ -0,0 +1,35 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for updating the tenant's business calendar
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateBusinessCalendarDto {
+    #[validate(length(max = 6))]
+    pub weekend_days: Vec<i16>, // ISO weekday numbers, 1 = Monday ... 7 = Sunday
+}
+
+// DTO for adding a holiday by hand
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateBusinessHolidayDto {
+    pub holiday_date: NaiveDate,
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+}
+
+// DTO for seeding a year of a country's public holidays
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct SeedBusinessHolidaysDto {
+    #[validate(length(equal = 2))]
+    pub country_code: String, // ISO 3166-1 alpha-2, e.g. "US"
+    #[validate(range(min = 1900, max = 2200))]
+    pub year: i32,
+}
+
+// Query parameters for listing holidays
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BusinessHolidaysQuery {
+    pub year: Option<i32>, // Defaults to every year
+}
@@ -0,0 +1,8 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+// Query parameters for the cash position
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CashPositionQuery {
+    pub as_of: Option<NaiveDate>, // Defaults to today
+}
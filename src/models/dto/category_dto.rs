@@ -1,10 +1,12 @@
 use crate::models::category::CategoryType;
+use crate::utils::patch::Patch;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate; // Import the enum
 
 // DTO for creating a new Category
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateCategoryDto {
     #[validate(length(min = 1, max = 255))]
     pub name: String,
@@ -16,12 +18,22 @@ pub struct CreateCategoryDto {
 
 // DTO for updating an existing Category
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateCategoryDto {
     #[validate(length(min = 1, max = 255))]
     pub name: Option<String>,
-    pub description: Option<String>,
-    pub r#type: Option<CategoryType>,     // Use the enum
-    pub parent_category_id: Option<Uuid>, // Nullable, can be updated
+    #[serde(default)]
+    pub description: Patch<String>,
+    pub r#type: Option<CategoryType>, // Use the enum
+    #[serde(default)]
+    pub parent_category_id: Patch<Uuid>, // null makes it a top-level category
     pub is_active: Option<bool>,
     // updated_by will be derived from context
 }
+
+// Query parameters for deactivating a Category
+#[derive(Debug, Deserialize)]
+pub struct DeactivateCategoryQuery {
+    #[serde(default)]
+    pub cascade: bool, // Also deactivate all subcategories and what depends on them instead of refusing
+}
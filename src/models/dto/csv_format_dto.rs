@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+// Query parameters shared by every CSV export (and the budget import)
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CsvFormatQuery {
+    pub delimiter: Option<String>, // "," (default), ";", "tab" or "|"; ";" when decimal_comma is set
+    pub decimal_comma: Option<bool>, // Write 1234,56 instead of 1234.56
+    pub date_format: Option<String>, // "iso" (default), "us", "eu", "uk" or a strftime pattern like "%d/%m/%Y"
+    pub bom: Option<bool>,           // Prefix a UTF-8 byte order mark so Excel detects the encoding
+}
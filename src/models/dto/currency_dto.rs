@@ -3,6 +3,7 @@ use validator::Validate;
 
 // DTO for creating a new Currency
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateCurrencyDto {
     #[validate(length(equal = 3))] // ISO 4217 code, e.g., 'USD'
     pub code: String,
@@ -15,6 +16,7 @@ pub struct CreateCurrencyDto {
 
 // DTO for updating an existing Currency
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateCurrencyDto {
     #[validate(length(min = 1, max = 100))]
     pub name: Option<String>,
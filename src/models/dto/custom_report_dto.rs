@@ -0,0 +1,37 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::custom_report::{CustomReportConfiguration, CustomReportType};
+
+// DTO for creating a new CustomReport
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateCustomReportDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String, // Unique per tenant
+    pub description: Option<String>,
+    pub report_type: CustomReportType,
+    #[serde(default)]
+    pub configuration: CustomReportConfiguration,
+    pub is_public: Option<bool>, // Defaults to false
+                                 // tenant_id, user_id and created_by will be derived from context
+}
+
+// DTO for updating an existing CustomReport
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateCustomReportDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub configuration: Option<CustomReportConfiguration>, // Replaces the whole configuration
+    pub is_public: Option<bool>,
+}
+
+// Query parameters for downloading a custom report as CSV
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExportCustomReportQuery {
+    pub from_date: Option<NaiveDate>, // Defaults to the start of the current month
+    pub to_date: Option<NaiveDate>,   // Defaults to today
+}
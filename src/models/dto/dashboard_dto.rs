@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for creating a new Dashboard
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateDashboardDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String, // Unique per user
+    pub description: Option<String>,
+    pub is_default: Option<bool>,
+    // tenant_id, user_id and created_by will be derived from context
+}
+
+// DTO for updating an existing Dashboard
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateDashboardDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub is_default: Option<bool>,
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use validator::Validate;
+
+use crate::models::dashboard_widget::WidgetType;
+
+// DTO for adding a widget to a dashboard
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateDashboardWidgetDto {
+    pub widget_type: WidgetType,
+    #[validate(length(min = 1, max = 255))]
+    pub title: String,
+    pub order_index: Option<i32>, // Defaults to after the last widget
+    pub parameters: Option<JsonValue>, // Query spec; checked against widget_type
+    pub properties: Option<JsonValue>, // Layout/presentation, stored as-is
+}
+
+// DTO for updating a widget; the type is fixed once created
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateDashboardWidgetDto {
+    #[validate(length(min = 1, max = 255))]
+    pub title: Option<String>,
+    pub order_index: Option<i32>,
+    pub parameters: Option<JsonValue>,
+    pub properties: Option<JsonValue>,
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::dimension::DimensionKind;
+
+// DTO for creating a dimension
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateDimensionDto {
+    pub kind: DimensionKind,
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(length(min = 1, max = 50))]
+    pub code: Option<String>,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    // tenant_id and created_by will be derived from context
+}
+
+// DTO for updating a dimension; its kind is fixed
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateDimensionDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    #[validate(length(min = 1, max = 50))]
+    pub code: Option<String>,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    // updated_by will be derived from context
+}
+
+// Query parameters for listing dimensions
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListDimensionsQuery {
+    pub kind: Option<DimensionKind>,
+    #[serde(default)]
+    pub include_inactive: bool,
+}
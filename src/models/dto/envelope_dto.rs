@@ -0,0 +1,21 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for moving money between two envelopes of a budget
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct MoveEnvelopeMoneyDto {
+    pub from_line_item_id: Uuid,
+    pub to_line_item_id: Uuid,
+    pub amount: Decimal, // Positive, at most what the source envelope has available
+    pub moved_on: Option<NaiveDate>, // Within the budget period; defaults to today
+    pub memo: Option<String>,
+}
+
+// Query parameters for envelope balances
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EnvelopeSummaryQuery {
+    pub as_of: Option<NaiveDate>, // Defaults to today, capped at the end of the budget period
+}
@@ -0,0 +1,37 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for creating a new Event
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateEventDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    // tenant_id and created_by will be derived from context
+}
+
+// DTO for updating an existing Event
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateEventDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    // updated_by will be derived from context
+}
+
+// DTO for assigning transactions to an event; a transaction already in another event is moved
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct AssignEventTransactionsDto {
+    #[validate(length(min = 1, max = 500))]
+    pub transaction_ids: Vec<Uuid>,
+}
@@ -4,8 +4,11 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::utils::validation::positive_amount;
+
 // DTO for creating a new ExchangeRate
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateExchangeRateDto {
     pub tenant_id: Option<Uuid>, // Nullable, only provided if tenant-specific rate
 
@@ -15,7 +18,7 @@ pub struct CreateExchangeRateDto {
     #[validate(length(equal = 3))]
     pub target_currency_code: String,
 
-    #[validate(range(min = 0.000001))] // Rate must be greater than 0
+    #[validate(custom(function = "positive_amount"))] // Rate must be greater than 0
     pub rate: Decimal,
 
     pub rate_date: NaiveDate,
@@ -26,8 +29,9 @@ pub struct CreateExchangeRateDto {
 
 // DTO for updating an existing ExchangeRate
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateExchangeRateDto {
-    #[validate(range(min = 0.000001))]
+    #[validate(custom(function = "positive_amount"))]
     pub rate: Option<Decimal>,
 
     pub rate_date: Option<NaiveDate>,
@@ -35,3 +39,19 @@ pub struct UpdateExchangeRateDto {
     #[validate(length(max = 100))]
     pub source: Option<String>,
 }
+
+// DTO for opting the tenant in or out of automatic rate fetching
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateExchangeRateFetchSettingsDto {
+    pub is_enabled: bool,
+}
+
+// Query parameters for converting an amount between currencies
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConvertCurrencyQuery {
+    pub amount: Decimal,
+    pub from: String,
+    pub to: String,
+    pub date: Option<NaiveDate>, // Defaults to today
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for creating a new ExtConn (linking a bank/provider item)
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateExtConnDto {
+    pub provider_id: Uuid,
+    // Short-lived token from the provider's link flow (e.g., Plaid Link public_token),
+    // exchanged server-side for a long-lived access token.
+    #[validate(length(min = 1))]
+    pub public_token: String,
+    pub metadata: Option<JsonValue>, // Optional client-side link metadata (institution, etc.)
+                                     // tenant_id, user_id and created_by will be derived from context
+}
+
+// DTO for updating an existing ExtConn
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateExtConnDto {
+    // Replacement public token after a re-authentication flow
+    #[validate(length(min = 1))]
+    pub public_token: Option<String>,
+    pub metadata: Option<JsonValue>,
+    // updated_by will be derived from context
+}
+
+// Summary returned after syncing a connection
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct ExtConnSyncSummary {
+    pub ext_conn_id: Uuid,
+    pub accounts_synced: usize,
+    pub transactions_staged: usize,
+    pub transactions_removed: usize,
+}
@@ -0,0 +1,34 @@
+use crate::models::ext_provider::ExtProviderType;
+use serde::{Deserialize, Serialize};
+use validator::Validate; // Import the enum
+
+// DTO for creating a new ExtProvider
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateExtProviderDto {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1, max = 50))]
+    pub code: String, // e.g., 'PLAID'; selects the connector implementation
+    pub r#type: ExtProviderType, // Use the enum
+    pub description: Option<String>,
+    #[validate(url)]
+    pub logo_url: Option<String>,
+    #[validate(url)]
+    pub api_base_url: Option<String>,
+    // created_by will be system user
+}
+
+// DTO for updating an existing ExtProvider
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateExtProviderDto {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[validate(url)]
+    pub logo_url: Option<String>,
+    pub is_active: Option<bool>,
+    // api_base_url is fixed at registration
+    // updated_by will be system user
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for updating an existing ExternalAccount (external accounts are created by sync)
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateExternalAccountDto {
+    pub account_id: Option<Uuid>, // Ledger account the bank feed posts into
+    pub is_active: Option<bool>,  // Inactive accounts are skipped during sync
+                                  // updated_by will be derived from context
+}
@@ -0,0 +1,14 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for creating a new FiscalPeriod
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateFiscalPeriodDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String, // e.g., "FY2025 Q1"
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate, // Inclusive; periods of a tenant cannot overlap
+                             // tenant_id and created_by will be derived from context
+}
@@ -0,0 +1,77 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::fixed_asset::{DepreciationMethod, FixedAssetStatus};
+
+// DTO for registering a fixed asset
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateFixedAssetDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(length(min = 1, max = 100))]
+    pub asset_tag: Option<String>,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    pub acquisition_date: NaiveDate,
+    pub in_service_date: Option<NaiveDate>, // Defaults to the acquisition date
+    pub cost: Decimal,
+    pub salvage_value: Option<Decimal>, // Defaults to zero
+    #[validate(range(min = 1, max = 1200))]
+    pub useful_life_months: i32,
+    pub method: DepreciationMethod,
+    pub declining_factor: Option<Decimal>, // Declining balance only; defaults to 2
+    #[validate(length(equal = 3))]
+    pub currency_code: String,
+    pub asset_account_id: Uuid,
+    pub accumulated_depreciation_account_id: Uuid, // Credited each month
+    pub depreciation_expense_account_id: Uuid,     // Debited each month
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    // tenant_id and created_by will be derived from context
+}
+
+// DTO for updating a fixed asset; what drives depreciation can only change until the
+// first month is posted
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateFixedAssetDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub asset_tag: Option<String>,
+    #[validate(length(max = 2000))]
+    pub description: Option<String>,
+    pub acquisition_date: Option<NaiveDate>,
+    pub in_service_date: Option<NaiveDate>,
+    pub cost: Option<Decimal>,
+    pub salvage_value: Option<Decimal>,
+    #[validate(range(min = 1, max = 1200))]
+    pub useful_life_months: Option<i32>,
+    pub method: Option<DepreciationMethod>,
+    pub declining_factor: Option<Decimal>,
+    #[validate(length(equal = 3))]
+    pub currency_code: Option<String>,
+    pub asset_account_id: Option<Uuid>,
+    pub accumulated_depreciation_account_id: Option<Uuid>,
+    pub depreciation_expense_account_id: Option<Uuid>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    // updated_by will be derived from context
+}
+
+// Query parameters for listing fixed assets
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListFixedAssetsQuery {
+    pub status: Option<FixedAssetStatus>,
+}
+
+// Query parameters for the depreciation schedule report
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DepreciationReportQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
@@ -0,0 +1,20 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for choosing the gain and loss accounts
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertFxRevaluationSettingsDto {
+    pub unrealized_gain_account_id: Uuid,
+    pub unrealized_loss_account_id: Uuid, // May be the same account as the gain account
+}
+
+// DTO for running a revaluation; give either a date or a fiscal period (its end date is used)
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct RunFxRevaluationDto {
+    pub as_of: Option<NaiveDate>,
+    pub fiscal_period_id: Option<Uuid>,
+    pub dry_run: Option<bool>, // Defaults to false; true only computes the adjustments
+}
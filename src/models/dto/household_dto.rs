@@ -0,0 +1,20 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for attributing a transaction to a member of the tenant
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct SetTransactionAttributionDto {
+    pub member_id: Uuid,
+    #[serde(default)]
+    pub is_shared: bool, // Paid by member_id on behalf of the household
+}
+
+// Query parameters for the household spending and settle-up reports
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HouseholdPeriodQuery {
+    pub from_date: Option<NaiveDate>, // Defaults to the start of the current month
+    pub to_date: Option<NaiveDate>,   // Defaults to today
+    pub member_ids: Option<String>, // Comma-separated; who shares expenses (defaults to the tenant's active users)
+}
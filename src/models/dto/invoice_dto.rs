@@ -0,0 +1,115 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::invoice::InvoiceStatus;
+
+// DTO for creating a new Customer
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateCustomerDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(email, length(max = 255))]
+    pub email: Option<String>,
+    #[validate(length(max = 2000))]
+    pub billing_address: Option<String>,
+    #[validate(length(max = 50))]
+    pub tax_id: Option<String>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    // tenant_id and created_by will be derived from context
+}
+
+// DTO for updating an existing Customer
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateCustomerDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    #[validate(email, length(max = 255))]
+    pub email: Option<String>,
+    #[validate(length(max = 2000))]
+    pub billing_address: Option<String>,
+    #[validate(length(max = 50))]
+    pub tax_id: Option<String>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    // updated_by will be derived from context
+}
+
+// DTO for creating a draft Invoice; the number is assigned when it is issued
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateInvoiceDto {
+    pub customer_id: Uuid,
+    pub issue_date: NaiveDate,
+    pub due_date: NaiveDate,
+    #[validate(length(equal = 3))]
+    pub currency_code: String,
+    pub receivable_account_id: Uuid, // Accounts receivable, debited when issued
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    #[validate(length(min = 1, max = 200), nested)]
+    pub lines: Vec<InvoiceLineDto>,
+}
+
+// One line of an invoice, in the invoice's currency
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct InvoiceLineDto {
+    #[validate(length(min = 1, max = 1000))]
+    pub description: String,
+    pub quantity: Decimal,        // Greater than zero, at most four decimals
+    pub unit_price: Decimal,      // Zero or more, at most four decimals
+    pub revenue_account_id: Uuid, // Credited when issued
+}
+
+// DTO for updating a draft Invoice; `lines`, when given, replaces all lines
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateInvoiceDto {
+    pub customer_id: Option<Uuid>,
+    pub issue_date: Option<NaiveDate>,
+    pub due_date: Option<NaiveDate>,
+    #[validate(length(equal = 3))]
+    pub currency_code: Option<String>,
+    pub receivable_account_id: Option<Uuid>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    #[validate(length(min = 1, max = 200), nested)]
+    pub lines: Option<Vec<InvoiceLineDto>>,
+}
+
+// Query parameters for listing invoices
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListInvoicesQuery {
+    pub status: Option<InvoiceStatus>,
+    pub customer_id: Option<Uuid>,
+    pub overdue: Option<bool>, // Only unpaid invoices past their due date
+}
+
+// DTO for recording a payment received against an issued invoice
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RecordInvoicePaymentDto {
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,          // At most what is still owed
+    pub deposit_account_id: Uuid, // The bank or cash account the money went to
+    #[validate(length(max = 1000))]
+    pub memo: Option<String>,
+}
+
+// DTO for changing how invoice numbers are built
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateInvoiceNumberingDto {
+    #[validate(length(max = 20))]
+    pub prefix: Option<String>,
+    #[validate(range(min = 1))]
+    pub next_number: Option<i64>, // Cannot go back to a number already used
+    #[validate(range(min = 1, max = 12))]
+    pub padding: Option<i16>,
+}
@@ -1,4 +1,5 @@
 use crate::models::journal_entry::JournalEntryType;
+use crate::utils::{patch::Patch, validation::non_negative_amount};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -9,30 +10,41 @@ use validator::Validate; // Import the enum
 // not directly by the client in this DTO unless it's for a specific scenario.
 // For composite transaction creation, a Transaction DTO might embed multiple JournalEntry DTOs.
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateJournalEntryDto {
     pub account_id: Uuid,
     pub entry_type: JournalEntryType, // Use the enum
-    #[validate(range(min = 0.0))] // Amount must be non-negative
+    #[validate(custom(function = "non_negative_amount"))] // Amount must be non-negative
     pub amount: Decimal,
     #[validate(length(equal = 3))]
     pub currency_code: String,
     pub exchange_rate: Option<Decimal>,
     pub converted_amount: Option<Decimal>,
     pub memo: Option<String>,
+    pub project_id: Option<Uuid>, // Dimensions; each must be an active one of that kind
+    pub class_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
     // transaction_id, created_by will be derived from context/parent operation
 }
 
 // DTO for updating an existing JournalEntry
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateJournalEntryDto {
     pub account_id: Option<Uuid>,
     pub entry_type: Option<JournalEntryType>, // Use the enum
-    #[validate(range(min = 0.0))]
+    #[validate(custom(function = "non_negative_amount"))]
     pub amount: Option<Decimal>,
     #[validate(length(equal = 3))]
     pub currency_code: Option<String>,
     pub exchange_rate: Option<Decimal>,
     pub converted_amount: Option<Decimal>,
     pub memo: Option<String>,
+    #[serde(default)]
+    pub project_id: Patch<Uuid>, // null clears it
+    #[serde(default)]
+    pub class_id: Patch<Uuid>,
+    #[serde(default)]
+    pub location_id: Patch<Uuid>,
     // updated_by will be derived from context
 }
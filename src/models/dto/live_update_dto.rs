@@ -0,0 +1,23 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::live_update::LiveTopic;
+
+/// Messages a client sends on `/ws`, tagged by `type`. The first must be `auth`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ClientMessage {
+    /// Same credentials as the `X-Tenant-Id`, `X-Api-Key` and `Authorization: Bearer`
+    /// headers, which browsers cannot set on a WebSocket.
+    Auth {
+        tenant_id: Option<Uuid>,
+        api_key: Option<String>,
+        access_token: Option<String>,
+    },
+    Subscribe {
+        topics: Vec<LiveTopic>,
+    },
+    Unsubscribe {
+        topics: Vec<LiveTopic>,
+    },
+}
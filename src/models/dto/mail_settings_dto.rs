@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for creating or replacing the tenant's outgoing mail server
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertMailSettingsDto {
+    #[validate(length(min = 1, max = 255))]
+    pub smtp_host: String,
+    #[validate(range(min = 1, max = 65535))]
+    pub smtp_port: Option<i32>, // Defaults to 587 (STARTTLS)
+    #[validate(length(max = 255))]
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>, // Omit to keep the stored password
+    #[validate(length(min = 3, max = 320))]
+    pub from_address: String, // e.g. "Acme Finance <finance@acme.example>"
+}
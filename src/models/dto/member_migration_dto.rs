@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for importing members; accepts an export from another deployment as it is
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ImportMembersDto {
+    #[validate(length(min = 1, max = 5000), nested)]
+    pub members: Vec<ImportMemberDto>,
+}
+
+// One member to import; other exported fields (e.g. is_active) are ignored
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ImportMemberDto {
+    #[validate(email, length(max = 255))]
+    pub email: String,
+    #[validate(length(min = 1, max = 100))]
+    pub first_name: String,
+    #[validate(length(min = 1, max = 100))]
+    pub last_name: String,
+    #[serde(default)]
+    pub roles: Vec<String>, // Role names
+}
+
+// Query parameters for importing members
+#[derive(Debug, Deserialize)]
+pub struct ImportMembersQuery {
+    #[serde(default)]
+    pub dry_run: bool, // Only report what would change
+    #[serde(default)]
+    pub on_conflict: MemberConflictPolicy,
+}
+
+// What to do with someone who is already a member, matched by email
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberConflictPolicy {
+    #[default]
+    Merge, // Add the imported roles, keep the others
+    Replace, // Make the roles exactly the imported ones
+    Skip,    // Leave existing members as they are
+}
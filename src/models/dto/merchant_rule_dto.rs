@@ -0,0 +1,36 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for creating a tenant merchant rule: exactly one of pattern or payee_id
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateMerchantRuleDto {
+    #[validate(length(min = 1, max = 255))]
+    pub pattern: Option<String>, // Raw descriptor text; cleaned the same way as bank descriptors
+    pub payee_id: Option<Uuid>, // Also categorizes transactions entered with this payee
+    #[validate(length(min = 1, max = 255))]
+    pub merchant_name: Option<String>, // Required with a pattern; defaults to the payee's name
+    pub category_id: Option<Uuid>,
+}
+
+// DTO for updating a tenant merchant rule
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateMerchantRuleDto {
+    #[validate(length(min = 1, max = 255))]
+    pub pattern: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub merchant_name: Option<String>,
+    pub category_id: Option<Uuid>,
+    #[serde(default)]
+    pub remove_category: bool, // Clears category_id
+}
+
+// DTO for re-running normalization over staged history
+#[derive(Debug, Default, Deserialize, Serialize, Validate)]
+pub struct ReapplyMerchantRulesDto {
+    pub from_date: Option<NaiveDate>, // Defaults to all history
+    pub to_date: Option<NaiveDate>,
+}
@@ -8,25 +8,58 @@ pub mod journal_entry_dto;
 pub mod tag_dto; // New
 pub mod tenant_dto;
 pub mod transaction_dto;
-pub mod user_dto;
 
 // DTOs for Phase 2 Advanced Features & Ecosystem Integration (will add later)
-// pub mod budget_dto;
-// pub mod budget_line_item_dto;
-// pub mod recurring_transaction_dto;
-// pub mod custom_report_dto;
-// pub mod dashboard_dto;
-// pub mod dashboard_widget_dto;
+pub mod budget_dto;
+pub mod budget_line_item_dto;
+pub mod recurring_transaction_dto;
+pub mod custom_report_dto;
+pub mod report_schedule_dto;
+pub mod mail_settings_dto;
+pub mod privacy_dto;
+pub mod user_preference_dto;
+pub mod quick_open_dto;
+pub mod cash_position_dto;
+pub mod merchant_rule_dto;
+pub mod event_dto;
+pub mod household_dto;
+pub mod reimbursement_dto;
+pub mod envelope_dto;
+pub mod invoice_dto;
+pub mod bill_dto;
+pub mod payment_dto;
+pub mod fixed_asset_dto;
+pub mod payee_dto;
+pub mod dimension_dto;
+pub mod business_calendar_dto;
+pub mod fx_revaluation_dto;
+pub mod csv_format_dto;
+pub mod report_dto;
+pub mod statement_layout_dto;
+pub mod dashboard_dto;
+pub mod dashboard_widget_dto;
 // pub mod role_dto;
 // pub mod permission_dto;
 // pub mod role_permission_dto;
 // pub mod user_tenant_role_dto;
-// pub mod ext_provider_dto;
-// pub mod ext_conn_dto;
-// pub mod external_account_dto;
+pub mod ext_provider_dto;
+pub mod ext_conn_dto;
+pub mod external_account_dto;
+pub mod transaction_match_dto;
+pub mod fiscal_period_dto;
+pub mod notification_dto;
+pub mod security_webhook_dto;
+pub mod api_key_dto;
+pub mod tenant_backup_dto;
+pub mod tenant_invitation_dto;
+pub mod member_migration_dto;
+pub mod opening_balance_dto;
+pub mod assistant_dto;
+pub mod archive_dto;
+pub mod live_update_dto;
 // pub mod external_transactions_staging_dto;
 // pub mod coa_template_dto;
 // pub mod coa_template_account_dto;
 
-// Placeholder for Authentication DTOs
+// Authentication DTOs
 pub mod auth_dto;
@@ -0,0 +1,24 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::notification::DigestFrequency;
+
+// DTO for updating the current user's notification preferences
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateNotificationPreferencesDto {
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>, // IANA name, e.g. "America/New_York"
+    pub quiet_hours_start: Option<NaiveTime>, // Set both, or send clear_quiet_hours
+    pub quiet_hours_end: Option<NaiveTime>,
+    pub clear_quiet_hours: Option<bool>,
+    pub digest_frequency: Option<DigestFrequency>,
+    pub email_enabled: Option<bool>,
+}
+
+// Query parameters for listing notifications
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListNotificationsQuery {
+    pub limit: Option<i64>, // Defaults to 50
+}
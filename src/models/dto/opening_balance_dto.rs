@@ -0,0 +1,30 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::transaction::TransactionStatus;
+
+// DTO for recording the balances carried over from a previous system at a cutover date
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateOpeningBalancesDto {
+    pub as_of: NaiveDate,        // The cutover date
+    pub equity_account_id: Uuid, // Takes the difference, e.g. "Opening Balance Equity"
+    #[validate(length(min = 1))]
+    pub description: Option<String>, // Defaults to "Opening balances"
+    pub status: Option<TransactionStatus>, // POSTED (default), DRAFT or PENDING_APPROVAL
+    #[validate(length(min = 1, max = 1000), nested)]
+    pub balances: Vec<OpeningBalanceLineDto>,
+}
+
+// One account's balance at the cutover, in the account's currency
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct OpeningBalanceLineDto {
+    pub account_id: Uuid,
+    pub amount: Decimal, // Positive on the account's normal side (debit for assets), negative otherwise
+    #[validate(length(max = 1000))]
+    pub memo: Option<String>,
+}
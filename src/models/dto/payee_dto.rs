@@ -0,0 +1,38 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for creating a payee
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreatePayeeDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    // tenant_id and created_by will be derived from context
+}
+
+// DTO for renaming or annotating a payee
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdatePayeeDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+    // updated_by will be derived from context
+}
+
+// Query parameters for listing payees
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListPayeesQuery {
+    pub q: Option<String>, // Case-insensitive substring of the name
+}
+
+// Query parameters for the spend-by-payee report
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PayeeSpendingQuery {
+    pub from_date: Option<NaiveDate>, // Defaults to the start of the current month
+    pub to_date: Option<NaiveDate>,   // Defaults to today
+}
@@ -0,0 +1,52 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::payment::PaymentDirection;
+
+// DTO for recording a payment and allocating it across invoices (RECEIVED) or bills (SENT)
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreatePaymentDto {
+    pub direction: PaymentDirection,
+    pub customer_id: Option<Uuid>, // Required for RECEIVED
+    pub vendor_id: Option<Uuid>,   // Required for SENT
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    #[validate(length(equal = 3))]
+    pub currency_code: String,
+    pub account_id: Uuid, // The bank or cash account deposited to or paid from
+    pub credit_account_id: Option<Uuid>, // Required when part of the amount is left unallocated
+    #[validate(length(max = 1000))]
+    pub memo: Option<String>,
+    #[validate(length(max = 200))]
+    pub allocations: Vec<PaymentAllocationDto>, // Applied in the order given
+}
+
+// Part of a payment, or of its credit, applied to one invoice or bill
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PaymentAllocationDto {
+    pub document_id: Uuid,       // Invoice or bill ID
+    pub amount: Option<Decimal>, // Defaults to what is owed, capped by what is left to allocate
+}
+
+// DTO for applying a payment's unapplied credit to more invoices or bills
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ApplyPaymentCreditDto {
+    pub applied_on: NaiveDate,
+    #[validate(length(min = 1, max = 200))]
+    pub allocations: Vec<PaymentAllocationDto>,
+}
+
+// Query parameters for listing payments
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListPaymentsQuery {
+    pub direction: Option<PaymentDirection>,
+    pub customer_id: Option<Uuid>,
+    pub vendor_id: Option<Uuid>,
+    pub with_credit: Option<bool>, // Only payments with an unapplied amount
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for turning privacy mode on or off
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdatePrivacySettingsDto {
+    pub privacy_mode: bool, // Enabling encrypts existing text; disabling decrypts it
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// Query parameters for quick-open search
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct QuickOpenQuery {
+    #[validate(length(max = 100))]
+    pub q: String,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<i64>, // Defaults to 20
+}
@@ -0,0 +1,70 @@
+use crate::models::journal_entry::JournalEntryType;
+use crate::models::recurring_transaction::{BusinessDayRule, RecurringFrequencyUnit};
+use crate::models::transaction::TransactionType;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate; // Import the enums
+
+// DTO for a single leg of a standing journal entry template
+#[derive(Debug, Deserialize, Serialize, Validate, Clone)]
+pub struct RecurringJournalLineDto {
+    pub account_id: Uuid,
+    pub entry_type: JournalEntryType, // Use the enum
+    pub amount: Decimal,              // Must be positive; checked by the service
+    pub memo: Option<String>,
+}
+
+// DTO for creating a new RecurringTransaction
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateRecurringTransactionDto {
+    #[validate(length(min = 1))]
+    pub description: String,
+    pub r#type: TransactionType, // Must be JOURNAL_ENTRY; the legs carry the accounts
+    pub category_id: Option<Uuid>,
+    pub amount: Option<Decimal>, // Derived from the legs; checked against them when given
+    #[validate(length(equal = 3))]
+    pub currency_code: String,
+    #[validate(range(min = 1))]
+    pub frequency_value: i32,
+    pub frequency_unit: RecurringFrequencyUnit,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+    // Legs of the journal entry template; required
+    #[validate(nested)]
+    pub journal_lines: Option<Vec<RecurringJournalLineDto>>,
+    // Yearly raise in percent (e.g. 3 for +3%), applied on the first of escalation_month
+    pub escalation_percent: Option<Decimal>,
+    #[validate(range(min = 1, max = 12))]
+    pub escalation_month: Option<i16>,
+    // Moves occurrences on weekends and holidays of the tenant's business calendar; defaults to NONE
+    pub business_day_rule: Option<BusinessDayRule>,
+    // tenant_id and created_by will be derived from context
+}
+
+// DTO for updating an existing RecurringTransaction
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateRecurringTransactionDto {
+    #[validate(length(min = 1))]
+    pub description: Option<String>,
+    pub category_id: Option<Uuid>,
+    pub amount: Option<Decimal>,
+    #[validate(range(min = 1))]
+    pub frequency_value: Option<i32>,
+    pub frequency_unit: Option<RecurringFrequencyUnit>,
+    pub end_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+    #[validate(nested)]
+    pub journal_lines: Option<Vec<RecurringJournalLineDto>>, // Re-validated for balance on update
+    pub is_active: Option<bool>,
+    pub escalation_percent: Option<Decimal>,
+    #[validate(range(min = 1, max = 12))]
+    pub escalation_month: Option<i16>,
+    pub remove_escalation: Option<bool>, // Drops the escalation rule
+    pub business_day_rule: Option<BusinessDayRule>,
+    // updated_by will be derived from context
+}
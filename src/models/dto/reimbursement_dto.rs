@@ -0,0 +1,35 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for marking an expense as reimbursable
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct MarkReimbursableDto {
+    #[validate(length(min = 1, max = 255))]
+    pub payer: String, // Who is expected to pay it back
+    pub notes: Option<String>,
+}
+
+// DTO for matching a reimbursement deposit to the expenses it pays back
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct MatchReimbursementDto {
+    pub deposit_transaction_id: Uuid, // An INCOME transaction in the expenses' currency
+    #[validate(length(min = 1, max = 500))]
+    pub allocations: Vec<ReimbursementAllocationDto>,
+}
+
+// One expense paid back by a deposit
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ReimbursementAllocationDto {
+    pub expense_transaction_id: Uuid,
+    pub amount: Option<Decimal>, // Defaults to what is still outstanding, capped by what is left of the deposit
+}
+
+// Query parameters for the outstanding reimbursables report
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OutstandingReimbursablesQuery {
+    pub payer: Option<String>,    // Case-insensitive exact match
+    pub as_of: Option<NaiveDate>, // Expenses and deposits up to this date; defaults to today
+}
@@ -0,0 +1,40 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{dimension::DimensionFilter, report::SpendGranularity};
+
+// Query parameters for period statements (e.g., income statement)
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReportPeriodQuery {
+    pub from_date: Option<NaiveDate>, // Defaults to the start of the current year
+    pub to_date: Option<NaiveDate>,   // Defaults to today
+    pub layout_id: Option<Uuid>,      // Defaults to the tenant's default layout, if any
+    #[serde(flatten)]
+    pub dimensions: DimensionFilter, // project_id, class_id, location_id; none = all entries
+}
+
+// Query parameters for the spend-by-category report
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SpendByCategoryQuery {
+    pub from_date: Option<NaiveDate>, // Defaults to the start of the current year
+    pub to_date: Option<NaiveDate>,   // Defaults to today
+    #[serde(default)]
+    pub granularity: SpendGranularity, // month (default) or quarter
+}
+
+// Query parameters for point-in-time statements (e.g., balance sheet, trial balance)
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReportAsOfQuery {
+    pub as_of: Option<NaiveDate>, // Defaults to today
+    pub layout_id: Option<Uuid>,  // Defaults to the tenant's default layout, if any
+    #[serde(flatten)]
+    pub dimensions: DimensionFilter, // Trial balance only; none = all entries
+}
+
+// Paging for drill-down results
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DrilldownQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::report_schedule::{AttachmentFormat, ReportPeriod};
+
+// DTO for scheduling a custom report for email delivery
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateReportScheduleDto {
+    #[validate(length(min = 9, max = 100))]
+    pub cron_expression: String, // e.g. "0 7 1 * *" = 07:00 on the 1st of every month
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>, // Defaults to UTC
+    pub period: Option<ReportPeriod>, // Defaults to PREVIOUS_MONTH
+    #[validate(length(min = 1, max = 20))]
+    pub recipients: Vec<String>,
+    pub is_active: Option<bool>, // Defaults to true
+    // Defaults to CSV; PDF is offered for statement reports only
+    pub attachment_format: Option<AttachmentFormat>,
+}
+
+// DTO for updating a report schedule
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateReportScheduleDto {
+    #[validate(length(min = 9, max = 100))]
+    pub cron_expression: Option<String>,
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+    pub period: Option<ReportPeriod>,
+    #[validate(length(min = 1, max = 20))]
+    pub recipients: Option<Vec<String>>,
+    pub is_active: Option<bool>,
+    pub attachment_format: Option<AttachmentFormat>,
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::security_webhook::SecurityEventType;
+
+// DTO for creating or replacing the tenant's security webhook
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpsertSecurityWebhookDto {
+    #[validate(url, length(max = 2048))]
+    pub url: String, // Must be https
+    pub event_types: Vec<SecurityEventType>, // Only these events are sent
+    pub is_active: Option<bool>,             // Defaults to true
+}
+
+// DTO for sending a synthetic event of a chosen type
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct SendTestEventDto {
+    pub event_type: SecurityEventType,
+}
@@ -0,0 +1,27 @@
+use crate::models::report::StatementType;
+use crate::models::statement_layout::StatementLayoutDefinition;
+use serde::{Deserialize, Serialize};
+use validator::Validate; // Import the enum
+
+// DTO for creating a new StatementLayout
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateStatementLayoutDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    pub statement_type: StatementType,         // Use the enum
+    pub definition: StatementLayoutDefinition, // Rows are checked by the service
+    pub is_default: Option<bool>,
+    // tenant_id and created_by will be derived from context
+}
+
+// DTO for updating an existing StatementLayout
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateStatementLayoutDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    pub definition: Option<StatementLayoutDefinition>,
+    pub is_default: Option<bool>,
+    // statement_type is fixed once created; updated_by will be derived from context
+}
@@ -3,6 +3,7 @@ use validator::Validate;
 
 // DTO for creating a new Tag
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateTagDto {
     #[validate(length(min = 1, max = 255))]
     pub name: String,
@@ -12,6 +13,7 @@ pub struct CreateTagDto {
 
 // DTO for updating an existing Tag
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateTagDto {
     #[validate(length(min = 1, max = 255))]
     pub name: Option<String>,
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for starting a tenant backup (no Debug/Serialize: the passphrase must not be logged)
+#[derive(Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateTenantBackupDto {
+    #[validate(length(min = 12, max = 1024))]
+    pub passphrase: Option<String>, // Encrypts the bundle; never stored, so it cannot be recovered
+    #[serde(default)]
+    pub anonymized: bool, // Scramble names, descriptions and amounts (see `utils::anonymize`)
+}
+
+// Outcome of restoring one file of a backup
+#[derive(Debug, Serialize)]
+pub struct EntityImportResult {
+    pub entity: String,        // File name without `.json`, e.g. "transactions"
+    pub rows: usize,           // Rows in the archive
+    pub imported: usize,       // Rows restored; 0 when the entity failed
+    pub error: Option<String>, // Why the entity was not restored
+}
+
+// Outcome of restoring a backup into a tenant
+#[derive(Debug, Serialize)]
+pub struct TenantImportResult {
+    pub source_tenant_id: Uuid, // Tenant the backup was taken from
+    pub anonymized: bool,
+    pub entities: Vec<EntityImportResult>,
+}
@@ -3,6 +3,7 @@ use validator::Validate;
 
 // DTO for creating a new Tenant
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateTenantDto {
     #[validate(length(min = 1, max = 255))]
     pub name: String,
@@ -17,6 +18,7 @@ pub struct CreateTenantDto {
 
 // DTO for updating an existing Tenant
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateTenantDto {
     #[validate(length(min = 1, max = 255))]
     pub name: Option<String>,
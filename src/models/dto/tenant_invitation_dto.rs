@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for inviting someone to a tenant
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateTenantInvitationDto {
+    #[validate(email, length(max = 255))]
+    pub email: String,
+    pub role_id: Uuid,
+}
+
+// DTO for accepting an invitation (no Debug: the token and password must not be logged).
+// The names and password are required only when no account exists for the invited email.
+#[derive(Deserialize, Validate)]
+pub struct AcceptInvitationDto {
+    #[validate(length(min = 1))]
+    pub token: String,
+    #[validate(length(min = 2, max = 255))]
+    pub first_name: Option<String>,
+    #[validate(length(min = 2, max = 255))]
+    pub last_name: Option<String>,
+    #[validate(length(min = 8, max = 1024))]
+    pub password: Option<String>,
+}
@@ -1,4 +1,9 @@
-use crate::models::transaction::TransactionType;
+use crate::models::{
+    dto::household_dto::SetTransactionAttributionDto,
+    dto::journal_entry_dto::CreateJournalEntryDto,
+    transaction::{TransactionStatus, TransactionType},
+};
+use crate::utils::{patch::Patch, validation::positive_amount};
 use chrono::NaiveDate;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -8,15 +13,17 @@ use validator::Validate; // Import the enum
 
 // DTO for creating a new Transaction
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateTransactionDto {
     pub transaction_date: NaiveDate,
     #[validate(length(min = 1))]
     pub description: String,
     pub r#type: TransactionType, // Use the enum
     pub category_id: Option<Uuid>,
+    pub payee_id: Option<Uuid>, // Without category_id, the payee's merchant rule supplies one
     // For tags_json, clients might send an array of UUID strings
     pub tags: Option<Vec<Uuid>>, // Changed from JsonValue for better type safety
-    #[validate(range(min = 0.01))] // Amount must be positive
+    #[validate(custom(function = "positive_amount"))] // Amount must be positive
     pub amount: Decimal,
     #[validate(length(equal = 3))]
     pub currency_code: String,
@@ -24,24 +31,146 @@ pub struct CreateTransactionDto {
     pub reconciliation_date: Option<NaiveDate>,
     pub notes: Option<String>,
     pub source_document_url: Option<String>,
+    pub status: Option<TransactionStatus>, // DRAFT (default), PENDING_APPROVAL or POSTED
+    #[serde(default)]
+    pub journal_entries: Vec<CreateJournalEntryDto>, // Must balance before the transaction is posted
+    pub payment_account_id: Option<Uuid>, // With splits: credited with the amount (debited for income)
+    #[serde(default)]
+    #[validate(length(max = 100), nested)]
+    pub splits: Vec<CreateTransactionSplitDto>, // Instead of journal_entries; must add up to the amount
+    #[validate(nested)]
+    pub metadata: Option<TransactionMetadataDto>, // Sent by clients such as the mobile app
+    #[validate(nested)]
+    pub attribution: Option<SetTransactionAttributionDto>, // The household member who spent or paid
     // tenant_id and created_by will be derived from context
 }
 
+// One categorized share of a split transaction, booked as its own journal entry
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateTransactionSplitDto {
+    pub category_id: Uuid,
+    pub account_id: Uuid, // The expense (or income) account booked for this share
+    #[validate(custom(function = "positive_amount"))]
+    pub amount: Decimal,
+    #[validate(length(max = 1000))]
+    pub memo: Option<String>, // Defaults to the category name
+}
+
+// Client metadata captured with a new transaction
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct TransactionMetadataDto {
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: Option<f64>, // Must be sent together with longitude
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: Option<f64>,
+    #[validate(length(min = 1, max = 255))]
+    pub device: Option<String>,
+    #[validate(length(min = 1, max = 50))]
+    pub entry_source: Option<String>, // e.g. "MOBILE", "WEB", "API"; stored upper-cased
+}
+
+// Query parameters for listing transactions; all filters are optional and combine
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ListTransactionsQuery {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub event_id: Option<Uuid>,       // Only transactions assigned to this event
+    pub payee_id: Option<Uuid>,       // Only transactions with this payee
+    pub entry_source: Option<String>, // Case-insensitive
+    pub device: Option<String>,       // Substring match, case-insensitive
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub near_lat: Option<f64>, // With near_lon: only transactions entered within radius_km
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub near_lon: Option<f64>,
+    #[validate(range(min = 0.01, max = 20000.0))]
+    pub radius_km: Option<f64>, // Defaults to 25
+}
+
 // DTO for updating an existing Transaction
 #[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateTransactionDto {
     pub transaction_date: Option<NaiveDate>,
     pub description: Option<String>,
     pub r#type: Option<TransactionType>, // Use the enum
-    pub category_id: Option<Uuid>,
+    #[serde(default)]
+    pub category_id: Patch<Uuid>, // null clears it
+    #[serde(default)]
+    pub payee_id: Patch<Uuid>, // null clears it
     pub tags: Option<Vec<Uuid>>, // Changed from JsonValue for better type safety
-    #[validate(range(min = 0.01))]
+    #[validate(custom(function = "positive_amount"))]
     pub amount: Option<Decimal>,
     #[validate(length(equal = 3))]
     pub currency_code: Option<String>,
     pub is_reconciled: Option<bool>,
-    pub reconciliation_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub reconciliation_date: Patch<NaiveDate>,
+    #[serde(default)]
+    pub notes: Patch<String>,
+    #[serde(default)]
+    pub source_document_url: Patch<String>,
+    // updated_by will be derived from context
+}
+
+// DTO for reversing a transaction
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ReverseTransactionDto {
+    pub reversal_date: Option<NaiveDate>, // Defaults to today
+    #[validate(length(min = 1))]
+    pub description: Option<String>, // Defaults to "Reversal of <original description>"
+}
+
+// DTO for voiding a posted transaction
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct VoidTransactionDto {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
+// DTO for a transfer between two of the tenant's accounts; both journal sides are generated
+#[derive(Debug, Deserialize, Serialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateTransferDto {
+    pub transaction_date: NaiveDate,
+    #[validate(length(min = 1))]
+    pub description: Option<String>, // Defaults to "Transfer from <account> to <account>"
+    pub from_account_id: Uuid, // Credited with the amount, in its own currency
+    pub to_account_id: Uuid,   // Debited with the received amount, in its own currency
+    #[validate(custom(function = "positive_amount"))]
+    pub amount: Decimal,
+    #[validate(custom(function = "positive_amount"))]
+    pub to_amount: Option<Decimal>, // Amount received across currencies; looked up when absent
+    pub status: Option<TransactionStatus>, // DRAFT (default), PENDING_APPROVAL or POSTED
+    pub notes: Option<String>,
+}
+
+// DTO for splitting a receipt across categories; generates one balanced transaction
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct SplitReceiptDto {
+    pub transaction_date: NaiveDate,
+    #[validate(length(min = 1))]
+    pub description: String,
+    pub r#type: Option<TransactionType>, // EXPENSE (default) or INCOME
+    pub payee_id: Option<Uuid>,
+    pub amount: Decimal,                 // The receipt total, positive
+    #[validate(length(equal = 3))]
+    pub currency_code: String,
+    pub payment_account_id: Uuid, // Credited with the total (debited for income)
+    pub status: Option<TransactionStatus>, // DRAFT (default), PENDING_APPROVAL or POSTED
     pub notes: Option<String>,
     pub source_document_url: Option<String>,
-    // updated_by will be derived from context
+    #[validate(length(min = 1, max = 100), nested)]
+    pub splits: Vec<ReceiptSplitDto>,
+}
+
+// One share of a split receipt: exactly one of percentage or amount
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct ReceiptSplitDto {
+    pub category_id: Uuid,
+    pub account_id: Uuid, // The expense (or income) account booked for this share
+    pub percentage: Option<Decimal>, // Of the total, e.g. 62.5
+    pub amount: Option<Decimal>,
+    #[validate(length(max = 1000))]
+    pub memo: Option<String>, // Defaults to the category name
 }
@@ -0,0 +1,19 @@
+use crate::models::transaction_match::MatchStatus;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid; // Import the enum
+
+// Query parameters for listing match proposals
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListTransactionMatchesQuery {
+    pub status: Option<MatchStatus>, // Defaults to PROPOSED
+    pub ext_conn_id: Option<Uuid>,
+}
+
+// Summary returned after a matching run
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct MatchRunSummary {
+    pub staged_rows_examined: usize,
+    pub proposals_created: usize,
+    pub duplicates_flagged: usize,
+    pub transfers_collapsed: usize, // Pairs of staged rows booked as one transfer
+}
@@ -0,0 +1,17 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for replacing the current user's pinned accounts and their order
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct SetPinnedAccountsDto {
+    #[validate(length(max = 50))]
+    pub account_ids: Vec<Uuid>, // First = top
+}
+
+// Query parameters for pinned account balances
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PinnedBalancesQuery {
+    pub as_of: Option<NaiveDate>, // Defaults to today
+}
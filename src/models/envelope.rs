@@ -0,0 +1,45 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Money moved from one envelope (budget line item) to another.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct EnvelopeMove {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub budget_id: Uuid,
+    pub from_line_item_id: Uuid,
+    pub to_line_item_id: Uuid,
+    pub amount: Decimal,
+    pub moved_on: NaiveDate,
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+/// One envelope's balance.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Envelope {
+    pub line_item_id: Uuid,
+    pub label: String,
+    pub budgeted: Decimal,  // The line item's budgeted amount
+    pub moved_in: Decimal,  // From other envelopes
+    pub moved_out: Decimal, // To other envelopes
+    pub assigned: Decimal,  // budgeted + moved_in - moved_out
+    pub spent: Decimal,
+    pub available: Decimal, // assigned - spent
+}
+
+/// Envelope balances of a budget and the income not yet given to an envelope.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnvelopeSummary {
+    pub budget_id: Uuid,
+    pub currency_code: String,
+    pub as_of: NaiveDate,
+    pub income: Decimal, // Posted income in the budget currency, period start to as_of
+    pub assigned: Decimal, // Total of all envelopes
+    pub ready_to_assign: Decimal, // income - assigned; negative when over-assigned
+    pub envelopes: Vec<Envelope>,
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A trip, conference or similar occasion that transactions can be grouped under.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Event {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String, // e.g. "Berlin conference"
+    pub description: Option<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// Totals of an event's transactions in one category and currency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventCategoryTotal {
+    pub category_id: Option<Uuid>, // None for uncategorized transactions
+    pub category_name: String,
+    pub transaction_count: i64,
+    pub income: Decimal,
+    pub expense: Decimal,
+}
+
+/// Totals of an event's transactions in one currency; amounts are never converted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventCurrencyTotal {
+    pub currency_code: String,
+    pub transaction_count: i64,
+    pub income: Decimal,
+    pub expense: Decimal,
+    pub categories: Vec<EventCategoryTotal>,
+}
+
+/// Per-event totals by currency and category.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventSummary {
+    pub event_id: Uuid,
+    pub name: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub currencies: Vec<EventCurrencyTotal>,
+}
+
+/// Result of assigning transactions to an event.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventAssignmentResult {
+    pub assigned: u64, // Including transactions moved from another event
+}
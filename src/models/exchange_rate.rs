@@ -1,10 +1,10 @@
-use chrono::{DateTime, NaiveDate, Utoc};
+use chrono::{DateTime, NaiveDate, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, FromRow, Serialize, Deserialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct ExchangeRate {
     pub id: Uuid,
     pub tenant_id: Option<Uuid>, // Nullable
@@ -18,3 +18,56 @@ pub struct ExchangeRate {
     pub updated_at: DateTime<Utc>,
     pub updated_by: Uuid,
 }
+
+// A tenant's opt-in to automatic daily rates from the configured provider
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ExchangeRateFetchSettings {
+    pub tenant_id: Uuid,
+    pub is_enabled: bool,
+    pub last_fetched_at: Option<DateTime<Utc>>,
+    pub last_rate_date: Option<NaiveDate>, // Publication date of the last fetched rates
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Outcome of pulling rates from the provider for one tenant
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExchangeRateRefreshResult {
+    pub source: String, // e.g. "ECB", "OPEN_EXCHANGE_RATES"
+    pub base_currency_code: String,
+    pub rate_date: NaiveDate,
+    pub rates_updated: usize,
+}
+
+// How a conversion rate was found, from most to least direct
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RateMethod {
+    SameCurrency, // No conversion needed
+    ExactDate,    // Stored rate for the pair on the requested date
+    PriorDate,    // Most recent stored rate for the pair before the requested date
+    Inverse,      // Reciprocal of the stored rate for the opposite pair
+    Triangulated, // Through the tenant's base currency
+}
+
+// A resolved rate between two currencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionRate {
+    pub from_currency_code: String,
+    pub to_currency_code: String,
+    pub rate: Decimal,
+    pub rate_date: Option<NaiveDate>, // Date of the (oldest) stored rate used; None for SameCurrency
+    pub method: RateMethod,
+}
+
+// An amount converted with a resolved rate
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Conversion {
+    pub amount: Decimal,
+    pub converted_amount: Decimal,
+    #[serde(flatten)]
+    pub rate: ConversionRate,
+}
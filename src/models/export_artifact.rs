@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ExportArtifact {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: String, // CUSTOM_REPORT, EVENT_REPORT, TENANT_BACKUP or TENANT_ANONYMIZED
+    pub source_id: Uuid, // The custom report, event or tenant exported
+    pub status: ExportStatus,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub size_bytes: Option<i64>,
+    pub sha256: Option<String>, // Also the download's ETag
+    #[serde(skip_serializing)]
+    pub storage_key: Option<String>,
+    pub is_encrypted: bool, // Encrypted with a passphrase the user supplied
+    pub error_message: Option<String>,
+    pub expires_at: DateTime<Utc>, // Downloads return 410 Gone after this
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+// Stored as the Postgres enum `export_status`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "export_status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExportStatus {
+    Pending,
+    Ready,
+    Failed,
+}
@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid; // For JSONB
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ExtConn {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub provider_id: Uuid,
+    #[serde(skip_serializing)] // Encrypted at rest; never returned to clients
+    pub provider_access_token: String,
+    pub provider_item_id: Option<String>, // Nullable
+    pub status: String,                   // Consider an enum here: ExtConnStatus
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub metadata: Option<JsonValue>, // Nullable JSONB, holds the provider sync cursor
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Enum for connection status for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExtConnStatus {
+    Connected,
+    Disconnected,
+    Error,
+    PendingReauth,
+    Disabled,
+}
+
+impl std::str::FromStr for ExtConnStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "CONNECTED" => Ok(ExtConnStatus::Connected),
+            "DISCONNECTED" => Ok(ExtConnStatus::Disconnected),
+            "ERROR" => Ok(ExtConnStatus::Error),
+            "PENDING_REAUTH" => Ok(ExtConnStatus::PendingReauth),
+            "DISABLED" => Ok(ExtConnStatus::Disabled),
+            _ => Err(format!("'{}' is not a valid ExtConnStatus", s)),
+        }
+    }
+}
+
+impl From<ExtConnStatus> for String {
+    fn from(status: ExtConnStatus) -> Self {
+        match status {
+            ExtConnStatus::Connected => "CONNECTED".to_string(),
+            ExtConnStatus::Disconnected => "DISCONNECTED".to_string(),
+            ExtConnStatus::Error => "ERROR".to_string(),
+            ExtConnStatus::PendingReauth => "PENDING_REAUTH".to_string(),
+            ExtConnStatus::Disabled => "DISABLED".to_string(),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ExtProvider {
+    pub id: Uuid,
+    pub name: String,
+    pub code: String,                 // e.g., 'PLAID', 'STRIPE'
+    pub r#type: String,               // 'type' is a Rust keyword
+    pub description: Option<String>,  // Nullable
+    pub logo_url: Option<String>,     // Nullable
+    pub api_base_url: Option<String>, // Nullable, overrides the connector's default endpoint
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Enum for provider type for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExtProviderType {
+    BankingAggregator,
+    PaymentGateway,
+    ECommerce,
+    Payroll,
+    Other,
+}
+
+impl std::str::FromStr for ExtProviderType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "BANKING_AGGREGATOR" => Ok(ExtProviderType::BankingAggregator),
+            "PAYMENT_GATEWAY" => Ok(ExtProviderType::PaymentGateway),
+            "E_COMMERCE" => Ok(ExtProviderType::ECommerce),
+            "PAYROLL" => Ok(ExtProviderType::Payroll),
+            "OTHER" => Ok(ExtProviderType::Other),
+            _ => Err(format!("'{}' is not a valid ExtProviderType", s)),
+        }
+    }
+}
+
+impl From<ExtProviderType> for String {
+    fn from(pt: ExtProviderType) -> Self {
+        match pt {
+            ExtProviderType::BankingAggregator => "BANKING_AGGREGATOR".to_string(),
+            ExtProviderType::PaymentGateway => "PAYMENT_GATEWAY".to_string(),
+            ExtProviderType::ECommerce => "E_COMMERCE".to_string(),
+            ExtProviderType::Payroll => "PAYROLL".to_string(),
+            ExtProviderType::Other => "OTHER".to_string(),
+        }
+    }
+}
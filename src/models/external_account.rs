@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ExternalAccount {
+    pub id: Uuid,
+    pub ext_conn_id: Uuid,
+    pub account_id: Option<Uuid>, // Nullable until linked to a ledger account
+    pub provider_account_id: String,
+    pub name: String,
+    pub mask: Option<String>,    // Nullable, e.g., last 4 digits
+    pub r#type: Option<String>,  // Provider's account type, e.g., 'depository'
+    pub subtype: Option<String>, // Provider's subtype, e.g., 'checking'
+    pub currency_code: String,
+    pub current_balance: Option<Decimal>, // Nullable NUMERIC(18,2)
+    pub available_balance: Option<Decimal>, // Nullable NUMERIC(18,2)
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
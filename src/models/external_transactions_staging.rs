@@ -0,0 +1,68 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid; // For JSONB
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ExternalTransactionsStaging {
+    pub id: Uuid,
+    pub external_account_id: Uuid,
+    pub provider_transaction_id: String,
+    pub description: String,
+    pub amount: Decimal, // Signed: negative for money leaving the account
+    pub transaction_date: NaiveDate,
+    pub posted_date: Option<NaiveDate>, // Nullable while pending at the bank
+    pub status: String,                 // Consider an enum here: StagingStatus
+    pub tx_id: Option<Uuid>,            // Ledger transaction once converted/matched
+    pub raw_data: Option<JsonValue>,    // Nullable JSONB, provider payload
+    pub merchant_name: Option<String>,  // Cleaned from the descriptor on import
+    pub merchant_category_hint: Option<String>,
+    pub merchant_category_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Enum for staging status for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StagingStatus {
+    PendingReview,
+    Converted,
+    MatchedManually,
+    Ignored,
+    Duplicate,
+    Error,
+}
+
+impl std::str::FromStr for StagingStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PENDING_REVIEW" => Ok(StagingStatus::PendingReview),
+            "CONVERTED" => Ok(StagingStatus::Converted),
+            "MATCHED_MANUALLY" => Ok(StagingStatus::MatchedManually),
+            "IGNORED" => Ok(StagingStatus::Ignored),
+            "DUPLICATE" => Ok(StagingStatus::Duplicate),
+            "ERROR" => Ok(StagingStatus::Error),
+            _ => Err(format!("'{}' is not a valid StagingStatus", s)),
+        }
+    }
+}
+
+impl From<StagingStatus> for String {
+    fn from(status: StagingStatus) -> Self {
+        match status {
+            StagingStatus::PendingReview => "PENDING_REVIEW".to_string(),
+            StagingStatus::Converted => "CONVERTED".to_string(),
+            StagingStatus::MatchedManually => "MATCHED_MANUALLY".to_string(),
+            StagingStatus::Ignored => "IGNORED".to_string(),
+            StagingStatus::Duplicate => "DUPLICATE".to_string(),
+            StagingStatus::Error => "ERROR".to_string(),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct FiscalPeriod {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate, // Inclusive
+    pub status: String,      // Consider an enum here: FiscalPeriodStatus
+    pub closed_at: Option<DateTime<Utc>>,
+    pub closed_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Enum for fiscal period status for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FiscalPeriodStatus {
+    Open,
+    Closed,
+}
+
+impl std::str::FromStr for FiscalPeriodStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "OPEN" => Ok(FiscalPeriodStatus::Open),
+            "CLOSED" => Ok(FiscalPeriodStatus::Closed),
+            _ => Err(format!("'{}' is not a valid FiscalPeriodStatus", s)),
+        }
+    }
+}
+
+impl From<FiscalPeriodStatus> for String {
+    fn from(status: FiscalPeriodStatus) -> Self {
+        match status {
+            FiscalPeriodStatus::Open => "OPEN".to_string(),
+            FiscalPeriodStatus::Closed => "CLOSED".to_string(),
+        }
+    }
+}
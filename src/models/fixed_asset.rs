@@ -0,0 +1,143 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Something the tenant owns and depreciates over its useful life.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct FixedAsset {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub asset_tag: Option<String>,
+    pub description: Option<String>,
+    pub acquisition_date: NaiveDate,
+    pub in_service_date: NaiveDate, // Depreciation starts with this month
+    pub cost: Decimal,
+    pub salvage_value: Decimal,
+    pub useful_life_months: i32,
+    pub method: String,            // Consider an enum here: DepreciationMethod
+    pub declining_factor: Decimal, // e.g. 2 for double declining balance
+    pub currency_code: String,
+    pub asset_account_id: Uuid,
+    pub accumulated_depreciation_account_id: Uuid,
+    pub depreciation_expense_account_id: Uuid,
+    pub status: String, // Consider an enum here: FixedAssetStatus
+    pub accumulated_depreciation: Decimal,
+    pub depreciated_through: Option<NaiveDate>, // Last month end posted
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// One month of an asset's depreciation schedule.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepreciationScheduleLine {
+    pub period_end: NaiveDate,
+    pub depreciation: Decimal,
+    pub accumulated_depreciation: Decimal,
+    pub book_value: Decimal,
+    pub transaction_id: Option<Uuid>, // Set once posted
+}
+
+/// An asset's depreciation over its whole useful life.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepreciationSchedule {
+    pub asset_id: Uuid,
+    pub method: String,
+    pub cost: Decimal,
+    pub salvage_value: Decimal,
+    pub currency_code: String,
+    pub lines: Vec<DepreciationScheduleLine>,
+}
+
+/// An asset's depreciation within the report's period.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepreciationReportAsset {
+    pub asset_id: Uuid,
+    pub name: String,
+    pub asset_tag: Option<String>,
+    pub method: String,
+    pub cost: Decimal,
+    pub opening_book_value: Decimal,
+    pub depreciation: Decimal,
+    pub closing_book_value: Decimal,
+    pub posted: Decimal, // The part of `depreciation` already posted
+}
+
+/// Depreciation of all the tenant's assets between two dates, by currency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepreciationReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub assets: Vec<DepreciationReportAsset>,
+    pub totals: Vec<DepreciationReportTotal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DepreciationReportTotal {
+    pub currency_code: String,
+    pub cost: Decimal,
+    pub opening_book_value: Decimal,
+    pub depreciation: Decimal,
+    pub closing_book_value: Decimal,
+}
+
+// Enum for how an asset is depreciated
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DepreciationMethod {
+    StraightLine,     // Evenly over the useful life
+    DecliningBalance, // A fixed rate of the remaining book value each month
+}
+
+impl std::str::FromStr for DepreciationMethod {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "STRAIGHT_LINE" => Ok(DepreciationMethod::StraightLine),
+            "DECLINING_BALANCE" => Ok(DepreciationMethod::DecliningBalance),
+            _ => Err(format!("'{}' is not a valid DepreciationMethod", s)),
+        }
+    }
+}
+
+impl From<DepreciationMethod> for String {
+    fn from(method: DepreciationMethod) -> Self {
+        match method {
+            DepreciationMethod::StraightLine => "STRAIGHT_LINE".to_string(),
+            DepreciationMethod::DecliningBalance => "DECLINING_BALANCE".to_string(),
+        }
+    }
+}
+
+// Enum for an asset's depreciation state
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FixedAssetStatus {
+    Active,
+    FullyDepreciated,
+}
+
+impl std::str::FromStr for FixedAssetStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ACTIVE" => Ok(FixedAssetStatus::Active),
+            "FULLY_DEPRECIATED" => Ok(FixedAssetStatus::FullyDepreciated),
+            _ => Err(format!("'{}' is not a valid FixedAssetStatus", s)),
+        }
+    }
+}
+
+impl From<FixedAssetStatus> for String {
+    fn from(status: FixedAssetStatus) -> Self {
+        match status {
+            FixedAssetStatus::Active => "ACTIVE".to_string(),
+            FixedAssetStatus::FullyDepreciated => "FULLY_DEPRECIATED".to_string(),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::exchange_rate::RateMethod;
+
+// Accounts that receive unrealized exchange gains and losses
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct FxRevaluationSettings {
+    pub tenant_id: Uuid,
+    pub unrealized_gain_account_id: Uuid,
+    pub unrealized_loss_account_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// A posted revaluation
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct FxRevaluation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub as_of: NaiveDate,
+    pub transaction_id: Uuid, // The ADJUSTMENT transaction holding the gain/loss entries
+    pub base_currency_code: String,
+    pub total_gain: Decimal,
+    pub total_loss: Decimal,
+    pub lines: JsonValue, // Vec<FxRevaluationLine>
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+// Revaluation of one foreign-currency account. Balances are debit-positive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FxRevaluationLine {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub currency_code: String,
+    pub foreign_balance: Decimal,     // In the account's currency
+    pub booked_base_balance: Decimal, // In the base currency, at the rates it was booked at
+    pub rate: Decimal,
+    pub rate_date: Option<NaiveDate>,
+    pub rate_method: RateMethod,
+    pub revalued_base_balance: Decimal, // foreign_balance * rate
+    pub adjustment: Decimal,            // Positive is a gain
+}
+
+// Result of a revaluation run, posted or previewed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FxRevaluationResult {
+    pub as_of: NaiveDate,
+    pub base_currency_code: String,
+    pub dry_run: bool,
+    pub revaluation: Option<FxRevaluation>, // None for dry runs or when nothing changed
+    pub total_gain: Decimal,
+    pub total_loss: Decimal,
+    pub lines: Vec<FxRevaluationLine>,
+}
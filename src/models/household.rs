@@ -0,0 +1,70 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// The member a transaction is attributed to, and whether it is shared.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TransactionAttribution {
+    pub transaction_id: Uuid,
+    pub tenant_id: Uuid,
+    pub member_id: Uuid, // A user of the tenant
+    pub is_shared: bool, // Paid by member_id, split between the household
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// One member's expenses in one currency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemberSpending {
+    pub member_id: Option<Uuid>, // None for expenses attributed to nobody
+    pub member_name: Option<String>,
+    pub currency_code: String,
+    pub transaction_count: i64,
+    pub personal: Decimal,    // Expenses attributed to the member alone
+    pub shared_paid: Decimal, // Shared expenses the member paid
+    pub total: Decimal,
+}
+
+/// Expenses over a period split per member.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HouseholdSpending {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub members: Vec<MemberSpending>,
+}
+
+/// A member's position on the shared expenses of one currency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettleUpBalance {
+    pub member_id: Uuid,
+    pub member_name: String,
+    pub paid: Decimal,    // Shared expenses paid
+    pub share: Decimal,   // Equal share of all shared expenses
+    pub balance: Decimal, // paid - share; positive means the member is owed money
+}
+
+/// A payment that settles part of the balances.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SettleUpTransfer {
+    pub from_member_id: Uuid,
+    pub to_member_id: Uuid,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettleUpCurrency {
+    pub currency_code: String,
+    pub total_shared: Decimal,
+    pub balances: Vec<SettleUpBalance>,
+    pub transfers: Vec<SettleUpTransfer>, // Who owes whom, in as few payments as the greedy match gives
+}
+
+/// Who owes whom for the shared expenses of a period.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettleUp {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub currencies: Vec<SettleUpCurrency>,
+}
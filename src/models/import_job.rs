@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: String, // e.g. BUDGET_CSV
+    pub status: ImportJobStatus,
+    pub total_rows: Option<i32>, // Known once the file has been read
+    pub processed_rows: i32,
+    pub failed_rows: i32,
+    pub result_id: Option<Uuid>, // What the import created, e.g. the budget
+    pub error_message: Option<String>,
+    pub has_error_file: bool, // GET /import-jobs/:id/errors returns the failed rows
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Stored as the Postgres enum `import_job_status`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "import_job_status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportJobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
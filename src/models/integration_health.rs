@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Outcome of the last check of an external dependency.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DependencyStatus {
+    Up,
+    Down,          // Unreachable or erroring
+    AuthFailed,    // Reachable, but our credentials were rejected
+    NotConfigured, // No configuration present; not counted against readiness
+}
+
+/// Health of one external dependency (mail, storage, rate provider, ...).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencyHealth {
+    pub name: String,
+    pub status: DependencyStatus,
+    pub message: Option<String>,
+    pub latency_ms: Option<u64>,
+    pub checked_at: DateTime<Utc>,
+    pub last_success_at: Option<DateTime<Utc>>, // Survives failed checks
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrationHealthReport {
+    pub healthy: bool, // Every configured dependency is up
+    pub dependencies: Vec<DependencyHealth>,
+}
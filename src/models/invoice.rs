@@ -0,0 +1,130 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Someone the tenant invoices.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Customer {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub billing_address: Option<String>,
+    pub tax_id: Option<String>, // e.g. a VAT number, printed on invoices
+    pub notes: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub customer_id: Uuid,
+    pub invoice_number: Option<String>, // None while a draft
+    pub status: String,                 // Consider an enum here: InvoiceStatus
+    pub issue_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub currency_code: String,
+    pub receivable_account_id: Uuid,
+    pub total: Decimal,
+    pub amount_paid: Decimal,
+    pub issue_transaction_id: Option<Uuid>, // Posted when issued
+    pub notes: Option<String>,
+    pub issued_at: Option<DateTime<Utc>>,
+    pub issued_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct InvoiceLine {
+    pub id: Uuid,
+    pub invoice_id: Uuid,
+    pub tenant_id: Uuid,
+    pub position: i32,
+    pub description: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    pub amount: Decimal, // quantity * unit_price, rounded to cents
+    pub revenue_account_id: Uuid,
+}
+
+/// A payment received against an invoice and the transaction that booked it.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct InvoicePayment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub invoice_id: Uuid,
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    pub deposit_account_id: Uuid,
+    pub transaction_id: Uuid,
+    pub payment_id: Option<Uuid>, // Set when made through the payments resource
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+/// An invoice with its lines and payments.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceDetail {
+    #[serde(flatten)]
+    pub invoice: Invoice,
+    pub lines: Vec<InvoiceLine>,
+    pub payments: Vec<InvoicePayment>,
+}
+
+/// How a tenant's invoice numbers are built: `prefix` followed by the number, zero-padded
+/// to `padding` digits.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct InvoiceNumbering {
+    pub tenant_id: Uuid,
+    pub prefix: String,
+    pub next_number: i64,
+    pub padding: i16,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Option<Uuid>,
+}
+
+// Enum for the invoice lifecycle.
+// DRAFT -> ISSUED -> PARTIALLY_PAID -> PAID; only drafts can be edited or deleted.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InvoiceStatus {
+    Draft,
+    Issued,
+    PartiallyPaid,
+    Paid,
+}
+
+impl std::str::FromStr for InvoiceStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DRAFT" => Ok(InvoiceStatus::Draft),
+            "ISSUED" => Ok(InvoiceStatus::Issued),
+            "PARTIALLY_PAID" => Ok(InvoiceStatus::PartiallyPaid),
+            "PAID" => Ok(InvoiceStatus::Paid),
+            _ => Err(format!("'{}' is not a valid InvoiceStatus", s)),
+        }
+    }
+}
+
+impl From<InvoiceStatus> for String {
+    fn from(status: InvoiceStatus) -> Self {
+        match status {
+            InvoiceStatus::Draft => "DRAFT".to_string(),
+            InvoiceStatus::Issued => "ISSUED".to_string(),
+            InvoiceStatus::PartiallyPaid => "PARTIALLY_PAID".to_string(),
+            InvoiceStatus::Paid => "PAID".to_string(),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+/// A stream of live updates a WebSocket client can subscribe to.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveTopic {
+    Transactions, // Posted, voided and reversed transactions
+    Imports,      // Progress and completion of import jobs
+}
+
+/// A change published to the tenant's subscribers of a topic.
+#[derive(Debug, Clone)]
+pub struct LiveUpdate {
+    pub tenant_id: Uuid,
+    pub topic: LiveTopic,
+    pub event: &'static str, // e.g. "transaction.posted", "import.progress"
+    pub data: JsonValue,
+}
+
+/// Messages the server sends on `/ws`, tagged by `type`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// The handshake succeeded; the connection is scoped to this tenant.
+    Ready {
+        tenant_id: Uuid,
+        user_id: Uuid,
+    },
+    /// The topics the connection now receives.
+    Subscribed {
+        topics: Vec<LiveTopic>,
+    },
+    Event {
+        topic: LiveTopic,
+        event: &'static str,
+        data: JsonValue,
+    },
+    /// The connection fell behind and this many updates were dropped; refetch to catch up.
+    Lagged {
+        missed: u64,
+    },
+    Error {
+        message: String,
+    },
+}
@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// A tenant's own outgoing mail server, used instead of the deployment's for its reports
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TenantMailSettings {
+    pub tenant_id: Uuid,
+    pub smtp_host: String,
+    pub smtp_port: i32,
+    pub smtp_username: Option<String>,
+    #[serde(skip_serializing)]
+    pub smtp_password: Option<String>, // Encrypted; never returned
+    pub from_address: String,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// One task of one run of the maintenance scheduler.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct MaintenanceRun {
+    pub id: Uuid,
+    pub task: String, // ANALYZE, REFRESH_AGGREGATES, PRUNE_SESSIONS or APPLY_RETENTION
+    pub status: MaintenanceRunStatus,
+    pub rows_affected: i64, // Tables analyzed, views refreshed or rows pruned
+    pub detail: Option<JsonValue>, // Nullable JSONB, what the task did
+    pub error_message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+// Stored as the Postgres enum `maintenance_run_status`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(
+    type_name = "maintenance_run_status",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum MaintenanceRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
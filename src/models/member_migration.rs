@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A tenant's members and their roles, as exported for another deployment. Roles are
+/// referred to by name, since IDs differ between deployments.
+#[derive(Debug, Serialize)]
+pub struct MemberExport {
+    pub format_version: u32,
+    pub tenant_id: Uuid,
+    pub exported_at: DateTime<Utc>,
+    pub members: Vec<ExportedMember>,
+}
+
+/// One member of an export, sorted by email.
+#[derive(Debug, Serialize)]
+pub struct ExportedMember {
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub is_active: bool,
+    pub roles: Vec<String>,
+}
+
+/// What an import did, or would do on a dry run, to each member.
+#[derive(Debug, Serialize)]
+pub struct MemberImportReport {
+    pub dry_run: bool,
+    pub applied: bool, // false on a dry run, or when any member has an error
+    pub members: Vec<MemberImportResult>,
+}
+
+/// The outcome for one imported member.
+#[derive(Debug, Serialize)]
+pub struct MemberImportResult {
+    pub email: String,
+    pub action: MemberImportAction,
+    pub roles_added: Vec<String>,
+    pub roles_removed: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MemberImportAction {
+    CreateUser,  // No account with the email yet: one is created without a password
+    AddMember,   // An existing account joins the tenant
+    UpdateRoles, // Already a member; roles changed per the conflict policy
+    Unchanged,   // Already a member with the same roles, or skipped by the policy
+    Error,       // Nothing is imported while any member has an error
+}
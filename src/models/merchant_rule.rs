@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A tenant's own rule; takes precedence over the bundled dataset. Keyed by either a
+/// descriptor pattern or a payee.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct MerchantRule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub pattern: Option<String>, // Cleaned and upper-cased, e.g. "ACME WIDGETS"
+    pub payee_id: Option<Uuid>,  // Instead of a pattern: matches the payee's name
+    pub merchant_name: String,
+    pub category_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// Result of normalizing one bank descriptor.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NormalizedMerchant {
+    pub merchant_name: Option<String>, // None when nothing is left after cleaning
+    pub category_hint: Option<String>, // From the bundled dataset, e.g. "Software"
+    pub category_id: Option<Uuid>, // Tenant rule's category, or the tenant category named like the hint
+}
+
+/// Summary of re-running normalization over staged bank transactions.
+#[derive(Debug, Default, Serialize)]
+pub struct MerchantReapplyResult {
+    pub rows_examined: usize,
+    pub rows_updated: usize,
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Option<Uuid>,
+    pub priority: String, // Consider an enum here: NotificationPriority
+    pub subject: String,
+    pub body: String,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub delivery_attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct NotificationPreference {
+    pub user_id: Uuid,
+    pub timezone: String, // IANA time zone name
+    pub quiet_hours_start: Option<NaiveTime>,
+    pub quiet_hours_end: Option<NaiveTime>,
+    pub digest_frequency: String, // Consider an enum here: DigestFrequency
+    pub email_enabled: bool,
+    pub last_digest_sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Enum for notification priority for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NotificationPriority {
+    Low,      // Batched into the user's digest
+    Normal,   // Sent right away outside quiet hours
+    Critical, // Always sent right away
+}
+
+impl std::str::FromStr for NotificationPriority {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LOW" => Ok(NotificationPriority::Low),
+            "NORMAL" => Ok(NotificationPriority::Normal),
+            "CRITICAL" => Ok(NotificationPriority::Critical),
+            _ => Err(format!("'{}' is not a valid NotificationPriority", s)),
+        }
+    }
+}
+
+impl From<NotificationPriority> for String {
+    fn from(priority: NotificationPriority) -> Self {
+        match priority {
+            NotificationPriority::Low => "LOW".to_string(),
+            NotificationPriority::Normal => "NORMAL".to_string(),
+            NotificationPriority::Critical => "CRITICAL".to_string(),
+        }
+    }
+}
+
+// Enum for digest frequency for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DigestFrequency {
+    Hourly,
+    Daily,
+}
+
+impl DigestFrequency {
+    /// Minimum time between two digest emails.
+    pub fn interval(self) -> chrono::Duration {
+        match self {
+            DigestFrequency::Hourly => chrono::Duration::hours(1),
+            DigestFrequency::Daily => chrono::Duration::days(1),
+        }
+    }
+}
+
+impl std::str::FromStr for DigestFrequency {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "HOURLY" => Ok(DigestFrequency::Hourly),
+            "DAILY" => Ok(DigestFrequency::Daily),
+            _ => Err(format!("'{}' is not a valid DigestFrequency", s)),
+        }
+    }
+}
+
+impl From<DigestFrequency> for String {
+    fn from(frequency: DigestFrequency) -> Self {
+        match frequency {
+            DigestFrequency::Hourly => "HOURLY".to_string(),
+            DigestFrequency::Daily => "DAILY".to_string(),
+        }
+    }
+}
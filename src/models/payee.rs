@@ -0,0 +1,45 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Who a transaction was paid to or received from, e.g. a shop or a landlord.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Payee {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String, // Unique per tenant, ignoring case
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// What `POST /payees/:id/merge-into/:target` moved from the source to the target.
+#[derive(Debug, Serialize)]
+pub struct PayeeMergeResult {
+    pub target: Payee, // The source is deleted
+    pub transactions_moved: u64,
+    pub merchant_rules_moved: u64,
+    pub merchant_rules_dropped: u64, // The target already had a rule of its own
+}
+
+/// One payee's expenses in one currency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayeeSpendingRow {
+    pub payee_id: Option<Uuid>, // None for expenses without a payee
+    pub payee_name: Option<String>,
+    pub currency_code: String,
+    pub transaction_count: i64,
+    pub total: Decimal,
+}
+
+/// Expenses over a period split per payee, largest first.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayeeSpending {
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub payees: Vec<PayeeSpendingRow>,
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Money received from a customer or sent to a vendor, allocated across their invoices or
+/// bills. What is not allocated is a credit that can be applied later.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Payment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub direction: String,         // Consider an enum here: PaymentDirection
+    pub customer_id: Option<Uuid>, // For RECEIVED payments
+    pub vendor_id: Option<Uuid>,   // For SENT payments
+    pub payment_date: NaiveDate,
+    pub amount: Decimal,
+    pub currency_code: String,
+    pub account_id: Uuid, // Deposited to or paid from
+    pub credit_account_id: Option<Uuid>,
+    pub unapplied_amount: Decimal,
+    pub transaction_id: Uuid,
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// Part of a payment applied to one invoice or bill.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct PaymentAllocation {
+    pub id: Uuid,          // The invoice or bill payment row
+    pub document_id: Uuid, // Invoice for RECEIVED payments, bill for SENT ones
+    pub document_number: Option<String>,
+    pub amount: Decimal,
+    pub applied_on: NaiveDate,
+    pub transaction_id: Uuid, // The payment's, or the one that applied its credit
+}
+
+/// A payment with its allocations.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentDetail {
+    #[serde(flatten)]
+    pub payment: Payment,
+    pub allocations: Vec<PaymentAllocation>,
+}
+
+// Enum for which way the money went
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentDirection {
+    Received, // From a customer, against invoices
+    Sent,     // To a vendor, against bills
+}
+
+impl std::str::FromStr for PaymentDirection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "RECEIVED" => Ok(PaymentDirection::Received),
+            "SENT" => Ok(PaymentDirection::Sent),
+            _ => Err(format!("'{}' is not a valid PaymentDirection", s)),
+        }
+    }
+}
+
+impl From<PaymentDirection> for String {
+    fn from(direction: PaymentDirection) -> Self {
+        match direction {
+            PaymentDirection::Received => "RECEIVED".to_string(),
+            PaymentDirection::Sent => "SENT".to_string(),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A tenant's privacy mode state.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrivacySettings {
+    pub tenant_id: Uuid,
+    pub privacy_mode: bool, // New descriptions and memos are stored encrypted
+    pub sealed_transactions: i64, // Transactions whose description is currently encrypted
+}
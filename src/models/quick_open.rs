@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// What a quick-open match points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum QuickOpenKind {
+    Account,
+    Category,
+    Transaction,
+    Report,
+}
+
+// One command-palette match
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuickOpenItem {
+    pub kind: QuickOpenKind,
+    pub id: Option<Uuid>, // None for built-in reports
+    pub title: String,
+    pub subtitle: Option<String>, // e.g. account code, category type, transaction date
+    pub path: String,             // API path of the matched resource, e.g. "/accounts/{id}"
+    pub score: f64,               // Higher is better
+}
@@ -0,0 +1,270 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid; // For JSONB
+
+use crate::models::{journal_entry::JournalEntryType, transaction::TransactionType};
+
+/// A recurring definition. `description`, `notes` and journal memos may contain
+/// placeholders rendered per occurrence (see `services::recurring_template`).
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct RecurringTransaction {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub description: String,
+    pub r#type: TransactionType,   // 'type' is a Rust keyword
+    pub category_id: Option<Uuid>, // Nullable
+    pub account_id: Option<Uuid>,  // Nullable for JOURNAL_ENTRY templates
+    pub amount: Decimal,           // NUMERIC(18,2)
+    pub currency_code: String,
+    pub frequency_value: i32,
+    pub frequency_unit: String, // Consider an enum here: RecurringFrequencyUnit
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>, // Nullable for indefinite recurrence
+    pub last_generated_date: Option<NaiveDate>,
+    pub next_due_date: Option<NaiveDate>,
+    pub is_active: bool,
+    pub notes: Option<String>,                // Nullable
+    pub journal_template: Option<JsonValue>,  // Nullable JSONB, array of RecurringJournalLine
+    pub escalation_percent: Option<Decimal>,  // Yearly raise, e.g. 3.0 for +3%
+    pub escalation_month: Option<i16>,        // 1-12; set together with escalation_percent
+    pub escalated_through: Option<NaiveDate>, // Escalations up to this date are in `amount`
+    pub paused_at: Option<DateTime<Utc>>,     // Set while paused; no occurrences are generated
+    pub business_day_rule: BusinessDayRule,   // What happens to occurrences on non-business days
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// A single leg of a standing journal entry template, stored in `journal_template`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecurringJournalLine {
+    pub account_id: Uuid,
+    pub entry_type: JournalEntryType,
+    pub amount: Decimal,
+    pub memo: Option<String>,
+}
+
+/// One date of a recurring series, as listed by `GET /recurring-transactions/:id/occurrences`.
+#[derive(Debug, Serialize)]
+pub struct RecurringOccurrence {
+    pub occurrence_date: NaiveDate,
+    pub status: RecurringOccurrenceStatus,
+    pub transaction_id: Option<Uuid>,    // Set once materialized
+    pub posting_date: Option<NaiveDate>, // Transaction date after business-day adjustment; None when the rule skips it
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RecurringOccurrenceStatus {
+    Materialized,
+    Skipped,
+    Upcoming,
+}
+
+// Stored as the Postgres enum `business_day_rule`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Default, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "business_day_rule", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BusinessDayRule {
+    #[default]
+    None, // Post on the scheduled date
+    Previous,     // Post on the last business day before
+    Next,         // Post on the first business day after
+    ModifiedNext, // As Next, unless that is in the following month; then as Previous
+    Skip,         // Do not post occurrences that fall on a non-business day
+}
+
+// Enum for frequency_unit for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RecurringFrequencyUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl RecurringFrequencyUnit {
+    /// Returns the `n`th date of a series that starts on `start` and repeats every `value`
+    /// units; `n = 0` is `start`.
+    ///
+    /// Every date is counted from the start rather than from the previous date, so a series
+    /// starting on Jan 31 runs Feb 28/29, Mar 31, Apr 30 instead of settling on the 28th.
+    pub fn nth(self, start: NaiveDate, value: i32, n: u32) -> NaiveDate {
+        let steps = (value.max(1) as u32).checked_mul(n);
+        let date = match self {
+            RecurringFrequencyUnit::Day => {
+                steps.and_then(|days| start.checked_add_days(Days::new(days as u64)))
+            }
+            RecurringFrequencyUnit::Week => steps
+                .and_then(|weeks| weeks.checked_mul(7))
+                .and_then(|days| start.checked_add_days(Days::new(days as u64))),
+            RecurringFrequencyUnit::Month => {
+                steps.and_then(|months| start.checked_add_months(Months::new(months)))
+            }
+            RecurringFrequencyUnit::Year => steps
+                .and_then(|years| years.checked_mul(12))
+                .and_then(|months| start.checked_add_months(Months::new(months))),
+        };
+        date.unwrap_or(NaiveDate::MAX)
+    }
+
+    /// Returns the first date of the series (see `nth`) after `date`.
+    pub fn next_after(self, start: NaiveDate, value: i32, date: NaiveDate) -> NaiveDate {
+        if date < start {
+            return start;
+        }
+        let value = value.max(1) as i64;
+        // The estimate is never past the answer; the loop closes the gap of at most two steps
+        let mut n = match self {
+            RecurringFrequencyUnit::Day => (date - start).num_days() / value,
+            RecurringFrequencyUnit::Week => (date - start).num_days() / (7 * value),
+            RecurringFrequencyUnit::Month | RecurringFrequencyUnit::Year => {
+                let months = (date.year() - start.year()) as i64 * 12 + date.month() as i64
+                    - start.month() as i64;
+                let step = if self == RecurringFrequencyUnit::Year {
+                    12 * value
+                } else {
+                    value
+                };
+                months / step
+            }
+        };
+        loop {
+            let Ok(index) = u32::try_from(n) else {
+                return NaiveDate::MAX;
+            };
+            let next = self.nth(start, value as i32, index);
+            if next > date || next == NaiveDate::MAX {
+                return next;
+            }
+            n += 1;
+        }
+    }
+}
+
+// Implement FromStr, sqlx::Type, Decode, Encode for RecurringFrequencyUnit similarly
+impl std::str::FromStr for RecurringFrequencyUnit {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DAY" => Ok(RecurringFrequencyUnit::Day),
+            "WEEK" => Ok(RecurringFrequencyUnit::Week),
+            "MONTH" => Ok(RecurringFrequencyUnit::Month),
+            "YEAR" => Ok(RecurringFrequencyUnit::Year),
+            _ => Err(format!("'{}' is not a valid RecurringFrequencyUnit", s)),
+        }
+    }
+}
+
+impl From<RecurringFrequencyUnit> for String {
+    fn from(unit: RecurringFrequencyUnit) -> Self {
+        match unit {
+            RecurringFrequencyUnit::Day => "DAY".to_string(),
+            RecurringFrequencyUnit::Week => "WEEK".to_string(),
+            RecurringFrequencyUnit::Month => "MONTH".to_string(),
+            RecurringFrequencyUnit::Year => "YEAR".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecurringFrequencyUnit::{self, *};
+    use chrono::NaiveDate;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    /// The first `count` dates of a series, stepping with `next_after` like the scheduler does.
+    fn series(
+        unit: RecurringFrequencyUnit,
+        start: &str,
+        value: i32,
+        count: usize,
+    ) -> Vec<NaiveDate> {
+        let start = date(start);
+        std::iter::successors(Some(start), |due| Some(unit.next_after(start, value, *due)))
+            .take(count)
+            .collect()
+    }
+
+    #[test]
+    fn month_end_start_returns_to_month_end() {
+        assert_eq!(
+            series(Month, "2025-01-31", 1, 5),
+            [
+                "2025-01-31",
+                "2025-02-28",
+                "2025-03-31",
+                "2025-04-30",
+                "2025-05-31"
+            ]
+            .map(date)
+        );
+        assert_eq!(
+            series(Month, "2024-01-31", 1, 3),
+            ["2024-01-31", "2024-02-29", "2024-03-31"].map(date)
+        );
+    }
+
+    #[test]
+    fn quarterly_month_end_start_keeps_its_day() {
+        assert_eq!(
+            series(Month, "2025-11-30", 3, 4),
+            ["2025-11-30", "2026-02-28", "2026-05-30", "2026-08-30"].map(date)
+        );
+    }
+
+    #[test]
+    fn leap_day_start_returns_to_leap_days() {
+        assert_eq!(
+            series(Year, "2024-02-29", 1, 5),
+            [
+                "2024-02-29",
+                "2025-02-28",
+                "2026-02-28",
+                "2027-02-28",
+                "2028-02-29"
+            ]
+            .map(date)
+        );
+        assert_eq!(Year.nth(date("2024-02-29"), 1, 4), date("2028-02-29"));
+    }
+
+    #[test]
+    fn day_and_week_steps_count_from_the_start() {
+        assert_eq!(
+            series(Day, "2025-02-27", 2, 3),
+            ["2025-02-27", "2025-03-01", "2025-03-03"].map(date)
+        );
+        assert_eq!(
+            series(Week, "2025-12-29", 2, 3),
+            ["2025-12-29", "2026-01-12", "2026-01-26"].map(date)
+        );
+    }
+
+    #[test]
+    fn next_after_an_off_schedule_date_returns_to_the_schedule() {
+        let start = date("2025-01-31");
+        assert_eq!(
+            Month.next_after(start, 1, date("2025-03-15")),
+            date("2025-03-31")
+        );
+        assert_eq!(
+            Month.next_after(start, 1, date("2025-03-31")),
+            date("2025-04-30")
+        );
+        assert_eq!(Month.next_after(start, 1, date("2024-12-01")), start);
+        assert_eq!(
+            Week.next_after(date("2025-01-01"), 1, date("2025-01-09")),
+            date("2025-01-15")
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// An expense marked as to be paid back by a third party.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ReimbursableExpense {
+    pub transaction_id: Uuid,
+    pub tenant_id: Uuid,
+    pub payer: String, // Who is expected to reimburse, e.g. "Acme Corp"
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// Part of a deposit applied to a reimbursable expense.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ReimbursementMatch {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub expense_transaction_id: Uuid,
+    pub deposit_transaction_id: Uuid,
+    pub amount: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+/// A reimbursable expense with what has been paid back so far.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct OutstandingReimbursable {
+    pub transaction_id: Uuid,
+    pub transaction_date: NaiveDate,
+    pub description: String,
+    pub payer: String,
+    pub currency_code: String,
+    pub amount: Decimal,
+    pub reimbursed: Decimal,
+    pub outstanding: Decimal,
+}
+
+/// Outstanding reimbursables of one payer in one currency.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayerOutstanding {
+    pub payer: String,
+    pub currency_code: String,
+    pub expense_count: i64,
+    pub amount: Decimal,
+    pub reimbursed: Decimal,
+    pub outstanding: Decimal,
+    pub expenses: Vec<OutstandingReimbursable>, // Oldest first
+}
+
+/// Reimbursable expenses not yet fully paid back, totaled by payer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutstandingReimbursablesReport {
+    pub as_of: NaiveDate,
+    pub payers: Vec<PayerOutstanding>,
+}
@@ -0,0 +1,192 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{dimension::DimensionFilter, journal_entry::JournalEntryType};
+
+// Financial statements produced by the report engine
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StatementType {
+    TrialBalance,
+    IncomeStatement,
+    BalanceSheet,
+}
+
+impl std::str::FromStr for StatementType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "TRIAL_BALANCE" => Ok(StatementType::TrialBalance),
+            "INCOME_STATEMENT" => Ok(StatementType::IncomeStatement),
+            "BALANCE_SHEET" => Ok(StatementType::BalanceSheet),
+            _ => Err(format!("'{}' is not a valid StatementType", s)),
+        }
+    }
+}
+
+impl From<StatementType> for String {
+    fn from(statement_type: StatementType) -> Self {
+        match statement_type {
+            StatementType::TrialBalance => "TRIAL_BALANCE".to_string(),
+            StatementType::IncomeStatement => "INCOME_STATEMENT".to_string(),
+            StatementType::BalanceSheet => "BALANCE_SHEET".to_string(),
+        }
+    }
+}
+
+/// Link from a report cell back to the journal entries that make up its amount.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DrilldownLink {
+    pub token: String,
+    pub href: String, // e.g., /api/v1/reports/drilldown/<token>
+}
+
+/// Filter encoded in a drill-down token. Resolving it returns every journal entry
+/// whose signed amounts sum to the originating report cell.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DrilldownFilter {
+    #[serde(rename = "t")]
+    pub tenant_id: Uuid,
+    #[serde(rename = "s")]
+    pub scope: DrilldownScope,
+    #[serde(rename = "f", default, skip_serializing_if = "Option::is_none")]
+    pub from_date: Option<NaiveDate>, // None = from the beginning of the ledger
+    #[serde(rename = "u")]
+    pub to_date: NaiveDate,
+    #[serde(
+        rename = "d",
+        default,
+        skip_serializing_if = "DimensionFilter::is_empty"
+    )]
+    pub dimensions: DimensionFilter, // Empty = entries with or without dimensions
+}
+
+/// The accounts behind a drill-down. Totals reference their accounts by type or layout row
+/// rather than listing them, so links stay short however large the chart of accounts grows; the
+/// accounts are looked up when the link is resolved.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum DrilldownScope {
+    #[serde(rename = "a")]
+    Account(Uuid),
+    #[serde(rename = "ty")]
+    AccountTypes(Vec<String>), // Every account of these types, matched case-insensitively
+    #[serde(rename = "all")]
+    AllAccounts,
+    #[serde(rename = "lr")]
+    LayoutRow {
+        #[serde(rename = "l")]
+        layout_id: Uuid,
+        #[serde(rename = "r")]
+        row_key: String, // The accounts the row selects, or a subtotal's terms select
+    },
+}
+
+/// A single row of a statement (an account, a subtotal or a net figure).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatementLine {
+    pub account_id: Option<Uuid>, // None for subtotal/total rows
+    pub account_code: Option<String>,
+    pub label: String,
+    pub amount: Decimal, // Signed by the account's normal balance
+    pub drilldown: DrilldownLink,
+}
+
+/// An ordered group of statement lines with its subtotal.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatementSection {
+    pub title: String,
+    pub lines: Vec<StatementLine>,
+    pub total: StatementLine,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FinancialStatement {
+    pub statement_type: StatementType,
+    pub tenant_id: Uuid,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: NaiveDate,
+    pub sections: Vec<StatementSection>,
+    pub net: Option<StatementLine>, // Net income, or assets less liabilities and equity
+}
+
+/// A journal entry returned when resolving a drill-down token.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DrilldownEntry {
+    pub journal_entry_id: Uuid,
+    pub transaction_id: Uuid,
+    pub transaction_date: NaiveDate,
+    pub description: String,
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub entry_type: JournalEntryType,
+    pub amount: Decimal, // In the account's currency
+    pub currency_code: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DrilldownResult {
+    pub filter: DrilldownFilter,
+    pub total_debits: Decimal,
+    pub total_credits: Decimal,
+    pub entries: Vec<DrilldownEntry>,
+}
+
+/// Length of the periods a spend report is grouped into.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SpendGranularity {
+    #[default]
+    Month,
+    Quarter,
+}
+
+impl SpendGranularity {
+    /// The `date_trunc` field for this granularity.
+    pub fn as_date_trunc(self) -> &'static str {
+        match self {
+            SpendGranularity::Month => "month",
+            SpendGranularity::Quarter => "quarter",
+        }
+    }
+
+    /// Number of months in one period.
+    pub fn months(self) -> u32 {
+        match self {
+            SpendGranularity::Month => 1,
+            SpendGranularity::Quarter => 3,
+        }
+    }
+}
+
+/// Spend on one category within a period.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategorySpendLine {
+    pub category_id: Option<Uuid>, // None = uncategorized
+    pub category_name: String,
+    pub amount: Decimal, // In the tenant's base currency
+    pub transaction_count: i64,
+}
+
+/// One month or quarter of a spend report; categories are ordered by amount, largest first.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategorySpendPeriod {
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate, // Last day of the month or quarter
+    pub total: Decimal,
+    pub categories: Vec<CategorySpendLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategorySpendReport {
+    pub tenant_id: Uuid,
+    pub from_date: NaiveDate,
+    pub to_date: NaiveDate,
+    pub granularity: SpendGranularity,
+    pub base_currency_code: String,
+    pub total: Decimal,
+    pub periods: Vec<CategorySpendPeriod>, // Every period in the range, including empty ones
+}
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct ReportSchedule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub custom_report_id: Uuid,
+    pub cron_expression: String, // Standard 5-field cron (minute hour day-of-month month day-of-week)
+    pub timezone: String,        // IANA time zone name the expression is evaluated in
+    pub period: String,          // Consider an enum here: ReportPeriod
+    pub recipients: Vec<String>, // Email addresses
+    pub is_active: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub attachment_format: String, // Consider an enum here: AttachmentFormat
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Date range a scheduled report covers, relative to the day it runs
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReportPeriod {
+    PreviousMonth, // The whole calendar month before the run (monthly reports)
+    MonthToDate,
+    YearToDate,
+}
+
+impl ReportPeriod {
+    /// Inclusive `(from, to)` dates for a run on `today`.
+    pub fn date_range(self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let month_start = today.with_day(1).expect("day 1 is always valid");
+        match self {
+            ReportPeriod::PreviousMonth => {
+                let last_month_end = month_start.pred_opt().expect("date is in range");
+                (
+                    last_month_end.with_day(1).expect("day 1 is always valid"),
+                    last_month_end,
+                )
+            }
+            ReportPeriod::MonthToDate => (month_start, today),
+            ReportPeriod::YearToDate => (
+                NaiveDate::from_ymd_opt(today.year(), 1, 1).expect("January 1st is always valid"),
+                today,
+            ),
+        }
+    }
+}
+
+impl std::str::FromStr for ReportPeriod {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PREVIOUS_MONTH" => Ok(ReportPeriod::PreviousMonth),
+            "MONTH_TO_DATE" => Ok(ReportPeriod::MonthToDate),
+            "YEAR_TO_DATE" => Ok(ReportPeriod::YearToDate),
+            _ => Err(format!("'{}' is not a valid ReportPeriod", s)),
+        }
+    }
+}
+
+impl From<ReportPeriod> for String {
+    fn from(period: ReportPeriod) -> Self {
+        match period {
+            ReportPeriod::PreviousMonth => "PREVIOUS_MONTH".to_string(),
+            ReportPeriod::MonthToDate => "MONTH_TO_DATE".to_string(),
+            ReportPeriod::YearToDate => "YEAR_TO_DATE".to_string(),
+        }
+    }
+}
+
+// File the report is attached as; PDF is offered for statement reports only
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AttachmentFormat {
+    Csv,
+    Pdf,
+}
+
+impl std::str::FromStr for AttachmentFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "CSV" => Ok(AttachmentFormat::Csv),
+            "PDF" => Ok(AttachmentFormat::Pdf),
+            _ => Err(format!("'{}' is not a valid AttachmentFormat", s)),
+        }
+    }
+}
+
+impl From<AttachmentFormat> for String {
+    fn from(format: AttachmentFormat) -> Self {
+        match format {
+            AttachmentFormat::Csv => "CSV".to_string(),
+            AttachmentFormat::Pdf => "PDF".to_string(),
+        }
+    }
+}
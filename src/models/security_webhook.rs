@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct SecurityWebhook {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub signing_secret: String, // Encrypted; only returned in plain text when created or rotated
+    pub event_types: Vec<String>, // Opted-in SecurityEventType values
+    pub is_active: bool,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub last_delivery_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Returned when a signing secret is generated; the secret is not retrievable afterwards
+#[derive(Debug, Serialize)]
+pub struct SecurityWebhookWithSecret {
+    #[serde(flatten)]
+    pub webhook: SecurityWebhook,
+    pub signing_secret: Option<String>,
+}
+
+// Body POSTed to the tenant's endpoint
+#[derive(Debug, Serialize)]
+pub struct SecurityEventPayload {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: SecurityEventType,
+    pub tenant_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub actor_user_id: Option<Uuid>,
+    pub data: serde_json::Value,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub test: bool, // Synthetic event sent by a test endpoint
+}
+
+// Enum for security event types for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SecurityEventType {
+    MemberAdded,
+    RoleChanged,
+    ApiKeyCreated,
+    RepeatedFailedLogins,
+    Test, // Sent by the test endpoint regardless of opt-ins
+}
+
+impl std::str::FromStr for SecurityEventType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "MEMBER_ADDED" => Ok(SecurityEventType::MemberAdded),
+            "ROLE_CHANGED" => Ok(SecurityEventType::RoleChanged),
+            "API_KEY_CREATED" => Ok(SecurityEventType::ApiKeyCreated),
+            "REPEATED_FAILED_LOGINS" => Ok(SecurityEventType::RepeatedFailedLogins),
+            "TEST" => Ok(SecurityEventType::Test),
+            _ => Err(format!("'{}' is not a valid SecurityEventType", s)),
+        }
+    }
+}
+
+impl From<SecurityEventType> for String {
+    fn from(event_type: SecurityEventType) -> Self {
+        match event_type {
+            SecurityEventType::MemberAdded => "MEMBER_ADDED".to_string(),
+            SecurityEventType::RoleChanged => "ROLE_CHANGED".to_string(),
+            SecurityEventType::ApiKeyCreated => "API_KEY_CREATED".to_string(),
+            SecurityEventType::RepeatedFailedLogins => "REPEATED_FAILED_LOGINS".to_string(),
+            SecurityEventType::Test => "TEST".to_string(),
+        }
+    }
+}
+
+impl SecurityEventType {
+    /// Every event type, in catalog order.
+    pub const ALL: [SecurityEventType; 5] = [
+        SecurityEventType::MemberAdded,
+        SecurityEventType::RoleChanged,
+        SecurityEventType::ApiKeyCreated,
+        SecurityEventType::RepeatedFailedLogins,
+        SecurityEventType::Test,
+    ];
+}
+
+// Catalog entry describing one event type and the JSON body delivered for it
+#[derive(Debug, Serialize)]
+pub struct SecurityEventTypeInfo {
+    #[serde(rename = "type")]
+    pub event_type: SecurityEventType,
+    pub description: &'static str,
+    pub schema: serde_json::Value, // JSON Schema of the whole request body
+    pub example: serde_json::Value, // The body `POST /webhooks/:id/test` sends
+}
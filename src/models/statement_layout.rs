@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid; // For JSONB
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct StatementLayout {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub statement_type: String, // Consider an enum here: StatementType
+    pub definition: JsonValue,  // JSONB, see StatementLayoutDefinition
+    pub is_default: bool,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+/// Typed form of `statement_layouts.definition`: rows rendered top to bottom.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatementLayoutDefinition {
+    pub rows: Vec<LayoutRow>,
+}
+
+/// A layout row is either a group of accounts or a subtotal over earlier rows.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LayoutRow {
+    Group(LayoutGroup),
+    Subtotal(LayoutSubtotal),
+}
+
+/// Accounts selected into a group. An account matching several groups is placed in the first one.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LayoutGroup {
+    pub key: String, // Referenced by subtotal terms
+    pub title: String,
+    #[serde(default)]
+    pub account_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub account_types: Vec<String>, // Account type names, e.g., 'Revenue'
+    #[serde(default)]
+    pub account_code_prefixes: Vec<String>, // e.g., '5' for all 5xxx cost-of-sales accounts
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LayoutSubtotal {
+    pub key: String,
+    pub title: String,
+    pub terms: Vec<SubtotalTerm>,
+}
+
+/// A reference to an earlier row, added or subtracted, e.g. revenue minus cost of sales.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubtotalTerm {
+    pub key: String,
+    #[serde(default)]
+    pub subtract: bool,
+}
@@ -16,3 +16,16 @@ pub struct Tenant {
     pub updated_at: DateTime<Utc>,
     pub updated_by: Uuid,
 }
+
+// The tenant's logo, printed in the header of PDF statements
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TenantLogo {
+    pub tenant_id: Uuid,
+    pub content_type: String, // image/png or image/jpeg
+    pub width: i32,           // Pixels
+    pub height: i32,
+    #[serde(skip)]
+    pub image: Vec<u8>, // Served by GET /tenants/:id/logo, not in JSON
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
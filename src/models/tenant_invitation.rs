@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// An invitation to join a tenant with a role. The token is only ever sent by email.
+#[derive(Debug, Serialize)]
+pub struct TenantInvitation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    pub role_id: Uuid,
+    pub role_name: String,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub accepted_by: Option<Uuid>, // The user who joined
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+}
+
+/// The membership created by accepting an invitation.
+#[derive(Debug, Serialize)]
+pub struct AcceptedInvitation {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+    pub created_user: bool, // false when an existing account was linked
+}
@@ -11,8 +11,9 @@ pub struct Transaction {
     pub tenant_id: Uuid,
     pub transaction_date: NaiveDate,
     pub description: String,
-    pub r#type: String,               // 'type' is a Rust keyword
+    pub r#type: TransactionType,      // 'type' is a Rust keyword
     pub category_id: Option<Uuid>,    // Nullable
+    pub payee_id: Option<Uuid>,       // Who was paid, or who paid; the description stays free text
     pub tags_json: Option<JsonValue>, // Nullable for JSONB
     pub amount: Decimal,              // NUMERIC(18,2)
     pub currency_code: String,
@@ -20,15 +21,27 @@ pub struct Transaction {
     pub reconciliation_date: Option<NaiveDate>, // Nullable
     pub notes: Option<String>,                  // Nullable
     pub source_document_url: Option<String>,    // Nullable
+    pub reversal_of_id: Option<Uuid>,           // Set on a reversal: the transaction it offsets
+    pub reversed_by_id: Option<Uuid>,           // Set on a reversed transaction: its reversal
+    pub recurring_transaction_id: Option<Uuid>, // Set on an occurrence: its recurring definition
+    pub recurring_occurrence_date: Option<NaiveDate>, // The scheduled date of that occurrence
+    pub status: String,                         // Consider an enum here: TransactionStatus
+    pub posted_at: Option<DateTime<Utc>>,
+    pub posted_by: Option<Uuid>,
+    pub voided_at: Option<DateTime<Utc>>,
+    pub voided_by: Option<Uuid>,
+    pub void_reason: Option<String>,
     pub created_at: DateTime<Utc>,
     pub created_by: Uuid,
     pub updated_at: DateTime<Utc>,
     pub updated_by: Uuid,
+    pub version: i32, // Bumped by every change; updates send it back in If-Match
 }
 
-// Optional: Enum for transaction_type for better type safety
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+// Stored as the Postgres enum `transaction_type`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "transaction_type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TransactionType {
     Income,
     Expense,
@@ -38,7 +51,6 @@ pub enum TransactionType {
     Adjustment,
 }
 
-// Implement FromStr, sqlx::Type, Decode, Encode for TransactionType similarly
 impl std::str::FromStr for TransactionType {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -67,21 +79,50 @@ impl From<TransactionType> for String {
     }
 }
 
-impl sqlx::Type<sqlx::Postgres> for TransactionType {
-    fn type_info() -> sqlx::postgres::PgTypeInfo {
-        <String as sqlx::Type<sqlx::Postgres>>::type_info()
+// Enum for the transaction workflow status.
+// DRAFT -> PENDING_APPROVAL -> POSTED -> VOIDED; DRAFT may also be posted directly and
+// PENDING_APPROVAL may be returned to DRAFT. Only DRAFT transactions have editable journal entries.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransactionStatus {
+    Draft,
+    PendingApproval,
+    Posted,
+    Voided,
+}
+
+impl TransactionStatus {
+    /// Whether the workflow allows moving from `self` to `next`.
+    pub fn can_transition_to(self, next: TransactionStatus) -> bool {
+        use TransactionStatus::*;
+        matches!(
+            (self, next),
+            (Draft, PendingApproval) | (Draft, Posted) | (PendingApproval, Posted) | (PendingApproval, Draft) | (Posted, Voided)
+        )
     }
 }
 
-impl<'r> sqlx::Decode<'r, sqlx::Postgres> for TransactionType {
-    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
-        let s = <String as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
-        s.parse().map_err(Into::into)
+impl std::str::FromStr for TransactionStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DRAFT" => Ok(TransactionStatus::Draft),
+            "PENDING_APPROVAL" => Ok(TransactionStatus::PendingApproval),
+            "POSTED" => Ok(TransactionStatus::Posted),
+            "VOIDED" => Ok(TransactionStatus::Voided),
+            _ => Err(format!("'{}' is not a valid TransactionStatus", s)),
+        }
     }
 }
 
-impl<'q> sqlx::Encode<'q, sqlx::Postgres> for TransactionType {
-    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
-        <String as sqlx::Encode<sqlx::Postgres>>::encode_by_ref(&self.to_string(), buf)
+impl From<TransactionStatus> for String {
+    fn from(status: TransactionStatus) -> Self {
+        match status {
+            TransactionStatus::Draft => "DRAFT".to_string(),
+            TransactionStatus::PendingApproval => "PENDING_APPROVAL".to_string(),
+            TransactionStatus::Posted => "POSTED".to_string(),
+            TransactionStatus::Voided => "VOIDED".to_string(),
+        }
     }
 }
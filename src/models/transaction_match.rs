@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid; // For JSONB
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TransactionMatch {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub staging_id: Uuid,
+    pub transaction_id: Uuid,
+    pub score: Decimal,             // 0..1, higher is a stronger match
+    pub status: String,             // Consider an enum here: MatchStatus
+    pub reasons: Option<JsonValue>, // Nullable JSONB, per-signal scores
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+    pub updated_by: Uuid,
+}
+
+// Enum for match proposal status for better type safety
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "varchar", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MatchStatus {
+    Proposed,
+    Accepted,
+    Rejected,
+}
+
+impl std::str::FromStr for MatchStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PROPOSED" => Ok(MatchStatus::Proposed),
+            "ACCEPTED" => Ok(MatchStatus::Accepted),
+            "REJECTED" => Ok(MatchStatus::Rejected),
+            _ => Err(format!("'{}' is not a valid MatchStatus", s)),
+        }
+    }
+}
+
+impl From<MatchStatus> for String {
+    fn from(status: MatchStatus) -> Self {
+        match status {
+            MatchStatus::Proposed => "PROPOSED".to_string(),
+            MatchStatus::Accepted => "ACCEPTED".to_string(),
+            MatchStatus::Rejected => "REJECTED".to_string(),
+        }
+    }
+}
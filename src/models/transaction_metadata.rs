@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Client metadata captured when a transaction was entered (e.g. from the mobile app).
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TransactionMetadata {
+    pub transaction_id: Uuid,
+    pub tenant_id: Uuid,
+    pub latitude: Option<f64>, // WGS 84 degrees; set together with longitude
+    pub longitude: Option<f64>,
+    pub device: Option<String>, // Free-form, e.g. "iPhone 15 / iOS 18.1"
+    pub entry_source: Option<String>, // e.g. "MOBILE", "WEB", "API"
+    pub created_at: DateTime<Utc>,
+}
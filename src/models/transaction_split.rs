@@ -0,0 +1,23 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::transaction::Transaction;
+
+/// One category share of a split receipt, booked as its own journal entry.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TransactionSplit {
+    pub journal_entry_id: Uuid,
+    pub category_id: Uuid,
+    pub account_id: Uuid,
+    pub percentage: Option<Decimal>, // As requested; None for fixed-amount shares
+    pub amount: Decimal,             // After rounding; the shares add up to the total
+}
+
+/// A transaction generated from a receipt split, with its shares in request order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SplitTransaction {
+    pub transaction: Transaction,
+    pub splits: Vec<TransactionSplit>,
+}
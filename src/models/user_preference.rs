@@ -0,0 +1,11 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::models::dashboard_widget::AccountBalanceItem;
+
+/// Balances of the current user's pinned accounts, in pin order.
+#[derive(Debug, Serialize)]
+pub struct PinnedAccountBalances {
+    pub as_of: NaiveDate,
+    pub accounts: Vec<AccountBalanceItem>,
+}
@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{
+        auth::TenantContext,
+        precondition::{Conditional, IfMatchVersion, IfNoneMatch},
+        validated_json::ValidatedJson,
+    },
+    models::{
+        account::Account,
+        dto::account_dto::{CreateAccountDto, DeactivateAccountQuery, UpdateAccountDto},
+        dto::archive_dto::{ArchiveUnusedDto, IncludeArchivedQuery, IncludeInactiveQuery},
+    },
+    services::account,
+};
+
+/// Creates a router for the tenant's chart of accounts.
+///
+/// All routes defined here will be nested under `/api/v1/accounts`.
+pub fn account_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_accounts).post(create_account))
+        .route("/archive-unused", post(archive_unused_accounts))
+        .route(
+            "/:id",
+            get(get_account)
+                .put(update_account)
+                .patch(update_account)
+                .delete(deactivate_account),
+        )
+        .route("/:id/archive", post(archive_account))
+        .route("/:id/unarchive", post(unarchive_account))
+        .route("/:id/restore", post(restore_account))
+}
+
+/// GET /accounts?include_archived=&include_inactive=
+/// Lists active accounts, the current user's pinned accounts first; archived ones only with
+/// `include_archived=true`, deactivated ones only with `include_inactive=true` and the
+/// `records.restore` permission.
+async fn list_accounts(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<IncludeArchivedQuery>,
+    Query(inactive): Query<IncludeInactiveQuery>,
+) -> Result<Json<Vec<Account>>, AppError> {
+    info!("Handler: Listing accounts for tenant {}", ctx.tenant_id);
+    let accounts = account::list_accounts(
+        &pool,
+        ctx.tenant_id,
+        ctx.user_id,
+        query.include_archived,
+        inactive.include_inactive,
+    )
+    .await?;
+    Ok(Json(accounts))
+}
+
+/// POST /accounts
+/// Creates a new account.
+async fn create_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateAccountDto>,
+) -> Result<(StatusCode, Json<Account>), AppError> {
+    info!("Handler: Creating account for tenant {}", ctx.tenant_id);
+    let account = account::create_account(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(account)))
+}
+
+/// GET /accounts/:id
+/// Retrieves a single account, or 304 when `If-None-Match` holds its current ETag.
+async fn get_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    if_none_match: IfNoneMatch,
+) -> Result<Conditional<Json<Account>>, AppError> {
+    info!("Handler: Getting account {}", id);
+    let account = account::get_account_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(if_none_match.respond(account.version, Json(account)))
+}
+
+/// PUT|PATCH /accounts/:id
+/// Updates an account as a JSON Merge Patch: absent fields are kept and `null` clears
+/// `account_code` or `description`. Requires `If-Match` with the version last read.
+async fn update_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    IfMatchVersion(expected_version): IfMatchVersion,
+    ValidatedJson(dto): ValidatedJson<UpdateAccountDto>,
+) -> Result<Json<Account>, AppError> {
+    info!("Handler: Updating account {}", id);
+    let account =
+        account::update_account(&pool, ctx.tenant_id, id, ctx.user_id, expected_version, dto)
+            .await?;
+    Ok(Json(account))
+}
+
+/// DELETE /accounts/:id?cascade=
+/// Deactivates an account; one that active budget lines or recurring transactions use only
+/// with `cascade=true`, which deactivates them too. Otherwise they are listed as blockers.
+async fn deactivate_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeactivateAccountQuery>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating account {}", id);
+    account::deactivate_account(&pool, ctx.tenant_id, id, ctx.user_id, query.cascade).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /accounts/:id/archive
+/// Archives an account: still in reports, but hidden from pickers and closed to new postings.
+async fn archive_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Account>, AppError> {
+    info!("Handler: Archiving account {}", id);
+    let account = account::archive_account(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(account))
+}
+
+/// POST /accounts/:id/unarchive
+/// Makes an archived account available again.
+async fn unarchive_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Account>, AppError> {
+    info!("Handler: Unarchiving account {}", id);
+    let account = account::unarchive_account(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(account))
+}
+
+/// POST /accounts/:id/restore
+/// Restores a deactivated account.
+async fn restore_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Account>, AppError> {
+    info!("Handler: Restoring account {}", id);
+    let account = account::restore_account(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(account))
+}
+
+/// POST /accounts/archive-unused
+/// Archives accounts without postings for `unused_for_months` and with a zero balance;
+/// `dry_run` only lists them.
+async fn archive_unused_accounts(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<ArchiveUnusedDto>,
+) -> Result<Json<Vec<Account>>, AppError> {
+    info!("Handler: Archiving unused accounts for tenant {}", ctx.tenant_id);
+    let accounts =
+        account::archive_unused_accounts(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(accounts))
+}
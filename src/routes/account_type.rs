@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::{header, StatusCode},
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::AuthUser, validated_json::ValidatedJson},
+    models::{
+        account_type::AccountType,
+        dto::account_type_dto::{CreateAccountTypeDto, UpdateAccountTypeDto},
+    },
+    services::{account_type, reference_cache},
+};
+
+/// Creates a router for the account types shared by all tenants.
+///
+/// All routes defined here will be nested under `/api/v1/account-types`.
+pub fn account_type_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_account_types).post(create_account_type))
+        .route(
+            "/:id",
+            get(get_account_type)
+                .put(update_account_type)
+                .delete(deactivate_account_type),
+        )
+}
+
+/// GET /account-types
+/// Lists active account types.
+async fn list_account_types(
+    State(pool): State<PgPool>,
+) -> Result<
+    (
+        [(header::HeaderName, &'static str); 1],
+        Json<Vec<AccountType>>,
+    ),
+    AppError,
+> {
+    info!("Handler: Listing account types");
+    let account_types = account_type::list_account_types(&pool).await?;
+    Ok((
+        [(header::CACHE_CONTROL, reference_cache::CACHE_CONTROL)],
+        Json(account_types),
+    ))
+}
+
+/// GET /account-types/:id
+/// Retrieves a single account type.
+async fn get_account_type(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<([(header::HeaderName, &'static str); 1], Json<AccountType>), AppError> {
+    info!("Handler: Getting account type {}", id);
+    let account_type = account_type::get_account_type_by_id(&pool, id).await?;
+    Ok((
+        [(header::CACHE_CONTROL, reference_cache::CACHE_CONTROL)],
+        Json(account_type),
+    ))
+}
+
+/// POST /account-types
+/// Creates an account type (system administrators only).
+async fn create_account_type(
+    State(pool): State<PgPool>,
+    AuthUser {
+        user_id: created_by_user_id,
+    }: AuthUser,
+    ValidatedJson(dto): ValidatedJson<CreateAccountTypeDto>,
+) -> Result<(StatusCode, Json<AccountType>), AppError> {
+    info!("Handler: Creating account type '{}'", dto.name);
+
+    let account_type = account_type::create_account_type(&pool, created_by_user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(account_type)))
+}
+
+/// PUT /account-types/:id
+/// Updates an account type (system administrators only).
+async fn update_account_type(
+    State(pool): State<PgPool>,
+    AuthUser {
+        user_id: updated_by_user_id,
+    }: AuthUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateAccountTypeDto>,
+) -> Result<Json<AccountType>, AppError> {
+    info!("Handler: Updating account type {}", id);
+
+    let account_type =
+        account_type::update_account_type(&pool, id, updated_by_user_id, dto).await?;
+    Ok(Json(account_type))
+}
+
+/// DELETE /account-types/:id
+/// Deactivates an account type (system administrators only).
+async fn deactivate_account_type(
+    State(pool): State<PgPool>,
+    AuthUser {
+        user_id: updated_by_user_id,
+    }: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating account type {}", id);
+
+    account_type::deactivate_account_type(&pool, id, updated_by_user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
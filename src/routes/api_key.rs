@@ -0,0 +1,77 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        api_key::{ApiKey, CreatedApiKey, TenantSandbox},
+        dto::api_key_dto::CreateApiKeyDto,
+    },
+    services::api_key,
+};
+
+/// Creates a router for the tenant's API keys and sandbox.
+///
+/// All routes defined here will be nested under `/api/v1/api-keys`.
+/// Requests authenticate with a key by sending it in the `X-Api-Key` header.
+pub fn api_key_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_api_keys).post(create_api_key))
+        .route("/sandbox", get(get_sandbox))
+        .route("/:id", delete(revoke_api_key))
+}
+
+/// GET /api-keys
+/// Lists the tenant's API keys.
+async fn list_api_keys(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<ApiKey>>, AppError> {
+    info!("Handler: Listing API keys for tenant {}", ctx.tenant_id);
+    let keys = api_key::list_api_keys(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(keys))
+}
+
+/// POST /api-keys
+/// Creates a live or sandbox key; the key is shown only in this response.
+async fn create_api_key(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateApiKeyDto>,
+) -> Result<(StatusCode, Json<CreatedApiKey>), AppError> {
+    info!("Handler: Creating API key for tenant {}", ctx.tenant_id);
+    let key = api_key::create_api_key(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(key)))
+}
+
+/// GET /api-keys/sandbox
+/// The tenant's sandbox, which sandbox keys operate on.
+async fn get_sandbox(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<TenantSandbox>, AppError> {
+    info!("Handler: Getting sandbox for tenant {}", ctx.tenant_id);
+    let sandbox = api_key::get_sandbox(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(sandbox))
+}
+
+/// DELETE /api-keys/:id
+/// Revokes an API key.
+async fn revoke_api_key(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Revoking API key {}", id);
+    api_key::revoke_api_key(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Path, State},
+    routing::post,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, field_policy::Redacted, validated_json::ValidatedJson},
+    models::{assistant::AssistantAnswer, dto::assistant_dto::AskQuestionDto},
+    services::{assistant, field_policy::FieldAccess},
+};
+
+/// Creates a router for natural-language questions.
+///
+/// All routes defined here will be nested under `/api/v1/tenants`.
+pub fn assistant_routes() -> Router<AppState> {
+    Router::new().route("/:id/ask", post(ask))
+}
+
+/// POST /tenants/:id/ask
+/// Answers a question about the tenant's books, returning the answer, the analytics query
+/// it was translated into and that query's result. 503 when no assistant is configured.
+async fn ask(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(tenant_id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<AskQuestionDto>,
+) -> Result<Redacted<AssistantAnswer>, AppError> {
+    info!("Handler: Answering a question for tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    let answer = assistant::ask(&pool, ctx.tenant_id, dto).await?;
+    Ok(Redacted(answer, access))
+}
@@ -0,0 +1,139 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::Redirect,
+    routing::{delete, get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::AuthUser, rate_limiting::client_ip, validated_json::ValidatedJson},
+    models::{
+        auth_session::{AuthSession, SessionTokens},
+        dto::auth_dto::{LoginRequest, OidcCallbackQuery, RefreshSessionRequest},
+    },
+    services::{
+        auth,
+        oidc::{self, OidcProvider},
+    },
+};
+
+/// Creates a router for signing in and managing login sessions.
+///
+/// All routes defined here will be nested under `/api/v1/auth`.
+pub fn auth_routes() -> Router<AppState> {
+    Router::new()
+        .route("/login", post(login))
+        .route("/refresh", post(refresh))
+        .route("/oidc/:provider/authorize", get(oidc_authorize))
+        .route("/oidc/:provider/callback", get(oidc_callback))
+        .route("/sessions", get(list_sessions).delete(revoke_all_sessions))
+        .route("/sessions/:id", delete(revoke_session))
+}
+
+/// POST /auth/login
+/// Signs in with email and password; returns the new session's refresh token.
+async fn login(
+    State(pool): State<PgPool>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    ValidatedJson(dto): ValidatedJson<LoginRequest>,
+) -> Result<(StatusCode, Json<SessionTokens>), AppError> {
+    info!("Handler: Signing in {}", dto.email);
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let ip_address = client_ip(peer.ip(), &headers).to_string();
+    let tokens = auth::login(&pool, dto, user_agent, Some(ip_address)).await?;
+    Ok((StatusCode::CREATED, Json(tokens)))
+}
+
+/// POST /auth/refresh
+/// Exchanges a refresh token for a new one. Reusing an exchanged token revokes the session.
+async fn refresh(
+    State(pool): State<PgPool>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    ValidatedJson(dto): ValidatedJson<RefreshSessionRequest>,
+) -> Result<Json<SessionTokens>, AppError> {
+    info!("Handler: Refreshing session");
+    let ip_address = client_ip(peer.ip(), &headers).to_string();
+    let tokens = auth::refresh(&pool, dto, Some(ip_address)).await?;
+    Ok(Json(tokens))
+}
+
+/// GET /auth/oidc/:provider/authorize
+/// Starts signing in with Google or Microsoft by redirecting to the provider.
+async fn oidc_authorize(
+    State(pool): State<PgPool>,
+    Path(provider): Path<String>,
+) -> Result<Redirect, AppError> {
+    info!("Handler: Starting {} sign-in", provider);
+    let provider: OidcProvider = provider.parse()?;
+    let url = oidc::authorization_url(&pool, provider).await?;
+    Ok(Redirect::to(&url))
+}
+
+/// GET /auth/oidc/:provider/callback?code=&state=
+/// Where the provider redirects back; signs the user in (creating or linking the account on
+/// first sign-in) and returns the new session's refresh token, as password login does.
+async fn oidc_callback(
+    State(pool): State<PgPool>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<(StatusCode, Json<SessionTokens>), AppError> {
+    info!("Handler: Completing {} sign-in", provider);
+    let provider: OidcProvider = provider.parse()?;
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let ip_address = client_ip(peer.ip(), &headers).to_string();
+    let tokens = oidc::complete_login(&pool, provider, query, user_agent, Some(ip_address)).await?;
+    Ok((StatusCode::CREATED, Json(tokens)))
+}
+
+/// GET /auth/sessions
+/// Lists the current user's active sessions (signed-in devices).
+async fn list_sessions(
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
+) -> Result<Json<Vec<AuthSession>>, AppError> {
+    info!("Handler: Listing sessions for user {}", user_id);
+    let sessions = auth::list_sessions(&pool, user_id).await?;
+    Ok(Json(sessions))
+}
+
+/// DELETE /auth/sessions
+/// Signs the current user out of every session.
+async fn revoke_all_sessions(
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Revoking all sessions for user {}", user_id);
+    let revoked = auth::revoke_all_sessions(&pool, user_id).await?;
+    info!("Handler: Revoked {} sessions for user {}", revoked, user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /auth/sessions/:id
+/// Signs one of the current user's sessions out.
+async fn revoke_session(
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Revoking session {} for user {}", id, user_id);
+    auth::revoke_session(&pool, user_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,132 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        bill::{Bill, BillDetail, PayablesAging},
+        dto::bill_dto::{
+            CreateBillDto, ListBillsQuery, PayablesAgingQuery, RecordBillPaymentDto, UpdateBillDto,
+        },
+    },
+    services::bill,
+};
+
+/// Creates a router for bills, their payments and the payables aging report.
+///
+/// All routes defined here will be nested under `/api/v1/bills`.
+pub fn bill_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_bills).post(create_bill))
+        .route("/aging", get(get_payables_aging))
+        .route("/:id", get(get_bill).put(update_bill).delete(delete_bill))
+        .route("/:id/approve", post(approve_bill))
+        .route("/:id/payments", post(record_payment))
+}
+
+/// GET /bills?status=&vendor_id=&due_before=
+/// Lists the tenant's bills, soonest due first.
+async fn list_bills(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<ListBillsQuery>,
+) -> Result<Json<Vec<Bill>>, AppError> {
+    info!("Handler: Listing bills for tenant {}", ctx.tenant_id);
+    let bills = bill::list_bills(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(bills))
+}
+
+/// POST /bills
+/// Enters a draft bill.
+async fn create_bill(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateBillDto>,
+) -> Result<(StatusCode, Json<BillDetail>), AppError> {
+    info!("Handler: Creating bill for tenant {}", ctx.tenant_id);
+    let bill = bill::create_bill(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(bill)))
+}
+
+/// GET /bills/:id
+/// Retrieves a bill with its lines and payments.
+async fn get_bill(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BillDetail>, AppError> {
+    info!("Handler: Getting bill {}", id);
+    let bill = bill::get_bill(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(bill))
+}
+
+/// PUT /bills/:id
+/// Updates a draft bill.
+async fn update_bill(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateBillDto>,
+) -> Result<Json<BillDetail>, AppError> {
+    info!("Handler: Updating bill {}", id);
+    let bill = bill::update_bill(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(bill))
+}
+
+/// DELETE /bills/:id
+/// Deletes a draft bill.
+async fn delete_bill(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting bill {}", id);
+    bill::delete_bill(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /bills/:id/approve
+/// Approves a draft bill and posts it to expenses and payables.
+async fn approve_bill(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BillDetail>, AppError> {
+    info!("Handler: Approving bill {}", id);
+    let bill = bill::approve_bill(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(bill))
+}
+
+/// POST /bills/:id/payments
+/// Records a payment made against an approved bill.
+async fn record_payment(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<RecordBillPaymentDto>,
+) -> Result<(StatusCode, Json<BillDetail>), AppError> {
+    info!("Handler: Recording payment against bill {}", id);
+    let bill = bill::record_payment(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(bill)))
+}
+
+/// GET /bills/aging?as_of=
+/// What is owed to each vendor, by days past due.
+async fn get_payables_aging(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<PayablesAgingQuery>,
+) -> Result<Json<PayablesAging>, AppError> {
+    info!("Handler: Payables aging for tenant {}", ctx.tenant_id);
+    let aging = bill::payables_aging(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(aging))
+}
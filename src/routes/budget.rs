@@ -0,0 +1,321 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{
+        auth::TenantContext,
+        precondition::{Conditional, IfMatchVersion, IfNoneMatch},
+        report_format::ReportFormat,
+        validated_json::ValidatedJson,
+    },
+    models::{
+        budget::{Budget, BudgetHealth},
+        budget_line_item::{BudgetAlert, BudgetLineItem},
+        dto::archive_dto::IncludeInactiveQuery,
+        dto::budget_dto::{
+            AddSuggestedLinesDto, BudgetHealthQuery, BudgetImportQuery, BudgetImportResult,
+            CreateBudgetDto, UpdateBudgetDto,
+        },
+        dto::csv_format_dto::CsvFormatQuery,
+        dto::envelope_dto::{EnvelopeSummaryQuery, MoveEnvelopeMoneyDto},
+        envelope::{EnvelopeMove, EnvelopeSummary},
+        import_job::ImportJob,
+    },
+    routes::report::xlsx_download,
+    services::{
+        budget, budget_alert, budget_csv, budget_health, budget_performance, envelope,
+        field_policy::FieldAccess, report_export,
+    },
+    utils::csv_format::CsvFormat,
+};
+
+/// Creates a router for budgets.
+///
+/// All routes defined here will be nested under `/api/v1/budgets`.
+pub fn budget_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_budgets).post(create_budget))
+        .route(
+            "/:id",
+            get(get_budget).put(update_budget).delete(deactivate_budget),
+        )
+        .route("/:id/restore", post(restore_budget))
+        .route("/import", post(import_budget_csv))
+        .route("/import-jobs", post(start_budget_import_job))
+        .route("/:id/export", get(export_budget_csv))
+        .route("/:id/performance", get(get_budget_performance))
+        .route("/:id/alerts", get(list_budget_alerts))
+        .route("/:id/health", get(get_budget_health))
+        .route("/:id/health/lines", post(add_suggested_lines))
+        .route("/:id/envelopes", get(get_envelope_summary))
+        .route(
+            "/:id/envelope-moves",
+            get(list_envelope_moves).post(move_envelope_money),
+        )
+}
+
+/// GET /budgets?include_inactive=
+/// Lists the tenant's active budgets; deactivated ones only with `include_inactive=true` and
+/// the `records.restore` permission.
+async fn list_budgets(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<IncludeInactiveQuery>,
+) -> Result<Json<Vec<Budget>>, AppError> {
+    info!("Handler: Listing budgets for tenant {}", ctx.tenant_id);
+    let budgets =
+        budget::list_budgets(&pool, ctx.tenant_id, ctx.user_id, query.include_inactive).await?;
+    Ok(Json(budgets))
+}
+
+/// POST /budgets
+/// Creates a new budget.
+async fn create_budget(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateBudgetDto>,
+) -> Result<(StatusCode, Json<Budget>), AppError> {
+    info!("Handler: Creating budget for tenant {}", ctx.tenant_id);
+    let budget = budget::create_budget(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(budget)))
+}
+
+/// GET /budgets/:id
+/// Retrieves a single budget, or 304 when `If-None-Match` holds its current ETag.
+async fn get_budget(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    if_none_match: IfNoneMatch,
+) -> Result<Conditional<Json<Budget>>, AppError> {
+    info!("Handler: Getting budget {}", id);
+    let budget = budget::get_budget_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(if_none_match.respond(budget.version, Json(budget)))
+}
+
+/// PUT /budgets/:id
+/// Updates a budget. Requires `If-Match` with the version last read.
+async fn update_budget(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    IfMatchVersion(expected_version): IfMatchVersion,
+    ValidatedJson(dto): ValidatedJson<UpdateBudgetDto>,
+) -> Result<Json<Budget>, AppError> {
+    info!("Handler: Updating budget {}", id);
+    let budget =
+        budget::update_budget(&pool, ctx.tenant_id, id, ctx.user_id, expected_version, dto).await?;
+    Ok(Json(budget))
+}
+
+/// DELETE /budgets/:id
+/// Deactivates a budget.
+async fn deactivate_budget(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating budget {}", id);
+    budget::deactivate_budget(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /budgets/:id/restore
+/// Restores a deactivated budget.
+async fn restore_budget(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Budget>, AppError> {
+    info!("Handler: Restoring budget {}", id);
+    let budget = budget::restore_budget(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(budget))
+}
+
+/// GET /budgets/:id/export?delimiter=&decimal_comma=&date_format=&bom=
+/// Downloads a budget and its line items as CSV.
+async fn export_budget_csv(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    Query(format): Query<CsvFormatQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Handler: Exporting budget {} as CSV", id);
+    let format = CsvFormat::from_query(&format)?;
+    let (file_name, csv_text) =
+        budget_csv::export_budget_csv(&pool, ctx.tenant_id, id, &format).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        csv_text,
+    ))
+}
+
+/// POST /budgets/import?name=&delimiter=&decimal_comma=&date_format=
+/// Creates a new budget from a CSV body in the export format (with the same format options).
+/// Responds 422 with row-level errors (and writes nothing) if any row is invalid.
+async fn import_budget_csv(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<BudgetImportQuery>,
+    Query(format): Query<CsvFormatQuery>,
+    body: String,
+) -> Result<(StatusCode, Json<BudgetImportResult>), AppError> {
+    info!(
+        "Handler: Importing budget from CSV for tenant {}",
+        ctx.tenant_id
+    );
+    let format = CsvFormat::from_query(&format)?;
+    let result = budget_csv::import_budget_csv(
+        &pool,
+        ctx.tenant_id,
+        ctx.user_id,
+        &body,
+        query.name,
+        &format,
+        None,
+    )
+    .await?;
+    let status = if result.errors.is_empty() {
+        StatusCode::CREATED
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    Ok((status, Json(result)))
+}
+
+/// POST /budgets/import-jobs?name=&delimiter=&decimal_comma=&date_format=&bom=
+/// Starts the same import in the background and responds 202 with the job; poll
+/// GET /import-jobs/:id for progress and download failed rows from /import-jobs/:id/errors.
+async fn start_budget_import_job(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<BudgetImportQuery>,
+    Query(format): Query<CsvFormatQuery>,
+    body: String,
+) -> Result<(StatusCode, Json<ImportJob>), AppError> {
+    info!(
+        "Handler: Starting budget import job for tenant {}",
+        ctx.tenant_id
+    );
+    let format = CsvFormat::from_query(&format)?;
+    let job =
+        budget_csv::start_import_job(&pool, ctx.tenant_id, ctx.user_id, body, query.name, format)
+            .await?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// GET /budgets/:id/performance?format=json|xlsx
+/// Budget vs actual per line item and month, honouring seasonal schedules.
+async fn get_budget_performance(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    format: ReportFormat,
+    Path(id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    info!("Handler: Budget performance for budget {}", id);
+    let report = budget_performance::budget_performance(&pool, ctx.tenant_id, id).await?;
+    match format {
+        ReportFormat::Json => Ok(Json(report).into_response()),
+        ReportFormat::Xlsx => {
+            let rendered = report_export::render_budget_performance_xlsx(&report, &access)?;
+            Ok(xlsx_download(rendered))
+        }
+        ReportFormat::Pdf => Err(AppError::Validation(
+            "Budget performance is available as json or xlsx".to_string(),
+        )),
+    }
+}
+
+/// GET /budgets/:id/alerts
+/// Thresholds the budget's line items have crossed, newest first.
+async fn list_budget_alerts(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<BudgetAlert>>, AppError> {
+    info!("Handler: Listing alerts for budget {}", id);
+    let alerts = budget_alert::list_budget_alerts(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(alerts))
+}
+
+/// GET /budgets/:id/health?as_of=&min_amount=
+/// Categories with significant spending in the budget period but no budget line, with suggested amounts.
+async fn get_budget_health(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    Query(query): Query<BudgetHealthQuery>,
+) -> Result<Json<BudgetHealth>, AppError> {
+    info!("Handler: Budget health for budget {}", id);
+    let health = budget_health::budget_health(&pool, ctx.tenant_id, id, query).await?;
+    Ok(Json(health))
+}
+
+/// POST /budgets/:id/health/lines
+/// Adds line items for unbudgeted categories at their suggested amounts.
+async fn add_suggested_lines(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<AddSuggestedLinesDto>,
+) -> Result<(StatusCode, Json<Vec<BudgetLineItem>>), AppError> {
+    info!("Handler: Adding suggested line items to budget {}", id);
+    let line_items =
+        budget_health::add_suggested_lines(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(line_items)))
+}
+
+/// GET /budgets/:id/envelopes?as_of=
+/// Envelope balances of an envelope-mode budget and the income still ready to assign.
+async fn get_envelope_summary(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    Query(query): Query<EnvelopeSummaryQuery>,
+) -> Result<Json<EnvelopeSummary>, AppError> {
+    info!("Handler: Envelope summary for budget {}", id);
+    let summary = envelope::envelope_summary(&pool, ctx.tenant_id, id, query).await?;
+    Ok(Json(summary))
+}
+
+/// GET /budgets/:id/envelope-moves
+/// History of money moved between the budget's envelopes, newest first.
+async fn list_envelope_moves(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EnvelopeMove>>, AppError> {
+    info!("Handler: Listing envelope moves for budget {}", id);
+    let moves = envelope::list_moves(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(moves))
+}
+
+/// POST /budgets/:id/envelope-moves
+/// Moves money from one envelope to another mid-period.
+async fn move_envelope_money(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<MoveEnvelopeMoneyDto>,
+) -> Result<(StatusCode, Json<EnvelopeMove>), AppError> {
+    info!("Handler: Moving envelope money in budget {}", id);
+    let envelope_move = envelope::move_money(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(envelope_move)))
+}
@@ -0,0 +1,104 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        budget_line_item::BudgetLineItem,
+        dto::budget_line_item_dto::{CreateBudgetLineItemDto, UpdateBudgetLineItemDto},
+    },
+    services::budget_line_item,
+};
+
+/// Creates a router for the line items of budgets.
+///
+/// All routes defined here will be nested under `/api/v1/budgets`.
+pub fn budget_line_item_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/:id/line-items",
+            get(list_budget_line_items).post(create_budget_line_item),
+        )
+        .route(
+            "/line-items/:item_id",
+            get(get_budget_line_item)
+                .put(update_budget_line_item)
+                .delete(deactivate_budget_line_item),
+        )
+}
+
+/// GET /budgets/:id/line-items
+/// Lists the active line items of a budget.
+async fn list_budget_line_items(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<BudgetLineItem>>, AppError> {
+    info!("Handler: Listing line items of budget {}", id);
+    let items = budget_line_item::list_budget_line_items(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(items))
+}
+
+/// POST /budgets/:id/line-items
+/// Adds a line item to a budget.
+async fn create_budget_line_item(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<CreateBudgetLineItemDto>,
+) -> Result<(StatusCode, Json<BudgetLineItem>), AppError> {
+    info!("Handler: Creating line item for budget {}", id);
+    let item =
+        budget_line_item::create_budget_line_item(&pool, ctx.tenant_id, ctx.user_id, id, dto)
+            .await?;
+    Ok((StatusCode::CREATED, Json(item)))
+}
+
+/// GET /budgets/line-items/:item_id
+/// Retrieves a single line item.
+async fn get_budget_line_item(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(item_id): Path<Uuid>,
+) -> Result<Json<BudgetLineItem>, AppError> {
+    info!("Handler: Getting budget line item {}", item_id);
+    let item = budget_line_item::get_budget_line_item_by_id(&pool, ctx.tenant_id, item_id).await?;
+    Ok(Json(item))
+}
+
+/// PUT /budgets/line-items/:item_id
+/// Updates a line item.
+async fn update_budget_line_item(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(item_id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateBudgetLineItemDto>,
+) -> Result<Json<BudgetLineItem>, AppError> {
+    info!("Handler: Updating budget line item {}", item_id);
+    let item =
+        budget_line_item::update_budget_line_item(&pool, ctx.tenant_id, item_id, ctx.user_id, dto)
+            .await?;
+    Ok(Json(item))
+}
+
+/// DELETE /budgets/line-items/:item_id
+/// Deactivates a line item.
+async fn deactivate_budget_line_item(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(item_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating budget line item {}", item_id);
+    budget_line_item::deactivate_budget_line_item(&pool, ctx.tenant_id, item_id, ctx.user_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
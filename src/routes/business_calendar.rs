@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        business_calendar::{BusinessCalendar, BusinessHoliday},
+        dto::business_calendar_dto::{
+            BusinessHolidaysQuery, CreateBusinessHolidayDto, SeedBusinessHolidaysDto,
+            UpdateBusinessCalendarDto,
+        },
+    },
+    services::business_calendar,
+};
+
+/// Creates a router for the tenant's business calendar (weekend days and holidays).
+///
+/// All routes defined here will be nested under `/api/v1/business-calendar`.
+pub fn business_calendar_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_calendar).put(update_calendar))
+        .route("/holidays", get(list_holidays).post(add_holiday))
+        .route("/holidays/seed", post(seed_holidays))
+        .route("/holidays/:id", delete(delete_holiday))
+}
+
+/// GET /business-calendar
+/// The tenant's weekend days and seeded country.
+async fn get_calendar(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<BusinessCalendar>, AppError> {
+    info!(
+        "Handler: Getting business calendar for tenant {}",
+        ctx.tenant_id
+    );
+    let calendar = business_calendar::get_calendar(&pool, ctx.tenant_id).await?;
+    Ok(Json(calendar))
+}
+
+/// PUT /business-calendar
+/// Sets the tenant's weekend days (ISO weekday numbers).
+async fn update_calendar(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<UpdateBusinessCalendarDto>,
+) -> Result<Json<BusinessCalendar>, AppError> {
+    info!(
+        "Handler: Updating business calendar for tenant {}",
+        ctx.tenant_id
+    );
+    let calendar =
+        business_calendar::update_calendar(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(calendar))
+}
+
+/// GET /business-calendar/holidays?year=
+/// Lists the tenant's holidays, optionally for one year.
+async fn list_holidays(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<BusinessHolidaysQuery>,
+) -> Result<Json<Vec<BusinessHoliday>>, AppError> {
+    info!(
+        "Handler: Listing business holidays for tenant {}",
+        ctx.tenant_id
+    );
+    let holidays = business_calendar::list_holidays(&pool, ctx.tenant_id, query.year).await?;
+    Ok(Json(holidays))
+}
+
+/// POST /business-calendar/holidays
+/// Adds a holiday by hand.
+async fn add_holiday(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateBusinessHolidayDto>,
+) -> Result<(StatusCode, Json<BusinessHoliday>), AppError> {
+    info!(
+        "Handler: Adding business holiday for tenant {}",
+        ctx.tenant_id
+    );
+    let holiday = business_calendar::add_holiday(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(holiday)))
+}
+
+/// POST /business-calendar/holidays/seed
+/// Adds a country's public holidays for a year; returns the holidays added.
+async fn seed_holidays(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<SeedBusinessHolidaysDto>,
+) -> Result<(StatusCode, Json<Vec<BusinessHoliday>>), AppError> {
+    info!(
+        "Handler: Seeding {} {} holidays for tenant {}",
+        dto.year, dto.country_code, ctx.tenant_id
+    );
+    let holidays = business_calendar::seed_holidays(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(holidays)))
+}
+
+/// DELETE /business-calendar/holidays/:id
+/// Removes a holiday.
+async fn delete_holiday(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting business holiday {}", id);
+    business_calendar::delete_holiday(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
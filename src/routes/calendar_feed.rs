@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState, error::AppError, middleware::auth::TenantContext,
+    models::calendar_feed::CalendarFeedToken, services::calendar_feed,
+};
+
+/// Creates a router for calendar subscriptions.
+///
+/// All routes defined here will be nested under `/api/v1/tenants`.
+pub fn calendar_feed_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/:id/calendar-token",
+            post(create_feed_token).delete(revoke_feed_token),
+        )
+        .route("/:id/calendar.ics", get(calendar_feed))
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarFeedQuery {
+    token: String,
+}
+
+/// POST /tenants/:id/calendar-token
+/// Creates the caller's feed URL for the tenant, replacing any earlier one.
+async fn create_feed_token(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<CalendarFeedToken>), AppError> {
+    info!(
+        "Handler: Creating calendar feed token for tenant {}",
+        tenant_id
+    );
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    let token = calendar_feed::create_feed_token(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok((StatusCode::CREATED, Json(token)))
+}
+
+/// DELETE /tenants/:id/calendar-token
+/// Revokes the caller's feed URL.
+async fn revoke_feed_token(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!(
+        "Handler: Revoking calendar feed token for tenant {}",
+        tenant_id
+    );
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    calendar_feed::revoke_feed_token(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /tenants/:id/calendar.ics?token=
+/// The tenant's upcoming events as iCalendar. Authenticated by the token alone, so
+/// calendar apps can subscribe.
+async fn calendar_feed(
+    State(pool): State<PgPool>,
+    Path(tenant_id): Path<Uuid>,
+    Query(query): Query<CalendarFeedQuery>,
+) -> Result<([(header::HeaderName, &'static str); 1], String), AppError> {
+    info!("Handler: Serving calendar feed for tenant {}", tenant_id);
+    let ics = calendar_feed::render_feed(&pool, tenant_id, &query.token).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        ics,
+    ))
+}
@@ -0,0 +1,32 @@
+use axum::{extract::Query, routing::get, Router};
+use chrono::Utc;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, consistency::ReadPool, field_policy::Redacted},
+    models::{cash_position::CashPosition, dto::cash_position_dto::CashPositionQuery},
+    services::{cash_position, field_policy::FieldAccess},
+};
+
+/// Creates a router for the treasury cash position.
+///
+/// All routes defined here will be nested under `/api/v1/cash-position`.
+pub fn cash_position_routes() -> Router<AppState> {
+    Router::new().route("/", get(get_cash_position))
+}
+
+/// GET /cash-position?as_of=
+/// Bank and cash balances by currency and institution, with week-over-week movement.
+async fn get_cash_position(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Query(query): Query<CashPositionQuery>,
+) -> Result<Redacted<CashPosition>, AppError> {
+    info!("Handler: Cash position for tenant {}", ctx.tenant_id);
+    let as_of = query.as_of.unwrap_or_else(|| Utc::now().date_naive());
+    let position = cash_position::cash_position(&pool, ctx.tenant_id, as_of).await?;
+    Ok(Redacted(position, access))
+}
@@ -0,0 +1,194 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        category::{Category, CategoryMergeResult, CategoryTreeNode},
+        dto::archive_dto::{ArchiveUnusedDto, IncludeArchivedQuery, IncludeInactiveQuery},
+        dto::category_dto::{CreateCategoryDto, DeactivateCategoryQuery, UpdateCategoryDto},
+    },
+    services::category,
+};
+
+/// Creates a router for the tenant's income and expense categories.
+///
+/// All routes defined here will be nested under `/api/v1/categories`.
+pub fn category_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_categories).post(create_category))
+        .route("/tree", get(category_tree))
+        .route("/archive-unused", post(archive_unused_categories))
+        .route(
+            "/:id",
+            get(get_category)
+                .put(update_category)
+                .patch(update_category)
+                .delete(deactivate_category),
+        )
+        .route("/:id/merge-into/:target", post(merge_category))
+        .route("/:id/archive", post(archive_category))
+        .route("/:id/unarchive", post(unarchive_category))
+        .route("/:id/restore", post(restore_category))
+}
+
+/// GET /categories?include_archived=&include_inactive=
+/// Lists the tenant's active categories; archived ones only with `include_archived=true`,
+/// deactivated ones only with `include_inactive=true` and the `records.restore` permission.
+async fn list_categories(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<IncludeArchivedQuery>,
+    Query(inactive): Query<IncludeInactiveQuery>,
+) -> Result<Json<Vec<Category>>, AppError> {
+    info!("Handler: Listing categories for tenant {}", ctx.tenant_id);
+    let include_inactive_for = inactive.include_inactive.then_some(ctx.user_id);
+    let categories = category::list_categories(
+        &pool,
+        ctx.tenant_id,
+        query.include_archived,
+        include_inactive_for,
+    )
+    .await?;
+    Ok(Json(categories))
+}
+
+/// GET /categories/tree
+/// Lists the tenant's active, unarchived categories nested under their parents.
+async fn category_tree(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<CategoryTreeNode>>, AppError> {
+    info!(
+        "Handler: Getting category tree for tenant {}",
+        ctx.tenant_id
+    );
+    let tree = category::category_tree(&pool, ctx.tenant_id).await?;
+    Ok(Json(tree))
+}
+
+/// POST /categories
+/// Creates a new category.
+async fn create_category(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateCategoryDto>,
+) -> Result<(StatusCode, Json<Category>), AppError> {
+    info!("Handler: Creating category for tenant {}", ctx.tenant_id);
+    let category = category::create_category(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(category)))
+}
+
+/// GET /categories/:id
+/// Retrieves a single category.
+async fn get_category(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Category>, AppError> {
+    info!("Handler: Getting category {}", id);
+    let category = category::get_category_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(category))
+}
+
+/// PUT|PATCH /categories/:id
+/// Updates a category as a JSON Merge Patch: absent fields are kept and `null` clears
+/// `description` or `parent_category_id` (making it a top-level category).
+async fn update_category(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateCategoryDto>,
+) -> Result<Json<Category>, AppError> {
+    info!("Handler: Updating category {}", id);
+    let category = category::update_category(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(category))
+}
+
+/// DELETE /categories/:id?cascade=
+/// Deactivates a category; one with active subcategories, budget lines or recurring
+/// transactions, or used by merchant rules, only with `cascade=true`, which deactivates them
+/// too and clears it from the rules. Otherwise they are listed as blockers.
+async fn deactivate_category(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeactivateCategoryQuery>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating category {}", id);
+    category::deactivate_category(&pool, ctx.tenant_id, id, ctx.user_id, query.cascade).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /categories/:id/merge-into/:target
+/// Moves everything filed under a category to another of the same type and deactivates it.
+async fn merge_category(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, target)): Path<(Uuid, Uuid)>,
+) -> Result<Json<CategoryMergeResult>, AppError> {
+    info!("Handler: Merging category {} into {}", id, target);
+    let result = category::merge_category(&pool, ctx.tenant_id, id, target, ctx.user_id).await?;
+    Ok(Json(result))
+}
+
+/// POST /categories/:id/archive
+/// Archives a category: still in reports, but hidden from pickers and no longer assignable.
+async fn archive_category(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Category>, AppError> {
+    info!("Handler: Archiving category {}", id);
+    let category = category::archive_category(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(category))
+}
+
+/// POST /categories/:id/unarchive
+/// Makes an archived category assignable again.
+async fn unarchive_category(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Category>, AppError> {
+    info!("Handler: Unarchiving category {}", id);
+    let category = category::unarchive_category(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(category))
+}
+
+/// POST /categories/:id/restore
+/// Restores a deactivated category.
+async fn restore_category(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Category>, AppError> {
+    info!("Handler: Restoring category {}", id);
+    let category = category::restore_category(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(category))
+}
+
+/// POST /categories/archive-unused
+/// Archives categories nothing has used for `unused_for_months`; `dry_run` only lists them.
+async fn archive_unused_categories(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<ArchiveUnusedDto>,
+) -> Result<Json<Vec<Category>>, AppError> {
+    info!(
+        "Handler: Archiving unused categories for tenant {}",
+        ctx.tenant_id
+    );
+    let categories =
+        category::archive_unused_categories(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(categories))
+}
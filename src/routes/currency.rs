@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::{header, StatusCode},
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::AuthUser, validated_json::ValidatedJson},
+    models::{
+        currency::Currency,
+        dto::currency_dto::{CreateCurrencyDto, UpdateCurrencyDto},
+    },
+    services::{currency, reference_cache},
+};
+
+/// Creates a router for the currency catalogue shared by all tenants.
+///
+/// All routes defined here will be nested under `/api/v1/currencies`.
+pub fn currency_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_currencies).post(create_currency))
+        .route(
+            "/:code",
+            get(get_currency).put(update_currency).delete(deactivate_currency),
+        )
+}
+
+/// GET /currencies
+/// Lists active currencies.
+async fn list_currencies(
+    State(pool): State<PgPool>,
+) -> Result<([(header::HeaderName, &'static str); 1], Json<Vec<Currency>>), AppError> {
+    info!("Handler: Listing currencies");
+    let currencies = currency::list_currencies(&pool).await?;
+    Ok(([(header::CACHE_CONTROL, reference_cache::CACHE_CONTROL)], Json(currencies)))
+}
+
+/// GET /currencies/:code
+/// Retrieves a single currency by its ISO 4217 code.
+async fn get_currency(
+    State(pool): State<PgPool>,
+    Path(code): Path<String>,
+) -> Result<([(header::HeaderName, &'static str); 1], Json<Currency>), AppError> {
+    info!("Handler: Getting currency {}", code);
+    let currency = currency::get_currency_by_code(&pool, &code).await?;
+    Ok(([(header::CACHE_CONTROL, reference_cache::CACHE_CONTROL)], Json(currency)))
+}
+
+/// POST /currencies
+/// Adds a currency (system administrators only).
+async fn create_currency(
+    State(pool): State<PgPool>,
+    AuthUser {
+        user_id: created_by_user_id,
+    }: AuthUser,
+    ValidatedJson(dto): ValidatedJson<CreateCurrencyDto>,
+) -> Result<(StatusCode, Json<Currency>), AppError> {
+    info!("Handler: Creating currency {}", dto.code);
+
+    let currency = currency::create_currency(&pool, created_by_user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(currency)))
+}
+
+/// PUT /currencies/:code
+/// Updates a currency (system administrators only).
+async fn update_currency(
+    State(pool): State<PgPool>,
+    AuthUser {
+        user_id: updated_by_user_id,
+    }: AuthUser,
+    Path(code): Path<String>,
+    ValidatedJson(dto): ValidatedJson<UpdateCurrencyDto>,
+) -> Result<Json<Currency>, AppError> {
+    info!("Handler: Updating currency {}", code);
+
+    let currency = currency::update_currency(&pool, &code, updated_by_user_id, dto).await?;
+    Ok(Json(currency))
+}
+
+/// DELETE /currencies/:code
+/// Deactivates a currency (system administrators only).
+async fn deactivate_currency(
+    State(pool): State<PgPool>,
+    AuthUser {
+        user_id: updated_by_user_id,
+    }: AuthUser,
+    Path(code): Path<String>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating currency {}", code);
+
+    currency::deactivate_currency(&pool, &code, updated_by_user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
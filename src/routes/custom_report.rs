@@ -0,0 +1,237 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post, put},
+    Router,
+};
+use chrono::{Datelike, Utc};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, consistency::ReadPool, validated_json::ValidatedJson},
+    models::{
+        custom_report::CustomReport,
+        dto::csv_format_dto::CsvFormatQuery,
+        dto::custom_report_dto::{
+            CreateCustomReportDto, ExportCustomReportQuery, UpdateCustomReportDto,
+        },
+        dto::report_schedule_dto::{CreateReportScheduleDto, UpdateReportScheduleDto},
+        report_schedule::ReportSchedule,
+    },
+    services::{custom_report, field_policy::FieldAccess, report_export, report_schedule},
+    utils::csv_format::CsvFormat,
+};
+
+/// Creates a router for saved custom reports and their email delivery schedules.
+///
+/// All routes defined here will be nested under `/api/v1/custom-reports`.
+pub fn custom_report_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_custom_reports).post(create_custom_report))
+        .route(
+            "/:id",
+            get(get_custom_report)
+                .put(update_custom_report)
+                .delete(delete_custom_report),
+        )
+        .route("/:id/export", get(export_custom_report))
+        .route(
+            "/:id/schedules",
+            get(list_report_schedules).post(create_report_schedule),
+        )
+        .route(
+            "/:id/schedules/:schedule_id",
+            put(update_report_schedule).delete(delete_report_schedule),
+        )
+        .route(
+            "/:id/schedules/:schedule_id/run",
+            post(run_report_schedule_now),
+        )
+}
+
+/// GET /custom-reports
+/// Lists the current user's reports and the tenant's public ones.
+async fn list_custom_reports(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<CustomReport>>, AppError> {
+    info!(
+        "Handler: Listing custom reports for tenant {}",
+        ctx.tenant_id
+    );
+    let reports = custom_report::list_custom_reports(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(reports))
+}
+
+/// POST /custom-reports
+/// Saves a new report definition.
+async fn create_custom_report(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateCustomReportDto>,
+) -> Result<(StatusCode, Json<CustomReport>), AppError> {
+    info!(
+        "Handler: Creating custom report for tenant {}",
+        ctx.tenant_id
+    );
+    let report =
+        custom_report::create_custom_report(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+/// GET /custom-reports/:id
+/// Retrieves a single report definition.
+async fn get_custom_report(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CustomReport>, AppError> {
+    info!("Handler: Getting custom report {}", id);
+    let report =
+        custom_report::get_custom_report_by_id(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(Json(report))
+}
+
+/// PUT /custom-reports/:id
+/// Updates a report owned by the current user.
+async fn update_custom_report(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateCustomReportDto>,
+) -> Result<Json<CustomReport>, AppError> {
+    info!("Handler: Updating custom report {}", id);
+    let report =
+        custom_report::update_custom_report(&pool, ctx.tenant_id, ctx.user_id, id, dto).await?;
+    Ok(Json(report))
+}
+
+/// DELETE /custom-reports/:id
+/// Deletes a report owned by the current user, with its schedules.
+async fn delete_custom_report(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting custom report {}", id);
+    custom_report::delete_custom_report(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /custom-reports/:id/export?from_date=&to_date=&delimiter=&decimal_comma=&date_format=&bom=
+/// Downloads the report as CSV (defaults to month to date).
+async fn export_custom_report(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ExportCustomReportQuery>,
+    Query(format): Query<CsvFormatQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Handler: Exporting custom report {} as CSV", id);
+    let format = CsvFormat::from_query(&format)?;
+    let report =
+        custom_report::get_custom_report_by_id(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    let to_date = query.to_date.unwrap_or_else(|| Utc::now().date_naive());
+    let from_date = query
+        .from_date
+        .unwrap_or_else(|| to_date.with_day(1).expect("day 1 is always valid"));
+    let rendered =
+        report_export::render_custom_report(&pool, &report, &access, from_date, to_date, &format)
+            .await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", rendered.file_name),
+            ),
+        ],
+        rendered.csv,
+    ))
+}
+
+/// GET /custom-reports/:id/schedules
+/// Lists the report's email delivery schedules.
+async fn list_report_schedules(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<ReportSchedule>>, AppError> {
+    info!("Handler: Listing schedules for custom report {}", id);
+    let schedules =
+        report_schedule::list_report_schedules(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(Json(schedules))
+}
+
+/// POST /custom-reports/:id/schedules
+/// Schedules the report for email delivery.
+async fn create_report_schedule(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<CreateReportScheduleDto>,
+) -> Result<(StatusCode, Json<ReportSchedule>), AppError> {
+    info!("Handler: Scheduling custom report {}", id);
+    let schedule =
+        report_schedule::create_report_schedule(&pool, ctx.tenant_id, ctx.user_id, id, dto).await?;
+    Ok((StatusCode::CREATED, Json(schedule)))
+}
+
+/// PUT /custom-reports/:id/schedules/:schedule_id
+/// Updates a schedule created by the current user.
+async fn update_report_schedule(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(dto): ValidatedJson<UpdateReportScheduleDto>,
+) -> Result<Json<ReportSchedule>, AppError> {
+    info!("Handler: Updating report schedule {}", schedule_id);
+    let schedule = report_schedule::update_report_schedule(
+        &pool,
+        ctx.tenant_id,
+        ctx.user_id,
+        id,
+        schedule_id,
+        dto,
+    )
+    .await?;
+    Ok(Json(schedule))
+}
+
+/// DELETE /custom-reports/:id/schedules/:schedule_id
+/// Deletes a schedule created by the current user.
+async fn delete_report_schedule(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting report schedule {}", schedule_id);
+    report_schedule::delete_report_schedule(&pool, ctx.tenant_id, ctx.user_id, id, schedule_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /custom-reports/:id/schedules/:schedule_id/run
+/// Sends the scheduled report now, without moving its next run.
+async fn run_report_schedule_now(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, schedule_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ReportSchedule>, AppError> {
+    info!("Handler: Running report schedule {} now", schedule_id);
+    let schedule = report_schedule::run_report_schedule_now(
+        &pool,
+        ctx.tenant_id,
+        ctx.user_id,
+        id,
+        schedule_id,
+    )
+    .await?;
+    Ok(Json(schedule))
+}
@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::invoice_dto::{CreateCustomerDto, UpdateCustomerDto},
+        invoice::Customer,
+    },
+    services::customer,
+};
+
+/// Creates a router for the customers a tenant invoices.
+///
+/// All routes defined here will be nested under `/api/v1/customers`.
+/// A customer's invoices are listed with `GET /invoices?customer_id=`.
+pub fn customer_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_customers).post(create_customer))
+        .route(
+            "/:id",
+            get(get_customer)
+                .put(update_customer)
+                .delete(deactivate_customer),
+        )
+}
+
+/// GET /customers
+/// Lists the tenant's active customers.
+async fn list_customers(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<Customer>>, AppError> {
+    info!("Handler: Listing customers for tenant {}", ctx.tenant_id);
+    let customers = customer::list_customers(&pool, ctx.tenant_id).await?;
+    Ok(Json(customers))
+}
+
+/// POST /customers
+/// Creates a new customer.
+async fn create_customer(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateCustomerDto>,
+) -> Result<(StatusCode, Json<Customer>), AppError> {
+    info!("Handler: Creating customer for tenant {}", ctx.tenant_id);
+    let customer = customer::create_customer(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(customer)))
+}
+
+/// GET /customers/:id
+/// Retrieves a single customer.
+async fn get_customer(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Customer>, AppError> {
+    info!("Handler: Getting customer {}", id);
+    let customer = customer::get_customer_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(customer))
+}
+
+/// PUT /customers/:id
+/// Updates a customer.
+async fn update_customer(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateCustomerDto>,
+) -> Result<Json<Customer>, AppError> {
+    info!("Handler: Updating customer {}", id);
+    let customer = customer::update_customer(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(customer))
+}
+
+/// DELETE /customers/:id
+/// Deactivates a customer with no draft or unpaid invoices.
+async fn deactivate_customer(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating customer {}", id);
+    customer::deactivate_customer(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
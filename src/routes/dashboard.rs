@@ -0,0 +1,183 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::{get, put},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{
+        auth::TenantContext, consistency::ReadPool, field_policy::Redacted,
+        validated_json::ValidatedJson,
+    },
+    models::{
+        dashboard::{Dashboard, DashboardData},
+        dashboard_widget::DashboardWidget,
+        dto::dashboard_dto::{CreateDashboardDto, UpdateDashboardDto},
+        dto::dashboard_widget_dto::{CreateDashboardWidgetDto, UpdateDashboardWidgetDto},
+    },
+    services::{dashboard, dashboard_data, dashboard_widget, field_policy::FieldAccess},
+};
+
+/// Creates a router for the current user's dashboards and their widgets.
+///
+/// All routes defined here will be nested under `/api/v1/dashboards`.
+pub fn dashboard_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_dashboards).post(create_dashboard))
+        .route(
+            "/:id",
+            get(get_dashboard)
+                .put(update_dashboard)
+                .delete(delete_dashboard),
+        )
+        .route("/:id/data", get(get_dashboard_data))
+        .route(
+            "/:id/widgets",
+            get(list_dashboard_widgets).post(create_dashboard_widget),
+        )
+        .route(
+            "/:id/widgets/:widget_id",
+            put(update_dashboard_widget).delete(delete_dashboard_widget),
+        )
+}
+
+/// GET /dashboards
+/// Lists the current user's dashboards.
+async fn list_dashboards(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<Dashboard>>, AppError> {
+    info!("Handler: Listing dashboards for user {}", ctx.user_id);
+    let dashboards = dashboard::list_dashboards(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(dashboards))
+}
+
+/// GET /dashboards/:id
+/// Retrieves a single dashboard.
+async fn get_dashboard(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Dashboard>, AppError> {
+    info!("Handler: Getting dashboard {}", id);
+    let dashboard = dashboard::get_dashboard_by_id(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(Json(dashboard))
+}
+
+/// POST /dashboards
+/// Creates a dashboard.
+async fn create_dashboard(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateDashboardDto>,
+) -> Result<(StatusCode, Json<Dashboard>), AppError> {
+    info!("Handler: Creating dashboard '{}'", dto.name);
+    let dashboard = dashboard::create_dashboard(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(dashboard)))
+}
+
+/// PUT /dashboards/:id
+/// Updates a dashboard.
+async fn update_dashboard(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateDashboardDto>,
+) -> Result<Json<Dashboard>, AppError> {
+    info!("Handler: Updating dashboard {}", id);
+    let dashboard = dashboard::update_dashboard(&pool, ctx.tenant_id, ctx.user_id, id, dto).await?;
+    Ok(Json(dashboard))
+}
+
+/// DELETE /dashboards/:id
+/// Deletes a dashboard and its widgets.
+async fn delete_dashboard(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting dashboard {}", id);
+    dashboard::delete_dashboard(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /dashboards/:id/data
+/// Evaluates every widget of the dashboard in one call.
+async fn get_dashboard_data(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+) -> Result<Redacted<DashboardData>, AppError> {
+    info!("Handler: Evaluating dashboard {}", id);
+    let data = dashboard_data::dashboard_data(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(Redacted(data, access))
+}
+
+/// GET /dashboards/:id/widgets
+/// Lists the widgets of a dashboard in display order.
+async fn list_dashboard_widgets(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<DashboardWidget>>, AppError> {
+    info!("Handler: Listing widgets of dashboard {}", id);
+    let widgets =
+        dashboard_widget::list_dashboard_widgets(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(Json(widgets))
+}
+
+/// POST /dashboards/:id/widgets
+/// Adds a widget to a dashboard.
+async fn create_dashboard_widget(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<CreateDashboardWidgetDto>,
+) -> Result<(StatusCode, Json<DashboardWidget>), AppError> {
+    info!("Handler: Adding widget to dashboard {}", id);
+    let widget =
+        dashboard_widget::create_dashboard_widget(&pool, ctx.tenant_id, ctx.user_id, id, dto)
+            .await?;
+    Ok((StatusCode::CREATED, Json(widget)))
+}
+
+/// PUT /dashboards/:id/widgets/:widget_id
+/// Updates a widget.
+async fn update_dashboard_widget(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, widget_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(dto): ValidatedJson<UpdateDashboardWidgetDto>,
+) -> Result<Json<DashboardWidget>, AppError> {
+    info!("Handler: Updating widget {} of dashboard {}", widget_id, id);
+    let widget = dashboard_widget::update_dashboard_widget(
+        &pool,
+        ctx.tenant_id,
+        ctx.user_id,
+        id,
+        widget_id,
+        dto,
+    )
+    .await?;
+    Ok(Json(widget))
+}
+
+/// DELETE /dashboards/:id/widgets/:widget_id
+/// Removes a widget from a dashboard.
+async fn delete_dashboard_widget(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, widget_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting widget {} of dashboard {}", widget_id, id);
+    dashboard_widget::delete_dashboard_widget(&pool, ctx.tenant_id, ctx.user_id, id, widget_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
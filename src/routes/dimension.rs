@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dimension::Dimension,
+        dto::dimension_dto::{CreateDimensionDto, ListDimensionsQuery, UpdateDimensionDto},
+    },
+    services::dimension,
+};
+
+/// Creates a router for projects, classes and locations.
+///
+/// All routes defined here will be nested under `/api/v1/dimensions`.
+pub fn dimension_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_dimensions).post(create_dimension))
+        .route(
+            "/:id",
+            get(get_dimension)
+                .put(update_dimension)
+                .delete(deactivate_dimension),
+        )
+}
+
+/// GET /dimensions?kind=&include_inactive=
+/// Lists the tenant's dimensions, optionally of one kind.
+async fn list_dimensions(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<ListDimensionsQuery>,
+) -> Result<Json<Vec<Dimension>>, AppError> {
+    info!("Handler: Listing dimensions for tenant {}", ctx.tenant_id);
+    let dimensions = dimension::list_dimensions(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(dimensions))
+}
+
+/// POST /dimensions
+/// Creates a project, class or location.
+async fn create_dimension(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateDimensionDto>,
+) -> Result<(StatusCode, Json<Dimension>), AppError> {
+    info!("Handler: Creating dimension for tenant {}", ctx.tenant_id);
+    let dimension = dimension::create_dimension(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(dimension)))
+}
+
+/// GET /dimensions/:id
+/// Retrieves a dimension.
+async fn get_dimension(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Dimension>, AppError> {
+    info!("Handler: Getting dimension {}", id);
+    let dimension = dimension::get_dimension(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(dimension))
+}
+
+/// PUT /dimensions/:id
+/// Renames or re-codes a dimension.
+async fn update_dimension(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateDimensionDto>,
+) -> Result<Json<Dimension>, AppError> {
+    info!("Handler: Updating dimension {}", id);
+    let dimension = dimension::update_dimension(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(dimension))
+}
+
+/// DELETE /dimensions/:id
+/// Deactivates a dimension; entries already tagged with it keep it.
+async fn deactivate_dimension(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating dimension {}", id);
+    dimension::deactivate_dimension(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
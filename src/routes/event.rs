@@ -0,0 +1,168 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::csv_format_dto::CsvFormatQuery,
+        dto::event_dto::{AssignEventTransactionsDto, CreateEventDto, UpdateEventDto},
+        event::{Event, EventAssignmentResult, EventSummary},
+    },
+    services::{event, field_policy::FieldAccess, report_export},
+    utils::csv_format::CsvFormat,
+};
+
+/// Creates a router for events (trips, conferences) that group transactions.
+///
+/// All routes defined here will be nested under `/api/v1/events`.
+/// An event's transactions are listed with `GET /transactions?event_id=`.
+pub fn event_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_events).post(create_event))
+        .route(
+            "/:id",
+            get(get_event).put(update_event).delete(deactivate_event),
+        )
+        .route("/:id/transactions", post(assign_transactions))
+        .route(
+            "/:id/transactions/:transaction_id",
+            delete(unassign_transaction),
+        )
+        .route("/:id/summary", get(get_event_summary))
+        .route("/:id/export", get(export_event_report))
+}
+
+/// GET /events
+/// Lists the tenant's active events.
+async fn list_events(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<Event>>, AppError> {
+    info!("Handler: Listing events for tenant {}", ctx.tenant_id);
+    let events = event::list_events(&pool, ctx.tenant_id).await?;
+    Ok(Json(events))
+}
+
+/// POST /events
+/// Creates a new event.
+async fn create_event(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateEventDto>,
+) -> Result<(StatusCode, Json<Event>), AppError> {
+    info!("Handler: Creating event for tenant {}", ctx.tenant_id);
+    let event = event::create_event(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+/// GET /events/:id
+/// Retrieves a single event.
+async fn get_event(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Event>, AppError> {
+    info!("Handler: Getting event {}", id);
+    let event = event::get_event_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(event))
+}
+
+/// PUT /events/:id
+/// Updates an event.
+async fn update_event(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateEventDto>,
+) -> Result<Json<Event>, AppError> {
+    info!("Handler: Updating event {}", id);
+    let event = event::update_event(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(event))
+}
+
+/// DELETE /events/:id
+/// Deactivates an event; its transactions stay assigned.
+async fn deactivate_event(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating event {}", id);
+    event::deactivate_event(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /events/:id/transactions
+/// Assigns transactions to the event, moving them out of any other event.
+async fn assign_transactions(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<AssignEventTransactionsDto>,
+) -> Result<Json<EventAssignmentResult>, AppError> {
+    info!("Handler: Assigning transactions to event {}", id);
+    let result = event::assign_transactions(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(result))
+}
+
+/// DELETE /events/:id/transactions/:transaction_id
+/// Removes a transaction from the event.
+async fn unassign_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, transaction_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    info!(
+        "Handler: Removing transaction {} from event {}",
+        transaction_id, id
+    );
+    event::unassign_transaction(&pool, ctx.tenant_id, id, transaction_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /events/:id/summary
+/// Totals of the event's transactions by currency and category.
+async fn get_event_summary(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EventSummary>, AppError> {
+    info!("Handler: Event summary for event {}", id);
+    let summary = event::event_summary(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(summary))
+}
+
+/// GET /events/:id/export?delimiter=&decimal_comma=&date_format=&bom=
+/// Downloads the event's transactions and totals as one CSV report.
+async fn export_event_report(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    Query(format): Query<CsvFormatQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Handler: Exporting event {} as CSV", id);
+    let format = CsvFormat::from_query(&format)?;
+    let rendered =
+        report_export::render_event_report(&pool, ctx.tenant_id, id, &access, &format).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", rendered.file_name),
+            ),
+        ],
+        rendered.csv,
+    ))
+}
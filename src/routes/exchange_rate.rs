@@ -0,0 +1,227 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use chrono::Utc;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::exchange_rate_dto::{
+            ConvertCurrencyQuery, CreateExchangeRateDto, UpdateExchangeRateDto,
+            UpdateExchangeRateFetchSettingsDto,
+        },
+        exchange_rate::{
+            Conversion, ExchangeRate, ExchangeRateFetchSettings, ExchangeRateRefreshResult,
+        },
+    },
+    services::{currency_conversion, exchange_rate, reference_cache},
+};
+
+/// Creates a router for the tenant's exchange rates and their automatic fetching.
+/// Fetch settings and refreshes require the `rates.manage` permission. Shared rates
+/// (without a tenant) can be read here but only changed by the tenant owning a rate.
+///
+/// All routes defined here will be nested under `/api/v1/exchange-rates`.
+pub fn exchange_rate_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_exchange_rates).post(create_exchange_rate))
+        .route("/convert", get(convert_amount))
+        .route(
+            "/auto-fetch",
+            get(get_fetch_settings).put(update_fetch_settings),
+        )
+        .route("/refresh", post(refresh_exchange_rates))
+        .route(
+            "/:id",
+            get(get_exchange_rate)
+                .put(update_exchange_rate)
+                .delete(delete_exchange_rate),
+        )
+}
+
+/// Loads a rate the tenant may see: its own or a shared one. Other tenants' rates are
+/// reported as missing.
+async fn visible_rate(
+    pool: &PgPool,
+    ctx: &TenantContext,
+    id: Uuid,
+) -> Result<ExchangeRate, AppError> {
+    let rate = exchange_rate::get_exchange_rate_by_id(pool, id).await?;
+    match rate.tenant_id {
+        Some(tenant_id) if tenant_id != ctx.tenant_id => Err(AppError::NotFound(format!(
+            "Exchange rate with ID {} not found",
+            id
+        ))),
+        _ => Ok(rate),
+    }
+}
+
+/// Loads a rate owned by the tenant, refusing shared rates.
+async fn owned_rate(
+    pool: &PgPool,
+    ctx: &TenantContext,
+    id: Uuid,
+) -> Result<ExchangeRate, AppError> {
+    let rate = visible_rate(pool, ctx, id).await?;
+    if rate.tenant_id.is_none() {
+        return Err(AppError::Forbidden(format!(
+            "Exchange rate {} is shared and cannot be changed",
+            id
+        )));
+    }
+    Ok(rate)
+}
+
+/// GET /exchange-rates
+/// Lists the tenant's exchange rates, newest first.
+async fn list_exchange_rates(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<
+    (
+        [(header::HeaderName, &'static str); 1],
+        Json<Vec<ExchangeRate>>,
+    ),
+    AppError,
+> {
+    info!(
+        "Handler: Listing exchange rates for tenant {}",
+        ctx.tenant_id
+    );
+    let rates = exchange_rate::list_exchange_rates(&pool, Some(ctx.tenant_id)).await?;
+    Ok((
+        [(header::CACHE_CONTROL, reference_cache::CACHE_CONTROL)],
+        Json(rates),
+    ))
+}
+
+/// POST /exchange-rates
+/// Records a rate for the tenant.
+async fn create_exchange_rate(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(mut dto): ValidatedJson<CreateExchangeRateDto>,
+) -> Result<(StatusCode, Json<ExchangeRate>), AppError> {
+    info!(
+        "Handler: Creating exchange rate for tenant {}",
+        ctx.tenant_id
+    );
+    dto.tenant_id = Some(ctx.tenant_id);
+    let rate = exchange_rate::create_exchange_rate(&pool, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(rate)))
+}
+
+/// GET /exchange-rates/:id
+/// Retrieves one of the tenant's rates or a shared rate.
+async fn get_exchange_rate(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ExchangeRate>, AppError> {
+    info!("Handler: Getting exchange rate {}", id);
+    let rate = visible_rate(&pool, &ctx, id).await?;
+    Ok(Json(rate))
+}
+
+/// PUT /exchange-rates/:id
+/// Updates one of the tenant's rates.
+async fn update_exchange_rate(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateExchangeRateDto>,
+) -> Result<Json<ExchangeRate>, AppError> {
+    info!("Handler: Updating exchange rate {}", id);
+    owned_rate(&pool, &ctx, id).await?;
+    let rate = exchange_rate::update_exchange_rate(&pool, id, ctx.user_id, dto).await?;
+    Ok(Json(rate))
+}
+
+/// DELETE /exchange-rates/:id
+/// Deletes one of the tenant's rates.
+async fn delete_exchange_rate(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting exchange rate {}", id);
+    owned_rate(&pool, &ctx, id).await?;
+    exchange_rate::delete_exchange_rate(&pool, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /exchange-rates/convert?amount=&from=&to=&date=
+/// Converts an amount with the best available rate and reports how the rate was found.
+async fn convert_amount(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<ConvertCurrencyQuery>,
+) -> Result<Json<Conversion>, AppError> {
+    info!(
+        "Handler: Converting {} to {} for tenant {}",
+        query.from, query.to, ctx.tenant_id
+    );
+    let date = query.date.unwrap_or_else(|| Utc::now().date_naive());
+    let conversion = currency_conversion::convert(
+        &pool,
+        ctx.tenant_id,
+        query.amount,
+        &query.from,
+        &query.to,
+        date,
+    )
+    .await?;
+    Ok(Json(conversion))
+}
+
+/// GET /exchange-rates/auto-fetch
+/// Retrieves the tenant's automatic fetch settings and last fetch outcome.
+async fn get_fetch_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<ExchangeRateFetchSettings>, AppError> {
+    info!(
+        "Handler: Getting exchange rate fetch settings for tenant {}",
+        ctx.tenant_id
+    );
+    let settings = exchange_rate::get_fetch_settings(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(settings))
+}
+
+/// PUT /exchange-rates/auto-fetch
+/// Opts the tenant in or out of the daily rate fetch.
+async fn update_fetch_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<UpdateExchangeRateFetchSettingsDto>,
+) -> Result<Json<ExchangeRateFetchSettings>, AppError> {
+    info!(
+        "Handler: Updating exchange rate fetch settings for tenant {}",
+        ctx.tenant_id
+    );
+    let settings =
+        exchange_rate::update_fetch_settings(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(settings))
+}
+
+/// POST /exchange-rates/refresh
+/// Fetches the provider's latest rates for the tenant now.
+async fn refresh_exchange_rates(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<ExchangeRateRefreshResult>, AppError> {
+    info!(
+        "Handler: Refreshing exchange rates for tenant {}",
+        ctx.tenant_id
+    );
+    let result = exchange_rate::refresh_exchange_rates(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(result))
+}
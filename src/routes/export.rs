@@ -0,0 +1,218 @@
+use axum::{
+    body::Body,
+    extract::{Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Router,
+};
+use chrono::{Datelike, Utc};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::csv_format_dto::CsvFormatQuery, dto::custom_report_dto::ExportCustomReportQuery,
+        dto::tenant_backup_dto::CreateTenantBackupDto, export_artifact::ExportArtifact,
+    },
+    services::{export_artifact, field_policy::FieldAccess, file_storage, tenant_backup},
+    utils::{
+        csv_format::CsvFormat,
+        http_range::{self, ByteRange},
+    },
+};
+
+/// Creates a router for export artifacts: report files rendered in the background and kept
+/// until `EXPORT_TTL_HOURS` have passed, for large exports that are downloaded (and resumed)
+/// rather than returned in one response.
+///
+/// All routes defined here will be nested under `/api/v1/exports`.
+pub fn export_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_exports))
+        .route("/custom-reports/:id", post(start_custom_report_export))
+        .route("/events/:id", post(start_event_report_export))
+        .route("/tenant-backup", post(start_tenant_backup))
+        .route("/:id", get(get_export).delete(delete_export))
+        .route("/:id/download", get(download_export))
+}
+
+/// POST /exports/custom-reports/:id?from_date=&to_date=&delimiter=&decimal_comma=&date_format=&bom=
+/// Starts exporting a custom report as CSV (defaults to month to date); poll the artifact.
+async fn start_custom_report_export(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ExportCustomReportQuery>,
+    Query(format): Query<CsvFormatQuery>,
+) -> Result<(StatusCode, Json<ExportArtifact>), AppError> {
+    info!("Handler: Starting export of custom report {}", id);
+    let format = CsvFormat::from_query(&format)?;
+    let to_date = query.to_date.unwrap_or_else(|| Utc::now().date_naive());
+    let from_date = query
+        .from_date
+        .unwrap_or_else(|| to_date.with_day(1).expect("day 1 is always valid"));
+    let artifact = export_artifact::start_custom_report_export(
+        &pool,
+        ctx.tenant_id,
+        ctx.user_id,
+        id,
+        from_date,
+        to_date,
+        access,
+        format,
+    )
+    .await?;
+    Ok((StatusCode::ACCEPTED, Json(artifact)))
+}
+
+/// POST /exports/events/:id?delimiter=&decimal_comma=&date_format=&bom=
+/// Starts exporting an event's transactions and totals as CSV; poll the artifact.
+async fn start_event_report_export(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    Query(format): Query<CsvFormatQuery>,
+) -> Result<(StatusCode, Json<ExportArtifact>), AppError> {
+    info!("Handler: Starting export of event {}", id);
+    let format = CsvFormat::from_query(&format)?;
+    let artifact = export_artifact::start_event_report_export(
+        &pool,
+        ctx.tenant_id,
+        ctx.user_id,
+        id,
+        access,
+        format,
+    )
+    .await?;
+    Ok((StatusCode::ACCEPTED, Json(artifact)))
+}
+
+/// POST /exports/tenant-backup
+/// Starts a full backup of the tenant's books as a ZIP, encrypted when a passphrase is given;
+/// poll the artifact. Requires `tenant.backup`, or `tenant.export_anonymized` with
+/// `"anonymized": true` for a scrambled copy to reproduce problems with.
+async fn start_tenant_backup(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateTenantBackupDto>,
+) -> Result<(StatusCode, Json<ExportArtifact>), AppError> {
+    info!("Handler: Starting backup of tenant {}", ctx.tenant_id);
+    let artifact =
+        tenant_backup::start_tenant_backup(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::ACCEPTED, Json(artifact)))
+}
+
+/// GET /exports
+/// Lists the current user's recent exports, newest first.
+async fn list_exports(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<ExportArtifact>>, AppError> {
+    info!("Handler: Listing exports for tenant {}", ctx.tenant_id);
+    let artifacts = export_artifact::list_exports(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(artifacts))
+}
+
+/// GET /exports/:id
+/// Retrieves an export, to poll its status.
+async fn get_export(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ExportArtifact>, AppError> {
+    info!("Handler: Getting export {}", id);
+    let artifact = export_artifact::get_export(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(Json(artifact))
+}
+
+/// DELETE /exports/:id
+/// Deletes an export and its file.
+async fn delete_export(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting export {}", id);
+    export_artifact::delete_export(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /exports/:id/download
+/// Downloads a ready export; 410 Gone once expired. On the S3 backend this redirects to a
+/// short-lived presigned URL. Otherwise a single `Range` is answered with 206 Partial
+/// Content, and `If-Range` with the ETag resumes only if the file is unchanged.
+async fn download_export(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    info!("Handler: Downloading export {}", id);
+    let file = export_artifact::get_export_file(&pool, ctx.tenant_id, ctx.user_id, id).await?;
+
+    if let Some(url) = file_storage::presigned_download_url(&file.storage_key, &file.file_name)? {
+        return Ok(Redirect::temporary(&url).into_response());
+    }
+
+    let etag = format!("\"{}\"", file.sha256);
+    // A Range is only honoured while If-Range (if sent) still matches the file
+    let if_range_matches = headers
+        .get(header::IF_RANGE)
+        .is_none_or(|value| value.as_bytes() == etag.as_bytes());
+    let range_header = headers
+        .get(header::RANGE)
+        .filter(|_| if_range_matches)
+        .and_then(|value| value.to_str().ok());
+
+    let size = file.size_bytes;
+    let (status, start, len, content_range) = match http_range::parse_range(range_header, size) {
+        ByteRange::Full => (StatusCode::OK, 0, size, None),
+        ByteRange::Partial { start, end } => (
+            StatusCode::PARTIAL_CONTENT,
+            start,
+            end - start + 1,
+            Some(format!("bytes {}-{}/{}", start, end, size)),
+        ),
+        ByteRange::Unsatisfiable => {
+            return Ok((
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", size))],
+                Body::empty(),
+            )
+                .into_response())
+        }
+    };
+
+    let body = file_storage::local_range_body(&file.storage_key, start, len).await?;
+    let mut response = (
+        status,
+        [
+            (header::CONTENT_TYPE, file.content_type),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file.file_name),
+            ),
+            (header::CONTENT_LENGTH, len.to_string()),
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+            (header::ETAG, etag),
+        ],
+        body,
+    )
+        .into_response();
+    if let Some(content_range) = content_range {
+        response.headers_mut().insert(
+            header::CONTENT_RANGE,
+            content_range
+                .parse()
+                .expect("a Content-Range value is a valid header"),
+        );
+    }
+    Ok(response)
+}
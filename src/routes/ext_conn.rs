@@ -0,0 +1,190 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Router,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::ext_conn_dto::{CreateExtConnDto, ExtConnSyncSummary, UpdateExtConnDto},
+        dto::external_account_dto::UpdateExternalAccountDto,
+        dto::transaction_match_dto::MatchRunSummary,
+        ext_conn::ExtConn,
+        external_account::ExternalAccount,
+        external_transactions_staging::{ExternalTransactionsStaging, StagingStatus},
+    },
+    services::{ext_conn, transaction_matching},
+};
+
+/// Creates a router for bank connections and their synced accounts.
+///
+/// All routes defined here will be nested under `/api/v1/bank-connections`.
+pub fn ext_conn_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_ext_conns).post(create_ext_conn))
+        .route(
+            "/:id",
+            get(get_ext_conn)
+                .put(update_ext_conn)
+                .delete(disconnect_ext_conn),
+        )
+        .route("/:id/sync", post(sync_ext_conn))
+        .route("/:id/match", post(match_ext_conn))
+        .route("/:id/accounts", get(list_external_accounts))
+        .route("/:id/accounts/:account_id", put(update_external_account))
+        .route("/:id/staged-transactions", get(list_staged_transactions))
+}
+
+#[derive(Debug, Deserialize)]
+struct StagedTransactionsQuery {
+    status: Option<StagingStatus>,
+}
+
+/// GET /bank-connections
+/// Lists the tenant's bank connections. Access tokens are never returned.
+async fn list_ext_conns(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<ExtConn>>, AppError> {
+    info!(
+        "Handler: Listing bank connections for tenant {}",
+        ctx.tenant_id
+    );
+    let conns = ext_conn::list_ext_conns(&pool, ctx.tenant_id).await?;
+    Ok(Json(conns))
+}
+
+/// GET /bank-connections/:id
+/// Retrieves a single bank connection.
+async fn get_ext_conn(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ExtConn>, AppError> {
+    info!("Handler: Getting bank connection {}", id);
+    let conn = ext_conn::get_ext_conn_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(conn))
+}
+
+/// POST /bank-connections
+/// Links a new bank connection from a provider link-flow token.
+async fn create_ext_conn(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateExtConnDto>,
+) -> Result<(StatusCode, Json<ExtConn>), AppError> {
+    info!(
+        "Handler: Creating bank connection for provider {}",
+        dto.provider_id
+    );
+    let conn = ext_conn::create_ext_conn(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(conn)))
+}
+
+/// PUT /bank-connections/:id
+/// Updates a bank connection, e.g. after re-authentication.
+async fn update_ext_conn(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateExtConnDto>,
+) -> Result<Json<ExtConn>, AppError> {
+    info!("Handler: Updating bank connection {}", id);
+    let conn = ext_conn::update_ext_conn(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(conn))
+}
+
+/// DELETE /bank-connections/:id
+/// Disconnects a bank connection (soft delete).
+async fn disconnect_ext_conn(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Disconnecting bank connection {}", id);
+    ext_conn::disconnect_ext_conn(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /bank-connections/:id/sync
+/// Pulls new transactions from the provider into the staging table now.
+async fn sync_ext_conn(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ExtConnSyncSummary>, AppError> {
+    info!("Handler: Syncing bank connection {}", id);
+    let summary = ext_conn::sync_ext_conn(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(summary))
+}
+
+/// POST /bank-connections/:id/match
+/// Proposes ledger matches for pending staged rows and flags re-delivered duplicates.
+async fn match_ext_conn(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MatchRunSummary>, AppError> {
+    info!(
+        "Handler: Matching staged transactions for bank connection {}",
+        id
+    );
+    let summary =
+        transaction_matching::run_matching_for_connection(&pool, ctx.tenant_id, id, ctx.user_id)
+            .await?;
+    Ok(Json(summary))
+}
+
+/// GET /bank-connections/:id/accounts
+/// Lists the external accounts discovered under a connection.
+async fn list_external_accounts(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<ExternalAccount>>, AppError> {
+    info!(
+        "Handler: Listing external accounts for bank connection {}",
+        id
+    );
+    let accounts = ext_conn::list_external_accounts(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(accounts))
+}
+
+/// PUT /bank-connections/:id/accounts/:account_id
+/// Links an external account to a ledger account or toggles syncing.
+async fn update_external_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, account_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(dto): ValidatedJson<UpdateExternalAccountDto>,
+) -> Result<Json<ExternalAccount>, AppError> {
+    info!("Handler: Updating external account {}", account_id);
+    let account =
+        ext_conn::update_external_account(&pool, ctx.tenant_id, id, account_id, ctx.user_id, dto)
+            .await?;
+    Ok(Json(account))
+}
+
+/// GET /bank-connections/:id/staged-transactions
+/// Lists staged transactions for review, optionally filtered by `?status=`.
+async fn list_staged_transactions(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    Query(query): Query<StagedTransactionsQuery>,
+) -> Result<Json<Vec<ExternalTransactionsStaging>>, AppError> {
+    info!(
+        "Handler: Listing staged transactions for bank connection {}",
+        id
+    );
+    let rows = ext_conn::list_staged_transactions(&pool, ctx.tenant_id, id, query.status).await?;
+    Ok(Json(rows))
+}
@@ -0,0 +1,81 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::AuthUser, validated_json::ValidatedJson},
+    models::{
+        dto::ext_provider_dto::{CreateExtProviderDto, UpdateExtProviderDto},
+        ext_provider::ExtProvider,
+    },
+    services::ext_provider,
+};
+
+/// Creates a router for the external provider catalogue.
+///
+/// All routes defined here will be nested under `/api/v1/ext-providers`.
+pub fn ext_provider_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_ext_providers).post(create_ext_provider))
+        .route("/:id", get(get_ext_provider).put(update_ext_provider))
+}
+
+/// GET /ext-providers
+/// Lists active external providers available for linking.
+async fn list_ext_providers(
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<ExtProvider>>, AppError> {
+    info!("Handler: Listing external providers");
+    let providers = ext_provider::list_ext_providers(&pool).await?;
+    Ok(Json(providers))
+}
+
+/// GET /ext-providers/:id
+/// Retrieves a single external provider.
+async fn get_ext_provider(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ExtProvider>, AppError> {
+    info!("Handler: Getting external provider {}", id);
+    let provider = ext_provider::get_ext_provider_by_id(&pool, id).await?;
+    Ok(Json(provider))
+}
+
+/// POST /ext-providers
+/// Registers a new external provider (system administrators only).
+async fn create_ext_provider(
+    State(pool): State<PgPool>,
+    AuthUser {
+        user_id: created_by_user_id,
+    }: AuthUser,
+    ValidatedJson(dto): ValidatedJson<CreateExtProviderDto>,
+) -> Result<(StatusCode, Json<ExtProvider>), AppError> {
+    info!("Handler: Creating external provider '{}'", dto.code);
+
+    let provider = ext_provider::create_ext_provider(&pool, created_by_user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(provider)))
+}
+
+/// PUT /ext-providers/:id
+/// Updates an external provider (system administrators only).
+async fn update_ext_provider(
+    State(pool): State<PgPool>,
+    AuthUser {
+        user_id: updated_by_user_id,
+    }: AuthUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateExtProviderDto>,
+) -> Result<Json<ExtProvider>, AppError> {
+    info!("Handler: Updating external provider {}", id);
+
+    let provider = ext_provider::update_ext_provider(&pool, id, updated_by_user_id, dto).await?;
+    Ok(Json(provider))
+}
@@ -0,0 +1,91 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{dto::fiscal_period_dto::CreateFiscalPeriodDto, fiscal_period::FiscalPeriod},
+    services::fiscal_period,
+};
+
+/// Creates a router for fiscal periods and period close.
+///
+/// All routes defined here will be nested under `/api/v1/fiscal-periods`.
+pub fn fiscal_period_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_fiscal_periods).post(create_fiscal_period))
+        .route("/:id", get(get_fiscal_period))
+        .route("/:id/close", post(close_fiscal_period))
+        .route("/:id/reopen", post(reopen_fiscal_period))
+}
+
+/// GET /fiscal-periods
+/// Lists the tenant's fiscal periods.
+async fn list_fiscal_periods(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<FiscalPeriod>>, AppError> {
+    info!(
+        "Handler: Listing fiscal periods for tenant {}",
+        ctx.tenant_id
+    );
+    let periods = fiscal_period::list_fiscal_periods(&pool, ctx.tenant_id).await?;
+    Ok(Json(periods))
+}
+
+/// GET /fiscal-periods/:id
+/// Retrieves a single fiscal period.
+async fn get_fiscal_period(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FiscalPeriod>, AppError> {
+    info!("Handler: Getting fiscal period {}", id);
+    let period = fiscal_period::get_fiscal_period_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(period))
+}
+
+/// POST /fiscal-periods
+/// Creates an open fiscal period.
+async fn create_fiscal_period(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateFiscalPeriodDto>,
+) -> Result<(StatusCode, Json<FiscalPeriod>), AppError> {
+    info!("Handler: Creating fiscal period '{}'", dto.name);
+    let period =
+        fiscal_period::create_fiscal_period(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(period)))
+}
+
+/// POST /fiscal-periods/:id/close
+/// Closes a period; transactions dated inside it become locked.
+async fn close_fiscal_period(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FiscalPeriod>, AppError> {
+    info!("Handler: Closing fiscal period {}", id);
+    let period = fiscal_period::close_fiscal_period(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(period))
+}
+
+/// POST /fiscal-periods/:id/reopen
+/// Reopens a closed period. Requires the `period.reopen` permission.
+async fn reopen_fiscal_period(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FiscalPeriod>, AppError> {
+    info!("Handler: Reopening fiscal period {}", id);
+    let period = fiscal_period::reopen_fiscal_period(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(period))
+}
@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::fixed_asset_dto::{
+            CreateFixedAssetDto, DepreciationReportQuery, ListFixedAssetsQuery, UpdateFixedAssetDto,
+        },
+        fixed_asset::{DepreciationReport, DepreciationSchedule, FixedAsset},
+    },
+    services::fixed_asset,
+};
+
+/// Creates a router for fixed assets and their depreciation.
+///
+/// All routes defined here will be nested under `/api/v1/fixed-assets`.
+pub fn fixed_asset_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_fixed_assets).post(create_fixed_asset))
+        .route("/depreciation", get(get_depreciation_report))
+        .route(
+            "/:id",
+            get(get_fixed_asset)
+                .put(update_fixed_asset)
+                .delete(delete_fixed_asset),
+        )
+        .route("/:id/schedule", get(get_schedule))
+}
+
+/// GET /fixed-assets?status=
+/// Lists the tenant's fixed assets.
+async fn list_fixed_assets(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<ListFixedAssetsQuery>,
+) -> Result<Json<Vec<FixedAsset>>, AppError> {
+    info!("Handler: Listing fixed assets for tenant {}", ctx.tenant_id);
+    let assets = fixed_asset::list_fixed_assets(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(assets))
+}
+
+/// POST /fixed-assets
+/// Registers a fixed asset.
+async fn create_fixed_asset(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateFixedAssetDto>,
+) -> Result<(StatusCode, Json<FixedAsset>), AppError> {
+    info!(
+        "Handler: Registering fixed asset for tenant {}",
+        ctx.tenant_id
+    );
+    let asset = fixed_asset::create_fixed_asset(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(asset)))
+}
+
+/// GET /fixed-assets/:id
+/// Retrieves a fixed asset.
+async fn get_fixed_asset(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FixedAsset>, AppError> {
+    info!("Handler: Getting fixed asset {}", id);
+    let asset = fixed_asset::get_fixed_asset(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(asset))
+}
+
+/// PUT /fixed-assets/:id
+/// Updates a fixed asset.
+async fn update_fixed_asset(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateFixedAssetDto>,
+) -> Result<Json<FixedAsset>, AppError> {
+    info!("Handler: Updating fixed asset {}", id);
+    let asset = fixed_asset::update_fixed_asset(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(asset))
+}
+
+/// DELETE /fixed-assets/:id
+/// Deletes a fixed asset with no posted depreciation.
+async fn delete_fixed_asset(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting fixed asset {}", id);
+    fixed_asset::delete_fixed_asset(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /fixed-assets/:id/schedule
+/// The asset's month-by-month depreciation over its useful life.
+async fn get_schedule(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DepreciationSchedule>, AppError> {
+    info!("Handler: Depreciation schedule of fixed asset {}", id);
+    let schedule = fixed_asset::get_schedule(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(schedule))
+}
+
+/// GET /fixed-assets/depreciation?from=&to=
+/// Depreciation of every asset between two dates, with book values.
+async fn get_depreciation_report(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<DepreciationReportQuery>,
+) -> Result<Json<DepreciationReport>, AppError> {
+    info!("Handler: Depreciation report for tenant {}", ctx.tenant_id);
+    let report = fixed_asset::depreciation_report(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(report))
+}
@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Json, State},
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::fx_revaluation_dto::{RunFxRevaluationDto, UpsertFxRevaluationSettingsDto},
+        fx_revaluation::{FxRevaluation, FxRevaluationResult, FxRevaluationSettings},
+    },
+    services::fx_revaluation,
+};
+
+/// Creates a router for period-end revaluation of foreign-currency accounts. All routes
+/// require the `fx.revalue` permission.
+///
+/// All routes defined here will be nested under `/api/v1/fx-revaluations`.
+pub fn fx_revaluation_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_revaluations).post(run_revaluation))
+        .route("/settings", get(get_settings).put(upsert_settings))
+}
+
+/// GET /fx-revaluations
+/// Lists posted revaluations, newest first.
+async fn list_revaluations(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<FxRevaluation>>, AppError> {
+    info!(
+        "Handler: Listing FX revaluations for tenant {}",
+        ctx.tenant_id
+    );
+    let revaluations = fx_revaluation::list_revaluations(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(revaluations))
+}
+
+/// POST /fx-revaluations
+/// Revalues foreign-currency accounts as of a date or fiscal period end; `dry_run` previews.
+async fn run_revaluation(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<RunFxRevaluationDto>,
+) -> Result<Json<FxRevaluationResult>, AppError> {
+    info!(
+        "Handler: Running FX revaluation for tenant {}",
+        ctx.tenant_id
+    );
+    let result = fx_revaluation::run_revaluation(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(result))
+}
+
+/// GET /fx-revaluations/settings
+/// Retrieves the unrealized gain and loss accounts.
+async fn get_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<FxRevaluationSettings>, AppError> {
+    info!(
+        "Handler: Getting FX revaluation settings for tenant {}",
+        ctx.tenant_id
+    );
+    let settings = fx_revaluation::get_settings(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(settings))
+}
+
+/// PUT /fx-revaluations/settings
+/// Sets the unrealized gain and loss accounts.
+async fn upsert_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<UpsertFxRevaluationSettingsDto>,
+) -> Result<Json<FxRevaluationSettings>, AppError> {
+    info!(
+        "Handler: Configuring FX revaluation accounts for tenant {}",
+        ctx.tenant_id
+    );
+    let settings = fx_revaluation::upsert_settings(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(settings))
+}
@@ -0,0 +1,50 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    models::{integration_health::IntegrationHealthReport, maintenance::MaintenanceRun},
+    services::{integration_health, maintenance},
+};
+
+/// Task runs listed by `GET /healthz/maintenance`: about two weeks of daily runs.
+const RECENT_MAINTENANCE_RUNS: i64 = 50;
+
+/// Creates a router for health probes.
+///
+/// All routes defined here will be nested under `/healthz` (outside `/api/v1`).
+pub fn health_routes() -> Router<AppState> {
+    Router::new()
+        .route("/integrations", get(integrations))
+        .route("/maintenance", get(maintenance_runs))
+}
+
+/// GET /healthz/integrations
+/// Per-dependency status of external services; 503 when a configured dependency is failing.
+async fn integrations() -> (StatusCode, Json<IntegrationHealthReport>) {
+    info!("Handler: Integration health check");
+    let report = integration_health::integration_health().await;
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// GET /healthz/maintenance
+/// Recent runs of the database maintenance tasks, newest first, with what each did.
+async fn maintenance_runs(
+    State(pool): State<PgPool>,
+) -> Result<Json<Vec<MaintenanceRun>>, AppError> {
+    info!("Handler: Listing maintenance runs");
+    let runs = maintenance::list_recent_runs(&pool, RECENT_MAINTENANCE_RUNS).await?;
+    Ok(Json(runs))
+}
@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Json, Query, State},
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::auth::TenantContext,
+    models::{
+        dto::household_dto::HouseholdPeriodQuery,
+        household::{HouseholdSpending, SettleUp},
+    },
+    services::household,
+};
+
+/// Creates a router for household reports: spending per member and settling up.
+///
+/// All routes defined here will be nested under `/api/v1/household`.
+/// Transactions are attributed with `PUT /transactions/:id/attribution`.
+pub fn household_routes() -> Router<AppState> {
+    Router::new()
+        .route("/spending", get(get_household_spending))
+        .route("/settle-up", get(get_settle_up))
+}
+
+/// GET /household/spending?from_date=&to_date=
+/// Expenses per member and currency, split into personal and shared.
+async fn get_household_spending(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<HouseholdPeriodQuery>,
+) -> Result<Json<HouseholdSpending>, AppError> {
+    info!("Handler: Household spending for tenant {}", ctx.tenant_id);
+    let spending = household::household_spending(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(spending))
+}
+
+/// GET /household/settle-up?from_date=&to_date=&member_ids=
+/// Who owes whom for the shared expenses of the period.
+async fn get_settle_up(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<HouseholdPeriodQuery>,
+) -> Result<Json<SettleUp>, AppError> {
+    info!("Handler: Settle-up for tenant {}", ctx.tenant_id);
+    let settle_up = household::settle_up(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(settle_up))
+}
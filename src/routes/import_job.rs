@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::header,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState, error::AppError, middleware::auth::TenantContext,
+    models::import_job::ImportJob, services::import_job,
+};
+
+/// Creates a router for background import jobs (started from the importers, e.g.
+/// POST /budgets/import-jobs).
+///
+/// All routes defined here will be nested under `/api/v1/import-jobs`.
+pub fn import_job_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_import_jobs))
+        .route("/:id", get(get_import_job))
+        .route("/:id/errors", get(download_error_file))
+}
+
+/// GET /import-jobs
+/// Lists the tenant's recent import jobs, newest first.
+async fn list_import_jobs(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<ImportJob>>, AppError> {
+    info!("Handler: Listing import jobs for tenant {}", ctx.tenant_id);
+    let jobs = import_job::list_import_jobs(&pool, ctx.tenant_id).await?;
+    Ok(Json(jobs))
+}
+
+/// GET /import-jobs/:id
+/// Status and processed/total row counts of an import job.
+async fn get_import_job(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ImportJob>, AppError> {
+    info!("Handler: Getting import job {}", id);
+    let job = import_job::get_import_job(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(job))
+}
+
+/// GET /import-jobs/:id/errors
+/// Downloads the failed rows as CSV, with `error_field` and `error_message` columns.
+async fn download_error_file(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Handler: Downloading error file of import job {}", id);
+    let (file_name, csv_text) = import_job::get_error_file(&pool, ctx.tenant_id, id).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        csv_text,
+    ))
+}
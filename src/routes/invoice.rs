@@ -0,0 +1,153 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::invoice_dto::{
+            CreateInvoiceDto, ListInvoicesQuery, RecordInvoicePaymentDto, UpdateInvoiceDto,
+            UpdateInvoiceNumberingDto,
+        },
+        invoice::{Invoice, InvoiceDetail, InvoiceNumbering},
+    },
+    services::invoice,
+};
+
+/// Creates a router for invoices, their payments and their numbering.
+///
+/// All routes defined here will be nested under `/api/v1/invoices`.
+pub fn invoice_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_invoices).post(create_invoice))
+        .route("/numbering", get(get_numbering).put(update_numbering))
+        .route(
+            "/:id",
+            get(get_invoice).put(update_invoice).delete(delete_invoice),
+        )
+        .route("/:id/issue", post(issue_invoice))
+        .route("/:id/payments", post(record_payment))
+}
+
+/// GET /invoices?status=&customer_id=&overdue=
+/// Lists the tenant's invoices, most recent first.
+async fn list_invoices(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<ListInvoicesQuery>,
+) -> Result<Json<Vec<Invoice>>, AppError> {
+    info!("Handler: Listing invoices for tenant {}", ctx.tenant_id);
+    let invoices = invoice::list_invoices(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(invoices))
+}
+
+/// POST /invoices
+/// Creates a draft invoice.
+async fn create_invoice(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateInvoiceDto>,
+) -> Result<(StatusCode, Json<InvoiceDetail>), AppError> {
+    info!("Handler: Creating invoice for tenant {}", ctx.tenant_id);
+    let invoice = invoice::create_invoice(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(invoice)))
+}
+
+/// GET /invoices/:id
+/// Retrieves an invoice with its lines and payments.
+async fn get_invoice(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<InvoiceDetail>, AppError> {
+    info!("Handler: Getting invoice {}", id);
+    let invoice = invoice::get_invoice(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(invoice))
+}
+
+/// PUT /invoices/:id
+/// Updates a draft invoice.
+async fn update_invoice(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateInvoiceDto>,
+) -> Result<Json<InvoiceDetail>, AppError> {
+    info!("Handler: Updating invoice {}", id);
+    let invoice = invoice::update_invoice(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(invoice))
+}
+
+/// DELETE /invoices/:id
+/// Deletes a draft invoice.
+async fn delete_invoice(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting invoice {}", id);
+    invoice::delete_invoice(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /invoices/:id/issue
+/// Numbers a draft invoice and posts it to receivables and revenue.
+async fn issue_invoice(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<InvoiceDetail>, AppError> {
+    info!("Handler: Issuing invoice {}", id);
+    let invoice = invoice::issue_invoice(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Json(invoice))
+}
+
+/// POST /invoices/:id/payments
+/// Records a payment received against an issued invoice.
+async fn record_payment(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<RecordInvoicePaymentDto>,
+) -> Result<(StatusCode, Json<InvoiceDetail>), AppError> {
+    info!("Handler: Recording payment against invoice {}", id);
+    let invoice = invoice::record_payment(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(invoice)))
+}
+
+/// GET /invoices/numbering
+/// Retrieves how invoice numbers are built and the next one to be used.
+async fn get_numbering(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<InvoiceNumbering>, AppError> {
+    info!(
+        "Handler: Getting invoice numbering for tenant {}",
+        ctx.tenant_id
+    );
+    let numbering = invoice::get_numbering(&pool, ctx.tenant_id).await?;
+    Ok(Json(numbering))
+}
+
+/// PUT /invoices/numbering
+/// Changes the prefix, padding or next number of invoice numbers.
+async fn update_numbering(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<UpdateInvoiceNumberingDto>,
+) -> Result<Json<InvoiceNumbering>, AppError> {
+    info!(
+        "Handler: Updating invoice numbering for tenant {}",
+        ctx.tenant_id
+    );
+    let numbering = invoice::update_numbering(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(numbering))
+}
@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, field_policy::Redacted, validated_json::ValidatedJson},
+    models::{
+        dto::journal_entry_dto::{CreateJournalEntryDto, UpdateJournalEntryDto},
+        journal_entry::JournalEntry,
+    },
+    services::{field_policy::FieldAccess, journal_entry},
+};
+
+/// Creates a router for the journal entries of transactions.
+///
+/// All routes defined here will be nested under `/api/v1/transactions`.
+pub fn journal_entry_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/:id/journal-entries",
+            get(list_journal_entries).post(create_journal_entry),
+        )
+        .route(
+            "/journal-entries/:entry_id",
+            get(get_journal_entry)
+                .put(update_journal_entry)
+                .delete(delete_journal_entry),
+        )
+}
+
+/// GET /transactions/:id/journal-entries
+/// Lists the journal entries of a transaction.
+async fn list_journal_entries(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+) -> Result<Redacted<Vec<JournalEntry>>, AppError> {
+    info!("Handler: Listing journal entries of transaction {}", id);
+    let entries =
+        journal_entry::list_journal_entries_for_transaction(&pool, ctx.tenant_id, id).await?;
+    Ok(Redacted(entries, access))
+}
+
+/// POST /transactions/:id/journal-entries
+/// Adds a journal entry to a draft transaction.
+async fn create_journal_entry(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<CreateJournalEntryDto>,
+) -> Result<(StatusCode, Redacted<JournalEntry>), AppError> {
+    info!("Handler: Creating journal entry for transaction {}", id);
+    let entry =
+        journal_entry::create_journal_entry(&pool, ctx.tenant_id, ctx.user_id, id, dto).await?;
+    Ok((StatusCode::CREATED, Redacted(entry, access)))
+}
+
+/// GET /transactions/journal-entries/:entry_id
+/// Retrieves a single journal entry.
+async fn get_journal_entry(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(entry_id): Path<Uuid>,
+) -> Result<Redacted<JournalEntry>, AppError> {
+    info!("Handler: Getting journal entry {}", entry_id);
+    let entry = journal_entry::get_journal_entry_by_id(&pool, ctx.tenant_id, entry_id).await?;
+    Ok(Redacted(entry, access))
+}
+
+/// PUT /transactions/journal-entries/:entry_id
+/// Updates a journal entry of a draft transaction.
+async fn update_journal_entry(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(entry_id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateJournalEntryDto>,
+) -> Result<Redacted<JournalEntry>, AppError> {
+    info!("Handler: Updating journal entry {}", entry_id);
+    let entry =
+        journal_entry::update_journal_entry(&pool, ctx.tenant_id, entry_id, ctx.user_id, dto)
+            .await?;
+    Ok(Redacted(entry, access))
+}
+
+/// DELETE /transactions/journal-entries/:entry_id
+/// Deletes a journal entry of a draft transaction.
+async fn delete_journal_entry(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(entry_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting journal entry {}", entry_id);
+    journal_entry::delete_journal_entry(&pool, ctx.tenant_id, entry_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{dto::mail_settings_dto::UpsertMailSettingsDto, mail_settings::TenantMailSettings},
+    services::mail_settings,
+};
+
+/// Creates a router for the tenant's outgoing mail server. All routes require the
+/// `mail.manage` permission.
+///
+/// All routes defined here will be nested under `/api/v1/mail-settings`.
+pub fn mail_settings_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(get_mail_settings)
+                .put(upsert_mail_settings)
+                .delete(delete_mail_settings),
+        )
+        .route("/test", post(send_test_email))
+}
+
+/// GET /mail-settings
+/// Retrieves the tenant's mail settings (without the password).
+async fn get_mail_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<TenantMailSettings>, AppError> {
+    info!(
+        "Handler: Getting mail settings for tenant {}",
+        ctx.tenant_id
+    );
+    let settings = mail_settings::get_mail_settings(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(settings))
+}
+
+/// PUT /mail-settings
+/// Creates or replaces the tenant's mail settings.
+async fn upsert_mail_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<UpsertMailSettingsDto>,
+) -> Result<Json<TenantMailSettings>, AppError> {
+    info!(
+        "Handler: Configuring mail settings for tenant {}",
+        ctx.tenant_id
+    );
+    let settings =
+        mail_settings::upsert_mail_settings(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(settings))
+}
+
+/// DELETE /mail-settings
+/// Removes the tenant's mail settings; email falls back to the deployment's server.
+async fn delete_mail_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<StatusCode, AppError> {
+    info!(
+        "Handler: Deleting mail settings for tenant {}",
+        ctx.tenant_id
+    );
+    mail_settings::delete_mail_settings(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /mail-settings/test
+/// Sends a test email to the current user through the tenant's mail server.
+async fn send_test_email(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Sending test email for tenant {}", ctx.tenant_id);
+    mail_settings::send_test_email(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::member_migration_dto::{ImportMembersDto, ImportMembersQuery},
+        member_migration::{MemberExport, MemberImportReport},
+    },
+    services::member_migration,
+};
+
+/// Creates a router for moving a tenant's members between deployments.
+///
+/// All routes defined here will be nested under `/api/v1/tenants`.
+pub fn member_migration_routes() -> Router<AppState> {
+    Router::new()
+        .route("/:id/members/export", get(export_members))
+        .route("/:id/members/import", post(import_members))
+}
+
+/// GET /tenants/:id/members/export
+/// Exports the members with their roles by name, for importing into another deployment.
+/// Requires `members.migrate`.
+async fn export_members(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<MemberExport>, AppError> {
+    info!("Handler: Exporting members of tenant {}", tenant_id);
+    ensure_current_tenant(&ctx, tenant_id)?;
+    let export = member_migration::export_members(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(export))
+}
+
+/// POST /tenants/:id/members/import?dry_run=&on_conflict=merge|replace|skip
+/// Imports members matched by email (an export can be sent as it is) and reports the outcome
+/// for each. Nothing is written on a dry run or when any member has an error. Requires
+/// `members.migrate`.
+async fn import_members(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+    Query(query): Query<ImportMembersQuery>,
+    ValidatedJson(dto): ValidatedJson<ImportMembersDto>,
+) -> Result<Json<MemberImportReport>, AppError> {
+    info!("Handler: Importing members into tenant {}", tenant_id);
+    ensure_current_tenant(&ctx, tenant_id)?;
+    let report =
+        member_migration::import_members(&pool, ctx.tenant_id, ctx.user_id, dto, query).await?;
+    Ok(Json(report))
+}
+
+fn ensure_current_tenant(ctx: &TenantContext, tenant_id: Uuid) -> Result<(), AppError> {
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    Ok(())
+}
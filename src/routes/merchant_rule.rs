@@ -0,0 +1,109 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::merchant_rule_dto::{
+            CreateMerchantRuleDto, ReapplyMerchantRulesDto, UpdateMerchantRuleDto,
+        },
+        merchant_rule::{MerchantReapplyResult, MerchantRule},
+    },
+    services::merchant_normalization,
+};
+
+/// Creates a router for the tenant's merchant normalization rules.
+///
+/// All routes defined here will be nested under `/api/v1/merchant-rules`.
+pub fn merchant_rule_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_merchant_rules).post(create_merchant_rule))
+        .route("/reapply", post(reapply_merchant_rules))
+        .route(
+            "/:id",
+            put(update_merchant_rule).delete(delete_merchant_rule),
+        )
+}
+
+/// GET /merchant-rules
+/// Lists the tenant's rules; they take precedence over the bundled dataset.
+async fn list_merchant_rules(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<MerchantRule>>, AppError> {
+    info!(
+        "Handler: Listing merchant rules for tenant {}",
+        ctx.tenant_id
+    );
+    let rules = merchant_normalization::list_merchant_rules(&pool, ctx.tenant_id).await?;
+    Ok(Json(rules))
+}
+
+/// POST /merchant-rules
+/// Maps descriptors containing a pattern, or a payee, to a merchant name and, optionally, a category.
+async fn create_merchant_rule(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateMerchantRuleDto>,
+) -> Result<(StatusCode, Json<MerchantRule>), AppError> {
+    info!(
+        "Handler: Creating merchant rule for tenant {}",
+        ctx.tenant_id
+    );
+    let rule = merchant_normalization::create_merchant_rule(&pool, ctx.tenant_id, ctx.user_id, dto)
+        .await?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+/// PUT /merchant-rules/:id
+/// Updates a rule.
+async fn update_merchant_rule(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateMerchantRuleDto>,
+) -> Result<Json<MerchantRule>, AppError> {
+    info!("Handler: Updating merchant rule {}", id);
+    let rule =
+        merchant_normalization::update_merchant_rule(&pool, ctx.tenant_id, id, ctx.user_id, dto)
+            .await?;
+    Ok(Json(rule))
+}
+
+/// DELETE /merchant-rules/:id
+/// Deletes a rule.
+async fn delete_merchant_rule(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting merchant rule {}", id);
+    merchant_normalization::delete_merchant_rule(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /merchant-rules/reapply
+/// Re-normalizes staged bank transactions (optionally within a date range) with the current rules.
+async fn reapply_merchant_rules(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<ReapplyMerchantRulesDto>,
+) -> Result<Json<MerchantReapplyResult>, AppError> {
+    info!(
+        "Handler: Re-running merchant normalization for tenant {}",
+        ctx.tenant_id
+    );
+    let result =
+        merchant_normalization::reapply_merchant_rules(&pool, ctx.tenant_id, ctx.user_id, dto)
+            .await?;
+    Ok(Json(result))
+}
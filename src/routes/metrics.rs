@@ -0,0 +1,22 @@
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+
+use crate::{app_state::AppState, error::AppError, services::metrics};
+
+/// Creates a router for the Prometheus scrape endpoint.
+///
+/// All routes defined here will be nested under `/metrics` (outside `/api/v1`).
+pub fn metrics_routes() -> Router<AppState> {
+    Router::new().route("/", get(render_metrics))
+}
+
+/// GET /metrics
+/// Request, database pool and job queue metrics in Prometheus text format.
+async fn render_metrics(
+    State(AppState {
+        pools,
+        metrics: handle,
+    }): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let body = metrics::render(&pools.writer, &handle).await?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+}
@@ -1 +1,53 @@
-
+pub mod tenant;
+pub mod currency;
+pub mod account_type;
+pub mod category;
+pub mod journal_entry;
+pub mod budget_line_item;
+pub mod account;
+pub mod recurring_transaction;
+pub mod ext_provider;
+pub mod ext_conn;
+pub mod report;
+pub mod statement_layout;
+pub mod transaction_match;
+pub mod budget;
+pub mod fiscal_period;
+pub mod notification;
+pub mod health;
+pub mod metrics;
+pub mod transaction;
+pub mod security_webhook;
+pub mod dashboard;
+pub mod custom_report;
+pub mod mail_settings;
+pub mod privacy;
+pub mod user_preference;
+pub mod exchange_rate;
+pub mod quick_open;
+pub mod fx_revaluation;
+pub mod import_job;
+pub mod calendar_feed;
+pub mod cash_position;
+pub mod event;
+pub mod customer;
+pub mod invoice;
+pub mod vendor;
+pub mod bill;
+pub mod payment;
+pub mod fixed_asset;
+pub mod payee;
+pub mod dimension;
+pub mod household;
+pub mod merchant_rule;
+pub mod reimbursement;
+pub mod business_calendar;
+pub mod auth;
+pub mod api_key;
+pub mod webhook;
+pub mod export;
+pub mod tenant_invitation;
+pub mod member_migration;
+pub mod opening_balance;
+pub mod assistant;
+pub mod ws;
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Json, Query, State},
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::AuthUser, validated_json::ValidatedJson},
+    models::{
+        dto::notification_dto::{ListNotificationsQuery, UpdateNotificationPreferencesDto},
+        notification::{Notification, NotificationPreference},
+    },
+    services::notification,
+};
+
+/// Creates a router for the current user's notifications and delivery preferences.
+///
+/// All routes defined here will be nested under `/api/v1/notifications`.
+pub fn notification_routes() -> Router<AppState> {
+    Router::new().route("/", get(list_notifications)).route(
+        "/preferences",
+        get(get_notification_preferences).put(update_notification_preferences),
+    )
+}
+
+/// GET /notifications?limit=
+/// Lists the current user's most recent notifications.
+async fn list_notifications(
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
+    Query(query): Query<ListNotificationsQuery>,
+) -> Result<Json<Vec<Notification>>, AppError> {
+    info!("Handler: Listing notifications for user {}", user_id);
+    let notifications = notification::list_notifications(&pool, user_id, query.limit).await?;
+    Ok(Json(notifications))
+}
+
+/// GET /notifications/preferences
+/// Retrieves the current user's delivery preferences.
+async fn get_notification_preferences(
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
+) -> Result<Json<NotificationPreference>, AppError> {
+    info!(
+        "Handler: Getting notification preferences for user {}",
+        user_id
+    );
+    let preferences = notification::get_notification_preferences(&pool, user_id).await?;
+    Ok(Json(preferences))
+}
+
+/// PUT /notifications/preferences
+/// Updates time zone, quiet hours, digest frequency and email opt-in.
+async fn update_notification_preferences(
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
+    ValidatedJson(dto): ValidatedJson<UpdateNotificationPreferencesDto>,
+) -> Result<Json<NotificationPreference>, AppError> {
+    info!(
+        "Handler: Updating notification preferences for user {}",
+        user_id
+    );
+    let preferences = notification::update_notification_preferences(&pool, user_id, dto).await?;
+    Ok(Json(preferences))
+}
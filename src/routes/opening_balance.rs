@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::post,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, field_policy::Redacted, validated_json::ValidatedJson},
+    models::{dto::opening_balance_dto::CreateOpeningBalancesDto, transaction::Transaction},
+    services::{field_policy::FieldAccess, opening_balance},
+};
+
+/// Creates a router for recording opening balances.
+///
+/// All routes defined here will be nested under `/api/v1/tenants`.
+pub fn opening_balance_routes() -> Router<AppState> {
+    Router::new().route("/:id/opening-balances", post(create_opening_balances))
+}
+
+/// POST /tenants/:id/opening-balances
+/// Books the balances carried over at a cutover date as one OPENING_BALANCE transaction,
+/// with the difference offset on the given equity account.
+async fn create_opening_balances(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(tenant_id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<CreateOpeningBalancesDto>,
+) -> Result<(StatusCode, Redacted<Transaction>), AppError> {
+    info!(
+        "Handler: Recording opening balances for tenant {}",
+        tenant_id
+    );
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    let transaction =
+        opening_balance::create_opening_balances(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Redacted(transaction, access)))
+}
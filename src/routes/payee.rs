@@ -0,0 +1,119 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::payee_dto::{CreatePayeeDto, ListPayeesQuery, PayeeSpendingQuery, UpdatePayeeDto},
+        payee::{Payee, PayeeMergeResult, PayeeSpending},
+    },
+    services::payee,
+};
+
+/// Creates a router for payees.
+///
+/// All routes defined here will be nested under `/api/v1/payees`.
+pub fn payee_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_payees).post(create_payee))
+        .route("/spending", get(get_payee_spending))
+        .route(
+            "/:id",
+            get(get_payee).put(update_payee).delete(delete_payee),
+        )
+        .route("/:id/merge-into/:target", post(merge_payee))
+}
+
+/// GET /payees?q=
+/// Lists the tenant's payees, optionally those whose name contains `q`.
+async fn list_payees(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<ListPayeesQuery>,
+) -> Result<Json<Vec<Payee>>, AppError> {
+    info!("Handler: Listing payees for tenant {}", ctx.tenant_id);
+    let payees = payee::list_payees(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(payees))
+}
+
+/// POST /payees
+/// Creates a payee.
+async fn create_payee(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreatePayeeDto>,
+) -> Result<(StatusCode, Json<Payee>), AppError> {
+    info!("Handler: Creating payee for tenant {}", ctx.tenant_id);
+    let payee = payee::create_payee(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(payee)))
+}
+
+/// GET /payees/:id
+/// Retrieves a payee.
+async fn get_payee(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Payee>, AppError> {
+    info!("Handler: Getting payee {}", id);
+    let payee = payee::get_payee(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(payee))
+}
+
+/// PUT /payees/:id
+/// Renames or annotates a payee.
+async fn update_payee(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdatePayeeDto>,
+) -> Result<Json<Payee>, AppError> {
+    info!("Handler: Updating payee {}", id);
+    let payee = payee::update_payee(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(payee))
+}
+
+/// DELETE /payees/:id
+/// Deletes a payee no transaction uses.
+async fn delete_payee(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting payee {}", id);
+    payee::delete_payee(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /payees/:id/merge-into/:target
+/// Moves a payee's transactions and merchant rule to another payee and deletes it.
+async fn merge_payee(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, target)): Path<(Uuid, Uuid)>,
+) -> Result<Json<PayeeMergeResult>, AppError> {
+    info!("Handler: Merging payee {} into {}", id, target);
+    let result = payee::merge_payee(&pool, ctx.tenant_id, id, target, ctx.user_id).await?;
+    Ok(Json(result))
+}
+
+/// GET /payees/spending?from_date=&to_date=
+/// Expenses over a period per payee, defaulting to the current month.
+async fn get_payee_spending(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<PayeeSpendingQuery>,
+) -> Result<Json<PayeeSpending>, AppError> {
+    info!("Handler: Spending by payee for tenant {}", ctx.tenant_id);
+    let spending = payee::payee_spending(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(spending))
+}
@@ -0,0 +1,79 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::payment_dto::{ApplyPaymentCreditDto, CreatePaymentDto, ListPaymentsQuery},
+        payment::{Payment, PaymentDetail},
+    },
+    services::payment,
+};
+
+/// Creates a router for payments allocated across invoices or bills.
+///
+/// All routes defined here will be nested under `/api/v1/payments`.
+pub fn payment_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_payments).post(create_payment))
+        .route("/:id", get(get_payment))
+        .route("/:id/allocations", post(apply_credit))
+}
+
+/// GET /payments?direction=&customer_id=&vendor_id=&with_credit=
+/// Lists the tenant's payments, most recent first.
+async fn list_payments(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<ListPaymentsQuery>,
+) -> Result<Json<Vec<Payment>>, AppError> {
+    info!("Handler: Listing payments for tenant {}", ctx.tenant_id);
+    let payments = payment::list_payments(&pool, ctx.tenant_id, query).await?;
+    Ok(Json(payments))
+}
+
+/// POST /payments
+/// Records a payment and allocates it across invoices or bills.
+async fn create_payment(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreatePaymentDto>,
+) -> Result<(StatusCode, Json<PaymentDetail>), AppError> {
+    info!("Handler: Recording payment for tenant {}", ctx.tenant_id);
+    let payment = payment::create_payment(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(payment)))
+}
+
+/// GET /payments/:id
+/// Retrieves a payment with its allocations.
+async fn get_payment(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PaymentDetail>, AppError> {
+    info!("Handler: Getting payment {}", id);
+    let payment = payment::get_payment(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(payment))
+}
+
+/// POST /payments/:id/allocations
+/// Applies a payment's unapplied credit to more invoices or bills.
+async fn apply_credit(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<ApplyPaymentCreditDto>,
+) -> Result<Json<PaymentDetail>, AppError> {
+    info!("Handler: Applying credit of payment {}", id);
+    let payment = payment::apply_credit(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(payment))
+}
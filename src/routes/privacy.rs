@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Json, State},
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{dto::privacy_dto::UpdatePrivacySettingsDto, privacy::PrivacySettings},
+    services::privacy,
+};
+
+/// Creates a router for the tenant's transaction privacy mode.
+///
+/// All routes defined here will be nested under `/api/v1/privacy`.
+pub fn privacy_routes() -> Router<AppState> {
+    Router::new().route("/", get(get_privacy_settings).put(update_privacy_settings))
+}
+
+/// GET /privacy
+/// Returns whether privacy mode is on and how many transactions are sealed.
+async fn get_privacy_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<PrivacySettings>, AppError> {
+    info!(
+        "Handler: Getting privacy settings for tenant {}",
+        ctx.tenant_id
+    );
+    let settings = privacy::get_privacy_settings(&pool, ctx.tenant_id).await?;
+    Ok(Json(settings))
+}
+
+/// PUT /privacy
+/// Turns privacy mode on or off (requires `privacy.manage`), sealing or opening existing
+/// descriptions and memos.
+async fn update_privacy_settings(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<UpdatePrivacySettingsDto>,
+) -> Result<Json<PrivacySettings>, AppError> {
+    info!(
+        "Handler: Updating privacy settings for tenant {}",
+        ctx.tenant_id
+    );
+    let settings = privacy::update_privacy_settings(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(settings))
+}
@@ -0,0 +1,42 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::auth::TenantContext,
+    models::{dto::quick_open_dto::QuickOpenQuery, quick_open::QuickOpenItem},
+    services::quick_open,
+};
+
+/// Creates a router for command-palette search within a tenant.
+///
+/// All routes defined here will be nested under `/api/v1/tenants`.
+pub fn quick_open_routes() -> Router<AppState> {
+    Router::new().route("/:id/quick-open", get(quick_open))
+}
+
+/// GET /tenants/:id/quick-open?q=&limit=
+/// Ranked matches across accounts, categories, recent transactions and reports.
+async fn quick_open(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+    Query(query): Query<QuickOpenQuery>,
+) -> Result<Json<Vec<QuickOpenItem>>, AppError> {
+    info!("Handler: Quick-open search for tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    let items = quick_open::quick_open(&pool, ctx.tenant_id, ctx.user_id, query).await?;
+    Ok(Json(items))
+}
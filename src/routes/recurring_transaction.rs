@@ -0,0 +1,232 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Router,
+};
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{
+        auth::TenantContext, field_policy::Redacted, precondition::IfMatchVersion,
+        validated_json::ValidatedJson,
+    },
+    models::{
+        dto::recurring_transaction_dto::{
+            CreateRecurringTransactionDto, UpdateRecurringTransactionDto,
+        },
+        dto::transaction_dto::UpdateTransactionDto,
+        recurring_transaction::{RecurringOccurrence, RecurringTransaction},
+        transaction::Transaction,
+    },
+    services::{field_policy::FieldAccess, recurring_transaction},
+};
+
+/// Creates a router for recurring transaction definitions.
+///
+/// All routes defined here will be nested under `/api/v1/recurring-transactions`.
+pub fn recurring_transaction_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(list_recurring_transactions).post(create_recurring_transaction),
+        )
+        .route(
+            "/:id",
+            get(get_recurring_transaction)
+                .put(update_recurring_transaction)
+                .delete(deactivate_recurring_transaction),
+        )
+        .route("/:id/pause", post(pause_recurring_transaction))
+        .route("/:id/resume", post(resume_recurring_transaction))
+        .route("/:id/occurrences", get(list_occurrences))
+        .route("/:id/occurrences/:date", put(update_occurrence))
+        .route(
+            "/:id/occurrences/:date/skip",
+            post(skip_occurrence).delete(unskip_occurrence),
+        )
+}
+
+/// GET /recurring-transactions
+/// Lists active recurring transaction definitions for the tenant.
+async fn list_recurring_transactions(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<RecurringTransaction>>, AppError> {
+    info!(
+        "Handler: Listing recurring transactions for tenant {}",
+        ctx.tenant_id
+    );
+    let definitions =
+        recurring_transaction::list_recurring_transactions(&pool, ctx.tenant_id).await?;
+    Ok(Json(definitions))
+}
+
+/// GET /recurring-transactions/:id
+/// Retrieves a single recurring transaction definition.
+async fn get_recurring_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RecurringTransaction>, AppError> {
+    info!("Handler: Getting recurring transaction {}", id);
+    let definition =
+        recurring_transaction::get_recurring_transaction_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(definition))
+}
+
+/// POST /recurring-transactions
+/// Creates a recurring definition; JOURNAL_ENTRY templates must balance.
+async fn create_recurring_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateRecurringTransactionDto>,
+) -> Result<(StatusCode, Json<RecurringTransaction>), AppError> {
+    info!(
+        "Handler: Creating recurring transaction '{}'",
+        dto.description
+    );
+    let definition =
+        recurring_transaction::create_recurring_transaction(&pool, ctx.tenant_id, ctx.user_id, dto)
+            .await?;
+    Ok((StatusCode::CREATED, Json(definition)))
+}
+
+/// PUT /recurring-transactions/:id
+/// Updates a recurring definition.
+async fn update_recurring_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateRecurringTransactionDto>,
+) -> Result<Json<RecurringTransaction>, AppError> {
+    info!("Handler: Updating recurring transaction {}", id);
+    let definition = recurring_transaction::update_recurring_transaction(
+        &pool,
+        ctx.tenant_id,
+        id,
+        ctx.user_id,
+        dto,
+    )
+    .await?;
+    Ok(Json(definition))
+}
+
+/// DELETE /recurring-transactions/:id
+/// Deactivates a recurring definition (soft delete).
+async fn deactivate_recurring_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating recurring transaction {}", id);
+    recurring_transaction::deactivate_recurring_transaction(&pool, ctx.tenant_id, id, ctx.user_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /recurring-transactions/:id/pause
+/// Stops generating occurrences until the definition is resumed.
+async fn pause_recurring_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RecurringTransaction>, AppError> {
+    info!("Handler: Pausing recurring transaction {}", id);
+    let definition =
+        recurring_transaction::pause_recurring_transaction(&pool, ctx.tenant_id, id, ctx.user_id)
+            .await?;
+    Ok(Json(definition))
+}
+
+/// POST /recurring-transactions/:id/resume
+/// Resumes a paused definition from its next occurrence on or after today.
+async fn resume_recurring_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RecurringTransaction>, AppError> {
+    info!("Handler: Resuming recurring transaction {}", id);
+    let definition =
+        recurring_transaction::resume_recurring_transaction(&pool, ctx.tenant_id, id, ctx.user_id)
+            .await?;
+    Ok(Json(definition))
+}
+
+/// GET /recurring-transactions/:id/occurrences
+/// Lists materialized, skipped and upcoming occurrences of the series.
+async fn list_occurrences(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<RecurringOccurrence>>, AppError> {
+    info!(
+        "Handler: Listing occurrences of recurring transaction {}",
+        id
+    );
+    let occurrences = recurring_transaction::list_occurrences(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(occurrences))
+}
+
+/// PUT /recurring-transactions/:id/occurrences/:date
+/// Edits the transaction materialized for one occurrence; it stays part of the series.
+/// Requires `If-Match` with the transaction's version last read.
+async fn update_occurrence(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path((id, date)): Path<(Uuid, NaiveDate)>,
+    IfMatchVersion(expected_version): IfMatchVersion,
+    ValidatedJson(dto): ValidatedJson<UpdateTransactionDto>,
+) -> Result<Redacted<Transaction>, AppError> {
+    info!(
+        "Handler: Updating occurrence {} of recurring transaction {}",
+        date, id
+    );
+    let transaction = recurring_transaction::update_occurrence(
+        &pool,
+        ctx.tenant_id,
+        id,
+        date,
+        ctx.user_id,
+        expected_version,
+        dto,
+    )
+    .await?;
+    Ok(Redacted(transaction, access))
+}
+
+/// POST /recurring-transactions/:id/occurrences/:date/skip
+/// Skips one upcoming occurrence.
+async fn skip_occurrence(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, date)): Path<(Uuid, NaiveDate)>,
+) -> Result<StatusCode, AppError> {
+    info!(
+        "Handler: Skipping occurrence {} of recurring transaction {}",
+        date, id
+    );
+    recurring_transaction::skip_occurrence(&pool, ctx.tenant_id, id, date, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /recurring-transactions/:id/occurrences/:date/skip
+/// Restores a skipped occurrence that has not fallen due yet.
+async fn unskip_occurrence(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((id, date)): Path<(Uuid, NaiveDate)>,
+) -> Result<StatusCode, AppError> {
+    info!(
+        "Handler: Restoring occurrence {} of recurring transaction {}",
+        date, id
+    );
+    recurring_transaction::unskip_occurrence(&pool, ctx.tenant_id, id, date).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
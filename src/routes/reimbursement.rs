@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, field_policy::Redacted, validated_json::ValidatedJson},
+    models::{
+        dto::reimbursement_dto::{MatchReimbursementDto, OutstandingReimbursablesQuery},
+        reimbursement::{
+            OutstandingReimbursable, OutstandingReimbursablesReport, ReimbursementMatch,
+        },
+    },
+    services::{field_policy::FieldAccess, reimbursement},
+};
+
+/// Creates a router for matching reimbursement deposits to reimbursable expenses.
+///
+/// All routes defined here will be nested under `/api/v1/reimbursements`.
+/// Expenses are marked with `PUT /transactions/:id/reimbursable`.
+pub fn reimbursement_routes() -> Router<AppState> {
+    Router::new()
+        .route("/matches", post(match_reimbursement))
+        .route("/matches/:id", delete(unmatch_reimbursement))
+        .route(
+            "/suggestions/:deposit_transaction_id",
+            get(suggest_expenses),
+        )
+        .route("/outstanding", get(outstanding_report))
+}
+
+/// POST /reimbursements/matches
+/// Applies a deposit to one or more reimbursable expenses.
+async fn match_reimbursement(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<MatchReimbursementDto>,
+) -> Result<(StatusCode, Json<Vec<ReimbursementMatch>>), AppError> {
+    info!(
+        "Handler: Matching deposit {} for tenant {}",
+        dto.deposit_transaction_id, ctx.tenant_id
+    );
+    let matches =
+        reimbursement::match_reimbursement(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(matches)))
+}
+
+/// DELETE /reimbursements/matches/:id
+/// Removes a match; the expense is outstanding again by its amount.
+async fn unmatch_reimbursement(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Removing reimbursement match {}", id);
+    reimbursement::unmatch_reimbursement(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /reimbursements/suggestions/:deposit_transaction_id
+/// Outstanding expenses the deposit could pay back, exact amounts first.
+async fn suggest_expenses(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(deposit_transaction_id): Path<Uuid>,
+) -> Result<Redacted<Vec<OutstandingReimbursable>>, AppError> {
+    info!(
+        "Handler: Suggesting reimbursable expenses for deposit {}",
+        deposit_transaction_id
+    );
+    let expenses =
+        reimbursement::suggest_expenses(&pool, ctx.tenant_id, deposit_transaction_id).await?;
+    Ok(Redacted(expenses, access))
+}
+
+/// GET /reimbursements/outstanding?payer=&as_of=
+/// Reimbursable expenses not yet fully paid back, totaled by payer and currency.
+async fn outstanding_report(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Query(query): Query<OutstandingReimbursablesQuery>,
+) -> Result<Redacted<OutstandingReimbursablesReport>, AppError> {
+    info!(
+        "Handler: Outstanding reimbursables for tenant {}",
+        ctx.tenant_id
+    );
+    let report = reimbursement::outstanding_report(&pool, ctx.tenant_id, query).await?;
+    Ok(Redacted(report, access))
+}
@@ -0,0 +1,213 @@
+use axum::{
+    extract::{Path, Query},
+    http::header,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{
+        auth::TenantContext, consistency::ReadPool, field_policy::Redacted,
+        report_format::ReportFormat,
+    },
+    models::{
+        dto::report_dto::{
+            DrilldownQuery, ReportAsOfQuery, ReportPeriodQuery, SpendByCategoryQuery,
+        },
+        report::{CategorySpendReport, DrilldownResult, FinancialStatement},
+    },
+    services::{field_policy::FieldAccess, redis_store, report, report_export},
+    utils::{pdf, xlsx},
+};
+
+/// Creates a router for financial statements and report drill-downs.
+///
+/// All routes defined here will be nested under `/api/v1/reports`.
+pub fn report_routes() -> Router<AppState> {
+    Router::new()
+        .route("/trial-balance", get(trial_balance))
+        .route("/income-statement", get(income_statement))
+        .route("/balance-sheet", get(balance_sheet))
+        .route("/spend-by-category", get(spend_by_category))
+        .route("/drilldown/:token", get(resolve_drilldown))
+}
+
+/// GET /reports/trial-balance?as_of=&layout_id=&project_id=&class_id=&location_id=&format=json|xlsx|pdf
+/// Account balances grouped by account type.
+async fn trial_balance(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    format: ReportFormat,
+    Query(query): Query<ReportAsOfQuery>,
+) -> Result<Response, AppError> {
+    info!("Handler: Trial balance for tenant {}", ctx.tenant_id);
+    let statement = redis_store::cached_report(
+        ctx.tenant_id,
+        "trial_balance",
+        &(query.as_of, query.layout_id, query.dimensions),
+        || {
+            report::trial_balance(
+                &pool,
+                ctx.tenant_id,
+                query.as_of,
+                query.layout_id,
+                query.dimensions,
+            )
+        },
+    )
+    .await?;
+    statement_response(&pool, statement, access, format).await
+}
+
+/// GET /reports/income-statement?from_date=&to_date=&layout_id=&project_id=&class_id=&location_id=&format=json|xlsx|pdf
+/// Revenue and expenses over a period with net income.
+async fn income_statement(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    format: ReportFormat,
+    Query(query): Query<ReportPeriodQuery>,
+) -> Result<Response, AppError> {
+    info!("Handler: Income statement for tenant {}", ctx.tenant_id);
+    let statement = redis_store::cached_report(
+        ctx.tenant_id,
+        "income_statement",
+        &(
+            query.from_date,
+            query.to_date,
+            query.layout_id,
+            query.dimensions,
+        ),
+        || {
+            report::income_statement(
+                &pool,
+                ctx.tenant_id,
+                query.from_date,
+                query.to_date,
+                query.layout_id,
+                query.dimensions,
+            )
+        },
+    )
+    .await?;
+    statement_response(&pool, statement, access, format).await
+}
+
+/// The statement as JSON, or as a workbook or PDF download.
+async fn statement_response(
+    pool: &PgPool,
+    statement: FinancialStatement,
+    access: FieldAccess,
+    format: ReportFormat,
+) -> Result<Response, AppError> {
+    match format {
+        ReportFormat::Json => Ok(Redacted(statement, access).into_response()),
+        ReportFormat::Xlsx => {
+            let rendered = report_export::render_statement_xlsx(&statement, &access)?;
+            Ok(xlsx_download(rendered))
+        }
+        ReportFormat::Pdf => {
+            let rendered = report_export::render_statement_pdf(pool, &statement, &access).await?;
+            Ok(download(
+                pdf::CONTENT_TYPE,
+                &rendered.file_name,
+                rendered.pdf,
+            ))
+        }
+    }
+}
+
+/// GET /reports/balance-sheet?as_of=&layout_id=&format=json|xlsx|pdf
+/// Assets, liabilities and equity as of a date.
+async fn balance_sheet(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    format: ReportFormat,
+    Query(query): Query<ReportAsOfQuery>,
+) -> Result<Response, AppError> {
+    info!("Handler: Balance sheet for tenant {}", ctx.tenant_id);
+    if !query.dimensions.is_empty() {
+        return Err(AppError::Validation(
+            "Dimension filters apply to the trial balance and income statement only".to_string(),
+        ));
+    }
+    let statement = redis_store::cached_report(
+        ctx.tenant_id,
+        "balance_sheet",
+        &(query.as_of, query.layout_id),
+        || report::balance_sheet(&pool, ctx.tenant_id, query.as_of, query.layout_id),
+    )
+    .await?;
+    statement_response(&pool, statement, access, format).await
+}
+
+/// GET /reports/spend-by-category?from_date=&to_date=&granularity=month|quarter
+/// Posted expenses per category and period, in the tenant's base currency.
+async fn spend_by_category(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Query(query): Query<SpendByCategoryQuery>,
+) -> Result<Redacted<CategorySpendReport>, AppError> {
+    info!("Handler: Spend by category for tenant {}", ctx.tenant_id);
+    let report = redis_store::cached_report(
+        ctx.tenant_id,
+        "spend_by_category",
+        &(query.from_date, query.to_date, query.granularity),
+        || {
+            report::spend_by_category(
+                &pool,
+                ctx.tenant_id,
+                query.from_date,
+                query.to_date,
+                query.granularity,
+            )
+        },
+    )
+    .await?;
+    Ok(Redacted(report, access))
+}
+
+/// GET /reports/drilldown/:token?limit=&offset=
+/// Returns the journal entries behind a report cell.
+async fn resolve_drilldown(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(token): Path<String>,
+    Query(query): Query<DrilldownQuery>,
+) -> Result<Redacted<DrilldownResult>, AppError> {
+    info!(
+        "Handler: Resolving report drill-down for tenant {}",
+        ctx.tenant_id
+    );
+    let result =
+        report::resolve_drilldown(&pool, ctx.tenant_id, &token, query.limit, query.offset).await?;
+    Ok(Redacted(result, access))
+}
+
+/// A workbook as a file download.
+pub fn xlsx_download(rendered: report_export::RenderedWorkbook) -> Response {
+    download(xlsx::CONTENT_TYPE, &rendered.file_name, rendered.xlsx)
+}
+
+fn download(content_type: &str, file_name: &str, file: Vec<u8>) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        file,
+    )
+        .into_response()
+}
@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::security_webhook_dto::UpsertSecurityWebhookDto,
+        security_webhook::{SecurityWebhook, SecurityWebhookWithSecret},
+    },
+    services::security_webhook,
+};
+
+/// Creates a router for the tenant's security webhook. All routes require the
+/// `security.manage` permission.
+///
+/// All routes defined here will be nested under `/api/v1/security-webhook`.
+pub fn security_webhook_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(get_security_webhook)
+                .put(upsert_security_webhook)
+                .delete(delete_security_webhook),
+        )
+        .route("/rotate-secret", post(rotate_security_webhook_secret))
+        .route("/test", post(send_test_event))
+}
+
+/// GET /security-webhook
+/// Retrieves the tenant's security webhook (without its signing secret).
+async fn get_security_webhook(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<SecurityWebhook>, AppError> {
+    info!(
+        "Handler: Getting security webhook for tenant {}",
+        ctx.tenant_id
+    );
+    let webhook = security_webhook::get_security_webhook(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(webhook))
+}
+
+/// PUT /security-webhook
+/// Creates or replaces the security webhook. The signing secret is returned on creation only.
+async fn upsert_security_webhook(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<UpsertSecurityWebhookDto>,
+) -> Result<Json<SecurityWebhookWithSecret>, AppError> {
+    info!(
+        "Handler: Configuring security webhook for tenant {}",
+        ctx.tenant_id
+    );
+    let webhook =
+        security_webhook::upsert_security_webhook(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(webhook))
+}
+
+/// DELETE /security-webhook
+/// Removes the security webhook.
+async fn delete_security_webhook(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<StatusCode, AppError> {
+    info!(
+        "Handler: Deleting security webhook for tenant {}",
+        ctx.tenant_id
+    );
+    security_webhook::delete_security_webhook(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /security-webhook/rotate-secret
+/// Generates a new signing secret and returns it.
+async fn rotate_security_webhook_secret(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<SecurityWebhookWithSecret>, AppError> {
+    info!(
+        "Handler: Rotating security webhook secret for tenant {}",
+        ctx.tenant_id
+    );
+    let webhook =
+        security_webhook::rotate_security_webhook_secret(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(webhook))
+}
+
+/// POST /security-webhook/test
+/// Sends a signed TEST event and returns the delivery outcome.
+async fn send_test_event(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<SecurityWebhook>, AppError> {
+    info!(
+        "Handler: Sending test security event for tenant {}",
+        ctx.tenant_id
+    );
+    let webhook = security_webhook::send_test_event(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(webhook))
+}
@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::statement_layout_dto::{CreateStatementLayoutDto, UpdateStatementLayoutDto},
+        statement_layout::StatementLayout,
+    },
+    services::statement_layout,
+};
+
+/// Creates a router for custom financial statement layouts.
+///
+/// All routes defined here will be nested under `/api/v1/statement-layouts`.
+pub fn statement_layout_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(list_statement_layouts).post(create_statement_layout),
+        )
+        .route(
+            "/:id",
+            get(get_statement_layout)
+                .put(update_statement_layout)
+                .delete(deactivate_statement_layout),
+        )
+}
+
+/// GET /statement-layouts
+/// Lists the tenant's active statement layouts.
+async fn list_statement_layouts(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<StatementLayout>>, AppError> {
+    info!(
+        "Handler: Listing statement layouts for tenant {}",
+        ctx.tenant_id
+    );
+    let layouts = statement_layout::list_statement_layouts(&pool, ctx.tenant_id).await?;
+    Ok(Json(layouts))
+}
+
+/// GET /statement-layouts/:id
+/// Retrieves a single statement layout.
+async fn get_statement_layout(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<StatementLayout>, AppError> {
+    info!("Handler: Getting statement layout {}", id);
+    let layout = statement_layout::get_statement_layout_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(layout))
+}
+
+/// POST /statement-layouts
+/// Creates a statement layout; subtotal rows may only reference rows above them.
+async fn create_statement_layout(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateStatementLayoutDto>,
+) -> Result<(StatusCode, Json<StatementLayout>), AppError> {
+    info!("Handler: Creating statement layout '{}'", dto.name);
+    let layout =
+        statement_layout::create_statement_layout(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(layout)))
+}
+
+/// PUT /statement-layouts/:id
+/// Updates a statement layout.
+async fn update_statement_layout(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateStatementLayoutDto>,
+) -> Result<Json<StatementLayout>, AppError> {
+    info!("Handler: Updating statement layout {}", id);
+    let layout =
+        statement_layout::update_statement_layout(&pool, ctx.tenant_id, id, ctx.user_id, dto)
+            .await?;
+    Ok(Json(layout))
+}
+
+/// DELETE /statement-layouts/:id
+/// Deactivates a statement layout (soft delete).
+async fn deactivate_statement_layout(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating statement layout {}", id);
+    statement_layout::deactivate_statement_layout(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,119 +1,202 @@
 use axum::{
-    extract::{Path, State, Json},
-    routing::{get, post, put, delete},
-    Router,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Json, Path, State},
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
-    http::StatusCode,
+    routing::{get, post},
+    Router,
 };
 use sqlx::PgPool;
-use uuid::Uuid;
 use tracing::info;
+use uuid::Uuid;
 
 use crate::{
+    app_state::AppState,
     error::AppError,
-    models::dto::tenant_dto::{CreateTenantRequest, UpdateTenantRequest, TenantResponse},
-    services::tenant,
-    // Placeholder for authentication context
-    utils::auth_middleware::get_current_user_id, // This utility would provide the user_id from auth
-    api::user_handlers::AppState, // Import AppState from user_handlers or a common api::mod
+    middleware::{
+        auth::{AuthUser, TenantContext},
+        validated_json::ValidatedJson,
+    },
+    models::{
+        dto::{
+            tenant_backup_dto::TenantImportResult,
+            tenant_dto::{CreateTenantDto, UpdateTenantDto},
+        },
+        tenant::{Tenant, TenantLogo},
+    },
+    services::{tenant, tenant_branding, tenant_restore},
 };
 
-
-// Function to create a router specifically for tenant-related routes
+/// Creates a router for tenants.
+///
+/// All routes defined here will be nested under `/api/v1/tenants`.
 pub fn tenant_routes() -> Router<AppState> {
     Router::new()
-        .route("/", get(list_tenants))
-        .route("/", post(create_tenant))
-        .route("/:id", get(get_tenant_by_id))
-        .route("/:id", put(update_tenant))
-        .route("/:id", delete(deactivate_tenant))
+        .route("/", get(list_tenants).post(create_tenant))
+        .route(
+            "/:id",
+            get(get_tenant).put(update_tenant).delete(deactivate_tenant),
+        )
+        .route(
+            "/:id/import",
+            post(import_tenant_backup)
+                .layer(DefaultBodyLimit::max(tenant_restore::MAX_ARCHIVE_BYTES)),
+        )
+        .route(
+            "/:id/logo",
+            get(get_tenant_logo)
+                .put(set_tenant_logo)
+                .delete(delete_tenant_logo)
+                .layer(DefaultBodyLimit::max(tenant_branding::MAX_LOGO_BYTES)),
+        )
 }
 
 /// GET /tenants
-/// Lists all active tenants.
-/// Requires current_user_id for filtering tenants that the user has access to.
+/// Lists the active tenants the caller is a member of.
 async fn list_tenants(
-    State(AppState { pool, .. }): State<AppState>,
-) -> Result<Json<Vec<TenantResponse>>, AppError> {
-    info!("Handler: Listing tenants");
-    // In a multi-tenant app, this would typically be `list_tenants_for_user`
-    // requiring `current_user_id` from auth context.
-    let tenants = tenant::list_tenants(&pool).await?;
-    let tenant_responses: Vec<TenantResponse> = tenants.into_iter().map(TenantResponse::from).collect();
-    Ok(Json(tenant_responses))
-}
-
-/// GET /tenants/:id
-/// Retrieves a single tenant by ID.
-async fn get_tenant_by_id(
-    State(AppState { pool, .. }): State<AppState>,
-    Path(tenant_id): Path<Uuid>,
-) -> Result<Json<TenantResponse>, AppError> {
-    info!("Handler: Getting tenant by ID: {}", tenant_id);
-    let found_tenant = tenant::get_tenant_by_id(&pool, tenant_id).await?;
-    Ok(Json(TenantResponse::from(found_tenant)))
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
+) -> Result<Json<Vec<Tenant>>, AppError> {
+    info!("Handler: Listing tenants of user {}", user_id);
+    let tenants = tenant::list_tenants(&pool, user_id).await?;
+    Ok(Json(tenants))
 }
 
 /// POST /tenants
-/// Creates a new tenant.
+/// Creates a new tenant; the caller becomes its administrator.
 async fn create_tenant(
-    State(AppState { pool, .. }): State<AppState>,
-    Json(req): Json<CreateTenantRequest>,
-) -> Result<(StatusCode, Json<TenantResponse>), AppError> {
-    info!("Handler: Creating new tenant with name: {}", req.name);
-
-    // Placeholder: Get current user ID from authentication context
-    let created_by_user_id = get_current_user_id();
-
-    let new_tenant = tenant::create_tenant(
-        &pool,
-        req.name,
-        req.industry,
-        req.base_currency_code,
-        created_by_user_id,
-    )
-    .await?;
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
+    ValidatedJson(dto): ValidatedJson<CreateTenantDto>,
+) -> Result<(StatusCode, Json<Tenant>), AppError> {
+    info!("Handler: Creating tenant {}", dto.name);
+    let tenant = tenant::create_tenant(&pool, user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(tenant)))
+}
 
-    Ok((StatusCode::CREATED, Json(TenantResponse::from(new_tenant))))
+/// GET /tenants/:id
+/// Retrieves the tenant the caller is signed in to.
+async fn get_tenant(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<Tenant>, AppError> {
+    info!("Handler: Getting tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!("Not signed in to tenant {}", tenant_id)));
+    }
+    let tenant = tenant::get_tenant_by_id(&pool, ctx.tenant_id).await?;
+    Ok(Json(tenant))
 }
 
 /// PUT /tenants/:id
-/// Updates an existing tenant.
+/// Updates the tenant the caller is signed in to. Requires `tenant.manage`.
 async fn update_tenant(
-    State(AppState { pool, .. }): State<AppState>,
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
     Path(tenant_id): Path<Uuid>,
-    Json(req): Json<UpdateTenantRequest>,
-) -> Result<Json<TenantResponse>, AppError> {
-    info!("Handler: Updating tenant with ID: {}", tenant_id);
+    ValidatedJson(dto): ValidatedJson<UpdateTenantDto>,
+) -> Result<Json<Tenant>, AppError> {
+    info!("Handler: Updating tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!("Not signed in to tenant {}", tenant_id)));
+    }
+    let tenant = tenant::update_tenant(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(tenant))
+}
 
-    // Placeholder: Get current user ID from authentication context
-    let updated_by_user_id = get_current_user_id();
+/// DELETE /tenants/:id
+/// Deactivates the tenant the caller is signed in to. Requires `tenant.manage`.
+async fn deactivate_tenant(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!("Not signed in to tenant {}", tenant_id)));
+    }
+    tenant::deactivate_tenant(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    let updated_tenant = tenant::update_tenant(
+/// POST /tenants/:id/import
+/// Restores a backup archive, sent as the body exactly as it was downloaded, into the tenant
+/// the caller is signed in to, which must be empty. An encrypted backup needs its passphrase
+/// in `X-Backup-Passphrase`. Requires `tenant.restore`; reports what was restored per file.
+async fn import_tenant_backup(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+    headers: HeaderMap,
+    archive: Bytes,
+) -> Result<Json<TenantImportResult>, AppError> {
+    info!("Handler: Importing backup into tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!("Not signed in to tenant {}", tenant_id)));
+    }
+    let passphrase = headers
+        .get(tenant_restore::PASSPHRASE_HEADER)
+        .map(|value| {
+            value.to_str().map(str::to_string).map_err(|_| {
+                AppError::Validation("The backup passphrase must be valid text".to_string())
+            })
+        })
+        .transpose()?;
+    let result = tenant_restore::import_tenant_backup(
         &pool,
-        tenant_id,
-        req.name,
-        req.industry,
-        req.base_currency_code,
-        req.is_active,
-        updated_by_user_id,
+        ctx.tenant_id,
+        ctx.user_id,
+        archive.to_vec(),
+        passphrase,
     )
     .await?;
-
-    Ok(Json(TenantResponse::from(updated_tenant)))
+    Ok(Json(result))
 }
 
-/// DELETE /tenants/:id
-/// Deactivates a tenant (soft delete).
-async fn deactivate_tenant(
-    State(AppState { pool, .. }): State<AppState>,
+/// GET /tenants/:id/logo
+/// The logo of the tenant the caller is signed in to, as the image file.
+async fn get_tenant_logo(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
     Path(tenant_id): Path<Uuid>,
-) -> Result<StatusCode, AppError> {
-    info!("Handler: Deactivating tenant with ID: {}", tenant_id);
+) -> Result<impl IntoResponse, AppError> {
+    info!("Handler: Getting logo of tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!("Not signed in to tenant {}", tenant_id)));
+    }
+    let logo = tenant_branding::get_logo(&pool, ctx.tenant_id).await?;
+    Ok(([(header::CONTENT_TYPE, logo.content_type)], logo.image))
+}
 
-    // Placeholder: Get current user ID from authentication context
-    let updated_by_user_id = get_current_user_id();
+/// PUT /tenants/:id/logo
+/// Sets the logo printed on PDF statements, sent as the PNG or JPEG file itself (at most
+/// 512 KB). Responds with the logo's size and type.
+async fn set_tenant_logo(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+    file: Bytes,
+) -> Result<Json<TenantLogo>, AppError> {
+    info!("Handler: Setting logo of tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!("Not signed in to tenant {}", tenant_id)));
+    }
+    let logo = tenant_branding::set_logo(&pool, ctx.tenant_id, ctx.user_id, file.to_vec()).await?;
+    Ok(Json(logo))
+}
 
-    tenant::deactivate_tenant(&pool, tenant_id, updated_by_user_id).await?;
+/// DELETE /tenants/:id/logo
+/// Removes the logo; statements show the tenant's name alone.
+async fn delete_tenant_logo(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting logo of tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!("Not signed in to tenant {}", tenant_id)));
+    }
+    tenant_branding::delete_logo(&pool, ctx.tenant_id).await?;
     Ok(StatusCode::NO_CONTENT)
-}
\ No newline at end of file
+}
@@ -0,0 +1,104 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::tenant_invitation_dto::{AcceptInvitationDto, CreateTenantInvitationDto},
+        tenant_invitation::{AcceptedInvitation, TenantInvitation},
+    },
+    services::tenant_invitation,
+};
+
+/// Creates a router for tenant invitations.
+///
+/// All routes defined here will be nested under `/api/v1/tenants`.
+pub fn tenant_invitation_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/:id/invitations",
+            get(list_invitations).post(create_invitation),
+        )
+        .route("/:id/invitations/:invitation_id", delete(revoke_invitation))
+        .route("/invitations/accept", post(accept_invitation))
+}
+
+/// POST /tenants/:id/invitations
+/// Invites someone by email with a role. Requires `members.invite`.
+async fn create_invitation(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<CreateTenantInvitationDto>,
+) -> Result<(StatusCode, Json<TenantInvitation>), AppError> {
+    info!("Handler: Inviting {} to tenant {}", dto.email, tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    let invitation =
+        tenant_invitation::create_invitation(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(invitation)))
+}
+
+/// GET /tenants/:id/invitations
+/// Lists the tenant's pending invitations. Requires `members.invite`.
+async fn list_invitations(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<Vec<TenantInvitation>>, AppError> {
+    info!("Handler: Listing invitations for tenant {}", tenant_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    let invitations =
+        tenant_invitation::list_pending_invitations(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(invitations))
+}
+
+/// DELETE /tenants/:id/invitations/:invitation_id
+/// Revokes a pending invitation. Requires `members.invite`.
+async fn revoke_invitation(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path((tenant_id, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<TenantInvitation>, AppError> {
+    info!("Handler: Revoking invitation {}", invitation_id);
+    if tenant_id != ctx.tenant_id {
+        return Err(AppError::Forbidden(format!(
+            "Not signed in to tenant {}",
+            tenant_id
+        )));
+    }
+    let invitation =
+        tenant_invitation::revoke_invitation(&pool, ctx.tenant_id, ctx.user_id, invitation_id)
+            .await?;
+    Ok(Json(invitation))
+}
+
+/// POST /tenants/invitations/accept
+/// Accepts an invitation by its emailed token. Links the account with the invited email,
+/// or creates one from the names and password given. Needs no sign-in.
+async fn accept_invitation(
+    State(pool): State<PgPool>,
+    ValidatedJson(dto): ValidatedJson<AcceptInvitationDto>,
+) -> Result<(StatusCode, Json<AcceptedInvitation>), AppError> {
+    info!("Handler: Accepting tenant invitation");
+    let accepted = tenant_invitation::accept_invitation(&pool, dto).await?;
+    Ok((StatusCode::CREATED, Json(accepted)))
+}
@@ -0,0 +1,330 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{get, post, put},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{
+        auth::TenantContext, consistency::ReadPool, field_policy::Redacted, precondition::{Conditional, IfMatchVersion, IfNoneMatch},
+        validated_json::ValidatedJson,
+    },
+    models::{
+        dto::csv_format_dto::CsvFormatQuery,
+        dto::household_dto::SetTransactionAttributionDto,
+        dto::reimbursement_dto::MarkReimbursableDto,
+        dto::transaction_dto::{
+            CreateTransactionDto, CreateTransferDto, ListTransactionsQuery, ReverseTransactionDto,
+            SplitReceiptDto, UpdateTransactionDto, VoidTransactionDto,
+        },
+        household::TransactionAttribution,
+        reimbursement::ReimbursableExpense,
+        transaction::Transaction,
+        transaction_metadata::TransactionMetadata,
+        transaction_split::SplitTransaction,
+    },
+    services::{
+        field_policy::FieldAccess, household, reimbursement, transaction, transaction_export,
+        transaction_split, transfer,
+    },
+    utils::csv_format::CsvFormat,
+};
+
+/// Creates a router for transactions.
+///
+/// All routes defined here will be nested under `/api/v1/transactions`.
+pub fn transaction_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_transactions).post(create_transaction))
+        .route("/export.csv", get(export_transactions_csv))
+        .route("/split", post(split_receipt))
+        .route("/transfer", post(create_transfer))
+        .route(
+            "/:id",
+            get(get_transaction)
+                .put(update_transaction)
+                .patch(update_transaction)
+                .delete(delete_transaction),
+        )
+        .route("/:id/metadata", get(get_transaction_metadata))
+        .route(
+            "/:id/attribution",
+            put(set_transaction_attribution).delete(clear_transaction_attribution),
+        )
+        .route(
+            "/:id/reimbursable",
+            put(mark_reimbursable).delete(unmark_reimbursable),
+        )
+        .route("/:id/reverse", post(reverse_transaction))
+        .route("/:id/submit", post(submit_transaction))
+        .route("/:id/return-to-draft", post(return_transaction_to_draft))
+        .route("/:id/post", post(post_transaction))
+        .route("/:id/void", post(void_transaction))
+}
+
+/// GET /transactions?from_date=&to_date=&event_id=&entry_source=&device=&near_lat=&near_lon=&radius_km=
+/// Lists the tenant's transactions, newest first, optionally filtered by date, event and entry metadata.
+async fn list_transactions(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Query(query): Query<ListTransactionsQuery>,
+) -> Result<Redacted<Vec<Transaction>>, AppError> {
+    info!("Handler: Listing transactions for tenant {}", ctx.tenant_id);
+    let transactions = transaction::list_transactions(&pool, ctx.tenant_id, query).await?;
+    Ok(Redacted(transactions, access))
+}
+
+/// GET /transactions/export.csv?<listing filters>&delimiter=&decimal_comma=&date_format=&bom=
+/// Downloads the transactions the listing would return as CSV, streamed as it is read.
+async fn export_transactions_csv(
+    ReadPool(pool): ReadPool,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Query(query): Query<ListTransactionsQuery>,
+    Query(format): Query<CsvFormatQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Handler: Exporting transactions as CSV for tenant {}", ctx.tenant_id);
+    let format = CsvFormat::from_query(&format)?;
+    let (file_name, body) =
+        transaction_export::export_transactions_csv(pool, ctx.tenant_id, query, access, format)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        body,
+    ))
+}
+
+/// POST /transactions
+/// Creates a transaction with its journal entries, as a draft unless another status is given.
+/// With `splits` (and `payment_account_id`) instead of entries, one entry is booked per split.
+async fn create_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    ValidatedJson(dto): ValidatedJson<CreateTransactionDto>,
+) -> Result<(StatusCode, Redacted<Transaction>), AppError> {
+    info!("Handler: Creating transaction for tenant {}", ctx.tenant_id);
+    let transaction = transaction::create_transaction(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Redacted(transaction, access)))
+}
+
+/// POST /transactions/split
+/// Creates one balanced transaction from a receipt split across categories by percentage or amount.
+async fn split_receipt(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    ValidatedJson(dto): ValidatedJson<SplitReceiptDto>,
+) -> Result<(StatusCode, Redacted<SplitTransaction>), AppError> {
+    info!("Handler: Splitting receipt for tenant {}", ctx.tenant_id);
+    let split = transaction_split::split_receipt(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Redacted(split, access)))
+}
+
+/// POST /transactions/transfer
+/// Creates a transfer between two accounts, generating the debit and credit (converted
+/// across currencies unless `to_amount` is given).
+async fn create_transfer(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    ValidatedJson(dto): ValidatedJson<CreateTransferDto>,
+) -> Result<(StatusCode, Redacted<Transaction>), AppError> {
+    info!("Handler: Creating transfer for tenant {}", ctx.tenant_id);
+    let transaction = transfer::create_transfer(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Redacted(transaction, access)))
+}
+
+/// GET /transactions/:id
+/// Retrieves a single transaction, or 304 when `If-None-Match` holds its current ETag.
+async fn get_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    if_none_match: IfNoneMatch,
+) -> Result<Conditional<Redacted<Transaction>>, AppError> {
+    info!("Handler: Getting transaction {}", id);
+    let transaction = transaction::get_transaction_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(if_none_match.respond(transaction.version, Redacted(transaction, access)))
+}
+
+/// GET /transactions/:id/metadata
+/// Client metadata (location, device, entry source) captured when the transaction was entered.
+async fn get_transaction_metadata(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TransactionMetadata>, AppError> {
+    info!("Handler: Getting metadata of transaction {}", id);
+    let metadata = transaction::get_transaction_metadata(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(metadata))
+}
+
+/// PUT /transactions/:id/attribution
+/// Attributes the transaction to a household member, optionally as a shared expense.
+async fn set_transaction_attribution(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<SetTransactionAttributionDto>,
+) -> Result<Json<TransactionAttribution>, AppError> {
+    info!("Handler: Attributing transaction {}", id);
+    let attribution = household::set_attribution(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(attribution))
+}
+
+/// DELETE /transactions/:id/attribution
+/// Removes the transaction's member attribution.
+async fn clear_transaction_attribution(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Clearing attribution of transaction {}", id);
+    household::clear_attribution(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /transactions/:id/reimbursable
+/// Marks an expense as to be paid back by a third party, or changes the payer.
+async fn mark_reimbursable(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<MarkReimbursableDto>,
+) -> Result<Json<ReimbursableExpense>, AppError> {
+    info!("Handler: Marking transaction {} reimbursable", id);
+    let expense = reimbursement::mark_reimbursable(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(expense))
+}
+
+/// DELETE /transactions/:id/reimbursable
+/// Removes the reimbursable marker from an expense with no reimbursements matched.
+async fn unmark_reimbursable(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Unmarking reimbursable transaction {}", id);
+    reimbursement::unmark_reimbursable(&pool, ctx.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT|PATCH /transactions/:id
+/// Updates a transaction as a JSON Merge Patch: absent fields are kept and `null` clears
+/// `category_id`, `reconciliation_date`, `notes` or `source_document_url`. Posted
+/// transactions only accept descriptive changes. Requires `If-Match` with the version last read.
+async fn update_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    IfMatchVersion(expected_version): IfMatchVersion,
+    ValidatedJson(dto): ValidatedJson<UpdateTransactionDto>,
+) -> Result<Redacted<Transaction>, AppError> {
+    info!("Handler: Updating transaction {}", id);
+    let transaction = transaction::update_transaction(
+        &pool,
+        ctx.tenant_id,
+        id,
+        ctx.user_id,
+        expected_version,
+        dto,
+    )
+    .await?;
+    Ok(Redacted(transaction, access))
+}
+
+/// DELETE /transactions/:id
+/// Deletes a transaction that is neither posted nor voided.
+async fn delete_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deleting transaction {}", id);
+    transaction::delete_transaction(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /transactions/:id/reverse
+/// Creates an offsetting transaction with mirrored journal entries and links the two.
+async fn reverse_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<ReverseTransactionDto>,
+) -> Result<(StatusCode, Redacted<Transaction>), AppError> {
+    info!("Handler: Reversing transaction {}", id);
+    let reversal = transaction::reverse_transaction(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Redacted(reversal, access)))
+}
+
+/// POST /transactions/:id/submit
+/// Submits a draft for approval.
+async fn submit_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+) -> Result<Redacted<Transaction>, AppError> {
+    info!("Handler: Submitting transaction {}", id);
+    let transaction = transaction::submit_transaction(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Redacted(transaction, access))
+}
+
+/// POST /transactions/:id/return-to-draft
+/// Sends a transaction waiting for approval back to draft.
+async fn return_transaction_to_draft(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+) -> Result<Redacted<Transaction>, AppError> {
+    info!("Handler: Returning transaction {} to draft", id);
+    let transaction =
+        transaction::return_transaction_to_draft(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Redacted(transaction, access))
+}
+
+/// POST /transactions/:id/post
+/// Posts a balanced transaction; its journal entries become immutable.
+async fn post_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+) -> Result<Redacted<Transaction>, AppError> {
+    info!("Handler: Posting transaction {}", id);
+    let transaction = transaction::post_transaction(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(Redacted(transaction, access))
+}
+
+/// POST /transactions/:id/void
+/// Voids a posted transaction; it no longer counts in reports.
+async fn void_transaction(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<VoidTransactionDto>,
+) -> Result<Redacted<Transaction>, AppError> {
+    info!("Handler: Voiding transaction {}", id);
+    let transaction = transaction::void_transaction(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Redacted(transaction, access))
+}
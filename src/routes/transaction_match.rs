@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::auth::TenantContext,
+    models::{
+        dto::transaction_match_dto::ListTransactionMatchesQuery,
+        transaction_match::TransactionMatch,
+    },
+    services::transaction_matching,
+};
+
+/// Creates a router for reviewing bank-feed match proposals.
+///
+/// All routes defined here will be nested under `/api/v1/transaction-matches`.
+pub fn transaction_match_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_transaction_matches))
+        .route("/:id/accept", post(accept_transaction_match))
+        .route("/:id/reject", post(reject_transaction_match))
+}
+
+/// GET /transaction-matches?status=&ext_conn_id=
+/// Lists match proposals, best first per staged row.
+async fn list_transaction_matches(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Query(query): Query<ListTransactionMatchesQuery>,
+) -> Result<Json<Vec<TransactionMatch>>, AppError> {
+    info!(
+        "Handler: Listing transaction matches for tenant {}",
+        ctx.tenant_id
+    );
+    let matches = transaction_matching::list_transaction_matches(
+        &pool,
+        ctx.tenant_id,
+        query.status,
+        query.ext_conn_id,
+    )
+    .await?;
+    Ok(Json(matches))
+}
+
+/// POST /transaction-matches/:id/accept
+/// Links the staged bank row to the existing transaction instead of creating a new one.
+async fn accept_transaction_match(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TransactionMatch>, AppError> {
+    info!("Handler: Accepting transaction match {}", id);
+    let accepted =
+        transaction_matching::accept_transaction_match(&pool, ctx.tenant_id, id, ctx.user_id)
+            .await?;
+    Ok(Json(accepted))
+}
+
+/// POST /transaction-matches/:id/reject
+/// Rejects a proposal; the same pair will not be proposed again.
+async fn reject_transaction_match(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TransactionMatch>, AppError> {
+    info!("Handler: Rejecting transaction match {}", id);
+    let rejected =
+        transaction_matching::reject_transaction_match(&pool, ctx.tenant_id, id, ctx.user_id)
+            .await?;
+    Ok(Json(rejected))
+}
@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    routing::{get, put},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, field_policy::Redacted, validated_json::ValidatedJson},
+    models::{
+        account::Account,
+        dto::user_preference_dto::{PinnedBalancesQuery, SetPinnedAccountsDto},
+        user_preference::PinnedAccountBalances,
+    },
+    services::{field_policy::FieldAccess, user_preference},
+};
+
+/// Creates a router for the current user's preferences within the tenant.
+///
+/// All routes defined here will be nested under `/api/v1/users/me`.
+pub fn user_preference_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/pinned-accounts",
+            get(list_pinned_accounts).put(set_pinned_accounts),
+        )
+        .route("/pinned-accounts/balances", get(pinned_account_balances))
+        .route(
+            "/pinned-accounts/:account_id",
+            put(pin_account).delete(unpin_account),
+        )
+}
+
+/// GET /users/me/pinned-accounts
+/// Lists the current user's pinned accounts in pin order.
+async fn list_pinned_accounts(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<Account>>, AppError> {
+    info!("Handler: Listing pinned accounts for user {}", ctx.user_id);
+    let accounts = user_preference::list_pinned_accounts(&pool, ctx.tenant_id, ctx.user_id).await?;
+    Ok(Json(accounts))
+}
+
+/// PUT /users/me/pinned-accounts
+/// Replaces the pinned accounts and their order.
+async fn set_pinned_accounts(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<SetPinnedAccountsDto>,
+) -> Result<Json<Vec<Account>>, AppError> {
+    info!("Handler: Setting pinned accounts for user {}", ctx.user_id);
+    let accounts =
+        user_preference::set_pinned_accounts(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok(Json(accounts))
+}
+
+/// GET /users/me/pinned-accounts/balances?as_of=
+/// Balances of all pinned accounts in one call, in pin order.
+async fn pinned_account_balances(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    access: FieldAccess,
+    Query(query): Query<PinnedBalancesQuery>,
+) -> Result<Redacted<PinnedAccountBalances>, AppError> {
+    info!("Handler: Pinned account balances for user {}", ctx.user_id);
+    let balances =
+        user_preference::pinned_account_balances(&pool, ctx.tenant_id, ctx.user_id, query.as_of)
+            .await?;
+    Ok(Redacted(balances, access))
+}
+
+/// PUT /users/me/pinned-accounts/:account_id
+/// Pins an account after the existing pins.
+async fn pin_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(account_id): Path<Uuid>,
+) -> Result<Json<Vec<Account>>, AppError> {
+    info!(
+        "Handler: Pinning account {} for user {}",
+        account_id, ctx.user_id
+    );
+    let accounts =
+        user_preference::pin_account(&pool, ctx.tenant_id, ctx.user_id, account_id).await?;
+    Ok(Json(accounts))
+}
+
+/// DELETE /users/me/pinned-accounts/:account_id
+/// Unpins an account.
+async fn unpin_account(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(account_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!(
+        "Handler: Unpinning account {} for user {}",
+        account_id, ctx.user_id
+    );
+    user_preference::unpin_account(&pool, ctx.tenant_id, ctx.user_id, account_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        bill::Vendor,
+        dto::bill_dto::{CreateVendorDto, UpdateVendorDto},
+    },
+    services::vendor,
+};
+
+/// Creates a router for the vendors a tenant pays bills to.
+///
+/// All routes defined here will be nested under `/api/v1/vendors`.
+/// A vendor's bills are listed with `GET /bills?vendor_id=`.
+pub fn vendor_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_vendors).post(create_vendor))
+        .route(
+            "/:id",
+            get(get_vendor).put(update_vendor).delete(deactivate_vendor),
+        )
+}
+
+/// GET /vendors
+/// Lists the tenant's active vendors.
+async fn list_vendors(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+) -> Result<Json<Vec<Vendor>>, AppError> {
+    info!("Handler: Listing vendors for tenant {}", ctx.tenant_id);
+    let vendors = vendor::list_vendors(&pool, ctx.tenant_id).await?;
+    Ok(Json(vendors))
+}
+
+/// POST /vendors
+/// Creates a new vendor.
+async fn create_vendor(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    ValidatedJson(dto): ValidatedJson<CreateVendorDto>,
+) -> Result<(StatusCode, Json<Vendor>), AppError> {
+    info!("Handler: Creating vendor for tenant {}", ctx.tenant_id);
+    let vendor = vendor::create_vendor(&pool, ctx.tenant_id, ctx.user_id, dto).await?;
+    Ok((StatusCode::CREATED, Json(vendor)))
+}
+
+/// GET /vendors/:id
+/// Retrieves a single vendor.
+async fn get_vendor(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vendor>, AppError> {
+    info!("Handler: Getting vendor {}", id);
+    let vendor = vendor::get_vendor_by_id(&pool, ctx.tenant_id, id).await?;
+    Ok(Json(vendor))
+}
+
+/// PUT /vendors/:id
+/// Updates a vendor.
+async fn update_vendor(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<UpdateVendorDto>,
+) -> Result<Json<Vendor>, AppError> {
+    info!("Handler: Updating vendor {}", id);
+    let vendor = vendor::update_vendor(&pool, ctx.tenant_id, id, ctx.user_id, dto).await?;
+    Ok(Json(vendor))
+}
+
+/// DELETE /vendors/:id
+/// Deactivates a vendor with no draft or unpaid bills.
+async fn deactivate_vendor(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    info!("Handler: Deactivating vendor {}", id);
+    vendor::deactivate_vendor(&pool, ctx.tenant_id, id, ctx.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,50 @@
+use axum::{
+    extract::{Json, Path, State},
+    routing::{get, post},
+    Router,
+};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    app_state::AppState,
+    error::AppError,
+    middleware::{auth::TenantContext, validated_json::ValidatedJson},
+    models::{
+        dto::security_webhook_dto::SendTestEventDto,
+        security_webhook::{SecurityEventTypeInfo, SecurityWebhook},
+    },
+    services::{security_event_catalog, security_webhook},
+};
+
+/// Creates a router for building webhook consumers: the event catalog and test deliveries.
+/// Webhooks themselves are configured under `/security-webhook`.
+///
+/// All routes defined here will be nested under `/api/v1/webhooks`.
+pub fn webhook_routes() -> Router<AppState> {
+    Router::new()
+        .route("/event-types", get(list_event_types))
+        .route("/:id/test", post(send_test_event))
+}
+
+/// GET /webhooks/event-types
+/// Every event type with its description, JSON Schema and example body.
+async fn list_event_types() -> Json<Vec<SecurityEventTypeInfo>> {
+    info!("Handler: Listing webhook event types");
+    Json(security_event_catalog::event_catalog())
+}
+
+/// POST /webhooks/:id/test
+/// Delivers the signed example event of the chosen type and returns the delivery outcome.
+async fn send_test_event(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(id): Path<Uuid>,
+    ValidatedJson(dto): ValidatedJson<SendTestEventDto>,
+) -> Result<Json<SecurityWebhook>, AppError> {
+    info!("Handler: Sending test event to webhook {}", id);
+    let webhook =
+        security_webhook::send_sample_event(&pool, ctx.tenant_id, ctx.user_id, id, dto).await?;
+    Ok(Json(webhook))
+}
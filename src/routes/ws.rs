@@ -0,0 +1,179 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+    routing::get,
+    Router,
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use crate::{
+    app_state::AppState,
+    db,
+    error::AppError,
+    middleware::auth::{self, TenantContext},
+    models::{
+        dto::live_update_dto::ClientMessage,
+        live_update::{LiveTopic, ServerMessage},
+    },
+    services::live_update,
+};
+
+/// How long a new connection has to send its `auth` message.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Creates a router for the live update WebSocket.
+///
+/// All routes defined here will be nested under `/api/v1/ws`.
+pub fn ws_routes() -> Router<AppState> {
+    Router::new().route("/", get(open_socket))
+}
+
+/// GET /ws
+/// Upgrades to a WebSocket carrying the tenant's live updates, an alternative to polling
+/// for interactive clients. The client first sends
+/// `{"type": "auth", "tenant_id": "...", "access_token": "..."}` or an `api_key` instead of
+/// the token (same rules as the X-Tenant-Id, Authorization and X-Api-Key headers), then
+/// `{"type": "subscribe", "topics": ["transactions", "imports"]}`.
+async fn open_socket(State(state): State<AppState>, upgrade: WebSocketUpgrade) -> Response {
+    info!("Handler: Opening live update socket");
+    upgrade.on_upgrade(move |socket| serve_socket(socket, state))
+}
+
+async fn serve_socket(mut socket: WebSocket, state: AppState) {
+    // Resolving the tenant reads its membership before any scope is set
+    let ctx = match db::without_tenant_scope(handshake(&mut socket, &state)).await {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            let _ = send(&mut socket, &error_message(&e)).await;
+            let _ = socket.send(Message::Close(None)).await;
+            return;
+        }
+    };
+    info!(
+        "Live update socket opened for tenant {} user {}",
+        ctx.tenant_id, ctx.user_id
+    );
+
+    // Subscribed before `ready`, so nothing published after it is missed
+    let mut updates = live_update::subscribe();
+    let ready = ServerMessage::Ready {
+        tenant_id: ctx.tenant_id,
+        user_id: ctx.user_id,
+    };
+    if send(&mut socket, &ready).await.is_err() {
+        return;
+    }
+
+    let mut topics: BTreeSet<LiveTopic> = BTreeSet::new();
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    // Pings are answered by axum; binary frames mean nothing here
+                    Some(Ok(Message::Binary(_) | Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                };
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Subscribe { topics: added }) => {
+                        topics.extend(added);
+                        ServerMessage::Subscribed { topics: topics.iter().copied().collect() }
+                    }
+                    Ok(ClientMessage::Unsubscribe { topics: removed }) => {
+                        for topic in &removed {
+                            topics.remove(topic);
+                        }
+                        ServerMessage::Subscribed { topics: topics.iter().copied().collect() }
+                    }
+                    Ok(ClientMessage::Auth { .. }) => ServerMessage::Error {
+                        message: "The connection is already authenticated".to_string(),
+                    },
+                    Err(e) => ServerMessage::Error { message: format!("Invalid message: {}", e) },
+                };
+                if send(&mut socket, &reply).await.is_err() {
+                    break;
+                }
+            }
+            update = updates.recv() => {
+                let message = match update {
+                    Ok(update) if update.tenant_id == ctx.tenant_id && topics.contains(&update.topic) => {
+                        ServerMessage::Event {
+                            topic: update.topic,
+                            event: update.event,
+                            data: update.data,
+                        }
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => ServerMessage::Lagged { missed },
+                    Err(RecvError::Closed) => break,
+                };
+                if send(&mut socket, &message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    info!(
+        "Live update socket closed for tenant {} user {}",
+        ctx.tenant_id, ctx.user_id
+    );
+}
+
+/// Waits for the `auth` message and resolves the tenant it names.
+async fn handshake(socket: &mut WebSocket, state: &AppState) -> Result<TenantContext, AppError> {
+    let message = tokio::time::timeout(AUTH_TIMEOUT, socket.recv())
+        .await
+        .map_err(|_| {
+            AppError::Unauthorized(format!(
+                "No auth message within {} seconds",
+                AUTH_TIMEOUT.as_secs()
+            ))
+        })?;
+    let Some(Ok(Message::Text(text))) = message else {
+        return Err(AppError::Unauthorized(
+            "The first message must be an auth message".to_string(),
+        ));
+    };
+    match serde_json::from_str::<ClientMessage>(&text) {
+        Ok(ClientMessage::Auth {
+            tenant_id,
+            api_key,
+            access_token,
+        }) => {
+            auth::resolve_tenant_context(
+                state,
+                tenant_id,
+                api_key.as_deref(),
+                access_token.as_deref(),
+            )
+            .await
+        }
+        Ok(_) => Err(AppError::Unauthorized(
+            "The first message must be an auth message".to_string(),
+        )),
+        Err(e) => Err(AppError::Validation(format!("Invalid auth message: {}", e))),
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).expect("server messages always serialize");
+    socket.send(Message::Text(text)).await
+}
+
+/// Like the HTTP responses, internal details stay in the log.
+fn error_message(error: &AppError) -> ServerMessage {
+    let message = match error {
+        AppError::DatabaseError(_) | AppError::InternalServerError(_) => {
+            warn!("Live update handshake failed: {}", error);
+            "The connection could not be authenticated; please try again".to_string()
+        }
+        other => other.to_string(),
+    };
+    ServerMessage::Error { message }
+}
@@ -1,30 +1,55 @@
+use chrono::{Months, Utc};
 use sqlx::{query_as, PgPool};
 use uuid::Uuid;
 use tracing::info;
 
 use crate::{
+    db,
     error::AppError,
     models::{
         account::Account,
         dto::account_dto::{CreateAccountDto, UpdateAccountDto},
+        dto::archive_dto::ArchiveUnusedDto,
     },
+    services::{
+        dependency,
+        domain_event::{self, DomainEvent},
+        permission::{self, RECORDS_RESTORE},
+    },
+    utils::update_builder::UpdateBuilder,
 };
 
-/// Retrieves a list of accounts for a specific tenant.
-pub async fn list_accounts(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<Account>, AppError> {
+/// Retrieves a list of accounts for a specific tenant, without archived or deactivated ones
+/// unless asked; deactivated ones need `records.restore`. The current user's pinned accounts
+/// come first, in pin order, followed by the rest by name.
+pub async fn list_accounts(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    include_archived: bool,
+    include_inactive: bool,
+) -> Result<Vec<Account>, AppError> {
     info!("Service: Listing accounts for tenant ID: {}", tenant_id);
 
+    if include_inactive {
+        permission::require_permission(pool, tenant_id, user_id, RECORDS_RESTORE).await?;
+    }
+
     let accounts = query_as!(
         Account,
         r#"
         SELECT
-            id, tenant_id, account_type_id, name, account_code, description,
-            currency_code, is_active, created_at, created_by, updated_at, updated_by
-        FROM accounts
-        WHERE tenant_id = $1 AND is_active = TRUE
-        ORDER BY name
+            a.id, a.tenant_id, a.account_type_id, a.name, a.account_code, a.description,
+            a.currency_code, a.is_sensitive, a.is_active, a.archived_at, a.created_at, a.created_by, a.updated_at, a.updated_by, a.version
+        FROM accounts a
+        LEFT JOIN user_preferences up ON up.user_id = $2 AND up.tenant_id = a.tenant_id
+        WHERE a.tenant_id = $1 AND (a.is_active = TRUE OR $4) AND (a.archived_at IS NULL OR $3)
+        ORDER BY array_position(up.pinned_account_ids, a.id) NULLS LAST, a.name
         "#,
-        tenant_id
+        tenant_id,
+        user_id,
+        include_archived,
+        include_inactive
     )
     .fetch_all(pool)
     .await?;
@@ -45,7 +70,7 @@ pub async fn get_account_by_id(
         r#"
         SELECT
             id, tenant_id, account_type_id, name, account_code, description,
-            currency_code, is_active, created_at, created_by, updated_at, updated_by
+            currency_code, is_sensitive, is_active, archived_at, created_at, created_by, updated_at, updated_by, version
         FROM accounts
         WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
         "#,
@@ -73,12 +98,12 @@ pub async fn create_account(
         r#"
         INSERT INTO accounts (
             tenant_id, account_type_id, name, account_code, description,
-            currency_code, is_active, created_by, updated_by
+            currency_code, is_sensitive, is_active, created_by, updated_by
         )
-        VALUES ($1, $2, $3, $4, $5, $6, TRUE, $7, $7)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, TRUE, $8, $8)
         RETURNING
             id, tenant_id, account_type_id, name, account_code, description,
-            currency_code, is_active, created_at, created_by, updated_at, updated_by
+            currency_code, is_sensitive, is_active, archived_at, created_at, created_by, updated_at, updated_by, version
         "#,
         tenant_id,
         dto.account_type_id,
@@ -86,6 +111,7 @@ pub async fn create_account(
         dto.account_code,
         dto.description,
         dto.currency_code,
+        dto.is_sensitive.unwrap_or(false),
         created_by_user_id
     )
     .fetch_one(pool)
@@ -94,120 +120,307 @@ pub async fn create_account(
     Ok(new_account)
 }
 
-/// Updates an existing account for a specific tenant.
+/// Updates an existing account for a specific tenant, provided it is still at
+/// `expected_version` (the version the client read).
 pub async fn update_account(
     pool: &PgPool,
     tenant_id: Uuid,
     account_id: Uuid,
     updated_by_user_id: Uuid,
+    expected_version: i32,
     dto: UpdateAccountDto,
 ) -> Result<Account, AppError> {
     info!("Service: Updating account with ID: {} for tenant ID: {}", account_id, tenant_id);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
-
-    if let Some(account_type_id) = dto.account_type_id {
-        update_cols.push(format!("account_type_id = ${}", param_idx));
-        update_values.push(Box::new(account_type_id));
-        param_idx += 1;
-    }
-    if let Some(name) = dto.name {
-        update_cols.push(format!("name = ${}", param_idx));
-        update_values.push(Box::new(name));
-        param_idx += 1;
-    }
-    if let Some(account_code) = dto.account_code {
-        update_cols.push(format!("account_code = ${}", param_idx));
-        update_values.push(Box::new(account_code));
-        param_idx += 1;
-    }
-    if let Some(description) = dto.description {
-        update_cols.push(format!("description = ${}", param_idx));
-        update_values.push(Box::new(description));
-        param_idx += 1;
-    }
-    if let Some(currency_code) = dto.currency_code {
-        update_cols.push(format!("currency_code = ${}", param_idx));
-        update_values.push(Box::new(currency_code));
-        param_idx += 1;
-    }
-    if let Some(is_active) = dto.is_active {
-        update_cols.push(format!("is_active = ${}", param_idx));
-        update_values.push(Box::new(is_active));
-        param_idx += 1;
+    if dto.is_active == Some(false) {
+        dependency::ensure_none(dependency::account_blockers(pool, tenant_id, account_id).await?)?;
     }
 
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
-
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
+    let mut update = UpdateBuilder::new("accounts");
+    update
+        .set("account_type_id", dto.account_type_id)
+        .set("name", dto.name)
+        .patch("account_code", dto.account_code)
+        .patch("description", dto.description)
+        .set("currency_code", dto.currency_code)
+        .set("is_sensitive", dto.is_sensitive)
+        .set("is_active", dto.is_active);
+    if update.is_empty() {
+        return Err(AppError::Validation("No fields provided for update".to_string()));
     }
+    update.bump_version();
 
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(account_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(" AND version = ").push_bind(expected_version);
+    query.push(
         r#"
-        UPDATE accounts
-        SET {}
-        WHERE id = ${} AND tenant_id = ${}
         RETURNING
             id, tenant_id, account_type_id, name, account_code, description,
-            currency_code, is_active, created_at, created_by, updated_at, updated_by
+            currency_code, is_sensitive, is_active, archived_at, created_at, created_by, updated_at, updated_by, version
         "#,
-        update_clause, param_idx, param_idx + 1 // account_id and tenant_id will be the last parameters
     );
 
-    let mut query = sqlx::query_as::<_, Account>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
+    let updated_account = query.build_query_as::<Account>().fetch_optional(pool).await?;
+    match updated_account {
+        Some(account) => Ok(account),
+        None => Err(db::version_mismatch(pool, "accounts", "Account", account_id, tenant_id, expected_version).await),
     }
-    // Bind account_id and tenant_id last
-    query = query.bind(account_id);
-    query = query.bind(tenant_id);
-
-    let updated_account = query
-        .fetch_optional(pool)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Account with ID {} not found or not owned by tenant {}", account_id, tenant_id)))?;
-
-    Ok(updated_account)
 }
 
-/// Deactivates an account (soft delete) for a specific tenant.
+/// Deactivates an account (soft delete) for a specific tenant. An account that active budget
+/// lines or recurring transactions still use is only deactivated with `cascade`, which
+/// deactivates them too.
 pub async fn deactivate_account(
     pool: &PgPool,
     tenant_id: Uuid,
     account_id: Uuid,
     updated_by_user_id: Uuid,
+    cascade: bool,
 ) -> Result<(), AppError> {
     info!("Service: Deactivating account with ID: {} for tenant ID: {}", account_id, tenant_id);
 
+    let mut db_tx = pool.begin().await?;
+    // The row lock also holds off new budget lines or recurring transactions referencing it
+    let exists = sqlx::query_scalar!(
+        "SELECT id FROM accounts WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE FOR UPDATE",
+        account_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound(format!("Account with ID {} not found or already inactive for tenant {}", account_id, tenant_id)));
+    }
+
+    if cascade {
+        let cascaded = dependency::cascade_account(&mut db_tx, tenant_id, account_id, updated_by_user_id).await?;
+        info!("Service: Deactivated {} dependents of account ID: {}", cascaded, account_id);
+    } else {
+        dependency::ensure_none(dependency::account_blockers(&mut *db_tx, tenant_id, account_id).await?)?;
+    }
+
     let affected_rows = sqlx::query!(
         r#"
         UPDATE accounts
         SET
             is_active = FALSE,
             updated_at = NOW(),
-            updated_by = $3
+            updated_by = $3,
+            version = version + 1
         WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
         "#,
         account_id,
         tenant_id,
         updated_by_user_id
     )
-    .execute(pool)
+    .execute(&mut *db_tx)
     .await?
     .rows_affected();
 
     if affected_rows == 0 {
         return Err(AppError::NotFound(format!("Account with ID {} not found or already inactive for tenant {}", account_id, tenant_id)));
     }
+    db_tx.commit().await?;
 
+    domain_event::publish(DomainEvent::AccountDeactivated { tenant_id, user_id: updated_by_user_id, account_id });
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Restores a deactivated account. Requires `records.restore`.
+pub async fn restore_account(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    account_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<Account, AppError> {
+    info!("Service: Restoring account with ID: {} for tenant ID: {}", account_id, tenant_id);
+
+    permission::require_permission(pool, tenant_id, updated_by_user_id, RECORDS_RESTORE).await?;
+
+    let account = query_as!(
+        Account,
+        r#"
+        UPDATE accounts
+        SET
+            is_active = TRUE,
+            updated_at = NOW(),
+            updated_by = $3,
+            version = version + 1
+        WHERE id = $1 AND tenant_id = $2 AND is_active = FALSE
+        RETURNING
+            id, tenant_id, account_type_id, name, account_code, description,
+            currency_code, is_sensitive, is_active, archived_at, created_at, created_by, updated_at, updated_by, version
+        "#,
+        account_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Deactivated account with ID {} not found for tenant {}", account_id, tenant_id)))?;
+
+    domain_event::publish(DomainEvent::AccountRestored { tenant_id, user_id: updated_by_user_id, account_id });
+    Ok(account)
+}
+
+/// Archives an account: it stays in reports and drill-downs but is hidden from pickers and
+/// can no longer be posted to.
+pub async fn archive_account(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    account_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<Account, AppError> {
+    info!("Service: Archiving account with ID: {} for tenant ID: {}", account_id, tenant_id);
+
+    let account = query_as!(
+        Account,
+        r#"
+        UPDATE accounts
+        SET
+            archived_at = NOW(),
+            updated_at = NOW(),
+            updated_by = $3,
+            version = version + 1
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE AND archived_at IS NULL
+        RETURNING
+            id, tenant_id, account_type_id, name, account_code, description,
+            currency_code, is_sensitive, is_active, archived_at, created_at, created_by, updated_at, updated_by, version
+        "#,
+        account_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Account with ID {} not found or already archived for tenant {}", account_id, tenant_id)))?;
+
+    Ok(account)
+}
+
+/// Unarchives an account, making it available for postings again.
+pub async fn unarchive_account(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    account_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<Account, AppError> {
+    info!("Service: Unarchiving account with ID: {} for tenant ID: {}", account_id, tenant_id);
+
+    let account = query_as!(
+        Account,
+        r#"
+        UPDATE accounts
+        SET
+            archived_at = NULL,
+            updated_at = NOW(),
+            updated_by = $3,
+            version = version + 1
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE AND archived_at IS NOT NULL
+        RETURNING
+            id, tenant_id, account_type_id, name, account_code, description,
+            currency_code, is_sensitive, is_active, archived_at, created_at, created_by, updated_at, updated_by, version
+        "#,
+        account_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Archived account with ID {} not found for tenant {}", account_id, tenant_id)))?;
+
+    Ok(account)
+}
+
+/// Archives every account created more than `unused_for_months` ago that has no postings
+/// dated since, a posted balance of zero, no drafts, and no active recurring transaction or
+/// linked bank account. With `dry_run` the accounts are only listed.
+pub async fn archive_unused_accounts(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: ArchiveUnusedDto,
+) -> Result<Vec<Account>, AppError> {
+    info!("Service: Archiving accounts unused for {} months for tenant ID: {}", dto.unused_for_months, tenant_id);
+
+    let cutoff = Utc::now()
+        .checked_sub_months(Months::new(dto.unused_for_months))
+        .ok_or_else(|| AppError::Validation("unused_for_months is out of range".to_string()))?;
+
+    let mut db_tx = pool.begin().await?;
+    let candidates = query_as!(
+        Account,
+        r#"
+        SELECT
+            a.id, a.tenant_id, a.account_type_id, a.name, a.account_code, a.description,
+            a.currency_code, a.is_sensitive, a.is_active, a.archived_at, a.created_at, a.created_by, a.updated_at, a.updated_by, a.version
+        FROM accounts a
+        WHERE a.tenant_id = $1
+            AND a.is_active = TRUE
+            AND a.archived_at IS NULL
+            AND a.created_at < $2
+            AND NOT EXISTS(
+                SELECT 1
+                FROM journal_entries je
+                JOIN transactions t ON t.id = je.transaction_id
+                WHERE je.account_id = a.id
+                    AND (t.transaction_date >= $2::date OR t.status IN ('DRAFT', 'PENDING_APPROVAL'))
+            )
+            AND COALESCE((
+                SELECT SUM(CASE WHEN je.entry_type = 'DEBIT' THEN je.amount ELSE -je.amount END)
+                FROM journal_entries je
+                JOIN transactions t ON t.id = je.transaction_id AND t.status = 'POSTED'
+                WHERE je.account_id = a.id
+            ), 0) = 0
+            AND NOT EXISTS(
+                SELECT 1
+                FROM recurring_transactions rt
+                WHERE rt.tenant_id = $1
+                    AND rt.is_active = TRUE
+                    AND (
+                        rt.account_id = a.id
+                        OR rt.journal_template @> jsonb_build_array(jsonb_build_object('account_id', a.id))
+                    )
+            )
+            AND NOT EXISTS(SELECT 1 FROM external_accounts ea WHERE ea.account_id = a.id)
+        ORDER BY a.account_code NULLS LAST, a.name
+        FOR UPDATE OF a
+        "#,
+        tenant_id,
+        cutoff
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    if dto.dry_run || candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    let ids: Vec<Uuid> = candidates.iter().map(|account| account.id).collect();
+    let archived = query_as!(
+        Account,
+        r#"
+        UPDATE accounts
+        SET
+            archived_at = NOW(),
+            updated_at = NOW(),
+            updated_by = $3,
+            version = version + 1
+        WHERE tenant_id = $1 AND id = ANY($2)
+        RETURNING
+            id, tenant_id, account_type_id, name, account_code, description,
+            currency_code, is_sensitive, is_active, archived_at, created_at, created_by, updated_at, updated_by, version
+        "#,
+        tenant_id,
+        &ids,
+        updated_by_user_id
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    db_tx.commit().await?;
+
+    info!("Service: Archived {} unused accounts for tenant ID: {}", archived.len(), tenant_id);
+    Ok(archived)
+}
@@ -8,10 +8,18 @@ use crate::{
         account_type::{AccountType, AccountNormalBalance},
         dto::account_type_dto::{CreateAccountTypeDto, UpdateAccountTypeDto},
     },
+    services::{
+        permission,
+        reference_cache::{self, ReferenceData},
+    },
+    utils::update_builder::UpdateBuilder,
 };
 
 /// Retrieves a list of all active account types.
 pub async fn list_account_types(pool: &PgPool) -> Result<Vec<AccountType>, AppError> {
+    if let Some(account_types) = reference_cache::account_types().get(&()).await {
+        return Ok(account_types);
+    }
     info!("Service: Listing all active account types.");
 
     let account_types = query_as!(
@@ -27,6 +35,7 @@ pub async fn list_account_types(pool: &PgPool) -> Result<Vec<AccountType>, AppEr
     )
     .fetch_all(pool)
     .await?;
+    reference_cache::account_types().insert((), account_types.clone()).await;
 
     Ok(account_types)
 }
@@ -35,26 +44,15 @@ pub async fn list_account_types(pool: &PgPool) -> Result<Vec<AccountType>, AppEr
 pub async fn get_account_type_by_id(pool: &PgPool, account_type_id: Uuid) -> Result<AccountType, AppError> {
     info!("Service: Getting account type with ID: {}", account_type_id);
 
-    let account_type = query_as!(
-        AccountType,
-        r#"
-        SELECT
-            id, name, normal_balance as "normal_balance!: AccountNormalBalance",
-            is_active, created_at, created_by, updated_at, updated_by
-        FROM account_types
-        WHERE id = $1 AND is_active = TRUE
-        "#,
-        account_type_id
-    )
-    .fetch_optional(pool)
-    .await?
-    .ok_or_else(|| AppError::NotFound(format!("Account type with ID {} not found", account_type_id)))?;
-
-    Ok(account_type)
+    list_account_types(pool)
+        .await?
+        .into_iter()
+        .find(|account_type| account_type.id == account_type_id)
+        .ok_or_else(|| AppError::NotFound(format!("Account type with ID {} not found", account_type_id)))
 }
 
 /// Creates a new account type.
-/// `created_by_user_id` should come from an authenticated system administrator.
+/// `created_by_user_id` must be a system administrator.
 pub async fn create_account_type(
     pool: &PgPool,
     created_by_user_id: Uuid,
@@ -62,6 +60,8 @@ pub async fn create_account_type(
 ) -> Result<AccountType, AppError> {
     info!("Service: Creating new account type with name: {}", dto.name);
 
+    permission::require_system_admin(pool, created_by_user_id).await?;
+
     let new_account_type = query_as!(
         AccountType,
         r#"
@@ -79,12 +79,13 @@ pub async fn create_account_type(
     )
     .fetch_one(pool)
     .await?;
+    reference_cache::changed(pool, ReferenceData::AccountTypes).await;
 
     Ok(new_account_type)
 }
 
 /// Updates an existing account type.
-/// `updated_by_user_id` should come from an authenticated system administrator.
+/// `updated_by_user_id` must be a system administrator.
 pub async fn update_account_type(
     pool: &PgPool,
     account_type_id: Uuid,
@@ -93,67 +94,38 @@ pub async fn update_account_type(
 ) -> Result<AccountType, AppError> {
     info!("Service: Updating account type with ID: {}", account_type_id);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
+    permission::require_system_admin(pool, updated_by_user_id).await?;
 
-    if let Some(name) = dto.name {
-        update_cols.push(format!("name = ${}", param_idx));
-        update_values.push(Box::new(name));
-        param_idx += 1;
-    }
-    if let Some(normal_balance) = dto.normal_balance {
-        update_cols.push(format!("normal_balance = ${}", param_idx));
-        update_values.push(Box::new(normal_balance as AccountNormalBalance)); // Cast enum for binding
-        param_idx += 1;
+    let mut update = UpdateBuilder::new("account_types");
+    update
+        .set("name", dto.name)
+        .set("normal_balance", dto.normal_balance)
+        .set("is_active", dto.is_active);
+    if update.is_empty() {
+        return Err(AppError::Validation("No fields provided for update".to_string()));
     }
-    if let Some(is_active) = dto.is_active {
-        update_cols.push(format!("is_active = ${}", param_idx));
-        update_values.push(Box::new(is_active));
-        param_idx += 1;
-    }
-
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
 
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
-    }
-
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(account_type_id);
+    query.push(
         r#"
-        UPDATE account_types
-        SET {}
-        WHERE id = ${}
         RETURNING
-            id, name, normal_balance as "normal_balance!: AccountNormalBalance",
-            is_active, created_at, created_by, updated_at, updated_by
+            id, name, normal_balance, is_active, created_at, created_by, updated_at, updated_by
         "#,
-        update_clause, param_idx // account_type_id will be the last parameter
     );
 
-    let mut query = sqlx::query_as::<_, AccountType>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
-    }
-    // Bind account_type_id last
-    query = query.bind(account_type_id);
-
     let updated_account_type = query
+        .build_query_as::<AccountType>()
         .fetch_optional(pool)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Account type with ID {} not found", account_type_id)))?;
+    reference_cache::changed(pool, ReferenceData::AccountTypes).await;
 
     Ok(updated_account_type)
 }
 
 /// Deactivates an account type (soft delete).
-/// `updated_by_user_id` should come from an authenticated system administrator.
+/// `updated_by_user_id` must be a system administrator.
 pub async fn deactivate_account_type(
     pool: &PgPool,
     account_type_id: Uuid,
@@ -161,6 +133,8 @@ pub async fn deactivate_account_type(
 ) -> Result<(), AppError> {
     info!("Service: Deactivating account type with ID: {}", account_type_id);
 
+    permission::require_system_admin(pool, updated_by_user_id).await?;
+
     let affected_rows = sqlx::query!(
         r#"
         UPDATE account_types
@@ -180,6 +154,7 @@ pub async fn deactivate_account_type(
     if affected_rows == 0 {
         return Err(AppError::NotFound(format!("Account type with ID {} not found or already inactive", account_type_id)));
     }
+    reference_cache::changed(pool, ReferenceData::AccountTypes).await;
 
     Ok(())
 }
\ No newline at end of file
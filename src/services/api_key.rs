@@ -0,0 +1,378 @@
+//! API keys and sandbox tenants.
+//!
+//! A key acts as the user who created it within the key's tenant. Sandbox keys are
+//! redirected to the tenant's sandbox, a separate tenant created with the first sandbox
+//! key: it starts with a copy of the chart of accounts, categories, tags and member roles
+//! but none of the books, so integrations can post transactions and receive webhooks
+//! without touching production data. Being an ordinary tenant, everything in it stays
+//! isolated by the usual tenant scoping.
+//!
+//! Keys look like `fk_live_...` or `fk_test_...` (sandbox); only a SHA-256 hash is stored.
+
+use serde_json::json;
+use sqlx::{query_as, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::AppError,
+    models::{
+        api_key::{ApiKey, ApiKeyScope, CreatedApiKey, TenantSandbox},
+        dto::api_key_dto::CreateApiKeyDto,
+        security_webhook::SecurityEventType,
+    },
+    services::{
+        permission::{self, API_KEYS_MANAGE},
+        security_webhook,
+    },
+    utils::crypto::{generate_secret, sha256_hex},
+};
+
+/// Random bytes in a key, after its prefix.
+const KEY_BYTES: usize = 32;
+
+/// Characters of the key kept (unhashed) to tell keys apart.
+const KEY_PREFIX_LEN: usize = 12;
+
+/// Lists the tenant's API keys, revoked ones included.
+pub async fn list_api_keys(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<ApiKey>, AppError> {
+    info!("Service: Listing API keys for tenant ID: {}", tenant_id);
+    permission::require_permission(pool, tenant_id, user_id, API_KEYS_MANAGE).await?;
+
+    let keys = query_as!(
+        ApiKey,
+        r#"
+        SELECT id, tenant_id, user_id, name, key_prefix, is_sandbox, last_used_at, revoked_at,
+               created_at, created_by
+        FROM api_keys
+        WHERE tenant_id = $1
+        ORDER BY revoked_at IS NOT NULL, created_at DESC
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
+
+/// Creates an API key acting as the creating user. The first sandbox key also creates the
+/// tenant's sandbox.
+pub async fn create_api_key(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: CreateApiKeyDto,
+) -> Result<CreatedApiKey, AppError> {
+    let is_sandbox = dto.is_sandbox.unwrap_or(false);
+    info!(
+        "Service: Creating {} API key '{}' for tenant ID: {}",
+        if is_sandbox { "sandbox" } else { "live" },
+        dto.name,
+        tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, API_KEYS_MANAGE).await?;
+
+    // The first sandbox key writes into the sandbox tenant, outside this tenant's scope
+    let mut db_tx = if is_sandbox {
+        db::without_tenant_scope(pool.begin()).await?
+    } else {
+        pool.begin().await?
+    };
+
+    let is_sandbox_tenant = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM tenant_sandboxes WHERE sandbox_tenant_id = $1) as "exists!""#,
+        tenant_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    if is_sandbox_tenant {
+        return Err(AppError::Validation(
+            "API keys are created in the production tenant, not its sandbox".to_string(),
+        ));
+    }
+    if is_sandbox {
+        ensure_sandbox(&mut db_tx, tenant_id, user_id).await?;
+    }
+
+    let key = format!(
+        "fk_{}_{}",
+        if is_sandbox { "test" } else { "live" },
+        generate_secret(KEY_BYTES)
+    );
+    let api_key = query_as!(
+        ApiKey,
+        r#"
+        INSERT INTO api_keys (tenant_id, user_id, name, key_prefix, key_hash, is_sandbox, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $2)
+        RETURNING id, tenant_id, user_id, name, key_prefix, is_sandbox, last_used_at, revoked_at,
+                  created_at, created_by
+        "#,
+        tenant_id,
+        user_id,
+        dto.name.trim(),
+        &key[..KEY_PREFIX_LEN],
+        sha256_hex(key.as_bytes()),
+        is_sandbox
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+
+    security_webhook::emit_security_event(
+        pool,
+        tenant_id,
+        SecurityEventType::ApiKeyCreated,
+        Some(user_id),
+        json!({
+            "api_key_id": api_key.id,
+            "name": api_key.name,
+            "key_prefix": api_key.key_prefix,
+            "is_sandbox": api_key.is_sandbox,
+        }),
+    )
+    .await;
+
+    Ok(CreatedApiKey { api_key, key })
+}
+
+/// Revokes an API key; requests made with it are rejected from then on.
+pub async fn revoke_api_key(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    api_key_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Revoking API key ID: {} for tenant ID: {}",
+        api_key_id, tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, API_KEYS_MANAGE).await?;
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE api_keys
+        SET revoked_at = NOW()
+        WHERE id = $1 AND tenant_id = $2 AND revoked_at IS NULL
+        "#,
+        api_key_id,
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Active API key with ID {} not found for tenant {}",
+            api_key_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// The tenant's sandbox, once a sandbox key has been created.
+pub async fn get_sandbox(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<TenantSandbox, AppError> {
+    info!("Service: Getting sandbox for tenant ID: {}", tenant_id);
+    permission::require_permission(pool, tenant_id, user_id, API_KEYS_MANAGE).await?;
+
+    let sandbox = query_as!(
+        TenantSandbox,
+        r#"
+        SELECT tenant_id, sandbox_tenant_id, created_at, created_by
+        FROM tenant_sandboxes
+        WHERE tenant_id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Tenant {} has no sandbox; create a sandbox API key first",
+            tenant_id
+        ))
+    })?;
+
+    Ok(sandbox)
+}
+
+/// Resolves an API key to what requests made with it act on.
+pub async fn authenticate(pool: &PgPool, key: &str) -> Result<ApiKeyScope, AppError> {
+    let row = sqlx::query!(
+        r#"
+        UPDATE api_keys k
+        SET last_used_at = NOW()
+        WHERE k.key_hash = $1 AND k.revoked_at IS NULL
+        RETURNING k.tenant_id, k.user_id, k.is_sandbox,
+                  (SELECT s.sandbox_tenant_id FROM tenant_sandboxes s WHERE s.tenant_id = k.tenant_id)
+                      as "sandbox_tenant_id?"
+        "#,
+        sha256_hex(key.as_bytes())
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::Unauthorized("Invalid or revoked API key".to_string()))?;
+
+    let tenant_id = if row.is_sandbox {
+        row.sandbox_tenant_id.ok_or_else(|| {
+            AppError::InternalServerError(format!(
+                "Sandbox key of tenant {} has no sandbox",
+                row.tenant_id
+            ))
+        })?
+    } else {
+        row.tenant_id
+    };
+
+    Ok(ApiKeyScope {
+        key_tenant_id: row.tenant_id,
+        tenant_id,
+        user_id: row.user_id,
+    })
+}
+
+/// Creates the tenant's sandbox if it does not exist yet and returns its tenant ID.
+async fn ensure_sandbox(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Uuid, AppError> {
+    // Locking the tenant serializes concurrent first sandbox keys
+    let tenant = sqlx::query!(
+        "SELECT name FROM tenants WHERE id = $1 AND is_active = TRUE FOR UPDATE",
+        tenant_id
+    )
+    .fetch_optional(&mut **db_tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+
+    let existing = sqlx::query_scalar!(
+        "SELECT sandbox_tenant_id FROM tenant_sandboxes WHERE tenant_id = $1",
+        tenant_id
+    )
+    .fetch_optional(&mut **db_tx)
+    .await?;
+    if let Some(sandbox_tenant_id) = existing {
+        return Ok(sandbox_tenant_id);
+    }
+
+    info!("Service: Creating sandbox for tenant ID: {}", tenant_id);
+
+    let sandbox_tenant_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO tenants (
+            name, industry, base_currency_code, fiscal_year_end_month, is_active, created_by, updated_by
+        )
+        SELECT $2, industry, base_currency_code, fiscal_year_end_month, TRUE, $3, $3
+        FROM tenants
+        WHERE id = $1
+        RETURNING id
+        "#,
+        tenant_id,
+        format!("{} (sandbox)", tenant.name),
+        user_id
+    )
+    .fetch_one(&mut **db_tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => AppError::Conflict(format!(
+            "A tenant named '{} (sandbox)' already exists",
+            tenant.name
+        )),
+        e => e.into(),
+    })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_tenant_roles (user_id, tenant_id, role_id, created_by, updated_by)
+        SELECT user_id, $2, role_id, $3, $3
+        FROM user_tenant_roles
+        WHERE tenant_id = $1
+        "#,
+        tenant_id,
+        sandbox_tenant_id,
+        user_id
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO accounts (
+            tenant_id, account_type_id, name, account_code, description, currency_code,
+            is_sensitive, is_active, archived_at, created_by, updated_by
+        )
+        SELECT $2, account_type_id, name, account_code, description, currency_code,
+               is_sensitive, is_active, archived_at, $3, $3
+        FROM accounts
+        WHERE tenant_id = $1
+        "#,
+        tenant_id,
+        sandbox_tenant_id,
+        user_id
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    // New IDs are assigned up front so copied subcategories can point at their copied parent
+    sqlx::query!(
+        r#"
+        WITH copies AS (
+            SELECT id as source_id, gen_random_uuid() as copy_id
+            FROM categories
+            WHERE tenant_id = $1
+        )
+        INSERT INTO categories (
+            id, tenant_id, name, description, type, parent_category_id, is_active, archived_at,
+            created_by, updated_by
+        )
+        SELECT c.copy_id, $2, src.name, src.description, src.type, parent.copy_id, src.is_active,
+               src.archived_at, $3, $3
+        FROM categories src
+        JOIN copies c ON c.source_id = src.id
+        LEFT JOIN copies parent ON parent.source_id = src.parent_category_id
+        "#,
+        tenant_id,
+        sandbox_tenant_id,
+        user_id
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO tags (tenant_id, name, description, is_active, created_by, updated_by)
+        SELECT $2, name, description, is_active, $3, $3
+        FROM tags
+        WHERE tenant_id = $1
+        "#,
+        tenant_id,
+        sandbox_tenant_id,
+        user_id
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO tenant_sandboxes (tenant_id, sandbox_tenant_id, created_by) VALUES ($1, $2, $3)",
+        tenant_id,
+        sandbox_tenant_id,
+        user_id
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    Ok(sandbox_tenant_id)
+}
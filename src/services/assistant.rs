@@ -0,0 +1,418 @@
+//! Natural-language questions about a tenant's books ("how much did we spend on travel last
+//! quarter?").
+//!
+//! A pluggable `AssistantProvider` (a language model) translates the question into one of
+//! the whitelisted `AnalyticsQuery` variants; the query is then run here like any report,
+//! and the answer is written from its result rather than by the model. Only the question,
+//! today's date and the fiscal year end are sent to the provider, never tenant data;
+//! category names in the query are matched against the tenant's categories afterwards.
+//!
+//! Disabled unless `ASSISTANT_PROVIDER` is set.
+
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        assistant::{AnalyticsQuery, AssistantAnswer},
+        dimension::DimensionFilter,
+        dto::assistant_dto::AskQuestionDto,
+        report::FinancialStatement,
+        transaction::TransactionType,
+    },
+    services::{report, tenant},
+};
+
+const OPENAI_CHAT_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Instructions for translating a question; `{today}` and `{fiscal_year_end_month}` are
+/// filled in per request.
+const SYSTEM_PROMPT: &str = r#"You translate questions about a company's or household's books into exactly one JSON object, with no other text. Today is {today}; the fiscal year ends in month {fiscal_year_end_month}. Resolve relative periods ("last quarter", "this year") to explicit inclusive dates; quarters are calendar quarters unless the question says fiscal.
+
+Choose one of:
+{"query": "category_totals", "from_date": "YYYY-MM-DD", "to_date": "YYYY-MM-DD", "transaction_type": "EXPENSE" | "INCOME" | null, "categories": ["category name", ...]}
+  Totals of posted transactions per category. Use EXPENSE for spending, INCOME for earnings. "categories" lists the categories the question names, or is empty for all.
+{"query": "income_statement", "from_date": "YYYY-MM-DD", "to_date": "YYYY-MM-DD"}
+  Revenue, expenses and net income (profit) over a period.
+{"query": "balance_sheet", "as_of": "YYYY-MM-DD"}
+  Assets, liabilities and equity (net worth) on a date.
+{"query": "unsupported", "reason": "..."}
+  When none of the above answers the question."#;
+
+/// Which `AssistantProvider` answers questions, from `ASSISTANT_PROVIDER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssistantBackend {
+    Disabled,
+    OpenAi,
+}
+
+impl FromStr for AssistantBackend {
+    type Err = String;
+
+    /// Accepts the `ASSISTANT_PROVIDER` values: `none` or `openai`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(AssistantBackend::Disabled),
+            "openai" => Ok(AssistantBackend::OpenAi),
+            _ => Err("expected 'none' or 'openai'".to_string()),
+        }
+    }
+}
+
+/// A language model that turns a question into a JSON analytics query.
+#[async_trait]
+pub trait AssistantProvider: Send + Sync {
+    /// Answers `question` following `instructions`; returns the model's JSON text.
+    async fn translate(&self, instructions: &str, question: &str) -> Result<String, AppError>;
+}
+
+/// Resolves the configured provider.
+pub fn provider_from_config() -> Result<Box<dyn AssistantProvider>, AppError> {
+    let integrations = &crate::config::get().integrations;
+    match integrations.assistant_provider {
+        AssistantBackend::Disabled => Err(AppError::ServiceUnavailable(
+            "Questions are not enabled on this server".to_string(),
+        )),
+        AssistantBackend::OpenAi => Ok(Box::new(OpenAiProvider {
+            client: Client::new(),
+            url: integrations
+                .assistant_api_url
+                .clone()
+                .unwrap_or_else(|| OPENAI_CHAT_URL.to_string()),
+            api_key: integrations.assistant_api_key.clone(),
+            model: integrations.assistant_model.clone(),
+        })),
+    }
+}
+
+/// Provider for OpenAI's chat completions API and compatible servers.
+pub struct OpenAiProvider {
+    client: Client,
+    url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+#[async_trait]
+impl AssistantProvider for OpenAiProvider {
+    async fn translate(&self, instructions: &str, question: &str) -> Result<String, AppError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(REQUEST_TIMEOUT)
+            .json(&json!({
+                "model": self.model,
+                "temperature": 0,
+                "response_format": { "type": "json_object" },
+                "messages": [
+                    { "role": "system", "content": instructions },
+                    { "role": "user", "content": question },
+                ],
+            }));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            AppError::ServiceUnavailable(format!("The assistant is unreachable: {}", e))
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            warn!("Assistant provider returned {}: {}", status, body);
+            return Err(AppError::ServiceUnavailable(format!(
+                "The assistant returned {}",
+                status
+            )));
+        }
+        let completion: ChatCompletion = response.json().await.map_err(|e| {
+            AppError::InternalServerError(format!("Invalid assistant response: {}", e))
+        })?;
+
+        completion
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| {
+                AppError::InternalServerError("The assistant gave no answer".to_string())
+            })
+    }
+}
+
+/// Translates the question with the configured provider, runs the resulting query and
+/// answers it.
+pub async fn ask(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    dto: AskQuestionDto,
+) -> Result<AssistantAnswer, AppError> {
+    info!("Service: Answering a question for tenant ID: {}", tenant_id);
+
+    let provider = provider_from_config()?;
+    let tenant = tenant::get_tenant_by_id(pool, tenant_id).await?;
+    let instructions = SYSTEM_PROMPT
+        .replace("{today}", &Utc::now().date_naive().to_string())
+        .replace(
+            "{fiscal_year_end_month}",
+            &tenant.fiscal_year_end_month.to_string(),
+        );
+
+    let reply = provider.translate(&instructions, &dto.question).await?;
+    let query = parse_query(&reply)?;
+    info!("Service: Question translated to {:?}", query);
+
+    let (answer, data) = match &query {
+        AnalyticsQuery::CategoryTotals {
+            from_date,
+            to_date,
+            transaction_type,
+            categories,
+        } => {
+            category_totals(
+                pool,
+                tenant_id,
+                *from_date,
+                *to_date,
+                *transaction_type,
+                categories,
+            )
+            .await?
+        }
+        AnalyticsQuery::IncomeStatement { from_date, to_date } => {
+            check_period(*from_date, *to_date)?;
+            let statement = report::income_statement(
+                pool,
+                tenant_id,
+                Some(*from_date),
+                Some(*to_date),
+                None,
+                DimensionFilter::default(),
+            )
+            .await?;
+            let heading = format!(
+                "Income statement from {} to {} ({})",
+                from_date, to_date, tenant.base_currency_code
+            );
+            statement_answer(heading, statement)?
+        }
+        AnalyticsQuery::BalanceSheet { as_of } => {
+            let statement = report::balance_sheet(pool, tenant_id, Some(*as_of), None).await?;
+            let heading = format!(
+                "Balance sheet as of {} ({})",
+                as_of, tenant.base_currency_code
+            );
+            statement_answer(heading, statement)?
+        }
+        AnalyticsQuery::Unsupported { reason } => {
+            return Err(AppError::Validation(format!(
+                "This question cannot be answered yet: {}",
+                reason
+            )));
+        }
+    };
+
+    Ok(AssistantAnswer {
+        question: dto.question,
+        query,
+        answer,
+        data,
+    })
+}
+
+/// Parses the provider's reply, tolerating a Markdown code fence around the JSON.
+fn parse_query(reply: &str) -> Result<AnalyticsQuery, AppError> {
+    let json = reply
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    serde_json::from_str(json).map_err(|e| {
+        warn!("Unusable assistant reply ({}): {}", e, reply);
+        AppError::ServiceUnavailable(
+            "The assistant did not return a usable query; try rephrasing the question".to_string(),
+        )
+    })
+}
+
+fn check_period(from_date: NaiveDate, to_date: NaiveDate) -> Result<(), AppError> {
+    if from_date > to_date {
+        return Err(AppError::Validation(
+            "from_date must not be after to_date".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Runs `category_totals`: posted transaction totals per category and currency.
+async fn category_totals(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    transaction_type: Option<TransactionType>,
+    categories: &[String],
+) -> Result<(String, JsonValue), AppError> {
+    check_period(from_date, to_date)?;
+
+    // Named categories include their subcategories
+    let names: Vec<String> = categories
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    let category_ids: Vec<Uuid> = if names.is_empty() {
+        Vec::new()
+    } else {
+        let ids = sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE matched(id) AS (
+                SELECT id FROM categories
+                WHERE tenant_id = $1 AND is_active = TRUE AND LOWER(name) = ANY($2)
+                UNION
+                SELECT c.id
+                FROM categories c
+                JOIN matched m ON c.parent_category_id = m.id
+                WHERE c.tenant_id = $1
+            )
+            SELECT id as "id!" FROM matched
+            "#,
+            tenant_id,
+            &names
+        )
+        .fetch_all(pool)
+        .await?;
+        if ids.is_empty() {
+            return Err(AppError::Validation(format!(
+                "No category named {}",
+                categories.join(" or ")
+            )));
+        }
+        ids
+    };
+
+    let totals = sqlx::query!(
+        r#"
+        SELECT COALESCE(c.name, 'Uncategorized') as "category!", t.currency_code,
+               COUNT(*) as "transaction_count!", SUM(t.amount) as "total!"
+        FROM transactions t
+        LEFT JOIN categories c ON t.category_id = c.id
+        WHERE t.tenant_id = $1
+          AND t.status = 'POSTED'
+          AND t.transaction_date BETWEEN $2 AND $3
+          AND ($4::transaction_type IS NULL OR t.type = $4)
+          AND (cardinality($5::uuid[]) = 0 OR t.category_id = ANY($5))
+        GROUP BY 1, 2
+        ORDER BY 1, 2
+        "#,
+        tenant_id,
+        from_date,
+        to_date,
+        transaction_type as Option<TransactionType>,
+        &category_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut by_currency: BTreeMap<&str, (Decimal, i64)> = BTreeMap::new();
+    for row in &totals {
+        let entry = by_currency.entry(&row.currency_code).or_default();
+        entry.0 += row.total;
+        entry.1 += row.transaction_count;
+    }
+
+    let subject = match transaction_type {
+        Some(TransactionType::Expense) => "Spending",
+        Some(TransactionType::Income) => "Income",
+        _ => "Transactions",
+    };
+    let scope = if categories.is_empty() {
+        String::new()
+    } else {
+        format!(" on {}", categories.join(", "))
+    };
+    let answer = if by_currency.is_empty() {
+        format!(
+            "No posted transactions{} from {} to {}.",
+            scope, from_date, to_date
+        )
+    } else {
+        let amounts: Vec<String> = by_currency
+            .iter()
+            .map(|(currency, (total, count))| {
+                format!("{} {} ({} transactions)", total, currency, count)
+            })
+            .collect();
+        format!(
+            "{}{} from {} to {}: {}.",
+            subject,
+            scope,
+            from_date,
+            to_date,
+            amounts.join(" and ")
+        )
+    };
+
+    let rows: Vec<JsonValue> = totals
+        .into_iter()
+        .map(|row| {
+            json!({
+                "category": row.category,
+                "currency_code": row.currency_code,
+                "transaction_count": row.transaction_count,
+                "total": row.total,
+            })
+        })
+        .collect();
+    Ok((answer, JsonValue::Array(rows)))
+}
+
+/// Answers with a statement's section totals and net line; the statement is the data.
+fn statement_answer(
+    heading: String,
+    statement: FinancialStatement,
+) -> Result<(String, JsonValue), AppError> {
+    let figures: Vec<String> = statement
+        .sections
+        .iter()
+        .map(|section| format!("{} {}", section.title, section.total.amount))
+        .chain(
+            statement
+                .net
+                .iter()
+                .map(|net| format!("{} {}", net.label, net.amount)),
+        )
+        .collect();
+    let answer = format!("{}: {}.", heading, figures.join(", "));
+    let data = serde_json::to_value(&statement).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to serialize statement: {}", e))
+    })?;
+    Ok((answer, data))
+}
@@ -0,0 +1,172 @@
+//! Audit trail of security- and ledger-relevant actions.
+//!
+//! Events are stored in `audit_events` and, when an `AUDIT_SINK` is configured, forwarded
+//! to it in near real time (see `audit_sink`). Ledger actions arrive as domain events (see
+//! `domain_event`); other callers record theirs directly.
+
+use serde_json::{json, Value as JsonValue};
+use sqlx::{query_as, PgPool};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    models::audit_event::AuditEvent,
+    services::{
+        audit_sink,
+        domain_event::{self, DomainEvent},
+    },
+};
+
+// Action names, `<entity>.<verb>`
+pub const TRANSACTION_POST: &str = "transaction.post";
+pub const TRANSACTION_VOID: &str = "transaction.void";
+pub const TRANSACTION_REVERSE: &str = "transaction.reverse";
+pub const FISCAL_PERIOD_CLOSE: &str = "fiscal_period.close";
+pub const FISCAL_PERIOD_REOPEN: &str = "fiscal_period.reopen";
+pub const FX_REVALUATION_POST: &str = "fx_revaluation.post";
+pub const ACCOUNT_DEACTIVATE: &str = "account.deactivate";
+pub const ACCOUNT_RESTORE: &str = "account.restore";
+pub const USER_ERASE: &str = "user.erase";
+
+/// Records an audit event for every domain event. Call once at startup.
+pub fn spawn_audit_subscriber(pool: PgPool) {
+    domain_event::spawn_subscriber("audit", move |event| {
+        let pool = pool.clone();
+        async move { record_domain_event(&pool, &event).await }
+    });
+}
+
+/// Records an audit event and hands it to the configured sink.
+///
+/// Called after the audited change has been committed. A failure to record is logged but
+/// does not fail the caller, whose change has already happened.
+pub async fn record_audit_event(
+    pool: &PgPool,
+    tenant_id: Option<Uuid>,
+    actor_user_id: Option<Uuid>,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<Uuid>,
+    details: Option<JsonValue>,
+) {
+    let result = query_as!(
+        AuditEvent,
+        r#"
+        INSERT INTO audit_events (tenant_id, actor_user_id, action, entity_type, entity_id, details)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, tenant_id, actor_user_id, action, entity_type, entity_id, details, created_at
+        "#,
+        tenant_id,
+        actor_user_id,
+        action,
+        entity_type,
+        entity_id,
+        details
+    )
+    .fetch_one(pool)
+    .await;
+
+    match result {
+        Ok(event) => audit_sink::forward(&event),
+        Err(e) => warn!(
+            "Failed to record audit event '{}' for {} {:?}: {}",
+            action, entity_type, entity_id, e
+        ),
+    }
+}
+
+/// The audit record of a domain event: action, entity and details.
+async fn record_domain_event(pool: &PgPool, event: &DomainEvent) {
+    let (action, entity_type, entity_id, details) = match event {
+        DomainEvent::TransactionPosted {
+            transaction_id,
+            amount,
+            transaction_date,
+            ..
+        } => (
+            TRANSACTION_POST,
+            "transaction",
+            *transaction_id,
+            json!({ "amount": amount, "transaction_date": transaction_date }),
+        ),
+        DomainEvent::TransactionVoided {
+            transaction_id,
+            reason,
+            ..
+        } => (
+            TRANSACTION_VOID,
+            "transaction",
+            *transaction_id,
+            json!({ "reason": reason }),
+        ),
+        DomainEvent::TransactionReversed {
+            transaction_id,
+            reversal_id,
+            reversal_date,
+            ..
+        } => (
+            TRANSACTION_REVERSE,
+            "transaction",
+            *transaction_id,
+            json!({ "reversal_id": reversal_id, "reversal_date": reversal_date }),
+        ),
+        DomainEvent::AccountDeactivated { account_id, .. } => {
+            (ACCOUNT_DEACTIVATE, "account", *account_id, json!({}))
+        }
+        DomainEvent::AccountRestored { account_id, .. } => {
+            (ACCOUNT_RESTORE, "account", *account_id, json!({}))
+        }
+        DomainEvent::FiscalPeriodClosed {
+            period_id,
+            name,
+            start_date,
+            end_date,
+            ..
+        } => (
+            FISCAL_PERIOD_CLOSE,
+            "fiscal_period",
+            *period_id,
+            json!({ "name": name, "start_date": start_date, "end_date": end_date }),
+        ),
+        DomainEvent::FiscalPeriodReopened {
+            period_id,
+            name,
+            start_date,
+            end_date,
+            ..
+        } => (
+            FISCAL_PERIOD_REOPEN,
+            "fiscal_period",
+            *period_id,
+            json!({ "name": name, "start_date": start_date, "end_date": end_date }),
+        ),
+        DomainEvent::FxRevaluationPosted {
+            revaluation_id,
+            transaction_id,
+            as_of,
+            total_gain,
+            total_loss,
+            ..
+        } => (
+            FX_REVALUATION_POST,
+            "fx_revaluation",
+            *revaluation_id,
+            json!({
+                "as_of": as_of,
+                "transaction_id": transaction_id,
+                "total_gain": total_gain,
+                "total_loss": total_loss
+            }),
+        ),
+    };
+    record_audit_event(
+        pool,
+        Some(event.tenant_id()),
+        Some(event.user_id()),
+        action,
+        entity_type,
+        Some(entity_id),
+        Some(details),
+    )
+    .await;
+}
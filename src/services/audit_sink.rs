@@ -0,0 +1,207 @@
+//! Forwarding of audit events to a SIEM, configured per deployment.
+//!
+//! | `AUDIT_SINK` | Destination                                                          |
+//! |--------------|----------------------------------------------------------------------|
+//! | unset/`none` | Database only                                                        |
+//! | `syslog`     | RFC 5424 syslog over TCP to `AUDIT_SYSLOG_ADDR` (`host:port`), with  |
+//! |              | octet-counting framing (RFC 6587) and a CEF message body             |
+//! | `cef_file`   | One CEF line per event appended to `AUDIT_CEF_PATH`                  |
+//!
+//! Events are queued and written by a background task, so recording never waits on the
+//! SIEM. If the queue is full or the destination is down, events are dropped from the
+//! stream with a warning; they remain in `audit_events`.
+
+use std::{sync::OnceLock, time::Duration};
+
+use tokio::{
+    fs::OpenOptions,
+    io::AsyncWriteExt,
+    net::TcpStream,
+    sync::mpsc::{self, Receiver, Sender},
+};
+use tracing::{info, warn};
+
+use crate::{
+    config::{self, AuditSink},
+    models::audit_event::AuditEvent,
+};
+
+const QUEUE_CAPACITY: usize = 10_000;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const CEF_VENDOR: &str = "Forge";
+const CEF_PRODUCT: &str = "forge-backend";
+/// Syslog facility 13 (log audit), severity 5 (notice).
+const SYSLOG_PRIORITY: u8 = 13 * 8 + 5;
+
+/// Where audit events are forwarded.
+#[derive(Debug, Clone)]
+enum SinkTarget {
+    Syslog { addr: String },
+    CefFile { path: String },
+}
+
+/// Sender of the running sink, or `None` when no sink is configured.
+static SINK: OnceLock<Option<Sender<String>>> = OnceLock::new();
+
+/// Queues an audit event for the configured sink, starting the sink on first use.
+pub fn forward(event: &AuditEvent) {
+    let Some(sender) = SINK.get_or_init(start_sink) else {
+        return;
+    };
+    if sender.try_send(format_cef(event)).is_err() {
+        warn!(
+            "Audit sink queue is full or closed; event {} not forwarded",
+            event.id
+        );
+    }
+}
+
+fn start_sink() -> Option<Sender<String>> {
+    let target = sink_target()?;
+    info!("Forwarding audit events to {:?}", target);
+
+    let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+    tokio::spawn(run_sink(target, receiver));
+    Some(sender)
+}
+
+/// The configured destination; the config check guarantees its address or path is set.
+fn sink_target() -> Option<SinkTarget> {
+    let audit = &config::get().audit;
+    match audit.sink {
+        AuditSink::None => None,
+        AuditSink::Syslog => audit
+            .syslog_addr
+            .clone()
+            .map(|addr| SinkTarget::Syslog { addr }),
+        AuditSink::CefFile => audit
+            .cef_path
+            .clone()
+            .map(|path| SinkTarget::CefFile { path }),
+    }
+}
+
+async fn run_sink(target: SinkTarget, mut receiver: Receiver<String>) {
+    match target {
+        SinkTarget::Syslog { addr } => {
+            let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "-".to_string());
+            let mut stream: Option<TcpStream> = None;
+            while let Some(cef) = receiver.recv().await {
+                let message = syslog_message(&hostname, &cef);
+                // One reconnect attempt per event; the event is dropped if that fails too
+                for attempt in 0..2 {
+                    if stream.is_none() {
+                        match TcpStream::connect(&addr).await {
+                            Ok(connected) => stream = Some(connected),
+                            Err(e) => {
+                                warn!("Could not connect to syslog at {}: {}", addr, e);
+                                tokio::time::sleep(RECONNECT_DELAY).await;
+                                continue;
+                            }
+                        }
+                    }
+                    let Some(connection) = stream.as_mut() else {
+                        continue;
+                    };
+                    match connection.write_all(message.as_bytes()).await {
+                        Ok(()) => break,
+                        Err(e) => {
+                            warn!("Syslog write failed (attempt {}): {}", attempt + 1, e);
+                            stream = None;
+                        }
+                    }
+                }
+            }
+        }
+        SinkTarget::CefFile { path } => {
+            while let Some(cef) = receiver.recv().await {
+                let written = async {
+                    let mut file = OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&path)
+                        .await?;
+                    file.write_all(format!("{}\n", cef).as_bytes()).await
+                }
+                .await;
+                if let Err(e) = written {
+                    warn!("Could not append audit event to {}: {}", path, e);
+                }
+            }
+        }
+    }
+}
+
+/// RFC 5424 message with RFC 6587 octet-counting framing.
+fn syslog_message(hostname: &str, cef: &str) -> String {
+    let message = format!(
+        "<{}>1 {} {} {} - AUDIT - {}",
+        SYSLOG_PRIORITY,
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        hostname,
+        CEF_PRODUCT,
+        cef
+    );
+    format!("{} {}", message.len(), message)
+}
+
+/// Formats an event as an ArcSight Common Event Format (CEF) line.
+fn format_cef(event: &AuditEvent) -> String {
+    let mut extension = vec![
+        format!("rt={}", event.created_at.timestamp_millis()),
+        format!("externalId={}", event.id),
+        format!("act={}", cef_value(&event.action)),
+        "cs1Label=tenantId".to_string(),
+        format!(
+            "cs1={}",
+            event.tenant_id.map(|id| id.to_string()).unwrap_or_default()
+        ),
+        "cs2Label=entityType".to_string(),
+        format!("cs2={}", cef_value(&event.entity_type)),
+        "cs3Label=entityId".to_string(),
+        format!(
+            "cs3={}",
+            event.entity_id.map(|id| id.to_string()).unwrap_or_default()
+        ),
+    ];
+    if let Some(actor) = event.actor_user_id {
+        extension.push(format!("suid={}", actor));
+    }
+    if let Some(details) = &event.details {
+        extension.push(format!("msg={}", cef_value(&details.to_string())));
+    }
+
+    format!(
+        "CEF:0|{}|{}|{}|{}|{}|{}|{}",
+        CEF_VENDOR,
+        CEF_PRODUCT,
+        env!("CARGO_PKG_VERSION"),
+        cef_header(&event.action),
+        cef_header(&format!("{} {}", event.entity_type, event.action)),
+        cef_severity(&event.action),
+        extension.join(" ")
+    )
+}
+
+/// Reopening a closed period or voiding posted entries is more notable than routine posting.
+fn cef_severity(action: &str) -> u8 {
+    if action.ends_with(".reopen") || action.ends_with(".void") {
+        6
+    } else {
+        3
+    }
+}
+
+/// Escapes a CEF header field (`\` and `|`).
+fn cef_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Escapes a CEF extension value (`\`, `=` and line breaks).
+fn cef_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
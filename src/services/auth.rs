@@ -0,0 +1,417 @@
+//! Login sessions with rotating refresh tokens.
+//!
+//! Signing in creates a session: a family of refresh tokens of which only the newest is
+//! valid. Each refresh exchanges it for a new token and marks the old one rotated. A
+//! rotated token presented again was copied by someone, so the whole session is revoked
+//! and both parties have to sign in again. Only SHA-256 hashes of the tokens are stored.
+//!
+//! Sessions expire `REFRESH_TOKEN_DAYS` after sign-in, however often they are refreshed.
+//! API requests authenticate with the session's access token, which lasts
+//! `ACCESS_TOKEN_MINUTES` and is replaced on every refresh. It stops working as soon as the
+//! session is revoked or its user deactivated, and refreshing a deactivated user's session
+//! is refused.
+//!
+//! Failed password sign-ins are counted per account; reaching `FAILED_LOGIN_THRESHOLD`
+//! within `FAILED_LOGIN_WINDOW_MINUTES` raises a security event in each of its tenants.
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{query_as, PgConnection, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    config,
+    error::AppError,
+    models::{
+        auth_session::{AuthSession, SessionRevocationReason, SessionTokens},
+        dto::auth_dto::{LoginRequest, RefreshSessionRequest},
+        security_webhook::SecurityEventType,
+    },
+    services::security_webhook,
+    user::service as user_service,
+    utils::crypto::{generate_secret, sha256_hex},
+};
+
+/// Random bytes in a refresh token.
+const REFRESH_TOKEN_BYTES: usize = 32;
+/// Random bytes in an access token.
+const ACCESS_TOKEN_BYTES: usize = 32;
+/// Failed sign-ins within the window that raise `REPEATED_FAILED_LOGINS`.
+const FAILED_LOGIN_THRESHOLD: i32 = 5;
+const FAILED_LOGIN_WINDOW_MINUTES: i32 = 15;
+
+/// Signs a user in with email and password, starting a new session.
+pub async fn login(
+    pool: &PgPool,
+    dto: LoginRequest,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+) -> Result<SessionTokens, AppError> {
+    info!("Service: Signing in {}", dto.email);
+
+    let invalid = || AppError::Unauthorized("Invalid email or password".to_string());
+    let user = match user_service::get_user_by_email(pool, &dto.email).await {
+        Ok(user) => user,
+        Err(AppError::NotFound(_)) => return Err(invalid()),
+        Err(e) => return Err(e),
+    };
+    let password_hash = user.password_hash.as_deref().ok_or_else(invalid)?;
+    if !user_service::verify_password(&dto.password, password_hash)? {
+        // Counting is best effort: the caller gets the same answer either way
+        if let Err(e) = record_failed_login(pool, user.id, &user.email, ip_address).await {
+            warn!("Could not record failed sign-in for user {}: {}", user.id, e);
+        }
+        return Err(invalid());
+    }
+
+    start_session(pool, user.id, user_agent, ip_address).await
+}
+
+/// Counts a failed sign-in in the account's current window (starting a new one when it has
+/// lapsed) and emits `REPEATED_FAILED_LOGINS` once, when the count reaches the threshold.
+async fn record_failed_login(
+    pool: &PgPool,
+    user_id: Uuid,
+    email: &str,
+    ip_address: Option<String>,
+) -> Result<(), AppError> {
+    let failed_attempts = sqlx::query_scalar!(
+        r#"
+        UPDATE users
+        SET failed_login_attempts = CASE
+                WHEN failed_login_window_started_at > NOW() - make_interval(mins => $2)
+                THEN failed_login_attempts + 1
+                ELSE 1
+            END,
+            failed_login_window_started_at = CASE
+                WHEN failed_login_window_started_at > NOW() - make_interval(mins => $2)
+                THEN failed_login_window_started_at
+                ELSE NOW()
+            END
+        WHERE id = $1
+        RETURNING failed_login_attempts
+        "#,
+        user_id,
+        FAILED_LOGIN_WINDOW_MINUTES
+    )
+    .fetch_one(pool)
+    .await?;
+    if failed_attempts != FAILED_LOGIN_THRESHOLD {
+        return Ok(());
+    }
+
+    warn!(
+        "{} failed sign-ins for user {} within {} minutes",
+        failed_attempts, user_id, FAILED_LOGIN_WINDOW_MINUTES
+    );
+    let tenant_ids = sqlx::query_scalar!(
+        "SELECT DISTINCT tenant_id FROM user_tenant_roles WHERE user_id = $1",
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+    for tenant_id in tenant_ids {
+        security_webhook::emit_security_event(
+            pool,
+            tenant_id,
+            SecurityEventType::RepeatedFailedLogins,
+            None,
+            json!({
+                "email": email,
+                "failed_attempts": failed_attempts,
+                "window_minutes": FAILED_LOGIN_WINDOW_MINUTES,
+                "ip_address": ip_address,
+            }),
+        )
+        .await;
+    }
+    Ok(())
+}
+
+/// Starts a session for a user who has just proven who they are (password or an identity
+/// provider) and records the sign-in.
+pub async fn start_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+) -> Result<SessionTokens, AppError> {
+    let mut db_tx = pool.begin().await?;
+
+    let session = sqlx::query!(
+        r#"
+        INSERT INTO auth_sessions (user_id, user_agent, ip_address, expires_at)
+        VALUES ($1, $2, $3, NOW() + make_interval(days => $4))
+        RETURNING id, expires_at
+        "#,
+        user_id,
+        user_agent,
+        ip_address,
+        config::get().auth.refresh_token_days as i32
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    let refresh_token = generate_secret(REFRESH_TOKEN_BYTES);
+    sqlx::query!(
+        "INSERT INTO session_refresh_tokens (session_id, token_hash) VALUES ($1, $2)",
+        session.id,
+        sha256_hex(refresh_token.as_bytes())
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    let (access_token, access_token_expires_at) =
+        issue_access_token(&mut db_tx, session.id).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET last_login_at = NOW(), failed_login_attempts = 0, failed_login_window_started_at = NULL
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+
+    Ok(SessionTokens {
+        session_id: session.id,
+        user_id,
+        access_token,
+        access_token_expires_at,
+        refresh_token,
+        expires_at: session.expires_at,
+    })
+}
+
+/// Issues a new access token for the session, replacing the ones issued before.
+async fn issue_access_token(
+    conn: &mut PgConnection,
+    session_id: Uuid,
+) -> Result<(String, DateTime<Utc>), AppError> {
+    sqlx::query!(
+        "DELETE FROM session_access_tokens WHERE session_id = $1",
+        session_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let access_token = generate_secret(ACCESS_TOKEN_BYTES);
+    let expires_at = sqlx::query_scalar!(
+        r#"
+        INSERT INTO session_access_tokens (session_id, token_hash, expires_at)
+        VALUES ($1, $2, NOW() + make_interval(mins => $3))
+        RETURNING expires_at
+        "#,
+        session_id,
+        sha256_hex(access_token.as_bytes()),
+        config::get().auth.access_token_minutes as i32
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok((access_token, expires_at))
+}
+
+/// Resolves the user behind an access token. The token must be unexpired, its session
+/// neither revoked nor expired, and its user active.
+pub async fn authenticate(pool: &PgPool, access_token: &str) -> Result<Uuid, AppError> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT s.user_id
+        FROM session_access_tokens token
+        JOIN auth_sessions s ON s.id = token.session_id
+        JOIN users u ON u.id = s.user_id
+        WHERE token.token_hash = $1
+          AND token.expires_at > NOW()
+          AND s.revoked_at IS NULL AND s.expires_at > NOW()
+          AND u.is_active = TRUE
+        "#,
+        sha256_hex(access_token.as_bytes())
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::Unauthorized("Invalid or expired access token".to_string()))
+}
+
+/// Exchanges the session's current refresh token for a new one. Presenting a token that
+/// was already exchanged revokes the session.
+pub async fn refresh(
+    pool: &PgPool,
+    dto: RefreshSessionRequest,
+    ip_address: Option<String>,
+) -> Result<SessionTokens, AppError> {
+    info!("Service: Refreshing session");
+
+    let mut db_tx = pool.begin().await?;
+
+    // Locking the token row makes concurrent refreshes with the same token count as reuse
+    let token = sqlx::query!(
+        r#"
+        SELECT rt.id, rt.session_id, rt.rotated_at, s.user_id, s.expires_at,
+               s.revoked_at, (s.expires_at <= NOW()) as "expired!", u.is_active as user_is_active
+        FROM session_refresh_tokens rt
+        JOIN auth_sessions s ON s.id = rt.session_id
+        JOIN users u ON u.id = s.user_id
+        WHERE rt.token_hash = $1
+        FOR UPDATE OF rt, s
+        "#,
+        sha256_hex(dto.refresh_token.as_bytes())
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+
+    if token.revoked_at.is_some() || token.expired {
+        return Err(AppError::Unauthorized(
+            "The session has ended; sign in again".to_string(),
+        ));
+    }
+    if !token.user_is_active {
+        return Err(AppError::Unauthorized(
+            "This account is disabled".to_string(),
+        ));
+    }
+
+    if token.rotated_at.is_some() {
+        sqlx::query!(
+            r#"
+            UPDATE auth_sessions
+            SET revoked_at = NOW(), revoked_reason = $2
+            WHERE id = $1
+            "#,
+            token.session_id,
+            SessionRevocationReason::TokenReuse as SessionRevocationReason
+        )
+        .execute(&mut *db_tx)
+        .await?;
+        db_tx.commit().await?;
+
+        warn!(
+            "Refresh token reuse on session {} of user {}; session revoked",
+            token.session_id, token.user_id
+        );
+        return Err(AppError::Unauthorized(
+            "Refresh token was already used; the session has been revoked".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        "UPDATE session_refresh_tokens SET rotated_at = NOW() WHERE id = $1",
+        token.id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    let refresh_token = generate_secret(REFRESH_TOKEN_BYTES);
+    sqlx::query!(
+        "INSERT INTO session_refresh_tokens (session_id, token_hash) VALUES ($1, $2)",
+        token.session_id,
+        sha256_hex(refresh_token.as_bytes())
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    let (access_token, access_token_expires_at) =
+        issue_access_token(&mut db_tx, token.session_id).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE auth_sessions
+        SET last_used_at = NOW(), ip_address = COALESCE($2, ip_address)
+        WHERE id = $1
+        "#,
+        token.session_id,
+        ip_address
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+
+    Ok(SessionTokens {
+        session_id: token.session_id,
+        user_id: token.user_id,
+        access_token,
+        access_token_expires_at,
+        refresh_token,
+        expires_at: token.expires_at,
+    })
+}
+
+/// Lists the user's active sessions, most recently used first.
+pub async fn list_sessions(pool: &PgPool, user_id: Uuid) -> Result<Vec<AuthSession>, AppError> {
+    info!("Service: Listing sessions for user ID: {}", user_id);
+
+    let sessions = query_as!(
+        AuthSession,
+        r#"
+        SELECT id, user_id, user_agent, ip_address, created_at, last_used_at, expires_at
+        FROM auth_sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+        ORDER BY last_used_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Signs one of the user's sessions out; its refresh token stops working.
+pub async fn revoke_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Revoking session ID: {} for user ID: {}",
+        session_id, user_id
+    );
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE auth_sessions
+        SET revoked_at = NOW(), revoked_reason = $3
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+        "#,
+        session_id,
+        user_id,
+        SessionRevocationReason::SignedOut as SessionRevocationReason
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Active session with ID {} not found",
+            session_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Signs the user out everywhere; returns the number of sessions revoked.
+pub async fn revoke_all_sessions(pool: &PgPool, user_id: Uuid) -> Result<u64, AppError> {
+    info!("Service: Revoking all sessions for user ID: {}", user_id);
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE auth_sessions
+        SET revoked_at = NOW(), revoked_reason = $2
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+        "#,
+        user_id,
+        SessionRevocationReason::SignedOut as SessionRevocationReason
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(affected_rows)
+}
@@ -0,0 +1,165 @@
+//! Monthly account balance snapshots: one row per account and calendar month holding the
+//! posted debits and credits in base currency (`account_balance_snapshots`).
+//!
+//! Statements read whole months from the snapshots and only the days of the bounding
+//! months from the journal. Posting, voiding and reversing move the rows along in the same
+//! database transaction, so a committed transaction is always reflected; the maintenance
+//! task `rebuild_balance_snapshots` recomputes them from the journal and repairs any drift.
+
+use sqlx::{PgExecutor, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    db::{self, IsolationLevel},
+    error::AppError,
+};
+
+/// How a transaction's status change moves the snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotChange {
+    /// The transaction was posted: its entries are added.
+    Posted,
+    /// A posted transaction was voided: its entries are taken out again.
+    Voided,
+}
+
+impl SnapshotChange {
+    fn sign(self) -> i32 {
+        match self {
+            SnapshotChange::Posted => 1,
+            SnapshotChange::Voided => -1,
+        }
+    }
+}
+
+/// Result of rebuilding every tenant's snapshots.
+#[derive(Debug, Default)]
+pub struct RebuildSummary {
+    pub tenants: usize,
+    /// Snapshot rows that were wrong, missing or stale and have been corrected.
+    pub corrected: i64,
+    pub failed_tenants: Vec<Uuid>,
+}
+
+/// Adds (or, for `Voided`, subtracts) a transaction's journal entries to the snapshots of
+/// its accounts. Call in the database transaction that changes its status, after its
+/// entries are written.
+pub async fn record_transaction<'e, E: PgExecutor<'e>>(
+    executor: E,
+    transaction_id: Uuid,
+    change: SnapshotChange,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO account_balance_snapshots (tenant_id, account_id, period_month, debits, credits)
+        SELECT
+            t.tenant_id, je.account_id, date_trunc('month', t.transaction_date)::date,
+            $2::int * COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'DEBIT'), 0),
+            $2::int * COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'CREDIT'), 0)
+        FROM journal_entries je
+        JOIN transactions t ON t.id = je.transaction_id
+        WHERE t.id = $1
+        GROUP BY t.tenant_id, je.account_id, date_trunc('month', t.transaction_date)::date
+        ON CONFLICT (account_id, period_month) DO UPDATE
+        SET debits = account_balance_snapshots.debits + EXCLUDED.debits,
+            credits = account_balance_snapshots.credits + EXCLUDED.credits,
+            updated_at = NOW()
+        "#,
+        transaction_id,
+        change.sign()
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Recomputes a tenant's snapshots from its posted journal entries, returning the number of
+/// rows corrected. Rows that already match are left alone.
+pub async fn rebuild_tenant<'e, E: PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+) -> Result<i64, AppError> {
+    let corrected = sqlx::query_scalar!(
+        r#"
+        WITH actual AS (
+            SELECT
+                a.tenant_id, je.account_id, date_trunc('month', t.transaction_date)::date AS period_month,
+                COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'DEBIT'), 0) AS debits,
+                COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'CREDIT'), 0) AS credits
+            FROM journal_entries je
+            JOIN transactions t ON t.id = je.transaction_id AND t.status = 'POSTED'
+            JOIN accounts a ON a.id = je.account_id
+            WHERE a.tenant_id = $1
+            GROUP BY a.tenant_id, je.account_id, date_trunc('month', t.transaction_date)::date
+        ),
+        removed AS (
+            DELETE FROM account_balance_snapshots s
+            WHERE s.tenant_id = $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM actual
+                  WHERE actual.account_id = s.account_id AND actual.period_month = s.period_month
+              )
+            RETURNING 1
+        ),
+        upserted AS (
+            INSERT INTO account_balance_snapshots (tenant_id, account_id, period_month, debits, credits)
+            SELECT tenant_id, account_id, period_month, debits, credits FROM actual
+            ON CONFLICT (account_id, period_month) DO UPDATE
+            SET debits = EXCLUDED.debits, credits = EXCLUDED.credits, updated_at = NOW()
+            WHERE (account_balance_snapshots.debits, account_balance_snapshots.credits)
+                IS DISTINCT FROM (EXCLUDED.debits, EXCLUDED.credits)
+            RETURNING 1
+        )
+        SELECT (SELECT COUNT(*) FROM removed) + (SELECT COUNT(*) FROM upserted) AS "corrected!"
+        "#,
+        tenant_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(corrected)
+}
+
+/// Rebuilds every tenant's snapshots, one database transaction per tenant. Each runs at
+/// REPEATABLE READ: a transaction posted while the rebuild reads makes the rebuild fail
+/// with a serialization error instead of overwriting the posting's update. A failed tenant
+/// is logged and retried on the next run.
+pub async fn rebuild_all(pool: &PgPool) -> Result<RebuildSummary, AppError> {
+    let tenant_ids = sqlx::query_scalar!("SELECT id FROM tenants ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+
+    let mut summary = RebuildSummary::default();
+    for tenant_id in tenant_ids {
+        summary.tenants += 1;
+        let rebuilt = async {
+            let mut db_tx = db::begin_with_isolation(pool, IsolationLevel::RepeatableRead).await?;
+            let corrected = rebuild_tenant(&mut *db_tx, tenant_id).await?;
+            db_tx.commit().await?;
+            Ok::<_, AppError>(corrected)
+        }
+        .await;
+
+        match rebuilt {
+            Ok(0) => {}
+            Ok(corrected) => {
+                info!(
+                    "Service: Corrected {} balance snapshot rows of tenant {}",
+                    corrected, tenant_id
+                );
+                summary.corrected += corrected;
+            }
+            Err(e) => {
+                warn!(
+                    "Could not rebuild balance snapshots of tenant {}: {}",
+                    tenant_id, e
+                );
+                summary.failed_tenants.push(tenant_id);
+            }
+        }
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,344 @@
+//! Pluggable connectors for bank-feed aggregators (Plaid and similar providers).
+//!
+//! A connector only talks to the provider's API; persisting accounts and staging
+//! transactions is handled by `services::ext_conn`.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+
+use crate::{error::AppError, models::ext_provider::ExtProvider};
+
+/// Bound on each call to the provider, so a stalled provider cannot hang a sync.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An account as reported by the provider.
+#[derive(Debug, Clone)]
+pub struct ProviderAccount {
+    pub provider_account_id: String,
+    pub name: String,
+    pub mask: Option<String>,
+    pub r#type: Option<String>,
+    pub subtype: Option<String>,
+    pub currency_code: String,
+    pub current_balance: Option<Decimal>,
+    pub available_balance: Option<Decimal>,
+}
+
+/// A transaction as reported by the provider, normalized to our sign convention
+/// (negative amounts are money leaving the account).
+#[derive(Debug, Clone)]
+pub struct ProviderTransaction {
+    pub provider_account_id: String,
+    pub provider_transaction_id: String,
+    pub description: String,
+    pub amount: Decimal,
+    pub transaction_date: NaiveDate,
+    pub posted_date: Option<NaiveDate>,
+    pub raw_data: JsonValue,
+}
+
+/// One page of incremental transaction changes since `cursor`.
+#[derive(Debug, Default)]
+pub struct TransactionSyncPage {
+    pub added: Vec<ProviderTransaction>,
+    pub modified: Vec<ProviderTransaction>,
+    pub removed: Vec<String>, // provider_transaction_ids
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+/// Errors a connector distinguishes so the sync job can set the connection status.
+#[derive(Debug)]
+pub enum ConnectorError {
+    /// The stored credentials are no longer valid; the user must re-link.
+    ReauthRequired(String),
+    /// Our own API credentials (client ID / secret) were rejected.
+    InvalidCredentials(String),
+    /// Any other provider or transport failure.
+    Provider(String),
+}
+
+impl From<ConnectorError> for AppError {
+    fn from(error: ConnectorError) -> Self {
+        match error {
+            ConnectorError::ReauthRequired(msg) => {
+                AppError::Validation(format!("Provider re-authentication required: {}", msg))
+            }
+            ConnectorError::InvalidCredentials(msg) => {
+                AppError::InternalServerError(format!("Provider rejected API credentials: {}", msg))
+            }
+            ConnectorError::Provider(msg) => {
+                AppError::InternalServerError(format!("Provider error: {}", msg))
+            }
+        }
+    }
+}
+
+#[async_trait]
+pub trait BankConnector: Send + Sync {
+    /// Exchanges a short-lived link token for a long-lived access token.
+    /// Returns `(access_token, provider_item_id)`.
+    async fn exchange_public_token(
+        &self,
+        public_token: &str,
+    ) -> Result<(String, Option<String>), ConnectorError>;
+
+    /// Lists the accounts available under an access token.
+    async fn fetch_accounts(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<ProviderAccount>, ConnectorError>;
+
+    /// Fetches the next page of transaction changes after `cursor` (None = from the beginning).
+    async fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+    ) -> Result<TransactionSyncPage, ConnectorError>;
+}
+
+/// Resolves the connector implementation for a provider by its `code`.
+pub fn connector_for(provider: &ExtProvider) -> Result<Box<dyn BankConnector>, AppError> {
+    match provider.code.as_str() {
+        "PLAID" => Ok(Box::new(PlaidConnector::from_config(
+            provider.api_base_url.as_deref(),
+        )?)),
+        other => Err(AppError::Validation(format!(
+            "No bank connector is available for provider '{}'",
+            other
+        ))),
+    }
+}
+
+/// Connector for the Plaid API (`/item/public_token/exchange`, `/accounts/get`, `/transactions/sync`).
+pub struct PlaidConnector {
+    client: Client,
+    base_url: String,
+    client_id: String,
+    secret: String,
+}
+
+impl PlaidConnector {
+    /// Builds a connector from `PLAID_CLIENT_ID`, `PLAID_SECRET` and `PLAID_ENV`.
+    /// `base_url_override` (from the provider row) takes precedence over `PLAID_ENV`.
+    pub fn from_config(base_url_override: Option<&str>) -> Result<Self, AppError> {
+        let integrations = &crate::config::get().integrations;
+        let client_id = integrations.plaid_client_id.clone().ok_or_else(|| {
+            AppError::InternalServerError("PLAID_CLIENT_ID must be set".to_string())
+        })?;
+        let secret = integrations
+            .plaid_secret
+            .clone()
+            .ok_or_else(|| AppError::InternalServerError("PLAID_SECRET must be set".to_string()))?;
+
+        let base_url = match base_url_override {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => integrations.plaid_env.base_url().to_string(),
+        };
+
+        Ok(PlaidConnector {
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to build HTTP client: {}", e))
+                })?,
+            base_url,
+            client_id,
+            secret,
+        })
+    }
+
+    /// Verifies the client ID and secret with a minimal `/institutions/get` call.
+    pub async fn check_credentials(&self) -> Result<(), ConnectorError> {
+        self.post(
+            "/institutions/get",
+            json!({ "count": 1, "offset": 0, "country_codes": ["US"] }),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn post(&self, path: &str, mut body: JsonValue) -> Result<JsonValue, ConnectorError> {
+        body["client_id"] = json!(self.client_id);
+        body["secret"] = json!(self.secret);
+
+        let response = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ConnectorError::Provider(e.to_string()))?;
+
+        let status = response.status();
+        let payload: JsonValue = response
+            .json()
+            .await
+            .map_err(|e| ConnectorError::Provider(e.to_string()))?;
+
+        if status.is_success() {
+            return Ok(payload);
+        }
+
+        let error_code = payload["error_code"].as_str().unwrap_or("UNKNOWN");
+        let message = payload["error_message"]
+            .as_str()
+            .unwrap_or("Unknown Plaid error")
+            .to_string();
+        match error_code {
+            "ITEM_LOGIN_REQUIRED" | "INVALID_ACCESS_TOKEN" | "ITEM_NOT_FOUND" => {
+                Err(ConnectorError::ReauthRequired(message))
+            }
+            "INVALID_API_KEYS" => Err(ConnectorError::InvalidCredentials(message)),
+            _ => Err(ConnectorError::Provider(format!(
+                "{}: {}",
+                error_code, message
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaidBalances {
+    current: Option<Decimal>,
+    available: Option<Decimal>,
+    iso_currency_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaidAccount {
+    account_id: String,
+    name: String,
+    mask: Option<String>,
+    r#type: Option<String>,
+    subtype: Option<String>,
+    balances: PlaidBalances,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaidTransaction {
+    transaction_id: String,
+    account_id: String,
+    name: String,
+    merchant_name: Option<String>,
+    amount: Decimal, // Plaid convention: positive = money out
+    date: NaiveDate,
+    authorized_date: Option<NaiveDate>,
+    pending: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlaidRemoved {
+    transaction_id: String,
+}
+
+impl PlaidTransaction {
+    fn into_provider_transaction(self, raw_data: JsonValue) -> ProviderTransaction {
+        ProviderTransaction {
+            provider_account_id: self.account_id,
+            provider_transaction_id: self.transaction_id,
+            description: self.merchant_name.unwrap_or(self.name),
+            amount: -self.amount, // Flip to our convention: negative = money out
+            transaction_date: self.authorized_date.unwrap_or(self.date),
+            posted_date: if self.pending { None } else { Some(self.date) },
+            raw_data,
+        }
+    }
+}
+
+fn parse_plaid_transactions(
+    values: &JsonValue,
+) -> Result<Vec<ProviderTransaction>, ConnectorError> {
+    values
+        .as_array()
+        .map(|items| items.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|raw| {
+            serde_json::from_value::<PlaidTransaction>(raw.clone())
+                .map(|tx| tx.into_provider_transaction(raw.clone()))
+                .map_err(|e| ConnectorError::Provider(format!("Malformed transaction: {}", e)))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl BankConnector for PlaidConnector {
+    async fn exchange_public_token(
+        &self,
+        public_token: &str,
+    ) -> Result<(String, Option<String>), ConnectorError> {
+        let payload = self
+            .post(
+                "/item/public_token/exchange",
+                json!({ "public_token": public_token }),
+            )
+            .await?;
+
+        let access_token = payload["access_token"]
+            .as_str()
+            .ok_or_else(|| ConnectorError::Provider("Missing access_token".to_string()))?
+            .to_string();
+        let item_id = payload["item_id"].as_str().map(str::to_string);
+        Ok((access_token, item_id))
+    }
+
+    async fn fetch_accounts(
+        &self,
+        access_token: &str,
+    ) -> Result<Vec<ProviderAccount>, ConnectorError> {
+        let payload = self
+            .post("/accounts/get", json!({ "access_token": access_token }))
+            .await?;
+
+        let accounts: Vec<PlaidAccount> = serde_json::from_value(payload["accounts"].clone())
+            .map_err(|e| ConnectorError::Provider(format!("Malformed accounts: {}", e)))?;
+
+        Ok(accounts
+            .into_iter()
+            .map(|account| ProviderAccount {
+                provider_account_id: account.account_id,
+                name: account.name,
+                mask: account.mask,
+                r#type: account.r#type,
+                subtype: account.subtype,
+                currency_code: account
+                    .balances
+                    .iso_currency_code
+                    .unwrap_or_else(|| "USD".to_string()),
+                current_balance: account.balances.current,
+                available_balance: account.balances.available,
+            })
+            .collect())
+    }
+
+    async fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+    ) -> Result<TransactionSyncPage, ConnectorError> {
+        let mut body = json!({ "access_token": access_token, "count": 500 });
+        if let Some(cursor) = cursor {
+            body["cursor"] = json!(cursor);
+        }
+        let payload = self.post("/transactions/sync", body).await?;
+
+        let removed: Vec<PlaidRemoved> = serde_json::from_value(payload["removed"].clone())
+            .map_err(|e| ConnectorError::Provider(format!("Malformed removals: {}", e)))?;
+
+        Ok(TransactionSyncPage {
+            added: parse_plaid_transactions(&payload["added"])?,
+            modified: parse_plaid_transactions(&payload["modified"])?,
+            removed: removed.into_iter().map(|r| r.transaction_id).collect(),
+            next_cursor: payload["next_cursor"].as_str().map(str::to_string),
+            has_more: payload["has_more"].as_bool().unwrap_or(false),
+        })
+    }
+}
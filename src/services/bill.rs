@@ -0,0 +1,833 @@
+//! Bills: what the tenant owes its vendors, entered as drafts, approved, then paid.
+//!
+//! Approving a bill posts an EXPENSE transaction that debits each line's expense account
+//! and credits accounts payable with the total. Each payment posts a TRANSFER from the
+//! account paid from to payable, so the payable balance always equals what is still owed.
+//! Approved bills are final. The aging report reads bills and payments as of a date, so
+//! it can be run for any past month end.
+
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgExecutor, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::{begin_financial, with_retry},
+    error::AppError,
+    models::{
+        bill::{
+            AgingBuckets, Bill, BillDetail, BillLine, BillPayment, BillStatus, PayablesAging,
+            PayablesAgingTotal, PayablesAgingVendor,
+        },
+        dto::bill_dto::{
+            BillLineDto, CreateBillDto, ListBillsQuery, PayablesAgingQuery, RecordBillPaymentDto,
+            UpdateBillDto,
+        },
+        dto::journal_entry_dto::CreateJournalEntryDto,
+        dto::transaction_dto::CreateTransactionDto,
+        journal_entry::JournalEntryType,
+        transaction::{TransactionStatus, TransactionType},
+    },
+    services::{
+        domain_event::{self, DomainEvent},
+        invoice::check_accounts,
+        transaction,
+    },
+};
+
+/// Decimal places of bill amounts.
+const AMOUNT_SCALE: u32 = 2;
+
+/// Retrieves the tenant's bills matching `query`, soonest due first.
+pub async fn list_bills(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: ListBillsQuery,
+) -> Result<Vec<Bill>, AppError> {
+    info!("Service: Listing bills for tenant ID: {}", tenant_id);
+
+    let bills = query_as!(
+        Bill,
+        r#"
+        SELECT id, tenant_id, vendor_id, bill_number, status, bill_date, due_date,
+               currency_code, payable_account_id, total, amount_paid, approve_transaction_id,
+               notes, approved_at, approved_by, created_at, created_by, updated_at, updated_by
+        FROM bills
+        WHERE tenant_id = $1
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::uuid IS NULL OR vendor_id = $3)
+          AND ($4::date IS NULL OR (status IN ('OPEN', 'PARTIALLY_PAID') AND due_date <= $4))
+        ORDER BY due_date, bill_date, created_at
+        "#,
+        tenant_id,
+        query.status.map(String::from),
+        query.vendor_id,
+        query.due_before
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(bills)
+}
+
+/// Retrieves a bill with its lines and payments.
+pub async fn get_bill(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    bill_id: Uuid,
+) -> Result<BillDetail, AppError> {
+    info!(
+        "Service: Getting bill with ID: {} for tenant ID: {}",
+        bill_id, tenant_id
+    );
+
+    let bill = query_as!(
+        Bill,
+        r#"
+        SELECT id, tenant_id, vendor_id, bill_number, status, bill_date, due_date,
+               currency_code, payable_account_id, total, amount_paid, approve_transaction_id,
+               notes, approved_at, approved_by, created_at, created_by, updated_at, updated_by
+        FROM bills
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        bill_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| not_found(bill_id, tenant_id))?;
+
+    let lines = load_lines(pool, bill_id).await?;
+    let payments = query_as!(
+        BillPayment,
+        r#"
+        SELECT id, tenant_id, bill_id, payment_date, amount, payment_account_id,
+               transaction_id, payment_id, memo, created_at, created_by
+        FROM bill_payments
+        WHERE bill_id = $1
+        ORDER BY payment_date, created_at
+        "#,
+        bill_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(BillDetail {
+        bill,
+        lines,
+        payments,
+    })
+}
+
+/// Enters a draft bill with its lines. Without a due date, the vendor's payment terms
+/// apply.
+pub async fn create_bill(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateBillDto,
+) -> Result<BillDetail, AppError> {
+    info!(
+        "Service: Creating bill for vendor ID: {} for tenant ID {}",
+        dto.vendor_id, tenant_id
+    );
+
+    check_lines(&dto.lines)?;
+    let total: Decimal = dto.lines.iter().map(|line| line.amount).sum();
+
+    let mut db_tx = pool.begin().await?;
+    let payment_terms_days = vendor_terms(&mut *db_tx, tenant_id, dto.vendor_id).await?;
+    let due_date = dto
+        .due_date
+        .unwrap_or_else(|| dto.bill_date + Duration::days(payment_terms_days.unwrap_or(0).into()));
+    if due_date < dto.bill_date {
+        return Err(AppError::Validation(
+            "Due date cannot be before bill date".to_string(),
+        ));
+    }
+    check_accounts(
+        &mut *db_tx,
+        tenant_id,
+        dto.lines
+            .iter()
+            .map(|line| line.expense_account_id)
+            .chain([dto.payable_account_id]),
+    )
+    .await?;
+
+    let bill_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO bills (
+            tenant_id, vendor_id, bill_number, bill_date, due_date, currency_code,
+            payable_account_id, total, notes, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+        RETURNING id
+        "#,
+        tenant_id,
+        dto.vendor_id,
+        dto.bill_number,
+        dto.bill_date,
+        due_date,
+        dto.currency_code.to_uppercase(),
+        dto.payable_account_id,
+        total,
+        dto.notes,
+        created_by_user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    insert_lines(&mut db_tx, tenant_id, bill_id, &dto.lines).await?;
+
+    db_tx.commit().await?;
+
+    get_bill(pool, tenant_id, bill_id).await
+}
+
+/// Updates a draft bill. Lines, when given, replace the current ones.
+pub async fn update_bill(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    bill_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateBillDto,
+) -> Result<BillDetail, AppError> {
+    info!(
+        "Service: Updating bill with ID: {} for tenant ID: {}",
+        bill_id, tenant_id
+    );
+
+    if let Some(lines) = &dto.lines {
+        check_lines(lines)?;
+    }
+    let total: Option<Decimal> = dto
+        .lines
+        .as_ref()
+        .map(|lines| lines.iter().map(|line| line.amount).sum());
+
+    let mut db_tx = pool.begin().await?;
+    let current = lock_bill(&mut db_tx, tenant_id, bill_id).await?;
+    require_draft(&current)?;
+
+    if dto.bill_date.unwrap_or(current.bill_date) > dto.due_date.unwrap_or(current.due_date) {
+        return Err(AppError::Validation(
+            "Resulting due date cannot be before resulting bill date".to_string(),
+        ));
+    }
+    if let Some(vendor_id) = dto.vendor_id {
+        vendor_terms(&mut *db_tx, tenant_id, vendor_id).await?;
+    }
+    let line_accounts = dto
+        .lines
+        .iter()
+        .flatten()
+        .map(|line| line.expense_account_id);
+    check_accounts(
+        &mut *db_tx,
+        tenant_id,
+        line_accounts.chain(dto.payable_account_id),
+    )
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE bills
+        SET vendor_id = COALESCE($3, vendor_id),
+            bill_number = COALESCE($4, bill_number),
+            bill_date = COALESCE($5, bill_date),
+            due_date = COALESCE($6, due_date),
+            currency_code = COALESCE($7, currency_code),
+            payable_account_id = COALESCE($8, payable_account_id),
+            notes = COALESCE($9, notes),
+            total = COALESCE($10, total),
+            updated_at = NOW(),
+            updated_by = $11
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        bill_id,
+        tenant_id,
+        dto.vendor_id,
+        dto.bill_number,
+        dto.bill_date,
+        dto.due_date,
+        dto.currency_code.map(|code| code.to_uppercase()),
+        dto.payable_account_id,
+        dto.notes,
+        total,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    if let Some(lines) = &dto.lines {
+        sqlx::query!("DELETE FROM bill_lines WHERE bill_id = $1", bill_id)
+            .execute(&mut *db_tx)
+            .await?;
+        insert_lines(&mut db_tx, tenant_id, bill_id, lines).await?;
+    }
+
+    db_tx.commit().await?;
+
+    get_bill(pool, tenant_id, bill_id).await
+}
+
+/// Deletes a draft bill. Approved bills cannot be deleted.
+pub async fn delete_bill(pool: &PgPool, tenant_id: Uuid, bill_id: Uuid) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting bill with ID: {} for tenant ID: {}",
+        bill_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    let current = lock_bill(&mut db_tx, tenant_id, bill_id).await?;
+    require_draft(&current)?;
+
+    // Lines go with it (ON DELETE CASCADE)
+    sqlx::query!(
+        "DELETE FROM bills WHERE id = $1 AND tenant_id = $2",
+        bill_id,
+        tenant_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(())
+}
+
+/// Approves a draft bill and posts the expenses against accounts payable.
+pub async fn approve_bill(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    bill_id: Uuid,
+    user_id: Uuid,
+) -> Result<BillDetail, AppError> {
+    info!(
+        "Service: Approving bill with ID: {} for tenant ID: {}",
+        bill_id, tenant_id
+    );
+
+    let bill = with_retry(pool, |pool| {
+        approve_bill_once(pool, tenant_id, bill_id, user_id)
+    })
+    .await?;
+
+    if let Some(transaction_id) = bill.approve_transaction_id {
+        domain_event::publish(DomainEvent::TransactionPosted {
+            tenant_id,
+            user_id,
+            transaction_id,
+            amount: bill.total,
+            transaction_date: bill.bill_date,
+        });
+    }
+    get_bill(pool, tenant_id, bill_id).await
+}
+
+/// One attempt at approving a bill, in its own database transaction.
+async fn approve_bill_once(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    bill_id: Uuid,
+    user_id: Uuid,
+) -> Result<Bill, AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let bill = lock_bill(&mut db_tx, tenant_id, bill_id).await?;
+    require_draft(&bill)?;
+    if bill.total.is_zero() {
+        return Err(AppError::Validation(
+            "A bill with a zero total cannot be approved".to_string(),
+        ));
+    }
+    let lines = load_lines(&mut *db_tx, bill_id).await?;
+    let vendor_name = sqlx::query_scalar!("SELECT name FROM vendors WHERE id = $1", bill.vendor_id)
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+    // One debit per expense account, in the order the lines first use them
+    let mut debits: Vec<(Uuid, Decimal)> = Vec::new();
+    for line in &lines {
+        match debits
+            .iter_mut()
+            .find(|(account_id, _)| *account_id == line.expense_account_id)
+        {
+            Some((_, amount)) => *amount += line.amount,
+            None => debits.push((line.expense_account_id, line.amount)),
+        }
+    }
+    let description = match &bill.bill_number {
+        Some(number) => format!("Bill {} from {}", number, vendor_name),
+        None => format!("Bill from {}", vendor_name),
+    };
+    let journal_entries = debits
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(account_id, amount)| (account_id, JournalEntryType::Debit, amount))
+        .chain([(
+            bill.payable_account_id,
+            JournalEntryType::Credit,
+            bill.total,
+        )])
+        .map(|(account_id, entry_type, amount)| CreateJournalEntryDto {
+            account_id,
+            entry_type,
+            amount,
+            currency_code: bill.currency_code.clone(),
+            exchange_rate: None,
+            converted_amount: None,
+            memo: Some(description.clone()),
+            project_id: None,
+            class_id: None,
+            location_id: None,
+        })
+        .collect();
+
+    let (posted, _) = transaction::insert_transaction(
+        &mut db_tx,
+        tenant_id,
+        user_id,
+        posting(
+            &bill,
+            bill.bill_date,
+            description,
+            TransactionType::Expense,
+            bill.total,
+            journal_entries,
+        ),
+    )
+    .await?;
+
+    let approved = query_as!(
+        Bill,
+        r#"
+        UPDATE bills
+        SET status = 'OPEN', approve_transaction_id = $3,
+            approved_at = NOW(), approved_by = $4, updated_at = NOW(), updated_by = $4
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING id, tenant_id, vendor_id, bill_number, status, bill_date, due_date,
+                  currency_code, payable_account_id, total, amount_paid, approve_transaction_id,
+                  notes, approved_at, approved_by, created_at, created_by, updated_at, updated_by
+        "#,
+        bill_id,
+        tenant_id,
+        posted.id,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(approved)
+}
+
+/// Records a payment against an approved bill and posts it from the account paid from to
+/// accounts payable. The bill is PAID once nothing is owed.
+pub async fn record_payment(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    bill_id: Uuid,
+    user_id: Uuid,
+    dto: RecordBillPaymentDto,
+) -> Result<BillDetail, AppError> {
+    info!(
+        "Service: Recording payment of {} against bill ID: {} for tenant ID: {}",
+        dto.amount, bill_id, tenant_id
+    );
+
+    if dto.amount <= Decimal::ZERO {
+        return Err(AppError::Validation(
+            "amount must be greater than zero".to_string(),
+        ));
+    }
+    if dto.amount.round_dp(AMOUNT_SCALE) != dto.amount {
+        return Err(AppError::Validation(
+            "amount must have at most two decimals".to_string(),
+        ));
+    }
+
+    let payment = with_retry(pool, |pool| {
+        record_payment_once(pool, tenant_id, bill_id, user_id, &dto)
+    })
+    .await?;
+
+    domain_event::publish(DomainEvent::TransactionPosted {
+        tenant_id,
+        user_id,
+        transaction_id: payment.transaction_id,
+        amount: payment.amount,
+        transaction_date: payment.payment_date,
+    });
+    get_bill(pool, tenant_id, bill_id).await
+}
+
+/// One attempt at recording a payment, in its own database transaction.
+async fn record_payment_once(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    bill_id: Uuid,
+    user_id: Uuid,
+    dto: &RecordBillPaymentDto,
+) -> Result<BillPayment, AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let bill = lock_bill(&mut db_tx, tenant_id, bill_id).await?;
+    let status: BillStatus = bill.status.parse().map_err(AppError::InternalServerError)?;
+    if !matches!(status, BillStatus::Open | BillStatus::PartiallyPaid) {
+        return Err(AppError::Validation(format!(
+            "Payments can only be recorded against approved bills; bill {} is {}",
+            bill_id, bill.status
+        )));
+    }
+    let outstanding = bill.total - bill.amount_paid;
+    if dto.amount > outstanding {
+        return Err(AppError::Validation(format!(
+            "The payment of {} is more than the {} still owed",
+            dto.amount, outstanding
+        )));
+    }
+    if dto.payment_date < bill.bill_date {
+        return Err(AppError::Validation(
+            "Payment date cannot be before the bill date".to_string(),
+        ));
+    }
+    if dto.payment_account_id == bill.payable_account_id {
+        return Err(AppError::Validation(
+            "The payment account cannot be the payable account".to_string(),
+        ));
+    }
+
+    let memo = match &bill.bill_number {
+        Some(number) => format!("Payment for bill {}", number),
+        None => "Payment for bill".to_string(),
+    };
+    let journal_entries = [
+        (bill.payable_account_id, JournalEntryType::Debit),
+        (dto.payment_account_id, JournalEntryType::Credit),
+    ]
+    .into_iter()
+    .map(|(account_id, entry_type)| CreateJournalEntryDto {
+        account_id,
+        entry_type,
+        amount: dto.amount,
+        currency_code: bill.currency_code.clone(),
+        exchange_rate: None,
+        converted_amount: None,
+        memo: dto.memo.clone().or_else(|| Some(memo.clone())),
+        project_id: None,
+        class_id: None,
+        location_id: None,
+    })
+    .collect();
+
+    let (posted, _) = transaction::insert_transaction(
+        &mut db_tx,
+        tenant_id,
+        user_id,
+        posting(
+            &bill,
+            dto.payment_date,
+            memo,
+            TransactionType::Transfer,
+            dto.amount,
+            journal_entries,
+        ),
+    )
+    .await?;
+
+    let payment = query_as!(
+        BillPayment,
+        r#"
+        INSERT INTO bill_payments (
+            tenant_id, bill_id, payment_date, amount, payment_account_id, transaction_id, memo, created_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, tenant_id, bill_id, payment_date, amount, payment_account_id,
+                  transaction_id, payment_id, memo, created_at, created_by
+        "#,
+        tenant_id,
+        bill_id,
+        dto.payment_date,
+        dto.amount,
+        dto.payment_account_id,
+        posted.id,
+        dto.memo,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    let status = if dto.amount == outstanding {
+        BillStatus::Paid
+    } else {
+        BillStatus::PartiallyPaid
+    };
+    sqlx::query!(
+        r#"
+        UPDATE bills
+        SET amount_paid = amount_paid + $3, status = $4, updated_at = NOW(), updated_by = $5
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        bill_id,
+        tenant_id,
+        dto.amount,
+        String::from(status),
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(payment)
+}
+
+/// What the tenant owed its vendors at the end of `as_of`, by vendor and currency, split by
+/// days past due: not yet due, 1-30, 31-60, 61-90 and over 90. Only payments made by then
+/// count.
+pub async fn payables_aging(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: PayablesAgingQuery,
+) -> Result<PayablesAging, AppError> {
+    let as_of = query.as_of.unwrap_or_else(|| Utc::now().date_naive());
+    info!(
+        "Service: Payables aging as of {} for tenant ID: {}",
+        as_of, tenant_id
+    );
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT b.vendor_id, v.name as vendor_name, b.currency_code, b.due_date,
+               b.total - COALESCE((
+                   SELECT SUM(p.amount) FROM bill_payments p
+                   WHERE p.bill_id = b.id AND p.payment_date <= $2
+               ), 0) as "outstanding!"
+        FROM bills b
+        JOIN vendors v ON v.id = b.vendor_id
+        WHERE b.tenant_id = $1 AND b.status <> 'DRAFT' AND b.bill_date <= $2
+        ORDER BY v.name, b.vendor_id, b.currency_code
+        "#,
+        tenant_id,
+        as_of
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut vendors: Vec<PayablesAgingVendor> = Vec::new();
+    let mut totals: BTreeMap<String, AgingBuckets> = BTreeMap::new();
+    for row in rows
+        .into_iter()
+        .filter(|row| row.outstanding > Decimal::ZERO)
+    {
+        let days_past_due = as_of.signed_duration_since(row.due_date).num_days();
+        let is_same_group = vendors.last().is_some_and(|last| {
+            last.vendor_id == row.vendor_id && last.currency_code == row.currency_code
+        });
+        if !is_same_group {
+            vendors.push(PayablesAgingVendor {
+                vendor_id: row.vendor_id,
+                vendor_name: row.vendor_name,
+                currency_code: row.currency_code.clone(),
+                bill_count: 0,
+                buckets: AgingBuckets::default(),
+            });
+        }
+        let vendor = vendors.last_mut().expect("a group was just pushed");
+        vendor.bill_count += 1;
+        add_to_bucket(&mut vendor.buckets, days_past_due, row.outstanding);
+        add_to_bucket(
+            totals.entry(row.currency_code).or_default(),
+            days_past_due,
+            row.outstanding,
+        );
+    }
+
+    Ok(PayablesAging {
+        as_of,
+        vendors,
+        totals: totals
+            .into_iter()
+            .map(|(currency_code, buckets)| PayablesAgingTotal {
+                currency_code,
+                buckets,
+            })
+            .collect(),
+    })
+}
+
+fn add_to_bucket(buckets: &mut AgingBuckets, days_past_due: i64, amount: Decimal) {
+    let bucket = match days_past_due {
+        i64::MIN..=0 => &mut buckets.current,
+        1..=30 => &mut buckets.days_1_30,
+        31..=60 => &mut buckets.days_31_60,
+        61..=90 => &mut buckets.days_61_90,
+        _ => &mut buckets.over_90,
+    };
+    *bucket += amount;
+    buckets.total += amount;
+}
+
+/// The transaction a bill posts, in the bill's currency.
+fn posting(
+    bill: &Bill,
+    transaction_date: NaiveDate,
+    description: String,
+    r#type: TransactionType,
+    amount: Decimal,
+    journal_entries: Vec<CreateJournalEntryDto>,
+) -> CreateTransactionDto {
+    CreateTransactionDto {
+        transaction_date,
+        description,
+        r#type,
+        category_id: None,
+        payee_id: None,
+        tags: None,
+        amount,
+        currency_code: bill.currency_code.clone(),
+        is_reconciled: None,
+        reconciliation_date: None,
+        notes: None,
+        source_document_url: None,
+        status: Some(TransactionStatus::Posted),
+        journal_entries,
+        payment_account_id: None,
+        splits: Vec::new(),
+        metadata: None,
+        attribution: None,
+    }
+}
+
+fn check_lines(lines: &[BillLineDto]) -> Result<(), AppError> {
+    for (index, line) in lines.iter().enumerate() {
+        if line.amount < Decimal::ZERO {
+            return Err(AppError::Validation(format!(
+                "lines[{}].amount cannot be negative",
+                index
+            )));
+        }
+        if line.amount.round_dp(AMOUNT_SCALE) != line.amount {
+            return Err(AppError::Validation(format!(
+                "lines[{}].amount must have at most two decimals",
+                index
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Writes the lines of a bill, numbered in the order given.
+async fn insert_lines(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    bill_id: Uuid,
+    lines: &[BillLineDto],
+) -> Result<(), AppError> {
+    let positions: Vec<i32> = (1..=lines.len() as i32).collect();
+    let descriptions: Vec<String> = lines.iter().map(|line| line.description.clone()).collect();
+    let amounts: Vec<Decimal> = lines.iter().map(|line| line.amount).collect();
+    let expense_account_ids: Vec<Uuid> = lines.iter().map(|line| line.expense_account_id).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO bill_lines (bill_id, tenant_id, position, description, amount, expense_account_id)
+        SELECT $1, $2, l.position, l.description, l.amount, l.expense_account_id
+        FROM UNNEST($3::int4[], $4::text[], $5::numeric[], $6::uuid[])
+            AS l(position, description, amount, expense_account_id)
+        "#,
+        bill_id,
+        tenant_id,
+        &positions,
+        &descriptions,
+        &amounts,
+        &expense_account_ids
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    Ok(())
+}
+
+async fn load_lines<'e, E: PgExecutor<'e>>(
+    executor: E,
+    bill_id: Uuid,
+) -> Result<Vec<BillLine>, AppError> {
+    let lines = query_as!(
+        BillLine,
+        r#"
+        SELECT id, bill_id, tenant_id, position, description, amount, expense_account_id
+        FROM bill_lines
+        WHERE bill_id = $1
+        ORDER BY position
+        "#,
+        bill_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(lines)
+}
+
+/// Reads a bill and locks it until the end of the database transaction.
+async fn lock_bill(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    bill_id: Uuid,
+) -> Result<Bill, AppError> {
+    query_as!(
+        Bill,
+        r#"
+        SELECT id, tenant_id, vendor_id, bill_number, status, bill_date, due_date,
+               currency_code, payable_account_id, total, amount_paid, approve_transaction_id,
+               notes, approved_at, approved_by, created_at, created_by, updated_at, updated_by
+        FROM bills
+        WHERE id = $1 AND tenant_id = $2
+        FOR UPDATE
+        "#,
+        bill_id,
+        tenant_id
+    )
+    .fetch_optional(&mut **db_tx)
+    .await?
+    .ok_or_else(|| not_found(bill_id, tenant_id))
+}
+
+fn require_draft(bill: &Bill) -> Result<(), AppError> {
+    if bill.status != String::from(BillStatus::Draft) {
+        return Err(AppError::Validation(format!(
+            "Bill {} is {} and can no longer be changed",
+            bill.id, bill.status
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that the vendor is the tenant's and active, and returns its payment terms.
+async fn vendor_terms<'e, E: PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    vendor_id: Uuid,
+) -> Result<Option<i32>, AppError> {
+    sqlx::query_scalar!(
+        "SELECT payment_terms_days FROM vendors WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE",
+        vendor_id,
+        tenant_id
+    )
+    .fetch_optional(executor)
+    .await?
+    .ok_or_else(|| {
+        AppError::Validation(format!(
+            "Vendor ID {} is invalid or inactive for tenant {}",
+            vendor_id, tenant_id
+        ))
+    })
+}
+
+fn not_found(bill_id: Uuid, tenant_id: Uuid) -> AppError {
+    AppError::NotFound(format!(
+        "Bill with ID {} not found for tenant {}",
+        bill_id, tenant_id
+    ))
+}
@@ -5,28 +5,42 @@ use chrono::{NaiveDate, Utc};
 use rust_decimal::Decimal;
 
 use crate::{
+    db,
     error::AppError,
     models::{
         budget::Budget,
         dto::budget_dto::{CreateBudgetDto, UpdateBudgetDto},
     },
+    services::permission::{self, RECORDS_RESTORE},
+    utils::update_builder::UpdateBuilder,
 };
 
-/// Retrieves a list of budgets for a specific tenant.
-pub async fn list_budgets(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<Budget>, AppError> {
+/// Retrieves a list of budgets for a specific tenant, without deactivated ones unless asked
+/// (which needs `records.restore`).
+pub async fn list_budgets(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    include_inactive: bool,
+) -> Result<Vec<Budget>, AppError> {
     info!("Service: Listing budgets for tenant ID: {}", tenant_id);
 
+    if include_inactive {
+        permission::require_permission(pool, tenant_id, user_id, RECORDS_RESTORE).await?;
+    }
+
     let budgets = query_as!(
         Budget,
         r#"
         SELECT
-            id, tenant_id, name, start_date, end_date, currency_code,
-            is_active, created_at, created_by, updated_at, updated_by
+            id, tenant_id, name, start_date, end_date, currency_code, is_envelope,
+            is_active, created_at, created_by, updated_at, updated_by, version
         FROM budgets
-        WHERE tenant_id = $1 AND is_active = TRUE
+        WHERE tenant_id = $1 AND (is_active = TRUE OR $2)
         ORDER BY start_date DESC, name
         "#,
-        tenant_id
+        tenant_id,
+        include_inactive
     )
     .fetch_all(pool)
     .await?;
@@ -46,8 +60,8 @@ pub async fn get_budget_by_id(
         Budget,
         r#"
         SELECT
-            id, tenant_id, name, start_date, end_date, currency_code,
-            is_active, created_at, created_by, updated_at, updated_by
+            id, tenant_id, name, start_date, end_date, currency_code, is_envelope,
+            is_active, created_at, created_by, updated_at, updated_by, version
         FROM budgets
         WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
         "#,
@@ -72,27 +86,28 @@ pub async fn create_budget(
 
     // Basic validation: Ensure end_date is not before start_date
     if dto.end_date < dto.start_date {
-        return Err(AppError::BadRequest("End date cannot be before start date".to_string()));
+        return Err(AppError::Validation("End date cannot be before start date".to_string()));
     }
 
     let new_budget = query_as!(
         Budget,
         r#"
         INSERT INTO budgets (
-            tenant_id, name, start_date, end_date, currency_code,
+            tenant_id, name, start_date, end_date, currency_code, is_envelope,
             is_active, created_by, updated_by
         )
-        VALUES ($1, $2, $3, $4, $5, TRUE, $6, $6)
+        VALUES ($1, $2, $3, $4, $5, $7, TRUE, $6, $6)
         RETURNING
-            id, tenant_id, name, start_date, end_date, currency_code,
-            is_active, created_at, created_by, updated_at, updated_by
+            id, tenant_id, name, start_date, end_date, currency_code, is_envelope,
+            is_active, created_at, created_by, updated_at, updated_by, version
         "#,
         tenant_id,
         dto.name,
         dto.start_date,
         dto.end_date,
         dto.currency_code,
-        created_by_user_id
+        created_by_user_id,
+        dto.is_envelope.unwrap_or(false)
     )
     .fetch_one(pool)
     .await?;
@@ -100,60 +115,22 @@ pub async fn create_budget(
     Ok(new_budget)
 }
 
-/// Updates an existing budget for a specific tenant.
+/// Updates an existing budget for a specific tenant, provided it is still at
+/// `expected_version` (the version the client read).
 pub async fn update_budget(
     pool: &PgPool,
     tenant_id: Uuid,
     budget_id: Uuid,
     updated_by_user_id: Uuid,
+    expected_version: i32,
     dto: UpdateBudgetDto,
 ) -> Result<Budget, AppError> {
     info!("Service: Updating budget with ID: {} for tenant ID: {}", budget_id, tenant_id);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
-
-    if let Some(name) = dto.name {
-        update_cols.push(format!("name = ${}", param_idx));
-        update_values.push(Box::new(name));
-        param_idx += 1;
-    }
-    if let Some(start_date) = dto.start_date {
-        update_cols.push(format!("start_date = ${}", param_idx));
-        update_values.push(Box::new(start_date));
-        param_idx += 1;
-    }
-    if let Some(end_date) = dto.end_date {
-        update_cols.push(format!("end_date = ${}", param_idx));
-        update_values.push(Box::new(end_date));
-        param_idx += 1;
-    }
-    if let Some(currency_code) = dto.currency_code {
-        update_cols.push(format!("currency_code = ${}", param_idx));
-        update_values.push(Box::new(currency_code));
-        param_idx += 1;
-    }
-    if let Some(is_active) = dto.is_active {
-        update_cols.push(format!("is_active = ${}", param_idx));
-        update_values.push(Box::new(is_active));
-        param_idx += 1;
-    }
-
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
-
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
-    }
-
     // Check for date consistency if both are provided or updated
     if let (Some(start), Some(end)) = (dto.start_date, dto.end_date) {
         if end < start {
-            return Err(AppError::BadRequest("Updated end date cannot be before updated start date".to_string()));
+            return Err(AppError::Validation("Updated end date cannot be before updated start date".to_string()));
         }
     } else if dto.start_date.is_some() || dto.end_date.is_some() {
         // If only one date is updated, fetch current values to validate
@@ -161,39 +138,40 @@ pub async fn update_budget(
         let effective_start_date = dto.start_date.unwrap_or(current_budget.start_date);
         let effective_end_date = dto.end_date.unwrap_or(current_budget.end_date);
         if effective_end_date < effective_start_date {
-            return Err(AppError::BadRequest("Resulting end date cannot be before resulting start date".to_string()));
+            return Err(AppError::Validation("Resulting end date cannot be before resulting start date".to_string()));
         }
     }
 
+    let mut update = UpdateBuilder::new("budgets");
+    update
+        .set("name", dto.name)
+        .set("start_date", dto.start_date)
+        .set("end_date", dto.end_date)
+        .set("currency_code", dto.currency_code)
+        .set("is_envelope", dto.is_envelope)
+        .set("is_active", dto.is_active);
+    if update.is_empty() {
+        return Err(AppError::Validation("No fields provided for update".to_string()));
+    }
+    update.bump_version();
 
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(budget_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(" AND version = ").push_bind(expected_version);
+    query.push(
         r#"
-        UPDATE budgets
-        SET {}
-        WHERE id = ${} AND tenant_id = ${}
         RETURNING
-            id, tenant_id, name, start_date, end_date, currency_code,
-            is_active, created_at, created_by, updated_at, updated_by
+            id, tenant_id, name, start_date, end_date, currency_code, is_envelope,
+            is_active, created_at, created_by, updated_at, updated_by, version
         "#,
-        update_clause, param_idx, param_idx + 1 // budget_id and tenant_id will be the last parameters
     );
 
-    let mut query = sqlx::query_as::<_, Budget>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
+    let updated_budget = query.build_query_as::<Budget>().fetch_optional(pool).await?;
+    match updated_budget {
+        Some(budget) => Ok(budget),
+        None => Err(db::version_mismatch(pool, "budgets", "Budget", budget_id, tenant_id, expected_version).await),
     }
-    // Bind budget_id and tenant_id last
-    query = query.bind(budget_id);
-    query = query.bind(tenant_id);
-
-    let updated_budget = query
-        .fetch_optional(pool)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Budget with ID {} not found or not owned by tenant {}", budget_id, tenant_id)))?;
-
-    Ok(updated_budget)
 }
 
 /// Deactivates a budget (soft delete) for a specific tenant.
@@ -212,7 +190,8 @@ pub async fn deactivate_budget(
         SET
             is_active = FALSE,
             updated_at = NOW(),
-            updated_by = $3
+            updated_by = $3,
+            version = version + 1
         WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
         "#,
         budget_id,
@@ -228,4 +207,40 @@ pub async fn deactivate_budget(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Restores a deactivated budget; its line items come back with it. Requires `records.restore`.
+pub async fn restore_budget(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<Budget, AppError> {
+    info!("Service: Restoring budget with ID: {} for tenant ID: {}", budget_id, tenant_id);
+
+    permission::require_permission(pool, tenant_id, updated_by_user_id, RECORDS_RESTORE).await?;
+
+    let budget = query_as!(
+        Budget,
+        r#"
+        UPDATE budgets
+        SET
+            is_active = TRUE,
+            updated_at = NOW(),
+            updated_by = $3,
+            version = version + 1
+        WHERE id = $1 AND tenant_id = $2 AND is_active = FALSE
+        RETURNING
+            id, tenant_id, name, start_date, end_date, currency_code, is_envelope,
+            is_active, created_at, created_by, updated_at, updated_by, version
+        "#,
+        budget_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Deactivated budget with ID {} not found for tenant {}", budget_id, tenant_id)))?;
+
+    Ok(budget)
+}
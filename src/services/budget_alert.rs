@@ -0,0 +1,214 @@
+//! Budget alerts: notifications when a line item's spending crosses one of its thresholds.
+//!
+//! Thresholds are percentages of the line's budgeted amount for the whole period (e.g. 80
+//! and 100). The budget alert job compares the spending so far in every running budget
+//! with them. Each crossed threshold is recorded in `budget_alerts`, so it never fires
+//! again, even when spending drops back under it and rises once more. When several
+//! thresholds are crossed at once, the budget's owner gets one notification for the highest.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{budget::Budget, budget_line_item::BudgetAlert, notification::NotificationPriority},
+    services::{budget, budget_performance, notification},
+};
+
+/// Lists the alerts raised for a budget, newest first.
+pub async fn list_budget_alerts(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+) -> Result<Vec<BudgetAlert>, AppError> {
+    info!(
+        "Service: Listing alerts for budget ID: {} tenant ID: {}",
+        budget_id, tenant_id
+    );
+
+    budget::get_budget_by_id(pool, tenant_id, budget_id).await?;
+    let alerts = query_as!(
+        BudgetAlert,
+        r#"
+        SELECT id, tenant_id, budget_id, budget_line_item_id, threshold_percent, budgeted_amount,
+               actual_amount, notified_user_id, created_at
+        FROM budget_alerts
+        WHERE budget_id = $1 AND tenant_id = $2
+        ORDER BY created_at DESC, threshold_percent DESC
+        "#,
+        budget_id,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(alerts)
+}
+
+/// Checks every budget running on `today` that has alert thresholds, and returns the
+/// number of thresholds newly crossed. A budget that fails is logged and skipped.
+pub async fn check_budget_alerts(pool: &PgPool, today: NaiveDate) -> Result<usize, AppError> {
+    let budgets = query_as!(
+        Budget,
+        r#"
+        SELECT b.id, b.tenant_id, b.name, b.start_date, b.end_date, b.currency_code, b.is_envelope,
+               b.is_active, b.created_at, b.created_by, b.updated_at, b.updated_by, b.version
+        FROM budgets b
+        WHERE b.is_active = TRUE AND b.start_date <= $1 AND b.end_date >= $1
+          AND EXISTS(
+              SELECT 1 FROM budget_line_items bli
+              WHERE bli.budget_id = b.id AND bli.is_active = TRUE
+                AND cardinality(bli.alert_thresholds) > 0
+          )
+        "#,
+        today
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut crossed = 0;
+    for budget in &budgets {
+        match check_budget(pool, budget, today).await {
+            Ok(count) => crossed += count,
+            Err(e) => warn!("Budget alert check for budget {} failed: {}", budget.id, e),
+        }
+    }
+    if crossed > 0 {
+        info!(
+            "Budget alerts: {} thresholds crossed across {} budgets",
+            crossed,
+            budgets.len()
+        );
+    }
+    Ok(crossed)
+}
+
+/// Records the thresholds newly crossed by the budget's lines and notifies its owner.
+async fn check_budget(pool: &PgPool, budget: &Budget, today: NaiveDate) -> Result<usize, AppError> {
+    let lines = sqlx::query!(
+        r#"
+        SELECT bli.id, bli.budgeted_amount, bli.alert_thresholds,
+               ARRAY(
+                   SELECT ba.threshold_percent FROM budget_alerts ba
+                   WHERE ba.budget_line_item_id = bli.id
+               ) as "alerted!",
+               c.name as "category_name?", a.account_code as "account_code?", a.name as "account_name?"
+        FROM budget_line_items bli
+        LEFT JOIN categories c ON bli.category_id = c.id
+        LEFT JOIN accounts a ON bli.account_id = a.id
+        WHERE bli.budget_id = $1 AND bli.is_active = TRUE AND cardinality(bli.alert_thresholds) > 0
+        "#,
+        budget.id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut actuals: HashMap<Uuid, Decimal> = HashMap::new();
+    let monthly = budget_performance::line_actuals(
+        pool,
+        budget.tenant_id,
+        budget.id,
+        budget.start_date,
+        today,
+    )
+    .await?;
+    for ((line_item_id, _), actual) in monthly {
+        *actuals.entry(line_item_id).or_default() += actual;
+    }
+
+    let owner_active = sqlx::query_scalar!(
+        "SELECT is_active FROM users WHERE id = $1",
+        budget.created_by
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or(false);
+    let notified_user_id = owner_active.then_some(budget.created_by);
+
+    let mut crossed = 0;
+    for line in lines {
+        if line.budgeted_amount <= Decimal::ZERO {
+            continue;
+        }
+        let actual = actuals.get(&line.id).copied().unwrap_or(Decimal::ZERO);
+        let percent_spent = actual / line.budgeted_amount * Decimal::ONE_HUNDRED;
+        let newly_crossed: Vec<Decimal> = line
+            .alert_thresholds
+            .iter()
+            .filter(|threshold| **threshold <= percent_spent && !line.alerted.contains(threshold))
+            .copied()
+            .collect();
+        if newly_crossed.is_empty() {
+            continue;
+        }
+
+        // Only thresholds this run recorded are notified, so concurrent runs cannot both fire
+        let recorded = sqlx::query_scalar!(
+            r#"
+            INSERT INTO budget_alerts (
+                tenant_id, budget_id, budget_line_item_id, threshold_percent, budgeted_amount,
+                actual_amount, notified_user_id
+            )
+            SELECT $1, $2, $3, threshold, $5, $6, $7 FROM UNNEST($4::numeric[]) as threshold
+            ON CONFLICT (budget_line_item_id, threshold_percent) DO NOTHING
+            RETURNING threshold_percent
+            "#,
+            budget.tenant_id,
+            budget.id,
+            line.id,
+            &newly_crossed,
+            line.budgeted_amount,
+            actual,
+            notified_user_id
+        )
+        .fetch_all(pool)
+        .await?;
+        crossed += recorded.len();
+
+        let (Some(highest), Some(user_id)) = (recorded.into_iter().max(), notified_user_id) else {
+            continue;
+        };
+        let label = budget_performance::line_label(
+            line.category_name,
+            line.account_code,
+            line.account_name,
+        );
+        let subject = format!(
+            "Budget '{}': {} reached {}% of its budget",
+            budget.name, label, highest
+        );
+        let body = format!(
+            "{} has spent {} {} of {} {} ({}%) in budget '{}' ({} to {}).",
+            label,
+            actual,
+            budget.currency_code,
+            line.budgeted_amount,
+            budget.currency_code,
+            percent_spent.round_dp(0),
+            budget.name,
+            budget.start_date,
+            budget.end_date
+        );
+        if let Err(e) = notification::notify(
+            pool,
+            user_id,
+            Some(budget.tenant_id),
+            NotificationPriority::Normal,
+            &subject,
+            &body,
+        )
+        .await
+        {
+            warn!(
+                "Budget alert notification for line {} failed: {}",
+                line.id, e
+            );
+        }
+    }
+    Ok(crossed)
+}
@@ -0,0 +1,575 @@
+//! Budget import/export in a spreadsheet-friendly CSV format.
+//!
+//! One row per line item; the budget's own fields are repeated on every row so an
+//! exported file can be edited and imported again as a new budget:
+//!
+//! `budget_name,start_date,end_date,currency_code,category,account,budgeted_amount`
+//!
+//! `category` is a category name and `account` an account code or name; either may be
+//! blank, but not both. Delimiter, decimal separator and date format follow the request's
+//! `CsvFormat`; a file is imported with the same options it was exported with.
+//!
+//! Large files can be imported as a background job (`start_import_job`) that reports its
+//! progress and, if rows fail, keeps an error file of just those rows with `error_field` and
+//! `error_message` columns. The reason columns are ignored when a fixed file is imported.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::AppError,
+    models::{
+        budget::Budget,
+        dto::budget_dto::{BudgetImportResult, CsvRowError},
+        import_job::ImportJob,
+    },
+    services::{
+        budget,
+        import_job::{self, ImportOutcome, ImportProgress},
+    },
+    utils::csv_format::{CsvCell, CsvFormat},
+};
+
+const CSV_HEADER: [&str; 7] = [
+    "budget_name",
+    "start_date",
+    "end_date",
+    "currency_code",
+    "category",
+    "account",
+    "budgeted_amount",
+];
+
+/// A CSV row as read from a file.
+#[derive(Debug, Deserialize)]
+struct BudgetCsvRow {
+    budget_name: String,
+    start_date: String,
+    end_date: String,
+    currency_code: String,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    account: String,
+    budgeted_amount: String,
+}
+
+/// A row that passed validation and is ready to insert.
+struct ResolvedLine {
+    category_id: Option<Uuid>,
+    account_id: Option<Uuid>,
+    budgeted_amount: Decimal,
+}
+
+/// Budget-level fields, taken from the first row and required to match on every row.
+#[derive(PartialEq)]
+struct BudgetHeader {
+    name: String,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    currency_code: String,
+}
+
+/// Exports a budget and its active line items. Returns `(file_name, csv_text)`.
+pub async fn export_budget_csv(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+    format: &CsvFormat,
+) -> Result<(String, String), AppError> {
+    info!(
+        "Service: Exporting budget ID: {} as CSV for tenant ID: {}",
+        budget_id, tenant_id
+    );
+
+    let budget = budget::get_budget_by_id(pool, tenant_id, budget_id).await?;
+
+    let lines = sqlx::query!(
+        r#"
+        SELECT c.name as "category_name?", a.account_code as "account_code?", a.name as "account_name?",
+               bli.budgeted_amount
+        FROM budget_line_items bli
+        LEFT JOIN categories c ON bli.category_id = c.id
+        LEFT JOIN accounts a ON bli.account_id = a.id
+        WHERE bli.budget_id = $1 AND bli.is_active = TRUE
+        ORDER BY c.name NULLS LAST, a.account_code NULLS LAST, a.name
+        "#,
+        budget_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // The header is written even without lines so the file can be used as a template
+    let mut writer = format.writer();
+    writer.write_header(&CSV_HEADER)?;
+    for line in lines {
+        writer.write_row([
+            CsvCell::from(budget.name.clone()),
+            CsvCell::from(budget.start_date),
+            CsvCell::from(budget.end_date),
+            CsvCell::from(budget.currency_code.clone()),
+            CsvCell::from(line.category_name),
+            // Prefer the code: it is stable across renames
+            CsvCell::from(line.account_code.or(line.account_name)),
+            CsvCell::from(line.budgeted_amount),
+        ])?;
+    }
+    let csv_text = writer.finish()?;
+
+    let file_name = format!("budget-{}.csv", slugify(&budget.name));
+    Ok((file_name, csv_text))
+}
+
+/// Imports a new budget from CSV. Every row is validated first; if any row has errors,
+/// nothing is written and the errors are returned with their row numbers.
+/// When run as a job, validated rows are reported to `progress`.
+pub async fn import_budget_csv(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    csv_text: &str,
+    name_override: Option<String>,
+    format: &CsvFormat,
+    progress: Option<&ImportProgress<'_>>,
+) -> Result<BudgetImportResult, AppError> {
+    info!(
+        "Service: Importing budget from CSV for tenant ID: {}",
+        tenant_id
+    );
+
+    let categories = name_lookup(
+        sqlx::query!(
+            "SELECT id, name FROM categories WHERE tenant_id = $1 AND is_active = TRUE",
+            tenant_id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|c| (c.name, c.id)),
+    );
+    let accounts = sqlx::query!(
+        "SELECT id, name, account_code FROM accounts WHERE tenant_id = $1 AND is_active = TRUE",
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+    let accounts_by_code = name_lookup(
+        accounts
+            .iter()
+            .filter_map(|a| a.account_code.clone().map(|code| (code, a.id))),
+    );
+    let accounts_by_name = name_lookup(accounts.iter().map(|a| (a.name.clone(), a.id)));
+
+    let mut reader = format.reader(csv_text);
+
+    let mut errors: Vec<CsvRowError> = Vec::new();
+    let mut header: Option<BudgetHeader> = None;
+    let mut lines: Vec<ResolvedLine> = Vec::new();
+    let mut seen_targets: HashSet<(Option<Uuid>, Option<Uuid>)> = HashSet::new();
+
+    for (index, record) in reader.deserialize::<BudgetCsvRow>().enumerate() {
+        if let Some(progress) = progress {
+            progress.rows_processed(index).await?;
+        }
+        let row_number = index + 1;
+        let mut row_error = |field: Option<&str>, message: String| {
+            errors.push(CsvRowError {
+                row: row_number,
+                field: field.map(str::to_string),
+                message,
+            })
+        };
+
+        let row = match record {
+            Ok(row) => row,
+            Err(e) => {
+                row_error(None, format!("Malformed row: {}", e));
+                continue;
+            }
+        };
+
+        let start_date = format.parse_date(&row.start_date);
+        let end_date = format.parse_date(&row.end_date);
+        if start_date.is_none() {
+            row_error(
+                Some("start_date"),
+                format!(
+                    "'{}' is not a date ({})",
+                    row.start_date, format.date_format
+                ),
+            );
+        }
+        if end_date.is_none() {
+            row_error(
+                Some("end_date"),
+                format!("'{}' is not a date ({})", row.end_date, format.date_format),
+            );
+        }
+        if row.currency_code.len() != 3 {
+            row_error(
+                Some("currency_code"),
+                format!("'{}' is not a 3-letter currency code", row.currency_code),
+            );
+        }
+        if let (Some(start_date), Some(end_date)) = (start_date, end_date) {
+            let row_header = BudgetHeader {
+                name: name_override
+                    .clone()
+                    .unwrap_or_else(|| row.budget_name.clone()),
+                start_date,
+                end_date,
+                currency_code: row.currency_code.to_uppercase(),
+            };
+            match &header {
+                None => header = Some(row_header),
+                Some(existing) if *existing != row_header => row_error(
+                    Some("budget_name"),
+                    "Budget name, dates and currency must be the same on every row".to_string(),
+                ),
+                Some(_) => {}
+            }
+        }
+
+        let budgeted_amount = match format.parse_decimal(&row.budgeted_amount) {
+            Some(amount) if amount >= Decimal::ZERO => Some(amount.round_dp(2)),
+            Some(_) => {
+                row_error(
+                    Some("budgeted_amount"),
+                    "Amount cannot be negative".to_string(),
+                );
+                None
+            }
+            None => {
+                row_error(
+                    Some("budgeted_amount"),
+                    format!("'{}' is not a number", row.budgeted_amount),
+                );
+                None
+            }
+        };
+
+        let category_id = if row.category.is_empty() {
+            None
+        } else {
+            let found = categories.get(&row.category.to_lowercase()).copied();
+            if found.is_none() {
+                row_error(
+                    Some("category"),
+                    format!("Unknown category '{}'", row.category),
+                );
+            }
+            found
+        };
+        let account_id = if row.account.is_empty() {
+            None
+        } else {
+            let key = row.account.to_lowercase();
+            let found = accounts_by_code
+                .get(&key)
+                .or_else(|| accounts_by_name.get(&key))
+                .copied();
+            if found.is_none() {
+                row_error(
+                    Some("account"),
+                    format!("Unknown account '{}'", row.account),
+                );
+            }
+            found
+        };
+        if row.category.is_empty() && row.account.is_empty() {
+            row_error(None, "Each row needs a category or an account".to_string());
+        }
+
+        if let Some(budgeted_amount) = budgeted_amount {
+            if category_id.is_some() || account_id.is_some() {
+                if !seen_targets.insert((category_id, account_id)) {
+                    row_error(None, "Duplicate category/account line".to_string());
+                    continue;
+                }
+                lines.push(ResolvedLine {
+                    category_id,
+                    account_id,
+                    budgeted_amount,
+                });
+            }
+        }
+    }
+
+    let Some(header) = header else {
+        if errors.is_empty() {
+            errors.push(CsvRowError {
+                row: 0,
+                field: None,
+                message: "The file has no data rows".to_string(),
+            });
+        }
+        return Ok(BudgetImportResult {
+            budget: None,
+            rows_imported: 0,
+            errors,
+        });
+    };
+
+    if header.name.is_empty() {
+        errors.push(CsvRowError {
+            row: 1,
+            field: Some("budget_name".to_string()),
+            message: "Budget name is required".to_string(),
+        });
+    }
+    if header.end_date < header.start_date {
+        errors.push(CsvRowError {
+            row: 1,
+            field: Some("end_date".to_string()),
+            message: "End date cannot be before start date".to_string(),
+        });
+    }
+    let name_taken = sqlx::query!(
+        "SELECT EXISTS(SELECT 1 FROM budgets WHERE tenant_id = $1 AND name = $2)",
+        tenant_id,
+        header.name
+    )
+    .fetch_one(pool)
+    .await?
+    .exists
+    .unwrap_or(false);
+    if name_taken {
+        errors.push(CsvRowError {
+            row: 1,
+            field: Some("budget_name".to_string()),
+            message: format!(
+                "A budget named '{}' already exists; pass ?name= to import under a new name",
+                header.name
+            ),
+        });
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|e| e.row);
+        return Ok(BudgetImportResult {
+            budget: None,
+            rows_imported: 0,
+            errors,
+        });
+    }
+
+    let mut db_tx = pool.begin().await?;
+
+    let new_budget = query_as!(
+        Budget,
+        r#"
+        INSERT INTO budgets (
+            tenant_id, name, start_date, end_date, currency_code,
+            is_active, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, TRUE, $6, $6)
+        RETURNING
+            id, tenant_id, name, start_date, end_date, currency_code, is_envelope,
+            is_active, created_at, created_by, updated_at, updated_by, version
+        "#,
+        tenant_id,
+        header.name,
+        header.start_date,
+        header.end_date,
+        header.currency_code,
+        created_by_user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    for line in &lines {
+        sqlx::query!(
+            r#"
+            INSERT INTO budget_line_items (
+                budget_id, category_id, account_id, budgeted_amount,
+                is_active, created_by, updated_by
+            )
+            VALUES ($1, $2, $3, $4, TRUE, $5, $5)
+            "#,
+            new_budget.id,
+            line.category_id,
+            line.account_id,
+            line.budgeted_amount,
+            created_by_user_id
+        )
+        .execute(&mut *db_tx)
+        .await?;
+    }
+
+    db_tx.commit().await?;
+
+    Ok(BudgetImportResult {
+        budget: Some(new_budget),
+        rows_imported: lines.len(),
+        errors,
+    })
+}
+
+/// Starts importing a budget in the background and returns the pending job to poll.
+pub async fn start_import_job(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    csv_text: String,
+    name_override: Option<String>,
+    format: CsvFormat,
+) -> Result<ImportJob, AppError> {
+    let job =
+        import_job::create_import_job(pool, tenant_id, created_by_user_id, import_job::BUDGET_CSV)
+            .await?;
+
+    let pool = pool.clone();
+    let job_id = job.id;
+    tokio::spawn(db::with_tenant_scope(Some(tenant_id), async move {
+        let result = run_import_job(
+            &pool,
+            job_id,
+            tenant_id,
+            created_by_user_id,
+            &csv_text,
+            name_override,
+            &format,
+        )
+        .await;
+        if let Err(e) = result {
+            import_job::fail_import_job(&pool, job_id, &e).await;
+        }
+    }));
+
+    Ok(job)
+}
+
+async fn run_import_job(
+    pool: &PgPool,
+    job_id: Uuid,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    csv_text: &str,
+    name_override: Option<String>,
+    format: &CsvFormat,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Running budget import job {} for tenant ID: {}",
+        job_id, tenant_id
+    );
+
+    let total_rows = format.reader(csv_text).records().count();
+    let progress = ImportProgress::start(pool, job_id, total_rows).await?;
+    let result = import_budget_csv(
+        pool,
+        tenant_id,
+        created_by_user_id,
+        csv_text,
+        name_override,
+        format,
+        Some(&progress),
+    )
+    .await?;
+
+    let failed_rows = result
+        .errors
+        .iter()
+        .filter(|e| e.row > 0)
+        .map(|e| e.row)
+        .collect::<HashSet<_>>()
+        .len();
+    let error_message = match result.errors.iter().find(|e| e.row == 0) {
+        Some(file_error) => Some(file_error.message.clone()),
+        None if failed_rows > 0 => Some(format!(
+            "{} rows have errors; nothing was imported",
+            failed_rows
+        )),
+        None => None,
+    };
+    let outcome = ImportOutcome {
+        result_id: result.budget.map(|budget| budget.id),
+        failed_rows,
+        error_message,
+        error_csv: error_rows_csv(csv_text, &result.errors, format)?,
+    };
+    import_job::finish_import_job(pool, job_id, outcome).await
+}
+
+/// The rows that have errors, with their original fields plus `error_field` and
+/// `error_message` (several problems on one row are joined with "; "). `None` when no
+/// data row failed.
+fn error_rows_csv(
+    csv_text: &str,
+    errors: &[CsvRowError],
+    format: &CsvFormat,
+) -> Result<Option<String>, AppError> {
+    let mut errors_by_row: BTreeMap<usize, Vec<&CsvRowError>> = BTreeMap::new();
+    for error in errors.iter().filter(|e| e.row > 0) {
+        errors_by_row.entry(error.row).or_default().push(error);
+    }
+    if errors_by_row.is_empty() {
+        return Ok(None);
+    }
+
+    let mut reader = format.reader(csv_text);
+    let headers = reader
+        .headers()
+        .map_err(|e| AppError::Validation(format!("Malformed CSV header: {}", e)))?
+        .clone();
+    let mut writer = format.writer();
+    let mut columns: Vec<&str> = headers.iter().collect();
+    columns.extend(["error_field", "error_message"]);
+    writer.write_header(&columns)?;
+
+    for (index, record) in reader.records().enumerate() {
+        let Some(row_errors) = errors_by_row.get(&(index + 1)) else {
+            continue;
+        };
+        let mut cells: Vec<CsvCell> = match record {
+            Ok(record) => record
+                .iter()
+                .map(|field| CsvCell::from(field.to_string()))
+                .collect(),
+            // A malformed row cannot be split into fields; only its reason is written
+            Err(_) => Vec::new(),
+        };
+        cells.resize(headers.len(), CsvCell::Empty);
+        let fields: Vec<&str> = row_errors
+            .iter()
+            .filter_map(|e| e.field.as_deref())
+            .collect();
+        let messages: Vec<&str> = row_errors.iter().map(|e| e.message.as_str()).collect();
+        cells.push(CsvCell::from(fields.join("; ")));
+        cells.push(CsvCell::from(messages.join("; ")));
+        writer.write_row(cells)?;
+    }
+
+    writer.finish().map(Some)
+}
+
+/// Case-insensitive name -> ID map.
+fn name_lookup(entries: impl Iterator<Item = (String, Uuid)>) -> HashMap<String, Uuid> {
+    entries
+        .map(|(name, id)| (name.to_lowercase(), id))
+        .collect()
+}
+
+/// File-name-safe version of a name.
+pub fn slugify(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
@@ -0,0 +1,209 @@
+//! Budget health: spending the budget does not plan for.
+//!
+//! Lists categories with significant posted expenses in the budget period (in the budget
+//! currency) but no active line item, and suggests rolling them forward at their average
+//! spending over the three periods of the same length before the budget starts.
+
+use chrono::{Duration, Utc};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        budget::{BudgetHealth, UnbudgetedCategory},
+        budget_line_item::BudgetLineItem,
+        dto::budget_dto::{AddSuggestedLinesDto, BudgetHealthQuery},
+    },
+    services::budget,
+};
+
+/// Number of earlier periods averaged for a suggested amount.
+const LOOKBACK_PERIODS: i64 = 3;
+
+/// Share of the period's categorised spending a category needs to be listed, unless
+/// `min_amount` is given.
+const DEFAULT_MIN_SHARE: Decimal = Decimal::from_parts(2, 0, 0, false, 2);
+
+/// Checks a budget for categories with significant unbudgeted spending.
+pub async fn budget_health(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+    query: BudgetHealthQuery,
+) -> Result<BudgetHealth, AppError> {
+    info!(
+        "Service: Budget health for budget ID: {} tenant ID: {}",
+        budget_id, tenant_id
+    );
+
+    let budget = budget::get_budget_by_id(pool, tenant_id, budget_id).await?;
+    let as_of = query
+        .as_of
+        .unwrap_or_else(|| Utc::now().date_naive())
+        .min(budget.end_date);
+    let period_days = (budget.end_date - budget.start_date).num_days() + 1;
+    let lookback_start = budget.start_date - Duration::days(period_days * LOOKBACK_PERIODS);
+
+    let rows = sqlx::query!(
+        r#"
+        WITH spending AS (
+            SELECT t.category_id,
+                   SUM(t.amount) FILTER (WHERE t.transaction_date >= $3) as spent,
+                   COUNT(*) FILTER (WHERE t.transaction_date >= $3) as transaction_count,
+                   SUM(t.amount) FILTER (WHERE t.transaction_date < $3) as prior_spent
+            FROM transactions t
+            WHERE t.tenant_id = $1
+              AND t.type = 'EXPENSE'
+              AND t.status = 'POSTED'
+              AND t.currency_code = $2
+              AND t.category_id IS NOT NULL
+              AND t.transaction_date BETWEEN $5 AND $4
+              AND t.reversal_of_id IS NULL AND t.reversed_by_id IS NULL
+            GROUP BY t.category_id
+        )
+        SELECT c.id, c.name,
+               COALESCE(s.spent, 0) as "spent!",
+               COALESCE(s.transaction_count, 0) as "transaction_count!",
+               COALESCE(s.prior_spent, 0) as "prior_spent!",
+               EXISTS(
+                   SELECT 1 FROM budget_line_items bli
+                   WHERE bli.budget_id = $6 AND bli.is_active = TRUE AND bli.category_id = c.id
+               ) as "has_line!"
+        FROM spending s
+        JOIN categories c ON c.id = s.category_id AND c.is_active = TRUE
+        "#,
+        tenant_id,
+        budget.currency_code,
+        budget.start_date,
+        as_of,
+        lookback_start,
+        budget_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let min_amount = match query.min_amount {
+        Some(amount) if amount < Decimal::ZERO => {
+            return Err(AppError::Validation(
+                "min_amount cannot be negative".to_string(),
+            ))
+        }
+        Some(amount) => amount,
+        None => {
+            let total: Decimal = rows.iter().map(|row| row.spent).sum();
+            (total * DEFAULT_MIN_SHARE).round_dp(2)
+        }
+    };
+
+    let mut unbudgeted: Vec<UnbudgetedCategory> = rows
+        .into_iter()
+        .filter(|row| !row.has_line && row.spent > Decimal::ZERO && row.spent >= min_amount)
+        .map(|row| UnbudgetedCategory {
+            category_id: row.id,
+            category_name: row.name,
+            suggested_amount: suggested_amount(row.prior_spent, row.spent),
+            spent: row.spent,
+            transaction_count: row.transaction_count,
+        })
+        .collect();
+    unbudgeted.sort_by(|a, b| {
+        b.spent
+            .cmp(&a.spent)
+            .then_with(|| a.category_name.cmp(&b.category_name))
+    });
+
+    Ok(BudgetHealth {
+        budget_id,
+        as_of,
+        min_amount,
+        unbudgeted_spent: unbudgeted.iter().map(|category| category.spent).sum(),
+        unbudgeted,
+    })
+}
+
+/// Adds a line item at its suggested amount for each of the given unbudgeted categories.
+/// All or nothing; categories that already have a line or no spending are rejected.
+pub async fn add_suggested_lines(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: AddSuggestedLinesDto,
+) -> Result<Vec<BudgetLineItem>, AppError> {
+    info!(
+        "Service: Adding {} suggested line items to budget ID: {} tenant ID: {}",
+        dto.category_ids.len(),
+        budget_id,
+        tenant_id
+    );
+
+    // Every unbudgeted category with spending qualifies, significant or not
+    let health = budget_health(
+        pool,
+        tenant_id,
+        budget_id,
+        BudgetHealthQuery {
+            as_of: None,
+            min_amount: Some(Decimal::ZERO),
+        },
+    )
+    .await?;
+
+    let mut db_tx = pool.begin().await?;
+    let mut line_items = Vec::with_capacity(dto.category_ids.len());
+    for category_id in &dto.category_ids {
+        let category = health
+            .unbudgeted
+            .iter()
+            .find(|category| category.category_id == *category_id)
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Category {} has no unbudgeted spending in budget {}",
+                    category_id, budget_id
+                ))
+            })?;
+
+        let line_item = sqlx::query_as!(
+            BudgetLineItem,
+            r#"
+            INSERT INTO budget_line_items (
+                budget_id, category_id, budgeted_amount, is_active, created_by, updated_by
+            )
+            VALUES ($1, $2, $3, TRUE, $4, $4)
+            RETURNING
+                id, budget_id, category_id, account_id, budgeted_amount, monthly_amounts,
+                alert_thresholds, is_active, created_at, created_by, updated_at, updated_by
+            "#,
+            budget_id,
+            category.category_id,
+            category.suggested_amount,
+            created_by_user_id
+        )
+        .fetch_one(&mut *db_tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => AppError::Conflict(format!(
+                "Category {} is already in budget {}",
+                category.category_id, budget_id
+            )),
+            e => e.into(),
+        })?;
+        line_items.push(line_item);
+    }
+
+    db_tx.commit().await?;
+    Ok(line_items)
+}
+
+/// Average spending of the earlier periods; a category with no history is suggested at
+/// what it has spent so far this period.
+fn suggested_amount(prior_spent: Decimal, spent: Decimal) -> Decimal {
+    if prior_spent > Decimal::ZERO {
+        (prior_spent / Decimal::from(LOOKBACK_PERIODS)).round_dp(2)
+    } else {
+        spent
+    }
+}
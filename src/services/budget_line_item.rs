@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
 use sqlx::{query_as, PgPool};
 use uuid::Uuid;
 use tracing::info;
@@ -9,6 +12,7 @@ use crate::{
         budget_line_item::BudgetLineItem,
         dto::budget_line_item_dto::{CreateBudgetLineItemDto, UpdateBudgetLineItemDto},
     },
+    utils::update_builder::UpdateBuilder,
 };
 
 /// Retrieves a list of budget line items for a specific budget.
@@ -38,8 +42,8 @@ pub async fn list_budget_line_items(
         BudgetLineItem,
         r#"
         SELECT
-            id, budget_id, category_id, account_id, budgeted_amount,
-            is_active, created_at, created_by, updated_at, updated_by
+            id, budget_id, category_id, account_id, budgeted_amount, monthly_amounts,
+            alert_thresholds, is_active, created_at, created_by, updated_at, updated_by
         FROM budget_line_items
         WHERE budget_id = $1 AND is_active = TRUE
         ORDER BY category_id, account_id
@@ -64,8 +68,8 @@ pub async fn get_budget_line_item_by_id(
         BudgetLineItem,
         r#"
         SELECT
-            bli.id, bli.budget_id, bli.category_id, bli.account_id, bli.budgeted_amount,
-            bli.is_active, bli.created_at, bli.created_by, bli.updated_at, bli.updated_by
+            bli.id, bli.budget_id, bli.category_id, bli.account_id, bli.budgeted_amount, bli.monthly_amounts,
+            bli.alert_thresholds, bli.is_active, bli.created_at, bli.created_by, bli.updated_at, bli.updated_by
         FROM budget_line_items bli
         JOIN budgets b ON bli.budget_id = b.id
         WHERE bli.id = $1 AND b.tenant_id = $2 AND bli.is_active = TRUE AND b.is_active = TRUE
@@ -90,20 +94,37 @@ pub async fn create_budget_line_item(
 ) -> Result<BudgetLineItem, AppError> {
     info!("Service: Creating new budget line item for budget ID {}", budget_id);
 
-    // Verify the budget exists and belongs to the tenant
-    let budget_exists = sqlx::query!(
-        "SELECT EXISTS(SELECT 1 FROM budgets WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE)",
+    // Verify the budget exists and belongs to the tenant; its period bounds the monthly schedule
+    let budget = sqlx::query!(
+        "SELECT start_date, end_date FROM budgets WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE",
         budget_id,
         tenant_id
     )
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await?
-    .exists
-    .unwrap_or(false);
+    .ok_or_else(|| AppError::NotFound(format!("Budget with ID {} not found or inactive for tenant {}", budget_id, tenant_id)))?;
 
-    if !budget_exists {
-        return Err(AppError::NotFound(format!("Budget with ID {} not found or inactive for tenant {}", budget_id, tenant_id)));
-    }
+    let (budgeted_amount, monthly_amounts) = match &dto.monthly_amounts {
+        Some(schedule) => {
+            let total = monthly_schedule_total(schedule, budget.start_date, budget.end_date)?;
+            if let Some(amount) = dto.budgeted_amount {
+                if amount != total {
+                    return Err(AppError::Validation(format!(
+                        "budgeted_amount {} does not match the monthly schedule total {}",
+                        amount, total
+                    )));
+                }
+            }
+            (total, Some(schedule_to_json(schedule)))
+        }
+        None => (
+            dto.budgeted_amount
+                .ok_or_else(|| AppError::Validation("budgeted_amount or monthly_amounts is required".to_string()))?,
+            None,
+        ),
+    };
+
+    let alert_thresholds = dto.alert_thresholds.as_deref().map(alert_thresholds).transpose()?.unwrap_or_default();
 
     // Verify category ownership (if provided)
     if let Some(category_id) = dto.category_id {
@@ -116,7 +137,7 @@ pub async fn create_budget_line_item(
         .exists
         .unwrap_or(false);
         if !category_exists {
-            return Err(AppError::ValidationError(format!("Category ID {} is invalid or inactive for tenant {}", category_id, tenant_id)));
+            return Err(AppError::Validation(format!("Category ID {} is invalid or inactive for tenant {}", category_id, tenant_id)));
         }
     }
 
@@ -131,7 +152,7 @@ pub async fn create_budget_line_item(
         .exists
         .unwrap_or(false);
         if !account_exists {
-            return Err(AppError::ValidationError(format!("Account ID {} is invalid or inactive for tenant {}", account_id, tenant_id)));
+            return Err(AppError::Validation(format!("Account ID {} is invalid or inactive for tenant {}", account_id, tenant_id)));
         }
     }
 
@@ -139,19 +160,21 @@ pub async fn create_budget_line_item(
         BudgetLineItem,
         r#"
         INSERT INTO budget_line_items (
-            budget_id, category_id, account_id, budgeted_amount,
-            is_active, created_by, updated_by
+            budget_id, category_id, account_id, budgeted_amount, monthly_amounts,
+            alert_thresholds, is_active, created_by, updated_by
         )
-        VALUES ($1, $2, $3, $4, TRUE, $5, $5)
+        VALUES ($1, $2, $3, $4, $5, $7, TRUE, $6, $6)
         RETURNING
-            id, budget_id, category_id, account_id, budgeted_amount,
-            is_active, created_at, created_by, updated_at, updated_by
+            id, budget_id, category_id, account_id, budgeted_amount, monthly_amounts,
+            alert_thresholds, is_active, created_at, created_by, updated_at, updated_by
         "#,
         budget_id,
         dto.category_id,
         dto.account_id,
-        dto.budgeted_amount,
-        created_by_user_id
+        budgeted_amount,
+        monthly_amounts,
+        created_by_user_id,
+        &alert_thresholds
     )
     .fetch_one(pool)
     .await?;
@@ -169,14 +192,10 @@ pub async fn update_budget_line_item(
 ) -> Result<BudgetLineItem, AppError> {
     info!("Service: Updating budget line item with ID: {}", budget_line_item_id);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
+    let mut update = UpdateBuilder::new("budget_line_items bli");
 
     if let Some(category_id) = dto.category_id {
-        update_cols.push(format!("category_id = ${}", param_idx));
-        update_values.push(Box::new(category_id));
-        param_idx += 1;
+        update.set("category_id", Some(category_id));
         // Verify category ownership
         let category_exists = sqlx::query!(
             "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE)",
@@ -187,13 +206,11 @@ pub async fn update_budget_line_item(
         .exists
         .unwrap_or(false);
         if !category_exists {
-            return Err(AppError::ValidationError(format!("Category ID {} is invalid or inactive for tenant {}", category_id, tenant_id)));
+            return Err(AppError::Validation(format!("Category ID {} is invalid or inactive for tenant {}", category_id, tenant_id)));
         }
     }
     if let Some(account_id) = dto.account_id {
-        update_cols.push(format!("account_id = ${}", param_idx));
-        update_values.push(Box::new(account_id));
-        param_idx += 1;
+        update.set("account_id", Some(account_id));
         // Verify account ownership
         let account_exists = sqlx::query!(
             "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE)",
@@ -204,54 +221,58 @@ pub async fn update_budget_line_item(
         .exists
         .unwrap_or(false);
         if !account_exists {
-            return Err(AppError::ValidationError(format!("Account ID {} is invalid or inactive for tenant {}", account_id, tenant_id)));
+            return Err(AppError::Validation(format!("Account ID {} is invalid or inactive for tenant {}", account_id, tenant_id)));
         }
     }
-    if let Some(budgeted_amount) = dto.budgeted_amount {
-        update_cols.push(format!("budgeted_amount = ${}", param_idx));
-        update_values.push(Box::new(budgeted_amount));
-        param_idx += 1;
-    }
-    if let Some(is_active) = dto.is_active {
-        update_cols.push(format!("is_active = ${}", param_idx));
-        update_values.push(Box::new(is_active));
-        param_idx += 1;
+    if let Some(schedule) = &dto.monthly_amounts {
+        // The schedule replaces the flat amount; budgeted_amount becomes its total
+        let budget = sqlx::query!(
+            r#"
+            SELECT b.start_date, b.end_date
+            FROM budget_line_items bli
+            JOIN budgets b ON bli.budget_id = b.id
+            WHERE bli.id = $1 AND b.tenant_id = $2
+            "#,
+            budget_line_item_id,
+            tenant_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Budget line item with ID {} not found or not owned by tenant {}", budget_line_item_id, tenant_id)))?;
+        let total = monthly_schedule_total(schedule, budget.start_date, budget.end_date)?;
+        if dto.budgeted_amount.is_some_and(|amount| amount != total) {
+            return Err(AppError::Validation(format!(
+                "budgeted_amount does not match the monthly schedule total {}",
+                total
+            )));
+        }
+        update
+            .set("monthly_amounts", Some(schedule_to_json(schedule)))
+            .set("budgeted_amount", Some(total));
+    } else if let Some(budgeted_amount) = dto.budgeted_amount {
+        // A new flat amount drops any existing schedule, which would no longer add up
+        update.set("budgeted_amount", Some(budgeted_amount)).set_null("monthly_amounts");
     }
+    update.set("alert_thresholds", dto.alert_thresholds.as_deref().map(alert_thresholds).transpose()?);
+    update.set("is_active", dto.is_active);
 
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
-
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
+    if update.is_empty() {
+        return Err(AppError::Validation("No fields provided for update".to_string()));
     }
 
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" FROM budgets b WHERE bli.id = ").push_bind(budget_line_item_id);
+    query.push(" AND bli.budget_id = b.id AND b.tenant_id = ").push_bind(tenant_id);
+    query.push(
         r#"
-        UPDATE budget_line_items bli
-        SET {}
-        FROM budgets b
-        WHERE bli.id = ${} AND bli.budget_id = b.id AND b.tenant_id = ${}
         RETURNING
-            bli.id, bli.budget_id, bli.category_id, bli.account_id, bli.budgeted_amount,
-            bli.is_active, bli.created_at, bli.created_by, bli.updated_at, bli.updated_by
+            bli.id, bli.budget_id, bli.category_id, bli.account_id, bli.budgeted_amount, bli.monthly_amounts,
+            bli.alert_thresholds, bli.is_active, bli.created_at, bli.created_by, bli.updated_at, bli.updated_by
         "#,
-        update_clause, param_idx, param_idx + 1 // budget_line_item_id and tenant_id will be the last parameters
     );
 
-    let mut query = sqlx::query_as::<_, BudgetLineItem>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
-    }
-    // Bind budget_line_item_id and tenant_id last
-    query = query.bind(budget_line_item_id);
-    query = query.bind(tenant_id);
-
     let updated_line_item = query
+        .build_query_as::<BudgetLineItem>()
         .fetch_optional(pool)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Budget line item with ID {} not found or not owned by tenant {}", budget_line_item_id, tenant_id)))?;
@@ -291,4 +312,101 @@ pub async fn deactivate_budget_line_item(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Validates a monthly schedule against the budget period and returns its total.
+/// Keys are `YYYY-MM` months between the period's first and last month; amounts are non-negative.
+fn monthly_schedule_total(
+    schedule: &BTreeMap<String, Decimal>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<Decimal, AppError> {
+    if schedule.is_empty() {
+        return Err(AppError::Validation("monthly_amounts cannot be empty".to_string()));
+    }
+    let first_month = month_start(start_date);
+    let last_month = month_start(end_date);
+    let mut total = Decimal::ZERO;
+    for (month, amount) in schedule {
+        let parsed = parse_month(month)
+            .ok_or_else(|| AppError::Validation(format!("'{}' is not a month (YYYY-MM)", month)))?;
+        if parsed < first_month || parsed > last_month {
+            return Err(AppError::Validation(format!(
+                "Month {} is outside the budget period {} to {}",
+                month, start_date, end_date
+            )));
+        }
+        if *amount < Decimal::ZERO {
+            return Err(AppError::Validation(format!("Amount for {} cannot be negative", month)));
+        }
+        total += amount.round_dp(2);
+    }
+    Ok(total)
+}
+
+/// Validates alert thresholds (percent of the budgeted amount, above 0 and at most 1000)
+/// and returns them ascending without duplicates.
+fn alert_thresholds(thresholds: &[Decimal]) -> Result<Vec<Decimal>, AppError> {
+    let max = Decimal::from(1000);
+    if let Some(invalid) = thresholds.iter().find(|t| **t <= Decimal::ZERO || **t > max) {
+        return Err(AppError::Validation(format!(
+            "Alert threshold {} must be a percentage above 0 and at most 1000",
+            invalid
+        )));
+    }
+    let mut thresholds: Vec<Decimal> = thresholds.iter().map(|t| t.round_dp(2).normalize()).collect();
+    thresholds.sort();
+    thresholds.dedup();
+    Ok(thresholds)
+}
+
+fn schedule_to_json(schedule: &BTreeMap<String, Decimal>) -> serde_json::Value {
+    serde_json::Value::Object(
+        schedule
+            .iter()
+            .map(|(month, amount)| (month.clone(), serde_json::Value::String(amount.round_dp(2).to_string())))
+            .collect(),
+    )
+}
+
+/// Reads a stored `monthly_amounts` value back into a month -> amount map.
+/// Unparseable entries are skipped.
+pub fn schedule_from_json(value: &serde_json::Value) -> BTreeMap<NaiveDate, Decimal> {
+    value
+        .as_object()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|(month, amount)| {
+                    let amount = match amount {
+                        serde_json::Value::String(s) => s.parse::<Decimal>().ok()?,
+                        serde_json::Value::Number(n) => n.to_string().parse::<Decimal>().ok()?,
+                        _ => return None,
+                    };
+                    Some((parse_month(month)?, amount))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// First day of each month touched by the period, in order.
+pub fn budget_months(start_date: NaiveDate, end_date: NaiveDate) -> Vec<NaiveDate> {
+    let mut months = Vec::new();
+    let mut month = month_start(start_date);
+    while month <= end_date {
+        months.push(month);
+        month = month
+            .checked_add_months(chrono::Months::new(1))
+            .expect("month overflow");
+    }
+    months
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("day 1 is always valid")
+}
+
+fn parse_month(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d").ok()
+}
@@ -0,0 +1,205 @@
+//! Budget vs actual, per line item and per month.
+//!
+//! Lines with a monthly schedule are compared month by month against it; lines without
+//! one have their amount spread evenly over the months of the budget period. Actuals come
+//! from categorised transactions for category lines and from journal entries (signed by
+//! the account's normal balance) for account lines; only posted transactions count.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::budget::{BudgetLinePerformance, BudgetMonthPerformance, BudgetPerformance},
+    services::{budget, budget_line_item},
+};
+
+/// Builds the budget vs actual report for a budget.
+pub async fn budget_performance(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+) -> Result<BudgetPerformance, AppError> {
+    info!(
+        "Service: Budget performance for budget ID: {} tenant ID: {}",
+        budget_id, tenant_id
+    );
+
+    let budget = budget::get_budget_by_id(pool, tenant_id, budget_id).await?;
+    let months = budget_line_item::budget_months(budget.start_date, budget.end_date);
+
+    let lines = sqlx::query!(
+        r#"
+        SELECT bli.id, bli.category_id, bli.account_id, bli.budgeted_amount, bli.monthly_amounts,
+               c.name as "category_name?", a.account_code as "account_code?", a.name as "account_name?"
+        FROM budget_line_items bli
+        LEFT JOIN categories c ON bli.category_id = c.id
+        LEFT JOIN accounts a ON bli.account_id = a.id
+        WHERE bli.budget_id = $1 AND bli.is_active = TRUE
+        ORDER BY c.name NULLS LAST, a.account_code NULLS LAST, a.name
+        "#,
+        budget_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let actuals = line_actuals(
+        pool,
+        tenant_id,
+        budget_id,
+        budget.start_date,
+        budget.end_date,
+    )
+    .await?;
+
+    let mut total_budgeted = Decimal::ZERO;
+    let mut total_actual = Decimal::ZERO;
+    let mut performance_lines = Vec::with_capacity(lines.len());
+
+    for line in lines {
+        let schedule = line
+            .monthly_amounts
+            .as_ref()
+            .map(budget_line_item::schedule_from_json);
+        let monthly_budget = match &schedule {
+            Some(schedule) => months
+                .iter()
+                .map(|month| schedule.get(month).copied().unwrap_or(Decimal::ZERO))
+                .collect(),
+            None => spread_evenly(line.budgeted_amount, months.len()),
+        };
+
+        let month_rows: Vec<BudgetMonthPerformance> = months
+            .iter()
+            .zip(monthly_budget)
+            .map(|(month, budgeted)| {
+                let actual = actuals
+                    .get(&(line.id, *month))
+                    .copied()
+                    .unwrap_or(Decimal::ZERO);
+                BudgetMonthPerformance {
+                    month: *month,
+                    budgeted,
+                    actual,
+                    variance: budgeted - actual,
+                }
+            })
+            .collect();
+
+        let budgeted: Decimal = month_rows.iter().map(|m| m.budgeted).sum();
+        let actual: Decimal = month_rows.iter().map(|m| m.actual).sum();
+        total_budgeted += budgeted;
+        total_actual += actual;
+
+        let label = line_label(line.category_name, line.account_code, line.account_name);
+
+        performance_lines.push(BudgetLinePerformance {
+            line_item_id: line.id,
+            category_id: line.category_id,
+            account_id: line.account_id,
+            label,
+            is_seasonal: schedule.is_some(),
+            budgeted,
+            actual,
+            variance: budgeted - actual,
+            months: month_rows,
+        });
+    }
+
+    Ok(BudgetPerformance {
+        budget_id: budget.id,
+        name: budget.name,
+        start_date: budget.start_date,
+        end_date: budget.end_date,
+        currency_code: budget.currency_code,
+        total_budgeted,
+        total_actual,
+        total_variance: total_budgeted - total_actual,
+        lines: performance_lines,
+    })
+}
+
+/// Actuals of a budget's active line items between two dates, by line item and month.
+///
+/// Account lines (optionally narrowed by category) use the ledger; category-only lines
+/// use transaction totals.
+pub async fn line_actuals(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> Result<HashMap<(Uuid, NaiveDate), Decimal>, AppError> {
+    let actual_rows = sqlx::query!(
+        r#"
+        SELECT bli.id as "line_item_id!", date_trunc('month', t.transaction_date)::date as "month!",
+               SUM(t.amount) as "actual!"
+        FROM budget_line_items bli
+        JOIN transactions t ON t.category_id = bli.category_id
+            AND t.tenant_id = $2
+            AND t.status = 'POSTED'
+            AND t.transaction_date BETWEEN $3 AND $4
+        WHERE bli.budget_id = $1 AND bli.is_active = TRUE AND bli.account_id IS NULL
+        GROUP BY 1, 2
+        UNION ALL
+        SELECT bli.id, date_trunc('month', t.transaction_date)::date,
+               SUM(CASE WHEN je.entry_type = at.normal_balance
+                        THEN COALESCE(je.converted_amount, je.amount)
+                        ELSE -COALESCE(je.converted_amount, je.amount) END)
+        FROM budget_line_items bli
+        JOIN accounts a ON bli.account_id = a.id
+        JOIN account_types at ON a.account_type_id = at.id
+        JOIN journal_entries je ON je.account_id = bli.account_id
+        JOIN transactions t ON je.transaction_id = t.id
+            AND t.tenant_id = $2
+            AND t.status = 'POSTED'
+            AND t.transaction_date BETWEEN $3 AND $4
+            AND (bli.category_id IS NULL OR t.category_id = bli.category_id)
+        WHERE bli.budget_id = $1 AND bli.is_active = TRUE AND bli.account_id IS NOT NULL
+        GROUP BY 1, 2
+        "#,
+        budget_id,
+        tenant_id,
+        from_date,
+        to_date
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut actuals: HashMap<(Uuid, NaiveDate), Decimal> = HashMap::new();
+    for row in actual_rows {
+        *actuals.entry((row.line_item_id, row.month)).or_default() += row.actual;
+    }
+    Ok(actuals)
+}
+
+/// Display label of a line item from its category and account.
+pub fn line_label(
+    category_name: Option<String>,
+    account_code: Option<String>,
+    account_name: Option<String>,
+) -> String {
+    match (category_name, account_code, account_name) {
+        (Some(category), Some(code), _) => format!("{} ({})", category, code),
+        (Some(category), None, Some(account)) => format!("{} ({})", category, account),
+        (Some(category), None, None) => category,
+        (None, Some(code), Some(account)) => format!("{} {}", code, account),
+        (None, _, account) => account.unwrap_or_default(),
+    }
+}
+
+/// Splits an amount into `parts` equal cents; the last part absorbs the rounding.
+fn spread_evenly(amount: Decimal, parts: usize) -> Vec<Decimal> {
+    if parts == 0 {
+        return Vec::new();
+    }
+    let share = (amount / Decimal::from(parts as u64)).round_dp(2);
+    let mut shares = vec![share; parts];
+    shares[parts - 1] = amount - share * Decimal::from((parts - 1) as u64);
+    shares
+}
@@ -0,0 +1,407 @@
+//! Per-tenant business calendars: weekend days and holidays, used to move recurring
+//! occurrences that fall on a non-business day (see `BusinessDayRule`).
+//!
+//! Holidays can be added by hand or seeded a year at a time from a country's public
+//! holidays (`utils::holidays`). Seeding never overwrites a date the tenant already has.
+
+use std::collections::HashSet;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use sqlx::{query_as, PgExecutor, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        business_calendar::{BusinessCalendar, BusinessHoliday},
+        dto::business_calendar_dto::{
+            CreateBusinessHolidayDto, SeedBusinessHolidaysDto, UpdateBusinessCalendarDto,
+        },
+        recurring_transaction::BusinessDayRule,
+    },
+    utils::holidays,
+};
+
+/// How far an occurrence is moved at most looking for a business day; past that it is
+/// posted on its scheduled date.
+pub const MAX_ADJUSTMENT_DAYS: i64 = 14;
+
+/// A tenant's non-business days, loaded for date adjustment.
+#[derive(Debug, Clone)]
+pub struct BusinessDays {
+    weekend_days: Vec<Weekday>,
+    holidays: HashSet<NaiveDate>,
+}
+
+impl Default for BusinessDays {
+    fn default() -> Self {
+        BusinessDays {
+            weekend_days: vec![Weekday::Sat, Weekday::Sun],
+            holidays: HashSet::new(),
+        }
+    }
+}
+
+impl BusinessDays {
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.weekend_days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// The date an occurrence scheduled on `date` is posted on, or `None` when the rule
+    /// skips it.
+    pub fn adjust(&self, date: NaiveDate, rule: BusinessDayRule) -> Option<NaiveDate> {
+        if rule == BusinessDayRule::None || self.is_business_day(date) {
+            return Some(date);
+        }
+        let adjusted = match rule {
+            BusinessDayRule::Previous => self.business_day_from(date, -1),
+            BusinessDayRule::Next => self.business_day_from(date, 1),
+            BusinessDayRule::ModifiedNext => self
+                .business_day_from(date, 1)
+                .filter(|next| next.month() == date.month())
+                .or_else(|| self.business_day_from(date, -1)),
+            BusinessDayRule::Skip => return None,
+            BusinessDayRule::None => unreachable!(),
+        };
+        Some(adjusted.unwrap_or(date))
+    }
+
+    /// The first business day after (`step` 1) or before (`step` -1) `date`, within
+    /// `MAX_ADJUSTMENT_DAYS`.
+    fn business_day_from(&self, date: NaiveDate, step: i64) -> Option<NaiveDate> {
+        (1..=MAX_ADJUSTMENT_DAYS)
+            .map(|days| date + Duration::days(days * step))
+            .find(|candidate| self.is_business_day(*candidate))
+    }
+}
+
+/// Loads a tenant's weekend days and holidays.
+pub async fn load_business_days<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+) -> Result<BusinessDays, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            COALESCE((SELECT weekend_days FROM business_calendars WHERE tenant_id = $1), '{6,7}') as "weekend_days!",
+            COALESCE((SELECT array_agg(holiday_date) FROM business_holidays WHERE tenant_id = $1), '{}') as "holidays!"
+        "#,
+        tenant_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(BusinessDays {
+        weekend_days: row
+            .weekend_days
+            .into_iter()
+            .filter_map(iso_weekday)
+            .collect(),
+        holidays: row.holidays.into_iter().collect(),
+    })
+}
+
+/// Retrieves the tenant's business calendar settings (defaults until first edited).
+pub async fn get_calendar(pool: &PgPool, tenant_id: Uuid) -> Result<BusinessCalendar, AppError> {
+    info!(
+        "Service: Getting business calendar for tenant ID: {}",
+        tenant_id
+    );
+
+    let calendar = query_as!(
+        BusinessCalendar,
+        r#"
+        SELECT t.id as tenant_id, bc.country_code as "country_code?",
+               COALESCE(bc.weekend_days, '{6,7}') as "weekend_days!",
+               bc.updated_at as "updated_at?", bc.updated_by as "updated_by?"
+        FROM tenants t
+        LEFT JOIN business_calendars bc ON bc.tenant_id = t.id
+        WHERE t.id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+
+    Ok(calendar)
+}
+
+/// Sets the tenant's weekend days.
+pub async fn update_calendar(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateBusinessCalendarDto,
+) -> Result<BusinessCalendar, AppError> {
+    info!(
+        "Service: Updating business calendar for tenant ID: {}",
+        tenant_id
+    );
+
+    let mut weekend_days = dto.weekend_days;
+    if weekend_days.iter().any(|day| iso_weekday(*day).is_none()) {
+        return Err(AppError::Validation(
+            "weekend_days must be ISO weekday numbers from 1 (Monday) to 7 (Sunday)".to_string(),
+        ));
+    }
+    weekend_days.sort_unstable();
+    weekend_days.dedup();
+    if weekend_days.len() >= 7 {
+        return Err(AppError::Validation(
+            "At least one day of the week must be a business day".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO business_calendars (tenant_id, weekend_days, updated_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (tenant_id) DO UPDATE
+        SET weekend_days = EXCLUDED.weekend_days, updated_at = NOW(), updated_by = EXCLUDED.updated_by
+        "#,
+        tenant_id,
+        &weekend_days,
+        updated_by_user_id
+    )
+    .execute(pool)
+    .await?;
+
+    get_calendar(pool, tenant_id).await
+}
+
+/// Lists the tenant's holidays by date, optionally for one year.
+pub async fn list_holidays(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    year: Option<i32>,
+) -> Result<Vec<BusinessHoliday>, AppError> {
+    info!(
+        "Service: Listing business holidays for tenant ID: {}",
+        tenant_id
+    );
+
+    let holidays = query_as!(
+        BusinessHoliday,
+        r#"
+        SELECT id, tenant_id, holiday_date, name, country_code, created_at, created_by
+        FROM business_holidays
+        WHERE tenant_id = $1 AND ($2::int IS NULL OR EXTRACT(YEAR FROM holiday_date)::int = $2)
+        ORDER BY holiday_date
+        "#,
+        tenant_id,
+        year
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(holidays)
+}
+
+/// Adds a holiday by hand.
+pub async fn add_holiday(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateBusinessHolidayDto,
+) -> Result<BusinessHoliday, AppError> {
+    info!(
+        "Service: Adding business holiday {} for tenant ID: {}",
+        dto.holiday_date, tenant_id
+    );
+
+    let holiday = query_as!(
+        BusinessHoliday,
+        r#"
+        INSERT INTO business_holidays (tenant_id, holiday_date, name, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, tenant_id, holiday_date, name, country_code, created_at, created_by
+        "#,
+        tenant_id,
+        dto.holiday_date,
+        dto.name.trim(),
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            AppError::Conflict(format!("{} is already a holiday", dto.holiday_date))
+        }
+        e => e.into(),
+    })?;
+
+    Ok(holiday)
+}
+
+/// Removes a holiday.
+pub async fn delete_holiday(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    holiday_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting business holiday ID: {} for tenant ID: {}",
+        holiday_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        "DELETE FROM business_holidays WHERE id = $1 AND tenant_id = $2",
+        holiday_id,
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Business holiday with ID {} not found for tenant {}",
+            holiday_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Adds a country's public holidays for a year and records the country on the calendar.
+/// Dates the tenant already has are left alone; returns the holidays added.
+pub async fn seed_holidays(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: SeedBusinessHolidaysDto,
+) -> Result<Vec<BusinessHoliday>, AppError> {
+    let country_code = dto.country_code.to_uppercase();
+    info!(
+        "Service: Seeding {} {} holidays for tenant ID: {}",
+        dto.year, country_code, tenant_id
+    );
+
+    let public_holidays = holidays::public_holidays(&country_code, dto.year).ok_or_else(|| {
+        AppError::Validation(format!(
+            "No holiday rules for country '{}'; supported: {}",
+            country_code,
+            holidays::SUPPORTED_COUNTRIES.join(", ")
+        ))
+    })?;
+
+    let mut db_tx = pool.begin().await?;
+    let mut added = Vec::with_capacity(public_holidays.len());
+    for (holiday_date, name) in public_holidays {
+        let holiday = query_as!(
+            BusinessHoliday,
+            r#"
+            INSERT INTO business_holidays (tenant_id, holiday_date, name, country_code, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id, holiday_date) DO NOTHING
+            RETURNING id, tenant_id, holiday_date, name, country_code, created_at, created_by
+            "#,
+            tenant_id,
+            holiday_date,
+            name,
+            country_code,
+            created_by_user_id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?;
+        added.extend(holiday);
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO business_calendars (tenant_id, country_code, updated_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (tenant_id) DO UPDATE
+        SET country_code = EXCLUDED.country_code, updated_at = NOW(), updated_by = EXCLUDED.updated_by
+        "#,
+        tenant_id,
+        country_code,
+        created_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(added)
+}
+
+/// The weekday of an ISO weekday number (1 = Monday).
+fn iso_weekday(day: i16) -> Option<Weekday> {
+    match day {
+        1..=7 => Weekday::try_from((day - 1) as u8).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn adjusts_non_business_days_by_rule() {
+        use BusinessDayRule::*;
+
+        let business_days = BusinessDays {
+            holidays: HashSet::from([
+                date(2026, 7, 3), // Independence Day, observed
+                date(2026, 10, 30),
+            ]),
+            ..BusinessDays::default()
+        };
+        // (scheduled, rule, posted)
+        let cases = [
+            // A business day is never moved
+            (date(2026, 7, 6), Previous, Some(date(2026, 7, 6))),
+            (date(2026, 7, 6), Skip, Some(date(2026, 7, 6))),
+            // Saturday after an observed Friday holiday
+            (date(2026, 7, 4), None, Some(date(2026, 7, 4))),
+            (date(2026, 7, 4), Previous, Some(date(2026, 7, 2))),
+            (date(2026, 7, 4), Next, Some(date(2026, 7, 6))),
+            (date(2026, 7, 4), ModifiedNext, Some(date(2026, 7, 6))),
+            (date(2026, 7, 4), Skip, Option::None),
+            // Month end on a Saturday: modified following stays in January
+            (date(2026, 1, 31), Next, Some(date(2026, 2, 2))),
+            (date(2026, 1, 31), ModifiedNext, Some(date(2026, 1, 30))),
+            (date(2026, 1, 31), Previous, Some(date(2026, 1, 30))),
+            // ... and steps back over a holiday too
+            (date(2026, 10, 31), ModifiedNext, Some(date(2026, 10, 29))),
+            // A Sunday whose next business day is in the same month
+            (date(2026, 3, 1), ModifiedNext, Some(date(2026, 3, 2))),
+        ];
+        for (scheduled, rule, posted) in cases {
+            assert_eq!(
+                business_days.adjust(scheduled, rule),
+                posted,
+                "{} under {:?}",
+                scheduled,
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn custom_weekends_and_iso_weekdays() {
+        let business_days = BusinessDays {
+            weekend_days: vec![Weekday::Fri, Weekday::Sat],
+            holidays: HashSet::new(),
+        };
+        assert!(!business_days.is_business_day(date(2026, 7, 3)));
+        assert!(business_days.is_business_day(date(2026, 7, 5)));
+        assert_eq!(
+            business_days.adjust(date(2026, 7, 3), BusinessDayRule::Previous),
+            Some(date(2026, 7, 2))
+        );
+        assert_eq!(iso_weekday(1), Some(Weekday::Mon));
+        assert_eq!(iso_weekday(7), Some(Weekday::Sun));
+        assert_eq!(iso_weekday(0), Option::None);
+    }
+}
@@ -0,0 +1,341 @@
+//! iCalendar feed of a tenant's upcoming financial events.
+//!
+//! Calendar apps cannot send our auth headers, so a subscription is a secret URL: each user
+//! creates a token for a tenant and subscribes to `/tenants/:id/calendar.ics?token=...`.
+//! Only a SHA-256 hash of the token is stored; creating a new one replaces the old URL.
+//!
+//! The feed holds all-day events for the next `HORIZON_DAYS` days: recurring transaction
+//! occurrences (skips excluded, paused series omitted) and budget end dates. There is no
+//! invoicing yet, so invoice due dates are not included. While privacy mode is on, the
+//! descriptions of recurring transactions are left out, as the URL is readable without a key.
+
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate, Utc};
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        calendar_feed::CalendarFeedToken,
+        recurring_transaction::{BusinessDayRule, RecurringTransaction},
+        transaction::TransactionType,
+    },
+    services::{business_calendar, privacy, recurring_template, recurring_transaction},
+    utils::crypto::{generate_secret, sha256_hex},
+};
+
+/// Events are listed this many days ahead.
+const HORIZON_DAYS: i64 = 90;
+
+/// Random bytes in a feed token.
+const TOKEN_BYTES: usize = 32;
+
+/// `PRODID` of the generated calendar.
+const PRODUCT_ID: &str = "-//Forge//Financial calendar//EN";
+
+/// Creates a feed token for the user, replacing (and so revoking) any earlier one.
+pub async fn create_feed_token(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<CalendarFeedToken, AppError> {
+    info!(
+        "Service: Creating calendar feed token for tenant ID: {}",
+        tenant_id
+    );
+
+    let token = generate_secret(TOKEN_BYTES);
+    let created_at = sqlx::query_scalar!(
+        r#"
+        INSERT INTO calendar_feeds (tenant_id, user_id, token_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (tenant_id, user_id) DO UPDATE
+        SET token_hash = EXCLUDED.token_hash, last_used_at = NULL, created_at = NOW()
+        RETURNING created_at
+        "#,
+        tenant_id,
+        user_id,
+        sha256_hex(token.as_bytes())
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(CalendarFeedToken {
+        tenant_id,
+        feed_path: format!("/api/v1/tenants/{}/calendar.ics?token={}", tenant_id, token),
+        token,
+        created_at,
+    })
+}
+
+/// Revokes the user's feed token; subscribed calendars stop updating.
+pub async fn revoke_feed_token(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Revoking calendar feed token for tenant ID: {}",
+        tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        "DELETE FROM calendar_feeds WHERE tenant_id = $1 AND user_id = $2",
+        tenant_id,
+        user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "No calendar feed for tenant {}",
+            tenant_id
+        )));
+    }
+    Ok(())
+}
+
+/// Renders the tenant's feed as iCalendar text, if `token` belongs to it.
+pub async fn render_feed(pool: &PgPool, tenant_id: Uuid, token: &str) -> Result<String, AppError> {
+    info!(
+        "Service: Rendering calendar feed for tenant ID: {}",
+        tenant_id
+    );
+
+    let feed_id = sqlx::query_scalar!(
+        r#"
+        UPDATE calendar_feeds
+        SET last_used_at = NOW()
+        WHERE tenant_id = $1 AND token_hash = $2
+        RETURNING id
+        "#,
+        tenant_id,
+        sha256_hex(token.as_bytes())
+    )
+    .fetch_optional(pool)
+    .await?;
+    if feed_id.is_none() {
+        return Err(AppError::Unauthorized(
+            "Invalid calendar feed token".to_string(),
+        ));
+    }
+
+    let tenant_name = sqlx::query_scalar!("SELECT name FROM tenants WHERE id = $1", tenant_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+
+    let today = Utc::now().date_naive();
+    let until = today + Duration::days(HORIZON_DAYS);
+    let mut events = recurring_events(pool, tenant_id, today, until).await?;
+    events.extend(budget_events(pool, tenant_id, today, until).await?);
+    events.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.summary.cmp(&b.summary)));
+
+    Ok(calendar(&tenant_name, &events))
+}
+
+/// One all-day event.
+struct CalendarEvent {
+    uid: String,
+    date: NaiveDate,
+    summary: String,
+    description: Option<String>,
+}
+
+/// Occurrences of the tenant's running recurring transactions between `from` and `until`.
+async fn recurring_events(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from: NaiveDate,
+    until: NaiveDate,
+) -> Result<Vec<CalendarEvent>, AppError> {
+    let definitions = query_as!(
+        RecurringTransaction,
+        r#"
+        SELECT
+            id, tenant_id, description, type as "r#type: TransactionType", category_id, account_id, amount,
+            currency_code, frequency_value, frequency_unit, start_date, end_date,
+            last_generated_date, next_due_date, is_active, notes, journal_template,
+            escalation_percent, escalation_month, escalated_through, paused_at,
+            business_day_rule as "business_day_rule: BusinessDayRule",
+            created_at, created_by, updated_at, updated_by
+        FROM recurring_transactions
+        WHERE tenant_id = $1 AND is_active = TRUE AND paused_at IS NULL AND next_due_date <= $2
+        "#,
+        tenant_id,
+        until
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let ids: Vec<Uuid> = definitions.iter().map(|d| d.id).collect();
+    let skipped: HashSet<(Uuid, NaiveDate)> = sqlx::query!(
+        r#"
+        SELECT recurring_transaction_id, occurrence_date
+        FROM recurring_transaction_skips
+        WHERE recurring_transaction_id = ANY($1) AND occurrence_date BETWEEN $2 AND $3
+        "#,
+        &ids,
+        from,
+        until
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.recurring_transaction_id, row.occurrence_date))
+    .collect();
+
+    // Descriptions are not sealed until materialized, so the feed hides them itself
+    let private = privacy::sealing_key(pool, tenant_id).await?.is_some();
+    let business_days = business_calendar::load_business_days(pool, tenant_id).await?;
+
+    let mut events = Vec::new();
+    for definition in &definitions {
+        for date in recurring_transaction::upcoming_dates(definition)?.take_while(|d| *d <= until) {
+            if date < from || skipped.contains(&(definition.id, date)) {
+                continue;
+            }
+            // Shown on the day it will be posted
+            let Some(posting_date) = business_days.adjust(date, definition.business_day_rule)
+            else {
+                continue;
+            };
+            let title = if private {
+                format!(
+                    "Recurring {}",
+                    String::from(definition.r#type)
+                        .to_lowercase()
+                        .replace('_', " ")
+                )
+            } else {
+                recurring_template::render_template(&definition.description, date)
+            };
+            events.push(CalendarEvent {
+                uid: format!("recurring-{}-{}", definition.id, date.format("%Y%m%d")),
+                date: posting_date,
+                summary: format!(
+                    "{}: {} {}",
+                    title, definition.amount, definition.currency_code
+                ),
+                description: (!private)
+                    .then(|| {
+                        definition
+                            .notes
+                            .as_deref()
+                            .map(|n| recurring_template::render_template(n, date))
+                    })
+                    .flatten(),
+            });
+        }
+    }
+    Ok(events)
+}
+
+/// End dates of the tenant's active budgets between `from` and `until`.
+async fn budget_events(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from: NaiveDate,
+    until: NaiveDate,
+) -> Result<Vec<CalendarEvent>, AppError> {
+    let budgets = sqlx::query!(
+        r#"
+        SELECT id, name, start_date, end_date
+        FROM budgets
+        WHERE tenant_id = $1 AND is_active = TRUE AND end_date BETWEEN $2 AND $3
+        "#,
+        tenant_id,
+        from,
+        until
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(budgets
+        .into_iter()
+        .map(|budget| CalendarEvent {
+            uid: format!("budget-end-{}", budget.id),
+            date: budget.end_date,
+            summary: format!("Budget period ends: {}", budget.name),
+            description: Some(format!(
+                "Budget period {} to {}",
+                budget.start_date, budget.end_date
+            )),
+        })
+        .collect())
+}
+
+/// Renders a `VCALENDAR` with CRLF line endings and folded lines (RFC 5545).
+fn calendar(tenant_name: &str, events: &[CalendarEvent]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!(
+            "X-WR-CALNAME:{}",
+            escape_text(&format!("{} – finances", tenant_name))
+        ),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@forge", event.uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!(
+            "DTSTART;VALUE=DATE:{}",
+            event.date.format("%Y%m%d")
+        ));
+        lines.push(format!(
+            "DTEND;VALUE=DATE:{}",
+            (event.date + Duration::days(1)).format("%Y%m%d")
+        ));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line) + "\r\n").collect()
+}
+
+/// Escapes a TEXT value: backslashes, semicolons, commas and newlines.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folds a content line into chunks of at most 75 octets, without splitting characters.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the next line
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
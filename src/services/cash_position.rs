@@ -0,0 +1,185 @@
+//! Treasury cash position: balances of bank and cash accounts by currency and institution.
+//!
+//! Cash accounts are active asset accounts linked to a bank feed (`external_accounts`);
+//! the institution is the one recorded on the connection, or else the provider's name.
+//! The scheduler stores each account's ledger balance once a day in
+//! `cash_position_snapshots` (re-running the same day overwrites that day's rows), so the
+//! API only reads the snapshot table and stays cheap for dashboards that poll it.
+
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::cash_position::{
+        CashPosition, CashPositionAccount, CashPositionCurrency, CashPositionInstitution,
+    },
+};
+
+/// Movement is measured against the latest snapshot at least this many days older.
+const COMPARISON_DAYS: i64 = 7;
+
+/// Records every active tenant's cash account balances as of `snapshot_date`.
+/// Returns the number of accounts recorded.
+pub async fn record_snapshots(pool: &PgPool, snapshot_date: NaiveDate) -> Result<u64, AppError> {
+    info!(
+        "Service: Recording cash position snapshots for {}",
+        snapshot_date
+    );
+
+    let recorded = sqlx::query!(
+        r#"
+        INSERT INTO cash_position_snapshots (tenant_id, account_id, snapshot_date, institution, currency_code, balance)
+        SELECT
+            a.tenant_id, a.id, $1, linked.institution, a.currency_code,
+            COALESCE((
+                SELECT SUM(
+                    CASE WHEN je.entry_type = at.normal_balance THEN COALESCE(je.converted_amount, je.amount)
+                         ELSE -COALESCE(je.converted_amount, je.amount) END
+                )
+                FROM journal_entries je
+                JOIN transactions t ON je.transaction_id = t.id
+                WHERE je.account_id = a.id
+                  AND t.status = 'POSTED'
+                  AND t.transaction_date <= $1
+            ), 0)
+        FROM accounts a
+        JOIN account_types at ON a.account_type_id = at.id
+        JOIN tenants tn ON tn.id = a.tenant_id AND tn.is_active = TRUE
+        JOIN LATERAL (
+            SELECT COALESCE(ec.metadata->>'institution_name', ep.name) as institution
+            FROM external_accounts ea
+            JOIN ext_conns ec ON ea.ext_conn_id = ec.id
+            JOIN ext_providers ep ON ec.provider_id = ep.id
+            WHERE ea.account_id = a.id AND ea.is_active = TRUE
+            ORDER BY ea.created_at
+            LIMIT 1
+        ) linked ON TRUE
+        WHERE a.is_active = TRUE AND at.name = 'Asset'
+        ON CONFLICT (account_id, snapshot_date) DO UPDATE
+        SET institution = EXCLUDED.institution,
+            currency_code = EXCLUDED.currency_code,
+            balance = EXCLUDED.balance,
+            updated_at = NOW()
+        "#,
+        snapshot_date
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(recorded)
+}
+
+/// The tenant's cash position from the latest snapshot on or before `as_of`, with the
+/// movement since the snapshot a week earlier.
+pub async fn cash_position(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    as_of: NaiveDate,
+) -> Result<CashPosition, AppError> {
+    info!(
+        "Service: Cash position for tenant ID: {} as of {}",
+        tenant_id, as_of
+    );
+
+    let Some(snapshot_date) = latest_snapshot_date(pool, tenant_id, as_of).await? else {
+        return Ok(CashPosition {
+            as_of: None,
+            compared_to: None,
+            currencies: Vec::new(),
+        });
+    };
+    let compared_to = latest_snapshot_date(
+        pool,
+        tenant_id,
+        snapshot_date - Duration::days(COMPARISON_DAYS),
+    )
+    .await?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            s.account_id, a.account_code, a.name, s.institution, s.currency_code, s.balance,
+            p.balance as "previous_balance?"
+        FROM cash_position_snapshots s
+        JOIN accounts a ON a.id = s.account_id
+        LEFT JOIN cash_position_snapshots p ON p.account_id = s.account_id AND p.snapshot_date = $3
+        WHERE s.tenant_id = $1 AND s.snapshot_date = $2
+        ORDER BY s.currency_code, s.institution, a.account_code NULLS LAST, a.name
+        "#,
+        tenant_id,
+        snapshot_date,
+        compared_to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Rows arrive sorted by currency and institution, so each group is contiguous
+    let mut currencies: Vec<CashPositionCurrency> = Vec::new();
+    for row in rows {
+        let account = CashPositionAccount {
+            account_id: row.account_id,
+            account_code: row.account_code,
+            name: row.name,
+            balance: row.balance,
+            previous_balance: row.previous_balance,
+            movement: row.balance - row.previous_balance.unwrap_or(Decimal::ZERO),
+        };
+
+        if currencies.last().map(|c| &c.currency_code) != Some(&row.currency_code) {
+            currencies.push(CashPositionCurrency {
+                currency_code: row.currency_code,
+                total: Decimal::ZERO,
+                previous_total: Decimal::ZERO,
+                total_movement: Decimal::ZERO,
+                institutions: Vec::new(),
+            });
+        }
+        let currency = currencies.last_mut().expect("pushed above");
+        if currency.institutions.last().map(|i| &i.institution) != Some(&row.institution) {
+            currency.institutions.push(CashPositionInstitution {
+                institution: row.institution,
+                total: Decimal::ZERO,
+                previous_total: Decimal::ZERO,
+                total_movement: Decimal::ZERO,
+                accounts: Vec::new(),
+            });
+        }
+        let institution = currency.institutions.last_mut().expect("pushed above");
+
+        let previous = account.previous_balance.unwrap_or(Decimal::ZERO);
+        institution.total += account.balance;
+        institution.previous_total += previous;
+        institution.total_movement += account.movement;
+        currency.total += account.balance;
+        currency.previous_total += previous;
+        currency.total_movement += account.movement;
+        institution.accounts.push(account);
+    }
+
+    Ok(CashPosition {
+        as_of: Some(snapshot_date),
+        compared_to,
+        currencies,
+    })
+}
+
+/// Date of the tenant's latest snapshot on or before `date`.
+async fn latest_snapshot_date(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    date: NaiveDate,
+) -> Result<Option<NaiveDate>, AppError> {
+    let snapshot_date = sqlx::query_scalar!(
+        "SELECT MAX(snapshot_date) FROM cash_position_snapshots WHERE tenant_id = $1 AND snapshot_date <= $2",
+        tenant_id,
+        date
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(snapshot_date)
+}
@@ -0,0 +1,1140 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Months, Utc};
+use serde_json::Value as JsonValue;
+use sqlx::{query_as, PgExecutor, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        category::{Category, CategoryMergeResult, CategoryTreeNode, CategoryType},
+        dto::archive_dto::ArchiveUnusedDto,
+        dto::category_dto::{CreateCategoryDto, UpdateCategoryDto},
+    },
+    services::{
+        budget_line_item, dependency, fiscal_period,
+        permission::{self, RECORDS_RESTORE},
+    },
+    utils::update_builder::UpdateBuilder,
+};
+
+/// A row of the category tree query, in depth-first order.
+struct CategoryTreeRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    name: String,
+    description: Option<String>,
+    r#type: CategoryType,
+    parent_category_id: Option<Uuid>,
+    is_active: bool,
+    archived_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    created_by: Uuid,
+    updated_at: DateTime<Utc>,
+    updated_by: Uuid,
+    depth: i32,
+}
+
+/// Retrieves a list of categories for a specific tenant, without archived or deactivated ones
+/// unless asked. Deactivated ones are only listed for `user_id` holding `records.restore`.
+pub async fn list_categories(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    include_archived: bool,
+    include_inactive_for: Option<Uuid>,
+) -> Result<Vec<Category>, AppError> {
+    info!("Service: Listing categories for tenant ID: {}", tenant_id);
+
+    if let Some(user_id) = include_inactive_for {
+        permission::require_permission(pool, tenant_id, user_id, RECORDS_RESTORE).await?;
+    }
+
+    let categories = query_as!(
+        Category,
+        r#"
+        SELECT
+            id, tenant_id, name, description, type as "r#type!: CategoryType", -- Cast for enum
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        FROM categories
+        WHERE tenant_id = $1 AND (is_active = TRUE OR $3) AND (archived_at IS NULL OR $2)
+        ORDER BY name
+        "#,
+        tenant_id,
+        include_archived,
+        include_inactive_for.is_some()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(categories)
+}
+
+/// Retrieves the tenant's active, unarchived categories as a tree, each level sorted by name.
+///
+/// Categories whose parent is inactive are shown at the top level. Categories caught in a
+/// cycle saved before parents were validated have no top-level ancestor and are left out;
+/// re-parenting one of them brings them back.
+pub async fn category_tree(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Vec<CategoryTreeNode>, AppError> {
+    info!(
+        "Service: Building category tree for tenant ID: {}",
+        tenant_id
+    );
+
+    let rows = query_as!(
+        CategoryTreeRow,
+        r#"
+        WITH RECURSIVE tree AS (
+            SELECT c.id, 0 AS depth, ARRAY[LOWER(c.name), c.id::TEXT] AS sort_path
+            FROM categories c
+            LEFT JOIN categories parent
+                ON parent.id = c.parent_category_id
+                AND parent.tenant_id = c.tenant_id
+                AND parent.is_active = TRUE
+                AND parent.archived_at IS NULL
+            WHERE c.tenant_id = $1 AND c.is_active = TRUE AND c.archived_at IS NULL AND parent.id IS NULL
+            UNION ALL
+            SELECT c.id, tree.depth + 1, tree.sort_path || ARRAY[LOWER(c.name), c.id::TEXT]
+            FROM categories c
+            JOIN tree ON c.parent_category_id = tree.id
+            WHERE c.tenant_id = $1 AND c.is_active = TRUE AND c.archived_at IS NULL
+        )
+        SELECT
+            c.id, c.tenant_id, c.name, c.description, c.type as "r#type!: CategoryType",
+            c.parent_category_id, c.is_active, c.archived_at, c.created_at, c.created_by, c.updated_at, c.updated_by,
+            tree.depth as "depth!"
+        FROM tree
+        JOIN categories c ON c.id = tree.id
+        ORDER BY tree.sort_path
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Rows arrive depth-first, so each row's parent is the last open node one level up
+    let mut roots = Vec::new();
+    let mut open: Vec<CategoryTreeNode> = Vec::new();
+    for row in rows {
+        while open.len() > row.depth as usize {
+            close_node(&mut open, &mut roots);
+        }
+        open.push(CategoryTreeNode {
+            category: Category {
+                id: row.id,
+                tenant_id: row.tenant_id,
+                name: row.name,
+                description: row.description,
+                r#type: row.r#type,
+                parent_category_id: row.parent_category_id,
+                is_active: row.is_active,
+                archived_at: row.archived_at,
+                created_at: row.created_at,
+                created_by: row.created_by,
+                updated_at: row.updated_at,
+                updated_by: row.updated_by,
+            },
+            depth: row.depth,
+            children: Vec::new(),
+        });
+    }
+    while !open.is_empty() {
+        close_node(&mut open, &mut roots);
+    }
+
+    Ok(roots)
+}
+
+/// Moves the innermost open node into its parent, or into `roots` at the top level.
+fn close_node(open: &mut Vec<CategoryTreeNode>, roots: &mut Vec<CategoryTreeNode>) {
+    let Some(node) = open.pop() else {
+        return;
+    };
+    match open.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+/// Retrieves a single category by ID for a specific tenant.
+pub async fn get_category_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    category_id: Uuid,
+) -> Result<Category, AppError> {
+    info!(
+        "Service: Getting category with ID: {} for tenant ID: {}",
+        category_id, tenant_id
+    );
+
+    let category = query_as!(
+        Category,
+        r#"
+        SELECT
+            id, tenant_id, name, description, type as "r#type!: CategoryType",
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        FROM categories
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        category_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Category with ID {} not found for tenant {}",
+            category_id, tenant_id
+        ))
+    })?;
+
+    Ok(category)
+}
+
+/// Creates a new category for a specific tenant.
+pub async fn create_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateCategoryDto,
+) -> Result<Category, AppError> {
+    info!(
+        "Service: Creating new category with name: {} for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    if let Some(parent_id) = dto.parent_category_id {
+        validate_parent(pool, tenant_id, None, parent_id).await?;
+    }
+
+    let new_category = query_as!(
+        Category,
+        r#"
+        INSERT INTO categories (
+            tenant_id, name, description, type, parent_category_id,
+            is_active, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, TRUE, $6, $6)
+        RETURNING
+            id, tenant_id, name, description, type as "r#type!: CategoryType",
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.name,
+        dto.description,
+        dto.r#type as CategoryType, // Cast to enum for query
+        dto.parent_category_id,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(new_category)
+}
+
+/// Updates an existing category for a specific tenant.
+pub async fn update_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    category_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateCategoryDto,
+) -> Result<Category, AppError> {
+    info!(
+        "Service: Updating category with ID: {} for tenant ID: {}",
+        category_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    if let Some(&parent_id) = dto.parent_category_id.value() {
+        // Serializes re-parenting within the tenant, so two concurrent moves cannot
+        // together close a cycle that neither creates alone
+        sqlx::query!(
+            "SELECT id FROM tenants WHERE id = $1 FOR NO KEY UPDATE",
+            tenant_id
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+        validate_parent(&mut *db_tx, tenant_id, Some(category_id), parent_id).await?;
+    }
+    if dto.is_active == Some(false) {
+        dependency::ensure_none(
+            dependency::category_blockers(&mut *db_tx, tenant_id, &[category_id]).await?,
+        )?;
+    }
+
+    let mut update = UpdateBuilder::new("categories");
+    update
+        .set("name", dto.name)
+        .patch("description", dto.description)
+        .set("type", dto.r#type)
+        .patch("parent_category_id", dto.parent_category_id)
+        .set("is_active", dto.is_active);
+    if update.is_empty() {
+        return Err(AppError::Validation(
+            "No fields provided for update".to_string(),
+        ));
+    }
+
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(category_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(
+        r#"
+        RETURNING
+            id, tenant_id, name, description, type,
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        "#,
+    );
+
+    let updated_category = query
+        .build_query_as::<Category>()
+        .fetch_optional(&mut *db_tx)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Category with ID {} not found or not owned by tenant {}",
+                category_id, tenant_id
+            ))
+        })?;
+    db_tx.commit().await?;
+
+    Ok(updated_category)
+}
+
+/// Deactivates a category (soft delete) for a specific tenant. A category with active
+/// subcategories, budget lines or recurring transactions, or with merchant rules assigning
+/// it, is only deactivated with `cascade`: that deactivates the whole subtree and what
+/// depends on it, and clears it from the rules.
+pub async fn deactivate_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    category_id: Uuid,
+    updated_by_user_id: Uuid,
+    cascade: bool,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deactivating category with ID: {} for tenant ID: {}",
+        category_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    // The row locks also hold off new records referencing the categories
+    let category_ids = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE subtree(id) AS (
+            SELECT id FROM categories WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+            UNION
+            SELECT c.id
+            FROM categories c
+            JOIN subtree ON c.parent_category_id = subtree.id
+            WHERE $3 AND c.tenant_id = $2 AND c.is_active = TRUE
+        )
+        SELECT id FROM categories WHERE id IN (SELECT id FROM subtree) FOR UPDATE
+        "#,
+        category_id,
+        tenant_id,
+        cascade
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    if category_ids.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "Category with ID {} not found or already inactive for tenant {}",
+            category_id, tenant_id
+        )));
+    }
+
+    if cascade {
+        let cascaded = dependency::cascade_categories(
+            &mut db_tx,
+            tenant_id,
+            &category_ids,
+            updated_by_user_id,
+        )
+        .await?;
+        info!(
+            "Service: Deactivating {} categories and {} dependents under category ID: {}",
+            category_ids.len(),
+            cascaded,
+            category_id
+        );
+    } else {
+        dependency::ensure_none(
+            dependency::category_blockers(&mut *db_tx, tenant_id, &category_ids).await?,
+        )?;
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE categories
+        SET
+            is_active = FALSE,
+            updated_at = NOW(),
+            updated_by = $3
+        WHERE id = ANY($1) AND tenant_id = $2
+        "#,
+        &category_ids,
+        tenant_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    db_tx.commit().await?;
+
+    Ok(())
+}
+
+/// Merges a category into another of the same type, in one transaction: its transactions,
+/// split lines, budget line items, recurring transactions, merchant rules, staged bank rows,
+/// subcategories and custom report filters move to the target, and the source is
+/// deactivated. Where a budget already has a line for the target, the source line's amount
+/// (and monthly schedule) is added to it and the source line is deactivated.
+pub async fn merge_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    source_id: Uuid,
+    target_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<CategoryMergeResult, AppError> {
+    info!(
+        "Service: Merging category ID: {} into category ID: {} for tenant ID: {}",
+        source_id, target_id, tenant_id
+    );
+
+    if source_id == target_id {
+        return Err(AppError::Validation(
+            "A category cannot be merged into itself".to_string(),
+        ));
+    }
+
+    let mut db_tx = pool.begin().await?;
+    // Subcategories are re-parented, so this is serialized with other re-parenting
+    sqlx::query!(
+        "SELECT id FROM tenants WHERE id = $1 FOR NO KEY UPDATE",
+        tenant_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    let categories = query_as!(
+        Category,
+        r#"
+        SELECT
+            id, tenant_id, name, description, type as "r#type!: CategoryType",
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        FROM categories
+        WHERE tenant_id = $1 AND id = ANY($2) AND is_active = TRUE
+        ORDER BY id
+        FOR UPDATE
+        "#,
+        tenant_id,
+        &[source_id, target_id][..]
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    let (mut source, mut target) = (None, None);
+    for category in categories {
+        if category.id == source_id {
+            source = Some(category);
+        } else {
+            target = Some(category);
+        }
+    }
+    let not_found = |category_id: Uuid| {
+        AppError::NotFound(format!(
+            "Category with ID {} not found for tenant {}",
+            category_id, tenant_id
+        ))
+    };
+    let source = source.ok_or_else(|| not_found(source_id))?;
+    let target = target.ok_or_else(|| not_found(target_id))?;
+
+    if target.archived_at.is_some() {
+        return Err(AppError::Validation(format!(
+            "Category '{}' is archived; unarchive it before merging into it",
+            target.name
+        )));
+    }
+    if source.r#type != target.r#type {
+        return Err(AppError::Validation(format!(
+            "Cannot merge {} category '{}' into {} category '{}'",
+            String::from(source.r#type),
+            source.name,
+            String::from(target.r#type),
+            target.name
+        )));
+    }
+
+    // The target takes over the source's subcategories, so it cannot be one of them
+    let target_is_descendant = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE ancestors(id, parent_category_id) AS (
+            SELECT id, parent_category_id FROM categories WHERE id = $1 AND tenant_id = $2
+            UNION
+            SELECT c.id, c.parent_category_id
+            FROM categories c
+            JOIN ancestors a ON c.id = a.parent_category_id
+            WHERE c.tenant_id = $2
+        )
+        SELECT EXISTS(SELECT 1 FROM ancestors WHERE parent_category_id = $3) as "exists!"
+        "#,
+        target_id,
+        tenant_id,
+        source_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    if target_is_descendant {
+        return Err(AppError::Validation(format!(
+            "Category '{}' is a subcategory of '{}' and cannot absorb it",
+            target.name, source.name
+        )));
+    }
+
+    // Re-categorizing a transaction is a change to it, so closed periods apply
+    let earliest_closed_date = sqlx::query_scalar!(
+        r#"
+        SELECT MIN(t.transaction_date)
+        FROM transactions t
+        JOIN fiscal_periods fp
+            ON fp.tenant_id = t.tenant_id
+            AND fp.status = 'CLOSED'
+            AND t.transaction_date BETWEEN fp.start_date AND fp.end_date
+        WHERE t.tenant_id = $1
+            AND (
+                t.category_id = $2
+                OR EXISTS(SELECT 1 FROM transaction_splits ts WHERE ts.transaction_id = t.id AND ts.category_id = $2)
+            )
+        "#,
+        tenant_id,
+        source_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    if let Some(date) = earliest_closed_date {
+        fiscal_period::ensure_date_open(&mut *db_tx, tenant_id, updated_by_user_id, date).await?;
+    }
+
+    let transactions_moved = sqlx::query!(
+        r#"
+        UPDATE transactions
+        SET category_id = $3, updated_at = NOW(), updated_by = $4, version = version + 1
+        WHERE tenant_id = $1 AND category_id = $2
+        "#,
+        tenant_id,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    let splits_moved = sqlx::query!(
+        r#"
+        UPDATE transaction_splits ts
+        SET category_id = $3
+        FROM transactions t
+        WHERE t.id = ts.transaction_id AND t.tenant_id = $1 AND ts.category_id = $2
+        "#,
+        tenant_id,
+        source_id,
+        target_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    // A budget holds one line per category, so lines for both are folded together
+    let collisions = sqlx::query!(
+        r#"
+        SELECT
+            s.id as source_line_id, s.budgeted_amount as source_amount,
+            s.monthly_amounts as source_schedule,
+            t.id as target_line_id, t.budgeted_amount as target_amount,
+            t.monthly_amounts as target_schedule, t.is_active as target_active
+        FROM budget_line_items s
+        JOIN budgets b ON b.id = s.budget_id
+        JOIN budget_line_items t ON t.budget_id = s.budget_id AND t.category_id = $3
+        WHERE b.tenant_id = $1 AND s.category_id = $2 AND s.is_active = TRUE
+        FOR UPDATE OF s, t
+        "#,
+        tenant_id,
+        source_id,
+        target_id
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    let budget_line_items_folded = collisions.len() as u64;
+    for line in collisions {
+        let (budgeted_amount, monthly_amounts) = if !line.target_active {
+            (line.source_amount, line.source_schedule)
+        } else {
+            let schedule = match (&line.source_schedule, &line.target_schedule) {
+                (Some(source), Some(target)) => Some(add_schedules(source, target)),
+                (None, None) => None,
+                _ => {
+                    return Err(AppError::Conflict(format!(
+                        "Budget line items {} and {} are in the same budget but only one has a monthly schedule; give both or neither a schedule before merging",
+                        line.source_line_id, line.target_line_id
+                    )))
+                }
+            };
+            (line.source_amount + line.target_amount, schedule)
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE budget_line_items
+            SET
+                budgeted_amount = $2,
+                monthly_amounts = $3,
+                is_active = TRUE,
+                updated_at = NOW(),
+                updated_by = $4
+            WHERE id = $1
+            "#,
+            line.target_line_id,
+            budgeted_amount,
+            monthly_amounts,
+            updated_by_user_id
+        )
+        .execute(&mut *db_tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            UPDATE budget_line_items
+            SET is_active = FALSE, updated_at = NOW(), updated_by = $2
+            WHERE id = $1
+            "#,
+            line.source_line_id,
+            updated_by_user_id
+        )
+        .execute(&mut *db_tx)
+        .await?;
+    }
+
+    // Folded lines (and inactive lines that would collide) stay on the inactive source
+    let budget_line_items_moved = sqlx::query!(
+        r#"
+        UPDATE budget_line_items bli
+        SET category_id = $3, updated_at = NOW(), updated_by = $4
+        FROM budgets b
+        WHERE b.id = bli.budget_id
+            AND b.tenant_id = $1
+            AND bli.category_id = $2
+            AND NOT EXISTS(
+                SELECT 1 FROM budget_line_items t
+                WHERE t.budget_id = bli.budget_id AND t.category_id = $3
+            )
+        "#,
+        tenant_id,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    let recurring_transactions_moved = sqlx::query!(
+        r#"
+        UPDATE recurring_transactions
+        SET category_id = $3, updated_at = NOW(), updated_by = $4
+        WHERE tenant_id = $1 AND category_id = $2
+        "#,
+        tenant_id,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    let merchant_rules_moved = sqlx::query!(
+        r#"
+        UPDATE merchant_rules
+        SET category_id = $3, updated_at = NOW(), updated_by = $4
+        WHERE tenant_id = $1 AND category_id = $2
+        "#,
+        tenant_id,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    // Staged rows have no tenant column; the source was checked to be the tenant's above
+    sqlx::query!(
+        r#"
+        UPDATE external_transactions_staging
+        SET merchant_category_id = $2, updated_at = NOW(), updated_by = $3
+        WHERE merchant_category_id = $1
+        "#,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    let subcategories_moved = sqlx::query!(
+        r#"
+        UPDATE categories
+        SET parent_category_id = $3, updated_at = NOW(), updated_by = $4
+        WHERE tenant_id = $1 AND parent_category_id = $2
+        "#,
+        tenant_id,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    let custom_reports_updated = sqlx::query!(
+        r#"
+        UPDATE custom_reports
+        SET
+            configuration = jsonb_set(
+                configuration,
+                '{category_ids}',
+                (
+                    SELECT jsonb_agg(DISTINCT CASE WHEN e = ($2::uuid)::text THEN ($3::uuid)::text ELSE e END)
+                    FROM jsonb_array_elements_text(configuration->'category_ids') e
+                )
+            ),
+            updated_at = NOW(),
+            updated_by = $4
+        WHERE tenant_id = $1 AND configuration->'category_ids' ? ($2::uuid)::text
+        "#,
+        tenant_id,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    let source = query_as!(
+        Category,
+        r#"
+        UPDATE categories
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING
+            id, tenant_id, name, description, type as "r#type!: CategoryType",
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        "#,
+        source_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    db_tx.commit().await?;
+
+    info!(
+        "Service: Merged category ID: {} into {}: {} transactions, {} budget line items moved",
+        source_id, target_id, transactions_moved, budget_line_items_moved
+    );
+    Ok(CategoryMergeResult {
+        source,
+        target,
+        transactions_moved,
+        splits_moved,
+        budget_line_items_moved,
+        budget_line_items_folded,
+        recurring_transactions_moved,
+        merchant_rules_moved,
+        subcategories_moved,
+        custom_reports_updated,
+    })
+}
+
+/// Archives a category: it stays in reports and drill-downs but is hidden from pickers and
+/// can no longer be assigned. Its unarchived subcategories must be archived first.
+pub async fn archive_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    category_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<Category, AppError> {
+    info!(
+        "Service: Archiving category with ID: {} for tenant ID: {}",
+        category_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    let has_unarchived_children = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM categories
+            WHERE tenant_id = $1 AND parent_category_id = $2 AND is_active = TRUE AND archived_at IS NULL
+        ) as "exists!"
+        "#,
+        tenant_id,
+        category_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    if has_unarchived_children {
+        return Err(AppError::Conflict(format!(
+            "Category {} has subcategories that are not archived; archive them first",
+            category_id
+        )));
+    }
+
+    let category = query_as!(
+        Category,
+        r#"
+        UPDATE categories
+        SET archived_at = NOW(), updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE AND archived_at IS NULL
+        RETURNING
+            id, tenant_id, name, description, type as "r#type!: CategoryType",
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        "#,
+        category_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Category with ID {} not found or already archived for tenant {}",
+            category_id, tenant_id
+        ))
+    })?;
+    db_tx.commit().await?;
+
+    Ok(category)
+}
+
+/// Unarchives a category, making it assignable again. Its parent must not be archived.
+pub async fn unarchive_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    category_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<Category, AppError> {
+    info!(
+        "Service: Unarchiving category with ID: {} for tenant ID: {}",
+        category_id, tenant_id
+    );
+
+    let category = query_as!(
+        Category,
+        r#"
+        UPDATE categories c
+        SET archived_at = NULL, updated_at = NOW(), updated_by = $3
+        WHERE c.id = $1
+            AND c.tenant_id = $2
+            AND c.is_active = TRUE
+            AND c.archived_at IS NOT NULL
+            AND NOT EXISTS(
+                SELECT 1 FROM categories parent
+                WHERE parent.id = c.parent_category_id AND parent.archived_at IS NOT NULL
+            )
+        RETURNING
+            c.id, c.tenant_id, c.name, c.description, c.type as "r#type!: CategoryType",
+            c.parent_category_id, c.is_active, c.archived_at, c.created_at, c.created_by, c.updated_at, c.updated_by
+        "#,
+        category_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Archived category with ID {} not found for tenant {}, or its parent is archived",
+            category_id, tenant_id
+        ))
+    })?;
+
+    Ok(category)
+}
+
+/// Restores a deactivated category. Subcategories deactivated with it stay deactivated and
+/// are restored one by one. Requires `records.restore`.
+pub async fn restore_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    category_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<Category, AppError> {
+    info!(
+        "Service: Restoring category with ID: {} for tenant ID: {}",
+        category_id, tenant_id
+    );
+
+    permission::require_permission(pool, tenant_id, updated_by_user_id, RECORDS_RESTORE).await?;
+
+    let category = query_as!(
+        Category,
+        r#"
+        UPDATE categories
+        SET is_active = TRUE, updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND is_active = FALSE
+        RETURNING
+            id, tenant_id, name, description, type as "r#type!: CategoryType",
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        "#,
+        category_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Deactivated category with ID {} not found for tenant {}",
+            category_id, tenant_id
+        ))
+    })?;
+
+    Ok(category)
+}
+
+/// Archives every category created more than `unused_for_months` ago that no transaction
+/// or split line dated since (or still a draft) uses, and that no active recurring
+/// transaction, merchant rule or current budget line points at. A parent is only archived
+/// together with all its unarchived subcategories. With `dry_run` the categories are only
+/// listed.
+pub async fn archive_unused_categories(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: ArchiveUnusedDto,
+) -> Result<Vec<Category>, AppError> {
+    info!(
+        "Service: Archiving categories unused for {} months for tenant ID: {}",
+        dto.unused_for_months, tenant_id
+    );
+
+    let cutoff = Utc::now()
+        .checked_sub_months(Months::new(dto.unused_for_months))
+        .ok_or_else(|| AppError::Validation("unused_for_months is out of range".to_string()))?;
+
+    let mut db_tx = pool.begin().await?;
+    let mut candidates = query_as!(
+        Category,
+        r#"
+        SELECT
+            c.id, c.tenant_id, c.name, c.description, c.type as "r#type!: CategoryType",
+            c.parent_category_id, c.is_active, c.archived_at, c.created_at, c.created_by, c.updated_at, c.updated_by
+        FROM categories c
+        WHERE c.tenant_id = $1
+            AND c.is_active = TRUE
+            AND c.archived_at IS NULL
+            AND c.created_at < $2
+            AND NOT EXISTS(
+                SELECT 1
+                FROM transactions t
+                WHERE t.tenant_id = $1
+                    AND (t.transaction_date >= $2::date OR t.status IN ('DRAFT', 'PENDING_APPROVAL'))
+                    AND (
+                        t.category_id = c.id
+                        OR EXISTS(SELECT 1 FROM transaction_splits ts WHERE ts.transaction_id = t.id AND ts.category_id = c.id)
+                    )
+            )
+            AND NOT EXISTS(
+                SELECT 1 FROM recurring_transactions rt
+                WHERE rt.tenant_id = $1 AND rt.category_id = c.id AND rt.is_active = TRUE
+            )
+            AND NOT EXISTS(SELECT 1 FROM merchant_rules mr WHERE mr.tenant_id = $1 AND mr.category_id = c.id)
+            AND NOT EXISTS(
+                SELECT 1
+                FROM budget_line_items bli
+                JOIN budgets b ON b.id = bli.budget_id
+                WHERE b.tenant_id = $1 AND bli.category_id = c.id AND bli.is_active = TRUE AND b.end_date >= CURRENT_DATE
+            )
+        ORDER BY c.name
+        FOR UPDATE OF c
+        "#,
+        tenant_id,
+        cutoff
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    // Archived categories are left out of the tree, so a parent may only go with all of its
+    // subcategories; dropping one candidate can disqualify its parent in turn
+    let children = sqlx::query!(
+        r#"
+        SELECT id, parent_category_id as "parent_category_id!"
+        FROM categories
+        WHERE tenant_id = $1 AND is_active = TRUE AND archived_at IS NULL AND parent_category_id IS NOT NULL
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    loop {
+        let candidate_ids: HashSet<Uuid> = candidates.iter().map(|category| category.id).collect();
+        let blocked: HashSet<Uuid> = children
+            .iter()
+            .filter(|child| !candidate_ids.contains(&child.id))
+            .map(|child| child.parent_category_id)
+            .collect();
+        let before = candidates.len();
+        candidates.retain(|category| !blocked.contains(&category.id));
+        if candidates.len() == before {
+            break;
+        }
+    }
+
+    if dto.dry_run || candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    let ids: Vec<Uuid> = candidates.iter().map(|category| category.id).collect();
+    let archived = query_as!(
+        Category,
+        r#"
+        UPDATE categories
+        SET archived_at = NOW(), updated_at = NOW(), updated_by = $3
+        WHERE tenant_id = $1 AND id = ANY($2)
+        RETURNING
+            id, tenant_id, name, description, type as "r#type!: CategoryType",
+            parent_category_id, is_active, archived_at, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        &ids,
+        updated_by_user_id
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    db_tx.commit().await?;
+
+    info!(
+        "Service: Archived {} unused categories for tenant ID: {}",
+        archived.len(),
+        tenant_id
+    );
+    Ok(archived)
+}
+
+/// Checks that a category can be given to a new or changed transaction: it belongs to the
+/// tenant, is active and is not archived.
+pub async fn ensure_assignable<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    category_id: Uuid,
+) -> Result<(), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let assignable = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM categories
+            WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE AND archived_at IS NULL
+        ) as "exists!"
+        "#,
+        category_id,
+        tenant_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    if !assignable {
+        return Err(AppError::Validation(format!(
+            "Category ID {} is invalid, inactive or archived for tenant {}",
+            category_id, tenant_id
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that `parent_id` is an active, unarchived category of the tenant and, when
+/// re-parenting `category_id`, that it is neither the category itself nor one of its
+/// descendants.
+async fn validate_parent<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    category_id: Option<Uuid>,
+    parent_id: Uuid,
+) -> Result<(), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    if category_id == Some(parent_id) {
+        return Err(AppError::Validation(
+            "A category cannot be its own parent".to_string(),
+        ));
+    }
+
+    // Walks up from the parent; UNION stops on cycles already in the data
+    let parent = sqlx::query!(
+        r#"
+        WITH RECURSIVE ancestors(id, parent_category_id) AS (
+            SELECT id, parent_category_id FROM categories WHERE id = $1 AND tenant_id = $2
+            UNION
+            SELECT c.id, c.parent_category_id
+            FROM categories c
+            JOIN ancestors a ON c.id = a.parent_category_id
+            WHERE c.tenant_id = $2
+        )
+        SELECT
+            EXISTS(
+                SELECT 1 FROM categories
+                WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE AND archived_at IS NULL
+            ) as "is_active!",
+            EXISTS(SELECT 1 FROM ancestors WHERE id = $3) as "is_descendant!"
+        "#,
+        parent_id,
+        tenant_id,
+        category_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    if !parent.is_active {
+        return Err(AppError::Validation(format!(
+            "Parent category {} not found or archived",
+            parent_id
+        )));
+    }
+    if parent.is_descendant {
+        return Err(AppError::Validation(format!(
+            "Category {} is a subcategory of this category and cannot be its parent",
+            parent_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Adds two `monthly_amounts` schedules month by month.
+fn add_schedules(a: &JsonValue, b: &JsonValue) -> JsonValue {
+    let mut months = budget_line_item::schedule_from_json(a);
+    for (month, amount) in budget_line_item::schedule_from_json(b) {
+        *months.entry(month).or_default() += amount;
+    }
+    JsonValue::Object(
+        months
+            .into_iter()
+            .map(|(month, amount)| {
+                (
+                    month.format("%Y-%m").to_string(),
+                    JsonValue::String(amount.round_dp(2).to_string()),
+                )
+            })
+            .collect(),
+    )
+}
@@ -9,10 +9,18 @@ use crate::{
         currency::Currency,
         dto::currency_dto::{CreateCurrencyDto, UpdateCurrencyDto},
     },
+    services::{
+        permission,
+        reference_cache::{self, ReferenceData},
+    },
+    utils::update_builder::UpdateBuilder,
 };
 
 /// Retrieves a list of all active currencies.
 pub async fn list_currencies(pool: &PgPool) -> Result<Vec<Currency>, AppError> {
+    if let Some(currencies) = reference_cache::currencies().get(&()).await {
+        return Ok(currencies);
+    }
     info!("Service: Listing all active currencies.");
 
     let currencies = query_as!(
@@ -28,6 +36,7 @@ pub async fn list_currencies(pool: &PgPool) -> Result<Vec<Currency>, AppError> {
     )
     .fetch_all(pool)
     .await?;
+    reference_cache::currencies().insert((), currencies.clone()).await;
 
     Ok(currencies)
 }
@@ -36,26 +45,15 @@ pub async fn list_currencies(pool: &PgPool) -> Result<Vec<Currency>, AppError> {
 pub async fn get_currency_by_code(pool: &PgPool, code: &str) -> Result<Currency, AppError> {
     info!("Service: Getting currency with code: {}", code);
 
-    let currency = query_as!(
-        Currency,
-        r#"
-        SELECT
-            code, name, symbol, is_active,
-            created_at, created_by, updated_at, updated_by
-        FROM currencies
-        WHERE code = $1 AND is_active = TRUE
-        "#,
-        code
-    )
-    .fetch_optional(pool)
-    .await?
-    .ok_or_else(|| AppError::NotFound(format!("Currency with code {} not found", code)))?;
-
-    Ok(currency)
+    list_currencies(pool)
+        .await?
+        .into_iter()
+        .find(|currency| currency.code == code)
+        .ok_or_else(|| AppError::NotFound(format!("Currency with code {} not found", code)))
 }
 
 /// Creates a new currency.
-/// `created_by_user_id` should come from an authenticated system administrator.
+/// `created_by_user_id` must be a system administrator.
 pub async fn create_currency(
     pool: &PgPool,
     created_by_user_id: Uuid,
@@ -63,6 +61,8 @@ pub async fn create_currency(
 ) -> Result<Currency, AppError> {
     info!("Service: Creating new currency with code: {}", dto.code);
 
+    permission::require_system_admin(pool, created_by_user_id).await?;
+
     let new_currency = query_as!(
         Currency,
         r#"
@@ -80,12 +80,13 @@ pub async fn create_currency(
     )
     .fetch_one(pool)
     .await?;
+    reference_cache::changed(pool, ReferenceData::Currencies).await;
 
     Ok(new_currency)
 }
 
 /// Updates an existing currency.
-/// `updated_by_user_id` should come from an authenticated system administrator.
+/// `updated_by_user_id` must be a system administrator.
 pub async fn update_currency(
     pool: &PgPool,
     code: &str,
@@ -94,66 +95,38 @@ pub async fn update_currency(
 ) -> Result<Currency, AppError> {
     info!("Service: Updating currency with code: {}", code);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
-
-    if let Some(name) = dto.name {
-        update_cols.push(format!("name = ${}", param_idx));
-        update_values.push(Box::new(name));
-        param_idx += 1;
-    }
-    if let Some(symbol) = dto.symbol {
-        update_cols.push(format!("symbol = ${}", param_idx));
-        update_values.push(Box::new(symbol));
-        param_idx += 1;
-    }
-    if let Some(is_active) = dto.is_active {
-        update_cols.push(format!("is_active = ${}", param_idx));
-        update_values.push(Box::new(is_active));
-        param_idx += 1;
-    }
-
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
+    permission::require_system_admin(pool, updated_by_user_id).await?;
 
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
+    let mut update = UpdateBuilder::new("currencies");
+    update
+        .set("name", dto.name)
+        .set("symbol", dto.symbol)
+        .set("is_active", dto.is_active);
+    if update.is_empty() {
+        return Err(AppError::Validation("No fields provided for update".to_string()));
     }
 
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE code = ").push_bind(code);
+    query.push(
         r#"
-        UPDATE currencies
-        SET {}
-        WHERE code = ${}
         RETURNING
             code, name, symbol, is_active, created_at, created_by, updated_at, updated_by
         "#,
-        update_clause, param_idx // code will be the last parameter
     );
 
-    let mut query = sqlx::query_as::<_, Currency>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
-    }
-    // Bind code last
-    query = query.bind(code.to_string()); // Bind String explicitly
-
     let updated_currency = query
+        .build_query_as::<Currency>()
         .fetch_optional(pool)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Currency with code {} not found", code)))?;
+    reference_cache::changed(pool, ReferenceData::Currencies).await;
 
     Ok(updated_currency)
 }
 
 /// Deactivates a currency (soft delete).
-/// `updated_by_user_id` should come from an authenticated system administrator.
+/// `updated_by_user_id` must be a system administrator.
 pub async fn deactivate_currency(
     pool: &PgPool,
     code: &str,
@@ -161,6 +134,8 @@ pub async fn deactivate_currency(
 ) -> Result<(), AppError> {
     info!("Service: Deactivating currency with code: {}", code);
 
+    permission::require_system_admin(pool, updated_by_user_id).await?;
+
     let affected_rows = sqlx::query!(
         r#"
         UPDATE currencies
@@ -180,6 +155,7 @@ pub async fn deactivate_currency(
     if affected_rows == 0 {
         return Err(AppError::NotFound(format!("Currency with code {} not found or already inactive", code)));
     }
+    reference_cache::changed(pool, ReferenceData::Currencies).await;
 
     Ok(())
 }
\ No newline at end of file
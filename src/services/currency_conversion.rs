@@ -0,0 +1,419 @@
+//! Currency conversion against the stored exchange rates.
+//!
+//! A rate from one currency to another on a date is looked up in this order:
+//!
+//! 1. the rate for the pair on that exact date,
+//! 2. the most recent rate for the pair before that date,
+//! 3. the reciprocal of the rate for the opposite pair (exact date or earlier),
+//! 4. triangulation through the tenant's base currency (each leg resolved as in 1-3).
+//!
+//! Tenant rates win over system-wide rates published on the same date. Journal entries
+//! store their amount in the tenant's base currency as `converted_amount`; reports and
+//! the balance check read `COALESCE(converted_amount, amount)`, so every entry written in
+//! a foreign currency goes through `base_currency_amounts`.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::PgExecutor;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::exchange_rate::{Conversion, ConversionRate, RateMethod},
+};
+
+/// Decimal places of converted amounts (matches `journal_entries.converted_amount NUMERIC(18, 2)`).
+const AMOUNT_SCALE: u32 = 2;
+/// Significant digits kept for inverse and triangulated rates. Fixed decimal places would
+/// lose most of a small rate such as IDR to USD (0.0000615...), and the legs of a
+/// triangulation are multiplied before rounding for the same reason.
+const DERIVED_RATE_DIGITS: u32 = 12;
+
+/// Latest stored rate per `(base, target)` pair, with its date.
+type RateTable = HashMap<(String, String), (Decimal, NaiveDate)>;
+
+/// Finds the best rate from `from` to `to` on `date`.
+pub async fn find_rate<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+) -> Result<ConversionRate, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let (tenant_base, rates) = load_rates(executor, tenant_id, from, to, date).await?;
+    resolve_rate(&rates, &tenant_base, from, to, date).ok_or_else(|| no_rate(from, to, date))
+}
+
+/// Converts `amount` from `from` to `to` with the best rate on `date`.
+pub async fn convert<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    amount: Decimal,
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+) -> Result<Conversion, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    info!(
+        "Service: Converting {} {} to {} as of {} for tenant ID: {}",
+        amount, from, to, date, tenant_id
+    );
+
+    let rate = find_rate(
+        executor,
+        tenant_id,
+        &from.to_uppercase(),
+        &to.to_uppercase(),
+        date,
+    )
+    .await?;
+    Ok(Conversion {
+        amount,
+        converted_amount: (amount * rate.rate).round_dp(AMOUNT_SCALE),
+        rate,
+    })
+}
+
+/// Works out `(exchange_rate, converted_amount)` for a journal entry, converting into the
+/// tenant's base currency.
+///
+/// An explicit `converted_amount` is kept as is, and an explicit `exchange_rate` is applied
+/// to the amount (e.g. the rate the bank actually charged). Otherwise the rate is looked up;
+/// entries already in the base currency get `(None, None)`.
+pub async fn base_currency_amounts<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    amount: Decimal,
+    currency_code: &str,
+    date: NaiveDate,
+    exchange_rate: Option<Decimal>,
+    converted_amount: Option<Decimal>,
+) -> Result<(Option<Decimal>, Option<Decimal>), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let base_rate = match (exchange_rate, converted_amount) {
+        (None, None) => base_currency_rate(executor, tenant_id, currency_code, date).await?,
+        _ => None,
+    };
+    Ok(amounts_with_rate(
+        amount,
+        exchange_rate,
+        converted_amount,
+        base_rate,
+    ))
+}
+
+/// The rate from `currency_code` into the tenant's base currency on `date`, or `None` for
+/// the base currency itself. Lets callers converting many entries look each currency up once.
+pub async fn base_currency_rate<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    currency_code: &str,
+    date: NaiveDate,
+) -> Result<Option<Decimal>, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let currency_code = currency_code.to_uppercase();
+    let (tenant_base, rates) =
+        load_rates(executor, tenant_id, &currency_code, &currency_code, date).await?;
+    let rate = resolve_rate(&rates, &tenant_base, &currency_code, &tenant_base, date)
+        .ok_or_else(|| no_rate(&currency_code, &tenant_base, date))?;
+    if rate.method == RateMethod::SameCurrency {
+        return Ok(None);
+    }
+    Ok(Some(rate.rate))
+}
+
+/// `base_currency_amounts` with the looked-up rate (from `base_currency_rate`) already known;
+/// `base_rate` is only used when neither an exchange rate nor a converted amount is given.
+pub fn amounts_with_rate(
+    amount: Decimal,
+    exchange_rate: Option<Decimal>,
+    converted_amount: Option<Decimal>,
+    base_rate: Option<Decimal>,
+) -> (Option<Decimal>, Option<Decimal>) {
+    match (exchange_rate, converted_amount) {
+        (rate, Some(converted)) => (rate, Some(converted)),
+        (Some(rate), None) => (Some(rate), Some((amount * rate).round_dp(AMOUNT_SCALE))),
+        (None, None) => match base_rate {
+            Some(rate) => (Some(rate), Some((amount * rate).round_dp(AMOUNT_SCALE))),
+            None => (None, None),
+        },
+    }
+}
+
+/// Loads the tenant's base currency and the latest rate on or before `date` for every pair
+/// among `from`, `to` and the base currency, in one round trip.
+async fn load_rates<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+) -> Result<(String, RateTable), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            t.base_currency_code as "tenant_base!",
+            r.base_currency_code as "base?", r.target_currency_code as "target?",
+            r.rate as "rate?", r.rate_date as "rate_date?"
+        FROM tenants t
+        LEFT JOIN LATERAL (
+            SELECT DISTINCT ON (er.base_currency_code, er.target_currency_code)
+                er.base_currency_code, er.target_currency_code, er.rate, er.rate_date
+            FROM exchange_rates er
+            WHERE (er.tenant_id = t.id OR er.tenant_id IS NULL)
+              AND er.base_currency_code IN ($2, $3, t.base_currency_code)
+              AND er.target_currency_code IN ($2, $3, t.base_currency_code)
+              AND er.rate_date <= $4
+            ORDER BY
+                er.base_currency_code, er.target_currency_code,
+                er.rate_date DESC, er.tenant_id IS NULL, er.updated_at DESC
+        ) r ON TRUE
+        WHERE t.id = $1
+        "#,
+        tenant_id,
+        from,
+        to,
+        date
+    )
+    .fetch_all(executor)
+    .await?;
+
+    let tenant_base = rows
+        .first()
+        .map(|row| row.tenant_base.clone())
+        .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+    let rates = rows
+        .into_iter()
+        .filter_map(|row| Some(((row.base?, row.target?), (row.rate?, row.rate_date?))))
+        .collect();
+
+    Ok((tenant_base, rates))
+}
+
+/// Applies the fallback chain described in the module docs.
+fn resolve_rate(
+    rates: &RateTable,
+    tenant_base: &str,
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+) -> Option<ConversionRate> {
+    let conversion_rate =
+        |rate: Decimal, rate_date: Option<NaiveDate>, method: RateMethod| ConversionRate {
+            from_currency_code: from.to_string(),
+            to_currency_code: to.to_string(),
+            rate,
+            rate_date,
+            method,
+        };
+
+    if from == to {
+        return Some(conversion_rate(
+            Decimal::ONE,
+            None,
+            RateMethod::SameCurrency,
+        ));
+    }
+    if let Some((rate, method, rate_date)) = pair_rate(rates, from, to, date) {
+        let rate = match method {
+            RateMethod::Inverse => derived_rate(rate),
+            _ => rate,
+        };
+        return Some(conversion_rate(rate, Some(rate_date), method));
+    }
+    if from == tenant_base || to == tenant_base {
+        return None;
+    }
+
+    let (to_base, _, to_base_date) = pair_rate(rates, from, tenant_base, date)?;
+    let (from_base, _, from_base_date) = pair_rate(rates, tenant_base, to, date)?;
+    Some(conversion_rate(
+        derived_rate(to_base * from_base),
+        Some(to_base_date.min(from_base_date)),
+        RateMethod::Triangulated,
+    ))
+}
+
+fn derived_rate(rate: Decimal) -> Decimal {
+    rate.round_sf(DERIVED_RATE_DIGITS)
+        .unwrap_or(rate)
+        .normalize()
+}
+
+/// A direct or inverse rate for one pair, with how it was found and its date. Inverse
+/// rates are not rounded here; see `DERIVED_RATE_DIGITS`.
+fn pair_rate(
+    rates: &RateTable,
+    from: &str,
+    to: &str,
+    date: NaiveDate,
+) -> Option<(Decimal, RateMethod, NaiveDate)> {
+    if let Some(&(rate, rate_date)) = rates.get(&(from.to_string(), to.to_string())) {
+        let method = if rate_date == date {
+            RateMethod::ExactDate
+        } else {
+            RateMethod::PriorDate
+        };
+        return Some((rate, method, rate_date));
+    }
+    let &(rate, rate_date) = rates.get(&(to.to_string(), from.to_string()))?;
+    if rate.is_zero() {
+        return None;
+    }
+    Some((Decimal::ONE / rate, RateMethod::Inverse, rate_date))
+}
+
+fn no_rate(from: &str, to: &str, date: NaiveDate) -> AppError {
+    AppError::Validation(format!(
+        "No exchange rate from {} to {} on or before {}; add one or enable automatic rates",
+        from, to, date
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(amount: &str) -> Decimal {
+        amount.parse().unwrap()
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        date.parse().unwrap()
+    }
+
+    fn table(rates: &[(&str, &str, &str, &str)]) -> RateTable {
+        rates
+            .iter()
+            .map(|&(base, target, rate, date)| {
+                (
+                    (base.to_string(), target.to_string()),
+                    (money(rate), day(date)),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_currency_needs_no_rate() {
+        let rate = resolve_rate(&RateTable::new(), "USD", "EUR", "EUR", day("2025-03-05")).unwrap();
+        assert_eq!(rate.rate, Decimal::ONE);
+        assert_eq!(rate.rate_date, None);
+        assert_eq!(rate.method, RateMethod::SameCurrency);
+    }
+
+    #[test]
+    fn stored_rates_are_used_as_is_and_dated() {
+        let rates = table(&[("EUR", "USD", "1.083417", "2025-03-01")]);
+        let cases = [
+            ("2025-03-01", RateMethod::ExactDate),
+            ("2025-03-05", RateMethod::PriorDate),
+        ];
+        for (date, method) in cases {
+            let rate = resolve_rate(&rates, "USD", "EUR", "USD", day(date)).unwrap();
+            assert_eq!(rate.rate, money("1.083417"), "{}", date);
+            assert_eq!(rate.rate_date, Some(day("2025-03-01")), "{}", date);
+            assert_eq!(rate.method, method, "{}", date);
+        }
+    }
+
+    #[test]
+    fn inverse_rates_keep_their_precision() {
+        let rates = table(&[("USD", "IDR", "16250", "2025-03-01")]);
+        let rate = resolve_rate(&rates, "USD", "IDR", "USD", day("2025-03-05")).unwrap();
+        assert_eq!(rate.method, RateMethod::Inverse);
+        assert_eq!(rate.rate_date, Some(day("2025-03-01")));
+        assert_eq!(rate.rate, money("0.0000615384615385"));
+        // Rounded to six places the rate would be 0.000062 and this would come to 62.00.
+        let (_, converted) = amounts_with_rate(money("1000000"), None, None, Some(rate.rate));
+        assert_eq!(converted, Some(money("61.54")));
+    }
+
+    #[test]
+    fn zero_rates_are_not_inverted() {
+        let rates = table(&[("USD", "IDR", "0", "2025-03-01")]);
+        assert!(resolve_rate(&rates, "USD", "IDR", "USD", day("2025-03-05")).is_none());
+    }
+
+    #[test]
+    fn triangulates_through_the_base_currency() {
+        let rates = table(&[
+            ("EUR", "USD", "1.08", "2025-03-04"),
+            ("USD", "JPY", "150", "2025-03-02"),
+        ]);
+        let rate = resolve_rate(&rates, "USD", "EUR", "JPY", day("2025-03-05")).unwrap();
+        assert_eq!(rate.method, RateMethod::Triangulated);
+        assert_eq!(rate.rate, money("162"));
+        // The oldest leg dates the rate.
+        assert_eq!(rate.rate_date, Some(day("2025-03-02")));
+    }
+
+    #[test]
+    fn triangulates_through_inverse_legs_before_rounding() {
+        let rates = table(&[
+            ("USD", "IDR", "16250", "2025-03-01"),
+            ("USD", "JPY", "150", "2025-03-01"),
+        ]);
+        let rate = resolve_rate(&rates, "USD", "IDR", "JPY", day("2025-03-05")).unwrap();
+        assert_eq!(rate.method, RateMethod::Triangulated);
+        // 150 / 16250; rounding the IDR leg first would give 0.0093.
+        assert_eq!(rate.rate, money("0.00923076923077"));
+    }
+
+    #[test]
+    fn does_not_triangulate_from_or_to_the_base_currency() {
+        let rates = table(&[("EUR", "USD", "1.08", "2025-03-01")]);
+        for (from, to) in [("USD", "JPY"), ("JPY", "USD"), ("EUR", "JPY")] {
+            assert!(
+                resolve_rate(&rates, "USD", from, to, day("2025-03-05")).is_none(),
+                "{} to {}",
+                from,
+                to
+            );
+        }
+    }
+
+    #[test]
+    fn given_amounts_take_precedence_over_the_looked_up_rate() {
+        let amount = money("100.00");
+        let base_rate = Some(money("1.25"));
+        let cases = [
+            (None, None, Some(money("1.25")), Some(money("125.00"))),
+            (
+                Some(money("1.1")),
+                None,
+                Some(money("1.1")),
+                Some(money("110.00")),
+            ),
+            (None, Some(money("120.00")), None, Some(money("120.00"))),
+            (
+                Some(money("1.1")),
+                Some(money("120.00")),
+                Some(money("1.1")),
+                Some(money("120.00")),
+            ),
+        ];
+        for (exchange_rate, converted_amount, expected_rate, expected_amount) in cases {
+            assert_eq!(
+                amounts_with_rate(amount, exchange_rate, converted_amount, base_rate),
+                (expected_rate, expected_amount)
+            );
+        }
+        assert_eq!(amounts_with_rate(amount, None, None, None), (None, None));
+    }
+}
@@ -0,0 +1,261 @@
+//! Saved report definitions. A report is visible to its owner and, when public, to every
+//! member of the tenant; only the owner can change or delete it.
+
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        custom_report::{CustomReport, CustomReportConfiguration, CustomReportType},
+        dto::custom_report_dto::{CreateCustomReportDto, UpdateCustomReportDto},
+    },
+    services::budget,
+};
+
+/// Retrieves the reports the current user can see in a tenant.
+pub async fn list_custom_reports(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<CustomReport>, AppError> {
+    info!(
+        "Service: Listing custom reports for user ID: {} in tenant ID: {}",
+        user_id, tenant_id
+    );
+
+    let reports = query_as!(
+        CustomReport,
+        r#"
+        SELECT
+            id, tenant_id, user_id, name, description, report_type, configuration, is_public,
+            created_at, created_by, updated_at, updated_by
+        FROM custom_reports
+        WHERE tenant_id = $1 AND (user_id = $2 OR is_public)
+        ORDER BY name
+        "#,
+        tenant_id,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(reports)
+}
+
+/// Retrieves a report the current user can see.
+pub async fn get_custom_report_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+) -> Result<CustomReport, AppError> {
+    info!(
+        "Service: Getting custom report with ID: {} for tenant ID: {}",
+        report_id, tenant_id
+    );
+
+    let report = query_as!(
+        CustomReport,
+        r#"
+        SELECT
+            id, tenant_id, user_id, name, description, report_type, configuration, is_public,
+            created_at, created_by, updated_at, updated_by
+        FROM custom_reports
+        WHERE id = $1 AND tenant_id = $2 AND (user_id = $3 OR is_public)
+        "#,
+        report_id,
+        tenant_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Custom report with ID {} not found", report_id)))?;
+
+    Ok(report)
+}
+
+/// Creates a report owned by the current user.
+pub async fn create_custom_report(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: CreateCustomReportDto,
+) -> Result<CustomReport, AppError> {
+    info!(
+        "Service: Creating custom report '{}' for tenant ID: {}",
+        dto.name, tenant_id
+    );
+
+    dto.validate()?;
+    validate_configuration(pool, tenant_id, dto.report_type, &dto.configuration).await?;
+
+    let new_report = query_as!(
+        CustomReport,
+        r#"
+        INSERT INTO custom_reports (
+            tenant_id, user_id, name, description, report_type, configuration, is_public,
+            created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $2, $2)
+        RETURNING
+            id, tenant_id, user_id, name, description, report_type, configuration, is_public,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        user_id,
+        dto.name,
+        dto.description,
+        String::from(dto.report_type),
+        to_json(&dto.configuration)?,
+        dto.is_public.unwrap_or(false)
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(new_report)
+}
+
+/// Updates one of the current user's reports. The report type cannot change.
+pub async fn update_custom_report(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+    dto: UpdateCustomReportDto,
+) -> Result<CustomReport, AppError> {
+    info!(
+        "Service: Updating custom report with ID: {} for tenant ID: {}",
+        report_id, tenant_id
+    );
+
+    dto.validate()?;
+
+    let existing = get_owned_report(pool, tenant_id, user_id, report_id).await?;
+    let configuration = match &dto.configuration {
+        Some(configuration) => {
+            let report_type = parse_report_type(&existing)?;
+            validate_configuration(pool, tenant_id, report_type, configuration).await?;
+            Some(to_json(configuration)?)
+        }
+        None => None,
+    };
+
+    let updated_report = query_as!(
+        CustomReport,
+        r#"
+        UPDATE custom_reports
+        SET
+            name = COALESCE($4, name),
+            description = COALESCE($5, description),
+            configuration = COALESCE($6, configuration),
+            is_public = COALESCE($7, is_public),
+            updated_at = NOW(),
+            updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND user_id = $3
+        RETURNING
+            id, tenant_id, user_id, name, description, report_type, configuration, is_public,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        report_id,
+        tenant_id,
+        user_id,
+        dto.name,
+        dto.description,
+        configuration,
+        dto.is_public
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(updated_report)
+}
+
+/// Deletes one of the current user's reports together with its schedules.
+pub async fn delete_custom_report(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting custom report with ID: {} for tenant ID: {}",
+        report_id, tenant_id
+    );
+
+    let result = sqlx::query!(
+        "DELETE FROM custom_reports WHERE id = $1 AND tenant_id = $2 AND user_id = $3",
+        report_id,
+        tenant_id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!(
+            "Custom report with ID {} not found",
+            report_id
+        )));
+    }
+    Ok(())
+}
+
+/// Parses a stored report's type.
+pub fn parse_report_type(report: &CustomReport) -> Result<CustomReportType, AppError> {
+    report
+        .report_type
+        .parse()
+        .map_err(AppError::InternalServerError)
+}
+
+/// Parses a stored report's configuration.
+pub fn parse_configuration(report: &CustomReport) -> Result<CustomReportConfiguration, AppError> {
+    serde_json::from_value(report.configuration.clone()).map_err(|e| {
+        AppError::InternalServerError(format!(
+            "Custom report {} has an invalid configuration: {}",
+            report.id, e
+        ))
+    })
+}
+
+/// Retrieves a report only if the current user owns it (public reports of others are read-only).
+async fn get_owned_report(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+) -> Result<CustomReport, AppError> {
+    let report = get_custom_report_by_id(pool, tenant_id, user_id, report_id).await?;
+    if report.user_id != user_id {
+        return Err(AppError::Forbidden(
+            "Only the owner can change a custom report".to_string(),
+        ));
+    }
+    Ok(report)
+}
+
+async fn validate_configuration(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    report_type: CustomReportType,
+    configuration: &CustomReportConfiguration,
+) -> Result<(), AppError> {
+    if report_type == CustomReportType::BudgetVsActual {
+        let budget_id = configuration.budget_id.ok_or_else(|| {
+            AppError::Validation(
+                "BUDGET_VS_ACTUAL reports need configuration.budget_id".to_string(),
+            )
+        })?;
+        budget::get_budget_by_id(pool, tenant_id, budget_id).await?;
+    }
+    Ok(())
+}
+
+fn to_json(configuration: &CustomReportConfiguration) -> Result<serde_json::Value, AppError> {
+    serde_json::to_value(configuration).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to serialize configuration: {}", e))
+    })
+}
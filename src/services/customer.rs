@@ -0,0 +1,210 @@
+//! Customers: the people and businesses a tenant invoices.
+//!
+//! Customers are deactivated rather than deleted, so their invoices keep pointing at them.
+
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::invoice_dto::{CreateCustomerDto, UpdateCustomerDto},
+        invoice::Customer,
+    },
+    utils::update_builder::UpdateBuilder,
+};
+
+/// Retrieves the active customers of a tenant, by name.
+pub async fn list_customers(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<Customer>, AppError> {
+    info!("Service: Listing customers for tenant ID: {}", tenant_id);
+
+    let customers = query_as!(
+        Customer,
+        r#"
+        SELECT id, tenant_id, name, email, billing_address, tax_id, notes,
+               is_active, created_at, created_by, updated_at, updated_by
+        FROM customers
+        WHERE tenant_id = $1 AND is_active = TRUE
+        ORDER BY name
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(customers)
+}
+
+/// Retrieves a single active customer by ID for a specific tenant.
+pub async fn get_customer_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    customer_id: Uuid,
+) -> Result<Customer, AppError> {
+    info!(
+        "Service: Getting customer with ID: {} for tenant ID: {}",
+        customer_id, tenant_id
+    );
+
+    let customer = query_as!(
+        Customer,
+        r#"
+        SELECT id, tenant_id, name, email, billing_address, tax_id, notes,
+               is_active, created_at, created_by, updated_at, updated_by
+        FROM customers
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        customer_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Customer with ID {} not found for tenant {}",
+            customer_id, tenant_id
+        ))
+    })?;
+
+    Ok(customer)
+}
+
+/// Creates a new customer for a specific tenant.
+pub async fn create_customer(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateCustomerDto,
+) -> Result<Customer, AppError> {
+    info!(
+        "Service: Creating new customer '{}' for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    let customer = query_as!(
+        Customer,
+        r#"
+        INSERT INTO customers (tenant_id, name, email, billing_address, tax_id, notes, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+        RETURNING id, tenant_id, name, email, billing_address, tax_id, notes,
+                  is_active, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.name.trim(),
+        dto.email,
+        dto.billing_address,
+        dto.tax_id,
+        dto.notes,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(customer)
+}
+
+/// Updates an existing customer for a specific tenant. Issued invoices are not reprinted.
+pub async fn update_customer(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    customer_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateCustomerDto,
+) -> Result<Customer, AppError> {
+    info!(
+        "Service: Updating customer with ID: {} for tenant ID: {}",
+        customer_id, tenant_id
+    );
+
+    let mut update = UpdateBuilder::new("customers");
+    update
+        .set("name", dto.name.map(|name| name.trim().to_string()))
+        .set("email", dto.email)
+        .set("billing_address", dto.billing_address)
+        .set("tax_id", dto.tax_id)
+        .set("notes", dto.notes);
+    if update.is_empty() {
+        return Err(AppError::Validation(
+            "No fields provided for update".to_string(),
+        ));
+    }
+
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(customer_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(" AND is_active = TRUE");
+    query.push(
+        r#"
+        RETURNING id, tenant_id, name, email, billing_address, tax_id, notes,
+                  is_active, created_at, created_by, updated_at, updated_by
+        "#,
+    );
+
+    let customer = query
+        .build_query_as::<Customer>()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Customer with ID {} not found for tenant {}",
+                customer_id, tenant_id
+            ))
+        })?;
+
+    Ok(customer)
+}
+
+/// Deactivates a customer (soft delete). Refused while one of their invoices is unpaid.
+pub async fn deactivate_customer(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    customer_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deactivating customer with ID: {} for tenant ID: {}",
+        customer_id, tenant_id
+    );
+
+    let open_invoices = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM invoices
+        WHERE customer_id = $1 AND tenant_id = $2 AND status IN ('DRAFT', 'ISSUED', 'PARTIALLY_PAID')
+        "#,
+        customer_id,
+        tenant_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if open_invoices > 0 {
+        return Err(AppError::Conflict(format!(
+            "Customer {} has {} draft or unpaid invoices",
+            customer_id, open_invoices
+        )));
+    }
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE customers
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        customer_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Customer with ID {} not found or already inactive for tenant {}",
+            customer_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use sqlx::{query_as, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        dashboard::Dashboard,
+        dto::dashboard_dto::{CreateDashboardDto, UpdateDashboardDto},
+    },
+};
+
+/// Retrieves the current user's dashboards in a tenant, default first.
+pub async fn list_dashboards(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<Dashboard>, AppError> {
+    info!(
+        "Service: Listing dashboards for user ID: {} in tenant ID: {}",
+        user_id, tenant_id
+    );
+
+    let dashboards = query_as!(
+        Dashboard,
+        r#"
+        SELECT
+            id, tenant_id, user_id, name, description, is_default,
+            created_at, created_by, updated_at, updated_by
+        FROM dashboards
+        WHERE tenant_id = $1 AND user_id = $2
+        ORDER BY is_default DESC, name
+        "#,
+        tenant_id,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(dashboards)
+}
+
+/// Retrieves one of the current user's dashboards.
+pub async fn get_dashboard_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dashboard_id: Uuid,
+) -> Result<Dashboard, AppError> {
+    info!(
+        "Service: Getting dashboard with ID: {} for tenant ID: {}",
+        dashboard_id, tenant_id
+    );
+
+    let dashboard = query_as!(
+        Dashboard,
+        r#"
+        SELECT
+            id, tenant_id, user_id, name, description, is_default,
+            created_at, created_by, updated_at, updated_by
+        FROM dashboards
+        WHERE id = $1 AND tenant_id = $2 AND user_id = $3
+        "#,
+        dashboard_id,
+        tenant_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Dashboard with ID {} not found", dashboard_id)))?;
+
+    Ok(dashboard)
+}
+
+/// Creates a dashboard owned by the current user.
+pub async fn create_dashboard(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: CreateDashboardDto,
+) -> Result<Dashboard, AppError> {
+    info!(
+        "Service: Creating dashboard '{}' for user ID: {}",
+        dto.name, user_id
+    );
+
+    dto.validate()?;
+
+    let mut db_tx = pool.begin().await?;
+
+    let is_default = dto.is_default.unwrap_or(false);
+    if is_default {
+        clear_default(&mut db_tx, tenant_id, user_id).await?;
+    }
+
+    let new_dashboard = query_as!(
+        Dashboard,
+        r#"
+        INSERT INTO dashboards (tenant_id, user_id, name, description, is_default, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $2, $2)
+        RETURNING
+            id, tenant_id, user_id, name, description, is_default,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        user_id,
+        dto.name,
+        dto.description,
+        is_default
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(new_dashboard)
+}
+
+/// Updates one of the current user's dashboards.
+pub async fn update_dashboard(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dashboard_id: Uuid,
+    dto: UpdateDashboardDto,
+) -> Result<Dashboard, AppError> {
+    info!(
+        "Service: Updating dashboard with ID: {} for tenant ID: {}",
+        dashboard_id, tenant_id
+    );
+
+    dto.validate()?;
+
+    let mut db_tx = pool.begin().await?;
+
+    if dto.is_default == Some(true) {
+        clear_default(&mut db_tx, tenant_id, user_id).await?;
+    }
+
+    let updated_dashboard = query_as!(
+        Dashboard,
+        r#"
+        UPDATE dashboards
+        SET
+            name = COALESCE($4, name),
+            description = COALESCE($5, description),
+            is_default = COALESCE($6, is_default),
+            updated_at = NOW(),
+            updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND user_id = $3
+        RETURNING
+            id, tenant_id, user_id, name, description, is_default,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        dashboard_id,
+        tenant_id,
+        user_id,
+        dto.name,
+        dto.description,
+        dto.is_default
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Dashboard with ID {} not found", dashboard_id)))?;
+
+    db_tx.commit().await?;
+    Ok(updated_dashboard)
+}
+
+/// Deletes one of the current user's dashboards together with its widgets.
+pub async fn delete_dashboard(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dashboard_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting dashboard with ID: {} for tenant ID: {}",
+        dashboard_id, tenant_id
+    );
+
+    // Ownership check before touching the widgets
+    get_dashboard_by_id(pool, tenant_id, user_id, dashboard_id).await?;
+
+    let mut db_tx = pool.begin().await?;
+    sqlx::query!(
+        "DELETE FROM dashboard_widgets WHERE dashboard_id = $1",
+        dashboard_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    sqlx::query!("DELETE FROM dashboards WHERE id = $1", dashboard_id)
+        .execute(&mut *db_tx)
+        .await?;
+    db_tx.commit().await?;
+
+    Ok(())
+}
+
+/// Unsets the user's current default dashboard so another can take its place.
+async fn clear_default(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE dashboards
+        SET is_default = FALSE, updated_at = NOW(), updated_by = $2
+        WHERE tenant_id = $1 AND user_id = $2 AND is_default = TRUE
+        "#,
+        tenant_id,
+        user_id
+    )
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(())
+}
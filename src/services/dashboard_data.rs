@@ -0,0 +1,352 @@
+//! Server-side evaluation of dashboard widgets.
+//!
+//! `dashboard_data` evaluates every widget of a dashboard in one call, using the query
+//! spec stored in each widget's `parameters`. Only posted transactions count. A widget
+//! that fails (e.g., its budget was deleted) reports an error without failing the others.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dashboard::DashboardData,
+        dashboard_widget::{
+            AccountBalanceItem, AccountBalanceListParams, BalanceSummary, BalanceTrendPoint,
+            BudgetBurn, BudgetBurnParams, CashBalanceParams, CategorySpend, DashboardWidget,
+            IncomeVsExpense, IncomeVsExpenseParams, SpendingByCategoryParams, WidgetData,
+            WidgetType,
+        },
+    },
+    services::{budget, budget_performance, dashboard, dashboard_widget},
+};
+
+const DEFAULT_CATEGORY_LIMIT: i64 = 10;
+const DEFAULT_TREND_MONTHS: u32 = 6;
+
+/// Evaluates every widget of one of the current user's dashboards.
+pub async fn dashboard_data(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dashboard_id: Uuid,
+) -> Result<DashboardData, AppError> {
+    info!(
+        "Service: Evaluating dashboard ID: {} for tenant ID: {}",
+        dashboard_id, tenant_id
+    );
+
+    let dashboard = dashboard::get_dashboard_by_id(pool, tenant_id, user_id, dashboard_id).await?;
+    let widgets =
+        dashboard_widget::list_dashboard_widgets(pool, tenant_id, user_id, dashboard_id).await?;
+
+    let mut evaluated = Vec::with_capacity(widgets.len());
+    for widget in widgets {
+        let (data, error) = match evaluate_widget(pool, tenant_id, &widget).await {
+            Ok(data) => (Some(data), None),
+            Err(e) => {
+                warn!(
+                    "Widget {} of dashboard {} failed: {}",
+                    widget.id, dashboard_id, e
+                );
+                (None, Some(e.to_string()))
+            }
+        };
+        evaluated.push(WidgetData {
+            widget,
+            data,
+            error,
+        });
+    }
+
+    Ok(DashboardData {
+        dashboard,
+        widgets: evaluated,
+    })
+}
+
+async fn evaluate_widget(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    widget: &DashboardWidget,
+) -> Result<JsonValue, AppError> {
+    let widget_type: WidgetType = widget
+        .widget_type
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    let parameters = widget.parameters.as_ref();
+    let today = Utc::now().date_naive();
+
+    match widget_type {
+        WidgetType::SpendingByCategory => {
+            let params: SpendingByCategoryParams = dashboard_widget::parse_parameters(parameters)?;
+            let (from_date, to_date) =
+                period_or_current_month(params.from_date, params.to_date, today);
+            let limit = params.limit.unwrap_or(DEFAULT_CATEGORY_LIMIT);
+            to_json(spending_by_category(pool, tenant_id, from_date, to_date, limit).await?)
+        }
+        WidgetType::CashBalanceSummary => {
+            let params: CashBalanceParams = dashboard_widget::parse_parameters(parameters)?;
+            let accounts =
+                account_balances(pool, tenant_id, &params.account_ids, false, today).await?;
+            to_json(BalanceSummary {
+                as_of: today,
+                total: accounts.iter().map(|a| a.balance).sum(),
+                accounts,
+            })
+        }
+        WidgetType::CashBalanceTrend => {
+            let params: CashBalanceParams = dashboard_widget::parse_parameters(parameters)?;
+            let months = params.months.unwrap_or(DEFAULT_TREND_MONTHS);
+            to_json(cash_balance_trend(pool, tenant_id, &params.account_ids, months, today).await?)
+        }
+        WidgetType::IncomeVsExpenseSummary => {
+            let params: IncomeVsExpenseParams = dashboard_widget::parse_parameters(parameters)?;
+            let (from_date, to_date) =
+                period_or_current_month(params.from_date, params.to_date, today);
+            to_json(income_vs_expense(pool, tenant_id, from_date, to_date).await?)
+        }
+        WidgetType::AccountBalanceList => {
+            let params: AccountBalanceListParams = dashboard_widget::parse_parameters(parameters)?;
+            let as_of = params.as_of.unwrap_or(today);
+            to_json(account_balances(pool, tenant_id, &params.account_ids, true, as_of).await?)
+        }
+        WidgetType::BudgetBurn => {
+            let value = parameters.ok_or_else(|| {
+                AppError::Validation("budget_burn widgets need a budget_id".to_string())
+            })?;
+            let params: BudgetBurnParams = serde_json::from_value(value.clone())
+                .map_err(|e| AppError::Validation(format!("Invalid widget parameters: {}", e)))?;
+            to_json(budget_burn(pool, tenant_id, params.budget_id, today).await?)
+        }
+        WidgetType::CustomReportLink => Ok(widget.parameters.clone().unwrap_or(JsonValue::Null)),
+    }
+}
+
+fn to_json<T: Serialize>(data: T) -> Result<JsonValue, AppError> {
+    serde_json::to_value(data).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to serialize widget data: {}", e))
+    })
+}
+
+fn period_or_current_month(
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+    today: NaiveDate,
+) -> (NaiveDate, NaiveDate) {
+    let month_start = today.with_day(1).unwrap_or(today);
+    (from_date.unwrap_or(month_start), to_date.unwrap_or(today))
+}
+
+/// Posted expense transactions grouped by category, largest first.
+async fn spending_by_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    limit: i64,
+) -> Result<Vec<CategorySpend>, AppError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT t.category_id, COALESCE(c.name, 'Uncategorized') as "category_name!", SUM(t.amount) as "amount!"
+        FROM transactions t
+        LEFT JOIN categories c ON t.category_id = c.id
+        WHERE t.tenant_id = $1
+          AND t.status = 'POSTED'
+          AND t.type = 'EXPENSE'
+          AND t.transaction_date BETWEEN $2 AND $3
+        GROUP BY t.category_id, c.name
+        ORDER BY 3 DESC
+        LIMIT $4
+        "#,
+        tenant_id,
+        from_date,
+        to_date,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| CategorySpend {
+            category_id: row.category_id,
+            category_name: row.category_name,
+            amount: row.amount,
+        })
+        .collect())
+}
+
+/// Posted income and expense totals over a period.
+async fn income_vs_expense(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> Result<IncomeVsExpense, AppError> {
+    let totals = sqlx::query!(
+        r#"
+        SELECT
+            COALESCE(SUM(amount) FILTER (WHERE type = 'INCOME'), 0) as "income!",
+            COALESCE(SUM(amount) FILTER (WHERE type = 'EXPENSE'), 0) as "expense!"
+        FROM transactions
+        WHERE tenant_id = $1 AND status = 'POSTED' AND transaction_date BETWEEN $2 AND $3
+        "#,
+        tenant_id,
+        from_date,
+        to_date
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(IncomeVsExpense {
+        from_date,
+        to_date,
+        income: totals.income,
+        expense: totals.expense,
+        net: totals.income - totals.expense,
+    })
+}
+
+/// Balances as of a date, signed by each account's normal balance. Without explicit
+/// `account_ids`, all active accounts (`all_accounts`) or only asset accounts are used.
+pub async fn account_balances(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    account_ids: &[Uuid],
+    all_accounts: bool,
+    as_of: NaiveDate,
+) -> Result<Vec<AccountBalanceItem>, AppError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            a.id, a.account_code, a.name,
+            COALESCE(SUM(
+                CASE WHEN je.entry_type = at.normal_balance THEN COALESCE(je.converted_amount, je.amount)
+                     ELSE -COALESCE(je.converted_amount, je.amount) END
+            ), 0) as "balance!"
+        FROM accounts a
+        JOIN account_types at ON a.account_type_id = at.id
+        LEFT JOIN (
+            journal_entries je
+            JOIN transactions t ON je.transaction_id = t.id
+                AND t.status = 'POSTED'
+                AND t.transaction_date <= $4
+        ) ON je.account_id = a.id
+        WHERE a.tenant_id = $1
+          AND a.is_active = TRUE
+          AND CASE WHEN cardinality($2::uuid[]) > 0 THEN a.id = ANY($2) ELSE ($3 OR at.name = 'Asset') END
+        GROUP BY a.id, a.account_code, a.name
+        ORDER BY a.account_code NULLS LAST, a.name
+        "#,
+        tenant_id,
+        account_ids,
+        all_accounts,
+        as_of
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AccountBalanceItem {
+            account_id: row.id,
+            account_code: row.account_code,
+            name: row.name,
+            balance: row.balance,
+        })
+        .collect())
+}
+
+/// Combined balance of the cash accounts at the end of each of the last `months` months
+/// (the current month ends today).
+async fn cash_balance_trend(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    account_ids: &[Uuid],
+    months: u32,
+    today: NaiveDate,
+) -> Result<Vec<BalanceTrendPoint>, AppError> {
+    let current_month = today.with_day(1).unwrap_or(today);
+    let first_month = current_month - Months::new(months.saturating_sub(1));
+
+    // Everything before the first month is folded into it as its opening balance
+    let movements = sqlx::query!(
+        r#"
+        SELECT
+            GREATEST(date_trunc('month', t.transaction_date)::date, $3) as "month!",
+            SUM(
+                CASE WHEN je.entry_type = at.normal_balance THEN COALESCE(je.converted_amount, je.amount)
+                     ELSE -COALESCE(je.converted_amount, je.amount) END
+            ) as "movement!"
+        FROM journal_entries je
+        JOIN transactions t ON je.transaction_id = t.id
+        JOIN accounts a ON je.account_id = a.id
+        JOIN account_types at ON a.account_type_id = at.id
+        WHERE t.tenant_id = $1
+          AND t.status = 'POSTED'
+          AND t.transaction_date <= $4
+          AND a.is_active = TRUE
+          AND CASE WHEN cardinality($2::uuid[]) > 0 THEN a.id = ANY($2) ELSE at.name = 'Asset' END
+        GROUP BY 1
+        "#,
+        tenant_id,
+        account_ids,
+        first_month,
+        today
+    )
+    .fetch_all(pool)
+    .await?;
+    let by_month: BTreeMap<NaiveDate, Decimal> = movements
+        .into_iter()
+        .map(|m| (m.month, m.movement))
+        .collect();
+
+    let mut balance = Decimal::ZERO;
+    let mut points = Vec::with_capacity(months as usize);
+    let mut month = first_month;
+    while month <= current_month {
+        balance += by_month.get(&month).copied().unwrap_or(Decimal::ZERO);
+        let month_end = (month + Months::new(1))
+            .pred_opt()
+            .unwrap_or(month)
+            .min(today);
+        points.push(BalanceTrendPoint { month_end, balance });
+        month = month + Months::new(1);
+    }
+    Ok(points)
+}
+
+/// Budget consumed so far against the share of the budget period that has elapsed.
+async fn budget_burn(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+    today: NaiveDate,
+) -> Result<BudgetBurn, AppError> {
+    let budget = budget::get_budget_by_id(pool, tenant_id, budget_id).await?;
+    let performance = budget_performance::budget_performance(pool, tenant_id, budget_id).await?;
+
+    let total_days = (budget.end_date - budget.start_date).num_days() + 1;
+    let elapsed_days =
+        ((today.min(budget.end_date) - budget.start_date).num_days() + 1).clamp(0, total_days);
+    let hundred = Decimal::from(100);
+
+    Ok(BudgetBurn {
+        budget_id,
+        name: performance.name,
+        total_budgeted: performance.total_budgeted,
+        total_actual: performance.total_actual,
+        burn_pct: (!performance.total_budgeted.is_zero())
+            .then(|| (performance.total_actual / performance.total_budgeted * hundred).round_dp(2)),
+        elapsed_pct: (Decimal::from(elapsed_days) / Decimal::from(total_days) * hundred)
+            .round_dp(2),
+    })
+}
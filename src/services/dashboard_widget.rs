@@ -0,0 +1,263 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        dashboard_widget::{
+            AccountBalanceListParams, BudgetBurnParams, CashBalanceParams, DashboardWidget,
+            IncomeVsExpenseParams, SpendingByCategoryParams, WidgetType,
+        },
+        dto::dashboard_widget_dto::{CreateDashboardWidgetDto, UpdateDashboardWidgetDto},
+    },
+    services::dashboard,
+};
+
+/// Most months a cash balance trend may cover.
+pub const MAX_TREND_MONTHS: u32 = 36;
+
+/// Retrieves the widgets of a dashboard in display order.
+pub async fn list_dashboard_widgets(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dashboard_id: Uuid,
+) -> Result<Vec<DashboardWidget>, AppError> {
+    info!("Service: Listing widgets of dashboard ID: {}", dashboard_id);
+
+    dashboard::get_dashboard_by_id(pool, tenant_id, user_id, dashboard_id).await?;
+
+    let widgets = query_as!(
+        DashboardWidget,
+        r#"
+        SELECT
+            id, dashboard_id, widget_type, title, order_index, parameters, properties,
+            created_at, created_by, updated_at, updated_by
+        FROM dashboard_widgets
+        WHERE dashboard_id = $1
+        ORDER BY order_index, created_at
+        "#,
+        dashboard_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(widgets)
+}
+
+/// Adds a widget to a dashboard after checking its query spec.
+pub async fn create_dashboard_widget(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dashboard_id: Uuid,
+    dto: CreateDashboardWidgetDto,
+) -> Result<DashboardWidget, AppError> {
+    info!(
+        "Service: Adding {:?} widget to dashboard ID: {}",
+        dto.widget_type, dashboard_id
+    );
+
+    dto.validate()?;
+    validate_parameters(dto.widget_type, dto.parameters.as_ref())?;
+
+    dashboard::get_dashboard_by_id(pool, tenant_id, user_id, dashboard_id).await?;
+
+    let new_widget = query_as!(
+        DashboardWidget,
+        r#"
+        INSERT INTO dashboard_widgets (
+            dashboard_id, widget_type, title, order_index, parameters, properties,
+            created_by, updated_by
+        )
+        VALUES (
+            $1, $2, $3,
+            COALESCE($4, (SELECT COALESCE(MAX(order_index) + 1, 0) FROM dashboard_widgets WHERE dashboard_id = $1)),
+            $5, $6, $7, $7
+        )
+        RETURNING
+            id, dashboard_id, widget_type, title, order_index, parameters, properties,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        dashboard_id,
+        String::from(dto.widget_type),
+        dto.title,
+        dto.order_index,
+        dto.parameters,
+        dto.properties,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(new_widget)
+}
+
+/// Updates a widget of a dashboard. New parameters are checked against the widget's type.
+pub async fn update_dashboard_widget(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dashboard_id: Uuid,
+    widget_id: Uuid,
+    dto: UpdateDashboardWidgetDto,
+) -> Result<DashboardWidget, AppError> {
+    info!(
+        "Service: Updating widget ID: {} of dashboard ID: {}",
+        widget_id, dashboard_id
+    );
+
+    dto.validate()?;
+    dashboard::get_dashboard_by_id(pool, tenant_id, user_id, dashboard_id).await?;
+
+    if dto.parameters.is_some() {
+        let widget_type = sqlx::query_scalar!(
+            "SELECT widget_type FROM dashboard_widgets WHERE id = $1 AND dashboard_id = $2",
+            widget_id,
+            dashboard_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Widget with ID {} not found", widget_id)))?;
+        let widget_type: WidgetType = widget_type.parse().map_err(AppError::InternalServerError)?;
+        validate_parameters(widget_type, dto.parameters.as_ref())?;
+    }
+
+    let updated_widget = query_as!(
+        DashboardWidget,
+        r#"
+        UPDATE dashboard_widgets
+        SET
+            title = COALESCE($3, title),
+            order_index = COALESCE($4, order_index),
+            parameters = COALESCE($5, parameters),
+            properties = COALESCE($6, properties),
+            updated_at = NOW(),
+            updated_by = $7
+        WHERE id = $1 AND dashboard_id = $2
+        RETURNING
+            id, dashboard_id, widget_type, title, order_index, parameters, properties,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        widget_id,
+        dashboard_id,
+        dto.title,
+        dto.order_index,
+        dto.parameters,
+        dto.properties,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Widget with ID {} not found", widget_id)))?;
+
+    Ok(updated_widget)
+}
+
+/// Removes a widget from a dashboard.
+pub async fn delete_dashboard_widget(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dashboard_id: Uuid,
+    widget_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting widget ID: {} of dashboard ID: {}",
+        widget_id, dashboard_id
+    );
+
+    dashboard::get_dashboard_by_id(pool, tenant_id, user_id, dashboard_id).await?;
+
+    let affected_rows = sqlx::query!(
+        "DELETE FROM dashboard_widgets WHERE id = $1 AND dashboard_id = $2",
+        widget_id,
+        dashboard_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Widget with ID {} not found",
+            widget_id
+        )));
+    }
+    Ok(())
+}
+
+/// Parses a widget's stored parameters into its typed query spec. Missing parameters
+/// mean "all defaults".
+pub fn parse_parameters<T: DeserializeOwned + Default>(
+    parameters: Option<&JsonValue>,
+) -> Result<T, AppError> {
+    match parameters {
+        None | Some(JsonValue::Null) => Ok(T::default()),
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| AppError::Validation(format!("Invalid widget parameters: {}", e))),
+    }
+}
+
+/// Checks that `parameters` is a valid query spec for the widget type.
+fn validate_parameters(
+    widget_type: WidgetType,
+    parameters: Option<&JsonValue>,
+) -> Result<(), AppError> {
+    match widget_type {
+        WidgetType::SpendingByCategory => {
+            let params: SpendingByCategoryParams = parse_parameters(parameters)?;
+            check_range(params.from_date, params.to_date)?;
+            if params.limit.is_some_and(|limit| limit < 1) {
+                return Err(AppError::Validation("limit must be at least 1".to_string()));
+            }
+        }
+        WidgetType::CashBalanceSummary | WidgetType::CashBalanceTrend => {
+            let params: CashBalanceParams = parse_parameters(parameters)?;
+            if params
+                .months
+                .is_some_and(|months| months == 0 || months > MAX_TREND_MONTHS)
+            {
+                return Err(AppError::Validation(format!(
+                    "months must be between 1 and {}",
+                    MAX_TREND_MONTHS
+                )));
+            }
+        }
+        WidgetType::IncomeVsExpenseSummary => {
+            let params: IncomeVsExpenseParams = parse_parameters(parameters)?;
+            check_range(params.from_date, params.to_date)?;
+        }
+        WidgetType::AccountBalanceList => {
+            parse_parameters::<AccountBalanceListParams>(parameters)?;
+        }
+        WidgetType::BudgetBurn => {
+            let value = parameters.ok_or_else(|| {
+                AppError::Validation("budget_burn widgets need a budget_id".to_string())
+            })?;
+            serde_json::from_value::<BudgetBurnParams>(value.clone())
+                .map_err(|e| AppError::Validation(format!("Invalid widget parameters: {}", e)))?;
+        }
+        // Links are rendered by the client; their parameters are opaque
+        WidgetType::CustomReportLink => {}
+    }
+    Ok(())
+}
+
+fn check_range(
+    from_date: Option<chrono::NaiveDate>,
+    to_date: Option<chrono::NaiveDate>,
+) -> Result<(), AppError> {
+    if let (Some(from_date), Some(to_date)) = (from_date, to_date) {
+        if to_date < from_date {
+            return Err(AppError::Validation(
+                "to_date cannot be before from_date".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,201 @@
+//! Records that depend on an account or category.
+//!
+//! Active budget lines and recurring transactions can point at either, merchant rules and
+//! subcategories at categories. Deactivating a record that still has such dependents is
+//! refused with the list of blockers, unless the caller cascades: then the budget lines and
+//! recurring transactions are deactivated too, and the merchant rules stop assigning the
+//! category. Callers run the check and the cascade in the transaction that deactivates.
+
+use sqlx::{query_as, PgExecutor, Postgres, Transaction as DbTransaction};
+use uuid::Uuid;
+
+use crate::error::{AppError, Blocker};
+
+/// Lists the active budget lines and recurring transactions that use the account, including
+/// journal templates with a leg on it.
+pub async fn account_blockers<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    account_id: Uuid,
+) -> Result<Vec<Blocker>, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let blockers = query_as!(
+        Blocker,
+        r#"
+        SELECT 'budget_line_item' as "kind!", bli.id as "id!", b.name as "name!"
+        FROM budget_line_items bli
+        JOIN budgets b ON b.id = bli.budget_id
+        WHERE b.tenant_id = $1 AND b.is_active = TRUE AND bli.is_active = TRUE
+            AND bli.account_id = $2
+        UNION ALL
+        SELECT 'recurring_transaction', rt.id, rt.description
+        FROM recurring_transactions rt
+        WHERE rt.tenant_id = $1 AND rt.is_active = TRUE
+            AND (
+                rt.account_id = $2
+                OR rt.journal_template @> jsonb_build_array(jsonb_build_object('account_id', $2::TEXT))
+            )
+        ORDER BY 1, 3
+        "#,
+        tenant_id,
+        account_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(blockers)
+}
+
+/// Lists what depends on any of the categories: active subcategories outside the set,
+/// active budget lines and recurring transactions, and merchant rules.
+pub async fn category_blockers<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    category_ids: &[Uuid],
+) -> Result<Vec<Blocker>, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let blockers = query_as!(
+        Blocker,
+        r#"
+        SELECT 'subcategory' as "kind!", c.id as "id!", c.name as "name!"
+        FROM categories c
+        WHERE c.tenant_id = $1 AND c.is_active = TRUE
+            AND c.parent_category_id = ANY($2) AND NOT c.id = ANY($2)
+        UNION ALL
+        SELECT 'budget_line_item', bli.id, b.name
+        FROM budget_line_items bli
+        JOIN budgets b ON b.id = bli.budget_id
+        WHERE b.tenant_id = $1 AND b.is_active = TRUE AND bli.is_active = TRUE
+            AND bli.category_id = ANY($2)
+        UNION ALL
+        SELECT 'recurring_transaction', rt.id, rt.description
+        FROM recurring_transactions rt
+        WHERE rt.tenant_id = $1 AND rt.is_active = TRUE AND rt.category_id = ANY($2)
+        UNION ALL
+        SELECT 'merchant_rule', mr.id, mr.merchant_name
+        FROM merchant_rules mr
+        WHERE mr.tenant_id = $1 AND mr.category_id = ANY($2)
+        ORDER BY 1, 3
+        "#,
+        tenant_id,
+        category_ids
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(blockers)
+}
+
+/// Fails with the blockers, if there are any.
+pub fn ensure_none(blockers: Vec<Blocker>) -> Result<(), AppError> {
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::HasDependents(blockers))
+    }
+}
+
+/// Deactivates the budget lines and recurring transactions that use the account. Returns
+/// how many were deactivated.
+pub async fn cascade_account(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    account_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<u64, AppError> {
+    let lines = sqlx::query!(
+        r#"
+        UPDATE budget_line_items bli
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        FROM budgets b
+        WHERE b.id = bli.budget_id AND b.tenant_id = $1 AND bli.is_active = TRUE
+            AND bli.account_id = $2
+        "#,
+        tenant_id,
+        account_id,
+        updated_by_user_id
+    )
+    .execute(&mut **db_tx)
+    .await?
+    .rows_affected();
+
+    let recurring = sqlx::query!(
+        r#"
+        UPDATE recurring_transactions
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        WHERE tenant_id = $1 AND is_active = TRUE
+            AND (
+                account_id = $2
+                OR journal_template @> jsonb_build_array(jsonb_build_object('account_id', $2::TEXT))
+            )
+        "#,
+        tenant_id,
+        account_id,
+        updated_by_user_id
+    )
+    .execute(&mut **db_tx)
+    .await?
+    .rows_affected();
+
+    Ok(lines + recurring)
+}
+
+/// Deactivates the budget lines and recurring transactions filed under the categories and
+/// clears them from merchant rules, which keep cleaning merchant names. Subcategories are
+/// left to the caller. Returns how many dependents were changed.
+pub async fn cascade_categories(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    category_ids: &[Uuid],
+    updated_by_user_id: Uuid,
+) -> Result<u64, AppError> {
+    let lines = sqlx::query!(
+        r#"
+        UPDATE budget_line_items bli
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        FROM budgets b
+        WHERE b.id = bli.budget_id AND b.tenant_id = $1 AND bli.is_active = TRUE
+            AND bli.category_id = ANY($2)
+        "#,
+        tenant_id,
+        category_ids,
+        updated_by_user_id
+    )
+    .execute(&mut **db_tx)
+    .await?
+    .rows_affected();
+
+    let recurring = sqlx::query!(
+        r#"
+        UPDATE recurring_transactions
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        WHERE tenant_id = $1 AND is_active = TRUE AND category_id = ANY($2)
+        "#,
+        tenant_id,
+        category_ids,
+        updated_by_user_id
+    )
+    .execute(&mut **db_tx)
+    .await?
+    .rows_affected();
+
+    let rules = sqlx::query!(
+        r#"
+        UPDATE merchant_rules
+        SET category_id = NULL, updated_at = NOW(), updated_by = $3
+        WHERE tenant_id = $1 AND category_id = ANY($2)
+        "#,
+        tenant_id,
+        category_ids,
+        updated_by_user_id
+    )
+    .execute(&mut **db_tx)
+    .await?
+    .rows_affected();
+
+    Ok(lines + recurring + rules)
+}
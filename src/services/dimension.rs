@@ -0,0 +1,264 @@
+//! Dimensions: projects, classes and locations for journal entries.
+//!
+//! An entry carries at most one dimension of each kind (`project_id`, `class_id`,
+//! `location_id`). The income statement and trial balance can be restricted to entries with
+//! given dimensions, which is how a tenant gets a P&L per project. Dimensions are deactivated
+//! rather than deleted, so tagged entries keep pointing at them.
+
+use std::collections::HashSet;
+
+use sqlx::{query_as, PgExecutor, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dimension::{Dimension, DimensionFilter, DimensionKind},
+        dto::dimension_dto::{CreateDimensionDto, ListDimensionsQuery, UpdateDimensionDto},
+    },
+    utils::update_builder::UpdateBuilder,
+};
+
+/// Retrieves the tenant's dimensions by kind and name.
+pub async fn list_dimensions(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: ListDimensionsQuery,
+) -> Result<Vec<Dimension>, AppError> {
+    info!("Service: Listing dimensions for tenant ID: {}", tenant_id);
+
+    let dimensions = query_as!(
+        Dimension,
+        r#"
+        SELECT id, tenant_id, kind, name, code, description, is_active,
+               created_at, created_by, updated_at, updated_by
+        FROM dimensions
+        WHERE tenant_id = $1
+          AND ($2::text IS NULL OR kind = $2)
+          AND ($3 OR is_active = TRUE)
+        ORDER BY kind, LOWER(name)
+        "#,
+        tenant_id,
+        query.kind.map(String::from),
+        query.include_inactive
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(dimensions)
+}
+
+/// Retrieves a single dimension by ID for a specific tenant, active or not.
+pub async fn get_dimension(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    dimension_id: Uuid,
+) -> Result<Dimension, AppError> {
+    info!(
+        "Service: Getting dimension with ID: {} for tenant ID: {}",
+        dimension_id, tenant_id
+    );
+
+    let dimension = query_as!(
+        Dimension,
+        r#"
+        SELECT id, tenant_id, kind, name, code, description, is_active,
+               created_at, created_by, updated_at, updated_by
+        FROM dimensions
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        dimension_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| not_found(tenant_id, dimension_id))?;
+
+    Ok(dimension)
+}
+
+/// Creates a dimension. Names, and codes when given, are unique per tenant and kind.
+pub async fn create_dimension(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateDimensionDto,
+) -> Result<Dimension, AppError> {
+    info!(
+        "Service: Creating dimension '{}' for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    let dimension = query_as!(
+        Dimension,
+        r#"
+        INSERT INTO dimensions (tenant_id, kind, name, code, description, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        RETURNING id, tenant_id, kind, name, code, description, is_active,
+                  created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        String::from(dto.kind),
+        dto.name.trim(),
+        dto.code.as_deref().map(str::trim),
+        dto.description,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| duplicate(e, dto.kind))?;
+
+    Ok(dimension)
+}
+
+/// Updates a dimension. Tagged entries follow, since they only hold its ID.
+pub async fn update_dimension(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    dimension_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateDimensionDto,
+) -> Result<Dimension, AppError> {
+    info!(
+        "Service: Updating dimension with ID: {} for tenant ID: {}",
+        dimension_id, tenant_id
+    );
+
+    let existing = get_dimension(pool, tenant_id, dimension_id).await?;
+    let kind: DimensionKind = existing
+        .kind
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+
+    let mut update = UpdateBuilder::new("dimensions");
+    update
+        .set("name", dto.name.map(|name| name.trim().to_string()))
+        .set("code", dto.code.map(|code| code.trim().to_string()))
+        .set("description", dto.description);
+    if update.is_empty() {
+        return Err(AppError::Validation(
+            "No fields provided for update".to_string(),
+        ));
+    }
+
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(dimension_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(
+        r#"
+        RETURNING id, tenant_id, kind, name, code, description, is_active,
+                  created_at, created_by, updated_at, updated_by
+        "#,
+    );
+
+    let dimension = query
+        .build_query_as::<Dimension>()
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| duplicate(e, kind))?
+        .ok_or_else(|| not_found(tenant_id, dimension_id))?;
+
+    Ok(dimension)
+}
+
+/// Deactivates a dimension (soft delete): it can no longer be assigned, but entries tagged
+/// with it keep it and still report under it.
+pub async fn deactivate_dimension(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    dimension_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deactivating dimension with ID: {} for tenant ID: {}",
+        dimension_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE dimensions
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        dimension_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Dimension with ID {} not found or already inactive for tenant {}",
+            dimension_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Rejects dimensions that are not the tenant's, inactive, or of another kind than the
+/// field they are given in.
+pub async fn check_assignable<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    filters: impl IntoIterator<Item = DimensionFilter>,
+) -> Result<(), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let assigned: Vec<(Uuid, DimensionKind)> = filters
+        .into_iter()
+        .flat_map(|filter| filter.assigned())
+        .collect();
+    if assigned.is_empty() {
+        return Ok(());
+    }
+
+    let ids: Vec<Uuid> = assigned.iter().map(|(id, _)| *id).collect();
+    let valid: HashSet<(Uuid, String)> = sqlx::query!(
+        r#"
+        SELECT id, kind
+        FROM dimensions
+        WHERE tenant_id = $1 AND id = ANY($2) AND is_active = TRUE
+        "#,
+        tenant_id,
+        &ids
+    )
+    .fetch_all(executor)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, row.kind))
+    .collect();
+
+    for (id, kind) in assigned {
+        if !valid.contains(&(id, String::from(kind))) {
+            return Err(AppError::Validation(format!(
+                "{} dimension ID {} is invalid or inactive for tenant {}",
+                String::from(kind),
+                id,
+                tenant_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn duplicate(e: sqlx::Error, kind: DimensionKind) -> AppError {
+    match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => AppError::Conflict(format!(
+            "A {} dimension with this name or code already exists",
+            String::from(kind)
+        )),
+        e => e.into(),
+    }
+}
+
+fn not_found(tenant_id: Uuid, dimension_id: Uuid) -> AppError {
+    AppError::NotFound(format!(
+        "Dimension with ID {} not found for tenant {}",
+        dimension_id, tenant_id
+    ))
+}
@@ -0,0 +1,146 @@
+//! In-process domain events.
+//!
+//! Services publish what happened (a transaction was posted, an account deactivated) once
+//! the change is committed, and cross-cutting features such as the audit trail subscribe to
+//! the events instead of being called from every service.
+//!
+//! Delivery is best effort within this process: events are not stored, and a subscriber
+//! that falls more than `CHANNEL_CAPACITY` events behind misses the oldest ones (logged).
+//! Anything that must happen atomically with the change belongs in its database transaction.
+
+use std::{future::Future, sync::OnceLock};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use tokio::sync::broadcast::{self, error::RecvError, Sender};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db;
+
+/// Events kept for subscribers that have not caught up yet.
+const CHANNEL_CAPACITY: usize = 1024;
+
+static BUS: OnceLock<Sender<DomainEvent>> = OnceLock::new();
+
+/// Something that happened in a tenant's books, with the user who did it.
+#[derive(Debug, Clone)]
+pub enum DomainEvent {
+    TransactionPosted {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        transaction_id: Uuid,
+        amount: Decimal,
+        transaction_date: NaiveDate,
+    },
+    TransactionVoided {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        transaction_id: Uuid,
+        reason: String,
+    },
+    TransactionReversed {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        transaction_id: Uuid,
+        reversal_id: Uuid,
+        reversal_date: NaiveDate,
+    },
+    AccountDeactivated {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        account_id: Uuid,
+    },
+    AccountRestored {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        account_id: Uuid,
+    },
+    FiscalPeriodClosed {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        period_id: Uuid,
+        name: String,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    },
+    FiscalPeriodReopened {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        period_id: Uuid,
+        name: String,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    },
+    FxRevaluationPosted {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        revaluation_id: Uuid,
+        transaction_id: Uuid,
+        as_of: NaiveDate,
+        total_gain: Decimal,
+        total_loss: Decimal,
+    },
+}
+
+impl DomainEvent {
+    pub fn tenant_id(&self) -> Uuid {
+        match self {
+            DomainEvent::TransactionPosted { tenant_id, .. }
+            | DomainEvent::TransactionVoided { tenant_id, .. }
+            | DomainEvent::TransactionReversed { tenant_id, .. }
+            | DomainEvent::AccountDeactivated { tenant_id, .. }
+            | DomainEvent::AccountRestored { tenant_id, .. }
+            | DomainEvent::FiscalPeriodClosed { tenant_id, .. }
+            | DomainEvent::FiscalPeriodReopened { tenant_id, .. }
+            | DomainEvent::FxRevaluationPosted { tenant_id, .. } => *tenant_id,
+        }
+    }
+
+    /// The user whose action caused the event.
+    pub fn user_id(&self) -> Uuid {
+        match self {
+            DomainEvent::TransactionPosted { user_id, .. }
+            | DomainEvent::TransactionVoided { user_id, .. }
+            | DomainEvent::TransactionReversed { user_id, .. }
+            | DomainEvent::AccountDeactivated { user_id, .. }
+            | DomainEvent::AccountRestored { user_id, .. }
+            | DomainEvent::FiscalPeriodClosed { user_id, .. }
+            | DomainEvent::FiscalPeriodReopened { user_id, .. }
+            | DomainEvent::FxRevaluationPosted { user_id, .. } => *user_id,
+        }
+    }
+}
+
+fn bus() -> &'static Sender<DomainEvent> {
+    BUS.get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+}
+
+/// Publishes an event to every subscriber. Call after the change has been committed.
+pub fn publish(event: DomainEvent) {
+    // An error only means nobody is subscribed
+    let _ = bus().send(event);
+}
+
+/// Runs `handle` on every event published from now on, one at a time, in a background task
+/// and scoped to the event's tenant. Subscribe at startup, before requests are served, so no
+/// event is missed.
+pub fn spawn_subscriber<F, Fut>(name: &'static str, mut handle: F)
+where
+    F: FnMut(DomainEvent) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    let mut receiver = bus().subscribe();
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => db::with_tenant_scope(Some(event.tenant_id()), handle(event)).await,
+                Err(RecvError::Lagged(missed)) => warn!(
+                    "Domain event subscriber '{}' fell behind and missed {} events",
+                    name, missed
+                ),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
@@ -0,0 +1,290 @@
+//! Envelope (zero-based) budgeting for budgets in envelope mode.
+//!
+//! Each active line item is an envelope. Posted income in the budget currency since the
+//! start of the period is "ready to assign" until envelopes are funded with it; envelopes
+//! are funded by their budgeted amount and by moves from other envelopes. Spending comes
+//! from the same actuals as budget performance. Moves only shift money between envelopes,
+//! so they never change what is ready to assign.
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        budget::Budget,
+        dto::envelope_dto::{EnvelopeSummaryQuery, MoveEnvelopeMoneyDto},
+        envelope::{Envelope, EnvelopeMove, EnvelopeSummary},
+    },
+    services::{budget, budget_performance},
+};
+
+/// Envelope balances of a budget and the income still ready to assign.
+pub async fn envelope_summary(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+    query: EnvelopeSummaryQuery,
+) -> Result<EnvelopeSummary, AppError> {
+    info!(
+        "Service: Envelope summary for budget ID: {} tenant ID: {}",
+        budget_id, tenant_id
+    );
+
+    let budget = envelope_budget(pool, tenant_id, budget_id).await?;
+    let as_of = query
+        .as_of
+        .unwrap_or_else(|| Utc::now().date_naive())
+        .min(budget.end_date);
+
+    let envelopes = envelopes(pool, &budget, as_of).await?;
+
+    let income = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(amount), 0) as "income!"
+        FROM transactions
+        WHERE tenant_id = $1
+          AND type = 'INCOME'
+          AND status = 'POSTED'
+          AND currency_code = $2
+          AND transaction_date BETWEEN $3 AND $4
+          AND reversal_of_id IS NULL AND reversed_by_id IS NULL
+        "#,
+        tenant_id,
+        budget.currency_code,
+        budget.start_date,
+        as_of
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let assigned: Decimal = envelopes.iter().map(|envelope| envelope.assigned).sum();
+
+    Ok(EnvelopeSummary {
+        budget_id: budget.id,
+        currency_code: budget.currency_code,
+        as_of,
+        income,
+        assigned,
+        ready_to_assign: income - assigned,
+        envelopes,
+    })
+}
+
+/// Moves money from one envelope to another; the source cannot give more than it has
+/// available.
+pub async fn move_money(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: MoveEnvelopeMoneyDto,
+) -> Result<EnvelopeMove, AppError> {
+    info!(
+        "Service: Moving {} from envelope {} to {} in budget ID: {} tenant ID: {}",
+        dto.amount, dto.from_line_item_id, dto.to_line_item_id, budget_id, tenant_id
+    );
+
+    if dto.amount <= Decimal::ZERO || dto.amount.round_dp(2) != dto.amount {
+        return Err(AppError::Validation(
+            "The amount must be positive with at most two decimals".to_string(),
+        ));
+    }
+    if dto.from_line_item_id == dto.to_line_item_id {
+        return Err(AppError::Validation(
+            "Money can only be moved between two different envelopes".to_string(),
+        ));
+    }
+
+    let budget = envelope_budget(pool, tenant_id, budget_id).await?;
+    let moved_on = dto.moved_on.unwrap_or_else(|| Utc::now().date_naive());
+    if moved_on < budget.start_date || moved_on > budget.end_date {
+        return Err(AppError::Validation(format!(
+            "Moves must fall within the budget period {} to {}",
+            budget.start_date, budget.end_date
+        )));
+    }
+
+    let mut db_tx = pool.begin().await?;
+
+    // Locking the budget serializes moves, so two moves cannot spend the same balance
+    sqlx::query!("SELECT id FROM budgets WHERE id = $1 FOR UPDATE", budget_id)
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+    let envelopes = envelopes(pool, &budget, budget.end_date).await?;
+    let find = |line_item_id: Uuid| {
+        envelopes
+            .iter()
+            .find(|envelope| envelope.line_item_id == line_item_id)
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Line item {} is not an active envelope of budget {}",
+                    line_item_id, budget_id
+                ))
+            })
+    };
+    let source = find(dto.from_line_item_id)?;
+    find(dto.to_line_item_id)?;
+    if dto.amount > source.available {
+        return Err(AppError::Validation(format!(
+            "Envelope '{}' only has {} available",
+            source.label, source.available
+        )));
+    }
+
+    let envelope_move = query_as!(
+        EnvelopeMove,
+        r#"
+        INSERT INTO envelope_moves (
+            tenant_id, budget_id, from_line_item_id, to_line_item_id, amount, moved_on, memo, created_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, tenant_id, budget_id, from_line_item_id, to_line_item_id, amount, moved_on, memo,
+                  created_at, created_by
+        "#,
+        tenant_id,
+        budget_id,
+        dto.from_line_item_id,
+        dto.to_line_item_id,
+        dto.amount,
+        moved_on,
+        dto.memo,
+        created_by_user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(envelope_move)
+}
+
+/// History of a budget's envelope moves, newest first.
+pub async fn list_moves(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+) -> Result<Vec<EnvelopeMove>, AppError> {
+    info!(
+        "Service: Listing envelope moves for budget ID: {} tenant ID: {}",
+        budget_id, tenant_id
+    );
+
+    envelope_budget(pool, tenant_id, budget_id).await?;
+
+    let moves = query_as!(
+        EnvelopeMove,
+        r#"
+        SELECT id, tenant_id, budget_id, from_line_item_id, to_line_item_id, amount, moved_on, memo,
+               created_at, created_by
+        FROM envelope_moves
+        WHERE budget_id = $1 AND tenant_id = $2
+        ORDER BY moved_on DESC, created_at DESC
+        "#,
+        budget_id,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(moves)
+}
+
+/// The tenant's budget, provided it is in envelope mode.
+async fn envelope_budget(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    budget_id: Uuid,
+) -> Result<Budget, AppError> {
+    let budget = budget::get_budget_by_id(pool, tenant_id, budget_id).await?;
+    if !budget.is_envelope {
+        return Err(AppError::Validation(format!(
+            "Budget {} is not in envelope mode",
+            budget_id
+        )));
+    }
+    Ok(budget)
+}
+
+/// Balances of the budget's active envelopes, counting moves and spending up to `as_of`.
+async fn envelopes(
+    pool: &PgPool,
+    budget: &Budget,
+    as_of: NaiveDate,
+) -> Result<Vec<Envelope>, AppError> {
+    let lines = sqlx::query!(
+        r#"
+        SELECT bli.id, bli.budgeted_amount,
+               c.name as "category_name?", a.account_code as "account_code?", a.name as "account_name?"
+        FROM budget_line_items bli
+        LEFT JOIN categories c ON bli.category_id = c.id
+        LEFT JOIN accounts a ON bli.account_id = a.id
+        WHERE bli.budget_id = $1 AND bli.is_active = TRUE
+        ORDER BY c.name NULLS LAST, a.account_code NULLS LAST, a.name
+        "#,
+        budget.id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let move_rows = sqlx::query!(
+        r#"
+        SELECT from_line_item_id, to_line_item_id, SUM(amount) as "amount!"
+        FROM envelope_moves
+        WHERE budget_id = $1 AND moved_on <= $2
+        GROUP BY from_line_item_id, to_line_item_id
+        "#,
+        budget.id,
+        as_of
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut moved_in: HashMap<Uuid, Decimal> = HashMap::new();
+    let mut moved_out: HashMap<Uuid, Decimal> = HashMap::new();
+    for row in move_rows {
+        *moved_out.entry(row.from_line_item_id).or_default() += row.amount;
+        *moved_in.entry(row.to_line_item_id).or_default() += row.amount;
+    }
+
+    let mut spent: HashMap<Uuid, Decimal> = HashMap::new();
+    let actuals = budget_performance::line_actuals(
+        pool,
+        budget.tenant_id,
+        budget.id,
+        budget.start_date,
+        as_of,
+    )
+    .await?;
+    for ((line_item_id, _), actual) in actuals {
+        *spent.entry(line_item_id).or_default() += actual;
+    }
+
+    Ok(lines
+        .into_iter()
+        .map(|line| {
+            let moved_in = moved_in.get(&line.id).copied().unwrap_or(Decimal::ZERO);
+            let moved_out = moved_out.get(&line.id).copied().unwrap_or(Decimal::ZERO);
+            let assigned = line.budgeted_amount + moved_in - moved_out;
+            let spent = spent.get(&line.id).copied().unwrap_or(Decimal::ZERO);
+            Envelope {
+                line_item_id: line.id,
+                label: budget_performance::line_label(
+                    line.category_name,
+                    line.account_code,
+                    line.account_name,
+                ),
+                budgeted: line.budgeted_amount,
+                moved_in,
+                moved_out,
+                assigned,
+                spent,
+                available: assigned - spent,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,384 @@
+//! Events group transactions around a trip, conference or similar occasion.
+//!
+//! A transaction belongs to at most one event (`event_transactions`); assigning it to
+//! another event moves it. Event totals leave out voided transactions and reversal pairs,
+//! which cancel out, and are kept per currency without conversion.
+
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::event_dto::{AssignEventTransactionsDto, CreateEventDto, UpdateEventDto},
+        event::{
+            Event, EventAssignmentResult, EventCategoryTotal, EventCurrencyTotal, EventSummary,
+        },
+    },
+    utils::update_builder::UpdateBuilder,
+};
+
+/// Retrieves the active events of a tenant, most recent first.
+pub async fn list_events(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<Event>, AppError> {
+    info!("Service: Listing events for tenant ID: {}", tenant_id);
+
+    let events = query_as!(
+        Event,
+        r#"
+        SELECT id, tenant_id, name, description, start_date, end_date,
+               is_active, created_at, created_by, updated_at, updated_by
+        FROM events
+        WHERE tenant_id = $1 AND is_active = TRUE
+        ORDER BY start_date DESC NULLS LAST, name
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
+
+/// Retrieves a single active event by ID for a specific tenant.
+pub async fn get_event_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_id: Uuid,
+) -> Result<Event, AppError> {
+    info!(
+        "Service: Getting event with ID: {} for tenant ID: {}",
+        event_id, tenant_id
+    );
+
+    let event = query_as!(
+        Event,
+        r#"
+        SELECT id, tenant_id, name, description, start_date, end_date,
+               is_active, created_at, created_by, updated_at, updated_by
+        FROM events
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        event_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Event with ID {} not found for tenant {}",
+            event_id, tenant_id
+        ))
+    })?;
+
+    Ok(event)
+}
+
+/// Creates a new event for a specific tenant.
+pub async fn create_event(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateEventDto,
+) -> Result<Event, AppError> {
+    info!(
+        "Service: Creating new event '{}' for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    if let (Some(start), Some(end)) = (dto.start_date, dto.end_date) {
+        if end < start {
+            return Err(AppError::Validation(
+                "End date cannot be before start date".to_string(),
+            ));
+        }
+    }
+
+    let event = query_as!(
+        Event,
+        r#"
+        INSERT INTO events (tenant_id, name, description, start_date, end_date, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        RETURNING id, tenant_id, name, description, start_date, end_date,
+                  is_active, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.name,
+        dto.description,
+        dto.start_date,
+        dto.end_date,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(event)
+}
+
+/// Updates an existing event for a specific tenant.
+pub async fn update_event(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateEventDto,
+) -> Result<Event, AppError> {
+    info!(
+        "Service: Updating event with ID: {} for tenant ID: {}",
+        event_id, tenant_id
+    );
+
+    if dto.start_date.is_some() || dto.end_date.is_some() {
+        let current = get_event_by_id(pool, tenant_id, event_id).await?;
+        let start_date = dto.start_date.or(current.start_date);
+        let end_date = dto.end_date.or(current.end_date);
+        if let (Some(start), Some(end)) = (start_date, end_date) {
+            if end < start {
+                return Err(AppError::Validation(
+                    "Resulting end date cannot be before resulting start date".to_string(),
+                ));
+            }
+        }
+    }
+
+    let mut update = UpdateBuilder::new("events");
+    update
+        .set("name", dto.name)
+        .set("description", dto.description)
+        .set("start_date", dto.start_date)
+        .set("end_date", dto.end_date);
+    if update.is_empty() {
+        return Err(AppError::Validation(
+            "No fields provided for update".to_string(),
+        ));
+    }
+
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(event_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(" AND is_active = TRUE");
+    query.push(
+        r#"
+        RETURNING id, tenant_id, name, description, start_date, end_date,
+                  is_active, created_at, created_by, updated_at, updated_by
+        "#,
+    );
+
+    let event = query
+        .build_query_as::<Event>()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Event with ID {} not found for tenant {}",
+                event_id, tenant_id
+            ))
+        })?;
+
+    Ok(event)
+}
+
+/// Deactivates an event (soft delete). Its transactions stay assigned to it.
+pub async fn deactivate_event(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deactivating event with ID: {} for tenant ID: {}",
+        event_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE events
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        event_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Event with ID {} not found or already inactive for tenant {}",
+            event_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Assigns transactions to an event, moving any that belong to another event.
+/// Nothing is assigned if one of the transactions is not the tenant's.
+pub async fn assign_transactions(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_id: Uuid,
+    assigned_by_user_id: Uuid,
+    dto: AssignEventTransactionsDto,
+) -> Result<EventAssignmentResult, AppError> {
+    info!(
+        "Service: Assigning {} transactions to event ID: {} for tenant ID: {}",
+        dto.transaction_ids.len(),
+        event_id,
+        tenant_id
+    );
+
+    get_event_by_id(pool, tenant_id, event_id).await?;
+
+    let mut db_tx = pool.begin().await?;
+
+    let unknown = sqlx::query_scalar!(
+        r#"
+        SELECT id as "id!"
+        FROM UNNEST($1::uuid[]) as requested(id)
+        WHERE NOT EXISTS (SELECT 1 FROM transactions t WHERE t.id = requested.id AND t.tenant_id = $2)
+        "#,
+        &dto.transaction_ids,
+        tenant_id
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    if !unknown.is_empty() {
+        let ids: Vec<String> = unknown.iter().map(Uuid::to_string).collect();
+        return Err(AppError::NotFound(format!(
+            "Transactions not found for tenant {}: {}",
+            tenant_id,
+            ids.join(", ")
+        )));
+    }
+
+    let assigned = sqlx::query!(
+        r#"
+        INSERT INTO event_transactions (transaction_id, event_id, created_by)
+        SELECT DISTINCT id, $2::uuid, $3::uuid FROM UNNEST($1::uuid[]) as requested(id)
+        ON CONFLICT (transaction_id) DO UPDATE
+        SET event_id = EXCLUDED.event_id, created_at = NOW(), created_by = EXCLUDED.created_by
+        "#,
+        &dto.transaction_ids,
+        event_id,
+        assigned_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    db_tx.commit().await?;
+
+    Ok(EventAssignmentResult { assigned })
+}
+
+/// Removes a transaction from an event.
+pub async fn unassign_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_id: Uuid,
+    transaction_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Removing transaction ID: {} from event ID: {} for tenant ID: {}",
+        transaction_id, event_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        r#"
+        DELETE FROM event_transactions et
+        USING events e
+        WHERE et.event_id = e.id
+          AND et.event_id = $1 AND et.transaction_id = $2 AND e.tenant_id = $3
+        "#,
+        event_id,
+        transaction_id,
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Transaction {} is not assigned to event {}",
+            transaction_id, event_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Totals of an event's transactions by currency and category.
+pub async fn event_summary(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_id: Uuid,
+) -> Result<EventSummary, AppError> {
+    info!(
+        "Service: Event summary for event ID: {} for tenant ID: {}",
+        event_id, tenant_id
+    );
+
+    let event = get_event_by_id(pool, tenant_id, event_id).await?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            t.currency_code, t.category_id, COALESCE(c.name, 'Uncategorized') as "category_name!",
+            COUNT(*) as "transaction_count!",
+            COALESCE(SUM(t.amount) FILTER (WHERE t.type = 'INCOME'), 0) as "income!",
+            COALESCE(SUM(t.amount) FILTER (WHERE t.type = 'EXPENSE'), 0) as "expense!"
+        FROM event_transactions et
+        JOIN transactions t ON t.id = et.transaction_id
+        LEFT JOIN categories c ON c.id = t.category_id
+        WHERE et.event_id = $1
+          AND t.tenant_id = $2
+          AND t.status <> 'VOIDED'
+          AND t.reversal_of_id IS NULL
+          AND t.reversed_by_id IS NULL
+        GROUP BY t.currency_code, t.category_id, c.name
+        ORDER BY t.currency_code, 3
+        "#,
+        event_id,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Rows arrive sorted by currency, so each currency's categories are contiguous
+    let mut currencies: Vec<EventCurrencyTotal> = Vec::new();
+    for row in rows {
+        if currencies.last().map(|c| &c.currency_code) != Some(&row.currency_code) {
+            currencies.push(EventCurrencyTotal {
+                currency_code: row.currency_code,
+                transaction_count: 0,
+                income: Decimal::ZERO,
+                expense: Decimal::ZERO,
+                categories: Vec::new(),
+            });
+        }
+        let currency = currencies.last_mut().expect("pushed above");
+        currency.transaction_count += row.transaction_count;
+        currency.income += row.income;
+        currency.expense += row.expense;
+        currency.categories.push(EventCategoryTotal {
+            category_id: row.category_id,
+            category_name: row.category_name,
+            transaction_count: row.transaction_count,
+            income: row.income,
+            expense: row.expense,
+        });
+    }
+
+    Ok(EventSummary {
+        event_id: event.id,
+        name: event.name,
+        start_date: event.start_date,
+        end_date: event.end_date,
+        currencies,
+    })
+}
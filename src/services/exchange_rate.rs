@@ -1,20 +1,40 @@
+use std::{collections::HashMap, str::FromStr};
+
 use sqlx::{query_as, PgPool};
 use uuid::Uuid;
-use tracing::info;
-use chrono::NaiveDate;
+use tracing::{info, warn};
+use chrono::{DateTime, NaiveDate, Utc};
+use reqwest::Client;
+use serde_json::Value as JsonValue;
 
 use crate::{
+    config,
     error::AppError,
     models::{
-        exchange_rate::ExchangeRate,
-        dto::exchange_rate_dto::{CreateExchangeRateDto, UpdateExchangeRateDto},
+        exchange_rate::{ExchangeRate, ExchangeRateFetchSettings, ExchangeRateRefreshResult},
+        dto::exchange_rate_dto::{
+            CreateExchangeRateDto, UpdateExchangeRateDto, UpdateExchangeRateFetchSettingsDto,
+        },
+    },
+    services::{
+        permission::{self, RATES_MANAGE},
+        reference_cache::{self, ReferenceData},
     },
 };
 use rust_decimal::Decimal;
 
+const ECB_DAILY_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml";
+const OPEN_EXCHANGE_RATES_URL: &str = "https://openexchangerates.org/api/latest.json";
+
+/// Decimal places kept for cross rates (matches `exchange_rates.rate NUMERIC(18, 6)`).
+pub const RATE_SCALE: u32 = 6;
+
 
 /// Retrieves a list of exchange rates for a given tenant or system-wide.
 pub async fn list_exchange_rates(pool: &PgPool, tenant_id: Option<Uuid>) -> Result<Vec<ExchangeRate>, AppError> {
+    if let Some(rates) = reference_cache::exchange_rates().get(&tenant_id).await {
+        return Ok(rates);
+    }
     info!("Service: Listing exchange rates for tenant ID: {:?}", tenant_id);
 
     let rates = query_as!(
@@ -33,6 +53,7 @@ pub async fn list_exchange_rates(pool: &PgPool, tenant_id: Option<Uuid>) -> Resu
     )
     .fetch_all(pool)
     .await?;
+    reference_cache::exchange_rates().insert(tenant_id, rates.clone()).await;
 
     Ok(rates)
 }
@@ -59,50 +80,6 @@ pub async fn get_exchange_rate_by_id(pool: &PgPool, rate_id: Uuid) -> Result<Exc
     Ok(rate)
 }
 
-/// Retrieves the latest exchange rate for a given currency pair, optionally tenant-specific.
-pub async fn get_latest_exchange_rate(
-    pool: &PgPool,
-    tenant_id: Option<Uuid>,
-    base_currency_code: &str,
-    target_currency_code: &str,
-) -> Result<ExchangeRate, AppError> {
-    info!(
-        "Service: Getting latest exchange rate for tenant {:?}, base: {}, target: {}",
-        tenant_id, base_currency_code, target_currency_code
-    );
-
-    let rate = query_as!(
-        ExchangeRate,
-        r#"
-        SELECT
-            id, tenant_id, base_currency_code, target_currency_code, rate, rate_date,
-            source, created_at, created_by, updated_at, updated_by
-        FROM exchange_rates
-        WHERE
-            ($1::uuid IS NULL AND tenant_id IS NULL) OR
-            ($1::uuid IS NOT NULL AND tenant_id = $1)
-            AND base_currency_code = $2
-            AND target_currency_code = $3
-        ORDER BY rate_date DESC, created_at DESC
-        LIMIT 1
-        "#,
-        tenant_id,
-        base_currency_code,
-        target_currency_code
-    )
-    .fetch_optional(pool)
-    .await?
-    .ok_or_else(|| {
-        AppError::NotFound(format!(
-            "No exchange rate found for tenant {:?}, base {} to target {}",
-            tenant_id, base_currency_code, target_currency_code
-        ))
-    })?;
-
-    Ok(rate)
-}
-
-
 /// Creates a new exchange rate.
 pub async fn create_exchange_rate(
     pool: &PgPool,
@@ -133,6 +110,7 @@ pub async fn create_exchange_rate(
     )
     .fetch_one(pool)
     .await?;
+    reference_cache::changed(pool, ReferenceData::ExchangeRates).await;
 
     Ok(new_rate)
 }
@@ -146,61 +124,31 @@ pub async fn update_exchange_rate(
 ) -> Result<ExchangeRate, AppError> {
     info!("Service: Updating exchange rate with ID: {}", rate_id);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
-
-    if let Some(rate) = dto.rate {
-        update_cols.push(format!("rate = ${}", param_idx));
-        update_values.push(Box::new(rate));
-        param_idx += 1;
-    }
-    if let Some(rate_date) = dto.rate_date {
-        update_cols.push(format!("rate_date = ${}", param_idx));
-        update_values.push(Box::new(rate_date));
-        param_idx += 1;
-    }
-    if let Some(source) = dto.source {
-        update_cols.push(format!("source = ${}", param_idx));
-        update_values.push(Box::new(source));
-        param_idx += 1;
-    }
-
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
-
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
-    }
-
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+    let updated_rate = query_as!(
+        ExchangeRate,
         r#"
         UPDATE exchange_rates
-        SET {}
-        WHERE id = ${}
+        SET
+            rate = COALESCE($2, rate),
+            rate_date = COALESCE($3, rate_date),
+            source = COALESCE($4, source),
+            updated_at = NOW(),
+            updated_by = $5
+        WHERE id = $1
         RETURNING
             id, tenant_id, base_currency_code, target_currency_code, rate, rate_date,
             source, created_at, created_by, updated_at, updated_by
         "#,
-        update_clause, param_idx // rate_id will be the last parameter
-    );
-
-    let mut query = sqlx::query_as::<_, ExchangeRate>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
-    }
-    // Bind rate_id last
-    query = query.bind(rate_id);
-
-    let updated_rate = query
-        .fetch_optional(pool)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Exchange rate with ID {} not found", rate_id)))?;
+        rate_id,
+        dto.rate,
+        dto.rate_date,
+        dto.source,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Exchange rate with ID {} not found", rate_id)))?;
+    reference_cache::changed(pool, ReferenceData::ExchangeRates).await;
 
     Ok(updated_rate)
 }
@@ -223,6 +171,424 @@ pub async fn delete_exchange_rate(pool: &PgPool, rate_id: Uuid) -> Result<(), Ap
     if affected_rows == 0 {
         return Err(AppError::NotFound(format!("Exchange rate with ID {} not found", rate_id)));
     }
+    reference_cache::changed(pool, ReferenceData::ExchangeRates).await;
+
+    Ok(())
+}
+
+// --- Automatic rate fetching ---
+//
+// The deployment picks one provider (`EXCHANGE_RATE_PROVIDER`); tenants opt in through
+// `exchange_rate_fetch_settings`. Rates are stored per tenant against its base currency,
+// cross-computed from the provider's base. A rate entered by hand (any other `source`)
+// for the same pair and day is never overwritten.
+
+/// A daily rate source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateProvider {
+    /// European Central Bank reference rates (EUR base, no API key).
+    Ecb,
+    /// Open Exchange Rates (USD base on the free plan, needs `CURRENCY_API_KEY`).
+    OpenExchangeRates,
+}
+
+impl RateProvider {
+    /// The deployment's provider (`EXCHANGE_RATE_PROVIDER`, ECB by default).
+    pub fn configured() -> Self {
+        config::get().integrations.exchange_rate_provider
+    }
+
+    /// Recorded as `exchange_rates.source` on every rate this provider supplies.
+    pub fn source(&self) -> &'static str {
+        match self {
+            RateProvider::Ecb => "ECB",
+            RateProvider::OpenExchangeRates => "OPEN_EXCHANGE_RATES",
+        }
+    }
+
+    /// Downloads the latest published rates. `CURRENCY_API_URL` overrides the endpoint.
+    pub async fn fetch(&self) -> Result<ProviderRates, AppError> {
+        let integrations = &config::get().integrations;
+        let url = integrations.currency_api_url.clone();
+        let client = Client::new();
+        match self {
+            RateProvider::Ecb => {
+                let body = get_text(client.get(url.as_deref().unwrap_or(ECB_DAILY_URL))).await?;
+                parse_ecb_daily(&body)
+            }
+            RateProvider::OpenExchangeRates => {
+                let app_id = integrations.currency_api_key.clone().ok_or_else(|| {
+                    AppError::InternalServerError(
+                        "CURRENCY_API_KEY must be set for Open Exchange Rates".to_string(),
+                    )
+                })?;
+                let request = client
+                    .get(url.as_deref().unwrap_or(OPEN_EXCHANGE_RATES_URL))
+                    .query(&[("app_id", app_id)]);
+                parse_open_exchange_rates(&get_text(request).await?)
+            }
+        }
+    }
+}
+
+impl FromStr for RateProvider {
+    type Err = String;
+
+    /// Accepts the `EXCHANGE_RATE_PROVIDER` values: `ecb` or `open_exchange_rates`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ecb" => Ok(RateProvider::Ecb),
+            "open_exchange_rates" => Ok(RateProvider::OpenExchangeRates),
+            _ => Err("expected 'ecb' or 'open_exchange_rates'".to_string()),
+        }
+    }
+}
+
+/// One day's rates as published by a provider: units of each currency per one `base`.
+#[derive(Debug, Clone)]
+pub struct ProviderRates {
+    pub base_currency_code: String,
+    pub rate_date: NaiveDate,
+    pub rates: HashMap<String, Decimal>,
+}
+
+impl ProviderRates {
+    /// Rates from `base` to every other published currency, or `None` when the provider
+    /// does not publish `base`.
+    pub fn cross_rates(&self, base: &str) -> Option<HashMap<String, Decimal>> {
+        let per_provider_base = |code: &str| {
+            if code == self.base_currency_code {
+                Some(Decimal::ONE)
+            } else {
+                self.rates.get(code).copied()
+            }
+        };
+        let base_rate = per_provider_base(base).filter(|rate| !rate.is_zero())?;
+
+        let cross = self
+            .rates
+            .keys()
+            .map(String::as_str)
+            .chain(std::iter::once(self.base_currency_code.as_str()))
+            .filter(|code| *code != base)
+            .filter_map(|code| {
+                let rate = (per_provider_base(code)? / base_rate).round_dp(RATE_SCALE);
+                (rate > Decimal::ZERO).then(|| (code.to_string(), rate))
+            })
+            .collect();
+        Some(cross)
+    }
+}
+
+/// Retrieves the tenant's auto-fetch settings.
+pub async fn get_fetch_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<ExchangeRateFetchSettings, AppError> {
+    info!("Service: Getting exchange rate fetch settings for tenant ID: {}", tenant_id);
+    permission::require_permission(pool, tenant_id, user_id, RATES_MANAGE).await?;
+
+    let settings = query_as!(
+        ExchangeRateFetchSettings,
+        r#"
+        SELECT
+            tenant_id, is_enabled, last_fetched_at, last_rate_date, last_error,
+            created_at, created_by, updated_at, updated_by
+        FROM exchange_rate_fetch_settings
+        WHERE tenant_id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!("Tenant {} has not set up exchange rate fetching", tenant_id))
+    })?;
+
+    Ok(settings)
+}
+
+/// Opts the tenant in or out of the daily fetch. Fetched rates are attributed to the
+/// user who last changed this setting.
+pub async fn update_fetch_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: UpdateExchangeRateFetchSettingsDto,
+) -> Result<ExchangeRateFetchSettings, AppError> {
+    info!(
+        "Service: Setting exchange rate auto-fetch to {} for tenant ID: {}",
+        dto.is_enabled, tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, RATES_MANAGE).await?;
+
+    let settings = query_as!(
+        ExchangeRateFetchSettings,
+        r#"
+        INSERT INTO exchange_rate_fetch_settings (tenant_id, is_enabled, created_by, updated_by)
+        VALUES ($1, $2, $3, $3)
+        ON CONFLICT (tenant_id) DO UPDATE
+        SET is_enabled = EXCLUDED.is_enabled, updated_at = NOW(), updated_by = EXCLUDED.updated_by
+        RETURNING
+            tenant_id, is_enabled, last_fetched_at, last_rate_date, last_error,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.is_enabled,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(settings)
+}
+
+/// Fetches the latest rates for the tenant right away, whether or not it opted in to
+/// the daily fetch.
+pub async fn refresh_exchange_rates(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<ExchangeRateRefreshResult, AppError> {
+    info!("Service: Refreshing exchange rates for tenant ID: {}", tenant_id);
+    permission::require_permission(pool, tenant_id, user_id, RATES_MANAGE).await?;
+
+    let provider = RateProvider::configured();
+    let fetched = provider.fetch().await;
+    let result = match &fetched {
+        Ok(rates) => store_tenant_rates(pool, tenant_id, user_id, provider, rates).await,
+        Err(e) => Err(AppError::ServiceUnavailable(format!(
+            "Could not fetch exchange rates from {}: {}",
+            provider.source(),
+            e
+        ))),
+    };
+    record_fetch(pool, tenant_id, fetched.ok().map(|r| r.rate_date), result.as_ref().err()).await?;
+
+    result
+}
+
+/// Pulls the provider's rates once and stores them for every tenant that opted in.
+/// Run by the exchange rate scheduler. Returns the number of tenants updated.
+pub async fn fetch_exchange_rates_for_tenants(pool: &PgPool) -> Result<usize, AppError> {
+    let tenants = sqlx::query!(
+        r#"
+        SELECT s.tenant_id, s.updated_by
+        FROM exchange_rate_fetch_settings s
+        JOIN tenants t ON t.id = s.tenant_id
+        WHERE s.is_enabled AND t.is_active
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    if tenants.is_empty() {
+        return Ok(0);
+    }
+
+    let provider = RateProvider::configured();
+    let rates = match provider.fetch().await {
+        Ok(rates) => rates,
+        Err(e) => {
+            for tenant in &tenants {
+                record_fetch(pool, tenant.tenant_id, None, Some(&e)).await?;
+            }
+            return Err(e);
+        }
+    };
+
+    let mut updated = 0;
+    for tenant in tenants {
+        let result = store_tenant_rates(pool, tenant.tenant_id, tenant.updated_by, provider, &rates).await;
+        if let Err(e) = &result {
+            warn!("Failed to store exchange rates for tenant {}: {}", tenant.tenant_id, e);
+        } else {
+            updated += 1;
+        }
+        record_fetch(pool, tenant.tenant_id, Some(rates.rate_date), result.as_ref().err()).await?;
+    }
+
+    if updated > 0 {
+        info!(
+            "Stored {} rates dated {} for {} tenant(s)",
+            provider.source(),
+            rates.rate_date,
+            updated
+        );
+    }
+    Ok(updated)
+}
+
+/// Upserts the provider's rates from the tenant's base currency to every active currency.
+async fn store_tenant_rates(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    provider: RateProvider,
+    rates: &ProviderRates,
+) -> Result<ExchangeRateRefreshResult, AppError> {
+    let base_currency_code = sqlx::query_scalar!(
+        "SELECT base_currency_code FROM tenants WHERE id = $1",
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+
+    let cross = rates.cross_rates(&base_currency_code).ok_or_else(|| {
+        AppError::Validation(format!(
+            "{} does not publish rates for {}",
+            provider.source(),
+            base_currency_code
+        ))
+    })?;
+
+    let active_currencies: Vec<String> = sqlx::query_scalar!(
+        "SELECT code FROM currencies WHERE is_active = TRUE"
+    )
+    .fetch_all(pool)
+    .await?;
+    let (codes, values): (Vec<String>, Vec<Decimal>) = active_currencies
+        .into_iter()
+        .filter_map(|code| cross.get(&code).map(|rate| (code, *rate)))
+        .unzip();
+
+    let rates_updated = sqlx::query!(
+        r#"
+        INSERT INTO exchange_rates (
+            tenant_id, base_currency_code, target_currency_code, rate, rate_date,
+            source, created_by, updated_by
+        )
+        SELECT $1, $2, t.code, t.rate, $3, $4, $5, $5
+        FROM UNNEST($6::text[], $7::numeric[]) AS t(code, rate)
+        ON CONFLICT (tenant_id, base_currency_code, target_currency_code, rate_date) DO UPDATE
+        SET rate = EXCLUDED.rate, updated_at = NOW(), updated_by = EXCLUDED.updated_by
+        WHERE exchange_rates.source = EXCLUDED.source
+        "#,
+        tenant_id,
+        base_currency_code,
+        rates.rate_date,
+        provider.source(),
+        user_id,
+        &codes,
+        &values
+    )
+    .execute(pool)
+    .await?
+    .rows_affected() as usize;
+    if rates_updated > 0 {
+        reference_cache::changed(pool, ReferenceData::ExchangeRates).await;
+    }
 
+    Ok(ExchangeRateRefreshResult {
+        source: provider.source().to_string(),
+        base_currency_code,
+        rate_date: rates.rate_date,
+        rates_updated,
+    })
+}
+
+/// Records the outcome of a fetch on the tenant's settings row, if it has one.
+async fn record_fetch(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    rate_date: Option<NaiveDate>,
+    error: Option<&AppError>,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE exchange_rate_fetch_settings
+        SET
+            last_fetched_at = CASE WHEN $3::text IS NULL THEN NOW() ELSE last_fetched_at END,
+            last_rate_date = COALESCE($2, last_rate_date),
+            last_error = $3
+        WHERE tenant_id = $1
+        "#,
+        tenant_id,
+        rate_date,
+        error.map(|e| e.to_string())
+    )
+    .execute(pool)
+    .await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn get_text(request: reqwest::RequestBuilder) -> Result<String, AppError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::ServiceUnavailable(format!("Rate provider unreachable: {}", e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AppError::ServiceUnavailable(format!("Rate provider returned {}", status)));
+    }
+    response
+        .text()
+        .await
+        .map_err(|e| AppError::ServiceUnavailable(format!("Failed to read rate provider response: {}", e)))
+}
+
+/// Parses the ECB daily reference rates file:
+/// `<Cube time='2025-07-10'><Cube currency='USD' rate='1.1702'/>...</Cube>`.
+fn parse_ecb_daily(xml: &str) -> Result<ProviderRates, AppError> {
+    let malformed = |what: &str| AppError::InternalServerError(format!("Malformed ECB rates file: {}", what));
+
+    let mut rate_date = None;
+    let mut rates = HashMap::new();
+    for tag in xml.split("<Cube").skip(1) {
+        let tag = tag.split('>').next().unwrap_or_default();
+        if let Some(time) = xml_attribute(tag, "time") {
+            rate_date = Some(NaiveDate::parse_from_str(time, "%Y-%m-%d").map_err(|_| malformed("bad date"))?);
+        }
+        if let (Some(currency), Some(rate)) = (xml_attribute(tag, "currency"), xml_attribute(tag, "rate")) {
+            let rate = rate.parse::<Decimal>().map_err(|_| malformed("bad rate"))?;
+            rates.insert(currency.to_string(), rate);
+        }
+    }
+
+    Ok(ProviderRates {
+        base_currency_code: "EUR".to_string(),
+        rate_date: rate_date.ok_or_else(|| malformed("no publication date"))?,
+        rates,
+    })
+}
+
+fn xml_attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    ['\'', '"'].into_iter().find_map(|quote| {
+        let start = tag.find(&format!("{}={}", name, quote))? + name.len() + 2;
+        let len = tag[start..].find(quote)?;
+        Some(&tag[start..start + len])
+    })
+}
+
+/// Parses an Open Exchange Rates `latest.json` response:
+/// `{"timestamp": 1752105600, "base": "USD", "rates": {"EUR": 0.8546, ...}}`.
+fn parse_open_exchange_rates(body: &str) -> Result<ProviderRates, AppError> {
+    let malformed = |what: &str| {
+        AppError::InternalServerError(format!("Malformed Open Exchange Rates response: {}", what))
+    };
+    let payload: JsonValue = serde_json::from_str(body).map_err(|e| malformed(&e.to_string()))?;
+
+    let rate_date = payload["timestamp"]
+        .as_i64()
+        .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0))
+        .ok_or_else(|| malformed("no timestamp"))?
+        .date_naive();
+    let base_currency_code = payload["base"].as_str().ok_or_else(|| malformed("no base"))?.to_string();
+    let rates = payload["rates"]
+        .as_object()
+        .ok_or_else(|| malformed("no rates"))?
+        .iter()
+        .filter_map(|(code, rate)| {
+            let text = rate.to_string();
+            let rate = text.parse::<Decimal>().or_else(|_| Decimal::from_scientific(&text)).ok()?;
+            Some((code.clone(), rate))
+        })
+        .collect();
+
+    Ok(ProviderRates {
+        base_currency_code,
+        rate_date,
+        rates,
+    })
+}
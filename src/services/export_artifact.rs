@@ -0,0 +1,419 @@
+//! Export artifacts: report files rendered in the background and kept on the storage
+//! backend until they expire.
+//!
+//! Starting an export returns a PENDING artifact to poll; once READY its file can be
+//! downloaded (and resumed with `Range` requests) until `expires_at`, after which downloads
+//! answer 410 Gone and the cleanup scheduler deletes the file. An export holds what its
+//! creator was allowed to see, so artifacts are only visible to the user who started them.
+
+use chrono::{Duration, NaiveDate, Utc};
+use sqlx::{query_as, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    config, db,
+    error::AppError,
+    models::export_artifact::{ExportArtifact, ExportStatus},
+    services::{custom_report, event, field_policy::FieldAccess, file_storage, report_export},
+    utils::{crypto, csv_format::CsvFormat},
+};
+
+/// Artifact kind of custom report exports.
+pub const CUSTOM_REPORT: &str = "CUSTOM_REPORT";
+
+/// Artifact kind of event report exports.
+pub const EVENT_REPORT: &str = "EVENT_REPORT";
+
+/// Artifact kind of tenant backups (see `tenant_backup`).
+pub const TENANT_BACKUP: &str = "TENANT_BACKUP";
+
+/// Artifact kind of anonymized tenant copies (see `tenant_backup`).
+pub const TENANT_ANONYMIZED: &str = "TENANT_ANONYMIZED";
+
+const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Most recent artifacts returned by `list_exports`.
+const LIST_LIMIT: i64 = 50;
+
+/// A READY artifact's stored file, for download.
+pub struct ExportFile {
+    pub storage_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+/// Starts exporting a custom report for `from_date..=to_date` and returns the pending
+/// artifact.
+#[allow(clippy::too_many_arguments)]
+pub async fn start_custom_report_export(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    access: FieldAccess,
+    format: CsvFormat,
+) -> Result<ExportArtifact, AppError> {
+    info!(
+        "Service: Starting export of custom report ID: {} for tenant ID: {}",
+        report_id, tenant_id
+    );
+
+    if from_date > to_date {
+        return Err(AppError::Validation(
+            "from_date must not be after to_date".to_string(),
+        ));
+    }
+    let report =
+        custom_report::get_custom_report_by_id(pool, tenant_id, user_id, report_id).await?;
+    let artifact =
+        create_artifact(pool, tenant_id, user_id, CUSTOM_REPORT, report_id, false).await?;
+
+    let pool = pool.clone();
+    let artifact_id = artifact.id;
+    tokio::spawn(db::with_tenant_scope(Some(tenant_id), async move {
+        let result = report_export::render_custom_report(
+            &pool, &report, &access, from_date, to_date, &format,
+        )
+        .await;
+        let result = match result {
+            Ok(rendered) => {
+                store_artifact(
+                    &pool,
+                    artifact_id,
+                    tenant_id,
+                    &rendered.file_name,
+                    CSV_CONTENT_TYPE,
+                    rendered.csv.into_bytes(),
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            fail_artifact(&pool, artifact_id, &e).await;
+        }
+    }));
+
+    Ok(artifact)
+}
+
+/// Starts exporting an event report and returns the pending artifact.
+pub async fn start_event_report_export(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    event_id: Uuid,
+    access: FieldAccess,
+    format: CsvFormat,
+) -> Result<ExportArtifact, AppError> {
+    info!(
+        "Service: Starting export of event ID: {} for tenant ID: {}",
+        event_id, tenant_id
+    );
+
+    event::get_event_by_id(pool, tenant_id, event_id).await?;
+    let artifact = create_artifact(pool, tenant_id, user_id, EVENT_REPORT, event_id, false).await?;
+
+    let pool = pool.clone();
+    let artifact_id = artifact.id;
+    tokio::spawn(db::with_tenant_scope(Some(tenant_id), async move {
+        let result =
+            report_export::render_event_report(&pool, tenant_id, event_id, &access, &format).await;
+        let result = match result {
+            Ok(rendered) => {
+                store_artifact(
+                    &pool,
+                    artifact_id,
+                    tenant_id,
+                    &rendered.file_name,
+                    CSV_CONTENT_TYPE,
+                    rendered.csv.into_bytes(),
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            fail_artifact(&pool, artifact_id, &e).await;
+        }
+    }));
+
+    Ok(artifact)
+}
+
+/// Lists the user's most recent exports in the tenant, newest first.
+pub async fn list_exports(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<ExportArtifact>, AppError> {
+    info!("Service: Listing exports for tenant ID: {}", tenant_id);
+
+    let artifacts = query_as!(
+        ExportArtifact,
+        r#"
+        SELECT
+            id, tenant_id, kind, source_id, status as "status: ExportStatus", file_name, content_type,
+            size_bytes, sha256, storage_key, is_encrypted, error_message, expires_at, completed_at,
+            created_at, created_by
+        FROM export_artifacts
+        WHERE tenant_id = $1 AND created_by = $2
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+        tenant_id,
+        user_id,
+        LIST_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(artifacts)
+}
+
+/// Retrieves one of the user's exports.
+pub async fn get_export(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    artifact_id: Uuid,
+) -> Result<ExportArtifact, AppError> {
+    info!(
+        "Service: Getting export {} for tenant ID: {}",
+        artifact_id, tenant_id
+    );
+
+    let artifact = query_as!(
+        ExportArtifact,
+        r#"
+        SELECT
+            id, tenant_id, kind, source_id, status as "status: ExportStatus", file_name, content_type,
+            size_bytes, sha256, storage_key, is_encrypted, error_message, expires_at, completed_at,
+            created_at, created_by
+        FROM export_artifacts
+        WHERE id = $1 AND tenant_id = $2 AND created_by = $3
+        "#,
+        artifact_id,
+        tenant_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Export with ID {} not found for tenant {}",
+            artifact_id, tenant_id
+        ))
+    })?;
+
+    Ok(artifact)
+}
+
+/// The stored file of a READY export. Expired exports are gone even before the cleanup
+/// scheduler has deleted them.
+pub async fn get_export_file(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    artifact_id: Uuid,
+) -> Result<ExportFile, AppError> {
+    let artifact = get_export(pool, tenant_id, user_id, artifact_id).await?;
+    if artifact.expires_at <= Utc::now() {
+        return Err(AppError::Gone(format!(
+            "Export {} expired at {}",
+            artifact_id, artifact.expires_at
+        )));
+    }
+
+    match artifact {
+        ExportArtifact {
+            status: ExportStatus::Ready,
+            storage_key: Some(storage_key),
+            file_name: Some(file_name),
+            size_bytes: Some(size_bytes),
+            sha256: Some(sha256),
+            content_type,
+            ..
+        } => Ok(ExportFile {
+            storage_key,
+            file_name,
+            content_type,
+            size_bytes: size_bytes as u64,
+            sha256,
+        }),
+        ExportArtifact {
+            status: ExportStatus::Failed,
+            error_message,
+            ..
+        } => Err(AppError::Conflict(format!(
+            "Export {} failed: {}",
+            artifact_id,
+            error_message.unwrap_or_default()
+        ))),
+        _ => Err(AppError::Conflict(format!(
+            "Export {} is not ready yet",
+            artifact_id
+        ))),
+    }
+}
+
+/// Deletes one of the user's exports and its file.
+pub async fn delete_export(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    artifact_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting export {} for tenant ID: {}",
+        artifact_id, tenant_id
+    );
+
+    let artifact = get_export(pool, tenant_id, user_id, artifact_id).await?;
+    if let Some(storage_key) = &artifact.storage_key {
+        file_storage::delete_object(storage_key).await?;
+    }
+    sqlx::query!("DELETE FROM export_artifacts WHERE id = $1", artifact_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes expired exports and their files. Returns how many were deleted; an export whose
+/// file cannot be deleted is kept for the next run.
+pub async fn purge_expired_exports(pool: &PgPool) -> Result<usize, AppError> {
+    let expired = sqlx::query!(
+        "SELECT id, storage_key FROM export_artifacts WHERE expires_at <= NOW() ORDER BY expires_at"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut purged = 0;
+    for artifact in expired {
+        if let Some(storage_key) = &artifact.storage_key {
+            if let Err(e) = file_storage::delete_object(storage_key).await {
+                warn!(
+                    "Could not delete file of expired export {}: {}",
+                    artifact.id, e
+                );
+                continue;
+            }
+        }
+        sqlx::query!("DELETE FROM export_artifacts WHERE id = $1", artifact.id)
+            .execute(pool)
+            .await?;
+        purged += 1;
+    }
+
+    if purged > 0 {
+        info!("Service: Purged {} expired exports", purged);
+    }
+    Ok(purged)
+}
+
+/// Creates a pending artifact that expires `EXPORT_TTL_HOURS` from now.
+pub async fn create_artifact(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    kind: &str,
+    source_id: Uuid,
+    is_encrypted: bool,
+) -> Result<ExportArtifact, AppError> {
+    let ttl_hours = config::get().storage.export_ttl_hours;
+    let expires_at = Utc::now() + Duration::hours(ttl_hours as i64);
+
+    let artifact = query_as!(
+        ExportArtifact,
+        r#"
+        INSERT INTO export_artifacts (tenant_id, kind, source_id, is_encrypted, expires_at, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING
+            id, tenant_id, kind, source_id, status as "status: ExportStatus", file_name, content_type,
+            size_bytes, sha256, storage_key, is_encrypted, error_message, expires_at, completed_at,
+            created_at, created_by
+        "#,
+        tenant_id,
+        kind,
+        source_id,
+        is_encrypted,
+        expires_at,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(artifact)
+}
+
+/// Stores a finished file and marks the artifact READY.
+pub async fn store_artifact(
+    pool: &PgPool,
+    artifact_id: Uuid,
+    tenant_id: Uuid,
+    file_name: &str,
+    content_type: &str,
+    body: Vec<u8>,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Storing export {} ({} bytes) for tenant ID: {}",
+        artifact_id,
+        body.len(),
+        tenant_id
+    );
+
+    let size_bytes = body.len() as i64;
+    let sha256 = crypto::sha256_hex(&body);
+    let storage_key = format!("exports/{}/{}", tenant_id, artifact_id);
+    file_storage::put_object(&storage_key, body, content_type).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE export_artifacts
+        SET status = 'READY', file_name = $2, content_type = $3, size_bytes = $4, sha256 = $5,
+            storage_key = $6, completed_at = NOW()
+        WHERE id = $1
+        "#,
+        artifact_id,
+        file_name,
+        content_type,
+        size_bytes,
+        sha256,
+        storage_key
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Marks an artifact as FAILED, logging if even that fails.
+pub async fn fail_artifact(pool: &PgPool, artifact_id: Uuid, error: &AppError) {
+    warn!("Export {} failed: {}", artifact_id, error);
+    // Like the HTTP responses, internal details stay in the log
+    let message = match error {
+        AppError::DatabaseError(_) | AppError::InternalServerError(_) => {
+            "The export failed unexpectedly; please try again".to_string()
+        }
+        other => other.to_string(),
+    };
+    let result = sqlx::query!(
+        r#"
+        UPDATE export_artifacts
+        SET status = 'FAILED', error_message = $2, completed_at = NOW()
+        WHERE id = $1
+        "#,
+        artifact_id,
+        message
+    )
+    .execute(pool)
+    .await;
+    if let Err(e) = result {
+        warn!("Could not record failure of export {}: {}", artifact_id, e);
+    }
+}
@@ -0,0 +1,722 @@
+use std::collections::HashMap;
+
+use serde_json::Value as JsonValue;
+use sqlx::{query_as, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    db::with_retry,
+    error::AppError,
+    models::{
+        dto::ext_conn_dto::{CreateExtConnDto, ExtConnSyncSummary, UpdateExtConnDto},
+        dto::external_account_dto::UpdateExternalAccountDto,
+        ext_conn::{ExtConn, ExtConnStatus},
+        external_account::ExternalAccount,
+        external_transactions_staging::{ExternalTransactionsStaging, StagingStatus},
+    },
+    services::{
+        bank_connector::{
+            connector_for, BankConnector, ConnectorError, ProviderAccount, ProviderTransaction,
+        },
+        ext_provider,
+        merchant_normalization::MerchantNormalizer,
+        transaction_matching,
+    },
+    utils::crypto::{decrypt_secret, encrypt_secret},
+};
+
+/// Key in `ext_conns.metadata` holding the provider's incremental sync cursor.
+const SYNC_CURSOR_KEY: &str = "sync_cursor";
+/// Metadata keys the server maintains; clients may not set them.
+const SERVER_METADATA_KEYS: &[&str] = &[SYNC_CURSOR_KEY];
+
+/// Retrieves a list of external connections for a specific tenant.
+pub async fn list_ext_conns(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<ExtConn>, AppError> {
+    info!(
+        "Service: Listing external connections for tenant ID: {}",
+        tenant_id
+    );
+
+    let conns = query_as!(
+        ExtConn,
+        r#"
+        SELECT
+            id, tenant_id, user_id, provider_id, provider_access_token, provider_item_id,
+            status, last_sync_at, metadata, created_at, created_by, updated_at, updated_by
+        FROM ext_conns
+        WHERE tenant_id = $1 AND status <> 'DISCONNECTED'
+        ORDER BY created_at
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(conns)
+}
+
+/// Retrieves a single external connection by ID for a specific tenant.
+pub async fn get_ext_conn_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    ext_conn_id: Uuid,
+) -> Result<ExtConn, AppError> {
+    info!(
+        "Service: Getting external connection with ID: {} for tenant ID: {}",
+        ext_conn_id, tenant_id
+    );
+
+    let conn = query_as!(
+        ExtConn,
+        r#"
+        SELECT
+            id, tenant_id, user_id, provider_id, provider_access_token, provider_item_id,
+            status, last_sync_at, metadata, created_at, created_by, updated_at, updated_by
+        FROM ext_conns
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        ext_conn_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "External connection with ID {} not found for tenant {}",
+            ext_conn_id, tenant_id
+        ))
+    })?;
+
+    Ok(conn)
+}
+
+/// Creates a new external connection by exchanging the provider link token.
+/// The resulting access token is encrypted before it is stored.
+pub async fn create_ext_conn(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateExtConnDto,
+) -> Result<ExtConn, AppError> {
+    info!(
+        "Service: Creating external connection for tenant ID {} with provider {}",
+        tenant_id, dto.provider_id
+    );
+
+    dto.validate()?;
+    check_client_metadata(dto.metadata.as_ref())?;
+
+    let provider = ext_provider::get_ext_provider_by_id(pool, dto.provider_id).await?;
+    let connector = connector_for(&provider)?;
+    let (access_token, item_id) = connector.exchange_public_token(&dto.public_token).await?;
+    let encrypted_token = encrypt_secret(&access_token)?;
+
+    let new_conn = query_as!(
+        ExtConn,
+        r#"
+        INSERT INTO ext_conns (
+            tenant_id, user_id, provider_id, provider_access_token, provider_item_id,
+            status, metadata, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $2, $2)
+        RETURNING
+            id, tenant_id, user_id, provider_id, provider_access_token, provider_item_id,
+            status, last_sync_at, metadata, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        created_by_user_id,
+        dto.provider_id,
+        encrypted_token,
+        item_id,
+        String::from(ExtConnStatus::Connected),
+        dto.metadata
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(new_conn)
+}
+
+/// Checks metadata sent by a client: an object, merged into the stored one, that leaves the
+/// server's keys alone.
+fn check_client_metadata(metadata: Option<&JsonValue>) -> Result<(), AppError> {
+    let Some(metadata) = metadata else {
+        return Ok(());
+    };
+    let Some(object) = metadata.as_object() else {
+        return Err(AppError::Validation(
+            "metadata must be a JSON object".to_string(),
+        ));
+    };
+    match SERVER_METADATA_KEYS
+        .iter()
+        .find(|key| object.contains_key(**key))
+    {
+        Some(key) => Err(AppError::Validation(format!(
+            "metadata.{} is maintained by the server and cannot be set",
+            key
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Updates an existing external connection, e.g. after the user re-authenticates.
+pub async fn update_ext_conn(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    ext_conn_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateExtConnDto,
+) -> Result<ExtConn, AppError> {
+    info!(
+        "Service: Updating external connection with ID: {} for tenant ID: {}",
+        ext_conn_id, tenant_id
+    );
+
+    dto.validate()?;
+    check_client_metadata(dto.metadata.as_ref())?;
+
+    let current = get_ext_conn_by_id(pool, tenant_id, ext_conn_id).await?;
+
+    let (encrypted_token, item_id, status) = match dto.public_token {
+        Some(public_token) => {
+            let provider = ext_provider::get_ext_provider_by_id(pool, current.provider_id).await?;
+            let connector = connector_for(&provider)?;
+            let (access_token, item_id) = connector.exchange_public_token(&public_token).await?;
+            (
+                Some(encrypt_secret(&access_token)?),
+                item_id,
+                Some(String::from(ExtConnStatus::Connected)),
+            )
+        }
+        None => (None, None, None),
+    };
+
+    let updated_conn = query_as!(
+        ExtConn,
+        r#"
+        UPDATE ext_conns
+        SET
+            provider_access_token = COALESCE($3, provider_access_token),
+            provider_item_id = COALESCE($4, provider_item_id),
+            status = COALESCE($5, status),
+            metadata = CASE WHEN $6::jsonb IS NULL THEN metadata
+                            ELSE COALESCE(metadata, '{}'::jsonb) || $6::jsonb END,
+            updated_at = NOW(),
+            updated_by = $7
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING
+            id, tenant_id, user_id, provider_id, provider_access_token, provider_item_id,
+            status, last_sync_at, metadata, created_at, created_by, updated_at, updated_by
+        "#,
+        ext_conn_id,
+        tenant_id,
+        encrypted_token,
+        item_id,
+        status,
+        dto.metadata,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "External connection with ID {} not found or not owned by tenant {}",
+            ext_conn_id, tenant_id
+        ))
+    })?;
+
+    Ok(updated_conn)
+}
+
+/// Disconnects an external connection (soft delete). Staged rows are kept for audit.
+pub async fn disconnect_ext_conn(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    ext_conn_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Disconnecting external connection with ID: {} for tenant ID: {}",
+        ext_conn_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE ext_conns
+        SET
+            status = 'DISCONNECTED',
+            updated_at = NOW(),
+            updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND status <> 'DISCONNECTED'
+        "#,
+        ext_conn_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "External connection with ID {} not found or already disconnected for tenant {}",
+            ext_conn_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Retrieves the external accounts discovered under a connection.
+pub async fn list_external_accounts(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    ext_conn_id: Uuid,
+) -> Result<Vec<ExternalAccount>, AppError> {
+    info!(
+        "Service: Listing external accounts for connection ID: {}",
+        ext_conn_id
+    );
+
+    // Verify the connection belongs to the tenant
+    get_ext_conn_by_id(pool, tenant_id, ext_conn_id).await?;
+
+    let accounts = query_as!(
+        ExternalAccount,
+        r#"
+        SELECT
+            id, ext_conn_id, account_id, provider_account_id, name, mask, type as "r#type",
+            subtype, currency_code, current_balance, available_balance, last_sync_at,
+            is_active, created_at, created_by, updated_at, updated_by
+        FROM external_accounts
+        WHERE ext_conn_id = $1
+        ORDER BY name
+        "#,
+        ext_conn_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(accounts)
+}
+
+/// Links an external account of the connection to a ledger account, or toggles whether it
+/// is synced.
+pub async fn update_external_account(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    ext_conn_id: Uuid,
+    external_account_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateExternalAccountDto,
+) -> Result<ExternalAccount, AppError> {
+    info!(
+        "Service: Updating external account with ID: {}",
+        external_account_id
+    );
+
+    if let Some(account_id) = dto.account_id {
+        let account_exists = sqlx::query!(
+            "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE AND archived_at IS NULL)",
+            account_id, tenant_id
+        )
+        .fetch_one(pool)
+        .await?
+        .exists
+        .unwrap_or(false);
+
+        if !account_exists {
+            return Err(AppError::Validation(format!(
+                "Account ID {} is invalid, inactive or archived for tenant {}",
+                account_id, tenant_id
+            )));
+        }
+    }
+
+    let updated_account = query_as!(
+        ExternalAccount,
+        r#"
+        UPDATE external_accounts ea
+        SET
+            account_id = COALESCE($4, ea.account_id),
+            is_active = COALESCE($5, ea.is_active),
+            updated_at = NOW(),
+            updated_by = $6
+        FROM ext_conns c
+        WHERE ea.id = $1 AND ea.ext_conn_id = $3 AND ea.ext_conn_id = c.id AND c.tenant_id = $2
+        RETURNING
+            ea.id, ea.ext_conn_id, ea.account_id, ea.provider_account_id, ea.name, ea.mask,
+            ea.type as "r#type", ea.subtype, ea.currency_code, ea.current_balance,
+            ea.available_balance, ea.last_sync_at, ea.is_active, ea.created_at, ea.created_by,
+            ea.updated_at, ea.updated_by
+        "#,
+        external_account_id,
+        tenant_id,
+        ext_conn_id,
+        dto.account_id,
+        dto.is_active,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "External account with ID {} not found on bank connection {}",
+            external_account_id, ext_conn_id
+        ))
+    })?;
+
+    Ok(updated_account)
+}
+
+/// Retrieves staged external transactions for a connection, optionally filtered by status.
+pub async fn list_staged_transactions(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    ext_conn_id: Uuid,
+    status: Option<StagingStatus>,
+) -> Result<Vec<ExternalTransactionsStaging>, AppError> {
+    info!(
+        "Service: Listing staged transactions for connection ID: {}",
+        ext_conn_id
+    );
+
+    get_ext_conn_by_id(pool, tenant_id, ext_conn_id).await?;
+
+    let rows = query_as!(
+        ExternalTransactionsStaging,
+        r#"
+        SELECT
+            s.id, s.external_account_id, s.provider_transaction_id, s.description, s.amount,
+            s.transaction_date, s.posted_date, s.status, s.tx_id, s.raw_data,
+            s.merchant_name, s.merchant_category_hint, s.merchant_category_id,
+            s.created_at, s.created_by, s.updated_at, s.updated_by
+        FROM external_transactions_staging s
+        JOIN external_accounts ea ON s.external_account_id = ea.id
+        WHERE ea.ext_conn_id = $1 AND ($2::text IS NULL OR s.status = $2)
+        ORDER BY s.transaction_date DESC, s.created_at DESC
+        "#,
+        ext_conn_id,
+        status.map(String::from)
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Syncs a single connection on demand (e.g., from a "sync now" button).
+pub async fn sync_ext_conn(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    ext_conn_id: Uuid,
+) -> Result<ExtConnSyncSummary, AppError> {
+    let conn = get_ext_conn_by_id(pool, tenant_id, ext_conn_id).await?;
+    run_sync(pool, conn).await
+}
+
+/// Syncs every connected connection across all tenants, then proposes ledger matches for
+/// the newly staged rows. Used by the scheduler.
+/// Failures are recorded on the connection and logged; they do not stop the run.
+pub async fn sync_all_ext_conns(pool: &PgPool) -> Result<usize, AppError> {
+    info!("Service: Syncing all connected external connections.");
+
+    let conns = query_as!(
+        ExtConn,
+        r#"
+        SELECT
+            id, tenant_id, user_id, provider_id, provider_access_token, provider_item_id,
+            status, last_sync_at, metadata, created_at, created_by, updated_at, updated_by
+        FROM ext_conns
+        WHERE status = 'CONNECTED'
+        ORDER BY last_sync_at NULLS FIRST
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut synced = 0;
+    for conn in conns {
+        let (conn_id, tenant_id, user_id) = (conn.id, conn.tenant_id, conn.user_id);
+        match run_sync(pool, conn).await {
+            Ok(summary) => {
+                synced += 1;
+                info!(
+                    "Synced connection {}: {} accounts, {} transactions staged",
+                    conn_id, summary.accounts_synced, summary.transactions_staged
+                );
+            }
+            Err(e) => {
+                warn!("Failed to sync external connection {}: {}", conn_id, e);
+                continue;
+            }
+        }
+
+        if let Err(e) =
+            transaction_matching::run_matching_for_connection(pool, tenant_id, conn_id, user_id)
+                .await
+        {
+            warn!(
+                "Failed to match transactions for connection {}: {}",
+                conn_id, e
+            );
+        }
+    }
+
+    Ok(synced)
+}
+
+/// Pulls accounts and transaction changes from the provider and persists them.
+async fn run_sync(pool: &PgPool, conn: ExtConn) -> Result<ExtConnSyncSummary, AppError> {
+    let status: ExtConnStatus = conn.status.parse().map_err(AppError::InternalServerError)?;
+    if matches!(
+        status,
+        ExtConnStatus::Disconnected | ExtConnStatus::Disabled
+    ) {
+        return Err(AppError::Validation(format!(
+            "External connection {} is {} and cannot be synced",
+            conn.id, conn.status
+        )));
+    }
+
+    let provider = ext_provider::get_ext_provider_by_id(pool, conn.provider_id).await?;
+    let connector = connector_for(&provider)?;
+    let access_token = decrypt_secret(&conn.provider_access_token)?;
+    let cursor = conn
+        .metadata
+        .as_ref()
+        .and_then(|m| m[SYNC_CURSOR_KEY].as_str())
+        .map(str::to_string);
+
+    match fetch_changes(connector.as_ref(), &access_token, cursor).await {
+        Ok(changes) => with_retry(pool, |pool| persist_changes(pool, &conn, &changes)).await,
+        Err(e) => {
+            let new_status = match e {
+                ConnectorError::ReauthRequired(_) => ExtConnStatus::PendingReauth,
+                ConnectorError::InvalidCredentials(_) | ConnectorError::Provider(_) => {
+                    ExtConnStatus::Error
+                }
+            };
+            sqlx::query!(
+                "UPDATE ext_conns SET status = $2, updated_at = NOW() WHERE id = $1",
+                conn.id,
+                String::from(new_status)
+            )
+            .execute(pool)
+            .await?;
+            Err(e.into())
+        }
+    }
+}
+
+/// Everything fetched from the provider in one sync run.
+struct ProviderChanges {
+    accounts: Vec<ProviderAccount>,
+    upserts: Vec<ProviderTransaction>,
+    removed: Vec<String>,
+    cursor: Option<String>,
+}
+
+/// Fetches accounts and all pending transaction pages before anything is written,
+/// so a provider failure mid-way never leaves a half-applied cursor.
+async fn fetch_changes(
+    connector: &dyn BankConnector,
+    access_token: &str,
+    mut cursor: Option<String>,
+) -> Result<ProviderChanges, ConnectorError> {
+    let accounts = connector.fetch_accounts(access_token).await?;
+
+    let mut upserts = Vec::new();
+    let mut removed = Vec::new();
+    loop {
+        let page = connector
+            .sync_transactions(access_token, cursor.as_deref())
+            .await?;
+        upserts.extend(page.added);
+        upserts.extend(page.modified);
+        removed.extend(page.removed);
+        if page.next_cursor.is_some() {
+            cursor = page.next_cursor;
+        }
+        if !page.has_more {
+            break;
+        }
+    }
+
+    Ok(ProviderChanges {
+        accounts,
+        upserts,
+        removed,
+        cursor,
+    })
+}
+
+/// Writes fetched accounts (including their balances) and staged transactions in one
+/// database transaction.
+async fn persist_changes(
+    pool: &PgPool,
+    conn: &ExtConn,
+    changes: &ProviderChanges,
+) -> Result<ExtConnSyncSummary, AppError> {
+    let normalizer = MerchantNormalizer::for_tenant(pool, conn.tenant_id).await?;
+    let mut db_tx = pool.begin().await?;
+    let mut summary = ExtConnSyncSummary {
+        ext_conn_id: conn.id,
+        ..Default::default()
+    };
+
+    // provider_account_id -> (external_account_id, is_active)
+    let mut account_ids: HashMap<String, (Uuid, bool)> = HashMap::new();
+    for account in &changes.accounts {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO external_accounts (
+                ext_conn_id, provider_account_id, name, mask, type, subtype, currency_code,
+                current_balance, available_balance, last_sync_at, created_by, updated_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), $10, $10)
+            ON CONFLICT (ext_conn_id, provider_account_id) DO UPDATE SET
+                name = EXCLUDED.name,
+                mask = EXCLUDED.mask,
+                type = EXCLUDED.type,
+                subtype = EXCLUDED.subtype,
+                current_balance = EXCLUDED.current_balance,
+                available_balance = EXCLUDED.available_balance,
+                last_sync_at = NOW(),
+                updated_at = NOW()
+            RETURNING id, is_active
+            "#,
+            conn.id,
+            account.provider_account_id,
+            account.name,
+            account.mask,
+            account.r#type,
+            account.subtype,
+            account.currency_code,
+            account.current_balance,
+            account.available_balance,
+            conn.user_id
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+
+        account_ids.insert(account.provider_account_id.clone(), (row.id, row.is_active));
+        summary.accounts_synced += 1;
+    }
+
+    for tx in &changes.upserts {
+        let Some(&(external_account_id, is_active)) = account_ids.get(&tx.provider_account_id)
+        else {
+            warn!(
+                "Skipping transaction {} for unknown provider account {}",
+                tx.provider_transaction_id, tx.provider_account_id
+            );
+            continue;
+        };
+        if !is_active {
+            continue;
+        }
+
+        // Only rows still awaiting review are refreshed; converted/matched rows are final.
+        let merchant = normalizer.normalize(&tx.description);
+        sqlx::query!(
+            r#"
+            INSERT INTO external_transactions_staging (
+                external_account_id, provider_transaction_id, description, amount,
+                transaction_date, posted_date, status, raw_data,
+                merchant_name, merchant_category_hint, merchant_category_id, created_by, updated_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, 'PENDING_REVIEW', $7, $8, $9, $10, $11, $11)
+            ON CONFLICT (external_account_id, provider_transaction_id) DO UPDATE SET
+                description = EXCLUDED.description,
+                amount = EXCLUDED.amount,
+                transaction_date = EXCLUDED.transaction_date,
+                posted_date = EXCLUDED.posted_date,
+                raw_data = EXCLUDED.raw_data,
+                merchant_name = EXCLUDED.merchant_name,
+                merchant_category_hint = EXCLUDED.merchant_category_hint,
+                merchant_category_id = EXCLUDED.merchant_category_id,
+                updated_at = NOW()
+            WHERE external_transactions_staging.status = 'PENDING_REVIEW'
+            "#,
+            external_account_id,
+            tx.provider_transaction_id,
+            tx.description,
+            tx.amount,
+            tx.transaction_date,
+            tx.posted_date,
+            tx.raw_data,
+            merchant.merchant_name,
+            merchant.category_hint,
+            merchant.category_id,
+            conn.user_id
+        )
+        .execute(&mut *db_tx)
+        .await?;
+        summary.transactions_staged += 1;
+    }
+
+    if !changes.removed.is_empty() {
+        summary.transactions_removed = sqlx::query!(
+            r#"
+            UPDATE external_transactions_staging s
+            SET status = 'IGNORED', updated_at = NOW()
+            FROM external_accounts ea
+            WHERE s.external_account_id = ea.id
+              AND ea.ext_conn_id = $1
+              AND s.provider_transaction_id = ANY($2)
+              AND s.status = 'PENDING_REVIEW'
+            "#,
+            conn.id,
+            &changes.removed
+        )
+        .execute(&mut *db_tx)
+        .await?
+        .rows_affected() as usize;
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE ext_conns
+        SET
+            status = 'CONNECTED',
+            last_sync_at = NOW(),
+            metadata = CASE WHEN $2::text IS NULL THEN metadata
+                            ELSE COALESCE(metadata, '{}'::jsonb) || jsonb_build_object($3::text, $2::text) END,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        conn.id,
+        changes.cursor.as_deref(),
+        SYNC_CURSOR_KEY
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn client_metadata_cannot_set_the_sync_cursor() {
+        assert!(check_client_metadata(None).is_ok());
+        assert!(check_client_metadata(Some(&json!({ "institution": "First Bank" }))).is_ok());
+        assert!(matches!(
+            check_client_metadata(Some(&json!({ "sync_cursor": "forged" }))),
+            Err(AppError::Validation(msg)) if msg.contains("metadata.sync_cursor")
+        ));
+        assert!(matches!(
+            check_client_metadata(Some(&json!(["not", "an", "object"]))),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::ext_provider_dto::{CreateExtProviderDto, UpdateExtProviderDto},
+        ext_provider::ExtProvider,
+    },
+    services::permission,
+};
+
+/// Retrieves a list of all active external providers.
+pub async fn list_ext_providers(pool: &PgPool) -> Result<Vec<ExtProvider>, AppError> {
+    info!("Service: Listing all active external providers.");
+
+    let providers = query_as!(
+        ExtProvider,
+        r#"
+        SELECT
+            id, name, code, type as "r#type", description, logo_url, api_base_url,
+            is_active, created_at, created_by, updated_at, updated_by
+        FROM ext_providers
+        WHERE is_active = TRUE
+        ORDER BY name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(providers)
+}
+
+/// Retrieves a single external provider by ID.
+pub async fn get_ext_provider_by_id(
+    pool: &PgPool,
+    provider_id: Uuid,
+) -> Result<ExtProvider, AppError> {
+    info!(
+        "Service: Getting external provider with ID: {}",
+        provider_id
+    );
+
+    let provider = query_as!(
+        ExtProvider,
+        r#"
+        SELECT
+            id, name, code, type as "r#type", description, logo_url, api_base_url,
+            is_active, created_at, created_by, updated_at, updated_by
+        FROM ext_providers
+        WHERE id = $1 AND is_active = TRUE
+        "#,
+        provider_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "External provider with ID {} not found",
+            provider_id
+        ))
+    })?;
+
+    Ok(provider)
+}
+
+/// Creates a new external provider. `created_by_user_id` must be a system administrator.
+pub async fn create_ext_provider(
+    pool: &PgPool,
+    created_by_user_id: Uuid,
+    dto: CreateExtProviderDto,
+) -> Result<ExtProvider, AppError> {
+    info!(
+        "Service: Creating new external provider with code: {}",
+        dto.code
+    );
+
+    permission::require_system_admin(pool, created_by_user_id).await?;
+    dto.validate()?;
+
+    let new_provider = query_as!(
+        ExtProvider,
+        r#"
+        INSERT INTO ext_providers (
+            name, code, type, description, logo_url, api_base_url,
+            is_active, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, TRUE, $7, $7)
+        RETURNING
+            id, name, code, type as "r#type", description, logo_url, api_base_url,
+            is_active, created_at, created_by, updated_at, updated_by
+        "#,
+        dto.name,
+        dto.code.to_uppercase(),
+        String::from(dto.r#type),
+        dto.description,
+        dto.logo_url,
+        dto.api_base_url,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(new_provider)
+}
+
+/// Updates an existing external provider. `updated_by_user_id` must be a system
+/// administrator. The API base URL is fixed at registration, since connectors send the
+/// provider's credentials and tenants' access tokens to it.
+pub async fn update_ext_provider(
+    pool: &PgPool,
+    provider_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateExtProviderDto,
+) -> Result<ExtProvider, AppError> {
+    info!(
+        "Service: Updating external provider with ID: {}",
+        provider_id
+    );
+
+    permission::require_system_admin(pool, updated_by_user_id).await?;
+    dto.validate()?;
+
+    let updated_provider = query_as!(
+        ExtProvider,
+        r#"
+        UPDATE ext_providers
+        SET
+            name = COALESCE($2, name),
+            description = COALESCE($3, description),
+            logo_url = COALESCE($4, logo_url),
+            is_active = COALESCE($5, is_active),
+            updated_at = NOW(),
+            updated_by = $6
+        WHERE id = $1
+        RETURNING
+            id, name, code, type as "r#type", description, logo_url, api_base_url,
+            is_active, created_at, created_by, updated_at, updated_by
+        "#,
+        provider_id,
+        dto.name,
+        dto.description,
+        dto.logo_url,
+        dto.is_active,
+        updated_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "External provider with ID {} not found",
+            provider_id
+        ))
+    })?;
+
+    Ok(updated_provider)
+}
+
+#[cfg(test)]
+mod system_admin {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::update_ext_provider;
+    use crate::{error::AppError, models::dto::ext_provider_dto::UpdateExtProviderDto};
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn only_system_admins_update_providers() {
+        let pool = connect().await;
+        let user_id = seed_user(&pool, false).await;
+        let admin_id = seed_user(&pool, true).await;
+        let provider_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO ext_providers (name, code, type, created_by, updated_by)
+             VALUES ($1, $1, 'BANKING_AGGREGATOR', $2, $2) RETURNING id",
+        )
+        .bind(format!("TEST-{}", Uuid::new_v4()))
+        .bind(admin_id)
+        .fetch_one(&pool)
+        .await
+        .expect("insert provider");
+
+        let result = update_ext_provider(&pool, provider_id, user_id, rename("Mine")).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+        let name = format!("Renamed {}", provider_id);
+        let provider = update_ext_provider(&pool, provider_id, admin_id, rename(&name))
+            .await
+            .expect("admin update");
+        assert_eq!(provider.name, name);
+    }
+
+    fn rename(name: &str) -> UpdateExtProviderDto {
+        UpdateExtProviderDto {
+            name: Some(name.to_string()),
+            description: None,
+            logo_url: None,
+            is_active: None,
+        }
+    }
+
+    async fn connect() -> PgPool {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPool::connect(&url)
+            .await
+            .expect("connect to DATABASE_URL")
+    }
+
+    async fn seed_user(pool: &PgPool, is_system_admin: bool) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name, is_system_admin)
+             VALUES ($1, 'EMAIL_PASSWORD', $1, 'Provider', 'Admin', $2) RETURNING id",
+        )
+        .bind(format!("provider-{}@example.com", Uuid::new_v4()))
+        .bind(is_system_admin)
+        .fetch_one(pool)
+        .await
+        .expect("insert user")
+    }
+}
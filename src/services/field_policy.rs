@@ -0,0 +1,162 @@
+//! Field-level access policy for API responses.
+//!
+//! Sensitive fields are hidden or masked centrally, on the serialized response, so the
+//! rules hold for every endpoint that returns the field instead of being re-implemented
+//! per handler. Handlers opt in by returning `Redacted<T>` (see `middleware::field_policy`).
+//!
+//! | Field(s)                                  | Applies to                            | Without permission            |
+//! |-------------------------------------------|---------------------------------------|-------------------------------|
+//! | `source_document_url`                     | any object                            | removed (`data.view_attachments`) |
+//! | `amount`, `converted_amount`, `balance`,  | objects whose `account_id` is a       | replaced with `"***"`         |
+//! | `previous_balance`, `movement`, `debits`, | sensitive account                     | (`data.view_sensitive_accounts`) |
+//! | `credits`, `memo`                         |                                       |                               |
+//! | `description`, `memo` sealed by privacy   | any object                            | replaced with `"***"`, opened |
+//! | mode                                      |                                       | with it (`transactions.read_sensitive`) |
+//!
+//! Totals and subtotals spanning several accounts are not masked.
+
+use std::collections::HashSet;
+
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    services::{
+        permission::{TX_READ_SENSITIVE, VIEW_ATTACHMENTS, VIEW_SENSITIVE_ACCOUNTS},
+        privacy::{self, TextKey},
+    },
+};
+
+/// Fields removed from responses unless the user holds the paired permission.
+const HIDDEN_FIELDS: &[(&str, &str)] = &[("source_document_url", VIEW_ATTACHMENTS)];
+
+/// Fields masked on objects that belong to a sensitive account.
+const SENSITIVE_ACCOUNT_FIELDS: &[&str] = &[
+    "amount",
+    "converted_amount",
+    "balance",
+    "previous_balance",
+    "movement",
+    "debits",
+    "credits",
+    "memo",
+];
+
+/// Fields that privacy mode may seal.
+const SEALABLE_FIELDS: &[&str] = &["description", "memo"];
+
+const MASK: &str = "***";
+
+/// What the current user may see, resolved once per request.
+#[derive(Debug, Clone, Default)]
+pub struct FieldAccess {
+    granted: HashSet<String>,
+    /// Sensitive accounts of the tenant; empty when the user may see them.
+    sensitive_account_ids: HashSet<Uuid>,
+    /// The tenant's data key, when the user may read sealed text.
+    text_key: Option<TextKey>,
+}
+
+/// Loads the user's field permissions and, if needed, the tenant's sensitive accounts.
+pub async fn load_field_access(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<FieldAccess, AppError> {
+    let field_permissions: Vec<String> = HIDDEN_FIELDS
+        .iter()
+        .map(|(_, permission)| permission.to_string())
+        .chain([
+            VIEW_SENSITIVE_ACCOUNTS.to_string(),
+            TX_READ_SENSITIVE.to_string(),
+        ])
+        .collect();
+
+    let granted: HashSet<String> = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT p.name
+        FROM user_tenant_roles utr
+        JOIN role_permissions rp ON rp.role_id = utr.role_id
+        JOIN permissions p ON p.id = rp.permission_id
+        WHERE utr.tenant_id = $1 AND utr.user_id = $2 AND p.name = ANY($3)
+        "#,
+        tenant_id,
+        user_id,
+        &field_permissions
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let sensitive_account_ids = if granted.contains(VIEW_SENSITIVE_ACCOUNTS) {
+        HashSet::new()
+    } else {
+        sqlx::query_scalar!(
+            "SELECT id FROM accounts WHERE tenant_id = $1 AND is_sensitive = TRUE",
+            tenant_id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect()
+    };
+
+    let text_key = if granted.contains(TX_READ_SENSITIVE) {
+        privacy::opening_key(pool, tenant_id).await?
+    } else {
+        None
+    };
+
+    Ok(FieldAccess {
+        granted,
+        sensitive_account_ids,
+        text_key,
+    })
+}
+
+impl FieldAccess {
+    /// Hides and masks the fields this user may not see, anywhere in `value`.
+    pub fn apply(&self, value: &mut JsonValue) {
+        match value {
+            JsonValue::Array(items) => items.iter_mut().for_each(|item| self.apply(item)),
+            JsonValue::Object(fields) => {
+                for (field, permission) in HIDDEN_FIELDS {
+                    if !self.granted.contains(*permission) {
+                        fields.remove(*field);
+                    }
+                }
+
+                let sensitive = fields
+                    .get("account_id")
+                    .and_then(JsonValue::as_str)
+                    .and_then(|id| id.parse::<Uuid>().ok())
+                    .is_some_and(|id| self.sensitive_account_ids.contains(&id));
+                if sensitive {
+                    for field in SENSITIVE_ACCOUNT_FIELDS {
+                        if let Some(masked) = fields.get_mut(*field) {
+                            *masked = JsonValue::String(MASK.to_string());
+                        }
+                    }
+                }
+
+                for field in SEALABLE_FIELDS {
+                    if let Some(JsonValue::String(text)) = fields.get_mut(*field) {
+                        if privacy::is_sealed(text) {
+                            *text = self
+                                .text_key
+                                .as_ref()
+                                .and_then(|key| privacy::unseal(key, text).ok())
+                                .unwrap_or_else(|| MASK.to_string());
+                        }
+                    }
+                }
+
+                fields.values_mut().for_each(|child| self.apply(child));
+            }
+            _ => {}
+        }
+    }
+}
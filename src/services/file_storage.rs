@@ -0,0 +1,344 @@
+//! Files kept by the app (currently export files), on the configured storage backend.
+//!
+//! The local backend writes under `STORAGE_LOCAL_PATH`; files are served by the app. The S3
+//! backend talks to the bucket with AWS Signature Version 4 and hands out presigned GET
+//! URLs, so large downloads (and their `Range` requests) go to S3 directly. With a custom
+//! `STORAGE_S3_ENDPOINT` (MinIO and other S3-compatible services) path-style URLs are used.
+
+use std::{
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use axum::body::Body;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method};
+use sha2::Sha256;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    config::{self, StorageBackend, StorageConfig},
+    error::AppError,
+    utils::crypto,
+};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Stores `body` under `key`, replacing any earlier file.
+pub async fn put_object(key: &str, body: Vec<u8>, content_type: &str) -> Result<(), AppError> {
+    let storage = &config::get().storage;
+    match storage.backend {
+        StorageBackend::Local => {
+            let path = local_path(storage, key)?;
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+            }
+            tokio::fs::write(&path, body).await.map_err(io_error)
+        }
+        StorageBackend::S3 => {
+            let payload_hash = crypto::sha256_hex(&body);
+            s3_request(
+                storage,
+                Method::PUT,
+                key,
+                Some(content_type),
+                &payload_hash,
+                body,
+            )
+            .await
+        }
+    }
+}
+
+/// Deletes the file under `key`; a file that is already gone is not an error.
+pub async fn delete_object(key: &str) -> Result<(), AppError> {
+    let storage = &config::get().storage;
+    match storage.backend {
+        StorageBackend::Local => match tokio::fs::remove_file(local_path(storage, key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(io_error(e)),
+        },
+        StorageBackend::S3 => {
+            let payload_hash = crypto::sha256_hex(b"");
+            s3_request(
+                storage,
+                Method::DELETE,
+                key,
+                None,
+                &payload_hash,
+                Vec::new(),
+            )
+            .await
+        }
+    }
+}
+
+/// A presigned URL downloading `key` as `file_name`, or `None` on the local backend, where
+/// the app serves files itself.
+pub fn presigned_download_url(key: &str, file_name: &str) -> Result<Option<String>, AppError> {
+    let storage = &config::get().storage;
+    if storage.backend != StorageBackend::S3 {
+        return Ok(None);
+    }
+    let target = S3Target::new(storage, key)?;
+    let now = Utc::now();
+    let (date, timestamp) = amz_dates(now);
+    let credentials = S3Credentials::new(storage)?;
+    let scope = format!("{}/{}/s3/aws4_request", date, credentials.region);
+
+    let mut query = vec![
+        (
+            "X-Amz-Algorithm".to_string(),
+            "AWS4-HMAC-SHA256".to_string(),
+        ),
+        (
+            "X-Amz-Credential".to_string(),
+            format!("{}/{}", credentials.access_key_id, scope),
+        ),
+        ("X-Amz-Date".to_string(), timestamp.clone()),
+        (
+            "X-Amz-Expires".to_string(),
+            storage.signed_url_ttl_secs.to_string(),
+        ),
+        ("X-Amz-SignedHeaders".to_string(), "host".to_string()),
+        (
+            "response-content-disposition".to_string(),
+            format!("attachment; filename=\"{}\"", file_name),
+        ),
+    ];
+    query.sort();
+    let canonical_query = canonical_query(&query);
+    let canonical_request = format!(
+        "GET\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+        target.path, canonical_query, target.host
+    );
+    let signature = credentials.sign(&date, &timestamp, &scope, &canonical_request);
+
+    Ok(Some(format!(
+        "{}{}?{}&X-Amz-Signature={}",
+        target.origin, target.path, canonical_query, signature
+    )))
+}
+
+/// Streams `len` bytes of a local file from `start`.
+pub async fn local_range_body(key: &str, start: u64, len: u64) -> Result<Body, AppError> {
+    let storage = &config::get().storage;
+    let mut file = tokio::fs::File::open(local_path(storage, key)?)
+        .await
+        .map_err(io_error)?;
+    file.seek(SeekFrom::Start(start)).await.map_err(io_error)?;
+    Ok(Body::from_stream(ReaderStream::new(file.take(len))))
+}
+
+/// Where a key lives on the local backend. Keys are generated by the app, but are still
+/// refused if they could leave the storage directory.
+fn local_path(storage: &StorageConfig, key: &str) -> Result<PathBuf, AppError> {
+    if key.is_empty() || key.split('/').any(|part| part.is_empty() || part == "..") {
+        return Err(AppError::InternalServerError(format!(
+            "Invalid storage key '{}'",
+            key
+        )));
+    }
+    Ok(Path::new(&storage.local_path).join(key))
+}
+
+fn io_error(e: std::io::Error) -> AppError {
+    AppError::InternalServerError(format!("Storage I/O failed: {}", e))
+}
+
+/// Sends one header-signed request to the bucket.
+async fn s3_request(
+    storage: &StorageConfig,
+    method: Method,
+    key: &str,
+    content_type: Option<&str>,
+    payload_hash: &str,
+    body: Vec<u8>,
+) -> Result<(), AppError> {
+    let target = S3Target::new(storage, key)?;
+    let credentials = S3Credentials::new(storage)?;
+    let (date, timestamp) = amz_dates(Utc::now());
+    let scope = format!("{}/{}/s3/aws4_request", date, credentials.region);
+
+    // Signed headers, sorted by name
+    let mut headers = vec![];
+    if let Some(content_type) = content_type {
+        headers.push(("content-type", content_type.to_string()));
+    }
+    headers.push(("host", target.host.clone()));
+    headers.push(("x-amz-content-sha256", payload_hash.to_string()));
+    headers.push(("x-amz-date", timestamp.clone()));
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method, target.path, canonical_headers, signed_headers, payload_hash
+    );
+    let signature = credentials.sign(&date, &timestamp, &scope, &canonical_request);
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    );
+
+    let mut request = Client::new()
+        .request(method.clone(), format!("{}{}", target.origin, target.path))
+        .timeout(REQUEST_TIMEOUT)
+        .header("x-amz-content-sha256", payload_hash)
+        .header("x-amz-date", &timestamp)
+        .header(reqwest::header::AUTHORIZATION, authorization);
+    if let Some(content_type) = content_type {
+        request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+    }
+    let response = request.body(body).send().await.map_err(|e| {
+        AppError::InternalServerError(format!("S3 {} {} failed: {}", method, key, e))
+    })?;
+
+    let status = response.status();
+    if status.is_success() || (method == Method::DELETE && status.as_u16() == 404) {
+        Ok(())
+    } else {
+        let text = response.text().await.unwrap_or_default();
+        Err(AppError::InternalServerError(format!(
+            "S3 {} {} returned {}: {}",
+            method, key, status, text
+        )))
+    }
+}
+
+/// Where an object is addressed.
+struct S3Target {
+    origin: String, // Scheme and host
+    host: String,
+    path: String, // URI-encoded, starting with `/`
+}
+
+impl S3Target {
+    fn new(storage: &StorageConfig, key: &str) -> Result<Self, AppError> {
+        let (bucket, region) = match (&storage.s3_bucket, &storage.s3_region) {
+            (Some(bucket), Some(region)) => (bucket, region),
+            _ => {
+                return Err(AppError::InternalServerError(
+                    "S3 bucket and region are not configured".to_string(),
+                ))
+            }
+        };
+        let key_path = uri_encode(key, false);
+        Ok(match &storage.s3_endpoint {
+            // Path-style for custom endpoints
+            Some(endpoint) => {
+                let origin = endpoint.trim_end_matches('/').to_string();
+                let host = origin
+                    .split_once("://")
+                    .map_or(origin.as_str(), |(_, host)| host)
+                    .to_string();
+                S3Target {
+                    path: format!("/{}/{}", uri_encode(bucket, true), key_path),
+                    origin,
+                    host,
+                }
+            }
+            None => {
+                let host = format!("{}.s3.{}.amazonaws.com", bucket, region);
+                S3Target {
+                    origin: format!("https://{}", host),
+                    host,
+                    path: format!("/{}", key_path),
+                }
+            }
+        })
+    }
+}
+
+struct S3Credentials<'a> {
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+    region: &'a str,
+}
+
+impl<'a> S3Credentials<'a> {
+    fn new(storage: &'a StorageConfig) -> Result<Self, AppError> {
+        match (
+            &storage.s3_access_key_id,
+            &storage.s3_secret_access_key,
+            &storage.s3_region,
+        ) {
+            (Some(access_key_id), Some(secret_access_key), Some(region)) => Ok(S3Credentials {
+                access_key_id,
+                secret_access_key,
+                region,
+            }),
+            _ => Err(AppError::InternalServerError(
+                "S3 credentials are not configured".to_string(),
+            )),
+        }
+    }
+
+    /// Signature V4 of a canonical request.
+    fn sign(&self, date: &str, timestamp: &str, scope: &str, canonical_request: &str) -> String {
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            crypto::sha256_hex(canonical_request.as_bytes())
+        );
+        let key = [date, self.region, "s3", "aws4_request"].iter().fold(
+            format!("AWS4{}", self.secret_access_key).into_bytes(),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        hex(&hmac_sha256(&key, string_to_sign.as_bytes()))
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length, so this cannot fail
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The `YYYYMMDD` date and `YYYYMMDDTHHMMSSZ` timestamp of a signature.
+fn amz_dates(now: DateTime<Utc>) -> (String, String) {
+    (
+        now.format("%Y%m%d").to_string(),
+        now.format("%Y%m%dT%H%M%SZ").to_string(),
+    )
+}
+
+/// Query string with names and values URI-encoded, in the given (sorted) order.
+fn canonical_query(params: &[(String, String)]) -> String {
+    params
+        .iter()
+        .map(|(name, value)| format!("{}={}", uri_encode(name, true), uri_encode(value, true)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Percent-encodes everything but unreserved characters (and `/` unless `encode_slash`).
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,302 @@
+use chrono::NaiveDate;
+use sqlx::{query_as, PgExecutor, PgPool};
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{dto::fiscal_period_dto::CreateFiscalPeriodDto, fiscal_period::FiscalPeriod},
+    services::{
+        domain_event::{self, DomainEvent},
+        permission::{self, PERIOD_REOPEN},
+    },
+};
+
+/// Retrieves the fiscal periods of a specific tenant, oldest first.
+pub async fn list_fiscal_periods(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Vec<FiscalPeriod>, AppError> {
+    info!(
+        "Service: Listing fiscal periods for tenant ID: {}",
+        tenant_id
+    );
+
+    let periods = query_as!(
+        FiscalPeriod,
+        r#"
+        SELECT
+            id, tenant_id, name, start_date, end_date, status, closed_at, closed_by,
+            created_at, created_by, updated_at, updated_by
+        FROM fiscal_periods
+        WHERE tenant_id = $1
+        ORDER BY start_date
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(periods)
+}
+
+/// Retrieves a single fiscal period by ID for a specific tenant.
+pub async fn get_fiscal_period_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    period_id: Uuid,
+) -> Result<FiscalPeriod, AppError> {
+    info!(
+        "Service: Getting fiscal period with ID: {} for tenant ID: {}",
+        period_id, tenant_id
+    );
+
+    let period = query_as!(
+        FiscalPeriod,
+        r#"
+        SELECT
+            id, tenant_id, name, start_date, end_date, status, closed_at, closed_by,
+            created_at, created_by, updated_at, updated_by
+        FROM fiscal_periods
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        period_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Fiscal period with ID {} not found for tenant {}",
+            period_id, tenant_id
+        ))
+    })?;
+
+    Ok(period)
+}
+
+/// Creates a new, open fiscal period. Periods of a tenant may not overlap.
+pub async fn create_fiscal_period(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateFiscalPeriodDto,
+) -> Result<FiscalPeriod, AppError> {
+    info!(
+        "Service: Creating fiscal period '{}' for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    dto.validate()?;
+    if dto.end_date < dto.start_date {
+        return Err(AppError::Validation(
+            "End date cannot be before start date".to_string(),
+        ));
+    }
+
+    let overlapping = sqlx::query_scalar!(
+        r#"
+        SELECT name
+        FROM fiscal_periods
+        WHERE tenant_id = $1 AND start_date <= $3 AND end_date >= $2
+        LIMIT 1
+        "#,
+        tenant_id,
+        dto.start_date,
+        dto.end_date
+    )
+    .fetch_optional(pool)
+    .await?;
+    if let Some(name) = overlapping {
+        return Err(AppError::Validation(format!(
+            "Period overlaps existing fiscal period '{}'",
+            name
+        )));
+    }
+
+    let new_period = query_as!(
+        FiscalPeriod,
+        r#"
+        INSERT INTO fiscal_periods (
+            tenant_id, name, start_date, end_date, status, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, 'OPEN', $5, $5)
+        RETURNING
+            id, tenant_id, name, start_date, end_date, status, closed_at, closed_by,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.name,
+        dto.start_date,
+        dto.end_date,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(new_period)
+}
+
+/// Closes a fiscal period, locking transactions dated inside it.
+pub async fn close_fiscal_period(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    period_id: Uuid,
+    closed_by_user_id: Uuid,
+) -> Result<FiscalPeriod, AppError> {
+    info!(
+        "Service: Closing fiscal period with ID: {} for tenant ID: {}",
+        period_id, tenant_id
+    );
+
+    let period = get_fiscal_period_by_id(pool, tenant_id, period_id).await?;
+    if period.status == "CLOSED" {
+        return Err(AppError::Validation(format!(
+            "Fiscal period '{}' is already closed",
+            period.name
+        )));
+    }
+
+    let closed = query_as!(
+        FiscalPeriod,
+        r#"
+        UPDATE fiscal_periods
+        SET status = 'CLOSED', closed_at = NOW(), closed_by = $3, updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND status = 'OPEN'
+        RETURNING
+            id, tenant_id, name, start_date, end_date, status, closed_at, closed_by,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        period_id,
+        tenant_id,
+        closed_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::Validation(format!("Fiscal period '{}' is already closed", period.name)))?;
+
+    domain_event::publish(DomainEvent::FiscalPeriodClosed {
+        tenant_id,
+        user_id: closed_by_user_id,
+        period_id,
+        name: closed.name.clone(),
+        start_date: closed.start_date,
+        end_date: closed.end_date,
+    });
+    Ok(closed)
+}
+
+/// Reopens a closed fiscal period. Requires the `period.reopen` permission.
+pub async fn reopen_fiscal_period(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    period_id: Uuid,
+    reopened_by_user_id: Uuid,
+) -> Result<FiscalPeriod, AppError> {
+    info!(
+        "Service: Reopening fiscal period with ID: {} for tenant ID: {}",
+        period_id, tenant_id
+    );
+
+    permission::require_permission(pool, tenant_id, reopened_by_user_id, PERIOD_REOPEN).await?;
+
+    let reopened = query_as!(
+        FiscalPeriod,
+        r#"
+        UPDATE fiscal_periods
+        SET status = 'OPEN', closed_at = NULL, closed_by = NULL, updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND status = 'CLOSED'
+        RETURNING
+            id, tenant_id, name, start_date, end_date, status, closed_at, closed_by,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        period_id,
+        tenant_id,
+        reopened_by_user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Closed fiscal period with ID {} not found for tenant {}",
+            period_id, tenant_id
+        ))
+    })?;
+
+    domain_event::publish(DomainEvent::FiscalPeriodReopened {
+        tenant_id,
+        user_id: reopened_by_user_id,
+        period_id,
+        name: reopened.name.clone(),
+        start_date: reopened.start_date,
+        end_date: reopened.end_date,
+    });
+    Ok(reopened)
+}
+
+/// Rejects a change to a transaction dated `date` when that date falls in a closed period,
+/// unless the user holds the `period.reopen` permission.
+///
+/// Called by every service path that creates, edits or deletes transactions.
+pub async fn ensure_date_open<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<(), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    match locked_period(executor, tenant_id, user_id, date).await? {
+        Some(period_name) => Err(closed_period_error(&period_name, date)),
+        None => Ok(()),
+    }
+}
+
+/// Returns the name of the closed period covering `date`, unless the user holds the
+/// `period.reopen` permission.
+pub async fn locked_period<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<Option<String>, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let closed = sqlx::query!(
+        r#"
+        SELECT
+            fp.name,
+            EXISTS(
+                SELECT 1
+                FROM user_tenant_roles utr
+                JOIN role_permissions rp ON rp.role_id = utr.role_id
+                JOIN permissions p ON p.id = rp.permission_id
+                WHERE utr.tenant_id = $1 AND utr.user_id = $2 AND p.name = $4
+            ) as "can_override!"
+        FROM fiscal_periods fp
+        WHERE fp.tenant_id = $1 AND fp.status = 'CLOSED' AND $3 BETWEEN fp.start_date AND fp.end_date
+        LIMIT 1
+        "#,
+        tenant_id,
+        user_id,
+        date,
+        PERIOD_REOPEN
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(closed
+        .filter(|period| !period.can_override)
+        .map(|period| period.name))
+}
+
+/// The error for a change to a transaction dated `date` inside the closed period.
+pub fn closed_period_error(period_name: &str, date: NaiveDate) -> AppError {
+    AppError::Forbidden(format!(
+        "Fiscal period '{}' is closed; transactions dated {} cannot be changed",
+        period_name, date
+    ))
+}
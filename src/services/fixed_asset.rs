@@ -0,0 +1,941 @@
+//! Fixed assets and their monthly depreciation.
+//!
+//! An asset is depreciated from the month it is placed in service, a full month's worth for
+//! that first month, over `useful_life_months` down to its salvage value. Straight-line
+//! spreads the depreciable amount evenly, rounding so the months add up exactly. Declining
+//! balance takes `declining_factor / useful_life_months` of the remaining book value each
+//! month, switching to straight-line over the remaining months once that gives more, so the
+//! asset still reaches its salvage value on time.
+//!
+//! The schedule is computed from the asset itself; the depreciation scheduler posts each
+//! month once it has ended (debit depreciation expense, credit accumulated depreciation)
+//! and records the transaction against the month. Once a month is posted, what drives the
+//! schedule can no longer change.
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Months, NaiveDate};
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgExecutor, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    db::{begin_financial, with_retry},
+    error::AppError,
+    models::{
+        dto::fixed_asset_dto::{
+            CreateFixedAssetDto, DepreciationReportQuery, ListFixedAssetsQuery, UpdateFixedAssetDto,
+        },
+        dto::journal_entry_dto::CreateJournalEntryDto,
+        dto::transaction_dto::CreateTransactionDto,
+        fixed_asset::{
+            DepreciationMethod, DepreciationReport, DepreciationReportAsset,
+            DepreciationReportTotal, DepreciationSchedule, DepreciationScheduleLine, FixedAsset,
+            FixedAssetStatus,
+        },
+        journal_entry::JournalEntryType,
+        transaction::{Transaction, TransactionStatus, TransactionType},
+    },
+    services::{
+        domain_event::{self, DomainEvent},
+        invoice::check_accounts,
+        transaction,
+    },
+};
+
+/// Decimal places of asset amounts.
+const AMOUNT_SCALE: u32 = 2;
+
+/// Declining balance factor used when none is given: double declining balance.
+const DEFAULT_DECLINING_FACTOR: Decimal = Decimal::TWO;
+
+/// Retrieves the tenant's fixed assets, by name.
+pub async fn list_fixed_assets(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: ListFixedAssetsQuery,
+) -> Result<Vec<FixedAsset>, AppError> {
+    info!("Service: Listing fixed assets for tenant ID: {}", tenant_id);
+
+    let assets = query_as!(
+        FixedAsset,
+        r#"
+        SELECT id, tenant_id, name, asset_tag, description, acquisition_date, in_service_date,
+               cost, salvage_value, useful_life_months, method, declining_factor, currency_code,
+               asset_account_id, accumulated_depreciation_account_id,
+               depreciation_expense_account_id, status, accumulated_depreciation,
+               depreciated_through, notes, created_at, created_by, updated_at, updated_by
+        FROM fixed_assets
+        WHERE tenant_id = $1 AND ($2::text IS NULL OR status = $2)
+        ORDER BY name, acquisition_date
+        "#,
+        tenant_id,
+        query.status.map(String::from)
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(assets)
+}
+
+/// Retrieves a fixed asset by ID.
+pub async fn get_fixed_asset(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    asset_id: Uuid,
+) -> Result<FixedAsset, AppError> {
+    info!(
+        "Service: Getting fixed asset with ID: {} for tenant ID: {}",
+        asset_id, tenant_id
+    );
+
+    query_as!(
+        FixedAsset,
+        r#"
+        SELECT id, tenant_id, name, asset_tag, description, acquisition_date, in_service_date,
+               cost, salvage_value, useful_life_months, method, declining_factor, currency_code,
+               asset_account_id, accumulated_depreciation_account_id,
+               depreciation_expense_account_id, status, accumulated_depreciation,
+               depreciated_through, notes, created_at, created_by, updated_at, updated_by
+        FROM fixed_assets
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        asset_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| not_found(asset_id, tenant_id))
+}
+
+/// Registers a fixed asset. Depreciation starts with its in-service month.
+pub async fn create_fixed_asset(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateFixedAssetDto,
+) -> Result<FixedAsset, AppError> {
+    info!(
+        "Service: Registering fixed asset '{}' for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    let in_service_date = dto.in_service_date.unwrap_or(dto.acquisition_date);
+    let salvage_value = dto.salvage_value.unwrap_or(Decimal::ZERO);
+    let declining_factor = dto.declining_factor.unwrap_or(DEFAULT_DECLINING_FACTOR);
+    check_terms(
+        dto.acquisition_date,
+        in_service_date,
+        dto.cost,
+        salvage_value,
+        declining_factor,
+    )?;
+    check_asset_accounts(
+        pool,
+        tenant_id,
+        dto.asset_account_id,
+        dto.accumulated_depreciation_account_id,
+        dto.depreciation_expense_account_id,
+    )
+    .await?;
+
+    query_as!(
+        FixedAsset,
+        r#"
+        INSERT INTO fixed_assets (
+            tenant_id, name, asset_tag, description, acquisition_date, in_service_date, cost,
+            salvage_value, useful_life_months, method, declining_factor, currency_code,
+            asset_account_id, accumulated_depreciation_account_id,
+            depreciation_expense_account_id, notes, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $17)
+        RETURNING id, tenant_id, name, asset_tag, description, acquisition_date, in_service_date,
+                  cost, salvage_value, useful_life_months, method, declining_factor, currency_code,
+                  asset_account_id, accumulated_depreciation_account_id,
+                  depreciation_expense_account_id, status, accumulated_depreciation,
+                  depreciated_through, notes, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.name,
+        dto.asset_tag,
+        dto.description,
+        dto.acquisition_date,
+        in_service_date,
+        dto.cost,
+        salvage_value,
+        dto.useful_life_months,
+        String::from(dto.method),
+        declining_factor,
+        dto.currency_code.to_uppercase(),
+        dto.asset_account_id,
+        dto.accumulated_depreciation_account_id,
+        dto.depreciation_expense_account_id,
+        dto.notes,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| tag_conflict(e, dto.asset_tag.as_deref()))
+}
+
+/// Updates a fixed asset. Cost, dates, life, method and accounts can only change until its
+/// first month of depreciation is posted.
+pub async fn update_fixed_asset(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    asset_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateFixedAssetDto,
+) -> Result<FixedAsset, AppError> {
+    info!(
+        "Service: Updating fixed asset with ID: {} for tenant ID: {}",
+        asset_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    let current = lock_asset(&mut db_tx, tenant_id, asset_id).await?;
+
+    let changes_schedule = dto.acquisition_date.is_some()
+        || dto.in_service_date.is_some()
+        || dto.cost.is_some()
+        || dto.salvage_value.is_some()
+        || dto.useful_life_months.is_some()
+        || dto.method.is_some()
+        || dto.declining_factor.is_some()
+        || dto.currency_code.is_some()
+        || dto.asset_account_id.is_some()
+        || dto.accumulated_depreciation_account_id.is_some()
+        || dto.depreciation_expense_account_id.is_some();
+    if changes_schedule && current.depreciated_through.is_some() {
+        return Err(AppError::Validation(format!(
+            "Fixed asset {} has posted depreciation; only its name, tag, description and notes can change",
+            asset_id
+        )));
+    }
+
+    let acquisition_date = dto.acquisition_date.unwrap_or(current.acquisition_date);
+    let in_service_date = dto.in_service_date.unwrap_or(current.in_service_date);
+    let cost = dto.cost.unwrap_or(current.cost);
+    let salvage_value = dto.salvage_value.unwrap_or(current.salvage_value);
+    let declining_factor = dto.declining_factor.unwrap_or(current.declining_factor);
+    check_terms(
+        acquisition_date,
+        in_service_date,
+        cost,
+        salvage_value,
+        declining_factor,
+    )?;
+    check_asset_accounts(
+        &mut *db_tx,
+        tenant_id,
+        dto.asset_account_id.unwrap_or(current.asset_account_id),
+        dto.accumulated_depreciation_account_id
+            .unwrap_or(current.accumulated_depreciation_account_id),
+        dto.depreciation_expense_account_id
+            .unwrap_or(current.depreciation_expense_account_id),
+    )
+    .await?;
+
+    let asset = query_as!(
+        FixedAsset,
+        r#"
+        UPDATE fixed_assets
+        SET name = COALESCE($3, name),
+            asset_tag = COALESCE($4, asset_tag),
+            description = COALESCE($5, description),
+            acquisition_date = $6,
+            in_service_date = $7,
+            cost = $8,
+            salvage_value = $9,
+            useful_life_months = COALESCE($10, useful_life_months),
+            method = COALESCE($11, method),
+            declining_factor = $12,
+            currency_code = COALESCE($13, currency_code),
+            asset_account_id = COALESCE($14, asset_account_id),
+            accumulated_depreciation_account_id = COALESCE($15, accumulated_depreciation_account_id),
+            depreciation_expense_account_id = COALESCE($16, depreciation_expense_account_id),
+            notes = COALESCE($17, notes),
+            updated_at = NOW(),
+            updated_by = $18
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING id, tenant_id, name, asset_tag, description, acquisition_date, in_service_date,
+                  cost, salvage_value, useful_life_months, method, declining_factor, currency_code,
+                  asset_account_id, accumulated_depreciation_account_id,
+                  depreciation_expense_account_id, status, accumulated_depreciation,
+                  depreciated_through, notes, created_at, created_by, updated_at, updated_by
+        "#,
+        asset_id,
+        tenant_id,
+        dto.name,
+        dto.asset_tag,
+        dto.description,
+        acquisition_date,
+        in_service_date,
+        cost,
+        salvage_value,
+        dto.useful_life_months,
+        dto.method.map(String::from),
+        declining_factor,
+        dto.currency_code.map(|code| code.to_uppercase()),
+        dto.asset_account_id,
+        dto.accumulated_depreciation_account_id,
+        dto.depreciation_expense_account_id,
+        dto.notes,
+        updated_by_user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await
+    .map_err(|e| tag_conflict(e, dto.asset_tag.as_deref()))?;
+
+    db_tx.commit().await?;
+    Ok(asset)
+}
+
+/// Deletes a fixed asset that has no posted depreciation.
+pub async fn delete_fixed_asset(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    asset_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting fixed asset with ID: {} for tenant ID: {}",
+        asset_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    let asset = lock_asset(&mut db_tx, tenant_id, asset_id).await?;
+    if asset.depreciated_through.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Fixed asset {} has posted depreciation and cannot be deleted",
+            asset_id
+        )));
+    }
+    sqlx::query!("DELETE FROM fixed_assets WHERE id = $1", asset_id)
+        .execute(&mut *db_tx)
+        .await?;
+    db_tx.commit().await?;
+    Ok(())
+}
+
+/// An asset's depreciation over its useful life, with the transactions of the months
+/// already posted.
+pub async fn get_schedule(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    asset_id: Uuid,
+) -> Result<DepreciationSchedule, AppError> {
+    info!(
+        "Service: Depreciation schedule of fixed asset ID: {} for tenant ID: {}",
+        asset_id, tenant_id
+    );
+
+    let asset = get_fixed_asset(pool, tenant_id, asset_id).await?;
+    let posted: HashMap<NaiveDate, Uuid> = sqlx::query!(
+        r#"
+        SELECT period_end, transaction_id
+        FROM fixed_asset_depreciations
+        WHERE asset_id = $1
+        "#,
+        asset_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.period_end, row.transaction_id))
+    .collect();
+
+    let lines = build_schedule(&asset)?
+        .into_iter()
+        .map(|mut line| {
+            line.transaction_id = posted.get(&line.period_end).copied();
+            line
+        })
+        .collect();
+
+    Ok(DepreciationSchedule {
+        asset_id: asset.id,
+        method: asset.method,
+        cost: asset.cost,
+        salvage_value: asset.salvage_value,
+        currency_code: asset.currency_code,
+        lines,
+    })
+}
+
+/// Depreciation of each of the tenant's assets over the months ending between `from` and
+/// `to`, with book values before and after, and totals by currency.
+pub async fn depreciation_report(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: DepreciationReportQuery,
+) -> Result<DepreciationReport, AppError> {
+    info!(
+        "Service: Depreciation report from {} to {} for tenant ID: {}",
+        query.from, query.to, tenant_id
+    );
+
+    if query.to < query.from {
+        return Err(AppError::Validation(
+            "'to' cannot be before 'from'".to_string(),
+        ));
+    }
+
+    let assets = query_as!(
+        FixedAsset,
+        r#"
+        SELECT id, tenant_id, name, asset_tag, description, acquisition_date, in_service_date,
+               cost, salvage_value, useful_life_months, method, declining_factor, currency_code,
+               asset_account_id, accumulated_depreciation_account_id,
+               depreciation_expense_account_id, status, accumulated_depreciation,
+               depreciated_through, notes, created_at, created_by, updated_at, updated_by
+        FROM fixed_assets
+        WHERE tenant_id = $1 AND in_service_date <= $2
+        ORDER BY currency_code, name, acquisition_date
+        "#,
+        tenant_id,
+        query.to
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut rows = Vec::with_capacity(assets.len());
+    let mut totals: Vec<DepreciationReportTotal> = Vec::new();
+    for asset in &assets {
+        let schedule = build_schedule(asset)?;
+        let before: Decimal = schedule
+            .iter()
+            .filter(|line| line.period_end < query.from)
+            .map(|line| line.depreciation)
+            .sum();
+        let in_period: Vec<&DepreciationScheduleLine> = schedule
+            .iter()
+            .filter(|line| line.period_end >= query.from && line.period_end <= query.to)
+            .collect();
+        let depreciation: Decimal = in_period.iter().map(|line| line.depreciation).sum();
+        let posted: Decimal = in_period
+            .iter()
+            .filter(|line| Some(line.period_end) <= asset.depreciated_through)
+            .map(|line| line.depreciation)
+            .sum();
+        let opening_book_value = asset.cost - before;
+        let closing_book_value = opening_book_value - depreciation;
+
+        match totals
+            .iter_mut()
+            .find(|total| total.currency_code == asset.currency_code)
+        {
+            Some(total) => {
+                total.cost += asset.cost;
+                total.opening_book_value += opening_book_value;
+                total.depreciation += depreciation;
+                total.closing_book_value += closing_book_value;
+            }
+            None => totals.push(DepreciationReportTotal {
+                currency_code: asset.currency_code.clone(),
+                cost: asset.cost,
+                opening_book_value,
+                depreciation,
+                closing_book_value,
+            }),
+        }
+        rows.push(DepreciationReportAsset {
+            asset_id: asset.id,
+            name: asset.name.clone(),
+            asset_tag: asset.asset_tag.clone(),
+            method: asset.method.clone(),
+            cost: asset.cost,
+            opening_book_value,
+            depreciation,
+            closing_book_value,
+            posted,
+        });
+    }
+
+    Ok(DepreciationReport {
+        from: query.from,
+        to: query.to,
+        assets: rows,
+        totals,
+    })
+}
+
+/// Posts every month of depreciation that has ended by `today` and is not posted yet, for
+/// all tenants. Called by the depreciation scheduler; an asset that fails (e.g. its month
+/// falls in a locked period) is retried on the next run.
+pub async fn post_due_depreciation(pool: &PgPool, today: NaiveDate) -> Result<usize, AppError> {
+    info!(
+        "Service: Posting depreciation for months ended by {}",
+        today
+    );
+
+    // The next month to post ends on or before today
+    let due_ids: Vec<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT id
+        FROM fixed_assets
+        WHERE status = 'ACTIVE'
+          AND (date_trunc('month', COALESCE(depreciated_through + 1, in_service_date))
+               + INTERVAL '1 month' - INTERVAL '1 day')::date <= $1
+        ORDER BY id
+        "#,
+        today
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut posted = 0;
+    for asset_id in due_ids {
+        match with_retry(pool, |pool| depreciate_asset(pool, asset_id, today)).await {
+            Ok(transactions) => {
+                posted += transactions.len();
+                for (tenant_id, user_id, transaction) in transactions {
+                    domain_event::publish(DomainEvent::TransactionPosted {
+                        tenant_id,
+                        user_id,
+                        transaction_id: transaction.id,
+                        amount: transaction.amount,
+                        transaction_date: transaction.transaction_date,
+                    });
+                }
+            }
+            Err(e) => warn!(
+                "Failed to post depreciation of fixed asset {}: {}",
+                asset_id, e
+            ),
+        }
+    }
+
+    if posted > 0 {
+        info!("Posted {} month(s) of depreciation", posted);
+    }
+    Ok(posted)
+}
+
+/// Posts an asset's due months in one database transaction. Returns what was posted, with
+/// the tenant and the user it was posted as (the asset's creator).
+async fn depreciate_asset(
+    pool: &PgPool,
+    asset_id: Uuid,
+    today: NaiveDate,
+) -> Result<Vec<(Uuid, Uuid, Transaction)>, AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let asset = query_as!(
+        FixedAsset,
+        r#"
+        SELECT id, tenant_id, name, asset_tag, description, acquisition_date, in_service_date,
+               cost, salvage_value, useful_life_months, method, declining_factor, currency_code,
+               asset_account_id, accumulated_depreciation_account_id,
+               depreciation_expense_account_id, status, accumulated_depreciation,
+               depreciated_through, notes, created_at, created_by, updated_at, updated_by
+        FROM fixed_assets
+        WHERE id = $1 AND status = 'ACTIVE'
+        FOR UPDATE SKIP LOCKED
+        "#,
+        asset_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?;
+    // Another scheduler run picked it up first, or it is fully depreciated.
+    let Some(asset) = asset else {
+        db_tx.rollback().await?;
+        return Ok(Vec::new());
+    };
+
+    let schedule = build_schedule(&asset)?;
+    let last_period_end = schedule.last().map(|line| line.period_end);
+    let due: Vec<&DepreciationScheduleLine> = schedule
+        .iter()
+        .filter(|line| Some(line.period_end) > asset.depreciated_through)
+        .filter(|line| line.period_end <= today)
+        .collect();
+
+    let mut posted = Vec::with_capacity(due.len());
+    for line in due {
+        // A month can round to nothing on a nearly depreciated asset
+        if !line.depreciation.is_zero() {
+            let transaction = post_month(&mut db_tx, &asset, line).await?;
+            sqlx::query!(
+                r#"
+                INSERT INTO fixed_asset_depreciations (tenant_id, asset_id, period_end, amount, transaction_id)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+                asset.tenant_id,
+                asset.id,
+                line.period_end,
+                line.depreciation,
+                transaction.id
+            )
+            .execute(&mut *db_tx)
+            .await?;
+            posted.push((asset.tenant_id, asset.created_by, transaction));
+        }
+
+        let status = if Some(line.period_end) == last_period_end {
+            FixedAssetStatus::FullyDepreciated
+        } else {
+            FixedAssetStatus::Active
+        };
+        sqlx::query!(
+            r#"
+            UPDATE fixed_assets
+            SET accumulated_depreciation = $2, depreciated_through = $3, status = $4, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            asset.id,
+            line.accumulated_depreciation,
+            line.period_end,
+            String::from(status)
+        )
+        .execute(&mut *db_tx)
+        .await?;
+    }
+
+    db_tx.commit().await?;
+    Ok(posted)
+}
+
+/// Posts one month of an asset's depreciation, dated the last day of the month.
+async fn post_month(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    asset: &FixedAsset,
+    line: &DepreciationScheduleLine,
+) -> Result<Transaction, AppError> {
+    let description = format!(
+        "Depreciation of {} for {}",
+        asset.name,
+        line.period_end.format("%Y-%m")
+    );
+    let journal_entries = [
+        (
+            asset.depreciation_expense_account_id,
+            JournalEntryType::Debit,
+        ),
+        (
+            asset.accumulated_depreciation_account_id,
+            JournalEntryType::Credit,
+        ),
+    ]
+    .into_iter()
+    .map(|(account_id, entry_type)| CreateJournalEntryDto {
+        account_id,
+        entry_type,
+        amount: line.depreciation,
+        currency_code: asset.currency_code.clone(),
+        exchange_rate: None,
+        converted_amount: None,
+        memo: Some(description.clone()),
+        project_id: None,
+        class_id: None,
+        location_id: None,
+    })
+    .collect();
+    let create = CreateTransactionDto {
+        transaction_date: line.period_end,
+        description,
+        r#type: TransactionType::Expense,
+        category_id: None,
+        payee_id: None,
+        tags: None,
+        amount: line.depreciation,
+        currency_code: asset.currency_code.clone(),
+        is_reconciled: None,
+        reconciliation_date: None,
+        notes: None,
+        source_document_url: None,
+        status: Some(TransactionStatus::Posted),
+        journal_entries,
+        payment_account_id: None,
+        splits: Vec::new(),
+        metadata: None,
+        attribution: None,
+    };
+    let (posted, _) =
+        transaction::insert_transaction(db_tx, asset.tenant_id, asset.created_by, create).await?;
+    Ok(posted)
+}
+
+/// Computes an asset's depreciation for every month of its useful life.
+fn build_schedule(asset: &FixedAsset) -> Result<Vec<DepreciationScheduleLine>, AppError> {
+    let method: DepreciationMethod = asset
+        .method
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    let months = asset.useful_life_months.max(1) as u32;
+    let depreciable = asset.cost - asset.salvage_value;
+    let monthly_rate = asset.declining_factor / Decimal::from(months);
+
+    let mut lines = Vec::with_capacity(months as usize);
+    let mut accumulated = Decimal::ZERO;
+    for month in 1..=months {
+        let depreciation = match method {
+            DepreciationMethod::StraightLine => {
+                // Round the running total, not each month, so rounding never piles up
+                let target = (depreciable * Decimal::from(month) / Decimal::from(months))
+                    .round_dp(AMOUNT_SCALE);
+                target - accumulated
+            }
+            DepreciationMethod::DecliningBalance => {
+                let remaining = depreciable - accumulated;
+                if month == months {
+                    remaining
+                } else {
+                    let book_value = asset.cost - accumulated;
+                    let declining = (book_value * monthly_rate).round_dp(AMOUNT_SCALE);
+                    let straight =
+                        (remaining / Decimal::from(months - month + 1)).round_dp(AMOUNT_SCALE);
+                    declining.max(straight).min(remaining)
+                }
+            }
+        };
+        accumulated += depreciation;
+        lines.push(DepreciationScheduleLine {
+            period_end: period_end(asset.in_service_date, month - 1)?,
+            depreciation,
+            accumulated_depreciation: accumulated,
+            book_value: asset.cost - accumulated,
+            transaction_id: None,
+        });
+    }
+
+    Ok(lines)
+}
+
+/// Last day of the month `offset` months after `start`'s.
+fn period_end(start: NaiveDate, offset: u32) -> Result<NaiveDate, AppError> {
+    start
+        .with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(offset + 1)))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(|| {
+            AppError::Validation("The useful life runs past the supported dates".to_string())
+        })
+}
+
+fn check_terms(
+    acquisition_date: NaiveDate,
+    in_service_date: NaiveDate,
+    cost: Decimal,
+    salvage_value: Decimal,
+    declining_factor: Decimal,
+) -> Result<(), AppError> {
+    if in_service_date < acquisition_date {
+        return Err(AppError::Validation(
+            "in_service_date cannot be before acquisition_date".to_string(),
+        ));
+    }
+    if cost <= Decimal::ZERO || cost.round_dp(AMOUNT_SCALE) != cost {
+        return Err(AppError::Validation(
+            "cost must be greater than zero with at most two decimals".to_string(),
+        ));
+    }
+    if salvage_value < Decimal::ZERO
+        || salvage_value >= cost
+        || salvage_value.round_dp(AMOUNT_SCALE) != salvage_value
+    {
+        return Err(AppError::Validation(
+            "salvage_value must be at least zero and below cost, with at most two decimals"
+                .to_string(),
+        ));
+    }
+    if declining_factor <= Decimal::ZERO || declining_factor > Decimal::from(4) {
+        return Err(AppError::Validation(
+            "declining_factor must be above 0 and at most 4".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn check_asset_accounts<'e, E: PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    asset_account_id: Uuid,
+    accumulated_depreciation_account_id: Uuid,
+    depreciation_expense_account_id: Uuid,
+) -> Result<(), AppError> {
+    if accumulated_depreciation_account_id == depreciation_expense_account_id {
+        return Err(AppError::Validation(
+            "The accumulated depreciation and depreciation expense accounts must differ"
+                .to_string(),
+        ));
+    }
+    check_accounts(
+        executor,
+        tenant_id,
+        [
+            asset_account_id,
+            accumulated_depreciation_account_id,
+            depreciation_expense_account_id,
+        ]
+        .into_iter(),
+    )
+    .await
+}
+
+async fn lock_asset(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    asset_id: Uuid,
+) -> Result<FixedAsset, AppError> {
+    query_as!(
+        FixedAsset,
+        r#"
+        SELECT id, tenant_id, name, asset_tag, description, acquisition_date, in_service_date,
+               cost, salvage_value, useful_life_months, method, declining_factor, currency_code,
+               asset_account_id, accumulated_depreciation_account_id,
+               depreciation_expense_account_id, status, accumulated_depreciation,
+               depreciated_through, notes, created_at, created_by, updated_at, updated_by
+        FROM fixed_assets
+        WHERE id = $1 AND tenant_id = $2
+        FOR UPDATE
+        "#,
+        asset_id,
+        tenant_id
+    )
+    .fetch_optional(&mut **db_tx)
+    .await?
+    .ok_or_else(|| not_found(asset_id, tenant_id))
+}
+
+fn tag_conflict(e: sqlx::Error, asset_tag: Option<&str>) -> AppError {
+    match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => AppError::Conflict(format!(
+            "Another fixed asset is tagged '{}'",
+            asset_tag.unwrap_or_default()
+        )),
+        e => e.into(),
+    }
+}
+
+fn not_found(asset_id: Uuid, tenant_id: Uuid) -> AppError {
+    AppError::NotFound(format!(
+        "Fixed asset with ID {} not found for tenant {}",
+        asset_id, tenant_id
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn money(amount: &str) -> Decimal {
+        amount.parse().unwrap()
+    }
+
+    fn asset(method: DepreciationMethod, cost: &str, salvage: &str, months: i32) -> FixedAsset {
+        let in_service_date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        FixedAsset {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Delivery van".to_string(),
+            asset_tag: None,
+            description: None,
+            acquisition_date: in_service_date,
+            in_service_date,
+            cost: money(cost),
+            salvage_value: money(salvage),
+            useful_life_months: months,
+            method: String::from(method),
+            declining_factor: Decimal::TWO,
+            currency_code: "USD".to_string(),
+            asset_account_id: Uuid::new_v4(),
+            accumulated_depreciation_account_id: Uuid::new_v4(),
+            depreciation_expense_account_id: Uuid::new_v4(),
+            status: String::from(FixedAssetStatus::Active),
+            accumulated_depreciation: Decimal::ZERO,
+            depreciated_through: None,
+            notes: None,
+            created_at: Utc::now(),
+            created_by: Uuid::new_v4(),
+            updated_at: Utc::now(),
+            updated_by: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn schedules_depreciate_exactly_cost_less_salvage() {
+        for method in [
+            DepreciationMethod::StraightLine,
+            DepreciationMethod::DecliningBalance,
+        ] {
+            for (cost, salvage, months) in [
+                ("1000.00", "100.00", 7),
+                ("10000.00", "1000.00", 12),
+                ("2499.99", "0.00", 36),
+                ("50000.00", "5000.00", 60),
+            ] {
+                let asset = asset(method, cost, salvage, months);
+                let schedule = build_schedule(&asset).unwrap();
+                assert_eq!(schedule.len(), months as usize);
+                let total: Decimal = schedule.iter().map(|line| line.depreciation).sum();
+                assert_eq!(
+                    total,
+                    asset.cost - asset.salvage_value,
+                    "{:?} {}",
+                    method,
+                    cost
+                );
+                let last = schedule.last().unwrap();
+                assert_eq!(last.book_value, asset.salvage_value);
+                assert_eq!(
+                    last.period_end,
+                    period_end(asset.in_service_date, months as u32 - 1).unwrap()
+                );
+                assert!(schedule
+                    .iter()
+                    .all(|line| line.depreciation >= Decimal::ZERO
+                        && line.depreciation.round_dp(AMOUNT_SCALE) == line.depreciation));
+            }
+        }
+    }
+
+    #[test]
+    fn straight_line_spreads_rounding_over_the_life() {
+        let schedule = build_schedule(&asset(
+            DepreciationMethod::StraightLine,
+            "1000.00",
+            "0.00",
+            3,
+        ))
+        .unwrap();
+        let amounts: Vec<Decimal> = schedule.iter().map(|line| line.depreciation).collect();
+        assert_eq!(
+            amounts,
+            vec![money("333.33"), money("333.34"), money("333.33")]
+        );
+    }
+
+    #[test]
+    fn declining_balance_switches_to_straight_line_when_that_is_larger() {
+        // (cost, salvage, months, factor, first straight-line month)
+        for (cost, salvage, months, factor, switch_month) in [
+            ("1000.00", "0.00", 7, "1.5", 4),
+            ("10000.00", "1000.00", 12, "2", 11),
+        ] {
+            let mut asset = asset(DepreciationMethod::DecliningBalance, cost, salvage, months);
+            asset.declining_factor = money(factor);
+            let rate = asset.declining_factor / Decimal::from(months);
+            let schedule = build_schedule(&asset).unwrap();
+
+            let mut accumulated = Decimal::ZERO;
+            for (index, line) in schedule.iter().enumerate() {
+                let month = index as i32 + 1;
+                let declining = ((asset.cost - accumulated) * rate).round_dp(AMOUNT_SCALE);
+                let remaining = asset.cost - asset.salvage_value - accumulated;
+                let straight =
+                    (remaining / Decimal::from(months - month + 1)).round_dp(AMOUNT_SCALE);
+                if month < switch_month {
+                    assert!(declining > straight, "month {} of {}", month, cost);
+                    assert_eq!(line.depreciation, declining, "month {} of {}", month, cost);
+                } else if month < months {
+                    assert!(straight > declining, "month {} of {}", month, cost);
+                    assert_eq!(line.depreciation, straight, "month {} of {}", month, cost);
+                }
+                accumulated = line.accumulated_depreciation;
+            }
+        }
+    }
+}
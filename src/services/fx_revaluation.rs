@@ -0,0 +1,486 @@
+//! Period-end revaluation of foreign-currency accounts.
+//!
+//! For every active account whose currency differs from the tenant's base currency, the
+//! balance in the account's currency is revalued at the rate on the revaluation date
+//! (see `currency_conversion`) and compared with its base-currency value as booked. The
+//! difference is posted as one `ADJUSTMENT` transaction: each account gets a zero-amount
+//! leg carrying the difference as `converted_amount`, balanced against the configured
+//! unrealized gain or loss account.
+//!
+//! Earlier revaluations are part of the booked value, so running again only posts the
+//! movement since the last run, and running twice for the same date posts nothing.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::fx_revaluation_dto::{RunFxRevaluationDto, UpsertFxRevaluationSettingsDto},
+        fx_revaluation::{
+            FxRevaluation, FxRevaluationLine, FxRevaluationResult, FxRevaluationSettings,
+        },
+        journal_entry::JournalEntryType,
+    },
+    services::{
+        balance_snapshot::{self, SnapshotChange},
+        currency_conversion,
+        domain_event::{self, DomainEvent},
+        fiscal_period,
+        permission::{self, FX_REVALUE},
+        privacy,
+    },
+};
+
+/// Retrieves the tenant's gain and loss accounts.
+pub async fn get_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<FxRevaluationSettings, AppError> {
+    info!(
+        "Service: Getting FX revaluation settings for tenant ID: {}",
+        tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, FX_REVALUE).await?;
+
+    find_settings(pool, tenant_id).await?.ok_or_else(|| {
+        AppError::NotFound(format!(
+            "No FX revaluation accounts configured for tenant {}",
+            tenant_id
+        ))
+    })
+}
+
+/// Sets the accounts that receive unrealized gains and losses. Both must be active
+/// accounts of the tenant in its base currency.
+pub async fn upsert_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: UpsertFxRevaluationSettingsDto,
+) -> Result<FxRevaluationSettings, AppError> {
+    info!(
+        "Service: Configuring FX revaluation accounts for tenant ID: {}",
+        tenant_id
+    );
+
+    dto.validate()?;
+    permission::require_permission(pool, tenant_id, user_id, FX_REVALUE).await?;
+
+    for account_id in [
+        dto.unrealized_gain_account_id,
+        dto.unrealized_loss_account_id,
+    ] {
+        let in_base_currency = sqlx::query_scalar!(
+            r#"
+            SELECT a.currency_code = t.base_currency_code as "in_base!"
+            FROM accounts a
+            JOIN tenants t ON t.id = a.tenant_id
+            WHERE a.id = $1 AND a.tenant_id = $2 AND a.is_active = TRUE AND a.archived_at IS NULL
+            "#,
+            account_id,
+            tenant_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "Account ID {} is invalid, inactive or archived for tenant {}",
+                account_id, tenant_id
+            ))
+        })?;
+        if !in_base_currency {
+            return Err(AppError::Validation(format!(
+                "Account ID {} must be in the tenant's base currency",
+                account_id
+            )));
+        }
+    }
+
+    let settings = query_as!(
+        FxRevaluationSettings,
+        r#"
+        INSERT INTO fx_revaluation_settings (
+            tenant_id, unrealized_gain_account_id, unrealized_loss_account_id, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $4)
+        ON CONFLICT (tenant_id) DO UPDATE
+        SET
+            unrealized_gain_account_id = EXCLUDED.unrealized_gain_account_id,
+            unrealized_loss_account_id = EXCLUDED.unrealized_loss_account_id,
+            updated_at = NOW(),
+            updated_by = EXCLUDED.updated_by
+        RETURNING
+            tenant_id, unrealized_gain_account_id, unrealized_loss_account_id,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.unrealized_gain_account_id,
+        dto.unrealized_loss_account_id,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(settings)
+}
+
+/// Lists the tenant's posted revaluations, newest first.
+pub async fn list_revaluations(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<FxRevaluation>, AppError> {
+    info!(
+        "Service: Listing FX revaluations for tenant ID: {}",
+        tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, FX_REVALUE).await?;
+
+    let revaluations = query_as!(
+        FxRevaluation,
+        r#"
+        SELECT
+            id, tenant_id, as_of, transaction_id, base_currency_code, total_gain, total_loss,
+            lines, created_at, created_by
+        FROM fx_revaluations
+        WHERE tenant_id = $1
+        ORDER BY as_of DESC, created_at DESC
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(revaluations)
+}
+
+/// Revalues the tenant's foreign-currency accounts as of a date (or a fiscal period's end)
+/// and, unless it is a dry run, posts the adjustments.
+pub async fn run_revaluation(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: RunFxRevaluationDto,
+) -> Result<FxRevaluationResult, AppError> {
+    info!(
+        "Service: Running FX revaluation for tenant ID: {}",
+        tenant_id
+    );
+
+    dto.validate()?;
+    permission::require_permission(pool, tenant_id, user_id, FX_REVALUE).await?;
+
+    let as_of = match (dto.as_of, dto.fiscal_period_id) {
+        (Some(as_of), None) => as_of,
+        (None, Some(period_id)) => sqlx::query_scalar!(
+            "SELECT end_date FROM fiscal_periods WHERE id = $1 AND tenant_id = $2",
+            period_id,
+            tenant_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Fiscal period with ID {} not found", period_id))
+        })?,
+        _ => {
+            return Err(AppError::Validation(
+                "Give exactly one of as_of or fiscal_period_id".to_string(),
+            ))
+        }
+    };
+    let dry_run = dto.dry_run.unwrap_or(false);
+
+    let base_currency_code = sqlx::query_scalar!(
+        "SELECT base_currency_code FROM tenants WHERE id = $1",
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+    let lines = revaluation_lines(pool, tenant_id, &base_currency_code, as_of).await?;
+
+    let (total_gain, total_loss) = totals(&lines);
+
+    let revaluation = if dry_run || lines.is_empty() {
+        None
+    } else {
+        let settings = find_settings(pool, tenant_id).await?.ok_or_else(|| {
+            AppError::Validation(
+                "Configure the unrealized gain and loss accounts before revaluing".to_string(),
+            )
+        })?;
+        let revaluation = post_revaluation(
+            pool,
+            tenant_id,
+            user_id,
+            &settings,
+            &base_currency_code,
+            as_of,
+            &lines,
+        )
+        .await?;
+
+        domain_event::publish(DomainEvent::FxRevaluationPosted {
+            tenant_id,
+            user_id,
+            revaluation_id: revaluation.id,
+            transaction_id: revaluation.transaction_id,
+            as_of,
+            total_gain,
+            total_loss,
+        });
+        Some(revaluation)
+    };
+
+    Ok(FxRevaluationResult {
+        as_of,
+        base_currency_code,
+        dry_run,
+        revaluation,
+        total_gain,
+        total_loss,
+        lines,
+    })
+}
+
+/// Computes the adjustment for every foreign-currency account with a non-zero difference.
+async fn revaluation_lines(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    base_currency_code: &str,
+    as_of: NaiveDate,
+) -> Result<Vec<FxRevaluationLine>, AppError> {
+    // Debit-positive balances of posted entries: in the account's currency, and as booked
+    // in the base currency
+    let balances = sqlx::query!(
+        r#"
+        SELECT
+            a.id, a.name, a.currency_code,
+            COALESCE(SUM(CASE WHEN je.currency_code = a.currency_code THEN
+                CASE WHEN je.entry_type = 'DEBIT' THEN je.amount ELSE -je.amount END
+            END), 0) as "foreign_balance!",
+            COALESCE(SUM(
+                CASE WHEN je.entry_type = 'DEBIT' THEN COALESCE(je.converted_amount, je.amount)
+                     ELSE -COALESCE(je.converted_amount, je.amount) END
+            ), 0) as "booked_base_balance!"
+        FROM accounts a
+        LEFT JOIN (
+            journal_entries je
+            JOIN transactions t ON je.transaction_id = t.id
+                AND t.status = 'POSTED'
+                AND t.transaction_date <= $3
+        ) ON je.account_id = a.id
+        WHERE a.tenant_id = $1 AND a.is_active = TRUE AND a.currency_code <> $2
+        GROUP BY a.id, a.name, a.currency_code
+        ORDER BY a.account_code NULLS LAST, a.name
+        "#,
+        tenant_id,
+        base_currency_code,
+        as_of
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut lines = Vec::new();
+    for balance in balances {
+        let rate = currency_conversion::find_rate(
+            pool,
+            tenant_id,
+            &balance.currency_code,
+            base_currency_code,
+            as_of,
+        )
+        .await?;
+        let revalued_base_balance = (balance.foreign_balance * rate.rate).round_dp(2);
+        let adjustment = revalued_base_balance - balance.booked_base_balance;
+        if adjustment.is_zero() {
+            continue;
+        }
+        lines.push(FxRevaluationLine {
+            account_id: balance.id,
+            account_name: balance.name,
+            currency_code: balance.currency_code,
+            foreign_balance: balance.foreign_balance,
+            booked_base_balance: balance.booked_base_balance,
+            rate: rate.rate,
+            rate_date: rate.rate_date,
+            rate_method: rate.method,
+            revalued_base_balance,
+            adjustment,
+        });
+    }
+    Ok(lines)
+}
+
+/// `(total_gain, total_loss)`, both non-negative.
+fn totals(lines: &[FxRevaluationLine]) -> (Decimal, Decimal) {
+    let gain = lines
+        .iter()
+        .map(|l| l.adjustment)
+        .filter(|a| *a > Decimal::ZERO)
+        .sum();
+    let loss = lines
+        .iter()
+        .map(|l| -l.adjustment)
+        .filter(|a| *a > Decimal::ZERO)
+        .sum();
+    (gain, loss)
+}
+
+/// Books the adjustments as one posted transaction and records the run.
+async fn post_revaluation(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    settings: &FxRevaluationSettings,
+    base_currency_code: &str,
+    as_of: NaiveDate,
+    lines: &[FxRevaluationLine],
+) -> Result<FxRevaluation, AppError> {
+    let (total_gain, total_loss) = totals(lines);
+    let text_key = privacy::sealing_key(pool, tenant_id).await?;
+    let mut db_tx = pool.begin().await?;
+
+    fiscal_period::ensure_date_open(&mut *db_tx, tenant_id, user_id, as_of).await?;
+
+    let transaction_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO transactions (
+            tenant_id, transaction_date, description, type, amount, currency_code,
+            status, posted_at, posted_by, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, 'ADJUSTMENT', $4, $5, 'POSTED', NOW(), $6, $6, $6)
+        RETURNING id
+        "#,
+        tenant_id,
+        as_of,
+        privacy::seal(
+            text_key.as_ref(),
+            format!("Unrealized FX revaluation as of {}", as_of)
+        )?,
+        total_gain + total_loss,
+        base_currency_code,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    // Zero-amount legs move only the base-currency value of each revalued account
+    for line in lines {
+        let entry_type = if line.adjustment > Decimal::ZERO {
+            JournalEntryType::Debit
+        } else {
+            JournalEntryType::Credit
+        };
+        sqlx::query!(
+            r#"
+            INSERT INTO journal_entries (
+                transaction_id, account_id, entry_type, amount, currency_code,
+                exchange_rate, converted_amount, created_by, updated_by
+            )
+            VALUES ($1, $2, $3, 0, $4, $5, $6, $7, $7)
+            "#,
+            transaction_id,
+            line.account_id,
+            entry_type as JournalEntryType,
+            line.currency_code,
+            line.rate,
+            line.adjustment.abs(),
+            user_id
+        )
+        .execute(&mut *db_tx)
+        .await?;
+    }
+
+    let offsets = [
+        (
+            settings.unrealized_gain_account_id,
+            JournalEntryType::Credit,
+            total_gain,
+        ),
+        (
+            settings.unrealized_loss_account_id,
+            JournalEntryType::Debit,
+            total_loss,
+        ),
+    ];
+    for (account_id, entry_type, amount) in offsets {
+        if amount.is_zero() {
+            continue;
+        }
+        sqlx::query!(
+            r#"
+            INSERT INTO journal_entries (
+                transaction_id, account_id, entry_type, amount, currency_code, created_by, updated_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $6)
+            "#,
+            transaction_id,
+            account_id,
+            entry_type as JournalEntryType,
+            amount,
+            base_currency_code,
+            user_id
+        )
+        .execute(&mut *db_tx)
+        .await?;
+    }
+    balance_snapshot::record_transaction(&mut *db_tx, transaction_id, SnapshotChange::Posted)
+        .await?;
+
+    let lines_json = serde_json::to_value(lines).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to serialize revaluation lines: {}", e))
+    })?;
+    let revaluation = query_as!(
+        FxRevaluation,
+        r#"
+        INSERT INTO fx_revaluations (
+            tenant_id, as_of, transaction_id, base_currency_code, total_gain, total_loss, lines, created_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING
+            id, tenant_id, as_of, transaction_id, base_currency_code, total_gain, total_loss,
+            lines, created_at, created_by
+        "#,
+        tenant_id,
+        as_of,
+        transaction_id,
+        base_currency_code,
+        total_gain,
+        total_loss,
+        lines_json,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(revaluation)
+}
+
+async fn find_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Option<FxRevaluationSettings>, AppError> {
+    let settings = query_as!(
+        FxRevaluationSettings,
+        r#"
+        SELECT
+            tenant_id, unrealized_gain_account_id, unrealized_loss_account_id,
+            created_at, created_by, updated_at, updated_by
+        FROM fx_revaluation_settings
+        WHERE tenant_id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(settings)
+}
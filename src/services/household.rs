@@ -0,0 +1,415 @@
+//! Household use of a tenant: per-member attribution of transactions, spending per member
+//! and settling up shared expenses.
+//!
+//! Members are the tenant's users. A transaction attributed to a member is that member's
+//! spending; a shared one was paid by the member for everyone, and is split in equal
+//! shares between the participants of the settle-up (by default the tenant's active
+//! users, plus anyone who paid a shared expense). Only expenses count; voided transactions
+//! and reversal pairs, which cancel out, are left out. Currencies are never mixed.
+
+use std::cmp::Reverse;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{PgConnection, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::household_dto::{HouseholdPeriodQuery, SetTransactionAttributionDto},
+        household::{
+            HouseholdSpending, MemberSpending, SettleUp, SettleUpBalance, SettleUpCurrency,
+            SettleUpTransfer, TransactionAttribution,
+        },
+    },
+};
+
+/// Attributes a transaction to a member, replacing any earlier attribution.
+pub async fn set_attribution(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: SetTransactionAttributionDto,
+) -> Result<TransactionAttribution, AppError> {
+    info!(
+        "Service: Attributing transaction ID: {} to member ID: {} for tenant ID: {}",
+        transaction_id, dto.member_id, tenant_id
+    );
+
+    let transaction_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM transactions WHERE id = $1 AND tenant_id = $2) as "exists!""#,
+        transaction_id,
+        tenant_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !transaction_exists {
+        return Err(AppError::NotFound(format!(
+            "Transaction with ID {} not found for tenant {}",
+            transaction_id, tenant_id
+        )));
+    }
+
+    let mut conn = pool.acquire().await?;
+    record_attribution(
+        &mut conn,
+        tenant_id,
+        transaction_id,
+        updated_by_user_id,
+        dto,
+    )
+    .await
+}
+
+/// Writes a transaction's attribution; the transaction must be the tenant's.
+/// Used on its own and inside `transaction::create_transaction`.
+pub async fn record_attribution(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: SetTransactionAttributionDto,
+) -> Result<TransactionAttribution, AppError> {
+    ensure_member(&mut *conn, tenant_id, dto.member_id).await?;
+
+    let attribution = sqlx::query_as!(
+        TransactionAttribution,
+        r#"
+        INSERT INTO transaction_attributions (transaction_id, tenant_id, member_id, is_shared, updated_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (transaction_id) DO UPDATE
+        SET member_id = EXCLUDED.member_id,
+            is_shared = EXCLUDED.is_shared,
+            updated_at = NOW(),
+            updated_by = EXCLUDED.updated_by
+        RETURNING transaction_id, tenant_id, member_id, is_shared, updated_at, updated_by
+        "#,
+        transaction_id,
+        tenant_id,
+        dto.member_id,
+        dto.is_shared,
+        updated_by_user_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(attribution)
+}
+
+/// Removes a transaction's attribution.
+pub async fn clear_attribution(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Clearing attribution of transaction ID: {} for tenant ID: {}",
+        transaction_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        "DELETE FROM transaction_attributions WHERE transaction_id = $1 AND tenant_id = $2",
+        transaction_id,
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Transaction {} is not attributed to a member",
+            transaction_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Expenses per member and currency over the period.
+pub async fn household_spending(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: HouseholdPeriodQuery,
+) -> Result<HouseholdSpending, AppError> {
+    info!("Service: Household spending for tenant ID: {}", tenant_id);
+
+    let (from_date, to_date) = period(&query)?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            a.member_id as "member_id?",
+            u.first_name || ' ' || u.last_name as "member_name?",
+            t.currency_code,
+            COUNT(*) as "transaction_count!",
+            COALESCE(SUM(t.amount) FILTER (WHERE NOT COALESCE(a.is_shared, FALSE)), 0) as "personal!",
+            COALESCE(SUM(t.amount) FILTER (WHERE a.is_shared), 0) as "shared_paid!",
+            SUM(t.amount) as "total!"
+        FROM transactions t
+        LEFT JOIN transaction_attributions a ON a.transaction_id = t.id
+        LEFT JOIN users u ON u.id = a.member_id
+        WHERE t.tenant_id = $1
+          AND t.type = 'EXPENSE'
+          AND t.status <> 'VOIDED'
+          AND t.reversal_of_id IS NULL
+          AND t.reversed_by_id IS NULL
+          AND t.transaction_date BETWEEN $2 AND $3
+        GROUP BY a.member_id, u.first_name, u.last_name, t.currency_code
+        ORDER BY 2 NULLS LAST, t.currency_code
+        "#,
+        tenant_id,
+        from_date,
+        to_date
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let members = rows
+        .into_iter()
+        .map(|row| MemberSpending {
+            member_id: row.member_id,
+            member_name: row.member_name,
+            currency_code: row.currency_code,
+            transaction_count: row.transaction_count,
+            personal: row.personal,
+            shared_paid: row.shared_paid,
+            total: row.total,
+        })
+        .collect();
+
+    Ok(HouseholdSpending {
+        from_date,
+        to_date,
+        members,
+    })
+}
+
+/// Balances on the period's shared expenses and the payments that settle them.
+pub async fn settle_up(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: HouseholdPeriodQuery,
+) -> Result<SettleUp, AppError> {
+    info!("Service: Settle-up for tenant ID: {}", tenant_id);
+
+    let (from_date, to_date) = period(&query)?;
+
+    let mut participants: Vec<Uuid> = match &query.member_ids {
+        Some(ids) => {
+            let ids = parse_member_ids(ids)?;
+            let mut conn = pool.acquire().await?;
+            for id in &ids {
+                ensure_member(&mut conn, tenant_id, *id).await?;
+            }
+            ids
+        }
+        None => {
+            sqlx::query_scalar!(
+                r#"
+            SELECT DISTINCT u.id
+            FROM user_tenant_roles utr
+            JOIN users u ON u.id = utr.user_id
+            WHERE utr.tenant_id = $1 AND u.is_active = TRUE
+            "#,
+                tenant_id
+            )
+            .fetch_all(pool)
+            .await?
+        }
+    };
+
+    let paid = sqlx::query!(
+        r#"
+        SELECT a.member_id, t.currency_code, SUM(t.amount) as "paid!"
+        FROM transactions t
+        JOIN transaction_attributions a ON a.transaction_id = t.id
+        WHERE t.tenant_id = $1
+          AND a.is_shared = TRUE
+          AND t.type = 'EXPENSE'
+          AND t.status <> 'VOIDED'
+          AND t.reversal_of_id IS NULL
+          AND t.reversed_by_id IS NULL
+          AND t.transaction_date BETWEEN $2 AND $3
+        GROUP BY a.member_id, t.currency_code
+        ORDER BY t.currency_code
+        "#,
+        tenant_id,
+        from_date,
+        to_date
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Whoever paid a shared expense takes part, even if not listed
+    for row in &paid {
+        if !participants.contains(&row.member_id) {
+            participants.push(row.member_id);
+        }
+    }
+
+    let mut names: Vec<(Uuid, String)> = sqlx::query!(
+        r#"SELECT id, first_name || ' ' || last_name as "name!" FROM users WHERE id = ANY($1)"#,
+        &participants
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, row.name))
+    .collect();
+    names.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+
+    let mut currency_codes: Vec<String> =
+        paid.iter().map(|row| row.currency_code.clone()).collect();
+    currency_codes.dedup(); // Sorted by the query
+
+    let currencies = currency_codes
+        .into_iter()
+        .map(|currency_code| {
+            let paid_by = |member_id: Uuid| {
+                paid.iter()
+                    .filter(|row| row.member_id == member_id && row.currency_code == currency_code)
+                    .map(|row| row.paid)
+                    .sum::<Decimal>()
+            };
+            let total_shared: Decimal = names.iter().map(|(id, _)| paid_by(*id)).sum();
+            let shares = equal_shares(total_shared, names.len());
+            let balances: Vec<SettleUpBalance> = names
+                .iter()
+                .zip(shares)
+                .map(|((member_id, member_name), share)| {
+                    let paid = paid_by(*member_id);
+                    SettleUpBalance {
+                        member_id: *member_id,
+                        member_name: member_name.clone(),
+                        paid,
+                        share,
+                        balance: paid - share,
+                    }
+                })
+                .collect();
+            let transfers = settle_transfers(&balances);
+            SettleUpCurrency {
+                currency_code,
+                total_shared,
+                balances,
+                transfers,
+            }
+        })
+        .collect();
+
+    Ok(SettleUp {
+        from_date,
+        to_date,
+        currencies,
+    })
+}
+
+/// Fails unless `member_id` is a user of the tenant.
+async fn ensure_member(
+    conn: &mut PgConnection,
+    tenant_id: Uuid,
+    member_id: Uuid,
+) -> Result<(), AppError> {
+    let is_member = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM user_tenant_roles WHERE tenant_id = $1 AND user_id = $2) as "exists!""#,
+        tenant_id,
+        member_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    if !is_member {
+        return Err(AppError::Validation(format!(
+            "User {} is not a member of tenant {}",
+            member_id, tenant_id
+        )));
+    }
+    Ok(())
+}
+
+/// The query's period; defaults to month to date.
+fn period(query: &HouseholdPeriodQuery) -> Result<(NaiveDate, NaiveDate), AppError> {
+    let to_date = query.to_date.unwrap_or_else(|| Utc::now().date_naive());
+    let from_date = query
+        .from_date
+        .unwrap_or_else(|| to_date.with_day(1).expect("day 1 is always valid"));
+    if from_date > to_date {
+        return Err(AppError::Validation(
+            "from_date must not be after to_date".to_string(),
+        ));
+    }
+    Ok((from_date, to_date))
+}
+
+fn parse_member_ids(ids: &str) -> Result<Vec<Uuid>, AppError> {
+    let mut parsed: Vec<Uuid> = Vec::new();
+    for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        let id = id.parse().map_err(|_| {
+            AppError::Validation(format!("'{}' in member_ids is not a valid UUID", id))
+        })?;
+        if !parsed.contains(&id) {
+            parsed.push(id);
+        }
+    }
+    Ok(parsed)
+}
+
+/// Splits `total` into `count` shares in cents; the first shares take the leftover cents,
+/// so the shares always add up to the total.
+fn equal_shares(total: Decimal, count: usize) -> Vec<Decimal> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let cent = Decimal::new(1, 2);
+    let base = (total / Decimal::from(count)).round_dp_with_strategy(2, RoundingStrategy::ToZero);
+    let mut leftover = total - base * Decimal::from(count);
+    (0..count)
+        .map(|_| {
+            if leftover >= cent {
+                leftover -= cent;
+                base + cent
+            } else {
+                base
+            }
+        })
+        .collect()
+}
+
+/// Pays off the largest debts against the largest credits first.
+fn settle_transfers(balances: &[SettleUpBalance]) -> Vec<SettleUpTransfer> {
+    let mut debtors: Vec<(Uuid, Decimal)> = balances
+        .iter()
+        .filter(|b| b.balance < Decimal::ZERO)
+        .map(|b| (b.member_id, -b.balance))
+        .collect();
+    let mut creditors: Vec<(Uuid, Decimal)> = balances
+        .iter()
+        .filter(|b| b.balance > Decimal::ZERO)
+        .map(|b| (b.member_id, b.balance))
+        .collect();
+    debtors.sort_by_key(|&(_, amount)| Reverse(amount));
+    creditors.sort_by_key(|&(_, amount)| Reverse(amount));
+
+    let mut transfers = Vec::new();
+    let (mut d, mut c) = (0, 0);
+    while d < debtors.len() && c < creditors.len() {
+        let amount = debtors[d].1.min(creditors[c].1);
+        transfers.push(SettleUpTransfer {
+            from_member_id: debtors[d].0,
+            to_member_id: creditors[c].0,
+            amount,
+        });
+        debtors[d].1 -= amount;
+        creditors[c].1 -= amount;
+        if debtors[d].1.is_zero() {
+            d += 1;
+        }
+        if creditors[c].1.is_zero() {
+            c += 1;
+        }
+    }
+    transfers
+}
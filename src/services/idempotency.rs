@@ -0,0 +1,276 @@
+//! Stored responses of POST requests sent with an `Idempotency-Key`.
+//!
+//! The first request with a key claims it, runs, and records its response; a retry with the
+//! same key and the same request gets that response back. Keys are kept for
+//! `IDEMPOTENCY_KEY_RETENTION_DAYS`, after which the key can be used again. They live in
+//! Redis when it is configured, and in `idempotency_keys` otherwise.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use redis::{aio::ConnectionManager, AsyncCommands, Script};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{config, error::AppError, services::redis_store};
+
+/// A claim still without a response after this long belongs to a request that died
+/// (e.g. the instance restarted), so the key can be claimed again.
+const STALE_CLAIM_SECS: f64 = 300.0;
+
+/// Deletes a claim only while it is still the caller's unanswered claim.
+const RELEASE_CLAIM: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Stores the response only while the key is still the caller's unanswered claim.
+const COMPLETE_CLAIM: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+end
+return false
+"#;
+
+/// What a request finds when it tries to claim a key.
+pub enum KeyClaim {
+    /// The key is new (or expired); the request runs and its response is recorded.
+    Claimed,
+    /// The first request with this key has not finished yet.
+    InProgress,
+    /// The key was used for a different request.
+    Mismatch,
+    Completed(StoredResponse),
+}
+
+pub struct StoredResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// A key as stored in Redis: the claim, and the response once there is one.
+#[derive(Serialize, Deserialize)]
+struct RedisEntry {
+    request_hash: String,
+    status: Option<u16>,
+    content_type: Option<String>,
+    body: Option<String>, // Base64
+}
+
+impl RedisEntry {
+    fn claim(request_hash: &str) -> String {
+        let entry = RedisEntry {
+            request_hash: request_hash.to_string(),
+            status: None,
+            content_type: None,
+            body: None,
+        };
+        serde_json::to_string(&entry).unwrap_or_default()
+    }
+}
+
+/// Claims `key` for a request, or reports what the earlier request with it did.
+pub async fn claim_key(
+    pool: &PgPool,
+    scope: &str,
+    key: &str,
+    request_hash: &str,
+) -> Result<KeyClaim, AppError> {
+    let existing = match redis_store::connection() {
+        Some(mut connection) => claim_in_redis(&mut connection, scope, key, request_hash).await?,
+        None => claim_in_database(pool, scope, key, request_hash).await?,
+    };
+    // `None`: the key was free and is now claimed
+    let Some((existing_hash, response)) = existing else {
+        return Ok(KeyClaim::Claimed);
+    };
+
+    if existing_hash != request_hash {
+        return Ok(KeyClaim::Mismatch);
+    }
+    Ok(match response {
+        None => KeyClaim::InProgress,
+        Some(response) => KeyClaim::Completed(response),
+    })
+}
+
+/// The key's request hash and response, or `None` once it is claimed for this request.
+async fn claim_in_database(
+    pool: &PgPool,
+    scope: &str,
+    key: &str,
+    request_hash: &str,
+) -> Result<Option<(String, Option<StoredResponse>)>, AppError> {
+    let retention_days = config::get().schedulers.idempotency_key_retention_days;
+    let claimed = sqlx::query_scalar!(
+        r#"
+        INSERT INTO idempotency_keys (scope, idempotency_key, request_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (scope, idempotency_key) DO UPDATE
+        SET request_hash = EXCLUDED.request_hash, response_status = NULL,
+            response_content_type = NULL, response_body = NULL, created_at = NOW(),
+            completed_at = NULL
+        WHERE idempotency_keys.created_at < NOW() - make_interval(days => $4)
+           OR (idempotency_keys.response_status IS NULL
+               AND idempotency_keys.created_at < NOW() - make_interval(secs => $5))
+        RETURNING id
+        "#,
+        scope,
+        key,
+        request_hash,
+        retention_days as i32,
+        STALE_CLAIM_SECS
+    )
+    .fetch_optional(pool)
+    .await?;
+    if claimed.is_some() {
+        return Ok(None);
+    }
+
+    let existing = sqlx::query!(
+        r#"
+        SELECT request_hash, response_status, response_content_type, response_body
+        FROM idempotency_keys
+        WHERE scope = $1 AND idempotency_key = $2
+        "#,
+        scope,
+        key
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(released)?;
+
+    let response = existing.response_status.map(|status| StoredResponse {
+        status: status as u16,
+        content_type: existing.response_content_type,
+        body: existing.response_body.unwrap_or_default(),
+    });
+    Ok(Some((existing.request_hash, response)))
+}
+
+/// As `claim_in_database`. A claim expires on its own once it goes stale, a response
+/// after the retention period.
+async fn claim_in_redis(
+    connection: &mut ConnectionManager,
+    scope: &str,
+    key: &str,
+    request_hash: &str,
+) -> Result<Option<(String, Option<StoredResponse>)>, AppError> {
+    let redis_key = redis_key(scope, key);
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(&redis_key)
+        .arg(RedisEntry::claim(request_hash))
+        .arg("NX")
+        .arg("EX")
+        .arg(STALE_CLAIM_SECS as u64)
+        .query_async(connection)
+        .await?;
+    if claimed.is_some() {
+        return Ok(None);
+    }
+
+    let existing: Option<String> = connection.get(&redis_key).await?;
+    let existing: RedisEntry = existing
+        .and_then(|entry| serde_json::from_str(&entry).ok())
+        .ok_or_else(released)?;
+    let response = existing.status.map(|status| StoredResponse {
+        status,
+        content_type: existing.content_type,
+        body: existing
+            .body
+            .and_then(|body| BASE64.decode(body).ok())
+            .unwrap_or_default(),
+    });
+    Ok(Some((existing.request_hash, response)))
+}
+
+fn redis_key(scope: &str, key: &str) -> String {
+    redis_store::key(&format!("idempotency:{}:{}", scope, key))
+}
+
+/// Released between claiming and reading it; the client can simply retry.
+fn released() -> AppError {
+    AppError::TransactionConflict("The Idempotency-Key was released; retry the request".to_string())
+}
+
+/// Records the response of the request that claimed `key`. Nothing is recorded if the claim
+/// has since gone stale and been taken by another request.
+pub async fn complete_key(
+    pool: &PgPool,
+    scope: &str,
+    key: &str,
+    request_hash: &str,
+    response: &StoredResponse,
+) -> Result<(), AppError> {
+    if let Some(mut connection) = redis_store::connection() {
+        let retention_days = config::get().schedulers.idempotency_key_retention_days;
+        let entry = RedisEntry {
+            request_hash: request_hash.to_string(),
+            status: Some(response.status),
+            content_type: response.content_type.clone(),
+            body: Some(BASE64.encode(&response.body)),
+        };
+        let entry = serde_json::to_string(&entry).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to store the response: {}", e))
+        })?;
+        Script::new(COMPLETE_CLAIM)
+            .key(redis_key(scope, key))
+            .arg(RedisEntry::claim(request_hash))
+            .arg(entry)
+            .arg(u64::from(retention_days) * 86_400)
+            .invoke_async::<Option<String>>(&mut connection)
+            .await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE idempotency_keys
+        SET response_status = $3, response_content_type = $4, response_body = $5,
+            completed_at = NOW()
+        WHERE scope = $1 AND idempotency_key = $2 AND request_hash = $6
+          AND response_status IS NULL
+        "#,
+        scope,
+        key,
+        response.status as i16,
+        response.content_type,
+        &response.body,
+        request_hash
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Gives up the claim on `key`, so a retry runs the request again.
+pub async fn release_key(
+    pool: &PgPool,
+    scope: &str,
+    key: &str,
+    request_hash: &str,
+) -> Result<(), AppError> {
+    if let Some(mut connection) = redis_store::connection() {
+        Script::new(RELEASE_CLAIM)
+            .key(redis_key(scope, key))
+            .arg(RedisEntry::claim(request_hash))
+            .invoke_async::<i64>(&mut connection)
+            .await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        DELETE FROM idempotency_keys
+        WHERE scope = $1 AND idempotency_key = $2 AND request_hash = $3
+          AND response_status IS NULL
+        "#,
+        scope,
+        key,
+        request_hash
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,287 @@
+//! Background import jobs.
+//!
+//! An importer started as a job runs after the request returns. Clients poll the job for
+//! `processed_rows` / `total_rows`, or follow the `imports` topic on `/ws`. Once it finishes,
+//! a job with failed rows offers an error file: the failed rows with their original columns
+//! plus `error_field` and `error_message`.
+
+use sqlx::{query_as, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::import_job::{ImportJob, ImportJobStatus},
+    services::live_update,
+};
+
+/// Job kind of `budget_csv` imports.
+pub const BUDGET_CSV: &str = "BUDGET_CSV";
+
+/// Progress is written every this many rows (and after the last one).
+const PROGRESS_INTERVAL_ROWS: usize = 100;
+
+/// Most recent jobs returned by `list_import_jobs`.
+const LIST_LIMIT: i64 = 50;
+
+/// Outcome of a finished job.
+pub struct ImportOutcome {
+    pub result_id: Option<Uuid>,
+    pub failed_rows: usize,
+    pub error_message: Option<String>,
+    pub error_csv: Option<String>,
+}
+
+/// Hands row progress from an importer to its job.
+pub struct ImportProgress<'a> {
+    pool: &'a PgPool,
+    tenant_id: Uuid,
+    job_id: Uuid,
+    total_rows: usize,
+}
+
+impl<'a> ImportProgress<'a> {
+    /// Marks the job as running over `total_rows` rows.
+    pub async fn start(
+        pool: &'a PgPool,
+        job_id: Uuid,
+        total_rows: usize,
+    ) -> Result<ImportProgress<'a>, AppError> {
+        let tenant_id = sqlx::query_scalar!(
+            r#"
+            UPDATE import_jobs
+            SET status = 'RUNNING', total_rows = $2, started_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING tenant_id
+            "#,
+            job_id,
+            total_rows as i32
+        )
+        .fetch_one(pool)
+        .await?;
+        live_update::publish_import_progress(
+            tenant_id,
+            job_id,
+            ImportJobStatus::Running,
+            0,
+            Some(total_rows as i32),
+            0,
+        );
+
+        Ok(ImportProgress {
+            pool,
+            tenant_id,
+            job_id,
+            total_rows,
+        })
+    }
+
+    /// Records that `processed_rows` rows are done; only every `PROGRESS_INTERVAL_ROWS`
+    /// rows (and the last one) reach the database and live subscribers.
+    pub async fn rows_processed(&self, processed_rows: usize) -> Result<(), AppError> {
+        if processed_rows == 0
+            || (!processed_rows.is_multiple_of(PROGRESS_INTERVAL_ROWS)
+                && processed_rows != self.total_rows)
+        {
+            return Ok(());
+        }
+        sqlx::query!(
+            "UPDATE import_jobs SET processed_rows = $2, updated_at = NOW() WHERE id = $1",
+            self.job_id,
+            processed_rows as i32
+        )
+        .execute(self.pool)
+        .await?;
+        live_update::publish_import_progress(
+            self.tenant_id,
+            self.job_id,
+            ImportJobStatus::Running,
+            processed_rows as i32,
+            Some(self.total_rows as i32),
+            0,
+        );
+        Ok(())
+    }
+}
+
+/// Creates a pending job for the tenant.
+pub async fn create_import_job(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    kind: &str,
+) -> Result<ImportJob, AppError> {
+    info!(
+        "Service: Creating {} import job for tenant ID: {}",
+        kind, tenant_id
+    );
+
+    let job = query_as!(
+        ImportJob,
+        r#"
+        INSERT INTO import_jobs (tenant_id, kind, created_by, updated_by)
+        VALUES ($1, $2, $3, $3)
+        RETURNING
+            id, tenant_id, kind, status as "status: ImportJobStatus", total_rows, processed_rows,
+            failed_rows, result_id, error_message, error_csv IS NOT NULL as "has_error_file!",
+            started_at, completed_at, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        kind,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(job)
+}
+
+/// Lists the tenant's most recent import jobs, newest first.
+pub async fn list_import_jobs(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<ImportJob>, AppError> {
+    info!("Service: Listing import jobs for tenant ID: {}", tenant_id);
+
+    let jobs = query_as!(
+        ImportJob,
+        r#"
+        SELECT
+            id, tenant_id, kind, status as "status: ImportJobStatus", total_rows, processed_rows,
+            failed_rows, result_id, error_message, error_csv IS NOT NULL as "has_error_file!",
+            started_at, completed_at, created_at, created_by, updated_at, updated_by
+        FROM import_jobs
+        WHERE tenant_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        tenant_id,
+        LIST_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(jobs)
+}
+
+/// Retrieves one import job, with its progress.
+pub async fn get_import_job(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    job_id: Uuid,
+) -> Result<ImportJob, AppError> {
+    info!(
+        "Service: Getting import job {} for tenant ID: {}",
+        job_id, tenant_id
+    );
+
+    let job = query_as!(
+        ImportJob,
+        r#"
+        SELECT
+            id, tenant_id, kind, status as "status: ImportJobStatus", total_rows, processed_rows,
+            failed_rows, result_id, error_message, error_csv IS NOT NULL as "has_error_file!",
+            started_at, completed_at, created_at, created_by, updated_at, updated_by
+        FROM import_jobs
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        job_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Import job with ID {} not found for tenant {}",
+            job_id, tenant_id
+        ))
+    })?;
+
+    Ok(job)
+}
+
+/// The failed rows of a finished job as CSV. Returns `(file_name, csv_text)`.
+pub async fn get_error_file(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    job_id: Uuid,
+) -> Result<(String, String), AppError> {
+    info!(
+        "Service: Getting error file of import job {} for tenant ID: {}",
+        job_id, tenant_id
+    );
+
+    let error_csv = sqlx::query_scalar!(
+        "SELECT error_csv FROM import_jobs WHERE id = $1 AND tenant_id = $2",
+        job_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Import job with ID {} not found for tenant {}",
+            job_id, tenant_id
+        ))
+    })?
+    .ok_or_else(|| AppError::NotFound(format!("Import job {} has no failed rows", job_id)))?;
+
+    Ok((format!("import-{}-errors.csv", job_id), error_csv))
+}
+
+/// Records how a job ended. A job that created nothing is FAILED, otherwise COMPLETED.
+pub async fn finish_import_job(
+    pool: &PgPool,
+    job_id: Uuid,
+    outcome: ImportOutcome,
+) -> Result<(), AppError> {
+    let status = if outcome.result_id.is_some() {
+        ImportJobStatus::Completed
+    } else {
+        ImportJobStatus::Failed
+    };
+    let job = sqlx::query!(
+        r#"
+        UPDATE import_jobs
+        SET status = $2, processed_rows = COALESCE(total_rows, processed_rows), failed_rows = $3,
+            result_id = $4, error_message = $5, error_csv = $6, completed_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        RETURNING tenant_id, processed_rows, total_rows
+        "#,
+        job_id,
+        status as ImportJobStatus,
+        outcome.failed_rows as i32,
+        outcome.result_id,
+        outcome.error_message,
+        outcome.error_csv
+    )
+    .fetch_one(pool)
+    .await?;
+    live_update::publish_import_progress(
+        job.tenant_id,
+        job_id,
+        status,
+        job.processed_rows,
+        job.total_rows,
+        outcome.failed_rows as i32,
+    );
+    Ok(())
+}
+
+/// Marks a job as FAILED after an unexpected error, logging if even that fails.
+pub async fn fail_import_job(pool: &PgPool, job_id: Uuid, error: &AppError) {
+    warn!("Import job {} failed: {}", job_id, error);
+    // Like the HTTP responses, internal details stay in the log
+    let message = match error {
+        AppError::DatabaseError(_) | AppError::InternalServerError(_) => {
+            "The import failed unexpectedly; please try again".to_string()
+        }
+        other => other.to_string(),
+    };
+    let outcome = ImportOutcome {
+        result_id: None,
+        failed_rows: 0,
+        error_message: Some(message),
+        error_csv: None,
+    };
+    if let Err(e) = finish_import_job(pool, job_id, outcome).await {
+        warn!("Could not record failure of import job {}: {}", job_id, e);
+    }
+}
@@ -0,0 +1,257 @@
+//! Reachability and credential checks for external integrations.
+//!
+//! Each dependency is checked at most once per `INTEGRATION_HEALTH_CACHE_SECS` (default 60);
+//! results in between are served from memory so health probes never hammer providers.
+//! Dependencies without configuration are reported as `NOT_CONFIGURED`.
+//!
+//! | Dependency         | Configuration                                            |
+//! |--------------------|----------------------------------------------------------|
+//! | `mail`             | `SMTP_HOST` (+ `SMTP_USERNAME`/`SMTP_PASSWORD`)          |
+//! | `bank_aggregator`  | `PLAID_CLIENT_ID`, `PLAID_SECRET`                        |
+//! | `rate_provider`    | `CURRENCY_API_URL` (+ `CURRENCY_API_KEY`)                |
+//! | `storage`          | `STORAGE_HEALTH_URL` (+ `STORAGE_API_KEY`)               |
+//! | `payment_provider` | `PAYMENT_PROVIDER_HEALTH_URL` (+ `PAYMENT_PROVIDER_API_KEY`) |
+
+use std::{
+    collections::HashMap,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use reqwest::{Client, StatusCode};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{
+    config,
+    models::integration_health::{DependencyHealth, DependencyStatus, IntegrationHealthReport},
+    services::{
+        bank_connector::{ConnectorError, PlaidConnector},
+        mailer::{self, SmtpCheck},
+    },
+};
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Last result per dependency. The lock is held while checks run, so concurrent probes
+/// wait for one round of checks instead of starting their own.
+static HEALTH_CACHE: OnceLock<Mutex<HashMap<&'static str, DependencyHealth>>> = OnceLock::new();
+
+/// Result of a single probe before it is merged with the cached history.
+struct Probe {
+    status: DependencyStatus,
+    message: Option<String>,
+}
+
+impl Probe {
+    fn up() -> Self {
+        Probe {
+            status: DependencyStatus::Up,
+            message: None,
+        }
+    }
+
+    fn not_configured() -> Self {
+        Probe {
+            status: DependencyStatus::NotConfigured,
+            message: None,
+        }
+    }
+
+    fn down(message: impl Into<String>) -> Self {
+        Probe {
+            status: DependencyStatus::Down,
+            message: Some(message.into()),
+        }
+    }
+
+    fn auth_failed(message: impl Into<String>) -> Self {
+        Probe {
+            status: DependencyStatus::AuthFailed,
+            message: Some(message.into()),
+        }
+    }
+}
+
+/// Returns the health of every external integration, re-checking stale entries.
+pub async fn integration_health() -> IntegrationHealthReport {
+    let ttl = Duration::from_secs(config::get().integrations.health_cache_secs);
+    let mut cache = HEALTH_CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .await;
+
+    let now = Utc::now();
+    let is_fresh = |name: &str| {
+        cache.get(name).is_some_and(|entry: &DependencyHealth| {
+            (now - entry.checked_at).to_std().is_ok_and(|age| age < ttl)
+        })
+    };
+    let stale: Vec<&'static str> = [
+        "mail",
+        "bank_aggregator",
+        "rate_provider",
+        "storage",
+        "payment_provider",
+    ]
+    .into_iter()
+    .filter(|name| !is_fresh(name))
+    .collect();
+
+    if !stale.is_empty() {
+        info!("Service: Checking integrations: {}", stale.join(", "));
+        let probes = run_probes(&stale).await;
+        for (name, (probe, latency)) in stale.into_iter().zip(probes) {
+            let checked_at = Utc::now();
+            let last_success_at = match probe.status {
+                DependencyStatus::Up => Some(checked_at),
+                _ => cache.get(name).and_then(|entry| entry.last_success_at),
+            };
+            if matches!(
+                probe.status,
+                DependencyStatus::Down | DependencyStatus::AuthFailed
+            ) {
+                warn!(
+                    "Integration '{}' is {:?}: {}",
+                    name,
+                    probe.status,
+                    probe.message.as_deref().unwrap_or("")
+                );
+            }
+            cache.insert(
+                name,
+                DependencyHealth {
+                    name: name.to_string(),
+                    status: probe.status,
+                    message: probe.message,
+                    latency_ms: (probe.status != DependencyStatus::NotConfigured)
+                        .then_some(latency.as_millis() as u64),
+                    checked_at,
+                    last_success_at,
+                },
+            );
+        }
+    }
+
+    let mut dependencies: Vec<DependencyHealth> = cache.values().cloned().collect();
+    dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+    let healthy = dependencies.iter().all(|d| {
+        matches!(
+            d.status,
+            DependencyStatus::Up | DependencyStatus::NotConfigured
+        )
+    });
+
+    IntegrationHealthReport {
+        healthy,
+        dependencies,
+    }
+}
+
+/// Runs the probes for the named dependencies concurrently, each bounded by `CHECK_TIMEOUT`.
+async fn run_probes(names: &[&'static str]) -> Vec<(Probe, Duration)> {
+    let handles: Vec<_> = names
+        .iter()
+        .map(|name| {
+            let name = *name;
+            tokio::spawn(async move {
+                let started = Instant::now();
+                let probe = match tokio::time::timeout(CHECK_TIMEOUT, probe(name)).await {
+                    Ok(probe) => probe,
+                    Err(_) => Probe::down(format!("Timed out after {}s", CHECK_TIMEOUT.as_secs())),
+                };
+                (probe, started.elapsed())
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(handle.await.unwrap_or_else(|e| {
+            (
+                Probe::down(format!("Check panicked: {}", e)),
+                Duration::ZERO,
+            )
+        }));
+    }
+    results
+}
+
+async fn probe(name: &str) -> Probe {
+    let config = config::get();
+    let integrations = &config.integrations;
+    match name {
+        "mail" => probe_mail().await,
+        "bank_aggregator" => probe_bank_aggregator().await,
+        "rate_provider" => {
+            probe_http(
+                &integrations.currency_api_url,
+                &integrations.currency_api_key,
+            )
+            .await
+        }
+        "storage" => probe_http(&config.storage.health_url, &config.storage.api_key).await,
+        "payment_provider" => {
+            probe_http(
+                &integrations.payment_provider_health_url,
+                &integrations.payment_provider_api_key,
+            )
+            .await
+        }
+        _ => Probe::not_configured(),
+    }
+}
+
+async fn probe_mail() -> Probe {
+    if !mailer::is_configured() {
+        return Probe::not_configured();
+    }
+    match mailer::test_connection().await {
+        SmtpCheck::Ok => Probe::up(),
+        SmtpCheck::Rejected(msg) => Probe::auth_failed(msg),
+        SmtpCheck::Unreachable(msg) => Probe::down(msg),
+    }
+}
+
+async fn probe_bank_aggregator() -> Probe {
+    if config::get().integrations.plaid_client_id.is_none() {
+        return Probe::not_configured();
+    }
+    let connector = match PlaidConnector::from_config(None) {
+        Ok(connector) => connector,
+        Err(e) => return Probe::down(e.to_string()),
+    };
+    match connector.check_credentials().await {
+        Ok(()) => Probe::up(),
+        Err(ConnectorError::InvalidCredentials(msg)) => Probe::auth_failed(msg),
+        Err(ConnectorError::ReauthRequired(msg)) | Err(ConnectorError::Provider(msg)) => {
+            Probe::down(msg)
+        }
+    }
+}
+
+/// GETs a health URL, sending the API key as a bearer token when one is configured.
+/// 401/403 are reported as rejected credentials.
+async fn probe_http(url: &Option<String>, key: &Option<String>) -> Probe {
+    let Some(url) = url else {
+        return Probe::not_configured();
+    };
+    let mut request = Client::new().get(url);
+    if let Some(key) = key {
+        request = request.bearer_auth(key);
+    }
+    match request.send().await {
+        Ok(response) if response.status().is_success() => Probe::up(),
+        Ok(response)
+            if matches!(
+                response.status(),
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+            ) =>
+        {
+            Probe::auth_failed(format!("{} returned {}", url, response.status()))
+        }
+        Ok(response) => Probe::down(format!("{} returned {}", url, response.status())),
+        Err(e) => Probe::down(e.to_string()),
+    }
+}
@@ -0,0 +1,919 @@
+//! Invoices: drafts that are edited freely, then issued and paid.
+//!
+//! Issuing an invoice gives it the tenant's next number and posts an INCOME transaction
+//! that debits accounts receivable with the total and credits each line's revenue account.
+//! Each payment posts a TRANSFER from receivable to the deposit account, so the receivable
+//! balance always equals what customers still owe. Numbers come from `invoice_sequences`
+//! and are only taken at issue, in the same database transaction as the posting, so a
+//! failed issue never leaves a gap. Issued invoices are final.
+
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgExecutor, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::{begin_financial, with_retry},
+    error::AppError,
+    models::{
+        dto::invoice_dto::{
+            CreateInvoiceDto, InvoiceLineDto, ListInvoicesQuery, RecordInvoicePaymentDto,
+            UpdateInvoiceDto, UpdateInvoiceNumberingDto,
+        },
+        dto::journal_entry_dto::CreateJournalEntryDto,
+        dto::transaction_dto::CreateTransactionDto,
+        invoice::{
+            Invoice, InvoiceDetail, InvoiceLine, InvoiceNumbering, InvoicePayment, InvoiceStatus,
+        },
+        journal_entry::JournalEntryType,
+        transaction::{TransactionStatus, TransactionType},
+    },
+    services::{
+        domain_event::{self, DomainEvent},
+        transaction,
+    },
+};
+
+/// Decimal places of invoice amounts.
+const AMOUNT_SCALE: u32 = 2;
+
+/// Decimal places of line quantities and unit prices.
+const PRICE_SCALE: u32 = 4;
+
+/// Retrieves the tenant's invoices matching `query`, most recent first.
+pub async fn list_invoices(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: ListInvoicesQuery,
+) -> Result<Vec<Invoice>, AppError> {
+    info!("Service: Listing invoices for tenant ID: {}", tenant_id);
+
+    let invoices = query_as!(
+        Invoice,
+        r#"
+        SELECT id, tenant_id, customer_id, invoice_number, status, issue_date, due_date,
+               currency_code, receivable_account_id, total, amount_paid, issue_transaction_id,
+               notes, issued_at, issued_by, created_at, created_by, updated_at, updated_by
+        FROM invoices
+        WHERE tenant_id = $1
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::uuid IS NULL OR customer_id = $3)
+          AND (NOT $4 OR (status IN ('ISSUED', 'PARTIALLY_PAID') AND due_date < CURRENT_DATE))
+        ORDER BY issue_date DESC, created_at DESC
+        "#,
+        tenant_id,
+        query.status.map(String::from),
+        query.customer_id,
+        query.overdue.unwrap_or(false)
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(invoices)
+}
+
+/// Retrieves an invoice with its lines and payments.
+pub async fn get_invoice(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+) -> Result<InvoiceDetail, AppError> {
+    info!(
+        "Service: Getting invoice with ID: {} for tenant ID: {}",
+        invoice_id, tenant_id
+    );
+
+    let invoice = query_as!(
+        Invoice,
+        r#"
+        SELECT id, tenant_id, customer_id, invoice_number, status, issue_date, due_date,
+               currency_code, receivable_account_id, total, amount_paid, issue_transaction_id,
+               notes, issued_at, issued_by, created_at, created_by, updated_at, updated_by
+        FROM invoices
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        invoice_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| not_found(invoice_id, tenant_id))?;
+
+    let lines = load_lines(pool, invoice_id).await?;
+    let payments = query_as!(
+        InvoicePayment,
+        r#"
+        SELECT id, tenant_id, invoice_id, payment_date, amount, deposit_account_id,
+               transaction_id, payment_id, memo, created_at, created_by
+        FROM invoice_payments
+        WHERE invoice_id = $1
+        ORDER BY payment_date, created_at
+        "#,
+        invoice_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(InvoiceDetail {
+        invoice,
+        lines,
+        payments,
+    })
+}
+
+/// Creates a draft invoice with its lines.
+pub async fn create_invoice(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateInvoiceDto,
+) -> Result<InvoiceDetail, AppError> {
+    info!(
+        "Service: Creating invoice for customer ID: {} for tenant ID {}",
+        dto.customer_id, tenant_id
+    );
+
+    if dto.due_date < dto.issue_date {
+        return Err(AppError::Validation(
+            "Due date cannot be before issue date".to_string(),
+        ));
+    }
+    let amounts = price_lines(&dto.lines)?;
+    let total: Decimal = amounts.iter().sum();
+
+    let mut db_tx = pool.begin().await?;
+    check_customer(&mut *db_tx, tenant_id, dto.customer_id).await?;
+    check_accounts(
+        &mut *db_tx,
+        tenant_id,
+        dto.lines
+            .iter()
+            .map(|line| line.revenue_account_id)
+            .chain([dto.receivable_account_id]),
+    )
+    .await?;
+
+    let invoice_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO invoices (
+            tenant_id, customer_id, issue_date, due_date, currency_code,
+            receivable_account_id, total, notes, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+        RETURNING id
+        "#,
+        tenant_id,
+        dto.customer_id,
+        dto.issue_date,
+        dto.due_date,
+        dto.currency_code.to_uppercase(),
+        dto.receivable_account_id,
+        total,
+        dto.notes,
+        created_by_user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    insert_lines(&mut db_tx, tenant_id, invoice_id, &dto.lines, &amounts).await?;
+
+    db_tx.commit().await?;
+
+    get_invoice(pool, tenant_id, invoice_id).await
+}
+
+/// Updates a draft invoice. Lines, when given, replace the current ones.
+pub async fn update_invoice(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateInvoiceDto,
+) -> Result<InvoiceDetail, AppError> {
+    info!(
+        "Service: Updating invoice with ID: {} for tenant ID: {}",
+        invoice_id, tenant_id
+    );
+
+    let amounts = dto.lines.as_deref().map(price_lines).transpose()?;
+    let total: Option<Decimal> = amounts.as_ref().map(|amounts| amounts.iter().sum());
+
+    let mut db_tx = pool.begin().await?;
+    let current = lock_invoice(&mut db_tx, tenant_id, invoice_id).await?;
+    require_draft(&current)?;
+
+    if dto.issue_date.unwrap_or(current.issue_date) > dto.due_date.unwrap_or(current.due_date) {
+        return Err(AppError::Validation(
+            "Resulting due date cannot be before resulting issue date".to_string(),
+        ));
+    }
+    if let Some(customer_id) = dto.customer_id {
+        check_customer(&mut *db_tx, tenant_id, customer_id).await?;
+    }
+    let line_accounts = dto
+        .lines
+        .iter()
+        .flatten()
+        .map(|line| line.revenue_account_id);
+    check_accounts(
+        &mut *db_tx,
+        tenant_id,
+        line_accounts.chain(dto.receivable_account_id),
+    )
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE invoices
+        SET customer_id = COALESCE($3, customer_id),
+            issue_date = COALESCE($4, issue_date),
+            due_date = COALESCE($5, due_date),
+            currency_code = COALESCE($6, currency_code),
+            receivable_account_id = COALESCE($7, receivable_account_id),
+            notes = COALESCE($8, notes),
+            total = COALESCE($9, total),
+            updated_at = NOW(),
+            updated_by = $10
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        invoice_id,
+        tenant_id,
+        dto.customer_id,
+        dto.issue_date,
+        dto.due_date,
+        dto.currency_code.map(|code| code.to_uppercase()),
+        dto.receivable_account_id,
+        dto.notes,
+        total,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    if let (Some(lines), Some(amounts)) = (&dto.lines, &amounts) {
+        sqlx::query!(
+            "DELETE FROM invoice_lines WHERE invoice_id = $1",
+            invoice_id
+        )
+        .execute(&mut *db_tx)
+        .await?;
+        insert_lines(&mut db_tx, tenant_id, invoice_id, lines, amounts).await?;
+    }
+
+    db_tx.commit().await?;
+
+    get_invoice(pool, tenant_id, invoice_id).await
+}
+
+/// Deletes a draft invoice. Issued invoices cannot be deleted.
+pub async fn delete_invoice(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting invoice with ID: {} for tenant ID: {}",
+        invoice_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    let current = lock_invoice(&mut db_tx, tenant_id, invoice_id).await?;
+    require_draft(&current)?;
+
+    // Lines go with it (ON DELETE CASCADE)
+    sqlx::query!(
+        "DELETE FROM invoices WHERE id = $1 AND tenant_id = $2",
+        invoice_id,
+        tenant_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(())
+}
+
+/// Issues a draft invoice: numbers it and posts the receivable against the revenue.
+pub async fn issue_invoice(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+    user_id: Uuid,
+) -> Result<InvoiceDetail, AppError> {
+    info!(
+        "Service: Issuing invoice with ID: {} for tenant ID: {}",
+        invoice_id, tenant_id
+    );
+
+    let invoice = with_retry(pool, |pool| {
+        issue_invoice_once(pool, tenant_id, invoice_id, user_id)
+    })
+    .await?;
+
+    if let Some(transaction_id) = invoice.issue_transaction_id {
+        domain_event::publish(DomainEvent::TransactionPosted {
+            tenant_id,
+            user_id,
+            transaction_id,
+            amount: invoice.total,
+            transaction_date: invoice.issue_date,
+        });
+    }
+    get_invoice(pool, tenant_id, invoice_id).await
+}
+
+/// One attempt at issuing an invoice, in its own database transaction.
+async fn issue_invoice_once(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+    user_id: Uuid,
+) -> Result<Invoice, AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let invoice = lock_invoice(&mut db_tx, tenant_id, invoice_id).await?;
+    require_draft(&invoice)?;
+    if invoice.total.is_zero() {
+        return Err(AppError::Validation(
+            "An invoice with a zero total cannot be issued".to_string(),
+        ));
+    }
+    let lines = load_lines(&mut *db_tx, invoice_id).await?;
+    let customer_name = sqlx::query_scalar!(
+        "SELECT name FROM customers WHERE id = $1",
+        invoice.customer_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    // The sequence row is created with its defaults on the tenant's first invoice
+    let sequence = sqlx::query!(
+        r#"
+        INSERT INTO invoice_sequences (tenant_id, next_number)
+        VALUES ($1, 2)
+        ON CONFLICT (tenant_id) DO UPDATE
+        SET next_number = invoice_sequences.next_number + 1, updated_at = NOW()
+        RETURNING prefix, next_number - 1 as "number!", padding
+        "#,
+        tenant_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    let invoice_number = format!(
+        "{}{:0width$}",
+        sequence.prefix,
+        sequence.number,
+        width = sequence.padding as usize
+    );
+
+    // One credit per revenue account, in the order the lines first use them
+    let mut credits: Vec<(Uuid, Decimal)> = Vec::new();
+    for line in &lines {
+        match credits
+            .iter_mut()
+            .find(|(account_id, _)| *account_id == line.revenue_account_id)
+        {
+            Some((_, amount)) => *amount += line.amount,
+            None => credits.push((line.revenue_account_id, line.amount)),
+        }
+    }
+    let memo = format!("Invoice {}", invoice_number);
+    let journal_entries = std::iter::once((
+        invoice.receivable_account_id,
+        JournalEntryType::Debit,
+        invoice.total,
+    ))
+    .chain(
+        credits
+            .into_iter()
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|(account_id, amount)| (account_id, JournalEntryType::Credit, amount)),
+    )
+    .map(|(account_id, entry_type, amount)| CreateJournalEntryDto {
+        account_id,
+        entry_type,
+        amount,
+        currency_code: invoice.currency_code.clone(),
+        exchange_rate: None,
+        converted_amount: None,
+        memo: Some(memo.clone()),
+        project_id: None,
+        class_id: None,
+        location_id: None,
+    })
+    .collect();
+
+    let (posted, _) = transaction::insert_transaction(
+        &mut db_tx,
+        tenant_id,
+        user_id,
+        posting(
+            &invoice,
+            invoice.issue_date,
+            format!("Invoice {} to {}", invoice_number, customer_name),
+            TransactionType::Income,
+            invoice.total,
+            journal_entries,
+        ),
+    )
+    .await?;
+
+    let issued = query_as!(
+        Invoice,
+        r#"
+        UPDATE invoices
+        SET status = 'ISSUED', invoice_number = $3, issue_transaction_id = $4,
+            issued_at = NOW(), issued_by = $5, updated_at = NOW(), updated_by = $5
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING id, tenant_id, customer_id, invoice_number, status, issue_date, due_date,
+                  currency_code, receivable_account_id, total, amount_paid, issue_transaction_id,
+                  notes, issued_at, issued_by, created_at, created_by, updated_at, updated_by
+        "#,
+        invoice_id,
+        tenant_id,
+        invoice_number,
+        posted.id,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(issued)
+}
+
+/// Records a payment against an issued invoice and posts it from receivable to the
+/// deposit account. The invoice is PAID once nothing is owed.
+pub async fn record_payment(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+    user_id: Uuid,
+    dto: RecordInvoicePaymentDto,
+) -> Result<InvoiceDetail, AppError> {
+    info!(
+        "Service: Recording payment of {} against invoice ID: {} for tenant ID: {}",
+        dto.amount, invoice_id, tenant_id
+    );
+
+    if dto.amount <= Decimal::ZERO {
+        return Err(AppError::Validation(
+            "amount must be greater than zero".to_string(),
+        ));
+    }
+    if dto.amount.round_dp(AMOUNT_SCALE) != dto.amount {
+        return Err(AppError::Validation(
+            "amount must have at most two decimals".to_string(),
+        ));
+    }
+
+    let payment = with_retry(pool, |pool| {
+        record_payment_once(pool, tenant_id, invoice_id, user_id, &dto)
+    })
+    .await?;
+
+    domain_event::publish(DomainEvent::TransactionPosted {
+        tenant_id,
+        user_id,
+        transaction_id: payment.transaction_id,
+        amount: payment.amount,
+        transaction_date: payment.payment_date,
+    });
+    get_invoice(pool, tenant_id, invoice_id).await
+}
+
+/// One attempt at recording a payment, in its own database transaction.
+async fn record_payment_once(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+    user_id: Uuid,
+    dto: &RecordInvoicePaymentDto,
+) -> Result<InvoicePayment, AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let invoice = lock_invoice(&mut db_tx, tenant_id, invoice_id).await?;
+    let status: InvoiceStatus = invoice
+        .status
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    if !matches!(status, InvoiceStatus::Issued | InvoiceStatus::PartiallyPaid) {
+        return Err(AppError::Validation(format!(
+            "Payments can only be recorded against issued invoices; invoice {} is {}",
+            invoice_id, invoice.status
+        )));
+    }
+    let outstanding = invoice.total - invoice.amount_paid;
+    if dto.amount > outstanding {
+        return Err(AppError::Validation(format!(
+            "The payment of {} is more than the {} still owed",
+            dto.amount, outstanding
+        )));
+    }
+    if dto.payment_date < invoice.issue_date {
+        return Err(AppError::Validation(
+            "Payment date cannot be before the invoice's issue date".to_string(),
+        ));
+    }
+    if dto.deposit_account_id == invoice.receivable_account_id {
+        return Err(AppError::Validation(
+            "The deposit account cannot be the receivable account".to_string(),
+        ));
+    }
+
+    let invoice_number = invoice.invoice_number.as_deref().unwrap_or_default();
+    let memo = format!("Payment for invoice {}", invoice_number);
+    let journal_entries = [
+        (dto.deposit_account_id, JournalEntryType::Debit),
+        (invoice.receivable_account_id, JournalEntryType::Credit),
+    ]
+    .into_iter()
+    .map(|(account_id, entry_type)| CreateJournalEntryDto {
+        account_id,
+        entry_type,
+        amount: dto.amount,
+        currency_code: invoice.currency_code.clone(),
+        exchange_rate: None,
+        converted_amount: None,
+        memo: dto.memo.clone().or_else(|| Some(memo.clone())),
+        project_id: None,
+        class_id: None,
+        location_id: None,
+    })
+    .collect();
+
+    let (posted, _) = transaction::insert_transaction(
+        &mut db_tx,
+        tenant_id,
+        user_id,
+        posting(
+            &invoice,
+            dto.payment_date,
+            memo,
+            TransactionType::Transfer,
+            dto.amount,
+            journal_entries,
+        ),
+    )
+    .await?;
+
+    let payment = query_as!(
+        InvoicePayment,
+        r#"
+        INSERT INTO invoice_payments (
+            tenant_id, invoice_id, payment_date, amount, deposit_account_id, transaction_id, memo, created_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, tenant_id, invoice_id, payment_date, amount, deposit_account_id,
+                  transaction_id, payment_id, memo, created_at, created_by
+        "#,
+        tenant_id,
+        invoice_id,
+        dto.payment_date,
+        dto.amount,
+        dto.deposit_account_id,
+        posted.id,
+        dto.memo,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    let status = if dto.amount == outstanding {
+        InvoiceStatus::Paid
+    } else {
+        InvoiceStatus::PartiallyPaid
+    };
+    sqlx::query!(
+        r#"
+        UPDATE invoices
+        SET amount_paid = amount_paid + $3, status = $4, updated_at = NOW(), updated_by = $5
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        invoice_id,
+        tenant_id,
+        dto.amount,
+        String::from(status),
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(payment)
+}
+
+/// Retrieves how the tenant's invoice numbers are built.
+pub async fn get_numbering(pool: &PgPool, tenant_id: Uuid) -> Result<InvoiceNumbering, AppError> {
+    info!(
+        "Service: Getting invoice numbering for tenant ID: {}",
+        tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    let numbering = load_numbering(&mut db_tx, tenant_id).await?;
+    db_tx.commit().await?;
+
+    Ok(numbering)
+}
+
+/// Changes the prefix, padding or next number of the tenant's invoice numbers. Moving the
+/// next number back is refused while the prefix stays the same, as it would reuse numbers.
+pub async fn update_numbering(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: UpdateInvoiceNumberingDto,
+) -> Result<InvoiceNumbering, AppError> {
+    info!(
+        "Service: Updating invoice numbering for tenant ID: {}",
+        tenant_id
+    );
+
+    if dto.prefix.is_none() && dto.next_number.is_none() && dto.padding.is_none() {
+        return Err(AppError::Validation(
+            "No fields provided for update".to_string(),
+        ));
+    }
+
+    let mut db_tx = pool.begin().await?;
+    let current = load_numbering(&mut db_tx, tenant_id).await?;
+    let same_prefix = dto
+        .prefix
+        .as_ref()
+        .is_none_or(|prefix| *prefix == current.prefix);
+    if let Some(next_number) = dto.next_number.filter(|_| same_prefix) {
+        if next_number < current.next_number {
+            return Err(AppError::Validation(format!(
+                "Invoice numbers up to {} may already be in use; choose {} or higher, or change the prefix",
+                current.next_number - 1,
+                current.next_number
+            )));
+        }
+    }
+
+    let numbering = query_as!(
+        InvoiceNumbering,
+        r#"
+        UPDATE invoice_sequences
+        SET prefix = COALESCE($2, prefix),
+            next_number = COALESCE($3, next_number),
+            padding = COALESCE($4, padding),
+            updated_at = NOW(),
+            updated_by = $5
+        WHERE tenant_id = $1
+        RETURNING tenant_id, prefix, next_number, padding, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.prefix,
+        dto.next_number,
+        dto.padding,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(numbering)
+}
+
+/// Reads the tenant's numbering, creating it with its defaults first, and locks it.
+async fn load_numbering(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+) -> Result<InvoiceNumbering, AppError> {
+    sqlx::query!(
+        "INSERT INTO invoice_sequences (tenant_id) VALUES ($1) ON CONFLICT (tenant_id) DO NOTHING",
+        tenant_id
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    let numbering = query_as!(
+        InvoiceNumbering,
+        r#"
+        SELECT tenant_id, prefix, next_number, padding, updated_at, updated_by
+        FROM invoice_sequences
+        WHERE tenant_id = $1
+        FOR UPDATE
+        "#,
+        tenant_id
+    )
+    .fetch_one(&mut **db_tx)
+    .await?;
+
+    Ok(numbering)
+}
+
+/// The transaction an invoice posts, in the invoice's currency.
+fn posting(
+    invoice: &Invoice,
+    transaction_date: NaiveDate,
+    description: String,
+    r#type: TransactionType,
+    amount: Decimal,
+    journal_entries: Vec<CreateJournalEntryDto>,
+) -> CreateTransactionDto {
+    CreateTransactionDto {
+        transaction_date,
+        description,
+        r#type,
+        category_id: None,
+        payee_id: None,
+        tags: None,
+        amount,
+        currency_code: invoice.currency_code.clone(),
+        is_reconciled: None,
+        reconciliation_date: None,
+        notes: None,
+        source_document_url: None,
+        status: Some(TransactionStatus::Posted),
+        journal_entries,
+        payment_account_id: None,
+        splits: Vec::new(),
+        metadata: None,
+        attribution: None,
+    }
+}
+
+/// Checks the lines' quantities and prices and returns each line's amount.
+fn price_lines(lines: &[InvoiceLineDto]) -> Result<Vec<Decimal>, AppError> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            if line.quantity <= Decimal::ZERO {
+                return Err(AppError::Validation(format!(
+                    "lines[{}].quantity must be greater than zero",
+                    index
+                )));
+            }
+            if line.unit_price < Decimal::ZERO {
+                return Err(AppError::Validation(format!(
+                    "lines[{}].unit_price cannot be negative",
+                    index
+                )));
+            }
+            if line.quantity.round_dp(PRICE_SCALE) != line.quantity
+                || line.unit_price.round_dp(PRICE_SCALE) != line.unit_price
+            {
+                return Err(AppError::Validation(format!(
+                    "lines[{}] quantity and unit_price must have at most four decimals",
+                    index
+                )));
+            }
+            Ok((line.quantity * line.unit_price).round_dp(AMOUNT_SCALE))
+        })
+        .collect()
+}
+
+/// Writes the lines of an invoice, numbered in the order given.
+async fn insert_lines(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+    lines: &[InvoiceLineDto],
+    amounts: &[Decimal],
+) -> Result<(), AppError> {
+    let positions: Vec<i32> = (1..=lines.len() as i32).collect();
+    let descriptions: Vec<String> = lines.iter().map(|line| line.description.clone()).collect();
+    let quantities: Vec<Decimal> = lines.iter().map(|line| line.quantity).collect();
+    let unit_prices: Vec<Decimal> = lines.iter().map(|line| line.unit_price).collect();
+    let revenue_account_ids: Vec<Uuid> = lines.iter().map(|line| line.revenue_account_id).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO invoice_lines (
+            invoice_id, tenant_id, position, description, quantity, unit_price, amount, revenue_account_id
+        )
+        SELECT $1, $2, l.position, l.description, l.quantity, l.unit_price, l.amount, l.revenue_account_id
+        FROM UNNEST($3::int4[], $4::text[], $5::numeric[], $6::numeric[], $7::numeric[], $8::uuid[])
+            AS l(position, description, quantity, unit_price, amount, revenue_account_id)
+        "#,
+        invoice_id,
+        tenant_id,
+        &positions,
+        &descriptions,
+        &quantities,
+        &unit_prices,
+        amounts,
+        &revenue_account_ids
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    Ok(())
+}
+
+async fn load_lines<'e, E: PgExecutor<'e>>(
+    executor: E,
+    invoice_id: Uuid,
+) -> Result<Vec<InvoiceLine>, AppError> {
+    let lines = query_as!(
+        InvoiceLine,
+        r#"
+        SELECT id, invoice_id, tenant_id, position, description, quantity, unit_price,
+               amount, revenue_account_id
+        FROM invoice_lines
+        WHERE invoice_id = $1
+        ORDER BY position
+        "#,
+        invoice_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(lines)
+}
+
+/// Reads an invoice and locks it until the end of the database transaction.
+async fn lock_invoice(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    invoice_id: Uuid,
+) -> Result<Invoice, AppError> {
+    query_as!(
+        Invoice,
+        r#"
+        SELECT id, tenant_id, customer_id, invoice_number, status, issue_date, due_date,
+               currency_code, receivable_account_id, total, amount_paid, issue_transaction_id,
+               notes, issued_at, issued_by, created_at, created_by, updated_at, updated_by
+        FROM invoices
+        WHERE id = $1 AND tenant_id = $2
+        FOR UPDATE
+        "#,
+        invoice_id,
+        tenant_id
+    )
+    .fetch_optional(&mut **db_tx)
+    .await?
+    .ok_or_else(|| not_found(invoice_id, tenant_id))
+}
+
+fn require_draft(invoice: &Invoice) -> Result<(), AppError> {
+    if invoice.status != String::from(InvoiceStatus::Draft) {
+        return Err(AppError::Validation(format!(
+            "Invoice {} is {} and can no longer be changed",
+            invoice.invoice_number.as_deref().unwrap_or_default(),
+            invoice.status
+        )));
+    }
+    Ok(())
+}
+
+async fn check_customer<'e, E: PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    customer_id: Uuid,
+) -> Result<(), AppError> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM customers WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE) as "exists!""#,
+        customer_id,
+        tenant_id
+    )
+    .fetch_one(executor)
+    .await?;
+    if !exists {
+        return Err(AppError::Validation(format!(
+            "Customer ID {} is invalid or inactive for tenant {}",
+            customer_id, tenant_id
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that each account is the tenant's, active and not archived.
+pub async fn check_accounts<'e, E: PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    account_ids: impl Iterator<Item = Uuid>,
+) -> Result<(), AppError> {
+    let account_ids: Vec<Uuid> = account_ids.collect::<HashSet<_>>().into_iter().collect();
+    let valid: HashSet<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM accounts
+        WHERE tenant_id = $1 AND id = ANY($2) AND is_active = TRUE AND archived_at IS NULL
+        "#,
+        tenant_id,
+        &account_ids
+    )
+    .fetch_all(executor)
+    .await?
+    .into_iter()
+    .collect();
+    if let Some(invalid) = account_ids.iter().find(|id| !valid.contains(id)) {
+        return Err(AppError::Validation(format!(
+            "Account ID {} is invalid, inactive or archived for tenant {}",
+            invalid, tenant_id
+        )));
+    }
+    Ok(())
+}
+
+fn not_found(invoice_id: Uuid, tenant_id: Uuid) -> AppError {
+    AppError::NotFound(format!(
+        "Invoice with ID {} not found for tenant {}",
+        invoice_id, tenant_id
+    ))
+}
@@ -6,9 +6,12 @@ use rust_decimal::Decimal;
 use crate::{
     error::AppError,
     models::{
+        dimension::DimensionFilter,
         journal_entry::{JournalEntry, JournalEntryType},
         dto::journal_entry_dto::{CreateJournalEntryDto, UpdateJournalEntryDto},
     },
+    services::{currency_conversion, dimension, privacy, transaction},
+    utils::update_builder::UpdateBuilder,
 };
 
 /// Retrieves a list of journal entries for a specific transaction.
@@ -40,7 +43,7 @@ pub async fn list_journal_entries_for_transaction(
         SELECT
             id, transaction_id, account_id, entry_type as "entry_type!: JournalEntryType",
             amount, currency_code, exchange_rate, converted_amount, memo,
-            created_at, created_by, updated_at, updated_by
+            project_id, class_id, location_id, created_at, created_by, updated_at, updated_by
         FROM journal_entries
         WHERE transaction_id = $1
         ORDER BY created_at
@@ -67,7 +70,7 @@ pub async fn get_journal_entry_by_id(
         SELECT
             je.id, je.transaction_id, je.account_id, je.entry_type as "entry_type!: JournalEntryType",
             je.amount, je.currency_code, je.exchange_rate, je.converted_amount, je.memo,
-            je.created_at, je.created_by, je.updated_at, je.updated_by
+            je.project_id, je.class_id, je.location_id, je.created_at, je.created_by, je.updated_at, je.updated_by
         FROM journal_entries je
         JOIN transactions t ON je.transaction_id = t.id
         WHERE je.id = $1 AND t.tenant_id = $2
@@ -94,23 +97,21 @@ pub async fn create_journal_entry(
     info!("Service: Creating new journal entry for transaction ID: {}", transaction_id);
 
     // Verify transaction exists and belongs to tenant
-    let transaction_exists = sqlx::query!(
-        "SELECT EXISTS(SELECT 1 FROM transactions WHERE id = $1 AND tenant_id = $2)",
+    let transaction_date = sqlx::query_scalar!(
+        "SELECT transaction_date FROM transactions WHERE id = $1 AND tenant_id = $2",
         transaction_id,
         tenant_id
     )
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await?
-    .exists
-    .unwrap_or(false);
+    .ok_or_else(|| AppError::NotFound(format!("Transaction with ID {} not found for tenant {}", transaction_id, tenant_id)))?;
 
-    if !transaction_exists {
-        return Err(AppError::NotFound(format!("Transaction with ID {} not found for tenant {}", transaction_id, tenant_id)));
-    }
+    // Entries can only be added while the transaction is a draft
+    transaction::ensure_draft(pool, tenant_id, transaction_id).await?;
 
     // Verify account exists and belongs to tenant
     let account_exists = sqlx::query!(
-        "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE)",
+        "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE AND archived_at IS NULL)",
         dto.account_id, tenant_id
     )
     .fetch_one(pool)
@@ -119,31 +120,55 @@ pub async fn create_journal_entry(
     .unwrap_or(false);
 
     if !account_exists {
-        return Err(AppError::ValidationError(format!("Account ID {} is invalid or inactive for tenant {}", dto.account_id, tenant_id)));
+        return Err(AppError::Validation(format!("Account ID {} is invalid, inactive or archived for tenant {}", dto.account_id, tenant_id)));
     }
+    let dimensions = DimensionFilter {
+        project_id: dto.project_id,
+        class_id: dto.class_id,
+        location_id: dto.location_id,
+    };
+    dimension::check_assignable(pool, tenant_id, [dimensions]).await?;
+
+    // With privacy mode on, the memo is stored sealed
+    let text_key = privacy::sealing_key(pool, tenant_id).await?;
+    let memo = privacy::seal_opt(text_key.as_ref(), dto.memo)?;
+
+    let (exchange_rate, converted_amount) = currency_conversion::base_currency_amounts(
+        pool,
+        tenant_id,
+        dto.amount,
+        &dto.currency_code,
+        transaction_date,
+        dto.exchange_rate,
+        dto.converted_amount,
+    )
+    .await?;
 
     let new_entry = query_as!(
         JournalEntry,
         r#"
         INSERT INTO journal_entries (
             transaction_id, account_id, entry_type, amount, currency_code,
-            exchange_rate, converted_amount, memo, created_by, updated_by
+            exchange_rate, converted_amount, memo, project_id, class_id, location_id, created_by, updated_by
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $10, $11, $12, $9, $9)
         RETURNING
             id, transaction_id, account_id, entry_type as "entry_type!: JournalEntryType",
             amount, currency_code, exchange_rate, converted_amount, memo,
-            created_at, created_by, updated_at, updated_by
+            project_id, class_id, location_id, created_at, created_by, updated_at, updated_by
         "#,
         transaction_id,
         dto.account_id,
         dto.entry_type as JournalEntryType,
         dto.amount,
         dto.currency_code,
-        dto.exchange_rate,
-        dto.converted_amount,
-        dto.memo,
+        exchange_rate,
+        converted_amount,
+        memo,
         created_by_user_id,
+        dimensions.project_id,
+        dimensions.class_id,
+        dimensions.location_id,
     )
     .fetch_one(pool)
     .await?;
@@ -151,10 +176,9 @@ pub async fn create_journal_entry(
     Ok(new_entry)
 }
 
-/// Updates an existing journal entry.
-/// Note: Changing core financial aspects of a journal entry for a posted transaction
-/// might require creating an adjusting entry rather than directly modifying it.
-/// This service allows modification of memo, exchange_rate, converted_amount.
+/// Updates an existing journal entry of a draft transaction.
+/// Posted transactions are corrected by reversal instead.
+/// This service allows modification of memo, exchange_rate, converted_amount and dimensions.
 pub async fn update_journal_entry(
     pool: &PgPool,
     tenant_id: Uuid, // Used to verify transaction ownership
@@ -164,64 +188,53 @@ pub async fn update_journal_entry(
 ) -> Result<JournalEntry, AppError> {
     info!("Service: Updating journal entry with ID: {}", journal_entry_id);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
+    // Entries are immutable once their transaction leaves draft
+    let entry = get_journal_entry_by_id(pool, tenant_id, journal_entry_id).await?;
+    transaction::ensure_draft(pool, tenant_id, entry.transaction_id).await?;
 
     // Only allow updating certain fields (e.g., memo, exchange_rate, converted_amount)
     // Changing account_id, entry_type, amount would typically require new adjusting entries
     // or a full transaction reversal/re-creation in a robust accounting system.
-    if let Some(memo) = dto.memo {
-        update_cols.push(format!("memo = ${}", param_idx));
-        update_values.push(Box::new(memo));
-        param_idx += 1;
-    }
-    if let Some(exchange_rate) = dto.exchange_rate {
-        update_cols.push(format!("exchange_rate = ${}", param_idx));
-        update_values.push(Box::new(exchange_rate));
-        param_idx += 1;
-    }
-    if let Some(converted_amount) = dto.converted_amount {
-        update_cols.push(format!("converted_amount = ${}", param_idx));
-        update_values.push(Box::new(converted_amount));
-        param_idx += 1;
-    }
+    let memo = match dto.memo {
+        Some(memo) => {
+            let text_key = privacy::sealing_key(pool, tenant_id).await?;
+            Some(privacy::seal(text_key.as_ref(), memo)?)
+        }
+        None => None,
+    };
+    let dimensions = DimensionFilter {
+        project_id: dto.project_id.value().copied(),
+        class_id: dto.class_id.value().copied(),
+        location_id: dto.location_id.value().copied(),
+    };
+    dimension::check_assignable(pool, tenant_id, [dimensions]).await?;
 
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
-
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
+    let mut update = UpdateBuilder::new("journal_entries je");
+    update
+        .set("memo", memo)
+        .set("exchange_rate", dto.exchange_rate)
+        .set("converted_amount", dto.converted_amount)
+        .patch("project_id", dto.project_id)
+        .patch("class_id", dto.class_id)
+        .patch("location_id", dto.location_id);
+    if update.is_empty() {
+        return Err(AppError::Validation("No fields provided for update".to_string()));
     }
 
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" FROM transactions t WHERE je.id = ").push_bind(journal_entry_id);
+    query.push(" AND je.transaction_id = t.id AND t.tenant_id = ").push_bind(tenant_id);
+    query.push(
         r#"
-        UPDATE journal_entries je
-        SET {}
-        FROM transactions t
-        WHERE je.id = ${} AND je.transaction_id = t.id AND t.tenant_id = ${}
         RETURNING
-            je.id, je.transaction_id, je.account_id, je.entry_type as "entry_type!: JournalEntryType",
+            je.id, je.transaction_id, je.account_id, je.entry_type,
             je.amount, je.currency_code, je.exchange_rate, je.converted_amount, je.memo,
-            je.created_at, je.created_by, je.updated_at, je.updated_by
+            je.project_id, je.class_id, je.location_id, je.created_at, je.created_by, je.updated_at, je.updated_by
         "#,
-        update_clause, param_idx, param_idx + 1 // journal_entry_id and tenant_id will be the last parameters
     );
 
-    let mut query = sqlx::query_as::<_, JournalEntry>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
-    }
-    // Bind journal_entry_id and tenant_id last
-    query = query.bind(journal_entry_id);
-    query = query.bind(tenant_id);
-
     let updated_entry = query
+        .build_query_as::<JournalEntry>()
         .fetch_optional(pool)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Journal entry with ID {} not found or not owned by tenant {}", journal_entry_id, tenant_id)))?;
@@ -229,10 +242,9 @@ pub async fn update_journal_entry(
     Ok(updated_entry)
 }
 
-/// Deletes a journal entry.
-/// Note: Deleting a journal entry directly can break the double-entry balance of its parent transaction.
-/// This operation should be used with extreme caution, typically only for draft transactions,
-/// or as part of a larger transaction modification/reversal logic.
+/// Deletes a journal entry of a draft transaction.
+/// Note: Deleting a journal entry directly can break the double-entry balance of its parent transaction;
+/// the balance is checked again when the transaction is posted.
 pub async fn delete_journal_entry(
     pool: &PgPool,
     tenant_id: Uuid, // Used to verify transaction ownership
@@ -240,6 +252,9 @@ pub async fn delete_journal_entry(
 ) -> Result<(), AppError> {
     info!("Service: Deleting journal entry with ID: {}", journal_entry_id);
 
+    let entry = get_journal_entry_by_id(pool, tenant_id, journal_entry_id).await?;
+    transaction::ensure_draft(pool, tenant_id, entry.transaction_id).await?;
+
     let affected_rows = sqlx::query!(
         r#"
         DELETE FROM journal_entries je
@@ -0,0 +1,147 @@
+//! Live updates pushed to connected clients over `/ws`.
+//!
+//! Updates are tenant-scoped and grouped by topic. Transaction changes come from the domain
+//! event bus and import jobs publish their own progress. Like domain events, updates are
+//! best effort and not stored: a client that reconnects or falls behind refetches over REST.
+
+use std::sync::OnceLock;
+
+use serde_json::json;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        import_job::ImportJobStatus,
+        live_update::{LiveTopic, LiveUpdate},
+    },
+    services::domain_event::{self, DomainEvent},
+};
+
+/// Updates kept for connections that have not caught up yet.
+const CHANNEL_CAPACITY: usize = 1024;
+
+static HUB: OnceLock<Sender<LiveUpdate>> = OnceLock::new();
+
+fn hub() -> &'static Sender<LiveUpdate> {
+    HUB.get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+}
+
+/// Sends an update to every connection; each keeps only its tenant's subscribed topics.
+pub fn publish(update: LiveUpdate) {
+    // An error only means no client is connected
+    let _ = hub().send(update);
+}
+
+/// Receives every update published from now on.
+pub fn subscribe() -> Receiver<LiveUpdate> {
+    hub().subscribe()
+}
+
+/// Forwards transaction changes from the domain event bus. Call once at startup.
+pub fn spawn_domain_event_forwarder() {
+    domain_event::spawn_subscriber("live_update", |event| async move {
+        if let Some(update) = transaction_update(&event) {
+            publish(update);
+        }
+    });
+}
+
+/// Publishes the progress of an import job to the tenant's `imports` subscribers.
+pub fn publish_import_progress(
+    tenant_id: Uuid,
+    job_id: Uuid,
+    status: ImportJobStatus,
+    processed_rows: i32,
+    total_rows: Option<i32>,
+    failed_rows: i32,
+) {
+    let event = match status {
+        ImportJobStatus::Completed | ImportJobStatus::Failed => "import.finished",
+        ImportJobStatus::Pending | ImportJobStatus::Running => "import.progress",
+    };
+    publish(LiveUpdate {
+        tenant_id,
+        topic: LiveTopic::Imports,
+        event,
+        data: json!({
+            "job_id": job_id,
+            "status": status,
+            "processed_rows": processed_rows,
+            "total_rows": total_rows,
+            "failed_rows": failed_rows,
+        }),
+    });
+}
+
+fn transaction_update(event: &DomainEvent) -> Option<LiveUpdate> {
+    let (event_name, data) = match event {
+        DomainEvent::TransactionPosted {
+            user_id,
+            transaction_id,
+            amount,
+            transaction_date,
+            ..
+        } => (
+            "transaction.posted",
+            json!({
+                "transaction_id": transaction_id,
+                "amount": amount,
+                "transaction_date": transaction_date,
+                "user_id": user_id,
+            }),
+        ),
+        DomainEvent::TransactionVoided {
+            user_id,
+            transaction_id,
+            reason,
+            ..
+        } => (
+            "transaction.voided",
+            json!({
+                "transaction_id": transaction_id,
+                "reason": reason,
+                "user_id": user_id,
+            }),
+        ),
+        DomainEvent::TransactionReversed {
+            user_id,
+            transaction_id,
+            reversal_id,
+            reversal_date,
+            ..
+        } => (
+            "transaction.reversed",
+            json!({
+                "transaction_id": transaction_id,
+                "reversal_id": reversal_id,
+                "reversal_date": reversal_date,
+                "user_id": user_id,
+            }),
+        ),
+        // The revaluation's adjusting transaction; its amount is split into gains and losses
+        DomainEvent::FxRevaluationPosted {
+            user_id,
+            transaction_id,
+            as_of,
+            ..
+        } => (
+            "transaction.posted",
+            json!({
+                "transaction_id": transaction_id,
+                "transaction_date": as_of,
+                "user_id": user_id,
+            }),
+        ),
+        DomainEvent::AccountDeactivated { .. }
+        | DomainEvent::AccountRestored { .. }
+        | DomainEvent::FiscalPeriodClosed { .. }
+        | DomainEvent::FiscalPeriodReopened { .. } => return None,
+    };
+    Some(LiveUpdate {
+        tenant_id: event.tenant_id(),
+        topic: LiveTopic::Transactions,
+        event: event_name,
+        data,
+    })
+}
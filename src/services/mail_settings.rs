@@ -0,0 +1,196 @@
+//! Per-tenant outgoing mail server. Tenants without their own settings send through the
+//! deployment's server (see `mailer`). Managing the settings requires `mail.manage`.
+
+use lettre::message::Mailbox;
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{dto::mail_settings_dto::UpsertMailSettingsDto, mail_settings::TenantMailSettings},
+    services::{
+        mailer::{self, SmtpConfig, DEFAULT_SMTP_PORT},
+        permission::{self, MAIL_MANAGE},
+    },
+    utils::crypto::{decrypt_secret, encrypt_secret},
+};
+
+/// Retrieves the tenant's mail settings (without the password).
+pub async fn get_mail_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<TenantMailSettings, AppError> {
+    info!(
+        "Service: Getting mail settings for tenant ID: {}",
+        tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, MAIL_MANAGE).await?;
+
+    find_settings(pool, tenant_id).await?.ok_or_else(|| {
+        AppError::NotFound(format!(
+            "No mail settings configured for tenant {}",
+            tenant_id
+        ))
+    })
+}
+
+/// Creates or replaces the tenant's mail settings. Leaving out the password keeps the
+/// stored one.
+pub async fn upsert_mail_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: UpsertMailSettingsDto,
+) -> Result<TenantMailSettings, AppError> {
+    info!(
+        "Service: Configuring mail settings for tenant ID: {}",
+        tenant_id
+    );
+
+    dto.validate()?;
+    dto.from_address
+        .parse::<Mailbox>()
+        .map_err(|e| AppError::Validation(format!("Invalid from_address: {}", e)))?;
+
+    permission::require_permission(pool, tenant_id, user_id, MAIL_MANAGE).await?;
+
+    let smtp_password = dto
+        .smtp_password
+        .as_deref()
+        .map(encrypt_secret)
+        .transpose()?;
+    let settings = query_as!(
+        TenantMailSettings,
+        r#"
+        INSERT INTO tenant_mail_settings (
+            tenant_id, smtp_host, smtp_port, smtp_username, smtp_password, from_address,
+            created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+        ON CONFLICT (tenant_id) DO UPDATE
+        SET
+            smtp_host = EXCLUDED.smtp_host,
+            smtp_port = EXCLUDED.smtp_port,
+            smtp_username = EXCLUDED.smtp_username,
+            smtp_password = COALESCE(EXCLUDED.smtp_password, tenant_mail_settings.smtp_password),
+            from_address = EXCLUDED.from_address,
+            updated_at = NOW(),
+            updated_by = EXCLUDED.updated_by
+        RETURNING
+            tenant_id, smtp_host, smtp_port, smtp_username, smtp_password, from_address,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.smtp_host,
+        dto.smtp_port.unwrap_or(i32::from(DEFAULT_SMTP_PORT)),
+        dto.smtp_username,
+        smtp_password,
+        dto.from_address,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(settings)
+}
+
+/// Removes the tenant's mail settings; its email goes through the deployment's server again.
+pub async fn delete_mail_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting mail settings for tenant ID: {}",
+        tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, MAIL_MANAGE).await?;
+
+    let rows_affected = sqlx::query!(
+        "DELETE FROM tenant_mail_settings WHERE tenant_id = $1",
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if rows_affected == 0 {
+        return Err(AppError::NotFound(format!(
+            "No mail settings configured for tenant {}",
+            tenant_id
+        )));
+    }
+    Ok(())
+}
+
+/// Sends a test email to the current user through the tenant's mail server.
+pub async fn send_test_email(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    info!("Service: Sending test email for tenant ID: {}", tenant_id);
+    permission::require_permission(pool, tenant_id, user_id, MAIL_MANAGE).await?;
+
+    let email = sqlx::query_scalar!("SELECT email FROM users WHERE id = $1", user_id)
+        .fetch_one(pool)
+        .await?;
+    let config = smtp_config_for_tenant(pool, tenant_id).await?;
+    mailer::send_email_with(
+        config.as_ref(),
+        &[email],
+        "Forge mail settings test",
+        "Your outgoing mail settings work.",
+        Vec::new(),
+    )
+    .await
+}
+
+/// The SMTP server to send the tenant's email through: its own settings if configured,
+/// otherwise the deployment's.
+pub async fn smtp_config_for_tenant(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Option<SmtpConfig>, AppError> {
+    let Some(settings) = find_settings(pool, tenant_id).await? else {
+        return Ok(SmtpConfig::deployment());
+    };
+
+    let port = u16::try_from(settings.smtp_port).map_err(|_| {
+        AppError::InternalServerError(format!("Invalid SMTP port {}", settings.smtp_port))
+    })?;
+    Ok(Some(SmtpConfig {
+        host: settings.smtp_host,
+        port,
+        username: settings.smtp_username,
+        password: settings
+            .smtp_password
+            .as_deref()
+            .map(decrypt_secret)
+            .transpose()?,
+        from: settings.from_address,
+    }))
+}
+
+async fn find_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Option<TenantMailSettings>, AppError> {
+    let settings = query_as!(
+        TenantMailSettings,
+        r#"
+        SELECT
+            tenant_id, smtp_host, smtp_port, smtp_username, smtp_password, from_address,
+            created_at, created_by, updated_at, updated_by
+        FROM tenant_mail_settings
+        WHERE tenant_id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(settings)
+}
@@ -0,0 +1,157 @@
+//! Outgoing email over SMTP.
+//!
+//! The deployment's server is configured with `SMTP_HOST`, `SMTP_PORT`, `SMTP_USERNAME`,
+//! `SMTP_PASSWORD` and `MAIL_FROM`; tenants can override it with their own settings (see
+//! `mail_settings`). Without any server configured emails are only logged, which keeps
+//! local development working.
+
+use lettre::{
+    message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use tracing::info;
+
+use crate::error::AppError;
+
+pub const DEFAULT_SMTP_PORT: u16 = 587;
+
+/// SMTP server settings: the deployment's own (from the configuration) or a tenant's.
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String, // e.g. "Forge <no-reply@example.com>"
+}
+
+impl SmtpConfig {
+    /// The deployment's SMTP server, if `SMTP_HOST` is set.
+    pub fn deployment() -> Option<Self> {
+        let mail = &crate::config::get().mail;
+        Some(SmtpConfig {
+            host: mail.smtp_host.clone()?,
+            port: mail.smtp_port,
+            username: mail.smtp_username.clone(),
+            password: mail.smtp_password.clone(),
+            from: mail.from.clone(),
+        })
+    }
+}
+
+/// A file attached to an email.
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub file_name: String,
+    pub content_type: String, // e.g. "text/csv"
+    pub content: Vec<u8>,
+}
+
+/// Sends a plain-text email through the deployment's SMTP server.
+pub async fn send_email(to: &str, subject: &str, body: &str) -> Result<(), AppError> {
+    send_email_with(
+        SmtpConfig::deployment().as_ref(),
+        &[to.to_string()],
+        subject,
+        body,
+        Vec::new(),
+    )
+    .await
+}
+
+/// Sends a plain-text email with attachments through the given SMTP server
+/// (`None` = not configured, only logged).
+pub async fn send_email_with(
+    config: Option<&SmtpConfig>,
+    to: &[String],
+    subject: &str,
+    body: &str,
+    attachments: Vec<EmailAttachment>,
+) -> Result<(), AppError> {
+    let Some(config) = config else {
+        info!(
+            "Mailer: no SMTP server configured, not sending '{}' to {}",
+            subject,
+            to.join(", ")
+        );
+        return Ok(());
+    };
+
+    let from: Mailbox = config
+        .from
+        .parse()
+        .map_err(|e| AppError::InternalServerError(format!("Invalid sender address: {}", e)))?;
+    let mut builder = Message::builder().from(from).subject(subject);
+    for recipient in to {
+        let mailbox: Mailbox = recipient.parse().map_err(|e| {
+            AppError::Validation(format!("Invalid recipient '{}': {}", recipient, e))
+        })?;
+        builder = builder.to(mailbox);
+    }
+
+    let message = if attachments.is_empty() {
+        builder
+            .header(ContentType::TEXT_PLAIN)
+            .body(body.to_string())
+    } else {
+        let mut parts = MultiPart::mixed().singlepart(SinglePart::plain(body.to_string()));
+        for attachment in attachments {
+            let content_type = ContentType::parse(&attachment.content_type).map_err(|e| {
+                AppError::InternalServerError(format!("Invalid attachment content type: {}", e))
+            })?;
+            parts = parts.singlepart(
+                Attachment::new(attachment.file_name).body(attachment.content, content_type),
+            );
+        }
+        builder.multipart(parts)
+    }
+    .map_err(|e| AppError::InternalServerError(format!("Failed to build email: {}", e)))?;
+
+    transport(config)?
+        .send(message)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to send email: {}", e)))?;
+
+    Ok(())
+}
+
+/// Whether an SMTP server is configured.
+pub fn is_configured() -> bool {
+    crate::config::get().mail.smtp_host.is_some()
+}
+
+/// Outcome of an SMTP connection test.
+pub enum SmtpCheck {
+    Ok,
+    /// The server answered but rejected our credentials (permanent SMTP error).
+    Rejected(String),
+    Unreachable(String),
+}
+
+/// Connects to the deployment's SMTP server and authenticates without sending anything.
+pub async fn test_connection() -> SmtpCheck {
+    let Some(config) = SmtpConfig::deployment() else {
+        return SmtpCheck::Unreachable("SMTP_HOST is not set".to_string());
+    };
+    let transport = match transport(&config) {
+        Ok(transport) => transport,
+        Err(e) => return SmtpCheck::Unreachable(e.to_string()),
+    };
+    match transport.test_connection().await {
+        Ok(true) => SmtpCheck::Ok,
+        Ok(false) => SmtpCheck::Unreachable("SMTP server did not respond".to_string()),
+        Err(e) if e.is_permanent() => SmtpCheck::Rejected(e.to_string()),
+        Err(e) => SmtpCheck::Unreachable(e.to_string()),
+    }
+}
+
+fn transport(config: &SmtpConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>, AppError> {
+    let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+        .map_err(|e| AppError::InternalServerError(format!("Invalid SMTP host: {}", e)))?
+        .port(config.port);
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    Ok(builder.build())
+}
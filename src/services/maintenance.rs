@@ -0,0 +1,428 @@
+//! Database maintenance run on a schedule (`MAINTENANCE_INTERVAL_SECS`, default daily).
+//!
+//! | Task                 | What it does                                                   |
+//! |----------------------|----------------------------------------------------------------|
+//! | `analyze`            | `ANALYZE`s tables (and partitions) with stale statistics; lists |
+//! |                      | tables with many dead rows as vacuum hints                     |
+//! | `refresh_aggregates` | Refreshes materialized views, concurrently where possible      |
+//! | `prune_sessions`     | Deletes sessions ended more than `SESSION_RETENTION_DAYS` ago  |
+//! | `apply_retention`    | Deletes rows past their table's retention, see [`RETENTION_POLICIES`] |
+//! | `rebuild_balance_snapshots` | Recomputes monthly account balance snapshots from the journal |
+//!
+//! `MAINTENANCE_TASKS` picks which tasks run (all by default). Every task run is recorded in
+//! `maintenance_runs`, listed by `GET /healthz/maintenance`; a failing task is recorded and
+//! does not stop the others.
+
+use std::{str::FromStr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use sqlx::PgPool;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    config,
+    error::AppError,
+    models::maintenance::{MaintenanceRun, MaintenanceRunStatus},
+    services::balance_snapshot,
+};
+
+/// Tables with fewer changed rows than this are never analyzed, however small they are.
+const MIN_CHANGED_ROWS: i64 = 500;
+/// Share of dead rows above which a table is recorded as needing a `VACUUM`.
+const DEAD_ROW_HINT_RATIO: f64 = 0.2;
+/// Tables with fewer dead rows than this get no vacuum hint.
+const MIN_DEAD_ROWS: i64 = 10_000;
+/// Pause between retention batches, so other writers get the table in between.
+const RETENTION_BATCH_PAUSE: Duration = Duration::from_millis(100);
+
+/// A table whose rows are deleted once `timestamp_column` is older than the retention.
+/// Each table needs an `id` column and an index on `(timestamp_column, id)`.
+struct RetentionPolicy {
+    table: &'static str,
+    timestamp_column: &'static str,
+    retention_days: fn(&config::SchedulerConfig) -> u32,
+}
+
+/// Tables that only grow unless pruned. Audit events are deliberately absent: they are
+/// kept for compliance.
+const RETENTION_POLICIES: &[RetentionPolicy] = &[
+    RetentionPolicy {
+        table: "notifications",
+        timestamp_column: "created_at",
+        retention_days: |schedulers| schedulers.notification_retention_days,
+    },
+    RetentionPolicy {
+        table: "maintenance_runs",
+        timestamp_column: "started_at",
+        retention_days: |schedulers| schedulers.maintenance_run_retention_days,
+    },
+    RetentionPolicy {
+        table: "idempotency_keys",
+        timestamp_column: "created_at",
+        retention_days: |schedulers| schedulers.idempotency_key_retention_days,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceTask {
+    Analyze,
+    RefreshAggregates,
+    PruneSessions,
+    ApplyRetention,
+    RebuildBalanceSnapshots,
+}
+
+impl MaintenanceTask {
+    pub const ALL: [MaintenanceTask; 5] = [
+        MaintenanceTask::Analyze,
+        MaintenanceTask::RefreshAggregates,
+        MaintenanceTask::PruneSessions,
+        MaintenanceTask::ApplyRetention,
+        MaintenanceTask::RebuildBalanceSnapshots,
+    ];
+
+    /// The value stored in `maintenance_runs.task`.
+    fn as_db(self) -> &'static str {
+        match self {
+            MaintenanceTask::Analyze => "ANALYZE",
+            MaintenanceTask::RefreshAggregates => "REFRESH_AGGREGATES",
+            MaintenanceTask::PruneSessions => "PRUNE_SESSIONS",
+            MaintenanceTask::ApplyRetention => "APPLY_RETENTION",
+            MaintenanceTask::RebuildBalanceSnapshots => "REBUILD_BALANCE_SNAPSHOTS",
+        }
+    }
+}
+
+impl FromStr for MaintenanceTask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "analyze" => Ok(MaintenanceTask::Analyze),
+            "refresh_aggregates" => Ok(MaintenanceTask::RefreshAggregates),
+            "prune_sessions" => Ok(MaintenanceTask::PruneSessions),
+            "apply_retention" => Ok(MaintenanceTask::ApplyRetention),
+            "rebuild_balance_snapshots" => Ok(MaintenanceTask::RebuildBalanceSnapshots),
+            _ => Err(
+                "expected 'analyze', 'refresh_aggregates', 'prune_sessions', \
+                 'apply_retention' or 'rebuild_balance_snapshots'"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+/// What a task did: the count stored in `rows_affected` and the details.
+struct TaskOutcome {
+    rows_affected: i64,
+    detail: JsonValue,
+}
+
+/// Runs every configured maintenance task once, recording each run.
+pub async fn run_maintenance(pool: &PgPool) -> Result<(), AppError> {
+    for task in &config::get().schedulers.maintenance_tasks {
+        run_task(pool, *task).await?;
+    }
+
+    Ok(())
+}
+
+/// Lists the most recent task runs, newest first.
+pub async fn list_recent_runs(pool: &PgPool, limit: i64) -> Result<Vec<MaintenanceRun>, AppError> {
+    let runs = sqlx::query_as!(
+        MaintenanceRun,
+        r#"
+        SELECT
+            id, task, status as "status!: MaintenanceRunStatus", rows_affected, detail,
+            error_message, started_at, finished_at
+        FROM maintenance_runs
+        ORDER BY started_at DESC
+        LIMIT $1
+        "#,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(runs)
+}
+
+/// Runs one task between a RUNNING row and its outcome. Only failing to record the run is
+/// an error; a failing task is recorded as FAILED.
+async fn run_task(pool: &PgPool, task: MaintenanceTask) -> Result<(), AppError> {
+    info!("Service: Running maintenance task {}", task.as_db());
+    let run_id = sqlx::query_scalar!(
+        "INSERT INTO maintenance_runs (task) VALUES ($1) RETURNING id",
+        task.as_db()
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let outcome = match task {
+        MaintenanceTask::Analyze => analyze_stale_tables(pool).await,
+        MaintenanceTask::RefreshAggregates => refresh_materialized_views(pool).await,
+        MaintenanceTask::PruneSessions => prune_sessions(pool).await,
+        MaintenanceTask::ApplyRetention => apply_retention(pool).await,
+        MaintenanceTask::RebuildBalanceSnapshots => rebuild_balance_snapshots(pool).await,
+    };
+
+    match outcome {
+        Ok(outcome) => {
+            finish_run(
+                pool,
+                run_id,
+                MaintenanceRunStatus::Succeeded,
+                outcome.rows_affected,
+                Some(outcome.detail),
+                None,
+            )
+            .await
+        }
+        Err(e) => {
+            error!("Maintenance task {} failed: {}", task.as_db(), e);
+            finish_run(
+                pool,
+                run_id,
+                MaintenanceRunStatus::Failed,
+                0,
+                None,
+                Some(e.to_string()),
+            )
+            .await
+        }
+    }
+}
+
+async fn finish_run(
+    pool: &PgPool,
+    run_id: Uuid,
+    status: MaintenanceRunStatus,
+    rows_affected: i64,
+    detail: Option<JsonValue>,
+    error_message: Option<String>,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE maintenance_runs
+        SET status = $2, rows_affected = $3, detail = $4, error_message = $5, finished_at = NOW()
+        WHERE id = $1
+        "#,
+        run_id,
+        status as MaintenanceRunStatus,
+        rows_affected,
+        detail,
+        error_message
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// `ANALYZE`s tables with more than `MAINTENANCE_ANALYZE_RATIO` of their rows changed since
+/// the last analyze (by us or autovacuum), and reports tables that look bloated. Vacuuming
+/// itself is left to autovacuum; the hints point at tables it is not keeping up with.
+async fn analyze_stale_tables(pool: &PgPool) -> Result<TaskOutcome, AppError> {
+    let ratio = config::get().schedulers.maintenance_analyze_ratio;
+    let tables = sqlx::query!(
+        r#"
+        SELECT
+            relname::text as "table_name!",
+            COALESCE(n_live_tup, 0) as "live_rows!",
+            COALESCE(n_dead_tup, 0) as "dead_rows!",
+            COALESCE(n_mod_since_analyze, 0) as "changed_rows!"
+        FROM pg_stat_user_tables
+        WHERE schemaname = current_schema()
+        ORDER BY relname
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut analyzed = Vec::new();
+    let mut vacuum_hints = Vec::new();
+    for table in tables {
+        let live_rows = table.live_rows as f64;
+        if table.changed_rows >= MIN_CHANGED_ROWS && table.changed_rows as f64 > live_rows * ratio {
+            sqlx::query(&format!("ANALYZE {}", quote_identifier(&table.table_name)))
+                .execute(pool)
+                .await?;
+            analyzed.push(table.table_name.clone());
+        }
+
+        let dead_rows = table.dead_rows as f64;
+        if table.dead_rows >= MIN_DEAD_ROWS
+            && dead_rows > (live_rows + dead_rows) * DEAD_ROW_HINT_RATIO
+        {
+            vacuum_hints.push(json!({
+                "table": table.table_name,
+                "live_rows": table.live_rows,
+                "dead_rows": table.dead_rows,
+            }));
+        }
+    }
+
+    if !vacuum_hints.is_empty() {
+        info!(
+            "Service: {} tables have many dead rows and may need a VACUUM",
+            vacuum_hints.len()
+        );
+    }
+    Ok(TaskOutcome {
+        rows_affected: analyzed.len() as i64,
+        detail: json!({ "analyzed": analyzed, "vacuum_recommended": vacuum_hints }),
+    })
+}
+
+/// Refreshes every materialized view in the schema. Populated views with a unique index are
+/// refreshed concurrently, so readers are not blocked.
+async fn refresh_materialized_views(pool: &PgPool) -> Result<TaskOutcome, AppError> {
+    let views = sqlx::query!(
+        r#"
+        SELECT
+            m.matviewname::text as "view_name!",
+            m.ispopulated as "is_populated!",
+            EXISTS(
+                SELECT 1
+                FROM pg_index i
+                JOIN pg_class c ON c.oid = i.indrelid
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE c.relname = m.matviewname AND n.nspname = m.schemaname AND i.indisunique
+            ) as "has_unique_index!"
+        FROM pg_matviews m
+        WHERE m.schemaname = current_schema()
+        ORDER BY m.matviewname
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut refreshed = Vec::new();
+    for view in views {
+        let concurrently = if view.is_populated && view.has_unique_index {
+            " CONCURRENTLY"
+        } else {
+            ""
+        };
+        sqlx::query(&format!(
+            "REFRESH MATERIALIZED VIEW{} {}",
+            concurrently,
+            quote_identifier(&view.view_name)
+        ))
+        .execute(pool)
+        .await?;
+        refreshed.push(view.view_name);
+    }
+
+    Ok(TaskOutcome {
+        rows_affected: refreshed.len() as i64,
+        detail: json!({ "refreshed": refreshed }),
+    })
+}
+
+/// Deletes sessions (and with them their refresh and access tokens) that ended, by expiry or
+/// revocation, more than `SESSION_RETENTION_DAYS` ago.
+async fn prune_sessions(pool: &PgPool) -> Result<TaskOutcome, AppError> {
+    let retention_days = config::get().schedulers.session_retention_days;
+    // LEAST ignores NULLs, so a session that was never revoked ends when it expires
+    let pruned = sqlx::query!(
+        r#"
+        DELETE FROM auth_sessions
+        WHERE LEAST(revoked_at, expires_at) < NOW() - make_interval(days => $1)
+        "#,
+        retention_days as i32
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(TaskOutcome {
+        rows_affected: pruned as i64,
+        detail: json!({ "sessions_deleted": pruned, "retention_days": retention_days }),
+    })
+}
+
+/// Applies every retention policy. Rows are deleted in batches of `RETENTION_BATCH_SIZE`,
+/// walking the `(timestamp, id)` index from the oldest row, so each delete holds its locks
+/// briefly and never rescans rows deleted by an earlier batch.
+async fn apply_retention(pool: &PgPool) -> Result<TaskOutcome, AppError> {
+    let schedulers = &config::get().schedulers;
+    let batch_size = i64::from(schedulers.retention_batch_size);
+
+    let mut total_deleted = 0;
+    let mut deleted_per_table = serde_json::Map::new();
+    for policy in RETENTION_POLICIES {
+        let retention_days = (policy.retention_days)(schedulers);
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(retention_days));
+        let select = format!(
+            "SELECT {column}, id FROM {table} \
+             WHERE {column} < $1 AND ($2::timestamptz IS NULL OR ({column}, id) > ($2, $3)) \
+             ORDER BY {column}, id LIMIT $4",
+            column = policy.timestamp_column,
+            table = policy.table,
+        );
+        let delete = format!("DELETE FROM {} WHERE id = ANY($1)", policy.table);
+
+        let mut cursor: Option<(DateTime<Utc>, Uuid)> = None;
+        let mut deleted = 0;
+        loop {
+            let batch: Vec<(DateTime<Utc>, Uuid)> = sqlx::query_as(&select)
+                .bind(cutoff)
+                .bind(cursor.map(|(timestamp, _)| timestamp))
+                .bind(cursor.map(|(_, id)| id))
+                .bind(batch_size)
+                .fetch_all(pool)
+                .await?;
+            let Some(last) = batch.last().copied() else {
+                break;
+            };
+
+            let ids: Vec<Uuid> = batch.iter().map(|(_, id)| *id).collect();
+            deleted += sqlx::query(&delete)
+                .bind(&ids)
+                .execute(pool)
+                .await?
+                .rows_affected();
+            if (batch.len() as i64) < batch_size {
+                break;
+            }
+            cursor = Some(last);
+            tokio::time::sleep(RETENTION_BATCH_PAUSE).await;
+        }
+
+        if deleted > 0 {
+            info!(
+                "Service: Deleted {} rows from {} older than {} days",
+                deleted, policy.table, retention_days
+            );
+        }
+        total_deleted += deleted;
+        deleted_per_table.insert(policy.table.to_string(), json!(deleted));
+    }
+
+    Ok(TaskOutcome {
+        rows_affected: total_deleted as i64,
+        detail: json!({ "deleted": deleted_per_table }),
+    })
+}
+
+/// Repairs balance snapshots that drifted from the journal; see `balance_snapshot`.
+async fn rebuild_balance_snapshots(pool: &PgPool) -> Result<TaskOutcome, AppError> {
+    let summary = balance_snapshot::rebuild_all(pool).await?;
+    Ok(TaskOutcome {
+        rows_affected: summary.corrected,
+        detail: json!({
+            "tenants": summary.tenants,
+            "rows_corrected": summary.corrected,
+            "failed_tenants": summary.failed_tenants,
+        }),
+    })
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
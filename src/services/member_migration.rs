@@ -0,0 +1,358 @@
+//! Moving a tenant's members between deployments (e.g. self-hosted to cloud).
+//!
+//! The export lists every member with their roles by name. An import matches people by
+//! email, case-insensitively: existing accounts are linked, and missing ones are created
+//! without a password, so they sign in with single sign-on or set one up later. People who
+//! are already members are merged, replaced or skipped according to the conflict policy.
+//!
+//! An import is all or nothing: while any member has an error (unknown role, disabled
+//! account, a role granting more than the importer holds) nothing is written, and the
+//! report says why. A dry run returns the same report without writing anything.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::Utc;
+use serde_json::{json, Value as JsonValue};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::member_migration_dto::{ImportMembersDto, ImportMembersQuery, MemberConflictPolicy},
+        member_migration::{
+            ExportedMember, MemberExport, MemberImportAction, MemberImportReport,
+            MemberImportResult,
+        },
+        security_webhook::SecurityEventType,
+    },
+    services::{permission, security_webhook, tenant_invitation::PASSWORD_PROVIDER},
+};
+
+/// Version of the export layout.
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Exports the tenant's members with their roles. Requires `members.migrate`.
+pub async fn export_members(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<MemberExport, AppError> {
+    info!("Service: Exporting members of tenant ID: {}", tenant_id);
+
+    permission::require_permission(pool, tenant_id, user_id, permission::MEMBERS_MIGRATE).await?;
+    let members = sqlx::query_as!(
+        ExportedMember,
+        r#"
+        SELECT u.email, u.first_name, u.last_name, u.is_active,
+               array_agg(r.name ORDER BY r.name) as "roles!"
+        FROM user_tenant_roles utr
+        JOIN users u ON u.id = utr.user_id
+        JOIN roles r ON r.id = utr.role_id
+        WHERE utr.tenant_id = $1
+        GROUP BY u.id
+        ORDER BY LOWER(u.email)
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(MemberExport {
+        format_version: EXPORT_FORMAT_VERSION,
+        tenant_id,
+        exported_at: Utc::now(),
+        members,
+    })
+}
+
+/// Imports members matched by email and reports what happened to each. Requires
+/// `members.migrate`; only roles within the importer's own permissions can be granted.
+pub async fn import_members(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: ImportMembersDto,
+    query: ImportMembersQuery,
+) -> Result<MemberImportReport, AppError> {
+    info!(
+        "Service: Importing {} members into tenant ID: {} (dry run: {})",
+        dto.members.len(),
+        tenant_id,
+        query.dry_run
+    );
+
+    permission::require_permission(pool, tenant_id, user_id, permission::MEMBERS_MIGRATE).await?;
+
+    let mut db_tx = pool.begin().await?;
+    // One import per tenant at a time, so the memberships read below stay current
+    sqlx::query!(
+        "SELECT id FROM tenants WHERE id = $1 FOR NO KEY UPDATE",
+        tenant_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    let role_names: Vec<String> = dto
+        .members
+        .iter()
+        .flat_map(|member| member.roles.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let role_ids: HashMap<String, Uuid> = sqlx::query!(
+        "SELECT id, name FROM roles WHERE name = ANY($1)",
+        &role_names
+    )
+    .fetch_all(&mut *db_tx)
+    .await?
+    .into_iter()
+    .map(|row| (row.name, row.id))
+    .collect();
+
+    // Roles granting a permission the importer does not hold in this tenant
+    let known_role_ids: Vec<Uuid> = role_ids.values().copied().collect();
+    let beyond_own: HashSet<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT rp.role_id
+        FROM role_permissions rp
+        WHERE rp.role_id = ANY($3)
+          AND rp.permission_id NOT IN (
+              SELECT own.permission_id
+              FROM user_tenant_roles utr
+              JOIN role_permissions own ON own.role_id = utr.role_id
+              WHERE utr.tenant_id = $1 AND utr.user_id = $2
+          )
+        "#,
+        tenant_id,
+        user_id,
+        &known_role_ids
+    )
+    .fetch_all(&mut *db_tx)
+    .await?
+    .into_iter()
+    .collect();
+
+    let emails: Vec<String> = dto
+        .members
+        .iter()
+        .map(|member| member.email.trim().to_lowercase())
+        .collect();
+    let users: HashMap<String, (Uuid, bool)> = sqlx::query!(
+        r#"
+        SELECT id, LOWER(email) as "email!", is_active
+        FROM users
+        WHERE LOWER(email) = ANY($1)
+        FOR UPDATE
+        "#,
+        &emails
+    )
+    .fetch_all(&mut *db_tx)
+    .await?
+    .into_iter()
+    .map(|row| (row.email, (row.id, row.is_active)))
+    .collect();
+
+    let mut current_roles: HashMap<Uuid, BTreeSet<String>> = HashMap::new();
+    for row in sqlx::query!(
+        r#"
+        SELECT utr.user_id, r.name
+        FROM user_tenant_roles utr
+        JOIN roles r ON r.id = utr.role_id
+        WHERE utr.tenant_id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *db_tx)
+    .await?
+    {
+        current_roles
+            .entry(row.user_id)
+            .or_default()
+            .insert(row.name);
+    }
+
+    let mut results = Vec::with_capacity(dto.members.len());
+    let mut seen = HashSet::new();
+    for (member, email) in dto.members.iter().zip(&emails) {
+        let roles: BTreeSet<String> = member.roles.iter().cloned().collect();
+        let mut result = MemberImportResult {
+            email: email.clone(),
+            action: MemberImportAction::Unchanged,
+            roles_added: Vec::new(),
+            roles_removed: Vec::new(),
+            error: None,
+        };
+
+        let error = if !seen.insert(email.clone()) {
+            Some("The email appears more than once".to_string())
+        } else if roles.is_empty() {
+            Some("No roles given".to_string())
+        } else if let Some(unknown) = roles.iter().find(|role| !role_ids.contains_key(*role)) {
+            Some(format!("Unknown role '{}'", unknown))
+        } else if let Some(role) = roles
+            .iter()
+            .find(|role| beyond_own.contains(&role_ids[*role]))
+        {
+            Some(format!(
+                "The role '{}' grants permissions you do not hold",
+                role
+            ))
+        } else {
+            match users.get(email) {
+                Some((_, false)) => Some("This account is disabled".to_string()),
+                _ => None,
+            }
+        };
+        if let Some(error) = error {
+            result.action = MemberImportAction::Error;
+            result.error = Some(error);
+            results.push(result);
+            continue;
+        }
+
+        match users.get(email).map(|(id, _)| (*id, current_roles.get(id))) {
+            None => {
+                result.action = MemberImportAction::CreateUser;
+                result.roles_added = roles.into_iter().collect();
+            }
+            Some((_, None)) => {
+                result.action = MemberImportAction::AddMember;
+                result.roles_added = roles.into_iter().collect();
+            }
+            Some((_, Some(_))) if query.on_conflict == MemberConflictPolicy::Skip => {}
+            Some((member_id, Some(current))) => {
+                result.roles_added = roles.difference(current).cloned().collect();
+                // The importer never takes away their own roles
+                if query.on_conflict == MemberConflictPolicy::Replace && member_id != user_id {
+                    result.roles_removed = current.difference(&roles).cloned().collect();
+                }
+                if !result.roles_added.is_empty() || !result.roles_removed.is_empty() {
+                    result.action = MemberImportAction::UpdateRoles;
+                }
+            }
+        }
+        results.push(result);
+    }
+
+    let has_errors = results
+        .iter()
+        .any(|result| result.action == MemberImportAction::Error);
+    if query.dry_run || has_errors {
+        return Ok(MemberImportReport {
+            dry_run: query.dry_run,
+            applied: false,
+            members: results,
+        });
+    }
+
+    // Sent once the import is committed
+    let mut events: Vec<(SecurityEventType, JsonValue)> = Vec::new();
+    for (member, result) in dto.members.iter().zip(&results) {
+        let member_id = match result.action {
+            MemberImportAction::CreateUser => {
+                sqlx::query_scalar!(
+                    r#"
+                    INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+                    VALUES (LOWER($1), $2, $1, $3, $4)
+                    RETURNING id
+                    "#,
+                    member.email.trim(),
+                    PASSWORD_PROVIDER,
+                    member.first_name.trim(),
+                    member.last_name.trim()
+                )
+                .fetch_one(&mut *db_tx)
+                .await?
+            }
+            MemberImportAction::AddMember | MemberImportAction::UpdateRoles => {
+                users[&result.email].0
+            }
+            MemberImportAction::Unchanged | MemberImportAction::Error => continue,
+        };
+
+        let added: Vec<Uuid> = result
+            .roles_added
+            .iter()
+            .map(|role| role_ids[role])
+            .collect();
+        sqlx::query!(
+            r#"
+            INSERT INTO user_tenant_roles (user_id, tenant_id, role_id, created_by, updated_by)
+            SELECT $1, $2, role_id, $4, $4 FROM UNNEST($3::uuid[]) as role_id
+            ON CONFLICT (user_id, tenant_id, role_id) DO NOTHING
+            "#,
+            member_id,
+            tenant_id,
+            &added,
+            user_id
+        )
+        .execute(&mut *db_tx)
+        .await?;
+
+        if !result.roles_removed.is_empty() {
+            sqlx::query!(
+                r#"
+                DELETE FROM user_tenant_roles utr
+                USING roles r
+                WHERE r.id = utr.role_id
+                  AND utr.user_id = $1 AND utr.tenant_id = $2 AND r.name = ANY($3)
+                "#,
+                member_id,
+                tenant_id,
+                &result.roles_removed
+            )
+            .execute(&mut *db_tx)
+            .await?;
+        }
+
+        events.push(if result.action == MemberImportAction::UpdateRoles {
+            let previous = current_roles.get(&member_id).cloned().unwrap_or_default();
+            let mut roles = previous.clone();
+            roles.extend(result.roles_added.iter().cloned());
+            for removed in &result.roles_removed {
+                roles.remove(removed);
+            }
+            (
+                SecurityEventType::RoleChanged,
+                json!({
+                    "user_id": member_id,
+                    "role": role_list(&roles),
+                    "previous_role": role_list(&previous),
+                }),
+            )
+        } else {
+            (
+                SecurityEventType::MemberAdded,
+                json!({
+                    "user_id": member_id,
+                    "email": result.email,
+                    "role": result.roles_added.join(", "),
+                }),
+            )
+        });
+    }
+    db_tx.commit().await?;
+
+    for (event_type, data) in events {
+        security_webhook::emit_security_event(pool, tenant_id, event_type, Some(user_id), data)
+            .await;
+    }
+
+    info!(
+        "Service: Imported {} members into tenant ID: {}",
+        results.len(),
+        tenant_id
+    );
+    Ok(MemberImportReport {
+        dry_run: false,
+        applied: true,
+        members: results,
+    })
+}
+
+/// Role names as one string for security events, e.g. "Accountant, Viewer".
+fn role_list(roles: &BTreeSet<String>) -> String {
+    roles.iter().cloned().collect::<Vec<_>>().join(", ")
+}
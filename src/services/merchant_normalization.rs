@@ -0,0 +1,507 @@
+//! Merchant names from raw bank descriptors.
+//!
+//! Bank feeds describe card payments with processor noise, store numbers and reference
+//! codes (`"AMZN Mktp US*2F4"`, `"SQ *BLUE BOTTLE 0421"`). A descriptor is first cleaned
+//! (upper-cased, processor prefixes and everything after `*` or `#` dropped, tokens with
+//! digits and a trailing US state code removed), then matched against rules:
+//!
+//! 1. the tenant's own `merchant_rules`, then
+//! 2. the bundled dataset in `data/merchant_rules.json`.
+//!
+//! A rule matches when its pattern occurs in the cleaned descriptor on word boundaries; the
+//! longest matching pattern wins. A tenant rule keyed by a payee instead of a pattern matches
+//! on the payee's cleaned name, and also categorizes transactions entered with that payee
+//! (see `transaction::insert_transaction`). Bundled rules carry a category hint (e.g. `"Software"`),
+//! which resolves to the tenant's active category of that name when there is one. Without a
+//! matching rule, the cleaned descriptor in title case is used as the merchant name.
+//!
+//! Normalization runs when bank transactions are staged and can be re-run over the staged
+//! history after rules change.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::merchant_rule_dto::{
+            CreateMerchantRuleDto, ReapplyMerchantRulesDto, UpdateMerchantRuleDto,
+        },
+        merchant_rule::{MerchantReapplyResult, MerchantRule, NormalizedMerchant},
+    },
+    services::{category, payee},
+    utils::update_builder::UpdateBuilder,
+};
+
+/// Prefixes added by card processors and banks, stripped before matching.
+const NOISE_PREFIXES: &[&str] = &[
+    "DEBIT CARD PURCHASE ",
+    "CARD PURCHASE ",
+    "RECURRING PAYMENT ",
+    "CHECKCARD ",
+    "PURCHASE ",
+    "POS ",
+    "PAYPAL *",
+    "SQ *",
+    "SQ*",
+    "TST* ",
+    "TST*",
+    "PP*",
+];
+
+/// Trailing location codes dropped from descriptors (US states and DC).
+const REGION_CODES: &[&str] = &[
+    "AL", "AK", "AZ", "AR", "CA", "CO", "CT", "DE", "DC", "FL", "GA", "HI", "ID", "IL", "IN", "IA",
+    "KS", "KY", "LA", "ME", "MD", "MA", "MI", "MN", "MS", "MO", "MT", "NE", "NV", "NH", "NJ", "NM",
+    "NY", "NC", "ND", "OH", "OK", "OR", "PA", "RI", "SC", "SD", "TN", "TX", "UT", "VT", "VA", "WA",
+    "WV", "WI", "WY",
+];
+
+/// Staged rows are re-normalized in batches of this size.
+const REAPPLY_BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+struct BundledRule {
+    pattern: String,
+    merchant: String,
+    category_hint: Option<String>,
+}
+
+static BUNDLED_RULES: OnceLock<Vec<BundledRule>> = OnceLock::new();
+
+fn bundled_rules() -> &'static [BundledRule] {
+    BUNDLED_RULES.get_or_init(|| {
+        serde_json::from_str(include_str!("../../data/merchant_rules.json"))
+            .expect("data/merchant_rules.json is valid")
+    })
+}
+
+/// Cleans a raw descriptor for matching: `"AMZN Mktp US*2F4"` becomes `"AMZN MKTP US"`.
+pub fn clean_descriptor(raw: &str) -> String {
+    let mut text = raw.trim().to_uppercase();
+    while let Some(prefix) = NOISE_PREFIXES.iter().find(|p| text.starts_with(*p)) {
+        text = text[prefix.len()..].trim_start().to_string();
+    }
+    if let Some(end) = text.find(['*', '#']) {
+        text.truncate(end);
+    }
+
+    let mut tokens: Vec<&str> = text
+        .split_whitespace()
+        .filter(|token| !token.chars().any(|c| c.is_ascii_digit()))
+        .collect();
+    if tokens.len() > 2 && tokens.last().is_some_and(|t| REGION_CODES.contains(t)) {
+        tokens.pop();
+    }
+    tokens
+        .join(" ")
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_string()
+}
+
+/// Whether `pattern` occurs in `descriptor` with no letter or digit directly around it.
+fn pattern_matches(descriptor: &str, pattern: &str) -> bool {
+    if pattern.is_empty() {
+        return false;
+    }
+    descriptor.match_indices(pattern).any(|(start, _)| {
+        let before = descriptor[..start].chars().next_back();
+        let after = descriptor[start + pattern.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// `"BLUE BOTTLE COFFEE"` -> `"Blue Bottle Coffee"`.
+fn title_case(text: &str) -> String {
+    text.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_string() + &chars.as_str().to_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A tenant's rules and categories, loaded once per import or re-run.
+pub struct MerchantNormalizer {
+    tenant_rules: Vec<(String, MerchantRule)>, // With the text each is matched on
+    categories_by_name: HashMap<String, Uuid>,
+}
+
+impl MerchantNormalizer {
+    pub async fn for_tenant(pool: &PgPool, tenant_id: Uuid) -> Result<Self, AppError> {
+        let payee_names: HashMap<Uuid, String> = sqlx::query!(
+            r#"
+            SELECT p.id, p.name
+            FROM payees p
+            JOIN merchant_rules mr ON mr.payee_id = p.id
+            WHERE p.tenant_id = $1
+            "#,
+            tenant_id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.id, clean_descriptor(&row.name)))
+        .collect();
+        let tenant_rules = list_merchant_rules(pool, tenant_id)
+            .await?
+            .into_iter()
+            .filter_map(|rule| {
+                let key = match (&rule.pattern, rule.payee_id) {
+                    (Some(pattern), _) => pattern.clone(),
+                    (None, Some(payee_id)) => payee_names.get(&payee_id)?.clone(),
+                    (None, None) => return None,
+                };
+                Some((key, rule))
+            })
+            .collect();
+        let mut categories_by_name = HashMap::new();
+        for category in category::list_categories(pool, tenant_id, false, None).await? {
+            categories_by_name
+                .entry(category.name.to_lowercase())
+                .or_insert(category.id);
+        }
+        Ok(MerchantNormalizer {
+            tenant_rules,
+            categories_by_name,
+        })
+    }
+
+    pub fn normalize(&self, descriptor: &str) -> NormalizedMerchant {
+        let cleaned = clean_descriptor(descriptor);
+        if cleaned.is_empty() {
+            return NormalizedMerchant::default();
+        }
+
+        if let Some((_, rule)) = self
+            .tenant_rules
+            .iter()
+            .filter(|(key, _)| pattern_matches(&cleaned, key))
+            .max_by_key(|(key, _)| key.len())
+        {
+            return NormalizedMerchant {
+                merchant_name: Some(rule.merchant_name.clone()),
+                category_hint: None,
+                category_id: rule.category_id,
+            };
+        }
+
+        if let Some(rule) = bundled_rules()
+            .iter()
+            .filter(|rule| pattern_matches(&cleaned, &rule.pattern))
+            .max_by_key(|rule| rule.pattern.len())
+        {
+            return NormalizedMerchant {
+                merchant_name: Some(rule.merchant.clone()),
+                category_hint: rule.category_hint.clone(),
+                category_id: rule
+                    .category_hint
+                    .as_ref()
+                    .and_then(|hint| self.categories_by_name.get(&hint.to_lowercase()).copied()),
+            };
+        }
+
+        NormalizedMerchant {
+            merchant_name: Some(title_case(&cleaned)),
+            category_hint: None,
+            category_id: None,
+        }
+    }
+}
+
+/// Retrieves the tenant's merchant rules.
+pub async fn list_merchant_rules(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Vec<MerchantRule>, AppError> {
+    info!(
+        "Service: Listing merchant rules for tenant ID: {}",
+        tenant_id
+    );
+
+    let rules = query_as!(
+        MerchantRule,
+        r#"
+        SELECT
+            id, tenant_id, pattern, payee_id, merchant_name, category_id,
+            created_at, created_by, updated_at, updated_by
+        FROM merchant_rules
+        WHERE tenant_id = $1
+        ORDER BY pattern NULLS LAST, merchant_name
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rules)
+}
+
+/// Creates a tenant merchant rule, keyed by a pattern (cleaned like a descriptor) or by a
+/// payee. A payee has at most one rule.
+pub async fn create_merchant_rule(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: CreateMerchantRuleDto,
+) -> Result<MerchantRule, AppError> {
+    info!(
+        "Service: Creating merchant rule for tenant ID: {}",
+        tenant_id
+    );
+
+    let (pattern, merchant_name, key) = match (dto.pattern, dto.payee_id) {
+        (Some(pattern), None) => {
+            let pattern = rule_pattern(&pattern)?;
+            let merchant_name = dto.merchant_name.ok_or_else(|| {
+                AppError::Validation("merchant_name is required for a pattern rule".to_string())
+            })?;
+            let key = format!("'{}'", pattern);
+            (Some(pattern), merchant_name, key)
+        }
+        (None, Some(payee_id)) => {
+            let payee = payee::get_payee(pool, tenant_id, payee_id).await?;
+            let key = format!("payee '{}'", payee.name);
+            (None, dto.merchant_name.unwrap_or(payee.name), key)
+        }
+        _ => {
+            return Err(AppError::Validation(
+                "Give exactly one of pattern or payee_id".to_string(),
+            ))
+        }
+    };
+    if let Some(category_id) = dto.category_id {
+        category::get_category_by_id(pool, tenant_id, category_id).await?;
+    }
+
+    let rule = query_as!(
+        MerchantRule,
+        r#"
+        INSERT INTO merchant_rules (tenant_id, pattern, payee_id, merchant_name, category_id, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        ON CONFLICT DO NOTHING
+        RETURNING
+            id, tenant_id, pattern, payee_id, merchant_name, category_id,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        pattern,
+        dto.payee_id,
+        merchant_name.trim(),
+        dto.category_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::Conflict(format!("A merchant rule for {} already exists", key)))?;
+
+    Ok(rule)
+}
+
+/// Updates a tenant merchant rule.
+pub async fn update_merchant_rule(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    rule_id: Uuid,
+    user_id: Uuid,
+    dto: UpdateMerchantRuleDto,
+) -> Result<MerchantRule, AppError> {
+    info!(
+        "Service: Updating merchant rule with ID: {} for tenant ID: {}",
+        rule_id, tenant_id
+    );
+
+    let pattern = dto.pattern.as_deref().map(rule_pattern).transpose()?;
+    if let Some(category_id) = dto.category_id {
+        category::get_category_by_id(pool, tenant_id, category_id).await?;
+    }
+
+    let mut update = UpdateBuilder::new("merchant_rules");
+    update
+        .set("pattern", pattern.clone())
+        .set(
+            "merchant_name",
+            dto.merchant_name.map(|name| name.trim().to_string()),
+        )
+        .set("category_id", dto.category_id);
+    if dto.remove_category {
+        update.set_null("category_id");
+    }
+    if update.is_empty() {
+        return Err(AppError::Validation(
+            "No fields provided for update".to_string(),
+        ));
+    }
+
+    let mut query = update.stamp(user_id);
+    query.push(" WHERE id = ").push_bind(rule_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(
+        r#"
+        RETURNING
+            id, tenant_id, pattern, payee_id, merchant_name, category_id,
+            created_at, created_by, updated_at, updated_by
+        "#,
+    );
+
+    let rule = query
+        .build_query_as::<MerchantRule>()
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => AppError::Conflict(format!(
+                "A merchant rule for '{}' already exists",
+                pattern.unwrap_or_default()
+            )),
+            // Setting a pattern on a payee rule would key it by both
+            sqlx::Error::Database(db) if db.is_check_violation() => AppError::Validation(
+                "A payee rule has no pattern; create a pattern rule instead".to_string(),
+            ),
+            e => e.into(),
+        })?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Merchant rule with ID {} not found", rule_id))
+        })?;
+
+    Ok(rule)
+}
+
+/// Deletes a tenant merchant rule. Already normalized rows keep their merchant until re-run.
+pub async fn delete_merchant_rule(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    rule_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting merchant rule with ID: {} for tenant ID: {}",
+        rule_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        "DELETE FROM merchant_rules WHERE id = $1 AND tenant_id = $2",
+        rule_id,
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Merchant rule with ID {} not found",
+            rule_id
+        )));
+    }
+    Ok(())
+}
+
+/// Re-normalizes the tenant's staged bank transactions, e.g. after adding rules.
+/// Rows are examined whatever their status; only changed rows are written.
+pub async fn reapply_merchant_rules(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: ReapplyMerchantRulesDto,
+) -> Result<MerchantReapplyResult, AppError> {
+    info!(
+        "Service: Re-running merchant normalization for tenant ID: {}",
+        tenant_id
+    );
+
+    if let (Some(from), Some(to)) = (dto.from_date, dto.to_date) {
+        if from > to {
+            return Err(AppError::Validation(
+                "from_date must not be after to_date".to_string(),
+            ));
+        }
+    }
+
+    let normalizer = MerchantNormalizer::for_tenant(pool, tenant_id).await?;
+    let rows = sqlx::query!(
+        r#"
+        SELECT s.id, s.description, s.merchant_name, s.merchant_category_hint, s.merchant_category_id
+        FROM external_transactions_staging s
+        JOIN external_accounts ea ON s.external_account_id = ea.id
+        JOIN ext_conns ec ON ea.ext_conn_id = ec.id
+        WHERE ec.tenant_id = $1
+          AND ($2::date IS NULL OR s.transaction_date >= $2)
+          AND ($3::date IS NULL OR s.transaction_date <= $3)
+        "#,
+        tenant_id,
+        dto.from_date as Option<NaiveDate>,
+        dto.to_date as Option<NaiveDate>
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut result = MerchantReapplyResult {
+        rows_examined: rows.len(),
+        ..Default::default()
+    };
+    let changed: Vec<(Uuid, NormalizedMerchant)> = rows
+        .into_iter()
+        .filter_map(|row| {
+            let normalized = normalizer.normalize(&row.description);
+            let current = NormalizedMerchant {
+                merchant_name: row.merchant_name,
+                category_hint: row.merchant_category_hint,
+                category_id: row.merchant_category_id,
+            };
+            (normalized != current).then_some((row.id, normalized))
+        })
+        .collect();
+
+    for batch in changed.chunks(REAPPLY_BATCH_SIZE) {
+        let ids: Vec<Uuid> = batch.iter().map(|(id, _)| *id).collect();
+        let names: Vec<Option<String>> =
+            batch.iter().map(|(_, n)| n.merchant_name.clone()).collect();
+        let hints: Vec<Option<String>> =
+            batch.iter().map(|(_, n)| n.category_hint.clone()).collect();
+        let category_ids: Vec<Option<Uuid>> = batch.iter().map(|(_, n)| n.category_id).collect();
+
+        result.rows_updated += sqlx::query!(
+            r#"
+            UPDATE external_transactions_staging s
+            SET merchant_name = v.merchant_name,
+                merchant_category_hint = v.category_hint,
+                merchant_category_id = v.category_id,
+                updated_at = NOW(),
+                updated_by = $5
+            FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::uuid[])
+                AS v(id, merchant_name, category_hint, category_id)
+            WHERE s.id = v.id
+            "#,
+            &ids,
+            &names as &[Option<String>],
+            &hints as &[Option<String>],
+            &category_ids as &[Option<Uuid>],
+            user_id
+        )
+        .execute(pool)
+        .await?
+        .rows_affected() as usize;
+    }
+
+    Ok(result)
+}
+
+/// Cleans a rule pattern the same way descriptors are cleaned.
+fn rule_pattern(raw: &str) -> Result<String, AppError> {
+    let pattern = clean_descriptor(raw);
+    if pattern.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Pattern '{}' is empty once store numbers and reference codes are removed",
+            raw
+        )));
+    }
+    Ok(pattern)
+}
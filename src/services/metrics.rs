@@ -0,0 +1,88 @@
+//! Prometheus metrics.
+//!
+//! Request counts and latencies are recorded per matched route by `middleware::metrics`.
+//! Database pool, job queue and table size gauges are sampled when `/metrics` is scraped,
+//! so they are current at scrape time without a background task. Row counts are the
+//! planner's estimates, which are cheap to read on large tables.
+
+use metrics::gauge;
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use sqlx::PgPool;
+
+use crate::error::AppError;
+
+/// Requests served, by method, route template and status code.
+pub const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+/// Request latency histogram, by method and route template.
+pub const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
+
+/// Latency buckets in seconds; reports and imports can take several seconds.
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// Import job statuses that count towards the queue depth.
+const QUEUED_JOB_STATUSES: &[&str] = &["PENDING", "RUNNING"];
+
+/// Installs the process-wide Prometheus recorder. Call once, at startup.
+pub fn install_recorder() -> PrometheusHandle {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(HTTP_REQUEST_DURATION_SECONDS.to_string()),
+            LATENCY_BUCKETS,
+        )
+        .expect("latency buckets are not empty")
+        .install_recorder()
+        .expect("the metrics recorder is installed only once")
+}
+
+/// Samples the pool, queue and table gauges and renders every metric in Prometheus text format.
+pub async fn render(pool: &PgPool, handle: &PrometheusHandle) -> Result<String, AppError> {
+    gauge!("db_pool_connections").set(pool.size() as f64);
+    gauge!("db_pool_idle_connections").set(pool.num_idle() as f64);
+    gauge!("db_pool_max_connections").set(pool.options().get_max_connections() as f64);
+
+    let depths = sqlx::query!(
+        r#"
+        SELECT status::text as "status!", COUNT(*) as "count!"
+        FROM import_jobs
+        WHERE status::text = ANY($1)
+        GROUP BY status
+        "#,
+        &QUEUED_JOB_STATUSES
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Every status is set, so a drained queue reports 0 rather than its last depth
+    for status in QUEUED_JOB_STATUSES {
+        let depth = depths
+            .iter()
+            .find(|d| d.status == *status)
+            .map_or(0, |d| d.count);
+        gauge!("job_queue_depth", "queue" => "import_jobs", "status" => *status).set(depth as f64);
+    }
+
+    let tables = sqlx::query!(
+        r#"
+        SELECT
+            relname::text as "table_name!",
+            COALESCE(n_live_tup, 0) as "rows!",
+            pg_total_relation_size(relid) as "bytes!"
+        FROM pg_stat_user_tables
+        WHERE schemaname = current_schema()
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    for table in tables {
+        gauge!("db_table_rows", "table" => table.table_name.clone()).set(table.rows as f64);
+        gauge!("db_table_size_bytes", "table" => table.table_name).set(table.bytes as f64);
+    }
+
+    handle.run_upkeep();
+    Ok(handle.render())
+}
@@ -1,28 +1,99 @@
 // pub mod user;
-// pub mod tenant;
-// pub mod currency;
-// pub mod exchange_rate; // New
-// pub mod account_type;
-// pub mod account;
-// pub mod category;    // New
+pub mod tenant;
+pub mod currency;
+pub mod exchange_rate;
+pub mod currency_conversion;
+pub mod fx_revaluation;
+pub mod account_type;
+pub mod account;
+pub mod category;
+pub mod dependency;
 // pub mod tag;         // New
-// pub mod transaction;
-// pub mod journal_entry; // New
+pub mod transaction;
+pub mod transaction_split;
+pub mod transaction_export;
+pub mod transfer;
+pub mod journal_entry;
+pub mod balance_snapshot;
 
 // Phase 2 Services (will add later)
-// pub mod budget;
-// pub mod budget_line_item;
-// pub mod recurring_transaction;
-// pub mod custom_report;
-// pub mod dashboard;
-// pub mod dashboard_widget;
+pub mod budget;
+pub mod budget_line_item;
+pub mod budget_csv;
+pub mod budget_alert;
+pub mod import_job;
+pub mod calendar_feed;
+pub mod cash_position;
+pub mod event;
+pub mod customer;
+pub mod invoice;
+pub mod vendor;
+pub mod bill;
+pub mod payment;
+pub mod fixed_asset;
+pub mod payee;
+pub mod dimension;
+pub mod household;
+pub mod budget_performance;
+pub mod budget_health;
+pub mod envelope;
+pub mod recurring_transaction;
+pub mod recurring_template;
+pub mod custom_report;
+pub mod report_export;
+pub mod report_schedule;
+pub mod report;
+pub mod statement_layout;
+pub mod dashboard;
+pub mod dashboard_widget;
+pub mod dashboard_data;
 // pub mod role;
-// pub mod permission;
+pub mod permission;
+pub mod field_policy;
+pub mod privacy;
+pub mod user_preference;
+pub mod quick_open;
 // pub mod role_permission;
 // pub mod user_tenant_role;
-// pub mod ext_provider;
-// pub mod ext_conn;
+pub mod ext_provider;
+pub mod ext_conn;
+pub mod bank_connector;
+pub mod transaction_matching;
+pub mod merchant_normalization;
+pub mod fiscal_period;
+pub mod notification;
+pub mod mailer;
+pub mod mail_settings;
+pub mod integration_health;
+pub mod metrics;
+pub mod security_webhook;
+pub mod audit;
+pub mod audit_sink;
+pub mod domain_event;
+pub mod reference_cache;
+pub mod redis_store;
 // pub mod external_account;
 // pub mod external_transactions_staging;
 // pub mod coa_template;
 // pub mod coa_template_account;
+
+// Background jobs
+pub mod scheduler;
+pub mod reimbursement;
+pub mod business_calendar;
+pub mod auth;
+pub mod api_key;
+pub mod security_event_catalog;
+pub mod file_storage;
+pub mod export_artifact;
+pub mod oidc;
+pub mod tenant_backup;
+pub mod tenant_restore;
+pub mod tenant_branding;
+pub mod tenant_invitation;
+pub mod member_migration;
+pub mod opening_balance;
+pub mod assistant;
+pub mod maintenance;
+pub mod live_update;
+pub mod idempotency;
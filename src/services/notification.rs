@@ -0,0 +1,407 @@
+//! User notifications and their email delivery rules.
+//!
+//! - `CRITICAL` notifications are emailed immediately, even during quiet hours.
+//! - `NORMAL` notifications are emailed immediately outside quiet hours, otherwise held
+//!   until quiet hours end.
+//! - `LOW` notifications are batched into one digest email per hour or day
+//!   (the user's `digest_frequency`), sent outside quiet hours.
+//!
+//! Anything not delivered straight away is picked up by the notification scheduler.
+
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use sqlx::{query_as, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::notification_dto::UpdateNotificationPreferencesDto,
+        notification::{
+            DigestFrequency, Notification, NotificationPreference, NotificationPriority,
+        },
+    },
+    services::mailer,
+};
+
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 500;
+
+/// Records a notification for a user and emails it right away when the user's
+/// delivery rules allow; otherwise it waits for the scheduler.
+pub async fn notify(
+    pool: &PgPool,
+    user_id: Uuid,
+    tenant_id: Option<Uuid>,
+    priority: NotificationPriority,
+    subject: &str,
+    body: &str,
+) -> Result<Notification, AppError> {
+    info!(
+        "Service: Queuing {:?} notification for user ID: {}",
+        priority, user_id
+    );
+
+    let notification = query_as!(
+        Notification,
+        r#"
+        INSERT INTO notifications (user_id, tenant_id, priority, subject, body)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING
+            id, user_id, tenant_id, priority, subject, body, delivered_at,
+            delivery_attempts, last_error, created_at
+        "#,
+        user_id,
+        tenant_id,
+        String::from(priority),
+        subject,
+        body
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if priority != NotificationPriority::Low {
+        // Delivery problems are recorded on the row and retried by the scheduler
+        if let Err(e) = deliver_pending_for_user(pool, user_id, Utc::now()).await {
+            warn!("Immediate delivery for user {} failed: {}", user_id, e);
+        }
+    }
+
+    Ok(notification)
+}
+
+/// Lists the most recent notifications of a user.
+pub async fn list_notifications(
+    pool: &PgPool,
+    user_id: Uuid,
+    limit: Option<i64>,
+) -> Result<Vec<Notification>, AppError> {
+    info!("Service: Listing notifications for user ID: {}", user_id);
+
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, MAX_LIST_LIMIT);
+    let notifications = query_as!(
+        Notification,
+        r#"
+        SELECT
+            id, user_id, tenant_id, priority, subject, body, delivered_at,
+            delivery_attempts, last_error, created_at
+        FROM notifications
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        user_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(notifications)
+}
+
+/// Retrieves a user's notification preferences, creating the defaults on first access.
+pub async fn get_notification_preferences(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<NotificationPreference, AppError> {
+    info!(
+        "Service: Getting notification preferences for user ID: {}",
+        user_id
+    );
+
+    sqlx::query!(
+        "INSERT INTO notification_preferences (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    let preferences = query_as!(
+        NotificationPreference,
+        r#"
+        SELECT
+            user_id, timezone, quiet_hours_start, quiet_hours_end, digest_frequency,
+            email_enabled, last_digest_sent_at, created_at, updated_at
+        FROM notification_preferences
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(preferences)
+}
+
+/// Updates a user's notification preferences.
+pub async fn update_notification_preferences(
+    pool: &PgPool,
+    user_id: Uuid,
+    dto: UpdateNotificationPreferencesDto,
+) -> Result<NotificationPreference, AppError> {
+    info!(
+        "Service: Updating notification preferences for user ID: {}",
+        user_id
+    );
+
+    dto.validate()?;
+    if let Some(timezone) = &dto.timezone {
+        timezone.parse::<Tz>().map_err(|_| {
+            AppError::Validation(format!("'{}' is not a known time zone", timezone))
+        })?;
+    }
+    if dto.quiet_hours_start.is_some() != dto.quiet_hours_end.is_some() {
+        return Err(AppError::Validation(
+            "quiet_hours_start and quiet_hours_end must be set together".to_string(),
+        ));
+    }
+
+    // Make sure the row exists before updating it
+    get_notification_preferences(pool, user_id).await?;
+
+    let preferences = query_as!(
+        NotificationPreference,
+        r#"
+        UPDATE notification_preferences
+        SET
+            timezone = COALESCE($2, timezone),
+            quiet_hours_start = CASE WHEN $3 THEN NULL ELSE COALESCE($4, quiet_hours_start) END,
+            quiet_hours_end = CASE WHEN $3 THEN NULL ELSE COALESCE($5, quiet_hours_end) END,
+            digest_frequency = COALESCE($6, digest_frequency),
+            email_enabled = COALESCE($7, email_enabled),
+            updated_at = NOW()
+        WHERE user_id = $1
+        RETURNING
+            user_id, timezone, quiet_hours_start, quiet_hours_end, digest_frequency,
+            email_enabled, last_digest_sent_at, created_at, updated_at
+        "#,
+        user_id,
+        dto.timezone,
+        dto.clear_quiet_hours.unwrap_or(false),
+        dto.quiet_hours_start,
+        dto.quiet_hours_end,
+        dto.digest_frequency.map(String::from),
+        dto.email_enabled
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(preferences)
+}
+
+/// Delivers everything that is due for every user with undelivered notifications.
+/// Run by the notification scheduler. Returns the number of notifications delivered.
+pub async fn deliver_pending_notifications(pool: &PgPool) -> Result<usize, AppError> {
+    let user_ids: Vec<Uuid> = sqlx::query_scalar!(
+        "SELECT DISTINCT user_id FROM notifications WHERE delivered_at IS NULL"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let now = Utc::now();
+    let mut delivered = 0;
+    for user_id in user_ids {
+        match deliver_pending_for_user(pool, user_id, now).await {
+            Ok(count) => delivered += count,
+            Err(e) => warn!(
+                "Failed to deliver notifications for user {}: {}",
+                user_id, e
+            ),
+        }
+    }
+
+    if delivered > 0 {
+        info!("Delivered {} notification(s)", delivered);
+    }
+    Ok(delivered)
+}
+
+/// Applies the user's delivery rules to their undelivered notifications.
+///
+/// Rows are locked with `FOR UPDATE SKIP LOCKED`, so an immediate delivery and a
+/// scheduler run never send the same notification twice.
+async fn deliver_pending_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<usize, AppError> {
+    let preferences = get_notification_preferences(pool, user_id).await?;
+    let email = sqlx::query_scalar!(
+        "SELECT email FROM users WHERE id = $1 AND is_active = TRUE",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let mut db_tx = pool.begin().await?;
+
+    let pending = query_as!(
+        Notification,
+        r#"
+        SELECT
+            id, user_id, tenant_id, priority, subject, body, delivered_at,
+            delivery_attempts, last_error, created_at
+        FROM notifications
+        WHERE user_id = $1 AND delivered_at IS NULL
+        ORDER BY created_at
+        FOR UPDATE SKIP LOCKED
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    let quiet = in_quiet_hours(&preferences, now);
+    let digest_frequency: DigestFrequency = preferences
+        .digest_frequency
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    let digest_due = preferences
+        .last_digest_sent_at
+        .is_none_or(|last| now - last >= digest_frequency.interval());
+
+    let mut delivered_ids: Vec<Uuid> = Vec::new();
+    let mut digest: Vec<&Notification> = Vec::new();
+
+    for notification in &pending {
+        let priority: NotificationPriority = notification
+            .priority
+            .parse()
+            .map_err(AppError::InternalServerError)?;
+        let send_now = match priority {
+            NotificationPriority::Critical => true,
+            NotificationPriority::Normal => !quiet,
+            NotificationPriority::Low => {
+                if !quiet && digest_due {
+                    digest.push(notification);
+                }
+                false
+            }
+        };
+        if !send_now {
+            continue;
+        }
+
+        // With email turned off, non-critical notifications stay in-app only
+        let wants_email = preferences.email_enabled || priority == NotificationPriority::Critical;
+        let result = match (&email, wants_email) {
+            (Some(address), true) => {
+                mailer::send_email(address, &notification.subject, &notification.body).await
+            }
+            _ => Ok(()),
+        };
+        match result {
+            Ok(()) => delivered_ids.push(notification.id),
+            Err(e) => record_failure(&mut db_tx, notification.id, &e).await?,
+        }
+    }
+
+    if !digest.is_empty() {
+        let result = match (&email, preferences.email_enabled) {
+            (Some(address), true) => {
+                let (subject, body) = digest_email(&digest, digest_frequency);
+                mailer::send_email(address, &subject, &body).await
+            }
+            _ => Ok(()),
+        };
+        match result {
+            Ok(()) => {
+                delivered_ids.extend(digest.iter().map(|n| n.id));
+                sqlx::query!(
+                    "UPDATE notification_preferences SET last_digest_sent_at = $2 WHERE user_id = $1",
+                    user_id,
+                    now
+                )
+                .execute(&mut *db_tx)
+                .await?;
+            }
+            Err(e) => {
+                for notification in &digest {
+                    record_failure(&mut db_tx, notification.id, &e).await?;
+                }
+            }
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE notifications
+        SET delivered_at = $2, delivery_attempts = delivery_attempts + 1, last_error = NULL
+        WHERE id = ANY($1)
+        "#,
+        &delivered_ids,
+        now
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(delivered_ids.len())
+}
+
+async fn record_failure(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    notification_id: Uuid,
+    error: &AppError,
+) -> Result<(), AppError> {
+    warn!(
+        "Failed to deliver notification {}: {}",
+        notification_id, error
+    );
+    sqlx::query!(
+        r#"
+        UPDATE notifications
+        SET delivery_attempts = delivery_attempts + 1, last_error = $2
+        WHERE id = $1
+        "#,
+        notification_id,
+        error.to_string()
+    )
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(())
+}
+
+/// Whether `now` falls in the user's quiet hours, evaluated in their time zone.
+/// A window whose end is before its start spans midnight (e.g. 22:00-07:00).
+fn in_quiet_hours(preferences: &NotificationPreference, now: DateTime<Utc>) -> bool {
+    let (Some(start), Some(end)) = (preferences.quiet_hours_start, preferences.quiet_hours_end)
+    else {
+        return false;
+    };
+    let timezone: Tz = preferences.timezone.parse().unwrap_or(Tz::UTC);
+    let local: NaiveTime = now.with_timezone(&timezone).time();
+
+    if start <= end {
+        local >= start && local < end
+    } else {
+        local >= start || local < end
+    }
+}
+
+fn digest_email(notifications: &[&Notification], frequency: DigestFrequency) -> (String, String) {
+    let period = match frequency {
+        DigestFrequency::Hourly => "hourly",
+        DigestFrequency::Daily => "daily",
+    };
+    let subject = format!(
+        "Your {} summary: {} notification(s)",
+        period,
+        notifications.len()
+    );
+    let body = notifications
+        .iter()
+        .map(|n| {
+            format!(
+                "- {} ({})\n  {}",
+                n.subject,
+                n.created_at.format("%Y-%m-%d %H:%M UTC"),
+                n.body
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    (subject, body)
+}
@@ -0,0 +1,688 @@
+//! Sign-in with OpenID Connect identity providers (Google, Microsoft), using the
+//! authorization code flow with PKCE.
+//!
+//! The ID token comes straight from the provider's token endpoint over TLS, so as OIDC Core
+//! (3.1.3.7) allows its signature is not checked; its issuer, audience, expiry and nonce
+//! are. Users are found by `auth_provider_type` and `auth_provider_id` (the provider's
+//! subject). On first sign-in an existing user with the same email is linked, provided the
+//! provider verified the email and the user is not linked to another provider yet;
+//! otherwise a new user without a password is created. Either way the sign-in starts the
+//! same session as password login.
+
+use std::{str::FromStr, time::Duration};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use chrono::Utc;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    config,
+    error::AppError,
+    models::{auth_session::SessionTokens, dto::auth_dto::OidcCallbackQuery},
+    services::auth,
+    utils::crypto::{generate_secret, sha256_hex},
+};
+
+/// How long a user has to finish signing in at the provider.
+const LOGIN_STATE_TTL_MINUTES: i32 = 10;
+
+/// Random bytes in the state, nonce and PKCE verifier.
+const SECRET_BYTES: usize = 32;
+
+/// Clock skew tolerated on the ID token's expiry, in seconds.
+const EXPIRY_LEEWAY_SECS: i64 = 60;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Directory (tenant) ID of personal Microsoft accounts.
+const MICROSOFT_CONSUMERS_TENANT_ID: &str = "9188040d-6c67-4c5b-b112-36a304b66dad";
+
+/// Identity providers users can sign in with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OidcProvider {
+    Google,
+    Microsoft,
+}
+
+impl OidcProvider {
+    /// The provider as stored in `users.auth_provider_type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OidcProvider::Google => "GOOGLE",
+            OidcProvider::Microsoft => "MICROSOFT",
+        }
+    }
+
+    fn display_name(&self) -> &'static str {
+        match self {
+            OidcProvider::Google => "Google",
+            OidcProvider::Microsoft => "Microsoft",
+        }
+    }
+
+    /// The provider's segment of the `/auth/oidc/:provider` routes.
+    fn path_segment(&self) -> &'static str {
+        match self {
+            OidcProvider::Google => "google",
+            OidcProvider::Microsoft => "microsoft",
+        }
+    }
+}
+
+impl FromStr for OidcProvider {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "google" => Ok(OidcProvider::Google),
+            "microsoft" => Ok(OidcProvider::Microsoft),
+            other => Err(AppError::NotFound(format!(
+                "Unknown identity provider '{}'; use 'google' or 'microsoft'",
+                other
+            ))),
+        }
+    }
+}
+
+/// A configured provider's OAuth client and endpoints.
+struct ProviderClient {
+    client_id: String,
+    client_secret: String,
+    authorize_url: String,
+    token_url: String,
+    redirect_uri: String,
+    microsoft_tenant: String, // Directories whose users may sign in (MICROSOFT_TENANT)
+}
+
+impl ProviderClient {
+    fn for_provider(provider: OidcProvider) -> Result<Self, AppError> {
+        let auth = &config::get().auth;
+        let not_configured = || {
+            AppError::NotFound(format!(
+                "Sign-in with {} is not configured",
+                provider.display_name()
+            ))
+        };
+        let (client_id, client_secret, authorize_url, token_url) = match provider {
+            OidcProvider::Google => (
+                &auth.google_client_id,
+                &auth.google_client_secret,
+                "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
+                "https://oauth2.googleapis.com/token".to_string(),
+            ),
+            OidcProvider::Microsoft => (
+                &auth.microsoft_client_id,
+                &auth.microsoft_client_secret,
+                format!(
+                    "https://login.microsoftonline.com/{}/oauth2/v2.0/authorize",
+                    auth.microsoft_tenant
+                ),
+                format!(
+                    "https://login.microsoftonline.com/{}/oauth2/v2.0/token",
+                    auth.microsoft_tenant
+                ),
+            ),
+        };
+        let (Some(client_id), Some(client_secret), Some(base_url)) =
+            (client_id, client_secret, &auth.oidc_redirect_base_url)
+        else {
+            return Err(not_configured());
+        };
+
+        Ok(ProviderClient {
+            client_id: client_id.clone(),
+            client_secret: client_secret.clone(),
+            authorize_url,
+            token_url,
+            redirect_uri: format!(
+                "{}/api/v1/auth/oidc/{}/callback",
+                base_url.trim_end_matches('/'),
+                provider.path_segment()
+            ),
+            microsoft_tenant: auth.microsoft_tenant.clone(),
+        })
+    }
+}
+
+/// Claims read from an ID token.
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    aud: JsonValue, // A string or an array of strings
+    exp: i64,
+    nonce: Option<String>,
+    email: Option<String>,
+    email_verified: Option<JsonValue>, // Google sends a bool (older tokens a string)
+    given_name: Option<String>,
+    family_name: Option<String>,
+    name: Option<String>,
+    tid: Option<String>, // Microsoft directory (tenant) ID
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// Starts signing in with a provider: records a login state and returns the provider's
+/// authorization URL to send the browser to.
+pub async fn authorization_url(pool: &PgPool, provider: OidcProvider) -> Result<String, AppError> {
+    info!("Service: Starting {} sign-in", provider.as_str());
+
+    let client = ProviderClient::for_provider(provider)?;
+    let state = generate_secret(SECRET_BYTES);
+    let nonce = generate_secret(SECRET_BYTES);
+    let code_verifier = generate_secret(SECRET_BYTES);
+    let code_challenge = BASE64_URL.encode(Sha256::digest(code_verifier.as_bytes()));
+
+    // Abandoned sign-ins are cleared as new ones start
+    sqlx::query!("DELETE FROM oidc_login_states WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO oidc_login_states (state_hash, provider, nonce, code_verifier, expires_at)
+        VALUES ($1, $2, $3, $4, NOW() + make_interval(mins => $5))
+        "#,
+        sha256_hex(state.as_bytes()),
+        provider.as_str(),
+        nonce,
+        code_verifier,
+        LOGIN_STATE_TTL_MINUTES
+    )
+    .execute(pool)
+    .await?;
+
+    let mut url = Url::parse(&client.authorize_url)
+        .map_err(|e| AppError::InternalServerError(format!("Invalid authorize URL: {}", e)))?;
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &client.client_id)
+        .append_pair("redirect_uri", &client.redirect_uri)
+        .append_pair("scope", "openid email profile")
+        .append_pair("state", &state)
+        .append_pair("nonce", &nonce)
+        .append_pair("code_challenge", &code_challenge)
+        .append_pair("code_challenge_method", "S256");
+    Ok(url.to_string())
+}
+
+/// Completes signing in when the provider redirects back: checks the state, exchanges the
+/// code for an ID token, finds, links or creates the user and starts a session.
+pub async fn complete_login(
+    pool: &PgPool,
+    provider: OidcProvider,
+    query: OidcCallbackQuery,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+) -> Result<SessionTokens, AppError> {
+    info!("Service: Completing {} sign-in", provider.as_str());
+
+    if let Some(error) = query.error {
+        return Err(AppError::Unauthorized(format!(
+            "{} sign-in failed: {}",
+            provider.display_name(),
+            query.error_description.unwrap_or(error)
+        )));
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Err(AppError::Validation(
+            "The callback needs both code and state".to_string(),
+        ));
+    };
+
+    // A state is used once, whatever the outcome
+    let login_state = sqlx::query!(
+        r#"
+        DELETE FROM oidc_login_states
+        WHERE state_hash = $1 AND provider = $2
+        RETURNING nonce, code_verifier, (expires_at <= NOW()) as "expired!"
+        "#,
+        sha256_hex(state.as_bytes()),
+        provider.as_str()
+    )
+    .fetch_optional(pool)
+    .await?
+    .filter(|login_state| !login_state.expired)
+    .ok_or_else(|| {
+        AppError::Unauthorized("The sign-in expired or was already used; try again".to_string())
+    })?;
+
+    let client = ProviderClient::for_provider(provider)?;
+    let id_token = exchange_code(&client, &code, &login_state.code_verifier).await?;
+    let claims = decode_id_token(&id_token)?;
+    verify_claims(provider, &client, &claims, &login_state.nonce)?;
+
+    let user_id = find_or_create_user(pool, provider, &claims).await?;
+    auth::start_session(pool, user_id, user_agent, ip_address).await
+}
+
+/// Exchanges an authorization code for the ID token.
+async fn exchange_code(
+    client: &ProviderClient,
+    code: &str,
+    code_verifier: &str,
+) -> Result<String, AppError> {
+    let response = Client::new()
+        .post(&client.token_url)
+        .timeout(REQUEST_TIMEOUT)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", client.redirect_uri.as_str()),
+            ("client_id", client.client_id.as_str()),
+            ("client_secret", client.client_secret.as_str()),
+            ("code_verifier", code_verifier),
+        ])
+        .send()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Token request failed: {}", e)))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        warn!("Token endpoint returned {}: {}", status, body);
+        return Err(AppError::Unauthorized(
+            "The identity provider did not accept the sign-in; try again".to_string(),
+        ));
+    }
+    let tokens: TokenResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Invalid token response: {}", e)))?;
+    tokens.id_token.ok_or_else(|| {
+        AppError::InternalServerError("The token response has no id_token".to_string())
+    })
+}
+
+/// Reads the claims of a JWT without checking its signature (see the module docs).
+fn decode_id_token(id_token: &str) -> Result<IdTokenClaims, AppError> {
+    let invalid = |reason: String| AppError::Unauthorized(format!("Invalid ID token: {}", reason));
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| invalid("not a JWT".to_string()))?;
+    let json = BASE64_URL
+        .decode(payload.trim_end_matches('='))
+        .map_err(|e| invalid(e.to_string()))?;
+    serde_json::from_slice(&json).map_err(|e| invalid(e.to_string()))
+}
+
+fn verify_claims(
+    provider: OidcProvider,
+    client: &ProviderClient,
+    claims: &IdTokenClaims,
+    nonce: &str,
+) -> Result<(), AppError> {
+    let invalid = |reason: &str| AppError::Unauthorized(format!("Invalid ID token: {}", reason));
+
+    let issuer_ok = match provider {
+        OidcProvider::Google => {
+            claims.iss == "https://accounts.google.com" || claims.iss == "accounts.google.com"
+        }
+        // Multi-tenant endpoints issue tokens from the user's own directory
+        OidcProvider::Microsoft => {
+            let tid = claims.tid.as_deref().ok_or_else(|| invalid("no tid"))?;
+            let tenant_ok = match client.microsoft_tenant.as_str() {
+                "common" => true,
+                "consumers" => tid == MICROSOFT_CONSUMERS_TENANT_ID,
+                "organizations" => tid != MICROSOFT_CONSUMERS_TENANT_ID,
+                tenant => tid.eq_ignore_ascii_case(tenant),
+            };
+            tenant_ok && claims.iss == format!("https://login.microsoftonline.com/{}/v2.0", tid)
+        }
+    };
+    if !issuer_ok {
+        return Err(invalid("unexpected issuer"));
+    }
+
+    let audience_ok = match &claims.aud {
+        JsonValue::String(aud) => *aud == client.client_id,
+        JsonValue::Array(auds) => auds
+            .iter()
+            .any(|aud| aud.as_str() == Some(&client.client_id)),
+        _ => false,
+    };
+    if !audience_ok {
+        return Err(invalid("issued to another client"));
+    }
+    if claims.exp + EXPIRY_LEEWAY_SECS < Utc::now().timestamp() {
+        return Err(invalid("expired"));
+    }
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(invalid("nonce mismatch"));
+    }
+    Ok(())
+}
+
+/// The user signing in: already linked to this provider subject, an existing user with
+/// the same verified email (linked now), or a new user.
+async fn find_or_create_user(
+    pool: &PgPool,
+    provider: OidcProvider,
+    claims: &IdTokenClaims,
+) -> Result<Uuid, AppError> {
+    let linked = sqlx::query!(
+        "SELECT id, is_active FROM users WHERE auth_provider_type = $1 AND auth_provider_id = $2",
+        provider.as_str(),
+        claims.sub
+    )
+    .fetch_optional(pool)
+    .await?;
+    if let Some(user) = linked {
+        if !user.is_active {
+            return Err(AppError::Unauthorized(
+                "This account is disabled".to_string(),
+            ));
+        }
+        return Ok(user.id);
+    }
+
+    let email = claims
+        .email
+        .as_deref()
+        .map(str::trim)
+        .filter(|email| email.contains('@'))
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "{} did not share an email address for this account",
+                provider.display_name()
+            ))
+        })?;
+    let email_verified = claims.email_verified.as_ref().and_then(verified_flag) == Some(true);
+
+    let existing = sqlx::query!(
+        "SELECT id, auth_provider_type, is_active FROM users WHERE email = $1",
+        email
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(user) = existing {
+        if !email_verified {
+            return Err(AppError::Conflict(format!(
+                "An account with {} already exists and {} has not verified this email; sign in with your password",
+                email,
+                provider.display_name()
+            )));
+        }
+        if OidcProvider::from_str(&user.auth_provider_type).is_ok() {
+            return Err(AppError::Conflict(format!(
+                "The account with {} signs in with {}",
+                email,
+                user.auth_provider_type.to_lowercase()
+            )));
+        }
+        if !user.is_active {
+            return Err(AppError::Unauthorized(
+                "This account is disabled".to_string(),
+            ));
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET auth_provider_type = $2, auth_provider_id = $3, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            user.id,
+            provider.as_str(),
+            claims.sub
+        )
+        .execute(pool)
+        .await?;
+        info!(
+            "Service: Linked user ID: {} to {}",
+            user.id,
+            provider.as_str()
+        );
+        return Ok(user.id);
+    }
+
+    let (first_name, last_name) = names(claims, email);
+    let user_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name, is_active)
+        VALUES ($1, $2, $3, $4, $5, TRUE)
+        RETURNING id
+        "#,
+        claims.sub,
+        provider.as_str(),
+        email,
+        first_name,
+        last_name
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => AppError::Conflict(format!(
+            "An account with {} was created at the same time; try again",
+            email
+        )),
+        e => e.into(),
+    })?;
+    info!(
+        "Service: Created user ID: {} from {} sign-in",
+        user_id,
+        provider.as_str()
+    );
+
+    Ok(user_id)
+}
+
+/// Whether an `email_verified` claim says yes.
+fn verified_flag(value: &JsonValue) -> Option<bool> {
+    match value {
+        JsonValue::Bool(verified) => Some(*verified),
+        JsonValue::String(verified) => verified.parse().ok(),
+        _ => None,
+    }
+}
+
+/// First and last name from the token, falling back to the full name and then the email.
+fn names(claims: &IdTokenClaims, email: &str) -> (String, String) {
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    if let (Some(first), Some(last)) = (
+        non_empty(&claims.given_name),
+        non_empty(&claims.family_name),
+    ) {
+        return (first, last);
+    }
+    if let Some(name) = non_empty(&claims.name) {
+        return match name.rsplit_once(' ') {
+            Some((first, last)) => (first.trim().to_string(), last.to_string()),
+            None => (name, String::new()),
+        };
+    }
+    let local_part = email.split('@').next().unwrap_or(email);
+    (local_part.to_string(), String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const CLIENT_ID: &str = "forge-client";
+    const NONCE: &str = "expected-nonce";
+    const DIRECTORY: &str = "3f5c8a1e-8d1b-4c2a-9f0e-2b7d6c4a1e90";
+
+    fn client(microsoft_tenant: &str) -> ProviderClient {
+        ProviderClient {
+            client_id: CLIENT_ID.to_string(),
+            client_secret: "secret".to_string(),
+            authorize_url: "https://idp.example.com/authorize".to_string(),
+            token_url: "https://idp.example.com/token".to_string(),
+            redirect_uri: "https://forge.example.com/callback".to_string(),
+            microsoft_tenant: microsoft_tenant.to_string(),
+        }
+    }
+
+    /// An unsigned JWT carrying `claims`, as the token endpoint would return it.
+    fn id_token(claims: &JsonValue) -> String {
+        let encode = |value: &JsonValue| BASE64_URL.encode(value.to_string());
+        format!(
+            "{}.{}.signature",
+            encode(&json!({ "alg": "RS256", "typ": "JWT" })),
+            encode(claims)
+        )
+    }
+
+    fn google_claims() -> JsonValue {
+        json!({
+            "iss": "https://accounts.google.com",
+            "sub": "google-subject",
+            "aud": CLIENT_ID,
+            "exp": Utc::now().timestamp() + 300,
+            "nonce": NONCE,
+            "email": "ada@example.com",
+            "email_verified": true,
+        })
+    }
+
+    fn microsoft_claims(tid: &str) -> JsonValue {
+        json!({
+            "iss": format!("https://login.microsoftonline.com/{}/v2.0", tid),
+            "sub": "microsoft-subject",
+            "aud": CLIENT_ID,
+            "exp": Utc::now().timestamp() + 300,
+            "nonce": NONCE,
+            "tid": tid,
+        })
+    }
+
+    /// Decodes and verifies `claims`; the rejection reason on failure.
+    fn check(
+        provider: OidcProvider,
+        microsoft_tenant: &str,
+        claims: JsonValue,
+    ) -> Result<(), String> {
+        let claims = decode_id_token(&id_token(&claims)).map_err(|e| e.to_string())?;
+        verify_claims(provider, &client(microsoft_tenant), &claims, NONCE)
+            .map_err(|e| e.to_string())
+    }
+
+    fn with(mut claims: JsonValue, key: &str, value: JsonValue) -> JsonValue {
+        claims[key] = value;
+        claims
+    }
+
+    #[test]
+    fn accepts_valid_tokens() {
+        assert_eq!(
+            check(OidcProvider::Google, "common", google_claims()),
+            Ok(())
+        );
+        let bare_issuer = with(google_claims(), "iss", json!("accounts.google.com"));
+        assert_eq!(check(OidcProvider::Google, "common", bare_issuer), Ok(()));
+        for tenant in ["common", "organizations", DIRECTORY] {
+            assert_eq!(
+                check(OidcProvider::Microsoft, tenant, microsoft_claims(DIRECTORY)),
+                Ok(()),
+                "{}",
+                tenant
+            );
+        }
+        let consumer = microsoft_claims(MICROSOFT_CONSUMERS_TENANT_ID);
+        assert_eq!(
+            check(OidcProvider::Microsoft, "consumers", consumer),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_a_wrong_issuer() {
+        let claims = with(
+            google_claims(),
+            "iss",
+            json!("https://accounts.example.com"),
+        );
+        let err = check(OidcProvider::Google, "common", claims).unwrap_err();
+        assert!(err.contains("unexpected issuer"), "{}", err);
+        // A Google token presented to the Microsoft callback
+        let err = check(OidcProvider::Microsoft, "common", google_claims()).unwrap_err();
+        assert!(err.contains("no tid"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_a_microsoft_directory_mismatch() {
+        let other = "0b6f4c2d-1a3e-4f5b-8c7d-9e0f1a2b3c4d";
+        let cases = [
+            // The issuer names another directory than tid
+            (
+                "common",
+                with(microsoft_claims(DIRECTORY), "tid", json!(other)),
+            ),
+            // Signed in to a directory other than the configured one
+            (DIRECTORY, microsoft_claims(other)),
+            ("consumers", microsoft_claims(DIRECTORY)),
+            (
+                "organizations",
+                microsoft_claims(MICROSOFT_CONSUMERS_TENANT_ID),
+            ),
+        ];
+        for (tenant, claims) in cases {
+            let err = check(OidcProvider::Microsoft, tenant, claims).unwrap_err();
+            assert!(err.contains("unexpected issuer"), "{}: {}", tenant, err);
+        }
+    }
+
+    #[test]
+    fn rejects_an_audience_without_the_client() {
+        let claims = with(
+            google_claims(),
+            "aud",
+            json!(["other-client", "third-client"]),
+        );
+        let err = check(OidcProvider::Google, "common", claims).unwrap_err();
+        assert!(err.contains("issued to another client"), "{}", err);
+        let claims = with(google_claims(), "aud", json!(["other-client", CLIENT_ID]));
+        assert_eq!(check(OidcProvider::Google, "common", claims), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_token_expired_beyond_the_leeway() {
+        let now = Utc::now().timestamp();
+        let claims = with(google_claims(), "exp", json!(now - EXPIRY_LEEWAY_SECS - 10));
+        let err = check(OidcProvider::Google, "common", claims).unwrap_err();
+        assert!(err.contains("expired"), "{}", err);
+        let within_leeway = with(google_claims(), "exp", json!(now - EXPIRY_LEEWAY_SECS / 2));
+        assert_eq!(check(OidcProvider::Google, "common", within_leeway), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_missing_or_mismatched_nonce() {
+        let mut missing = google_claims();
+        missing.as_object_mut().unwrap().remove("nonce");
+        for claims in [
+            missing,
+            with(google_claims(), "nonce", json!("replayed-nonce")),
+        ] {
+            let err = check(OidcProvider::Google, "common", claims).unwrap_err();
+            assert!(err.contains("nonce mismatch"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        assert!(decode_id_token("not-a-jwt").is_err());
+        assert!(decode_id_token("header.!!!.signature").is_err());
+        let no_subject =
+            BASE64_URL.encode(json!({ "iss": "https://accounts.google.com" }).to_string());
+        assert!(decode_id_token(&format!("header.{}.signature", no_subject)).is_err());
+    }
+}
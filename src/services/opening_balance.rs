@@ -0,0 +1,251 @@
+//! Opening balances: the balances carried over from a previous system, booked at the
+//! cutover date as one OPENING_BALANCE transaction.
+//!
+//! Each balance is given in its account's currency, positive on the account's normal side.
+//! Balances that do not net to zero are offset on an equity account (typically "Opening
+//! Balance Equity"), in the tenant's base currency, so the transaction always balances.
+//! A tenant has at most one opening balance transaction that is not voided; to correct it,
+//! void it and record the balances again.
+
+use std::collections::{HashMap, HashSet};
+
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        account_type::AccountNormalBalance,
+        dto::journal_entry_dto::CreateJournalEntryDto,
+        dto::opening_balance_dto::CreateOpeningBalancesDto,
+        dto::transaction_dto::CreateTransactionDto,
+        journal_entry::JournalEntryType,
+        transaction::{Transaction, TransactionStatus, TransactionType},
+    },
+    services::{currency_conversion, transaction},
+};
+
+/// Decimal places of journal entry amounts.
+const AMOUNT_SCALE: u32 = 2;
+
+/// Name of the account type the offsetting account must have.
+const EQUITY_ACCOUNT_TYPE: &str = "Equity";
+
+/// Books the opening balances as of the cutover date, posted unless another status is given.
+pub async fn create_opening_balances(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateOpeningBalancesDto,
+) -> Result<Transaction, AppError> {
+    info!(
+        "Service: Recording {} opening balances as of {} for tenant ID {}",
+        dto.balances.len(),
+        dto.as_of,
+        tenant_id
+    );
+
+    let existing = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM transactions
+        WHERE tenant_id = $1 AND type = 'OPENING_BALANCE' AND status <> 'VOIDED'
+        LIMIT 1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    if let Some(existing) = existing {
+        return Err(AppError::Conflict(format!(
+            "Opening balances were already recorded in transaction {}; void it to record them again",
+            existing
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    for (index, line) in dto.balances.iter().enumerate() {
+        if line.account_id == dto.equity_account_id {
+            return Err(AppError::Validation(format!(
+                "balances[{}] is the equity account, which takes the difference",
+                index
+            )));
+        }
+        if !seen.insert(line.account_id) {
+            return Err(AppError::Validation(format!(
+                "balances[{}] repeats account {}",
+                index, line.account_id
+            )));
+        }
+        if line.amount.round_dp(AMOUNT_SCALE) != line.amount {
+            return Err(AppError::Validation(format!(
+                "balances[{}] must have at most two decimals",
+                index
+            )));
+        }
+    }
+
+    let account_ids: Vec<Uuid> = dto
+        .balances
+        .iter()
+        .map(|line| line.account_id)
+        .chain([dto.equity_account_id])
+        .collect();
+    let accounts: HashMap<Uuid, (String, AccountNormalBalance, String)> = sqlx::query!(
+        r#"
+        SELECT a.id, a.currency_code, at.normal_balance as "normal_balance!: AccountNormalBalance",
+               at.name as type_name
+        FROM accounts a
+        JOIN account_types at ON at.id = a.account_type_id
+        WHERE a.tenant_id = $1 AND a.id = ANY($2) AND a.is_active = TRUE AND a.archived_at IS NULL
+        "#,
+        tenant_id,
+        &account_ids
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| {
+        (
+            row.id,
+            (row.currency_code, row.normal_balance, row.type_name),
+        )
+    })
+    .collect();
+    let account = |id: &Uuid| {
+        accounts.get(id).ok_or_else(|| {
+            AppError::Validation(format!(
+                "Account ID {} is invalid, inactive or archived for tenant {}",
+                id, tenant_id
+            ))
+        })
+    };
+
+    let (equity_currency, _, equity_type) = account(&dto.equity_account_id)?;
+    if !equity_type.eq_ignore_ascii_case(EQUITY_ACCOUNT_TYPE) {
+        return Err(AppError::Validation(format!(
+            "Account {} is not an equity account",
+            dto.equity_account_id
+        )));
+    }
+    if currency_conversion::base_currency_rate(pool, tenant_id, equity_currency, dto.as_of)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Validation(
+            "The equity account must be in the tenant's base currency".to_string(),
+        ));
+    }
+
+    // Foreign balances are valued at the cutover date's rate, once per currency
+    let mut base_rates: HashMap<String, Option<Decimal>> = HashMap::new();
+    let mut journal_entries = Vec::with_capacity(dto.balances.len() + 1);
+    let mut net_debit = Decimal::ZERO; // In the base currency
+    for line in dto
+        .balances
+        .into_iter()
+        .filter(|line| !line.amount.is_zero())
+    {
+        let (currency_code, normal_balance, _) = account(&line.account_id)?;
+        let normal_side = match normal_balance {
+            AccountNormalBalance::DEBIT => JournalEntryType::Debit,
+            AccountNormalBalance::CREDIT => JournalEntryType::Credit,
+        };
+        let entry_type = match (normal_side, line.amount.is_sign_positive()) {
+            (side, true) => side,
+            (JournalEntryType::Debit, false) => JournalEntryType::Credit,
+            (JournalEntryType::Credit, false) => JournalEntryType::Debit,
+        };
+
+        let base_rate = match base_rates.get(currency_code) {
+            Some(rate) => *rate,
+            None => {
+                let rate = currency_conversion::base_currency_rate(
+                    pool,
+                    tenant_id,
+                    currency_code,
+                    dto.as_of,
+                )
+                .await?;
+                base_rates.insert(currency_code.to_string(), rate);
+                rate
+            }
+        };
+        let amount = line.amount.abs();
+        let (exchange_rate, converted_amount) =
+            currency_conversion::amounts_with_rate(amount, None, None, base_rate);
+        let base_amount = converted_amount.unwrap_or(amount);
+        net_debit += match entry_type {
+            JournalEntryType::Debit => base_amount,
+            JournalEntryType::Credit => -base_amount,
+        };
+
+        journal_entries.push(CreateJournalEntryDto {
+            account_id: line.account_id,
+            entry_type,
+            amount,
+            currency_code: currency_code.to_string(),
+            exchange_rate,
+            converted_amount,
+            memo: line.memo,
+            project_id: None,
+            class_id: None,
+            location_id: None,
+        });
+    }
+    if journal_entries.is_empty() {
+        return Err(AppError::Validation(
+            "At least one balance must be non-zero".to_string(),
+        ));
+    }
+
+    // The difference goes to equity, so debits equal credits
+    if !net_debit.is_zero() {
+        journal_entries.push(CreateJournalEntryDto {
+            account_id: dto.equity_account_id,
+            entry_type: if net_debit.is_sign_positive() {
+                JournalEntryType::Credit
+            } else {
+                JournalEntryType::Debit
+            },
+            amount: net_debit.abs(),
+            currency_code: equity_currency.to_string(),
+            exchange_rate: None,
+            converted_amount: None,
+            memo: None,
+            project_id: None,
+            class_id: None,
+            location_id: None,
+        });
+    }
+    let total_debits: Decimal = journal_entries
+        .iter()
+        .filter(|entry| entry.entry_type == JournalEntryType::Debit)
+        .map(|entry| entry.converted_amount.unwrap_or(entry.amount))
+        .sum();
+
+    let create = CreateTransactionDto {
+        transaction_date: dto.as_of,
+        description: dto
+            .description
+            .unwrap_or_else(|| "Opening balances".to_string()),
+        r#type: TransactionType::OpeningBalance,
+        category_id: None,
+        payee_id: None,
+        tags: None,
+        amount: total_debits,
+        currency_code: equity_currency.to_string(),
+        is_reconciled: None,
+        reconciliation_date: None,
+        notes: None,
+        source_document_url: None,
+        status: Some(dto.status.unwrap_or(TransactionStatus::Posted)),
+        journal_entries,
+        payment_account_id: None,
+        splits: Vec::new(),
+        metadata: None,
+        attribution: None,
+    };
+    transaction::create_transaction(pool, tenant_id, created_by_user_id, create).await
+}
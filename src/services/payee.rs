@@ -0,0 +1,407 @@
+//! Payees: who a transaction was paid to or received from.
+//!
+//! A transaction's description stays free text; its optional payee is what spend reports
+//! group by and what payee-keyed merchant rules categorize on. Duplicates (e.g. "Amazon" and
+//! "Amazon.com") are folded together with a merge, which repoints the source's transactions
+//! and rules at the target and deletes the source.
+
+use chrono::{Datelike, Utc};
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::payee_dto::{CreatePayeeDto, ListPayeesQuery, PayeeSpendingQuery, UpdatePayeeDto},
+        payee::{Payee, PayeeMergeResult, PayeeSpending, PayeeSpendingRow},
+    },
+    services::fiscal_period,
+    utils::update_builder::UpdateBuilder,
+};
+
+/// Retrieves the tenant's payees by name, optionally filtered by a name fragment.
+pub async fn list_payees(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: ListPayeesQuery,
+) -> Result<Vec<Payee>, AppError> {
+    info!("Service: Listing payees for tenant ID: {}", tenant_id);
+
+    let term = query
+        .q
+        .as_deref()
+        .map(str::trim)
+        .filter(|term| !term.is_empty());
+    let payees = query_as!(
+        Payee,
+        r#"
+        SELECT id, tenant_id, name, notes, created_at, created_by, updated_at, updated_by
+        FROM payees
+        WHERE tenant_id = $1 AND ($2::text IS NULL OR name ILIKE '%' || $2 || '%')
+        ORDER BY LOWER(name)
+        "#,
+        tenant_id,
+        term
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(payees)
+}
+
+/// Retrieves a single payee by ID for a specific tenant.
+pub async fn get_payee(pool: &PgPool, tenant_id: Uuid, payee_id: Uuid) -> Result<Payee, AppError> {
+    info!(
+        "Service: Getting payee with ID: {} for tenant ID: {}",
+        payee_id, tenant_id
+    );
+
+    let payee = query_as!(
+        Payee,
+        r#"
+        SELECT id, tenant_id, name, notes, created_at, created_by, updated_at, updated_by
+        FROM payees
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        payee_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| not_found(tenant_id, payee_id))?;
+
+    Ok(payee)
+}
+
+/// Creates a payee. Names are unique per tenant, ignoring case.
+pub async fn create_payee(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreatePayeeDto,
+) -> Result<Payee, AppError> {
+    info!(
+        "Service: Creating payee '{}' for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    let name = payee_name(&dto.name)?;
+    let payee = query_as!(
+        Payee,
+        r#"
+        INSERT INTO payees (tenant_id, name, notes, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $4)
+        RETURNING id, tenant_id, name, notes, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        name,
+        dto.notes,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| name_conflict(e, &name))?;
+
+    Ok(payee)
+}
+
+/// Renames or annotates a payee. Linked transactions follow, since they only hold its ID.
+pub async fn update_payee(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    payee_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdatePayeeDto,
+) -> Result<Payee, AppError> {
+    info!(
+        "Service: Updating payee with ID: {} for tenant ID: {}",
+        payee_id, tenant_id
+    );
+
+    let name = dto.name.as_deref().map(payee_name).transpose()?;
+    let mut update = UpdateBuilder::new("payees");
+    update.set("name", name.clone()).set("notes", dto.notes);
+    if update.is_empty() {
+        return Err(AppError::Validation(
+            "No fields provided for update".to_string(),
+        ));
+    }
+
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(payee_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(
+        r#"
+        RETURNING id, tenant_id, name, notes, created_at, created_by, updated_at, updated_by
+        "#,
+    );
+
+    let payee = query
+        .build_query_as::<Payee>()
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| name_conflict(e, name.as_deref().unwrap_or_default()))?
+        .ok_or_else(|| not_found(tenant_id, payee_id))?;
+
+    Ok(payee)
+}
+
+/// Deletes a payee along with its merchant rule. Refused while transactions reference it;
+/// merge it into another payee instead.
+pub async fn delete_payee(pool: &PgPool, tenant_id: Uuid, payee_id: Uuid) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting payee with ID: {} for tenant ID: {}",
+        payee_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+    sqlx::query!(
+        "SELECT id FROM payees WHERE id = $1 AND tenant_id = $2 FOR UPDATE",
+        payee_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| not_found(tenant_id, payee_id))?;
+
+    let transaction_count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM transactions WHERE tenant_id = $1 AND payee_id = $2"#,
+        tenant_id,
+        payee_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    if transaction_count > 0 {
+        return Err(AppError::Conflict(format!(
+            "Payee {} is used by {} transactions; merge it into another payee instead",
+            payee_id, transaction_count
+        )));
+    }
+
+    sqlx::query!(
+        "DELETE FROM merchant_rules WHERE tenant_id = $1 AND payee_id = $2",
+        tenant_id,
+        payee_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM payees WHERE id = $1 AND tenant_id = $2",
+        payee_id,
+        tenant_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    db_tx.commit().await?;
+
+    Ok(())
+}
+
+/// Merges a payee into another: its transactions and merchant rule move to the target and
+/// the source is deleted. When both have a rule, the target's is kept.
+pub async fn merge_payee(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    source_id: Uuid,
+    target_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<PayeeMergeResult, AppError> {
+    info!(
+        "Service: Merging payee ID: {} into payee ID: {} for tenant ID: {}",
+        source_id, target_id, tenant_id
+    );
+
+    if source_id == target_id {
+        return Err(AppError::Validation(
+            "A payee cannot be merged into itself".to_string(),
+        ));
+    }
+
+    let mut db_tx = pool.begin().await?;
+    let payees = query_as!(
+        Payee,
+        r#"
+        SELECT id, tenant_id, name, notes, created_at, created_by, updated_at, updated_by
+        FROM payees
+        WHERE tenant_id = $1 AND id = ANY($2)
+        ORDER BY id
+        FOR UPDATE
+        "#,
+        tenant_id,
+        &[source_id, target_id][..]
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    if !payees.iter().any(|payee| payee.id == source_id) {
+        return Err(not_found(tenant_id, source_id));
+    }
+    let target = payees
+        .into_iter()
+        .find(|payee| payee.id == target_id)
+        .ok_or_else(|| not_found(tenant_id, target_id))?;
+
+    // Changing a transaction's payee is a change to it, so closed periods apply
+    let earliest_closed_date = sqlx::query_scalar!(
+        r#"
+        SELECT MIN(t.transaction_date)
+        FROM transactions t
+        JOIN fiscal_periods fp
+            ON fp.tenant_id = t.tenant_id
+            AND fp.status = 'CLOSED'
+            AND t.transaction_date BETWEEN fp.start_date AND fp.end_date
+        WHERE t.tenant_id = $1 AND t.payee_id = $2
+        "#,
+        tenant_id,
+        source_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+    if let Some(date) = earliest_closed_date {
+        fiscal_period::ensure_date_open(&mut *db_tx, tenant_id, updated_by_user_id, date).await?;
+    }
+
+    let transactions_moved = sqlx::query!(
+        r#"
+        UPDATE transactions
+        SET payee_id = $3, updated_at = NOW(), updated_by = $4, version = version + 1
+        WHERE tenant_id = $1 AND payee_id = $2
+        "#,
+        tenant_id,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    // A payee has at most one rule, so the source's is dropped when the target has one
+    let merchant_rules_dropped = sqlx::query!(
+        r#"
+        DELETE FROM merchant_rules
+        WHERE tenant_id = $1
+            AND payee_id = $2
+            AND EXISTS(SELECT 1 FROM merchant_rules t WHERE t.tenant_id = $1 AND t.payee_id = $3)
+        "#,
+        tenant_id,
+        source_id,
+        target_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+    let merchant_rules_moved = sqlx::query!(
+        r#"
+        UPDATE merchant_rules
+        SET payee_id = $3, updated_at = NOW(), updated_by = $4
+        WHERE tenant_id = $1 AND payee_id = $2
+        "#,
+        tenant_id,
+        source_id,
+        target_id,
+        updated_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query!(
+        "DELETE FROM payees WHERE id = $1 AND tenant_id = $2",
+        source_id,
+        tenant_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+    db_tx.commit().await?;
+
+    info!(
+        "Service: Merged payee ID: {} into {}: {} transactions moved",
+        source_id, target_id, transactions_moved
+    );
+    Ok(PayeeMergeResult {
+        target,
+        transactions_moved,
+        merchant_rules_moved,
+        merchant_rules_dropped,
+    })
+}
+
+/// Expenses over a period per payee and currency. Voided and reversed transactions are left
+/// out, as in the other spending reports.
+pub async fn payee_spending(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: PayeeSpendingQuery,
+) -> Result<PayeeSpending, AppError> {
+    info!("Service: Spending by payee for tenant ID: {}", tenant_id);
+
+    let to_date = query.to_date.unwrap_or_else(|| Utc::now().date_naive());
+    let from_date = query
+        .from_date
+        .unwrap_or_else(|| to_date.with_day(1).expect("day 1 is always valid"));
+    if from_date > to_date {
+        return Err(AppError::Validation(
+            "from_date must not be after to_date".to_string(),
+        ));
+    }
+
+    let payees = sqlx::query_as!(
+        PayeeSpendingRow,
+        r#"
+        SELECT
+            t.payee_id as "payee_id?",
+            p.name as "payee_name?",
+            t.currency_code,
+            COUNT(*) as "transaction_count!",
+            SUM(t.amount) as "total!"
+        FROM transactions t
+        LEFT JOIN payees p ON p.id = t.payee_id
+        WHERE t.tenant_id = $1
+          AND t.type = 'EXPENSE'
+          AND t.status <> 'VOIDED'
+          AND t.reversal_of_id IS NULL
+          AND t.reversed_by_id IS NULL
+          AND t.transaction_date BETWEEN $2 AND $3
+        GROUP BY t.payee_id, p.name, t.currency_code
+        ORDER BY t.currency_code, SUM(t.amount) DESC, p.name NULLS LAST
+        "#,
+        tenant_id,
+        from_date,
+        to_date
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(PayeeSpending {
+        from_date,
+        to_date,
+        payees,
+    })
+}
+
+fn payee_name(raw: &str) -> Result<String, AppError> {
+    let name = raw.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation(
+            "Payee name must not be blank".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+fn name_conflict(e: sqlx::Error, name: &str) -> AppError {
+    match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            AppError::Conflict(format!("A payee named '{}' already exists", name))
+        }
+        e => e.into(),
+    }
+}
+
+fn not_found(tenant_id: Uuid, payee_id: Uuid) -> AppError {
+    AppError::NotFound(format!(
+        "Payee with ID {} not found for tenant {}",
+        payee_id, tenant_id
+    ))
+}
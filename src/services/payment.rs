@@ -0,0 +1,987 @@
+//! Payments allocated across several invoices or bills at once.
+//!
+//! A payment RECEIVED from a customer debits the account it was deposited to and credits
+//! the receivable account of each invoice it pays; one SENT to a vendor mirrors that against
+//! payable. Whatever is not allocated is an overpayment: it is booked to the payment's
+//! credit account (customer deposits, vendor prepayments) and kept on the payment as
+//! `unapplied_amount`, to be applied to later invoices or bills. Each allocation is an
+//! invoice or bill payment row linked to the payment, so invoices and bills show it like
+//! any other payment. A payment and all its allocations post as one transaction, or not
+//! at all.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgExecutor, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::{begin_financial, with_retry},
+    error::AppError,
+    models::{
+        dto::journal_entry_dto::CreateJournalEntryDto,
+        dto::payment_dto::{
+            ApplyPaymentCreditDto, CreatePaymentDto, ListPaymentsQuery, PaymentAllocationDto,
+        },
+        dto::transaction_dto::CreateTransactionDto,
+        journal_entry::JournalEntryType,
+        payment::{Payment, PaymentAllocation, PaymentDetail, PaymentDirection},
+        transaction::{Transaction, TransactionStatus, TransactionType},
+    },
+    services::{
+        domain_event::{self, DomainEvent},
+        invoice::check_accounts,
+        transaction,
+    },
+};
+
+/// Decimal places of payment amounts.
+const AMOUNT_SCALE: u32 = 2;
+
+/// An invoice or bill that can still be paid, locked for the allocation.
+struct OpenDocument {
+    party_id: Uuid, // Customer or vendor
+    number: Option<String>,
+    status: String,
+    issue_date: NaiveDate,
+    currency_code: String,
+    control_account_id: Uuid, // Receivable or payable
+    outstanding: Decimal,
+}
+
+/// Retrieves the tenant's payments matching `query`, most recent first.
+pub async fn list_payments(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: ListPaymentsQuery,
+) -> Result<Vec<Payment>, AppError> {
+    info!("Service: Listing payments for tenant ID: {}", tenant_id);
+
+    let payments = query_as!(
+        Payment,
+        r#"
+        SELECT id, tenant_id, direction, customer_id, vendor_id, payment_date, amount,
+               currency_code, account_id, credit_account_id, unapplied_amount, transaction_id,
+               memo, created_at, created_by, updated_at, updated_by
+        FROM payments
+        WHERE tenant_id = $1
+          AND ($2::text IS NULL OR direction = $2)
+          AND ($3::uuid IS NULL OR customer_id = $3)
+          AND ($4::uuid IS NULL OR vendor_id = $4)
+          AND (NOT $5 OR unapplied_amount > 0)
+        ORDER BY payment_date DESC, created_at DESC
+        "#,
+        tenant_id,
+        query.direction.map(String::from),
+        query.customer_id,
+        query.vendor_id,
+        query.with_credit.unwrap_or(false)
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(payments)
+}
+
+/// Retrieves a payment with its allocations.
+pub async fn get_payment(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    payment_id: Uuid,
+) -> Result<PaymentDetail, AppError> {
+    info!(
+        "Service: Getting payment with ID: {} for tenant ID: {}",
+        payment_id, tenant_id
+    );
+
+    let payment = query_as!(
+        Payment,
+        r#"
+        SELECT id, tenant_id, direction, customer_id, vendor_id, payment_date, amount,
+               currency_code, account_id, credit_account_id, unapplied_amount, transaction_id,
+               memo, created_at, created_by, updated_at, updated_by
+        FROM payments
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        payment_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| not_found(payment_id, tenant_id))?;
+
+    let allocations = query_as!(
+        PaymentAllocation,
+        r#"
+        SELECT ip.id as "id!", ip.invoice_id as "document_id!", i.invoice_number as document_number,
+               ip.amount as "amount!", ip.payment_date as "applied_on!", ip.transaction_id as "transaction_id!"
+        FROM invoice_payments ip
+        JOIN invoices i ON i.id = ip.invoice_id
+        WHERE ip.payment_id = $1
+        UNION ALL
+        SELECT bp.id, bp.bill_id, b.bill_number, bp.amount, bp.payment_date, bp.transaction_id
+        FROM bill_payments bp
+        JOIN bills b ON b.id = bp.bill_id
+        WHERE bp.payment_id = $1
+        ORDER BY 5, 1
+        "#,
+        payment_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(PaymentDetail {
+        payment,
+        allocations,
+    })
+}
+
+/// Records a payment and allocates it. Allocations are applied in the order given; what is
+/// left over stays on the payment as a credit.
+pub async fn create_payment(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: CreatePaymentDto,
+) -> Result<PaymentDetail, AppError> {
+    info!(
+        "Service: Recording {:?} payment of {} with {} allocations for tenant ID: {}",
+        dto.direction,
+        dto.amount,
+        dto.allocations.len(),
+        tenant_id
+    );
+
+    if dto.amount <= Decimal::ZERO {
+        return Err(AppError::Validation(
+            "amount must be greater than zero".to_string(),
+        ));
+    }
+    if dto.amount.round_dp(AMOUNT_SCALE) != dto.amount {
+        return Err(AppError::Validation(
+            "amount must have at most two decimals".to_string(),
+        ));
+    }
+    match dto.direction {
+        PaymentDirection::Received if dto.customer_id.is_none() || dto.vendor_id.is_some() => {
+            return Err(AppError::Validation(
+                "A RECEIVED payment needs a customer_id and no vendor_id".to_string(),
+            ))
+        }
+        PaymentDirection::Sent if dto.vendor_id.is_none() || dto.customer_id.is_some() => {
+            return Err(AppError::Validation(
+                "A SENT payment needs a vendor_id and no customer_id".to_string(),
+            ))
+        }
+        _ => {}
+    }
+    if dto.credit_account_id == Some(dto.account_id) {
+        return Err(AppError::Validation(
+            "The credit account cannot be the account the payment went through".to_string(),
+        ));
+    }
+    check_allocation_amounts(&dto.allocations)?;
+
+    let payment_id = with_retry(pool, |pool| {
+        create_payment_once(pool, tenant_id, user_id, &dto)
+    })
+    .await?;
+    get_payment(pool, tenant_id, payment_id).await
+}
+
+/// One attempt at recording a payment, in its own database transaction.
+async fn create_payment_once(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: &CreatePaymentDto,
+) -> Result<Uuid, AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let party_id = dto.customer_id.or(dto.vendor_id).unwrap_or_default();
+    check_party(&mut *db_tx, tenant_id, dto.direction, party_id).await?;
+    check_accounts(
+        &mut *db_tx,
+        tenant_id,
+        [dto.account_id].into_iter().chain(dto.credit_account_id),
+    )
+    .await?;
+    let currency_code = dto.currency_code.to_uppercase();
+    let documents = lock_documents(&mut db_tx, tenant_id, dto.direction, &dto.allocations).await?;
+    let allocations = allocate(
+        dto.direction,
+        party_id,
+        &currency_code,
+        dto.payment_date,
+        &documents,
+        &dto.allocations,
+        dto.amount,
+    )?;
+    let unapplied = dto.amount
+        - allocations
+            .iter()
+            .map(|(_, amount)| *amount)
+            .sum::<Decimal>();
+    if !unapplied.is_zero() && dto.credit_account_id.is_none() {
+        return Err(AppError::Validation(format!(
+            "{} of the payment is not allocated; give a credit_account_id to keep it as a credit",
+            unapplied
+        )));
+    }
+    if allocations
+        .iter()
+        .any(|(document_id, _)| documents[document_id].control_account_id == dto.account_id)
+    {
+        return Err(AppError::Validation(
+            "The payment cannot go through the receivable or payable account it settles"
+                .to_string(),
+        ));
+    }
+
+    // Received: cash in, receivables (and the credit) out. Sent mirrors it.
+    let (cash_side, settled_side) = match dto.direction {
+        PaymentDirection::Received => (JournalEntryType::Debit, JournalEntryType::Credit),
+        PaymentDirection::Sent => (JournalEntryType::Credit, JournalEntryType::Debit),
+    };
+    let mut entries = vec![(dto.account_id, cash_side, dto.amount)];
+    entries.extend(control_entries(&documents, &allocations, settled_side));
+    if let Some(credit_account_id) = dto.credit_account_id.filter(|_| !unapplied.is_zero()) {
+        entries.push((credit_account_id, settled_side, unapplied));
+    }
+    let description = match dto.direction {
+        PaymentDirection::Received => "Payment received",
+        PaymentDirection::Sent => "Payment sent",
+    };
+    let posted = post(
+        &mut db_tx,
+        tenant_id,
+        user_id,
+        dto.payment_date,
+        description,
+        &currency_code,
+        dto.amount,
+        entries,
+        dto.memo.clone(),
+    )
+    .await?;
+
+    let payment_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO payments (
+            tenant_id, direction, customer_id, vendor_id, payment_date, amount, currency_code,
+            account_id, credit_account_id, unapplied_amount, transaction_id, memo, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $13)
+        RETURNING id
+        "#,
+        tenant_id,
+        String::from(dto.direction),
+        dto.customer_id,
+        dto.vendor_id,
+        dto.payment_date,
+        dto.amount,
+        currency_code,
+        dto.account_id,
+        dto.credit_account_id,
+        unapplied,
+        posted.id,
+        dto.memo,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    for (document_id, amount) in &allocations {
+        settle(
+            &mut db_tx,
+            tenant_id,
+            user_id,
+            dto.direction,
+            *document_id,
+            *amount,
+            dto.payment_date,
+            dto.account_id,
+            posted.id,
+            payment_id,
+            dto.memo.as_deref(),
+        )
+        .await?;
+    }
+
+    db_tx.commit().await?;
+
+    publish_posted(tenant_id, user_id, &posted);
+    Ok(payment_id)
+}
+
+/// Applies a payment's unapplied credit to invoices or bills of the same customer or
+/// vendor, moving it from the credit account to receivable or payable.
+pub async fn apply_credit(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    payment_id: Uuid,
+    user_id: Uuid,
+    dto: ApplyPaymentCreditDto,
+) -> Result<PaymentDetail, AppError> {
+    info!(
+        "Service: Applying credit of payment ID: {} to {} documents for tenant ID: {}",
+        payment_id,
+        dto.allocations.len(),
+        tenant_id
+    );
+
+    check_allocation_amounts(&dto.allocations)?;
+    with_retry(pool, |pool| {
+        apply_credit_once(pool, tenant_id, payment_id, user_id, &dto)
+    })
+    .await?;
+    get_payment(pool, tenant_id, payment_id).await
+}
+
+/// One attempt at applying a credit, in its own database transaction.
+async fn apply_credit_once(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    payment_id: Uuid,
+    user_id: Uuid,
+    dto: &ApplyPaymentCreditDto,
+) -> Result<(), AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let payment = query_as!(
+        Payment,
+        r#"
+        SELECT id, tenant_id, direction, customer_id, vendor_id, payment_date, amount,
+               currency_code, account_id, credit_account_id, unapplied_amount, transaction_id,
+               memo, created_at, created_by, updated_at, updated_by
+        FROM payments
+        WHERE id = $1 AND tenant_id = $2
+        FOR UPDATE
+        "#,
+        payment_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| not_found(payment_id, tenant_id))?;
+    let credit_account_id = match payment.credit_account_id {
+        Some(account_id) if payment.unapplied_amount > Decimal::ZERO => account_id,
+        _ => {
+            return Err(AppError::Validation(format!(
+                "Payment {} has no unapplied credit",
+                payment_id
+            )))
+        }
+    };
+    if dto.applied_on < payment.payment_date {
+        return Err(AppError::Validation(
+            "A credit cannot be applied before the payment date".to_string(),
+        ));
+    }
+    let direction: PaymentDirection = payment
+        .direction
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    let party_id = payment
+        .customer_id
+        .or(payment.vendor_id)
+        .unwrap_or_default();
+
+    let documents = lock_documents(&mut db_tx, tenant_id, direction, &dto.allocations).await?;
+    let allocations = allocate(
+        direction,
+        party_id,
+        &payment.currency_code,
+        dto.applied_on,
+        &documents,
+        &dto.allocations,
+        payment.unapplied_amount,
+    )?;
+    let applied: Decimal = allocations.iter().map(|(_, amount)| *amount).sum();
+
+    // The credit account gives back what the receivable or payable accounts take
+    let (credit_side, settled_side) = match direction {
+        PaymentDirection::Received => (JournalEntryType::Debit, JournalEntryType::Credit),
+        PaymentDirection::Sent => (JournalEntryType::Credit, JournalEntryType::Debit),
+    };
+    let mut entries = vec![(credit_account_id, credit_side, applied)];
+    entries.extend(control_entries(&documents, &allocations, settled_side));
+    let posted = post(
+        &mut db_tx,
+        tenant_id,
+        user_id,
+        dto.applied_on,
+        "Credit applied",
+        &payment.currency_code,
+        applied,
+        entries,
+        None,
+    )
+    .await?;
+
+    for (document_id, amount) in &allocations {
+        settle(
+            &mut db_tx,
+            tenant_id,
+            user_id,
+            direction,
+            *document_id,
+            *amount,
+            dto.applied_on,
+            credit_account_id,
+            posted.id,
+            payment_id,
+            None,
+        )
+        .await?;
+    }
+    sqlx::query!(
+        r#"
+        UPDATE payments
+        SET unapplied_amount = unapplied_amount - $2, updated_at = NOW(), updated_by = $3
+        WHERE id = $1
+        "#,
+        payment_id,
+        applied,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+
+    publish_posted(tenant_id, user_id, &posted);
+    Ok(())
+}
+
+/// Locks the invoices or bills named in `allocations`, in ID order so that concurrent
+/// payments cannot deadlock. Documents that are not the tenant's are left out.
+async fn lock_documents(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    direction: PaymentDirection,
+    allocations: &[PaymentAllocationDto],
+) -> Result<HashMap<Uuid, OpenDocument>, AppError> {
+    let mut ids: Vec<Uuid> = allocations.iter().map(|a| a.document_id).collect();
+    ids.sort();
+    ids.dedup();
+    if ids.len() != allocations.len() {
+        return Err(AppError::Validation(
+            "Each invoice or bill can only be allocated once per request".to_string(),
+        ));
+    }
+
+    let documents = match direction {
+        PaymentDirection::Received => sqlx::query!(
+            r#"
+            SELECT id, customer_id, invoice_number, status, issue_date, currency_code,
+                   receivable_account_id, total - amount_paid as "outstanding!"
+            FROM invoices
+            WHERE tenant_id = $1 AND id = ANY($2)
+            ORDER BY id
+            FOR UPDATE
+            "#,
+            tenant_id,
+            &ids
+        )
+        .fetch_all(&mut **db_tx)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let document = OpenDocument {
+                party_id: row.customer_id,
+                number: row.invoice_number,
+                status: row.status,
+                issue_date: row.issue_date,
+                currency_code: row.currency_code,
+                control_account_id: row.receivable_account_id,
+                outstanding: row.outstanding,
+            };
+            (row.id, document)
+        })
+        .collect(),
+        PaymentDirection::Sent => sqlx::query!(
+            r#"
+            SELECT id, vendor_id, bill_number, status, bill_date, currency_code,
+                   payable_account_id, total - amount_paid as "outstanding!"
+            FROM bills
+            WHERE tenant_id = $1 AND id = ANY($2)
+            ORDER BY id
+            FOR UPDATE
+            "#,
+            tenant_id,
+            &ids
+        )
+        .fetch_all(&mut **db_tx)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let document = OpenDocument {
+                party_id: row.vendor_id,
+                number: row.bill_number,
+                status: row.status,
+                issue_date: row.bill_date,
+                currency_code: row.currency_code,
+                control_account_id: row.payable_account_id,
+                outstanding: row.outstanding,
+            };
+            (row.id, document)
+        })
+        .collect(),
+    };
+
+    Ok(documents)
+}
+
+/// Resolves the requested allocations against the locked documents and what is available,
+/// in order. Returns each document with the amount applied to it.
+fn allocate(
+    direction: PaymentDirection,
+    party_id: Uuid,
+    currency_code: &str,
+    date: NaiveDate,
+    documents: &HashMap<Uuid, OpenDocument>,
+    requested: &[PaymentAllocationDto],
+    available: Decimal,
+) -> Result<Vec<(Uuid, Decimal)>, AppError> {
+    let (kind, party, payable_statuses) = match direction {
+        PaymentDirection::Received => ("Invoice", "customer", ["ISSUED", "PARTIALLY_PAID"]),
+        PaymentDirection::Sent => ("Bill", "vendor", ["OPEN", "PARTIALLY_PAID"]),
+    };
+
+    let mut remaining = available;
+    let mut allocations = Vec::with_capacity(requested.len());
+    for (index, allocation) in requested.iter().enumerate() {
+        let document = documents.get(&allocation.document_id).ok_or_else(|| {
+            AppError::NotFound(format!(
+                "{} with ID {} not found",
+                kind, allocation.document_id
+            ))
+        })?;
+        let label = document
+            .number
+            .clone()
+            .unwrap_or_else(|| allocation.document_id.to_string());
+        if document.party_id != party_id {
+            return Err(AppError::Validation(format!(
+                "{} {} belongs to another {}",
+                kind, label, party
+            )));
+        }
+        if !payable_statuses.contains(&document.status.as_str()) {
+            return Err(AppError::Validation(format!(
+                "{} {} is {} and cannot be paid",
+                kind, label, document.status
+            )));
+        }
+        if !document.currency_code.eq_ignore_ascii_case(currency_code) {
+            return Err(AppError::Validation(format!(
+                "{} {} is in {}, not {}",
+                kind, label, document.currency_code, currency_code
+            )));
+        }
+        if date < document.issue_date {
+            return Err(AppError::Validation(format!(
+                "{} {} is dated after the payment",
+                kind, label
+            )));
+        }
+
+        let amount = allocation
+            .amount
+            .unwrap_or_else(|| document.outstanding.min(remaining));
+        if amount <= Decimal::ZERO {
+            return Err(AppError::Validation(format!(
+                "allocations[{}] has nothing left to apply",
+                index
+            )));
+        }
+        if amount > document.outstanding {
+            return Err(AppError::Validation(format!(
+                "allocations[{}] is more than the {} still owed on {} {}",
+                index, document.outstanding, kind, label
+            )));
+        }
+        if amount > remaining {
+            return Err(AppError::Validation(format!(
+                "allocations[{}] is more than the {} left to allocate",
+                index, remaining
+            )));
+        }
+        remaining -= amount;
+        allocations.push((allocation.document_id, amount));
+    }
+
+    Ok(allocations)
+}
+
+/// One entry per receivable or payable account, in the order the documents use them.
+fn control_entries(
+    documents: &HashMap<Uuid, OpenDocument>,
+    allocations: &[(Uuid, Decimal)],
+    entry_type: JournalEntryType,
+) -> Vec<(Uuid, JournalEntryType, Decimal)> {
+    let mut entries: Vec<(Uuid, JournalEntryType, Decimal)> = Vec::new();
+    for (document_id, amount) in allocations {
+        let account_id = documents[document_id].control_account_id;
+        match entries.iter_mut().find(|(id, _, _)| *id == account_id) {
+            Some((_, _, total)) => *total += *amount,
+            None => entries.push((account_id, entry_type, *amount)),
+        }
+    }
+    entries
+}
+
+/// Posts a payment's transaction in the payment's currency.
+#[allow(clippy::too_many_arguments)]
+async fn post(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    transaction_date: NaiveDate,
+    description: &str,
+    currency_code: &str,
+    amount: Decimal,
+    entries: Vec<(Uuid, JournalEntryType, Decimal)>,
+    memo: Option<String>,
+) -> Result<Transaction, AppError> {
+    let journal_entries = entries
+        .into_iter()
+        .map(|(account_id, entry_type, amount)| CreateJournalEntryDto {
+            account_id,
+            entry_type,
+            amount,
+            currency_code: currency_code.to_string(),
+            exchange_rate: None,
+            converted_amount: None,
+            memo: memo.clone(),
+            project_id: None,
+            class_id: None,
+            location_id: None,
+        })
+        .collect();
+    let create = CreateTransactionDto {
+        transaction_date,
+        description: description.to_string(),
+        r#type: TransactionType::Transfer,
+        category_id: None,
+        payee_id: None,
+        tags: None,
+        amount,
+        currency_code: currency_code.to_string(),
+        is_reconciled: None,
+        reconciliation_date: None,
+        notes: None,
+        source_document_url: None,
+        status: Some(TransactionStatus::Posted),
+        journal_entries,
+        payment_account_id: None,
+        splits: Vec::new(),
+        metadata: None,
+        attribution: None,
+    };
+    let (posted, _) = transaction::insert_transaction(db_tx, tenant_id, user_id, create).await?;
+    Ok(posted)
+}
+
+/// Records an allocation on the invoice or bill and updates what has been paid on it.
+#[allow(clippy::too_many_arguments)]
+async fn settle(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    direction: PaymentDirection,
+    document_id: Uuid,
+    amount: Decimal,
+    date: NaiveDate,
+    account_id: Uuid,
+    transaction_id: Uuid,
+    payment_id: Uuid,
+    memo: Option<&str>,
+) -> Result<(), AppError> {
+    match direction {
+        PaymentDirection::Received => {
+            sqlx::query!(
+                r#"
+                INSERT INTO invoice_payments (
+                    tenant_id, invoice_id, payment_date, amount, deposit_account_id,
+                    transaction_id, payment_id, memo, created_by
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+                tenant_id,
+                document_id,
+                date,
+                amount,
+                account_id,
+                transaction_id,
+                payment_id,
+                memo,
+                user_id
+            )
+            .execute(&mut **db_tx)
+            .await?;
+            sqlx::query!(
+                r#"
+                UPDATE invoices
+                SET amount_paid = amount_paid + $2,
+                    status = CASE WHEN amount_paid + $2 = total THEN 'PAID' ELSE 'PARTIALLY_PAID' END,
+                    updated_at = NOW(), updated_by = $3
+                WHERE id = $1
+                "#,
+                document_id,
+                amount,
+                user_id
+            )
+            .execute(&mut **db_tx)
+            .await?;
+        }
+        PaymentDirection::Sent => {
+            sqlx::query!(
+                r#"
+                INSERT INTO bill_payments (
+                    tenant_id, bill_id, payment_date, amount, payment_account_id,
+                    transaction_id, payment_id, memo, created_by
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+                tenant_id,
+                document_id,
+                date,
+                amount,
+                account_id,
+                transaction_id,
+                payment_id,
+                memo,
+                user_id
+            )
+            .execute(&mut **db_tx)
+            .await?;
+            sqlx::query!(
+                r#"
+                UPDATE bills
+                SET amount_paid = amount_paid + $2,
+                    status = CASE WHEN amount_paid + $2 = total THEN 'PAID' ELSE 'PARTIALLY_PAID' END,
+                    updated_at = NOW(), updated_by = $3
+                WHERE id = $1
+                "#,
+                document_id,
+                amount,
+                user_id
+            )
+            .execute(&mut **db_tx)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the customer (RECEIVED) or vendor (SENT) is the tenant's. Inactive ones can
+/// still settle what they owe or are owed.
+async fn check_party<'e, E: PgExecutor<'e>>(
+    executor: E,
+    tenant_id: Uuid,
+    direction: PaymentDirection,
+    party_id: Uuid,
+) -> Result<(), AppError> {
+    let exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM customers WHERE $3 = 'RECEIVED' AND id = $1 AND tenant_id = $2
+            UNION ALL
+            SELECT 1 FROM vendors WHERE $3 = 'SENT' AND id = $1 AND tenant_id = $2
+        ) as "exists!"
+        "#,
+        party_id,
+        tenant_id,
+        String::from(direction)
+    )
+    .fetch_one(executor)
+    .await?;
+    if !exists {
+        let party = match direction {
+            PaymentDirection::Received => "Customer",
+            PaymentDirection::Sent => "Vendor",
+        };
+        return Err(AppError::Validation(format!(
+            "{} ID {} is invalid for tenant {}",
+            party, party_id, tenant_id
+        )));
+    }
+    Ok(())
+}
+
+fn check_allocation_amounts(allocations: &[PaymentAllocationDto]) -> Result<(), AppError> {
+    for (index, allocation) in allocations.iter().enumerate() {
+        if let Some(amount) = allocation.amount {
+            if amount <= Decimal::ZERO || amount.round_dp(AMOUNT_SCALE) != amount {
+                return Err(AppError::Validation(format!(
+                    "allocations[{}].amount must be greater than zero with at most two decimals",
+                    index
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn publish_posted(tenant_id: Uuid, user_id: Uuid, posted: &Transaction) {
+    domain_event::publish(DomainEvent::TransactionPosted {
+        tenant_id,
+        user_id,
+        transaction_id: posted.id,
+        amount: posted.amount,
+        transaction_date: posted.transaction_date,
+    });
+}
+
+fn not_found(payment_id: Uuid, tenant_id: Uuid) -> AppError {
+    AppError::NotFound(format!(
+        "Payment with ID {} not found for tenant {}",
+        payment_id, tenant_id
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use uuid::Uuid;
+
+    use super::{allocate, check_allocation_amounts, OpenDocument};
+    use crate::{
+        error::AppError,
+        models::{dto::payment_dto::PaymentAllocationDto, payment::PaymentDirection},
+    };
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 31).unwrap()
+    }
+
+    fn money(amount: &str) -> Decimal {
+        amount.parse().unwrap()
+    }
+
+    /// Open invoices of one customer, with what is still owed on each.
+    fn invoices(
+        customer_id: Uuid,
+        outstanding: &[&str],
+    ) -> (Vec<Uuid>, HashMap<Uuid, OpenDocument>) {
+        let ids: Vec<Uuid> = outstanding.iter().map(|_| Uuid::new_v4()).collect();
+        let documents = ids
+            .iter()
+            .zip(outstanding)
+            .map(|(id, amount)| {
+                let document = OpenDocument {
+                    party_id: customer_id,
+                    number: None,
+                    status: "ISSUED".to_string(),
+                    issue_date: date(),
+                    currency_code: "USD".to_string(),
+                    control_account_id: Uuid::new_v4(),
+                    outstanding: money(amount),
+                };
+                (*id, document)
+            })
+            .collect();
+        (ids, documents)
+    }
+
+    fn request(document_id: Uuid, amount: Option<&str>) -> PaymentAllocationDto {
+        PaymentAllocationDto {
+            document_id,
+            amount: amount.map(money),
+        }
+    }
+
+    fn allocate_received(
+        customer_id: Uuid,
+        documents: &HashMap<Uuid, OpenDocument>,
+        requested: &[PaymentAllocationDto],
+        available: &str,
+    ) -> Result<Vec<(Uuid, Decimal)>, AppError> {
+        allocate(
+            PaymentDirection::Received,
+            customer_id,
+            "USD",
+            date(),
+            documents,
+            requested,
+            money(available),
+        )
+    }
+
+    #[test]
+    fn overpayments_settle_the_invoice_and_leave_the_rest() {
+        let customer_id = Uuid::new_v4();
+        let (ids, documents) = invoices(customer_id, &["40.00"]);
+
+        let allocations =
+            allocate_received(customer_id, &documents, &[request(ids[0], None)], "100.00").unwrap();
+        // The other 60.00 stays unapplied
+        assert_eq!(allocations, vec![(ids[0], money("40.00"))]);
+    }
+
+    #[test]
+    fn allocations_over_what_is_owed_or_available_are_rejected() {
+        let customer_id = Uuid::new_v4();
+        let (ids, documents) = invoices(customer_id, &["40.00", "80.00"]);
+
+        let over_owed = [request(ids[0], Some("40.01"))];
+        assert!(allocate_received(customer_id, &documents, &over_owed, "100.00").is_err());
+        let over_available = [
+            request(ids[0], Some("30.00")),
+            request(ids[1], Some("70.01")),
+        ];
+        assert!(allocate_received(customer_id, &documents, &over_available, "100.00").is_err());
+    }
+
+    #[test]
+    fn partial_payments_apply_what_is_left_in_order() {
+        let customer_id = Uuid::new_v4();
+        let (ids, documents) = invoices(customer_id, &["40.00", "80.00", "25.00"]);
+
+        let requested = [request(ids[0], None), request(ids[1], None)];
+        let allocations = allocate_received(customer_id, &documents, &requested, "100.00").unwrap();
+        assert_eq!(
+            allocations,
+            vec![(ids[0], money("40.00")), (ids[1], money("60.00"))]
+        );
+
+        // Nothing is left for the third invoice
+        let requested = [
+            request(ids[0], None),
+            request(ids[1], None),
+            request(ids[2], None),
+        ];
+        assert!(allocate_received(customer_id, &documents, &requested, "100.00").is_err());
+    }
+
+    #[test]
+    fn split_payments_keep_every_cent() {
+        let customer_id = Uuid::new_v4();
+        let (ids, documents) = invoices(customer_id, &["33.33", "33.33", "50.00"]);
+
+        let requested = [
+            request(ids[0], Some("33.33")),
+            request(ids[1], Some("33.33")),
+            request(ids[2], None),
+        ];
+        let allocations = allocate_received(customer_id, &documents, &requested, "100.00").unwrap();
+        assert_eq!(allocations[2], (ids[2], money("33.34")));
+        let applied: Decimal = allocations.iter().map(|(_, amount)| *amount).sum();
+        assert_eq!(applied, money("100.00"));
+    }
+
+    #[test]
+    fn allocation_amounts_are_whole_cents() {
+        let document_id = Uuid::new_v4();
+        assert!(check_allocation_amounts(&[request(document_id, Some("10.00"))]).is_ok());
+        assert!(check_allocation_amounts(&[request(document_id, Some("10.005"))]).is_err());
+        assert!(check_allocation_amounts(&[request(document_id, Some("0"))]).is_err());
+    }
+}
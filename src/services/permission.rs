@@ -0,0 +1,145 @@
+//! Permission checks against a user's roles within a tenant.
+//!
+//! Permissions are granted through `user_tenant_roles` -> `role_permissions` -> `permissions`
+//! and referenced by name (e.g., `tx.create`).
+
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// Built-in role holding every permission; granted to whoever creates a tenant.
+pub const ADMIN_ROLE: &str = "admin";
+
+/// Reopen closed fiscal periods and change transactions dated inside them.
+pub const PERIOD_REOPEN: &str = "period.reopen";
+
+/// Post transactions that were submitted for approval.
+pub const TX_APPROVE: &str = "tx.approve";
+
+/// Configure the tenant's security webhook.
+pub const SECURITY_MANAGE: &str = "security.manage";
+
+/// See amounts on accounts flagged `is_sensitive` (e.g., payroll).
+pub const VIEW_SENSITIVE_ACCOUNTS: &str = "data.view_sensitive_accounts";
+
+/// See attachment URLs (`source_document_url`) on transactions.
+pub const VIEW_ATTACHMENTS: &str = "data.view_attachments";
+
+/// Configure the tenant's outgoing mail server.
+pub const MAIL_MANAGE: &str = "mail.manage";
+
+/// Read transaction descriptions and memos sealed by privacy mode.
+pub const TX_READ_SENSITIVE: &str = "transactions.read_sensitive";
+
+/// Turn the tenant's privacy mode on or off.
+pub const PRIVACY_MANAGE: &str = "privacy.manage";
+
+/// Opt in to automatic exchange rates and refresh them on demand.
+pub const RATES_MANAGE: &str = "rates.manage";
+
+/// Configure and post foreign-currency revaluations.
+pub const FX_REVALUE: &str = "fx.revalue";
+
+/// Create and revoke the tenant's API keys, including sandbox keys.
+pub const API_KEYS_MANAGE: &str = "api_keys.manage";
+
+/// Download full backups of the tenant's books.
+pub const TENANT_BACKUP: &str = "tenant.backup";
+
+/// Download anonymized copies of the tenant's books, to reproduce problems without real data.
+pub const TENANT_EXPORT_ANONYMIZED: &str = "tenant.export_anonymized";
+
+/// Restore a backup into the tenant while it is still empty.
+pub const TENANT_RESTORE: &str = "tenant.restore";
+
+/// Export and erase the personal data of the tenant's members.
+pub const USERS_PERSONAL_DATA: &str = "users.personal_data";
+
+/// Invite people to the tenant and revoke pending invitations.
+pub const MEMBERS_INVITE: &str = "members.invite";
+
+/// Export the tenant's members and roles, and import them from another deployment.
+pub const MEMBERS_MIGRATE: &str = "members.migrate";
+
+/// List deactivated accounts, categories and budgets, and restore them and deactivated members.
+pub const RECORDS_RESTORE: &str = "records.restore";
+
+/// Change the tenant's name and settings, and deactivate it.
+pub const TENANT_MANAGE: &str = "tenant.manage";
+
+/// Returns whether the user holds the named permission through any of their roles in the tenant.
+pub async fn user_has_permission<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    permission: &str,
+) -> Result<bool, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let granted = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM user_tenant_roles utr
+            JOIN role_permissions rp ON rp.role_id = utr.role_id
+            JOIN permissions p ON p.id = rp.permission_id
+            WHERE utr.tenant_id = $1 AND utr.user_id = $2 AND p.name = $3
+        ) as "granted!"
+        "#,
+        tenant_id,
+        user_id,
+        permission
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(granted)
+}
+
+/// Fails with `Forbidden` unless the user holds the named permission in the tenant.
+pub async fn require_permission<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    permission: &str,
+) -> Result<(), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    if user_has_permission(executor, tenant_id, user_id, permission).await? {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(format!(
+            "Missing permission '{}'",
+            permission
+        )))
+    }
+}
+
+/// Fails with `Forbidden` unless the user is an active system administrator, who alone may
+/// change the reference data shared by every tenant.
+pub async fn require_system_admin<'e, E>(executor: E, user_id: Uuid) -> Result<(), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let is_admin = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM users WHERE id = $1 AND is_system_admin AND is_active
+        ) as "is_admin!"
+        "#,
+        user_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    if is_admin {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only system administrators can change this".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,271 @@
+//! Privacy mode: transaction descriptions and journal entry memos sealed with a tenant key.
+//!
+//! While a tenant has privacy mode on, every description and memo written is encrypted
+//! with the tenant's data key and stored as `sealed:<ciphertext>`. The data key is
+//! generated on first enable and stored encrypted under `TOKEN_ENCRYPTION_KEY`.
+//!
+//! Sealed text is opened in API responses only for holders of
+//! `transactions.read_sensitive` (see `field_policy`); everyone else sees it masked.
+//! Amounts, dates, accounts and categories are never sealed, so reports keep working.
+//! Turning the mode on seals existing text; turning it off opens it again.
+
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use sqlx::{PgExecutor, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{dto::privacy_dto::UpdatePrivacySettingsDto, privacy::PrivacySettings},
+    services::permission::{self, PRIVACY_MANAGE},
+    utils::crypto::{
+        decrypt_secret, decrypt_with_key, encrypt_secret, encrypt_with_key, generate_key,
+    },
+};
+
+/// Prefix marking a sealed description or memo.
+pub const SEALED_PREFIX: &str = "sealed:";
+
+/// A tenant's decrypted data key.
+#[derive(Clone)]
+pub struct TextKey([u8; 32]);
+
+impl fmt::Debug for TextKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TextKey(..)")
+    }
+}
+
+/// Whether a stored description or memo is sealed.
+pub fn is_sealed(text: &str) -> bool {
+    text.starts_with(SEALED_PREFIX)
+}
+
+/// Seals `text` when a key is given (privacy mode on); returns it unchanged otherwise.
+pub fn seal(key: Option<&TextKey>, text: String) -> Result<String, AppError> {
+    match key {
+        Some(key) if !is_sealed(&text) => Ok(format!(
+            "{}{}",
+            SEALED_PREFIX,
+            encrypt_with_key(&key.0, &text)?
+        )),
+        _ => Ok(text),
+    }
+}
+
+/// [`seal`] for optional text such as memos.
+pub fn seal_opt(key: Option<&TextKey>, text: Option<String>) -> Result<Option<String>, AppError> {
+    text.map(|text| seal(key, text)).transpose()
+}
+
+/// Opens sealed text; plain text is returned unchanged.
+pub fn unseal(key: &TextKey, text: &str) -> Result<String, AppError> {
+    match text.strip_prefix(SEALED_PREFIX) {
+        Some(sealed) => decrypt_with_key(&key.0, sealed),
+        None => Ok(text.to_string()),
+    }
+}
+
+/// The key to seal new text with: the tenant's data key while privacy mode is on, `None`
+/// otherwise.
+pub async fn sealing_key<'e, E>(executor: E, tenant_id: Uuid) -> Result<Option<TextKey>, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let row = sqlx::query!(
+        "SELECT privacy_mode, data_key FROM tenants WHERE id = $1",
+        tenant_id
+    )
+    .fetch_optional(executor)
+    .await?;
+    match row {
+        Some(row) => sealing_key_from(row.privacy_mode, row.data_key.as_deref()),
+        None => Ok(None),
+    }
+}
+
+/// [`sealing_key`] from the tenant's `privacy_mode` and `data_key`, for callers that have
+/// already loaded them.
+pub fn sealing_key_from(
+    privacy_mode: bool,
+    data_key: Option<&str>,
+) -> Result<Option<TextKey>, AppError> {
+    match data_key {
+        Some(data_key) if privacy_mode => open_data_key(data_key).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// The tenant's data key whether or not privacy mode is on, for opening sealed text.
+pub async fn opening_key<'e, E>(executor: E, tenant_id: Uuid) -> Result<Option<TextKey>, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let data_key = sqlx::query_scalar!("SELECT data_key FROM tenants WHERE id = $1", tenant_id)
+        .fetch_optional(executor)
+        .await?
+        .flatten();
+    data_key.as_deref().map(open_data_key).transpose()
+}
+
+/// Retrieves the tenant's privacy mode state.
+pub async fn get_privacy_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<PrivacySettings, AppError> {
+    info!(
+        "Service: Getting privacy settings for tenant ID: {}",
+        tenant_id
+    );
+
+    let privacy_mode =
+        sqlx::query_scalar!("SELECT privacy_mode FROM tenants WHERE id = $1", tenant_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+    let sealed_transactions = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM transactions WHERE tenant_id = $1 AND description LIKE 'sealed:%'"#,
+        tenant_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(PrivacySettings {
+        tenant_id,
+        privacy_mode,
+        sealed_transactions,
+    })
+}
+
+/// Turns privacy mode on or off, sealing or opening every existing description and memo
+/// of the tenant in one database transaction.
+pub async fn update_privacy_settings(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: UpdatePrivacySettingsDto,
+) -> Result<PrivacySettings, AppError> {
+    info!(
+        "Service: Setting privacy mode to {} for tenant ID: {}",
+        dto.privacy_mode, tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, PRIVACY_MANAGE).await?;
+
+    let mut db_tx = pool.begin().await?;
+
+    // Lock the tenant row so concurrent toggles run one after the other
+    let data_key = sqlx::query_scalar!(
+        "SELECT data_key FROM tenants WHERE id = $1 FOR UPDATE",
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+    let key = match data_key {
+        Some(stored) => open_data_key(&stored)?,
+        None => {
+            let key = TextKey(generate_key());
+            sqlx::query!(
+                "UPDATE tenants SET data_key = $2 WHERE id = $1",
+                tenant_id,
+                encrypt_secret(&base64_key(&key))?
+            )
+            .execute(&mut *db_tx)
+            .await?;
+            key
+        }
+    };
+
+    let convert = |text: &str| -> Result<String, AppError> {
+        if dto.privacy_mode {
+            seal(Some(&key), text.to_string())
+        } else {
+            unseal(&key, text)
+        }
+    };
+
+    // Only rows still in the other state need rewriting
+    let descriptions = sqlx::query!(
+        "SELECT id, description FROM transactions WHERE tenant_id = $1 AND (description LIKE 'sealed:%') <> $2",
+        tenant_id,
+        dto.privacy_mode
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    for row in descriptions {
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET description = $2, version = version + 1, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            row.id,
+            convert(&row.description)?
+        )
+        .execute(&mut *db_tx)
+        .await?;
+    }
+
+    let memos = sqlx::query!(
+        r#"
+        SELECT je.id, je.transaction_id, je.memo as "memo!"
+        FROM journal_entries je
+        JOIN transactions t ON t.id = je.transaction_id
+        WHERE t.tenant_id = $1 AND je.memo IS NOT NULL AND (je.memo LIKE 'sealed:%') <> $2
+        "#,
+        tenant_id,
+        dto.privacy_mode
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    let mut resealed_transaction_ids = Vec::new();
+    for row in memos {
+        sqlx::query!(
+            "UPDATE journal_entries SET memo = $2 WHERE id = $1",
+            row.id,
+            convert(&row.memo)?
+        )
+        .execute(&mut *db_tx)
+        .await?;
+        resealed_transaction_ids.push(row.transaction_id);
+    }
+    // Memos are part of their transaction, so its version (and ETag) moves with them
+    sqlx::query!(
+        r#"
+        UPDATE transactions
+        SET version = version + 1, updated_at = NOW()
+        WHERE id = ANY($1::uuid[])
+        "#,
+        &resealed_transaction_ids[..]
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE tenants SET privacy_mode = $2, updated_at = NOW(), updated_by = $3 WHERE id = $1",
+        tenant_id,
+        dto.privacy_mode,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    get_privacy_settings(pool, tenant_id).await
+}
+
+fn open_data_key(stored: &str) -> Result<TextKey, AppError> {
+    let bytes = BASE64.decode(decrypt_secret(stored)?).map_err(|e| {
+        AppError::InternalServerError(format!("Tenant data key is not valid base64: {}", e))
+    })?;
+    let key: [u8; 32] = bytes.try_into().map_err(|_| {
+        AppError::InternalServerError("Tenant data key must be 32 bytes".to_string())
+    })?;
+    Ok(TextKey(key))
+}
+
+fn base64_key(key: &TextKey) -> String {
+    BASE64.encode(key.0)
+}
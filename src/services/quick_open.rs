@@ -0,0 +1,192 @@
+//! Quick-open search for command palettes.
+//!
+//! One round trip runs a small, capped lookup per kind (accounts, categories, recent
+//! transactions, custom reports), each served by a trigram index on the searched column.
+//! Matches are ranked by trigram similarity with a bonus for prefix matches; built-in
+//! statements are matched in memory. There is no contact book yet, so contacts are not
+//! searched. Descriptions sealed by privacy mode cannot be searched and are skipped.
+
+use std::cmp::Ordering;
+
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::quick_open_dto::QuickOpenQuery,
+        quick_open::{QuickOpenItem, QuickOpenKind},
+    },
+    services::privacy::SEALED_PREFIX,
+};
+
+const DEFAULT_LIMIT: i64 = 20;
+
+/// Only transactions dated within this many days are searched.
+const RECENT_TRANSACTION_DAYS: i32 = 90;
+
+/// Score added when the title (or account code) starts with the query.
+const PREFIX_BONUS: f64 = 1.0;
+
+/// Built-in statements: `(title, path)`.
+const BUILT_IN_REPORTS: [(&str, &str); 3] = [
+    ("Trial balance", "/reports/trial-balance"),
+    ("Income statement", "/reports/income-statement"),
+    ("Balance sheet", "/reports/balance-sheet"),
+];
+
+/// Searches the tenant for `query.q` and returns the best matches of every kind, best first.
+pub async fn quick_open(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    query: QuickOpenQuery,
+) -> Result<Vec<QuickOpenItem>, AppError> {
+    query.validate()?;
+
+    let term = query.q.trim();
+    if term.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    info!("Service: Quick-open search for tenant ID: {}", tenant_id);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT kind as "kind!", id as "id!", title as "title!", subtitle, score as "score!"
+        FROM (
+            (SELECT 'ACCOUNT' as kind, a.id, a.name as title, a.account_code as subtitle,
+                    GREATEST(similarity(a.name, $2), similarity(COALESCE(a.account_code, ''), $2))::float8
+                    + CASE WHEN a.name ILIKE $3 || '%' OR a.account_code ILIKE $3 || '%' THEN $7::float8 ELSE 0 END as score
+             FROM accounts a
+             WHERE a.tenant_id = $1 AND a.is_active
+               AND (a.name ILIKE '%' || $3 || '%' OR a.account_code ILIKE '%' || $3 || '%')
+             ORDER BY score DESC
+             LIMIT $4)
+            UNION ALL
+            (SELECT 'CATEGORY' as kind, c.id, c.name as title, c.type::text as subtitle,
+                    similarity(c.name, $2)::float8
+                    + CASE WHEN c.name ILIKE $3 || '%' THEN $7::float8 ELSE 0 END as score
+             FROM categories c
+             WHERE c.tenant_id = $1 AND c.is_active AND c.name ILIKE '%' || $3 || '%'
+             ORDER BY score DESC
+             LIMIT $4)
+            UNION ALL
+            (SELECT 'TRANSACTION' as kind, t.id, t.description as title, t.transaction_date::text as subtitle,
+                    similarity(t.description, $2)::float8
+                    + CASE WHEN t.description ILIKE $3 || '%' THEN $7::float8 ELSE 0 END as score
+             FROM transactions t
+             WHERE t.tenant_id = $1
+               AND t.transaction_date >= CURRENT_DATE - $6::int
+               AND t.description NOT LIKE $8 || '%'
+               AND t.description ILIKE '%' || $3 || '%'
+             ORDER BY score DESC, t.transaction_date DESC
+             LIMIT $4)
+            UNION ALL
+            (SELECT 'REPORT' as kind, r.id, r.name as title, r.report_type as subtitle,
+                    similarity(r.name, $2)::float8
+                    + CASE WHEN r.name ILIKE $3 || '%' THEN $7::float8 ELSE 0 END as score
+             FROM custom_reports r
+             WHERE r.tenant_id = $1 AND (r.user_id = $5 OR r.is_public) AND r.name ILIKE '%' || $3 || '%'
+             ORDER BY score DESC
+             LIMIT $4)
+        ) matches
+        "#,
+        tenant_id,
+        term,
+        escape_like(term),
+        limit,
+        user_id,
+        RECENT_TRANSACTION_DAYS,
+        PREFIX_BONUS,
+        SEALED_PREFIX
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut items: Vec<QuickOpenItem> = rows
+        .into_iter()
+        .filter_map(|row| {
+            let (kind, path) = match row.kind.as_str() {
+                "ACCOUNT" => (QuickOpenKind::Account, format!("/accounts/{}", row.id)),
+                "CATEGORY" => (QuickOpenKind::Category, format!("/categories/{}", row.id)),
+                "TRANSACTION" => (
+                    QuickOpenKind::Transaction,
+                    format!("/transactions/{}", row.id),
+                ),
+                "REPORT" => (QuickOpenKind::Report, format!("/custom-reports/{}", row.id)),
+                _ => return None,
+            };
+            Some(QuickOpenItem {
+                kind,
+                id: Some(row.id),
+                title: row.title,
+                subtitle: row.subtitle,
+                path,
+                score: row.score,
+            })
+        })
+        .collect();
+    items.extend(built_in_report_matches(term));
+
+    items.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| kind_rank(a.kind).cmp(&kind_rank(b.kind)))
+    });
+    items.truncate(limit as usize);
+    Ok(items)
+}
+
+/// Built-in statements whose title contains the term, scored like the database matches.
+fn built_in_report_matches(term: &str) -> impl Iterator<Item = QuickOpenItem> + '_ {
+    let term = term.to_lowercase();
+    BUILT_IN_REPORTS
+        .into_iter()
+        .filter_map(move |(title, path)| {
+            let lower = title.to_lowercase();
+            if !lower.contains(&term) {
+                return None;
+            }
+            // Share of the title covered by the term stands in for trigram similarity
+            let coverage = term.chars().count() as f64 / lower.chars().count() as f64;
+            let bonus = if lower.starts_with(&term) {
+                PREFIX_BONUS
+            } else {
+                0.0
+            };
+            Some(QuickOpenItem {
+                kind: QuickOpenKind::Report,
+                id: None,
+                title: title.to_string(),
+                subtitle: None,
+                path: path.to_string(),
+                score: coverage + bonus,
+            })
+        })
+}
+
+/// Tie-break order between kinds with equal scores.
+fn kind_rank(kind: QuickOpenKind) -> u8 {
+    match kind {
+        QuickOpenKind::Account => 0,
+        QuickOpenKind::Category => 1,
+        QuickOpenKind::Report => 2,
+        QuickOpenKind::Transaction => 3,
+    }
+}
+
+/// Escapes `%`, `_` and `\` so the term is matched literally by `ILIKE`.
+fn escape_like(term: &str) -> String {
+    let mut escaped = String::with_capacity(term.len());
+    for c in term.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,234 @@
+//! Placeholders and amount escalation for recurring transactions.
+//!
+//! Descriptions, notes and journal memos of a recurring definition may contain placeholders
+//! such as `{{month_name}} {{year}} rent`. They are rendered against each occurrence's due
+//! date when the scheduler materializes it. Supported variables:
+//!
+//! | Variable         | Example (due 2025-03-01) |
+//! |------------------|--------------------------|
+//! | `{{date}}`       | `2025-03-01`             |
+//! | `{{day}}`        | `01`                     |
+//! | `{{month}}`      | `03`                     |
+//! | `{{month_name}}` | `March`                  |
+//! | `{{quarter}}`    | `Q1`                     |
+//! | `{{year}}`       | `2025`                   |
+//!
+//! An escalation rule raises the amount by a percentage on the first of a given month every
+//! year (e.g. +3% each January), starting with the first such date after the start date.
+
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::Decimal;
+
+use crate::models::{journal_entry::JournalEntryType, recurring_transaction::RecurringJournalLine};
+
+/// Names accepted between `{{` and `}}`.
+pub const TEMPLATE_VARIABLES: [&str; 6] = ["date", "day", "month", "month_name", "quarter", "year"];
+
+/// Returns the value of `variable` for an occurrence due on `date`.
+fn variable_value(variable: &str, date: NaiveDate) -> Option<String> {
+    let value = match variable {
+        "date" => date.format("%Y-%m-%d").to_string(),
+        "day" => date.format("%d").to_string(),
+        "month" => date.format("%m").to_string(),
+        "month_name" => date.format("%B").to_string(),
+        "quarter" => format!("Q{}", date.month0() / 3 + 1),
+        "year" => date.year().to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Splits `text` into literal parts and placeholder names, calling `on_variable` for each
+/// placeholder. Text without a closing `}}` is kept literally.
+fn expand(
+    text: &str,
+    mut on_variable: impl FnMut(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open + 2..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..open]);
+        let variable = rest[open + 2..open + 2 + close].trim();
+        match on_variable(variable) {
+            Some(value) => rendered.push_str(&value),
+            None => {
+                return Err(format!(
+                    "Unknown placeholder '{{{{{}}}}}'; use one of: {}",
+                    variable,
+                    TEMPLATE_VARIABLES.join(", ")
+                ))
+            }
+        }
+        rest = &rest[open + 2 + close + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Checks that every placeholder in `text` is a known variable.
+pub fn check_template(text: &str) -> Result<(), String> {
+    expand(text, |variable| {
+        TEMPLATE_VARIABLES.contains(&variable).then(String::new)
+    })
+    .map(|_| ())
+}
+
+/// Renders the placeholders in `text` for an occurrence due on `date`. Templates are
+/// checked when the definition is saved, so unknown placeholders are left as written.
+pub fn render_template(text: &str, date: NaiveDate) -> String {
+    expand(text, |variable| {
+        Some(variable_value(variable, date).unwrap_or_else(|| format!("{{{{{}}}}}", variable)))
+    })
+    .unwrap_or_else(|_| text.to_string())
+}
+
+/// Escalation dates (the first of `month`) after `after` and on or before `through`, oldest first.
+pub fn escalation_dates(month: u32, after: NaiveDate, through: NaiveDate) -> Vec<NaiveDate> {
+    (after.year()..=through.year())
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, 1))
+        .filter(|date| *date > after && *date <= through)
+        .collect()
+}
+
+/// Raises `amount` by `percent`, rounded to cents.
+pub fn escalate_amount(amount: Decimal, percent: Decimal) -> Decimal {
+    (amount * (Decimal::ONE + percent / Decimal::ONE_HUNDRED)).round_dp(2)
+}
+
+/// Raises every leg of a journal template by `percent` and returns the new total.
+///
+/// Legs are rounded to cents individually; any rounding difference goes to the largest
+/// credit leg so the template stays balanced.
+pub fn escalate_journal_lines(lines: &mut [RecurringJournalLine], percent: Decimal) -> Decimal {
+    for line in lines.iter_mut() {
+        line.amount = escalate_amount(line.amount, percent);
+    }
+
+    let side_total = |side: JournalEntryType, lines: &[RecurringJournalLine]| -> Decimal {
+        lines
+            .iter()
+            .filter(|l| l.entry_type == side)
+            .map(|l| l.amount)
+            .sum()
+    };
+    let debits = side_total(JournalEntryType::Debit, lines);
+    let difference = debits - side_total(JournalEntryType::Credit, lines);
+    if !difference.is_zero() {
+        if let Some(largest_credit) = lines
+            .iter_mut()
+            .filter(|l| l.entry_type == JournalEntryType::Credit)
+            .max_by_key(|l| l.amount)
+        {
+            largest_credit.amount += difference;
+        }
+    }
+    debits
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn money(amount: &str) -> Decimal {
+        amount.parse().unwrap()
+    }
+
+    fn line(entry_type: JournalEntryType, amount: Decimal) -> RecurringJournalLine {
+        RecurringJournalLine {
+            account_id: Uuid::new_v4(),
+            entry_type,
+            amount,
+            memo: None,
+        }
+    }
+
+    #[test]
+    fn renders_known_placeholders() {
+        assert_eq!(
+            render_template(
+                "{{month_name}} {{ year }} rent ({{quarter}}, {{date}})",
+                date(2025, 3, 1)
+            ),
+            "March 2025 rent (Q1, 2025-03-01)"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        let err = check_template("{{month_name}} {{week}} rent").unwrap_err();
+        assert!(err.contains("'{{week}}'"), "{}", err);
+        assert!(check_template("{{month_name}} {{year}} rent").is_ok());
+        // Only closed placeholders count
+        assert!(check_template("{{year").is_ok());
+        // A template saved before a variable was withdrawn still renders the rest
+        assert_eq!(
+            render_template("{{week}} of {{year}}", date(2025, 3, 1)),
+            "{{week}} of 2025"
+        );
+    }
+
+    #[test]
+    fn escalations_compound_once_per_year() {
+        let dates = escalation_dates(1, date(2023, 3, 1), date(2025, 2, 1));
+        assert_eq!(dates, vec![date(2024, 1, 1), date(2025, 1, 1)]);
+        let amount = dates.iter().fold(money("1000.00"), |amount, _| {
+            escalate_amount(amount, money("3"))
+        });
+        assert_eq!(amount, money("1060.90"));
+
+        let mut lines = vec![
+            line(JournalEntryType::Debit, money("1000.00")),
+            line(JournalEntryType::Credit, money("1000.00")),
+        ];
+        escalate_journal_lines(&mut lines, money("3"));
+        assert_eq!(
+            escalate_journal_lines(&mut lines, money("3")),
+            money("1060.90")
+        );
+        assert_eq!(lines[1].amount, money("1060.90"));
+    }
+
+    #[test]
+    fn escalation_dates_exclude_the_start_date() {
+        assert_eq!(
+            escalation_dates(1, date(2024, 1, 1), date(2024, 12, 31)),
+            Vec::<NaiveDate>::new()
+        );
+        assert_eq!(
+            escalation_dates(1, date(2024, 1, 1), date(2025, 1, 1)),
+            vec![date(2025, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn rounding_difference_goes_to_the_largest_credit_leg() {
+        let mut lines = vec![
+            line(JournalEntryType::Debit, money("100.00")),
+            line(JournalEntryType::Credit, money("0.05")),
+            line(JournalEntryType::Credit, money("99.90")),
+            line(JournalEntryType::Credit, money("0.05")),
+        ];
+        // 0.055 rounds up on both small legs, so the credits come to 110.01
+        let total = escalate_journal_lines(&mut lines, money("10"));
+        assert_eq!(total, money("110.00"));
+        let amounts: Vec<Decimal> = lines.iter().map(|l| l.amount).collect();
+        assert_eq!(
+            amounts,
+            vec![
+                money("110.00"),
+                money("0.06"),
+                money("109.88"),
+                money("0.06")
+            ]
+        );
+    }
+}
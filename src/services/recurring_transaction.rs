@@ -0,0 +1,1145 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde_json::Value as JsonValue;
+use sqlx::{query_as, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::recurring_transaction_dto::{
+            CreateRecurringTransactionDto, RecurringJournalLineDto, UpdateRecurringTransactionDto,
+        },
+        dto::transaction_dto::UpdateTransactionDto,
+        journal_entry::JournalEntryType,
+        recurring_transaction::{
+            BusinessDayRule, RecurringFrequencyUnit, RecurringJournalLine, RecurringOccurrence,
+            RecurringOccurrenceStatus, RecurringTransaction,
+        },
+        transaction::{Transaction, TransactionType},
+    },
+    services::{
+        balance_snapshot::{self, SnapshotChange},
+        business_calendar, currency_conversion, fiscal_period, privacy, recurring_template,
+        transaction,
+    },
+};
+
+/// Number of upcoming dates listed by `list_occurrences`.
+const UPCOMING_OCCURRENCES: usize = 12;
+
+/// Retrieves a list of active recurring transaction definitions for a specific tenant.
+pub async fn list_recurring_transactions(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Vec<RecurringTransaction>, AppError> {
+    info!(
+        "Service: Listing recurring transactions for tenant ID: {}",
+        tenant_id
+    );
+
+    let definitions = query_as!(
+        RecurringTransaction,
+        r#"
+        SELECT
+            id, tenant_id, description, type as "r#type: TransactionType", category_id, account_id, amount,
+            currency_code, frequency_value, frequency_unit, start_date, end_date,
+            last_generated_date, next_due_date, is_active, notes, journal_template,
+            escalation_percent, escalation_month, escalated_through, paused_at,
+            business_day_rule as "business_day_rule: BusinessDayRule",
+            created_at, created_by, updated_at, updated_by
+        FROM recurring_transactions
+        WHERE tenant_id = $1 AND is_active = TRUE
+        ORDER BY next_due_date NULLS LAST, description
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(definitions)
+}
+
+/// Retrieves a single recurring transaction definition by ID for a specific tenant.
+pub async fn get_recurring_transaction_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+) -> Result<RecurringTransaction, AppError> {
+    info!(
+        "Service: Getting recurring transaction with ID: {} for tenant ID: {}",
+        recurring_transaction_id, tenant_id
+    );
+
+    let definition = query_as!(
+        RecurringTransaction,
+        r#"
+        SELECT
+            id, tenant_id, description, type as "r#type: TransactionType", category_id, account_id, amount,
+            currency_code, frequency_value, frequency_unit, start_date, end_date,
+            last_generated_date, next_due_date, is_active, notes, journal_template,
+            escalation_percent, escalation_month, escalated_through, paused_at,
+            business_day_rule as "business_day_rule: BusinessDayRule",
+            created_at, created_by, updated_at, updated_by
+        FROM recurring_transactions
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        recurring_transaction_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Recurring transaction with ID {} not found for tenant {}",
+            recurring_transaction_id, tenant_id
+        ))
+    })?;
+
+    Ok(definition)
+}
+
+/// Creates a new recurring transaction definition for a specific tenant.
+///
+/// Only JOURNAL_ENTRY definitions are accepted: each occurrence is posted from the full
+/// multi-leg template, which is validated for balance here so the scheduler never has to
+/// reject an occurrence later. Placeholders in the description, notes and memos are checked
+/// here too.
+pub async fn create_recurring_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateRecurringTransactionDto,
+) -> Result<RecurringTransaction, AppError> {
+    info!(
+        "Service: Creating new recurring transaction '{}' for tenant ID {}",
+        dto.description, tenant_id
+    );
+
+    if let Some(end_date) = dto.end_date {
+        if end_date < dto.start_date {
+            return Err(AppError::Validation(
+                "End date cannot be before start date".to_string(),
+            ));
+        }
+    }
+    check_templates(
+        Some(&dto.description),
+        dto.notes.as_deref(),
+        dto.journal_lines.as_deref(),
+    )?;
+    let escalation = escalation_rule(dto.escalation_percent, dto.escalation_month)?;
+
+    let (amount, journal_template) = match dto.r#type {
+        TransactionType::JournalEntry => {
+            let lines = dto.journal_lines.as_deref().ok_or_else(|| {
+                AppError::Validation(
+                    "journal_lines are required for JOURNAL_ENTRY recurring transactions"
+                        .to_string(),
+                )
+            })?;
+            let total = validate_journal_lines(pool, tenant_id, lines).await?;
+            if let Some(amount) = dto.amount {
+                if amount != total {
+                    return Err(AppError::Validation(format!(
+                        "Amount {} does not match the journal template total {}",
+                        amount, total
+                    )));
+                }
+            }
+            (total, Some(journal_template_json(lines)?))
+        }
+        // A single account has no counter-leg, so an occurrence could not be posted balanced
+        other => {
+            return Err(AppError::Validation(format!(
+                "{} is not a valid recurring transaction type; use JOURNAL_ENTRY with journal_lines",
+                String::from(other)
+            )));
+        }
+    };
+
+    let new_definition = query_as!(
+        RecurringTransaction,
+        r#"
+        INSERT INTO recurring_transactions (
+            tenant_id, description, type, category_id, amount, currency_code,
+            frequency_value, frequency_unit, start_date, end_date, next_due_date,
+            is_active, notes, journal_template, escalation_percent, escalation_month,
+            business_day_rule, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $9, TRUE, $11, $12, $14, $15, $16, $13, $13)
+        RETURNING
+            id, tenant_id, description, type as "r#type: TransactionType", category_id, account_id, amount,
+            currency_code, frequency_value, frequency_unit, start_date, end_date,
+            last_generated_date, next_due_date, is_active, notes, journal_template,
+            escalation_percent, escalation_month, escalated_through, paused_at,
+            business_day_rule as "business_day_rule: BusinessDayRule",
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.description,
+        dto.r#type as TransactionType,
+        dto.category_id,
+        amount,
+        dto.currency_code,
+        dto.frequency_value,
+        String::from(dto.frequency_unit),
+        dto.start_date,
+        dto.end_date,
+        dto.notes,
+        journal_template,
+        created_by_user_id,
+        escalation.map(|(percent, _)| percent),
+        escalation.map(|(_, month)| month),
+        dto.business_day_rule.unwrap_or_default() as BusinessDayRule
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(new_definition)
+}
+
+/// Updates an existing recurring transaction definition for a specific tenant.
+///
+/// A replacement journal template is re-validated for balance before it is stored. A new
+/// escalation rule counts from the last generated occurrence, so it never applies retroactively.
+pub async fn update_recurring_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateRecurringTransactionDto,
+) -> Result<RecurringTransaction, AppError> {
+    info!(
+        "Service: Updating recurring transaction with ID: {} for tenant ID: {}",
+        recurring_transaction_id, tenant_id
+    );
+
+    check_templates(
+        dto.description.as_deref(),
+        dto.notes.as_deref(),
+        dto.journal_lines.as_deref(),
+    )?;
+    let escalation = escalation_rule(dto.escalation_percent, dto.escalation_month)?;
+    let remove_escalation = dto.remove_escalation.unwrap_or(false);
+    if remove_escalation && escalation.is_some() {
+        return Err(AppError::Validation(
+            "Give either an escalation rule or remove_escalation, not both".to_string(),
+        ));
+    }
+
+    let current =
+        get_recurring_transaction_by_id(pool, tenant_id, recurring_transaction_id).await?;
+    let is_journal = current.r#type == TransactionType::JournalEntry;
+
+    let mut amount = dto.amount;
+    let mut journal_template: Option<JsonValue> = None;
+    if let Some(lines) = dto.journal_lines.as_deref() {
+        if !is_journal {
+            return Err(AppError::Validation(
+                "journal_lines are only allowed for JOURNAL_ENTRY recurring transactions"
+                    .to_string(),
+            ));
+        }
+        let total = validate_journal_lines(pool, tenant_id, lines).await?;
+        if dto.amount.is_some_and(|a| a != total) {
+            return Err(AppError::Validation(format!(
+                "Amount does not match the journal template total {}",
+                total
+            )));
+        }
+        amount = Some(total);
+        journal_template = Some(journal_template_json(lines)?);
+    } else if is_journal && dto.amount.is_some() {
+        return Err(AppError::Validation(
+            "The amount of a JOURNAL_ENTRY recurring transaction is derived from its journal_lines"
+                .to_string(),
+        ));
+    }
+
+    if let Some(end_date) = dto.end_date {
+        if end_date < current.start_date {
+            return Err(AppError::Validation(
+                "End date cannot be before start date".to_string(),
+            ));
+        }
+    }
+
+    let updated_definition = query_as!(
+        RecurringTransaction,
+        r#"
+        UPDATE recurring_transactions
+        SET
+            description = COALESCE($3, description),
+            category_id = COALESCE($4, category_id),
+            amount = COALESCE($5, amount),
+            frequency_value = COALESCE($6, frequency_value),
+            frequency_unit = COALESCE($7, frequency_unit),
+            end_date = COALESCE($8, end_date),
+            notes = COALESCE($9, notes),
+            journal_template = COALESCE($10, journal_template),
+            is_active = COALESCE($11, is_active),
+            escalation_percent = CASE WHEN $13 THEN NULL ELSE COALESCE($14, escalation_percent) END,
+            escalation_month = CASE WHEN $13 THEN NULL ELSE COALESCE($15, escalation_month) END,
+            escalated_through = CASE
+                WHEN $13 THEN NULL
+                WHEN $14::numeric IS NOT NULL THEN COALESCE(last_generated_date, start_date)
+                ELSE escalated_through
+            END,
+            business_day_rule = COALESCE($16, business_day_rule),
+            updated_at = NOW(),
+            updated_by = $12
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING
+            id, tenant_id, description, type as "r#type: TransactionType", category_id, account_id, amount,
+            currency_code, frequency_value, frequency_unit, start_date, end_date,
+            last_generated_date, next_due_date, is_active, notes, journal_template,
+            escalation_percent, escalation_month, escalated_through, paused_at,
+            business_day_rule as "business_day_rule: BusinessDayRule",
+            created_at, created_by, updated_at, updated_by
+        "#,
+        recurring_transaction_id,
+        tenant_id,
+        dto.description,
+        dto.category_id,
+        amount,
+        dto.frequency_value,
+        dto.frequency_unit.map(String::from),
+        dto.end_date,
+        dto.notes,
+        journal_template,
+        dto.is_active,
+        updated_by_user_id,
+        remove_escalation,
+        escalation.map(|(percent, _)| percent),
+        escalation.map(|(_, month)| month),
+        dto.business_day_rule as Option<BusinessDayRule>
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Recurring transaction with ID {} not found or not owned by tenant {}",
+            recurring_transaction_id, tenant_id
+        ))
+    })?;
+
+    Ok(updated_definition)
+}
+
+/// Deactivates a recurring transaction definition (soft delete) for a specific tenant.
+/// Already materialized transactions are left untouched.
+pub async fn deactivate_recurring_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deactivating recurring transaction with ID: {} for tenant ID: {}",
+        recurring_transaction_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE recurring_transactions
+        SET
+            is_active = FALSE,
+            updated_at = NOW(),
+            updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        recurring_transaction_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Recurring transaction with ID {} not found or already inactive for tenant {}",
+            recurring_transaction_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Pauses a definition: no occurrences are generated until it is resumed.
+pub async fn pause_recurring_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<RecurringTransaction, AppError> {
+    info!(
+        "Service: Pausing recurring transaction with ID: {} for tenant ID: {}",
+        recurring_transaction_id, tenant_id
+    );
+
+    let current =
+        get_recurring_transaction_by_id(pool, tenant_id, recurring_transaction_id).await?;
+    if !current.is_active {
+        return Err(AppError::Conflict(format!(
+            "Recurring transaction {} is inactive",
+            recurring_transaction_id
+        )));
+    }
+    if current.paused_at.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Recurring transaction {} is already paused",
+            recurring_transaction_id
+        )));
+    }
+
+    let paused = query_as!(
+        RecurringTransaction,
+        r#"
+        UPDATE recurring_transactions
+        SET paused_at = NOW(), updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING
+            id, tenant_id, description, type as "r#type: TransactionType", category_id, account_id, amount,
+            currency_code, frequency_value, frequency_unit, start_date, end_date,
+            last_generated_date, next_due_date, is_active, notes, journal_template,
+            escalation_percent, escalation_month, escalated_through, paused_at,
+            business_day_rule as "business_day_rule: BusinessDayRule",
+            created_at, created_by, updated_at, updated_by
+        "#,
+        recurring_transaction_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(paused)
+}
+
+/// Resumes a paused definition. Occurrences that fell due while it was paused are not
+/// generated; the series continues with its first occurrence on or after today.
+pub async fn resume_recurring_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<RecurringTransaction, AppError> {
+    info!(
+        "Service: Resuming recurring transaction with ID: {} for tenant ID: {}",
+        recurring_transaction_id, tenant_id
+    );
+
+    let current =
+        get_recurring_transaction_by_id(pool, tenant_id, recurring_transaction_id).await?;
+    if current.paused_at.is_none() {
+        return Err(AppError::Conflict(format!(
+            "Recurring transaction {} is not paused",
+            recurring_transaction_id
+        )));
+    }
+
+    let today = Utc::now().date_naive();
+    let next_due = upcoming_dates(&current)?.find(|due_date| *due_date >= today);
+
+    let resumed = query_as!(
+        RecurringTransaction,
+        r#"
+        UPDATE recurring_transactions
+        SET paused_at = NULL, next_due_date = $3, updated_at = NOW(), updated_by = $4
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING
+            id, tenant_id, description, type as "r#type: TransactionType", category_id, account_id, amount,
+            currency_code, frequency_value, frequency_unit, start_date, end_date,
+            last_generated_date, next_due_date, is_active, notes, journal_template,
+            escalation_percent, escalation_month, escalated_through, paused_at,
+            business_day_rule as "business_day_rule: BusinessDayRule",
+            created_at, created_by, updated_at, updated_by
+        "#,
+        recurring_transaction_id,
+        tenant_id,
+        next_due,
+        updated_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(resumed)
+}
+
+/// Lists the occurrences of a series by date: materialized ones with their transaction,
+/// skipped ones, and (unless the series is paused or inactive) the next upcoming dates.
+pub async fn list_occurrences(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+) -> Result<Vec<RecurringOccurrence>, AppError> {
+    info!(
+        "Service: Listing occurrences of recurring transaction with ID: {} for tenant ID: {}",
+        recurring_transaction_id, tenant_id
+    );
+
+    let definition =
+        get_recurring_transaction_by_id(pool, tenant_id, recurring_transaction_id).await?;
+    let mut occurrences: BTreeMap<NaiveDate, RecurringOccurrence> = BTreeMap::new();
+
+    if definition.is_active && definition.paused_at.is_none() {
+        let business_days = business_calendar::load_business_days(pool, tenant_id).await?;
+        for occurrence_date in upcoming_dates(&definition)?.take(UPCOMING_OCCURRENCES) {
+            occurrences.insert(
+                occurrence_date,
+                RecurringOccurrence {
+                    occurrence_date,
+                    status: RecurringOccurrenceStatus::Upcoming,
+                    transaction_id: None,
+                    posting_date: business_days
+                        .adjust(occurrence_date, definition.business_day_rule),
+                },
+            );
+        }
+    }
+
+    let skipped = sqlx::query_scalar!(
+        "SELECT occurrence_date FROM recurring_transaction_skips WHERE recurring_transaction_id = $1",
+        recurring_transaction_id
+    )
+    .fetch_all(pool)
+    .await?;
+    for occurrence_date in skipped {
+        occurrences.insert(
+            occurrence_date,
+            RecurringOccurrence {
+                occurrence_date,
+                status: RecurringOccurrenceStatus::Skipped,
+                transaction_id: None,
+                posting_date: None,
+            },
+        );
+    }
+
+    let materialized = sqlx::query!(
+        r#"
+        SELECT id, transaction_date, recurring_occurrence_date as "occurrence_date!"
+        FROM transactions
+        WHERE tenant_id = $1 AND recurring_transaction_id = $2
+        "#,
+        tenant_id,
+        recurring_transaction_id
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in materialized {
+        occurrences.insert(
+            row.occurrence_date,
+            RecurringOccurrence {
+                occurrence_date: row.occurrence_date,
+                status: RecurringOccurrenceStatus::Materialized,
+                transaction_id: Some(row.id),
+                posting_date: Some(row.transaction_date),
+            },
+        );
+    }
+
+    Ok(occurrences.into_values().collect())
+}
+
+/// Skips one upcoming occurrence; the series continues with the next one.
+pub async fn skip_occurrence(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+    occurrence_date: NaiveDate,
+    created_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Skipping occurrence {} of recurring transaction with ID: {} for tenant ID: {}",
+        occurrence_date, recurring_transaction_id, tenant_id
+    );
+
+    let definition =
+        get_recurring_transaction_by_id(pool, tenant_id, recurring_transaction_id).await?;
+    ensure_upcoming(&definition, occurrence_date)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO recurring_transaction_skips (recurring_transaction_id, occurrence_date, created_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+        recurring_transaction_id,
+        occurrence_date,
+        created_by_user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Restores a skipped occurrence that has not fallen due yet.
+pub async fn unskip_occurrence(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+    occurrence_date: NaiveDate,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Restoring occurrence {} of recurring transaction with ID: {} for tenant ID: {}",
+        occurrence_date, recurring_transaction_id, tenant_id
+    );
+
+    let definition =
+        get_recurring_transaction_by_id(pool, tenant_id, recurring_transaction_id).await?;
+    ensure_upcoming(&definition, occurrence_date)?;
+
+    let affected_rows = sqlx::query!(
+        "DELETE FROM recurring_transaction_skips WHERE recurring_transaction_id = $1 AND occurrence_date = $2",
+        recurring_transaction_id,
+        occurrence_date
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "The occurrence on {} is not skipped",
+            occurrence_date
+        )));
+    }
+
+    Ok(())
+}
+
+/// Edits one materialized occurrence. The transaction stays linked to its series, and the
+/// usual rules for editing submitted or posted transactions apply.
+pub async fn update_occurrence(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    recurring_transaction_id: Uuid,
+    occurrence_date: NaiveDate,
+    updated_by_user_id: Uuid,
+    expected_version: i32,
+    dto: UpdateTransactionDto,
+) -> Result<Transaction, AppError> {
+    info!(
+        "Service: Updating occurrence {} of recurring transaction with ID: {} for tenant ID: {}",
+        occurrence_date, recurring_transaction_id, tenant_id
+    );
+
+    let transaction_id = sqlx::query_scalar!(
+        r#"
+        SELECT id
+        FROM transactions
+        WHERE tenant_id = $1 AND recurring_transaction_id = $2 AND recurring_occurrence_date = $3
+        "#,
+        tenant_id,
+        recurring_transaction_id,
+        occurrence_date
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "No materialized occurrence on {} for recurring transaction {}",
+            occurrence_date, recurring_transaction_id
+        ))
+    })?;
+
+    transaction::update_transaction(
+        pool,
+        tenant_id,
+        transaction_id,
+        updated_by_user_id,
+        expected_version,
+        dto,
+    )
+    .await
+}
+
+/// Materializes every occurrence that is due on or before `as_of`, across all tenants.
+/// Occurrences moved to an earlier business day are due on that day.
+///
+/// Each definition is processed in its own database transaction and locked with
+/// `FOR UPDATE SKIP LOCKED`, so concurrent scheduler runs never generate an occurrence twice.
+/// Returns the number of transactions created.
+pub async fn materialize_due_recurring_transactions(
+    pool: &PgPool,
+    as_of: NaiveDate,
+) -> Result<usize, AppError> {
+    info!(
+        "Service: Materializing recurring transactions due on or before {}",
+        as_of
+    );
+
+    let due_ids: Vec<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT id
+        FROM recurring_transactions
+        WHERE is_active = TRUE AND paused_at IS NULL
+            AND next_due_date IS NOT NULL
+            AND (next_due_date <= $1 OR (business_day_rule IN ('PREVIOUS', 'MODIFIED_NEXT') AND next_due_date <= $2))
+        ORDER BY next_due_date
+        "#,
+        as_of,
+        as_of + Duration::days(business_calendar::MAX_ADJUSTMENT_DAYS)
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut created = 0;
+    for recurring_transaction_id in due_ids {
+        match materialize_definition(pool, recurring_transaction_id, as_of).await {
+            Ok(count) => created += count,
+            Err(e) => warn!(
+                "Failed to materialize recurring transaction {}: {}",
+                recurring_transaction_id, e
+            ),
+        }
+    }
+
+    info!(
+        "Materialized {} recurring transaction occurrence(s)",
+        created
+    );
+    Ok(created)
+}
+
+/// Generates all outstanding occurrences of a single definition and advances its schedule.
+async fn materialize_definition(
+    pool: &PgPool,
+    recurring_transaction_id: Uuid,
+    as_of: NaiveDate,
+) -> Result<usize, AppError> {
+    let mut db_tx = pool.begin().await?;
+
+    let definition = query_as!(
+        RecurringTransaction,
+        r#"
+        SELECT
+            id, tenant_id, description, type as "r#type: TransactionType", category_id, account_id, amount,
+            currency_code, frequency_value, frequency_unit, start_date, end_date,
+            last_generated_date, next_due_date, is_active, notes, journal_template,
+            escalation_percent, escalation_month, escalated_through, paused_at,
+            business_day_rule as "business_day_rule: BusinessDayRule",
+            created_at, created_by, updated_at, updated_by
+        FROM recurring_transactions
+        WHERE id = $1 AND is_active = TRUE AND paused_at IS NULL
+            AND (next_due_date <= $2 OR (business_day_rule IN ('PREVIOUS', 'MODIFIED_NEXT') AND next_due_date <= $3))
+        FOR UPDATE SKIP LOCKED
+        "#,
+        recurring_transaction_id,
+        as_of,
+        as_of + Duration::days(business_calendar::MAX_ADJUSTMENT_DAYS)
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?;
+
+    // Another scheduler run picked it up first, or it is no longer due.
+    let Some(definition) = definition else {
+        db_tx.rollback().await?;
+        return Ok(0);
+    };
+
+    let unit: RecurringFrequencyUnit = definition
+        .frequency_unit
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    // Occurrences are posted from the template; without one they would have no journal entries
+    let Some(journal_template) = definition.journal_template.clone() else {
+        db_tx.rollback().await?;
+        return Err(AppError::Validation(format!(
+            "Recurring transaction {} has no journal template and cannot be posted",
+            definition.id
+        )));
+    };
+    let mut journal_lines: Vec<RecurringJournalLine> = serde_json::from_value(journal_template)
+        .map_err(|e| AppError::InternalServerError(format!("Invalid journal template: {}", e)))?;
+    let business_days =
+        business_calendar::load_business_days(&mut *db_tx, definition.tenant_id).await?;
+    let skipped: HashSet<NaiveDate> = sqlx::query_scalar!(
+        r#"
+        SELECT occurrence_date
+        FROM recurring_transaction_skips
+        WHERE recurring_transaction_id = $1
+        "#,
+        definition.id
+    )
+    .fetch_all(&mut *db_tx)
+    .await?
+    .into_iter()
+    .collect();
+
+    let mut created = 0;
+    let mut amount = definition.amount;
+    let mut escalated_through = definition.escalated_through;
+    let mut last_generated = definition.last_generated_date;
+    let mut next_due = definition.next_due_date;
+    while let Some(due_date) = next_due {
+        if definition.end_date.is_some_and(|end| due_date > end) {
+            break;
+        }
+        // The occurrence is generated on its posting date; None means the rule skips it
+        let posting_date = business_days.adjust(due_date, definition.business_day_rule);
+        if posting_date.unwrap_or(due_date) > as_of {
+            break;
+        }
+
+        // Apply every escalation date passed since the last one, before this occurrence
+        if let (Some(percent), Some(month)) =
+            (definition.escalation_percent, definition.escalation_month)
+        {
+            let after = escalated_through.unwrap_or(definition.start_date);
+            for escalation_date in
+                recurring_template::escalation_dates(month as u32, after, due_date)
+            {
+                amount = recurring_template::escalate_journal_lines(&mut journal_lines, percent);
+                escalated_through = Some(escalation_date);
+            }
+        }
+
+        let mut posting_date = posting_date.filter(|_| !skipped.contains(&due_date));
+        // An occurrence dated in a closed period is skipped rather than retried on every run
+        if let Some(date) = posting_date {
+            let locked = fiscal_period::locked_period(
+                &mut *db_tx,
+                definition.tenant_id,
+                definition.created_by,
+                date,
+            )
+            .await?;
+            if let Some(period_name) = locked {
+                warn!(
+                    "Skipping occurrence {} of recurring transaction {}: fiscal period '{}' is closed",
+                    due_date, definition.id, period_name
+                );
+                skip_closed_occurrence(&mut db_tx, &definition, due_date).await?;
+                posting_date = None;
+            }
+        }
+        if let Some(posting_date) = posting_date {
+            create_occurrence(
+                &mut db_tx,
+                &definition,
+                amount,
+                &journal_lines,
+                due_date,
+                posting_date,
+            )
+            .await?;
+            created += 1;
+            last_generated = Some(due_date);
+        }
+        next_due =
+            Some(unit.next_after(definition.start_date, definition.frequency_value, due_date));
+    }
+
+    // Past the end of the series: stop scheduling.
+    if let (Some(due), Some(end)) = (next_due, definition.end_date) {
+        if due > end {
+            next_due = None;
+        }
+    }
+
+    let journal_template = serde_json::to_value(&journal_lines).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to serialize journal template: {}", e))
+    })?;
+
+    sqlx::query!(
+        r#"
+        UPDATE recurring_transactions
+        SET
+            last_generated_date = $2, next_due_date = $3, amount = $4,
+            journal_template = $5, escalated_through = $6,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        definition.id,
+        last_generated,
+        next_due,
+        amount,
+        journal_template,
+        escalated_through
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(created)
+}
+
+/// Inserts one materialized occurrence with the journal legs of its template.
+/// Placeholders in the description, notes and memos are rendered for `due_date`; the
+/// transaction is dated `posting_date`, the due date moved to a business day.
+async fn create_occurrence(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    definition: &RecurringTransaction,
+    amount: Decimal,
+    journal_lines: &[RecurringJournalLine],
+    due_date: NaiveDate,
+    posting_date: NaiveDate,
+) -> Result<Uuid, AppError> {
+    let text_key = privacy::sealing_key(&mut **db_tx, definition.tenant_id).await?;
+
+    let transaction_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO transactions (
+            tenant_id, transaction_date, description, type, category_id,
+            amount, currency_code, notes, status, posted_at, posted_by, created_by, updated_by,
+            recurring_transaction_id, recurring_occurrence_date
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'POSTED', NOW(), $9, $9, $9, $10, $11)
+        RETURNING id
+        "#,
+        definition.tenant_id,
+        posting_date,
+        privacy::seal(
+            text_key.as_ref(),
+            recurring_template::render_template(&definition.description, due_date)
+        )?,
+        definition.r#type as TransactionType,
+        definition.category_id,
+        amount,
+        definition.currency_code,
+        definition
+            .notes
+            .as_deref()
+            .map(|notes| recurring_template::render_template(notes, due_date)),
+        definition.created_by,
+        definition.id,
+        due_date
+    )
+    .fetch_one(&mut **db_tx)
+    .await?;
+
+    for line in journal_lines {
+        let (exchange_rate, converted_amount) = currency_conversion::base_currency_amounts(
+            &mut **db_tx,
+            definition.tenant_id,
+            line.amount,
+            &definition.currency_code,
+            posting_date,
+            None,
+            None,
+        )
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO journal_entries (
+                transaction_id, account_id, entry_type, amount, currency_code,
+                exchange_rate, converted_amount, memo, created_by, updated_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            "#,
+            transaction_id,
+            line.account_id,
+            line.entry_type as JournalEntryType,
+            line.amount,
+            definition.currency_code,
+            exchange_rate,
+            converted_amount,
+            privacy::seal_opt(
+                text_key.as_ref(),
+                line.memo
+                    .as_deref()
+                    .map(|memo| recurring_template::render_template(memo, due_date))
+            )?,
+            definition.created_by
+        )
+        .execute(&mut **db_tx)
+        .await?;
+    }
+    balance_snapshot::record_transaction(&mut **db_tx, transaction_id, SnapshotChange::Posted)
+        .await?;
+
+    Ok(transaction_id)
+}
+
+/// Records an occurrence whose posting date is in a closed period as skipped, so it is
+/// listed as such and the schedule moves past it.
+async fn skip_closed_occurrence(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    definition: &RecurringTransaction,
+    due_date: NaiveDate,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO recurring_transaction_skips (recurring_transaction_id, occurrence_date, created_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+        definition.id,
+        due_date,
+        definition.created_by
+    )
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(())
+}
+
+/// Scheduled dates from the next due date on, ending with the series. Skipped dates are
+/// included.
+pub fn upcoming_dates(
+    definition: &RecurringTransaction,
+) -> Result<impl Iterator<Item = NaiveDate> + '_, AppError> {
+    let unit: RecurringFrequencyUnit = definition
+        .frequency_unit
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    Ok(
+        std::iter::successors(definition.next_due_date, move |due_date| {
+            Some(unit.next_after(definition.start_date, definition.frequency_value, *due_date))
+        })
+        .take_while(|due_date| definition.end_date.is_none_or(|end| *due_date <= end)),
+    )
+}
+
+/// Ensures `date` is a scheduled occurrence that has not fallen due yet.
+fn ensure_upcoming(definition: &RecurringTransaction, date: NaiveDate) -> Result<(), AppError> {
+    let is_upcoming = upcoming_dates(definition)?
+        .take_while(|due_date| *due_date <= date)
+        .any(|due_date| due_date == date);
+    if !is_upcoming {
+        return Err(AppError::Validation(format!(
+            "{} is not an upcoming occurrence of recurring transaction {}",
+            date, definition.id
+        )));
+    }
+    Ok(())
+}
+
+/// Validates a standing journal entry template and returns its total (sum of debits).
+///
+/// Requires at least one debit and one credit leg, positive amounts, balanced totals, at most
+/// one leg per account and side, and every account to be active and owned by the tenant.
+async fn validate_journal_lines(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    lines: &[RecurringJournalLineDto],
+) -> Result<Decimal, AppError> {
+    if lines.len() < 2 {
+        return Err(AppError::Validation(
+            "A journal entry template needs at least two lines".to_string(),
+        ));
+    }
+
+    let mut debits = Decimal::ZERO;
+    let mut credits = Decimal::ZERO;
+    let mut legs = HashSet::new();
+    for line in lines {
+        // A template lists each account at most once per side
+        if !legs.insert((line.account_id, line.entry_type)) {
+            return Err(AppError::Validation(format!(
+                "Account {} has more than one {} line; combine them into one",
+                line.account_id,
+                String::from(line.entry_type)
+            )));
+        }
+        if line.amount <= Decimal::ZERO {
+            return Err(AppError::Validation(format!(
+                "Journal line amount for account {} must be positive",
+                line.account_id
+            )));
+        }
+        match line.entry_type {
+            JournalEntryType::Debit => debits += line.amount,
+            JournalEntryType::Credit => credits += line.amount,
+        }
+    }
+
+    if debits.is_zero() || credits.is_zero() {
+        return Err(AppError::Validation(
+            "A journal entry template needs both debit and credit lines".to_string(),
+        ));
+    }
+    if debits != credits {
+        return Err(AppError::Validation(format!(
+            "Journal entry template is unbalanced: debits {} != credits {}",
+            debits, credits
+        )));
+    }
+
+    let account_ids: Vec<Uuid> = lines.iter().map(|line| line.account_id).collect();
+    let valid_accounts = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(DISTINCT id) as "count!"
+        FROM accounts
+        WHERE id = ANY($1) AND tenant_id = $2 AND is_active = TRUE AND archived_at IS NULL
+        "#,
+        &account_ids,
+        tenant_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let mut distinct_ids = account_ids.clone();
+    distinct_ids.sort();
+    distinct_ids.dedup();
+    if valid_accounts != distinct_ids.len() as i64 {
+        return Err(AppError::Validation(format!(
+            "One or more template accounts are invalid, inactive or archived for tenant {}",
+            tenant_id
+        )));
+    }
+
+    Ok(debits)
+}
+
+/// Checks the placeholders of every text that is rendered per occurrence.
+fn check_templates(
+    description: Option<&str>,
+    notes: Option<&str>,
+    lines: Option<&[RecurringJournalLineDto]>,
+) -> Result<(), AppError> {
+    let memos = lines
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|line| line.memo.as_deref());
+    for text in description.into_iter().chain(notes).chain(memos) {
+        recurring_template::check_template(text).map_err(AppError::Validation)?;
+    }
+    Ok(())
+}
+
+/// Validates an optional escalation rule, returning `(percent, month)`.
+fn escalation_rule(
+    percent: Option<Decimal>,
+    month: Option<i16>,
+) -> Result<Option<(Decimal, i16)>, AppError> {
+    match (percent, month) {
+        (None, None) => Ok(None),
+        (Some(percent), Some(month)) => {
+            if percent <= Decimal::ZERO || percent > Decimal::ONE_HUNDRED {
+                return Err(AppError::Validation(
+                    "escalation_percent must be greater than 0 and at most 100".to_string(),
+                ));
+            }
+            Ok(Some((percent, month)))
+        }
+        _ => Err(AppError::Validation(
+            "escalation_percent and escalation_month must be given together".to_string(),
+        )),
+    }
+}
+
+/// Serializes validated template lines into the JSONB representation stored on the definition.
+fn journal_template_json(lines: &[RecurringJournalLineDto]) -> Result<JsonValue, AppError> {
+    let template: Vec<RecurringJournalLine> = lines
+        .iter()
+        .map(|line| RecurringJournalLine {
+            account_id: line.account_id,
+            entry_type: line.entry_type,
+            amount: line.amount,
+            memo: line.memo.clone(),
+        })
+        .collect();
+
+    serde_json::to_value(&template).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to serialize journal template: {}", e))
+    })
+}
@@ -0,0 +1,219 @@
+//! Optional Redis shared by every instance of a deployment (`REDIS_URL`).
+//!
+//! | Use               | With Redis                                   | Without                          |
+//! |-------------------|----------------------------------------------|----------------------------------|
+//! | Reports           | Served from Redis for `REPORT_CACHE_SECS`    | Computed on every request        |
+//! | Rate limiting     | One bucket per client across instances       | Each instance limits on its own  |
+//! | Idempotency keys  | Claimed and replayed from Redis              | Kept in `idempotency_keys`       |
+//! | Scheduler locks   | One instance runs a locked job at a time     | Every instance runs it           |
+//!
+//! Keys start with `REDIS_KEY_PREFIX`, so deployments can share a Redis. Cached reports
+//! belong to a per-tenant generation that every domain event of the tenant moves on, so a
+//! posted, voided or reversed transaction shows up in the next report. A Redis outage turns
+//! the cache and rate limits back into their per-instance behavior; idempotent requests
+//! fail with 503 and locked jobs skip their run instead.
+
+use std::{future::Future, sync::OnceLock, time::Duration};
+
+use redis::{
+    aio::{ConnectionManager, ConnectionManagerConfig},
+    AsyncCommands, RedisError, Script,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{config, error::AppError, services::domain_event, utils::crypto::sha256_hex};
+
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+/// Requests wait at most this long on Redis before falling back or failing.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Deletes a lock only while it still holds the caller's token.
+const RELEASE_LOCK: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+static CONNECTION: OnceLock<ConnectionManager> = OnceLock::new();
+
+/// Connects to `REDIS_URL` when it is set. Call once at startup; a configured but
+/// unreachable Redis stops the boot.
+pub async fn connect() -> Result<(), RedisError> {
+    let Some(url) = &config::get().redis.url else {
+        return Ok(());
+    };
+    let client = redis::Client::open(url.as_str())?;
+    let connection = ConnectionManager::new_with_config(
+        client,
+        ConnectionManagerConfig::new()
+            .set_connection_timeout(CONNECTION_TIMEOUT)
+            .set_response_timeout(RESPONSE_TIMEOUT),
+    )
+    .await?;
+    info!("Connected to Redis; shared state is kept there");
+    let _ = CONNECTION.set(connection);
+    Ok(())
+}
+
+/// A handle on the shared connection, or `None` when Redis is not configured. Handles are
+/// cheap; the connection reconnects on its own.
+pub fn connection() -> Option<ConnectionManager> {
+    CONNECTION.get().cloned()
+}
+
+/// `name` under the deployment's key prefix.
+pub fn key(name: &str) -> String {
+    format!("{}{}", config::get().redis.key_prefix, name)
+}
+
+// --- Report cache ---
+
+/// Returns the tenant's report for `params` from the cache, computing and storing it on a
+/// miss. Without Redis, or with the cache disabled, the report is computed every time.
+pub async fn cached_report<T, P, F, Fut>(
+    tenant_id: Uuid,
+    report: &str,
+    params: &P,
+    compute: F,
+) -> Result<T, AppError>
+where
+    T: Serialize + DeserializeOwned,
+    P: Serialize,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    let ttl_secs = config::get().redis.report_cache_secs;
+    let Some(mut connection) = connection().filter(|_| ttl_secs > 0) else {
+        return compute().await;
+    };
+
+    let cache_key = match report_key(&mut connection, tenant_id, report, params).await {
+        Ok(cache_key) => cache_key,
+        Err(e) => {
+            warn!("Report cache unavailable, computing {}: {}", report, e);
+            return compute().await;
+        }
+    };
+    match connection.get::<_, Option<Vec<u8>>>(&cache_key).await {
+        Ok(Some(cached)) => match serde_json::from_slice(&cached) {
+            Ok(cached) => return Ok(cached),
+            Err(e) => warn!("Discarding unreadable cached {}: {}", report, e),
+        },
+        Ok(None) => {}
+        Err(e) => warn!("Report cache read failed for {}: {}", report, e),
+    }
+
+    let computed = compute().await?;
+    match serde_json::to_vec(&computed) {
+        Ok(serialized) => {
+            if let Err(e) = connection
+                .set_ex::<_, _, ()>(&cache_key, serialized, ttl_secs)
+                .await
+            {
+                warn!("Report cache write failed for {}: {}", report, e);
+            }
+        }
+        Err(e) => warn!("Could not serialize {} for the cache: {}", report, e),
+    }
+    Ok(computed)
+}
+
+/// Key of a report in the tenant's current generation.
+async fn report_key<P: Serialize>(
+    connection: &mut ConnectionManager,
+    tenant_id: Uuid,
+    report: &str,
+    params: &P,
+) -> Result<String, RedisError> {
+    let generation: Option<u64> = connection.get(generation_key(tenant_id)).await?;
+    let params = serde_json::to_vec(params).unwrap_or_default();
+    Ok(key(&format!(
+        "report:{}:{}:{}:{}",
+        tenant_id,
+        generation.unwrap_or(0),
+        report,
+        sha256_hex(&params)
+    )))
+}
+
+fn generation_key(tenant_id: Uuid) -> String {
+    key(&format!("report_generation:{}", tenant_id))
+}
+
+/// Moves a tenant's reports to a new generation on each of its domain events, so cached
+/// reports from before the change are no longer found. A no-op without Redis.
+pub fn spawn_report_cache_invalidator() {
+    if connection().is_none() || config::get().redis.report_cache_secs == 0 {
+        return;
+    }
+    domain_event::spawn_subscriber("report_cache", |event| async move {
+        let Some(mut connection) = connection() else {
+            return;
+        };
+        let tenant_id = event.tenant_id();
+        if let Err(e) = connection
+            .incr::<_, _, u64>(generation_key(tenant_id), 1)
+            .await
+        {
+            warn!(
+                "Could not invalidate cached reports of tenant {}: {}",
+                tenant_id, e
+            );
+        }
+    });
+}
+
+// --- Locks ---
+
+/// Runs `job` unless another instance holds the lock `name`. The lock expires after
+/// `ttl` should this instance die mid-run, so `ttl` must exceed the longest run. If Redis
+/// cannot be reached the run is skipped; without Redis the job always runs.
+pub async fn run_exclusive<F, Fut>(name: &str, ttl: Duration, job: F)
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let Some(mut connection) = connection() else {
+        job().await;
+        return;
+    };
+
+    let lock_key = key(&format!("lock:{}", name));
+    let token = Uuid::new_v4().to_string();
+    let acquired: Result<Option<String>, RedisError> = redis::cmd("SET")
+        .arg(&lock_key)
+        .arg(&token)
+        .arg("NX")
+        .arg("PX")
+        .arg(ttl.as_millis() as u64)
+        .query_async(&mut connection)
+        .await;
+    match acquired {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            info!("Skipping {}: another instance is running it", name);
+            return;
+        }
+        Err(e) => {
+            warn!("Skipping {}: could not take its lock: {}", name, e);
+            return;
+        }
+    }
+
+    job().await;
+
+    let released: Result<i64, RedisError> = Script::new(RELEASE_LOCK)
+        .key(&lock_key)
+        .arg(&token)
+        .invoke_async(&mut connection)
+        .await;
+    if let Err(e) = released {
+        warn!(
+            "Could not release the lock of {}; it expires on its own: {}",
+            name, e
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! In-process cache of reference data: currencies, account types and exchange rates.
+//!
+//! Dashboards ask for these on every refresh while they change rarely (rates about once a
+//! day). The owning services read through the caches below and call `changed` after every
+//! write; it clears the entries in this process and tells the other instances over
+//! Postgres `NOTIFY`, where `spawn_invalidation_listener` clears theirs. Entries also expire
+//! after `TIME_TO_LIVE`, which bounds how stale a missed notification can leave them.
+
+use std::{sync::OnceLock, time::Duration};
+
+use moka::future::Cache;
+use sqlx::{postgres::PgListener, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::{account_type::AccountType, currency::Currency, exchange_rate::ExchangeRate};
+
+/// `Cache-Control` value for responses built from cached reference data. Private, as
+/// exchange rates are per tenant and every response needs a signed-in user.
+pub const CACHE_CONTROL: &str = "private, max-age=300";
+
+const TIME_TO_LIVE: Duration = Duration::from_secs(600);
+
+/// Tenants whose rate lists are kept per instance.
+const MAX_RATE_LISTS: u64 = 1_000;
+
+/// Postgres channel carrying the kind of reference data that changed.
+const CHANNEL: &str = "reference_data_changed";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A kind of cached reference data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceData {
+    Currencies,
+    AccountTypes,
+    ExchangeRates,
+}
+
+impl ReferenceData {
+    const ALL: [ReferenceData; 3] = [
+        ReferenceData::Currencies,
+        ReferenceData::AccountTypes,
+        ReferenceData::ExchangeRates,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ReferenceData::Currencies => "currencies",
+            ReferenceData::AccountTypes => "account_types",
+            ReferenceData::ExchangeRates => "exchange_rates",
+        }
+    }
+
+    fn parse(payload: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == payload)
+    }
+}
+
+/// Active currencies, ordered by name.
+pub fn currencies() -> &'static Cache<(), Vec<Currency>> {
+    static CACHE: OnceLock<Cache<(), Vec<Currency>>> = OnceLock::new();
+    CACHE.get_or_init(|| Cache::builder().time_to_live(TIME_TO_LIVE).build())
+}
+
+/// Active account types, ordered by name.
+pub fn account_types() -> &'static Cache<(), Vec<AccountType>> {
+    static CACHE: OnceLock<Cache<(), Vec<AccountType>>> = OnceLock::new();
+    CACHE.get_or_init(|| Cache::builder().time_to_live(TIME_TO_LIVE).build())
+}
+
+/// Exchange rates by tenant; `None` holds the system-wide rates.
+pub fn exchange_rates() -> &'static Cache<Option<Uuid>, Vec<ExchangeRate>> {
+    static CACHE: OnceLock<Cache<Option<Uuid>, Vec<ExchangeRate>>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        Cache::builder()
+            .max_capacity(MAX_RATE_LISTS)
+            .time_to_live(TIME_TO_LIVE)
+            .build()
+    })
+}
+
+/// Drops this process's entries of the given kind.
+fn invalidate(kind: ReferenceData) {
+    match kind {
+        ReferenceData::Currencies => currencies().invalidate_all(),
+        ReferenceData::AccountTypes => account_types().invalidate_all(),
+        ReferenceData::ExchangeRates => exchange_rates().invalidate_all(),
+    }
+}
+
+/// Reports a write to reference data: clears the entries here and notifies the other
+/// instances. A failed notification is logged; their entries then expire with the TTL.
+pub async fn changed(pool: &PgPool, kind: ReferenceData) {
+    invalidate(kind);
+    if let Err(e) = sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(CHANNEL)
+        .bind(kind.as_str())
+        .execute(pool)
+        .await
+    {
+        warn!(
+            "Failed to notify other instances that {} changed: {}",
+            kind.as_str(),
+            e
+        );
+    }
+}
+
+/// Clears the caches when another instance reports a change. While the connection is down
+/// notifications are lost, so every cache is cleared once it is back.
+pub fn spawn_invalidation_listener(pool: PgPool) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = listen(&pool).await {
+                warn!("Reference data listener failed: {}", e);
+            }
+            ReferenceData::ALL.into_iter().for_each(invalidate);
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+async fn listen(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(CHANNEL).await?;
+    info!("Listening for reference data changes on '{}'", CHANNEL);
+
+    loop {
+        // `None` means the connection was lost; the next call reconnects
+        match listener.try_recv().await? {
+            Some(notification) => match ReferenceData::parse(notification.payload()) {
+                Some(kind) => invalidate(kind),
+                None => warn!(
+                    "Ignoring unknown reference data change '{}'",
+                    notification.payload()
+                ),
+            },
+            None => ReferenceData::ALL.into_iter().for_each(invalidate),
+        }
+    }
+}
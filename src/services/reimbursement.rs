@@ -0,0 +1,431 @@
+//! Reimbursable expenses: expenses paid on behalf of someone else (an employer, a client)
+//! and matched to the deposits that later pay them back.
+//!
+//! A deposit can be split across several expenses and an expense can be repaid by several
+//! deposits; neither side is ever over-allocated. Voided transactions and reversed
+//! expenses are left out, and a voided deposit no longer counts as a reimbursement.
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::reimbursement_dto::{
+            MarkReimbursableDto, MatchReimbursementDto, OutstandingReimbursablesQuery,
+        },
+        reimbursement::{
+            OutstandingReimbursable, OutstandingReimbursablesReport, PayerOutstanding,
+            ReimbursableExpense, ReimbursementMatch,
+        },
+    },
+};
+
+/// Marks an expense as reimbursable, or changes who is expected to pay it back.
+pub async fn mark_reimbursable(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    user_id: Uuid,
+    dto: MarkReimbursableDto,
+) -> Result<ReimbursableExpense, AppError> {
+    info!(
+        "Service: Marking transaction ID: {} reimbursable by '{}' for tenant ID: {}",
+        transaction_id, dto.payer, tenant_id
+    );
+
+    let transaction = sqlx::query!(
+        "SELECT type::text as \"type!\", status FROM transactions WHERE id = $1 AND tenant_id = $2",
+        transaction_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Transaction with ID {} not found for tenant {}",
+            transaction_id, tenant_id
+        ))
+    })?;
+    if transaction.r#type != "EXPENSE" {
+        return Err(AppError::Validation(
+            "Only expenses can be marked reimbursable".to_string(),
+        ));
+    }
+    if transaction.status == "VOIDED" {
+        return Err(AppError::Validation(
+            "A voided transaction cannot be marked reimbursable".to_string(),
+        ));
+    }
+
+    let expense = query_as!(
+        ReimbursableExpense,
+        r#"
+        INSERT INTO reimbursable_expenses (transaction_id, tenant_id, payer, notes, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $5)
+        ON CONFLICT (transaction_id) DO UPDATE
+        SET payer = EXCLUDED.payer, notes = EXCLUDED.notes, updated_at = NOW(), updated_by = EXCLUDED.updated_by
+        RETURNING transaction_id, tenant_id, payer, notes, created_at, created_by, updated_at, updated_by
+        "#,
+        transaction_id,
+        tenant_id,
+        dto.payer.trim(),
+        dto.notes,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(expense)
+}
+
+/// Removes the reimbursable marker. Refused while deposits are matched to the expense.
+pub async fn unmark_reimbursable(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Unmarking reimbursable transaction ID: {} for tenant ID: {}",
+        transaction_id, tenant_id
+    );
+
+    let matched = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM reimbursement_matches WHERE expense_transaction_id = $1 AND tenant_id = $2) as "exists!""#,
+        transaction_id,
+        tenant_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if matched {
+        return Err(AppError::Conflict(format!(
+            "Transaction {} has reimbursements matched to it; remove them first",
+            transaction_id
+        )));
+    }
+
+    let affected_rows = sqlx::query!(
+        "DELETE FROM reimbursable_expenses WHERE transaction_id = $1 AND tenant_id = $2",
+        transaction_id,
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Transaction {} is not marked reimbursable",
+            transaction_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Applies a deposit to reimbursable expenses. Each allocation defaults to what the expense
+/// still has outstanding, capped by what is left of the deposit. All or nothing.
+pub async fn match_reimbursement(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: MatchReimbursementDto,
+) -> Result<Vec<ReimbursementMatch>, AppError> {
+    info!(
+        "Service: Matching deposit ID: {} to {} expenses for tenant ID: {}",
+        dto.deposit_transaction_id,
+        dto.allocations.len(),
+        tenant_id
+    );
+
+    let mut expense_ids: Vec<Uuid> = dto
+        .allocations
+        .iter()
+        .map(|a| a.expense_transaction_id)
+        .collect();
+    expense_ids.sort();
+    expense_ids.dedup();
+    if expense_ids.len() != dto.allocations.len() {
+        return Err(AppError::Validation(
+            "Each expense can only be allocated once per request".to_string(),
+        ));
+    }
+
+    let mut db_tx = pool.begin().await?;
+
+    // Locking the deposit serializes concurrent matches against it
+    let deposit = sqlx::query!(
+        r#"
+        SELECT type::text as "type!", status, amount, currency_code, transaction_date,
+               COALESCE((SELECT SUM(m.amount) FROM reimbursement_matches m WHERE m.deposit_transaction_id = t.id), 0) as "allocated!"
+        FROM transactions t
+        WHERE t.id = $1 AND t.tenant_id = $2
+        FOR UPDATE
+        "#,
+        dto.deposit_transaction_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!("Transaction with ID {} not found for tenant {}", dto.deposit_transaction_id, tenant_id))
+    })?;
+    if deposit.r#type != "INCOME" || deposit.status == "VOIDED" {
+        return Err(AppError::Validation(
+            "A reimbursement must be a deposit (INCOME) that is not voided".to_string(),
+        ));
+    }
+    let mut remaining = deposit.amount - deposit.allocated;
+
+    let mut matches = Vec::with_capacity(dto.allocations.len());
+    for allocation in dto.allocations {
+        let expense = sqlx::query!(
+            r#"
+            SELECT t.amount, t.currency_code, t.transaction_date, t.status,
+                   COALESCE((
+                       SELECT SUM(m.amount)
+                       FROM reimbursement_matches m
+                       JOIN transactions d ON d.id = m.deposit_transaction_id
+                       WHERE m.expense_transaction_id = r.transaction_id AND d.status <> 'VOIDED'
+                   ), 0) as "reimbursed!"
+            FROM reimbursable_expenses r
+            JOIN transactions t ON t.id = r.transaction_id
+            WHERE r.transaction_id = $1 AND r.tenant_id = $2
+            FOR UPDATE OF r
+            "#,
+            allocation.expense_transaction_id,
+            tenant_id
+        )
+        .fetch_optional(&mut *db_tx)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Transaction {} is not a reimbursable expense",
+                allocation.expense_transaction_id
+            ))
+        })?;
+
+        if expense.status == "VOIDED" {
+            return Err(AppError::Validation(format!(
+                "Expense {} is voided",
+                allocation.expense_transaction_id
+            )));
+        }
+        if expense.currency_code != deposit.currency_code {
+            return Err(AppError::Validation(format!(
+                "Expense {} is in {} but the deposit is in {}",
+                allocation.expense_transaction_id, expense.currency_code, deposit.currency_code
+            )));
+        }
+        if deposit.transaction_date < expense.transaction_date {
+            return Err(AppError::Validation(format!(
+                "The deposit predates expense {}",
+                allocation.expense_transaction_id
+            )));
+        }
+
+        let outstanding = expense.amount - expense.reimbursed;
+        if outstanding <= Decimal::ZERO {
+            return Err(AppError::Conflict(format!(
+                "Expense {} is already fully reimbursed",
+                allocation.expense_transaction_id
+            )));
+        }
+        let amount = allocation
+            .amount
+            .unwrap_or_else(|| outstanding.min(remaining));
+        if amount <= Decimal::ZERO {
+            return Err(AppError::Validation(
+                "The deposit is already fully allocated".to_string(),
+            ));
+        }
+        if amount > outstanding {
+            return Err(AppError::Validation(format!(
+                "Expense {} only has {} outstanding",
+                allocation.expense_transaction_id, outstanding
+            )));
+        }
+        if amount > remaining {
+            return Err(AppError::Validation(format!(
+                "Only {} of the deposit is left to allocate",
+                remaining
+            )));
+        }
+        remaining -= amount;
+
+        let matched = query_as!(
+            ReimbursementMatch,
+            r#"
+            INSERT INTO reimbursement_matches (tenant_id, expense_transaction_id, deposit_transaction_id, amount, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (expense_transaction_id, deposit_transaction_id) DO UPDATE
+            SET amount = reimbursement_matches.amount + EXCLUDED.amount
+            RETURNING id, tenant_id, expense_transaction_id, deposit_transaction_id, amount, created_at, created_by
+            "#,
+            tenant_id,
+            allocation.expense_transaction_id,
+            dto.deposit_transaction_id,
+            amount,
+            user_id
+        )
+        .fetch_one(&mut *db_tx)
+        .await?;
+        matches.push(matched);
+    }
+
+    db_tx.commit().await?;
+    Ok(matches)
+}
+
+/// Removes a match; the expense is outstanding again by its amount.
+pub async fn unmatch_reimbursement(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    match_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Removing reimbursement match ID: {} for tenant ID: {}",
+        match_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        "DELETE FROM reimbursement_matches WHERE id = $1 AND tenant_id = $2",
+        match_id,
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Reimbursement match with ID {} not found for tenant {}",
+            match_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Outstanding expenses a deposit could pay back: same currency, dated on or before it.
+/// Expenses whose outstanding amount equals what is left of the deposit come first, then
+/// the oldest.
+pub async fn suggest_expenses(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    deposit_transaction_id: Uuid,
+) -> Result<Vec<OutstandingReimbursable>, AppError> {
+    info!(
+        "Service: Suggesting reimbursable expenses for deposit ID: {} for tenant ID: {}",
+        deposit_transaction_id, tenant_id
+    );
+
+    let deposit = sqlx::query!(
+        r#"
+        SELECT t.currency_code, t.transaction_date,
+               t.amount - COALESCE((SELECT SUM(m.amount) FROM reimbursement_matches m WHERE m.deposit_transaction_id = t.id), 0) as "remaining!"
+        FROM transactions t
+        WHERE t.id = $1 AND t.tenant_id = $2 AND t.type = 'INCOME'
+        "#,
+        deposit_transaction_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Deposit with ID {} not found for tenant {}", deposit_transaction_id, tenant_id)))?;
+
+    let mut expenses =
+        outstanding_expenses(pool, tenant_id, deposit.transaction_date, None).await?;
+    expenses.retain(|expense| expense.currency_code == deposit.currency_code);
+    expenses.sort_by_key(|expense| {
+        (
+            expense.outstanding != deposit.remaining,
+            expense.transaction_date,
+        )
+    });
+
+    Ok(expenses)
+}
+
+/// Reimbursable expenses not yet fully paid back as of a date, totaled by payer and currency.
+pub async fn outstanding_report(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: OutstandingReimbursablesQuery,
+) -> Result<OutstandingReimbursablesReport, AppError> {
+    info!(
+        "Service: Outstanding reimbursables for tenant ID: {}",
+        tenant_id
+    );
+
+    let as_of = query.as_of.unwrap_or_else(|| Utc::now().date_naive());
+    let expenses = outstanding_expenses(pool, tenant_id, as_of, query.payer.as_deref()).await?;
+
+    // Expenses arrive sorted by payer and currency, so each group is contiguous
+    let mut payers: Vec<PayerOutstanding> = Vec::new();
+    for expense in expenses {
+        let same_group = payers
+            .last()
+            .is_some_and(|p| p.payer == expense.payer && p.currency_code == expense.currency_code);
+        if !same_group {
+            payers.push(PayerOutstanding {
+                payer: expense.payer.clone(),
+                currency_code: expense.currency_code.clone(),
+                expense_count: 0,
+                amount: Decimal::ZERO,
+                reimbursed: Decimal::ZERO,
+                outstanding: Decimal::ZERO,
+                expenses: Vec::new(),
+            });
+        }
+        let payer = payers.last_mut().expect("pushed above");
+        payer.expense_count += 1;
+        payer.amount += expense.amount;
+        payer.reimbursed += expense.reimbursed;
+        payer.outstanding += expense.outstanding;
+        payer.expenses.push(expense);
+    }
+
+    Ok(OutstandingReimbursablesReport { as_of, payers })
+}
+
+/// Reimbursable expenses dated up to `as_of` with an amount left to pay back, counting
+/// only deposits dated up to `as_of` that are not voided.
+async fn outstanding_expenses(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    as_of: NaiveDate,
+    payer: Option<&str>,
+) -> Result<Vec<OutstandingReimbursable>, AppError> {
+    let expenses = query_as!(
+        OutstandingReimbursable,
+        r#"
+        SELECT r.transaction_id, t.transaction_date, t.description, r.payer, t.currency_code, t.amount,
+               COALESCE(SUM(m.amount) FILTER (WHERE d.id IS NOT NULL), 0) as "reimbursed!",
+               t.amount - COALESCE(SUM(m.amount) FILTER (WHERE d.id IS NOT NULL), 0) as "outstanding!"
+        FROM reimbursable_expenses r
+        JOIN transactions t ON t.id = r.transaction_id
+        LEFT JOIN reimbursement_matches m ON m.expense_transaction_id = r.transaction_id
+        LEFT JOIN transactions d
+               ON d.id = m.deposit_transaction_id AND d.status <> 'VOIDED' AND d.transaction_date <= $2
+        WHERE r.tenant_id = $1
+          AND t.transaction_date <= $2
+          AND t.status <> 'VOIDED'
+          AND t.reversed_by_id IS NULL
+          AND ($3::text IS NULL OR LOWER(r.payer) = LOWER($3))
+        GROUP BY r.transaction_id, r.payer, t.id
+        HAVING t.amount - COALESCE(SUM(m.amount) FILTER (WHERE d.id IS NOT NULL), 0) > 0
+        ORDER BY LOWER(r.payer), r.payer, t.currency_code, t.transaction_date, t.id
+        "#,
+        tenant_id,
+        as_of,
+        payer
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(expenses)
+}
@@ -0,0 +1,1112 @@
+//! Financial statements built from journal entries.
+//!
+//! Every amount in a statement carries a drill-down link; resolving it with
+//! `resolve_drilldown` returns the journal entries that sum to that amount.
+//! Tenants can replace the built-in section layout with a custom statement layout.
+//! The trial balance and income statement can be restricted to entries tagged with
+//! given dimensions (a P&L per project, say).
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        account_type::AccountNormalBalance,
+        dimension::DimensionFilter,
+        journal_entry::JournalEntryType,
+        report::{
+            CategorySpendLine, CategorySpendPeriod, CategorySpendReport, DrilldownEntry,
+            DrilldownFilter, DrilldownLink, DrilldownResult, DrilldownScope, FinancialStatement,
+            SpendGranularity, StatementLine, StatementSection, StatementType,
+        },
+        statement_layout::{LayoutRow, StatementLayoutDefinition},
+    },
+    services::statement_layout::{self, LayoutAccount},
+};
+
+/// Prefix identifying the drill-down token format, so it can evolve without breaking old links.
+const DRILLDOWN_TOKEN_PREFIX: &str = "d2.";
+const DEFAULT_DRILLDOWN_LIMIT: i64 = 500;
+const MAX_DRILLDOWN_LIMIT: i64 = 5000;
+
+/// Per-account debit/credit totals for a date range.
+#[derive(Debug, Clone)]
+struct AccountBalance {
+    account_id: Uuid,
+    account_code: Option<String>,
+    account_name: String,
+    account_type: String,
+    normal_balance: AccountNormalBalance,
+    debits: Decimal,
+    credits: Decimal,
+}
+
+impl AccountBalance {
+    /// Balance signed by the account's normal side (a debit-normal asset is positive when debited).
+    fn balance(&self) -> Decimal {
+        if self.normal_balance == AccountNormalBalance::DEBIT {
+            self.debits - self.credits
+        } else {
+            self.credits - self.debits
+        }
+    }
+
+    fn as_layout_account(&self) -> LayoutAccount<'_> {
+        LayoutAccount {
+            account_id: self.account_id,
+            account_type: &self.account_type,
+            account_code: self.account_code.as_deref(),
+        }
+    }
+}
+
+/// Sums posted journal entries per account for the tenant between the given dates (inclusive).
+///
+/// The movement is the running total up to `to_date` less the one up to the day before
+/// `from_date`. Each running total adds the whole months before its date from
+/// `account_balance_snapshots` to the entries of its own month read from the journal.
+/// Snapshots are not kept per dimension, so a dimension-filtered report reads the journal only.
+async fn account_balances(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from_date: Option<NaiveDate>,
+    to_date: NaiveDate,
+    dimensions: DimensionFilter,
+) -> Result<Vec<AccountBalance>, AppError> {
+    if !dimensions.is_empty() {
+        return tagged_account_balances(pool, tenant_id, from_date, to_date, dimensions).await;
+    }
+
+    let to_month = month_start(to_date);
+    let before_from = from_date.and_then(|date| date.pred_opt());
+    let before_from_month = before_from.map(month_start);
+
+    let rows = sqlx::query!(
+        r#"
+        WITH movements AS (
+            SELECT
+                s.account_id,
+                SUM(s.debits) - COALESCE(SUM(s.debits) FILTER (WHERE s.period_month < $5), 0) AS debits,
+                SUM(s.credits) - COALESCE(SUM(s.credits) FILTER (WHERE s.period_month < $5), 0) AS credits
+            FROM account_balance_snapshots s
+            WHERE s.tenant_id = $1 AND s.period_month < $3
+            GROUP BY s.account_id
+            UNION ALL
+            SELECT
+                je.account_id,
+                COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'DEBIT' AND t.transaction_date >= $3), 0)
+                    - COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'DEBIT' AND t.transaction_date BETWEEN $5 AND $4), 0),
+                COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'CREDIT' AND t.transaction_date >= $3), 0)
+                    - COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'CREDIT' AND t.transaction_date BETWEEN $5 AND $4), 0)
+            FROM transactions t
+            JOIN journal_entries je ON je.transaction_id = t.id
+            WHERE t.tenant_id = $1
+              AND t.status = 'POSTED'
+              AND (t.transaction_date BETWEEN $3 AND $2 OR t.transaction_date BETWEEN $5 AND $4)
+            GROUP BY je.account_id
+        )
+        SELECT
+            a.id, a.account_code, a.name, at.name as account_type,
+            at.normal_balance as "normal_balance: AccountNormalBalance",
+            COALESCE(SUM(m.debits), 0) as "debits!",
+            COALESCE(SUM(m.credits), 0) as "credits!"
+        FROM accounts a
+        JOIN account_types at ON a.account_type_id = at.id
+        LEFT JOIN movements m ON m.account_id = a.id
+        WHERE a.tenant_id = $1
+        GROUP BY a.id, a.account_code, a.name, at.name, at.normal_balance
+        ORDER BY a.account_code NULLS LAST, a.name
+        "#,
+        tenant_id,
+        to_date,
+        to_month,
+        before_from,
+        before_from_month
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AccountBalance {
+            account_id: row.id,
+            account_code: row.account_code,
+            account_name: row.name,
+            account_type: row.account_type,
+            normal_balance: row.normal_balance,
+            debits: row.debits,
+            credits: row.credits,
+        })
+        .collect())
+}
+
+/// Sums posted journal entries carrying the given dimensions per account, between the
+/// given dates (inclusive).
+async fn tagged_account_balances(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from_date: Option<NaiveDate>,
+    to_date: NaiveDate,
+    dimensions: DimensionFilter,
+) -> Result<Vec<AccountBalance>, AppError> {
+    let rows = sqlx::query!(
+        r#"
+        WITH movements AS (
+            SELECT
+                je.account_id,
+                COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'DEBIT'), 0) AS debits,
+                COALESCE(SUM(COALESCE(je.converted_amount, je.amount)) FILTER (WHERE je.entry_type = 'CREDIT'), 0) AS credits
+            FROM transactions t
+            JOIN journal_entries je ON je.transaction_id = t.id
+            WHERE t.tenant_id = $1
+              AND t.status = 'POSTED'
+              AND t.transaction_date <= $2
+              AND ($3::date IS NULL OR t.transaction_date >= $3)
+              AND ($4::uuid IS NULL OR je.project_id = $4)
+              AND ($5::uuid IS NULL OR je.class_id = $5)
+              AND ($6::uuid IS NULL OR je.location_id = $6)
+            GROUP BY je.account_id
+        )
+        SELECT
+            a.id, a.account_code, a.name, at.name as account_type,
+            at.normal_balance as "normal_balance: AccountNormalBalance",
+            COALESCE(m.debits, 0) as "debits!",
+            COALESCE(m.credits, 0) as "credits!"
+        FROM accounts a
+        JOIN account_types at ON a.account_type_id = at.id
+        LEFT JOIN movements m ON m.account_id = a.id
+        WHERE a.tenant_id = $1
+        ORDER BY a.account_code NULLS LAST, a.name
+        "#,
+        tenant_id,
+        to_date,
+        from_date,
+        dimensions.project_id,
+        dimensions.class_id,
+        dimensions.location_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AccountBalance {
+            account_id: row.id,
+            account_code: row.account_code,
+            account_name: row.name,
+            account_type: row.account_type,
+            normal_balance: row.normal_balance,
+            debits: row.debits,
+            credits: row.credits,
+        })
+        .collect())
+}
+
+/// First day of the month containing `date`.
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("the 1st exists in every month")
+}
+
+/// Encodes a drill-down filter into an opaque, URL-safe token.
+///
+/// Tokens are not credentials: resolution always re-checks the caller's tenant.
+pub fn encode_drilldown_token(filter: &DrilldownFilter) -> Result<String, AppError> {
+    let json = serde_json::to_vec(filter).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to encode drill-down: {}", e))
+    })?;
+    Ok(format!(
+        "{}{}",
+        DRILLDOWN_TOKEN_PREFIX,
+        BASE64_URL.encode(json)
+    ))
+}
+
+/// Decodes a token produced by `encode_drilldown_token`.
+pub fn decode_drilldown_token(token: &str) -> Result<DrilldownFilter, AppError> {
+    let invalid = || AppError::Validation("Invalid drill-down token".to_string());
+    let payload = token
+        .strip_prefix(DRILLDOWN_TOKEN_PREFIX)
+        .ok_or_else(invalid)?;
+    let json = BASE64_URL.decode(payload).map_err(|_| invalid())?;
+    serde_json::from_slice(&json).map_err(|_| invalid())
+}
+
+/// Builds the drill-down link for the accounts in `scope` over a date range, restricted to
+/// entries with the given dimensions.
+pub fn drilldown_link(
+    tenant_id: Uuid,
+    scope: DrilldownScope,
+    from_date: Option<NaiveDate>,
+    to_date: NaiveDate,
+    dimensions: DimensionFilter,
+) -> Result<DrilldownLink, AppError> {
+    let token = encode_drilldown_token(&DrilldownFilter {
+        tenant_id,
+        scope,
+        from_date,
+        to_date,
+        dimensions,
+    })?;
+    Ok(DrilldownLink {
+        href: format!("/api/v1/reports/drilldown/{}", token),
+        token,
+    })
+}
+
+/// Builds a section with one line per account and a subtotal line, whose drill-down covers
+/// `total_scope`.
+fn build_section(
+    tenant_id: Uuid,
+    title: &str,
+    balances: &[&AccountBalance],
+    total_scope: DrilldownScope,
+    from_date: Option<NaiveDate>,
+    to_date: NaiveDate,
+    dimensions: DimensionFilter,
+) -> Result<StatementSection, AppError> {
+    let mut lines = Vec::with_capacity(balances.len());
+    for balance in balances {
+        lines.push(StatementLine {
+            account_id: Some(balance.account_id),
+            account_code: balance.account_code.clone(),
+            label: balance.account_name.clone(),
+            amount: balance.balance(),
+            drilldown: drilldown_link(
+                tenant_id,
+                DrilldownScope::Account(balance.account_id),
+                from_date,
+                to_date,
+                dimensions,
+            )?,
+        });
+    }
+
+    let total = StatementLine {
+        account_id: None,
+        account_code: None,
+        label: format!("Total {}", title),
+        amount: balances.iter().map(|b| b.balance()).sum(),
+        drilldown: drilldown_link(tenant_id, total_scope, from_date, to_date, dimensions)?,
+    };
+
+    Ok(StatementSection {
+        title: title.to_string(),
+        lines,
+        total,
+    })
+}
+
+/// Drill-down scope covering every account of the named types.
+fn account_types(type_names: &[&str]) -> DrilldownScope {
+    DrilldownScope::AccountTypes(type_names.iter().map(|name| name.to_string()).collect())
+}
+
+/// Returns the balances whose account type name matches `type_name` (case-insensitive).
+fn of_type<'a>(balances: &'a [AccountBalance], type_name: &str) -> Vec<&'a AccountBalance> {
+    balances
+        .iter()
+        .filter(|b| b.account_type.eq_ignore_ascii_case(type_name))
+        .collect()
+}
+
+/// Renders balances through a custom layout. Groups become sections with their accounts;
+/// subtotals become sections with only a total line. Accounts not selected by any group
+/// are left out, and an account is only ever counted in the first group that selects it.
+/// Totals drill down to their row of the layout.
+fn apply_layout(
+    tenant_id: Uuid,
+    layout_id: Uuid,
+    definition: &StatementLayoutDefinition,
+    balances: &[AccountBalance],
+    from_date: Option<NaiveDate>,
+    to_date: NaiveDate,
+    dimensions: DimensionFilter,
+) -> Result<Vec<StatementSection>, AppError> {
+    let accounts: Vec<LayoutAccount> = balances
+        .iter()
+        .map(AccountBalance::as_layout_account)
+        .collect();
+    let row_accounts = statement_layout::row_accounts(definition, &accounts)?;
+    let row_scope = |key: &str| DrilldownScope::LayoutRow {
+        layout_id,
+        row_key: key.to_string(),
+    };
+
+    // row key -> amount
+    let mut computed: HashMap<&str, Decimal> = HashMap::new();
+    let mut sections = Vec::with_capacity(definition.rows.len());
+
+    for row in &definition.rows {
+        match row {
+            LayoutRow::Group(group) => {
+                let member_ids = &row_accounts[group.key.as_str()];
+                let members: Vec<&AccountBalance> = balances
+                    .iter()
+                    .filter(|b| member_ids.contains(&b.account_id))
+                    .collect();
+
+                let section = build_section(
+                    tenant_id,
+                    &group.title,
+                    &members,
+                    row_scope(&group.key),
+                    from_date,
+                    to_date,
+                    dimensions,
+                )?;
+                computed.insert(group.key.as_str(), section.total.amount);
+                sections.push(section);
+            }
+            LayoutRow::Subtotal(subtotal) => {
+                // Every term is known: `row_accounts` rejects the layout otherwise
+                let amount = subtotal.terms.iter().fold(Decimal::ZERO, |amount, term| {
+                    let term_amount = computed[term.key.as_str()];
+                    if term.subtract {
+                        amount - term_amount
+                    } else {
+                        amount + term_amount
+                    }
+                });
+
+                let total = StatementLine {
+                    account_id: None,
+                    account_code: None,
+                    label: subtotal.title.clone(),
+                    amount,
+                    drilldown: drilldown_link(
+                        tenant_id,
+                        row_scope(&subtotal.key),
+                        from_date,
+                        to_date,
+                        dimensions,
+                    )?,
+                };
+                computed.insert(subtotal.key.as_str(), amount);
+                sections.push(StatementSection {
+                    title: subtotal.title.clone(),
+                    lines: Vec::new(),
+                    total,
+                });
+            }
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Builds a statement from a tenant's custom layout, if one applies to this request.
+#[allow(clippy::too_many_arguments)]
+async fn statement_from_layout(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    statement_type: StatementType,
+    layout_id: Option<Uuid>,
+    balances: &[AccountBalance],
+    from_date: Option<NaiveDate>,
+    to_date: NaiveDate,
+    dimensions: DimensionFilter,
+) -> Result<Option<FinancialStatement>, AppError> {
+    let Some((layout_id, definition)) =
+        statement_layout::find_layout_definition(pool, tenant_id, statement_type, layout_id)
+            .await?
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(FinancialStatement {
+        statement_type,
+        tenant_id,
+        from_date,
+        to_date,
+        sections: apply_layout(
+            tenant_id,
+            layout_id,
+            &definition,
+            balances,
+            from_date,
+            to_date,
+            dimensions,
+        )?,
+        net: None, // Custom layouts express their bottom line as a subtotal row
+    }))
+}
+
+/// Trial balance: every account's balance as of a date, grouped by account type.
+/// With dimensions, only entries tagged with all of them count.
+pub async fn trial_balance(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    as_of: Option<NaiveDate>,
+    layout_id: Option<Uuid>,
+    dimensions: DimensionFilter,
+) -> Result<FinancialStatement, AppError> {
+    let to_date = as_of.unwrap_or_else(|| Utc::now().date_naive());
+    info!(
+        "Service: Building trial balance for tenant ID: {} as of {}",
+        tenant_id, to_date
+    );
+
+    let balances = account_balances(pool, tenant_id, None, to_date, dimensions).await?;
+    if let Some(statement) = statement_from_layout(
+        pool,
+        tenant_id,
+        StatementType::TrialBalance,
+        layout_id,
+        &balances,
+        None,
+        to_date,
+        dimensions,
+    )
+    .await?
+    {
+        return Ok(statement);
+    }
+
+    let mut type_names: Vec<&str> = Vec::new();
+    for balance in &balances {
+        if !type_names.contains(&balance.account_type.as_str()) {
+            type_names.push(&balance.account_type);
+        }
+    }
+
+    let sections = type_names
+        .into_iter()
+        .map(|type_name| {
+            build_section(
+                tenant_id,
+                type_name,
+                &of_type(&balances, type_name),
+                account_types(&[type_name]),
+                None,
+                to_date,
+                dimensions,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(FinancialStatement {
+        statement_type: StatementType::TrialBalance,
+        tenant_id,
+        from_date: None,
+        to_date,
+        sections,
+        net: None,
+    })
+}
+
+/// Income statement: revenue and expenses over a period, with net income.
+/// With dimensions, only entries tagged with all of them count (a P&L per project, say).
+pub async fn income_statement(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+    layout_id: Option<Uuid>,
+    dimensions: DimensionFilter,
+) -> Result<FinancialStatement, AppError> {
+    let to_date = to_date.unwrap_or_else(|| Utc::now().date_naive());
+    let from_date = from_date.unwrap_or_else(|| {
+        NaiveDate::from_ymd_opt(to_date.year(), 1, 1).expect("January 1st is always valid")
+    });
+    if from_date > to_date {
+        return Err(AppError::Validation(
+            "from_date must not be after to_date".to_string(),
+        ));
+    }
+    info!(
+        "Service: Building income statement for tenant ID: {} from {} to {}",
+        tenant_id, from_date, to_date
+    );
+
+    let balances = account_balances(pool, tenant_id, Some(from_date), to_date, dimensions).await?;
+    if let Some(statement) = statement_from_layout(
+        pool,
+        tenant_id,
+        StatementType::IncomeStatement,
+        layout_id,
+        &balances,
+        Some(from_date),
+        to_date,
+        dimensions,
+    )
+    .await?
+    {
+        return Ok(statement);
+    }
+
+    let revenue = of_type(&balances, "Revenue");
+    let expenses = of_type(&balances, "Expense");
+
+    let revenue_section = build_section(
+        tenant_id,
+        "Revenue",
+        &revenue,
+        account_types(&["Revenue"]),
+        Some(from_date),
+        to_date,
+        dimensions,
+    )?;
+    let expense_section = build_section(
+        tenant_id,
+        "Expenses",
+        &expenses,
+        account_types(&["Expense"]),
+        Some(from_date),
+        to_date,
+        dimensions,
+    )?;
+
+    let net = StatementLine {
+        account_id: None,
+        account_code: None,
+        label: "Net Income".to_string(),
+        amount: revenue_section.total.amount - expense_section.total.amount,
+        drilldown: drilldown_link(
+            tenant_id,
+            account_types(&["Revenue", "Expense"]),
+            Some(from_date),
+            to_date,
+            dimensions,
+        )?,
+    };
+
+    Ok(FinancialStatement {
+        statement_type: StatementType::IncomeStatement,
+        tenant_id,
+        from_date: Some(from_date),
+        to_date,
+        sections: vec![revenue_section, expense_section],
+        net: Some(net),
+    })
+}
+
+/// Balance sheet: assets, liabilities and equity as of a date.
+///
+/// Revenue and expense activity not yet closed to equity is shown as "Current Earnings"
+/// in the equity section so the statement balances.
+pub async fn balance_sheet(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    as_of: Option<NaiveDate>,
+    layout_id: Option<Uuid>,
+) -> Result<FinancialStatement, AppError> {
+    let to_date = as_of.unwrap_or_else(|| Utc::now().date_naive());
+    info!(
+        "Service: Building balance sheet for tenant ID: {} as of {}",
+        tenant_id, to_date
+    );
+    // Entries are tagged one leg at a time, so a per-dimension balance sheet wouldn't balance
+    let dimensions = DimensionFilter::default();
+
+    let balances = account_balances(pool, tenant_id, None, to_date, dimensions).await?;
+    if let Some(statement) = statement_from_layout(
+        pool,
+        tenant_id,
+        StatementType::BalanceSheet,
+        layout_id,
+        &balances,
+        None,
+        to_date,
+        dimensions,
+    )
+    .await?
+    {
+        return Ok(statement);
+    }
+
+    let assets = of_type(&balances, "Asset");
+    let liabilities = of_type(&balances, "Liability");
+    let equity = of_type(&balances, "Equity");
+    let earnings_accounts: Vec<&AccountBalance> = of_type(&balances, "Revenue")
+        .into_iter()
+        .chain(of_type(&balances, "Expense"))
+        .collect();
+
+    let asset_section = build_section(
+        tenant_id,
+        "Assets",
+        &assets,
+        account_types(&["Asset"]),
+        None,
+        to_date,
+        dimensions,
+    )?;
+    let liability_section = build_section(
+        tenant_id,
+        "Liabilities",
+        &liabilities,
+        account_types(&["Liability"]),
+        None,
+        to_date,
+        dimensions,
+    )?;
+    let mut equity_section = build_section(
+        tenant_id,
+        "Equity",
+        &equity,
+        account_types(&["Equity"]),
+        None,
+        to_date,
+        dimensions,
+    )?;
+
+    let current_earnings: Decimal = earnings_accounts
+        .iter()
+        .map(|b| {
+            if b.account_type.eq_ignore_ascii_case("Revenue") {
+                b.balance()
+            } else {
+                -b.balance()
+            }
+        })
+        .sum();
+
+    equity_section.lines.push(StatementLine {
+        account_id: None,
+        account_code: None,
+        label: "Current Earnings".to_string(),
+        amount: current_earnings,
+        drilldown: drilldown_link(
+            tenant_id,
+            account_types(&["Revenue", "Expense"]),
+            None,
+            to_date,
+            dimensions,
+        )?,
+    });
+    equity_section.total.amount += current_earnings;
+    equity_section.total.drilldown = drilldown_link(
+        tenant_id,
+        account_types(&["Equity", "Revenue", "Expense"]),
+        None,
+        to_date,
+        dimensions,
+    )?;
+
+    let net = StatementLine {
+        account_id: None,
+        account_code: None,
+        label: "Assets less Liabilities and Equity".to_string(),
+        amount: asset_section.total.amount
+            - liability_section.total.amount
+            - equity_section.total.amount,
+        drilldown: drilldown_link(
+            tenant_id,
+            DrilldownScope::AllAccounts,
+            None,
+            to_date,
+            dimensions,
+        )?,
+    };
+
+    Ok(FinancialStatement {
+        statement_type: StatementType::BalanceSheet,
+        tenant_id,
+        from_date: None,
+        to_date,
+        sections: vec![asset_section, liability_section, equity_section],
+        net: Some(net),
+    })
+}
+
+/// Posted expenses per category and month or quarter, in the tenant's base currency.
+///
+/// Aggregated in the database: split transactions count each share under its own category,
+/// other transactions their debit entries under the transaction's category. Amounts are the
+/// entries' base-currency values, so foreign-currency spend is converted at posting.
+pub async fn spend_by_category(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+    granularity: SpendGranularity,
+) -> Result<CategorySpendReport, AppError> {
+    let to_date = to_date.unwrap_or_else(|| Utc::now().date_naive());
+    let from_date = from_date.unwrap_or_else(|| {
+        NaiveDate::from_ymd_opt(to_date.year(), 1, 1).expect("January 1st is always valid")
+    });
+    if from_date > to_date {
+        return Err(AppError::Validation(
+            "from_date must not be after to_date".to_string(),
+        ));
+    }
+    info!(
+        "Service: Building spend by category for tenant ID: {} from {} to {}",
+        tenant_id, from_date, to_date
+    );
+
+    let base_currency_code = sqlx::query_scalar!(
+        "SELECT base_currency_code FROM tenants WHERE id = $1",
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+
+    let rows = sqlx::query!(
+        r#"
+        WITH spend AS (
+            SELECT t.id AS transaction_id, t.transaction_date, ts.category_id,
+                   COALESCE(je.converted_amount, je.amount) AS amount
+            FROM transactions t
+            JOIN transaction_splits ts ON ts.transaction_id = t.id
+            JOIN journal_entries je ON je.id = ts.journal_entry_id
+            WHERE t.tenant_id = $1
+              AND t.status = 'POSTED'
+              AND t.type = 'EXPENSE'
+              AND t.transaction_date BETWEEN $2 AND $3
+            UNION ALL
+            SELECT t.id, t.transaction_date, t.category_id,
+                   SUM(COALESCE(je.converted_amount, je.amount))
+            FROM transactions t
+            JOIN journal_entries je ON je.transaction_id = t.id AND je.entry_type = 'DEBIT'
+            WHERE t.tenant_id = $1
+              AND t.status = 'POSTED'
+              AND t.type = 'EXPENSE'
+              AND t.transaction_date BETWEEN $2 AND $3
+              AND NOT EXISTS (SELECT 1 FROM transaction_splits ts WHERE ts.transaction_id = t.id)
+            GROUP BY t.id, t.transaction_date, t.category_id
+        )
+        SELECT
+            date_trunc($4, s.transaction_date)::date as "period_start!",
+            s.category_id,
+            COALESCE(c.name, 'Uncategorized') as "category_name!",
+            SUM(s.amount) as "amount!",
+            COUNT(DISTINCT s.transaction_id) as "transaction_count!"
+        FROM spend s
+        LEFT JOIN categories c ON c.id = s.category_id
+        GROUP BY 1, s.category_id, c.name
+        ORDER BY 1, 4 DESC, 3
+        "#,
+        tenant_id,
+        from_date,
+        to_date,
+        granularity.as_date_trunc()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut by_period: HashMap<NaiveDate, Vec<CategorySpendLine>> = HashMap::new();
+    for row in rows {
+        by_period
+            .entry(row.period_start)
+            .or_default()
+            .push(CategorySpendLine {
+                category_id: row.category_id,
+                category_name: row.category_name,
+                amount: row.amount,
+                transaction_count: row.transaction_count,
+            });
+    }
+
+    let months = Months::new(granularity.months());
+    let mut period_start = period_start(from_date, granularity);
+    let mut periods = Vec::new();
+    while period_start <= to_date {
+        let next_start = period_start + months;
+        let categories = by_period.remove(&period_start).unwrap_or_default();
+        periods.push(CategorySpendPeriod {
+            period_start,
+            period_end: next_start.pred_opt().unwrap_or(next_start),
+            total: categories.iter().map(|line| line.amount).sum(),
+            categories,
+        });
+        period_start = next_start;
+    }
+
+    Ok(CategorySpendReport {
+        tenant_id,
+        from_date,
+        to_date,
+        granularity,
+        base_currency_code,
+        total: periods.iter().map(|period| period.total).sum(),
+        periods,
+    })
+}
+
+/// First day of the month or quarter containing `date`.
+fn period_start(date: NaiveDate, granularity: SpendGranularity) -> NaiveDate {
+    let month = match granularity {
+        SpendGranularity::Month => date.month(),
+        SpendGranularity::Quarter => (date.month() - 1) / 3 * 3 + 1,
+    };
+    NaiveDate::from_ymd_opt(date.year(), month, 1).expect("the 1st exists in every month")
+}
+
+/// The tenant's accounts in a drill-down scope.
+async fn scope_account_ids(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    scope: &DrilldownScope,
+) -> Result<Vec<Uuid>, AppError> {
+    let account_ids = match scope {
+        DrilldownScope::Account(account_id) => vec![*account_id],
+        DrilldownScope::LayoutRow { layout_id, row_key } => {
+            let definition =
+                statement_layout::get_layout_definition(pool, tenant_id, *layout_id).await?;
+            let accounts = sqlx::query!(
+                r#"
+                SELECT a.id, at.name as account_type, a.account_code
+                FROM accounts a
+                JOIN account_types at ON a.account_type_id = at.id
+                WHERE a.tenant_id = $1
+                ORDER BY a.account_code NULLS LAST, a.name
+                "#,
+                tenant_id
+            )
+            .fetch_all(pool)
+            .await?;
+            let accounts: Vec<LayoutAccount> = accounts
+                .iter()
+                .map(|a| LayoutAccount {
+                    account_id: a.id,
+                    account_type: &a.account_type,
+                    account_code: a.account_code.as_deref(),
+                })
+                .collect();
+            statement_layout::row_accounts(&definition, &accounts)?
+                .remove(row_key.as_str())
+                .ok_or_else(|| AppError::NotFound("Drill-down not found".to_string()))?
+        }
+        DrilldownScope::AccountTypes(type_names) => {
+            let type_names: Vec<String> =
+                type_names.iter().map(|name| name.to_lowercase()).collect();
+            sqlx::query_scalar!(
+                r#"
+                SELECT a.id
+                FROM accounts a
+                JOIN account_types at ON a.account_type_id = at.id
+                WHERE a.tenant_id = $1 AND lower(at.name) = ANY($2)
+                "#,
+                tenant_id,
+                &type_names
+            )
+            .fetch_all(pool)
+            .await?
+        }
+        DrilldownScope::AllAccounts => {
+            sqlx::query_scalar!("SELECT id FROM accounts WHERE tenant_id = $1", tenant_id)
+                .fetch_all(pool)
+                .await?
+        }
+    };
+    Ok(account_ids)
+}
+
+/// Resolves a drill-down token into the journal entries behind a report cell.
+pub async fn resolve_drilldown(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    token: &str,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<DrilldownResult, AppError> {
+    let filter = decode_drilldown_token(token)?;
+    if filter.tenant_id != tenant_id {
+        // Don't reveal that the token belongs to another tenant
+        return Err(AppError::NotFound("Drill-down not found".to_string()));
+    }
+    let account_ids = scope_account_ids(pool, tenant_id, &filter.scope).await?;
+    info!(
+        "Service: Resolving drill-down over {} accounts for tenant ID: {}",
+        account_ids.len(),
+        tenant_id
+    );
+
+    let limit = limit
+        .unwrap_or(DEFAULT_DRILLDOWN_LIMIT)
+        .clamp(1, MAX_DRILLDOWN_LIMIT);
+    let offset = offset.unwrap_or(0).max(0);
+
+    let totals = sqlx::query!(
+        r#"
+        SELECT
+            COALESCE(SUM(CASE WHEN je.entry_type = 'DEBIT' THEN COALESCE(je.converted_amount, je.amount) END), 0) as "debits!",
+            COALESCE(SUM(CASE WHEN je.entry_type = 'CREDIT' THEN COALESCE(je.converted_amount, je.amount) END), 0) as "credits!"
+        FROM journal_entries je
+        JOIN transactions t ON je.transaction_id = t.id
+        WHERE t.tenant_id = $1
+          AND t.status = 'POSTED'
+          AND je.account_id = ANY($2)
+          AND t.transaction_date <= $4
+          AND ($3::date IS NULL OR t.transaction_date >= $3)
+          AND ($5::uuid IS NULL OR je.project_id = $5)
+          AND ($6::uuid IS NULL OR je.class_id = $6)
+          AND ($7::uuid IS NULL OR je.location_id = $7)
+        "#,
+        tenant_id,
+        &account_ids,
+        filter.from_date,
+        filter.to_date,
+        filter.dimensions.project_id,
+        filter.dimensions.class_id,
+        filter.dimensions.location_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let entries = sqlx::query_as!(
+        DrilldownEntry,
+        r#"
+        SELECT
+            je.id as journal_entry_id, t.id as transaction_id, t.transaction_date, t.description,
+            a.id as account_id, a.name as account_name, je.entry_type as "entry_type: JournalEntryType",
+            COALESCE(je.converted_amount, je.amount) as "amount!", a.currency_code, je.memo
+        FROM journal_entries je
+        JOIN transactions t ON je.transaction_id = t.id
+        JOIN accounts a ON je.account_id = a.id
+        WHERE t.tenant_id = $1
+          AND t.status = 'POSTED'
+          AND je.account_id = ANY($2)
+          AND t.transaction_date <= $4
+          AND ($3::date IS NULL OR t.transaction_date >= $3)
+          AND ($5::uuid IS NULL OR je.project_id = $5)
+          AND ($6::uuid IS NULL OR je.class_id = $6)
+          AND ($7::uuid IS NULL OR je.location_id = $7)
+        ORDER BY t.transaction_date, t.created_at, je.entry_type
+        LIMIT $8 OFFSET $9
+        "#,
+        tenant_id,
+        &account_ids,
+        filter.from_date,
+        filter.to_date,
+        filter.dimensions.project_id,
+        filter.dimensions.class_id,
+        filter.dimensions.location_id,
+        limit,
+        offset
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(DrilldownResult {
+        filter,
+        total_debits: totals.debits,
+        total_credits: totals.credits,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::{
+        account_types, apply_layout, decode_drilldown_token, drilldown_link, AccountBalance,
+    };
+    use crate::models::{
+        account_type::AccountNormalBalance, dimension::DimensionFilter, report::DrilldownScope,
+        statement_layout::StatementLayoutDefinition,
+    };
+
+    fn link(scope: DrilldownScope) -> String {
+        let to_date = NaiveDate::from_ymd_opt(2025, 6, 30).unwrap();
+        drilldown_link(
+            Uuid::new_v4(),
+            scope,
+            None,
+            to_date,
+            DimensionFilter::default(),
+        )
+        .unwrap()
+        .token
+    }
+
+    #[test]
+    fn tokens_round_trip_their_scope() {
+        let scope = account_types(&["Revenue", "Expense"]);
+        let filter = decode_drilldown_token(&link(scope.clone())).unwrap();
+        assert_eq!(filter.scope, scope);
+        assert_eq!(filter.from_date, None);
+    }
+
+    #[test]
+    fn totals_stay_short_whatever_the_chart_of_accounts() {
+        // A total references account types, so its link is as short as a single account's
+        assert!(link(account_types(&["Equity", "Revenue", "Expense"])).len() < 200);
+        assert!(link(DrilldownScope::AllAccounts).len() < 200);
+    }
+
+    #[test]
+    fn tokens_of_other_formats_are_rejected() {
+        let token = link(DrilldownScope::Account(Uuid::new_v4()));
+        let legacy = token.replacen("d2.", "d1.", 1);
+        assert!(decode_drilldown_token(&legacy).is_err());
+        assert!(decode_drilldown_token("d2.not-base64!").is_err());
+    }
+
+    fn balance(
+        account_type: &str,
+        code: &str,
+        normal: AccountNormalBalance,
+        debits: i64,
+        credits: i64,
+    ) -> AccountBalance {
+        AccountBalance {
+            account_id: Uuid::new_v4(),
+            account_code: Some(code.to_string()),
+            account_name: format!("Account {}", code),
+            account_type: account_type.to_string(),
+            normal_balance: normal,
+            debits: Decimal::from(debits),
+            credits: Decimal::from(credits),
+        }
+    }
+
+    #[test]
+    fn layout_subtotals_nest_and_drill_down_to_their_row() {
+        let layout_id = Uuid::new_v4();
+        let definition: StatementLayoutDefinition = serde_json::from_value(json!({ "rows": [
+            { "kind": "GROUP", "key": "rev", "title": "Revenue", "account_types": ["Revenue"] },
+            { "kind": "GROUP", "key": "cogs", "title": "Cost of sales", "account_code_prefixes": ["5"] },
+            { "kind": "SUBTOTAL", "key": "gross", "title": "Gross profit",
+              "terms": [{ "key": "rev" }, { "key": "cogs", "subtract": true }] },
+            { "kind": "GROUP", "key": "opex", "title": "Operating expenses", "account_types": ["Expense"] },
+            { "kind": "SUBTOTAL", "key": "net", "title": "Net income",
+              "terms": [{ "key": "gross" }, { "key": "opex", "subtract": true }] },
+        ]}))
+        .unwrap();
+        let balances = [
+            balance("Revenue", "4000", AccountNormalBalance::CREDIT, 0, 1000),
+            balance("Expense", "5000", AccountNormalBalance::DEBIT, 400, 0),
+            balance("Expense", "6000", AccountNormalBalance::DEBIT, 250, 0),
+        ];
+        let to_date = NaiveDate::from_ymd_opt(2025, 6, 30).unwrap();
+
+        let sections = apply_layout(
+            Uuid::new_v4(),
+            layout_id,
+            &definition,
+            &balances,
+            None,
+            to_date,
+            DimensionFilter::default(),
+        )
+        .unwrap();
+
+        let totals: Vec<Decimal> = sections.iter().map(|s| s.total.amount).collect();
+        assert_eq!(totals, [1000, 400, 600, 250, 350].map(Decimal::from));
+        // The cost-of-sales account is not counted again under operating expenses
+        assert_eq!(sections[3].lines.len(), 1);
+
+        let net = decode_drilldown_token(&sections[4].total.drilldown.token).unwrap();
+        assert_eq!(
+            net.scope,
+            DrilldownScope::LayoutRow {
+                layout_id,
+                row_key: "net".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,825 @@
+//! Renders custom reports and event reports as CSV files, for download and for scheduled
+//! email delivery, statements and budget performance as XLSX workbooks, and statements as
+//! printable PDFs with the tenant's name and logo.
+//!
+//! Rows are built as JSON objects and passed through the reader's `FieldAccess` before
+//! being written, so an exported or emailed report hides exactly what the API would.
+//! Numbers and dates are written in the requested `CsvFormat`; workbooks format them as
+//! numbers and dates Excel can calculate with.
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde_json::{json, Map, Value as JsonValue};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        budget::BudgetPerformance,
+        custom_report::{CustomReport, CustomReportType},
+        dimension::DimensionFilter,
+        report::{FinancialStatement, StatementLine, StatementType},
+    },
+    services::{
+        budget_csv::slugify, budget_performance, custom_report, event, field_policy::FieldAccess,
+        report, tenant_branding,
+    },
+    utils::{
+        csv_format::{CsvCell, CsvColumnKind, CsvFormat},
+        pdf::{self, Document, Font, Page, PAGE_HEIGHT, PAGE_WIDTH},
+        xlsx::{Sheet, Workbook},
+    },
+};
+
+use CsvColumnKind::{Date, Number, Text};
+
+/// Most transactions written to a TRANSACTION_LIST export.
+const MAX_TRANSACTION_ROWS: i64 = 10_000;
+
+/// A rendered report file.
+pub struct RenderedReport {
+    pub file_name: String,
+    pub csv: String,
+}
+
+/// A rendered XLSX workbook.
+pub struct RenderedWorkbook {
+    pub file_name: String,
+    pub xlsx: Vec<u8>,
+}
+
+/// A rendered PDF document.
+pub struct RenderedPdf {
+    pub file_name: String,
+    pub pdf: Vec<u8>,
+}
+
+/// Renders a custom report for the inclusive period `from_date..=to_date`.
+/// Point-in-time reports (account balances) are taken as of `to_date`; budget reports
+/// cover their budget's own period.
+pub async fn render_custom_report(
+    pool: &PgPool,
+    report: &CustomReport,
+    access: &FieldAccess,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    format: &CsvFormat,
+) -> Result<RenderedReport, AppError> {
+    info!(
+        "Service: Rendering custom report ID: {} from {} to {}",
+        report.id, from_date, to_date
+    );
+
+    if from_date > to_date {
+        return Err(AppError::Validation(
+            "from_date must not be after to_date".to_string(),
+        ));
+    }
+    let report_type = custom_report::parse_report_type(report)?;
+    let configuration = custom_report::parse_configuration(report)?;
+    let tenant_id = report.tenant_id;
+
+    let (columns, mut rows): (&[(&str, CsvColumnKind)], Vec<JsonValue>) = match report_type {
+        CustomReportType::IncomeExpenseStatement
+        | CustomReportType::AccountBalanceSummary
+        | CustomReportType::BalanceSheet => {
+            let statement = custom_report_statement(
+                pool,
+                tenant_id,
+                report_type,
+                configuration.layout_id,
+                from_date,
+                to_date,
+            )
+            .await?;
+            (&STATEMENT_COLUMNS, statement_rows(&statement))
+        }
+        CustomReportType::BudgetVsActual => {
+            let budget_id = configuration.budget_id.ok_or_else(|| {
+                AppError::Validation(
+                    "BUDGET_VS_ACTUAL reports need configuration.budget_id".to_string(),
+                )
+            })?;
+            let performance =
+                budget_performance::budget_performance(pool, tenant_id, budget_id).await?;
+            let mut rows: Vec<JsonValue> = performance
+                .lines
+                .iter()
+                .map(|line| {
+                    json!({
+                        "line": line.label,
+                        "account_id": line.account_id,
+                        "budgeted": line.budgeted,
+                        "actual": line.actual,
+                        "variance": line.variance,
+                    })
+                })
+                .collect();
+            rows.push(json!({
+                "line": "Total",
+                "budgeted": performance.total_budgeted,
+                "actual": performance.total_actual,
+                "variance": performance.total_variance,
+            }));
+            (&BUDGET_COLUMNS, rows)
+        }
+        CustomReportType::TransactionList => {
+            let transactions = sqlx::query!(
+                r#"
+                SELECT t.transaction_date, t.description, t.type::text as "transaction_type!",
+                       c.name as "category_name?", t.amount, t.currency_code, t.notes
+                FROM transactions t
+                LEFT JOIN categories c ON t.category_id = c.id
+                WHERE t.tenant_id = $1
+                  AND t.status = 'POSTED'
+                  AND t.transaction_date BETWEEN $2 AND $3
+                  AND (cardinality($4::uuid[]) = 0 OR t.category_id = ANY($4))
+                  AND (cardinality($5::uuid[]) = 0 OR EXISTS (
+                      SELECT 1 FROM journal_entries je
+                      WHERE je.transaction_id = t.id AND je.account_id = ANY($5)
+                  ))
+                ORDER BY t.transaction_date, t.created_at
+                LIMIT $6
+                "#,
+                tenant_id,
+                from_date,
+                to_date,
+                &configuration.category_ids,
+                &configuration.account_ids,
+                MAX_TRANSACTION_ROWS
+            )
+            .fetch_all(pool)
+            .await?;
+            let rows = transactions
+                .into_iter()
+                .map(|t| {
+                    json!({
+                        "date": t.transaction_date,
+                        "description": t.description,
+                        "type": t.transaction_type,
+                        "category": t.category_name,
+                        "amount": t.amount,
+                        "currency_code": t.currency_code,
+                        "notes": t.notes,
+                    })
+                })
+                .collect();
+            let columns: &[(&str, CsvColumnKind)] = &[
+                ("date", Date),
+                ("description", Text),
+                ("type", Text),
+                ("category", Text),
+                ("amount", Number),
+                ("currency_code", Text),
+                ("notes", Text),
+            ];
+            (columns, rows)
+        }
+        CustomReportType::SummaryByCategory => {
+            let totals = sqlx::query!(
+                r#"
+                SELECT COALESCE(c.name, 'Uncategorized') as "category!", t.currency_code,
+                       COUNT(*) as "transaction_count!", SUM(t.amount) as "total!"
+                FROM transactions t
+                LEFT JOIN categories c ON t.category_id = c.id
+                WHERE t.tenant_id = $1
+                  AND t.status = 'POSTED'
+                  AND t.transaction_date BETWEEN $2 AND $3
+                  AND (cardinality($4::uuid[]) = 0 OR t.category_id = ANY($4))
+                GROUP BY 1, 2
+                ORDER BY 1, 2
+                "#,
+                tenant_id,
+                from_date,
+                to_date,
+                &configuration.category_ids
+            )
+            .fetch_all(pool)
+            .await?;
+            let rows = totals
+                .into_iter()
+                .map(|row| {
+                    json!({
+                        "category": row.category,
+                        "currency_code": row.currency_code,
+                        "transaction_count": row.transaction_count,
+                        "total": row.total,
+                    })
+                })
+                .collect();
+            let columns: &[(&str, CsvColumnKind)] = &[
+                ("category", Text),
+                ("currency_code", Text),
+                ("transaction_count", Number),
+                ("total", Number),
+            ];
+            (columns, rows)
+        }
+    };
+
+    rows.iter_mut().for_each(|row| access.apply(row));
+
+    Ok(RenderedReport {
+        file_name: format!("{}-{}-{}.csv", slugify(&report.name), from_date, to_date),
+        csv: write_csv(columns, &rows, format)?,
+    })
+}
+
+const EVENT_COLUMNS: [(&str, CsvColumnKind); 9] = [
+    ("section", Text),
+    ("date", Date),
+    ("description", Text),
+    ("type", Text),
+    ("category", Text),
+    ("currency_code", Text),
+    ("amount", Number),
+    ("income", Number),
+    ("expense", Number),
+];
+
+/// Renders an event as one file: its transactions, then its totals by currency and category
+/// (as in `event::event_summary`) and a total per currency.
+pub async fn render_event_report(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_id: Uuid,
+    access: &FieldAccess,
+    format: &CsvFormat,
+) -> Result<RenderedReport, AppError> {
+    info!("Service: Rendering event report for event ID: {}", event_id);
+
+    let summary = event::event_summary(pool, tenant_id, event_id).await?;
+    let transactions = sqlx::query!(
+        r#"
+        SELECT t.transaction_date, t.description, t.type::text as "transaction_type!",
+               c.name as "category_name?", t.currency_code, t.amount
+        FROM event_transactions et
+        JOIN transactions t ON t.id = et.transaction_id
+        LEFT JOIN categories c ON c.id = t.category_id
+        WHERE et.event_id = $1
+          AND t.tenant_id = $2
+          AND t.status <> 'VOIDED'
+          AND t.reversal_of_id IS NULL
+          AND t.reversed_by_id IS NULL
+        ORDER BY t.transaction_date, t.created_at
+        LIMIT $3
+        "#,
+        event_id,
+        tenant_id,
+        MAX_TRANSACTION_ROWS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut rows: Vec<JsonValue> = transactions
+        .into_iter()
+        .map(|t| {
+            json!({
+                "section": "Transaction",
+                "date": t.transaction_date,
+                "description": t.description,
+                "type": t.transaction_type,
+                "category": t.category_name,
+                "currency_code": t.currency_code,
+                "amount": t.amount,
+            })
+        })
+        .collect();
+    for currency in &summary.currencies {
+        rows.extend(currency.categories.iter().map(|category| {
+            json!({
+                "section": "Category total",
+                "category": category.category_name,
+                "currency_code": currency.currency_code,
+                "income": category.income,
+                "expense": category.expense,
+            })
+        }));
+        rows.push(json!({
+            "section": "Total",
+            "currency_code": currency.currency_code,
+            "income": currency.income,
+            "expense": currency.expense,
+        }));
+    }
+    rows.iter_mut().for_each(|row| access.apply(row));
+
+    Ok(RenderedReport {
+        file_name: format!("event-{}.csv", slugify(&summary.name)),
+        csv: write_csv(&EVENT_COLUMNS, &rows, format)?,
+    })
+}
+
+/// Whether a custom report type is a statement, and so can be rendered as a PDF.
+pub fn supports_pdf(report_type: CustomReportType) -> bool {
+    matches!(
+        report_type,
+        CustomReportType::IncomeExpenseStatement
+            | CustomReportType::AccountBalanceSummary
+            | CustomReportType::BalanceSheet
+    )
+}
+
+/// Renders a statement custom report (see `supports_pdf`) for the inclusive period
+/// `from_date..=to_date` as a PDF.
+pub async fn render_custom_report_pdf(
+    pool: &PgPool,
+    report: &CustomReport,
+    access: &FieldAccess,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> Result<RenderedPdf, AppError> {
+    info!(
+        "Service: Rendering custom report ID: {} from {} to {} as PDF",
+        report.id, from_date, to_date
+    );
+
+    if from_date > to_date {
+        return Err(AppError::Validation(
+            "from_date must not be after to_date".to_string(),
+        ));
+    }
+    let report_type = custom_report::parse_report_type(report)?;
+    let configuration = custom_report::parse_configuration(report)?;
+    let statement = custom_report_statement(
+        pool,
+        report.tenant_id,
+        report_type,
+        configuration.layout_id,
+        from_date,
+        to_date,
+    )
+    .await?;
+    render_statement_pdf(pool, &statement, access).await
+}
+
+/// The statement a statement custom report shows for the period. Point-in-time statements
+/// are taken as of `to_date`.
+async fn custom_report_statement(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    report_type: CustomReportType,
+    layout_id: Option<Uuid>,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+) -> Result<FinancialStatement, AppError> {
+    match report_type {
+        CustomReportType::IncomeExpenseStatement => {
+            report::income_statement(
+                pool,
+                tenant_id,
+                Some(from_date),
+                Some(to_date),
+                layout_id,
+                DimensionFilter::default(),
+            )
+            .await
+        }
+        CustomReportType::AccountBalanceSummary => {
+            report::trial_balance(
+                pool,
+                tenant_id,
+                Some(to_date),
+                layout_id,
+                DimensionFilter::default(),
+            )
+            .await
+        }
+        CustomReportType::BalanceSheet => {
+            report::balance_sheet(pool, tenant_id, Some(to_date), layout_id).await
+        }
+        other => Err(AppError::Validation(format!(
+            "{} reports are not statements and cannot be rendered as PDF",
+            String::from(other)
+        ))),
+    }
+}
+
+const STATEMENT_COLUMNS: [(&str, CsvColumnKind); 4] = [
+    ("section", Text),
+    ("account_code", Text),
+    ("account", Text),
+    ("amount", Number),
+];
+
+/// Flattens a statement into one row per line, followed by each section's total and the net line.
+fn statement_rows(statement: &FinancialStatement) -> Vec<JsonValue> {
+    let row = |section: &str, line: &StatementLine| {
+        json!({
+            "section": section,
+            "account_id": line.account_id,
+            "account_code": line.account_code,
+            "account": line.label,
+            "amount": line.amount,
+        })
+    };
+
+    let mut rows = Vec::new();
+    for section in &statement.sections {
+        rows.extend(section.lines.iter().map(|line| row(&section.title, line)));
+        rows.push(row(&section.title, &section.total));
+    }
+    if let Some(net) = &statement.net {
+        rows.push(row("", net));
+    }
+    rows
+}
+
+const BUDGET_COLUMNS: [(&str, CsvColumnKind); 4] = [
+    ("line", Text),
+    ("budgeted", Number),
+    ("actual", Number),
+    ("variance", Number),
+];
+
+/// Renders a statement as a workbook: a summary sheet with the period and each section's
+/// total, then every line with the subtotals in bold.
+pub fn render_statement_xlsx(
+    statement: &FinancialStatement,
+    access: &FieldAccess,
+) -> Result<RenderedWorkbook, AppError> {
+    let title = statement_title(statement.statement_type);
+    info!(
+        "Service: Rendering {} for tenant ID: {} as XLSX",
+        title, statement.tenant_id
+    );
+
+    let mut summary = Sheet::new("Summary");
+    summary.bold_row([CsvCell::from(title)]);
+    match statement.from_date {
+        Some(from_date) => {
+            summary.row([CsvCell::from("From"), CsvCell::from(from_date)]);
+            summary.row([CsvCell::from("To"), CsvCell::from(statement.to_date)]);
+        }
+        None => summary.row([CsvCell::from("As of"), CsvCell::from(statement.to_date)]),
+    }
+    summary.row([CsvCell::from("Generated"), CsvCell::from(generated_at())]);
+    summary.blank_row();
+    summary.header(&["Section", "Total"]);
+    for section in &statement.sections {
+        summary.row([
+            CsvCell::from(section.title.clone()),
+            CsvCell::from(section.total.amount),
+        ]);
+    }
+    if let Some(net) = &statement.net {
+        summary.bold_row([CsvCell::from(net.label.clone()), CsvCell::from(net.amount)]);
+    }
+
+    let mut rows = statement_rows(statement);
+    rows.iter_mut().for_each(|row| access.apply(row));
+    // Subtotal and net rows span accounts, so they carry no account ID
+    let mut detail = Sheet::new(title);
+    write_sheet_rows(&mut detail, &STATEMENT_COLUMNS, &rows, |row| {
+        row["account_id"].is_null()
+    });
+
+    let mut workbook = Workbook::new();
+    workbook.add_sheet(summary);
+    workbook.add_sheet(detail);
+    Ok(RenderedWorkbook {
+        file_name: statement_file_name(statement, "xlsx"),
+        xlsx: workbook.finish()?,
+    })
+}
+
+/// Renders budget vs actual as a workbook: a summary sheet with the budget's totals, then
+/// every line and the total row.
+pub fn render_budget_performance_xlsx(
+    performance: &BudgetPerformance,
+    access: &FieldAccess,
+) -> Result<RenderedWorkbook, AppError> {
+    info!(
+        "Service: Rendering performance of budget ID: {} as XLSX",
+        performance.budget_id
+    );
+
+    let mut summary = Sheet::new("Summary");
+    summary.bold_row([CsvCell::from(format!(
+        "Budget vs Actual: {}",
+        performance.name
+    ))]);
+    summary.row([CsvCell::from("From"), CsvCell::from(performance.start_date)]);
+    summary.row([CsvCell::from("To"), CsvCell::from(performance.end_date)]);
+    summary.row([
+        CsvCell::from("Currency"),
+        CsvCell::from(performance.currency_code.clone()),
+    ]);
+    summary.row([CsvCell::from("Generated"), CsvCell::from(generated_at())]);
+    summary.blank_row();
+    summary.row([
+        CsvCell::from("Budgeted"),
+        CsvCell::from(performance.total_budgeted),
+    ]);
+    summary.row([
+        CsvCell::from("Actual"),
+        CsvCell::from(performance.total_actual),
+    ]);
+    summary.bold_row([
+        CsvCell::from("Variance"),
+        CsvCell::from(performance.total_variance),
+    ]);
+
+    let mut rows: Vec<JsonValue> = performance
+        .lines
+        .iter()
+        .map(|line| {
+            json!({
+                "line": line.label,
+                "account_id": line.account_id,
+                "budgeted": line.budgeted,
+                "actual": line.actual,
+                "variance": line.variance,
+            })
+        })
+        .collect();
+    rows.iter_mut().for_each(|row| access.apply(row));
+    let mut detail = Sheet::new("Lines");
+    write_sheet_rows(&mut detail, &BUDGET_COLUMNS, &rows, |_| false);
+    detail.bold_row([
+        CsvCell::from("Total"),
+        CsvCell::from(performance.total_budgeted),
+        CsvCell::from(performance.total_actual),
+        CsvCell::from(performance.total_variance),
+    ]);
+
+    let mut workbook = Workbook::new();
+    workbook.add_sheet(summary);
+    workbook.add_sheet(detail);
+    Ok(RenderedWorkbook {
+        file_name: format!("budget-vs-actual-{}.xlsx", slugify(&performance.name)),
+        xlsx: workbook.finish()?,
+    })
+}
+
+/// Left margin, and the right edge of the amount column.
+const PDF_MARGIN: f32 = 50.0;
+const PDF_TEXT_SIZE: f32 = 10.0;
+const PDF_LINE_HEIGHT: f32 = 15.0;
+/// Account lines are indented under their section's heading.
+const PDF_INDENT: f32 = 12.0;
+const PDF_AMOUNT_WIDTH: f32 = 110.0;
+const PDF_LOGO_MAX_WIDTH: f32 = 140.0;
+const PDF_LOGO_MAX_HEIGHT: f32 = 50.0;
+
+/// Renders a statement as a printable A4 PDF: the tenant's logo and name, the statement's
+/// title and period, then each section's lines and total and the net line. Pages repeat
+/// the column headings and are numbered in the footer.
+pub async fn render_statement_pdf(
+    pool: &PgPool,
+    statement: &FinancialStatement,
+    access: &FieldAccess,
+) -> Result<RenderedPdf, AppError> {
+    let title = statement_title(statement.statement_type);
+    info!(
+        "Service: Rendering {} for tenant ID: {} as PDF",
+        title, statement.tenant_id
+    );
+
+    let branding = tenant_branding::load_branding(pool, statement.tenant_id).await?;
+    let mut rows = statement_rows(statement);
+    rows.iter_mut().for_each(|row| access.apply(row));
+
+    let mut document = Document::new(&format!("{} - {}", title, branding.name));
+    let amount_right = PAGE_WIDTH - PDF_MARGIN;
+    let mut pages = Vec::new();
+    let mut page = Page::new();
+
+    // Header: the logo scaled into its box, with the name, title and period beside it
+    let top = PAGE_HEIGHT - PDF_MARGIN;
+    let mut text_left = PDF_MARGIN;
+    let mut header_height = 44.0_f32;
+    if let Some(logo) = branding.logo {
+        let scale = (PDF_LOGO_MAX_WIDTH / logo.width() as f32)
+            .min(PDF_LOGO_MAX_HEIGHT / logo.height() as f32);
+        let (width, height) = (logo.width() as f32 * scale, logo.height() as f32 * scale);
+        let logo = document.add_image(logo);
+        page.image(logo, PDF_MARGIN, top - height, width, height);
+        text_left += width + 15.0;
+        header_height = header_height.max(height);
+    }
+    let period = match statement.from_date {
+        Some(from_date) => format!("{} to {}", from_date, statement.to_date),
+        None => format!("As of {}", statement.to_date),
+    };
+    page.text(text_left, top - 14.0, Font::Bold, 14.0, &branding.name);
+    page.text(text_left, top - 30.0, Font::Bold, 12.0, title);
+    page.text(text_left, top - 44.0, Font::Regular, PDF_TEXT_SIZE, &period);
+    let mut y = pdf_table_heading(&mut page, top - header_height - 30.0);
+
+    let empty = Map::new();
+    let mut current_section = None;
+    for row in &rows {
+        let fields = row.as_object().unwrap_or(&empty);
+        let section = fields
+            .get("section")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default();
+        let starts_section = !section.is_empty() && current_section != Some(section);
+        let needed = if starts_section {
+            2.5 * PDF_LINE_HEIGHT
+        } else {
+            PDF_LINE_HEIGHT
+        };
+        if y - needed < PDF_MARGIN + PDF_LINE_HEIGHT {
+            pages.push(std::mem::take(&mut page));
+            y = pdf_table_heading(&mut page, PAGE_HEIGHT - PDF_MARGIN);
+        }
+        if starts_section {
+            y -= PDF_LINE_HEIGHT / 2.0;
+            page.text(PDF_MARGIN, y, Font::Bold, PDF_TEXT_SIZE, section);
+            y -= PDF_LINE_HEIGHT;
+        }
+        current_section = Some(section);
+
+        let label = pdf_cell_text(fields.get("account"));
+        let amount = pdf_cell_text(fields.get("amount"));
+        let label_width = amount_right - PDF_AMOUNT_WIDTH - PDF_MARGIN - PDF_INDENT;
+        // Section totals and the net line span accounts, so they carry no account ID
+        if fields.get("account_id").is_none_or(JsonValue::is_null) {
+            let rule_y = y + PDF_TEXT_SIZE + 1.0;
+            page.line(
+                (amount_right - PDF_AMOUNT_WIDTH, rule_y),
+                (amount_right, rule_y),
+                0.5,
+            );
+            page.text(
+                PDF_MARGIN,
+                y,
+                Font::Bold,
+                PDF_TEXT_SIZE,
+                &pdf_fit(Font::Bold, &label, label_width),
+            );
+            page.text_right(amount_right, y, Font::Bold, PDF_TEXT_SIZE, &amount);
+            if section.is_empty() {
+                for offset in [3.0, 5.0] {
+                    page.line(
+                        (amount_right - PDF_AMOUNT_WIDTH, y - offset),
+                        (amount_right, y - offset),
+                        0.5,
+                    );
+                }
+            }
+        } else {
+            let label = match pdf_cell_text(fields.get("account_code")) {
+                code if code.is_empty() => label,
+                code => format!("{}  {}", code, label),
+            };
+            let label = pdf_fit(Font::Regular, &label, label_width);
+            page.text(
+                PDF_MARGIN + PDF_INDENT,
+                y,
+                Font::Regular,
+                PDF_TEXT_SIZE,
+                &label,
+            );
+            page.text_right(amount_right, y, Font::Regular, PDF_TEXT_SIZE, &amount);
+        }
+        y -= PDF_LINE_HEIGHT;
+    }
+    pages.push(page);
+
+    let footer = format!(
+        "{} \u{b7} {} \u{b7} Generated {}",
+        branding.name,
+        title,
+        generated_at()
+    );
+    let page_count = pages.len();
+    for (index, mut page) in pages.into_iter().enumerate() {
+        page.text(PDF_MARGIN, PDF_MARGIN / 2.0, Font::Regular, 8.0, &footer);
+        let number = format!("Page {} of {}", index + 1, page_count);
+        page.text_right(amount_right, PDF_MARGIN / 2.0, Font::Regular, 8.0, &number);
+        document.add_page(page);
+    }
+
+    Ok(RenderedPdf {
+        file_name: statement_file_name(statement, "pdf"),
+        pdf: document.finish()?,
+    })
+}
+
+/// Writes the column headings with a rule below at `top`; returns the first row's baseline.
+fn pdf_table_heading(page: &mut Page, top: f32) -> f32 {
+    let amount_right = PAGE_WIDTH - PDF_MARGIN;
+    page.text(PDF_MARGIN, top, Font::Bold, 9.0, "Account");
+    page.text_right(amount_right, top, Font::Bold, 9.0, "Amount");
+    page.line((PDF_MARGIN, top - 4.0), (amount_right, top - 4.0), 0.75);
+    top - 4.0 - PDF_LINE_HEIGHT
+}
+
+/// A JSON cell as printed: amounts with thousands separators and two decimals, a masked
+/// value as it is.
+fn pdf_cell_text(value: Option<&JsonValue>) -> String {
+    match CsvCell::from_json(value, Number) {
+        CsvCell::Empty => String::new(),
+        CsvCell::Number(amount) => format_amount(amount),
+        CsvCell::Text(text) => text,
+        CsvCell::Date(date) => date.to_string(),
+    }
+}
+
+/// `-1,234.50` for -1234.5.
+fn format_amount(amount: Decimal) -> String {
+    let fixed = format!("{:.2}", amount.round_dp(2).abs());
+    let (whole, fraction) = fixed.split_once('.').unwrap_or((&fixed, "00"));
+    let mut grouped = String::with_capacity(fixed.len() + whole.len() / 3 + 1);
+    if amount.is_sign_negative() && !amount.round_dp(2).is_zero() {
+        grouped.push('-');
+    }
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}.{}", grouped, fraction)
+}
+
+/// Cuts text that would run past `max_width`, marking the cut with an ellipsis.
+fn pdf_fit(font: Font, text: &str, max_width: f32) -> String {
+    if pdf::text_width(font, PDF_TEXT_SIZE, text) <= max_width {
+        return text.to_string();
+    }
+    let mut fitted: String = text.to_string();
+    while !fitted.is_empty()
+        && pdf::text_width(font, PDF_TEXT_SIZE, &format!("{}\u{2026}", fitted)) > max_width
+    {
+        fitted.pop();
+    }
+    format!("{}\u{2026}", fitted.trim_end())
+}
+
+/// The statement's heading, e.g. "Income Statement".
+fn statement_title(statement_type: StatementType) -> &'static str {
+    match statement_type {
+        StatementType::TrialBalance => "Trial Balance",
+        StatementType::IncomeStatement => "Income Statement",
+        StatementType::BalanceSheet => "Balance Sheet",
+    }
+}
+
+/// e.g. `income-statement-2025-01-01-2025-03-31.pdf`, or `balance-sheet-2025-03-31.pdf`.
+fn statement_file_name(statement: &FinancialStatement, extension: &str) -> String {
+    let period = match statement.from_date {
+        Some(from_date) => format!("{}-{}", from_date, statement.to_date),
+        None => statement.to_date.to_string(),
+    };
+    format!(
+        "{}-{}.{}",
+        slugify(statement_title(statement.statement_type)),
+        period,
+        extension
+    )
+}
+
+fn generated_at() -> String {
+    Utc::now().format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+/// Writes a header and one row per JSON object; rows matching `is_bold` are set in bold.
+fn write_sheet_rows(
+    sheet: &mut Sheet,
+    columns: &[(&str, CsvColumnKind)],
+    rows: &[JsonValue],
+    is_bold: impl Fn(&JsonValue) -> bool,
+) {
+    let header: Vec<&str> = columns.iter().map(|(name, _)| *name).collect();
+    sheet.header(&header);
+
+    let empty = Map::new();
+    for row in rows {
+        let fields = row.as_object().unwrap_or(&empty);
+        let cells = columns
+            .iter()
+            .map(|(name, kind)| CsvCell::from_json(fields.get(*name), *kind));
+        if is_bold(row) {
+            sheet.bold_row(cells);
+        } else {
+            sheet.row(cells);
+        }
+    }
+}
+
+fn write_csv(
+    columns: &[(&str, CsvColumnKind)],
+    rows: &[JsonValue],
+    format: &CsvFormat,
+) -> Result<String, AppError> {
+    let mut writer = format.writer();
+    let header: Vec<&str> = columns.iter().map(|(name, _)| *name).collect();
+    writer.write_header(&header)?;
+
+    let empty = Map::new();
+    for row in rows {
+        let fields = row.as_object().unwrap_or(&empty);
+        writer.write_row(
+            columns
+                .iter()
+                .map(|(name, kind)| CsvCell::from_json(fields.get(*name), *kind)),
+        )?;
+    }
+    writer.finish()
+}
@@ -0,0 +1,493 @@
+//! Scheduled email delivery of custom reports.
+//!
+//! Each schedule has a standard 5-field cron expression (`minute hour day-of-month month
+//! day-of-week`) evaluated in its own time zone, e.g. `0 7 1 * *` for 07:00 on the 1st of
+//! every month. Day-of-week is best given by name (`MON-FRI`); numbers follow the `cron`
+//! crate, where 1 is Sunday. On each run the report is rendered for the schedule's period,
+//! as CSV or (for statement reports) as a PDF with the tenant's branding, and emailed as an
+//! attachment through the tenant's mail server.
+//!
+//! Reports are rendered as the user who created the schedule, with their field access,
+//! and only while that user can still see the report.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use sqlx::{query_as, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::{Validate, ValidateEmail};
+
+use crate::{
+    error::AppError,
+    models::{
+        custom_report::CustomReport,
+        dto::report_schedule_dto::{CreateReportScheduleDto, UpdateReportScheduleDto},
+        report_schedule::{AttachmentFormat, ReportPeriod, ReportSchedule},
+    },
+    services::{
+        custom_report, field_policy, mail_settings,
+        mailer::{self, EmailAttachment},
+        report_export,
+    },
+    utils::{csv_format::CsvFormat, pdf},
+};
+
+/// Most due schedules claimed by one scheduler run; the rest wait for the next one.
+const MAX_SCHEDULES_PER_RUN: i64 = 100;
+/// Longest error text kept in `last_error`.
+const MAX_ERROR_LEN: usize = 500;
+
+/// Lists the schedules of a report the current user can see.
+pub async fn list_report_schedules(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+) -> Result<Vec<ReportSchedule>, AppError> {
+    info!(
+        "Service: Listing schedules for custom report ID: {}",
+        report_id
+    );
+    custom_report::get_custom_report_by_id(pool, tenant_id, user_id, report_id).await?;
+
+    let schedules = query_as!(
+        ReportSchedule,
+        r#"
+        SELECT
+            id, tenant_id, custom_report_id, cron_expression, timezone, period, recipients,
+            is_active, next_run_at, last_run_at, last_error, attachment_format,
+            created_at, created_by, updated_at, updated_by
+        FROM report_schedules
+        WHERE custom_report_id = $1 AND tenant_id = $2
+        ORDER BY created_at
+        "#,
+        report_id,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(schedules)
+}
+
+/// Schedules a report the current user can see for email delivery.
+pub async fn create_report_schedule(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+    dto: CreateReportScheduleDto,
+) -> Result<ReportSchedule, AppError> {
+    info!(
+        "Service: Scheduling custom report ID: {} for tenant ID: {}",
+        report_id, tenant_id
+    );
+
+    dto.validate()?;
+    validate_recipients(&dto.recipients)?;
+    let timezone = dto.timezone.unwrap_or_else(|| "UTC".to_string());
+    let next_run_at = next_run_after(&dto.cron_expression, &timezone, Utc::now())?;
+
+    let report =
+        custom_report::get_custom_report_by_id(pool, tenant_id, user_id, report_id).await?;
+    let attachment_format = dto.attachment_format.unwrap_or(AttachmentFormat::Csv);
+    check_attachment_format(&report, attachment_format)?;
+
+    let schedule = query_as!(
+        ReportSchedule,
+        r#"
+        INSERT INTO report_schedules (
+            tenant_id, custom_report_id, cron_expression, timezone, period, recipients,
+            is_active, next_run_at, attachment_format, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+        RETURNING
+            id, tenant_id, custom_report_id, cron_expression, timezone, period, recipients,
+            is_active, next_run_at, last_run_at, last_error, attachment_format,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        report_id,
+        dto.cron_expression.trim(),
+        timezone,
+        String::from(dto.period.unwrap_or(ReportPeriod::PreviousMonth)),
+        &dto.recipients,
+        dto.is_active.unwrap_or(true),
+        next_run_at,
+        String::from(attachment_format),
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(schedule)
+}
+
+/// Updates a schedule created by the current user. Changing the expression or time zone
+/// recomputes the next run.
+pub async fn update_report_schedule(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+    schedule_id: Uuid,
+    dto: UpdateReportScheduleDto,
+) -> Result<ReportSchedule, AppError> {
+    info!(
+        "Service: Updating report schedule ID: {} for tenant ID: {}",
+        schedule_id, tenant_id
+    );
+
+    dto.validate()?;
+    if let Some(recipients) = &dto.recipients {
+        validate_recipients(recipients)?;
+    }
+
+    let existing = get_own_schedule(pool, tenant_id, user_id, report_id, schedule_id).await?;
+    let cron_expression = dto
+        .cron_expression
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or(&existing.cron_expression);
+    let timezone = dto.timezone.as_deref().unwrap_or(&existing.timezone);
+    let next_run_at = next_run_after(cron_expression, timezone, Utc::now())?;
+    if let Some(attachment_format) = dto.attachment_format {
+        let report =
+            custom_report::get_custom_report_by_id(pool, tenant_id, user_id, report_id).await?;
+        check_attachment_format(&report, attachment_format)?;
+    }
+
+    let schedule = query_as!(
+        ReportSchedule,
+        r#"
+        UPDATE report_schedules
+        SET
+            cron_expression = $3,
+            timezone = $4,
+            period = COALESCE($5, period),
+            recipients = COALESCE($6, recipients),
+            is_active = COALESCE($7, is_active),
+            next_run_at = $8,
+            attachment_format = COALESCE($10, attachment_format),
+            updated_at = NOW(),
+            updated_by = $9
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING
+            id, tenant_id, custom_report_id, cron_expression, timezone, period, recipients,
+            is_active, next_run_at, last_run_at, last_error, attachment_format,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        schedule_id,
+        tenant_id,
+        cron_expression,
+        timezone,
+        dto.period.map(String::from),
+        dto.recipients.as_deref(),
+        dto.is_active,
+        next_run_at,
+        user_id,
+        dto.attachment_format.map(String::from)
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(schedule)
+}
+
+/// Deletes a schedule created by the current user.
+pub async fn delete_report_schedule(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+    schedule_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting report schedule ID: {} for tenant ID: {}",
+        schedule_id, tenant_id
+    );
+
+    get_own_schedule(pool, tenant_id, user_id, report_id, schedule_id).await?;
+    sqlx::query!(
+        "DELETE FROM report_schedules WHERE id = $1 AND tenant_id = $2",
+        schedule_id,
+        tenant_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Sends a schedule's report right away without moving its next run. Returns the schedule
+/// with the outcome recorded.
+pub async fn run_report_schedule_now(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+    schedule_id: Uuid,
+) -> Result<ReportSchedule, AppError> {
+    info!(
+        "Service: Running report schedule ID: {} now for tenant ID: {}",
+        schedule_id, tenant_id
+    );
+
+    let schedule = get_own_schedule(pool, tenant_id, user_id, report_id, schedule_id).await?;
+    let now = Utc::now();
+    let result = deliver_schedule(pool, &schedule, now).await;
+    record_outcome(pool, schedule.id, now, result.as_ref().err()).await?;
+    result?;
+
+    get_own_schedule(pool, tenant_id, user_id, report_id, schedule_id).await
+}
+
+/// Claims every due schedule, moves it to its next run and sends its report.
+/// Run by the report scheduler. Returns the number of reports sent.
+///
+/// Schedules are claimed with `FOR UPDATE SKIP LOCKED` and advanced before sending, so
+/// concurrent scheduler instances never send the same run twice. Runs missed while the
+/// service was down are collapsed into one.
+pub async fn run_due_report_schedules(pool: &PgPool) -> Result<usize, AppError> {
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await?;
+
+    let due = query_as!(
+        ReportSchedule,
+        r#"
+        SELECT
+            id, tenant_id, custom_report_id, cron_expression, timezone, period, recipients,
+            is_active, next_run_at, last_run_at, last_error, attachment_format,
+            created_at, created_by, updated_at, updated_by
+        FROM report_schedules
+        WHERE is_active = TRUE AND next_run_at <= $1
+        ORDER BY next_run_at
+        LIMIT $2
+        FOR UPDATE SKIP LOCKED
+        "#,
+        now,
+        MAX_SCHEDULES_PER_RUN
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    for schedule in &due {
+        match next_run_after(&schedule.cron_expression, &schedule.timezone, now) {
+            Ok(next_run_at) => {
+                sqlx::query!(
+                    "UPDATE report_schedules SET next_run_at = $2 WHERE id = $1",
+                    schedule.id,
+                    next_run_at
+                )
+                .execute(&mut *db_tx)
+                .await?;
+            }
+            Err(e) => {
+                // Only reachable if the stored expression stopped parsing; stop retrying it
+                warn!("Deactivating report schedule {}: {}", schedule.id, e);
+                sqlx::query!(
+                    "UPDATE report_schedules SET is_active = FALSE, last_error = $2 WHERE id = $1",
+                    schedule.id,
+                    truncate(&e.to_string())
+                )
+                .execute(&mut *db_tx)
+                .await?;
+            }
+        }
+    }
+
+    db_tx.commit().await?;
+
+    let mut sent = 0;
+    for schedule in &due {
+        let result = deliver_schedule(pool, schedule, now).await;
+        match &result {
+            Ok(()) => sent += 1,
+            Err(e) => warn!("Report schedule {} failed: {}", schedule.id, e),
+        }
+        record_outcome(pool, schedule.id, now, result.as_ref().err()).await?;
+    }
+
+    if sent > 0 {
+        info!("Sent {} scheduled report(s)", sent);
+    }
+    Ok(sent)
+}
+
+/// Next time the expression fires strictly after `after`, evaluated in `timezone`.
+pub fn next_run_after(
+    cron_expression: &str,
+    timezone: &str,
+    after: DateTime<Utc>,
+) -> Result<DateTime<Utc>, AppError> {
+    let tz: Tz = timezone
+        .parse()
+        .map_err(|_| AppError::Validation(format!("'{}' is not a known time zone", timezone)))?;
+    let fields = cron_expression.split_whitespace().collect::<Vec<_>>();
+    if fields.len() != 5 {
+        return Err(AppError::Validation(format!(
+            "'{}' is not a 5-field cron expression (minute hour day-of-month month day-of-week)",
+            cron_expression
+        )));
+    }
+    // The cron crate expects a leading seconds field
+    let schedule = cron::Schedule::from_str(&format!("0 {}", fields.join(" "))).map_err(|e| {
+        AppError::Validation(format!(
+            "Invalid cron expression '{}': {}",
+            cron_expression, e
+        ))
+    })?;
+
+    schedule
+        .after(&after.with_timezone(&tz))
+        .next()
+        .map(|next| next.with_timezone(&Utc))
+        .ok_or_else(|| {
+            AppError::Validation(format!("Cron expression '{}' never fires", cron_expression))
+        })
+}
+
+/// Renders the schedule's report for its period and emails it to the recipients.
+async fn deliver_schedule(
+    pool: &PgPool,
+    schedule: &ReportSchedule,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    let report = custom_report::get_custom_report_by_id(
+        pool,
+        schedule.tenant_id,
+        schedule.created_by,
+        schedule.custom_report_id,
+    )
+    .await?;
+    let period: ReportPeriod = schedule
+        .period
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    let tz: Tz = schedule.timezone.parse().unwrap_or(Tz::UTC);
+    let (from_date, to_date) = period.date_range(now.with_timezone(&tz).date_naive());
+
+    let access =
+        field_policy::load_field_access(pool, schedule.tenant_id, schedule.created_by).await?;
+    let attachment_format: AttachmentFormat = schedule
+        .attachment_format
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+    let attachment = match attachment_format {
+        AttachmentFormat::Csv => {
+            let rendered = report_export::render_custom_report(
+                pool,
+                &report,
+                &access,
+                from_date,
+                to_date,
+                &CsvFormat::default(),
+            )
+            .await?;
+            EmailAttachment {
+                file_name: rendered.file_name,
+                content_type: "text/csv; charset=utf-8".to_string(),
+                content: rendered.csv.into_bytes(),
+            }
+        }
+        AttachmentFormat::Pdf => {
+            let rendered =
+                report_export::render_custom_report_pdf(pool, &report, &access, from_date, to_date)
+                    .await?;
+            EmailAttachment {
+                file_name: rendered.file_name,
+                content_type: pdf::CONTENT_TYPE.to_string(),
+                content: rendered.pdf,
+            }
+        }
+    };
+
+    let subject = format!("{}: {} to {}", report.name, from_date, to_date);
+    let body = format!(
+        "Attached is the report \"{}\" for {} to {}.\n\nYou receive this email because the report is scheduled for delivery to this address.",
+        report.name, from_date, to_date
+    );
+    let config = mail_settings::smtp_config_for_tenant(pool, schedule.tenant_id).await?;
+    mailer::send_email_with(
+        config.as_ref(),
+        &schedule.recipients,
+        &subject,
+        &body,
+        vec![attachment],
+    )
+    .await
+}
+
+async fn record_outcome(
+    pool: &PgPool,
+    schedule_id: Uuid,
+    ran_at: DateTime<Utc>,
+    error: Option<&AppError>,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        "UPDATE report_schedules SET last_run_at = $2, last_error = $3 WHERE id = $1",
+        schedule_id,
+        ran_at,
+        error.map(|e| truncate(&e.to_string()))
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Retrieves a schedule of a visible report, only if the current user created it.
+async fn get_own_schedule(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    report_id: Uuid,
+    schedule_id: Uuid,
+) -> Result<ReportSchedule, AppError> {
+    let schedule = list_report_schedules(pool, tenant_id, user_id, report_id)
+        .await?
+        .into_iter()
+        .find(|schedule| schedule.id == schedule_id)
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Report schedule with ID {} not found", schedule_id))
+        })?;
+    if schedule.created_by != user_id {
+        return Err(AppError::Forbidden(
+            "Only the creator can change a report schedule".to_string(),
+        ));
+    }
+    Ok(schedule)
+}
+
+/// PDF attachments are only offered for statement reports.
+fn check_attachment_format(
+    report: &CustomReport,
+    attachment_format: AttachmentFormat,
+) -> Result<(), AppError> {
+    let report_type = custom_report::parse_report_type(report)?;
+    if attachment_format == AttachmentFormat::Pdf && !report_export::supports_pdf(report_type) {
+        return Err(AppError::Validation(format!(
+            "{} reports can only be attached as CSV",
+            String::from(report_type)
+        )));
+    }
+    Ok(())
+}
+
+fn validate_recipients(recipients: &[String]) -> Result<(), AppError> {
+    match recipients
+        .iter()
+        .find(|recipient| !recipient.validate_email())
+    {
+        Some(invalid) => Err(AppError::Validation(format!(
+            "'{}' is not a valid email address",
+            invalid
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn truncate(message: &str) -> String {
+    message.chars().take(MAX_ERROR_LEN).collect()
+}
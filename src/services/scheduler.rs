@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::{
+    config, db,
+    services::{
+        budget_alert, cash_position, exchange_rate, export_artifact, ext_conn, fixed_asset,
+        maintenance, notification, recurring_transaction, redis_store, report_schedule,
+    },
+};
+
+/// How long a run of the recurring scheduler may hold its lock; a crashed instance's lock
+/// is freed after this.
+const RECURRING_LOCK_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Spawns the background task that materializes due recurring transactions.
+///
+/// The interval can be tuned with `RECURRING_SCHEDULER_INTERVAL_SECS` (defaults to hourly).
+/// With Redis, a run is skipped while another instance is materializing.
+pub fn spawn_recurring_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.recurring_interval_secs;
+
+    info!(
+        "Starting recurring transaction scheduler (every {}s)",
+        interval_secs
+    );
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            let today = Utc::now().date_naive();
+            redis_store::run_exclusive("recurring_scheduler", RECURRING_LOCK_TTL, || async {
+                if let Err(e) =
+                    recurring_transaction::materialize_due_recurring_transactions(&pool, today)
+                        .await
+                {
+                    error!("Recurring transaction scheduler run failed: {}", e);
+                }
+            })
+            .await;
+        }
+    }))
+}
+
+/// Spawns the background task that pulls new transactions from connected banks
+/// into the external transactions staging table.
+///
+/// The interval can be tuned with `BANK_SYNC_INTERVAL_SECS` (defaults to every 6 hours).
+pub fn spawn_bank_sync_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.bank_sync_interval_secs;
+
+    info!("Starting bank sync scheduler (every {}s)", interval_secs);
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = ext_conn::sync_all_ext_conns(&pool).await {
+                error!("Bank sync scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
+
+/// Spawns the background task that delivers held notifications and digest emails.
+///
+/// The interval can be tuned with `NOTIFICATION_SCHEDULER_INTERVAL_SECS` (defaults to every 5 minutes).
+pub fn spawn_notification_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.notification_interval_secs;
+
+    info!("Starting notification scheduler (every {}s)", interval_secs);
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = notification::deliver_pending_notifications(&pool).await {
+                error!("Notification scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
+
+/// Spawns the background task that emails scheduled custom reports when they are due.
+///
+/// The interval can be tuned with `REPORT_SCHEDULER_INTERVAL_SECS` (defaults to every minute,
+/// the finest granularity of a cron expression).
+pub fn spawn_report_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.report_interval_secs;
+
+    info!("Starting report scheduler (every {}s)", interval_secs);
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = report_schedule::run_due_report_schedules(&pool).await {
+                error!("Report scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
+
+/// Spawns the background task that pulls the provider's daily exchange rates for tenants
+/// that opted in.
+///
+/// The interval can be tuned with `EXCHANGE_RATE_SCHEDULER_INTERVAL_SECS` (defaults to every
+/// 6 hours; providers publish once per business day).
+pub fn spawn_exchange_rate_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.exchange_rate_interval_secs;
+
+    info!(
+        "Starting exchange rate scheduler (every {}s)",
+        interval_secs
+    );
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = exchange_rate::fetch_exchange_rates_for_tenants(&pool).await {
+                error!("Exchange rate scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
+
+/// Spawns the background task that records today's cash position snapshot. Each run
+/// overwrites the day's rows, so the snapshot follows postings made during the day.
+///
+/// The interval can be tuned with `CASH_POSITION_SCHEDULER_INTERVAL_SECS` (defaults to hourly).
+pub fn spawn_cash_position_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.cash_position_interval_secs;
+
+    info!(
+        "Starting cash position scheduler (every {}s)",
+        interval_secs
+    );
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            let today = Utc::now().date_naive();
+            if let Err(e) = cash_position::record_snapshots(&pool, today).await {
+                error!("Cash position scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
+
+/// Spawns the background task that deletes expired export files.
+///
+/// The interval can be tuned with `EXPORT_CLEANUP_INTERVAL_SECS` (defaults to hourly).
+pub fn spawn_export_cleanup_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.export_cleanup_interval_secs;
+
+    info!(
+        "Starting export cleanup scheduler (every {}s)",
+        interval_secs
+    );
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = export_artifact::purge_expired_exports(&pool).await {
+                error!("Export cleanup scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
+
+/// Spawns the background task that runs database maintenance: statistics, materialized
+/// views and pruning of ended sessions (see `services::maintenance`).
+///
+/// The interval can be tuned with `MAINTENANCE_INTERVAL_SECS` (defaults to daily).
+pub fn spawn_maintenance_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.maintenance_interval_secs;
+
+    info!("Starting maintenance scheduler (every {}s)", interval_secs);
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = maintenance::run_maintenance(&pool).await {
+                error!("Maintenance scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
+
+/// Spawns the background task that raises budget alerts for line items whose spending
+/// crossed one of their thresholds (see `services::budget_alert`).
+///
+/// The interval can be tuned with `BUDGET_ALERT_SCHEDULER_INTERVAL_SECS` (defaults to hourly).
+pub fn spawn_budget_alert_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.budget_alert_interval_secs;
+
+    info!("Starting budget alert scheduler (every {}s)", interval_secs);
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            let today = Utc::now().date_naive();
+            if let Err(e) = budget_alert::check_budget_alerts(&pool, today).await {
+                error!("Budget alert scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
+
+/// Spawns the background task that posts each fixed asset's depreciation once a month has
+/// ended (see `services::fixed_asset`).
+///
+/// The interval can be tuned with `DEPRECIATION_SCHEDULER_INTERVAL_SECS` (defaults to daily).
+pub fn spawn_depreciation_scheduler(pool: PgPool) -> JoinHandle<()> {
+    let interval_secs = config::get().schedulers.depreciation_interval_secs;
+
+    info!("Starting depreciation scheduler (every {}s)", interval_secs);
+
+    tokio::spawn(db::without_tenant_scope(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            let today = Utc::now().date_naive();
+            if let Err(e) = fixed_asset::post_due_depreciation(&pool, today).await {
+                error!("Depreciation scheduler run failed: {}", e);
+            }
+        }
+    }))
+}
@@ -0,0 +1,173 @@
+//! Catalog of security webhook events: what each event type means, the JSON Schema of
+//! the body delivered for it and a fixed example body.
+//!
+//! Examples are deterministic so integrators can use them as fixtures: the same event
+//! type always carries the same `data`, `actor_user_id` and `occurred_at`. Only `id`
+//! (unique per delivery, for de-duplication) and `tenant_id` vary when one is sent.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::{json, Value as JsonValue};
+use uuid::Uuid;
+
+use crate::models::security_webhook::{
+    SecurityEventPayload, SecurityEventType, SecurityEventTypeInfo,
+};
+
+/// User ID used in example events.
+const EXAMPLE_USER_ID: Uuid = Uuid::from_u128(0x0000_0000_0000_4000_8000_0000_0000_0001);
+
+/// Other IDs used in example events.
+const EXAMPLE_RESOURCE_ID: Uuid = Uuid::from_u128(0x0000_0000_0000_4000_8000_0000_0000_0002);
+
+/// Describes every event type.
+pub fn event_catalog() -> Vec<SecurityEventTypeInfo> {
+    SecurityEventType::ALL
+        .into_iter()
+        .map(|event_type| SecurityEventTypeInfo {
+            event_type,
+            description: description(event_type),
+            schema: body_schema(event_type),
+            example: serde_json::to_value(example_payload(
+                event_type,
+                Uuid::nil(),
+                EXAMPLE_RESOURCE_ID,
+            ))
+            .unwrap_or(JsonValue::Null),
+        })
+        .collect()
+}
+
+/// The example body of `event_type` for a tenant, as delivered with ID `id`.
+pub fn example_payload(
+    event_type: SecurityEventType,
+    tenant_id: Uuid,
+    id: Uuid,
+) -> SecurityEventPayload {
+    SecurityEventPayload {
+        id,
+        event_type,
+        tenant_id,
+        occurred_at: example_time(),
+        actor_user_id: Some(EXAMPLE_USER_ID),
+        data: example_data(event_type),
+        test: true,
+    }
+}
+
+fn description(event_type: SecurityEventType) -> &'static str {
+    match event_type {
+        SecurityEventType::MemberAdded => "A user was given access to the tenant.",
+        SecurityEventType::RoleChanged => "A member's role in the tenant changed.",
+        SecurityEventType::ApiKeyCreated => {
+            "An API key was created; sandbox keys operate on the tenant's sandbox."
+        }
+        SecurityEventType::RepeatedFailedLogins => {
+            "Several sign-in attempts for one account failed within a short time."
+        }
+        SecurityEventType::Test => "Sent by the test endpoint to check the webhook is reachable.",
+    }
+}
+
+/// JSON Schema of the `data` object of an event type.
+fn data_schema(event_type: SecurityEventType) -> JsonValue {
+    let uuid = json!({ "type": "string", "format": "uuid" });
+    let (properties, required) = match event_type {
+        SecurityEventType::MemberAdded => (
+            json!({
+                "user_id": uuid,
+                "email": { "type": "string", "format": "email" },
+                "role": { "type": "string" },
+            }),
+            json!(["user_id", "email", "role"]),
+        ),
+        SecurityEventType::RoleChanged => (
+            json!({
+                "user_id": uuid,
+                "role": { "type": "string" },
+                "previous_role": { "type": ["string", "null"] },
+            }),
+            json!(["user_id", "role", "previous_role"]),
+        ),
+        SecurityEventType::ApiKeyCreated => (
+            json!({
+                "api_key_id": uuid,
+                "name": { "type": "string" },
+                "key_prefix": { "type": "string" },
+                "is_sandbox": { "type": "boolean" },
+            }),
+            json!(["api_key_id", "name", "key_prefix", "is_sandbox"]),
+        ),
+        SecurityEventType::RepeatedFailedLogins => (
+            json!({
+                "email": { "type": "string", "format": "email" },
+                "failed_attempts": { "type": "integer", "minimum": 1 },
+                "window_minutes": { "type": "integer", "minimum": 1 },
+                "ip_address": { "type": ["string", "null"] },
+            }),
+            json!(["email", "failed_attempts", "window_minutes"]),
+        ),
+        SecurityEventType::Test => (
+            json!({ "message": { "type": "string" } }),
+            json!(["message"]),
+        ),
+    };
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// JSON Schema of the whole request body of an event type.
+fn body_schema(event_type: SecurityEventType) -> JsonValue {
+    let uuid = json!({ "type": "string", "format": "uuid" });
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "id": uuid,
+            "type": { "const": String::from(event_type) },
+            "tenant_id": uuid,
+            "occurred_at": { "type": "string", "format": "date-time" },
+            "actor_user_id": { "type": ["string", "null"], "format": "uuid" },
+            "data": data_schema(event_type),
+            "test": { "type": "boolean", "description": "Present and true on synthetic events only" },
+        },
+        "required": ["id", "type", "tenant_id", "occurred_at", "actor_user_id", "data"],
+    })
+}
+
+fn example_data(event_type: SecurityEventType) -> JsonValue {
+    match event_type {
+        SecurityEventType::MemberAdded => json!({
+            "user_id": EXAMPLE_RESOURCE_ID,
+            "email": "jane.doe@example.com",
+            "role": "Accountant",
+        }),
+        SecurityEventType::RoleChanged => json!({
+            "user_id": EXAMPLE_RESOURCE_ID,
+            "role": "Administrator",
+            "previous_role": "Accountant",
+        }),
+        SecurityEventType::ApiKeyCreated => json!({
+            "api_key_id": EXAMPLE_RESOURCE_ID,
+            "name": "Example integration",
+            "key_prefix": "fk_test_AbCd",
+            "is_sandbox": true,
+        }),
+        SecurityEventType::RepeatedFailedLogins => json!({
+            "email": "jane.doe@example.com",
+            "failed_attempts": 5,
+            "window_minutes": 15,
+            "ip_address": "203.0.113.7",
+        }),
+        SecurityEventType::Test => json!({ "message": "Security webhook test" }),
+    }
+}
+
+/// `occurred_at` of example events.
+fn example_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0)
+        .single()
+        .unwrap_or_default()
+}
@@ -0,0 +1,406 @@
+//! Security webhooks: signed notifications of security events (new members, role changes,
+//! API keys, repeated failed logins) sent to a tenant-configured endpoint such as a SIEM.
+//!
+//! Each tenant opts into individual event types. Every request carries:
+//! - `X-Forge-Event`: the event type
+//! - `X-Forge-Delivery`: the event ID, for de-duplication on the receiving side
+//! - `X-Forge-Signature`: `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>">`, keyed
+//!   with the tenant's signing secret
+//!
+//! Emitting never blocks or fails the caller: delivery runs in the background and its
+//! outcome is recorded on the webhook.
+//!
+//! Bodies are documented by `security_event_catalog`, whose examples can also be sent on
+//! demand to test a consumer.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::Value as JsonValue;
+use sqlx::{query_as, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    db,
+    error::AppError,
+    models::{
+        dto::security_webhook_dto::{SendTestEventDto, UpsertSecurityWebhookDto},
+        security_webhook::{
+            SecurityEventPayload, SecurityEventType, SecurityWebhook, SecurityWebhookWithSecret,
+        },
+    },
+    services::{
+        permission::{self, SECURITY_MANAGE},
+        security_event_catalog,
+    },
+    utils::crypto::{decrypt_secret, encrypt_secret, generate_secret, hmac_sha256_hex},
+};
+
+const SIGNING_SECRET_BYTES: usize = 32;
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest response or error text kept in `last_error`.
+const MAX_ERROR_LEN: usize = 500;
+
+/// Retrieves the tenant's security webhook.
+pub async fn get_security_webhook(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<SecurityWebhook, AppError> {
+    info!(
+        "Service: Getting security webhook for tenant ID: {}",
+        tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, SECURITY_MANAGE).await?;
+
+    find_webhook(pool, tenant_id).await?.ok_or_else(|| {
+        AppError::NotFound(format!(
+            "No security webhook configured for tenant {}",
+            tenant_id
+        ))
+    })
+}
+
+/// Creates or replaces the tenant's security webhook. A signing secret is generated on
+/// creation and returned only in that response.
+pub async fn upsert_security_webhook(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: UpsertSecurityWebhookDto,
+) -> Result<SecurityWebhookWithSecret, AppError> {
+    info!(
+        "Service: Configuring security webhook for tenant ID: {}",
+        tenant_id
+    );
+
+    dto.validate()?;
+    if !dto.url.starts_with("https://") {
+        return Err(AppError::Validation(
+            "Security webhook URL must use https".to_string(),
+        ));
+    }
+    if dto.event_types.contains(&SecurityEventType::Test) {
+        return Err(AppError::Validation(
+            "TEST events cannot be subscribed to".to_string(),
+        ));
+    }
+    let mut event_types: Vec<String> = dto.event_types.into_iter().map(String::from).collect();
+    event_types.sort();
+    event_types.dedup();
+    let is_active = dto.is_active.unwrap_or(true);
+
+    permission::require_permission(pool, tenant_id, user_id, SECURITY_MANAGE).await?;
+
+    if find_webhook(pool, tenant_id).await?.is_some() {
+        let webhook = query_as!(
+            SecurityWebhook,
+            r#"
+            UPDATE security_webhooks
+            SET url = $2, event_types = $3, is_active = $4, updated_at = NOW(), updated_by = $5
+            WHERE tenant_id = $1
+            RETURNING
+                id, tenant_id, url, signing_secret, event_types, is_active, last_delivery_at,
+                last_delivery_status, last_error, created_at, created_by, updated_at, updated_by
+            "#,
+            tenant_id,
+            dto.url,
+            &event_types,
+            is_active,
+            user_id
+        )
+        .fetch_one(pool)
+        .await?;
+        return Ok(SecurityWebhookWithSecret {
+            webhook,
+            signing_secret: None,
+        });
+    }
+
+    let signing_secret = generate_secret(SIGNING_SECRET_BYTES);
+    let webhook = query_as!(
+        SecurityWebhook,
+        r#"
+        INSERT INTO security_webhooks (
+            tenant_id, url, signing_secret, event_types, is_active, created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        RETURNING
+            id, tenant_id, url, signing_secret, event_types, is_active, last_delivery_at,
+            last_delivery_status, last_error, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.url,
+        encrypt_secret(&signing_secret)?,
+        &event_types,
+        is_active,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(SecurityWebhookWithSecret {
+        webhook,
+        signing_secret: Some(signing_secret),
+    })
+}
+
+/// Replaces the signing secret and returns the new one. Deliveries are signed with the
+/// new secret immediately.
+pub async fn rotate_security_webhook_secret(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<SecurityWebhookWithSecret, AppError> {
+    info!(
+        "Service: Rotating security webhook secret for tenant ID: {}",
+        tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, SECURITY_MANAGE).await?;
+
+    let signing_secret = generate_secret(SIGNING_SECRET_BYTES);
+    let webhook = query_as!(
+        SecurityWebhook,
+        r#"
+        UPDATE security_webhooks
+        SET signing_secret = $2, updated_at = NOW(), updated_by = $3
+        WHERE tenant_id = $1
+        RETURNING
+            id, tenant_id, url, signing_secret, event_types, is_active, last_delivery_at,
+            last_delivery_status, last_error, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        encrypt_secret(&signing_secret)?,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "No security webhook configured for tenant {}",
+            tenant_id
+        ))
+    })?;
+
+    Ok(SecurityWebhookWithSecret {
+        webhook,
+        signing_secret: Some(signing_secret),
+    })
+}
+
+/// Removes the tenant's security webhook.
+pub async fn delete_security_webhook(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deleting security webhook for tenant ID: {}",
+        tenant_id
+    );
+    permission::require_permission(pool, tenant_id, user_id, SECURITY_MANAGE).await?;
+
+    let rows_affected = sqlx::query!(
+        "DELETE FROM security_webhooks WHERE tenant_id = $1",
+        tenant_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if rows_affected == 0 {
+        return Err(AppError::NotFound(format!(
+            "No security webhook configured for tenant {}",
+            tenant_id
+        )));
+    }
+    Ok(())
+}
+
+/// Sends a `TEST` event right away and returns the webhook with the delivery outcome.
+pub async fn send_test_event(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<SecurityWebhook, AppError> {
+    info!(
+        "Service: Sending test security event for tenant ID: {}",
+        tenant_id
+    );
+    let webhook = get_security_webhook(pool, tenant_id, user_id).await?;
+    let mut payload = event_payload(
+        tenant_id,
+        SecurityEventType::Test,
+        Some(user_id),
+        serde_json::json!({ "message": "Security webhook test" }),
+    );
+    payload.test = true;
+    deliver(pool, &webhook, &payload).await
+}
+
+/// Sends the catalog example of `dto.event_type` to the webhook right away, whether or not
+/// the tenant opted into that type, and returns the webhook with the delivery outcome.
+pub async fn send_sample_event(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    webhook_id: Uuid,
+    dto: SendTestEventDto,
+) -> Result<SecurityWebhook, AppError> {
+    info!(
+        "Service: Sending sample {} event to webhook ID: {} for tenant ID: {}",
+        String::from(dto.event_type),
+        webhook_id,
+        tenant_id
+    );
+    let webhook = get_security_webhook(pool, tenant_id, user_id).await?;
+    if webhook.id != webhook_id {
+        return Err(AppError::NotFound(format!(
+            "Webhook with ID {} not found for tenant {}",
+            webhook_id, tenant_id
+        )));
+    }
+    let payload =
+        security_event_catalog::example_payload(dto.event_type, tenant_id, Uuid::new_v4());
+    deliver(pool, &webhook, &payload).await
+}
+
+/// Sends a security event to the tenant's webhook if it is active and subscribed to the
+/// event type. Delivery happens in the background; failures are logged and recorded.
+pub async fn emit_security_event(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    event_type: SecurityEventType,
+    actor_user_id: Option<Uuid>,
+    data: JsonValue,
+) {
+    let webhook = match find_webhook(pool, tenant_id).await {
+        Ok(Some(webhook)) => webhook,
+        Ok(None) => return,
+        Err(e) => {
+            warn!(
+                "Could not load security webhook for tenant {}: {}",
+                tenant_id, e
+            );
+            return;
+        }
+    };
+    if !webhook.is_active || !webhook.event_types.contains(&String::from(event_type)) {
+        return;
+    }
+
+    let payload = event_payload(tenant_id, event_type, actor_user_id, data);
+    let pool = pool.clone();
+    tokio::spawn(db::with_tenant_scope(Some(tenant_id), async move {
+        if let Err(e) = deliver(&pool, &webhook, &payload).await {
+            warn!(
+                "Security webhook delivery {} for tenant {} failed: {}",
+                payload.id, payload.tenant_id, e
+            );
+        }
+    }));
+}
+
+fn event_payload(
+    tenant_id: Uuid,
+    event_type: SecurityEventType,
+    actor_user_id: Option<Uuid>,
+    data: JsonValue,
+) -> SecurityEventPayload {
+    SecurityEventPayload {
+        id: Uuid::new_v4(),
+        event_type,
+        tenant_id,
+        occurred_at: Utc::now(),
+        actor_user_id,
+        data,
+        test: false,
+    }
+}
+
+async fn find_webhook(pool: &PgPool, tenant_id: Uuid) -> Result<Option<SecurityWebhook>, AppError> {
+    let webhook = query_as!(
+        SecurityWebhook,
+        r#"
+        SELECT
+            id, tenant_id, url, signing_secret, event_types, is_active, last_delivery_at,
+            last_delivery_status, last_error, created_at, created_by, updated_at, updated_by
+        FROM security_webhooks
+        WHERE tenant_id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(webhook)
+}
+
+/// Signs and POSTs one event, then records the outcome on the webhook.
+async fn deliver(
+    pool: &PgPool,
+    webhook: &SecurityWebhook,
+    payload: &SecurityEventPayload,
+) -> Result<SecurityWebhook, AppError> {
+    let secret = decrypt_secret(&webhook.signing_secret)?;
+    let body = serde_json::to_vec(payload).map_err(|e| {
+        AppError::InternalServerError(format!("Failed to serialize security event: {}", e))
+    })?;
+    let timestamp = Utc::now().timestamp();
+    let mut signed = format!("{}.", timestamp).into_bytes();
+    signed.extend_from_slice(&body);
+    let signature = format!("t={},v1={}", timestamp, hmac_sha256_hex(&secret, &signed));
+
+    let result = Client::new()
+        .post(&webhook.url)
+        .timeout(DELIVERY_TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Forge-Event", String::from(payload.event_type))
+        .header("X-Forge-Delivery", payload.id.to_string())
+        .header("X-Forge-Signature", signature)
+        .body(body)
+        .send()
+        .await;
+
+    let (status, error) = match result {
+        Ok(response) if response.status().is_success() => {
+            (Some(response.status().as_u16() as i32), None)
+        }
+        Ok(response) => {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            (
+                Some(status.as_u16() as i32),
+                Some(format!("Endpoint returned {}: {}", status, text)),
+            )
+        }
+        Err(e) => (None, Some(format!("Endpoint unreachable: {}", e))),
+    };
+    if let Some(error) = &error {
+        warn!(
+            "Security event {} to tenant {} not delivered: {}",
+            payload.id, payload.tenant_id, error
+        );
+    }
+
+    let updated = query_as!(
+        SecurityWebhook,
+        r#"
+        UPDATE security_webhooks
+        SET last_delivery_at = NOW(), last_delivery_status = $2, last_error = $3
+        WHERE id = $1
+        RETURNING
+            id, tenant_id, url, signing_secret, event_types, is_active, last_delivery_at,
+            last_delivery_status, last_error, created_at, created_by, updated_at, updated_by
+        "#,
+        webhook.id,
+        status,
+        error.map(|e| e.chars().take(MAX_ERROR_LEN).collect::<String>())
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(updated)
+}
@@ -0,0 +1,574 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::{query_as, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::statement_layout_dto::{CreateStatementLayoutDto, UpdateStatementLayoutDto},
+        report::StatementType,
+        statement_layout::{LayoutGroup, LayoutRow, StatementLayout, StatementLayoutDefinition},
+    },
+};
+
+/// An account as layout groups see it.
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutAccount<'a> {
+    pub account_id: Uuid,
+    pub account_type: &'a str,
+    pub account_code: Option<&'a str>,
+}
+
+/// Retrieves the active statement layouts for a specific tenant.
+pub async fn list_statement_layouts(
+    pool: &PgPool,
+    tenant_id: Uuid,
+) -> Result<Vec<StatementLayout>, AppError> {
+    info!(
+        "Service: Listing statement layouts for tenant ID: {}",
+        tenant_id
+    );
+
+    let layouts = query_as!(
+        StatementLayout,
+        r#"
+        SELECT
+            id, tenant_id, name, statement_type, definition, is_default, is_active,
+            created_at, created_by, updated_at, updated_by
+        FROM statement_layouts
+        WHERE tenant_id = $1 AND is_active = TRUE
+        ORDER BY statement_type, name
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(layouts)
+}
+
+/// Retrieves a single active statement layout by ID for a specific tenant.
+pub async fn get_statement_layout_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    layout_id: Uuid,
+) -> Result<StatementLayout, AppError> {
+    info!(
+        "Service: Getting statement layout with ID: {} for tenant ID: {}",
+        layout_id, tenant_id
+    );
+
+    let layout = query_as!(
+        StatementLayout,
+        r#"
+        SELECT
+            id, tenant_id, name, statement_type, definition, is_default, is_active,
+            created_at, created_by, updated_at, updated_by
+        FROM statement_layouts
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        layout_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Statement layout with ID {} not found for tenant {}",
+            layout_id, tenant_id
+        ))
+    })?;
+
+    Ok(layout)
+}
+
+/// Creates a new statement layout. Marking it as default replaces the previous default
+/// for the same statement type.
+pub async fn create_statement_layout(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateStatementLayoutDto,
+) -> Result<StatementLayout, AppError> {
+    info!(
+        "Service: Creating statement layout '{}' for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    dto.validate()?;
+    validate_definition(&dto.definition)?;
+
+    let is_default = dto.is_default.unwrap_or(false);
+    let definition = definition_json(&dto.definition)?;
+
+    let mut db_tx = pool.begin().await?;
+
+    if is_default {
+        clear_default(
+            &mut db_tx,
+            tenant_id,
+            dto.statement_type,
+            created_by_user_id,
+        )
+        .await?;
+    }
+
+    let new_layout = query_as!(
+        StatementLayout,
+        r#"
+        INSERT INTO statement_layouts (
+            tenant_id, name, statement_type, definition, is_default, is_active,
+            created_by, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5, TRUE, $6, $6)
+        RETURNING
+            id, tenant_id, name, statement_type, definition, is_default, is_active,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.name,
+        String::from(dto.statement_type),
+        definition,
+        is_default,
+        created_by_user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(new_layout)
+}
+
+/// Updates an existing statement layout.
+pub async fn update_statement_layout(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    layout_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateStatementLayoutDto,
+) -> Result<StatementLayout, AppError> {
+    info!(
+        "Service: Updating statement layout with ID: {} for tenant ID: {}",
+        layout_id, tenant_id
+    );
+
+    dto.validate()?;
+    let definition = match &dto.definition {
+        Some(definition) => {
+            validate_definition(definition)?;
+            Some(definition_json(definition)?)
+        }
+        None => None,
+    };
+
+    let current = get_statement_layout_by_id(pool, tenant_id, layout_id).await?;
+    let statement_type: StatementType = current
+        .statement_type
+        .parse()
+        .map_err(AppError::InternalServerError)?;
+
+    let mut db_tx = pool.begin().await?;
+
+    if dto.is_default == Some(true) && !current.is_default {
+        clear_default(&mut db_tx, tenant_id, statement_type, updated_by_user_id).await?;
+    }
+
+    let updated_layout = query_as!(
+        StatementLayout,
+        r#"
+        UPDATE statement_layouts
+        SET
+            name = COALESCE($3, name),
+            definition = COALESCE($4, definition),
+            is_default = COALESCE($5, is_default),
+            updated_at = NOW(),
+            updated_by = $6
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        RETURNING
+            id, tenant_id, name, statement_type, definition, is_default, is_active,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        layout_id,
+        tenant_id,
+        dto.name,
+        definition,
+        dto.is_default,
+        updated_by_user_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Statement layout with ID {} not found or not owned by tenant {}",
+            layout_id, tenant_id
+        ))
+    })?;
+
+    db_tx.commit().await?;
+    Ok(updated_layout)
+}
+
+/// Deactivates a statement layout (soft delete).
+pub async fn deactivate_statement_layout(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    layout_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deactivating statement layout with ID: {} for tenant ID: {}",
+        layout_id, tenant_id
+    );
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE statement_layouts
+        SET
+            is_active = FALSE,
+            is_default = FALSE,
+            updated_at = NOW(),
+            updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        layout_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Statement layout with ID {} not found or already inactive for tenant {}",
+            layout_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Finds the layout to apply to a statement: the requested one, otherwise the tenant's
+/// default for that statement type. `None` means the built-in layout is used.
+pub async fn find_layout_definition(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    statement_type: StatementType,
+    layout_id: Option<Uuid>,
+) -> Result<Option<(Uuid, StatementLayoutDefinition)>, AppError> {
+    let layout = match layout_id {
+        Some(layout_id) => {
+            let layout = get_statement_layout_by_id(pool, tenant_id, layout_id).await?;
+            if layout.statement_type != String::from(statement_type) {
+                return Err(AppError::Validation(format!(
+                    "Statement layout {} is for {} statements",
+                    layout_id, layout.statement_type
+                )));
+            }
+            Some(layout)
+        }
+        None => {
+            query_as!(
+                StatementLayout,
+                r#"
+            SELECT
+                id, tenant_id, name, statement_type, definition, is_default, is_active,
+                created_at, created_by, updated_at, updated_by
+            FROM statement_layouts
+            WHERE tenant_id = $1 AND statement_type = $2 AND is_default = TRUE AND is_active = TRUE
+            "#,
+                tenant_id,
+                String::from(statement_type)
+            )
+            .fetch_optional(pool)
+            .await?
+        }
+    };
+
+    layout
+        .map(|layout| Ok((layout.id, parse_definition(layout)?)))
+        .transpose()
+}
+
+/// The definition of one of the tenant's active layouts.
+pub async fn get_layout_definition(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    layout_id: Uuid,
+) -> Result<StatementLayoutDefinition, AppError> {
+    parse_definition(get_statement_layout_by_id(pool, tenant_id, layout_id).await?)
+}
+
+fn parse_definition(layout: StatementLayout) -> Result<StatementLayoutDefinition, AppError> {
+    serde_json::from_value(layout.definition).map_err(|e| {
+        AppError::InternalServerError(format!(
+            "Stored statement layout {} is malformed: {}",
+            layout.id, e
+        ))
+    })
+}
+
+/// Whether an account is selected by a layout group.
+fn group_selects(group: &LayoutGroup, account: &LayoutAccount) -> bool {
+    group.account_ids.contains(&account.account_id)
+        || group
+            .account_types
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(account.account_type))
+        || account.account_code.is_some_and(|code| {
+            group
+                .account_code_prefixes
+                .iter()
+                .any(|prefix| code.starts_with(prefix.as_str()))
+        })
+}
+
+/// The accounts behind every row of a layout, by row key. A group has the accounts it
+/// selects that no earlier group did; accounts no group selects are left out. A subtotal
+/// has the accounts of its terms.
+pub fn row_accounts<'d>(
+    definition: &'d StatementLayoutDefinition,
+    accounts: &[LayoutAccount],
+) -> Result<HashMap<&'d str, Vec<Uuid>>, AppError> {
+    let mut assigned: HashSet<Uuid> = HashSet::new();
+    let mut rows: HashMap<&str, Vec<Uuid>> = HashMap::new();
+
+    for row in &definition.rows {
+        match row {
+            LayoutRow::Group(group) => {
+                let members: Vec<Uuid> = accounts
+                    .iter()
+                    .filter(|a| !assigned.contains(&a.account_id) && group_selects(group, a))
+                    .map(|a| a.account_id)
+                    .collect();
+                assigned.extend(members.iter().copied());
+                rows.insert(group.key.as_str(), members);
+            }
+            LayoutRow::Subtotal(subtotal) => {
+                let mut account_ids: Vec<Uuid> = Vec::new();
+                for term in &subtotal.terms {
+                    let term_ids = rows.get(term.key.as_str()).ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "Subtotal '{}' references unknown row '{}'",
+                            subtotal.key, term.key
+                        ))
+                    })?;
+                    account_ids.extend(term_ids.iter().copied());
+                }
+                account_ids.sort();
+                account_ids.dedup();
+                rows.insert(subtotal.key.as_str(), account_ids);
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Unsets the current default layout for a statement type.
+async fn clear_default(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    statement_type: StatementType,
+    updated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE statement_layouts
+        SET is_default = FALSE, updated_at = NOW(), updated_by = $3
+        WHERE tenant_id = $1 AND statement_type = $2 AND is_default = TRUE
+        "#,
+        tenant_id,
+        String::from(statement_type),
+        updated_by_user_id
+    )
+    .execute(&mut **db_tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Checks that keys are unique, groups select something, and subtotals only reference
+/// rows defined above them.
+fn validate_definition(definition: &StatementLayoutDefinition) -> Result<(), AppError> {
+    if definition.rows.is_empty() {
+        return Err(AppError::Validation(
+            "A statement layout needs at least one row".to_string(),
+        ));
+    }
+
+    let mut seen_keys: HashSet<&str> = HashSet::new();
+    for row in &definition.rows {
+        let (key, title) = match row {
+            LayoutRow::Group(group) => (group.key.as_str(), group.title.as_str()),
+            LayoutRow::Subtotal(subtotal) => (subtotal.key.as_str(), subtotal.title.as_str()),
+        };
+        if key.trim().is_empty() || title.trim().is_empty() {
+            return Err(AppError::Validation(
+                "Every layout row needs a key and a title".to_string(),
+            ));
+        }
+
+        match row {
+            LayoutRow::Group(group) => {
+                if group.account_ids.is_empty()
+                    && group.account_types.is_empty()
+                    && group.account_code_prefixes.is_empty()
+                {
+                    return Err(AppError::Validation(format!(
+                        "Group '{}' must select accounts by ID, type or code prefix",
+                        key
+                    )));
+                }
+            }
+            LayoutRow::Subtotal(subtotal) => {
+                if subtotal.terms.is_empty() {
+                    return Err(AppError::Validation(format!(
+                        "Subtotal '{}' has no terms",
+                        key
+                    )));
+                }
+                if let Some(term) = subtotal
+                    .terms
+                    .iter()
+                    .find(|t| !seen_keys.contains(t.key.as_str()))
+                {
+                    return Err(AppError::Validation(format!(
+                        "Subtotal '{}' references '{}', which is not defined above it",
+                        key, term.key
+                    )));
+                }
+            }
+        }
+
+        if !seen_keys.insert(key) {
+            return Err(AppError::Validation(format!(
+                "Duplicate layout row key '{}'",
+                key
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn definition_json(definition: &StatementLayoutDefinition) -> Result<serde_json::Value, AppError> {
+    serde_json::to_value(definition)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode layout: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::{row_accounts, validate_definition, LayoutAccount};
+    use crate::{error::AppError, models::statement_layout::StatementLayoutDefinition};
+
+    fn definition(rows: serde_json::Value) -> StatementLayoutDefinition {
+        serde_json::from_value(json!({ "rows": rows })).expect("layout definition")
+    }
+
+    fn account<'a>(account_type: &'a str, account_code: Option<&'a str>) -> LayoutAccount<'a> {
+        LayoutAccount {
+            account_id: Uuid::new_v4(),
+            account_type,
+            account_code,
+        }
+    }
+
+    fn invalid(rows: serde_json::Value) -> bool {
+        matches!(
+            validate_definition(&definition(rows)),
+            Err(AppError::Validation(_))
+        )
+    }
+
+    #[test]
+    fn nested_subtotals_are_valid() {
+        let layout = definition(json!([
+            { "kind": "GROUP", "key": "rev", "title": "Revenue", "account_types": ["Revenue"] },
+            { "kind": "GROUP", "key": "cogs", "title": "Cost of sales", "account_code_prefixes": ["5"] },
+            { "kind": "SUBTOTAL", "key": "gross", "title": "Gross profit",
+              "terms": [{ "key": "rev" }, { "key": "cogs", "subtract": true }] },
+            { "kind": "GROUP", "key": "opex", "title": "Operating expenses", "account_types": ["Expense"] },
+            { "kind": "SUBTOTAL", "key": "net", "title": "Net income",
+              "terms": [{ "key": "gross" }, { "key": "opex", "subtract": true }] },
+        ]));
+        assert!(validate_definition(&layout).is_ok());
+    }
+
+    #[test]
+    fn invalid_definitions_are_rejected() {
+        assert!(invalid(json!([])));
+        // Missing title
+        assert!(invalid(json!([
+            { "kind": "GROUP", "key": "rev", "title": " ", "account_types": ["Revenue"] },
+        ])));
+        // A group selecting nothing
+        assert!(invalid(
+            json!([{ "kind": "GROUP", "key": "rev", "title": "Revenue" }])
+        ));
+        // Duplicate keys
+        assert!(invalid(json!([
+            { "kind": "GROUP", "key": "rev", "title": "Revenue", "account_types": ["Revenue"] },
+            { "kind": "GROUP", "key": "rev", "title": "Other", "account_types": ["Expense"] },
+        ])));
+        // A subtotal without terms, and one referencing a row below it
+        assert!(invalid(json!([
+            { "kind": "GROUP", "key": "rev", "title": "Revenue", "account_types": ["Revenue"] },
+            { "kind": "SUBTOTAL", "key": "total", "title": "Total", "terms": [] },
+        ])));
+        assert!(invalid(json!([
+            { "kind": "SUBTOTAL", "key": "total", "title": "Total", "terms": [{ "key": "rev" }] },
+            { "kind": "GROUP", "key": "rev", "title": "Revenue", "account_types": ["Revenue"] },
+        ])));
+    }
+
+    #[test]
+    fn accounts_go_to_the_first_group_that_selects_them() {
+        let sales = account("Revenue", Some("4000"));
+        let cost = account("Expense", Some("5000"));
+        let rent = account("Expense", Some("6100"));
+        let cash = account("Asset", Some("1000")); // Selected by no group
+        let layout = definition(json!([
+            { "kind": "GROUP", "key": "rev", "title": "Revenue", "account_types": ["revenue"] },
+            { "kind": "GROUP", "key": "cogs", "title": "Cost of sales", "account_code_prefixes": ["5"] },
+            { "kind": "GROUP", "key": "opex", "title": "Operating expenses", "account_types": ["Expense"] },
+            { "kind": "SUBTOTAL", "key": "net", "title": "Net income",
+              "terms": [{ "key": "rev" }, { "key": "cogs", "subtract": true }, { "key": "opex", "subtract": true }] },
+        ]));
+
+        let rows = row_accounts(&layout, &[sales, cost, rent, cash]).unwrap();
+        assert_eq!(rows["rev"], vec![sales.account_id]);
+        assert_eq!(rows["cogs"], vec![cost.account_id]);
+        assert_eq!(rows["opex"], vec![rent.account_id]);
+        let mut net = vec![sales.account_id, cost.account_id, rent.account_id];
+        net.sort();
+        assert_eq!(rows["net"], net);
+        assert!(!rows.values().any(|ids| ids.contains(&cash.account_id)));
+    }
+
+    #[test]
+    fn groups_naming_missing_accounts_are_empty() {
+        let layout = definition(json!([
+            { "kind": "GROUP", "key": "gone", "title": "Closed accounts", "account_ids": [Uuid::new_v4()] },
+        ]));
+        let rows = row_accounts(&layout, &[account("Asset", None)]).unwrap();
+        assert!(rows["gone"].is_empty());
+    }
+
+    #[test]
+    fn subtotals_of_unknown_rows_are_rejected() {
+        let layout = definition(json!([
+            { "kind": "SUBTOTAL", "key": "total", "title": "Total", "terms": [{ "key": "rev" }] },
+        ]));
+        assert!(matches!(
+            row_accounts(&layout, &[]),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
@@ -9,11 +9,14 @@ use crate::{
         tenant::Tenant,
         dto::tenant_dto::{CreateTenantDto, UpdateTenantDto},
     },
+    services::permission,
+    utils::update_builder::UpdateBuilder,
 };
 
-/// Retrieves a list of all active tenants.
-pub async fn list_tenants(pool: &PgPool) -> Result<Vec<Tenant>, AppError> {
-    info!("Service: Listing all active tenants.");
+/// Retrieves the active tenants the user is a member of. Sandboxes (see `services::api_key`)
+/// are left out.
+pub async fn list_tenants(pool: &PgPool, user_id: Uuid) -> Result<Vec<Tenant>, AppError> {
+    info!("Service: Listing active tenants of user {}.", user_id);
 
     let tenants = query_as!(
         Tenant,
@@ -23,8 +26,11 @@ pub async fn list_tenants(pool: &PgPool) -> Result<Vec<Tenant>, AppError> {
             created_at, created_by, updated_at, updated_by
         FROM tenants
         WHERE is_active = TRUE
+          AND id NOT IN (SELECT sandbox_tenant_id FROM tenant_sandboxes)
+          AND id IN (SELECT tenant_id FROM user_tenant_roles WHERE user_id = $1)
         ORDER BY name
         "#,
+        user_id
     )
     .fetch_all(pool)
     .await?;
@@ -54,7 +60,7 @@ pub async fn get_tenant_by_id(pool: &PgPool, tenant_id: Uuid) -> Result<Tenant,
     Ok(tenant)
 }
 
-/// Creates a new tenant.
+/// Creates a new tenant and grants its creator the `admin` role in it.
 /// `created_by_user_id` should come from the authenticated system administrator or initial setup process.
 pub async fn create_tenant(
     pool: &PgPool,
@@ -63,6 +69,7 @@ pub async fn create_tenant(
 ) -> Result<Tenant, AppError> {
     info!("Service: Creating new tenant with name: {}", dto.name);
 
+    let mut db_tx = pool.begin().await?;
     let new_tenant = query_as!(
         Tenant,
         r#"
@@ -81,14 +88,35 @@ pub async fn create_tenant(
         dto.fiscal_year_end_month,
         created_by_user_id
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *db_tx)
     .await?;
 
+    let granted = sqlx::query!(
+        r#"
+        INSERT INTO user_tenant_roles (user_id, tenant_id, role_id, created_by, updated_by)
+        SELECT $1, $2, id, $1, $1
+        FROM roles
+        WHERE name = $3
+        "#,
+        created_by_user_id,
+        new_tenant.id,
+        permission::ADMIN_ROLE
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+    if granted == 0 {
+        return Err(AppError::InternalServerError(format!(
+            "The '{}' role is missing; run the database migrations",
+            permission::ADMIN_ROLE
+        )));
+    }
+
+    db_tx.commit().await?;
     Ok(new_tenant)
 }
 
-/// Updates an existing tenant.
-/// `updated_by_user_id` should come from the authenticated system administrator.
+/// Updates an existing tenant. Requires `tenant.manage` there.
 pub async fn update_tenant(
     pool: &PgPool,
     tenant_id: Uuid,
@@ -97,68 +125,32 @@ pub async fn update_tenant(
 ) -> Result<Tenant, AppError> {
     info!("Service: Updating tenant with ID: {}", tenant_id);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
-
-    if let Some(name) = dto.name {
-        update_cols.push(format!("name = ${}", param_idx));
-        update_values.push(Box::new(name));
-        param_idx += 1;
-    }
-    if let Some(industry) = dto.industry {
-        update_cols.push(format!("industry = ${}", param_idx));
-        update_values.push(Box::new(industry));
-        param_idx += 1;
-    }
-    if let Some(base_currency_code) = dto.base_currency_code {
-        update_cols.push(format!("base_currency_code = ${}", param_idx));
-        update_values.push(Box::new(base_currency_code));
-        param_idx += 1;
-    }
-    if let Some(fiscal_year_end_month) = dto.fiscal_year_end_month {
-        update_cols.push(format!("fiscal_year_end_month = ${}", param_idx));
-        update_values.push(Box::new(fiscal_year_end_month));
-        param_idx += 1;
-    }
-    if let Some(is_active) = dto.is_active {
-        update_cols.push(format!("is_active = ${}", param_idx));
-        update_values.push(Box::new(is_active));
-        param_idx += 1;
-    }
-
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
+    permission::require_permission(pool, tenant_id, updated_by_user_id, permission::TENANT_MANAGE)
+        .await?;
 
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
+    let mut update = UpdateBuilder::new("tenants");
+    update
+        .set("name", dto.name)
+        .set("industry", dto.industry)
+        .set("base_currency_code", dto.base_currency_code)
+        .set("fiscal_year_end_month", dto.fiscal_year_end_month)
+        .set("is_active", dto.is_active);
+    if update.is_empty() {
+        return Err(AppError::Validation("No fields provided for update".to_string()));
     }
 
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(tenant_id);
+    query.push(
         r#"
-        UPDATE tenants
-        SET {}
-        WHERE id = ${}
         RETURNING
             id, name, industry, base_currency_code, fiscal_year_end_month, is_active,
             created_at, created_by, updated_at, updated_by
         "#,
-        update_clause, param_idx // tenant_id will be the last parameter
     );
 
-    let mut query = sqlx::query_as::<_, Tenant>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
-    }
-    // Bind tenant_id last
-    query = query.bind(tenant_id);
-
     let updated_tenant = query
+        .build_query_as::<Tenant>()
         .fetch_optional(pool)
         .await?
         .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
@@ -166,8 +158,7 @@ pub async fn update_tenant(
     Ok(updated_tenant)
 }
 
-/// Deactivates a tenant (soft delete).
-/// `updated_by_user_id` should come from the authenticated system administrator.
+/// Deactivates a tenant (soft delete). Requires `tenant.manage` there.
 pub async fn deactivate_tenant(
     pool: &PgPool,
     tenant_id: Uuid,
@@ -175,6 +166,9 @@ pub async fn deactivate_tenant(
 ) -> Result<(), AppError> {
     info!("Service: Deactivating tenant with ID: {}", tenant_id);
 
+    permission::require_permission(pool, tenant_id, updated_by_user_id, permission::TENANT_MANAGE)
+        .await?;
+
     let affected_rows = sqlx::query!(
         r#"
         UPDATE tenants
@@ -196,4 +190,114 @@ pub async fn deactivate_tenant(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::{create_tenant, list_tenants};
+    use crate::{models::dto::tenant_dto::CreateTenantDto, services::permission};
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn creator_holds_every_permission_in_the_new_tenant() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPool::connect(&url).await.expect("connect to DATABASE_URL");
+        let run = Uuid::new_v4();
+        let creator_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+             VALUES ($1, 'EMAIL_PASSWORD', $1, 'Tenant', 'Creator') RETURNING id",
+        )
+        .bind(format!("creator-{}@example.com", run))
+        .fetch_one(&pool)
+        .await
+        .expect("insert user");
+        sqlx::query(
+            "INSERT INTO currencies (code, name, symbol, created_by, updated_by)
+             VALUES ('USD', 'US Dollar', '$', $1, $1) ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(creator_id)
+        .execute(&pool)
+        .await
+        .expect("insert currency");
+
+        let tenant = create_tenant(
+            &pool,
+            creator_id,
+            CreateTenantDto {
+                name: format!("Creator {}", run),
+                industry: None,
+                base_currency_code: "USD".to_string(),
+                fiscal_year_end_month: 12,
+            },
+        )
+        .await
+        .expect("create tenant");
+
+        for name in [
+            permission::PERIOD_REOPEN,
+            permission::TX_APPROVE,
+            permission::MEMBERS_INVITE,
+            permission::RECORDS_RESTORE,
+            permission::USERS_PERSONAL_DATA,
+            permission::TENANT_MANAGE,
+        ] {
+            let granted = permission::user_has_permission(&pool, tenant.id, creator_id, name)
+                .await
+                .expect("check permission");
+            assert!(granted, "creator lacks {}", name);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn users_list_only_their_own_tenants() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPool::connect(&url).await.expect("connect to DATABASE_URL");
+        let run = Uuid::new_v4();
+        let mut user_ids = Vec::new();
+        for name in ["creator", "stranger"] {
+            let user_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+                 VALUES ($1, 'EMAIL_PASSWORD', $1, 'Tenant', $2) RETURNING id",
+            )
+            .bind(format!("{}-{}@example.com", name, run))
+            .bind(name)
+            .fetch_one(&pool)
+            .await
+            .expect("insert user");
+            user_ids.push(user_id);
+        }
+        let (creator_id, stranger_id) = (user_ids[0], user_ids[1]);
+        sqlx::query(
+            "INSERT INTO currencies (code, name, symbol, created_by, updated_by)
+             VALUES ('USD', 'US Dollar', '$', $1, $1) ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(creator_id)
+        .execute(&pool)
+        .await
+        .expect("insert currency");
+
+        let tenant = create_tenant(
+            &pool,
+            creator_id,
+            CreateTenantDto {
+                name: format!("Listed {}", run),
+                industry: None,
+                base_currency_code: "USD".to_string(),
+                fiscal_year_end_month: 12,
+            },
+        )
+        .await
+        .expect("create tenant");
+
+        let mine = list_tenants(&pool, creator_id).await.expect("list tenants");
+        assert_eq!(
+            mine.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![tenant.id]
+        );
+        let theirs = list_tenants(&pool, stranger_id).await.expect("list tenants");
+        assert!(theirs.is_empty());
+    }
+}
@@ -0,0 +1,570 @@
+//! Self-service tenant backups: the tenant's books as a ZIP of JSON files, one per table,
+//! with a `manifest.json` listing the row counts.
+//!
+//! Backups are built in the background and kept as export artifacts (see `export_artifact`),
+//! so they are downloaded, resumed and expired like other exports. With a passphrase the ZIP
+//! is encrypted as an age file before it is stored (`crypto::encrypt_with_passphrase`), so
+//! the bundle can be kept anywhere and opened with `age -d`; the passphrase itself is never
+//! stored. Credentials (bank tokens, webhook secrets, API keys, mail passwords) are left out,
+//! and text sealed by privacy mode stays sealed.
+//!
+//! An anonymized copy is the same bundle with the columns in `ANONYMIZED_COLUMNS` scrambled
+//! (see `utils::anonymize`), for engineers reproducing a tenant's problem. IDs, dates,
+//! currencies, codes and flags are kept, so every report runs on the same structure.
+
+use std::{
+    collections::HashMap,
+    io::{Cursor, Write},
+};
+
+use chrono::{NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use serde_json::{json, Map, Value as JsonValue};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::AppError,
+    models::{dto::tenant_backup_dto::CreateTenantBackupDto, export_artifact::ExportArtifact},
+    services::{budget_csv::slugify, export_artifact, permission, privacy, tenant},
+    utils::{
+        anonymize::{self, Scrambler},
+        crypto,
+    },
+};
+
+/// Version of the bundle layout, recorded in the manifest.
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Files in a backup and the rows they hold; `$1` is the tenant.
+pub const BACKUP_TABLES: &[(&str, &str)] = &[
+    (
+        "tenant",
+        "SELECT id, name, industry, base_currency_code, fiscal_year_end_month, privacy_mode, created_at \
+         FROM tenants WHERE id = $1",
+    ),
+    ("accounts", "SELECT * FROM accounts WHERE tenant_id = $1 ORDER BY account_code, name"),
+    ("categories", "SELECT * FROM categories WHERE tenant_id = $1 ORDER BY name"),
+    ("tags", "SELECT * FROM tags WHERE tenant_id = $1 ORDER BY name"),
+    ("payees", "SELECT * FROM payees WHERE tenant_id = $1 ORDER BY name"),
+    ("dimensions", "SELECT * FROM dimensions WHERE tenant_id = $1 ORDER BY kind, name"),
+    (
+        "transactions",
+        "SELECT * FROM transactions WHERE tenant_id = $1 ORDER BY transaction_date, created_at",
+    ),
+    (
+        "journal_entries",
+        "SELECT je.* FROM journal_entries je JOIN transactions t ON t.id = je.transaction_id \
+         WHERE t.tenant_id = $1 ORDER BY t.transaction_date, je.transaction_id, je.created_at",
+    ),
+    (
+        "transaction_splits",
+        "SELECT ts.* FROM transaction_splits ts JOIN transactions t ON t.id = ts.transaction_id \
+         WHERE t.tenant_id = $1 ORDER BY ts.transaction_id, ts.position",
+    ),
+    ("transaction_metadata", "SELECT * FROM transaction_metadata WHERE tenant_id = $1"),
+    ("transaction_attributions", "SELECT * FROM transaction_attributions WHERE tenant_id = $1"),
+    ("reimbursable_expenses", "SELECT * FROM reimbursable_expenses WHERE tenant_id = $1"),
+    ("reimbursement_matches", "SELECT * FROM reimbursement_matches WHERE tenant_id = $1"),
+    (
+        "recurring_transactions",
+        "SELECT * FROM recurring_transactions WHERE tenant_id = $1 ORDER BY created_at",
+    ),
+    (
+        "recurring_transaction_skips",
+        "SELECT s.* FROM recurring_transaction_skips s \
+         JOIN recurring_transactions r ON r.id = s.recurring_transaction_id \
+         WHERE r.tenant_id = $1 ORDER BY s.recurring_transaction_id, s.occurrence_date",
+    ),
+    ("budgets", "SELECT * FROM budgets WHERE tenant_id = $1 ORDER BY start_date, name"),
+    (
+        "budget_line_items",
+        "SELECT bli.* FROM budget_line_items bli JOIN budgets b ON b.id = bli.budget_id \
+         WHERE b.tenant_id = $1 ORDER BY bli.budget_id, bli.created_at",
+    ),
+    ("envelope_moves", "SELECT * FROM envelope_moves WHERE tenant_id = $1 ORDER BY moved_on"),
+    ("events", "SELECT * FROM events WHERE tenant_id = $1 ORDER BY start_date, name"),
+    (
+        "event_transactions",
+        "SELECT et.* FROM event_transactions et JOIN events e ON e.id = et.event_id \
+         WHERE e.tenant_id = $1 ORDER BY et.event_id",
+    ),
+    ("customers", "SELECT * FROM customers WHERE tenant_id = $1 ORDER BY name"),
+    ("invoice_sequences", "SELECT * FROM invoice_sequences WHERE tenant_id = $1"),
+    ("invoices", "SELECT * FROM invoices WHERE tenant_id = $1 ORDER BY issue_date, created_at"),
+    (
+        "invoice_lines",
+        "SELECT * FROM invoice_lines WHERE tenant_id = $1 ORDER BY invoice_id, position",
+    ),
+    (
+        "invoice_payments",
+        "SELECT * FROM invoice_payments WHERE tenant_id = $1 ORDER BY payment_date, created_at",
+    ),
+    ("vendors", "SELECT * FROM vendors WHERE tenant_id = $1 ORDER BY name"),
+    ("bills", "SELECT * FROM bills WHERE tenant_id = $1 ORDER BY bill_date, created_at"),
+    ("bill_lines", "SELECT * FROM bill_lines WHERE tenant_id = $1 ORDER BY bill_id, position"),
+    (
+        "bill_payments",
+        "SELECT * FROM bill_payments WHERE tenant_id = $1 ORDER BY payment_date, created_at",
+    ),
+    ("payments", "SELECT * FROM payments WHERE tenant_id = $1 ORDER BY payment_date, created_at"),
+    ("fixed_assets", "SELECT * FROM fixed_assets WHERE tenant_id = $1 ORDER BY acquisition_date, created_at"),
+    (
+        "fixed_asset_depreciations",
+        "SELECT * FROM fixed_asset_depreciations WHERE tenant_id = $1 ORDER BY asset_id, period_end",
+    ),
+    (
+        "exchange_rates",
+        "SELECT * FROM exchange_rates WHERE tenant_id = $1 ORDER BY rate_date, target_currency_code",
+    ),
+    ("fiscal_periods", "SELECT * FROM fiscal_periods WHERE tenant_id = $1 ORDER BY start_date"),
+    ("business_holidays", "SELECT * FROM business_holidays WHERE tenant_id = $1 ORDER BY holiday_date"),
+    ("merchant_rules", "SELECT * FROM merchant_rules WHERE tenant_id = $1 ORDER BY created_at"),
+    ("statement_layouts", "SELECT * FROM statement_layouts WHERE tenant_id = $1 ORDER BY name"),
+    ("custom_reports", "SELECT * FROM custom_reports WHERE tenant_id = $1 ORDER BY name"),
+];
+
+/// How a column is rewritten in anonymized copies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scramble {
+    Text,
+    Amount,
+    Scaled,   // By the row's own scale factor, keyed by its ID
+    EntryLeg, // By the scale factor of the leg's transaction, rebalanced per entry
+    Clear,
+    JournalTemplate, // Legs scaled by the row's factor, memos scrambled
+    MonthlyAmounts,  // Amounts keyed by month
+}
+
+/// Columns rewritten in anonymized copies, by file; all others are copied as they are.
+const ANONYMIZED_COLUMNS: &[(&str, &[(&str, Scramble)])] = &[
+    ("tenant", &[("name", Scramble::Text)]),
+    (
+        "accounts",
+        &[("name", Scramble::Text), ("description", Scramble::Text)],
+    ),
+    (
+        "categories",
+        &[("name", Scramble::Text), ("description", Scramble::Text)],
+    ),
+    (
+        "tags",
+        &[("name", Scramble::Text), ("description", Scramble::Text)],
+    ),
+    (
+        "payees",
+        &[("name", Scramble::Text), ("notes", Scramble::Text)],
+    ),
+    (
+        "dimensions",
+        &[
+            ("name", Scramble::Text),
+            ("code", Scramble::Clear),
+            ("description", Scramble::Text),
+        ],
+    ),
+    (
+        "transactions",
+        &[
+            ("description", Scramble::Text),
+            ("notes", Scramble::Text),
+            ("void_reason", Scramble::Text),
+            ("source_document_url", Scramble::Clear),
+            ("amount", Scramble::Scaled),
+        ],
+    ),
+    (
+        "journal_entries",
+        &[
+            ("memo", Scramble::Text),
+            ("amount", Scramble::EntryLeg),
+            ("converted_amount", Scramble::EntryLeg),
+        ],
+    ),
+    (
+        "transaction_metadata",
+        &[
+            ("latitude", Scramble::Clear),
+            ("longitude", Scramble::Clear),
+            ("device", Scramble::Text),
+        ],
+    ),
+    (
+        "reimbursable_expenses",
+        &[("payer", Scramble::Text), ("notes", Scramble::Text)],
+    ),
+    ("reimbursement_matches", &[("amount", Scramble::Amount)]),
+    (
+        "recurring_transactions",
+        &[
+            ("description", Scramble::Text),
+            ("notes", Scramble::Text),
+            ("amount", Scramble::Scaled),
+            ("journal_template", Scramble::JournalTemplate),
+        ],
+    ),
+    ("budgets", &[("name", Scramble::Text)]),
+    (
+        "budget_line_items",
+        &[
+            ("budgeted_amount", Scramble::Amount),
+            ("monthly_amounts", Scramble::MonthlyAmounts),
+        ],
+    ),
+    (
+        "envelope_moves",
+        &[("amount", Scramble::Amount), ("memo", Scramble::Text)],
+    ),
+    (
+        "events",
+        &[("name", Scramble::Text), ("description", Scramble::Text)],
+    ),
+    (
+        "customers",
+        &[
+            ("name", Scramble::Text),
+            ("email", Scramble::Clear),
+            ("billing_address", Scramble::Clear),
+            ("tax_id", Scramble::Clear),
+            ("notes", Scramble::Text),
+        ],
+    ),
+    // Totals are kept: scrambled separately, what was paid could exceed the total
+    ("invoices", &[("notes", Scramble::Text)]),
+    (
+        "invoice_lines",
+        &[
+            ("description", Scramble::Text),
+            ("unit_price", Scramble::Amount),
+            ("amount", Scramble::Amount),
+        ],
+    ),
+    (
+        "invoice_payments",
+        &[("amount", Scramble::Amount), ("memo", Scramble::Text)],
+    ),
+    (
+        "vendors",
+        &[
+            ("name", Scramble::Text),
+            ("email", Scramble::Clear),
+            ("address", Scramble::Clear),
+            ("tax_id", Scramble::Clear),
+            ("notes", Scramble::Text),
+        ],
+    ),
+    // Totals are kept, as for invoices
+    (
+        "bills",
+        &[("bill_number", Scramble::Text), ("notes", Scramble::Text)],
+    ),
+    (
+        "bill_lines",
+        &[
+            ("description", Scramble::Text),
+            ("amount", Scramble::Amount),
+        ],
+    ),
+    (
+        "bill_payments",
+        &[("amount", Scramble::Amount), ("memo", Scramble::Text)],
+    ),
+    // Amounts are kept: the unapplied amount is bound by the payment's
+    ("payments", &[("memo", Scramble::Text)]),
+    // Amounts are kept: accumulated depreciation is bound by cost and salvage value
+    (
+        "fixed_assets",
+        &[
+            ("name", Scramble::Text),
+            ("asset_tag", Scramble::Clear),
+            ("description", Scramble::Text),
+            ("notes", Scramble::Text),
+        ],
+    ),
+    (
+        "merchant_rules",
+        &[
+            ("pattern", Scramble::Text),
+            ("merchant_name", Scramble::Text),
+        ],
+    ),
+    ("statement_layouts", &[("name", Scramble::Text)]),
+    (
+        "custom_reports",
+        &[("name", Scramble::Text), ("description", Scramble::Text)],
+    ),
+];
+
+/// Starts a backup of the tenant and returns the pending artifact to poll.
+pub async fn start_tenant_backup(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: CreateTenantBackupDto,
+) -> Result<ExportArtifact, AppError> {
+    info!("Service: Starting backup of tenant ID: {}", tenant_id);
+
+    let anonymized = dto.anonymized;
+    let (required_permission, kind) = if anonymized {
+        (
+            permission::TENANT_EXPORT_ANONYMIZED,
+            export_artifact::TENANT_ANONYMIZED,
+        )
+    } else {
+        (permission::TENANT_BACKUP, export_artifact::TENANT_BACKUP)
+    };
+    permission::require_permission(pool, tenant_id, user_id, required_permission).await?;
+    let tenant = tenant::get_tenant_by_id(pool, tenant_id).await?;
+    let passphrase = dto.passphrase;
+    let artifact = export_artifact::create_artifact(
+        pool,
+        tenant_id,
+        user_id,
+        kind,
+        tenant_id,
+        passphrase.is_some(),
+    )
+    .await?;
+
+    let pool = pool.clone();
+    let artifact_id = artifact.id;
+    tokio::spawn(db::with_tenant_scope(Some(tenant_id), async move {
+        let now = Utc::now();
+        // The tenant's name is not part of an anonymized copy, so neither is it in the file name
+        let base_name = if anonymized {
+            format!("anonymized-{}-{}", tenant_id, now.format("%Y%m%d-%H%M%S"))
+        } else {
+            format!(
+                "backup-{}-{}",
+                slugify(&tenant.name),
+                now.format("%Y%m%d-%H%M%S")
+            )
+        };
+        let result = match build_bundle(&pool, tenant_id, artifact_id, anonymized, passphrase).await
+        {
+            Ok((bundle, is_encrypted)) => {
+                let (extension, content_type) = if is_encrypted {
+                    ("zip.enc", "application/octet-stream")
+                } else {
+                    ("zip", "application/zip")
+                };
+                export_artifact::store_artifact(
+                    &pool,
+                    artifact_id,
+                    tenant_id,
+                    &format!("{}.{}", base_name, extension),
+                    content_type,
+                    bundle,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            export_artifact::fail_artifact(&pool, artifact_id, &e).await;
+        }
+    }));
+
+    Ok(artifact)
+}
+
+/// Reads the tenant's tables and builds the (possibly anonymized and encrypted) bundle.
+/// Returns the bytes and whether they are encrypted.
+async fn build_bundle(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    artifact_id: Uuid,
+    anonymized: bool,
+    passphrase: Option<String>,
+) -> Result<(Vec<u8>, bool), AppError> {
+    info!(
+        "Service: Building backup {} for tenant ID: {}",
+        artifact_id, tenant_id
+    );
+
+    let created_at = Utc::now();
+    // A fresh key per copy, dropped with it: copies cannot be correlated or reversed
+    let scrambler = anonymized.then(Scrambler::with_random_key);
+    let mut files = Vec::with_capacity(BACKUP_TABLES.len() + 1);
+    let mut row_counts = Map::new();
+    for (name, sql) in BACKUP_TABLES {
+        let mut rows: JsonValue = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(json_agg(r), '[]'::json) FROM ({}) r",
+            sql
+        ))
+        .bind(tenant_id)
+        .fetch_one(pool)
+        .await?;
+        if let Some(scrambler) = &scrambler {
+            anonymize_rows(scrambler, name, &mut rows);
+        }
+        let count = rows.as_array().map_or(0, Vec::len);
+        row_counts.insert(name.to_string(), json!(count));
+        files.push((format!("{}.json", name), to_json_file(&rows)?));
+    }
+    let manifest = json!({
+        "format_version": BACKUP_FORMAT_VERSION,
+        "tenant_id": tenant_id,
+        "created_at": created_at,
+        "anonymized": anonymized,
+        "row_counts": row_counts,
+    });
+    files.insert(0, ("manifest.json".to_string(), to_json_file(&manifest)?));
+
+    // Compression and key derivation are CPU-bound
+    tokio::task::spawn_blocking(move || {
+        let bundle = write_zip(&files, created_at.naive_utc())?;
+        match passphrase {
+            Some(passphrase) => Ok((crypto::encrypt_with_passphrase(&passphrase, &bundle)?, true)),
+            None => Ok((bundle, false)),
+        }
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Backup task failed: {}", e)))?
+}
+
+/// Packs `files` into a deflated ZIP, every entry stamped `modified`.
+fn write_zip(files: &[(String, Vec<u8>)], modified: NaiveDateTime) -> Result<Vec<u8>, AppError> {
+    let zip_error = |e: zip::result::ZipError| {
+        AppError::InternalServerError(format!("Failed to write backup archive: {}", e))
+    };
+    // ZIP dates start in 1980; an earlier clock leaves the entries undated
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(zip::DateTime::try_from(modified).unwrap_or_default());
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in files {
+        writer
+            .start_file(
+                name.as_str(),
+                options.large_file(data.len() as u64 >= u32::MAX as u64),
+            )
+            .map_err(zip_error)?;
+        writer
+            .write_all(data)
+            .map_err(|e| zip_error(zip::result::ZipError::Io(e)))?;
+    }
+    Ok(writer.finish().map_err(zip_error)?.into_inner())
+}
+
+/// Scrambles the columns of `file` listed in `ANONYMIZED_COLUMNS`, in place.
+///
+/// A transaction's amounts are scaled by one factor keyed by its ID, so its header and the
+/// legs of its entry move together and every entry that balanced still does.
+fn anonymize_rows(scrambler: &Scrambler, file: &str, rows: &mut JsonValue) {
+    let Some((_, columns)) = ANONYMIZED_COLUMNS.iter().find(|(name, _)| *name == file) else {
+        return;
+    };
+    let Some(rows) = rows.as_array_mut() else {
+        return;
+    };
+    let scaled = columns
+        .iter()
+        .any(|(_, scramble)| matches!(scramble, Scramble::Scaled | Scramble::JournalTemplate));
+    for row in rows.iter_mut().filter_map(JsonValue::as_object_mut) {
+        let factor = row
+            .get("id")
+            .and_then(JsonValue::as_str)
+            .filter(|_| scaled)
+            .map_or(Decimal::ONE, |id| scrambler.scale_factor(id));
+        for (column, scramble) in columns.iter() {
+            if let Some(value) = row.get_mut(*column).filter(|value| !value.is_null()) {
+                *value = scramble_value(scrambler, *scramble, value, factor);
+            }
+        }
+    }
+
+    let legs: Vec<&str> = columns
+        .iter()
+        .filter(|(_, scramble)| *scramble == Scramble::EntryLeg)
+        .map(|(column, _)| *column)
+        .collect();
+    if !legs.is_empty() {
+        scale_journal_entries(scrambler, rows, &legs);
+    }
+}
+
+/// Scales journal entry legs by the factor of their transaction, one entry at a time.
+fn scale_journal_entries(scrambler: &Scrambler, rows: &mut [JsonValue], columns: &[&str]) {
+    let mut entries: HashMap<String, Vec<&mut Map<String, JsonValue>>> = HashMap::new();
+    for row in rows.iter_mut().filter_map(JsonValue::as_object_mut) {
+        if let Some(transaction_id) = row.get("transaction_id").and_then(JsonValue::as_str) {
+            let transaction_id = transaction_id.to_string();
+            entries.entry(transaction_id).or_default().push(row);
+        }
+    }
+    for (transaction_id, mut legs) in entries {
+        let factor = scrambler.scale_factor(&transaction_id);
+        for column in columns {
+            scale_legs(&mut legs, column, factor);
+        }
+    }
+}
+
+/// Scales `column` of one entry's legs by `factor`, keeping debits equal to credits (see
+/// `anonymize::scale_legs`). Legs without an amount in the column are left alone.
+fn scale_legs(legs: &mut [&mut Map<String, JsonValue>], column: &str, factor: Decimal) {
+    let signed: Vec<(usize, Decimal)> = legs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, leg)| {
+            let amount = anonymize::json_decimal(leg.get(column)?)?;
+            let credit = leg.get("entry_type").and_then(JsonValue::as_str) == Some("CREDIT");
+            Some((index, if credit { -amount } else { amount }))
+        })
+        .collect();
+    let amounts: Vec<Decimal> = signed.iter().map(|(_, amount)| *amount).collect();
+    for ((index, _), scaled) in signed.iter().zip(anonymize::scale_legs(&amounts, factor)) {
+        if let Some(value) = legs[*index].get_mut(column) {
+            *value = anonymize::decimal_json(scaled.abs(), value);
+        }
+    }
+}
+
+fn scramble_value(
+    scrambler: &Scrambler,
+    scramble: Scramble,
+    value: &JsonValue,
+    factor: Decimal,
+) -> JsonValue {
+    match (scramble, value) {
+        // Sealed text is unreadable already and has to stay openable with the tenant's key
+        (Scramble::Text, JsonValue::String(text)) if privacy::is_sealed(text) => value.clone(),
+        (Scramble::Text, JsonValue::String(text)) => JsonValue::String(scrambler.text(text)),
+        (Scramble::Amount, value) => scrambler.json_amount(value),
+        (Scramble::Scaled, value) => anonymize::json_decimal(value).map_or_else(
+            || value.clone(),
+            |amount| anonymize::decimal_json(anonymize::scale(amount, factor), value),
+        ),
+        (Scramble::Clear, _) => JsonValue::Null,
+        (Scramble::JournalTemplate, JsonValue::Array(legs)) => {
+            let mut legs = legs.clone();
+            let mut lines: Vec<_> = legs
+                .iter_mut()
+                .filter_map(JsonValue::as_object_mut)
+                .collect();
+            scale_legs(&mut lines, "amount", factor);
+            for line in lines {
+                if let Some(memo) = line.get_mut("memo").filter(|memo| memo.is_string()) {
+                    *memo = scramble_value(scrambler, Scramble::Text, memo, factor);
+                }
+            }
+            JsonValue::Array(legs)
+        }
+        (Scramble::MonthlyAmounts, JsonValue::Object(months)) => JsonValue::Object(
+            months
+                .iter()
+                .map(|(month, amount)| (month.clone(), scrambler.json_amount(amount)))
+                .collect(),
+        ),
+        (_, value) => value.clone(),
+    }
+}
+
+fn to_json_file(value: &JsonValue) -> Result<Vec<u8>, AppError> {
+    serde_json::to_vec_pretty(value)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to write backup file: {}", e)))
+}
@@ -0,0 +1,128 @@
+//! The tenant's branding on printed statements: its name and an optional logo.
+//!
+//! Logos are PNG or JPEG files checked on upload with the PDF writer's image reader, so a
+//! stored logo always renders. They are kept in the database as uploaded.
+
+use sqlx::{query_as, PgPool};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{error::AppError, models::tenant::TenantLogo, utils::pdf::Image};
+
+/// Largest logo file accepted.
+pub const MAX_LOGO_BYTES: usize = 512 * 1024;
+
+/// What a statement's header shows.
+pub struct Branding {
+    pub name: String,
+    pub logo: Option<Image>,
+}
+
+/// Retrieves the tenant's logo.
+pub async fn get_logo(pool: &PgPool, tenant_id: Uuid) -> Result<TenantLogo, AppError> {
+    info!("Service: Getting logo for tenant ID: {}", tenant_id);
+
+    query_as!(
+        TenantLogo,
+        r#"
+        SELECT tenant_id, content_type, width, height, image, updated_at, updated_by
+        FROM tenant_logos
+        WHERE tenant_id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant {} has no logo", tenant_id)))
+}
+
+/// Sets or replaces the tenant's logo.
+pub async fn set_logo(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    file: Vec<u8>,
+) -> Result<TenantLogo, AppError> {
+    info!("Service: Setting logo for tenant ID: {}", tenant_id);
+
+    if file.len() > MAX_LOGO_BYTES {
+        return Err(AppError::Validation(format!(
+            "The logo may be at most {} KB",
+            MAX_LOGO_BYTES / 1024
+        )));
+    }
+    let image = Image::from_bytes(&file)?;
+
+    let logo = query_as!(
+        TenantLogo,
+        r#"
+        INSERT INTO tenant_logos (tenant_id, content_type, width, height, image, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (tenant_id) DO UPDATE
+        SET content_type = EXCLUDED.content_type,
+            width = EXCLUDED.width,
+            height = EXCLUDED.height,
+            image = EXCLUDED.image,
+            updated_at = NOW(),
+            updated_by = EXCLUDED.updated_by
+        RETURNING tenant_id, content_type, width, height, image, updated_at, updated_by
+        "#,
+        tenant_id,
+        image.media_type(),
+        image.width() as i32,
+        image.height() as i32,
+        file,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(logo)
+}
+
+/// Removes the tenant's logo; statements are printed with the name alone.
+pub async fn delete_logo(pool: &PgPool, tenant_id: Uuid) -> Result<(), AppError> {
+    info!("Service: Deleting logo for tenant ID: {}", tenant_id);
+
+    let rows_affected = sqlx::query!("DELETE FROM tenant_logos WHERE tenant_id = $1", tenant_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+    if rows_affected == 0 {
+        return Err(AppError::NotFound(format!(
+            "Tenant {} has no logo",
+            tenant_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Loads the tenant's name and logo for a statement header. A logo that no longer reads is
+/// logged and left out rather than failing the statement.
+pub async fn load_branding(pool: &PgPool, tenant_id: Uuid) -> Result<Branding, AppError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT t.name, l.image as "image?"
+        FROM tenants t
+        LEFT JOIN tenant_logos l ON l.tenant_id = t.id
+        WHERE t.id = $1
+        "#,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+
+    let logo = row.image.and_then(|file| match Image::from_bytes(&file) {
+        Ok(image) => Some(image),
+        Err(e) => {
+            warn!("Ignoring the logo of tenant {}: {}", tenant_id, e);
+            None
+        }
+    });
+    Ok(Branding {
+        name: row.name,
+        logo,
+    })
+}
@@ -0,0 +1,527 @@
+//! Invitations to join a tenant.
+//!
+//! An invitation is emailed as a single-use token and grants a role when accepted. Accepting
+//! links the account that already uses the invited email, or creates a password account for
+//! it, and grants the role in the same transaction. Only SHA-256 hashes of the tokens are
+//! stored, and invitations expire after `INVITATION_TTL_DAYS`.
+//!
+//! Inviters need `members.invite` and can only hand out roles whose permissions they hold
+//! themselves in the tenant. A new tenant's creator holds the `admin` role, which has every
+//! permission, so the first invitations come from them.
+
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::{PgExecutor, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    config,
+    error::AppError,
+    models::{
+        dto::tenant_invitation_dto::{AcceptInvitationDto, CreateTenantInvitationDto},
+        security_webhook::SecurityEventType,
+        tenant_invitation::{AcceptedInvitation, TenantInvitation},
+    },
+    services::{mail_settings, mailer, permission, security_webhook, tenant},
+    user::service as user_service,
+    utils::crypto::{generate_secret, sha256_hex},
+};
+
+/// Random bytes in an invitation token.
+const TOKEN_BYTES: usize = 32;
+
+/// `auth_provider_type` of accounts that sign in with a password.
+pub const PASSWORD_PROVIDER: &str = "PASSWORD";
+
+/// Invites `dto.email` to the tenant and emails them the token. Expired invitations to the
+/// same address are revoked; a still pending one is a conflict.
+pub async fn create_invitation(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: CreateTenantInvitationDto,
+) -> Result<TenantInvitation, AppError> {
+    info!(
+        "Service: Inviting {} to tenant ID: {}",
+        dto.email, tenant_id
+    );
+
+    permission::require_permission(pool, tenant_id, user_id, permission::MEMBERS_INVITE).await?;
+    let tenant = tenant::get_tenant_by_id(pool, tenant_id).await?;
+    let email = dto.email.trim().to_string();
+
+    let role_name = sqlx::query_scalar!("SELECT name FROM roles WHERE id = $1", dto.role_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Role with ID {} not found", dto.role_id)))?;
+
+    // The role must not grant anything the inviter cannot do themselves
+    let within_own_permissions = sqlx::query_scalar!(
+        r#"
+        SELECT NOT EXISTS(
+            SELECT 1
+            FROM role_permissions rp
+            WHERE rp.role_id = $3
+              AND rp.permission_id NOT IN (
+                  SELECT own.permission_id
+                  FROM user_tenant_roles utr
+                  JOIN role_permissions own ON own.role_id = utr.role_id
+                  WHERE utr.tenant_id = $1 AND utr.user_id = $2
+              )
+        ) as "allowed!"
+        "#,
+        tenant_id,
+        user_id,
+        dto.role_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !within_own_permissions {
+        return Err(AppError::Forbidden(format!(
+            "The role '{}' grants permissions you do not hold",
+            role_name
+        )));
+    }
+
+    let already_member = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM user_tenant_roles utr
+            JOIN users u ON u.id = utr.user_id
+            WHERE utr.tenant_id = $1 AND LOWER(u.email) = LOWER($2)
+        ) as "exists!"
+        "#,
+        tenant_id,
+        email
+    )
+    .fetch_one(pool)
+    .await?;
+    if already_member {
+        return Err(AppError::Conflict(format!(
+            "{} is already a member of this tenant",
+            email
+        )));
+    }
+
+    let token = generate_secret(TOKEN_BYTES);
+    let expires_at = Utc::now() + Duration::days(config::get().auth.invitation_ttl_days as i64);
+    let mut db_tx = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE tenant_invitations
+        SET revoked_at = NOW(), revoked_by = $3
+        WHERE tenant_id = $1 AND LOWER(email) = LOWER($2)
+          AND accepted_at IS NULL AND revoked_at IS NULL AND expires_at <= NOW()
+        "#,
+        tenant_id,
+        email,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    let invitation_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO tenant_invitations (tenant_id, email, role_id, token_hash, expires_at, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
+        "#,
+        tenant_id,
+        email,
+        dto.role_id,
+        sha256_hex(token.as_bytes()),
+        expires_at,
+        user_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            AppError::Conflict(format!("An invitation to {} is already pending", email))
+        }
+        e => e.into(),
+    })?;
+    let invitation = find_invitation(&mut *db_tx, tenant_id, invitation_id).await?;
+
+    // Sent before committing, so an invitation that could not be delivered is not kept
+    let inviter = user_service::get_user_by_id(pool, user_id).await?;
+    let accept_link = match &config::get().auth.app_base_url {
+        Some(base_url) => format!(
+            "Accept the invitation: {}/invitations/accept?token={}",
+            base_url.trim_end_matches('/'),
+            token
+        ),
+        None => format!("Accept the invitation in Forge with this code: {}", token),
+    };
+    let body = format!(
+        "{} {} invited you to join {} on Forge as {}.\n\n{}\n\nThe invitation expires on {}. If you were not expecting it, you can ignore this email.",
+        inviter.first_name,
+        inviter.last_name,
+        tenant.name,
+        role_name,
+        accept_link,
+        expires_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    let smtp = mail_settings::smtp_config_for_tenant(pool, tenant_id).await?;
+    mailer::send_email_with(
+        smtp.as_ref(),
+        &[email],
+        &format!("You're invited to join {} on Forge", tenant.name),
+        &body,
+        Vec::new(),
+    )
+    .await?;
+
+    db_tx.commit().await?;
+    Ok(invitation)
+}
+
+/// Lists the tenant's invitations that can still be accepted, newest first.
+pub async fn list_pending_invitations(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<TenantInvitation>, AppError> {
+    info!(
+        "Service: Listing pending invitations for tenant ID: {}",
+        tenant_id
+    );
+
+    permission::require_permission(pool, tenant_id, user_id, permission::MEMBERS_INVITE).await?;
+    let invitations = sqlx::query_as!(
+        TenantInvitation,
+        r#"
+        SELECT
+            i.id, i.tenant_id, i.email, i.role_id, r.name as role_name, i.expires_at,
+            i.accepted_at, i.accepted_by, i.revoked_at, i.revoked_by, i.created_at, i.created_by
+        FROM tenant_invitations i
+        JOIN roles r ON r.id = i.role_id
+        WHERE i.tenant_id = $1
+          AND i.accepted_at IS NULL AND i.revoked_at IS NULL AND i.expires_at > NOW()
+        ORDER BY i.created_at DESC
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(invitations)
+}
+
+/// Revokes a pending invitation; its token stops working.
+pub async fn revoke_invitation(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<TenantInvitation, AppError> {
+    info!(
+        "Service: Revoking invitation ID: {} for tenant ID: {}",
+        invitation_id, tenant_id
+    );
+
+    permission::require_permission(pool, tenant_id, user_id, permission::MEMBERS_INVITE).await?;
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE tenant_invitations
+        SET revoked_at = NOW(), revoked_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND accepted_at IS NULL AND revoked_at IS NULL
+        "#,
+        invitation_id,
+        tenant_id,
+        user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let invitation = find_invitation(pool, tenant_id, invitation_id).await?;
+    if affected_rows == 0 {
+        let state = if invitation.accepted_at.is_some() {
+            "accepted"
+        } else {
+            "revoked"
+        };
+        return Err(AppError::Conflict(format!(
+            "Invitation {} has already been {}",
+            invitation_id, state
+        )));
+    }
+
+    Ok(invitation)
+}
+
+/// Accepts an invitation by its token: links the account with the invited email, or creates
+/// one from `dto`, and grants the invited role, all in one transaction.
+pub async fn accept_invitation(
+    pool: &PgPool,
+    dto: AcceptInvitationDto,
+) -> Result<AcceptedInvitation, AppError> {
+    info!("Service: Accepting tenant invitation");
+
+    let mut db_tx = pool.begin().await?;
+    let invitation = sqlx::query!(
+        r#"
+        SELECT
+            i.id, i.tenant_id, i.email, i.role_id, i.expires_at, i.accepted_at, i.revoked_at,
+            i.created_by, t.is_active as tenant_is_active, r.name as role_name
+        FROM tenant_invitations i
+        JOIN tenants t ON t.id = i.tenant_id
+        JOIN roles r ON r.id = i.role_id
+        WHERE i.token_hash = $1
+        FOR UPDATE OF i
+        "#,
+        sha256_hex(dto.token.as_bytes())
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Invitation not found".to_string()))?;
+
+    if invitation.accepted_at.is_some() {
+        return Err(AppError::Gone(
+            "This invitation has already been accepted".to_string(),
+        ));
+    }
+    if invitation.revoked_at.is_some() || !invitation.tenant_is_active {
+        return Err(AppError::Gone(
+            "This invitation has been revoked".to_string(),
+        ));
+    }
+    if invitation.expires_at <= Utc::now() {
+        return Err(AppError::Gone("This invitation has expired".to_string()));
+    }
+
+    let existing = sqlx::query!(
+        "SELECT id, is_active FROM users WHERE LOWER(email) = LOWER($1) FOR UPDATE",
+        invitation.email
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?;
+
+    let (user_id, created_user) = match existing {
+        Some(user) if !user.is_active => {
+            return Err(AppError::Forbidden("This account is disabled".to_string()));
+        }
+        Some(user) => (user.id, false),
+        None => {
+            let (Some(first_name), Some(last_name), Some(password)) =
+                (dto.first_name, dto.last_name, dto.password)
+            else {
+                return Err(AppError::Validation(
+                    "first_name, last_name and password are required to create an account"
+                        .to_string(),
+                ));
+            };
+            let password_hash = user_service::hash_password(&password)?;
+            let user_id = sqlx::query_scalar!(
+                r#"
+                INSERT INTO users (auth_provider_id, auth_provider_type, email, password_hash, first_name, last_name)
+                VALUES (LOWER($1), $2, $1, $3, $4, $5)
+                RETURNING id
+                "#,
+                invitation.email,
+                PASSWORD_PROVIDER,
+                password_hash,
+                first_name.trim(),
+                last_name.trim()
+            )
+            .fetch_one(&mut *db_tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db) if db.is_unique_violation() => AppError::Conflict(
+                    format!("An account with {} already exists", invitation.email),
+                ),
+                e => e.into(),
+            })?;
+            (user_id, true)
+        }
+    };
+
+    let granted = sqlx::query!(
+        r#"
+        INSERT INTO user_tenant_roles (user_id, tenant_id, role_id, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $4)
+        ON CONFLICT (user_id, tenant_id, role_id) DO NOTHING
+        "#,
+        user_id,
+        invitation.tenant_id,
+        invitation.role_id,
+        invitation.created_by
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    sqlx::query!(
+        "UPDATE tenant_invitations SET accepted_at = NOW(), accepted_by = $2 WHERE id = $1",
+        invitation.id,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    info!(
+        "Service: User ID: {} joined tenant ID: {} by invitation ID: {}",
+        user_id, invitation.tenant_id, invitation.id
+    );
+
+    if granted {
+        security_webhook::emit_security_event(
+            pool,
+            invitation.tenant_id,
+            SecurityEventType::MemberAdded,
+            Some(invitation.created_by),
+            json!({
+                "user_id": user_id,
+                "email": invitation.email,
+                "role": invitation.role_name,
+            }),
+        )
+        .await;
+    }
+
+    Ok(AcceptedInvitation {
+        tenant_id: invitation.tenant_id,
+        user_id,
+        role_id: invitation.role_id,
+        created_user,
+    })
+}
+
+async fn find_invitation<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<TenantInvitation, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    sqlx::query_as!(
+        TenantInvitation,
+        r#"
+        SELECT
+            i.id, i.tenant_id, i.email, i.role_id, r.name as role_name, i.expires_at,
+            i.accepted_at, i.accepted_by, i.revoked_at, i.revoked_by, i.created_at, i.created_by
+        FROM tenant_invitations i
+        JOIN roles r ON r.id = i.role_id
+        WHERE i.id = $1 AND i.tenant_id = $2
+        "#,
+        invitation_id,
+        tenant_id
+    )
+    .fetch_optional(executor)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Invitation with ID {} not found", invitation_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::{accept_invitation, create_invitation};
+    use crate::{
+        config,
+        models::dto::{
+            tenant_dto::CreateTenantDto,
+            tenant_invitation_dto::{AcceptInvitationDto, CreateTenantInvitationDto},
+        },
+        services::{permission, tenant},
+        utils::crypto::sha256_hex,
+    };
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn tenant_creator_can_invite_and_the_invitee_gains_the_role() {
+        config::init().expect("load config");
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPool::connect(&url)
+            .await
+            .expect("connect to DATABASE_URL");
+        let run = Uuid::new_v4();
+
+        let creator_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+             VALUES ($1, 'EMAIL_PASSWORD', $1, 'Tenant', 'Creator') RETURNING id",
+        )
+        .bind(format!("inviter-{}@example.com", run))
+        .fetch_one(&pool)
+        .await
+        .expect("insert user");
+        sqlx::query(
+            "INSERT INTO currencies (code, name, symbol, created_by, updated_by)
+             VALUES ('USD', 'US Dollar', '$', $1, $1) ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(creator_id)
+        .execute(&pool)
+        .await
+        .expect("insert currency");
+        let tenant = tenant::create_tenant(
+            &pool,
+            creator_id,
+            CreateTenantDto {
+                name: format!("Invitations {}", run),
+                industry: None,
+                base_currency_code: "USD".to_string(),
+                fiscal_year_end_month: 12,
+            },
+        )
+        .await
+        .expect("create tenant");
+        let admin_role_id: Uuid = sqlx::query_scalar("SELECT id FROM roles WHERE name = $1")
+            .bind(permission::ADMIN_ROLE)
+            .fetch_one(&pool)
+            .await
+            .expect("find admin role");
+
+        let invitation = create_invitation(
+            &pool,
+            tenant.id,
+            creator_id,
+            CreateTenantInvitationDto {
+                email: format!("invitee-{}@example.com", run),
+                role_id: admin_role_id,
+            },
+        )
+        .await
+        .expect("invite");
+
+        // The token only travels by email; swap in one the test knows
+        let token = format!("test-token-{}", run);
+        sqlx::query("UPDATE tenant_invitations SET token_hash = $2 WHERE id = $1")
+            .bind(invitation.id)
+            .bind(sha256_hex(token.as_bytes()))
+            .execute(&pool)
+            .await
+            .expect("set token");
+
+        let accepted = accept_invitation(
+            &pool,
+            AcceptInvitationDto {
+                token,
+                first_name: Some("New".to_string()),
+                last_name: Some("Member".to_string()),
+                password: Some("correct horse battery".to_string()),
+            },
+        )
+        .await
+        .expect("accept");
+        assert!(accepted.created_user);
+        assert_eq!(accepted.tenant_id, tenant.id);
+
+        let granted = permission::user_has_permission(
+            &pool,
+            tenant.id,
+            accepted.user_id,
+            permission::MEMBERS_INVITE,
+        )
+        .await
+        .expect("check permission");
+        assert!(granted);
+    }
+}
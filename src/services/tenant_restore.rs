@@ -0,0 +1,540 @@
+//! Restoring a tenant backup (see `tenant_backup`) into an empty tenant.
+//!
+//! Every record gets a new ID, so a backup can be restored next to the tenant it was taken
+//! from. References between restored records follow, including the ones held in JSON columns
+//! (journal templates, report definitions); the source tenant becomes the target tenant.
+//! People who are not members of the target tenant are replaced by the importing user in
+//! `created_by`-style columns. References to tenant records a backup does not carry (import
+//! jobs, bank connections) are cleared.
+//!
+//! Files are restored in dependency order, each in its own savepoint: a file that cannot be
+//! restored is reported and the rest carry on, so the caller sees per file what was restored.
+//! The tenant's own settings are not touched, and text sealed by privacy mode stays readable
+//! only in the tenant it was sealed in.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Cursor, Read},
+};
+
+use serde_json::{Map, Value as JsonValue};
+use sqlx::{Acquire, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::dto::tenant_backup_dto::{EntityImportResult, TenantImportResult},
+    services::{
+        balance_snapshot, permission,
+        reference_cache::{self, ReferenceData},
+        tenant_backup::{BACKUP_FORMAT_VERSION, BACKUP_TABLES},
+    },
+    utils::crypto,
+};
+
+/// Largest archive accepted for a restore.
+pub const MAX_ARCHIVE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Largest total size of the files in an archive, against ZIP bombs.
+const MAX_UNPACKED_BYTES: usize = 1024 * 1024 * 1024;
+
+/// Header carrying the passphrase of an encrypted backup.
+pub const PASSPHRASE_HEADER: &str = "x-backup-passphrase";
+
+/// Rows sent to the database per insert.
+const INSERT_BATCH_SIZE: usize = 1000;
+
+/// Files restored, in an order where records come after the ones they reference. References
+/// the other way round are set once every file is in (see `deferred_columns`).
+const IMPORT_ORDER: &[&str] = &[
+    "accounts",
+    "categories",
+    "tags",
+    "payees",
+    "dimensions",
+    "exchange_rates",
+    "fiscal_periods",
+    "business_holidays",
+    "statement_layouts",
+    "custom_reports",
+    "merchant_rules",
+    "budgets",
+    "budget_line_items",
+    "envelope_moves",
+    "recurring_transactions",
+    "recurring_transaction_skips",
+    "transactions",
+    "journal_entries",
+    "transaction_splits",
+    "transaction_metadata",
+    "transaction_attributions",
+    "reimbursable_expenses",
+    "reimbursement_matches",
+    "events",
+    "event_transactions",
+    "customers",
+    "vendors",
+    "payments",
+    "invoice_sequences",
+    "invoices",
+    "invoice_lines",
+    "invoice_payments",
+    "bills",
+    "bill_lines",
+    "bill_payments",
+    "fixed_assets",
+    "fixed_asset_depreciations",
+];
+
+/// A foreign key column of a restored table.
+struct ForeignKey {
+    table: String,
+    column: String,
+    referenced_table: String,
+}
+
+/// Restores a backup into the tenant, which must hold no records yet. An encrypted backup
+/// needs its passphrase.
+pub async fn import_tenant_backup(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    archive: Vec<u8>,
+    passphrase: Option<String>,
+) -> Result<TenantImportResult, AppError> {
+    info!("Service: Importing backup into tenant ID: {}", tenant_id);
+
+    permission::require_permission(pool, tenant_id, user_id, permission::TENANT_RESTORE).await?;
+
+    // Key derivation and decompression are CPU-bound
+    let files = tokio::task::spawn_blocking(move || {
+        let archive = if crypto::is_passphrase_encrypted(&archive) {
+            let passphrase = passphrase.ok_or_else(|| {
+                AppError::Validation(format!(
+                    "The backup is encrypted; send its passphrase in {}",
+                    PASSPHRASE_HEADER
+                ))
+            })?;
+            crypto::decrypt_with_passphrase(&passphrase, &archive)?
+        } else {
+            archive
+        };
+        read_zip(&archive, MAX_UNPACKED_BYTES)
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Import task failed: {}", e)))??;
+
+    let mut files: HashMap<String, JsonValue> = files
+        .into_iter()
+        .map(|(name, data)| {
+            let value = serde_json::from_slice(&data).map_err(|e| {
+                AppError::Validation(format!("Not a valid backup archive: {}: {}", name, e))
+            })?;
+            Ok((name, value))
+        })
+        .collect::<Result<_, AppError>>()?;
+    let manifest = files.remove("manifest.json").ok_or_else(|| {
+        AppError::Validation("Not a valid backup archive: no manifest.json".to_string())
+    })?;
+    if manifest["format_version"].as_u64() != Some(BACKUP_FORMAT_VERSION as u64) {
+        return Err(AppError::Validation(format!(
+            "Unsupported backup format version {}; expected {}",
+            manifest["format_version"], BACKUP_FORMAT_VERSION
+        )));
+    }
+    let source_tenant_id = manifest["tenant_id"]
+        .as_str()
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(|| {
+            AppError::Validation(
+                "Not a valid backup archive: no tenant_id in manifest.json".to_string(),
+            )
+        })?;
+    let anonymized = manifest["anonymized"].as_bool().unwrap_or(false);
+
+    let mut db_tx = pool.begin().await?;
+    // Two restores into the same tenant must not both pass the emptiness check
+    sqlx::query("SELECT id FROM tenants WHERE id = $1 FOR UPDATE")
+        .bind(tenant_id)
+        .fetch_optional(&mut *db_tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+    ensure_empty(&mut db_tx, tenant_id).await?;
+
+    let members: HashSet<Uuid> =
+        sqlx::query_scalar("SELECT DISTINCT user_id FROM user_tenant_roles WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .fetch_all(&mut *db_tx)
+            .await?
+            .into_iter()
+            .collect();
+    let foreign_keys = load_foreign_keys(&mut db_tx).await?;
+    let columns = load_columns(&mut db_tx).await?;
+
+    let mut tables: Vec<(&str, Vec<JsonValue>)> = IMPORT_ORDER
+        .iter()
+        .map(|&table| {
+            let rows = match files.remove(&format!("{}.json", table)) {
+                Some(JsonValue::Array(rows)) => rows,
+                Some(_) => {
+                    return Err(AppError::Validation(format!(
+                        "Not a valid backup archive: {}.json is not a list of rows",
+                        table
+                    )))
+                }
+                None => Vec::new(),
+            };
+            Ok((table, rows))
+        })
+        .collect::<Result<_, AppError>>()?;
+
+    let mut ids = HashMap::from([(source_tenant_id, tenant_id)]);
+    for (_, rows) in &tables {
+        for row in rows {
+            if let Some(id) = row["id"].as_str().and_then(|id| Uuid::parse_str(id).ok()) {
+                ids.insert(id, Uuid::new_v4());
+            }
+        }
+    }
+    let user_columns: HashSet<(&str, &str)> = foreign_keys
+        .iter()
+        .filter(|fk| fk.referenced_table == "users")
+        .map(|fk| (fk.table.as_str(), fk.column.as_str()))
+        .collect();
+    for (table, rows) in &mut tables {
+        for row in rows.iter_mut() {
+            remap(row, &ids);
+            let Some(fields) = row.as_object_mut() else {
+                continue;
+            };
+            for (column, value) in fields.iter_mut() {
+                if !user_columns.contains(&(*table, column.as_str())) {
+                    continue;
+                }
+                let is_member = value
+                    .as_str()
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                    .is_some_and(|id| members.contains(&id));
+                if !value.is_null() && !is_member {
+                    *value = JsonValue::String(user_id.to_string());
+                }
+            }
+        }
+    }
+
+    let tenant_tables = tenant_scoped_tables(&columns);
+    let restored: HashSet<Uuid> = ids.values().copied().collect();
+    let mut entities = Vec::with_capacity(tables.len());
+    let mut deferred_updates = Vec::new();
+    for (position, (table, rows)) in tables.iter_mut().enumerate() {
+        let mut result = EntityImportResult {
+            entity: table.to_string(),
+            rows: rows.len(),
+            imported: 0,
+            error: None,
+        };
+        if rows.is_empty() {
+            entities.push(result);
+            continue;
+        }
+        let table_columns = columns.get(*table).map(Vec::as_slice).unwrap_or_default();
+        let deferred = deferred_columns(table, position, &foreign_keys);
+        clear_missing_references(table, rows, &foreign_keys, &tenant_tables, &restored);
+        let mut savepoint = db_tx.begin().await?;
+        match insert_rows(&mut savepoint, table, table_columns, &deferred, rows).await {
+            Ok(imported) => {
+                savepoint.commit().await?;
+                result.imported = imported;
+                if !deferred.is_empty() {
+                    deferred_updates.push((position, deferred));
+                }
+            }
+            Err(e) => {
+                savepoint.rollback().await?;
+                result.error = Some(failure_message(table, e));
+            }
+        }
+        entities.push(result);
+    }
+
+    for (position, deferred) in deferred_updates {
+        let (table, rows) = &tables[position];
+        let mut savepoint = db_tx.begin().await?;
+        match update_deferred(&mut savepoint, table, &deferred, rows).await {
+            Ok(()) => savepoint.commit().await?,
+            Err(e) => {
+                savepoint.rollback().await?;
+                let entity = &mut entities[position];
+                entity.error = Some(failure_message(table, e));
+            }
+        }
+    }
+
+    // Snapshots are not part of backups; they are derived from the restored journal
+    balance_snapshot::rebuild_tenant(&mut *db_tx, tenant_id).await?;
+
+    db_tx.commit().await?;
+    if entities
+        .iter()
+        .any(|e| e.entity == "exchange_rates" && e.imported > 0)
+    {
+        reference_cache::changed(pool, ReferenceData::ExchangeRates).await;
+    }
+
+    Ok(TenantImportResult {
+        source_tenant_id,
+        anonymized,
+        entities,
+    })
+}
+
+/// Reads every file of a ZIP archive, in central directory order. Fails with `Validation`
+/// on anything that is not a readable archive, or once the files together would exceed
+/// `max_size` bytes.
+fn read_zip(data: &[u8], max_size: usize) -> Result<Vec<(String, Vec<u8>)>, AppError> {
+    let invalid = |e: zip::result::ZipError| {
+        AppError::Validation(format!("Not a valid backup archive: {}", e))
+    };
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(invalid)?;
+
+    let mut files = Vec::with_capacity(archive.len());
+    let mut remaining = max_size as u64;
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(invalid)?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().to_string();
+        // Declared sizes are not trusted: at most one byte past the limit is inflated
+        let mut contents = Vec::new();
+        (&mut file)
+            .take(remaining + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| invalid(zip::result::ZipError::Io(e)))?;
+        if contents.len() as u64 > remaining {
+            return Err(AppError::Validation(format!(
+                "Archive is larger than {} bytes unpacked",
+                max_size
+            )));
+        }
+        remaining -= contents.len() as u64;
+        files.push((name, contents));
+    }
+
+    Ok(files)
+}
+
+/// Fails with `Conflict` when the tenant already holds records in any backed-up table.
+async fn ensure_empty(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+) -> Result<(), AppError> {
+    for (name, sql) in BACKUP_TABLES.iter().filter(|(name, _)| *name != "tenant") {
+        let has_rows: bool = sqlx::query_scalar(&format!("SELECT EXISTS({})", sql))
+            .bind(tenant_id)
+            .fetch_one(&mut **db_tx)
+            .await?;
+        if has_rows {
+            return Err(AppError::Conflict(format!(
+                "Backups can only be restored into an empty tenant; this one already has {}",
+                name.replace('_', " ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Loads the single-column foreign keys of the restored tables.
+async fn load_foreign_keys(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+) -> Result<Vec<ForeignKey>, AppError> {
+    let rows: Vec<(String, String, String)> = sqlx::query_as(
+        r#"
+        SELECT c.conrelid::regclass::text, a.attname::text, c.confrelid::regclass::text
+        FROM pg_constraint c
+        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = c.conkey[1]
+        WHERE c.contype = 'f' AND cardinality(c.conkey) = 1
+            AND c.conrelid::regclass::text = ANY($1)
+        "#,
+    )
+    .bind(IMPORT_ORDER)
+    .fetch_all(&mut **db_tx)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(table, column, referenced_table)| ForeignKey {
+            table,
+            column,
+            referenced_table,
+        })
+        .collect())
+}
+
+/// Loads the writable columns of every table, by table.
+async fn load_columns(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+) -> Result<HashMap<String, Vec<String>>, AppError> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT table_name::text, column_name::text
+        FROM information_schema.columns
+        WHERE table_schema = current_schema() AND is_generated = 'NEVER'
+        ORDER BY table_name, ordinal_position
+        "#,
+    )
+    .fetch_all(&mut **db_tx)
+    .await?;
+
+    let mut columns: HashMap<String, Vec<String>> = HashMap::new();
+    for (table, column) in rows {
+        columns.entry(table).or_default().push(column);
+    }
+    Ok(columns)
+}
+
+/// Tables holding per-tenant records, as opposed to shared ones such as currencies.
+fn tenant_scoped_tables(columns: &HashMap<String, Vec<String>>) -> HashSet<&str> {
+    columns
+        .iter()
+        .filter(|(_, table_columns)| table_columns.iter().any(|c| c == "tenant_id"))
+        .map(|(table, _)| table.as_str())
+        .collect()
+}
+
+/// Columns of the table referencing itself or a table restored after it; they are inserted
+/// empty and set once every file is in.
+fn deferred_columns(table: &str, position: usize, foreign_keys: &[ForeignKey]) -> Vec<String> {
+    foreign_keys
+        .iter()
+        .filter(|fk| fk.table == table)
+        .filter(|fk| {
+            IMPORT_ORDER
+                .iter()
+                .position(|&t| t == fk.referenced_table)
+                .is_some_and(|referenced| referenced >= position)
+        })
+        .map(|fk| fk.column.clone())
+        .collect()
+}
+
+/// Clears references to per-tenant records that are not part of the backup.
+fn clear_missing_references(
+    table: &str,
+    rows: &mut [JsonValue],
+    foreign_keys: &[ForeignKey],
+    tenant_tables: &HashSet<&str>,
+    restored: &HashSet<Uuid>,
+) {
+    let columns: Vec<&str> = foreign_keys
+        .iter()
+        .filter(|fk| fk.table == table && fk.referenced_table != "tenants")
+        .filter(|fk| tenant_tables.contains(fk.referenced_table.as_str()))
+        .map(|fk| fk.column.as_str())
+        .collect();
+    for row in rows {
+        for column in &columns {
+            let is_restored = row[*column]
+                .as_str()
+                .and_then(|id| Uuid::parse_str(id).ok())
+                .is_some_and(|id| restored.contains(&id));
+            if !is_restored {
+                if let Some(fields) = row.as_object_mut() {
+                    fields.insert(column.to_string(), JsonValue::Null);
+                }
+            }
+        }
+    }
+}
+
+/// Inserts the rows, leaving the deferred columns empty. Returns how many were inserted.
+async fn insert_rows(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    table: &str,
+    table_columns: &[String],
+    deferred: &[String],
+    rows: &[JsonValue],
+) -> Result<usize, AppError> {
+    // Only columns both in the backup and in this schema, so older backups restore too
+    let insert_columns: Vec<String> = table_columns
+        .iter()
+        .filter(|column| !deferred.contains(column))
+        .filter(|column| rows[0].get(column.as_str()).is_some())
+        .map(|column| format!("\"{}\"", column))
+        .collect();
+    let columns = insert_columns.join(", ");
+    let sql = format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM jsonb_populate_recordset(NULL::{table}, $1)"
+    );
+    let mut imported = 0;
+    for chunk in rows.chunks(INSERT_BATCH_SIZE) {
+        imported += sqlx::query(&sql)
+            .bind(JsonValue::Array(chunk.to_vec()))
+            .execute(&mut **db_tx)
+            .await?
+            .rows_affected() as usize;
+    }
+    Ok(imported)
+}
+
+/// Sets the deferred columns of inserted rows.
+async fn update_deferred(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    table: &str,
+    deferred: &[String],
+    rows: &[JsonValue],
+) -> Result<(), AppError> {
+    let assignments: Vec<String> = deferred
+        .iter()
+        .map(|column| format!("\"{column}\" = r.\"{column}\""))
+        .collect();
+    let sql = format!(
+        "UPDATE {table} t SET {} FROM jsonb_populate_recordset(NULL::{table}, $1) r WHERE t.id = r.id",
+        assignments.join(", ")
+    );
+    for chunk in rows.chunks(INSERT_BATCH_SIZE) {
+        // Only the key and the deferred columns, so nothing else is sent twice
+        let chunk: Vec<JsonValue> = chunk
+            .iter()
+            .map(|row| {
+                let fields: Map<String, JsonValue> = std::iter::once("id")
+                    .chain(deferred.iter().map(String::as_str))
+                    .map(|column| (column.to_string(), row[column].clone()))
+                    .collect();
+                JsonValue::Object(fields)
+            })
+            .collect();
+        sqlx::query(&sql)
+            .bind(JsonValue::Array(chunk))
+            .execute(&mut **db_tx)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Rewrites the IDs of restored records (and of the source tenant) in the value, in place.
+fn remap(value: &mut JsonValue, ids: &HashMap<Uuid, Uuid>) {
+    match value {
+        JsonValue::String(text) if text.len() == 36 => {
+            if let Some(new_id) = Uuid::parse_str(text).ok().and_then(|id| ids.get(&id)) {
+                *text = new_id.to_string();
+            }
+        }
+        JsonValue::Array(items) => items.iter_mut().for_each(|item| remap(item, ids)),
+        JsonValue::Object(fields) => fields.values_mut().for_each(|field| remap(field, ids)),
+        _ => {}
+    }
+}
+
+/// Why a file could not be restored, for the caller. Like the HTTP responses, internal
+/// details stay in the log.
+fn failure_message(table: &str, error: AppError) -> String {
+    warn!("Import of {} failed: {}", table, error);
+    match error {
+        AppError::DatabaseError(_) | AppError::InternalServerError(_) => {
+            "The rows could not be restored".to_string()
+        }
+        other => other.to_string(),
+    }
+}
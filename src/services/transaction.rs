@@ -1,36 +1,94 @@
+use std::collections::{HashMap, HashSet};
+
 use sqlx::{query_as, PgPool, Postgres, Transaction as DbTransaction};
 use uuid::Uuid;
 use tracing::info;
 use chrono::NaiveDate;
 use rust_decimal::Decimal;
 use serde_json::Value as JsonValue;
+use validator::Validate;
 
 use crate::{
+    db::{self, begin_financial, with_retry},
     error::AppError,
     models::{
-        transaction::{Transaction, TransactionType},
+        dimension::DimensionFilter,
+        transaction::{Transaction, TransactionStatus, TransactionType},
+        transaction_metadata::TransactionMetadata,
         journal_entry::{JournalEntry, JournalEntryType}, // Assuming JournalEntry and its DTOs are defined
-        dto::transaction_dto::{CreateTransactionDto, UpdateTransactionDto},
+        dto::transaction_dto::{
+            CreateTransactionDto, ListTransactionsQuery, ReverseTransactionDto, UpdateTransactionDto,
+            VoidTransactionDto,
+        },
         dto::journal_entry_dto::{CreateJournalEntryDto}, // Assuming CreateJournalEntryDto is defined
     },
+    services::{
+        balance_snapshot::{self, SnapshotChange},
+        category, currency_conversion,
+        domain_event::{self, DomainEvent},
+        dimension, fiscal_period, household, payee,
+        permission::{self, TX_APPROVE},
+        privacy, transaction_split,
+    },
+    utils::update_builder::UpdateBuilder,
 };
 
+/// Default search radius of the `near_lat`/`near_lon` listing filter.
+const DEFAULT_RADIUS_KM: f64 = 25.0;
+
 /// Retrieves a list of transactions for a specific tenant.
-pub async fn list_transactions(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<Transaction>, AppError> {
+/// Location and device filters use the client metadata captured at entry, so transactions
+/// entered without metadata only match when no such filter is given.
+pub async fn list_transactions(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: ListTransactionsQuery,
+) -> Result<Vec<Transaction>, AppError> {
     info!("Service: Listing transactions for tenant ID: {}", tenant_id);
 
+    let radius_km = check_list_query(&query)?;
+
     let transactions = query_as!(
         Transaction,
         r#"
         SELECT
-            id, tenant_id, transaction_date, description, type as "r#type!: TransactionType",
-            category_id, tags_json, amount, currency_code, is_reconciled, reconciliation_date,
-            notes, source_document_url, created_at, created_by, updated_at, updated_by
-        FROM transactions
-        WHERE tenant_id = $1
-        ORDER BY transaction_date DESC, created_at DESC
+            t.id, t.tenant_id, t.transaction_date, t.description, t.type as "r#type!: TransactionType",
+            t.category_id, t.payee_id, t.tags_json, t.amount, t.currency_code, t.is_reconciled, t.reconciliation_date,
+            t.notes, t.source_document_url, t.reversal_of_id, t.reversed_by_id,
+            t.recurring_transaction_id, t.recurring_occurrence_date,
+            t.status, t.posted_at, t.posted_by, t.voided_at, t.voided_by, t.void_reason,
+            t.created_at, t.created_by, t.updated_at, t.updated_by, t.version
+        FROM transactions t
+        LEFT JOIN transaction_metadata m ON m.transaction_id = t.id
+        WHERE t.tenant_id = $1
+          AND ($2::date IS NULL OR t.transaction_date >= $2)
+          AND ($3::date IS NULL OR t.transaction_date <= $3)
+          AND ($4::text IS NULL OR m.entry_source = UPPER($4))
+          AND ($5::text IS NULL OR m.device ILIKE '%' || $5 || '%')
+          AND ($6::float8 IS NULL OR (
+              m.latitude IS NOT NULL
+              -- Haversine distance in km on a 6371 km sphere
+              AND 2 * 6371 * ASIN(SQRT(
+                  POWER(SIN(RADIANS(m.latitude - $6) / 2), 2)
+                  + COS(RADIANS($6)) * COS(RADIANS(m.latitude)) * POWER(SIN(RADIANS(m.longitude - $7) / 2), 2)
+              )) <= $8
+          ))
+          AND ($9::uuid IS NULL OR EXISTS (
+              SELECT 1 FROM event_transactions et WHERE et.transaction_id = t.id AND et.event_id = $9
+          ))
+          AND ($10::uuid IS NULL OR t.payee_id = $10)
+        ORDER BY t.transaction_date DESC, t.created_at DESC
         "#,
-        tenant_id
+        tenant_id,
+        query.from_date,
+        query.to_date,
+        query.entry_source,
+        query.device,
+        query.near_lat,
+        query.near_lon,
+        radius_km,
+        query.event_id,
+        query.payee_id
     )
     .fetch_all(pool)
     .await?;
@@ -38,6 +96,41 @@ pub async fn list_transactions(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<Tra
     Ok(transactions)
 }
 
+/// Validates the listing filters, returning the search radius of the location filter.
+/// Shared with the CSV export, which takes the same filters.
+pub fn check_list_query(query: &ListTransactionsQuery) -> Result<f64, AppError> {
+    query.validate()?;
+    if query.near_lat.is_some() != query.near_lon.is_some() {
+        return Err(AppError::Validation("near_lat and near_lon must be given together".to_string()));
+    }
+    Ok(query.radius_km.unwrap_or(DEFAULT_RADIUS_KM))
+}
+
+/// Retrieves the client metadata captured when a transaction was entered.
+pub async fn get_transaction_metadata(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+) -> Result<TransactionMetadata, AppError> {
+    info!("Service: Getting metadata of transaction ID: {} for tenant ID: {}", transaction_id, tenant_id);
+
+    let metadata = query_as!(
+        TransactionMetadata,
+        r#"
+        SELECT transaction_id, tenant_id, latitude, longitude, device, entry_source, created_at
+        FROM transaction_metadata
+        WHERE transaction_id = $1 AND tenant_id = $2
+        "#,
+        transaction_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No metadata recorded for transaction {}", transaction_id)))?;
+
+    Ok(metadata)
+}
+
 /// Retrieves a single transaction by ID for a specific tenant.
 pub async fn get_transaction_by_id(
     pool: &PgPool,
@@ -51,8 +144,11 @@ pub async fn get_transaction_by_id(
         r#"
         SELECT
             id, tenant_id, transaction_date, description, type as "r#type!: TransactionType",
-            category_id, tags_json, amount, currency_code, is_reconciled, reconciliation_date,
-            notes, source_document_url, created_at, created_by, updated_at, updated_by
+            category_id, payee_id, tags_json, amount, currency_code, is_reconciled, reconciliation_date,
+            notes, source_document_url, reversal_of_id, reversed_by_id,
+            recurring_transaction_id, recurring_occurrence_date,
+            status, posted_at, posted_by, voided_at, voided_by, void_reason,
+            created_at, created_by, updated_at, updated_by, version
         FROM transactions
         WHERE id = $1 AND tenant_id = $2
         "#,
@@ -76,12 +172,148 @@ pub async fn create_transaction(
 ) -> Result<Transaction, AppError> {
     info!("Service: Creating new transaction for tenant ID {}", tenant_id);
 
-    // Start a database transaction
     let mut db_tx = pool.begin().await?;
+    let (new_transaction, _) = insert_transaction(&mut db_tx, tenant_id, created_by_user_id, dto).await?;
+    db_tx.commit().await?;
+
+    Ok(new_transaction)
+}
+
+/// Inserts a transaction with its journal entries, metadata and attribution inside the
+/// caller's database transaction. Returns the new journal entry IDs in the order given.
+pub async fn insert_transaction(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    mut dto: CreateTransactionDto,
+) -> Result<(Transaction, Vec<Uuid>), AppError> {
+    // Splits are booked as journal entries, then recorded against them below
+    if !dto.splits.is_empty() {
+        transaction_split::prepare_split_entries(&mut **db_tx, tenant_id, &mut dto).await?;
+    }
+    let splits = std::mem::take(&mut dto.splits);
+
+    let status = dto.status.unwrap_or(TransactionStatus::Draft);
+    if status == TransactionStatus::Voided {
+        return Err(AppError::Validation("A transaction cannot be created as VOIDED".to_string()));
+    }
+    if status == TransactionStatus::Posted && dto.journal_entries.is_empty() {
+        return Err(AppError::Validation("A posted transaction needs journal entries".to_string()));
+    }
+    if let Some(metadata) = &dto.metadata {
+        if metadata.latitude.is_some() != metadata.longitude.is_some() {
+            return Err(AppError::Validation("latitude and longitude must be given together".to_string()));
+        }
+    }
+
+    // Every check against the database runs in one round trip, before anything is written
+    let account_ids: Vec<Uuid> = dto.journal_entries.iter().map(|entry| entry.account_id).collect();
+    let checks = load_posting_checks(
+        db_tx,
+        tenant_id,
+        created_by_user_id,
+        dto.transaction_date,
+        dto.category_id,
+        dto.payee_id,
+        &account_ids,
+    )
+    .await?;
+    // Transactions cannot be booked into a closed period
+    if let Some(period_name) = &checks.locked_period {
+        return Err(fiscal_period::closed_period_error(period_name, dto.transaction_date));
+    }
+    if let Some(category_id) = dto.category_id.filter(|_| !checks.category_assignable) {
+        return Err(AppError::Validation(format!(
+            "Category ID {} is invalid, inactive or archived for tenant {}",
+            category_id, tenant_id
+        )));
+    }
+    if let Some(invalid) = account_ids.iter().find(|id| !checks.valid_accounts.contains(id)) {
+        return Err(AppError::Validation(format!("Account ID {} is invalid, inactive or archived for tenant {}", invalid, tenant_id)));
+    }
+    if let Some(payee_id) = dto.payee_id.filter(|_| !checks.payee_valid) {
+        return Err(AppError::Validation(format!("Payee ID {} not found for tenant {}", payee_id, tenant_id)));
+    }
+    // A payee's merchant rule categorizes transactions entered without a category
+    if dto.category_id.is_none() {
+        dto.category_id = checks.payee_category_id;
+    }
+    // With privacy mode on, the description and memos are stored sealed
+    let text_key = checks.text_key;
+    // Untagged entries (the common case) cost no extra round trip
+    let entry_dimensions: Vec<DimensionFilter> = dto
+        .journal_entries
+        .iter()
+        .map(|entry| DimensionFilter {
+            project_id: entry.project_id,
+            class_id: entry.class_id,
+            location_id: entry.location_id,
+        })
+        .collect();
+    dimension::check_assignable(&mut **db_tx, tenant_id, entry_dimensions.iter().copied()).await?;
+
+    // Foreign-currency legs are also stored in the tenant's base currency; rates are looked up
+    // once per foreign currency rather than once per entry
+    let mut base_rates: HashMap<String, Option<Decimal>> = HashMap::new();
+    for entry_dto in &dto.journal_entries {
+        let currency_code = entry_dto.currency_code.to_uppercase();
+        if entry_dto.exchange_rate.is_some()
+            || entry_dto.converted_amount.is_some()
+            || currency_code == checks.base_currency_code
+            || base_rates.contains_key(&currency_code)
+        {
+            continue;
+        }
+        let rate = currency_conversion::base_currency_rate(&mut **db_tx, tenant_id, &currency_code, dto.transaction_date).await?;
+        base_rates.insert(currency_code, rate);
+    }
+
+    let entry_count = dto.journal_entries.len();
+    let mut entry_types = Vec::with_capacity(entry_count);
+    let mut amounts = Vec::with_capacity(entry_count);
+    let mut currency_codes = Vec::with_capacity(entry_count);
+    let mut exchange_rates = Vec::with_capacity(entry_count);
+    let mut converted_amounts = Vec::with_capacity(entry_count);
+    let mut memos = Vec::with_capacity(entry_count);
+    let project_ids: Vec<Option<Uuid>> = entry_dimensions.iter().map(|d| d.project_id).collect();
+    let class_ids: Vec<Option<Uuid>> = entry_dimensions.iter().map(|d| d.class_id).collect();
+    let location_ids: Vec<Option<Uuid>> = entry_dimensions.iter().map(|d| d.location_id).collect();
+    let (mut debits, mut credits) = (Decimal::ZERO, Decimal::ZERO);
+    for entry_dto in dto.journal_entries {
+        let base_rate = base_rates.get(&entry_dto.currency_code.to_uppercase()).copied().flatten();
+        let (exchange_rate, converted_amount) = currency_conversion::amounts_with_rate(
+            entry_dto.amount,
+            entry_dto.exchange_rate,
+            entry_dto.converted_amount,
+            base_rate,
+        );
+        match entry_dto.entry_type {
+            JournalEntryType::Debit => debits += converted_amount.unwrap_or(entry_dto.amount),
+            JournalEntryType::Credit => credits += converted_amount.unwrap_or(entry_dto.amount),
+        }
+        entry_types.push(String::from(entry_dto.entry_type));
+        amounts.push(entry_dto.amount);
+        currency_codes.push(entry_dto.currency_code);
+        exchange_rates.push(exchange_rate);
+        converted_amounts.push(converted_amount);
+        memos.push(privacy::seal_opt(text_key.as_ref(), entry_dto.memo)?);
+    }
+    // The same rule as `ensure_balanced`, checked on the entries before they are written
+    if status == TransactionStatus::Posted {
+        if entry_count < 2 {
+            return Err(AppError::Validation("A posted transaction needs at least two journal entries".to_string()));
+        }
+        if debits != credits {
+            return Err(AppError::Validation(format!(
+                "Transaction is not balanced: debits {} != credits {}",
+                debits, credits
+            )));
+        }
+    }
 
     // --- 1. Create the main transaction record ---
     let tags_json: Option<JsonValue> = if let Some(tags) = dto.tags {
-        Some(serde_json::to_value(&tags).map_err(|e| AppError::InternalError(format!("Failed to serialize tags: {}", e)))?)
+        Some(serde_json::to_value(&tags).map_err(|e| AppError::InternalServerError(format!("Failed to serialize tags: {}", e)))?)
     } else {
         None
     };
@@ -92,18 +324,26 @@ pub async fn create_transaction(
         INSERT INTO transactions (
             tenant_id, transaction_date, description, type, category_id,
             tags_json, amount, currency_code, is_reconciled, reconciliation_date,
-            notes, source_document_url, created_by, updated_by
+            notes, source_document_url, status, posted_at, posted_by, created_by, updated_by, payee_id
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $14::text,
+            CASE WHEN $14::text = 'POSTED' THEN NOW() END,
+            CASE WHEN $14::text = 'POSTED' THEN $13::uuid END,
+            $13, $13, $15
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $13)
         RETURNING
-            id, tenant_id, transaction_date, description, type as "r#type!: TransactionType", category_id,
+            id, tenant_id, transaction_date, description, type as "r#type!: TransactionType", category_id, payee_id,
             tags_json, amount, currency_code, is_reconciled, reconciliation_date,
-            notes, source_document_url, created_at, created_by, updated_at, updated_by
+            notes, source_document_url, reversal_of_id, reversed_by_id,
+            recurring_transaction_id, recurring_occurrence_date,
+            status, posted_at, posted_by, voided_at, voided_by, void_reason,
+            created_at, created_by, updated_at, updated_by, version
         "#,
         tenant_id,
         dto.transaction_date,
-        dto.description,
-        dto.r#type as TransactionType, // Cast enum to string for DB
+        privacy::seal(text_key.as_ref(), dto.description)?,
+        dto.r#type as TransactionType,
         dto.category_id,
         tags_json,
         dto.amount,
@@ -113,174 +353,268 @@ pub async fn create_transaction(
         dto.notes,
         dto.source_document_url,
         created_by_user_id,
+        String::from(status),
+        dto.payee_id,
     )
-    .fetch_one(&mut *db_tx) // Use the database transaction
+    .fetch_one(&mut **db_tx) // Use the database transaction
     .await?;
 
     // --- 2. Create associated journal entries ---
-    // For simplicity, this example assumes journal entries are provided directly.
-    // In a real accounting system, for 'INCOME', 'EXPENSE', 'TRANSFER' types,
-    // the journal entries might be auto-generated based on the transaction type
-    // and the primary account involved, with only one side provided by the user.
-    // For 'JOURNAL_ENTRY' type, both sides would be explicitly provided.
-    // This boilerplate supports explicit provision for now.
-    for entry_dto in dto.journal_entries {
-        // Basic validation: Ensure account exists and is valid for tenant
-        let account_exists = sqlx::query!(
-            "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE)",
-            entry_dto.account_id, tenant_id
+    // One insert for all entries; IDs are generated here so they come back in request order
+    let journal_entry_ids: Vec<Uuid> = (0..entry_count).map(|_| Uuid::new_v4()).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO journal_entries (
+            id, transaction_id, account_id, entry_type, amount, currency_code,
+            exchange_rate, converted_amount, memo, project_id, class_id, location_id, created_by, updated_by
         )
-        .fetch_one(&mut *db_tx)
-        .await?
-        .exists
-        .unwrap_or(false);
-
-        if !account_exists {
-            db_tx.rollback().await?; // Rollback if any account is invalid
-            return Err(AppError::ValidationError(format!("Account ID {} is invalid or inactive for tenant {}", entry_dto.account_id, tenant_id)));
-        }
+        SELECT e.id, $1, e.account_id, e.entry_type::entry_side, e.amount, e.currency_code,
+               e.exchange_rate, e.converted_amount, e.memo, e.project_id, e.class_id, e.location_id, $2, $2
+        FROM UNNEST(
+            $3::uuid[], $4::uuid[], $5::text[], $6::numeric[], $7::text[], $8::numeric[], $9::numeric[], $10::text[],
+            $11::uuid[], $12::uuid[], $13::uuid[]
+        ) AS e(
+            id, account_id, entry_type, amount, currency_code, exchange_rate, converted_amount, memo,
+            project_id, class_id, location_id
+        )
+        "#,
+        new_transaction.id,
+        created_by_user_id,
+        &journal_entry_ids,
+        &account_ids,
+        &entry_types,
+        &amounts,
+        &currency_codes,
+        &exchange_rates as &[Option<Decimal>],
+        &converted_amounts as &[Option<Decimal>],
+        &memos as &[Option<String>],
+        &project_ids as &[Option<Uuid>],
+        &class_ids as &[Option<Uuid>],
+        &location_ids as &[Option<Uuid>],
+    )
+    .execute(&mut **db_tx) // Use the database transaction
+    .await?;
+    if !splits.is_empty() {
+        transaction_split::record_splits(&mut **db_tx, new_transaction.id, &splits, &journal_entry_ids).await?;
+    }
+    if status == TransactionStatus::Posted {
+        balance_snapshot::record_transaction(&mut **db_tx, new_transaction.id, SnapshotChange::Posted).await?;
+    }
 
+    // --- 3. Record the client metadata, if any ---
+    if let Some(metadata) = dto.metadata {
         sqlx::query!(
             r#"
-            INSERT INTO journal_entries (
-                transaction_id, account_id, entry_type, amount, currency_code,
-                exchange_rate, converted_amount, memo, created_by, updated_by
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            INSERT INTO transaction_metadata (transaction_id, tenant_id, latitude, longitude, device, entry_source)
+            VALUES ($1, $2, $3, $4, $5, UPPER($6))
             "#,
             new_transaction.id,
-            entry_dto.account_id,
-            entry_dto.entry_type as JournalEntryType, // Cast enum to string for DB
-            entry_dto.amount,
-            entry_dto.currency_code,
-            entry_dto.exchange_rate,
-            entry_dto.converted_amount,
-            entry_dto.memo,
-            created_by_user_id,
+            tenant_id,
+            metadata.latitude,
+            metadata.longitude,
+            metadata.device,
+            metadata.entry_source,
         )
-        .execute(&mut *db_tx) // Use the database transaction
+        .execute(&mut **db_tx)
         .await?;
     }
 
-    // --- 3. Commit the transaction ---
-    db_tx.commit().await?;
+    // --- 4. Attribute it to a household member, if asked ---
+    if let Some(attribution) = dto.attribution {
+        household::record_attribution(db_tx, tenant_id, new_transaction.id, created_by_user_id, attribution)
+            .await?;
+    }
 
-    Ok(new_transaction)
+    Ok((new_transaction, journal_entry_ids))
+}
+
+/// What `insert_transaction` checks against the database before writing.
+struct PostingChecks {
+    base_currency_code: String,
+    text_key: Option<privacy::TextKey>,
+    /// The closed fiscal period covering the date, unless the user may post into it.
+    locked_period: Option<String>,
+    /// True when no category is given.
+    category_assignable: bool,
+    /// The given accounts that are active and unarchived.
+    valid_accounts: HashSet<Uuid>,
+    /// True when no payee is given.
+    payee_valid: bool,
+    /// The category of the payee's merchant rule, when it can be assigned.
+    payee_category_id: Option<Uuid>,
+}
+
+/// Loads the tenant's base currency and sealing key, the closed period covering `date`, and
+/// whether the category, accounts and payee can be used, in one round trip instead of one query
+/// per check. Same rules as `fiscal_period::ensure_date_open` and `category::ensure_assignable`.
+async fn load_posting_checks(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    date: NaiveDate,
+    category_id: Option<Uuid>,
+    payee_id: Option<Uuid>,
+    account_ids: &[Uuid],
+) -> Result<PostingChecks, AppError> {
+    let row = sqlx::query!(
+        r#"
+        WITH closed_period AS (
+            SELECT fp.name
+            FROM fiscal_periods fp
+            WHERE fp.tenant_id = $1 AND fp.status = 'CLOSED' AND $3 BETWEEN fp.start_date AND fp.end_date
+            LIMIT 1
+        ),
+        can_reopen AS (
+            SELECT EXISTS(
+                SELECT 1
+                FROM user_tenant_roles utr
+                JOIN role_permissions rp ON rp.role_id = utr.role_id
+                JOIN permissions p ON p.id = rp.permission_id
+                WHERE utr.tenant_id = $1 AND utr.user_id = $2 AND p.name = $6
+            ) as allowed
+        ),
+        valid_accounts AS (
+            SELECT id FROM accounts
+            WHERE tenant_id = $1 AND id = ANY($5) AND is_active = TRUE AND archived_at IS NULL
+        )
+        SELECT
+            t.base_currency_code,
+            t.privacy_mode,
+            t.data_key,
+            (SELECT cp.name FROM closed_period cp, can_reopen cr WHERE NOT cr.allowed) as locked_period,
+            ($4::uuid IS NULL OR EXISTS(
+                SELECT 1 FROM categories c
+                WHERE c.id = $4 AND c.tenant_id = $1 AND c.is_active = TRUE AND c.archived_at IS NULL
+            )) as "category_assignable!",
+            ARRAY(SELECT id FROM valid_accounts) as "valid_account_ids!",
+            ($7::uuid IS NULL OR EXISTS(
+                SELECT 1 FROM payees p WHERE p.id = $7 AND p.tenant_id = $1
+            )) as "payee_valid!",
+            (
+                SELECT mr.category_id
+                FROM merchant_rules mr
+                JOIN categories c ON c.id = mr.category_id AND c.is_active = TRUE AND c.archived_at IS NULL
+                WHERE mr.tenant_id = $1 AND mr.payee_id = $7
+            ) as payee_category_id
+        FROM tenants t
+        WHERE t.id = $1
+        "#,
+        tenant_id,
+        user_id,
+        date,
+        category_id,
+        account_ids,
+        permission::PERIOD_REOPEN,
+        payee_id,
+    )
+    .fetch_optional(&mut **db_tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Tenant with ID {} not found", tenant_id)))?;
+
+    Ok(PostingChecks {
+        base_currency_code: row.base_currency_code.to_uppercase(),
+        text_key: privacy::sealing_key_from(row.privacy_mode, row.data_key.as_deref())?,
+        locked_period: row.locked_period,
+        category_assignable: row.category_assignable,
+        valid_accounts: row.valid_account_ids.into_iter().collect(),
+        payee_valid: row.payee_valid,
+        payee_category_id: row.payee_category_id,
+    })
 }
 
 /// Updates an existing transaction for a specific tenant.
-/// Note: Updating a transaction, especially its amount or type, often requires
-/// complex logic to adjust or reverse associated journal entries.
-/// This implementation provides a basic update for metadata.
+/// Drafts can be edited freely. Once submitted or posted only descriptive fields can be
+/// edited; the date, type, amount and currency are changed by reversing the transaction
+/// (see `reverse_transaction`). The update only applies while the transaction is still at
+/// `expected_version` (the version the client read).
 pub async fn update_transaction(
     pool: &PgPool,
     tenant_id: Uuid,
     transaction_id: Uuid,
     updated_by_user_id: Uuid,
+    expected_version: i32,
     dto: UpdateTransactionDto,
 ) -> Result<Transaction, AppError> {
     info!("Service: Updating transaction with ID: {} for tenant ID: {}", transaction_id, tenant_id);
 
-    let mut update_cols: Vec<String> = Vec::new();
-    let mut update_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_idx = 1;
+    let existing = get_transaction_by_id(pool, tenant_id, transaction_id).await?;
+    if existing.version != expected_version {
+        return Err(db::version_mismatch(pool, "transactions", "Transaction", transaction_id, tenant_id, expected_version).await);
+    }
+    let edits_ledger = dto.transaction_date.is_some() || dto.r#type.is_some() || dto.amount.is_some() || dto.currency_code.is_some();
+    if edits_ledger && existing.status != String::from(TransactionStatus::Draft) {
+        return Err(AppError::Validation(format!(
+            "Date, type, amount and currency of a {} transaction cannot be edited; reverse it with POST /transactions/:id/reverse and record a new one",
+            existing.status
+        )));
+    }
 
+    // Neither the current date nor a new one may fall in a closed period
+    fiscal_period::ensure_date_open(pool, tenant_id, updated_by_user_id, existing.transaction_date).await?;
     if let Some(transaction_date) = dto.transaction_date {
-        update_cols.push(format!("transaction_date = ${}", param_idx));
-        update_values.push(Box::new(transaction_date));
-        param_idx += 1;
-    }
-    if let Some(description) = dto.description {
-        update_cols.push(format!("description = ${}", param_idx));
-        update_values.push(Box::new(description));
-        param_idx += 1;
-    }
-    if let Some(r#type) = dto.r#type {
-        update_cols.push(format!("type = ${}", param_idx));
-        update_values.push(Box::new(r#type as TransactionType));
-        param_idx += 1;
-    }
-    if let Some(category_id) = dto.category_id {
-        update_cols.push(format!("category_id = ${}", param_idx));
-        update_values.push(Box::new(category_id));
-        param_idx += 1;
-    }
-    if let Some(tags) = dto.tags {
-        let tags_json = serde_json::to_value(&tags).map_err(|e| AppError::InternalError(format!("Failed to serialize tags: {}", e)))?;
-        update_cols.push(format!("tags_json = ${}", param_idx));
-        update_values.push(Box::new(tags_json));
-        param_idx += 1;
-    }
-    if let Some(amount) = dto.amount {
-        update_cols.push(format!("amount = ${}", param_idx));
-        update_values.push(Box::new(amount));
-        param_idx += 1;
-    }
-    if let Some(currency_code) = dto.currency_code {
-        update_cols.push(format!("currency_code = ${}", param_idx));
-        update_values.push(Box::new(currency_code));
-        param_idx += 1;
-    }
-    if let Some(is_reconciled) = dto.is_reconciled {
-        update_cols.push(format!("is_reconciled = ${}", param_idx));
-        update_values.push(Box::new(is_reconciled));
-        param_idx += 1;
-    }
-    if let Some(reconciliation_date) = dto.reconciliation_date {
-        update_cols.push(format!("reconciliation_date = ${}", param_idx));
-        update_values.push(Box::new(reconciliation_date));
-        param_idx += 1;
-    }
-    if let Some(notes) = dto.notes {
-        update_cols.push(format!("notes = ${}", param_idx));
-        update_values.push(Box::new(notes));
-        param_idx += 1;
-    }
-    if let Some(source_document_url) = dto.source_document_url {
-        update_cols.push(format!("source_document_url = ${}", param_idx));
-        update_values.push(Box::new(source_document_url));
-        param_idx += 1;
-    }
-
-    // Always update updated_at and updated_by
-    update_cols.push(format!("updated_at = NOW()"));
-    update_cols.push(format!("updated_by = ${}", param_idx));
-    update_values.push(Box::new(updated_by_user_id));
-    param_idx += 1;
-
-    if update_cols.is_empty() {
-        return Err(AppError::BadRequest("No fields provided for update".to_string()));
-    }
-
-    let update_clause = update_cols.join(", ");
-    let query_str = format!(
+        fiscal_period::ensure_date_open(pool, tenant_id, updated_by_user_id, transaction_date).await?;
+    }
+    if let Some(&category_id) = dto.category_id.value() {
+        category::ensure_assignable(pool, tenant_id, category_id).await?;
+    }
+    if let Some(&payee_id) = dto.payee_id.value() {
+        payee::get_payee(pool, tenant_id, payee_id).await?;
+    }
+
+    let description = match dto.description {
+        Some(description) => {
+            let text_key = privacy::sealing_key(pool, tenant_id).await?;
+            Some(privacy::seal(text_key.as_ref(), description)?)
+        }
+        None => None,
+    };
+    let tags_json = dto
+        .tags
+        .map(|tags| serde_json::to_value(&tags))
+        .transpose()
+        .map_err(|e| AppError::InternalServerError(format!("Failed to serialize tags: {}", e)))?;
+
+    let mut update = UpdateBuilder::new("transactions");
+    update
+        .set("transaction_date", dto.transaction_date)
+        .set("description", description)
+        .set("type", dto.r#type)
+        .patch("category_id", dto.category_id)
+        .patch("payee_id", dto.payee_id)
+        .set("tags_json", tags_json)
+        .set("amount", dto.amount)
+        .set("currency_code", dto.currency_code)
+        .set("is_reconciled", dto.is_reconciled)
+        .patch("reconciliation_date", dto.reconciliation_date)
+        .patch("notes", dto.notes)
+        .patch("source_document_url", dto.source_document_url);
+    if update.is_empty() {
+        return Err(AppError::Validation("No fields provided for update".to_string()));
+    }
+    update.bump_version();
+
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(transaction_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    // Someone may have changed it since it was read above
+    query.push(" AND version = ").push_bind(expected_version);
+    query.push(
         r#"
-        UPDATE transactions
-        SET {}
-        WHERE id = ${} AND tenant_id = ${}
         RETURNING
-            id, tenant_id, transaction_date, description, type as "r#type!: TransactionType",
-            category_id, tags_json, amount, currency_code, is_reconciled, reconciliation_date,
-            notes, source_document_url, created_at, created_by, updated_at, updated_by
+            id, tenant_id, transaction_date, description, type,
+            category_id, payee_id, tags_json, amount, currency_code, is_reconciled, reconciliation_date,
+            notes, source_document_url, reversal_of_id, reversed_by_id,
+            recurring_transaction_id, recurring_occurrence_date,
+            status, posted_at, posted_by, voided_at, voided_by, void_reason,
+            created_at, created_by, updated_at, updated_by, version
         "#,
-        update_clause, param_idx, param_idx + 1 // transaction_id and tenant_id will be the last parameters
     );
 
-    let mut query = sqlx::query_as::<_, Transaction>(&query_str);
-
-    for val in update_values {
-        query = query.bind(val);
+    let updated_transaction = query.build_query_as::<Transaction>().fetch_optional(pool).await?;
+    match updated_transaction {
+        Some(transaction) => Ok(transaction),
+        None => Err(db::version_mismatch(pool, "transactions", "Transaction", transaction_id, tenant_id, expected_version).await),
     }
-    // Bind transaction_id and tenant_id last
-    query = query.bind(transaction_id);
-    query = query.bind(tenant_id);
-
-    let updated_transaction = query
-        .fetch_optional(pool)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Transaction with ID {} not found or not owned by tenant {}", transaction_id, tenant_id)))?;
-
-    Ok(updated_transaction)
 }
 
 /// Deletes a transaction by ID for a specific tenant.
@@ -290,11 +624,29 @@ pub async fn delete_transaction(
     pool: &PgPool,
     tenant_id: Uuid,
     transaction_id: Uuid,
+    deleted_by_user_id: Uuid, // Checked against closed fiscal periods
 ) -> Result<(), AppError> {
     info!("Service: Deleting transaction with ID: {} for tenant ID: {}", transaction_id, tenant_id);
 
+    let existing = get_transaction_by_id(pool, tenant_id, transaction_id).await?;
+    let status: TransactionStatus = existing.status.parse().map_err(AppError::InternalServerError)?;
+    if matches!(status, TransactionStatus::Posted | TransactionStatus::Voided) {
+        return Err(AppError::Validation(format!(
+            "Transaction {} is {}; void or reverse it instead of deleting",
+            transaction_id, existing.status
+        )));
+    }
+    if existing.reversal_of_id.is_some() || existing.reversed_by_id.is_some() {
+        return Err(AppError::Validation(format!(
+            "Transaction {} is part of a reversal pair and cannot be deleted",
+            transaction_id
+        )));
+    }
+
     let mut db_tx = pool.begin().await?;
 
+    fiscal_period::ensure_date_open(&mut *db_tx, tenant_id, deleted_by_user_id, existing.transaction_date).await?;
+
     // First, delete associated journal entries
     let journal_entries_deleted = sqlx::query!(
         r#"
@@ -330,4 +682,529 @@ pub async fn delete_transaction(
     db_tx.commit().await?; // Commit if both deletions are successful
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Reverses a transaction: creates an offsetting transaction dated `reversal_date`
+/// (default today) whose journal entries mirror the original's (debits become credits and
+/// vice versa), and links the two. The original is left untouched apart from the link.
+pub async fn reverse_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    reversed_by_user_id: Uuid,
+    dto: ReverseTransactionDto,
+) -> Result<Transaction, AppError> {
+    info!("Service: Reversing transaction with ID: {} for tenant ID: {}", transaction_id, tenant_id);
+
+    dto.validate()?;
+    let reversal_date = dto.reversal_date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+
+    let mut db_tx = pool.begin().await?;
+
+    let original = sqlx::query!(
+        r#"
+        SELECT id, transaction_date, description, type as "r#type: TransactionType", category_id, payee_id,
+               tags_json, amount, currency_code, reversal_of_id, reversed_by_id, status
+        FROM transactions
+        WHERE id = $1 AND tenant_id = $2
+        FOR UPDATE
+        "#,
+        transaction_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Transaction with ID {} not found for tenant {}", transaction_id, tenant_id)))?;
+
+    if original.status != String::from(TransactionStatus::Posted) {
+        return Err(AppError::Validation(format!(
+            "Only posted transactions can be reversed; transaction {} is {}",
+            transaction_id, original.status
+        )));
+    }
+    if original.reversed_by_id.is_some() {
+        return Err(AppError::Validation(format!("Transaction {} has already been reversed", transaction_id)));
+    }
+    if original.reversal_of_id.is_some() {
+        return Err(AppError::Validation(format!(
+            "Transaction {} is itself a reversal; record a new transaction instead",
+            transaction_id
+        )));
+    }
+    if reversal_date < original.transaction_date {
+        return Err(AppError::Validation("Reversal date cannot be before the original transaction date".to_string()));
+    }
+    fiscal_period::ensure_date_open(&mut *db_tx, tenant_id, reversed_by_user_id, reversal_date).await?;
+
+    let description = match dto.description {
+        Some(description) => description,
+        None => {
+            // Open a sealed original so the reversal reads as text once sealed again
+            let original_description = match privacy::opening_key(&mut *db_tx, tenant_id).await? {
+                Some(key) => privacy::unseal(&key, &original.description)?,
+                None => original.description.clone(),
+            };
+            format!("Reversal of {}", original_description)
+        }
+    };
+    let text_key = privacy::sealing_key(&mut *db_tx, tenant_id).await?;
+    let description = privacy::seal(text_key.as_ref(), description)?;
+
+    let reversal = query_as!(
+        Transaction,
+        r#"
+        INSERT INTO transactions (
+            tenant_id, transaction_date, description, type, category_id,
+            tags_json, amount, currency_code, reversal_of_id,
+            status, posted_at, posted_by, created_by, updated_by, payee_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 'POSTED', NOW(), $10, $10, $10, $11)
+        RETURNING
+            id, tenant_id, transaction_date, description, type as "r#type!: TransactionType",
+            category_id, payee_id, tags_json, amount, currency_code, is_reconciled, reconciliation_date,
+            notes, source_document_url, reversal_of_id, reversed_by_id,
+            recurring_transaction_id, recurring_occurrence_date,
+            status, posted_at, posted_by, voided_at, voided_by, void_reason,
+            created_at, created_by, updated_at, updated_by, version
+        "#,
+        tenant_id,
+        reversal_date,
+        description,
+        original.r#type as TransactionType,
+        original.category_id,
+        original.tags_json,
+        original.amount,
+        original.currency_code,
+        transaction_id,
+        reversed_by_user_id,
+        original.payee_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    // Mirror every leg: same account and amounts, opposite side
+    let mirrored = sqlx::query!(
+        r#"
+        INSERT INTO journal_entries (
+            transaction_id, account_id, entry_type, amount, currency_code,
+            exchange_rate, converted_amount, memo, project_id, class_id, location_id, created_by, updated_by
+        )
+        SELECT
+            $2, account_id,
+            CASE entry_type WHEN 'DEBIT' THEN 'CREDIT'::entry_side ELSE 'DEBIT'::entry_side END,
+            amount, currency_code, exchange_rate, converted_amount, memo, project_id, class_id, location_id, $3, $3
+        FROM journal_entries
+        WHERE transaction_id = $1
+        "#,
+        transaction_id,
+        reversal.id,
+        reversed_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?
+    .rows_affected();
+    balance_snapshot::record_transaction(&mut *db_tx, reversal.id, SnapshotChange::Posted).await?;
+
+    sqlx::query!(
+        "UPDATE transactions SET reversed_by_id = $2, updated_at = NOW(), updated_by = $3, version = version + 1 WHERE id = $1",
+        transaction_id,
+        reversal.id,
+        reversed_by_user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+
+    info!("Reversed transaction {} with {} ({} journal entries mirrored)", transaction_id, reversal.id, mirrored);
+    domain_event::publish(DomainEvent::TransactionReversed {
+        tenant_id,
+        user_id: reversed_by_user_id,
+        transaction_id,
+        reversal_id: reversal.id,
+        reversal_date: reversal.transaction_date,
+    });
+    Ok(reversal)
+}
+
+/// Submits a draft for approval (DRAFT -> PENDING_APPROVAL).
+pub async fn submit_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    user_id: Uuid,
+) -> Result<Transaction, AppError> {
+    info!("Service: Submitting transaction with ID: {} for approval", transaction_id);
+    transition(pool, tenant_id, transaction_id, user_id, TransactionStatus::PendingApproval, None).await
+}
+
+/// Returns a transaction waiting for approval to draft (PENDING_APPROVAL -> DRAFT).
+pub async fn return_transaction_to_draft(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    user_id: Uuid,
+) -> Result<Transaction, AppError> {
+    info!("Service: Returning transaction with ID: {} to draft", transaction_id);
+    transition(pool, tenant_id, transaction_id, user_id, TransactionStatus::Draft, None).await
+}
+
+/// Posts a transaction (DRAFT or PENDING_APPROVAL -> POSTED). Its journal entries must
+/// balance and become immutable. Posting a submitted transaction requires `tx.approve`.
+pub async fn post_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    user_id: Uuid,
+) -> Result<Transaction, AppError> {
+    info!("Service: Posting transaction with ID: {}", transaction_id);
+    let posted = with_retry(pool, |pool| {
+        transition(pool, tenant_id, transaction_id, user_id, TransactionStatus::Posted, None)
+    })
+    .await?;
+
+    domain_event::publish(DomainEvent::TransactionPosted {
+        tenant_id,
+        user_id,
+        transaction_id,
+        amount: posted.amount,
+        transaction_date: posted.transaction_date,
+    });
+    Ok(posted)
+}
+
+/// Voids a posted transaction (POSTED -> VOIDED). It stays in the audit trail but no
+/// longer counts in reports.
+pub async fn void_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    user_id: Uuid,
+    dto: VoidTransactionDto,
+) -> Result<Transaction, AppError> {
+    info!("Service: Voiding transaction with ID: {}", transaction_id);
+    dto.validate()?;
+    let voided = with_retry(pool, |pool| {
+        transition(pool, tenant_id, transaction_id, user_id, TransactionStatus::Voided, Some(dto.reason.clone()))
+    })
+    .await?;
+
+    domain_event::publish(DomainEvent::TransactionVoided {
+        tenant_id,
+        user_id,
+        transaction_id,
+        reason: dto.reason,
+    });
+    Ok(voided)
+}
+
+/// Moves a transaction through the workflow after checking the transition is allowed.
+async fn transition(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    transaction_id: Uuid,
+    user_id: Uuid,
+    next: TransactionStatus,
+    void_reason: Option<String>,
+) -> Result<Transaction, AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let current = sqlx::query!(
+        "SELECT status, transaction_date, reversal_of_id, reversed_by_id FROM transactions WHERE id = $1 AND tenant_id = $2 FOR UPDATE",
+        transaction_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Transaction with ID {} not found for tenant {}", transaction_id, tenant_id)))?;
+
+    let status: TransactionStatus = current.status.parse().map_err(AppError::InternalServerError)?;
+    if !status.can_transition_to(next) {
+        return Err(AppError::Validation(format!(
+            "Transaction {} cannot move from {} to {}",
+            transaction_id,
+            current.status,
+            String::from(next)
+        )));
+    }
+
+    match next {
+        TransactionStatus::Posted => {
+            if status == TransactionStatus::PendingApproval {
+                permission::require_permission(&mut *db_tx, tenant_id, user_id, TX_APPROVE).await?;
+            }
+            ensure_balanced(&mut db_tx, transaction_id).await?;
+            fiscal_period::ensure_date_open(&mut *db_tx, tenant_id, user_id, current.transaction_date).await?;
+        }
+        TransactionStatus::Voided => {
+            if current.reversal_of_id.is_some() || current.reversed_by_id.is_some() {
+                return Err(AppError::Validation(format!(
+                    "Transaction {} is part of a reversal pair and cannot be voided",
+                    transaction_id
+                )));
+            }
+            fiscal_period::ensure_date_open(&mut *db_tx, tenant_id, user_id, current.transaction_date).await?;
+        }
+        TransactionStatus::Draft | TransactionStatus::PendingApproval => {}
+    }
+
+    let updated = query_as!(
+        Transaction,
+        r#"
+        UPDATE transactions
+        SET
+            status = $3::text,
+            posted_at = CASE WHEN $3::text = 'POSTED' THEN NOW() ELSE posted_at END,
+            posted_by = CASE WHEN $3::text = 'POSTED' THEN $4::uuid ELSE posted_by END,
+            voided_at = CASE WHEN $3::text = 'VOIDED' THEN NOW() ELSE voided_at END,
+            voided_by = CASE WHEN $3::text = 'VOIDED' THEN $4::uuid ELSE voided_by END,
+            void_reason = COALESCE($5, void_reason),
+            updated_at = NOW(),
+            updated_by = $4,
+            version = version + 1
+        WHERE id = $1 AND tenant_id = $2
+        RETURNING
+            id, tenant_id, transaction_date, description, type as "r#type!: TransactionType",
+            category_id, payee_id, tags_json, amount, currency_code, is_reconciled, reconciliation_date,
+            notes, source_document_url, reversal_of_id, reversed_by_id,
+            recurring_transaction_id, recurring_occurrence_date,
+            status, posted_at, posted_by, voided_at, voided_by, void_reason,
+            created_at, created_by, updated_at, updated_by, version
+        "#,
+        transaction_id,
+        tenant_id,
+        String::from(next),
+        user_id,
+        void_reason
+    )
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    // Only posted transactions count towards balances
+    let snapshot_change = match next {
+        TransactionStatus::Posted => Some(SnapshotChange::Posted),
+        TransactionStatus::Voided => Some(SnapshotChange::Voided),
+        TransactionStatus::Draft | TransactionStatus::PendingApproval => None,
+    };
+    if let Some(change) = snapshot_change {
+        balance_snapshot::record_transaction(&mut *db_tx, transaction_id, change).await?;
+    }
+
+    db_tx.commit().await?;
+    Ok(updated)
+}
+
+/// Checks that a transaction has journal entries and that its debits equal its credits.
+async fn ensure_balanced(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    transaction_id: Uuid,
+) -> Result<(), AppError> {
+    let totals = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) as "entries!",
+            COALESCE(SUM(CASE WHEN entry_type = 'DEBIT' THEN COALESCE(converted_amount, amount) END), 0) as "debits!",
+            COALESCE(SUM(CASE WHEN entry_type = 'CREDIT' THEN COALESCE(converted_amount, amount) END), 0) as "credits!"
+        FROM journal_entries
+        WHERE transaction_id = $1
+        "#,
+        transaction_id
+    )
+    .fetch_one(&mut **db_tx)
+    .await?;
+
+    if totals.entries < 2 {
+        return Err(AppError::Validation(format!(
+            "Transaction {} needs at least two journal entries to be posted",
+            transaction_id
+        )));
+    }
+    if totals.debits != totals.credits {
+        return Err(AppError::Validation(format!(
+            "Transaction {} is not balanced: debits {} != credits {}",
+            transaction_id, totals.debits, totals.credits
+        )));
+    }
+    Ok(())
+}
+
+/// Fails unless the transaction is a draft; journal entries are immutable afterwards.
+pub async fn ensure_draft(pool: &PgPool, tenant_id: Uuid, transaction_id: Uuid) -> Result<(), AppError> {
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM transactions WHERE id = $1 AND tenant_id = $2",
+        transaction_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Transaction with ID {} not found for tenant {}", transaction_id, tenant_id)))?;
+
+    if status != String::from(TransactionStatus::Draft) {
+        return Err(AppError::Validation(format!(
+            "Journal entries of a {} transaction cannot be changed",
+            status
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::create_transaction;
+    use crate::models::{
+        dto::{journal_entry_dto::CreateJournalEntryDto, transaction_dto::CreateTransactionDto},
+        journal_entry::JournalEntryType,
+        transaction::{TransactionStatus, TransactionType},
+    };
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn one_account_can_carry_a_leg_per_project() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPool::connect(&url)
+            .await
+            .expect("connect to DATABASE_URL");
+        let run = Uuid::new_v4();
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+             VALUES ($1, 'EMAIL_PASSWORD', $1, 'Project', 'Tester') RETURNING id",
+        )
+        .bind(format!("projects-{}@example.com", run))
+        .fetch_one(&pool)
+        .await
+        .expect("insert user");
+        sqlx::query(
+            "INSERT INTO currencies (code, name, symbol, created_by, updated_by)
+             VALUES ('USD', 'US Dollar', '$', $1, $1) ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .expect("insert currency");
+        let tenant_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO tenants (name, base_currency_code, fiscal_year_end_month, created_by, updated_by)
+             VALUES ($1, 'USD', 12, $2, $2) RETURNING id",
+        )
+        .bind(format!("Projects {}", run))
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .expect("insert tenant");
+        let mut account_ids = Vec::new();
+        for (type_name, account_name) in [("Asset", "Cash"), ("Expense", "Consulting")] {
+            let account_type_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO account_types (name, normal_balance, created_by, updated_by)
+                 VALUES ($1, 'DEBIT', $2, $2)
+                 ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING id",
+            )
+            .bind(type_name)
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .expect("insert account type");
+            let account_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO accounts (tenant_id, account_type_id, name, currency_code, created_by, updated_by)
+                 VALUES ($1, $2, $3, 'USD', $4, $4) RETURNING id",
+            )
+            .bind(tenant_id)
+            .bind(account_type_id)
+            .bind(account_name)
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .expect("insert account");
+            account_ids.push(account_id);
+        }
+        let (cash_id, expense_id) = (account_ids[0], account_ids[1]);
+        let mut project_ids = Vec::new();
+        for name in ["Website", "Warehouse"] {
+            let project_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO dimensions (tenant_id, kind, name, created_by, updated_by)
+                 VALUES ($1, 'PROJECT', $2, $3, $3) RETURNING id",
+            )
+            .bind(tenant_id)
+            .bind(name)
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .expect("insert project");
+            project_ids.push(project_id);
+        }
+
+        let entry = |account_id, entry_type, amount: &str, project_id| CreateJournalEntryDto {
+            account_id,
+            entry_type,
+            amount: amount.parse().unwrap(),
+            currency_code: "USD".to_string(),
+            exchange_rate: None,
+            converted_amount: None,
+            memo: None,
+            project_id,
+            class_id: None,
+            location_id: None,
+        };
+        let transaction = create_transaction(
+            &pool,
+            tenant_id,
+            user_id,
+            CreateTransactionDto {
+                transaction_date: chrono::Utc::now().date_naive(),
+                description: "Consultant invoice".to_string(),
+                r#type: TransactionType::Expense,
+                category_id: None,
+                payee_id: None,
+                tags: None,
+                amount: "1000.00".parse().unwrap(),
+                currency_code: "USD".to_string(),
+                is_reconciled: None,
+                reconciliation_date: None,
+                notes: None,
+                source_document_url: None,
+                status: Some(TransactionStatus::Posted),
+                journal_entries: vec![
+                    entry(
+                        expense_id,
+                        JournalEntryType::Debit,
+                        "600.00",
+                        Some(project_ids[0]),
+                    ),
+                    entry(
+                        expense_id,
+                        JournalEntryType::Debit,
+                        "400.00",
+                        Some(project_ids[1]),
+                    ),
+                    entry(cash_id, JournalEntryType::Credit, "1000.00", None),
+                ],
+                payment_account_id: None,
+                splits: Vec::new(),
+                metadata: None,
+                attribution: None,
+            },
+        )
+        .await
+        .expect("create transaction");
+
+        let legs: Vec<(Option<Uuid>, Decimal)> = sqlx::query_as(
+            "SELECT project_id, amount FROM journal_entries
+             WHERE transaction_id = $1 AND account_id = $2 AND entry_type = 'DEBIT'
+             ORDER BY amount DESC",
+        )
+        .bind(transaction.id)
+        .bind(expense_id)
+        .fetch_all(&pool)
+        .await
+        .expect("load entries");
+        assert_eq!(
+            legs,
+            [
+                (Some(project_ids[0]), "600.00".parse().unwrap()),
+                (Some(project_ids[1]), "400.00".parse().unwrap())
+            ]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Streamed CSV export of transactions, with the same filters as the listing.
+//!
+//! Rows are read from a single query with a row stream and written to the response in
+//! batches of `BATCH_ROWS`, so memory stays flat however many years are exported. A
+//! background task owns the query and hands batches to the body through a small channel;
+//! a slow client holds the query back, a client that goes away stops it. A failure after
+//! the first bytes were sent aborts the response, so a cut-off file is never mistaken for
+//! a complete one.
+
+use std::io;
+
+use axum::body::Body;
+use chrono::Utc;
+use futures::{stream, TryStreamExt};
+use serde_json::{json, Map};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::AppError,
+    models::dto::transaction_dto::ListTransactionsQuery,
+    services::{field_policy::FieldAccess, transaction},
+    utils::csv_format::{CsvCell, CsvColumnKind, CsvFormat},
+};
+
+use CsvColumnKind::{Date, Number, Text};
+
+/// Rows written per body chunk.
+const BATCH_ROWS: usize = 500;
+
+/// Batches buffered ahead of the client.
+const BUFFERED_BATCHES: usize = 4;
+
+const COLUMNS: [(&str, CsvColumnKind); 13] = [
+    ("id", Text),
+    ("transaction_date", Date),
+    ("description", Text),
+    ("type", Text),
+    ("status", Text),
+    ("category", Text),
+    ("amount", Number),
+    ("currency_code", Text),
+    ("is_reconciled", Text),
+    ("reconciliation_date", Date),
+    ("notes", Text),
+    ("source_document_url", Text),
+    ("reversal_of_id", Text),
+];
+
+/// Starts exporting the tenant's transactions matching `query`, newest first. Returns the
+/// file name and a body that produces the file as it is read. Rows pass through the
+/// reader's `FieldAccess`, so the file hides what the listing would.
+pub fn export_transactions_csv(
+    pool: PgPool,
+    tenant_id: Uuid,
+    query: ListTransactionsQuery,
+    access: FieldAccess,
+    format: CsvFormat,
+) -> Result<(String, Body), AppError> {
+    info!(
+        "Service: Exporting transactions as CSV for tenant ID: {}",
+        tenant_id
+    );
+
+    let radius_km = transaction::check_list_query(&query)?;
+    let (sender, receiver) = mpsc::channel::<Result<Vec<u8>, io::Error>>(BUFFERED_BATCHES);
+    tokio::spawn(db::with_tenant_scope(Some(tenant_id), async move {
+        let result = write_rows(
+            &pool, tenant_id, &query, radius_km, &access, &format, &sender,
+        )
+        .await;
+        if let Err(e) = result {
+            warn!("Transaction export for tenant {} failed: {}", tenant_id, e);
+            let _ = sender.send(Err(io::Error::other(e.to_string()))).await;
+        }
+    }));
+
+    let body = Body::from_stream(stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    }));
+    let file_name = format!("transactions-{}.csv", Utc::now().date_naive());
+    Ok((file_name, body))
+}
+
+/// Runs the query and sends the file batch by batch. Stops early, without an error, once
+/// the body is dropped.
+async fn write_rows(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    query: &ListTransactionsQuery,
+    radius_km: f64,
+    access: &FieldAccess,
+    format: &CsvFormat,
+    sender: &mpsc::Sender<Result<Vec<u8>, io::Error>>,
+) -> Result<(), AppError> {
+    let mut rows = sqlx::query!(
+        r#"
+        SELECT
+            t.id, t.transaction_date, t.description, t.type::text as "transaction_type!", t.status,
+            c.name as "category_name?", t.amount, t.currency_code, t.is_reconciled,
+            t.reconciliation_date, t.notes, t.source_document_url, t.reversal_of_id
+        FROM transactions t
+        LEFT JOIN categories c ON c.id = t.category_id
+        LEFT JOIN transaction_metadata m ON m.transaction_id = t.id
+        WHERE t.tenant_id = $1
+          AND ($2::date IS NULL OR t.transaction_date >= $2)
+          AND ($3::date IS NULL OR t.transaction_date <= $3)
+          AND ($4::text IS NULL OR m.entry_source = UPPER($4))
+          AND ($5::text IS NULL OR m.device ILIKE '%' || $5 || '%')
+          AND ($6::float8 IS NULL OR (
+              m.latitude IS NOT NULL
+              -- Haversine distance in km on a 6371 km sphere
+              AND 2 * 6371 * ASIN(SQRT(
+                  POWER(SIN(RADIANS(m.latitude - $6) / 2), 2)
+                  + COS(RADIANS($6)) * COS(RADIANS(m.latitude)) * POWER(SIN(RADIANS(m.longitude - $7) / 2), 2)
+              )) <= $8
+          ))
+          AND ($9::uuid IS NULL OR EXISTS (
+              SELECT 1 FROM event_transactions et WHERE et.transaction_id = t.id AND et.event_id = $9
+          ))
+          AND ($10::uuid IS NULL OR t.payee_id = $10)
+        ORDER BY t.transaction_date DESC, t.created_at DESC
+        "#,
+        tenant_id,
+        query.from_date,
+        query.to_date,
+        query.entry_source,
+        query.device,
+        query.near_lat,
+        query.near_lon,
+        radius_km,
+        query.event_id,
+        query.payee_id
+    )
+    .fetch(pool);
+
+    // Each batch is a file of its own; only the first carries the header and the BOM
+    let mut writer = format.writer();
+    let header: Vec<&str> = COLUMNS.iter().map(|(name, _)| *name).collect();
+    writer.write_header(&header)?;
+    let later_batches = CsvFormat {
+        bom: false,
+        ..format.clone()
+    };
+
+    let empty = Map::new();
+    let mut batch_rows = 0;
+    let mut exported = 0;
+    while let Some(row) = rows.try_next().await? {
+        let mut value = json!({
+            "id": row.id,
+            "transaction_date": row.transaction_date,
+            "description": row.description,
+            "type": row.transaction_type,
+            "status": row.status,
+            "category": row.category_name,
+            "amount": row.amount,
+            "currency_code": row.currency_code,
+            "is_reconciled": row.is_reconciled,
+            "reconciliation_date": row.reconciliation_date,
+            "notes": row.notes,
+            "source_document_url": row.source_document_url,
+            "reversal_of_id": row.reversal_of_id,
+        });
+        access.apply(&mut value);
+        let fields = value.as_object().unwrap_or(&empty);
+        writer.write_row(
+            COLUMNS
+                .iter()
+                .map(|(name, kind)| CsvCell::from_json(fields.get(*name), *kind)),
+        )?;
+
+        batch_rows += 1;
+        exported += 1;
+        if batch_rows == BATCH_ROWS {
+            let full = std::mem::replace(&mut writer, later_batches.writer());
+            if sender.send(Ok(full.finish()?.into_bytes())).await.is_err() {
+                info!(
+                    "Transaction export for tenant {} cancelled by the client",
+                    tenant_id
+                );
+                return Ok(());
+            }
+            batch_rows = 0;
+        }
+    }
+    let _ = sender.send(Ok(writer.finish()?.into_bytes())).await;
+
+    info!(
+        "Exported {} transactions for tenant {}",
+        exported, tenant_id
+    );
+    Ok(())
+}
@@ -0,0 +1,789 @@
+//! Matching of staged bank-feed rows against existing ledger transactions.
+//!
+//! A matching run proposes candidates for every pending staged row on linked
+//! accounts; nothing is linked until a user accepts a proposal. Exact
+//! re-deliveries of rows already handled are flagged as duplicates, and the two
+//! sides of a transfer between linked accounts are collapsed into one TRANSFER
+//! transaction rather than left to be booked as an expense and an income.
+
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde_json::json;
+use sqlx::{query_as, PgPool, Postgres, Transaction as DbTransaction};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db::{begin_financial, with_retry},
+    error::AppError,
+    models::{
+        dto::{transaction_dto::CreateTransferDto, transaction_match_dto::MatchRunSummary},
+        journal_entry::JournalEntryType,
+        transaction_match::{MatchStatus, TransactionMatch},
+    },
+    services::{
+        ext_conn,
+        privacy::{self, TextKey},
+        transaction, transfer,
+    },
+};
+
+/// Candidates must be within this many days of the bank date.
+const MATCH_WINDOW_DAYS: i64 = 5;
+/// Maximum number of proposals kept per staged row.
+const MAX_PROPOSALS_PER_ROW: usize = 3;
+/// The two sides of a transfer must be within this many days of each other.
+const TRANSFER_WINDOW_DAYS: i64 = 3;
+
+// Weights of each signal in the final score. Amount must match exactly to be a candidate.
+const AMOUNT_WEIGHT: f64 = 0.6;
+const DATE_WEIGHT: f64 = 0.25;
+const DESCRIPTION_WEIGHT: f64 = 0.15;
+
+/// A staged row awaiting review on an external account linked to a ledger account.
+struct PendingRow {
+    id: Uuid,
+    external_account_id: Uuid,
+    account_id: Uuid,
+    provider_transaction_id: String,
+    description: String,
+    amount: Decimal,
+    transaction_date: NaiveDate,
+    bank_date: NaiveDate, // Posted date, or the transaction date while pending
+}
+
+/// One side of a transfer detected in the staged rows.
+#[derive(Clone, Copy)]
+struct TransferSide {
+    staging_id: Uuid,
+    account_id: Uuid,
+    transaction_date: NaiveDate,
+    bank_date: NaiveDate,
+}
+
+/// Lowercased alphanumeric tokens, ignoring digits-only noise like card suffixes.
+fn description_tokens(description: &str) -> HashSet<String> {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.len() > 1 && !token.chars().all(|c| c.is_ascii_digit()))
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+/// Jaccard similarity of description tokens, in 0..=1.
+fn description_similarity(a: &str, b: &str) -> f64 {
+    let a = description_tokens(a);
+    let b = description_tokens(b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / a.union(&b).count() as f64
+}
+
+/// Runs matching for every pending staged row on a connection's linked accounts.
+pub async fn run_matching_for_connection(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    ext_conn_id: Uuid,
+    user_id: Uuid,
+) -> Result<MatchRunSummary, AppError> {
+    info!(
+        "Service: Running transaction matching for connection ID: {}",
+        ext_conn_id
+    );
+
+    // Verify the connection belongs to the tenant
+    ext_conn::get_ext_conn_by_id(pool, tenant_id, ext_conn_id).await?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            s.id, s.external_account_id, ea.account_id as "account_id!", s.provider_transaction_id,
+            s.description, s.amount, s.transaction_date,
+            COALESCE(s.posted_date, s.transaction_date) as "bank_date!"
+        FROM external_transactions_staging s
+        JOIN external_accounts ea ON s.external_account_id = ea.id
+        WHERE ea.ext_conn_id = $1 AND ea.account_id IS NOT NULL AND s.status = 'PENDING_REVIEW'
+        ORDER BY s.transaction_date
+        "#,
+        ext_conn_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Descriptions sealed by privacy mode are opened for scoring only
+    let text_key = privacy::opening_key(pool, tenant_id).await?;
+
+    let mut summary = MatchRunSummary::default();
+    // Counterparts already collapsed into a transfer during this run
+    let mut collapsed: HashSet<Uuid> = HashSet::new();
+    for row in rows {
+        let row = PendingRow {
+            id: row.id,
+            external_account_id: row.external_account_id,
+            account_id: row.account_id,
+            provider_transaction_id: row.provider_transaction_id,
+            description: row.description,
+            amount: row.amount,
+            transaction_date: row.transaction_date,
+            bank_date: row.bank_date,
+        };
+        summary.staged_rows_examined += 1;
+        if collapsed.contains(&row.id) {
+            continue;
+        }
+
+        if flag_if_duplicate(pool, &row, user_id).await? {
+            summary.duplicates_flagged += 1;
+            continue;
+        }
+        if let Some(counterpart_id) = collapse_if_transfer(pool, tenant_id, &row, user_id).await? {
+            collapsed.insert(counterpart_id);
+            summary.transfers_collapsed += 1;
+            continue;
+        }
+        summary.proposals_created +=
+            propose_for_row(pool, tenant_id, &row, text_key.as_ref(), user_id).await?;
+    }
+
+    Ok(summary)
+}
+
+/// Marks a staged row as DUPLICATE when the same account already has a handled row with the
+/// same amount, date and description under a different provider ID (e.g., a pending
+/// transaction re-delivered as posted).
+async fn flag_if_duplicate(
+    pool: &PgPool,
+    row: &PendingRow,
+    user_id: Uuid,
+) -> Result<bool, AppError> {
+    let handled = sqlx::query!(
+        r#"
+        SELECT description
+        FROM external_transactions_staging
+        WHERE external_account_id = $1
+          AND provider_transaction_id <> $2
+          AND amount = $3
+          AND transaction_date = $4
+          AND status IN ('CONVERTED', 'MATCHED_MANUALLY')
+        "#,
+        row.external_account_id,
+        row.provider_transaction_id,
+        row.amount,
+        row.transaction_date
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let own_tokens = description_tokens(&row.description);
+    if !handled
+        .iter()
+        .any(|h| description_tokens(&h.description) == own_tokens)
+    {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE external_transactions_staging
+        SET status = 'DUPLICATE', updated_at = NOW(), updated_by = $2
+        WHERE id = $1 AND status = 'PENDING_REVIEW'
+        "#,
+        row.id,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(true)
+}
+
+/// Collapses a staged row and its mirror image on another of the tenant's linked accounts
+/// (checking -> savings, say) into one TRANSFER transaction, linking both rows to it. Returns
+/// the counterpart's staging ID when it did.
+///
+/// Only an unambiguous pair is collapsed: a single opposite row of the same amount, in the
+/// same currency, nearest in date. Rows that already match a ledger transaction are left to
+/// the proposals, since the transfer was probably entered by hand.
+async fn collapse_if_transfer(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    row: &PendingRow,
+    user_id: Uuid,
+) -> Result<Option<Uuid>, AppError> {
+    let window = Duration::days(TRANSFER_WINDOW_DAYS);
+    let counterparts = sqlx::query!(
+        r#"
+        SELECT
+            s.id, ea.account_id as "account_id!", s.amount, s.transaction_date,
+            COALESCE(s.posted_date, s.transaction_date) as "bank_date!"
+        FROM external_transactions_staging s
+        JOIN external_accounts ea ON s.external_account_id = ea.id
+        JOIN ext_conns c ON ea.ext_conn_id = c.id
+        JOIN accounts a ON ea.account_id = a.id
+        JOIN accounts own ON own.id = $2
+        WHERE c.tenant_id = $1
+          AND s.status = 'PENDING_REVIEW'
+          AND ea.account_id <> $2
+          AND a.currency_code = own.currency_code
+          AND s.amount = $3
+          AND s.transaction_date BETWEEN $4 AND $5
+        "#,
+        tenant_id,
+        row.account_id,
+        -row.amount,
+        row.transaction_date - window,
+        row.transaction_date + window
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let days_apart = |date: NaiveDate| (date - row.transaction_date).num_days().abs();
+    let Some(nearest) = counterparts
+        .iter()
+        .map(|c| days_apart(c.transaction_date))
+        .min()
+    else {
+        return Ok(None);
+    };
+    let mut nearest = counterparts
+        .into_iter()
+        .filter(|c| days_apart(c.transaction_date) == nearest);
+    let (Some(counterpart), None) = (nearest.next(), nearest.next()) else {
+        return Ok(None);
+    };
+
+    if has_ledger_candidate(
+        pool,
+        tenant_id,
+        row.account_id,
+        row.amount,
+        row.transaction_date,
+    )
+    .await?
+        || has_ledger_candidate(
+            pool,
+            tenant_id,
+            counterpart.account_id,
+            counterpart.amount,
+            counterpart.transaction_date,
+        )
+        .await?
+    {
+        return Ok(None);
+    }
+
+    let own = TransferSide {
+        staging_id: row.id,
+        account_id: row.account_id,
+        transaction_date: row.transaction_date,
+        bank_date: row.bank_date,
+    };
+    let other = TransferSide {
+        staging_id: counterpart.id,
+        account_id: counterpart.account_id,
+        transaction_date: counterpart.transaction_date,
+        bank_date: counterpart.bank_date,
+    };
+    // Money leaves the account whose row is negative
+    let (from, to) = if row.amount.is_sign_negative() {
+        (own, other)
+    } else {
+        (other, own)
+    };
+    let amount = row.amount.abs();
+
+    let collapsed = with_retry(pool, |pool| {
+        collapse_transfer_once(pool, tenant_id, from, to, amount, user_id)
+    })
+    .await?;
+    Ok(collapsed.then_some(counterpart.id))
+}
+
+/// Whether an unlinked ledger transaction on the account could be the staged movement.
+async fn has_ledger_candidate(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    account_id: Uuid,
+    amount: Decimal,
+    transaction_date: NaiveDate,
+) -> Result<bool, AppError> {
+    let entry_type = if amount.is_sign_negative() {
+        JournalEntryType::Credit
+    } else {
+        JournalEntryType::Debit
+    };
+    let window = Duration::days(MATCH_WINDOW_DAYS);
+
+    let exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM transactions t
+            JOIN journal_entries je ON je.transaction_id = t.id
+            WHERE t.tenant_id = $1
+              AND je.account_id = $2
+              AND je.entry_type = $3
+              AND COALESCE(je.converted_amount, je.amount) = $4
+              AND t.transaction_date BETWEEN $5 AND $6
+              AND t.status <> 'VOIDED'
+              AND NOT EXISTS (SELECT 1 FROM external_transactions_staging s WHERE s.tx_id = t.id)
+        ) as "exists!"
+        "#,
+        tenant_id,
+        account_id,
+        entry_type as JournalEntryType,
+        amount.abs(),
+        transaction_date - window,
+        transaction_date + window
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(exists)
+}
+
+/// One attempt at booking a detected transfer and linking both staged rows to it, in its own
+/// database transaction. Returns false when either row was handled in the meantime.
+///
+/// The transfer is created as a draft dated when the money left, and reconciled as of the
+/// later bank date since both accounts' statements show it.
+async fn collapse_transfer_once(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    from: TransferSide,
+    to: TransferSide,
+    amount: Decimal,
+    user_id: Uuid,
+) -> Result<bool, AppError> {
+    let mut create = transfer::transfer_transaction(
+        pool,
+        tenant_id,
+        CreateTransferDto {
+            transaction_date: from.transaction_date,
+            description: None,
+            from_account_id: from.account_id,
+            to_account_id: to.account_id,
+            amount,
+            to_amount: None,
+            status: None,
+            notes: None,
+        },
+    )
+    .await?;
+    create.is_reconciled = Some(true);
+    create.reconciliation_date = Some(from.bank_date.max(to.bank_date));
+
+    let staging_ids = [from.staging_id, to.staging_id];
+    let mut db_tx = begin_financial(pool).await?;
+
+    let pending = sqlx::query_scalar!(
+        r#"
+        SELECT id
+        FROM external_transactions_staging
+        WHERE id = ANY($1) AND status = 'PENDING_REVIEW'
+        FOR UPDATE
+        "#,
+        &staging_ids
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+    if pending.len() != staging_ids.len() {
+        return Ok(false);
+    }
+
+    let (transfer, _) =
+        transaction::insert_transaction(&mut db_tx, tenant_id, user_id, create).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE external_transactions_staging
+        SET status = 'CONVERTED', tx_id = $2, updated_at = NOW(), updated_by = $3
+        WHERE id = ANY($1)
+        "#,
+        &staging_ids,
+        transfer.id,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    // Proposals from earlier runs no longer apply
+    sqlx::query!(
+        r#"
+        UPDATE transaction_match_proposals
+        SET status = 'REJECTED', updated_at = NOW(), updated_by = $2
+        WHERE staging_id = ANY($1) AND status = 'PROPOSED'
+        "#,
+        &staging_ids,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    db_tx.commit().await?;
+    info!(
+        "Service: Collapsed staged rows {} and {} into transfer {}",
+        from.staging_id, to.staging_id, transfer.id
+    );
+    Ok(true)
+}
+
+/// Scores unmatched ledger transactions on the linked account and stores the best proposals.
+async fn propose_for_row(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    row: &PendingRow,
+    text_key: Option<&TextKey>,
+    user_id: Uuid,
+) -> Result<usize, AppError> {
+    // Money leaving the bank is a credit to the (asset) bank account, and vice versa.
+    let entry_type = if row.amount.is_sign_negative() {
+        JournalEntryType::Credit
+    } else {
+        JournalEntryType::Debit
+    };
+    let window = Duration::days(MATCH_WINDOW_DAYS);
+
+    let candidates = sqlx::query!(
+        r#"
+        SELECT DISTINCT t.id, t.transaction_date, t.description
+        FROM transactions t
+        JOIN journal_entries je ON je.transaction_id = t.id
+        WHERE t.tenant_id = $1
+          AND je.account_id = $2
+          AND je.entry_type = $3
+          AND COALESCE(je.converted_amount, je.amount) = $4
+          AND t.transaction_date BETWEEN $5 AND $6
+          AND t.status = 'POSTED'
+          AND NOT EXISTS (SELECT 1 FROM external_transactions_staging s WHERE s.tx_id = t.id)
+        "#,
+        tenant_id,
+        row.account_id,
+        entry_type as JournalEntryType,
+        row.amount.abs(),
+        row.transaction_date - window,
+        row.transaction_date + window
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut scored: Vec<(Uuid, f64, serde_json::Value)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            let days_apart = (candidate.transaction_date - row.transaction_date)
+                .num_days()
+                .abs();
+            let date_score = 1.0 - days_apart as f64 / (MATCH_WINDOW_DAYS + 1) as f64;
+            let candidate_description = match text_key {
+                Some(key) => privacy::unseal(key, &candidate.description).unwrap_or_default(),
+                None => candidate.description,
+            };
+            let description_score =
+                description_similarity(&row.description, &candidate_description);
+            let score =
+                AMOUNT_WEIGHT + DATE_WEIGHT * date_score + DESCRIPTION_WEIGHT * description_score;
+            let reasons = json!({
+                "amount": 1.0,
+                "date": date_score,
+                "description": description_score,
+                "days_apart": days_apart,
+            });
+            (candidate.id, score, reasons)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.truncate(MAX_PROPOSALS_PER_ROW);
+
+    let mut created = 0;
+    for (transaction_id, score, reasons) in scored {
+        let score = Decimal::from_f64(score)
+            .unwrap_or(Decimal::ZERO)
+            .round_dp(4);
+        // Refresh open proposals; accepted/rejected pairs are left untouched.
+        let affected = sqlx::query!(
+            r#"
+            INSERT INTO transaction_match_proposals (
+                tenant_id, staging_id, transaction_id, score, status, reasons, created_by, updated_by
+            )
+            VALUES ($1, $2, $3, $4, 'PROPOSED', $5, $6, $6)
+            ON CONFLICT (staging_id, transaction_id) DO UPDATE SET
+                score = EXCLUDED.score,
+                reasons = EXCLUDED.reasons,
+                updated_at = NOW(),
+                updated_by = EXCLUDED.updated_by
+            WHERE transaction_match_proposals.status = 'PROPOSED'
+            "#,
+            tenant_id,
+            row.id,
+            transaction_id,
+            score,
+            reasons,
+            user_id
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+        created += affected as usize;
+    }
+
+    Ok(created)
+}
+
+/// Lists match proposals for a tenant, optionally limited to one connection.
+pub async fn list_transaction_matches(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    status: Option<MatchStatus>,
+    ext_conn_id: Option<Uuid>,
+) -> Result<Vec<TransactionMatch>, AppError> {
+    info!(
+        "Service: Listing transaction match proposals for tenant ID: {}",
+        tenant_id
+    );
+
+    let status = String::from(status.unwrap_or(MatchStatus::Proposed));
+    let matches = query_as!(
+        TransactionMatch,
+        r#"
+        SELECT
+            m.id, m.tenant_id, m.staging_id, m.transaction_id, m.score, m.status, m.reasons,
+            m.created_at, m.created_by, m.updated_at, m.updated_by
+        FROM transaction_match_proposals m
+        JOIN external_transactions_staging s ON m.staging_id = s.id
+        JOIN external_accounts ea ON s.external_account_id = ea.id
+        WHERE m.tenant_id = $1 AND m.status = $2 AND ($3::uuid IS NULL OR ea.ext_conn_id = $3)
+        ORDER BY m.staging_id, m.score DESC
+        "#,
+        tenant_id,
+        status,
+        ext_conn_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(matches)
+}
+
+/// Accepts a proposal: links the staged row to the transaction, marks the transaction
+/// reconciled, and closes every other open proposal for either side.
+pub async fn accept_transaction_match(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    match_id: Uuid,
+    user_id: Uuid,
+) -> Result<TransactionMatch, AppError> {
+    info!(
+        "Service: Accepting transaction match {} for tenant ID: {}",
+        match_id, tenant_id
+    );
+    with_retry(pool, |pool| {
+        accept_match_once(pool, tenant_id, match_id, user_id)
+    })
+    .await
+}
+
+/// One attempt at accepting a proposal, in its own database transaction.
+async fn accept_match_once(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    match_id: Uuid,
+    user_id: Uuid,
+) -> Result<TransactionMatch, AppError> {
+    let mut db_tx = begin_financial(pool).await?;
+
+    let proposal = sqlx::query!(
+        r#"
+        SELECT m.staging_id, m.transaction_id, m.status, s.status as staging_status,
+               COALESCE(s.posted_date, s.transaction_date) as "bank_date!"
+        FROM transaction_match_proposals m
+        JOIN external_transactions_staging s ON m.staging_id = s.id
+        WHERE m.id = $1 AND m.tenant_id = $2
+        FOR UPDATE OF m, s
+        "#,
+        match_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Transaction match with ID {} not found for tenant {}",
+            match_id, tenant_id
+        ))
+    })?;
+
+    if proposal.status != String::from(MatchStatus::Proposed) {
+        return Err(AppError::Validation(format!(
+            "Transaction match {} is already {}",
+            match_id, proposal.status
+        )));
+    }
+    if proposal.staging_status != "PENDING_REVIEW" {
+        return Err(AppError::Validation(format!(
+            "Staged transaction {} is already {}",
+            proposal.staging_id, proposal.staging_status
+        )));
+    }
+
+    let already_linked = sqlx::query!(
+        "SELECT EXISTS(SELECT 1 FROM external_transactions_staging WHERE tx_id = $1)",
+        proposal.transaction_id
+    )
+    .fetch_one(&mut *db_tx)
+    .await?
+    .exists
+    .unwrap_or(false);
+    if already_linked {
+        return Err(AppError::Validation(format!(
+            "Transaction {} is already matched to another bank transaction",
+            proposal.transaction_id
+        )));
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE external_transactions_staging
+        SET status = 'MATCHED_MANUALLY', tx_id = $2, updated_at = NOW(), updated_by = $3
+        WHERE id = $1
+        "#,
+        proposal.staging_id,
+        proposal.transaction_id,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE transactions
+        SET is_reconciled = TRUE, reconciliation_date = $2, updated_at = NOW(), updated_by = $3,
+            version = version + 1
+        WHERE id = $1
+        "#,
+        proposal.transaction_id,
+        proposal.bank_date,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE transaction_match_proposals
+        SET status = 'REJECTED', updated_at = NOW(), updated_by = $4
+        WHERE id <> $1 AND status = 'PROPOSED' AND (staging_id = $2 OR transaction_id = $3)
+        "#,
+        match_id,
+        proposal.staging_id,
+        proposal.transaction_id,
+        user_id
+    )
+    .execute(&mut *db_tx)
+    .await?;
+
+    let accepted = set_match_status(&mut db_tx, match_id, MatchStatus::Accepted, user_id).await?;
+
+    db_tx.commit().await?;
+    Ok(accepted)
+}
+
+/// Rejects a proposal so the pair is never proposed again.
+pub async fn reject_transaction_match(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    match_id: Uuid,
+    user_id: Uuid,
+) -> Result<TransactionMatch, AppError> {
+    info!(
+        "Service: Rejecting transaction match {} for tenant ID: {}",
+        match_id, tenant_id
+    );
+
+    let mut db_tx = pool.begin().await?;
+
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM transaction_match_proposals WHERE id = $1 AND tenant_id = $2 FOR UPDATE",
+        match_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *db_tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Transaction match with ID {} not found for tenant {}", match_id, tenant_id)))?;
+
+    if status != String::from(MatchStatus::Proposed) {
+        return Err(AppError::Validation(format!(
+            "Transaction match {} is already {}",
+            match_id, status
+        )));
+    }
+
+    let rejected = set_match_status(&mut db_tx, match_id, MatchStatus::Rejected, user_id).await?;
+
+    db_tx.commit().await?;
+    Ok(rejected)
+}
+
+async fn set_match_status(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    match_id: Uuid,
+    status: MatchStatus,
+    user_id: Uuid,
+) -> Result<TransactionMatch, AppError> {
+    let updated = query_as!(
+        TransactionMatch,
+        r#"
+        UPDATE transaction_match_proposals
+        SET status = $2, updated_at = NOW(), updated_by = $3
+        WHERE id = $1
+        RETURNING
+            id, tenant_id, staging_id, transaction_id, score, status, reasons,
+            created_at, created_by, updated_at, updated_by
+        "#,
+        match_id,
+        String::from(status),
+        user_id
+    )
+    .fetch_one(&mut **db_tx)
+    .await?;
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::description_similarity;
+
+    #[test]
+    fn identical_descriptions_match_fully() {
+        assert_eq!(
+            description_similarity("Coffee Roasters", "coffee roasters"),
+            1.0
+        );
+    }
+
+    #[test]
+    fn shared_tokens_score_partially() {
+        // {acme, payroll} against {acme, payroll, june}
+        let score = description_similarity("ACME Payroll", "acme payroll - june");
+        assert!((score - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn card_suffixes_and_punctuation_are_ignored() {
+        assert_eq!(
+            description_similarity("AMZN Mktp *1234", "amzn mktp 9876"),
+            1.0
+        );
+    }
+
+    #[test]
+    fn empty_or_unrelated_descriptions_score_zero() {
+        assert_eq!(description_similarity("", "rent"), 0.0);
+        assert_eq!(description_similarity("1234 5678", "1234 5678"), 0.0);
+        assert_eq!(description_similarity("rent", "groceries"), 0.0);
+    }
+}
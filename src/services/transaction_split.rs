@@ -0,0 +1,669 @@
+//! Receipt splitting: one receipt total shared across categories by percentage or amount,
+//! booked as a single balanced transaction with one journal entry per share. A transaction
+//! created with `splits` is booked the same way, with the share amounts given directly.
+//!
+//! Shares are rounded down to the cent and the leftover cents go, one at a time, to the
+//! shares that lost the most in rounding (earlier shares first on ties), so the entries
+//! always add up to the receipt total and the same request always yields the same split.
+
+use std::collections::HashMap;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{PgExecutor, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::journal_entry_dto::CreateJournalEntryDto,
+        dto::transaction_dto::{
+            CreateTransactionDto, CreateTransactionSplitDto, ReceiptSplitDto, SplitReceiptDto,
+        },
+        journal_entry::JournalEntryType,
+        transaction::TransactionType,
+        transaction_split::{SplitTransaction, TransactionSplit},
+    },
+    services::transaction,
+};
+
+/// Decimal places of transaction and journal entry amounts.
+const AMOUNT_SCALE: u32 = 2;
+
+/// Creates the transaction for a receipt split across categories: one entry per share on
+/// its account, balanced by a single entry for the total on the payment account.
+pub async fn split_receipt(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: SplitReceiptDto,
+) -> Result<SplitTransaction, AppError> {
+    info!(
+        "Service: Splitting receipt of {} {} across {} shares for tenant ID {}",
+        dto.amount,
+        dto.currency_code,
+        dto.splits.len(),
+        tenant_id
+    );
+
+    let r#type = dto.r#type.unwrap_or(TransactionType::Expense);
+    let (share_entry_type, payment_entry_type) = split_entry_types(r#type)?;
+    ensure_distinct_shares(
+        dto.splits
+            .iter()
+            .map(|split| (split.account_id, split.category_id)),
+    )?;
+    let amounts = split_amounts(dto.amount, &dto.splits)?;
+
+    // Every category must be one of the tenant's; their names are the default memos
+    let category_ids: Vec<Uuid> = dto.splits.iter().map(|split| split.category_id).collect();
+    let category_names = split_category_names(pool, tenant_id, &category_ids).await?;
+    let category_id = common_category(&category_ids);
+
+    let mut journal_entries: Vec<CreateJournalEntryDto> = dto
+        .splits
+        .iter()
+        .zip(&amounts)
+        .map(|(split, amount)| CreateJournalEntryDto {
+            account_id: split.account_id,
+            entry_type: share_entry_type,
+            amount: *amount,
+            currency_code: dto.currency_code.clone(),
+            exchange_rate: None,
+            converted_amount: None,
+            memo: split
+                .memo
+                .clone()
+                .or_else(|| category_names.get(&split.category_id).cloned()),
+            project_id: None,
+            class_id: None,
+            location_id: None,
+        })
+        .collect();
+    journal_entries.push(CreateJournalEntryDto {
+        account_id: dto.payment_account_id,
+        entry_type: payment_entry_type,
+        amount: dto.amount,
+        currency_code: dto.currency_code.clone(),
+        exchange_rate: None,
+        converted_amount: None,
+        memo: None,
+        project_id: None,
+        class_id: None,
+        location_id: None,
+    });
+
+    let create = CreateTransactionDto {
+        transaction_date: dto.transaction_date,
+        description: dto.description,
+        r#type,
+        category_id,
+        payee_id: dto.payee_id,
+        tags: None,
+        amount: dto.amount,
+        currency_code: dto.currency_code,
+        is_reconciled: None,
+        reconciliation_date: None,
+        notes: dto.notes,
+        source_document_url: dto.source_document_url,
+        status: dto.status,
+        journal_entries,
+        payment_account_id: None,
+        splits: Vec::new(),
+        metadata: None,
+        attribution: None,
+    };
+
+    let mut db_tx = pool.begin().await?;
+    let (new_transaction, journal_entry_ids) =
+        transaction::insert_transaction(&mut db_tx, tenant_id, created_by_user_id, create).await?;
+
+    let mut splits = Vec::with_capacity(dto.splits.len());
+    for (position, ((split, amount), journal_entry_id)) in dto
+        .splits
+        .iter()
+        .zip(amounts)
+        .zip(journal_entry_ids)
+        .enumerate()
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO transaction_splits (journal_entry_id, transaction_id, category_id, percentage, position)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            journal_entry_id,
+            new_transaction.id,
+            split.category_id,
+            split.percentage,
+            position as i32
+        )
+        .execute(&mut *db_tx)
+        .await?;
+
+        splits.push(TransactionSplit {
+            journal_entry_id,
+            category_id: split.category_id,
+            account_id: split.account_id,
+            percentage: split.percentage,
+            amount,
+        });
+    }
+
+    db_tx.commit().await?;
+
+    Ok(SplitTransaction {
+        transaction: new_transaction,
+        splits,
+    })
+}
+
+/// Turns the `splits` of a new transaction into its journal entries: one entry per split on
+/// its account, balanced by an entry for the amount on the payment account. The splits must
+/// add up exactly to the transaction amount. Called by `transaction::insert_transaction`,
+/// which records the splits with `record_splits` once the entries exist.
+pub async fn prepare_split_entries<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    dto: &mut CreateTransactionDto,
+) -> Result<(), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    if !dto.journal_entries.is_empty() {
+        return Err(AppError::Validation(
+            "Give either journal_entries or splits, not both".to_string(),
+        ));
+    }
+    let payment_account_id = dto.payment_account_id.ok_or_else(|| {
+        AppError::Validation("payment_account_id is required with splits".to_string())
+    })?;
+    let (share_entry_type, payment_entry_type) = split_entry_types(dto.r#type)?;
+    ensure_distinct_shares(
+        dto.splits
+            .iter()
+            .map(|split| (split.account_id, split.category_id)),
+    )?;
+
+    if let Some(index) = dto
+        .splits
+        .iter()
+        .position(|split| split.amount.round_dp(AMOUNT_SCALE) != split.amount)
+    {
+        return Err(AppError::Validation(format!(
+            "splits[{}] must have at most two decimals",
+            index
+        )));
+    }
+    let split_total: Decimal = dto.splits.iter().map(|split| split.amount).sum();
+    if split_total != dto.amount {
+        return Err(AppError::Validation(format!(
+            "The splits add up to {} but the transaction amount is {}",
+            split_total, dto.amount
+        )));
+    }
+
+    let category_ids: Vec<Uuid> = dto.splits.iter().map(|split| split.category_id).collect();
+    let category_names = split_category_names(executor, tenant_id, &category_ids).await?;
+    if dto.category_id.is_none() {
+        dto.category_id = common_category(&category_ids);
+    }
+
+    let mut journal_entries: Vec<CreateJournalEntryDto> = dto
+        .splits
+        .iter()
+        .map(|split| CreateJournalEntryDto {
+            account_id: split.account_id,
+            entry_type: share_entry_type,
+            amount: split.amount,
+            currency_code: dto.currency_code.clone(),
+            exchange_rate: None,
+            converted_amount: None,
+            memo: split
+                .memo
+                .clone()
+                .or_else(|| category_names.get(&split.category_id).cloned()),
+            project_id: None,
+            class_id: None,
+            location_id: None,
+        })
+        .collect();
+    journal_entries.push(CreateJournalEntryDto {
+        account_id: payment_account_id,
+        entry_type: payment_entry_type,
+        amount: dto.amount,
+        currency_code: dto.currency_code.clone(),
+        exchange_rate: None,
+        converted_amount: None,
+        memo: None,
+        project_id: None,
+        class_id: None,
+        location_id: None,
+    });
+    dto.journal_entries = journal_entries;
+    Ok(())
+}
+
+/// Records the splits of a transaction created by `prepare_split_entries`, against the
+/// journal entries generated for them (which come first, in split order).
+pub async fn record_splits<'e, E>(
+    executor: E,
+    transaction_id: Uuid,
+    splits: &[CreateTransactionSplitDto],
+    journal_entry_ids: &[Uuid],
+) -> Result<(), AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let entry_ids = &journal_entry_ids[..splits.len()];
+    let category_ids: Vec<Uuid> = splits.iter().map(|split| split.category_id).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO transaction_splits (journal_entry_id, transaction_id, category_id, position)
+        SELECT s.journal_entry_id, $1, s.category_id, (s.position - 1)::int
+        FROM UNNEST($2::uuid[], $3::uuid[]) WITH ORDINALITY AS s(journal_entry_id, category_id, position)
+        "#,
+        transaction_id,
+        entry_ids,
+        &category_ids
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Rejects a share that books to the same account and category as an earlier one. Shares
+/// on one account under different categories are fine: each gets its own journal entry.
+fn ensure_distinct_shares(shares: impl Iterator<Item = (Uuid, Uuid)>) -> Result<(), AppError> {
+    let mut seen: HashMap<(Uuid, Uuid), usize> = HashMap::new();
+    for (index, share) in shares.enumerate() {
+        if let Some(earlier) = seen.insert(share, index) {
+            return Err(AppError::Validation(format!(
+                "splits[{}] has the same account and category as splits[{}]; combine them into one share",
+                index, earlier
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Journal entry types of the shares and of the balancing payment entry.
+fn split_entry_types(
+    r#type: TransactionType,
+) -> Result<(JournalEntryType, JournalEntryType), AppError> {
+    match r#type {
+        TransactionType::Expense => Ok((JournalEntryType::Debit, JournalEntryType::Credit)),
+        TransactionType::Income => Ok((JournalEntryType::Credit, JournalEntryType::Debit)),
+        _ => Err(AppError::Validation(
+            "Only EXPENSE and INCOME transactions can be split".to_string(),
+        )),
+    }
+}
+
+/// Names of the split categories, which must all be the tenant's and assignable.
+async fn split_category_names<'e, E>(
+    executor: E,
+    tenant_id: Uuid,
+    category_ids: &[Uuid],
+) -> Result<HashMap<Uuid, String>, AppError>
+where
+    E: PgExecutor<'e>,
+{
+    let category_names: HashMap<Uuid, String> = sqlx::query!(
+        "SELECT id, name FROM categories WHERE tenant_id = $1 AND is_active = TRUE AND archived_at IS NULL AND id = ANY($2)",
+        tenant_id,
+        category_ids
+    )
+    .fetch_all(executor)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, row.name))
+    .collect();
+    if let Some(missing) = category_ids
+        .iter()
+        .find(|id| !category_names.contains_key(id))
+    {
+        return Err(AppError::NotFound(format!(
+            "Category with ID {} not found for tenant {}",
+            missing, tenant_id
+        )));
+    }
+    Ok(category_names)
+}
+
+/// The transaction itself only carries a category when every share has the same one.
+fn common_category(category_ids: &[Uuid]) -> Option<Uuid> {
+    let first = *category_ids.first()?;
+    category_ids.iter().all(|id| *id == first).then_some(first)
+}
+
+/// Turns the requested shares into cent amounts that add up exactly to `total`.
+///
+/// The requested shares may miss the total by up to one cent per share (e.g. three times
+/// 33.33%); anything further off is rejected.
+fn split_amounts(total: Decimal, splits: &[ReceiptSplitDto]) -> Result<Vec<Decimal>, AppError> {
+    if total <= Decimal::ZERO || total.round_dp(AMOUNT_SCALE) != total {
+        return Err(AppError::Validation(
+            "The receipt amount must be positive with at most two decimals".to_string(),
+        ));
+    }
+
+    let mut raw = Vec::with_capacity(splits.len());
+    for (index, split) in splits.iter().enumerate() {
+        let share = match (split.percentage, split.amount) {
+            (Some(percentage), None) if percentage > Decimal::ZERO && percentage <= Decimal::ONE_HUNDRED => {
+                total * percentage / Decimal::ONE_HUNDRED
+            }
+            (None, Some(amount)) if amount > Decimal::ZERO && amount.round_dp(AMOUNT_SCALE) == amount => amount,
+            (Some(_), Some(_)) | (None, None) => {
+                return Err(AppError::Validation(format!(
+                    "splits[{}] needs exactly one of percentage or amount",
+                    index
+                )))
+            }
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "splits[{}] must be a percentage above 0 and at most 100, or a positive amount with at most two decimals",
+                    index
+                )))
+            }
+        };
+        raw.push(share);
+    }
+
+    let cent = Decimal::new(1, AMOUNT_SCALE);
+    let requested: Decimal = raw.iter().sum();
+    if (requested - total).abs() > cent * Decimal::from(splits.len()) {
+        return Err(AppError::Validation(format!(
+            "The splits add up to {} but the receipt total is {}",
+            requested.round_dp(AMOUNT_SCALE),
+            total
+        )));
+    }
+
+    let mut shares: Vec<Decimal> = raw
+        .iter()
+        .map(|share| share.round_dp_with_strategy(AMOUNT_SCALE, RoundingStrategy::ToZero))
+        .collect();
+
+    // Leftover cents go to the shares that lost the most in rounding, earlier shares first
+    // on ties; surplus cents are taken back in the opposite order
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|&a, &b| {
+        (raw[b] - shares[b])
+            .cmp(&(raw[a] - shares[a]))
+            .then(a.cmp(&b))
+    });
+    let mut remainder = total - shares.iter().sum::<Decimal>();
+    let step = if remainder > Decimal::ZERO {
+        cent
+    } else {
+        -cent
+    };
+    if step < Decimal::ZERO {
+        order.reverse();
+    }
+    for index in order.iter().cycle() {
+        if remainder.is_zero() {
+            break;
+        }
+        shares[*index] += step;
+        remainder -= step;
+    }
+
+    if let Some(index) = shares.iter().position(|share| *share <= Decimal::ZERO) {
+        return Err(AppError::Validation(format!(
+            "splits[{}] rounds to nothing; give it a larger share",
+            index
+        )));
+    }
+    Ok(shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::{ensure_distinct_shares, split_receipt};
+    use crate::{
+        models::{
+            dto::transaction_dto::{
+                CreateTransactionDto, CreateTransactionSplitDto, ReceiptSplitDto, SplitReceiptDto,
+            },
+            transaction::{TransactionStatus, TransactionType},
+        },
+        services::transaction::create_transaction,
+    };
+
+    /// A tenant with a cash account, an expense account and two expense categories.
+    struct Books {
+        tenant_id: Uuid,
+        user_id: Uuid,
+        cash_id: Uuid,
+        expense_id: Uuid,
+        category_ids: [Uuid; 2],
+    }
+
+    async fn seed(pool: &PgPool) -> Books {
+        let run = Uuid::new_v4();
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+             VALUES ($1, 'EMAIL_PASSWORD', $1, 'Split', 'Tester') RETURNING id",
+        )
+        .bind(format!("splits-{}@example.com", run))
+        .fetch_one(pool)
+        .await
+        .expect("insert user");
+        sqlx::query(
+            "INSERT INTO currencies (code, name, symbol, created_by, updated_by)
+             VALUES ('USD', 'US Dollar', '$', $1, $1) ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .expect("insert currency");
+        let tenant_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO tenants (name, base_currency_code, fiscal_year_end_month, created_by, updated_by)
+             VALUES ($1, 'USD', 12, $2, $2) RETURNING id",
+        )
+        .bind(format!("Splits {}", run))
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .expect("insert tenant");
+
+        let mut account_ids = Vec::new();
+        for (type_name, account_name) in [("Asset", "Cash"), ("Expense", "Household")] {
+            let account_type_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO account_types (name, normal_balance, created_by, updated_by)
+                 VALUES ($1, 'DEBIT', $2, $2)
+                 ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING id",
+            )
+            .bind(type_name)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .expect("insert account type");
+            let account_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO accounts (tenant_id, account_type_id, name, currency_code, created_by, updated_by)
+                 VALUES ($1, $2, $3, 'USD', $4, $4) RETURNING id",
+            )
+            .bind(tenant_id)
+            .bind(account_type_id)
+            .bind(account_name)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .expect("insert account");
+            account_ids.push(account_id);
+        }
+
+        let mut category_ids = [Uuid::nil(); 2];
+        for (category_id, name) in category_ids.iter_mut().zip(["Groceries", "Cleaning"]) {
+            *category_id = sqlx::query_scalar(
+                "INSERT INTO categories (tenant_id, name, type, created_by, updated_by)
+                 VALUES ($1, $2, 'EXPENSE', $3, $3) RETURNING id",
+            )
+            .bind(tenant_id)
+            .bind(name)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .expect("insert category");
+        }
+
+        Books {
+            tenant_id,
+            user_id,
+            cash_id: account_ids[0],
+            expense_id: account_ids[1],
+            category_ids,
+        }
+    }
+
+    fn money(amount: &str) -> Decimal {
+        amount.parse().unwrap()
+    }
+
+    #[test]
+    fn shares_may_repeat_an_account_but_not_account_and_category() {
+        let (account, groceries, cleaning) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        assert!(
+            ensure_distinct_shares([(account, groceries), (account, cleaning)].into_iter()).is_ok()
+        );
+        let repeated = ensure_distinct_shares(
+            [
+                (account, groceries),
+                (account, cleaning),
+                (account, groceries),
+            ]
+            .into_iter(),
+        )
+        .unwrap_err();
+        assert!(repeated.to_string().contains("splits[2]"), "{}", repeated);
+        assert!(repeated.to_string().contains("splits[0]"), "{}", repeated);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn receipt_shares_can_book_to_the_same_account() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPool::connect(&url)
+            .await
+            .expect("connect to DATABASE_URL");
+        let books = seed(&pool).await;
+
+        let share = |category_id, percentage: &str| ReceiptSplitDto {
+            category_id,
+            account_id: books.expense_id,
+            percentage: Some(money(percentage)),
+            amount: None,
+            memo: None,
+        };
+        let split = split_receipt(
+            &pool,
+            books.tenant_id,
+            books.user_id,
+            SplitReceiptDto {
+                transaction_date: chrono::Utc::now().date_naive(),
+                description: "Supermarket".to_string(),
+                r#type: None,
+                payee_id: None,
+                amount: money("80.00"),
+                currency_code: "USD".to_string(),
+                payment_account_id: books.cash_id,
+                status: Some(TransactionStatus::Posted),
+                notes: None,
+                source_document_url: None,
+                splits: vec![
+                    share(books.category_ids[0], "75"),
+                    share(books.category_ids[1], "25"),
+                ],
+            },
+        )
+        .await
+        .expect("split receipt");
+
+        let amounts: Vec<Decimal> = split.splits.iter().map(|share| share.amount).collect();
+        assert_eq!(amounts, [money("60.00"), money("20.00")]);
+        let debits: Vec<Decimal> = sqlx::query_scalar(
+            "SELECT amount FROM journal_entries
+             WHERE transaction_id = $1 AND account_id = $2 AND entry_type = 'DEBIT'
+             ORDER BY amount DESC",
+        )
+        .bind(split.transaction.id)
+        .bind(books.expense_id)
+        .fetch_all(&pool)
+        .await
+        .expect("load entries");
+        assert_eq!(debits, amounts);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn transaction_splits_can_book_to_the_same_account() {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPool::connect(&url)
+            .await
+            .expect("connect to DATABASE_URL");
+        let books = seed(&pool).await;
+
+        let split = |category_id, amount: &str| CreateTransactionSplitDto {
+            category_id,
+            account_id: books.expense_id,
+            amount: money(amount),
+            memo: None,
+        };
+        let transaction = create_transaction(
+            &pool,
+            books.tenant_id,
+            books.user_id,
+            CreateTransactionDto {
+                transaction_date: chrono::Utc::now().date_naive(),
+                description: "Supermarket".to_string(),
+                r#type: TransactionType::Expense,
+                category_id: None,
+                payee_id: None,
+                tags: None,
+                amount: money("45.50"),
+                currency_code: "USD".to_string(),
+                is_reconciled: None,
+                reconciliation_date: None,
+                notes: None,
+                source_document_url: None,
+                status: Some(TransactionStatus::Posted),
+                journal_entries: Vec::new(),
+                payment_account_id: Some(books.cash_id),
+                splits: vec![
+                    split(books.category_ids[0], "30.00"),
+                    split(books.category_ids[1], "15.50"),
+                ],
+                metadata: None,
+                attribution: None,
+            },
+        )
+        .await
+        .expect("create split transaction");
+
+        let shares: Vec<(Uuid, Decimal)> = sqlx::query_as(
+            "SELECT ts.category_id, je.amount
+             FROM transaction_splits ts
+             JOIN journal_entries je ON je.id = ts.journal_entry_id
+             WHERE ts.transaction_id = $1 AND je.account_id = $2
+             ORDER BY ts.position",
+        )
+        .bind(transaction.id)
+        .bind(books.expense_id)
+        .fetch_all(&pool)
+        .await
+        .expect("load splits");
+        assert_eq!(
+            shares,
+            [
+                (books.category_ids[0], money("30.00")),
+                (books.category_ids[1], money("15.50"))
+            ]
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! Transfers between two of the tenant's accounts, booked as one TRANSFER transaction whose
+//! debit and credit are generated rather than sent by the client.
+//!
+//! Each side is booked in its account's currency. Across currencies the received amount is
+//! the one given (what the bank actually credited) or else converted at the stored rate, and
+//! both sides carry the same base-currency value, so the pair always balances.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        dto::journal_entry_dto::CreateJournalEntryDto,
+        dto::transaction_dto::{CreateTransactionDto, CreateTransferDto},
+        journal_entry::JournalEntryType,
+        transaction::{Transaction, TransactionType},
+    },
+    services::{currency_conversion, transaction},
+};
+
+/// Decimal places of journal entry amounts.
+const AMOUNT_SCALE: u32 = 2;
+
+/// Creates a transfer: the source account is credited with the amount and the destination
+/// debited with the amount received.
+pub async fn create_transfer(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateTransferDto,
+) -> Result<Transaction, AppError> {
+    info!(
+        "Service: Creating transfer of {} from account {} to account {} for tenant ID {}",
+        dto.amount, dto.from_account_id, dto.to_account_id, tenant_id
+    );
+
+    let create = transfer_transaction(pool, tenant_id, dto).await?;
+    transaction::create_transaction(pool, tenant_id, created_by_user_id, create).await
+}
+
+/// Validates a transfer and builds the TRANSFER transaction booking it, for callers that
+/// insert it in their own database transaction.
+pub async fn transfer_transaction(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    dto: CreateTransferDto,
+) -> Result<CreateTransactionDto, AppError> {
+    check_transfer(&dto)?;
+
+    let accounts: HashMap<Uuid, (String, String)> = sqlx::query!(
+        r#"
+        SELECT id, name, currency_code FROM accounts
+        WHERE tenant_id = $1 AND id = ANY($2) AND is_active = TRUE AND archived_at IS NULL
+        "#,
+        tenant_id,
+        &[dto.from_account_id, dto.to_account_id]
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, (row.name, row.currency_code)))
+    .collect();
+    let account = |id: Uuid| {
+        accounts.get(&id).ok_or_else(|| {
+            AppError::Validation(format!(
+                "Account ID {} is invalid, inactive or archived for tenant {}",
+                id, tenant_id
+            ))
+        })
+    };
+    let (from_name, from_currency) = account(dto.from_account_id)?;
+    let (to_name, to_currency) = account(dto.to_account_id)?;
+
+    // (amount received, base-currency value of each side when it has to be given)
+    let (to_amount, from_converted, to_converted) = if from_currency == to_currency {
+        if dto
+            .to_amount
+            .is_some_and(|to_amount| to_amount != dto.amount)
+        {
+            return Err(AppError::Validation(
+                "to_amount must equal the amount when both accounts use the same currency"
+                    .to_string(),
+            ));
+        }
+        (dto.amount, None, None)
+    } else {
+        let to_amount = match dto.to_amount {
+            Some(to_amount) => to_amount,
+            None => {
+                currency_conversion::convert(
+                    pool,
+                    tenant_id,
+                    dto.amount,
+                    from_currency,
+                    to_currency,
+                    dto.transaction_date,
+                )
+                .await?
+                .converted_amount
+            }
+        };
+        let from_rate = currency_conversion::base_currency_rate(
+            pool,
+            tenant_id,
+            from_currency,
+            dto.transaction_date,
+        )
+        .await?;
+        let to_rate = currency_conversion::base_currency_rate(
+            pool,
+            tenant_id,
+            to_currency,
+            dto.transaction_date,
+        )
+        .await?;
+        let (from_converted, to_converted) = base_values(dto.amount, to_amount, from_rate, to_rate);
+        (to_amount, from_converted, to_converted)
+    };
+
+    Ok(CreateTransactionDto {
+        transaction_date: dto.transaction_date,
+        description: dto
+            .description
+            .unwrap_or_else(|| format!("Transfer from {} to {}", from_name, to_name)),
+        r#type: TransactionType::Transfer,
+        category_id: None,
+        payee_id: None,
+        tags: None,
+        amount: dto.amount,
+        currency_code: from_currency.to_string(),
+        is_reconciled: None,
+        reconciliation_date: None,
+        notes: dto.notes,
+        source_document_url: None,
+        status: dto.status,
+        journal_entries: vec![
+            CreateJournalEntryDto {
+                account_id: dto.to_account_id,
+                entry_type: JournalEntryType::Debit,
+                amount: to_amount,
+                currency_code: to_currency.to_string(),
+                exchange_rate: None,
+                converted_amount: to_converted,
+                memo: None,
+                project_id: None,
+                class_id: None,
+                location_id: None,
+            },
+            CreateJournalEntryDto {
+                account_id: dto.from_account_id,
+                entry_type: JournalEntryType::Credit,
+                amount: dto.amount,
+                currency_code: from_currency.to_string(),
+                exchange_rate: None,
+                converted_amount: from_converted,
+                memo: None,
+                project_id: None,
+                class_id: None,
+                location_id: None,
+            },
+        ],
+        payment_account_id: None,
+        splits: Vec::new(),
+        metadata: None,
+        attribution: None,
+    })
+}
+
+fn check_transfer(dto: &CreateTransferDto) -> Result<(), AppError> {
+    if dto.from_account_id == dto.to_account_id {
+        return Err(AppError::Validation(
+            "A transfer needs two different accounts".to_string(),
+        ));
+    }
+    if dto.amount.round_dp(AMOUNT_SCALE) != dto.amount {
+        return Err(AppError::Validation(
+            "The amount must have at most two decimals".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The base-currency value of the (source, destination) sides, given each side's rate to the
+/// base currency (`None` when it already is the base currency). Both sides get the same value.
+fn base_values(
+    amount: Decimal,
+    to_amount: Decimal,
+    from_rate: Option<Decimal>,
+    to_rate: Option<Decimal>,
+) -> (Option<Decimal>, Option<Decimal>) {
+    // A side already in the base currency fixes the value; otherwise the source's rate does
+    let base_value = match (from_rate, to_rate) {
+        (None, _) => amount,
+        (_, None) => to_amount,
+        (Some(rate), Some(_)) => (amount * rate).round_dp(AMOUNT_SCALE),
+    };
+    (from_rate.map(|_| base_value), to_rate.map(|_| base_value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(amount: &str) -> Decimal {
+        amount.parse().unwrap()
+    }
+
+    fn transfer(from_account_id: Uuid, to_account_id: Uuid, amount: &str) -> CreateTransferDto {
+        CreateTransferDto {
+            transaction_date: "2025-03-05".parse().unwrap(),
+            description: None,
+            from_account_id,
+            to_account_id,
+            amount: money(amount),
+            to_amount: None,
+            status: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn rejects_transfers_to_the_same_account_and_sub_cent_amounts() {
+        let (checking, savings) = (Uuid::new_v4(), Uuid::new_v4());
+        let cases = [
+            (transfer(checking, savings, "250.00"), None),
+            (transfer(checking, savings, "250"), None),
+            (
+                transfer(checking, checking, "250.00"),
+                Some("A transfer needs two different accounts"),
+            ),
+            (
+                transfer(checking, savings, "250.005"),
+                Some("The amount must have at most two decimals"),
+            ),
+        ];
+        for (dto, expected) in cases {
+            match (check_transfer(&dto), expected) {
+                (Ok(()), None) => {}
+                (Err(AppError::Validation(message)), Some(expected)) => {
+                    assert_eq!(message, expected)
+                }
+                (result, expected) => {
+                    panic!("{:?}: got {:?}, expected {:?}", dto, result, expected)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn both_sides_carry_the_same_base_currency_value() {
+        // (amount, to_amount, from_rate, to_rate, expected) with USD as the base currency
+        let cases = [
+            // USD to EUR: the amount sent is the value
+            (
+                "100.00",
+                "92.50",
+                None,
+                Some("1.081081"),
+                (None, Some("100.00")),
+            ),
+            // EUR to USD: the amount received is the value
+            (
+                "100.00",
+                "108.25",
+                Some("1.083417"),
+                None,
+                (Some("108.25"), None),
+            ),
+            // EUR to JPY: the source's rate values both sides
+            (
+                "100.00",
+                "15550.00",
+                Some("1.083417"),
+                Some("0.006967"),
+                (Some("108.34"), Some("108.34")),
+            ),
+        ];
+        for (amount, to_amount, from_rate, to_rate, (from_value, to_value)) in cases {
+            assert_eq!(
+                base_values(
+                    money(amount),
+                    money(to_amount),
+                    from_rate.map(money),
+                    to_rate.map(money)
+                ),
+                (from_value.map(money), to_value.map(money)),
+                "{} to {}",
+                amount,
+                to_amount
+            );
+        }
+    }
+}
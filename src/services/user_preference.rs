@@ -0,0 +1,226 @@
+//! Per-user preferences within a tenant: pinned accounts and their order.
+
+use std::collections::HashSet;
+
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+use tracing::info;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::{
+        account::Account, dto::user_preference_dto::SetPinnedAccountsDto,
+        user_preference::PinnedAccountBalances,
+    },
+    services::dashboard_data,
+};
+
+/// Most accounts a user can pin.
+const MAX_PINNED_ACCOUNTS: usize = 50;
+
+/// Retrieves the current user's pinned accounts in pin order. Accounts deactivated since
+/// they were pinned are left out.
+pub async fn list_pinned_accounts(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<Account>, AppError> {
+    info!(
+        "Service: Listing pinned accounts for user ID: {} in tenant ID: {}",
+        user_id, tenant_id
+    );
+
+    let accounts = sqlx::query_as!(
+        Account,
+        r#"
+        SELECT
+            a.id, a.tenant_id, a.account_type_id, a.name, a.account_code, a.description,
+            a.currency_code, a.is_sensitive, a.is_active, a.archived_at, a.created_at, a.created_by, a.updated_at, a.updated_by, a.version
+        FROM user_preferences up
+        JOIN accounts a ON a.id = ANY(up.pinned_account_ids)
+        WHERE up.user_id = $1 AND up.tenant_id = $2 AND a.tenant_id = $2 AND a.is_active = TRUE
+        ORDER BY array_position(up.pinned_account_ids, a.id)
+        "#,
+        user_id,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(accounts)
+}
+
+/// Replaces the current user's pinned accounts with `account_ids`, in that order.
+pub async fn set_pinned_accounts(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    dto: SetPinnedAccountsDto,
+) -> Result<Vec<Account>, AppError> {
+    info!(
+        "Service: Setting pinned accounts for user ID: {} in tenant ID: {}",
+        user_id, tenant_id
+    );
+
+    dto.validate()?;
+
+    let mut seen = HashSet::new();
+    let account_ids: Vec<Uuid> = dto
+        .account_ids
+        .into_iter()
+        .filter(|id| seen.insert(*id))
+        .collect();
+    ensure_active_accounts(pool, tenant_id, &account_ids).await?;
+    save_pins(pool, tenant_id, user_id, &account_ids).await?;
+
+    list_pinned_accounts(pool, tenant_id, user_id).await
+}
+
+/// Pins an account at the end of the current user's pins; pinning it again keeps its place.
+pub async fn pin_account(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    account_id: Uuid,
+) -> Result<Vec<Account>, AppError> {
+    info!(
+        "Service: Pinning account ID: {} for user ID: {}",
+        account_id, user_id
+    );
+
+    ensure_active_accounts(pool, tenant_id, &[account_id]).await?;
+    let mut account_ids = pinned_account_ids(pool, tenant_id, user_id).await?;
+    if !account_ids.contains(&account_id) {
+        if account_ids.len() >= MAX_PINNED_ACCOUNTS {
+            return Err(AppError::Validation(format!(
+                "At most {} accounts can be pinned",
+                MAX_PINNED_ACCOUNTS
+            )));
+        }
+        account_ids.push(account_id);
+        save_pins(pool, tenant_id, user_id, &account_ids).await?;
+    }
+
+    list_pinned_accounts(pool, tenant_id, user_id).await
+}
+
+/// Unpins an account for the current user.
+pub async fn unpin_account(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    account_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Unpinning account ID: {} for user ID: {}",
+        account_id, user_id
+    );
+
+    sqlx::query!(
+        r#"
+        UPDATE user_preferences
+        SET pinned_account_ids = array_remove(pinned_account_ids, $3), updated_at = NOW()
+        WHERE user_id = $1 AND tenant_id = $2
+        "#,
+        user_id,
+        tenant_id,
+        account_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Balances of the current user's pinned accounts as of a date, in pin order, in one call.
+pub async fn pinned_account_balances(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    as_of: Option<NaiveDate>,
+) -> Result<PinnedAccountBalances, AppError> {
+    let as_of = as_of.unwrap_or_else(|| Utc::now().date_naive());
+    info!(
+        "Service: Pinned account balances for user ID: {} as of {}",
+        user_id, as_of
+    );
+
+    let account_ids = pinned_account_ids(pool, tenant_id, user_id).await?;
+    if account_ids.is_empty() {
+        return Ok(PinnedAccountBalances {
+            as_of,
+            accounts: Vec::new(),
+        });
+    }
+
+    let mut accounts =
+        dashboard_data::account_balances(pool, tenant_id, &account_ids, false, as_of).await?;
+    accounts.sort_by_key(|item| account_ids.iter().position(|id| *id == item.account_id));
+
+    Ok(PinnedAccountBalances { as_of, accounts })
+}
+
+/// The current user's pinned account IDs in pin order (empty without preferences).
+pub async fn pinned_account_ids(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> Result<Vec<Uuid>, AppError> {
+    let account_ids = sqlx::query_scalar!(
+        "SELECT pinned_account_ids FROM user_preferences WHERE user_id = $1 AND tenant_id = $2",
+        user_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or_default();
+
+    Ok(account_ids)
+}
+
+async fn save_pins(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    account_ids: &[Uuid],
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_preferences (user_id, tenant_id, pinned_account_ids)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, tenant_id) DO UPDATE
+        SET pinned_account_ids = EXCLUDED.pinned_account_ids, updated_at = NOW()
+        "#,
+        user_id,
+        tenant_id,
+        account_ids
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn ensure_active_accounts(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    account_ids: &[Uuid],
+) -> Result<(), AppError> {
+    let found: Vec<Uuid> = sqlx::query_scalar!(
+        "SELECT id FROM accounts WHERE tenant_id = $1 AND is_active = TRUE AND id = ANY($2)",
+        tenant_id,
+        account_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    match account_ids.iter().find(|id| !found.contains(id)) {
+        Some(missing) => Err(AppError::NotFound(format!(
+            "Account with ID {} not found for tenant {}",
+            missing, tenant_id
+        ))),
+        None => Ok(()),
+    }
+}
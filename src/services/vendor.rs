@@ -0,0 +1,212 @@
+//! Vendors: the people and businesses a tenant buys from and pays bills to.
+//!
+//! Vendors are deactivated rather than deleted, so their bills keep pointing at them.
+
+use sqlx::{query_as, PgPool};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{
+        bill::Vendor,
+        dto::bill_dto::{CreateVendorDto, UpdateVendorDto},
+    },
+    utils::update_builder::UpdateBuilder,
+};
+
+/// Retrieves the active vendors of a tenant, by name.
+pub async fn list_vendors(pool: &PgPool, tenant_id: Uuid) -> Result<Vec<Vendor>, AppError> {
+    info!("Service: Listing vendors for tenant ID: {}", tenant_id);
+
+    let vendors = query_as!(
+        Vendor,
+        r#"
+        SELECT id, tenant_id, name, email, address, tax_id, payment_terms_days, notes,
+               is_active, created_at, created_by, updated_at, updated_by
+        FROM vendors
+        WHERE tenant_id = $1 AND is_active = TRUE
+        ORDER BY name
+        "#,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(vendors)
+}
+
+/// Retrieves a single active vendor by ID for a specific tenant.
+pub async fn get_vendor_by_id(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    vendor_id: Uuid,
+) -> Result<Vendor, AppError> {
+    info!(
+        "Service: Getting vendor with ID: {} for tenant ID: {}",
+        vendor_id, tenant_id
+    );
+
+    let vendor = query_as!(
+        Vendor,
+        r#"
+        SELECT id, tenant_id, name, email, address, tax_id, payment_terms_days, notes,
+               is_active, created_at, created_by, updated_at, updated_by
+        FROM vendors
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        vendor_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Vendor with ID {} not found for tenant {}",
+            vendor_id, tenant_id
+        ))
+    })?;
+
+    Ok(vendor)
+}
+
+/// Creates a new vendor for a specific tenant.
+pub async fn create_vendor(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    created_by_user_id: Uuid,
+    dto: CreateVendorDto,
+) -> Result<Vendor, AppError> {
+    info!(
+        "Service: Creating new vendor '{}' for tenant ID {}",
+        dto.name, tenant_id
+    );
+
+    let vendor = query_as!(
+        Vendor,
+        r#"
+        INSERT INTO vendors (tenant_id, name, email, address, tax_id, payment_terms_days, notes, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+        RETURNING id, tenant_id, name, email, address, tax_id, payment_terms_days, notes,
+                  is_active, created_at, created_by, updated_at, updated_by
+        "#,
+        tenant_id,
+        dto.name.trim(),
+        dto.email,
+        dto.address,
+        dto.tax_id,
+        dto.payment_terms_days,
+        dto.notes,
+        created_by_user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(vendor)
+}
+
+/// Updates an existing vendor for a specific tenant. Entered bills keep their due dates.
+pub async fn update_vendor(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    vendor_id: Uuid,
+    updated_by_user_id: Uuid,
+    dto: UpdateVendorDto,
+) -> Result<Vendor, AppError> {
+    info!(
+        "Service: Updating vendor with ID: {} for tenant ID: {}",
+        vendor_id, tenant_id
+    );
+
+    let mut update = UpdateBuilder::new("vendors");
+    update
+        .set("name", dto.name.map(|name| name.trim().to_string()))
+        .set("email", dto.email)
+        .set("address", dto.address)
+        .set("tax_id", dto.tax_id)
+        .set("payment_terms_days", dto.payment_terms_days)
+        .set("notes", dto.notes);
+    if update.is_empty() {
+        return Err(AppError::Validation(
+            "No fields provided for update".to_string(),
+        ));
+    }
+
+    let mut query = update.stamp(updated_by_user_id);
+    query.push(" WHERE id = ").push_bind(vendor_id);
+    query.push(" AND tenant_id = ").push_bind(tenant_id);
+    query.push(" AND is_active = TRUE");
+    query.push(
+        r#"
+        RETURNING id, tenant_id, name, email, address, tax_id, payment_terms_days, notes,
+                  is_active, created_at, created_by, updated_at, updated_by
+        "#,
+    );
+
+    let vendor = query
+        .build_query_as::<Vendor>()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Vendor with ID {} not found for tenant {}",
+                vendor_id, tenant_id
+            ))
+        })?;
+
+    Ok(vendor)
+}
+
+/// Deactivates a vendor (soft delete). Refused while one of their bills is unpaid.
+pub async fn deactivate_vendor(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    vendor_id: Uuid,
+    updated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    info!(
+        "Service: Deactivating vendor with ID: {} for tenant ID: {}",
+        vendor_id, tenant_id
+    );
+
+    let open_bills = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM bills
+        WHERE vendor_id = $1 AND tenant_id = $2 AND status IN ('DRAFT', 'OPEN', 'PARTIALLY_PAID')
+        "#,
+        vendor_id,
+        tenant_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if open_bills > 0 {
+        return Err(AppError::Conflict(format!(
+            "Vendor {} has {} draft or unpaid bills",
+            vendor_id, open_bills
+        )));
+    }
+
+    let affected_rows = sqlx::query!(
+        r#"
+        UPDATE vendors
+        SET is_active = FALSE, updated_at = NOW(), updated_by = $3
+        WHERE id = $1 AND tenant_id = $2 AND is_active = TRUE
+        "#,
+        vendor_id,
+        tenant_id,
+        updated_by_user_id
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        return Err(AppError::NotFound(format!(
+            "Vendor with ID {} not found or already inactive for tenant {}",
+            vendor_id, tenant_id
+        )));
+    }
+
+    Ok(())
+}
@@ -6,6 +6,7 @@ use validator::Validate;
 use crate::user::models::User;
 
 #[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateUserRequest {
     pub auth_provider_id: String,
 
@@ -24,6 +25,7 @@ pub struct CreateUserRequest {
 }
 
 #[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateUserRequest {
     #[validate(email)]
     pub email: Option<String>,
@@ -64,3 +66,19 @@ impl From<User> for UserResponse {
         }
     }
 }
+
+/// Everything stored about a user, by kind, for a data subject access request.
+#[derive(Debug, Serialize)]
+pub struct UserDataExport {
+    pub user_id: Uuid,
+    pub exported_at: DateTime<Utc>,
+    pub data: serde_json::Map<String, serde_json::Value>,
+}
+
+/// What erasing a user changed.
+#[derive(Debug, Serialize)]
+pub struct UserErasureSummary {
+    pub user_id: Uuid,
+    pub records_deleted: u64, // Sessions, keys, memberships, preferences, notifications
+    pub references_replaced: u64, // created_by-style references now pointing at the tombstone user
+}
@@ -11,7 +11,12 @@ use uuid::Uuid;
 
 use crate::app_state::AppState; // Assuming AppState is defined in src/app_state.rs
 use crate::error::AppError; // Importing our custom AppError
-use crate::user::dto::{CreateUserRequest, UpdateUserRequest, UserResponse}; // Importing DTOs
+use crate::middleware::auth::{AuthUser, TenantContext}; // Caller and tenant, for permission checks
+use crate::middleware::validated_json::ValidatedJson; // Body extractor that runs the DTO's validation
+use crate::user::dto::{
+    CreateUserRequest, UpdateUserRequest, UserDataExport, UserErasureSummary, UserResponse,
+}; // Importing DTOs
+use crate::user::personal_data; // Data subject requests
 use crate::user::service as user; // Importing our user service
 
 /// Creates a router for user-related API endpoints.
@@ -24,60 +29,108 @@ pub fn user_routes() -> Router<AppState> {
         .route("/:id", get(get_user_by_id)) // GET /api/v1/users/:id
         .route("/:id", put(update_user)) // PUT /api/v1/users/:id
         .route("/:id", delete(deactivate_user)) // DELETE /api/v1/users/:id (soft delete)
+        .route("/:id/restore", post(restore_user)) // POST /api/v1/users/:id/restore
+        .route("/:id/export", get(export_user_data)) // GET /api/v1/users/:id/export
+        .route("/:id/erase", post(erase_user)) // POST /api/v1/users/:id/erase
 }
 
 /// GET /api/v1/users
-/// Lists all active users.
+/// Lists the caller and the active members of the caller's tenants.
 async fn list_users(
-    State(AppState { pool, .. }): State<AppState>,
+    State(pool): State<PgPool>,
+    AuthUser { user_id }: AuthUser,
 ) -> Result<Json<Vec<UserResponse>>, AppError> {
-    info!("Handler: Listing all users");
-    let users = user::list_users(&pool).await?;
+    info!("Handler: Listing users visible to {}", user_id);
+    let users = user::list_users(&pool, user_id).await?;
     let user_responses: Vec<UserResponse> = users.into_iter().map(UserResponse::from).collect();
     Ok(Json(user_responses))
 }
 
 /// GET /api/v1/users/:id
-/// Retrieves a single user by their ID.
+/// Retrieves a single user by their ID, if they share a tenant with the caller.
 async fn get_user_by_id(
-    State(AppState { pool, .. }): State<AppState>,
+    State(pool): State<PgPool>,
+    caller: AuthUser,
     Path(user_id): Path<Uuid>,
 ) -> Result<Json<UserResponse>, AppError> {
     info!("Handler: Getting user by ID: {}", user_id);
-    let found_user = user::get_user_by_id(&pool, user_id).await?;
+    let found_user = user::get_visible_user(&pool, caller.user_id, user_id).await?;
     Ok(Json(UserResponse::from(found_user)))
 }
 
 /// POST /api/v1/users
-/// Creates a new user.
+/// Creates a new user (system administrators only).
 async fn create_user(
-    State(AppState { pool, .. }): State<AppState>,
-    Json(req): Json<CreateUserRequest>,
+    State(pool): State<PgPool>,
+    caller: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<UserResponse>), AppError> {
     info!("Handler: Creating new user with email: {}", req.email);
-    let new_user = user::create_user(&pool, req).await?;
+    let new_user = user::create_user(&pool, caller.user_id, req).await?;
     Ok((StatusCode::CREATED, Json(UserResponse::from(new_user))))
 }
 
 /// PUT /api/v1/users/:id
-/// Updates an existing user's information.
+/// Updates the caller's own information; system administrators may update anyone.
 async fn update_user(
-    State(AppState { pool, .. }): State<AppState>,
+    State(pool): State<PgPool>,
+    caller: AuthUser,
     Path(user_id): Path<Uuid>,
-    Json(req): Json<UpdateUserRequest>,
+    ValidatedJson(req): ValidatedJson<UpdateUserRequest>,
 ) -> Result<Json<UserResponse>, AppError> {
     info!("Handler: Updating user with ID: {}", user_id);
-    let updated_user = user::update_user(&pool, user_id, req).await?;
+    let updated_user = user::update_user(&pool, user_id, caller.user_id, req).await?;
     Ok(Json(UserResponse::from(updated_user)))
 }
 
 /// DELETE /api/v1/users/:id
-/// Deactivates a user (soft delete by setting `is_active` to false).
+/// Deactivates the caller (soft delete by setting `is_active` to false); system
+/// administrators may deactivate anyone.
 async fn deactivate_user(
-    State(AppState { pool, .. }): State<AppState>,
+    State(pool): State<PgPool>,
+    caller: AuthUser,
     Path(user_id): Path<Uuid>,
 ) -> Result<StatusCode, AppError> {
     info!("Handler: Deactivating user with ID: {}", user_id);
-    user::deactivate_user(&pool, user_id).await?;
+    user::deactivate_user(&pool, user_id, caller.user_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// POST /api/v1/users/:id/restore
+/// Reactivates a deactivated member of the caller's tenant. Requires `records.restore`.
+async fn restore_user(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserResponse>, AppError> {
+    info!("Handler: Restoring user with ID: {}", user_id);
+    let restored_user = user::restore_user(&pool, ctx.tenant_id, user_id, ctx.user_id).await?;
+    Ok(Json(UserResponse::from(restored_user)))
+}
+
+/// GET /api/v1/users/:id/export
+/// Exports all personal data stored about the user as JSON. Requires `users.personal_data`
+/// in every tenant the user belongs to.
+async fn export_user_data(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserDataExport>, AppError> {
+    info!("Handler: Exporting personal data of user {}", user_id);
+    let export =
+        personal_data::export_user_data(&pool, ctx.tenant_id, ctx.user_id, user_id).await?;
+    Ok(Json(export))
+}
+
+/// POST /api/v1/users/:id/erase
+/// Erases the user's personal data; records they created stay, attributed to a tombstone
+/// user. Irreversible. Requires `users.personal_data` in every tenant the user belongs to.
+async fn erase_user(
+    State(pool): State<PgPool>,
+    ctx: TenantContext,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserErasureSummary>, AppError> {
+    info!("Handler: Erasing user {}", user_id);
+    let summary = personal_data::erase_user(&pool, ctx.tenant_id, ctx.user_id, user_id).await?;
+    Ok(Json(summary))
+}
@@ -1,4 +1,5 @@
 pub mod dto;
 pub mod handlers;
 pub mod models;
+pub mod personal_data;
 pub mod service;
@@ -0,0 +1,330 @@
+//! Data subject requests: exporting everything stored about a user, and erasing it.
+//!
+//! Erasure keeps the books intact. Sessions, API keys, calendar feeds, memberships,
+//! preferences, notifications and pending invitations are deleted; every other reference to
+//! the user (`created_by`, `updated_by`, attributions, audit events, ...) is pointed at a
+//! shared, inactive tombstone user before the user row itself is deleted. The references
+//! are found in the database's foreign keys, so tables added later are covered too.
+//!
+//! Both need `users.personal_data` in every tenant the user belongs to, and in the tenant
+//! the caller is signed in to. As they span tenants, both run outside the request's
+//! row-level security scope.
+
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+use serde_json::{Map, Value as JsonValue};
+use sqlx::{PgPool, Postgres, Transaction as DbTransaction};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    error::AppError,
+    services::{audit, permission},
+    user::dto::{UserDataExport, UserErasureSummary},
+};
+
+/// The user that erased users' records are attributed to afterwards.
+pub const TOMBSTONE_USER_ID: Uuid = Uuid::nil();
+
+/// Email of the tombstone user; also replaces the address on past invitations.
+const ERASED_EMAIL: &str = "erased-user@invalid";
+
+/// Personal data in an export, by kind; `$1` is the user. Secrets (password and token
+/// hashes, bank credentials) are left out.
+const PERSONAL_DATA: &[(&str, &str)] = &[
+    (
+        "profile",
+        "SELECT id, auth_provider_id, auth_provider_type, email, first_name, last_name, is_active, \
+         last_login_at, created_at, updated_at FROM users WHERE id = $1",
+    ),
+    (
+        "memberships",
+        "SELECT utr.tenant_id, t.name AS tenant_name, r.name AS role, utr.created_at \
+         FROM user_tenant_roles utr JOIN tenants t ON t.id = utr.tenant_id \
+         JOIN roles r ON r.id = utr.role_id WHERE utr.user_id = $1 ORDER BY t.name, r.name",
+    ),
+    ("preferences", "SELECT * FROM user_preferences WHERE user_id = $1"),
+    (
+        "notification_preferences",
+        "SELECT * FROM notification_preferences WHERE user_id = $1",
+    ),
+    (
+        "notifications",
+        "SELECT id, tenant_id, priority, subject, body, delivered_at, created_at \
+         FROM notifications WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "sessions",
+        "SELECT id, user_agent, ip_address, created_at, last_used_at, expires_at, revoked_at, \
+         revoked_reason FROM auth_sessions WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "api_keys",
+        "SELECT id, tenant_id, name, key_prefix, is_sandbox, last_used_at, revoked_at, created_at \
+         FROM api_keys WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "calendar_feeds",
+        "SELECT id, tenant_id, last_used_at, created_at FROM calendar_feeds WHERE user_id = $1",
+    ),
+    (
+        "bank_connections",
+        "SELECT id, tenant_id, provider_id, status, last_sync_at, created_at \
+         FROM ext_conns WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "invitations",
+        "SELECT i.id, i.tenant_id, i.email, i.expires_at, i.accepted_at, i.revoked_at, i.created_at \
+         FROM tenant_invitations i JOIN users u ON LOWER(u.email) = LOWER(i.email) \
+         WHERE u.id = $1 ORDER BY i.created_at",
+    ),
+    (
+        "transaction_attributions",
+        "SELECT transaction_id, tenant_id, is_shared, updated_at \
+         FROM transaction_attributions WHERE member_id = $1",
+    ),
+    (
+        "audit_events",
+        "SELECT id, tenant_id, action, entity_type, entity_id, details, created_at \
+         FROM audit_events WHERE actor_user_id = $1 ORDER BY created_at",
+    ),
+];
+
+/// Exports everything stored about the user.
+pub async fn export_user_data(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    acting_user_id: Uuid,
+    user_id: Uuid,
+) -> Result<UserDataExport, AppError> {
+    db::without_tenant_scope(export(pool, tenant_id, acting_user_id, user_id)).await
+}
+
+/// Erases the user: personal records are deleted, references from the books move to the
+/// tombstone user, and the user row is deleted.
+pub async fn erase_user(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    acting_user_id: Uuid,
+    user_id: Uuid,
+) -> Result<UserErasureSummary, AppError> {
+    db::without_tenant_scope(erase(pool, tenant_id, acting_user_id, user_id)).await
+}
+
+async fn export(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    acting_user_id: Uuid,
+    user_id: Uuid,
+) -> Result<UserDataExport, AppError> {
+    info!("Service: Exporting personal data of user ID: {}", user_id);
+
+    require_personal_data_permission(pool, tenant_id, acting_user_id, user_id).await?;
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+    if !exists || user_id == TOMBSTONE_USER_ID {
+        return Err(AppError::NotFound(format!(
+            "User with ID {} not found",
+            user_id
+        )));
+    }
+
+    let mut data = Map::new();
+    for (kind, sql) in PERSONAL_DATA {
+        let rows: JsonValue = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(json_agg(r), '[]'::json) FROM ({}) r",
+            sql
+        ))
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+        data.insert(kind.to_string(), rows);
+    }
+
+    Ok(UserDataExport {
+        user_id,
+        exported_at: Utc::now(),
+        data,
+    })
+}
+
+async fn erase(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    acting_user_id: Uuid,
+    user_id: Uuid,
+) -> Result<UserErasureSummary, AppError> {
+    info!("Service: Erasing user ID: {}", user_id);
+
+    if user_id == acting_user_id {
+        return Err(AppError::Validation(
+            "You cannot erase your own account".to_string(),
+        ));
+    }
+    require_personal_data_permission(pool, tenant_id, acting_user_id, user_id).await?;
+
+    let mut db_tx = pool.begin().await?;
+    let email: String =
+        sqlx::query_scalar("SELECT email FROM users WHERE id = $1 AND id <> $2 FOR UPDATE")
+            .bind(user_id)
+            .bind(TOMBSTONE_USER_ID)
+            .fetch_optional(&mut *db_tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("User with ID {} not found", user_id)))?;
+    ensure_tombstone_user(&mut db_tx).await?;
+
+    let records_deleted = delete_personal_records(&mut db_tx, user_id, &email).await?;
+    let references_replaced = replace_references(&mut db_tx, user_id).await?;
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *db_tx)
+        .await?;
+
+    db_tx.commit().await?;
+
+    audit::record_audit_event(
+        pool,
+        Some(tenant_id),
+        Some(acting_user_id),
+        audit::USER_ERASE,
+        "user",
+        Some(user_id),
+        None,
+    )
+    .await;
+
+    Ok(UserErasureSummary {
+        user_id,
+        records_deleted,
+        references_replaced,
+    })
+}
+
+/// Requires `users.personal_data` in the caller's tenant and every tenant the user belongs to.
+async fn require_personal_data_permission(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    acting_user_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    let mut tenant_ids: BTreeSet<Uuid> =
+        sqlx::query_scalar("SELECT DISTINCT tenant_id FROM user_tenant_roles WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+    tenant_ids.insert(tenant_id);
+    for tenant_id in tenant_ids {
+        permission::require_permission(
+            pool,
+            tenant_id,
+            acting_user_id,
+            permission::USERS_PERSONAL_DATA,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Creates the tombstone user on the first erasure. It cannot sign in: it has no password
+/// and is inactive.
+async fn ensure_tombstone_user(db_tx: &mut DbTransaction<'_, Postgres>) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, auth_provider_id, auth_provider_type, email, first_name, last_name, is_active)
+        VALUES ($1, $2, 'TOMBSTONE', $2, 'Erased', 'user', FALSE)
+        ON CONFLICT (id) DO NOTHING
+        "#,
+    )
+    .bind(TOMBSTONE_USER_ID)
+    .bind(ERASED_EMAIL)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(())
+}
+
+/// Deletes the records that exist only for the user, and clears their address from past
+/// invitations. Returns how many records were deleted.
+async fn delete_personal_records(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    user_id: Uuid,
+    email: &str,
+) -> Result<u64, AppError> {
+    let mut deleted = 0;
+    // Refresh and access tokens go with their sessions
+    for table in [
+        "auth_sessions",
+        "api_keys",
+        "calendar_feeds",
+        "user_preferences",
+        "notification_preferences",
+        "notifications",
+        "user_tenant_roles",
+    ] {
+        deleted += sqlx::query(&format!("DELETE FROM {} WHERE user_id = $1", table))
+            .bind(user_id)
+            .execute(&mut **db_tx)
+            .await?
+            .rows_affected();
+    }
+
+    deleted += sqlx::query(
+        r#"
+        DELETE FROM tenant_invitations
+        WHERE LOWER(email) = LOWER($1) AND accepted_at IS NULL AND revoked_at IS NULL
+        "#,
+    )
+    .bind(email)
+    .execute(&mut **db_tx)
+    .await?
+    .rows_affected();
+    sqlx::query("UPDATE tenant_invitations SET email = $2 WHERE LOWER(email) = LOWER($1)")
+        .bind(email)
+        .bind(ERASED_EMAIL)
+        .execute(&mut **db_tx)
+        .await?;
+
+    Ok(deleted)
+}
+
+/// Points every remaining reference to the user at the tombstone user. Returns how many
+/// were changed.
+async fn replace_references(
+    db_tx: &mut DbTransaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<u64, AppError> {
+    let mut columns: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT c.conrelid::regclass::text, a.attname::text
+        FROM pg_constraint c
+        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = c.conkey[1]
+        WHERE c.contype = 'f' AND cardinality(c.conkey) = 1 AND c.confrelid = 'users'::regclass
+        ORDER BY 1, 2
+        "#,
+    )
+    .fetch_all(&mut **db_tx)
+    .await?;
+    // Written by the user but not declared as a foreign key
+    columns.push((
+        "transaction_attributions".to_string(),
+        "updated_by".to_string(),
+    ));
+
+    let mut replaced = 0;
+    for (table, column) in columns {
+        replaced += sqlx::query(&format!(
+            "UPDATE {table} SET \"{column}\" = $2 WHERE \"{column}\" = $1"
+        ))
+        .bind(user_id)
+        .bind(TOMBSTONE_USER_ID)
+        .execute(&mut **db_tx)
+        .await?
+        .rows_affected();
+    }
+    Ok(replaced)
+}
@@ -12,6 +12,7 @@ use validator::Validate;
 
 use crate::{
     error::AppError,
+    services::permission::{self, RECORDS_RESTORE},
     user::{
         dto::{CreateUserRequest, UpdateUserRequest, UserResponse},
         models::User,
@@ -40,12 +41,17 @@ pub(crate) fn verify_password(password: &str, hash: &str) -> Result<bool, AppErr
         .is_ok())
 }
 
-/// Creates a new user in the database.
+/// Creates a new user in the database. Only system administrators create users directly;
+/// everyone else joins through an invitation or single sign-on.
 ///
 /// Hashes the password before storing it.
-pub async fn create_user(pool: &PgPool, req: CreateUserRequest) -> Result<User, AppError> {
-    req.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+pub async fn create_user(
+    pool: &PgPool,
+    created_by_user_id: Uuid,
+    req: CreateUserRequest,
+) -> Result<User, AppError> {
+    permission::require_system_admin(pool, created_by_user_id).await?;
+    req.validate()?;
 
     let password_hash = if let Some(pwd) = req.password {
         Some(hash_password(&pwd)?)
@@ -110,16 +116,62 @@ pub async fn get_user_by_email(pool: &PgPool, email: &str) -> Result<User, AppEr
     Ok(user)
 }
 
-/// Lists all active users.
-pub async fn list_users(pool: &PgPool) -> Result<Vec<User>, AppError> {
+/// Retrieves a user the viewer may see: themselves or someone sharing one of their tenants.
+/// System administrators see everyone; anyone else is reported as not found.
+pub async fn get_visible_user(
+    pool: &PgPool,
+    viewer_id: Uuid,
+    user_id: Uuid,
+) -> Result<User, AppError> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, auth_provider_id, auth_provider_type, email, password_hash, first_name, last_name, is_active, last_login_at, created_at, updated_at
+        FROM users u
+        WHERE u.id = $2 AND u.is_active = TRUE
+          AND (
+            u.id = $1
+            OR EXISTS (SELECT 1 FROM users viewer WHERE viewer.id = $1 AND viewer.is_system_admin)
+            OR EXISTS (
+                SELECT 1
+                FROM user_tenant_roles mine
+                JOIN user_tenant_roles theirs ON theirs.tenant_id = mine.tenant_id
+                WHERE mine.user_id = $1 AND theirs.user_id = u.id
+            )
+          )
+        "#,
+        viewer_id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("User with ID {} not found", user_id)))?;
+
+    Ok(user)
+}
+
+/// Lists the active users the viewer may see: themselves and the members of their tenants,
+/// or everyone for system administrators.
+pub async fn list_users(pool: &PgPool, viewer_id: Uuid) -> Result<Vec<User>, AppError> {
     let users = sqlx::query_as!(
         User,
         r#"
         SELECT id, auth_provider_id, auth_provider_type, email, password_hash, first_name, last_name, is_active, last_login_at, created_at, updated_at
-        FROM users
-        WHERE is_active = TRUE
-        ORDER BY created_at DESC
-        "#
+        FROM users u
+        WHERE u.is_active = TRUE
+          AND (
+            u.id = $1
+            OR EXISTS (SELECT 1 FROM users viewer WHERE viewer.id = $1 AND viewer.is_system_admin)
+            OR EXISTS (
+                SELECT 1
+                FROM user_tenant_roles mine
+                JOIN user_tenant_roles theirs ON theirs.tenant_id = mine.tenant_id
+                WHERE mine.user_id = $1 AND theirs.user_id = u.id
+            )
+          )
+        ORDER BY u.created_at DESC
+        "#,
+        viewer_id
     )
     .fetch_all(pool)
     .await?;
@@ -127,27 +179,37 @@ pub async fn list_users(pool: &PgPool) -> Result<Vec<User>, AppError> {
     Ok(users)
 }
 
-/// Updates an existing user's information.
+/// Fails with `Forbidden` unless the acting user is the user themselves or a system
+/// administrator.
+async fn require_self_or_system_admin(
+    pool: &PgPool,
+    acting_user_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    if acting_user_id == user_id {
+        return Ok(());
+    }
+    permission::require_system_admin(pool, acting_user_id).await
+}
+
+/// Updates an existing user's information. Users change only their own details, unless
+/// `updated_by_user_id` is a system administrator.
 ///
 /// Can update password if provided.
 pub async fn update_user(
     pool: &PgPool,
     user_id: Uuid,
+    updated_by_user_id: Uuid,
     req: UpdateUserRequest,
 ) -> Result<User, AppError> {
-    req.validate()
-        .map_err(|e| AppError::Validation(e.to_string()))?;
+    require_self_or_system_admin(pool, updated_by_user_id, user_id).await?;
+    req.validate()?;
 
-    // Fetch current user to compare fields and handle partial updates
-    let mut current_user = get_user_by_id(pool, user_id).await?;
+    // Only active users can be updated
+    get_user_by_id(pool, user_id).await?;
 
-    let mut password_hash_to_update: Option<String> = None;
-    if let Some(new_password) = req.password {
-        password_hash_to_update = Some(hash_password(&new_password)?);
-    } else {
-        // If password is not provided in the request, retain the existing hash
-        password_hash_to_update = current_user.password_hash;
-    }
+    // Without a new password the stored hash is kept
+    let password_hash_to_update = req.password.as_deref().map(hash_password).transpose()?;
 
     let updated_user = sqlx::query_as!(
         User,
@@ -175,8 +237,15 @@ pub async fn update_user(
     Ok(updated_user)
 }
 
-/// Deactivates a user by setting `is_active` to `FALSE`.
-pub async fn deactivate_user(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+/// Deactivates a user by setting `is_active` to `FALSE`. Users deactivate only themselves,
+/// unless `deactivated_by_user_id` is a system administrator.
+pub async fn deactivate_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    deactivated_by_user_id: Uuid,
+) -> Result<(), AppError> {
+    require_self_or_system_admin(pool, deactivated_by_user_id, user_id).await?;
+
     let result = sqlx::query!(
         r#"
         UPDATE users
@@ -198,3 +267,273 @@ pub async fn deactivate_user(pool: &PgPool, user_id: Uuid) -> Result<(), AppErro
     info!("User with ID {} deactivated successfully", user_id);
     Ok(())
 }
+
+/// Restores a deactivated member of the tenant. Requires `records.restore` there; users who
+/// are not members of the tenant are reported as not found.
+pub async fn restore_user(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    user_id: Uuid,
+    restored_by_user_id: Uuid,
+) -> Result<User, AppError> {
+    info!(
+        "Service: Restoring user with ID: {} for tenant ID: {}",
+        user_id, tenant_id
+    );
+
+    permission::require_permission(pool, tenant_id, restored_by_user_id, RECORDS_RESTORE)
+        .await?;
+
+    let restored_user = sqlx::query_as!(
+        User,
+        r#"
+        UPDATE users
+        SET is_active = TRUE, updated_at = NOW()
+        WHERE id = $1
+          AND is_active = FALSE
+          AND EXISTS (
+              SELECT 1 FROM user_tenant_roles WHERE user_id = $1 AND tenant_id = $2
+          )
+        RETURNING id, auth_provider_id, auth_provider_type, email, password_hash, first_name, last_name, is_active, last_login_at, created_at, updated_at
+        "#,
+        user_id,
+        tenant_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Deactivated user with ID {} not found for tenant {}",
+            user_id, tenant_id
+        ))
+    })?;
+
+    info!("User with ID {} restored successfully", user_id);
+    Ok(restored_user)
+}
+
+#[cfg(test)]
+mod restore {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::{deactivate_user, restore_user};
+    use crate::{error::AppError, services::permission::RECORDS_RESTORE};
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn restores_deactivated_member_with_records_restore() {
+        let pool = connect().await;
+        let (tenant_id, admin_id, member_id) = seed(&pool, true).await;
+        deactivate_user(&pool, member_id, member_id)
+            .await
+            .expect("deactivate member");
+
+        let restored = restore_user(&pool, tenant_id, member_id, admin_id)
+            .await
+            .expect("restore member");
+        assert!(restored.is_active);
+
+        let again = restore_user(&pool, tenant_id, member_id, admin_id).await;
+        assert!(matches!(again, Err(AppError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn restore_requires_records_restore() {
+        let pool = connect().await;
+        let (tenant_id, admin_id, member_id) = seed(&pool, false).await;
+        deactivate_user(&pool, member_id, member_id)
+            .await
+            .expect("deactivate member");
+
+        let result = restore_user(&pool, tenant_id, member_id, admin_id).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn restore_ignores_users_outside_the_tenant() {
+        let pool = connect().await;
+        let (tenant_id, admin_id, _) = seed(&pool, true).await;
+        let (_, _, outsider_id) = seed(&pool, true).await;
+        deactivate_user(&pool, outsider_id, outsider_id)
+            .await
+            .expect("deactivate outsider");
+
+        let result = restore_user(&pool, tenant_id, outsider_id, admin_id).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    async fn connect() -> PgPool {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPool::connect(&url).await.expect("connect to DATABASE_URL")
+    }
+
+    /// A fresh tenant with an admin and a member; the admin's role holds `records.restore`
+    /// only if `grant_restore`.
+    async fn seed(pool: &PgPool, grant_restore: bool) -> (Uuid, Uuid, Uuid) {
+        let run = Uuid::new_v4();
+        let mut user_ids = Vec::new();
+        for name in ["admin", "member"] {
+            let user_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name)
+                 VALUES ($1, 'EMAIL_PASSWORD', $1, 'Restore', $2) RETURNING id",
+            )
+            .bind(format!("restore-{}-{}@example.com", name, run))
+            .bind(name)
+            .fetch_one(pool)
+            .await
+            .expect("insert user");
+            user_ids.push(user_id);
+        }
+        let (admin_id, member_id) = (user_ids[0], user_ids[1]);
+
+        sqlx::query(
+            "INSERT INTO currencies (code, name, symbol, created_by, updated_by)
+             VALUES ('USD', 'US Dollar', '$', $1, $1) ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(admin_id)
+        .execute(pool)
+        .await
+        .expect("insert currency");
+        let tenant_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO tenants (name, base_currency_code, fiscal_year_end_month, created_by, updated_by)
+             VALUES ($1, 'USD', 12, $2, $2) RETURNING id",
+        )
+        .bind(format!("Restore users {}", run))
+        .bind(admin_id)
+        .fetch_one(pool)
+        .await
+        .expect("insert tenant");
+
+        let admin_role_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO roles (name, created_by, updated_by) VALUES ($1, $2, $2) RETURNING id",
+        )
+        .bind(format!("restore-admin-{}", run))
+        .bind(admin_id)
+        .fetch_one(pool)
+        .await
+        .expect("insert role");
+        if grant_restore {
+            let permission_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO permissions (name, created_by, updated_by) VALUES ($1, $2, $2)
+                 ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING id",
+            )
+            .bind(RECORDS_RESTORE)
+            .bind(admin_id)
+            .fetch_one(pool)
+            .await
+            .expect("insert permission");
+            sqlx::query(
+                "INSERT INTO role_permissions (role_id, permission_id, created_by) VALUES ($1, $2, $3)",
+            )
+            .bind(admin_role_id)
+            .bind(permission_id)
+            .bind(admin_id)
+            .execute(pool)
+            .await
+            .expect("grant permission");
+        }
+        for user_id in [admin_id, member_id] {
+            sqlx::query(
+                "INSERT INTO user_tenant_roles (user_id, tenant_id, role_id, created_by, updated_by)
+                 VALUES ($1, $2, $3, $4, $4)",
+            )
+            .bind(user_id)
+            .bind(tenant_id)
+            .bind(admin_role_id)
+            .bind(admin_id)
+            .execute(pool)
+            .await
+            .expect("insert membership");
+        }
+        (tenant_id, admin_id, member_id)
+    }
+}
+
+#[cfg(test)]
+mod access {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::{deactivate_user, get_visible_user, list_users, update_user};
+    use crate::{error::AppError, user::dto::UpdateUserRequest};
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn users_change_only_themselves() {
+        let pool = connect().await;
+        let alice = seed_user(&pool, false).await;
+        let bob = seed_user(&pool, false).await;
+
+        let takeover = update_user(&pool, bob, alice, new_password()).await;
+        assert!(matches!(takeover, Err(AppError::Forbidden(_))));
+        let deactivation = deactivate_user(&pool, bob, alice).await;
+        assert!(matches!(deactivation, Err(AppError::Forbidden(_))));
+
+        let updated = update_user(&pool, alice, alice, new_password())
+            .await
+            .expect("update self");
+        assert_ne!(updated.password_hash, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn system_admins_change_anyone() {
+        let pool = connect().await;
+        let admin = seed_user(&pool, true).await;
+        let bob = seed_user(&pool, false).await;
+
+        update_user(&pool, bob, admin, new_password())
+            .await
+            .expect("admin update");
+        deactivate_user(&pool, bob, admin)
+            .await
+            .expect("admin deactivation");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated PostgreSQL database in DATABASE_URL"]
+    async fn strangers_are_not_visible() {
+        let pool = connect().await;
+        let alice = seed_user(&pool, false).await;
+        let bob = seed_user(&pool, false).await;
+
+        let found = get_visible_user(&pool, alice, bob).await;
+        assert!(matches!(found, Err(AppError::NotFound(_))));
+        let listed = list_users(&pool, alice).await.expect("list users");
+        assert_eq!(
+            listed.iter().map(|user| user.id).collect::<Vec<_>>(),
+            vec![alice]
+        );
+    }
+
+    fn new_password() -> UpdateUserRequest {
+        UpdateUserRequest {
+            email: None,
+            password: Some("correct horse battery staple".to_string()),
+            first_name: None,
+            last_name: None,
+        }
+    }
+
+    async fn connect() -> PgPool {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        PgPool::connect(&url)
+            .await
+            .expect("connect to DATABASE_URL")
+    }
+
+    async fn seed_user(pool: &PgPool, is_system_admin: bool) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO users (auth_provider_id, auth_provider_type, email, first_name, last_name, is_system_admin)
+             VALUES ($1, 'EMAIL_PASSWORD', $1, 'Access', 'Test', $2) RETURNING id",
+        )
+        .bind(format!("access-{}@example.com", Uuid::new_v4()))
+        .bind(is_system_admin)
+        .fetch_one(pool)
+        .await
+        .expect("insert user")
+    }
+}
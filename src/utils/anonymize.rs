@@ -0,0 +1,254 @@
+//! Scrambling of text and amounts for anonymized tenant copies.
+//!
+//! Output keeps the shape of the input: text keeps its length, case, digits, spacing and
+//! punctuation; amounts keep their sign, number of digits before the decimal point and
+//! decimal places. The scramble is keyed and deterministic, so within one copy equal values
+//! stay equal (a merchant's transactions still group together), while the original cannot
+//! be recovered without the key, which is thrown away after the copy is made.
+//!
+//! Amounts that have to add up, the legs of a journal entry, are not scrambled one by one
+//! but scaled together by a keyed factor per entry ([`Scrambler::scale_factor`]), and
+//! [`scale_legs`] puts the rounding on one leg so debits still equal credits.
+
+use std::str::FromStr;
+
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde_json::Value as JsonValue;
+use sha2::Sha256;
+
+use crate::utils::crypto::generate_key;
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+
+/// Scrambles values with a random key of its own.
+pub struct Scrambler {
+    key: [u8; 32],
+}
+
+impl Scrambler {
+    pub fn with_random_key() -> Self {
+        Scrambler {
+            key: generate_key(),
+        }
+    }
+
+    /// Replaces letters and digits with others of the same kind. Any other letter
+    /// (accented, non-Latin) becomes a lowercase ASCII letter.
+    pub fn text(&self, text: &str) -> String {
+        let mut stream = self.stream("text", text);
+        text.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    stream.pick(UPPERCASE)
+                } else if c.is_ascii_digit() {
+                    stream.pick(DIGITS)
+                } else if c.is_alphabetic() {
+                    stream.pick(LOWERCASE)
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+
+    /// Scrambles a decimal such as `-1234.50` digit by digit, keeping leading zeros and
+    /// never starting with one, so the magnitude stays the same. Anything that is not a
+    /// plain decimal is returned unchanged.
+    pub fn amount(&self, amount: &str) -> String {
+        let (sign, digits) = match amount.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", amount),
+        };
+        let is_decimal = !digits.is_empty()
+            && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+            && digits.matches('.').count() <= 1;
+        if !is_decimal {
+            return amount.to_string();
+        }
+
+        let mut stream = self.stream("amount", digits);
+        let mut significant = false;
+        let scrambled: String = digits
+            .chars()
+            .map(|c| match c {
+                '0' if !significant => '0',
+                c if c.is_ascii_digit() && !significant => {
+                    significant = true;
+                    stream.pick(&DIGITS[1..])
+                }
+                c if c.is_ascii_digit() => stream.pick(DIGITS),
+                c => c,
+            })
+            .collect();
+        format!("{}{}", sign, scrambled)
+    }
+
+    /// [`Scrambler::amount`] for a JSON number or numeric string, keeping its JSON type.
+    pub fn json_amount(&self, value: &JsonValue) -> JsonValue {
+        match value {
+            JsonValue::Number(number) => {
+                serde_json::from_str(&self.amount(&number.to_string())).unwrap_or(JsonValue::Null)
+            }
+            JsonValue::String(amount) => JsonValue::String(self.amount(amount)),
+            other => other.clone(),
+        }
+    }
+
+    /// A keyed factor for the amounts of one group, such as a transaction and its legs:
+    /// between 0.75 and 1.25 in hundredths, but never 1, so no group keeps its amounts.
+    pub fn scale_factor(&self, group: &str) -> Decimal {
+        let mut stream = self.stream("scale", group);
+        let hundredths = 75 + i64::from(stream.byte() % 50);
+        let hundredths = if hundredths < 100 {
+            hundredths
+        } else {
+            hundredths + 1
+        };
+        Decimal::new(hundredths, 2)
+    }
+
+    fn stream(&self, domain: &str, value: &str) -> ByteStream {
+        ByteStream {
+            key: self.key,
+            seed: format!("{}:{}", domain, value),
+            block: Vec::new(),
+            counter: 0,
+        }
+    }
+}
+
+/// Pseudo-random bytes from HMAC-SHA256 of the value and a block counter.
+struct ByteStream {
+    key: [u8; 32],
+    seed: String,
+    block: Vec<u8>,
+    counter: u64,
+}
+
+impl ByteStream {
+    fn pick(&mut self, alphabet: &[u8]) -> char {
+        // The slight bias towards the first letters is irrelevant here
+        alphabet[self.byte() as usize % alphabet.len()] as char
+    }
+
+    fn byte(&mut self) -> u8 {
+        if self.block.is_empty() {
+            let mut mac =
+                Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts any key length");
+            mac.update(&self.counter.to_be_bytes());
+            mac.update(self.seed.as_bytes());
+            self.block = mac.finalize().into_bytes().to_vec();
+            self.counter += 1;
+        }
+        self.block.pop().expect("a block holds 32 bytes")
+    }
+}
+
+/// `amount` times `factor`, rounded to the amount's decimal places but at least to cents.
+pub fn scale(amount: Decimal, factor: Decimal) -> Decimal {
+    (amount * factor).round_dp(amount.scale().max(2))
+}
+
+/// Scales the legs of one entry, given signed (debits positive, credits negative), by the
+/// same factor. If they summed to zero they still do: the last leg takes the rounding
+/// difference, or the largest one if that would zero the last leg or turn its sign.
+pub fn scale_legs(legs: &[Decimal], factor: Decimal) -> Vec<Decimal> {
+    let mut scaled: Vec<Decimal> = legs.iter().map(|leg| scale(*leg, factor)).collect();
+    let difference: Decimal = scaled.iter().sum();
+    if difference.is_zero() || !legs.iter().sum::<Decimal>().is_zero() {
+        return scaled;
+    }
+
+    let last = scaled.len() - 1;
+    let keeps_sign = |leg: Decimal| {
+        let adjusted = leg - difference;
+        !adjusted.is_zero() && adjusted.is_sign_negative() == leg.is_sign_negative()
+    };
+    let index = if keeps_sign(scaled[last]) {
+        last
+    } else {
+        (0..scaled.len())
+            .max_by_key(|index| scaled[*index].abs())
+            .unwrap_or(last)
+    };
+    scaled[index] -= difference;
+    scaled
+}
+
+/// An amount given as a JSON number or numeric string.
+pub fn json_decimal(value: &JsonValue) -> Option<Decimal> {
+    match value {
+        JsonValue::Number(number) => Decimal::from_str(&number.to_string()).ok(),
+        JsonValue::String(amount) => Decimal::from_str(amount).ok(),
+        _ => None,
+    }
+}
+
+/// `amount` as JSON of the same type as `like`: a string if it is one, a number otherwise.
+pub fn decimal_json(amount: Decimal, like: &JsonValue) -> JsonValue {
+    match like {
+        JsonValue::String(_) => JsonValue::String(amount.to_string()),
+        _ => serde_json::from_str(&amount.to_string()).unwrap_or(JsonValue::Null),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amounts(values: &[&str]) -> Vec<Decimal> {
+        values.iter().map(|value| value.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn scaled_balanced_entry_still_sums_to_zero() {
+        let scrambler = Scrambler::with_random_key();
+        let legs = amounts(&["100.00", "-33.33", "-33.33", "-33.34"]);
+        for entry in ["a", "b", "c", "d", "e", "f", "g", "h"] {
+            let factor = scrambler.scale_factor(entry);
+            let scaled = scale_legs(&legs, factor);
+            assert_eq!(
+                scaled.iter().sum::<Decimal>(),
+                Decimal::ZERO,
+                "factor {}",
+                factor
+            );
+            assert!(
+                scaled[0].is_sign_positive()
+                    && scaled[1..].iter().all(|leg| leg.is_sign_negative())
+            );
+            assert_ne!(scaled, legs);
+        }
+    }
+
+    #[test]
+    fn last_leg_takes_the_rounding() {
+        // Both credits round up to 0.06, the debit to 0.11
+        let scaled = scale_legs(&amounts(&["-0.05", "-0.05", "0.10"]), Decimal::new(110, 2));
+        assert_eq!(scaled, amounts(&["-0.06", "-0.06", "0.12"]));
+        // The last leg would drop to zero, so the largest takes the cent
+        let scaled = scale_legs(
+            &amounts(&["-0.05", "-0.05", "0.09", "0.01"]),
+            Decimal::new(90, 2),
+        );
+        assert_eq!(scaled, amounts(&["-0.04", "-0.04", "0.07", "0.01"]));
+    }
+
+    #[test]
+    fn unbalanced_legs_are_only_scaled() {
+        let scaled = scale_legs(&amounts(&["10.01", "-5.00"]), Decimal::new(125, 2));
+        assert_eq!(scaled, amounts(&["12.51", "-6.25"]));
+    }
+
+    #[test]
+    fn scale_factor_is_keyed_per_group() {
+        let scrambler = Scrambler::with_random_key();
+        let factor = scrambler.scale_factor("entry");
+        assert_eq!(scrambler.scale_factor("entry"), factor);
+        assert!(factor >= Decimal::new(75, 2) && factor <= Decimal::new(125, 2));
+        assert_ne!(factor, Decimal::ONE);
+    }
+}
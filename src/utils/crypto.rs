@@ -0,0 +1,243 @@
+// src/utils/crypto.rs
+
+//! Symmetric encryption for secrets stored at rest (e.g., provider access tokens).
+//!
+//! Values are encrypted with AES-256-GCM using the key in `TOKEN_ENCRYPTION_KEY`
+//! (base64-encoded, 32 bytes) and stored as `v1:<base64(nonce || ciphertext)>`.
+//!
+//! The same format is used with caller-supplied keys, such as per-tenant data keys.
+//!
+//! Also generates random secrets, HMAC-SHA256 signatures for outgoing webhooks and
+//! SHA-256 digests of bearer tokens that are stored for lookup only, and encrypts files
+//! that leave the app (backups) with a user-supplied passphrase, and decrypts them when
+//! they come back.
+
+use std::{
+    io::{self, Read, Write},
+    iter,
+};
+
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use age::secrecy::SecretString;
+use base64::{
+    engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64_URL},
+    Engine,
+};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppError;
+
+const CIPHERTEXT_PREFIX: &str = "v1:";
+const NONCE_LEN: usize = 12;
+
+/// Magic line opening an age file.
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1\n";
+/// scrypt work factor of passphrase-encrypted files: N = 2^16, 64 MiB.
+const PASSPHRASE_SCRYPT_LOG_N: u8 = 16;
+
+/// Loads the AES-256 key configured as `TOKEN_ENCRYPTION_KEY`.
+fn load_key() -> Result<[u8; 32], AppError> {
+    let encoded = crate::config::get()
+        .auth
+        .token_encryption_key
+        .as_deref()
+        .ok_or_else(|| {
+            AppError::InternalServerError("TOKEN_ENCRYPTION_KEY must be set".to_string())
+        })?;
+    parse_key(encoded).map_err(AppError::InternalServerError)
+}
+
+/// Decodes a base64 AES-256 key; also used to check the key at startup.
+pub fn parse_key(encoded: &str) -> Result<[u8; 32], String> {
+    let bytes = BASE64
+        .decode(encoded.trim())
+        .map_err(|e| format!("TOKEN_ENCRYPTION_KEY is not valid base64: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|_| "TOKEN_ENCRYPTION_KEY must decode to exactly 32 bytes".to_string())
+}
+
+/// Encrypts a plain-text secret for storage.
+pub fn encrypt_secret(plaintext: &str) -> Result<String, AppError> {
+    encrypt_with_key(&load_key()?, plaintext)
+}
+
+/// Decrypts a secret previously produced by [`encrypt_secret`].
+pub fn decrypt_secret(stored: &str) -> Result<String, AppError> {
+    decrypt_with_key(&load_key()?, stored)
+}
+
+/// Encrypts with a caller-supplied key (e.g., a tenant's data key), in the same format as
+/// [`encrypt_secret`].
+pub fn encrypt_with_key(key: &[u8; 32], plaintext: &str) -> Result<String, AppError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encrypt secret: {}", e)))?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", CIPHERTEXT_PREFIX, BASE64.encode(payload)))
+}
+
+/// Decrypts a value previously produced by [`encrypt_with_key`] with the same key.
+pub fn decrypt_with_key(key: &[u8; 32], stored: &str) -> Result<String, AppError> {
+    let encoded = stored.strip_prefix(CIPHERTEXT_PREFIX).ok_or_else(|| {
+        AppError::InternalServerError("Stored secret has an unknown format".to_string())
+    })?;
+    let payload = BASE64.decode(encoded).map_err(|e| {
+        AppError::InternalServerError(format!("Stored secret is not valid base64: {}", e))
+    })?;
+    if payload.len() <= NONCE_LEN {
+        return Err(AppError::InternalServerError(
+            "Stored secret is truncated".to_string(),
+        ));
+    }
+
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to decrypt secret: {}", e)))?;
+
+    String::from_utf8(plaintext).map_err(|e| {
+        AppError::InternalServerError(format!("Decrypted secret is not valid UTF-8: {}", e))
+    })
+}
+
+/// Encrypts a file with a passphrase, so it can be kept anywhere (e.g., a backup).
+///
+/// The result is an age file (age-encryption.org/v1) with a single scrypt recipient at
+/// work factor 2^`PASSPHRASE_SCRYPT_LOG_N`, so the `age` tool can decrypt it too.
+pub fn encrypt_with_passphrase(passphrase: &str, data: &[u8]) -> Result<Vec<u8>, AppError> {
+    encrypt_with_work_factor(passphrase, data, PASSPHRASE_SCRYPT_LOG_N)
+}
+
+/// [`encrypt_with_passphrase`] at scrypt work factor 2^`log_n`.
+fn encrypt_with_work_factor(passphrase: &str, data: &[u8], log_n: u8) -> Result<Vec<u8>, AppError> {
+    let mut recipient = age::scrypt::Recipient::new(SecretString::from(passphrase.to_string()));
+    recipient.set_work_factor(log_n);
+    let encryptor = age::Encryptor::with_recipients(iter::once(&recipient as &dyn age::Recipient))
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encrypt file: {}", e)))?;
+
+    let mut encrypted = Vec::with_capacity(data.len() + 256);
+    let write_error =
+        |e: io::Error| AppError::InternalServerError(format!("Failed to encrypt file: {}", e));
+    let mut writer = encryptor.wrap_output(&mut encrypted).map_err(write_error)?;
+    writer.write_all(data).map_err(write_error)?;
+    writer.finish().map_err(write_error)?;
+    Ok(encrypted)
+}
+
+/// Whether `data` was written by [`encrypt_with_passphrase`].
+pub fn is_passphrase_encrypted(data: &[u8]) -> bool {
+    data.starts_with(AGE_MAGIC)
+}
+
+/// Decrypts a file written by [`encrypt_with_passphrase`]. A wrong passphrase or a damaged
+/// file fails with `Validation`, as both come from the caller.
+pub fn decrypt_with_passphrase(passphrase: &str, data: &[u8]) -> Result<Vec<u8>, AppError> {
+    if !data.starts_with(AGE_MAGIC) {
+        return Err(AppError::Validation(
+            "Not a passphrase-encrypted file".to_string(),
+        ));
+    }
+
+    let mut identity = age::scrypt::Identity::new(SecretString::from(passphrase.to_string()));
+    // The work factor comes from the file, so anything above what we write is refused
+    identity.set_max_work_factor(PASSPHRASE_SCRYPT_LOG_N);
+    let decryptor = age::Decryptor::new_buffered(data).map_err(age_decrypt_error)?;
+    let mut reader = decryptor
+        .decrypt(iter::once(&identity as &dyn age::Identity))
+        .map_err(age_decrypt_error)?;
+    let mut decrypted = Vec::with_capacity(data.len());
+    reader.read_to_end(&mut decrypted).map_err(|_| {
+        AppError::Validation("Wrong passphrase, or the file is damaged".to_string())
+    })?;
+    Ok(decrypted)
+}
+
+fn age_decrypt_error(e: age::DecryptError) -> AppError {
+    match e {
+        age::DecryptError::ExcessiveWork { .. } => AppError::Validation(
+            "Encrypted file asks for an unreasonable key derivation cost".to_string(),
+        ),
+        age::DecryptError::InvalidHeader | age::DecryptError::UnknownFormat => {
+            AppError::Validation("Not a passphrase-encrypted file".to_string())
+        }
+        _ => AppError::Validation("Wrong passphrase, or the file is damaged".to_string()),
+    }
+}
+
+/// Generates a random 32-byte AES-256 key.
+pub fn generate_key() -> [u8; 32] {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    key
+}
+
+/// Generates a random secret of `len` bytes, encoded as URL-safe base64.
+pub fn generate_secret(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    BASE64_URL.encode(bytes)
+}
+
+/// SHA-256 of `data`, as lowercase hex.
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// HMAC-SHA256 of `message` keyed with `secret`, as lowercase hex.
+pub fn hmac_sha256_hex(secret: &str, message: &[u8]) -> String {
+    // HMAC accepts keys of any length, so this cannot fail
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts any key length");
+    mac.update(message);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap enough for a debug build; decryption accepts any factor up to the written one.
+    const TEST_SCRYPT_LOG_N: u8 = 10;
+
+    #[test]
+    fn passphrase_encryption_round_trips() {
+        let data = b"tenant backup bundle";
+        let encrypted =
+            encrypt_with_work_factor("correct horse", data, TEST_SCRYPT_LOG_N).expect("encrypt");
+        assert!(is_passphrase_encrypted(&encrypted));
+        assert!(!encrypted
+            .windows(data.len())
+            .any(|window| window == data.as_slice()));
+        let decrypted = decrypt_with_passphrase("correct horse", &encrypted).expect("decrypt");
+        assert_eq!(decrypted, data);
+    }
+
+    #[test]
+    fn decrypt_rejects_a_wrong_passphrase() {
+        let encrypted = encrypt_with_work_factor("correct horse", b"bundle", TEST_SCRYPT_LOG_N)
+            .expect("encrypt");
+        match decrypt_with_passphrase("battery staple", &encrypted) {
+            Err(AppError::Validation(message)) => {
+                assert!(message.contains("Wrong passphrase"), "{}", message)
+            }
+            other => panic!("expected a validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,396 @@
+//! Locale-aware CSV writing shared by every exporter.
+//!
+//! A `CsvFormat` is built from the request's `CsvFormatQuery` and controls the delimiter,
+//! the decimal separator, how dates are written and whether the file starts with a BOM.
+//! Cells are typed (`CsvCell`) so numbers and dates are formatted and everything else is
+//! written as is. The same format parses files written with it (see `budget_csv`).
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    NaiveDate,
+};
+use rust_decimal::Decimal;
+use serde_json::Value as JsonValue;
+
+use crate::{error::AppError, models::dto::csv_format_dto::CsvFormatQuery};
+
+const UTF8_BOM: char = '\u{feff}';
+const ISO_DATE: &str = "%Y-%m-%d";
+
+/// Delimiter, decimal separator, date format and BOM of a CSV file.
+#[derive(Debug, Clone)]
+pub struct CsvFormat {
+    pub delimiter: u8,
+    pub decimal_comma: bool,
+    pub date_format: String,
+    pub bom: bool,
+}
+
+impl Default for CsvFormat {
+    /// Comma-separated, decimal point, ISO dates, no BOM.
+    fn default() -> Self {
+        CsvFormat {
+            delimiter: b',',
+            decimal_comma: false,
+            date_format: ISO_DATE.to_string(),
+            bom: false,
+        }
+    }
+}
+
+impl CsvFormat {
+    /// Validates the query options; anything left out keeps the default.
+    pub fn from_query(query: &CsvFormatQuery) -> Result<Self, AppError> {
+        let decimal_comma = query.decimal_comma.unwrap_or(false);
+        let delimiter = match query.delimiter.as_deref() {
+            // A decimal comma needs another delimiter to stay readable
+            None if decimal_comma => b';',
+            None | Some(",") | Some("comma") => b',',
+            Some(";") | Some("semicolon") => b';',
+            Some("\t") | Some("tab") => b'\t',
+            Some("|") | Some("pipe") => b'|',
+            Some(other) => {
+                return Err(AppError::Validation(format!(
+                    "Unsupported CSV delimiter '{}'; use ',', ';', 'tab' or '|'",
+                    other
+                )))
+            }
+        };
+
+        let date_format = match query.date_format.as_deref() {
+            None | Some("iso") => ISO_DATE.to_string(),
+            Some("us") => "%m/%d/%Y".to_string(),
+            Some("eu") => "%d.%m.%Y".to_string(),
+            Some("uk") => "%d/%m/%Y".to_string(),
+            Some(pattern) => {
+                let invalid = !pattern.contains('%')
+                    || StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error));
+                if invalid {
+                    return Err(AppError::Validation(format!(
+                        "Invalid date_format '{}'; use iso, us, eu, uk or a strftime pattern",
+                        pattern
+                    )));
+                }
+                pattern.to_string()
+            }
+        };
+
+        Ok(CsvFormat {
+            delimiter,
+            decimal_comma,
+            date_format,
+            bom: query.bom.unwrap_or(false),
+        })
+    }
+
+    pub fn format_decimal(&self, value: Decimal) -> String {
+        let text = value.to_string();
+        if self.decimal_comma {
+            text.replace('.', ",")
+        } else {
+            text
+        }
+    }
+
+    pub fn format_date(&self, value: NaiveDate) -> String {
+        value.format(&self.date_format).to_string()
+    }
+
+    pub fn parse_decimal(&self, text: &str) -> Option<Decimal> {
+        if self.decimal_comma {
+            text.replace(',', ".").parse().ok()
+        } else {
+            text.parse().ok()
+        }
+    }
+
+    pub fn parse_date(&self, text: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(text, &self.date_format).ok()
+    }
+
+    /// A reader for a file in this format, with fields trimmed and any BOM skipped.
+    pub fn reader<'a>(&self, text: &'a str) -> csv::Reader<&'a [u8]> {
+        csv::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .trim(csv::Trim::All)
+            .from_reader(text.trim_start_matches(UTF8_BOM).as_bytes())
+    }
+
+    pub fn writer(&self) -> CsvWriter {
+        CsvWriter {
+            inner: csv::WriterBuilder::new()
+                .delimiter(self.delimiter)
+                .from_writer(Vec::new()),
+            format: self.clone(),
+        }
+    }
+}
+
+/// How the values of a column are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvColumnKind {
+    Text,
+    Number,
+    Date,
+}
+
+/// One typed value to write.
+#[derive(Debug, Clone)]
+pub enum CsvCell {
+    Empty,
+    Text(String),
+    Number(Decimal),
+    Date(NaiveDate),
+}
+
+impl CsvCell {
+    /// Types a JSON value by its column. Values that do not parse as the column's kind
+    /// (e.g. a masked `"***"` amount) are written as text.
+    pub fn from_json(value: Option<&JsonValue>, kind: CsvColumnKind) -> Self {
+        let text = match value {
+            None | Some(JsonValue::Null) => return CsvCell::Empty,
+            Some(JsonValue::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        };
+        let typed = match kind {
+            CsvColumnKind::Text => None,
+            CsvColumnKind::Number => text.parse().ok().map(CsvCell::Number),
+            CsvColumnKind::Date => NaiveDate::parse_from_str(&text, ISO_DATE)
+                .ok()
+                .map(CsvCell::Date),
+        };
+        typed.unwrap_or(CsvCell::Text(text))
+    }
+}
+
+impl From<String> for CsvCell {
+    fn from(value: String) -> Self {
+        CsvCell::Text(value)
+    }
+}
+
+impl From<&str> for CsvCell {
+    fn from(value: &str) -> Self {
+        CsvCell::Text(value.to_string())
+    }
+}
+
+impl From<Option<String>> for CsvCell {
+    fn from(value: Option<String>) -> Self {
+        value.map_or(CsvCell::Empty, CsvCell::Text)
+    }
+}
+
+impl From<Decimal> for CsvCell {
+    fn from(value: Decimal) -> Self {
+        CsvCell::Number(value)
+    }
+}
+
+impl From<NaiveDate> for CsvCell {
+    fn from(value: NaiveDate) -> Self {
+        CsvCell::Date(value)
+    }
+}
+
+/// Writes records in a `CsvFormat`.
+pub struct CsvWriter {
+    inner: csv::Writer<Vec<u8>>,
+    format: CsvFormat,
+}
+
+impl CsvWriter {
+    pub fn write_header(&mut self, columns: &[&str]) -> Result<(), AppError> {
+        self.inner.write_record(columns).map_err(csv_error)
+    }
+
+    pub fn write_row(&mut self, cells: impl IntoIterator<Item = CsvCell>) -> Result<(), AppError> {
+        let format = &self.format;
+        let fields = cells.into_iter().map(|cell| match cell {
+            CsvCell::Empty => String::new(),
+            CsvCell::Text(text) => text,
+            CsvCell::Number(value) => format.format_decimal(value),
+            CsvCell::Date(value) => format.format_date(value),
+        });
+        self.inner.write_record(fields).map_err(csv_error)
+    }
+
+    /// The finished file, with a BOM first if the format asks for one.
+    pub fn finish(self) -> Result<String, AppError> {
+        let bytes = self
+            .inner
+            .into_inner()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write CSV: {}", e)))?;
+        let text = String::from_utf8(bytes)
+            .map_err(|e| AppError::InternalServerError(format!("CSV is not valid UTF-8: {}", e)))?;
+        if self.format.bom {
+            Ok(format!("{}{}", UTF8_BOM, text))
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+fn csv_error(e: csv::Error) -> AppError {
+    AppError::InternalServerError(format!("Failed to write CSV: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(
+        delimiter: Option<&str>,
+        decimal_comma: Option<bool>,
+        date_format: Option<&str>,
+    ) -> CsvFormatQuery {
+        CsvFormatQuery {
+            delimiter: delimiter.map(str::to_string),
+            decimal_comma,
+            date_format: date_format.map(str::to_string),
+            bom: None,
+        }
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        date.parse().unwrap()
+    }
+
+    #[test]
+    fn picks_the_delimiter() {
+        let cases = [
+            (None, None, b','),
+            (None, Some(true), b';'),
+            (Some(","), Some(true), b','),
+            (Some("comma"), None, b','),
+            (Some(";"), None, b';'),
+            (Some("semicolon"), None, b';'),
+            (Some("\t"), None, b'\t'),
+            (Some("tab"), None, b'\t'),
+            (Some("|"), None, b'|'),
+            (Some("pipe"), None, b'|'),
+        ];
+        for (delimiter, decimal_comma, expected) in cases {
+            let format = CsvFormat::from_query(&query(delimiter, decimal_comma, None)).unwrap();
+            assert_eq!(format.delimiter, expected, "{:?}", delimiter);
+        }
+        assert!(matches!(
+            CsvFormat::from_query(&query(Some(":"), None, None)),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn picks_the_date_format() {
+        let date = day("2025-03-05");
+        let cases = [
+            (None, "2025-03-05"),
+            (Some("iso"), "2025-03-05"),
+            (Some("us"), "03/05/2025"),
+            (Some("eu"), "05.03.2025"),
+            (Some("uk"), "05/03/2025"),
+            (Some("%Y%m%d"), "20250305"),
+        ];
+        for (date_format, expected) in cases {
+            let format = CsvFormat::from_query(&query(None, None, date_format)).unwrap();
+            assert_eq!(format.format_date(date), expected, "{:?}", date_format);
+            assert_eq!(format.parse_date(expected), Some(date), "{:?}", date_format);
+        }
+        for pattern in ["dd/mm/yyyy", "%Y-%m-%Q", "%"] {
+            assert!(
+                matches!(
+                    CsvFormat::from_query(&query(None, None, Some(pattern))),
+                    Err(AppError::Validation(_))
+                ),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn formats_and_parses_decimals() {
+        let value: Decimal = "-1234.56".parse().unwrap();
+        let point = CsvFormat::default();
+        let comma = CsvFormat::from_query(&query(None, Some(true), None)).unwrap();
+        assert_eq!(point.format_decimal(value), "-1234.56");
+        assert_eq!(comma.format_decimal(value), "-1234,56");
+        assert_eq!(point.parse_decimal("-1234.56"), Some(value));
+        assert_eq!(comma.parse_decimal("-1234,56"), Some(value));
+        assert_eq!(point.parse_decimal("-1234,56"), None);
+    }
+
+    #[test]
+    fn types_json_values_by_column() {
+        let cases = [
+            (None, CsvColumnKind::Number, ""),
+            (Some(JsonValue::Null), CsvColumnKind::Text, ""),
+            (
+                Some(serde_json::json!("12.50")),
+                CsvColumnKind::Number,
+                "12,50",
+            ),
+            (Some(serde_json::json!(12.5)), CsvColumnKind::Number, "12,5"),
+            (
+                Some(serde_json::json!("12.50")),
+                CsvColumnKind::Text,
+                "12.50",
+            ),
+            (Some(serde_json::json!("***")), CsvColumnKind::Number, "***"),
+            (
+                Some(serde_json::json!("2025-03-05")),
+                CsvColumnKind::Date,
+                "05.03.2025",
+            ),
+            (Some(serde_json::json!("soon")), CsvColumnKind::Date, "soon"),
+            (Some(serde_json::json!(true)), CsvColumnKind::Text, "true"),
+        ];
+        let format = CsvFormat::from_query(&query(None, Some(true), Some("eu"))).unwrap();
+        for (value, kind, expected) in cases {
+            let mut writer = format.writer();
+            writer
+                .write_row([CsvCell::from_json(value.as_ref(), kind), "end".into()])
+                .unwrap();
+            assert_eq!(
+                writer.finish().unwrap(),
+                format!("{};end\n", expected),
+                "{:?} as {:?}",
+                value,
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let mut format = CsvFormat::from_query(&query(None, Some(true), Some("eu"))).unwrap();
+        format.bom = true;
+        let mut writer = format.writer();
+        writer.write_header(&["date", "memo", "amount"]).unwrap();
+        writer
+            .write_row([
+                day("2025-03-05").into(),
+                "Rent; March".into(),
+                "1500.25".parse::<Decimal>().unwrap().into(),
+            ])
+            .unwrap();
+        writer
+            .write_row([CsvCell::Empty, None::<String>.into(), CsvCell::Empty])
+            .unwrap();
+        let text = writer.finish().unwrap();
+        assert!(text.starts_with(UTF8_BOM));
+        assert!(text.contains("05.03.2025;\"Rent; March\";1500,25\n"));
+
+        let mut reader = format.reader(&text);
+        assert_eq!(
+            reader.headers().unwrap(),
+            &csv::StringRecord::from(vec!["date", "memo", "amount"])
+        );
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(format.parse_date(&rows[0][0]), Some(day("2025-03-05")));
+        assert_eq!(&rows[0][1], "Rent; March");
+        assert_eq!(format.parse_decimal(&rows[0][2]), "1500.25".parse().ok());
+        assert_eq!(&rows[1][1], "");
+    }
+}
@@ -0,0 +1,292 @@
+//! Public holiday rules used to seed a tenant's business calendar.
+//!
+//! Covers the nationwide bank/public holidays of a few countries. Where a country moves a
+//! holiday that falls on a weekend, the dates returned are the days actually taken off:
+//! the US observes Saturday holidays on the Friday before and Sunday holidays on the
+//! Monday after; GB and CA substitute the next weekday that is not already a holiday.
+//! Regional holidays are left to be added by hand.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Country codes (ISO 3166-1 alpha-2) with holiday rules.
+pub const SUPPORTED_COUNTRIES: [&str; 5] = ["CA", "DE", "FR", "GB", "US"];
+
+/// How a country moves holidays that fall on a weekend.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WeekendRule {
+    Keep,
+    NearestWeekday,
+    NextFreeWeekday,
+}
+
+/// The public holidays of `country` in `year`, by date. `None` for unsupported countries.
+pub fn public_holidays(country: &str, year: i32) -> Option<Vec<(NaiveDate, &'static str)>> {
+    let easter = easter_sunday(year)?;
+    let date = |month, day| NaiveDate::from_ymd_opt(year, month, day);
+    let (holidays, rule): (Vec<(Option<NaiveDate>, &'static str)>, WeekendRule) = match country {
+        "US" => {
+            let mut holidays = vec![
+                (date(1, 1), "New Year's Day"),
+                (
+                    nth_weekday(year, 1, Weekday::Mon, 3),
+                    "Martin Luther King Jr. Day",
+                ),
+                (
+                    nth_weekday(year, 2, Weekday::Mon, 3),
+                    "Washington's Birthday",
+                ),
+                (last_weekday(year, 5, Weekday::Mon), "Memorial Day"),
+                (date(7, 4), "Independence Day"),
+                (nth_weekday(year, 9, Weekday::Mon, 1), "Labor Day"),
+                (nth_weekday(year, 10, Weekday::Mon, 2), "Columbus Day"),
+                (date(11, 11), "Veterans Day"),
+                (nth_weekday(year, 11, Weekday::Thu, 4), "Thanksgiving Day"),
+                (date(12, 25), "Christmas Day"),
+            ];
+            if year >= 2021 {
+                holidays.push((date(6, 19), "Juneteenth National Independence Day"));
+            }
+            (holidays, WeekendRule::NearestWeekday)
+        }
+        "GB" => (
+            vec![
+                (date(1, 1), "New Year's Day"),
+                (Some(easter - Duration::days(2)), "Good Friday"),
+                (Some(easter + Duration::days(1)), "Easter Monday"),
+                (
+                    nth_weekday(year, 5, Weekday::Mon, 1),
+                    "Early May bank holiday",
+                ),
+                (last_weekday(year, 5, Weekday::Mon), "Spring bank holiday"),
+                (last_weekday(year, 8, Weekday::Mon), "Summer bank holiday"),
+                (date(12, 25), "Christmas Day"),
+                (date(12, 26), "Boxing Day"),
+            ],
+            WeekendRule::NextFreeWeekday,
+        ),
+        "CA" => {
+            // Victoria Day is the last Monday before May 25
+            let victoria_day = date(5, 24).map(|may_24| {
+                may_24 - Duration::days(i64::from(may_24.weekday().num_days_from_monday()))
+            });
+            let mut holidays = vec![
+                (date(1, 1), "New Year's Day"),
+                (Some(easter - Duration::days(2)), "Good Friday"),
+                (victoria_day, "Victoria Day"),
+                (date(7, 1), "Canada Day"),
+                (nth_weekday(year, 9, Weekday::Mon, 1), "Labour Day"),
+                (nth_weekday(year, 10, Weekday::Mon, 2), "Thanksgiving"),
+                (date(11, 11), "Remembrance Day"),
+                (date(12, 25), "Christmas Day"),
+                (date(12, 26), "Boxing Day"),
+            ];
+            if year >= 2021 {
+                holidays.push((date(9, 30), "National Day for Truth and Reconciliation"));
+            }
+            (holidays, WeekendRule::NextFreeWeekday)
+        }
+        "DE" => (
+            vec![
+                (date(1, 1), "Neujahr"),
+                (Some(easter - Duration::days(2)), "Karfreitag"),
+                (Some(easter + Duration::days(1)), "Ostermontag"),
+                (date(5, 1), "Tag der Arbeit"),
+                (Some(easter + Duration::days(39)), "Christi Himmelfahrt"),
+                (Some(easter + Duration::days(50)), "Pfingstmontag"),
+                (date(10, 3), "Tag der Deutschen Einheit"),
+                (date(12, 25), "1. Weihnachtstag"),
+                (date(12, 26), "2. Weihnachtstag"),
+            ],
+            WeekendRule::Keep,
+        ),
+        "FR" => (
+            vec![
+                (date(1, 1), "Jour de l'an"),
+                (Some(easter + Duration::days(1)), "Lundi de Pâques"),
+                (date(5, 1), "Fête du Travail"),
+                (date(5, 8), "Victoire 1945"),
+                (Some(easter + Duration::days(39)), "Ascension"),
+                (Some(easter + Duration::days(50)), "Lundi de Pentecôte"),
+                (date(7, 14), "Fête nationale"),
+                (date(8, 15), "Assomption"),
+                (date(11, 1), "Toussaint"),
+                (date(11, 11), "Armistice 1918"),
+                (date(12, 25), "Noël"),
+            ],
+            WeekendRule::Keep,
+        ),
+        _ => return None,
+    };
+
+    let mut holidays: Vec<(NaiveDate, &'static str)> = holidays
+        .into_iter()
+        .map(|(date, name)| date.map(|date| (date, name)))
+        .collect::<Option<_>>()?;
+    holidays.sort();
+    Some(apply_weekend_rule(holidays, rule))
+}
+
+/// Moves weekend holidays to the days taken off instead.
+fn apply_weekend_rule(
+    holidays: Vec<(NaiveDate, &'static str)>,
+    rule: WeekendRule,
+) -> Vec<(NaiveDate, &'static str)> {
+    let is_weekend = |date: NaiveDate| matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+    let mut moved: Vec<(NaiveDate, &'static str)> = match rule {
+        WeekendRule::Keep => return holidays,
+        WeekendRule::NearestWeekday => holidays
+            .into_iter()
+            .map(|(date, name)| match date.weekday() {
+                Weekday::Sat => (date - Duration::days(1), name),
+                Weekday::Sun => (date + Duration::days(1), name),
+                _ => (date, name),
+            })
+            .collect(),
+        WeekendRule::NextFreeWeekday => {
+            // Weekday holidays keep their date; weekend ones take the next free weekday in order
+            let (weekend, mut moved): (Vec<_>, Vec<_>) = holidays
+                .into_iter()
+                .partition(|(date, _)| is_weekend(*date));
+            for (date, name) in weekend {
+                let mut substitute = date + Duration::days(1);
+                while is_weekend(substitute) || moved.iter().any(|(taken, _)| *taken == substitute)
+                {
+                    substitute += Duration::days(1);
+                }
+                moved.push((substitute, name));
+            }
+            moved
+        }
+    };
+    moved.sort();
+    moved
+}
+
+/// Easter Sunday (Gregorian calendar), by the anonymous Gregorian algorithm.
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// The `n`th (1-based) `weekday` of a month.
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)
+}
+
+/// The last `weekday` of a month.
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+    nth_weekday(year, month, weekday, 5).or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn weekend_holidays_move_to_the_day_taken_off() {
+        // (country, holiday, scheduled date, date taken off)
+        let cases = [
+            // US: Saturday to the Friday before, Sunday to the Monday after
+            ("US", "Independence Day", date(2026, 7, 4), date(2026, 7, 3)),
+            ("US", "Independence Day", date(2021, 7, 4), date(2021, 7, 5)),
+            (
+                "US",
+                "Christmas Day",
+                date(2022, 12, 25),
+                date(2022, 12, 26),
+            ),
+            ("US", "Independence Day", date(2025, 7, 4), date(2025, 7, 4)),
+            // GB and CA: the next weekday that is not already a holiday
+            (
+                "GB",
+                "Christmas Day",
+                date(2021, 12, 25),
+                date(2021, 12, 27),
+            ),
+            ("GB", "Boxing Day", date(2021, 12, 26), date(2021, 12, 28)),
+            (
+                "GB",
+                "Christmas Day",
+                date(2022, 12, 25),
+                date(2022, 12, 27),
+            ),
+            ("GB", "Boxing Day", date(2022, 12, 26), date(2022, 12, 26)),
+            ("CA", "Canada Day", date(2023, 7, 1), date(2023, 7, 3)),
+            // DE and FR keep the date
+            (
+                "DE",
+                "1. Weihnachtstag",
+                date(2021, 12, 25),
+                date(2021, 12, 25),
+            ),
+            ("FR", "Fête nationale", date(2021, 7, 14), date(2021, 7, 14)),
+        ];
+        for (country, name, scheduled, observed) in cases {
+            let holidays = public_holidays(country, scheduled.year()).unwrap();
+            assert!(
+                holidays.contains(&(observed, name)),
+                "{} {} should be taken off on {}",
+                country,
+                name,
+                observed
+            );
+            if observed != scheduled {
+                assert!(
+                    !holidays.iter().any(|(date, _)| *date == scheduled),
+                    "{} {} should no longer fall on {}",
+                    country,
+                    name,
+                    scheduled
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn movable_holidays_follow_their_rule() {
+        let cases = [
+            ("GB", "Good Friday", date(2024, 3, 29)),
+            ("GB", "Easter Monday", date(2025, 4, 21)),
+            ("DE", "Christi Himmelfahrt", date(2025, 5, 29)),
+            ("US", "Thanksgiving Day", date(2025, 11, 27)),
+            ("US", "Memorial Day", date(2026, 5, 25)),
+            ("CA", "Victoria Day", date(2025, 5, 19)),
+            ("CA", "Victoria Day", date(2026, 5, 18)),
+        ];
+        for (country, name, expected) in cases {
+            let holidays = public_holidays(country, expected.year()).unwrap();
+            assert!(
+                holidays.contains(&(expected, name)),
+                "{} {} should be on {}",
+                country,
+                name,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn unsupported_countries_have_no_rules() {
+        assert_eq!(public_holidays("ZZ", 2025), None);
+        for country in SUPPORTED_COUNTRIES {
+            assert!(public_holidays(country, 2025).is_some(), "{}", country);
+        }
+    }
+}
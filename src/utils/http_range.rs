@@ -0,0 +1,63 @@
+//! `Range` request headers (RFC 9110) for resumable downloads.
+//!
+//! Only single byte ranges are served partially. A header that is malformed, uses another
+//! unit or asks for several ranges is ignored and the whole file is sent, which the RFC
+//! allows and every download client handles.
+
+/// What to send for a file of a known size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// The whole file (200).
+    Full,
+    /// Bytes `start..=end` (206).
+    Partial { start: u64, end: u64 },
+    /// No requested byte exists (416).
+    Unsatisfiable,
+}
+
+/// Resolves a `Range` header value against a file of `size` bytes.
+pub fn parse_range(header: Option<&str>, size: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|value| value.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+
+    match (first.trim(), last.trim()) {
+        // `-n`: the last n bytes
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if size == 0 => ByteRange::Unsatisfiable,
+            Ok(len) => ByteRange::Partial {
+                start: size.saturating_sub(len),
+                end: size - 1,
+            },
+            Err(_) => ByteRange::Full,
+        },
+        // `a-` or `a-b`; an end past the file is cut to its last byte
+        (start, end) => {
+            let Ok(start) = start.parse::<u64>() else {
+                return ByteRange::Full;
+            };
+            let end = match end {
+                "" => u64::MAX,
+                end => match end.parse::<u64>() {
+                    Ok(end) if end >= start => end,
+                    _ => return ByteRange::Full,
+                },
+            };
+            if start >= size {
+                ByteRange::Unsatisfiable
+            } else {
+                ByteRange::Partial {
+                    start,
+                    end: end.min(size - 1),
+                }
+            }
+        }
+    }
+}
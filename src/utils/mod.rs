@@ -7,6 +7,15 @@
 //! particular domain or application layer.
 
 // pub mod auth_middleware; // Placeholder for authentication utility functions (e.g., extracting user ID)
+pub mod crypto;          // Encryption of secrets stored at rest (e.g., provider access tokens)
+pub mod csv_format;      // Locale-aware CSV writer shared by all exporters
+pub mod update_builder;  // Partial UPDATE statements for the update_* services
+pub mod patch;           // Absent / null / value fields for JSON Merge Patch updates
+pub mod holidays;        // Public holiday rules for seeding business calendars
+pub mod http_range;      // Range headers for resumable downloads
+pub mod xlsx;            // XLSX workbooks for report downloads
+pub mod pdf;             // PDF documents for printable statements
+pub mod anonymize;       // Scrambled copies of tenant data for support
 pub mod hashing;         // For password hashing (e.g., using Argon2) - currently in user service, could be moved here
 pub mod validation;      // For custom validation logic or helpers (beyond `validator` crate)
 // pub mod date_time;       // Example for date/time formatting or manipulation
\ No newline at end of file
@@ -0,0 +1,73 @@
+//! Three-state fields for update DTOs.
+//!
+//! An `Option` field cannot tell a field left out of the body from one sent as `null`, so a
+//! nullable column could never be cleared. `Patch<T>` follows JSON Merge Patch (RFC 7396):
+//! an absent field leaves the column alone, `null` clears it and a value sets it.
+//!
+//! Fields need `#[serde(default)]` so that an absent field becomes `Patch::Missing`:
+//!
+//! ```ignore
+//! #[serde(default)]
+//! pub category_id: Patch<Uuid>,
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use validator::ValidateLength;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    /// Not in the body: left as it is.
+    #[default]
+    Missing,
+    /// Sent as `null`: cleared.
+    Null,
+    /// Set to a new value.
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    /// The new value, if one is set (`None` for both absent and `null`).
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Patch<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<T>::deserialize(deserializer)?.map_or(Patch::Null, Patch::Value))
+    }
+}
+
+impl<T> Serialize for Patch<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Patch::Value(value) => value.serialize(serializer),
+            Patch::Missing | Patch::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+/// `#[validate(length(...))]` checks new values only, like it does for `Option`.
+impl<T> ValidateLength<u64> for Patch<T>
+where
+    T: ValidateLength<u64>,
+{
+    fn length(&self) -> Option<u64> {
+        self.value().and_then(ValidateLength::length)
+    }
+}
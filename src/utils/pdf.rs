@@ -0,0 +1,325 @@
+//! PDF documents for printable reports, written with `printpdf`.
+//!
+//! Pages are A4 and drawn with absolute coordinates in points from the bottom-left corner.
+//! Text is set in DejaVu Sans, embedded in every file, so names in any script the font
+//! covers print as they are. Images (a tenant's logo) may be PNG or JPEG; transparency is
+//! flattened onto white, the page's color.
+//!
+//! Pages are collected as drawing operations and only laid out on a `printpdf` document
+//! in [`Document::finish`], so a page's content may depend on the final page count.
+
+use std::{io::Cursor, sync::OnceLock};
+
+use image::{
+    io::{Limits, Reader as ImageReader},
+    DynamicImage, ImageFormat, Rgb, RgbImage,
+};
+use printpdf::{
+    ImageTransform, IndirectFontRef, Line, Mm, PdfDocument, PdfLayerReference, Point, Pt,
+};
+use ttf_parser::Face;
+
+use crate::error::AppError;
+
+/// Media type of a PDF file.
+pub const CONTENT_TYPE: &str = "application/pdf";
+
+/// A4 in points.
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+
+/// Largest image width or height accepted.
+const MAX_IMAGE_SIDE: u32 = 4096;
+
+const REGULAR_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const BOLD_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Font::Regular => REGULAR_FONT,
+            Font::Bold => BOLD_FONT,
+        }
+    }
+
+    /// The parsed font, for measuring text.
+    fn face(self) -> &'static Face<'static> {
+        static REGULAR: OnceLock<Face<'static>> = OnceLock::new();
+        static BOLD: OnceLock<Face<'static>> = OnceLock::new();
+        let cell = match self {
+            Font::Regular => &REGULAR,
+            Font::Bold => &BOLD,
+        };
+        cell.get_or_init(|| Face::parse(self.bytes(), 0).expect("bundled font parses"))
+    }
+}
+
+/// Width of `text` in points when set in `font` at `size`. Characters the font lacks are
+/// measured as its missing-glyph box, which is what gets printed.
+pub fn text_width(font: Font, size: f32, text: &str) -> f32 {
+    let face = font.face();
+    let units: u32 = text
+        .chars()
+        .map(|c| {
+            let glyph = face.glyph_index(c).unwrap_or_default();
+            u32::from(face.glyph_hor_advance(glyph).unwrap_or(0))
+        })
+        .sum();
+    units as f32 * size / f32::from(face.units_per_em())
+}
+
+/// An image ready to be placed on pages.
+pub struct Image {
+    media_type: &'static str,
+    pixels: RgbImage,
+}
+
+impl Image {
+    /// Reads a PNG or JPEG file of at most `MAX_IMAGE_SIDE` pixels a side.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AppError> {
+        let (format, media_type) = match image::guess_format(bytes) {
+            Ok(ImageFormat::Png) => (ImageFormat::Png, "image/png"),
+            Ok(ImageFormat::Jpeg) => (ImageFormat::Jpeg, "image/jpeg"),
+            _ => return Err(invalid_image("the image must be a PNG or JPEG file")),
+        };
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(MAX_IMAGE_SIDE);
+        limits.max_image_height = Some(MAX_IMAGE_SIDE);
+        let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+        reader.limits(limits);
+        let decoded = reader.decode().map_err(|e| invalid_image(&e.to_string()))?;
+
+        Ok(Self {
+            media_type,
+            pixels: flatten_onto_white(decoded),
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.pixels.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.pixels.height()
+    }
+
+    /// `image/jpeg` or `image/png`.
+    pub fn media_type(&self) -> &'static str {
+        self.media_type
+    }
+}
+
+/// Index of an image added to a document.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageId(usize);
+
+enum Operation {
+    Text {
+        x: f32,
+        y: f32,
+        font: Font,
+        size: f32,
+        text: String,
+    },
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+    },
+    Image {
+        image: ImageId,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+}
+
+/// One page's drawing operations.
+#[derive(Default)]
+pub struct Page {
+    operations: Vec<Operation>,
+}
+
+impl Page {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Text starting at `x`, with its baseline at `y`.
+    pub fn text(&mut self, x: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.operations.push(Operation::Text {
+            x,
+            y,
+            font,
+            size,
+            text: text.to_string(),
+        });
+    }
+
+    /// Text ending at `right`, for columns of amounts.
+    pub fn text_right(&mut self, right: f32, y: f32, font: Font, size: f32, text: &str) {
+        self.text(right - text_width(font, size, text), y, font, size, text);
+    }
+
+    /// A straight black line `width` points thick.
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32) {
+        self.operations.push(Operation::Line { from, to, width });
+    }
+
+    /// An image stretched over the box with its bottom-left corner at `(x, y)`.
+    pub fn image(&mut self, image: ImageId, x: f32, y: f32, width: f32, height: f32) {
+        self.operations.push(Operation::Image {
+            image,
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+}
+
+/// A PDF document built page by page.
+pub struct Document {
+    title: String,
+    images: Vec<Image>,
+    pages: Vec<Page>,
+}
+
+impl Document {
+    /// An empty document; `title` is shown by viewers in place of the file name.
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            images: Vec::new(),
+            pages: Vec::new(),
+        }
+    }
+
+    pub fn add_image(&mut self, image: Image) -> ImageId {
+        self.images.push(image);
+        ImageId(self.images.len() - 1)
+    }
+
+    pub fn add_page(&mut self, page: Page) {
+        self.pages.push(page);
+    }
+
+    /// The finished file. A document without pages gets one blank page.
+    pub fn finish(mut self) -> Result<Vec<u8>, AppError> {
+        if self.pages.is_empty() {
+            self.pages.push(Page::new());
+        }
+        let (width, height) = (points(PAGE_WIDTH), points(PAGE_HEIGHT));
+        let (document, first_page, first_layer) =
+            PdfDocument::new(self.title.as_str(), width, height, "Content");
+        let regular = document
+            .add_external_font(Font::Regular.bytes())
+            .map_err(pdf_error)?;
+        let bold = document
+            .add_external_font(Font::Bold.bytes())
+            .map_err(pdf_error)?;
+        let font_ref = |font: Font| match font {
+            Font::Regular => &regular,
+            Font::Bold => &bold,
+        };
+
+        for (index, page) in self.pages.into_iter().enumerate() {
+            let (page_index, layer_index) = if index == 0 {
+                (first_page, first_layer)
+            } else {
+                document.add_page(width, height, "Content")
+            };
+            let layer = document.get_page(page_index).get_layer(layer_index);
+            for operation in page.operations {
+                draw(&layer, operation, &self.images, font_ref);
+            }
+        }
+
+        document.save_to_bytes().map_err(pdf_error)
+    }
+}
+
+fn draw<'a>(
+    layer: &PdfLayerReference,
+    operation: Operation,
+    images: &[Image],
+    font_ref: impl Fn(Font) -> &'a IndirectFontRef,
+) {
+    match operation {
+        Operation::Text {
+            x,
+            y,
+            font,
+            size,
+            text,
+        } => layer.use_text(text, size, points(x), points(y), font_ref(font)),
+        Operation::Line { from, to, width } => {
+            layer.set_outline_thickness(width);
+            layer.add_line(Line {
+                points: vec![
+                    (Point::new(points(from.0), points(from.1)), false),
+                    (Point::new(points(to.0), points(to.1)), false),
+                ],
+                is_closed: false,
+            });
+        }
+        Operation::Image {
+            image,
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let image = &images[image.0];
+            let pixels = DynamicImage::ImageRgb8(image.pixels.clone());
+            // At 72 dpi a pixel is a point, so the scale is the box's size in pixels
+            printpdf::Image::from_dynamic_image(&pixels).add_to_layer(
+                layer.clone(),
+                ImageTransform {
+                    translate_x: Some(points(x)),
+                    translate_y: Some(points(y)),
+                    scale_x: Some(width / image.width() as f32),
+                    scale_y: Some(height / image.height() as f32),
+                    dpi: Some(72.0),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+/// `printpdf` positions in millimetres.
+fn points(value: f32) -> Mm {
+    Mm::from(Pt(value))
+}
+
+/// Composites the image over a white background, dropping its alpha channel.
+fn flatten_onto_white(image: DynamicImage) -> RgbImage {
+    if !image.color().has_alpha() {
+        return image.into_rgb8();
+    }
+    let rgba = image.into_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let over_white = |channel: u8| {
+            let (channel, alpha) = (u32::from(channel), u32::from(a));
+            ((channel * alpha + 255 * (255 - alpha) + 127) / 255) as u8
+        };
+        Rgb([over_white(r), over_white(g), over_white(b)])
+    })
+}
+
+fn invalid_image(reason: &str) -> AppError {
+    AppError::Validation(format!("Unsupported image: {}", reason))
+}
+
+fn pdf_error(e: printpdf::Error) -> AppError {
+    AppError::InternalServerError(format!("Failed to write PDF: {}", e))
+}
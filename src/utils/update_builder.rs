@@ -0,0 +1,193 @@
+//! Partial `UPDATE` statements for the `update_*` services.
+//!
+//! Update DTOs carry every editable column as an `Option` (or a `Patch` for nullable
+//! columns that can be cleared); only the columns that were given are written. `UpdateBuilder` collects those as `column = $n` assignments with
+//! their values bound in order, then hands back a `sqlx::QueryBuilder` for the
+//! `WHERE` / `RETURNING` part, which differs per table:
+//!
+//! ```ignore
+//! let mut update = UpdateBuilder::new("budgets");
+//! update.set("name", dto.name).set("is_active", dto.is_active);
+//! if update.is_empty() {
+//!     return Err(AppError::Validation("No fields provided for update".to_string()));
+//! }
+//! let mut query = update.stamp(updated_by_user_id);
+//! query.push(" WHERE id = ").push_bind(budget_id);
+//! query.push(" RETURNING id, name, is_active");
+//! let budget = query.build_query_as::<Budget>().fetch_optional(pool).await?;
+//! ```
+
+use sqlx::{Encode, Postgres, QueryBuilder, Type};
+use uuid::Uuid;
+
+use crate::utils::patch::Patch;
+
+pub struct UpdateBuilder<'args> {
+    query: QueryBuilder<'args, Postgres>,
+    assignments: usize,
+}
+
+impl<'args> UpdateBuilder<'args> {
+    /// Starts `UPDATE <table> SET`. `table` may carry an alias (`"journal_entries je"`).
+    pub fn new(table: &str) -> Self {
+        UpdateBuilder {
+            query: QueryBuilder::new(format!("UPDATE {} SET ", table)),
+            assignments: 0,
+        }
+    }
+
+    /// Adds `column = value` when `value` is `Some`.
+    pub fn set<T>(&mut self, column: &str, value: Option<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        if let Some(value) = value {
+            self.push_column(column);
+            self.query.push_bind(value);
+        }
+        self
+    }
+
+    /// Adds `column = NULL`.
+    pub fn set_null(&mut self, column: &str) -> &mut Self {
+        self.push_column(column);
+        self.query.push("NULL");
+        self
+    }
+
+    /// Adds `column = value` or `column = NULL` for a given `Patch` field; nothing when absent.
+    pub fn patch<T>(&mut self, column: &str, value: Patch<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        match value {
+            Patch::Missing => self,
+            Patch::Null => self.set_null(column),
+            Patch::Value(value) => self.set(column, Some(value)),
+        }
+    }
+
+    /// Adds `version = version + 1`, for tables whose updates are checked against the
+    /// version the client read (`If-Match`).
+    pub fn bump_version(&mut self) -> &mut Self {
+        self.push_column("version");
+        self.query.push("version + 1");
+        self
+    }
+
+    /// Whether no column was assigned yet.
+    pub fn is_empty(&self) -> bool {
+        self.assignments == 0
+    }
+
+    /// Sets `updated_at` and `updated_by` and returns the query for its `WHERE` clause.
+    pub fn stamp(mut self, updated_by_user_id: Uuid) -> QueryBuilder<'args, Postgres> {
+        self.push_column("updated_at");
+        self.query.push("NOW()");
+        self.push_column("updated_by");
+        self.query.push_bind(updated_by_user_id);
+        self.query
+    }
+
+    fn push_column(&mut self, column: &str) {
+        if self.assignments > 0 {
+            self.query.push(", ");
+        }
+        self.query.push(column).push(" = ");
+        self.assignments += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn only_given_columns_are_assigned() {
+        let mut update = UpdateBuilder::new("budgets");
+        update
+            .set("name", Some("Operating".to_string()))
+            .set("start_date", None::<NaiveDate>)
+            .set("end_date", None::<NaiveDate>)
+            .set("is_active", Some(false));
+        let mut query = update.stamp(Uuid::nil());
+        query.push(" WHERE id = ").push_bind(Uuid::nil());
+
+        assert_eq!(
+            query.sql(),
+            "UPDATE budgets SET name = $1, is_active = $2, updated_at = NOW(), updated_by = $3 WHERE id = $4"
+        );
+    }
+
+    #[test]
+    fn a_single_column_is_a_valid_update() {
+        let mut update = UpdateBuilder::new("currencies");
+        update
+            .set("name", None::<String>)
+            .set("symbol", Some("€".to_string()));
+        assert!(!update.is_empty());
+
+        let mut query = update.stamp(Uuid::nil());
+        query.push(" WHERE code = ").push_bind("EUR".to_string());
+
+        assert_eq!(
+            query.sql(),
+            "UPDATE currencies SET symbol = $1, updated_at = NOW(), updated_by = $2 WHERE code = $3"
+        );
+    }
+
+    #[test]
+    fn version_bumps_follow_the_given_columns() {
+        let mut update = UpdateBuilder::new("accounts");
+        update.set("name", Some("Cash".to_string())).bump_version();
+        let mut query = update.stamp(Uuid::nil());
+        query.push(" WHERE id = ").push_bind(Uuid::nil());
+        query.push(" AND version = ").push_bind(3);
+
+        assert_eq!(
+            query.sql(),
+            "UPDATE accounts SET name = $1, version = version + 1, updated_at = NOW(), updated_by = $2 WHERE id = $3 AND version = $4"
+        );
+    }
+
+    #[test]
+    fn no_given_columns_is_empty() {
+        let mut update = UpdateBuilder::new("accounts");
+        update
+            .set("name", None::<String>)
+            .set("is_active", None::<bool>);
+
+        assert!(update.is_empty());
+    }
+
+    #[test]
+    fn patches_skip_absent_fields_and_clear_null_ones() {
+        let mut update = UpdateBuilder::new("transactions");
+        update
+            .patch("category_id", Patch::<Uuid>::Null)
+            .patch("notes", Patch::<String>::Missing)
+            .patch("reconciliation_date", Patch::Value(NaiveDate::MIN));
+        let query = update.stamp(Uuid::nil());
+
+        assert_eq!(
+            query.sql(),
+            "UPDATE transactions SET category_id = NULL, reconciliation_date = $1, updated_at = NOW(), updated_by = $2"
+        );
+    }
+
+    #[test]
+    fn null_assignments_bind_nothing() {
+        let mut update = UpdateBuilder::new("budget_line_items bli");
+        update
+            .set("budgeted_amount", Some(Decimal::new(120_000, 2)))
+            .set_null("monthly_amounts");
+        let query = update.stamp(Uuid::nil());
+
+        assert_eq!(
+            query.sql(),
+            "UPDATE budget_line_items bli SET budgeted_amount = $1, monthly_amounts = NULL, updated_at = NOW(), updated_by = $2"
+        );
+    }
+}
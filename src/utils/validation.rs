@@ -0,0 +1,25 @@
+//! Field rules for DTOs that the `validator` crate doesn't cover.
+//!
+//! `range` only applies to primitive numbers, so `Decimal` amounts are checked here. Errors
+//! keep the `range` code so clients see the same error for any out-of-range number.
+
+use rust_decimal::Decimal;
+use validator::ValidationError;
+
+/// Amounts and rates that must be greater than zero.
+pub fn positive_amount(value: &Decimal) -> Result<(), ValidationError> {
+    if *value > Decimal::ZERO {
+        Ok(())
+    } else {
+        Err(ValidationError::new("range").with_message("must be greater than zero".into()))
+    }
+}
+
+/// Amounts that may be zero but not negative.
+pub fn non_negative_amount(value: &Decimal) -> Result<(), ValidationError> {
+    if *value >= Decimal::ZERO {
+        Ok(())
+    } else {
+        Err(ValidationError::new("range").with_message("must not be negative".into()))
+    }
+}
@@ -0,0 +1,151 @@
+//! XLSX workbooks for report downloads, written with `rust_xlsxwriter`.
+//!
+//! Cells are typed with `CsvCell`, as for CSV exports, so a masked amount (`"***"`) ends
+//! up as text. Numbers are formatted `#,##0.00` and dates `yyyy-mm-dd`, each optionally
+//! bold. Columns are fitted to their contents and a sheet with a header keeps it in view
+//! while scrolling.
+
+use rust_xlsxwriter::{Format, Workbook as XlsxWorkbook, Worksheet, XlsxError};
+
+use crate::{error::AppError, utils::csv_format::CsvCell};
+
+/// Media type of an XLSX file.
+pub const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Excel's sheet name limit.
+const MAX_SHEET_NAME: usize = 31;
+/// Widest a column is fitted to, in pixels (about 60 characters).
+const MAX_COLUMN_WIDTH: u32 = 425;
+
+/// One worksheet, filled row by row.
+pub struct Sheet {
+    name: String,
+    rows: Vec<Row>,
+    has_header: bool,
+}
+
+struct Row {
+    cells: Vec<CsvCell>,
+    bold: bool,
+}
+
+impl Sheet {
+    /// A new sheet; the name is cut to what Excel accepts.
+    pub fn new(name: &str) -> Self {
+        let name: String = name
+            .chars()
+            .map(|c| if "[]:*?/\\".contains(c) { '-' } else { c })
+            .take(MAX_SHEET_NAME)
+            .collect();
+        Sheet {
+            name,
+            rows: Vec::new(),
+            has_header: false,
+        }
+    }
+
+    /// Adds a bold header row, kept in view while scrolling if it is the first row.
+    pub fn header(&mut self, columns: &[&str]) {
+        self.has_header |= self.rows.is_empty();
+        self.bold_row(
+            columns
+                .iter()
+                .map(|column| CsvCell::Text(column.to_string())),
+        );
+    }
+
+    pub fn row(&mut self, cells: impl IntoIterator<Item = CsvCell>) {
+        self.rows.push(Row {
+            cells: cells.into_iter().collect(),
+            bold: false,
+        });
+    }
+
+    /// Adds a row in bold, e.g. a subtotal.
+    pub fn bold_row(&mut self, cells: impl IntoIterator<Item = CsvCell>) {
+        self.rows.push(Row {
+            cells: cells.into_iter().collect(),
+            bold: true,
+        });
+    }
+
+    pub fn blank_row(&mut self) {
+        self.row([]);
+    }
+
+    fn into_worksheet(self) -> Result<Worksheet, XlsxError> {
+        let mut worksheet = Worksheet::new();
+        worksheet.set_name(&self.name)?;
+
+        let text = [Format::new(), Format::new().set_bold()];
+        let number = [
+            Format::new().set_num_format("#,##0.00"),
+            Format::new().set_num_format("#,##0.00").set_bold(),
+        ];
+        let date = [
+            Format::new().set_num_format("yyyy-mm-dd"),
+            Format::new().set_num_format("yyyy-mm-dd").set_bold(),
+        ];
+        for (row_number, row) in (0u32..).zip(&self.rows) {
+            let style = usize::from(row.bold);
+            for (column, cell) in (0u16..).zip(&row.cells) {
+                match cell {
+                    CsvCell::Empty => continue,
+                    CsvCell::Text(value) => worksheet.write_string_with_format(
+                        row_number,
+                        column,
+                        value,
+                        &text[style],
+                    )?,
+                    CsvCell::Number(value) => {
+                        worksheet.write_with_format(row_number, column, *value, &number[style])?
+                    }
+                    CsvCell::Date(value) => {
+                        worksheet.write_with_format(row_number, column, value, &date[style])?
+                    }
+                };
+            }
+        }
+
+        if self.has_header {
+            worksheet.set_freeze_panes(1, 0)?;
+        }
+        worksheet.set_autofit_max_width(MAX_COLUMN_WIDTH).autofit();
+        Ok(worksheet)
+    }
+}
+
+/// Sheets in the order they are added; the first one opens first.
+#[derive(Default)]
+pub struct Workbook {
+    sheets: Vec<Sheet>,
+}
+
+impl Workbook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_sheet(&mut self, sheet: Sheet) {
+        self.sheets.push(sheet);
+    }
+
+    /// The finished file.
+    pub fn finish(self) -> Result<Vec<u8>, AppError> {
+        if self.sheets.is_empty() {
+            return Err(AppError::InternalServerError(
+                "A workbook needs at least one sheet".to_string(),
+            ));
+        }
+
+        let mut workbook = XlsxWorkbook::new();
+        for sheet in self.sheets {
+            workbook.push_worksheet(sheet.into_worksheet().map_err(xlsx_error)?);
+        }
+        workbook.save_to_buffer().map_err(xlsx_error)
+    }
+}
+
+fn xlsx_error(e: XlsxError) -> AppError {
+    AppError::InternalServerError(format!("Failed to write workbook: {}", e))
+}